@@ -0,0 +1,440 @@
+//! Subcommand implementations
+//!
+//! Each command returns `Ok(false)` when it ran but found problems (invalid
+//! files, failing tests), and an error when it could not run at all.
+
+use anyhow::{bail, Context, Result};
+use corint_core::Value;
+use corint_sdk::{
+    validate_references, DecisionEngine, DecisionEngineBuilder, DecisionRequest, Diagnostic,
+    DiagnosticSeverity, DslType, DslValidator, RepositoryConfig, RepositoryLoader,
+};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::test_suite::TestSuite;
+
+/// Repository directories holding rules, rulesets and pipelines
+const DEFINITION_DIRS: &[&str] = &["library", "pipelines"];
+
+/// Validate every definition file, then compile the repository as a whole
+pub async fn validate(repository: &Path) -> Result<bool> {
+    let files = DEFINITION_DIRS
+        .iter()
+        .map(|dir| yaml_files(&repository.join(dir)))
+        .collect::<Result<Vec<_>>>()?
+        .concat();
+    if files.is_empty() {
+        bail!(
+            "no rules, rulesets or pipelines found in {}",
+            repository.display()
+        );
+    }
+
+    let validator = DslValidator::new();
+    let mut invalid = 0;
+    for path in &files {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+
+        let mut diagnostics = Vec::new();
+        let mut valid = true;
+        for (offset, document) in definition_documents(&content) {
+            let result = validator.validate(&document, DslType::Auto);
+            valid &= result.valid;
+            for mut diagnostic in result.errors.into_iter().chain(result.warnings) {
+                diagnostic.line = diagnostic.line.map(|line| line + offset);
+                diagnostics.push(diagnostic);
+            }
+        }
+
+        let mark = if valid { "✓" } else { "✗" };
+        println!("{} {}", mark, relative(repository, path).display());
+        for diagnostic in &diagnostics {
+            println!("    {}", describe(diagnostic));
+        }
+        if !valid {
+            invalid += 1;
+        }
+    }
+
+    // Rules, rulesets, pipelines, features, lists and APIs referenced from
+    // other files, all reported at once
+    let references = if invalid == 0 {
+        let content = RepositoryLoader::new(repository_config(repository))
+            .load_all()
+            .await
+            .with_context(|| format!("failed to load repository {}", repository.display()))?;
+        let diagnostics = validate_references(&content);
+        for diagnostic in &diagnostics {
+            println!("✗ {}", describe(diagnostic));
+        }
+        diagnostics.is_empty()
+    } else {
+        false
+    };
+
+    // Imports and reason codes are only checked when the whole repository
+    // is compiled
+    let compiled = if references {
+        match build_engine(repository).await {
+            Ok(engine) => {
+                println!("✓ compiled {} programs", engine.programs().len());
+                true
+            }
+            Err(e) => {
+                println!("✗ compilation failed: {:#}", e);
+                false
+            }
+        }
+    } else {
+        false
+    };
+
+    println!();
+    println!("{} files checked, {} invalid", files.len(), invalid);
+    Ok(invalid == 0 && compiled)
+}
+
+/// Compile the repository and write one JSON file per program
+///
+/// Programs are written to `<out>/<type>/<id>.json`, e.g.
+/// `artifacts/pipeline/payment_pipeline.json`.
+pub async fn compile(repository: &Path, out: &Path) -> Result<bool> {
+    let engine = build_engine(repository).await?;
+
+    let loaded = engine.programs();
+    let programs: BTreeMap<_, _> = loaded
+        .iter()
+        .map(|p| {
+            let key = (p.metadata.source_type.clone(), p.metadata.source_id.clone());
+            (key, p)
+        })
+        .collect();
+
+    for ((source_type, source_id), program) in &programs {
+        let dir = out.join(source_type);
+        fs::create_dir_all(&dir).with_context(|| format!("failed to create {}", dir.display()))?;
+        let path = dir.join(format!("{}.json", source_id));
+        let json = serde_json::to_string_pretty(program)?;
+        fs::write(&path, json).with_context(|| format!("failed to write {}", path.display()))?;
+        println!("✓ {}", path.display());
+    }
+
+    println!();
+    println!("{} programs written to {}", programs.len(), out.display());
+    Ok(true)
+}
+
+/// Run the test cases in `files` (default: `<repository>/tests`)
+pub async fn test(repository: &Path, files: &[PathBuf]) -> Result<bool> {
+    let paths = if files.is_empty() {
+        vec![repository.join("tests")]
+    } else {
+        files.to_vec()
+    };
+    let mut test_files = Vec::new();
+    for path in &paths {
+        if path.is_dir() {
+            test_files.extend(yaml_files(path)?);
+        } else if path.is_file() {
+            test_files.push(path.clone());
+        } else {
+            bail!("test path {} does not exist", path.display());
+        }
+    }
+    if test_files.is_empty() {
+        bail!("no test files found");
+    }
+
+    let engine = build_engine(repository).await?;
+    let (mut passed, mut failed) = (0, 0);
+    for path in &test_files {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        let suite = TestSuite::parse(&content)
+            .with_context(|| format!("invalid test file {}", path.display()))?;
+
+        println!("{}", path.display());
+        for case in &suite.tests {
+            let request = case.request(suite.pipeline.as_deref());
+            let failures = match engine.decide(request).await {
+                Ok(response) => case.expect.failures(&response.result),
+                Err(e) => vec![format!("decision failed: {}", e)],
+            };
+
+            if failures.is_empty() {
+                passed += 1;
+                println!("  ✓ {}", case.name);
+            } else {
+                failed += 1;
+                println!("  ✗ {}", case.name);
+                for failure in failures {
+                    println!("      {}", failure);
+                }
+            }
+        }
+    }
+
+    println!();
+    println!("{} passed, {} failed", passed, failed);
+    Ok(failed == 0)
+}
+
+/// Make a decision for the event in `event_file` and print the response
+pub async fn decide(
+    repository: &Path,
+    event_file: &Path,
+    pipeline: Option<String>,
+    trace: bool,
+) -> Result<bool> {
+    let content = fs::read_to_string(event_file)
+        .with_context(|| format!("failed to read {}", event_file.display()))?;
+    let event: HashMap<String, Value> = serde_json::from_str(&content)
+        .with_context(|| format!("{} is not a JSON object", event_file.display()))?;
+
+    let engine = build_engine(repository).await?;
+    let mut request = DecisionRequest::new(event);
+    if let Some(pipeline) = pipeline {
+        request = request.with_pipeline(pipeline);
+    }
+    if trace {
+        request = request.with_trace();
+    }
+
+    let response = engine.decide(request).await?;
+    println!("{}", serde_json::to_string_pretty(&response)?);
+    Ok(true)
+}
+
+async fn build_engine(repository: &Path) -> Result<DecisionEngine> {
+    if !repository.is_dir() {
+        bail!("repository {} is not a directory", repository.display());
+    }
+    DecisionEngineBuilder::new()
+        .with_repository(repository_config(repository))
+        .build()
+        .await
+        .with_context(|| format!("failed to load repository {}", repository.display()))
+}
+
+fn repository_config(repository: &Path) -> RepositoryConfig {
+    RepositoryConfig::file_system(repository.to_string_lossy().to_string())
+}
+
+/// YAML files below `dir`, sorted; none if `dir` doesn't exist
+fn yaml_files(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    if !dir.is_dir() {
+        return Ok(files);
+    }
+    for entry in fs::read_dir(dir).with_context(|| format!("failed to read {}", dir.display()))? {
+        let path = entry?.path();
+        if path.is_dir() {
+            files.extend(yaml_files(&path)?);
+        } else if matches!(
+            path.extension().and_then(|e| e.to_str()),
+            Some("yaml" | "yml")
+        ) {
+            files.push(path);
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// Documents of a YAML file that define a rule, ruleset or pipeline
+///
+/// Files may start with a `version`/`import` document and hold several
+/// definitions separated by `---`; the validator takes one definition at a
+/// time. Each document is returned with the number of lines before it.
+fn definition_documents(content: &str) -> Vec<(usize, String)> {
+    let mut documents = vec![(0, String::new())];
+    for (index, line) in content.lines().enumerate() {
+        if line.trim_end() == "---" {
+            documents.push((index + 1, String::new()));
+        } else if let Some((_, document)) = documents.last_mut() {
+            document.push_str(line);
+            document.push('\n');
+        }
+    }
+
+    documents
+        .into_iter()
+        .filter(|(_, document)| {
+            document.lines().any(|line| {
+                ["rule:", "ruleset:", "pipeline:"]
+                    .iter()
+                    .any(|key| line.trim_end() == *key)
+            })
+        })
+        .collect()
+}
+
+fn relative<'a>(base: &Path, path: &'a Path) -> &'a Path {
+    path.strip_prefix(base).unwrap_or(path)
+}
+
+fn describe(diagnostic: &Diagnostic) -> String {
+    let severity = match diagnostic.severity {
+        DiagnosticSeverity::Error => "error",
+        DiagnosticSeverity::Warning => "warning",
+        DiagnosticSeverity::Info => "info",
+    };
+    let location = match (&diagnostic.file, diagnostic.line) {
+        (Some(file), Some(line)) => format!(" {} line {}", file, line),
+        (Some(file), None) => format!(" {}", file),
+        (None, Some(line)) => format!(" line {}", line),
+        (None, None) => String::new(),
+    };
+    format!(
+        "{}[{}]{}: {}",
+        severity, diagnostic.code, location, diagnostic.message
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    const RULE: &str = r#"version: "0.1"
+
+rule:
+  id: high_amount
+  name: High amount
+  when:
+    conditions:
+      - event.amount > 1000
+  score: 80
+"#;
+
+    const PIPELINE: &str = r#"version: "0.1"
+
+import:
+  rules:
+    - library/rules/high_amount.yaml
+
+---
+
+ruleset:
+  id: payment_rules
+  name: Payment rules
+  rules:
+    - high_amount
+  conclusion:
+    - when: total_score >= 80
+      signal: decline
+    - default: true
+      signal: approve
+
+---
+
+pipeline:
+  id: payment_pipeline
+  name: Payment pipeline
+  entry: check
+  steps:
+    - step:
+        id: check
+        name: Check
+        type: ruleset
+        ruleset: payment_rules
+        next: end
+"#;
+
+    const TESTS: &str = r#"
+pipeline: payment_pipeline
+tests:
+  - name: large payment is declined
+    event:
+      type: transaction
+      amount: 5000
+    expect:
+      signal: decline
+      triggered_rules: [high_amount]
+  - name: small payment is approved
+    event:
+      type: transaction
+      amount: 10
+    expect:
+      signal: approve
+"#;
+
+    fn create_repo() -> TempDir {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        for dir in ["library/rules", "pipelines", "tests"] {
+            fs::create_dir_all(root.join(dir)).unwrap();
+        }
+        fs::write(root.join("library/rules/high_amount.yaml"), RULE).unwrap();
+        fs::write(root.join("pipelines/payment.yaml"), PIPELINE).unwrap();
+        fs::write(root.join("tests/payment.yaml"), TESTS).unwrap();
+        temp
+    }
+
+    #[tokio::test]
+    async fn test_validate_compile_and_test() {
+        let repo = create_repo();
+        let root = repo.path();
+
+        assert!(validate(root).await.unwrap());
+
+        let out = root.join("artifacts");
+        assert!(compile(root, &out).await.unwrap());
+        assert!(out.join("pipeline/payment_pipeline.json").is_file());
+
+        assert!(test(root, &[]).await.unwrap());
+
+        let failing = root.join("failing.yaml");
+        fs::write(&failing, TESTS.replace("signal: approve", "signal: review")).unwrap();
+        assert!(!test(root, &[failing]).await.unwrap());
+    }
+
+    #[test]
+    fn test_definition_documents() {
+        let documents = definition_documents(PIPELINE);
+        assert_eq!(documents.len(), 2);
+        assert_eq!(documents[0].0, 7);
+        assert!(documents[0].1.starts_with("\nruleset:"));
+        assert!(documents[1].1.contains("pipeline:\n"));
+
+        assert_eq!(definition_documents(RULE), vec![(0, RULE.to_string())]);
+    }
+
+    #[tokio::test]
+    async fn test_validate_reports_invalid_files() {
+        let repo = create_repo();
+        let root = repo.path();
+        fs::write(
+            root.join("library/rules/broken.yaml"),
+            "rule:\n  id: broken\n  when: [\n",
+        )
+        .unwrap();
+
+        assert!(!validate(root).await.unwrap());
+        assert!(validate(&root.join("missing")).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_validate_reports_undefined_references() {
+        let repo = create_repo();
+        let root = repo.path();
+        fs::write(
+            root.join("pipelines/payment.yaml"),
+            PIPELINE.replace("ruleset: payment_rules", "ruleset: card_rules"),
+        )
+        .unwrap();
+
+        assert!(!validate(root).await.unwrap());
+
+        let diagnostic = Diagnostic::error("E004", "undefined ruleset 'card_rules'")
+            .with_file("pipelines/payment.yaml")
+            .with_location(34, 18);
+        assert_eq!(
+            describe(&diagnostic),
+            "error[E004] pipelines/payment.yaml line 34: undefined ruleset 'card_rules'"
+        );
+    }
+}
@@ -0,0 +1,115 @@
+//! CORINT Decision Engine command-line interface
+//!
+//! Lets rule authors and CI pipelines work with a repository without writing
+//! Rust or running the server:
+//!
+//! ```text
+//! corint validate repository/
+//! corint compile --repository repository/ --out artifacts/
+//! corint test --repository repository/
+//! corint decide --repository repository/ --event event.json
+//! ```
+//!
+//! Exit status is 0 on success, 1 when validation or tests fail, and 2 when
+//! the command itself could not run (e.g. a missing file).
+
+mod commands;
+mod test_suite;
+
+use clap::{Parser, Subcommand};
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+#[derive(Parser)]
+#[command(
+    name = "corint",
+    version,
+    about = "CORINT Decision Engine command-line interface"
+)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Validate the rules, rulesets and pipelines of a repository
+    Validate {
+        /// Repository directory
+        #[arg(default_value = "repository")]
+        repository: PathBuf,
+    },
+
+    /// Compile a repository and write the compiled programs as JSON
+    Compile {
+        /// Repository directory
+        #[arg(long, short, default_value = "repository")]
+        repository: PathBuf,
+
+        /// Output directory
+        #[arg(long, short, default_value = "artifacts")]
+        out: PathBuf,
+    },
+
+    /// Run decision test cases against a repository
+    Test {
+        /// Repository directory
+        #[arg(long, short, default_value = "repository")]
+        repository: PathBuf,
+
+        /// Test files or directories (default: <repository>/tests)
+        files: Vec<PathBuf>,
+    },
+
+    /// Make a decision for an event and print the response as JSON
+    Decide {
+        /// Repository directory
+        #[arg(long, short, default_value = "repository")]
+        repository: PathBuf,
+
+        /// JSON file with the event data
+        #[arg(long, short)]
+        event: PathBuf,
+
+        /// Pipeline to run instead of routing through the registry
+        #[arg(long, short)]
+        pipeline: Option<String>,
+
+        /// Include the execution trace
+        #[arg(long)]
+        trace: bool,
+    },
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    // Engine logs go to stderr and are quiet unless RUST_LOG asks for more
+    tracing_subscriber::fmt()
+        .with_env_filter(
+            tracing_subscriber::EnvFilter::try_from_default_env().unwrap_or_else(|_| "warn".into()),
+        )
+        .with_writer(std::io::stderr)
+        .init();
+
+    let cli = Cli::parse();
+    let result = match cli.command {
+        Command::Validate { repository } => commands::validate(&repository).await,
+        Command::Compile { repository, out } => commands::compile(&repository, &out).await,
+        Command::Test { repository, files } => commands::test(&repository, &files).await,
+        Command::Decide {
+            repository,
+            event,
+            pipeline,
+            trace,
+        } => commands::decide(&repository, &event, pipeline, trace).await,
+    };
+
+    match result {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::from(1),
+        Err(e) => {
+            eprintln!("error: {:#}", e);
+            ExitCode::from(2)
+        }
+    }
+}
@@ -0,0 +1,235 @@
+//! Decision test cases
+//!
+//! A test file lists events together with the decision they must produce:
+//!
+//! ```yaml
+//! pipeline: payment_pipeline      # optional default for all tests
+//! tests:
+//!   - name: large transfer from a new account is declined
+//!     event:
+//!       type: transaction
+//!       amount: 25000
+//!       user:
+//!         account_age_days: 2
+//!     features:                   # optional precomputed feature values
+//!       txn_count_24h: 14
+//!     expect:
+//!       signal: decline
+//!       triggered_rules: [high_amount, new_account]
+//!       not_triggered_rules: [vip_user]
+//! ```
+//!
+//! Every expectation is optional; `triggered_rules` and `actions` must be
+//! contained in the result, not equal to it.
+
+use corint_core::ast::Signal;
+use corint_core::Value;
+use corint_sdk::{DecisionRequest, DecisionResult};
+use serde::Deserialize;
+use std::collections::HashMap;
+
+/// Test cases of one file
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TestSuite {
+    /// Pipeline used by tests that don't name one
+    #[serde(default)]
+    pub pipeline: Option<String>,
+
+    /// Test cases
+    pub tests: Vec<TestCase>,
+}
+
+/// One event and the decision it must produce
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TestCase {
+    /// Test name
+    pub name: String,
+
+    /// Pipeline to run; routed through the registry when unset
+    #[serde(default)]
+    pub pipeline: Option<String>,
+
+    /// Event data
+    pub event: HashMap<String, Value>,
+
+    /// Precomputed feature values
+    #[serde(default)]
+    pub features: Option<HashMap<String, Value>>,
+
+    /// Expected decision
+    pub expect: Expectation,
+}
+
+/// Expected decision of a test case
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Expectation {
+    /// Decision signal (`approve`, `decline`, `review`, `hold` or `pass`)
+    #[serde(default)]
+    pub signal: Option<String>,
+
+    /// Exact total score
+    #[serde(default)]
+    pub score: Option<i32>,
+
+    /// Rules that must trigger
+    #[serde(default)]
+    pub triggered_rules: Vec<String>,
+
+    /// Rules that must not trigger
+    #[serde(default)]
+    pub not_triggered_rules: Vec<String>,
+
+    /// Actions that must be returned
+    #[serde(default)]
+    pub actions: Vec<String>,
+}
+
+impl TestSuite {
+    /// Parse a test file
+    pub fn parse(content: &str) -> serde_yaml::Result<Self> {
+        serde_yaml::from_str(content)
+    }
+}
+
+impl TestCase {
+    /// Decision request for the test event
+    pub fn request(&self, default_pipeline: Option<&str>) -> DecisionRequest {
+        let mut request = DecisionRequest::new(self.event.clone());
+        if let Some(pipeline) = self.pipeline.as_deref().or(default_pipeline) {
+            request = request.with_pipeline(pipeline);
+        }
+        if let Some(features) = &self.features {
+            request = request.with_features(features.clone());
+        }
+        request
+    }
+}
+
+impl Expectation {
+    /// Describe every way `result` differs from the expectation
+    pub fn failures(&self, result: &DecisionResult) -> Vec<String> {
+        let mut failures = Vec::new();
+
+        if let Some(expected) = &self.signal {
+            let actual = result.signal.as_ref().map(signal_name).unwrap_or("none");
+            if !expected.eq_ignore_ascii_case(actual) {
+                failures.push(format!("expected signal {}, got {}", expected, actual));
+            }
+        }
+        if let Some(expected) = self.score {
+            if expected != result.score {
+                failures.push(format!("expected score {}, got {}", expected, result.score));
+            }
+        }
+        for rule in &self.triggered_rules {
+            if !result.triggered_rules.contains(rule) {
+                failures.push(format!("expected rule {} to trigger", rule));
+            }
+        }
+        for rule in &self.not_triggered_rules {
+            if result.triggered_rules.contains(rule) {
+                failures.push(format!("expected rule {} not to trigger", rule));
+            }
+        }
+        for action in &self.actions {
+            if !result.actions.contains(action) {
+                failures.push(format!("expected action {}", action));
+            }
+        }
+
+        failures
+    }
+}
+
+/// Lower-case name of a signal, as written in test files
+pub fn signal_name(signal: &Signal) -> &'static str {
+    match signal {
+        Signal::Approve => "approve",
+        Signal::Decline => "decline",
+        Signal::Review => "review",
+        Signal::Hold => "hold",
+        Signal::Pass => "pass",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SUITE: &str = r#"
+pipeline: payment_pipeline
+tests:
+  - name: large transfer is declined
+    event:
+      type: transaction
+      amount: 25000
+    expect:
+      signal: decline
+      triggered_rules: [high_amount]
+      not_triggered_rules: [vip_user]
+  - name: login is routed by the registry
+    pipeline: login_pipeline
+    event:
+      type: login
+    features:
+      failed_logins_1h: 0
+    expect:
+      signal: approve
+      score: 0
+"#;
+
+    fn result(signal: Signal, score: i32, rules: &[&str]) -> DecisionResult {
+        DecisionResult {
+            signal: Some(signal),
+            actions: vec![],
+            score,
+            triggered_rules: rules.iter().map(|r| r.to_string()).collect(),
+            explanation: String::new(),
+            reason_code: None,
+            context: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn test_parse_suite() {
+        let suite = TestSuite::parse(SUITE).unwrap();
+        assert_eq!(suite.tests.len(), 2);
+
+        let first = suite.tests[0].request(suite.pipeline.as_deref());
+        assert_eq!(first.options.pipeline.as_deref(), Some("payment_pipeline"));
+        assert_eq!(first.event_data["amount"], Value::Number(25000.0));
+
+        let second = suite.tests[1].request(suite.pipeline.as_deref());
+        assert_eq!(second.options.pipeline.as_deref(), Some("login_pipeline"));
+        assert!(second.features.is_some());
+
+        let err = TestSuite::parse(
+            "tests:\n  - name: x\n    event: {}\n    expect:\n      decision: decline\n",
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("unknown field `decision`"));
+    }
+
+    #[test]
+    fn test_expectation_failures() {
+        let suite = TestSuite::parse(SUITE).unwrap();
+        let expect = &suite.tests[0].expect;
+
+        assert!(expect
+            .failures(&result(Signal::Decline, 80, &["high_amount"]))
+            .is_empty());
+
+        let failures = expect.failures(&result(Signal::Review, 80, &["vip_user"]));
+        assert_eq!(
+            failures,
+            vec![
+                "expected signal decline, got review",
+                "expected rule high_amount to trigger",
+                "expected rule vip_user not to trigger",
+            ]
+        );
+    }
+}
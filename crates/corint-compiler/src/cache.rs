@@ -0,0 +1,196 @@
+//! Cache of compiled programs
+//!
+//! Reloading a repository recompiles every rule, ruleset and pipeline in it,
+//! though most are unchanged since the last load. A [`CompilationCache`]
+//! shared by the compilers of successive loads keeps the program compiled
+//! from each document, keyed by a SHA-256 hash of the document and of the
+//! compiler options, so only new or changed documents are compiled again.
+//!
+//! Documents are hashed once parsed and their imports resolved, so a change
+//! to an imported library file is picked up like a change to the document
+//! itself.
+
+use corint_core::ir::Program;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+/// Hash of a document and of the compiler options
+pub(crate) type CacheKey = [u8; 32];
+
+/// A compiled program, and whether it was used since the last sweep
+#[derive(Debug)]
+struct Entry {
+    program: Program,
+    used: bool,
+}
+
+/// Point-in-time statistics of a compilation cache
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct CompilationCacheStats {
+    /// Programs cached
+    pub entries: usize,
+
+    /// Documents whose program was found in the cache
+    pub hits: u64,
+
+    /// Documents compiled, then cached
+    pub misses: u64,
+}
+
+/// Programs compiled from unchanged documents, reused across loads
+#[derive(Debug, Default)]
+pub struct CompilationCache {
+    entries: Mutex<HashMap<CacheKey, Entry>>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl CompilationCache {
+    /// Create an empty cache
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Key of a document of `kind`, compiled in `context`
+    ///
+    /// `context` describes everything else the program depends on, such as
+    /// the compiler options. `None` if the document cannot be serialized.
+    pub(crate) fn key<T: Serialize>(kind: &str, document: &T, context: &str) -> Option<CacheKey> {
+        // Through a `Value`, whose maps are sorted, so maps of the document
+        // hash the same whatever their iteration order
+        let document = serde_json::to_vec(&serde_json::to_value(document).ok()?).ok()?;
+        let mut hasher = Sha256::new();
+        for part in [kind.as_bytes(), context.as_bytes(), &document] {
+            hasher.update((part.len() as u64).to_le_bytes());
+            hasher.update(part);
+        }
+        Some(hasher.finalize().into())
+    }
+
+    /// Program cached for `key`, counting a hit or a miss
+    pub(crate) fn get(&self, key: &CacheKey) -> Option<Program> {
+        let mut entries = self.entries.lock().unwrap();
+        let Some(entry) = entries.get_mut(key) else {
+            self.misses.fetch_add(1, Ordering::Relaxed);
+            return None;
+        };
+        entry.used = true;
+        self.hits.fetch_add(1, Ordering::Relaxed);
+        Some(entry.program.clone())
+    }
+
+    /// Cache the program compiled for `key`
+    pub(crate) fn insert(&self, key: CacheKey, program: Program) {
+        self.entries.lock().unwrap().insert(
+            key,
+            Entry {
+                program,
+                used: true,
+            },
+        );
+    }
+
+    /// Drop the programs not used since the last sweep
+    ///
+    /// Called after a complete load, this drops the programs of documents
+    /// that were changed or removed. Returns the number of programs dropped.
+    pub fn sweep(&self) -> usize {
+        let mut entries = self.entries.lock().unwrap();
+        let before = entries.len();
+        entries.retain(|_, entry| std::mem::take(&mut entry.used));
+        before - entries.len()
+    }
+
+    /// Number of programs cached
+    pub fn len(&self) -> usize {
+        self.entries.lock().unwrap().len()
+    }
+
+    /// Whether no programs are cached
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Current statistics
+    pub fn stats(&self) -> CompilationCacheStats {
+        CompilationCacheStats {
+            entries: self.len(),
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use corint_core::ir::{Instruction, ProgramMetadata};
+
+    fn program(id: &str) -> Program {
+        Program::new(
+            vec![Instruction::Return],
+            ProgramMetadata::for_rule(id.to_string()),
+        )
+    }
+
+    #[test]
+    fn test_key_depends_on_kind_document_and_context() {
+        let key = CompilationCache::key("rule", &"high_amount", "options").unwrap();
+
+        assert_eq!(
+            CompilationCache::key("rule", &"high_amount", "options"),
+            Some(key)
+        );
+        assert_ne!(
+            CompilationCache::key("ruleset", &"high_amount", "options"),
+            Some(key)
+        );
+        assert_ne!(
+            CompilationCache::key("rule", &"new_device", "options"),
+            Some(key)
+        );
+        assert_ne!(
+            CompilationCache::key("rule", &"high_amount", "other options"),
+            Some(key)
+        );
+    }
+
+    #[test]
+    fn test_key_ignores_map_order() {
+        let first: HashMap<String, u32> = (0..32).map(|i| (i.to_string(), i)).collect();
+        let second: HashMap<String, u32> = (0..32).rev().map(|i| (i.to_string(), i)).collect();
+
+        assert_eq!(
+            CompilationCache::key("rule", &first, ""),
+            CompilationCache::key("rule", &second, "")
+        );
+    }
+
+    #[test]
+    fn test_get_counts_hits_and_sweep_drops_unused() {
+        let cache = CompilationCache::new();
+        let kept = CompilationCache::key("rule", &"kept", "").unwrap();
+        let removed = CompilationCache::key("rule", &"removed", "").unwrap();
+
+        assert_eq!(cache.get(&kept), None);
+        cache.insert(kept, program("kept"));
+        cache.insert(removed, program("removed"));
+        assert_eq!(cache.sweep(), 0);
+
+        // Next load: only `kept` is still in the repository
+        assert_eq!(cache.get(&kept), Some(program("kept")));
+        assert_eq!(cache.sweep(), 1);
+        assert_eq!(cache.get(&removed), None);
+        assert_eq!(
+            cache.stats(),
+            CompilationCacheStats {
+                entries: 1,
+                hits: 1,
+                misses: 2,
+            }
+        );
+    }
+}
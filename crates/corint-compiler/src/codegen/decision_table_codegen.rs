@@ -0,0 +1,185 @@
+//! Decision table compiler
+//!
+//! Compiles DecisionTable AST nodes into IR programs. A table compiles into a
+//! rule program: rulesets list it by ID like a rule, and each row that
+//! applies adds its score and is recorded as triggered as `<table>.<row>`.
+
+use super::expression_codegen::ExpressionCompiler;
+use crate::error::Result;
+use corint_core::ast::{DecisionTable, DecisionTableRow, HitPolicy, Operator};
+use corint_core::ir::{Instruction, Program, ProgramMetadata, SourceSpan};
+
+/// Decision table compiler
+pub struct DecisionTableCompiler;
+
+impl DecisionTableCompiler {
+    /// Compile a decision table into an IR program
+    pub fn compile(table: &DecisionTable) -> Result<Program> {
+        let mut instructions = Vec::new();
+        let mut source_map = Vec::new();
+
+        // Only the first row to apply counts, unless all of them are collected
+        let stops_at_hit = table.hit_policy != HitPolicy::Collect;
+
+        for row in table.evaluation_order() {
+            let condition = Self::compile_row_condition(row, instructions.len(), &mut source_map)?;
+            let has_condition = !condition.is_empty();
+            instructions.extend(condition);
+
+            // Skip the row's score when one of its cells does not match
+            if has_condition {
+                let body_len = if stops_at_hit { 3 } else { 2 };
+                instructions.push(Instruction::JumpIfFalse {
+                    offset: body_len + 1,
+                });
+            }
+
+            instructions.push(Instruction::AddScore { value: row.score });
+            instructions.push(Instruction::MarkRuleTriggered {
+                rule_id: table.row_rule_id(row),
+            });
+            if stops_at_hit {
+                instructions.push(Instruction::Return);
+            }
+        }
+        instructions.push(Instruction::Return);
+
+        let mut metadata =
+            ProgramMetadata::for_rule(table.id.clone()).with_name(table.name.clone());
+        if let Some(description) = &table.description {
+            metadata = metadata.with_description(description.clone());
+        }
+        metadata.custom.insert(
+            "decision_table".to_string(),
+            table.hit_policy.as_str().to_string(),
+        );
+        metadata.source_map = source_map;
+
+        Ok(Program::new(instructions, metadata))
+    }
+
+    /// Instructions leaving on the stack whether every cell of `row` matches,
+    /// or none when the row has no conditions and always applies
+    ///
+    /// `base` is the position of the first instruction in the program; the
+    /// span of each cell is added to `source_map`.
+    fn compile_row_condition(
+        row: &DecisionTableRow,
+        base: usize,
+        source_map: &mut Vec<SourceSpan>,
+    ) -> Result<Vec<Instruction>> {
+        let mut instructions = Vec::new();
+
+        for (i, cell) in row.cells.iter().flatten().enumerate() {
+            let start = base + instructions.len();
+            instructions.extend(ExpressionCompiler::compile(cell)?);
+            source_map.push(SourceSpan::new(
+                start,
+                base + instructions.len(),
+                ExpressionCompiler::expression_to_string(cell),
+            ));
+
+            if i > 0 {
+                instructions.push(Instruction::BinaryOp { op: Operator::And });
+            }
+        }
+
+        Ok(instructions)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use corint_core::ast::{DecisionTableRow, Expression};
+    use corint_core::Value;
+
+    fn amount_above(threshold: f64) -> Option<Expression> {
+        Some(Expression::binary(
+            Expression::field_access(vec!["event".to_string(), "amount".to_string()]),
+            Operator::Gt,
+            Expression::literal(Value::Number(threshold)),
+        ))
+    }
+
+    fn table(hit_policy: HitPolicy) -> DecisionTable {
+        DecisionTable::new(
+            "amount_risk".to_string(),
+            "Amount Risk".to_string(),
+            vec![Expression::field_access(vec![
+                "event".to_string(),
+                "amount".to_string(),
+            ])],
+        )
+        .with_hit_policy(hit_policy)
+        .add_row(DecisionTableRow::new(
+            "large".to_string(),
+            vec![amount_above(1000.0)],
+            30,
+        ))
+        .add_row(
+            DecisionTableRow::new("huge".to_string(), vec![amount_above(10000.0)], 80)
+                .with_priority(10),
+        )
+        .add_row(DecisionTableRow::new("any".to_string(), vec![None], 5))
+    }
+
+    #[test]
+    fn test_compile_first_hit_policy() {
+        let program = DecisionTableCompiler::compile(&table(HitPolicy::First)).unwrap();
+
+        assert_eq!(program.metadata.source_type, "rule");
+        assert_eq!(program.metadata.source_id, "amount_risk");
+        assert_eq!(
+            program.metadata.custom.get("decision_table"),
+            Some(&"first".to_string())
+        );
+        // Each row with cells: condition (3), JumpIfFalse, AddScore, Mark, Return
+        assert_eq!(
+            program.instructions[3],
+            Instruction::JumpIfFalse { offset: 4 }
+        );
+        assert_eq!(
+            program.instructions[5],
+            Instruction::MarkRuleTriggered {
+                rule_id: "amount_risk.large".to_string()
+            }
+        );
+        // The row without conditions always applies
+        assert_eq!(
+            &program.instructions[14..],
+            &[
+                Instruction::AddScore { value: 5 },
+                Instruction::MarkRuleTriggered {
+                    rule_id: "amount_risk.any".to_string()
+                },
+                Instruction::Return,
+                Instruction::Return,
+            ]
+        );
+        assert_eq!(program.metadata.source_map.len(), 2);
+    }
+
+    #[test]
+    fn test_compile_priority_and_collect_hit_policies() {
+        let program = DecisionTableCompiler::compile(&table(HitPolicy::Priority)).unwrap();
+        assert_eq!(
+            program.instructions[5],
+            Instruction::MarkRuleTriggered {
+                rule_id: "amount_risk.huge".to_string()
+            }
+        );
+
+        let program = DecisionTableCompiler::compile(&table(HitPolicy::Collect)).unwrap();
+        assert_eq!(
+            program.instructions[3],
+            Instruction::JumpIfFalse { offset: 3 }
+        );
+        let returns = program
+            .instructions
+            .iter()
+            .filter(|instruction| matches!(instruction, Instruction::Return))
+            .count();
+        assert_eq!(returns, 1);
+    }
+}
@@ -3,9 +3,7 @@
 //! Compiles Expression AST nodes into IR instructions.
 
 use crate::error::{CompileError, Result};
-#[cfg(test)]
-use corint_core::ast::UnaryOperator;
-use corint_core::ast::{Expression, LogicalGroupOp, Operator};
+use corint_core::ast::{Expression, LogicalGroupOp, Operator, UnaryOperator};
 use corint_core::ir::Instruction;
 
 /// Expression compiler
@@ -79,6 +77,10 @@ impl ExpressionCompiler {
                 Ok(instructions)
             }
 
+            Expression::FunctionCall { name, args } if name == "coalesce" => {
+                Self::compile_coalesce(args)
+            }
+
             Expression::FunctionCall { name, args: _ } => {
                 // For now, we'll handle function calls as a placeholder
                 // In a real implementation, this would analyze the function
@@ -144,9 +146,85 @@ impl ExpressionCompiler {
                     field: field.clone(),
                 }])
             }
+
+            Expression::Quantifier {
+                op,
+                collection,
+                binding,
+                predicate,
+            } => {
+                // Push the array, then evaluate the predicate once per element
+                let predicate = Self::compile(predicate)?;
+                if let Some(unsupported) = predicate.iter().find(|i| !i.allowed_in_predicate()) {
+                    return Err(CompileError::UnsupportedFeature(format!(
+                        "{} cannot be used in the predicate of {}",
+                        unsupported.opcode(),
+                        op.name()
+                    )));
+                }
+
+                let mut instructions = Self::compile(collection)?;
+                instructions.push(Instruction::IterateArray {
+                    op: *op,
+                    binding: binding.clone(),
+                    predicate,
+                });
+                Ok(instructions)
+            }
+
+            Expression::Aggregate {
+                op,
+                collection,
+                path,
+            } => {
+                // Push the array, then reduce it to a single value
+                let mut instructions = Self::compile(collection)?;
+                instructions.push(Instruction::AggregateArray {
+                    op: *op,
+                    path: path.clone(),
+                });
+                Ok(instructions)
+            }
         }
     }
 
+    /// Compile `coalesce(a, b, ...)`: the first argument that is not null
+    ///
+    /// Arguments after it are not evaluated. If all are null, the result is null.
+    fn compile_coalesce(args: &[Expression]) -> Result<Vec<Instruction>> {
+        let (last, rest) = args.split_last().ok_or_else(|| {
+            CompileError::InvalidExpression("coalesce requires at least one argument".to_string())
+        })?;
+
+        let mut instructions = Vec::new();
+        let mut jump_to_end_offsets = Vec::new();
+
+        for arg in rest {
+            // Keep a copy of the value: JumpIfTrue pops the null check
+            instructions.extend(Self::compile(arg)?);
+            instructions.push(Instruction::Dup);
+            instructions.push(Instruction::UnaryOp {
+                op: UnaryOperator::IsNotNull,
+            });
+            jump_to_end_offsets.push(instructions.len());
+            instructions.push(Instruction::JumpIfTrue { offset: 0 });
+
+            // Null: discard it and try the next argument
+            instructions.push(Instruction::Pop);
+        }
+
+        instructions.extend(Self::compile(last)?);
+
+        let end_pos = instructions.len();
+        for jump_pos in jump_to_end_offsets {
+            if let Instruction::JumpIfTrue { offset } = &mut instructions[jump_pos] {
+                *offset = (end_pos - jump_pos) as isize;
+            }
+        }
+
+        Ok(instructions)
+    }
+
     /// Compile 'any' logical group (OR logic with short-circuit evaluation)
     /// Returns true if ANY condition is true
     ///
@@ -420,6 +498,34 @@ impl ExpressionCompiler {
                     "expression": expr_str
                 })
             }
+            Expression::Quantifier {
+                op,
+                collection,
+                binding,
+                predicate,
+            } => {
+                json!({
+                    "type": "quantifier",
+                    "quantifier": op.name(),
+                    "collection": Self::expression_to_json(collection),
+                    "binding": binding,
+                    "predicate": Self::expression_to_json(predicate),
+                    "expression": Self::expression_to_string(expr)
+                })
+            }
+            Expression::Aggregate {
+                op,
+                collection,
+                path,
+            } => {
+                json!({
+                    "type": "aggregate",
+                    "aggregate": op.name(),
+                    "collection": Self::expression_to_json(collection),
+                    "path": path,
+                    "expression": Self::expression_to_string(expr)
+                })
+            }
         }
     }
 
@@ -461,9 +567,15 @@ impl ExpressionCompiler {
                     Self::expression_to_string(right)
                 )
             }
-            Expression::Unary { op, operand } => {
-                format!("{:?} {}", op, Self::expression_to_string(operand))
-            }
+            Expression::Unary { op, operand } => match op {
+                UnaryOperator::IsNull => {
+                    format!("{} is null", Self::expression_to_string(operand))
+                }
+                UnaryOperator::IsNotNull => {
+                    format!("{} is not null", Self::expression_to_string(operand))
+                }
+                _ => format!("{:?} {}", op, Self::expression_to_string(operand)),
+            },
             Expression::FunctionCall { name, args } => {
                 let args_str = args
                     .iter()
@@ -491,6 +603,30 @@ impl ExpressionCompiler {
                     None => format!("result.{}", field),
                 }
             }
+            Expression::Quantifier {
+                op,
+                collection,
+                binding,
+                predicate,
+            } => format!(
+                "{}({}, {}, {})",
+                op.name(),
+                Self::expression_to_string(collection),
+                binding,
+                Self::expression_to_string(predicate)
+            ),
+            Expression::Aggregate {
+                op,
+                collection,
+                path,
+            } => {
+                let collection = Self::expression_to_string(collection);
+                if path.is_empty() {
+                    format!("{}({})", op.name(), collection)
+                } else {
+                    format!("{}({}[*].{})", op.name(), collection, path.join("."))
+                }
+            }
         }
     }
 
@@ -525,6 +661,7 @@ impl ExpressionCompiler {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use corint_core::ast::{AggregateOp, QuantifierOp};
     use corint_core::Value;
 
     #[test]
@@ -637,6 +774,94 @@ mod tests {
         assert!(matches!(instructions[2], Instruction::BinaryOp { .. }));
     }
 
+    #[test]
+    fn test_compile_quantifier() {
+        // any_of(event.items, item.price > 1000)
+        let expr = Expression::quantifier(
+            QuantifierOp::AnyOf,
+            Expression::field_access(vec!["event".to_string(), "items".to_string()]),
+            "item".to_string(),
+            Expression::binary(
+                Expression::field_access(vec!["item".to_string(), "price".to_string()]),
+                Operator::Gt,
+                Expression::literal(Value::Number(1000.0)),
+            ),
+        );
+
+        let instructions = ExpressionCompiler::compile(&expr).unwrap();
+
+        // LoadField(event.items), IterateArray { LoadField(item.price), LoadConst(1000), Compare(>) }
+        assert_eq!(instructions.len(), 2);
+        assert!(matches!(instructions[0], Instruction::LoadField { .. }));
+        if let Instruction::IterateArray {
+            op,
+            binding,
+            predicate,
+        } = &instructions[1]
+        {
+            assert_eq!(*op, QuantifierOp::AnyOf);
+            assert_eq!(binding, "item");
+            assert_eq!(predicate.len(), 3);
+            assert!(matches!(predicate[2], Instruction::Compare { .. }));
+        } else {
+            panic!("Expected IterateArray instruction");
+        }
+
+        assert_eq!(
+            ExpressionCompiler::expression_to_string(&expr),
+            "any_of(event.items, item, item.price > 1000)"
+        );
+
+        // Ruleset results are not available to predicates
+        let expr = Expression::quantifier(
+            QuantifierOp::AllOf,
+            Expression::field_access(vec!["event".to_string(), "items".to_string()]),
+            "item".to_string(),
+            Expression::binary(
+                Expression::field_access(vec!["item".to_string(), "price".to_string()]),
+                Operator::Lt,
+                Expression::ResultAccess {
+                    ruleset_id: None,
+                    field: "score".to_string(),
+                },
+            ),
+        );
+        assert!(matches!(
+            ExpressionCompiler::compile(&expr),
+            Err(CompileError::UnsupportedFeature(_))
+        ));
+    }
+
+    #[test]
+    fn test_compile_aggregate() {
+        // sum(event.items[*].amount) > 5000
+        let expr = Expression::binary(
+            Expression::aggregate(
+                AggregateOp::Sum,
+                Expression::field_access(vec!["event".to_string(), "items".to_string()]),
+                vec!["amount".to_string()],
+            ),
+            Operator::Gt,
+            Expression::literal(Value::Number(5000.0)),
+        );
+
+        let instructions = ExpressionCompiler::compile(&expr).unwrap();
+
+        // LoadField(event.items), AggregateArray(sum, amount), LoadConst(5000), Compare(>)
+        assert_eq!(instructions.len(), 4);
+        assert!(matches!(instructions[0], Instruction::LoadField { .. }));
+        assert!(matches!(
+            &instructions[1],
+            Instruction::AggregateArray { op: AggregateOp::Sum, path } if path == &vec!["amount".to_string()]
+        ));
+        assert!(matches!(instructions[3], Instruction::Compare { .. }));
+
+        assert_eq!(
+            ExpressionCompiler::expression_to_string(&expr),
+            "sum(event.items[*].amount) > 5000"
+        );
+    }
+
     #[test]
     fn test_compile_any_logical_group_empty() {
         // any: [] should evaluate to false
@@ -816,4 +1041,37 @@ mod tests {
             }
         )); // push false
     }
+
+    #[test]
+    fn test_compile_coalesce() {
+        let expr = Expression::function_call(
+            "coalesce".to_string(),
+            vec![
+                Expression::field_access(vec!["user".to_string(), "tier".to_string()]),
+                Expression::literal(Value::String("basic".to_string())),
+            ],
+        );
+        let instructions = ExpressionCompiler::compile(&expr).unwrap();
+
+        assert_eq!(
+            instructions,
+            vec![
+                Instruction::LoadField {
+                    path: vec!["user".to_string(), "tier".to_string()]
+                },
+                Instruction::Dup,
+                Instruction::UnaryOp {
+                    op: UnaryOperator::IsNotNull
+                },
+                Instruction::JumpIfTrue { offset: 3 },
+                Instruction::Pop,
+                Instruction::LoadConst {
+                    value: Value::String("basic".to_string())
+                },
+            ]
+        );
+
+        let empty = Expression::function_call("coalesce".to_string(), Vec::new());
+        assert!(ExpressionCompiler::compile(&empty).is_err());
+    }
 }
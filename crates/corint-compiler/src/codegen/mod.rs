@@ -2,13 +2,17 @@
 //!
 //! This module contains code generators that transform AST into IR.
 
+pub mod decision_table_codegen;
 pub mod expression_codegen;
 pub mod pipeline_codegen;
 pub mod rule_codegen;
 pub mod ruleset_codegen;
+pub mod sql_codegen;
 
 // Re-export for convenience and backward compatibility
+pub use decision_table_codegen::DecisionTableCompiler;
 pub use expression_codegen::ExpressionCompiler;
 pub use pipeline_codegen::PipelineCompiler;
 pub use rule_codegen::RuleCompiler;
 pub use ruleset_codegen::RulesetCompiler;
+pub use sql_codegen::{SqlCompiler, SqlDialect, SqlRule, SqlRuleset};
@@ -3,10 +3,11 @@
 //! Main compilation logic for pipelines with DAG structure.
 
 use crate::error::{CompileError, Result};
+use crate::plugin::StepPlugins;
 use super::condition_compiler::compile_when_block;
 use super::instruction_gen::compile_step;
 use super::metadata_builder::build_steps_metadata;
-use super::validator::topological_sort;
+use super::validator::{topological_sort, validate_variable_declarations};
 use corint_core::ast::pipeline::Pipeline;
 use corint_core::ir::{Instruction, Program, ProgramMetadata};
 use std::collections::HashMap;
@@ -24,15 +25,19 @@ pub(super) struct CompileContext {
 
     /// Position of pipeline when guard jump instruction (if present)
     pub(super) pipeline_when_guard_pos: Option<usize>,
+
+    /// Plugins compiling the steps of custom types
+    pub(super) plugins: StepPlugins,
 }
 
 impl CompileContext {
-    pub(super) fn new() -> Self {
+    pub(super) fn new(plugins: StepPlugins) -> Self {
         Self {
             step_positions: HashMap::new(),
             pending_jumps: Vec::new(),
             instructions: Vec::new(),
             pipeline_when_guard_pos: None,
+            plugins,
         }
     }
 
@@ -50,7 +55,6 @@ impl CompileContext {
     }
 
     /// Add a conditional jump instruction that needs to be resolved later
-    #[allow(dead_code)]
     pub(super) fn add_pending_conditional_jump(&mut self, target: String, jump_if_true: bool) {
         let jump_pos = self.instructions.len();
         if jump_if_true {
@@ -73,6 +77,12 @@ pub struct PipelineCompiler;
 impl PipelineCompiler {
     /// Compile a pipeline into an IR program
     pub fn compile(pipeline: &Pipeline) -> Result<Program> {
+        Self::compile_with_plugins(pipeline, &StepPlugins::new())
+    }
+
+    /// Compile a pipeline into an IR program, with `plugins` compiling the
+    /// steps of custom types
+    pub fn compile_with_plugins(pipeline: &Pipeline, plugins: &StepPlugins) -> Result<Program> {
         // Check if this is the new DAG format
         if pipeline.entry.is_empty() {
             return Err(CompileError::UnsupportedFeature(
@@ -80,7 +90,7 @@ impl PipelineCompiler {
             ));
         }
 
-        let mut ctx = CompileContext::new();
+        let mut ctx = CompileContext::new(plugins.clone());
 
         // Step 0: Compile pipeline-level when condition if present
         // This acts as a guard - if condition fails, skip entire pipeline
@@ -99,6 +109,7 @@ impl PipelineCompiler {
 
         // Step 1: Topological sort - get ordered list of reachable steps from entry
         let sorted_steps = topological_sort(pipeline)?;
+        validate_variable_declarations(&sorted_steps)?;
 
         // Step 2: Compile each step in order
         for step in &sorted_steps {
@@ -130,6 +141,25 @@ impl PipelineCompiler {
         let steps_json = build_steps_metadata(&sorted_steps);
         metadata = metadata.with_custom("steps_json".to_string(), steps_json);
 
+        // Step 7b: Add time budgets, enforced by the executor
+        if let Some(timeout_ms) = pipeline.timeout_ms {
+            metadata = metadata.with_custom("timeout_ms".to_string(), timeout_ms.to_string());
+        }
+        let step_timeouts: Vec<String> = sorted_steps
+            .iter()
+            .filter_map(|step| step.timeout_ms.map(|ms| format!("{}={}", step.id, ms)))
+            .collect();
+        if !step_timeouts.is_empty() {
+            metadata = metadata.with_custom("step_timeouts".to_string(), step_timeouts.join(","));
+        }
+
+        // Step 7c: Add the fallback decision, applied when a step fails
+        if let Some(ref on_error) = pipeline.on_error {
+            if let Ok(on_error_json) = serde_json::to_string(on_error) {
+                metadata = metadata.with_custom("on_error".to_string(), on_error_json);
+            }
+        }
+
         // Step 8: Compile pipeline decision logic separately if present
         // Decision logic runs AFTER rulesets have been executed
         if let Some(ref decision_rules) = pipeline.decision {
@@ -285,7 +315,7 @@ mod tests {
     use super::*;
     use corint_core::ast::pipeline::{PipelineStep, Route};
     use corint_core::ast::rule::{Condition, ConditionGroup};
-    use corint_core::ast::{Expression, Operator, WhenBlock};
+    use corint_core::ast::{ErrorPolicy, Expression, Operator, PipelineOnError, WhenBlock};
     use corint_core::Value;
 
     #[test]
@@ -317,6 +347,139 @@ mod tests {
         assert!(matches!(program.instructions[3], Instruction::Return));
     }
 
+    #[test]
+    fn test_compile_set_step() {
+        use corint_core::ast::pipeline::VariableAssignment;
+
+        let pipeline = Pipeline::new(
+            "test_pipeline".to_string(),
+            "Test Pipeline".to_string(),
+            "assign".to_string(),
+        )
+        .add_step(
+            PipelineStep::set(
+                "assign".to_string(),
+                "Assign Variables".to_string(),
+                vec![
+                    VariableAssignment {
+                        name: "risk_band".to_string(),
+                        value: Expression::literal(Value::String("high".to_string())),
+                    },
+                    VariableAssignment {
+                        name: "limit".to_string(),
+                        value: Expression::binary(
+                            Expression::field_access(vec![
+                                "event".to_string(),
+                                "amount".to_string(),
+                            ]),
+                            Operator::Mul,
+                            Expression::literal(Value::Number(2.0)),
+                        ),
+                    },
+                ],
+            )
+            .with_next("end".to_string()),
+        );
+
+        let program = PipelineCompiler::compile(&pipeline).unwrap();
+
+        let stores: Vec<&str> = program
+            .instructions
+            .iter()
+            .filter_map(|i| match i {
+                Instruction::Store { name } => Some(name.as_str()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(stores, vec!["vars.risk_band", "vars.limit"]);
+    }
+
+    #[test]
+    fn test_compile_let_and_assign_steps() {
+        use corint_core::ast::pipeline::VariableAssignment;
+
+        let assignment = |value: f64| {
+            vec![VariableAssignment {
+                name: "normalized_amount".to_string(),
+                value: Expression::literal(Value::Number(value)),
+            }]
+        };
+        let pipeline = |assign_step: PipelineStep| {
+            Pipeline::new(
+                "test_pipeline".to_string(),
+                "Test Pipeline".to_string(),
+                "normalize".to_string(),
+            )
+            .add_step(
+                PipelineStep::declare(
+                    "normalize".to_string(),
+                    "Normalize".to_string(),
+                    assignment(10.0),
+                )
+                .with_next("cap".to_string()),
+            )
+            .add_step(assign_step.with_next("end".to_string()))
+        };
+
+        let program = PipelineCompiler::compile(&pipeline(PipelineStep::assign(
+            "cap".to_string(),
+            "Cap".to_string(),
+            assignment(5.0),
+        )))
+        .unwrap();
+        let variables: Vec<_> = program
+            .instructions
+            .iter()
+            .filter(|i| matches!(i, Instruction::Declare { .. } | Instruction::Assign { .. }))
+            .collect();
+        assert_eq!(
+            variables,
+            vec![
+                &Instruction::Declare {
+                    name: "normalized_amount".to_string()
+                },
+                &Instruction::Assign {
+                    name: "normalized_amount".to_string()
+                },
+            ]
+        );
+
+        // Declaring the same variable twice is rejected
+        let result = PipelineCompiler::compile(&pipeline(PipelineStep::declare(
+            "cap".to_string(),
+            "Cap".to_string(),
+            assignment(5.0),
+        )));
+        assert!(matches!(result, Err(CompileError::CompileError(_))));
+
+        // So is assigning a variable that is never declared
+        let mut undeclared = assignment(5.0);
+        undeclared[0].name = "limit".to_string();
+        let result = PipelineCompiler::compile(&pipeline(PipelineStep::assign(
+            "cap".to_string(),
+            "Cap".to_string(),
+            undeclared,
+        )));
+        assert!(matches!(result, Err(CompileError::UndefinedSymbol(_))));
+
+        // Set steps may not bypass the declarations
+        let result = PipelineCompiler::compile(&pipeline(PipelineStep::set(
+            "cap".to_string(),
+            "Cap".to_string(),
+            assignment(5.0),
+        )));
+        assert!(matches!(result, Err(CompileError::CompileError(_))));
+
+        let mut undeclared = assignment(5.0);
+        undeclared[0].name = "limit".to_string();
+        let result = PipelineCompiler::compile(&pipeline(PipelineStep::set(
+            "cap".to_string(),
+            "Cap".to_string(),
+            undeclared,
+        )));
+        assert!(matches!(result, Err(CompileError::UndefinedSymbol(_))));
+    }
+
     #[test]
     fn test_compile_router_pipeline() {
         // Create pipeline with router
@@ -370,6 +533,117 @@ mod tests {
         assert!(program.instructions.iter().any(|i| matches!(i, Instruction::JumpIfFalse { .. })));
     }
 
+    #[test]
+    fn test_compile_timeouts() {
+        let pipeline = Pipeline::new(
+            "test_pipeline".to_string(),
+            "Test Pipeline".to_string(),
+            "enrich".to_string(),
+        )
+        .with_timeout_ms(800)
+        .add_step(
+            PipelineStep::api("enrich".to_string(), "Enrich".to_string(), "ipinfo".to_string())
+                .with_timeout_ms(250)
+                .with_next("score".to_string()),
+        )
+        .add_step(
+            PipelineStep::ruleset(
+                "score".to_string(),
+                "Score".to_string(),
+                "fraud_rules".to_string(),
+            )
+            .with_next("end".to_string()),
+        );
+
+        let program = PipelineCompiler::compile(&pipeline).unwrap();
+
+        let custom = &program.metadata.custom;
+        assert_eq!(custom.get("timeout_ms").map(String::as_str), Some("800"));
+        assert_eq!(custom.get("step_timeouts").map(String::as_str), Some("enrich=250"));
+    }
+
+    #[test]
+    fn test_compile_on_error() {
+        let pipeline = Pipeline::new(
+            "test_pipeline".to_string(),
+            "Test Pipeline".to_string(),
+            "score".to_string(),
+        )
+        .with_on_error(PipelineOnError::new(ErrorPolicy::Review))
+        .add_step(PipelineStep::ruleset(
+            "score".to_string(),
+            "Score".to_string(),
+            "fraud_rules".to_string(),
+        ));
+
+        let program = PipelineCompiler::compile(&pipeline).unwrap();
+
+        let on_error: PipelineOnError =
+            serde_json::from_str(&program.metadata.custom["on_error"]).unwrap();
+        assert_eq!(on_error, PipelineOnError::new(ErrorPolicy::Review));
+    }
+
+    #[test]
+    fn test_compile_step_when_guard() {
+        let kyc_pending = WhenBlock {
+            event_type: None,
+            condition_group: Some(ConditionGroup::All(vec![Condition::Expression(
+                Expression::binary(
+                    Expression::field_access(vec!["event".to_string(), "kyc_verified".to_string()]),
+                    Operator::Ne,
+                    Expression::literal(Value::Bool(true)),
+                ),
+            )])),
+            conditions: None,
+        };
+        let pipeline = |document_check: PipelineStep| {
+            Pipeline::new(
+                "onboarding".to_string(),
+                "Onboarding".to_string(),
+                "documents".to_string(),
+            )
+            .add_step(document_check.with_when(kyc_pending.clone()))
+            .add_step(
+                PipelineStep::ruleset(
+                    "score".to_string(),
+                    "Score".to_string(),
+                    "onboarding_rules".to_string(),
+                )
+                .with_next("end".to_string()),
+            )
+        };
+        let documents = PipelineStep::ruleset(
+            "documents".to_string(),
+            "Document Check".to_string(),
+            "document_rules".to_string(),
+        );
+        fn guard_target(program: &Program) -> &Instruction {
+            let (pos, offset) = program
+                .instructions
+                .iter()
+                .enumerate()
+                .find_map(|(pos, i)| match i {
+                    Instruction::JumpIfFalse { offset } => Some((pos, *offset)),
+                    _ => None,
+                })
+                .unwrap();
+            &program.instructions[(pos as isize + offset) as usize]
+        }
+
+        // A skipped step continues with its next step
+        let documents = documents.with_next("score".to_string());
+        let program = PipelineCompiler::compile(&pipeline(documents.clone())).unwrap();
+        assert!(matches!(
+            guard_target(&program),
+            Instruction::MarkStepExecuted { step_id, .. } if step_id == "score"
+        ));
+
+        // else.skip_to takes precedence over next
+        let step = documents.with_skip_to("end".to_string());
+        let program = PipelineCompiler::compile(&pipeline(step)).unwrap();
+        assert!(matches!(guard_target(&program), Instruction::Return));
+    }
+
     #[test]
     fn test_legacy_pipeline_format_not_supported() {
         let pipeline = Pipeline {
@@ -378,6 +652,9 @@ mod tests {
             description: None,
             entry: String::new(), // Empty entry indicates legacy format
             when: None,
+            timeout_ms: None,
+            on_error: None,
+            schema: None,
             steps: vec![],
             decision: None,
             metadata: None,
@@ -2,6 +2,7 @@
 //!
 //! Generates IR instructions for different types of pipeline steps.
 
+use crate::codegen::expression_codegen::ExpressionCompiler;
 use crate::error::Result;
 use super::compiler::CompileContext;
 use super::condition_compiler::compile_when_block;
@@ -14,9 +15,10 @@ use std::collections::HashMap;
 /// Compile a single pipeline step
 pub(super) fn compile_step(step: &PipelineStep, ctx: &mut CompileContext) -> Result<()> {
     // Check step-level when condition if present
-    if let Some(when) = &step.when {
-        compile_step_when_guard(when, step, ctx)?;
-    }
+    let guard_pos = match &step.when {
+        Some(when) => compile_step_when_guard(when, step, ctx)?,
+        None => None,
+    };
 
     // Compile based on step type
     match step.step_type.as_str() {
@@ -26,31 +28,54 @@ pub(super) fn compile_step(step: &PipelineStep, ctx: &mut CompileContext) -> Res
         "service" => compile_service_step(step, ctx),
         "api" => compile_api_step(step, ctx),
         "trigger" => compile_trigger_step(step, ctx),
+        "emit" => compile_emit_step(step, ctx),
         "rule" => compile_rule_step(step, ctx),
         "pipeline" => compile_subpipeline_step(step, ctx),
-        _ => {
-            // Unknown step types are allowed but do nothing
-            compile_next_jump(step, ctx)
+        "set" => compile_set_step(step, ctx),
+        "let" | "assign" => compile_let_or_assign_step(step, ctx),
+        _ => compile_custom_step(step, ctx),
+    }?;
+
+    // A skipped step without a target falls through past its instructions
+    if let Some(pos) = guard_pos {
+        let offset = (ctx.instructions.len() as isize) - (pos as isize);
+        if let Instruction::JumpIfFalse { offset: o } = &mut ctx.instructions[pos] {
+            *o = offset;
         }
     }
+
+    Ok(())
 }
 
 /// Compile step-level when condition as a guard
+///
+/// When the condition is false the step is skipped: execution continues with
+/// the `else.skip_to` target, or the step's `next`. Without either, returns
+/// the position of the guard jump, to be backfilled past the step's own
+/// instructions once they are compiled.
 fn compile_step_when_guard(
-    _when: &WhenBlock,
-    _step: &PipelineStep,
-    _ctx: &mut CompileContext,
-) -> Result<()> {
-    // TODO: Implement proper step-level when guards
-    // For now, step-level when conditions are not used in comprehensive_dsl_demo.yaml
-    // So we'll leave this as a no-op to avoid breaking existing functionality
-    //
-    // When implementing, we need to:
-    // 1. Compile the when condition
-    // 2. Add JumpIfFalse with proper offset calculation
-    // 3. Handle the case where the step should be skipped
-
-    Ok(())
+    when: &WhenBlock,
+    step: &PipelineStep,
+    ctx: &mut CompileContext,
+) -> Result<Option<usize>> {
+    ctx.instructions.extend(compile_when_block(when)?);
+
+    let target = step
+        .else_branch
+        .as_ref()
+        .map(|else_branch| else_branch.skip_to.clone())
+        .or_else(|| get_next_step_id(step));
+    match target {
+        Some(target) => {
+            ctx.add_pending_conditional_jump(target, false);
+            Ok(None)
+        }
+        None => {
+            let pos = ctx.instructions.len();
+            ctx.instructions.push(Instruction::JumpIfFalse { offset: 0 });
+            Ok(Some(pos))
+        }
+    }
 }
 
 /// Compile a router step
@@ -258,6 +283,65 @@ fn compile_trigger_step(step: &PipelineStep, ctx: &mut CompileContext) -> Result
     compile_next_jump(step, ctx)
 }
 
+/// Compile an emit step
+///
+/// The payload is evaluated when the step runs; the side effect itself is
+/// only dispatched once the decision is final.
+fn compile_emit_step(step: &PipelineStep, ctx: &mut CompileContext) -> Result<()> {
+    let next_step_id = get_next_step_id(step);
+
+    // Mark step as executed
+    ctx.instructions.push(Instruction::MarkStepExecuted {
+        step_id: step.id.clone(),
+        next_step_id,
+        route_index: None,
+        is_default_route: false,
+    });
+
+    if let StepDetails::Emit {
+        emit,
+        target,
+        payload,
+        signals,
+    } = &step.details
+    {
+        // Sorted so the same step always compiles to the same program
+        let mut fields: Vec<_> = payload.iter().flatten().collect();
+        fields.sort_by_key(|(name, _)| *name);
+        for (_, value) in &fields {
+            ctx.instructions.extend(ExpressionCompiler::compile(value)?);
+        }
+
+        ctx.instructions.push(Instruction::Emit {
+            step_id: step.id.clone(),
+            kind: *emit,
+            target: target.clone(),
+            fields: fields.into_iter().map(|(name, _)| name.clone()).collect(),
+            signals: signals.clone(),
+        });
+    }
+
+    compile_next_jump(step, ctx)
+}
+
+/// Compile a step of a custom type with the plugin registered for it
+fn compile_custom_step(step: &PipelineStep, ctx: &mut CompileContext) -> Result<()> {
+    // Unknown step types without fields are allowed but do nothing
+    let Some(instructions) = ctx.plugins.compile_step(step)? else {
+        return compile_next_jump(step, ctx);
+    };
+
+    ctx.instructions.push(Instruction::MarkStepExecuted {
+        step_id: step.id.clone(),
+        next_step_id: get_next_step_id(step),
+        route_index: None,
+        is_default_route: false,
+    });
+    ctx.instructions.extend(instructions);
+
+    compile_next_jump(step, ctx)
+}
+
 /// Compile a rule step (single rule execution)
 fn compile_rule_step(step: &PipelineStep, ctx: &mut CompileContext) -> Result<()> {
     let next_step_id = get_next_step_id(step);
@@ -294,6 +378,69 @@ fn compile_subpipeline_step(step: &PipelineStep, ctx: &mut CompileContext) -> Re
     compile_next_jump(step, ctx)
 }
 
+/// Compile a set step (variable assignments into the vars namespace)
+fn compile_set_step(step: &PipelineStep, ctx: &mut CompileContext) -> Result<()> {
+    let next_step_id = get_next_step_id(step);
+
+    // Mark step as executed
+    ctx.instructions.push(Instruction::MarkStepExecuted {
+        step_id: step.id.clone(),
+        next_step_id,
+        route_index: None,
+        is_default_route: false,
+    });
+
+    if let StepDetails::Set { set } = &step.details {
+        // Evaluate each value and store it, in order, so later assignments can read earlier ones
+        for assignment in set {
+            ctx.instructions
+                .extend(ExpressionCompiler::compile(&assignment.value)?);
+            ctx.instructions.push(Instruction::Store {
+                name: format!("vars.{}", assignment.name),
+            });
+        }
+    }
+
+    compile_next_jump(step, ctx)
+}
+
+/// Compile a let or assign step (declared variables in the vars namespace)
+fn compile_let_or_assign_step(step: &PipelineStep, ctx: &mut CompileContext) -> Result<()> {
+    let next_step_id = get_next_step_id(step);
+
+    // Mark step as executed
+    ctx.instructions.push(Instruction::MarkStepExecuted {
+        step_id: step.id.clone(),
+        next_step_id,
+        route_index: None,
+        is_default_route: false,
+    });
+
+    match &step.details {
+        StepDetails::Let { declare } => {
+            for declaration in declare {
+                ctx.instructions
+                    .extend(ExpressionCompiler::compile(&declaration.value)?);
+                ctx.instructions.push(Instruction::Declare {
+                    name: declaration.name.clone(),
+                });
+            }
+        }
+        StepDetails::Assign { assign } => {
+            for assignment in assign {
+                ctx.instructions
+                    .extend(ExpressionCompiler::compile(&assignment.value)?);
+                ctx.instructions.push(Instruction::Assign {
+                    name: assignment.name.clone(),
+                });
+            }
+        }
+        _ => {}
+    }
+
+    compile_next_jump(step, ctx)
+}
+
 /// Compile the unconditional next jump for a step
 fn compile_next_jump(step: &PipelineStep, ctx: &mut CompileContext) -> Result<()> {
     if let Some(next) = &step.next {
@@ -86,6 +86,20 @@ pub(super) fn build_steps_metadata(steps: &[&PipelineStep]) -> String {
                 StepDetails::SubPipeline { pipeline_id } => {
                     info["sub_pipeline"] = serde_json::Value::String(pipeline_id.clone());
                 }
+                StepDetails::Set { set: assignments }
+                | StepDetails::Let {
+                    declare: assignments,
+                }
+                | StepDetails::Assign {
+                    assign: assignments,
+                } => {
+                    info[step.step_type.as_str()] = serde_json::Value::Array(
+                        assignments
+                            .iter()
+                            .map(|a| serde_json::Value::String(a.name.clone()))
+                            .collect(),
+                    );
+                }
                 _ => {}
             }
 
@@ -174,6 +188,12 @@ fn expression_to_string(expr: &corint_core::ast::Expression) -> String {
             let op_symbol = match op {
                 UnaryOperator::Not => "!",
                 UnaryOperator::Negate => "-",
+                UnaryOperator::IsNull => {
+                    return format!("{} is null", expression_to_string(operand))
+                }
+                UnaryOperator::IsNotNull => {
+                    return format!("{} is not null", expression_to_string(operand))
+                }
             };
             format!("{}{}", op_symbol, expression_to_string(operand))
         }
@@ -215,6 +235,34 @@ fn expression_to_string(expr: &corint_core::ast::Expression) -> String {
                 None => format!("result.{}", field),
             }
         }
+        Expression::Quantifier {
+            op,
+            collection,
+            binding,
+            predicate,
+        } => format!(
+            "{}({}, {}, {})",
+            op.name(),
+            expression_to_string(collection),
+            binding,
+            expression_to_string(predicate)
+        ),
+        Expression::Aggregate {
+            op,
+            collection,
+            path,
+        } => {
+            if path.is_empty() {
+                format!("{}({})", op.name(), expression_to_string(collection))
+            } else {
+                format!(
+                    "{}({}[*].{})",
+                    op.name(),
+                    expression_to_string(collection),
+                    path.join(".")
+                )
+            }
+        }
     }
 }
 
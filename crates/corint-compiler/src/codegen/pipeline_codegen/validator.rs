@@ -3,7 +3,7 @@
 //! Performs validation and topological sorting of pipeline steps.
 
 use crate::error::{CompileError, Result};
-use corint_core::ast::pipeline::{Pipeline, PipelineStep, StepNext};
+use corint_core::ast::pipeline::{Pipeline, PipelineStep, StepDetails, StepNext};
 use std::collections::{HashMap, HashSet, VecDeque};
 
 /// Perform topological sort using BFS from entry point
@@ -51,6 +51,63 @@ pub(super) fn topological_sort(pipeline: &Pipeline) -> Result<Vec<&PipelineStep>
     Ok(sorted)
 }
 
+/// Check the variables of let, assign and set steps
+///
+/// A variable may be declared by one let step of the pipeline only, and an
+/// assign step may only update variables the pipeline declares. Whether the
+/// declaration runs before the assignment depends on the route taken, and is
+/// checked by the executor. A pipeline declaring variables with let scopes
+/// all of them, so its set steps may not bypass the declarations.
+pub(super) fn validate_variable_declarations(steps: &[&PipelineStep]) -> Result<()> {
+    let mut declared = HashSet::new();
+    for step in steps {
+        if let StepDetails::Let { declare } = &step.details {
+            for declaration in declare {
+                if !declared.insert(declaration.name.as_str()) {
+                    return Err(CompileError::CompileError(format!(
+                        "Variable '{}' is declared more than once (step '{}')",
+                        declaration.name, step.id
+                    )));
+                }
+            }
+        }
+    }
+
+    for step in steps {
+        if let StepDetails::Assign { assign } = &step.details {
+            if let Some(assignment) = assign
+                .iter()
+                .find(|a| !declared.contains(a.name.as_str()))
+            {
+                return Err(CompileError::UndefinedSymbol(format!(
+                    "vars.{} is assigned in step '{}' but never declared with let",
+                    assignment.name, step.id
+                )));
+            }
+        }
+
+        if let StepDetails::Set { set } = &step.details {
+            if declared.is_empty() {
+                continue;
+            }
+            if let Some(assignment) = set.iter().find(|a| declared.contains(a.name.as_str())) {
+                return Err(CompileError::CompileError(format!(
+                    "vars.{} is declared with let, so step '{}' must update it with assign",
+                    assignment.name, step.id
+                )));
+            }
+            if let Some(assignment) = set.first() {
+                return Err(CompileError::UndefinedSymbol(format!(
+                    "vars.{} is set in step '{}' but never declared with let",
+                    assignment.name, step.id
+                )));
+            }
+        }
+    }
+
+    Ok(())
+}
+
 /// Get all possible next step IDs from a step
 pub(super) fn get_next_step_ids(step: &PipelineStep) -> Vec<String> {
     let mut next_ids = Vec::new();
@@ -73,6 +130,11 @@ pub(super) fn get_next_step_ids(step: &PipelineStep) -> Vec<String> {
         next_ids.push(next_id.clone());
     }
 
+    // Collect from else branch
+    if let Some(else_branch) = &step.else_branch {
+        next_ids.push(else_branch.skip_to.clone());
+    }
+
     next_ids
 }
 
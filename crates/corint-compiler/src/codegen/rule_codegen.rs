@@ -5,8 +5,8 @@
 use super::expression_codegen::ExpressionCompiler;
 use crate::error::Result;
 use corint_core::ast::rule::{Condition, ConditionGroup};
-use corint_core::ast::{Rule, UnaryOperator};
-use corint_core::ir::{Instruction, Program, ProgramMetadata};
+use corint_core::ast::{Operator, Rule, UnaryOperator};
+use corint_core::ir::{Instruction, Program, ProgramMetadata, SourceSpan};
 
 /// Rule compiler
 pub struct RuleCompiler;
@@ -15,6 +15,7 @@ impl RuleCompiler {
     /// Compile a rule into an IR program
     pub fn compile(rule: &Rule) -> Result<Program> {
         let mut instructions = Vec::new();
+        let mut source_map = Vec::new();
 
         // 1. Check event type if specified
         if let Some(event_type) = &rule.when.event_type {
@@ -25,12 +26,13 @@ impl RuleCompiler {
 
         // 2. Compile conditions (support both old and new formats)
         // Conditions produce a single boolean on the stack
+        let base = instructions.len();
         let condition_instructions = if let Some(ref group) = rule.when.condition_group {
             // New format: use condition groups (produces single boolean)
-            Self::compile_condition_group(group)?
+            Self::compile_condition_group(group, base, &mut source_map)?
         } else if let Some(ref conditions) = rule.when.conditions {
             // Legacy format: treat as implicit "all" (produces single boolean)
-            Self::compile_legacy_conditions_chained(conditions)?
+            Self::compile_legacy_conditions_chained(conditions, base, &mut source_map)?
         } else {
             // No conditions - always passes
             Vec::new()
@@ -68,6 +70,7 @@ impl RuleCompiler {
 
         // Store condition information in metadata
         Self::store_condition_metadata(&rule.when, &mut metadata);
+        metadata.source_map = source_map;
 
         // Store event type if specified
         if let Some(event_type) = &rule.when.event_type {
@@ -76,99 +79,155 @@ impl RuleCompiler {
                 .insert("event_type".to_string(), event_type.clone());
         }
 
+        // Store the effective window so the engine can skip inactive rules
+        if let Some(from) = &rule.effective_from {
+            metadata
+                .custom
+                .insert("effective_from".to_string(), from.clone());
+        }
+        if let Some(until) = &rule.effective_until {
+            metadata
+                .custom
+                .insert("effective_until".to_string(), until.clone());
+        }
+
+        // Store the priority so the engine can order the rules of a ruleset
+        if let Some(priority) = rule.priority {
+            metadata
+                .custom
+                .insert("priority".to_string(), priority.to_string());
+        }
+
         Ok(Program::new(instructions, metadata))
     }
 
-    /// Compile legacy format conditions (implicit AND) using BinaryOp::And chaining
+    /// Compile legacy format conditions (implicit AND) as a short-circuit chain
     /// Produces a single boolean on the stack
+    ///
+    /// `base` is the position of the first instruction in the program; the
+    /// span of each condition is added to `source_map`.
     fn compile_legacy_conditions_chained(
         conditions: &[corint_core::ast::Expression],
+        base: usize,
+        source_map: &mut Vec<SourceSpan>,
     ) -> Result<Vec<Instruction>> {
-        let mut instructions = Vec::new();
-
         // Handle empty conditions: no conditions means always true
         if conditions.is_empty() {
-            return Ok(instructions);
+            return Ok(Vec::new());
         }
 
-        // Compile each condition and chain with AND
-        for (i, condition) in conditions.iter().enumerate() {
-            let cond_instructions = ExpressionCompiler::compile(condition)?;
-            instructions.extend(cond_instructions);
-
-            // After first condition, AND with previous result
-            if i > 0 {
-                instructions.push(Instruction::BinaryOp {
-                    op: corint_core::ast::Operator::And,
-                });
-            }
-        }
-
-        Ok(instructions)
+        Self::chain_conditions(conditions, Operator::And, |condition, start| {
+            Self::compile_expression(condition, base + start, source_map)
+        })
     }
 
     /// Compile a condition group (new format) - produces a single boolean on the stack
     /// This version uses BinaryOp::And/Or chaining, which works correctly for nested groups
-    fn compile_condition_group(group: &ConditionGroup) -> Result<Vec<Instruction>> {
+    fn compile_condition_group(
+        group: &ConditionGroup,
+        base: usize,
+        source_map: &mut Vec<SourceSpan>,
+    ) -> Result<Vec<Instruction>> {
         match group {
-            ConditionGroup::All(conditions) => Self::compile_all_conditions_chained(conditions),
-            ConditionGroup::Any(conditions) => Self::compile_any_conditions_chained(conditions),
-            ConditionGroup::Not(conditions) => Self::compile_not_conditions_chained(conditions),
+            ConditionGroup::All(conditions) => {
+                Self::compile_all_conditions_chained(conditions, base, source_map)
+            }
+            ConditionGroup::Any(conditions) => {
+                Self::compile_any_conditions_chained(conditions, base, source_map)
+            }
+            ConditionGroup::Not(conditions) => {
+                Self::compile_not_conditions_chained(conditions, base, source_map)
+            }
         }
     }
 
-    /// Compile "all" conditions using BinaryOp::And chaining
+    /// Compile "all" conditions as a short-circuit chain
     /// Produces a single boolean on the stack
-    fn compile_all_conditions_chained(conditions: &[Condition]) -> Result<Vec<Instruction>> {
-        let mut instructions = Vec::new();
-
+    fn compile_all_conditions_chained(
+        conditions: &[Condition],
+        base: usize,
+        source_map: &mut Vec<SourceSpan>,
+    ) -> Result<Vec<Instruction>> {
         // Handle empty conditions: ALL of nothing is true
         if conditions.is_empty() {
-            instructions.push(Instruction::LoadConst {
+            return Ok(vec![Instruction::LoadConst {
                 value: corint_core::Value::Bool(true),
-            });
-            return Ok(instructions);
-        }
-
-        // Compile each condition and chain with AND
-        for (i, condition) in conditions.iter().enumerate() {
-            let cond_instructions = Self::compile_condition(condition)?;
-            instructions.extend(cond_instructions);
-
-            // After first condition, AND with previous result
-            if i > 0 {
-                instructions.push(Instruction::BinaryOp {
-                    op: corint_core::ast::Operator::And,
-                });
-            }
+            }]);
         }
 
-        Ok(instructions)
+        Self::chain_conditions(conditions, Operator::And, |condition, start| {
+            Self::compile_condition(condition, base + start, source_map)
+        })
     }
 
-    /// Compile "any" conditions using BinaryOp::Or chaining
+    /// Compile "any" conditions as a short-circuit chain
     /// Produces a single boolean on the stack
-    fn compile_any_conditions_chained(conditions: &[Condition]) -> Result<Vec<Instruction>> {
-        let mut instructions = Vec::new();
-
+    fn compile_any_conditions_chained(
+        conditions: &[Condition],
+        base: usize,
+        source_map: &mut Vec<SourceSpan>,
+    ) -> Result<Vec<Instruction>> {
         // Handle empty conditions: ANY of nothing is false
         if conditions.is_empty() {
-            instructions.push(Instruction::LoadConst {
+            return Ok(vec![Instruction::LoadConst {
                 value: corint_core::Value::Bool(false),
-            });
-            return Ok(instructions);
+            }]);
         }
 
-        // Compile each condition and chain with OR
+        Self::chain_conditions(conditions, Operator::Or, |condition, start| {
+            Self::compile_condition(condition, base + start, source_map)
+        })
+    }
+
+    /// Chain conditions with `op` (`&&` or `||`), skipping the remaining
+    /// conditions once the result so far decides the chain
+    ///
+    /// The result so far stays on the stack and is combined with each next
+    /// condition by `BinaryOp`, so an Unknown condition under three-valued
+    /// null semantics still follows Kleene logic: only a definite `false`
+    /// (for `&&`) or `true` (for `||`) ends the chain early.
+    ///
+    /// For [A, B] with `&&`:
+    /// - Eval A
+    /// - Dup, Not, JumpIfTrue to end (A is false)
+    /// - Eval B
+    /// - BinaryOp And
+    /// - end:
+    ///
+    /// `compile` compiles a condition given its position in the chain.
+    fn chain_conditions<T>(
+        conditions: &[T],
+        op: Operator,
+        mut compile: impl FnMut(&T, usize) -> Result<Vec<Instruction>>,
+    ) -> Result<Vec<Instruction>> {
+        let mut instructions = Vec::new();
+        let mut jumps_to_end = Vec::new();
+
         for (i, condition) in conditions.iter().enumerate() {
-            let cond_instructions = Self::compile_condition(condition)?;
+            if i > 0 {
+                // JumpIfTrue pops the copy, leaving the result so far
+                instructions.push(Instruction::Dup);
+                if op == Operator::And {
+                    instructions.push(Instruction::UnaryOp {
+                        op: UnaryOperator::Not,
+                    });
+                }
+                jumps_to_end.push(instructions.len());
+                instructions.push(Instruction::JumpIfTrue { offset: 0 });
+            }
+
+            let cond_instructions = compile(condition, instructions.len())?;
             instructions.extend(cond_instructions);
 
-            // After first condition, OR with previous result
             if i > 0 {
-                instructions.push(Instruction::BinaryOp {
-                    op: corint_core::ast::Operator::Or,
-                });
+                instructions.push(Instruction::BinaryOp { op });
+            }
+        }
+
+        let end = instructions.len();
+        for jump_pos in jumps_to_end {
+            if let Instruction::JumpIfTrue { offset } = &mut instructions[jump_pos] {
+                *offset = (end - jump_pos) as isize;
             }
         }
 
@@ -177,16 +236,21 @@ impl RuleCompiler {
 
     /// Compile "not" conditions (negation)
     /// Produces a single boolean on the stack
-    fn compile_not_conditions_chained(conditions: &[Condition]) -> Result<Vec<Instruction>> {
+    fn compile_not_conditions_chained(
+        conditions: &[Condition],
+        base: usize,
+        source_map: &mut Vec<SourceSpan>,
+    ) -> Result<Vec<Instruction>> {
         let mut instructions = Vec::new();
 
         if conditions.len() == 1 {
             // Single condition: compile and negate
-            let cond_instructions = Self::compile_condition(&conditions[0])?;
+            let cond_instructions = Self::compile_condition(&conditions[0], base, source_map)?;
             instructions.extend(cond_instructions);
         } else {
             // Multiple conditions: treat as implicit AND, then negate
-            let all_cond_instructions = Self::compile_all_conditions_chained(conditions)?;
+            let all_cond_instructions =
+                Self::compile_all_conditions_chained(conditions, base, source_map)?;
             instructions.extend(all_cond_instructions);
         }
 
@@ -200,15 +264,34 @@ impl RuleCompiler {
 
     /// Compile a single condition (expression or nested group)
     /// Produces a single boolean on the stack
-    fn compile_condition(condition: &Condition) -> Result<Vec<Instruction>> {
+    fn compile_condition(
+        condition: &Condition,
+        base: usize,
+        source_map: &mut Vec<SourceSpan>,
+    ) -> Result<Vec<Instruction>> {
         match condition {
-            Condition::Expression(expr) => ExpressionCompiler::compile(expr),
-            Condition::Group(group) => Self::compile_condition_group(group),
+            Condition::Expression(expr) => Self::compile_expression(expr, base, source_map),
+            Condition::Group(group) => Self::compile_condition_group(group, base, source_map),
         }
     }
 
+    /// Compile a condition expression, recording its span
+    fn compile_expression(
+        expr: &corint_core::ast::Expression,
+        base: usize,
+        source_map: &mut Vec<SourceSpan>,
+    ) -> Result<Vec<Instruction>> {
+        let instructions = ExpressionCompiler::compile(expr)?;
+        source_map.push(SourceSpan::new(
+            base,
+            base + instructions.len(),
+            ExpressionCompiler::expression_to_string(expr),
+        ));
+        Ok(instructions)
+    }
+
     /// Store condition metadata for tracing
-    fn store_condition_metadata(
+    pub(crate) fn store_condition_metadata(
         when: &corint_core::ast::WhenBlock,
         metadata: &mut ProgramMetadata,
     ) {
@@ -267,6 +350,9 @@ mod tests {
             when,
             score: 50,
             params: None,
+            priority: None,
+            effective_from: None,
+            effective_until: None,
             metadata: None,
         };
 
@@ -315,6 +401,9 @@ mod tests {
             when,
             score: 25,
             params: None,
+            priority: None,
+            effective_from: None,
+            effective_until: None,
             metadata: None,
         };
 
@@ -348,6 +437,9 @@ mod tests {
             when,
             score: 75,
             params: None,
+            priority: None,
+            effective_from: None,
+            effective_until: None,
             metadata: None,
         };
 
@@ -369,6 +461,9 @@ mod tests {
             when,
             score: 100,
             params: None,
+            priority: None,
+            effective_from: None,
+            effective_until: None,
             metadata: None,
         };
 
@@ -382,4 +477,27 @@ mod tests {
             Some("Test description".to_string())
         );
     }
+
+    #[test]
+    fn test_effective_window_metadata() {
+        let rule = Rule::new(
+            "promo_rule".to_string(),
+            "Promo Rule".to_string(),
+            WhenBlock::new(),
+            20,
+        )
+        .with_effective_from("2026-12-01T00:00:00Z".to_string())
+        .with_effective_until("2027-01-01T00:00:00Z".to_string());
+
+        let program = RuleCompiler::compile(&rule).unwrap();
+
+        assert_eq!(
+            program.metadata.custom.get("effective_from").map(String::as_str),
+            Some("2026-12-01T00:00:00Z")
+        );
+        assert_eq!(
+            program.metadata.custom.get("effective_until").map(String::as_str),
+            Some("2027-01-01T00:00:00Z")
+        );
+    }
 }
@@ -4,8 +4,8 @@
 
 use crate::codegen::expression_codegen::ExpressionCompiler;
 use crate::error::Result;
-use corint_core::ast::{Expression, Ruleset, Signal};
-use corint_core::ir::{Instruction, Program, ProgramMetadata};
+use corint_core::ast::{DecisionRule, Expression, Ruleset, Signal};
+use corint_core::ir::{Instruction, Program, ProgramMetadata, SourceSpan};
 
 /// Ruleset compiler
 pub struct RulesetCompiler;
@@ -15,15 +15,26 @@ impl RulesetCompiler {
     pub fn compile(ruleset: &Ruleset) -> Result<Program> {
         let mut instructions = Vec::new();
 
+        // Combine the rule scores before the conclusion reads total_score
+        if let Some(scoring) = &ruleset.scoring {
+            instructions.push(Instruction::AggregateScore {
+                rules: ruleset.rules.clone(),
+                scoring: scoring.clone(),
+            });
+        }
+
         // Compile decision logic
         // This evaluates conditions and executes appropriate actions
-        instructions.extend(Self::compile_conclusion(ruleset)?);
+        let mut source_map = Vec::new();
+        let base = instructions.len();
+        instructions.extend(Self::compile_conclusion(ruleset, base, &mut source_map)?);
 
         // Always end with return
         instructions.push(Instruction::Return);
 
         // Create program metadata and store the list of rules
         let mut metadata = ProgramMetadata::for_ruleset(ruleset.id.clone());
+        metadata.source_map = source_map;
 
         // Store the rule IDs in custom metadata so DecisionEngine can execute them first
         if !ruleset.rules.is_empty() {
@@ -40,13 +51,44 @@ impl RulesetCompiler {
                 .insert("conclusion_json".to_string(), conclusion_json);
         }
 
+        // Store the effective window so the engine can skip inactive rulesets
+        if let Some(from) = &ruleset.effective_from {
+            metadata
+                .custom
+                .insert("effective_from".to_string(), from.clone());
+        }
+        if let Some(until) = &ruleset.effective_until {
+            metadata
+                .custom
+                .insert("effective_until".to_string(), until.clone());
+        }
+
+        // Store the early termination conditions for the engine's rule loop
+        if let Some(score) = ruleset.stop_on_score {
+            metadata
+                .custom
+                .insert("stop_on_score".to_string(), score.to_string());
+        }
+        if let Some(signal) = &ruleset.stop_on_signal {
+            let signal_json = serde_json::to_string(signal).unwrap_or_default();
+            metadata
+                .custom
+                .insert("stop_on_signal".to_string(), signal_json);
+        }
+
         Ok(Program::new(instructions, metadata))
     }
 
     /// Compile decision logic
     ///
-    /// Decision logic maps score ranges or conditions to signals
-    fn compile_conclusion(ruleset: &Ruleset) -> Result<Vec<Instruction>> {
+    /// Decision logic maps score ranges or conditions to signals. The span of
+    /// each branch condition is added to `source_map`, in branch order, with
+    /// `base` the position of the first instruction in the program.
+    fn compile_conclusion(
+        ruleset: &Ruleset,
+        base: usize,
+        source_map: &mut Vec<SourceSpan>,
+    ) -> Result<Vec<Instruction>> {
         let mut instructions = Vec::new();
 
         // Iterate through decision rules in order
@@ -54,18 +96,24 @@ impl RulesetCompiler {
             // Check if this is a default rule (no condition)
             if decision_rule.default {
                 // Execute signal directly
-                instructions.extend(Self::compile_signal(&decision_rule.signal)?);
+                instructions.extend(Self::compile_outcome(decision_rule)?);
                 continue;
             }
 
             // If there's a condition, compile it
             if let Some(condition) = &decision_rule.condition {
                 // Compile the condition expression
+                let start = base + instructions.len();
                 instructions.extend(ExpressionCompiler::compile(condition)?);
+                source_map.push(SourceSpan::new(
+                    start,
+                    base + instructions.len(),
+                    ExpressionCompiler::expression_to_string(condition),
+                ));
 
                 // Calculate the jump offset if condition is false
                 // We need to count the instructions that will be executed if true
-                let mut signal_instructions = Self::compile_signal(&decision_rule.signal)?;
+                let mut signal_instructions = Self::compile_outcome(decision_rule)?;
                 // Jump to the end after executing this signal if there are remaining rules
                 let remaining_rules = ruleset.conclusion.len() - idx - 1;
                 if remaining_rules > 0 {
@@ -107,6 +155,17 @@ impl RulesetCompiler {
         Ok(instructions)
     }
 
+    /// Compile the outcome of a decision rule (signal plus optional reason code)
+    fn compile_outcome(decision_rule: &DecisionRule) -> Result<Vec<Instruction>> {
+        let mut instructions = Self::compile_signal(&decision_rule.signal)?;
+
+        if let Some(code) = &decision_rule.reason_code {
+            instructions.push(Instruction::SetReasonCode { code: code.clone() });
+        }
+
+        Ok(instructions)
+    }
+
     /// Compile a signal into IR instructions
     fn compile_signal(signal: &Signal) -> Result<Vec<Instruction>> {
         let mut instructions = Vec::new();
@@ -148,7 +207,7 @@ impl RulesetCompiler {
     }
 
     /// Convert conclusion to JSON for trace building
-    fn conclusion_to_json(conclusion: &[corint_core::ast::DecisionRule]) -> String {
+    fn conclusion_to_json(conclusion: &[DecisionRule]) -> String {
         let json_array: Vec<serde_json::Value> = conclusion
             .iter()
             .map(|rule| {
@@ -199,6 +258,14 @@ impl RulesetCompiler {
                     );
                 }
 
+                // Add reason code if present
+                if let Some(ref reason_code) = rule.reason_code {
+                    obj.insert(
+                        "reason_code".to_string(),
+                        serde_json::Value::String(reason_code.clone()),
+                    );
+                }
+
                 serde_json::Value::Object(obj)
             })
             .collect();
@@ -266,6 +333,15 @@ impl RulesetCompiler {
                 let op_str = match op {
                     UnaryOperator::Not => "!",
                     UnaryOperator::Negate => "-",
+                    UnaryOperator::IsNull => {
+                        return format!("{} is null", Self::expression_to_readable_string(operand))
+                    }
+                    UnaryOperator::IsNotNull => {
+                        return format!(
+                            "{} is not null",
+                            Self::expression_to_readable_string(operand)
+                        )
+                    }
                 };
                 format!("{}{}", op_str, Self::expression_to_readable_string(operand))
             }
@@ -307,6 +383,30 @@ impl RulesetCompiler {
                 Some(id) => format!("result.{}.{}", id, field),
                 None => format!("result.{}", field),
             },
+            Expression::Quantifier {
+                op,
+                collection,
+                binding,
+                predicate,
+            } => format!(
+                "{}({}, {}, {})",
+                op.name(),
+                Self::expression_to_readable_string(collection),
+                binding,
+                Self::expression_to_readable_string(predicate)
+            ),
+            Expression::Aggregate {
+                op,
+                collection,
+                path,
+            } => {
+                let collection = Self::expression_to_readable_string(collection);
+                if path.is_empty() {
+                    format!("{}({})", op.name(), collection)
+                } else {
+                    format!("{}({}[*].{})", op.name(), collection, path.join("."))
+                }
+            }
         }
     }
 }
@@ -406,9 +506,15 @@ mod tests {
                 signal: Signal::Approve,
                 actions: vec![],
                 reason: Some("Default signal".to_string()),
+                reason_code: None,
             }],
             description: None,
+            effective_from: None,
+            effective_until: None,
             metadata: None,
+            scoring: None,
+            stop_on_score: None,
+            stop_on_signal: None,
         };
 
         let program = RulesetCompiler::compile(&ruleset).unwrap();
@@ -437,6 +543,7 @@ mod tests {
                     signal: Signal::Review,
                     actions: vec!["KYC_AUTH".to_string()],
                     reason: Some("High amount".to_string()),
+                    reason_code: None,
                 },
                 DecisionRule {
                     condition: None,
@@ -444,10 +551,16 @@ mod tests {
                     signal: Signal::Approve,
                     actions: vec![],
                     reason: None,
+                    reason_code: None,
                 },
             ],
             description: None,
+            effective_from: None,
+            effective_until: None,
             metadata: None,
+            scoring: None,
+            stop_on_score: None,
+            stop_on_signal: None,
         };
 
         let program = RulesetCompiler::compile(&ruleset).unwrap();
@@ -485,6 +598,7 @@ mod tests {
                     signal: Signal::Decline,
                     actions: vec!["BLOCK_CARD".to_string()],
                     reason: Some("Extremely high value".to_string()),
+                    reason_code: None,
                 },
                 DecisionRule {
                     condition: Some(Expression::Binary {
@@ -498,6 +612,7 @@ mod tests {
                     signal: Signal::Review,
                     actions: vec!["KYC_AUTH".to_string()],
                     reason: Some("High value".to_string()),
+                    reason_code: None,
                 },
                 DecisionRule {
                     condition: Some(Expression::Binary {
@@ -511,6 +626,7 @@ mod tests {
                     signal: Signal::Review,
                     actions: vec![],
                     reason: Some("Elevated amount".to_string()),
+                    reason_code: None,
                 },
                 DecisionRule {
                     condition: None,
@@ -518,10 +634,16 @@ mod tests {
                     signal: Signal::Approve,
                     actions: vec![],
                     reason: None,
+                    reason_code: None,
                 },
             ],
             description: None,
+            effective_from: None,
+            effective_until: None,
             metadata: None,
+            scoring: None,
+            stop_on_score: None,
+            stop_on_signal: None,
         };
 
         let program = RulesetCompiler::compile(&ruleset).unwrap();
@@ -533,6 +655,32 @@ mod tests {
 
         assert!(program.instructions.len() > 10);
     }
+
+    #[test]
+    fn test_compile_ruleset_with_scoring() {
+        use corint_core::ast::{ScoreAggregation, ScoreStrategy};
+
+        let scoring = ScoreAggregation {
+            strategy: ScoreStrategy::Max,
+            max: Some(100),
+            ..Default::default()
+        };
+        let ruleset = Ruleset::new("capped".to_string())
+            .with_rules(vec!["velocity".to_string(), "new_device".to_string()])
+            .add_decision_rule(DecisionRule::default(Signal::Approve))
+            .with_scoring(scoring.clone());
+
+        let program = RulesetCompiler::compile(&ruleset).unwrap();
+
+        // Scores are aggregated before the conclusion runs
+        assert_eq!(
+            program.instructions[0],
+            Instruction::AggregateScore {
+                rules: vec!["velocity".to_string(), "new_device".to_string()],
+                scoring,
+            }
+        );
+    }
 }
 #[test]
 fn test_compile_simple_ruleset_outside() {
@@ -557,6 +705,7 @@ fn test_compile_simple_ruleset_outside() {
                 signal: Signal::Decline,
                 actions: vec![],
                 reason: None,
+                reason_code: None,
             },
             DecisionRule {
                 condition: None,
@@ -564,10 +713,16 @@ fn test_compile_simple_ruleset_outside() {
                 signal: Signal::Approve,
                 actions: vec![],
                 reason: None,
+                reason_code: None,
             },
         ],
         description: None,
+        effective_from: None,
+        effective_until: None,
         metadata: None,
+        scoring: None,
+        stop_on_score: None,
+        stop_on_signal: None,
     };
 
     let program = RulesetCompiler::compile(&ruleset).unwrap();
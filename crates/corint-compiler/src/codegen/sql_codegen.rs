@@ -0,0 +1,783 @@
+//! SQL compiler
+//!
+//! Compiles the conditions of rules into SQL expressions, so analysts can
+//! count how many historical events a ruleset would have flagged directly in
+//! the warehouse. Each construct is translated to SQL that yields the value
+//! the runtime computes for it, missing values included: comparisons over
+//! `NULL` are false under the default two-valued semantics and `NULL` under
+//! three-valued semantics, `and`/`or` propagate `NULL` or follow Kleene logic
+//! accordingly, and a rule is flagged when its condition is true.
+//!
+//! Event fields map to columns of the same name, nested fields to nested
+//! columns (`event.device.id` is `` `device`.`id` ``). Other fields, such as
+//! features, must be mapped to a column with [`SqlCompiler::with_column`],
+//! and lists to a table with [`SqlCompiler::with_list_table`].
+//!
+//! Constructs the runtime fails on are not flagged: divisions by zero yield
+//! `NULL`. Constructs without a SQL equivalent, such as quantifiers, result
+//! access and function calls other than `coalesce`, fail to compile.
+
+use crate::error::{CompileError, Result};
+use corint_core::ast::rule::{Condition, ConditionGroup};
+use corint_core::ast::{Expression, LogicalGroupOp, Operator, Rule, Ruleset, UnaryOperator};
+use corint_core::Value;
+use std::collections::HashMap;
+
+/// SQL dialect of the warehouse
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SqlDialect {
+    /// ClickHouse
+    ClickHouse,
+    /// Google BigQuery (GoogleSQL)
+    BigQuery,
+}
+
+/// A rule compiled to SQL
+#[derive(Debug, Clone, PartialEq)]
+pub struct SqlRule {
+    /// Rule ID
+    pub rule_id: String,
+    /// Score the rule adds when triggered
+    pub score: i32,
+    /// Boolean SQL expression, true for the events the rule flags
+    pub condition: String,
+}
+
+/// The rules of a ruleset compiled to SQL
+#[derive(Debug, Clone, PartialEq)]
+pub struct SqlRuleset {
+    /// Ruleset ID
+    pub ruleset_id: String,
+    /// Rules, in the order of the ruleset
+    pub rules: Vec<SqlRule>,
+}
+
+impl SqlRuleset {
+    /// Query counting the events of `table` flagged by each rule
+    ///
+    /// Returns one row: the number of `events`, the number flagged by each
+    /// rule in a column named after it, and the number flagged by any rule
+    /// (`flagged`). `table` is used as given, so it may be qualified or a
+    /// subquery restricting the events to a period.
+    ///
+    /// Rules are counted as if all were evaluated: rules a ruleset skips
+    /// with `stop_on_score` / `stop_on_signal` are counted too.
+    pub fn backtest_query(&self, table: &str) -> String {
+        let mut columns = vec!["COUNT(*) AS `events`".to_string()];
+        for rule in &self.rules {
+            columns.push(format!(
+                "SUM(CASE WHEN {} THEN 1 ELSE 0 END) AS {}",
+                rule.condition,
+                quote_identifier(&rule.rule_id)
+            ));
+        }
+        let any = if self.rules.is_empty() {
+            "FALSE".to_string()
+        } else {
+            self.rules
+                .iter()
+                .map(|rule| rule.condition.as_str())
+                .collect::<Vec<_>>()
+                .join(" OR ")
+        };
+        columns.push(format!(
+            "SUM(CASE WHEN {} THEN 1 ELSE 0 END) AS `flagged`",
+            any
+        ));
+
+        format!("SELECT\n  {}\nFROM {}", columns.join(",\n  "), table)
+    }
+}
+
+/// A compiled SQL expression, and whether it may be `NULL`
+struct Fragment {
+    sql: String,
+    nullable: bool,
+}
+
+impl Fragment {
+    fn new(sql: String, nullable: bool) -> Self {
+        Self { sql, nullable }
+    }
+
+    /// The fragment as a condition: `NULL` is not true
+    fn into_condition(self) -> String {
+        if self.nullable {
+            format!("COALESCE({}, FALSE)", self.sql)
+        } else {
+            self.sql
+        }
+    }
+}
+
+/// Compiler of rule conditions to SQL
+#[derive(Debug, Clone)]
+pub struct SqlCompiler {
+    dialect: SqlDialect,
+    three_valued: bool,
+    columns: HashMap<String, String>,
+    list_tables: HashMap<String, String>,
+}
+
+impl SqlCompiler {
+    /// Create a SQL compiler for a dialect, with two-valued null semantics
+    pub fn new(dialect: SqlDialect) -> Self {
+        Self {
+            dialect,
+            three_valued: false,
+            columns: HashMap::new(),
+            list_tables: HashMap::new(),
+        }
+    }
+
+    /// Compile for engines running with three-valued null semantics
+    pub fn with_three_valued_logic(mut self, enabled: bool) -> Self {
+        self.three_valued = enabled;
+        self
+    }
+
+    /// Read a field, such as `features.txn_count_1h`, from a SQL expression
+    pub fn with_column(mut self, field: impl Into<String>, sql: impl Into<String>) -> Self {
+        self.columns.insert(field.into(), sql.into());
+        self
+    }
+
+    /// Look a list up in a table, whose `value` column holds its entries
+    pub fn with_list_table(mut self, list_id: impl Into<String>, table: impl Into<String>) -> Self {
+        self.list_tables.insert(list_id.into(), table.into());
+        self
+    }
+
+    /// Compile the rules of a ruleset
+    ///
+    /// `rules` must contain the definitions of the rules the ruleset lists.
+    pub fn compile_ruleset(&self, ruleset: &Ruleset, rules: &[Rule]) -> Result<SqlRuleset> {
+        let rules = ruleset
+            .rules
+            .iter()
+            .map(|rule_id| {
+                let rule = rules
+                    .iter()
+                    .find(|rule| &rule.id == rule_id)
+                    .ok_or_else(|| CompileError::RuleNotFound {
+                        id: rule_id.clone(),
+                    })?;
+                Ok(SqlRule {
+                    rule_id: rule.id.clone(),
+                    score: rule.score,
+                    condition: self.compile_rule(rule)?,
+                })
+            })
+            .collect::<Result<_>>()?;
+
+        Ok(SqlRuleset {
+            ruleset_id: ruleset.id.clone(),
+            rules,
+        })
+    }
+
+    /// Compile the condition of a rule, true for the events it flags
+    pub fn compile_rule(&self, rule: &Rule) -> Result<String> {
+        let mut parts = Vec::new();
+        if let Some(event_type) = &rule.when.event_type {
+            let column = self.column(&["event".to_string(), "type".to_string()])?;
+            parts.push(format!(
+                "COALESCE({} = {}, FALSE)",
+                column,
+                quote_string(event_type)
+            ));
+        }
+
+        let condition = if let Some(group) = &rule.when.condition_group {
+            Some(self.compile_group(group)?)
+        } else if let Some(conditions) = rule.when.conditions.as_deref().filter(|c| !c.is_empty()) {
+            let fragments = conditions
+                .iter()
+                .map(|condition| self.compile_fragment(condition))
+                .collect::<Result<Vec<_>>>()?;
+            Some(self.chain(fragments, Operator::And))
+        } else {
+            None
+        };
+        if let Some(condition) = condition {
+            parts.push(condition.into_condition());
+        }
+
+        Ok(match parts.len() {
+            0 => "TRUE".to_string(),
+            1 => parts.remove(0),
+            _ => format!("({})", parts.join(" AND ")),
+        })
+    }
+
+    /// Compile an expression
+    pub fn compile_expression(&self, expression: &Expression) -> Result<String> {
+        Ok(self.compile_fragment(expression)?.sql)
+    }
+
+    fn compile_group(&self, group: &ConditionGroup) -> Result<Fragment> {
+        let (ConditionGroup::All(conditions)
+        | ConditionGroup::Any(conditions)
+        | ConditionGroup::Not(conditions)) = group;
+        let fragments = conditions
+            .iter()
+            .map(|condition| match condition {
+                Condition::Expression(expression) => self.compile_fragment(expression),
+                Condition::Group(group) => self.compile_group(group),
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(match group {
+            ConditionGroup::All(_) if fragments.is_empty() => Fragment::new("TRUE".into(), false),
+            ConditionGroup::Any(_) if fragments.is_empty() => Fragment::new("FALSE".into(), false),
+            ConditionGroup::Any(_) => self.chain(fragments, Operator::Or),
+            ConditionGroup::All(_) | ConditionGroup::Not(_) => {
+                let all = self.chain(fragments, Operator::And);
+                if matches!(group, ConditionGroup::Not(_)) {
+                    Fragment::new(format!("(NOT {})", all.sql), all.nullable)
+                } else {
+                    all
+                }
+            }
+        })
+    }
+
+    /// Chain fragments with `and`/`or`, as rule conditions are
+    fn chain(&self, fragments: Vec<Fragment>, op: Operator) -> Fragment {
+        let mut fragments = fragments.into_iter();
+        let first = fragments
+            .next()
+            .unwrap_or_else(|| Fragment::new("TRUE".into(), false));
+        fragments.fold(first, |left, right| self.logical(left, &op, right))
+    }
+
+    fn compile_fragment(&self, expression: &Expression) -> Result<Fragment> {
+        match expression {
+            Expression::Literal(value) => self.literal(value),
+
+            Expression::FieldAccess(path) => Ok(Fragment::new(self.column(path)?, true)),
+
+            Expression::Binary { left, op, right } => match op {
+                Operator::InList | Operator::NotInList => self.list_membership(left, op, right),
+                Operator::In | Operator::NotIn => self.membership(left, op, right),
+                _ => {
+                    let left = self.compile_fragment(left)?;
+                    let right = self.compile_fragment(right)?;
+                    self.binary(left, op, right)
+                }
+            },
+
+            Expression::Unary { op, operand } => {
+                let operand = self.compile_fragment(operand)?;
+                Ok(match op {
+                    UnaryOperator::Not => {
+                        Fragment::new(format!("(NOT {})", operand.sql), operand.nullable)
+                    }
+                    UnaryOperator::Negate => {
+                        Fragment::new(format!("(-{})", operand.sql), operand.nullable)
+                    }
+                    UnaryOperator::IsNull => {
+                        Fragment::new(format!("({} IS NULL)", operand.sql), false)
+                    }
+                    UnaryOperator::IsNotNull => {
+                        Fragment::new(format!("({} IS NOT NULL)", operand.sql), false)
+                    }
+                })
+            }
+
+            Expression::FunctionCall { name, args } if name == "coalesce" => {
+                if args.is_empty() {
+                    return Err(CompileError::InvalidExpression(
+                        "coalesce requires at least one argument".to_string(),
+                    ));
+                }
+                let args = args
+                    .iter()
+                    .map(|arg| self.compile_fragment(arg))
+                    .collect::<Result<Vec<_>>>()?;
+                let nullable = args.iter().all(|arg| arg.nullable);
+                let args: Vec<_> = args.into_iter().map(|arg| arg.sql).collect();
+                Ok(Fragment::new(
+                    format!("COALESCE({})", args.join(", ")),
+                    nullable,
+                ))
+            }
+
+            Expression::Ternary {
+                condition,
+                true_expr,
+                false_expr,
+            } => {
+                let condition = self.compile_fragment(condition)?;
+                let true_expr = self.compile_fragment(true_expr)?;
+                let false_expr = self.compile_fragment(false_expr)?;
+                Ok(Fragment::new(
+                    format!(
+                        "(CASE WHEN {} THEN {} ELSE {} END)",
+                        condition.sql, true_expr.sql, false_expr.sql
+                    ),
+                    true_expr.nullable || false_expr.nullable,
+                ))
+            }
+
+            // Short-circuits on the first false (or true) condition, and
+            // otherwise yields the value of the last one
+            Expression::LogicalGroup { op, conditions } => {
+                let mut fragments = conditions
+                    .iter()
+                    .map(|condition| self.compile_fragment(condition))
+                    .collect::<Result<Vec<_>>>()?;
+                let Some(last) = fragments.pop() else {
+                    let empty = matches!(op, LogicalGroupOp::All);
+                    return Ok(Fragment::new(sql_bool(empty).to_string(), false));
+                };
+                if fragments.is_empty() {
+                    return Ok(last);
+                }
+
+                let (joiner, then, otherwise) = match op {
+                    LogicalGroupOp::All => (" AND ", last.sql, "FALSE".to_string()),
+                    LogicalGroupOp::Any => (" OR ", "TRUE".to_string(), last.sql),
+                };
+                let leading: Vec<_> = fragments.into_iter().map(|f| f.sql).collect();
+                Ok(Fragment::new(
+                    format!(
+                        "(CASE WHEN {} THEN {} ELSE {} END)",
+                        leading.join(joiner),
+                        then,
+                        otherwise
+                    ),
+                    last.nullable,
+                ))
+            }
+
+            Expression::FunctionCall { name, .. } => {
+                Err(unsupported(format!("function '{}'", name)))
+            }
+            Expression::ListReference { list_id } => Err(CompileError::InvalidExpression(format!(
+                "list.{} can only be the right operand of 'in list'",
+                list_id
+            ))),
+            Expression::ResultAccess { .. } => Err(unsupported("ruleset results".to_string())),
+            Expression::Quantifier { op, .. } => Err(unsupported(op.name().to_string())),
+            Expression::Aggregate { op, .. } => Err(unsupported(op.name().to_string())),
+        }
+    }
+
+    fn binary(&self, left: Fragment, op: &Operator, right: Fragment) -> Result<Fragment> {
+        let nullable = left.nullable || right.nullable;
+        let (l, r) = (&left.sql, &right.sql);
+        let sql = match op {
+            Operator::Eq
+            | Operator::Ne
+            | Operator::Gt
+            | Operator::Ge
+            | Operator::Lt
+            | Operator::Le => {
+                let comparison = format!("({} {} {})", l, comparison_operator(op), r);
+                // Comparisons over `NULL` are false, or Unknown under three-valued logic
+                return Ok(if nullable && !self.three_valued {
+                    Fragment::new(format!("COALESCE({}, FALSE)", comparison), false)
+                } else {
+                    Fragment::new(comparison, nullable)
+                });
+            }
+            Operator::And | Operator::Or => return Ok(self.logical(left, op, right)),
+
+            Operator::Add => format!("({} + {})", l, r),
+            Operator::Sub => format!("({} - {})", l, r),
+            Operator::Mul => format!("({} * {})", l, r),
+            // The runtime fails on a zero divisor, which does not flag the event
+            Operator::Div => {
+                return Ok(Fragment::new(format!("({} / NULLIF({}, 0))", l, r), true));
+            }
+            Operator::Mod => {
+                let sql = match self.dialect {
+                    SqlDialect::ClickHouse => format!("({} % NULLIF({}, 0))", l, r),
+                    SqlDialect::BigQuery => format!("MOD({}, NULLIF({}, 0))", l, r),
+                };
+                return Ok(Fragment::new(sql, true));
+            }
+
+            Operator::Contains => match self.dialect {
+                SqlDialect::ClickHouse => format!("(position({}, {}) > 0)", l, r),
+                SqlDialect::BigQuery => format!("(STRPOS({}, {}) > 0)", l, r),
+            },
+            Operator::StartsWith => match self.dialect {
+                SqlDialect::ClickHouse => format!("startsWith({}, {})", l, r),
+                SqlDialect::BigQuery => format!("STARTS_WITH({}, {})", l, r),
+            },
+            Operator::EndsWith => match self.dialect {
+                SqlDialect::ClickHouse => format!("endsWith({}, {})", l, r),
+                SqlDialect::BigQuery => format!("ENDS_WITH({}, {})", l, r),
+            },
+
+            Operator::Regex => return Err(unsupported("regex matching".to_string())),
+            Operator::In | Operator::NotIn | Operator::InList | Operator::NotInList => {
+                return Err(CompileError::InvalidExpression(format!(
+                    "{:?} requires an array or list on its right side",
+                    op
+                )))
+            }
+        };
+        Ok(Fragment::new(sql, nullable))
+    }
+
+    /// `and`/`or`: `NULL` if either side is, or Kleene logic under three-valued logic
+    fn logical(&self, left: Fragment, op: &Operator, right: Fragment) -> Fragment {
+        let joiner = if *op == Operator::Or { "OR" } else { "AND" };
+        let combined = format!("({} {} {})", left.sql, joiner, right.sql);
+        let nullable = left.nullable || right.nullable;
+        if !nullable || self.three_valued {
+            return Fragment::new(combined, nullable);
+        }
+
+        let mut checks = Vec::new();
+        for side in [&left, &right] {
+            if side.nullable {
+                checks.push(format!("{} IS NULL", side.sql));
+            }
+        }
+        Fragment::new(
+            format!(
+                "(CASE WHEN {} THEN NULL ELSE {} END)",
+                checks.join(" OR "),
+                combined
+            ),
+            true,
+        )
+    }
+
+    /// `x in [..]` / `x not in [..]`, `NULL` for a missing `x`
+    fn membership(&self, left: &Expression, op: &Operator, right: &Expression) -> Result<Fragment> {
+        let Expression::Literal(Value::Array(items)) = right else {
+            return Err(unsupported(format!(
+                "{:?} over anything but an array literal",
+                op
+            )));
+        };
+        let left = self.compile_fragment(left)?;
+        let negate = *op == Operator::NotIn;
+        if items.is_empty() {
+            let empty = sql_bool(negate);
+            return Ok(if left.nullable {
+                Fragment::new(
+                    format!(
+                        "(CASE WHEN {} IS NULL THEN NULL ELSE {} END)",
+                        left.sql, empty
+                    ),
+                    true,
+                )
+            } else {
+                Fragment::new(empty.to_string(), false)
+            });
+        }
+
+        let items = items
+            .iter()
+            .map(|item| match item {
+                Value::Null | Value::Array(_) | Value::Object(_) => {
+                    Err(unsupported(format!("{:?} in a membership array", item)))
+                }
+                _ => Ok(self.literal(item)?.sql),
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let keyword = if negate { "NOT IN" } else { "IN" };
+        Ok(Fragment::new(
+            format!("({} {} ({}))", left.sql, keyword, items.join(", ")),
+            left.nullable,
+        ))
+    }
+
+    /// `x in list.id` / `x not in list.id`, over the list's table
+    fn list_membership(
+        &self,
+        left: &Expression,
+        op: &Operator,
+        right: &Expression,
+    ) -> Result<Fragment> {
+        let Expression::ListReference { list_id } = right else {
+            return Err(CompileError::InvalidExpression(
+                "InList/NotInList operator requires ListReference on right side".to_string(),
+            ));
+        };
+        let table = self.list_tables.get(list_id).ok_or_else(|| {
+            CompileError::UnsupportedFeature(format!(
+                "list '{}' has no table; map it with SqlCompiler::with_list_table",
+                list_id
+            ))
+        })?;
+        let left = self.compile_fragment(left)?;
+        let not = if *op == Operator::NotInList {
+            "NOT "
+        } else {
+            ""
+        };
+        let lookup = format!(
+            "({}COALESCE({} IN (SELECT `value` FROM {}), FALSE))",
+            not, left.sql, table
+        );
+
+        // Missing values are not in any list, or Unknown under three-valued logic
+        Ok(if self.three_valued && left.nullable {
+            Fragment::new(
+                format!(
+                    "(CASE WHEN {} IS NULL THEN NULL ELSE {} END)",
+                    left.sql, lookup
+                ),
+                true,
+            )
+        } else {
+            Fragment::new(lookup, false)
+        })
+    }
+
+    fn literal(&self, value: &Value) -> Result<Fragment> {
+        let sql = match value {
+            Value::Null => return Ok(Fragment::new("NULL".to_string(), true)),
+            Value::Bool(b) => sql_bool(*b).to_string(),
+            Value::Number(n) if !n.is_finite() => {
+                return Err(CompileError::InvalidExpression(format!(
+                    "{} has no SQL literal",
+                    n
+                )))
+            }
+            Value::Number(n) if n.fract() == 0.0 && n.abs() < 1e15 => format!("{}", *n as i64),
+            Value::Number(n) => format!("{}", n),
+            Value::String(s) => quote_string(s),
+            Value::Array(_) | Value::Object(_) => {
+                return Err(unsupported(
+                    "array and object literals outside 'in'".to_string(),
+                ))
+            }
+        };
+        Ok(Fragment::new(sql, false))
+    }
+
+    /// Column of a field
+    fn column(&self, path: &[String]) -> Result<String> {
+        let field = path.join(".");
+        if let Some(sql) = self.columns.get(&field) {
+            return Ok(sql.clone());
+        }
+        match path {
+            [namespace, rest @ ..] if namespace == "event" && !rest.is_empty() => Ok(rest
+                .iter()
+                .map(|part| quote_identifier(part))
+                .collect::<Vec<_>>()
+                .join(".")),
+            _ => Err(CompileError::UnsupportedFeature(format!(
+                "field '{}' has no column; map it with SqlCompiler::with_column",
+                field
+            ))),
+        }
+    }
+}
+
+fn unsupported(what: String) -> CompileError {
+    CompileError::UnsupportedFeature(format!("{} cannot be compiled to SQL", what))
+}
+
+fn sql_bool(value: bool) -> &'static str {
+    if value {
+        "TRUE"
+    } else {
+        "FALSE"
+    }
+}
+
+fn comparison_operator(op: &Operator) -> &'static str {
+    match op {
+        Operator::Eq => "=",
+        Operator::Ne => "<>",
+        Operator::Gt => ">",
+        Operator::Ge => ">=",
+        Operator::Lt => "<",
+        _ => "<=",
+    }
+}
+
+/// Quote an identifier with backticks, which both dialects accept
+fn quote_identifier(identifier: &str) -> String {
+    format!("`{}`", identifier.replace('\\', "\\\\").replace('`', "\\`"))
+}
+
+/// Quote a string literal, escaping with backslashes as both dialects do
+fn quote_string(value: &str) -> String {
+    format!("'{}'", value.replace('\\', "\\\\").replace('\'', "\\'"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use corint_core::ast::WhenBlock;
+
+    fn field(path: &str) -> Expression {
+        Expression::field_access(path.split('.').map(str::to_string).collect())
+    }
+
+    fn number(value: f64) -> Expression {
+        Expression::literal(Value::Number(value))
+    }
+
+    fn rule(id: &str, when: WhenBlock, score: i32) -> Rule {
+        Rule::new(id.to_string(), id.to_string(), when, score)
+    }
+
+    #[test]
+    fn test_compiles_comparisons_with_null_semantics() {
+        let when = WhenBlock::new()
+            .add_condition(Expression::binary(
+                field("event.amount"),
+                Operator::Gt,
+                number(1000.0),
+            ))
+            .add_condition(Expression::binary(
+                field("event.country"),
+                Operator::Ne,
+                Expression::literal(Value::String("US".to_string())),
+            ));
+        let large_foreign = rule("large_foreign", when, 50);
+
+        // Both comparisons are false for missing values, so never NULL
+        assert_eq!(
+            SqlCompiler::new(SqlDialect::ClickHouse)
+                .compile_rule(&large_foreign)
+                .unwrap(),
+            "(COALESCE((`amount` > 1000), FALSE) AND COALESCE((`country` <> 'US'), FALSE))"
+        );
+        // Kleene logic is SQL's own
+        assert_eq!(
+            SqlCompiler::new(SqlDialect::ClickHouse)
+                .with_three_valued_logic(true)
+                .compile_rule(&large_foreign)
+                .unwrap(),
+            "COALESCE(((`amount` > 1000) AND (`country` <> 'US')), FALSE)"
+        );
+    }
+
+    #[test]
+    fn test_compiles_groups_lists_and_dialect_functions() {
+        let when = WhenBlock {
+            event_type: Some("payment".to_string()),
+            condition_group: Some(ConditionGroup::Any(vec![
+                Condition::Expression(Expression::binary(
+                    field("event.email"),
+                    Operator::EndsWith,
+                    Expression::literal(Value::String("@tempmail.io".to_string())),
+                )),
+                Condition::Group(Box::new(ConditionGroup::Not(vec![Condition::Expression(
+                    Expression::binary(
+                        field("event.ip"),
+                        Operator::InList,
+                        Expression::ListReference {
+                            list_id: "trusted_ips".to_string(),
+                        },
+                    ),
+                )]))),
+            ])),
+            conditions: None,
+        };
+        let suspicious = rule("suspicious", when, 30);
+        let compiler = SqlCompiler::new(SqlDialect::BigQuery)
+            .with_list_table("trusted_ips", "risk.trusted_ips");
+
+        assert_eq!(
+            compiler.compile_rule(&suspicious).unwrap(),
+            "(COALESCE(`type` = 'payment', FALSE) AND COALESCE((CASE WHEN \
+             ENDS_WITH(`email`, '@tempmail.io') IS NULL THEN NULL ELSE \
+             (ENDS_WITH(`email`, '@tempmail.io') OR (NOT (COALESCE(`ip` IN \
+             (SELECT `value` FROM risk.trusted_ips), FALSE)))) END), FALSE))"
+        );
+
+        // Lists and fields outside the event must be mapped
+        assert!(SqlCompiler::new(SqlDialect::BigQuery)
+            .compile_rule(&suspicious)
+            .is_err());
+        let velocity =
+            Expression::binary(field("features.txn_count_1h"), Operator::Gt, number(5.0));
+        assert!(compiler.compile_expression(&velocity).is_err());
+        assert_eq!(
+            compiler
+                .with_column("features.txn_count_1h", "`txn_count_1h`")
+                .compile_expression(&velocity)
+                .unwrap(),
+            "COALESCE((`txn_count_1h` > 5), FALSE)"
+        );
+    }
+
+    #[test]
+    fn test_backtest_query_counts_flagged_events() {
+        let high_amount = rule(
+            "high_amount",
+            WhenBlock::new().add_condition(Expression::binary(
+                field("event.amount"),
+                Operator::Ge,
+                number(0.5),
+            )),
+            40,
+        );
+        let risky_name = rule(
+            "risky_name",
+            WhenBlock::new().add_condition(Expression::binary(
+                field("event.user.name"),
+                Operator::Eq,
+                Expression::literal(Value::String("O'Brien".to_string())),
+            )),
+            10,
+        );
+        let ruleset = Ruleset {
+            id: "fraud".to_string(),
+            name: None,
+            extends: None,
+            rules: vec!["high_amount".to_string(), "risky_name".to_string()],
+            conclusion: vec![],
+            description: None,
+            effective_from: None,
+            effective_until: None,
+            metadata: None,
+            scoring: None,
+            stop_on_score: None,
+            stop_on_signal: None,
+        };
+
+        let compiled = SqlCompiler::new(SqlDialect::ClickHouse)
+            .compile_ruleset(&ruleset, &[risky_name, high_amount])
+            .unwrap();
+
+        assert_eq!(compiled.rules[0].rule_id, "high_amount");
+        assert_eq!(compiled.rules[0].score, 40);
+        assert_eq!(
+            compiled.backtest_query("events"),
+            "SELECT\n  COUNT(*) AS `events`,\n  \
+             SUM(CASE WHEN COALESCE((`amount` >= 0.5), FALSE) THEN 1 ELSE 0 END) AS `high_amount`,\n  \
+             SUM(CASE WHEN COALESCE((`user`.`name` = 'O\\'Brien'), FALSE) THEN 1 ELSE 0 END) AS `risky_name`,\n  \
+             SUM(CASE WHEN COALESCE((`amount` >= 0.5), FALSE) OR COALESCE((`user`.`name` = 'O\\'Brien'), FALSE) \
+             THEN 1 ELSE 0 END) AS `flagged`\nFROM events"
+        );
+
+        // Every rule of the ruleset must be given
+        let missing = SqlCompiler::new(SqlDialect::ClickHouse).compile_ruleset(&ruleset, &[]);
+        assert!(matches!(missing, Err(CompileError::RuleNotFound { .. })));
+    }
+
+    #[test]
+    fn test_rejects_constructs_without_sql_equivalent() {
+        let compiler = SqlCompiler::new(SqlDialect::ClickHouse);
+        let regex = Expression::binary(
+            field("event.email"),
+            Operator::Regex,
+            Expression::literal(Value::String(".*".to_string())),
+        );
+        let call = Expression::function_call("velocity".to_string(), vec![]);
+
+        assert!(matches!(
+            compiler.compile_expression(&regex),
+            Err(CompileError::UnsupportedFeature(_))
+        ));
+        assert!(matches!(
+            compiler.compile_expression(&call),
+            Err(CompileError::UnsupportedFeature(_))
+        ));
+    }
+}
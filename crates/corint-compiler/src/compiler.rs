@@ -2,15 +2,21 @@
 //!
 //! Provides a unified interface for compiling different AST node types.
 
-use crate::codegen::{PipelineCompiler, RuleCompiler, RulesetCompiler};
-use crate::error::Result;
+use crate::cache::CompilationCache;
+use crate::codegen::{DecisionTableCompiler, PipelineCompiler, RuleCompiler, RulesetCompiler};
+use crate::error::{CompileError, Result};
 use crate::import_resolver::ImportResolver;
-use crate::optimizer::{ConstantFolder, DeadCodeEliminator};
-use crate::semantic::SemanticAnalyzer;
-use corint_core::ast::{Pipeline, Rule, Ruleset};
+use crate::optimizer::{
+    CommonSubexpressionEliminator, ConditionReorderer, ConstantFolder, DeadCodeEliminator,
+};
+use crate::plugin::{StepPlugin, StepPlugins};
+use crate::semantic::{SemanticAnalyzer, TypeChecker};
+use corint_core::ast::{DecisionTable, Pipeline, ReasonCatalog, Rule, Ruleset};
 use corint_core::ir::Program;
 use corint_parser::PipelineParser;
+use serde::Serialize;
 use std::path::Path;
+use std::sync::Arc;
 
 /// Compiler options
 #[derive(Debug, Clone)]
@@ -21,6 +27,10 @@ pub struct CompilerOptions {
     pub enable_constant_folding: bool,
     /// Enable dead code elimination
     pub enable_dead_code_elimination: bool,
+    /// Enable common subexpression elimination across the rules of a ruleset
+    pub enable_common_subexpression_elimination: bool,
+    /// Enable cost-based reordering of the conditions of a rule
+    pub enable_condition_reordering: bool,
     /// Library base path for imports (default: "repository")
     pub library_base_path: String,
 }
@@ -31,6 +41,8 @@ impl Default for CompilerOptions {
             enable_semantic_analysis: true,
             enable_constant_folding: true,
             enable_dead_code_elimination: true,
+            enable_common_subexpression_elimination: true,
+            enable_condition_reordering: true,
             library_base_path: "repository".to_string(),
         }
     }
@@ -46,8 +58,18 @@ pub struct Compiler {
     constant_folder: ConstantFolder,
     /// Dead code eliminator
     dead_code_eliminator: DeadCodeEliminator,
+    /// Common subexpression eliminator
+    common_subexpression_eliminator: CommonSubexpressionEliminator,
+    /// Condition reorderer
+    condition_reorderer: ConditionReorderer,
     /// Import resolver
     import_resolver: ImportResolver,
+    /// Reason code catalog used to validate conclusion reason codes
+    reason_catalog: Option<ReasonCatalog>,
+    /// Programs compiled by this or earlier compilers, reused when unchanged
+    cache: Option<Arc<CompilationCache>>,
+    /// Plugins compiling the pipeline steps of custom types
+    step_plugins: StepPlugins,
 }
 
 impl Compiler {
@@ -65,19 +87,118 @@ impl Compiler {
             semantic_analyzer: SemanticAnalyzer::new(),
             constant_folder: ConstantFolder::new(),
             dead_code_eliminator: DeadCodeEliminator::new(),
+            common_subexpression_eliminator: CommonSubexpressionEliminator::new(),
+            condition_reorderer: ConditionReorderer::new(),
             import_resolver,
+            reason_catalog: None,
+            cache: None,
+            step_plugins: StepPlugins::new(),
         }
     }
 
+    /// Set the reason code catalog
+    ///
+    /// Once set, rulesets whose conclusions reference codes missing from
+    /// the catalog fail to compile. Without a catalog, codes are not checked.
+    pub fn set_reason_catalog(&mut self, catalog: ReasonCatalog) {
+        self.reason_catalog = Some(catalog);
+    }
+
+    /// Set the compilation cache
+    ///
+    /// Rules, rulesets and pipelines found unchanged in the cache, compiled
+    /// with the same options, are not compiled again. Share one cache across
+    /// the compilers of successive loads to only recompile what changed.
+    pub fn set_cache(&mut self, cache: Arc<CompilationCache>) {
+        self.cache = Some(cache);
+    }
+
+    /// Register a plugin compiling the pipeline steps of a custom type
+    ///
+    /// Steps of types that are neither built in nor registered, and have
+    /// fields of their own, fail to compile.
+    pub fn register_step_plugin(&mut self, plugin: Arc<dyn StepPlugin>) {
+        self.step_plugins.register(plugin);
+    }
+
+    /// Replace the registered step plugins, e.g. with those of a previous compiler
+    pub fn set_step_plugins(&mut self, plugins: StepPlugins) {
+        self.step_plugins = plugins;
+    }
+
     /// Compile a rule into an IR program
     pub fn compile_rule(&mut self, rule: &Rule) -> Result<Program> {
+        self.cached("rule", rule, |compiler| compiler.generate_rule(rule))
+    }
+
+    /// Compile a ruleset into an IR program
+    pub fn compile_ruleset(&mut self, ruleset: &Ruleset) -> Result<Program> {
+        self.cached("ruleset", ruleset, |compiler| {
+            compiler.generate_ruleset(ruleset)
+        })
+    }
+
+    /// Compile a decision table into an IR program
+    ///
+    /// The table compiles into a rule program, run by rulesets that list its ID.
+    pub fn compile_decision_table(&mut self, table: &DecisionTable) -> Result<Program> {
+        self.cached("decision_table", table, |compiler| {
+            compiler.generate_decision_table(table)
+        })
+    }
+
+    /// Compile a pipeline into an IR program
+    pub fn compile_pipeline(&mut self, pipeline: &Pipeline) -> Result<Program> {
+        self.cached("pipeline", pipeline, |compiler| {
+            compiler.generate_pipeline(pipeline)
+        })
+    }
+
+    /// Program of `document` from the cache, or compiled with `compile` and cached
+    fn cached<T: Serialize>(
+        &mut self,
+        kind: &str,
+        document: &T,
+        compile: impl FnOnce(&mut Self) -> Result<Program>,
+    ) -> Result<Program> {
+        let Some(cache) = self.cache.clone() else {
+            return compile(self);
+        };
+
+        // Everything besides the document that its program depends on
+        let mut context = format!("{:?}", self.options);
+        if let Some(catalog) = &self.reason_catalog {
+            context.push_str(&serde_json::to_string(catalog).unwrap_or_default());
+        }
+        if !self.step_plugins.is_empty() {
+            context.push_str(&format!("{:?}", self.step_plugins));
+        }
+        let Some(key) = CompilationCache::key(kind, document, &context) else {
+            return compile(self);
+        };
+
+        if let Some(program) = cache.get(&key) {
+            return Ok(program);
+        }
+        let program = compile(self)?;
+        cache.insert(key, program.clone());
+        Ok(program)
+    }
+
+    fn generate_rule(&mut self, rule: &Rule) -> Result<Program> {
         // Semantic analysis
         if self.options.enable_semantic_analysis {
             self.semantic_analyzer.analyze_rule(rule)?;
         }
 
-        // Code generation
-        let mut program = RuleCompiler::compile(rule)?;
+        // Code generation, cheapest conditions first; traces keep the declared order
+        let mut program = if self.options.enable_condition_reordering {
+            let mut program = RuleCompiler::compile(&self.condition_reorderer.reorder(rule))?;
+            RuleCompiler::store_condition_metadata(&rule.when, &mut program.metadata);
+            program
+        } else {
+            RuleCompiler::compile(rule)?
+        };
 
         // Optimization
         if self.options.enable_dead_code_elimination {
@@ -87,13 +208,26 @@ impl Compiler {
         Ok(program)
     }
 
-    /// Compile a ruleset into an IR program
-    pub fn compile_ruleset(&mut self, ruleset: &Ruleset) -> Result<Program> {
+    fn generate_ruleset(&mut self, ruleset: &Ruleset) -> Result<Program> {
         // Semantic analysis
         if self.options.enable_semantic_analysis {
             self.semantic_analyzer.analyze_ruleset(ruleset)?;
         }
 
+        // Reason code validation
+        if let Some(catalog) = &self.reason_catalog {
+            for decision_rule in &ruleset.conclusion {
+                if let Some(code) = &decision_rule.reason_code {
+                    if !catalog.contains(code) {
+                        return Err(CompileError::UnknownReasonCode {
+                            code: code.clone(),
+                            ruleset_id: ruleset.id.clone(),
+                        });
+                    }
+                }
+            }
+        }
+
         // Code generation
         let mut program = RulesetCompiler::compile(ruleset)?;
 
@@ -105,15 +239,26 @@ impl Compiler {
         Ok(program)
     }
 
-    /// Compile a pipeline into an IR program
-    pub fn compile_pipeline(&mut self, pipeline: &Pipeline) -> Result<Program> {
+    fn generate_decision_table(&mut self, table: &DecisionTable) -> Result<Program> {
+        let mut program = DecisionTableCompiler::compile(table)?;
+
+        // Optimization
+        if self.options.enable_dead_code_elimination {
+            program = self.dead_code_eliminator.optimize(&program);
+        }
+
+        Ok(program)
+    }
+
+    fn generate_pipeline(&mut self, pipeline: &Pipeline) -> Result<Program> {
         // Semantic analysis
         if self.options.enable_semantic_analysis {
             self.semantic_analyzer.analyze_pipeline(pipeline)?;
+            self.check_types(pipeline, &[])?;
         }
 
         // Code generation
-        let mut program = PipelineCompiler::compile(pipeline)?;
+        let mut program = PipelineCompiler::compile_with_plugins(pipeline, &self.step_plugins)?;
 
         // Optimization
         if self.options.enable_dead_code_elimination {
@@ -123,6 +268,89 @@ impl Compiler {
         Ok(program)
     }
 
+    /// Type check the conditions of a pipeline, and of the rules it runs,
+    /// against the event schema the pipeline declares
+    ///
+    /// Comparisons between incompatible types (`event.country > 100`) and
+    /// event fields missing from the schema fail to compile, instead of
+    /// evaluating to false at runtime. Pipelines without a schema pass.
+    pub fn check_types(&self, pipeline: &Pipeline, rules: &[Rule]) -> Result<()> {
+        let Some(schema) = &pipeline.schema else {
+            return Ok(());
+        };
+        let checker = TypeChecker::with_schema(schema);
+        let in_context = |context: String| {
+            move |error: CompileError| match error {
+                CompileError::TypeError(message) => {
+                    CompileError::TypeError(format!("{}: {}", context, message))
+                }
+                error => error,
+            }
+        };
+
+        let steps = pipeline.steps.iter().flat_map(|step| {
+            step.when
+                .iter()
+                .chain(step.routes.iter().flatten().map(|route| &route.when))
+        });
+        for when in pipeline.when.iter().chain(steps) {
+            checker
+                .check_when(when)
+                .map_err(in_context(format!("Pipeline '{}'", pipeline.id)))?;
+        }
+        for rule in rules {
+            checker
+                .check_when(&rule.when)
+                .map_err(in_context(format!("Rule '{}'", rule.id)))?;
+        }
+        Ok(())
+    }
+
+    /// Share the conditions repeated across the rules of each ruleset
+    ///
+    /// Rules are compiled one at a time, so this pass runs over the compiled
+    /// programs once all of them are: the rules of each ruleset in
+    /// `programs` compute a condition they have in common once per request.
+    pub fn eliminate_common_subexpressions(&self, programs: &mut [Program]) {
+        if !self.options.enable_common_subexpression_elimination {
+            return;
+        }
+
+        let rulesets: Vec<(String, Vec<String>)> = programs
+            .iter()
+            .filter(|program| program.metadata.source_type == "ruleset")
+            .map(|program| {
+                let rules = program
+                    .metadata
+                    .custom
+                    .get("rules")
+                    .map(|rules| rules.split(',').map(str::to_string).collect())
+                    .unwrap_or_default();
+                (program.metadata.source_id.clone(), rules)
+            })
+            .collect();
+
+        for (ruleset_id, rule_ids) in rulesets {
+            let mut indices = Vec::new();
+            for rule_id in &rule_ids {
+                let index = programs.iter().position(|program| {
+                    program.metadata.source_type == "rule" && &program.metadata.source_id == rule_id
+                });
+                if let Some(index) = index.filter(|index| !indices.contains(index)) {
+                    indices.push(index);
+                }
+            }
+
+            let rules: Vec<Program> = indices.iter().map(|&i| programs[i].clone()).collect();
+            let optimized = self
+                .common_subexpression_eliminator
+                .eliminate(&ruleset_id, &rules);
+            for (index, program) in indices.into_iter().zip(optimized) {
+                programs[index] = program;
+            }
+        }
+    }
+
     /// Get a reference to the semantic analyzer
     pub fn semantic_analyzer(&self) -> &SemanticAnalyzer {
         &self.semantic_analyzer
@@ -188,18 +416,117 @@ impl Compiler {
         })?;
 
         // 3. Resolve imports
-        let _resolved = self.import_resolver.resolve_imports(&document)?;
+        let resolved = self.import_resolver.resolve_imports(&document)?;
+        self.check_types(&document.definition, &resolved.rules)?;
 
         // 4. TODO: For now, we just compile the pipeline itself
         // In a full implementation, we would need to:
         // - Store resolved rules and rulesets in a context
         // - Pass this context to the compiler
         // - The compiler would use this context when compiling ruleset references
-        // - Use `_resolved` to get the complete list of rules and rulesets
+        // - Use `resolved` to get the complete list of rules and rulesets
 
         // For now, just compile the pipeline
         self.compile_pipeline(&document.definition)
     }
+
+    /// Fill in the file and line of the conditions in the source map of a
+    /// program compiled from `content`, read from `file`
+    ///
+    /// Conditions are looked up from the line declaring the program's ID, by
+    /// their text; those not found (e.g. written differently in the source)
+    /// are left without a location.
+    pub fn locate_source(program: &mut Program, file: &str, content: &str) {
+        let lines: Vec<&str> = content.lines().collect();
+        let id = program.metadata.source_id.as_str();
+        let declaration = lines.iter().position(|line| {
+            line.trim()
+                .strip_prefix("id:")
+                .map(|value| value.trim().trim_matches(|c| c == '"' || c == '\''))
+                == Some(id)
+        });
+        let first = declaration.unwrap_or(0);
+
+        for span in &mut program.metadata.source_map {
+            if let Some(index) = lines[first..]
+                .iter()
+                .position(|line| line.contains(&span.text))
+            {
+                span.file = Some(file.to_string());
+                span.line = Some(first + index + 1);
+            }
+        }
+    }
+
+    /// Locate the source map of a program compiled from a rule or ruleset
+    /// loaded by the import resolver (see [`Compiler::locate_source`])
+    pub fn locate_imported_source(&self, program: &mut Program) {
+        let Some(path) = self
+            .import_resolver
+            .source_file(&program.metadata.source_id)
+        else {
+            return;
+        };
+        if let Ok(content) = std::fs::read_to_string(&path) {
+            Self::locate_source(program, &path.display().to_string(), &content);
+        }
+    }
+
+    /// Compile a pipeline file and its imports into a binary artifact
+    ///
+    /// The pipeline, and the rules and rulesets it imports, are compiled and
+    /// written to `output` in the format of [`corint_core::ir::binary`], from
+    /// which runtimes load them with `Program::load_all_from_bytes` without
+    /// the parser or compiler. Returns the programs written.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use corint_compiler::Compiler;
+    /// use std::path::Path;
+    ///
+    /// let mut compiler = Compiler::new();
+    /// compiler
+    ///     .compile_to_file(
+    ///         Path::new("repository/pipelines/fraud_detection.yaml"),
+    ///         Path::new("fraud_detection.crir"),
+    ///     )
+    ///     .unwrap();
+    /// ```
+    pub fn compile_to_file(&mut self, input: &Path, output: &Path) -> Result<Vec<Program>> {
+        let content = std::fs::read_to_string(input).map_err(|e| CompileError::ImportNotFound {
+            path: input.display().to_string(),
+            source: e,
+        })?;
+        let document =
+            PipelineParser::parse_with_imports(&content).map_err(|e| CompileError::ParseError {
+                path: input.display().to_string(),
+                message: e.to_string(),
+            })?;
+        let resolved = self.import_resolver.resolve_imports(&document)?;
+        self.check_types(&document.definition, &resolved.rules)?;
+
+        let mut programs = Vec::new();
+        for rule in &resolved.rules {
+            programs.push(self.compile_rule(rule)?);
+        }
+        for ruleset in &resolved.rulesets {
+            programs.push(self.compile_ruleset(ruleset)?);
+        }
+        for program in &mut programs {
+            self.locate_imported_source(program);
+        }
+        programs.push(self.compile_pipeline(&document.definition)?);
+        self.eliminate_common_subexpressions(&mut programs);
+
+        let bytes = corint_core::ir::binary::encode(&programs)
+            .map_err(|e| CompileError::CompileError(e.to_string()))?;
+        std::fs::write(output, bytes).map_err(|e| CompileError::ArtifactWriteError {
+            path: output.display().to_string(),
+            source: e,
+        })?;
+        Ok(programs)
+    }
 }
 
 impl Default for Compiler {
@@ -211,7 +538,11 @@ impl Default for Compiler {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use corint_core::ast::{Expression, Operator, WhenBlock};
+    use corint_core::ast::pipeline::{PipelineStep, StepDetails};
+    use corint_core::ast::{
+        DecisionRule, Expression, Operator, ReasonCode, ReasonMessage, Signal, WhenBlock,
+    };
+    use corint_core::ir::Instruction;
     use corint_core::Value;
 
     #[test]
@@ -244,6 +575,8 @@ mod tests {
             enable_semantic_analysis: false,
             enable_constant_folding: false,
             enable_dead_code_elimination: false,
+            enable_common_subexpression_elimination: false,
+            enable_condition_reordering: false,
             library_base_path: "repository".to_string(),
         };
 
@@ -272,6 +605,331 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_compiler_validates_reason_codes() {
+        let mut compiler = Compiler::new();
+        compiler.set_reason_catalog(ReasonCatalog::new().add_code(
+            ReasonCode::new("HIGH_RISK".to_string()).with_message(
+                "en".to_string(),
+                ReasonMessage::new("High risk".to_string()),
+            ),
+        ));
+
+        let known = Ruleset::new("known".to_string()).add_decision_rule(
+            DecisionRule::default(Signal::Decline).with_reason_code("HIGH_RISK".to_string()),
+        );
+        let program = compiler.compile_ruleset(&known).unwrap();
+        assert!(program.instructions.iter().any(|inst| matches!(
+            inst,
+            corint_core::ir::Instruction::SetReasonCode { code } if code == "HIGH_RISK"
+        )));
+
+        let unknown = Ruleset::new("unknown".to_string()).add_decision_rule(
+            DecisionRule::default(Signal::Decline).with_reason_code("MISSING".to_string()),
+        );
+        let result = compiler.compile_ruleset(&unknown);
+        assert!(matches!(
+            result,
+            Err(CompileError::UnknownReasonCode { ref code, .. }) if code == "MISSING"
+        ));
+    }
+
+    #[test]
+    fn test_compiler_shares_conditions_of_ruleset() {
+        let mut compiler = Compiler::new();
+        let large_amount = || {
+            Expression::binary(
+                Expression::field_access(vec!["event".to_string(), "amount".to_string()]),
+                Operator::Gt,
+                Expression::literal(Value::Number(1000.0)),
+            )
+        };
+        let rule = |id: &str| {
+            Rule::new(
+                id.to_string(),
+                id.to_string(),
+                WhenBlock::new().add_condition(large_amount()),
+                10,
+            )
+        };
+
+        let mut programs = vec![
+            compiler.compile_rule(&rule("large_amount")).unwrap(),
+            compiler.compile_rule(&rule("large_amount_again")).unwrap(),
+            compiler.compile_rule(&rule("unused")).unwrap(),
+            compiler
+                .compile_ruleset(&Ruleset::new("payments".to_string()).with_rules(vec![
+                    "large_amount".to_string(),
+                    "large_amount_again".to_string(),
+                ]))
+                .unwrap(),
+        ];
+        let unused = programs[2].clone();
+        compiler.eliminate_common_subexpressions(&mut programs);
+
+        for program in &programs[..2] {
+            assert!(matches!(
+                &program.instructions[0],
+                corint_core::ir::Instruction::LoadSlot { slot, .. } if slot == "payments#0"
+            ));
+        }
+        // Rules outside the ruleset are not touched
+        assert_eq!(programs[2], unused);
+    }
+
+    #[test]
+    fn test_compiler_reorders_conditions_cheapest_first() {
+        let mut compiler = Compiler::new();
+        let field =
+            |path: &[&str]| Expression::field_access(path.iter().map(|s| s.to_string()).collect());
+        let when = WhenBlock::new()
+            .add_condition(Expression::binary(
+                field(&["features", "txn_count_1h"]),
+                Operator::Gt,
+                Expression::literal(Value::Number(10.0)),
+            ))
+            .add_condition(Expression::binary(
+                field(&["event", "amount"]),
+                Operator::Gt,
+                Expression::literal(Value::Number(1000.0)),
+            ));
+        let rule = Rule::new("velocity".to_string(), "Velocity".to_string(), when, 50);
+
+        let program = compiler.compile_rule(&rule).unwrap();
+
+        assert_eq!(
+            program.instructions[0],
+            corint_core::ir::Instruction::LoadField {
+                path: vec!["event".to_string(), "amount".to_string()]
+            }
+        );
+        // Traces show the conditions as declared
+        assert!(program.metadata.custom["conditions"].starts_with("features.txn_count_1h"));
+    }
+
+    #[test]
+    fn test_compiler_reuses_cached_programs() {
+        let cache = Arc::new(CompilationCache::new());
+        let rule = |threshold: f64| {
+            let when = WhenBlock::new().add_condition(Expression::binary(
+                Expression::field_access(vec!["event".to_string(), "amount".to_string()]),
+                Operator::Gt,
+                Expression::literal(Value::Number(threshold)),
+            ));
+            Rule::new("large".to_string(), "Large".to_string(), when, 50)
+        };
+
+        let mut first = Compiler::new();
+        first.set_cache(cache.clone());
+        let program = first.compile_rule(&rule(1000.0)).unwrap();
+
+        // A later load finds the unchanged rule, compiled with the same options
+        let mut second = Compiler::new();
+        second.set_cache(cache.clone());
+        assert_eq!(second.compile_rule(&rule(1000.0)).unwrap(), program);
+        assert_eq!(cache.stats().hits, 1);
+
+        // A changed rule, or other options, compile again
+        assert_ne!(second.compile_rule(&rule(2000.0)).unwrap(), program);
+        let mut unoptimized = Compiler::with_options(CompilerOptions {
+            enable_dead_code_elimination: false,
+            ..CompilerOptions::default()
+        });
+        unoptimized.set_cache(cache.clone());
+        unoptimized.compile_rule(&rule(1000.0)).unwrap();
+        assert_eq!(cache.stats().hits, 1);
+        assert_eq!(cache.stats().misses, 3);
+    }
+
+    #[test]
+    fn test_compiler_locates_conditions_in_source() {
+        use corint_parser::RuleParser;
+
+        let content = r#"
+rule:
+  id: velocity
+  name: Velocity
+  when:
+    all:
+      - features.txn_count_1h > 10
+      - event.amount > 1000
+  score: 50
+"#;
+        let mut compiler = Compiler::new();
+        let mut program = compiler
+            .compile_rule(&RuleParser::parse(content).unwrap())
+            .unwrap();
+        Compiler::locate_source(&mut program, "rules/velocity.yaml", content);
+
+        // The cheaper event condition is evaluated first, and still points
+        // at its own line
+        let first = program.source_span(0).unwrap();
+        assert_eq!(first.text, "event.amount > 1000");
+        assert_eq!(first.location().as_deref(), Some("rules/velocity.yaml:8"));
+        let velocity = program
+            .metadata
+            .source_map
+            .iter()
+            .find(|span| span.text == "features.txn_count_1h > 10")
+            .unwrap();
+        assert_eq!(velocity.line, Some(7));
+    }
+
+    #[test]
+    fn test_compiler_checks_types_against_event_schema() {
+        use corint_parser::{PipelineParser, RuleParser};
+
+        let pipeline = PipelineParser::parse(
+            r#"
+pipeline:
+  id: payment_pipeline
+  name: Payment Pipeline
+  entry: score
+  when:
+    all:
+      - event.type == "payment"
+  schema:
+    type: string
+    amount: number
+    country: string
+  steps:
+    - step:
+        id: score
+        name: Score
+        type: ruleset
+        ruleset: fraud_detection
+"#,
+        )
+        .unwrap();
+        let rule = |condition: &str| {
+            RuleParser::parse(&format!(
+                "rule:\n  id: check\n  name: Check\n  when:\n    all:\n      - {}\n  score: 10\n",
+                condition
+            ))
+            .unwrap()
+        };
+
+        let mut compiler = Compiler::new();
+        assert!(compiler.compile_pipeline(&pipeline).is_ok());
+        assert!(compiler
+            .check_types(&pipeline, &[rule("event.amount > 1000")])
+            .is_ok());
+
+        let error = compiler
+            .check_types(&pipeline, &[rule("event.country > 100")])
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Type error: Rule 'check': Cannot compare string with number"
+        );
+        assert!(compiler
+            .check_types(&pipeline, &[rule("event.amout > 1000")])
+            .is_err());
+
+        // Pipeline conditions are checked when the pipeline compiles
+        let mut mistyped = pipeline.clone();
+        mistyped.when = Some(rule("event.type == 1").when);
+        assert!(compiler.compile_pipeline(&mistyped).is_err());
+
+        mistyped.schema = None;
+        assert!(compiler.compile_pipeline(&mistyped).is_ok());
+    }
+
+    /// Plugin compiling `geo_velocity` steps into a call of the handler of the same name
+    struct GeoVelocityPlugin;
+
+    impl StepPlugin for GeoVelocityPlugin {
+        fn step_type(&self) -> &str {
+            "geo_velocity"
+        }
+
+        fn validate(&self, step: &PipelineStep) -> Result<()> {
+            match &step.details {
+                StepDetails::Custom { fields }
+                    if fields.get("window_hours").is_some_and(|v| v.is_number()) =>
+                {
+                    Ok(())
+                }
+                _ => Err(CompileError::CompileError(format!(
+                    "Step '{}' needs a numeric window_hours",
+                    step.id
+                ))),
+            }
+        }
+
+        fn compile(&self, step: &PipelineStep) -> Result<Vec<Instruction>> {
+            let StepDetails::Custom { fields } = &step.details else {
+                return Ok(Vec::new());
+            };
+            let window = fields["window_hours"].as_f64().unwrap_or_default();
+            Ok(vec![
+                Instruction::LoadConst {
+                    value: Value::Number(window),
+                },
+                Instruction::CallCustom {
+                    handler: "geo_velocity".to_string(),
+                    step_id: step.id.clone(),
+                    args: vec!["window_hours".to_string()],
+                },
+                Instruction::Store {
+                    name: "geo_velocity".to_string(),
+                },
+            ])
+        }
+    }
+
+    #[test]
+    fn test_compiler_step_plugin() {
+        use corint_parser::PipelineParser;
+
+        let yaml = r#"
+pipeline:
+  id: login_pipeline
+  name: Login Pipeline
+  entry: velocity
+  steps:
+    - step:
+        id: velocity
+        name: Geo Velocity
+        type: geo_velocity
+        window_hours: 24
+        next: score
+    - step:
+        id: score
+        name: Score
+        type: ruleset
+        ruleset: login_risk
+"#;
+        let pipeline = PipelineParser::parse(yaml).unwrap();
+
+        // Without the plugin, the step's fields would be ignored
+        assert!(Compiler::new().compile_pipeline(&pipeline).is_err());
+
+        let mut compiler = Compiler::new();
+        compiler.register_step_plugin(Arc::new(GeoVelocityPlugin));
+        let program = compiler.compile_pipeline(&pipeline).unwrap();
+        let call = program
+            .instructions
+            .iter()
+            .position(|instruction| matches!(instruction, Instruction::CallCustom { .. }))
+            .unwrap();
+        assert!(matches!(
+            &program.instructions[call - 2],
+            Instruction::MarkStepExecuted { step_id, .. } if step_id == "velocity"
+        ));
+        assert_eq!(
+            program.instructions[call - 1],
+            Instruction::LoadConst {
+                value: Value::Number(24.0)
+            }
+        );
+
+        // The plugin validates the steps it compiles
+        let invalid =
+            PipelineParser::parse(&yaml.replace("window_hours: 24", "window_hours: day")).unwrap();
+        assert!(compiler.compile_pipeline(&invalid).is_err());
+    }
+
     #[test]
     fn test_compiler_accessors() {
         let compiler = Compiler::new();
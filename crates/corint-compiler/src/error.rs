@@ -104,6 +104,18 @@ pub enum CompileError {
         child_id: String,
         extends_id: String,
     },
+
+    /// Conclusion references a reason code missing from the catalog
+    #[error("Ruleset '{ruleset_id}' references unknown reason code '{code}'")]
+    UnknownReasonCode { code: String, ruleset_id: String },
+
+    /// Compiled programs could not be written to an artifact
+    #[error("Failed to write compiled programs to {path}")]
+    ArtifactWriteError {
+        path: String,
+        #[source]
+        source: std::io::Error,
+    },
 }
 
 /// Result type for compiler operations
@@ -294,6 +294,23 @@ impl ImportResolver {
             child.metadata = parent.metadata.clone();
         }
 
+        // Inherit score aggregation if child doesn't define one
+        if child.scoring.is_none() {
+            child.scoring = parent.scoring.clone();
+        }
+
+        // Inherit early termination if child doesn't stop on its own
+        if child.stop_on_score.is_none() && child.stop_on_signal.is_none() {
+            child.stop_on_score = parent.stop_on_score;
+            child.stop_on_signal = parent.stop_on_signal.clone();
+        }
+
+        // Inherit the effective window if child doesn't bound itself
+        if child.effective_from.is_none() && child.effective_until.is_none() {
+            child.effective_from = parent.effective_from.clone();
+            child.effective_until = parent.effective_until.clone();
+        }
+
         Ok(child)
     }
 
@@ -434,6 +451,13 @@ impl ImportResolver {
         None
     }
 
+    /// Path of the file the loaded rule or ruleset with this ID was read from
+    pub fn source_file(&self, id: &str) -> Option<PathBuf> {
+        self.get_rule_source(id)
+            .or_else(|| self.get_ruleset_source(id))
+            .map(|path| self.library_base_path.join(path))
+    }
+
     /// Clear all caches
     pub fn clear_cache(&mut self) {
         self.rule_cache.clear();
@@ -2,32 +2,42 @@
 //!
 //! This crate compiles CORINT AST into executable IR (Intermediate Representation).
 
+pub mod cache;
 pub mod codegen;
 pub mod compiler;
 pub mod error;
 pub mod import_resolver;
 pub mod optimizer;
+pub mod plugin;
 pub mod semantic;
 pub mod validator;
 
 // Re-export main types
+pub use cache::{CompilationCache, CompilationCacheStats};
 pub use compiler::{Compiler, CompilerOptions};
 pub use error::{CompileError, Result};
 
 // Re-export import resolver types
 pub use import_resolver::{ImportResolver, ResolvedDocument};
 
+// Re-export plugin types
+pub use plugin::{StepPlugin, StepPlugins};
+
 // Re-export codegen types for backward compatibility
+pub use codegen::DecisionTableCompiler;
 pub use codegen::ExpressionCompiler;
 pub use codegen::PipelineCompiler;
 pub use codegen::RuleCompiler;
 pub use codegen::RulesetCompiler;
+pub use codegen::{SqlCompiler, SqlDialect, SqlRule, SqlRuleset};
 
 // Re-export semantic types
-pub use semantic::{SemanticAnalyzer, TypeChecker, TypeInfo};
+pub use semantic::{ConfigDefinitions, SemanticAnalyzer, SourceFile, TypeChecker, TypeInfo};
 
 // Re-export optimizer types
-pub use optimizer::{ConstantFolder, DeadCodeEliminator};
+pub use optimizer::{
+    CommonSubexpressionEliminator, ConditionReorderer, ConstantFolder, DeadCodeEliminator,
+};
 
 // Re-export validator types
 pub use validator::{
@@ -0,0 +1,353 @@
+//! Common subexpression elimination optimizer
+//!
+//! Each rule of a ruleset is compiled to a separate program, so a condition
+//! such as `event.amount > 1000` repeated by twenty rules is evaluated twenty
+//! times per decision. This pass finds the condition subtrees that occur more
+//! than once across the rules of a ruleset and hoists each into a shared
+//! slot: the first program to reach it computes the condition and keeps the
+//! value with `StoreSlot`, and every later occurrence loads it back with
+//! `LoadSlot`, skipping the computation.
+//!
+//! Slot values live as long as the request, so only conditions over fields
+//! that cannot change within one are hoisted: event fields, feature values
+//! and environment variables. Conditions reading variables, results or
+//! system fields, or calling lists and external sources, are left alone.
+
+use corint_core::ir::{Instruction, Program};
+use std::collections::{HashMap, HashSet};
+use std::ops::Range;
+
+/// Namespaces whose fields keep their value for the whole request
+const STABLE_NAMESPACES: &[&str] = &["event", "features", "env"];
+
+/// Common subexpression eliminator
+pub struct CommonSubexpressionEliminator;
+
+impl CommonSubexpressionEliminator {
+    /// Create a new common subexpression eliminator
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Hoist the conditions repeated across `programs` into shared slots
+    ///
+    /// `programs` are the rules of the ruleset `scope`, whose ID prefixes the
+    /// slot names. Larger conditions are hoisted first; a condition nested in
+    /// a hoisted one is only hoisted if it still occurs twice elsewhere.
+    pub fn eliminate(&self, scope: &str, programs: &[Program]) -> Vec<Program> {
+        // Occurrences of each condition, keyed by its instructions
+        let mut occurrences: HashMap<String, Vec<(usize, Range<usize>)>> = HashMap::new();
+        let mut keys = Vec::new();
+        for (index, program) in programs.iter().enumerate() {
+            for range in Self::conditions(program) {
+                let Ok(key) = serde_json::to_string(&program.instructions[range.clone()]) else {
+                    continue;
+                };
+                let entry = occurrences.entry(key.clone()).or_default();
+                if entry.is_empty() {
+                    keys.push(key);
+                }
+                entry.push((index, range));
+            }
+        }
+        keys.retain(|key| occurrences[key].len() > 1);
+        keys.sort_by_key(|key| std::cmp::Reverse(occurrences[key][0].1.len()));
+
+        let mut hoisted: Vec<Vec<(Range<usize>, String)>> = vec![Vec::new(); programs.len()];
+        let mut slots = 0;
+        for key in keys {
+            let free: Vec<_> = occurrences[&key]
+                .iter()
+                .filter(|(index, range)| {
+                    !hoisted[*index]
+                        .iter()
+                        .any(|(taken, _)| taken.start < range.end && range.start < taken.end)
+                })
+                .collect();
+            if free.len() < 2 {
+                continue;
+            }
+
+            let slot = format!("{}#{}", scope, slots);
+            slots += 1;
+            for (index, range) in free {
+                hoisted[*index].push((range.clone(), slot.clone()));
+            }
+        }
+
+        programs
+            .iter()
+            .zip(hoisted)
+            .map(|(program, mut slots)| {
+                if slots.is_empty() {
+                    return program.clone();
+                }
+                slots.sort_by_key(|(range, _)| range.start);
+                Self::hoist(program, &slots)
+            })
+            .collect()
+    }
+
+    /// Ranges of the conditions of a program that can be hoisted
+    ///
+    /// A condition is a jump-free run of instructions leaving one value on
+    /// the stack, with at least one comparison of a stable field, that no
+    /// jump lands in the middle of.
+    fn conditions(program: &Program) -> Vec<Range<usize>> {
+        let instructions = &program.instructions;
+        let targets: HashSet<usize> = instructions
+            .iter()
+            .enumerate()
+            .filter_map(|(pc, instruction)| Some(pc as isize + Self::jump_offset(instruction)?))
+            .filter_map(|target| usize::try_from(target).ok())
+            .collect();
+
+        let mut conditions = Vec::new();
+        for end in 0..instructions.len() {
+            if !matches!(
+                instructions[end],
+                Instruction::Compare { .. }
+                    | Instruction::BinaryOp { .. }
+                    | Instruction::UnaryOp { .. }
+            ) {
+                continue;
+            }
+
+            // Walk back until the operands of the last instruction are complete
+            let mut start = end;
+            let mut needed = 1;
+            let complete = loop {
+                let Some(pops) = Self::operands(&instructions[start]) else {
+                    break false;
+                };
+                needed = needed - 1 + pops;
+                if needed == 0 {
+                    break true;
+                }
+                if start == 0 {
+                    break false;
+                }
+                start -= 1;
+            };
+            if !complete {
+                continue;
+            }
+
+            let range = start..end + 1;
+            let condition = &instructions[range.clone()];
+            if condition
+                .iter()
+                .any(|instruction| matches!(instruction, Instruction::Compare { .. }))
+                && condition
+                    .iter()
+                    .any(|instruction| matches!(instruction, Instruction::LoadField { .. }))
+                && !(start + 1..=end).any(|pc| targets.contains(&pc))
+            {
+                conditions.push(range);
+            }
+        }
+        conditions
+    }
+
+    /// Values a pure instruction pops, each pushing one; `None` for others
+    fn operands(instruction: &Instruction) -> Option<usize> {
+        match instruction {
+            Instruction::LoadField { path } => path
+                .first()
+                .filter(|namespace| STABLE_NAMESPACES.contains(&namespace.as_str()))
+                .map(|_| 0),
+            Instruction::LoadConst { .. } => Some(0),
+            Instruction::UnaryOp { .. } => Some(1),
+            Instruction::Compare { .. } | Instruction::BinaryOp { .. } => Some(2),
+            _ => None,
+        }
+    }
+
+    /// Offset of an instruction that may jump
+    fn jump_offset(instruction: &Instruction) -> Option<isize> {
+        match instruction {
+            Instruction::Jump { offset }
+            | Instruction::JumpIfTrue { offset }
+            | Instruction::JumpIfFalse { offset }
+            | Instruction::LoadSlot { offset, .. } => Some(*offset),
+            _ => None,
+        }
+    }
+
+    /// Wrap each range of `slots` in a `LoadSlot` / `StoreSlot` pair,
+    /// preserving jump targets
+    fn hoist(program: &Program, slots: &[(Range<usize>, String)]) -> Program {
+        let len = program.instructions.len();
+
+        // New position of each old position (and of the end of the program);
+        // a jump to the start of a condition lands on its `LoadSlot`
+        let mut new_positions = Vec::with_capacity(len + 1);
+        let mut inserted = 0;
+        for pc in 0..=len {
+            if slots.iter().any(|(range, _)| range.end == pc) {
+                inserted += 1;
+            }
+            new_positions.push(pc + inserted);
+            if slots.iter().any(|(range, _)| range.start == pc) {
+                inserted += 1;
+            }
+        }
+
+        let mut instructions = Vec::with_capacity(len + 2 * slots.len());
+        for (pc, instruction) in program.instructions.iter().enumerate() {
+            if let Some((range, slot)) = slots.iter().find(|(range, _)| range.start == pc) {
+                instructions.push(Instruction::LoadSlot {
+                    slot: slot.clone(),
+                    offset: range.len() as isize + 2,
+                });
+            }
+
+            let mut instruction = instruction.clone();
+            if let Instruction::Jump { offset }
+            | Instruction::JumpIfTrue { offset }
+            | Instruction::JumpIfFalse { offset }
+            | Instruction::LoadSlot { offset, .. } = &mut instruction
+            {
+                let target = (pc as isize + *offset).clamp(0, len as isize) as usize;
+                *offset = new_positions[target] as isize - new_positions[pc] as isize;
+            }
+            instructions.push(instruction);
+
+            if let Some((_, slot)) = slots.iter().find(|(range, _)| range.end == pc + 1) {
+                instructions.push(Instruction::StoreSlot { slot: slot.clone() });
+            }
+        }
+
+        let mut optimized = program.clone();
+        optimized.instructions = instructions;
+        optimized.metadata.remap_source_map(&new_positions);
+        optimized
+    }
+}
+
+impl Default for CommonSubexpressionEliminator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use corint_core::ast::Operator;
+    use corint_core::ir::ProgramMetadata;
+    use corint_core::Value;
+
+    fn field(path: &[&str]) -> Instruction {
+        Instruction::LoadField {
+            path: path.iter().map(|s| s.to_string()).collect(),
+        }
+    }
+
+    fn number(value: f64) -> Instruction {
+        Instruction::LoadConst {
+            value: Value::Number(value),
+        }
+    }
+
+    fn rule(id: &str, condition: Vec<Instruction>) -> Program {
+        let mut instructions = condition;
+        instructions.extend([
+            Instruction::JumpIfFalse { offset: 3 },
+            Instruction::AddScore { value: 10 },
+            Instruction::MarkRuleTriggered {
+                rule_id: id.to_string(),
+            },
+            Instruction::Return,
+        ]);
+        Program::new(instructions, ProgramMetadata::for_rule(id.to_string()))
+    }
+
+    fn large_amount() -> Vec<Instruction> {
+        vec![
+            field(&["event", "amount"]),
+            number(1000.0),
+            Instruction::Compare { op: Operator::Gt },
+        ]
+    }
+
+    #[test]
+    fn test_hoists_condition_shared_by_rules() {
+        let eliminator = CommonSubexpressionEliminator::new();
+        let mut new_device = large_amount();
+        new_device.extend([
+            field(&["event", "device_age_days"]),
+            number(1.0),
+            Instruction::Compare { op: Operator::Lt },
+            Instruction::BinaryOp { op: Operator::And },
+        ]);
+        let programs = vec![
+            rule("large_amount", large_amount()),
+            rule("new_device", new_device),
+            rule(
+                "foreign_ip",
+                vec![
+                    field(&["event", "ip_country"]),
+                    Instruction::LoadConst {
+                        value: Value::String("US".to_string()),
+                    },
+                    Instruction::Compare { op: Operator::Ne },
+                ],
+            ),
+        ];
+
+        let optimized = eliminator.eliminate("fraud", &programs);
+
+        let slot = "fraud#0".to_string();
+        assert_eq!(
+            optimized[0].instructions[..5],
+            [
+                Instruction::LoadSlot {
+                    slot: slot.clone(),
+                    offset: 5
+                },
+                field(&["event", "amount"]),
+                number(1000.0),
+                Instruction::Compare { op: Operator::Gt },
+                Instruction::StoreSlot { slot: slot.clone() },
+            ]
+        );
+        assert_eq!(
+            optimized[1].instructions[0],
+            Instruction::LoadSlot { slot, offset: 5 }
+        );
+        assert_eq!(
+            optimized[1].instructions.len(),
+            programs[1].instructions.len() + 2
+        );
+
+        // The jump past the rule's score still lands on its return
+        let jump = optimized[0]
+            .instructions
+            .iter()
+            .position(|i| matches!(i, Instruction::JumpIfFalse { .. }))
+            .unwrap();
+        assert_eq!(
+            optimized[0].instructions[jump],
+            Instruction::JumpIfFalse { offset: 3 }
+        );
+
+        // A condition occurring once is left as it was
+        assert_eq!(optimized[2], programs[2]);
+    }
+
+    #[test]
+    fn test_skips_conditions_over_changing_fields() {
+        let eliminator = CommonSubexpressionEliminator::new();
+        let over_limit = || {
+            vec![
+                field(&["vars", "attempts"]),
+                number(3.0),
+                Instruction::Compare { op: Operator::Gt },
+            ]
+        };
+        let programs = vec![rule("first", over_limit()), rule("second", over_limit())];
+
+        assert_eq!(eliminator.eliminate("login", &programs), programs);
+    }
+}
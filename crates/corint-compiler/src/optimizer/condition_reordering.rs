@@ -0,0 +1,213 @@
+//! Cost-based condition reordering optimizer
+//!
+//! Reorders the conditions of each `all` / `any` group of a rule so cheap
+//! comparisons of event fields come before predicates that compute
+//! features, look lists up or call external sources. Groups are conjunctions
+//! or disjunctions, so their conditions can be evaluated in any order; as a
+//! group stops at the first condition that decides it, the expensive ones
+//! are not evaluated at all when a cheap one does.
+//!
+//! The cost of a condition is the sum of the static costs of the
+//! instructions it compiles to (see [`instruction_cost`]). Conditions of
+//! equal cost keep their declared order.
+//!
+//! Rule order is not changed: conclusions and early stops of a ruleset
+//! depend on it.
+
+use crate::codegen::ExpressionCompiler;
+use corint_core::ast::rule::{Condition, ConditionGroup};
+use corint_core::ast::{Expression, Rule};
+use corint_core::ir::Instruction;
+
+/// Static cost of an instruction, in units of a field load
+///
+/// Features may be computed from storage on first access, lists may be
+/// backed by a database, and service and external calls leave the process.
+pub fn instruction_cost(instruction: &Instruction) -> u32 {
+    match instruction {
+        Instruction::LoadConst { .. } => 0,
+        Instruction::LoadField { path } if path.first().map(String::as_str) == Some("features") => {
+            20
+        }
+        Instruction::ListLookup { .. } => 30,
+        Instruction::IterateArray { predicate, .. } => {
+            5 + 4 * predicate.iter().map(instruction_cost).sum::<u32>()
+        }
+        Instruction::AggregateArray { .. } => 5,
+        Instruction::CallFeature { .. } => 50,
+        Instruction::CallService { .. } | Instruction::CallExternal { .. } => 100,
+        _ => 1,
+    }
+}
+
+/// Cost-based condition reorderer
+pub struct ConditionReorderer;
+
+impl ConditionReorderer {
+    /// Create a new condition reorderer
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Reorder the conditions of a rule, cheapest first
+    pub fn reorder(&self, rule: &Rule) -> Rule {
+        let mut rule = rule.clone();
+        if let Some(group) = &mut rule.when.condition_group {
+            Self::reorder_group(group);
+        }
+        if let Some(conditions) = &mut rule.when.conditions {
+            Self::reorder_expressions(conditions);
+        }
+        rule
+    }
+
+    /// Static cost of evaluating an expression
+    pub fn expression_cost(expression: &Expression) -> u32 {
+        match ExpressionCompiler::compile(expression) {
+            Ok(instructions) => instructions.iter().map(instruction_cost).sum(),
+            // Left for compilation to report; placed last meanwhile
+            Err(_) => u32::MAX,
+        }
+    }
+
+    fn reorder_group(group: &mut ConditionGroup) {
+        let (ConditionGroup::All(conditions)
+        | ConditionGroup::Any(conditions)
+        | ConditionGroup::Not(conditions)) = group;
+        for condition in conditions.iter_mut() {
+            match condition {
+                Condition::Expression(expression) => Self::reorder_expression(expression),
+                Condition::Group(group) => Self::reorder_group(group),
+            }
+        }
+        conditions.sort_by_cached_key(Self::condition_cost);
+    }
+
+    fn reorder_expressions(expressions: &mut [Expression]) {
+        for expression in expressions.iter_mut() {
+            Self::reorder_expression(expression);
+        }
+        expressions.sort_by_cached_key(Self::expression_cost);
+    }
+
+    fn reorder_expression(expression: &mut Expression) {
+        match expression {
+            Expression::LogicalGroup { conditions, .. } => Self::reorder_expressions(conditions),
+            Expression::Binary { left, right, .. } => {
+                Self::reorder_expression(left);
+                Self::reorder_expression(right);
+            }
+            Expression::Unary { operand, .. } => Self::reorder_expression(operand),
+            _ => {}
+        }
+    }
+
+    fn condition_cost(condition: &Condition) -> u32 {
+        match condition {
+            Condition::Expression(expression) => Self::expression_cost(expression),
+            Condition::Group(group) => {
+                let (ConditionGroup::All(conditions)
+                | ConditionGroup::Any(conditions)
+                | ConditionGroup::Not(conditions)) = group.as_ref();
+                conditions
+                    .iter()
+                    .map(Self::condition_cost)
+                    .fold(0, u32::saturating_add)
+            }
+        }
+    }
+}
+
+impl Default for ConditionReorderer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use corint_core::ast::{LogicalGroupOp, Operator, WhenBlock};
+    use corint_core::Value;
+
+    fn compare(path: &[&str], value: f64) -> Expression {
+        Expression::binary(
+            Expression::field_access(path.iter().map(|s| s.to_string()).collect()),
+            Operator::Gt,
+            Expression::literal(Value::Number(value)),
+        )
+    }
+
+    fn blocked_ip() -> Expression {
+        Expression::binary(
+            Expression::field_access(vec!["event".to_string(), "ip".to_string()]),
+            Operator::InList,
+            Expression::ListReference {
+                list_id: "blocked_ips".to_string(),
+            },
+        )
+    }
+
+    #[test]
+    fn test_reorders_group_cheapest_first() {
+        let velocity = compare(&["features", "txn_count_1h"], 10.0);
+        let amount = compare(&["event", "amount"], 1000.0);
+        let mut when = WhenBlock::new();
+        when.condition_group = Some(ConditionGroup::All(vec![
+            Condition::Expression(blocked_ip()),
+            Condition::Group(Box::new(ConditionGroup::Any(vec![
+                Condition::Expression(velocity.clone()),
+                Condition::Expression(amount.clone()),
+            ]))),
+            Condition::Expression(amount.clone()),
+        ]));
+        let rule = Rule::new("risky".to_string(), "Risky".to_string(), when, 50);
+
+        let reordered = ConditionReorderer::new().reorder(&rule);
+
+        assert_eq!(
+            reordered.when.condition_group,
+            Some(ConditionGroup::All(vec![
+                Condition::Expression(amount.clone()),
+                Condition::Group(Box::new(ConditionGroup::Any(vec![
+                    Condition::Expression(amount),
+                    Condition::Expression(velocity),
+                ]))),
+                Condition::Expression(blocked_ip()),
+            ]))
+        );
+    }
+
+    #[test]
+    fn test_reorders_legacy_conditions_and_logical_groups() {
+        let amount = compare(&["event", "amount"], 1000.0);
+        let velocity = compare(&["features", "txn_count_1h"], 10.0);
+        let same_cost = compare(&["event", "items"], 5.0);
+        let rule = Rule::new(
+            "legacy".to_string(),
+            "Legacy".to_string(),
+            WhenBlock::new()
+                .add_condition(Expression::LogicalGroup {
+                    op: LogicalGroupOp::Any,
+                    conditions: vec![velocity.clone(), amount.clone()],
+                })
+                .add_condition(amount.clone())
+                .add_condition(same_cost.clone()),
+            10,
+        );
+
+        let reordered = ConditionReorderer::new().reorder(&rule);
+
+        assert_eq!(
+            reordered.when.conditions,
+            Some(vec![
+                amount.clone(),
+                same_cost,
+                Expression::LogicalGroup {
+                    op: LogicalGroupOp::Any,
+                    conditions: vec![amount, velocity],
+                },
+            ])
+        );
+    }
+}
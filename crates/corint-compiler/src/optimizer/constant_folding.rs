@@ -111,6 +111,31 @@ impl ConstantFolder {
                 // ResultAccess cannot be folded (runtime value), return as-is
                 expr.clone()
             }
+
+            Expression::Quantifier {
+                op,
+                collection,
+                binding,
+                predicate,
+            } => {
+                // Fold the collection and the predicate independently
+                Expression::Quantifier {
+                    op: *op,
+                    collection: Box::new(self.fold(collection)),
+                    binding: binding.clone(),
+                    predicate: Box::new(self.fold(predicate)),
+                }
+            }
+
+            Expression::Aggregate {
+                op,
+                collection,
+                path,
+            } => Expression::Aggregate {
+                op: *op,
+                collection: Box::new(self.fold(collection)),
+                path: path.clone(),
+            },
         }
     }
 
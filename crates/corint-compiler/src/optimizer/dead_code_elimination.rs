@@ -19,18 +19,53 @@ impl DeadCodeEliminator {
         // Build a map of potentially reachable instructions
         let reachable = self.compute_reachable_instructions(program);
 
+        let keep: Vec<bool> = (0..program.instructions.len())
+            .map(|i| reachable.contains(&i))
+            .collect();
+        Self::retain(program, &keep)
+    }
+
+    /// Keep the instructions marked in `keep`, preserving jump targets
+    ///
+    /// A jump to a removed instruction lands on the next kept one.
+    fn retain(program: &Program, keep: &[bool]) -> Program {
+        // New position of each old position (and of the end of the program)
+        let mut new_positions = Vec::with_capacity(keep.len() + 1);
+        let mut kept = 0isize;
+        for &k in keep {
+            new_positions.push(kept);
+            if k {
+                kept += 1;
+            }
+        }
+        new_positions.push(kept);
+
         let mut optimized_instructions = Vec::new();
-        for (i, instruction) in program.instructions.iter().enumerate() {
-            if reachable.contains(&i) {
-                optimized_instructions.push(instruction.clone());
+        for (pc, instruction) in program.instructions.iter().enumerate() {
+            if !keep[pc] {
+                continue;
+            }
+            let mut instruction = instruction.clone();
+            if let Instruction::Jump { offset }
+            | Instruction::JumpIfTrue { offset }
+            | Instruction::JumpIfFalse { offset }
+            | Instruction::LoadSlot { offset, .. } = &mut instruction
+            {
+                let target = (pc as isize + *offset).clamp(0, keep.len() as isize) as usize;
+                *offset = new_positions[target] - new_positions[pc];
             }
+            optimized_instructions.push(instruction);
         }
 
+        let mut metadata = program.metadata.clone();
+        let positions: Vec<usize> = new_positions.iter().map(|&p| p as usize).collect();
+        metadata.remap_source_map(&positions);
+
         // Preserve decision_instructions when optimizing
         if let Some(ref decision_instructions) = program.decision_instructions {
-            Program::new_with_decision(optimized_instructions, program.metadata.clone(), decision_instructions.clone())
+            Program::new_with_decision(optimized_instructions, metadata, decision_instructions.clone())
         } else {
-            Program::new(optimized_instructions, program.metadata.clone())
+            Program::new(optimized_instructions, metadata)
         }
     }
 
@@ -70,7 +105,9 @@ impl DeadCodeEliminator {
                         worklist.push_back(target);
                     }
                 }
-                Instruction::JumpIfTrue { offset } | Instruction::JumpIfFalse { offset } => {
+                Instruction::JumpIfTrue { offset }
+                | Instruction::JumpIfFalse { offset }
+                | Instruction::LoadSlot { offset, .. } => {
                     // Conditional jump - two successors:
                     // 1. Next instruction (fall-through)
                     // 2. Jump target
@@ -92,13 +129,27 @@ impl DeadCodeEliminator {
 
     /// Eliminate duplicate consecutive instructions
     pub fn eliminate_duplicates(&self, program: &Program) -> Program {
-        let mut optimized_instructions = Vec::new();
+        // An instruction jumped to does not always follow its predecessor
+        let jump_targets: std::collections::HashSet<isize> = program
+            .instructions
+            .iter()
+            .enumerate()
+            .filter_map(|(pc, instruction)| match instruction {
+                Instruction::Jump { offset }
+                | Instruction::JumpIfTrue { offset }
+                | Instruction::JumpIfFalse { offset }
+                | Instruction::LoadSlot { offset, .. } => Some(pc as isize + offset),
+                _ => None,
+            })
+            .collect();
+
+        let mut keep = Vec::with_capacity(program.instructions.len());
         let mut last_instruction: Option<&Instruction> = None;
 
-        for instruction in &program.instructions {
+        for (pc, instruction) in program.instructions.iter().enumerate() {
             // Skip if it's the same as the last instruction
             // (only for certain instruction types that are safe to deduplicate)
-            let should_skip = match (last_instruction, instruction) {
+            let is_duplicate = match (last_instruction, instruction) {
                 // Don't duplicate SetScore with same value
                 (
                     Some(Instruction::SetScore { value: v1 }),
@@ -113,36 +164,25 @@ impl DeadCodeEliminator {
 
                 _ => false,
             };
+            let should_skip = is_duplicate && !jump_targets.contains(&(pc as isize));
 
+            keep.push(!should_skip);
             if !should_skip {
-                optimized_instructions.push(instruction.clone());
                 last_instruction = Some(instruction);
             }
         }
 
-        // Preserve decision_instructions when optimizing
-        if let Some(ref decision_instructions) = program.decision_instructions {
-            Program::new_with_decision(optimized_instructions, program.metadata.clone(), decision_instructions.clone())
-        } else {
-            Program::new(optimized_instructions, program.metadata.clone())
-        }
+        Self::retain(program, &keep)
     }
 
     /// Remove no-op instructions
     pub fn eliminate_nops(&self, program: &Program) -> Program {
-        let optimized_instructions: Vec<Instruction> = program
+        let keep: Vec<bool> = program
             .instructions
             .iter()
-            .filter(|instr| !self.is_nop(instr))
-            .cloned()
+            .map(|instr| !self.is_nop(instr))
             .collect();
-
-        // Preserve decision_instructions when optimizing
-        if let Some(ref decision_instructions) = program.decision_instructions {
-            Program::new_with_decision(optimized_instructions, program.metadata.clone(), decision_instructions.clone())
-        } else {
-            Program::new(optimized_instructions, program.metadata.clone())
-        }
+        Self::retain(program, &keep)
     }
 
     /// Check if an instruction is a no-op
@@ -338,6 +378,38 @@ mod tests {
         assert!(matches!(optimized.instructions[1], Instruction::Return));
     }
 
+    #[test]
+    fn test_jumps_across_removed_instructions() {
+        let eliminator = DeadCodeEliminator::new();
+
+        let instructions = vec![
+            Instruction::LoadConst {
+                value: corint_core::Value::Bool(false),
+            },
+            Instruction::JumpIfFalse { offset: 5 },
+            Instruction::AddScore { value: 10 },
+            Instruction::Jump { offset: 1 }, // No-op
+            Instruction::AddScore { value: 20 },
+            Instruction::SetScore { value: 5 },
+            Instruction::SetScore { value: 5 }, // Jump target, kept
+            Instruction::Return,
+        ];
+
+        let program = Program::new(instructions, ProgramMetadata::for_rule("test".to_string()));
+
+        let optimized = eliminator.optimize(&program);
+
+        assert_eq!(optimized.instructions.len(), 7);
+        assert!(matches!(
+            optimized.instructions[1],
+            Instruction::JumpIfFalse { offset: 4 }
+        ));
+        assert!(matches!(
+            optimized.instructions[5],
+            Instruction::SetScore { value: 5 }
+        ));
+    }
+
     #[test]
     fn test_optimize_combined() {
         let eliminator = DeadCodeEliminator::new();
@@ -2,9 +2,13 @@
 //!
 //! This module provides optimizations for CORINT programs.
 
+pub mod common_subexpression;
+pub mod condition_reordering;
 pub mod constant_folding;
 pub mod dead_code_elimination;
 
 // Re-export for convenience
+pub use common_subexpression::CommonSubexpressionEliminator;
+pub use condition_reordering::ConditionReorderer;
 pub use constant_folding::ConstantFolder;
 pub use dead_code_elimination::DeadCodeEliminator;
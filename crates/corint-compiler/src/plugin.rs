@@ -0,0 +1,92 @@
+//! Compiler plugins for custom step types
+//!
+//! Organizations extend pipelines with step types of their own (`type:
+//! geo_velocity`) without forking the compiler. The parser keeps the fields of
+//! steps of unknown types in `StepDetails::Custom`; a [`StepPlugin`] registered
+//! for the type validates them and compiles the step, usually into a
+//! `CallCustom` instruction run by the handler registered under the same name
+//! on the runtime's `PipelineExecutor`.
+
+use crate::error::{CompileError, Result};
+use corint_core::ast::pipeline::{PipelineStep, StepDetails};
+use corint_core::ir::Instruction;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Compiler plugin for a custom step type
+pub trait StepPlugin: Send + Sync {
+    /// Value of the `type` field of the steps this plugin compiles
+    fn step_type(&self) -> &str;
+
+    /// Check the fields of a step before it is compiled
+    fn validate(&self, _step: &PipelineStep) -> Result<()> {
+        Ok(())
+    }
+
+    /// Instructions of a step
+    ///
+    /// They run after the step is marked executed and before the jump to its
+    /// next step; jumps among them must stay within them.
+    fn compile(&self, step: &PipelineStep) -> Result<Vec<Instruction>>;
+}
+
+/// Step plugins, by step type
+#[derive(Clone, Default)]
+pub struct StepPlugins {
+    plugins: HashMap<String, Arc<dyn StepPlugin>>,
+}
+
+impl StepPlugins {
+    /// Create an empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a plugin, replacing any registered for the same step type
+    pub fn register(&mut self, plugin: Arc<dyn StepPlugin>) {
+        self.plugins.insert(plugin.step_type().to_string(), plugin);
+    }
+
+    /// Plugin registered for `step_type`
+    pub fn get(&self, step_type: &str) -> Option<&Arc<dyn StepPlugin>> {
+        self.plugins.get(step_type)
+    }
+
+    /// Registered step types, sorted
+    pub fn step_types(&self) -> Vec<&str> {
+        let mut types: Vec<&str> = self.plugins.keys().map(String::as_str).collect();
+        types.sort_unstable();
+        types
+    }
+
+    /// Whether no plugin is registered
+    pub fn is_empty(&self) -> bool {
+        self.plugins.is_empty()
+    }
+
+    /// Instructions of a step of a custom type
+    ///
+    /// Returns `None` for steps no plugin is registered for. Those with fields
+    /// fail to compile, since their fields would be silently ignored.
+    pub(crate) fn compile_step(&self, step: &PipelineStep) -> Result<Option<Vec<Instruction>>> {
+        match self.get(&step.step_type) {
+            Some(plugin) => {
+                plugin.validate(step)?;
+                plugin.compile(step).map(Some)
+            }
+            None if matches!(step.details, StepDetails::Custom { .. }) => {
+                Err(CompileError::UnsupportedFeature(format!(
+                    "Step '{}' has type '{}', which no compiler plugin is registered for",
+                    step.id, step.step_type
+                )))
+            }
+            None => Ok(None),
+        }
+    }
+}
+
+impl std::fmt::Debug for StepPlugins {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(self.step_types()).finish()
+    }
+}
@@ -3,6 +3,9 @@
 //! Performs semantic analysis on AST nodes to detect errors before compilation.
 
 use crate::error::{CompileError, Result};
+use crate::semantic::references::{self, ConfigDefinitions, SourceFile};
+use crate::semantic::satisfiability;
+use crate::validator::Diagnostic;
 use corint_core::ast::{Expression, Pipeline, Rule, Ruleset};
 use std::collections::HashSet;
 
@@ -98,6 +101,31 @@ impl SemanticAnalyzer {
         // Legacy analysis code removed - will be rewritten in Task 5 to support new PipelineStep format
     }
 
+    /// Validate the references between the rule, ruleset and pipeline files
+    /// of a repository, and to its features, lists and APIs
+    ///
+    /// Unlike the per-document checks, every undefined reference is reported,
+    /// each with the file and line that makes it.
+    pub fn analyze_references(
+        &mut self,
+        files: &[SourceFile],
+        configs: &ConfigDefinitions,
+    ) -> Vec<Diagnostic> {
+        references::analyze(files, configs)
+    }
+
+    /// Find rule conditions that are always false (the rule can never
+    /// trigger) or always true
+    pub fn check_rule_conditions(&mut self, rule: &Rule) -> Vec<Diagnostic> {
+        satisfiability::analyze_rule(rule)
+    }
+
+    /// Find conclusion branches of a ruleset that can never fire, because
+    /// their condition is always false or earlier branches always fire first
+    pub fn check_conclusion(&mut self, ruleset: &Ruleset) -> Vec<Diagnostic> {
+        satisfiability::analyze_conclusion(ruleset)
+    }
+
     /// Analyze a single pipeline step
     #[allow(dead_code)]
     fn analyze_step(
@@ -256,6 +284,24 @@ impl SemanticAnalyzer {
                 };
                 references.insert(ref_str);
             }
+            Expression::Quantifier {
+                collection,
+                binding,
+                predicate,
+                ..
+            } => {
+                self.collect_variable_references(collection, references);
+
+                // References to the bound element are local to the predicate
+                let mut inner = HashSet::new();
+                self.collect_variable_references(predicate, &mut inner);
+                references.extend(inner.into_iter().filter(|r| {
+                    r != binding && !r.starts_with(&format!("{}.", binding))
+                }));
+            }
+            Expression::Aggregate { collection, .. } => {
+                self.collect_variable_references(collection, references);
+            }
         }
     }
 
@@ -346,6 +392,26 @@ impl SemanticAnalyzer {
                 }
                 Ok(())
             }
+
+            Expression::Quantifier {
+                op,
+                collection,
+                binding,
+                predicate,
+            } => {
+                // Element name must be a usable identifier
+                if binding.is_empty() {
+                    return Err(CompileError::InvalidExpression(format!(
+                        "{} element name cannot be empty",
+                        op.name()
+                    )));
+                }
+                self.analyze_expression(collection)?;
+                self.analyze_expression(predicate)?;
+                Ok(())
+            }
+
+            Expression::Aggregate { collection, .. } => self.analyze_expression(collection),
         }
     }
 
@@ -4,9 +4,12 @@
 
 pub mod analyzer;
 pub mod pipeline_analyzer;
+pub mod references;
+pub mod satisfiability;
 pub mod type_checker;
 
 // Re-export for convenience
 pub use analyzer::SemanticAnalyzer;
 pub use pipeline_analyzer::analyze_new_pipeline;
+pub use references::{ConfigDefinitions, SourceFile};
 pub use type_checker::{TypeChecker, TypeInfo};
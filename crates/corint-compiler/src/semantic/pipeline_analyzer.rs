@@ -169,9 +169,14 @@ fn validate_step(
         let StepNext::StepId(next_id) = next;
         validate_step_reference(next_id, &step.id, step_map, result);
     }
+
+    // E006: Validate else.skip_to reference
+    if let Some(else_branch) = &step.else_branch {
+        validate_step_reference(&else_branch.skip_to, &step.id, step_map, result);
+    }
 }
 
-/// Validate a step reference (next, default, route.next)
+/// Validate a step reference (next, default, route.next, else.skip_to)
 fn validate_step_reference(
     target: &str,
     source_step_id: &str,
@@ -238,6 +243,10 @@ fn check_circular_dependencies(
                 next_steps.push(next_id.as_str());
             }
 
+            if let Some(else_branch) = &step.else_branch {
+                next_steps.push(else_branch.skip_to.as_str());
+            }
+
             // Visit each next step
             for next_step_id in next_steps {
                 if next_step_id == "end" {
@@ -310,6 +319,12 @@ fn find_reachable_steps(
                     queue.push_back(next_id.clone());
                 }
             }
+
+            if let Some(else_branch) = &step.else_branch {
+                if !reachable.contains(&else_branch.skip_to) {
+                    queue.push_back(else_branch.skip_to.clone());
+                }
+            }
         }
     }
 
@@ -330,8 +345,8 @@ fn check_dead_ends(
 
         // If no exit path, it's a dead end (unless it's explicitly allowed by type)
         if !has_next && !has_routes && !has_default {
-            // Some step types are allowed to be terminal (e.g., trigger)
-            if step.step_type != "trigger" {
+            // Some step types are allowed to be terminal (e.g., trigger, emit)
+            if step.step_type != "trigger" && step.step_type != "emit" {
                 result.add_warning(
                     "W002",
                     format!(
@@ -357,6 +372,9 @@ mod tests {
             description: None,
             entry: entry.to_string(),
             when: None,
+            timeout_ms: None,
+            on_error: None,
+            schema: None,
             steps,
             decision: None,
             metadata: None,
@@ -372,6 +390,8 @@ mod tests {
             default: None,
             next: None,
             when: None,
+            else_branch: None,
+            timeout_ms: None,
             details: StepDetails::Unknown {},
         }
     }
@@ -384,6 +404,9 @@ mod tests {
             description: None,
             entry: String::new(), // Empty entry
             when: None,
+            timeout_ms: None,
+            on_error: None,
+            schema: None,
             steps: vec![],
             decision: None,
             metadata: None,
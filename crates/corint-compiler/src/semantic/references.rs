@@ -0,0 +1,629 @@
+//! Cross-document reference validation
+//!
+//! Rules, rulesets and pipelines reference each other by ID, and reference
+//! features (`features.<name>`), lists (`list.<id>`) and external APIs
+//! (`api.<name>.<endpoint>`) defined in the repository configs. Each document
+//! is valid on its own, so a dangling reference used to surface only when the
+//! repository was compiled as a whole, or at runtime. These checks run over
+//! all the documents of a repository at once and report every dangling
+//! reference, located at the line of the file that makes it.
+
+use crate::validator::Diagnostic;
+use corint_core::ast::pipeline::{ApiTarget, StepDetails};
+use corint_core::ast::{
+    Condition, ConditionGroup, DecisionTable, Expression, Pipeline, Rule, Ruleset, WhenBlock,
+};
+use corint_parser::{DecisionTableParser, PipelineParser, RuleParser, RulesetParser};
+use std::collections::{HashMap, HashSet};
+
+/// Code of undefined reference diagnostics
+pub const UNDEFINED_REFERENCE: &str = "E004";
+
+/// A YAML file holding rules, rulesets or pipelines
+#[derive(Debug, Clone)]
+pub struct SourceFile {
+    /// Path reported in diagnostics
+    pub path: String,
+
+    /// File content, possibly several `---` separated documents
+    pub content: String,
+}
+
+impl SourceFile {
+    /// Create a source file
+    pub fn new(path: impl Into<String>, content: impl Into<String>) -> Self {
+        Self {
+            path: path.into(),
+            content: content.into(),
+        }
+    }
+}
+
+/// Features, lists and APIs defined by the repository configs
+#[derive(Debug, Clone, Default)]
+pub struct ConfigDefinitions {
+    /// Feature names
+    pub features: HashSet<String>,
+
+    /// List IDs
+    pub lists: HashSet<String>,
+
+    /// Endpoint names by API name
+    pub apis: HashMap<String, HashSet<String>>,
+}
+
+impl ConfigDefinitions {
+    /// Create empty definitions
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a feature
+    pub fn with_feature(mut self, name: impl Into<String>) -> Self {
+        self.features.insert(name.into());
+        self
+    }
+
+    /// Add a list
+    pub fn with_list(mut self, id: impl Into<String>) -> Self {
+        self.lists.insert(id.into());
+        self
+    }
+
+    /// Add an API with its endpoints
+    pub fn with_api<I, S>(mut self, name: impl Into<String>, endpoints: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.apis
+            .insert(name.into(), endpoints.into_iter().map(Into::into).collect());
+        self
+    }
+}
+
+/// A definition parsed from one document of a source file
+enum Definition {
+    Rule(Rule),
+    DecisionTable(DecisionTable),
+    Ruleset(Ruleset),
+    Pipeline(Pipeline),
+}
+
+/// A document of a source file, with the number of lines before it
+struct Document<'a> {
+    file: &'a str,
+    offset: usize,
+    text: String,
+    definition: Definition,
+}
+
+/// Validate the references between `files` and to `configs`
+pub(crate) fn analyze(files: &[SourceFile], configs: &ConfigDefinitions) -> Vec<Diagnostic> {
+    let documents: Vec<Document> = files.iter().flat_map(documents).collect();
+
+    let mut rules = HashSet::new();
+    let mut rulesets = HashSet::new();
+    let mut pipelines = HashSet::new();
+    // Results of API steps stored under a custom name are not API configs
+    let mut api_outputs = Vec::new();
+    for document in &documents {
+        match &document.definition {
+            Definition::Rule(rule) => {
+                rules.insert(rule.id.as_str());
+            }
+            // Rulesets list decision tables like rules
+            Definition::DecisionTable(table) => {
+                rules.insert(table.id.as_str());
+            }
+            Definition::Ruleset(ruleset) => {
+                rulesets.insert(ruleset.id.as_str());
+            }
+            Definition::Pipeline(pipeline) => {
+                pipelines.insert(pipeline.id.as_str());
+                for step in &pipeline.steps {
+                    if let StepDetails::Api {
+                        output: Some(output),
+                        ..
+                    } = &step.details
+                    {
+                        api_outputs.push(output.as_str());
+                    }
+                }
+            }
+        }
+    }
+
+    let mut diagnostics = Vec::new();
+    for document in &documents {
+        let mut check = |kind: &str, id: &str, defined: bool, needle: &str| {
+            if !defined {
+                diagnostics.push(undefined(document, kind, id, needle));
+            }
+        };
+
+        let mut expressions = Vec::new();
+        match &document.definition {
+            Definition::Rule(rule) => when_expressions(&rule.when, &mut expressions),
+            Definition::DecisionTable(table) => table_expressions(table, &mut expressions),
+            Definition::Ruleset(ruleset) => {
+                if let Some(parent) = &ruleset.extends {
+                    check(
+                        "ruleset",
+                        parent,
+                        rulesets.contains(parent.as_str()),
+                        parent,
+                    );
+                }
+                for rule in &ruleset.rules {
+                    check("rule", rule, rules.contains(rule.as_str()), rule);
+                }
+                expressions.extend(ruleset.conclusion.iter().flat_map(|c| &c.condition));
+            }
+            Definition::Pipeline(pipeline) => {
+                expressions.extend(pipeline_expressions(pipeline));
+                for step in &pipeline.steps {
+                    match &step.details {
+                        StepDetails::Rule { rule } => {
+                            check("rule", rule, rules.contains(rule.as_str()), rule)
+                        }
+                        StepDetails::Ruleset { ruleset } => check(
+                            "ruleset",
+                            ruleset,
+                            rulesets.contains(ruleset.as_str()),
+                            ruleset,
+                        ),
+                        StepDetails::SubPipeline { pipeline_id } => check(
+                            "pipeline",
+                            pipeline_id,
+                            pipelines.contains(pipeline_id.as_str()),
+                            pipeline_id,
+                        ),
+                        StepDetails::Api {
+                            api_target,
+                            endpoint,
+                            ..
+                        } => {
+                            let apis = match api_target {
+                                ApiTarget::Single { api } => std::slice::from_ref(api),
+                                ApiTarget::Any { any } => any.as_slice(),
+                                ApiTarget::All { all } => all.as_slice(),
+                            };
+                            for api in apis {
+                                match configs.apis.get(api) {
+                                    None => check("API", api, false, api),
+                                    Some(endpoints) => {
+                                        if let Some(endpoint) = endpoint {
+                                            check(
+                                                "API endpoint",
+                                                &format!("{}.{}", api, endpoint),
+                                                endpoints.contains(endpoint),
+                                                endpoint,
+                                            );
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+            }
+        }
+
+        let mut references = Vec::new();
+        for expr in expressions {
+            field_references(expr, &mut references);
+        }
+        for reference in references {
+            match reference {
+                Reference::Feature(name) => check(
+                    "feature",
+                    name,
+                    configs.features.contains(name),
+                    &format!("features.{}", name),
+                ),
+                Reference::List(id) => check(
+                    "list",
+                    id,
+                    configs.lists.contains(id),
+                    &format!("list.{}", id),
+                ),
+                Reference::Api(path) => {
+                    let joined = path.join(".");
+                    if api_outputs.iter().any(|output| {
+                        joined == *output || joined.starts_with(&format!("{}.", output))
+                    }) {
+                        continue;
+                    }
+                    let needle = format!("api.{}", joined);
+                    match configs.apis.get(&path[0]) {
+                        None => check("API", &path[0], false, &needle),
+                        Some(endpoints) => {
+                            if let Some(endpoint) = path.get(1) {
+                                check(
+                                    "API endpoint",
+                                    &format!("{}.{}", path[0], endpoint),
+                                    endpoints.contains(endpoint),
+                                    &needle,
+                                );
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+    diagnostics
+}
+
+/// Expressions of a decision table: its inputs, and what each cell tests them against
+fn table_expressions<'a>(table: &'a DecisionTable, expressions: &mut Vec<&'a Expression>) {
+    expressions.extend(&table.inputs);
+    for row in &table.rows {
+        for (cell, input) in row.cells.iter().zip(&table.inputs) {
+            match cell {
+                Some(Expression::Binary { left, right, .. }) if **left == *input => {
+                    expressions.push(right)
+                }
+                Some(cell) => expressions.push(cell),
+                None => {}
+            }
+        }
+    }
+}
+
+/// Parse the rule, decision table, ruleset and pipeline documents of a file
+///
+/// Documents that don't parse are skipped: they are reported by the
+/// validation of each document.
+fn documents(file: &SourceFile) -> Vec<Document<'_>> {
+    let mut chunks = vec![(0, String::new())];
+    for (index, line) in file.content.lines().enumerate() {
+        if line.trim_end() == "---" {
+            chunks.push((index + 1, String::new()));
+        } else if let Some((_, chunk)) = chunks.last_mut() {
+            chunk.push_str(line);
+            chunk.push('\n');
+        }
+    }
+
+    chunks
+        .into_iter()
+        .filter_map(|(offset, text)| {
+            let yaml: serde_yaml::Value = serde_yaml::from_str(&text).ok()?;
+            let definition = if yaml.get("rule").is_some() {
+                Definition::Rule(RuleParser::parse_from_yaml(&yaml).ok()?)
+            } else if yaml.get("decision_table").is_some() {
+                Definition::DecisionTable(DecisionTableParser::parse_from_yaml(&yaml).ok()?)
+            } else if yaml.get("ruleset").is_some() {
+                Definition::Ruleset(RulesetParser::parse_from_yaml(&yaml).ok()?)
+            } else if yaml.get("pipeline").is_some() {
+                Definition::Pipeline(PipelineParser::parse_from_yaml(&yaml).ok()?)
+            } else {
+                return None;
+            };
+            Some(Document {
+                file: &file.path,
+                offset,
+                text,
+                definition,
+            })
+        })
+        .collect()
+}
+
+fn undefined(document: &Document, kind: &str, id: &str, needle: &str) -> Diagnostic {
+    let subject = match &document.definition {
+        Definition::Rule(rule) => format!("Rule '{}'", rule.id),
+        Definition::DecisionTable(table) => format!("Decision table '{}'", table.id),
+        Definition::Ruleset(ruleset) => format!("Ruleset '{}'", ruleset.id),
+        Definition::Pipeline(pipeline) => format!("Pipeline '{}'", pipeline.id),
+    };
+    let mut diagnostic = Diagnostic::error(
+        UNDEFINED_REFERENCE,
+        format!("{} references undefined {} '{}'", subject, kind, id),
+    )
+    .with_file(document.file);
+    if let Some((index, column, line)) = locate(&document.text, needle) {
+        diagnostic = diagnostic
+            .with_location(document.offset + index + 1, column + 1)
+            .with_context(line.trim());
+    }
+    diagnostic
+}
+
+/// First line of `text` with `needle` as a whole word: (line index, column, line)
+fn locate<'a>(text: &'a str, needle: &str) -> Option<(usize, usize, &'a str)> {
+    let is_word = |c: char| c.is_alphanumeric() || c == '_' || c == '.';
+    text.lines().enumerate().find_map(|(index, line)| {
+        line.match_indices(needle)
+            .find(|(column, _)| {
+                let before = line[..*column].chars().next_back();
+                let after = line[column + needle.len()..].chars().next();
+                !before.is_some_and(is_word) && !after.is_some_and(|c| is_word(c) && c != '.')
+            })
+            .map(|(column, _)| (index, column, line))
+    })
+}
+
+fn pipeline_expressions(pipeline: &Pipeline) -> Vec<&Expression> {
+    let mut expressions = Vec::new();
+    if let Some(when) = &pipeline.when {
+        when_expressions(when, &mut expressions);
+    }
+    for step in &pipeline.steps {
+        if let Some(when) = &step.when {
+            when_expressions(when, &mut expressions);
+        }
+        for route in step.routes.iter().flatten() {
+            when_expressions(&route.when, &mut expressions);
+        }
+        if let StepDetails::Function { params, .. }
+        | StepDetails::Service { params, .. }
+        | StepDetails::Api { params, .. } = &step.details
+        {
+            expressions.extend(params.iter().flat_map(|p| p.values()));
+        }
+    }
+    for rule in pipeline.decision.iter().flatten() {
+        if let Some(when) = &rule.when {
+            when_expressions(when, &mut expressions);
+        }
+    }
+    expressions
+}
+
+fn when_expressions<'a>(when: &'a WhenBlock, expressions: &mut Vec<&'a Expression>) {
+    if let Some(group) = &when.condition_group {
+        group_expressions(group, expressions);
+    }
+    expressions.extend(when.conditions.iter().flatten());
+}
+
+fn group_expressions<'a>(group: &'a ConditionGroup, expressions: &mut Vec<&'a Expression>) {
+    let (ConditionGroup::All(conditions)
+    | ConditionGroup::Any(conditions)
+    | ConditionGroup::Not(conditions)) = group;
+    for condition in conditions {
+        match condition {
+            Condition::Expression(expr) => expressions.push(expr),
+            Condition::Group(group) => group_expressions(group, expressions),
+        }
+    }
+}
+
+/// A reference from an expression to a config definition
+enum Reference<'a> {
+    Feature(&'a str),
+    List(&'a str),
+    /// Path after `api.`: the API name, then usually the endpoint
+    Api(&'a [String]),
+}
+
+fn field_references<'a>(expr: &'a Expression, references: &mut Vec<Reference<'a>>) {
+    match expr {
+        Expression::FieldAccess(path) if path.len() >= 2 && path[0] == "features" => {
+            references.push(Reference::Feature(&path[1]));
+        }
+        Expression::FieldAccess(path) if path.len() >= 2 && path[0] == "api" => {
+            references.push(Reference::Api(&path[1..]));
+        }
+        Expression::ListReference { list_id } => references.push(Reference::List(list_id)),
+        Expression::Binary { left, right, .. } => {
+            field_references(left, references);
+            field_references(right, references);
+        }
+        Expression::Unary { operand, .. } => field_references(operand, references),
+        Expression::FunctionCall { args, .. } => {
+            for arg in args {
+                field_references(arg, references);
+            }
+        }
+        Expression::Ternary {
+            condition,
+            true_expr,
+            false_expr,
+        } => {
+            field_references(condition, references);
+            field_references(true_expr, references);
+            field_references(false_expr, references);
+        }
+        Expression::LogicalGroup { conditions, .. } => {
+            for condition in conditions {
+                field_references(condition, references);
+            }
+        }
+        Expression::Quantifier {
+            collection,
+            predicate,
+            ..
+        } => {
+            field_references(collection, references);
+            field_references(predicate, references);
+        }
+        Expression::Aggregate { collection, .. } => field_references(collection, references),
+        Expression::Literal(_) | Expression::FieldAccess(_) | Expression::ResultAccess { .. } => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RULES: &str = r#"rule:
+  id: high_amount
+  name: High amount
+  when:
+    all:
+      - event.amount > features.avg_amount_30d
+      - event.ip in list.blocked_ips
+  score: 80
+---
+rule:
+  id: risky_country
+  name: Risky country
+  when:
+    all:
+      - api.ipinfo.ip_lookup.country_code == "KP"
+  score: 60
+"#;
+
+    const PIPELINE: &str = r#"ruleset:
+  id: payment_rules
+  name: Payment rules
+  rules:
+    - high_amount
+    - velocity_check
+  conclusion:
+    - default: true
+      signal: approve
+---
+pipeline:
+  id: payment_pipeline
+  name: Payment pipeline
+  entry: lookup
+  when:
+    all:
+      - event.type == "payment"
+  steps:
+    - step:
+        id: lookup
+        name: Lookup
+        type: api
+        api: ipinfo
+        endpoint: ip_lookup
+        next: check
+    - step:
+        id: check
+        name: Check
+        type: ruleset
+        ruleset: card_rules
+        next: end
+"#;
+
+    fn files() -> Vec<SourceFile> {
+        vec![
+            SourceFile::new("library/rules/payment.yaml", RULES),
+            SourceFile::new("pipelines/payment.yaml", PIPELINE),
+        ]
+    }
+
+    #[test]
+    fn test_reports_undefined_references_with_location() {
+        let configs = ConfigDefinitions::new()
+            .with_feature("txn_count_1h")
+            .with_api("ipinfo", ["ip_lookup"]);
+
+        let diagnostics = analyze(&files(), &configs);
+        let found: Vec<_> = diagnostics
+            .iter()
+            .map(|d| {
+                (
+                    d.file.as_deref().unwrap(),
+                    d.line.unwrap(),
+                    d.message.as_str(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                (
+                    "library/rules/payment.yaml",
+                    6,
+                    "Rule 'high_amount' references undefined feature 'avg_amount_30d'"
+                ),
+                (
+                    "library/rules/payment.yaml",
+                    7,
+                    "Rule 'high_amount' references undefined list 'blocked_ips'"
+                ),
+                (
+                    "pipelines/payment.yaml",
+                    6,
+                    "Ruleset 'payment_rules' references undefined rule 'velocity_check'"
+                ),
+                (
+                    "pipelines/payment.yaml",
+                    30,
+                    "Pipeline 'payment_pipeline' references undefined ruleset 'card_rules'"
+                ),
+            ]
+        );
+        assert!(diagnostics.iter().all(|d| d.code == UNDEFINED_REFERENCE));
+        assert_eq!(diagnostics[0].column, Some(24));
+        assert_eq!(
+            diagnostics[1].context.as_deref(),
+            Some("- event.ip in list.blocked_ips")
+        );
+    }
+
+    #[test]
+    fn test_api_endpoints_and_defined_references() {
+        let configs = ConfigDefinitions::new()
+            .with_feature("avg_amount_30d")
+            .with_list("blocked_ips")
+            .with_api("ipinfo", ["asn_lookup"]);
+        let mut files = files();
+        files.push(SourceFile::new(
+            "library/rules/velocity.yaml",
+            "rule:\n  id: velocity_check\n  name: Velocity\n  when:\n    all:\n      - event.count > 3\n  score: 10\n",
+        ));
+        files[1].content = files[1].content.replace("card_rules", "payment_rules");
+
+        let messages: Vec<_> = analyze(&files, &configs)
+            .into_iter()
+            .map(|d| (d.line.unwrap(), d.message))
+            .collect();
+        assert_eq!(
+            messages,
+            vec![
+                (
+                    15,
+                    "Rule 'risky_country' references undefined API endpoint 'ipinfo.ip_lookup'"
+                        .to_string()
+                ),
+                (
+                    24,
+                    "Pipeline 'payment_pipeline' references undefined API endpoint 'ipinfo.ip_lookup'"
+                        .to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_decision_tables_are_referenced_as_rules() {
+        let configs = ConfigDefinitions::new()
+            .with_feature("txn_count_1h")
+            .with_list("blocked_ips")
+            .with_api("ipinfo", ["ip_lookup"]);
+        let mut files = files();
+        files.push(SourceFile::new(
+            "library/rules/velocity.yaml",
+            "decision_table:\n  id: velocity_check\n  name: Velocity\n  inputs:\n    - features.txn_count_24h\n  rows:\n    - id: burst\n      cells: ['> 20']\n      score: 50\n",
+        ));
+
+        let messages: Vec<_> = analyze(&files, &configs)
+            .into_iter()
+            .map(|d| d.message)
+            .filter(|message| message.contains("velocity"))
+            .collect();
+        assert_eq!(
+            messages,
+            vec!["Decision table 'velocity_check' references undefined feature 'txn_count_24h'"]
+        );
+    }
+
+    #[test]
+    fn test_locate_whole_words() {
+        let text = "rules:\n  - high_amount_v2\n  - high_amount\n";
+        assert_eq!(locate(text, "high_amount"), Some((2, 4, "  - high_amount")));
+        assert_eq!(
+            locate("- features.x.y > 1", "features.x"),
+            Some((0, 2, "- features.x.y > 1"))
+        );
+        assert_eq!(locate("- features.xy > 1", "features.x"), None);
+    }
+}
@@ -0,0 +1,615 @@
+//! Contradictory conditions and unreachable conclusion branches
+//!
+//! Conditions are brought into disjunctive normal form over comparisons of a
+//! field with a constant (`event.amount > 100`, `event.country in ["US"]`);
+//! any other condition is kept as an opaque proposition. A conjunction is
+//! unsatisfiable when the comparisons on one field leave no possible value,
+//! e.g. `event.amount > 100 and event.amount < 50`. A condition is always
+//! false when every conjunction is unsatisfiable, and always true when its
+//! negation is always false (for events that have the compared fields).
+//!
+//! Conclusion branches are evaluated in order, so a branch can never fire
+//! when its condition implies that an earlier branch fired.
+
+use crate::codegen::ExpressionCompiler;
+use crate::validator::Diagnostic;
+use corint_core::ast::{
+    Condition, ConditionGroup, Expression, LogicalGroupOp, Operator, Rule, Ruleset, UnaryOperator,
+};
+use corint_core::Value;
+use std::collections::HashMap;
+
+/// Diagnostic code for conclusion branches that can never fire
+pub const UNREACHABLE_CONCLUSION: &str = "W004";
+
+/// Diagnostic code for conditions that are always false
+pub const CONTRADICTORY_CONDITION: &str = "W005";
+
+/// Diagnostic code for conditions that are always true
+pub const TAUTOLOGICAL_CONDITION: &str = "W006";
+
+/// Conjunctions beyond which a condition is left unanalyzed
+const MAX_TERMS: usize = 256;
+
+/// Find rule conditions that are always false or always true
+pub(crate) fn analyze_rule(rule: &Rule) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    let Some(when) = rule_condition(rule) else {
+        return diagnostics;
+    };
+
+    let conditions = match &when {
+        Expression::LogicalGroup { conditions, .. } => conditions.as_slice(),
+        _ => std::slice::from_ref(&when),
+    };
+    let mut contradiction_found = false;
+    for condition in conditions {
+        if is_contradiction(condition) {
+            contradiction_found = true;
+            diagnostics.push(condition_warning(
+                CONTRADICTORY_CONDITION,
+                format!("Condition of rule '{}' is always false", rule.id),
+                condition,
+            ));
+        } else if is_tautology(condition) {
+            diagnostics.push(condition_warning(
+                TAUTOLOGICAL_CONDITION,
+                format!("Condition of rule '{}' is always true", rule.id),
+                condition,
+            ));
+        }
+    }
+
+    if conditions.len() > 1 && !contradiction_found {
+        if is_contradiction(&when) {
+            diagnostics.push(condition_warning(
+                CONTRADICTORY_CONDITION,
+                format!(
+                    "Rule '{}' can never trigger: its conditions contradict each other",
+                    rule.id
+                ),
+                &when,
+            ));
+        } else if diagnostics.is_empty() && is_tautology(&when) {
+            diagnostics.push(condition_warning(
+                TAUTOLOGICAL_CONDITION,
+                format!(
+                    "Rule '{}' always triggers: its conditions are always true",
+                    rule.id
+                ),
+                &when,
+            ));
+        }
+    }
+
+    diagnostics
+}
+
+/// Find conclusion branches of a ruleset that can never fire
+pub(crate) fn analyze_conclusion(ruleset: &Ruleset) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    let mut earlier: Vec<(usize, &Expression)> = Vec::new();
+    let mut catch_all: Option<usize> = None;
+
+    for (index, branch) in ruleset.conclusion.iter().enumerate() {
+        let number = index + 1;
+        let unreachable = |reason: String| {
+            let diagnostic = Diagnostic::warning(
+                UNREACHABLE_CONCLUSION,
+                format!(
+                    "Conclusion branch {} of ruleset '{}' can never fire: {}",
+                    number, ruleset.id, reason
+                ),
+            );
+            match &branch.condition {
+                Some(condition) if !branch.default => {
+                    diagnostic.with_context(ExpressionCompiler::expression_to_string(condition))
+                }
+                _ => diagnostic,
+            }
+        };
+
+        if let Some(catch_all) = catch_all {
+            diagnostics.push(unreachable(format!(
+                "branch {} always fires before it",
+                catch_all
+            )));
+            continue;
+        }
+
+        let condition = match &branch.condition {
+            Some(condition) if !branch.default => condition,
+            _ => {
+                catch_all = Some(number);
+                continue;
+            }
+        };
+
+        if is_contradiction(condition) {
+            diagnostics.push(unreachable("its condition is always false".to_string()));
+        } else if let Some(shadowing) = shadowing_branch(condition, &earlier) {
+            diagnostics.push(unreachable(format!("shadowed by {}", shadowing)));
+        } else if is_tautology(condition) {
+            catch_all = Some(number);
+        }
+        earlier.push((number, condition));
+    }
+
+    diagnostics
+}
+
+/// Describe the earlier branches that fire whenever `condition` holds
+fn shadowing_branch(condition: &Expression, earlier: &[(usize, &Expression)]) -> Option<String> {
+    if earlier.is_empty() {
+        return None;
+    }
+    if let Some((number, _)) = earlier
+        .iter()
+        .find(|(_, previous)| is_contradiction(&and_not(condition, &[previous])))
+    {
+        return Some(format!("branch {}", number));
+    }
+    let previous: Vec<&Expression> = earlier.iter().map(|(_, previous)| *previous).collect();
+    is_contradiction(&and_not(condition, &previous)).then(|| "earlier branches".to_string())
+}
+
+/// `condition and not a and not b ...`
+fn and_not(condition: &Expression, others: &[&Expression]) -> Expression {
+    let mut conditions = vec![condition.clone()];
+    conditions.extend(others.iter().map(|other| Expression::Unary {
+        op: UnaryOperator::Not,
+        operand: Box::new((*other).clone()),
+    }));
+    Expression::LogicalGroup {
+        op: LogicalGroupOp::All,
+        conditions,
+    }
+}
+
+fn condition_warning(code: &str, message: String, condition: &Expression) -> Diagnostic {
+    Diagnostic::warning(code, message)
+        .with_context(ExpressionCompiler::expression_to_string(condition))
+}
+
+/// The `when` block of a rule as a single expression
+fn rule_condition(rule: &Rule) -> Option<Expression> {
+    let mut conditions = rule.when.conditions.clone().unwrap_or_default();
+    if let Some(group) = &rule.when.condition_group {
+        match group_expression(group) {
+            Expression::LogicalGroup {
+                op: LogicalGroupOp::All,
+                conditions: group,
+            } if conditions.is_empty() => conditions = group,
+            expression if conditions.is_empty() => return Some(expression),
+            expression => conditions.push(expression),
+        }
+    }
+    (!conditions.is_empty()).then_some(Expression::LogicalGroup {
+        op: LogicalGroupOp::All,
+        conditions,
+    })
+}
+
+fn group_expression(group: &ConditionGroup) -> Expression {
+    let (ConditionGroup::All(conditions)
+    | ConditionGroup::Any(conditions)
+    | ConditionGroup::Not(conditions)) = group;
+    let conditions = conditions
+        .iter()
+        .map(|condition| match condition {
+            Condition::Expression(expression) => expression.clone(),
+            Condition::Group(group) => group_expression(group),
+        })
+        .collect();
+
+    match group {
+        ConditionGroup::Any(_) => Expression::LogicalGroup {
+            op: LogicalGroupOp::Any,
+            conditions,
+        },
+        ConditionGroup::All(_) => Expression::LogicalGroup {
+            op: LogicalGroupOp::All,
+            conditions,
+        },
+        ConditionGroup::Not(_) => Expression::Unary {
+            op: UnaryOperator::Not,
+            operand: Box::new(Expression::LogicalGroup {
+                op: LogicalGroupOp::All,
+                conditions,
+            }),
+        },
+    }
+}
+
+/// Whether `expression` can never be true
+pub fn is_contradiction(expression: &Expression) -> bool {
+    normal_form(expression, false).is_some_and(|terms| terms.is_empty())
+}
+
+/// Whether `expression` is true for every event that has the compared fields
+pub fn is_tautology(expression: &Expression) -> bool {
+    normal_form(expression, true).is_some_and(|terms| terms.is_empty())
+}
+
+/// Proposition of a conjunction
+#[derive(Debug, Clone)]
+enum Atom {
+    /// Comparison of a field with a constant
+    Compare {
+        field: String,
+        op: Operator,
+        value: Value,
+    },
+    /// Any other condition, required to hold or not
+    Opaque { key: String, holds: bool },
+}
+
+/// Disjunction of satisfiable conjunctions; `None` when too large to analyze
+type NormalForm = Option<Vec<Vec<Atom>>>;
+
+fn normal_form(expression: &Expression, negated: bool) -> NormalForm {
+    match expression {
+        Expression::Literal(Value::Bool(value)) => Some(constant(*value != negated)),
+
+        Expression::Unary {
+            op: UnaryOperator::Not,
+            operand,
+        } => normal_form(operand, !negated),
+
+        Expression::LogicalGroup { op, conditions } => {
+            let conjunction = (*op == LogicalGroupOp::All) != negated;
+            combine(
+                conditions
+                    .iter()
+                    .map(|condition| normal_form(condition, negated)),
+                conjunction,
+            )
+        }
+
+        Expression::Binary { left, op, right } if matches!(op, Operator::And | Operator::Or) => {
+            let conjunction = (*op == Operator::And) != negated;
+            combine(
+                [normal_form(left, negated), normal_form(right, negated)].into_iter(),
+                conjunction,
+            )
+        }
+
+        Expression::Binary { left, op, right } => match comparison(left, *op, right) {
+            Some(atoms) => {
+                // `in` is a disjunction of equalities, `not in` a conjunction
+                let conjunction = matches!(op, Operator::NotIn) != negated;
+                let atoms = atoms.into_iter().map(|(field, op, value)| {
+                    let op = if negated { complement(op) } else { op };
+                    Some(vec![vec![Atom::Compare { field, op, value }]])
+                });
+                combine(atoms, conjunction)
+            }
+            None => Some(opaque(expression, negated)),
+        },
+
+        _ => Some(opaque(expression, negated)),
+    }
+}
+
+fn constant(value: bool) -> Vec<Vec<Atom>> {
+    if value {
+        vec![Vec::new()]
+    } else {
+        Vec::new()
+    }
+}
+
+fn opaque(expression: &Expression, negated: bool) -> Vec<Vec<Atom>> {
+    vec![vec![Atom::Opaque {
+        key: format!("{:?}", expression),
+        holds: !negated,
+    }]]
+}
+
+/// Combine normal forms with `and` (`conjunction`) or `or`
+fn combine(forms: impl Iterator<Item = NormalForm>, conjunction: bool) -> NormalForm {
+    let mut result = constant(conjunction);
+    for form in forms {
+        let form = form?;
+        if conjunction {
+            if result.len() * form.len() > MAX_TERMS {
+                return None;
+            }
+            result = result
+                .iter()
+                .flat_map(|left| {
+                    form.iter()
+                        .map(move |right| left.iter().chain(right).cloned().collect::<Vec<_>>())
+                })
+                .filter(|term| satisfiable(term))
+                .collect();
+        } else {
+            result.extend(form);
+            if result.len() > MAX_TERMS {
+                return None;
+            }
+        }
+    }
+    Some(result)
+}
+
+/// Comparisons of a field with constants made by `left op right`
+fn comparison(
+    left: &Expression,
+    op: Operator,
+    right: &Expression,
+) -> Option<Vec<(String, Operator, Value)>> {
+    match (left, op, right) {
+        (
+            Expression::FieldAccess(path),
+            Operator::In | Operator::NotIn,
+            Expression::Literal(Value::Array(values)),
+        ) => {
+            let op = if op == Operator::In {
+                Operator::Eq
+            } else {
+                Operator::Ne
+            };
+            values
+                .iter()
+                .map(|value| scalar(value).then(|| (path.join("."), op, value.clone())))
+                .collect()
+        }
+        (Expression::FieldAccess(path), op, Expression::Literal(value)) if op.is_comparison() => {
+            comparable(op, value).then(|| vec![(path.join("."), op, value.clone())])
+        }
+        (Expression::Literal(value), op, Expression::FieldAccess(path)) if op.is_comparison() => {
+            let op = match op {
+                Operator::Gt => Operator::Lt,
+                Operator::Ge => Operator::Le,
+                Operator::Lt => Operator::Gt,
+                Operator::Le => Operator::Ge,
+                op => op,
+            };
+            comparable(op, value).then(|| vec![(path.join("."), op, value.clone())])
+        }
+        _ => None,
+    }
+}
+
+fn scalar(value: &Value) -> bool {
+    matches!(value, Value::Number(_) | Value::String(_) | Value::Bool(_))
+}
+
+fn comparable(op: Operator, value: &Value) -> bool {
+    match op {
+        Operator::Eq | Operator::Ne => scalar(value),
+        _ => matches!(value, Value::Number(_)),
+    }
+}
+
+fn complement(op: Operator) -> Operator {
+    match op {
+        Operator::Eq => Operator::Ne,
+        Operator::Ne => Operator::Eq,
+        Operator::Gt => Operator::Le,
+        Operator::Ge => Operator::Lt,
+        Operator::Lt => Operator::Ge,
+        Operator::Le => Operator::Gt,
+        op => op,
+    }
+}
+
+/// Values left to a field by the comparisons of a conjunction
+#[derive(Default)]
+struct Domain<'a> {
+    /// Lower bound, and whether it is excluded
+    lower: Option<(f64, bool)>,
+    /// Upper bound, and whether it is excluded
+    upper: Option<(f64, bool)>,
+    equal: Option<&'a Value>,
+    excluded: Vec<&'a Value>,
+}
+
+impl Domain<'_> {
+    fn is_empty(&self) -> bool {
+        if let Some(value) = self.equal {
+            if self.excluded.contains(&value) {
+                return true;
+            }
+            return match value {
+                Value::Number(n) => !self.admits(*n),
+                _ => self.lower.is_some() || self.upper.is_some(),
+            };
+        }
+        match (self.lower, self.upper) {
+            (Some((lower, lower_strict)), Some((upper, upper_strict))) => {
+                lower > upper
+                    || (lower == upper
+                        && (lower_strict
+                            || upper_strict
+                            || self.excluded.contains(&&Value::Number(lower))))
+            }
+            _ => false,
+        }
+    }
+
+    fn admits(&self, n: f64) -> bool {
+        let above = self
+            .lower
+            .is_none_or(|(lower, strict)| n > lower || (!strict && n == lower));
+        let below = self
+            .upper
+            .is_none_or(|(upper, strict)| n < upper || (!strict && n == upper));
+        above && below
+    }
+}
+
+fn satisfiable(term: &[Atom]) -> bool {
+    let mut domains: HashMap<&str, Domain> = HashMap::new();
+    let mut opaque: HashMap<&str, bool> = HashMap::new();
+
+    for atom in term {
+        match atom {
+            Atom::Opaque { key, holds } => {
+                if *opaque.entry(key).or_insert(*holds) != *holds {
+                    return false;
+                }
+            }
+            Atom::Compare { field, op, value } => {
+                let domain = domains.entry(field).or_default();
+                match (op, value) {
+                    (Operator::Eq, value) => {
+                        if domain.equal.is_some_and(|equal| equal != value) {
+                            return false;
+                        }
+                        domain.equal = Some(value);
+                    }
+                    (Operator::Ne, value) => domain.excluded.push(value),
+                    (Operator::Gt | Operator::Ge, Value::Number(n)) => {
+                        let strict = *op == Operator::Gt;
+                        if domain.lower.is_none_or(|(lower, was_strict)| {
+                            *n > lower || (*n == lower && strict && !was_strict)
+                        }) {
+                            domain.lower = Some((*n, strict));
+                        }
+                    }
+                    (Operator::Lt | Operator::Le, Value::Number(n)) => {
+                        let strict = *op == Operator::Lt;
+                        if domain.upper.is_none_or(|(upper, was_strict)| {
+                            *n < upper || (*n == upper && strict && !was_strict)
+                        }) {
+                            domain.upper = Some((*n, strict));
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    !domains.values().any(Domain::is_empty)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use corint_parser::{ExpressionParser, RuleParser, RulesetParser};
+
+    fn condition(source: &str) -> Expression {
+        ExpressionParser::parse(source).unwrap()
+    }
+
+    #[test]
+    fn test_contradictions_and_tautologies() {
+        assert!(is_contradiction(&condition(
+            "event.amount > 100 && event.amount < 50"
+        )));
+        assert!(is_contradiction(&condition(
+            "event.amount >= 100 && event.amount < 100"
+        )));
+        assert!(is_contradiction(&condition(
+            "event.country == \"US\" && event.country in [\"CA\", \"MX\"]"
+        )));
+        assert!(is_contradiction(&condition(
+            "event.vip == true && !(event.vip == true)"
+        )));
+        assert!(!is_contradiction(&condition(
+            "event.amount >= 100 && event.amount <= 100"
+        )));
+        assert!(!is_contradiction(&condition(
+            "event.amount > 100 && event.count < 50"
+        )));
+
+        assert!(is_tautology(&condition(
+            "event.amount > 100 || event.amount <= 100"
+        )));
+        assert!(is_tautology(&condition(
+            "event.country != \"US\" || event.country != \"CA\""
+        )));
+        assert!(!is_tautology(&condition(
+            "event.amount > 100 || event.amount < 100"
+        )));
+        assert!(!is_tautology(&condition("event.amount > 100")));
+    }
+
+    #[test]
+    fn test_rule_conditions() {
+        let rule = RuleParser::parse(
+            r#"
+rule:
+  id: impossible_amount
+  name: Impossible amount
+  when:
+    all:
+      - event.amount > 100
+      - event.amount < 50
+  score: 50
+"#,
+        )
+        .unwrap();
+        let diagnostics = analyze_rule(&rule);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].code, CONTRADICTORY_CONDITION);
+        assert_eq!(
+            diagnostics[0].message,
+            "Rule 'impossible_amount' can never trigger: its conditions contradict each other"
+        );
+
+        let rule = RuleParser::parse(
+            r#"
+rule:
+  id: any_amount
+  name: Any amount
+  when:
+    all:
+      - event.amount > 100 || event.amount <= 100
+      - event.country == "US"
+  score: 10
+"#,
+        )
+        .unwrap();
+        let diagnostics = analyze_rule(&rule);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].code, TAUTOLOGICAL_CONDITION);
+        assert_eq!(
+            diagnostics[0].context.as_deref(),
+            Some("event.amount > 100 || event.amount <= 100")
+        );
+    }
+
+    #[test]
+    fn test_unreachable_conclusion_branches() {
+        let ruleset = RulesetParser::parse(
+            r#"
+ruleset:
+  id: payment
+  rules: []
+  conclusion:
+    - when: total_score >= 100
+      signal: decline
+    - when: total_score >= 150
+      signal: hold
+    - when: total_score >= 50
+      signal: review
+    - when: total_score > 80 && total_score < 60
+      signal: review
+    - default: true
+      signal: approve
+    - when: total_score < 10
+      signal: approve
+"#,
+        )
+        .unwrap();
+        let diagnostics = analyze_conclusion(&ruleset);
+        let messages: Vec<_> = diagnostics.iter().map(|d| d.message.as_str()).collect();
+        assert_eq!(
+            messages,
+            vec![
+                "Conclusion branch 2 of ruleset 'payment' can never fire: shadowed by branch 1",
+                "Conclusion branch 4 of ruleset 'payment' can never fire: its condition is always false",
+                "Conclusion branch 6 of ruleset 'payment' can never fire: branch 5 always fires before it",
+            ]
+        );
+        assert!(diagnostics.iter().all(|d| d.code == UNREACHABLE_CONCLUSION));
+        assert_eq!(
+            diagnostics[0].context.as_deref(),
+            Some("total_score >= 150")
+        );
+    }
+}
@@ -3,9 +3,10 @@
 //! Performs type inference and validation on expressions.
 
 use crate::error::{CompileError, Result};
-use corint_core::ast::{Expression, Operator, UnaryOperator};
+use corint_core::ast::{Condition, ConditionGroup, Expression, Operator, UnaryOperator, WhenBlock};
+use corint_core::types::{FieldType, Schema, SchemaField};
 use corint_core::Value;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 /// Type information for expressions
 #[derive(Debug, Clone, PartialEq)]
@@ -34,6 +35,19 @@ impl TypeInfo {
         }
     }
 
+    /// Name of the type in error messages
+    pub fn name(&self) -> String {
+        match self {
+            TypeInfo::Number => "number".to_string(),
+            TypeInfo::String => "string".to_string(),
+            TypeInfo::Boolean => "boolean".to_string(),
+            TypeInfo::Array(item) => format!("array of {}", item.name()),
+            TypeInfo::Object => "object".to_string(),
+            TypeInfo::Any => "any".to_string(),
+            TypeInfo::Unknown => "unknown".to_string(),
+        }
+    }
+
     /// Check if this type can be used in a numeric operation
     pub fn is_numeric(&self) -> bool {
         matches!(self, TypeInfo::Number | TypeInfo::Any | TypeInfo::Unknown)
@@ -57,12 +71,27 @@ impl TypeInfo {
     }
 }
 
+impl From<&FieldType> for TypeInfo {
+    fn from(field_type: &FieldType) -> Self {
+        match field_type {
+            FieldType::Number => TypeInfo::Number,
+            FieldType::String => TypeInfo::String,
+            FieldType::Boolean => TypeInfo::Boolean,
+            FieldType::Array { item_type } => TypeInfo::Array(Box::new(item_type.as_ref().into())),
+            FieldType::Object { .. } => TypeInfo::Object,
+            FieldType::Null | FieldType::Any => TypeInfo::Any,
+        }
+    }
+}
+
 /// Type checker
 pub struct TypeChecker {
     /// Type information for known variables
     variable_types: HashMap<String, TypeInfo>,
     /// Type information for known fields
     field_types: HashMap<String, TypeInfo>,
+    /// Paths (namespaces and objects) whose fields are all declared by a schema
+    declared_objects: HashSet<String>,
 }
 
 impl TypeChecker {
@@ -71,6 +100,37 @@ impl TypeChecker {
         Self {
             variable_types: HashMap::new(),
             field_types: HashMap::new(),
+            declared_objects: HashSet::new(),
+        }
+    }
+
+    /// Create a type checker for events of the given schema
+    pub fn with_schema(schema: &Schema) -> Self {
+        let mut checker = Self::new();
+        checker.register_schema("event", schema);
+        checker
+    }
+
+    /// Register the fields of a schema under `namespace` (e.g. `event`)
+    ///
+    /// Fields of the namespace missing from the schema are then rejected,
+    /// except under objects declared without fields and fields of type `any`.
+    pub fn register_schema(&mut self, namespace: &str, schema: &Schema) {
+        self.declared_objects.insert(namespace.to_string());
+        self.register_schema_fields(namespace, &schema.fields);
+    }
+
+    fn register_schema_fields(&mut self, prefix: &str, fields: &HashMap<String, SchemaField>) {
+        for (name, field) in fields {
+            let path = format!("{}.{}", prefix, name);
+            if let FieldType::Object {
+                schema: Some(schema),
+            } = &field.field_type
+            {
+                self.declared_objects.insert(path.clone());
+                self.register_schema_fields(&path, &schema.fields);
+            }
+            self.register_field(path, (&field.field_type).into());
         }
     }
 
@@ -104,6 +164,11 @@ impl TypeChecker {
                 // If we have type information for this field, use it
                 if let Some(type_info) = self.field_types.get(&path_str) {
                     Ok(type_info.clone())
+                } else if self.is_undeclared(path) {
+                    Err(CompileError::TypeError(format!(
+                        "Field '{}' is not declared in the schema",
+                        path_str
+                    )))
                 } else {
                     // Otherwise, assume it's unknown
                     Ok(TypeInfo::Unknown)
@@ -183,7 +248,98 @@ impl TypeChecker {
                 // The actual result will be resolved at runtime
                 Ok(TypeInfo::Unknown)
             }
+
+            Expression::Quantifier {
+                op,
+                collection,
+                predicate,
+                ..
+            } => {
+                // Collection must be an array (or unknown until runtime)
+                let collection_type = self.check_expression(collection)?;
+                if !matches!(
+                    collection_type,
+                    TypeInfo::Array(_) | TypeInfo::Any | TypeInfo::Unknown
+                ) {
+                    return Err(CompileError::TypeError(format!(
+                        "{} requires an array, got {:?}",
+                        op.name(),
+                        collection_type
+                    )));
+                }
+
+                // Predicate must be boolean
+                let predicate_type = self.check_expression(predicate)?;
+                if !predicate_type.is_boolean() {
+                    return Err(CompileError::TypeError(format!(
+                        "{} predicate must be boolean",
+                        op.name()
+                    )));
+                }
+
+                Ok(TypeInfo::Boolean)
+            }
+
+            Expression::Aggregate { op, collection, .. } => {
+                // Collection must be an array (or unknown until runtime)
+                let collection_type = self.check_expression(collection)?;
+                if !matches!(
+                    collection_type,
+                    TypeInfo::Array(_) | TypeInfo::Any | TypeInfo::Unknown
+                ) {
+                    return Err(CompileError::TypeError(format!(
+                        "{} requires an array, got {:?}",
+                        op.name(),
+                        collection_type
+                    )));
+                }
+
+                Ok(TypeInfo::Number)
+            }
+        }
+    }
+
+    /// Check every condition of a `when` block
+    pub fn check_when(&self, when: &WhenBlock) -> Result<()> {
+        for condition in when.conditions.iter().flatten() {
+            self.check_expression(condition)?;
+        }
+        if let Some(group) = &when.condition_group {
+            self.check_condition_group(group)?;
+        }
+        Ok(())
+    }
+
+    fn check_condition_group(&self, group: &ConditionGroup) -> Result<()> {
+        let (ConditionGroup::All(conditions)
+        | ConditionGroup::Any(conditions)
+        | ConditionGroup::Not(conditions)) = group;
+        for condition in conditions {
+            match condition {
+                Condition::Expression(expression) => {
+                    self.check_expression(expression)?;
+                }
+                Condition::Group(group) => self.check_condition_group(group)?,
+            }
         }
+        Ok(())
+    }
+
+    /// Whether `path` is missing from a schema that declares all its siblings
+    fn is_undeclared(&self, path: &[String]) -> bool {
+        // The closest registered ancestor decides: a declared object or
+        // namespace rejects unknown fields, any other type (an object without
+        // declared fields, an array, `any`) lets them through
+        for len in (1..path.len()).rev() {
+            let prefix = path[..len].join(".");
+            if self.declared_objects.contains(&prefix) {
+                return true;
+            }
+            if self.field_types.contains_key(&prefix) {
+                return false;
+            }
+        }
+        false
     }
 
     /// Infer the type of a literal value
@@ -254,9 +410,11 @@ impl TypeChecker {
                     && !matches!(left, TypeInfo::Unknown)
                     && !matches!(right, TypeInfo::Unknown)
                 {
-                    return Err(CompileError::TypeError(
-                        "Operands must have compatible types".to_string(),
-                    ));
+                    return Err(CompileError::TypeError(format!(
+                        "Cannot compare {} with {}",
+                        left.name(),
+                        right.name()
+                    )));
                 }
                 Ok(TypeInfo::Boolean)
             }
@@ -304,6 +462,9 @@ impl TypeChecker {
                 Ok(TypeInfo::Boolean)
             }
 
+            // Contains also tests membership in an array
+            Operator::Contains if matches!(left, TypeInfo::Array(_)) => Ok(TypeInfo::Boolean),
+
             // String operators: require string operands
             Operator::Contains | Operator::StartsWith | Operator::EndsWith | Operator::Regex => {
                 if !matches!(left, TypeInfo::String | TypeInfo::Unknown | TypeInfo::Any) {
@@ -348,6 +509,7 @@ impl TypeChecker {
                 }
                 Ok(TypeInfo::Number)
             }
+            UnaryOperator::IsNull | UnaryOperator::IsNotNull => Ok(TypeInfo::Boolean),
         }
     }
 }
@@ -563,6 +725,49 @@ mod tests {
         assert_eq!(type_info, TypeInfo::Boolean);
     }
 
+    #[test]
+    fn test_check_against_schema() {
+        let schema = Schema::new("payment".to_string())
+            .add_field(SchemaField::new("amount".to_string(), FieldType::Number))
+            .add_field(SchemaField::new("country".to_string(), FieldType::String))
+            .add_field(SchemaField::new(
+                "user".to_string(),
+                FieldType::Object {
+                    schema: Some(Box::new(
+                        Schema::new("user".to_string())
+                            .add_field(SchemaField::new("age".to_string(), FieldType::Number)),
+                    )),
+                },
+            ))
+            .add_field(SchemaField::new(
+                "device".to_string(),
+                FieldType::Object { schema: None },
+            ));
+        let checker = TypeChecker::with_schema(&schema);
+        let check = |source: &str| {
+            checker.check_expression(&corint_parser::ExpressionParser::parse(source).unwrap())
+        };
+
+        assert_eq!(check("event.amount > 100").unwrap(), TypeInfo::Boolean);
+        assert_eq!(check("event.user.age >= 18").unwrap(), TypeInfo::Boolean);
+        assert!(check("event.device.os == \"ios\"").is_ok());
+        assert!(check("features.txn_count > 3").is_ok());
+
+        let error = check("event.country > 100").unwrap_err().to_string();
+        assert!(
+            error.contains("Cannot compare string with number"),
+            "{}",
+            error
+        );
+        let error = check("event.amout > 100").unwrap_err().to_string();
+        assert!(
+            error.contains("Field 'event.amout' is not declared"),
+            "{}",
+            error
+        );
+        assert!(check("event.user.name == \"bob\"").is_err());
+    }
+
     #[test]
     fn test_variable_registration() {
         let mut checker = TypeChecker::new();
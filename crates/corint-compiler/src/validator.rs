@@ -45,6 +45,10 @@ pub struct Diagnostic {
     /// Human-readable message
     pub message: String,
 
+    /// File the diagnostic applies to (for repository-wide checks)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+
     /// Line number (1-based, if available)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub line: Option<usize>,
@@ -65,6 +69,7 @@ impl Diagnostic {
             severity: DiagnosticSeverity::Error,
             code: code.into(),
             message: message.into(),
+            file: None,
             line: None,
             column: None,
             context: None,
@@ -77,12 +82,19 @@ impl Diagnostic {
             severity: DiagnosticSeverity::Warning,
             code: code.into(),
             message: message.into(),
+            file: None,
             line: None,
             column: None,
             context: None,
         }
     }
 
+    /// Add the file the diagnostic applies to
+    pub fn with_file(mut self, file: impl Into<String>) -> Self {
+        self.file = Some(file.into());
+        self
+    }
+
     /// Add line/column location
     pub fn with_location(mut self, line: usize, column: usize) -> Self {
         self.line = Some(line);
@@ -293,13 +305,14 @@ impl DslValidator {
         };
 
         // Run semantic analysis
-        let warnings = Vec::new();
+        let mut warnings = Vec::new();
         if self.options.enable_semantic_analysis {
             let mut analyzer = SemanticAnalyzer::new();
             if let Err(e) = analyzer.analyze_rule(&rule) {
                 let diagnostic = Diagnostic::error("E002", format!("Semantic error: {}", e));
                 return ValidationResult::failure(diagnostic, start.elapsed().as_millis() as u64);
             }
+            warnings = locate_warnings(content, analyzer.check_rule_conditions(&rule));
         }
 
         // Try compilation to catch any codegen errors
@@ -338,12 +351,14 @@ impl DslValidator {
         };
 
         // Run semantic analysis
+        let mut warnings = Vec::new();
         if self.options.enable_semantic_analysis {
             let mut analyzer = SemanticAnalyzer::new();
             if let Err(e) = analyzer.analyze_ruleset(&ruleset) {
                 let diagnostic = Diagnostic::error("E002", format!("Semantic error: {}", e));
                 return ValidationResult::failure(diagnostic, start.elapsed().as_millis() as u64);
             }
+            warnings = locate_warnings(content, analyzer.check_conclusion(&ruleset));
         }
 
         // Try compilation
@@ -366,7 +381,11 @@ impl DslValidator {
             imports: Vec::new(),
         };
 
-        ValidationResult::success(metadata, start.elapsed().as_millis() as u64)
+        let mut result = ValidationResult::success(metadata, start.elapsed().as_millis() as u64);
+        for warning in warnings {
+            result = result.with_warning(warning);
+        }
+        result
     }
 
     /// Validate a pipeline document
@@ -493,6 +512,24 @@ impl DslValidator {
     }
 }
 
+/// Point warnings at the first line containing their condition, if any
+fn locate_warnings(content: &str, warnings: Vec<Diagnostic>) -> Vec<Diagnostic> {
+    warnings
+        .into_iter()
+        .map(|warning| {
+            let location = warning.context.as_deref().and_then(|context| {
+                content.lines().enumerate().find_map(|(index, line)| {
+                    line.find(context).map(|column| (index + 1, column + 1))
+                })
+            });
+            match location {
+                Some((line, column)) => warning.with_location(line, column),
+                None => warning,
+            }
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -579,4 +616,28 @@ rule:
         let meta = result.metadata.unwrap();
         assert_eq!(meta.doc_type, DslType::Rule);
     }
+
+    #[test]
+    fn test_unreachable_conclusion_warning() {
+        let validator = DslValidator::new();
+        let content = r#"
+ruleset:
+  id: payment
+  rules: []
+  conclusion:
+    - when: total_score >= 50
+      signal: review
+    - when: total_score >= 100
+      signal: decline
+"#;
+
+        let result = validator.validate(content, DslType::Ruleset);
+        assert!(result.valid);
+        assert_eq!(result.warnings.len(), 1);
+        let warning = &result.warnings[0];
+        assert_eq!(warning.severity, DiagnosticSeverity::Warning);
+        assert_eq!(warning.code, "W004");
+        assert_eq!(warning.line, Some(8));
+        assert_eq!(warning.column, Some(13));
+    }
 }
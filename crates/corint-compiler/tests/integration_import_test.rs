@@ -39,6 +39,8 @@ fn test_compiler_with_import_resolver() {
         enable_semantic_analysis: true,
         enable_constant_folding: true,
         enable_dead_code_elimination: true,
+        enable_common_subexpression_elimination: true,
+        enable_condition_reordering: true,
         library_base_path: "repository".to_string(),
     };
 
@@ -126,6 +128,33 @@ fn test_compile_pipeline_file_method_exists() {
     assert!(result.is_err());
 }
 
+#[test]
+fn test_compile_to_file_writes_loadable_artifact() {
+    let repository = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../../repository");
+    let mut compiler = Compiler::with_options(CompilerOptions {
+        library_base_path: repository.display().to_string(),
+        ..CompilerOptions::default()
+    });
+    let output = std::env::temp_dir().join(format!("corint_artifact_{}.crir", std::process::id()));
+
+    let programs = compiler
+        .compile_to_file(&repository.join("pipelines/fraud_detection.yaml"), &output)
+        .unwrap();
+    let bytes = std::fs::read(&output).unwrap();
+    std::fs::remove_file(&output).unwrap();
+
+    // The pipeline ships with the ruleset and rules it imports
+    let loaded = corint_core::ir::Program::load_all_from_bytes(&bytes).unwrap();
+    assert_eq!(loaded, programs);
+    let source_types: Vec<_> = loaded
+        .iter()
+        .map(|program| program.metadata.source_type.as_str())
+        .collect();
+    assert_eq!(source_types.last(), Some(&"pipeline"));
+    assert!(source_types.contains(&"ruleset"));
+    assert!(source_types.contains(&"rule"));
+}
+
 #[test]
 fn test_import_resolver_error_messages() {
     let mut resolver = ImportResolver::new("nonexistent_directory");
@@ -19,6 +19,8 @@ fn create_step(id: &str, step_type: &str) -> PipelineStep {
         default: None,
         next: None,
         when: None,
+        else_branch: None,
+        timeout_ms: None,
         details: StepDetails::Unknown {},
     }
 }
@@ -33,6 +35,8 @@ fn create_ruleset_step(id: &str, ruleset: &str, next: Option<&str>) -> PipelineS
         default: None,
         next: next.map(|n| StepNext::StepId(n.to_string())),
         when: None,
+        else_branch: None,
+        timeout_ms: None,
         details: StepDetails::Ruleset {
             ruleset: ruleset.to_string(),
         },
@@ -65,6 +69,8 @@ fn create_router_step(
         default: default.map(|d| d.to_string()),
         next: None,
         when: None,
+        else_branch: None,
+        timeout_ms: None,
         details: StepDetails::Unknown {},
     }
 }
@@ -81,6 +87,9 @@ fn test_simple_linear_pipeline() {
         description: None,
         entry: "step1".to_string(),
         when: None,
+        timeout_ms: None,
+        on_error: None,
+        schema: None,
         steps: vec![step1, step2],
         decision: None,
         metadata: None,
@@ -158,6 +167,9 @@ fn test_router_with_multiple_routes() {
         description: None,
         entry: "router1".to_string(),
         when: None,
+        timeout_ms: None,
+        on_error: None,
+        schema: None,
         steps: vec![router, high_step, medium_step, low_step],
         decision: None,
         metadata: None,
@@ -248,6 +260,9 @@ fn test_sequential_routers() {
         description: None,
         entry: "router1".to_string(),
         when: None,
+        timeout_ms: None,
+        on_error: None,
+        schema: None,
         steps: vec![router1, router2, approve, manual, reject],
         decision: None,
         metadata: None,
@@ -316,6 +331,9 @@ fn test_complex_routing_logic() {
         description: None,
         entry: "router".to_string(),
         when: None,
+        timeout_ms: None,
+        on_error: None,
+        schema: None,
         steps: vec![router, high, low],
         decision: None,
         metadata: None,
@@ -350,6 +368,9 @@ fn test_unreachable_step_in_pipeline() {
         description: None,
         entry: "step1".to_string(),
         when: None,
+        timeout_ms: None,
+        on_error: None,
+        schema: None,
         steps: vec![step1, step2],
         decision: None,
         metadata: None,
@@ -397,6 +418,9 @@ fn test_router_without_default() {
         description: None,
         entry: "router".to_string(),
         when: None,
+        timeout_ms: None,
+        on_error: None,
+        schema: None,
         steps: vec![router, approve],
         decision: None,
         metadata: None,
@@ -428,6 +452,9 @@ fn test_all_routes_lead_to_end() {
         description: None,
         entry: "router".to_string(),
         when: None,
+        timeout_ms: None,
+        on_error: None,
+        schema: None,
         steps: vec![router],
         decision: None,
         metadata: None,
@@ -462,6 +489,9 @@ fn test_invalid_step_reference() {
         description: None,
         entry: "step1".to_string(),
         when: None,
+        timeout_ms: None,
+        on_error: None,
+        schema: None,
         steps: vec![step1],
         decision: None,
         metadata: None,
@@ -483,6 +513,9 @@ fn test_empty_pipeline() {
         description: None,
         entry: "step1".to_string(),
         when: None,
+        timeout_ms: None,
+        on_error: None,
+        schema: None,
         steps: vec![],
         decision: None,
         metadata: None,
@@ -423,6 +423,9 @@ fn test_semantic_analyze_valid_rule() {
         params: None,
         when,
         score: 50,
+        priority: None,
+        effective_from: None,
+        effective_until: None,
         metadata: None,
     };
 
@@ -441,7 +444,12 @@ fn test_semantic_analyze_valid_ruleset() {
         rules: vec!["rule1".to_string(), "rule2".to_string()],
         conclusion: vec![],
         description: None,
+        effective_from: None,
+        effective_until: None,
         metadata: None,
+        scoring: None,
+        stop_on_score: None,
+        stop_on_signal: None,
     };
 
     let mut analyzer = semantic::SemanticAnalyzer::new();
@@ -615,6 +623,9 @@ fn test_compiler_compile_rule() {
         params: None,
         when,
         score: 50,
+        priority: None,
+        effective_from: None,
+        effective_until: None,
         metadata: None,
     };
 
@@ -633,7 +644,12 @@ fn test_compiler_compile_ruleset() {
         rules: vec!["rule1".to_string()],
         conclusion: vec![],
         description: None,
+        effective_from: None,
+        effective_until: None,
         metadata: None,
+        scoring: None,
+        stop_on_score: None,
+        stop_on_signal: None,
     };
 
     let mut compiler = Compiler::new();
@@ -649,6 +665,8 @@ fn test_compiler_with_options() {
         enable_semantic_analysis: false,
         enable_constant_folding: false,
         enable_dead_code_elimination: false,
+        enable_common_subexpression_elimination: false,
+        enable_condition_reordering: false,
         library_base_path: "test_repo".to_string(),
     };
 
@@ -663,6 +681,9 @@ fn test_compiler_with_options() {
         params: None,
         when,
         score: 25,
+        priority: None,
+        effective_from: None,
+        effective_until: None,
         metadata: None,
     };
 
@@ -714,6 +735,9 @@ fn test_rule_compiler_simple_rule() {
         params: None,
         when,
         score: 50,
+        priority: None,
+        effective_from: None,
+        effective_until: None,
         metadata: None,
     };
 
@@ -742,6 +766,9 @@ fn test_rule_compiler_with_multiple_conditions() {
         params: None,
         when,
         score: 75,
+        priority: None,
+        effective_from: None,
+        effective_until: None,
         metadata: None,
     };
 
@@ -762,7 +789,12 @@ fn test_ruleset_compiler_empty_ruleset() {
         rules: vec![],
         conclusion: vec![],
         description: None,
+        effective_from: None,
+        effective_until: None,
         metadata: None,
+        scoring: None,
+        stop_on_score: None,
+        stop_on_signal: None,
     };
 
     let result = codegen::RulesetCompiler::compile(&ruleset);
@@ -844,6 +876,9 @@ fn test_rule_with_negative_score() {
         params: None,
         when,
         score: -10,
+        priority: None,
+        effective_from: None,
+        effective_until: None,
         metadata: None,
     };
 
@@ -862,6 +897,9 @@ fn test_rule_with_zero_score() {
         params: None,
         when,
         score: 0,
+        priority: None,
+        effective_from: None,
+        effective_until: None,
         metadata: None,
     };
 
@@ -884,6 +922,9 @@ fn test_rule_with_metadata() {
         params: None,
         when,
         score: 50,
+        priority: None,
+        effective_from: None,
+        effective_until: None,
         metadata: Some(metadata),
     };
 
@@ -900,7 +941,12 @@ fn test_ruleset_with_extends() {
         rules: vec!["additional_rule".to_string()],
         conclusion: vec![],
         description: None,
+        effective_from: None,
+        effective_until: None,
         metadata: None,
+        scoring: None,
+        stop_on_score: None,
+        stop_on_signal: None,
     };
 
     let result = codegen::RulesetCompiler::compile(&ruleset);
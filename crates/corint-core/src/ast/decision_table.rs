@@ -0,0 +1,173 @@
+//! Decision table AST definitions
+//!
+//! A decision table expresses a lookup-style policy as rows of cells instead
+//! of a rule per case: each column is an input expression, each row tests
+//! the inputs with one cell per column and adds a score when all its cells
+//! match. The hit policy decides which of the matching rows apply.
+
+use super::expression::Expression;
+use serde::{Deserialize, Serialize};
+
+/// Decision table definition
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DecisionTable {
+    /// Unique table ID, referenced from rulesets like a rule ID
+    pub id: String,
+
+    /// Human-readable name
+    pub name: String,
+
+    /// Optional description
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+
+    /// Which of the matching rows apply
+    #[serde(default)]
+    pub hit_policy: HitPolicy,
+
+    /// Input expressions, one per column
+    pub inputs: Vec<Expression>,
+
+    /// Rows, in declaration order
+    pub rows: Vec<DecisionTableRow>,
+}
+
+/// Hit policy of a decision table
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HitPolicy {
+    /// The first matching row, in declaration order
+    #[default]
+    First,
+    /// The matching row with the highest priority, ties in declaration order
+    Priority,
+    /// Every matching row; their scores add up
+    Collect,
+}
+
+/// Row of a decision table
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DecisionTableRow {
+    /// Row ID, unique within the table
+    pub id: String,
+
+    /// Condition of each cell, one per input; `None` matches any value
+    pub cells: Vec<Option<Expression>>,
+
+    /// Score added when the row applies
+    pub score: i32,
+
+    /// Priority under the `priority` hit policy (default: 0)
+    #[serde(default)]
+    pub priority: i32,
+}
+
+impl DecisionTable {
+    /// Create a table without rows
+    pub fn new(id: String, name: String, inputs: Vec<Expression>) -> Self {
+        Self {
+            id,
+            name,
+            description: None,
+            hit_policy: HitPolicy::First,
+            inputs,
+            rows: Vec::new(),
+        }
+    }
+
+    /// Set the description
+    pub fn with_description(mut self, description: String) -> Self {
+        self.description = Some(description);
+        self
+    }
+
+    /// Set the hit policy
+    pub fn with_hit_policy(mut self, hit_policy: HitPolicy) -> Self {
+        self.hit_policy = hit_policy;
+        self
+    }
+
+    /// Add a row
+    pub fn add_row(mut self, row: DecisionTableRow) -> Self {
+        self.rows.push(row);
+        self
+    }
+
+    /// ID recorded as triggered when `row` applies
+    pub fn row_rule_id(&self, row: &DecisionTableRow) -> String {
+        format!("{}.{}", self.id, row.id)
+    }
+
+    /// Rows in the order they are tried: by descending priority under the
+    /// `priority` hit policy, in declaration order otherwise
+    pub fn evaluation_order(&self) -> Vec<&DecisionTableRow> {
+        let mut rows: Vec<&DecisionTableRow> = self.rows.iter().collect();
+        if self.hit_policy == HitPolicy::Priority {
+            rows.sort_by_key(|row| std::cmp::Reverse(row.priority));
+        }
+        rows
+    }
+}
+
+impl HitPolicy {
+    /// Name of the hit policy, as written in the DSL
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            HitPolicy::First => "first",
+            HitPolicy::Priority => "priority",
+            HitPolicy::Collect => "collect",
+        }
+    }
+}
+
+impl DecisionTableRow {
+    /// Create a row
+    pub fn new(id: String, cells: Vec<Option<Expression>>, score: i32) -> Self {
+        Self {
+            id,
+            cells,
+            score,
+            priority: 0,
+        }
+    }
+
+    /// Set the priority
+    pub fn with_priority(mut self, priority: i32) -> Self {
+        self.priority = priority;
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Value;
+
+    #[test]
+    fn test_evaluation_order() {
+        let row = |id: &str, priority: i32| {
+            DecisionTableRow::new(id.to_string(), vec![None], 10).with_priority(priority)
+        };
+        let table = DecisionTable::new(
+            "shipping_risk".to_string(),
+            "Shipping Risk".to_string(),
+            vec![Expression::literal(Value::Bool(true))],
+        )
+        .add_row(row("low", 1))
+        .add_row(row("high", 5))
+        .add_row(row("also_low", 1));
+
+        let ids = |table: &DecisionTable| -> Vec<String> {
+            table
+                .evaluation_order()
+                .iter()
+                .map(|row| row.id.clone())
+                .collect()
+        };
+        assert_eq!(ids(&table), vec!["low", "high", "also_low"]);
+
+        let table = table.with_hit_policy(HitPolicy::Priority);
+        assert_eq!(ids(&table), vec!["high", "low", "also_low"]);
+        assert_eq!(table.row_rule_id(&table.rows[1]), "shipping_risk.high");
+    }
+}
@@ -58,6 +58,28 @@ pub enum Expression {
         /// Field to access (e.g., "action", "total_score", "reason")
         field: String,
     },
+
+    /// Quantified predicate over an array (e.g., any_of(event.items, item.price > 1000))
+    Quantifier {
+        /// Quantifier kind: "any_of" or "all_of"
+        op: QuantifierOp,
+        /// Expression producing the array to iterate
+        collection: Box<Expression>,
+        /// Name bound to the current element inside the predicate (default: "item")
+        binding: String,
+        /// Predicate evaluated once per element
+        predicate: Box<Expression>,
+    },
+
+    /// Aggregate over an array (e.g., sum(event.items[*].amount), count(event.items))
+    Aggregate {
+        /// Aggregation function
+        op: AggregateOp,
+        /// Expression producing the array to aggregate
+        collection: Box<Expression>,
+        /// Field path within each element (empty = the elements themselves)
+        path: Vec<String>,
+    },
 }
 
 /// Logical group operation type
@@ -69,6 +91,65 @@ pub enum LogicalGroupOp {
     All,
 }
 
+/// Quantifier over array elements
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum QuantifierOp {
+    /// At least one element satisfies the predicate
+    AnyOf,
+    /// Every element satisfies the predicate
+    AllOf,
+}
+
+impl QuantifierOp {
+    /// Get the function name of this quantifier
+    pub fn name(&self) -> &'static str {
+        match self {
+            QuantifierOp::AnyOf => "any_of",
+            QuantifierOp::AllOf => "all_of",
+        }
+    }
+}
+
+/// Aggregation function over array elements
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AggregateOp {
+    /// Number of elements (or of non-null values when a path is given)
+    Count,
+    /// Sum of numeric values
+    Sum,
+    /// Average of numeric values
+    Avg,
+    /// Minimum numeric value
+    Min,
+    /// Maximum numeric value
+    Max,
+}
+
+impl AggregateOp {
+    /// Get the function name of this aggregate
+    pub fn name(&self) -> &'static str {
+        match self {
+            AggregateOp::Count => "count",
+            AggregateOp::Sum => "sum",
+            AggregateOp::Avg => "avg",
+            AggregateOp::Min => "min",
+            AggregateOp::Max => "max",
+        }
+    }
+
+    /// Look up an aggregate by function name
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "count" => Some(AggregateOp::Count),
+            "sum" => Some(AggregateOp::Sum),
+            "avg" => Some(AggregateOp::Avg),
+            "min" => Some(AggregateOp::Min),
+            "max" => Some(AggregateOp::Max),
+            _ => None,
+        }
+    }
+}
+
 /// Unary operators
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum UnaryOperator {
@@ -76,6 +157,10 @@ pub enum UnaryOperator {
     Not,
     /// Arithmetic negation (-)
     Negate,
+    /// Null check (`is null`)
+    IsNull,
+    /// Non-null check (`is not null`)
+    IsNotNull,
 }
 
 impl Expression {
@@ -135,6 +220,30 @@ impl Expression {
             field,
         }
     }
+
+    /// Create a quantifier expression over an array
+    pub fn quantifier(
+        op: QuantifierOp,
+        collection: Expression,
+        binding: String,
+        predicate: Expression,
+    ) -> Self {
+        Expression::Quantifier {
+            op,
+            collection: Box::new(collection),
+            binding,
+            predicate: Box::new(predicate),
+        }
+    }
+
+    /// Create an aggregate expression over an array
+    pub fn aggregate(op: AggregateOp, collection: Expression, path: Vec<String>) -> Self {
+        Expression::Aggregate {
+            op,
+            collection: Box::new(collection),
+            path,
+        }
+    }
 }
 
 #[cfg(test)]
@@ -284,4 +393,49 @@ mod tests {
             _ => panic!("Expected Ternary expression"),
         }
     }
+
+    #[test]
+    fn test_quantifier_expression() {
+        // any_of(event.items, item.price > 1000)
+        let expr = Expression::quantifier(
+            QuantifierOp::AnyOf,
+            Expression::field_access(vec!["event".to_string(), "items".to_string()]),
+            "item".to_string(),
+            Expression::binary(
+                Expression::field_access(vec!["item".to_string(), "price".to_string()]),
+                Operator::Gt,
+                Expression::literal(Value::Number(1000.0)),
+            ),
+        );
+
+        match expr {
+            Expression::Quantifier { op, binding, .. } => {
+                assert_eq!(op, QuantifierOp::AnyOf);
+                assert_eq!(op.name(), "any_of");
+                assert_eq!(binding, "item");
+            }
+            _ => panic!("Expected Quantifier expression"),
+        }
+    }
+
+    #[test]
+    fn test_aggregate_expression() {
+        // sum(event.items[*].amount)
+        let expr = Expression::aggregate(
+            AggregateOp::Sum,
+            Expression::field_access(vec!["event".to_string(), "items".to_string()]),
+            vec!["amount".to_string()],
+        );
+
+        match expr {
+            Expression::Aggregate { op, path, .. } => {
+                assert_eq!(op, AggregateOp::Sum);
+                assert_eq!(path, vec!["amount".to_string()]);
+            }
+            _ => panic!("Expected Aggregate expression"),
+        }
+
+        assert_eq!(AggregateOp::from_name("max"), Some(AggregateOp::Max));
+        assert_eq!(AggregateOp::from_name("median"), None);
+    }
 }
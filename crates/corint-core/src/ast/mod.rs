@@ -3,25 +3,32 @@
 //! This module contains the AST node definitions for:
 //! - Expressions
 //! - Rules
+//! - Decision tables
 //! - Rulesets
 //! - Pipelines
 //! - Pipeline Registry
+//! - Reason code catalogs
 //! - Imports and dependency management
 
+pub mod decision_table;
 pub mod expression;
 pub mod import;
 pub mod operator;
 pub mod pipeline;
+pub mod reason_code;
 pub mod registry;
 pub mod rule;
 pub mod ruleset;
 
-pub use expression::{Expression, LogicalGroupOp, UnaryOperator};
+pub use decision_table::{DecisionTable, DecisionTableRow, HitPolicy};
+pub use expression::{AggregateOp, Expression, LogicalGroupOp, QuantifierOp, UnaryOperator};
 pub use import::{ImportContext, Imports, RdlDocument};
 pub use operator::Operator;
 pub use pipeline::{
-    Branch, FeatureDefinition, MergeStrategy, Pipeline, PipelineDecisionRule, Step,
+    Branch, EmitKind, ErrorPolicy, FeatureDefinition, MergeStrategy, Pipeline,
+    PipelineDecisionRule, PipelineOnError, Step, VariableAssignment,
 };
-pub use registry::{PipelineRegistry, RegistryEntry};
+pub use reason_code::{ReasonCatalog, ReasonCode, ReasonMessage, ResolvedReason};
+pub use registry::{EntityQuery, Experiment, PipelineRegistry, RegistryEntry, ScheduleEntry};
 pub use rule::{Condition, ConditionGroup, Rule, RuleParams, WhenBlock};
-pub use ruleset::{DecisionRule, Ruleset, Signal};
+pub use ruleset::{DecisionRule, Ruleset, ScoreAggregation, ScoreStrategy, Signal};
@@ -12,6 +12,8 @@
 
 use crate::ast::Expression;
 use crate::ast::rule::WhenBlock;
+use crate::ast::Signal;
+use crate::types::Schema;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
@@ -35,6 +37,18 @@ pub struct Pipeline {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub when: Option<WhenBlock>,
 
+    /// Time budget for the whole pipeline in milliseconds (optional)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_ms: Option<u64>,
+
+    /// Fallback decision when a step fails (optional, errors propagate otherwise)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub on_error: Option<PipelineOnError>,
+
+    /// Declared schema of the input event, used to type check conditions (optional)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub schema: Option<Schema>,
+
     /// The processing steps (required, non-empty)
     pub steps: Vec<PipelineStep>,
 
@@ -76,6 +90,15 @@ pub struct PipelineStep {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub when: Option<WhenBlock>,
 
+    /// Where execution continues when the `when` condition is false
+    /// (optional, defaults to the step's `next`)
+    #[serde(rename = "else", default, skip_serializing_if = "Option::is_none")]
+    pub else_branch: Option<StepElse>,
+
+    /// Time budget for the step in milliseconds (optional)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_ms: Option<u64>,
+
     /// Type-specific fields (flattened)
     #[serde(flatten)]
     pub details: StepDetails,
@@ -89,6 +112,13 @@ pub enum StepNext {
     StepId(String),
 }
 
+/// Branch taken when a step's `when` condition is false
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StepElse {
+    /// Step to continue with instead, or "end"
+    pub skip_to: String,
+}
+
 /// Route with condition (consistent with registry format: target outside, condition inside)
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Route {
@@ -206,6 +236,20 @@ pub enum StepDetails {
         min_success: Option<usize>,
     },
 
+    /// Emit step - side effect dispatched once the decision is final
+    Emit {
+        /// Kind of side effect
+        emit: EmitKind,
+        /// Webhook URL, Kafka topic or case queue
+        target: String,
+        /// Payload fields, evaluated when the step runs
+        #[serde(skip_serializing_if = "Option::is_none")]
+        payload: Option<HashMap<String, Expression>>,
+        /// Final signals the side effect is emitted for (all when empty)
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        signals: Vec<Signal>,
+    },
+
     /// Trigger step - external action (MQ, Webhook, notification)
     Trigger {
         /// Target service/endpoint
@@ -215,6 +259,25 @@ pub enum StepDetails {
         params: Option<HashMap<String, Expression>>,
     },
 
+    /// Set step - assign values to the vars namespace
+    Set {
+        /// Assignments, applied in order (later ones may read earlier ones via vars.<name>)
+        set: Vec<VariableAssignment>,
+    },
+
+    /// Let step - declare request-scoped variables in the vars namespace
+    Let {
+        /// Declarations, applied in order; each name may be declared once per pipeline
+        #[serde(rename = "let")]
+        declare: Vec<VariableAssignment>,
+    },
+
+    /// Assign step - update variables declared by an earlier let step
+    Assign {
+        /// Assignments, applied in order
+        assign: Vec<VariableAssignment>,
+    },
+
     /// Legacy/compatibility steps
     Extract {
         /// Feature definitions
@@ -222,6 +285,13 @@ pub enum StepDetails {
         features: Option<Vec<FeatureDefinition>>,
     },
 
+    /// Step of a type registered by a compiler plugin
+    Custom {
+        /// Fields of the step other than the common ones, as written
+        #[serde(flatten)]
+        fields: HashMap<String, serde_json::Value>,
+    },
+
     /// Catch-all for unknown step types
     Unknown {},
 }
@@ -247,6 +317,38 @@ pub enum ApiTarget {
     },
 }
 
+/// Kind of side effect dispatched by an emit step
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EmitKind {
+    /// HTTP POST of the payload to a URL
+    Webhook,
+    /// Message published to a Kafka topic
+    Kafka,
+    /// Case opened in a case management queue
+    Case,
+}
+
+impl EmitKind {
+    /// Name of the kind as written in the DSL
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EmitKind::Webhook => "webhook",
+            EmitKind::Kafka => "kafka",
+            EmitKind::Case => "case",
+        }
+    }
+}
+
+/// Variable assignment in a set step
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VariableAssignment {
+    /// Variable name (written to vars.<name>)
+    pub name: String,
+    /// Expression producing the value
+    pub value: Expression,
+}
+
 /// Feature definition for extraction
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct FeatureDefinition {
@@ -373,6 +475,64 @@ pub enum ErrorAction {
     Retry,
 }
 
+/// Fallback decision of a pipeline whose step failed
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PipelineOnError {
+    /// How the failure is decided
+    pub policy: ErrorPolicy,
+
+    /// Signal of a fail-open decision (optional, defaults to approve)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signal: Option<Signal>,
+
+    /// Reason code of the fallback decision (optional)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason_code: Option<String>,
+}
+
+impl PipelineOnError {
+    /// Fallback decided by `policy`
+    pub fn new(policy: ErrorPolicy) -> Self {
+        Self {
+            policy,
+            signal: None,
+            reason_code: None,
+        }
+    }
+
+    /// Signal of the fallback decision
+    pub fn fallback_signal(&self) -> Signal {
+        match self.policy {
+            ErrorPolicy::FailOpen => self.signal.clone().unwrap_or(Signal::Approve),
+            ErrorPolicy::FailClosed => Signal::Decline,
+            ErrorPolicy::Review => Signal::Review,
+        }
+    }
+}
+
+/// How a pipeline decides when one of its steps fails
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorPolicy {
+    /// Let the request through with the default signal
+    FailOpen,
+    /// Decline the request
+    FailClosed,
+    /// Send the request for manual review
+    Review,
+}
+
+impl ErrorPolicy {
+    /// Name of the policy as written in the DSL
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ErrorPolicy::FailOpen => "fail_open",
+            ErrorPolicy::FailClosed => "fail_closed",
+            ErrorPolicy::Review => "review",
+        }
+    }
+}
+
 impl Pipeline {
     /// Create a new pipeline with required fields
     pub fn new(id: String, name: String, entry: String) -> Self {
@@ -382,6 +542,9 @@ impl Pipeline {
             description: None,
             entry,
             when: None,
+            timeout_ms: None,
+            on_error: None,
+            schema: None,
             steps: Vec::new(),
             decision: None,
             metadata: None,
@@ -423,6 +586,24 @@ impl Pipeline {
         self.decision = Some(decision);
         self
     }
+
+    /// Set the time budget of the pipeline in milliseconds
+    pub fn with_timeout_ms(mut self, timeout_ms: u64) -> Self {
+        self.timeout_ms = Some(timeout_ms);
+        self
+    }
+
+    /// Set the fallback decision when a step fails
+    pub fn with_on_error(mut self, on_error: PipelineOnError) -> Self {
+        self.on_error = Some(on_error);
+        self
+    }
+
+    /// Set the declared schema of the input event
+    pub fn with_schema(mut self, schema: Schema) -> Self {
+        self.schema = Some(schema);
+        self
+    }
 }
 
 impl PipelineStep {
@@ -436,6 +617,8 @@ impl PipelineStep {
             default: None,
             next: None,
             when: None,
+            else_branch: None,
+            timeout_ms: None,
             details: StepDetails::Router {},
         }
     }
@@ -450,6 +633,8 @@ impl PipelineStep {
             default: None,
             next: None,
             when: None,
+            else_branch: None,
+            timeout_ms: None,
             details: StepDetails::Api {
                 api_target: ApiTarget::Single { api },
                 endpoint: None,
@@ -472,10 +657,89 @@ impl PipelineStep {
             default: None,
             next: None,
             when: None,
+            else_branch: None,
+            timeout_ms: None,
             details: StepDetails::Ruleset { ruleset },
         }
     }
 
+    /// Create a set step
+    pub fn set(id: String, name: String, assignments: Vec<VariableAssignment>) -> Self {
+        Self {
+            id,
+            name,
+            step_type: "set".to_string(),
+            routes: None,
+            default: None,
+            next: None,
+            when: None,
+            else_branch: None,
+            timeout_ms: None,
+            details: StepDetails::Set { set: assignments },
+        }
+    }
+
+    /// Create a let step
+    pub fn declare(id: String, name: String, declarations: Vec<VariableAssignment>) -> Self {
+        Self {
+            id,
+            name,
+            step_type: "let".to_string(),
+            routes: None,
+            default: None,
+            next: None,
+            when: None,
+            else_branch: None,
+            timeout_ms: None,
+            details: StepDetails::Let {
+                declare: declarations,
+            },
+        }
+    }
+
+    /// Create an assign step
+    pub fn assign(id: String, name: String, assignments: Vec<VariableAssignment>) -> Self {
+        Self {
+            id,
+            name,
+            step_type: "assign".to_string(),
+            routes: None,
+            default: None,
+            next: None,
+            when: None,
+            else_branch: None,
+            timeout_ms: None,
+            details: StepDetails::Assign { assign: assignments },
+        }
+    }
+
+    /// Create an emit step dispatching a side effect of `kind` to `target`
+    pub fn emit(
+        id: String,
+        name: String,
+        kind: EmitKind,
+        target: String,
+        payload: HashMap<String, Expression>,
+    ) -> Self {
+        Self {
+            id,
+            name,
+            step_type: "emit".to_string(),
+            routes: None,
+            default: None,
+            next: None,
+            when: None,
+            else_branch: None,
+            timeout_ms: None,
+            details: StepDetails::Emit {
+                emit: kind,
+                target,
+                payload: Some(payload),
+                signals: Vec::new(),
+            },
+        }
+    }
+
     /// Add routes to the step
     pub fn with_routes(mut self, routes: Vec<Route>) -> Self {
         self.routes = Some(routes);
@@ -493,6 +757,24 @@ impl PipelineStep {
         self.next = Some(StepNext::StepId(next));
         self
     }
+
+    /// Only run the step when `when` holds
+    pub fn with_when(mut self, when: WhenBlock) -> Self {
+        self.when = Some(when);
+        self
+    }
+
+    /// Continue with `target` (a step ID or "end") when the step's `when` is false
+    pub fn with_skip_to(mut self, target: String) -> Self {
+        self.else_branch = Some(StepElse { skip_to: target });
+        self
+    }
+
+    /// Set the time budget of the step in milliseconds
+    pub fn with_timeout_ms(mut self, timeout_ms: u64) -> Self {
+        self.timeout_ms = Some(timeout_ms);
+        self
+    }
 }
 
 impl Route {
@@ -609,6 +891,8 @@ mod tests {
             default: None,
             next: None,
             when: None,
+            else_branch: None,
+            timeout_ms: None,
             details,
         };
 
@@ -644,6 +928,8 @@ mod tests {
             default: None,
             next: None,
             when: None,
+            else_branch: None,
+            timeout_ms: None,
             details,
         };
 
@@ -683,6 +969,28 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_set_step() {
+        let step = PipelineStep::set(
+            "derive".to_string(),
+            "Derive Variables".to_string(),
+            vec![VariableAssignment {
+                name: "risk_band".to_string(),
+                value: Expression::literal(Value::String("high".to_string())),
+            }],
+        )
+        .with_next("fraud_rules".to_string());
+
+        assert_eq!(step.step_type, "set");
+
+        if let StepDetails::Set { set } = &step.details {
+            assert_eq!(set.len(), 1);
+            assert_eq!(set[0].name, "risk_band");
+        } else {
+            panic!("Expected Set step");
+        }
+    }
+
     #[test]
     fn test_pipeline_serde() {
         let pipeline = Pipeline::new(
@@ -0,0 +1,250 @@
+//! Reason code catalog AST definitions
+//!
+//! A reason code catalog maps stable, machine-readable reason codes to
+//! localized messages. Ruleset conclusions reference codes from the catalog
+//! instead of embedding free-text reasons, and the engine resolves the
+//! customer-facing text for the requested locale when building a response.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// A catalog of reason codes and their localized messages
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReasonCatalog {
+    /// Locale used when a requested locale has no message
+    #[serde(default = "default_locale")]
+    pub default_locale: String,
+
+    /// Reason code definitions
+    #[serde(default)]
+    pub codes: Vec<ReasonCode>,
+}
+
+/// A single reason code definition
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReasonCode {
+    /// Stable code referenced from conclusions (e.g., "HIGH_RISK_DEVICE")
+    pub code: String,
+
+    /// Optional description for catalog maintainers
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+
+    /// Messages keyed by locale (e.g., "en", "zh-CN")
+    #[serde(default)]
+    pub messages: HashMap<String, ReasonMessage>,
+}
+
+/// Localized text for a reason code
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReasonMessage {
+    /// Text that is safe to show to the end customer
+    pub customer: String,
+
+    /// Text for analysts and internal tooling only
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub internal: Option<String>,
+}
+
+/// A reason code resolved against a catalog for a specific locale
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ResolvedReason {
+    /// The reason code
+    pub code: String,
+
+    /// Locale the message was resolved in
+    pub locale: String,
+
+    /// Customer-facing message
+    pub message: String,
+
+    /// Internal message, if defined
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub internal_message: Option<String>,
+}
+
+fn default_locale() -> String {
+    "en".to_string()
+}
+
+impl ReasonCatalog {
+    /// Create a new empty catalog with the "en" default locale
+    pub fn new() -> Self {
+        Self {
+            default_locale: default_locale(),
+            codes: Vec::new(),
+        }
+    }
+
+    /// Set the default locale
+    pub fn with_default_locale(mut self, locale: String) -> Self {
+        self.default_locale = locale;
+        self
+    }
+
+    /// Add a reason code
+    pub fn add_code(mut self, code: ReasonCode) -> Self {
+        self.codes.push(code);
+        self
+    }
+
+    /// Look up a reason code definition
+    pub fn get(&self, code: &str) -> Option<&ReasonCode> {
+        self.codes.iter().find(|c| c.code == code)
+    }
+
+    /// Check whether the catalog defines a code
+    pub fn contains(&self, code: &str) -> bool {
+        self.get(code).is_some()
+    }
+
+    /// Merge another catalog into this one.
+    ///
+    /// Codes from `other` replace existing codes with the same name.
+    pub fn merge(&mut self, other: ReasonCatalog) {
+        for code in other.codes {
+            match self.codes.iter_mut().find(|c| c.code == code.code) {
+                Some(existing) => *existing = code,
+                None => self.codes.push(code),
+            }
+        }
+    }
+
+    /// Resolve a code to its message in the requested locale.
+    ///
+    /// Falls back from the exact locale (e.g., "pt-BR") to its language
+    /// ("pt"), then to the catalog's default locale.
+    pub fn resolve(&self, code: &str, locale: Option<&str>) -> Option<ResolvedReason> {
+        let entry = self.get(code)?;
+
+        let mut candidates: Vec<String> = Vec::new();
+        if let Some(locale) = locale {
+            candidates.push(locale.to_string());
+            if let Some((language, _)) = locale.split_once(['-', '_']) {
+                candidates.push(language.to_string());
+            }
+        }
+        candidates.push(self.default_locale.clone());
+
+        candidates.into_iter().find_map(|candidate| {
+            entry
+                .messages
+                .get(&candidate)
+                .map(|message| ResolvedReason {
+                    code: entry.code.clone(),
+                    locale: candidate,
+                    message: message.customer.clone(),
+                    internal_message: message.internal.clone(),
+                })
+        })
+    }
+}
+
+impl Default for ReasonCatalog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ReasonCode {
+    /// Create a new reason code without messages
+    pub fn new(code: String) -> Self {
+        Self {
+            code,
+            description: None,
+            messages: HashMap::new(),
+        }
+    }
+
+    /// Set the description
+    pub fn with_description(mut self, description: String) -> Self {
+        self.description = Some(description);
+        self
+    }
+
+    /// Add a message for a locale
+    pub fn with_message(mut self, locale: String, message: ReasonMessage) -> Self {
+        self.messages.insert(locale, message);
+        self
+    }
+}
+
+impl ReasonMessage {
+    /// Create a customer-facing message
+    pub fn new(customer: String) -> Self {
+        Self {
+            customer,
+            internal: None,
+        }
+    }
+
+    /// Set the internal message
+    pub fn with_internal(mut self, internal: String) -> Self {
+        self.internal = Some(internal);
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn catalog() -> ReasonCatalog {
+        ReasonCatalog::new().add_code(
+            ReasonCode::new("HIGH_RISK_DEVICE".to_string())
+                .with_message(
+                    "en".to_string(),
+                    ReasonMessage::new("We could not verify your device.".to_string())
+                        .with_internal("Device fingerprint on deny list".to_string()),
+                )
+                .with_message(
+                    "pt".to_string(),
+                    ReasonMessage::new("Não foi possível verificar seu dispositivo.".to_string()),
+                ),
+        )
+    }
+
+    #[test]
+    fn test_resolve_locale_fallback() {
+        let catalog = catalog();
+
+        let exact = catalog.resolve("HIGH_RISK_DEVICE", Some("en")).unwrap();
+        assert_eq!(exact.locale, "en");
+        assert_eq!(
+            exact.internal_message.as_deref(),
+            Some("Device fingerprint on deny list")
+        );
+
+        let language = catalog.resolve("HIGH_RISK_DEVICE", Some("pt-BR")).unwrap();
+        assert_eq!(language.locale, "pt");
+        assert_eq!(language.internal_message, None);
+
+        let default = catalog.resolve("HIGH_RISK_DEVICE", Some("fr")).unwrap();
+        assert_eq!(default.locale, "en");
+        assert_eq!(default.message, "We could not verify your device.");
+
+        assert!(catalog.resolve("UNKNOWN", None).is_none());
+    }
+
+    #[test]
+    fn test_merge_replaces_existing_codes() {
+        let mut catalog = catalog();
+        catalog.merge(
+            ReasonCatalog::new()
+                .add_code(
+                    ReasonCode::new("HIGH_RISK_DEVICE".to_string()).with_message(
+                        "en".to_string(),
+                        ReasonMessage::new("Device check failed.".to_string()),
+                    ),
+                )
+                .add_code(ReasonCode::new("VELOCITY".to_string())),
+        );
+
+        assert_eq!(catalog.codes.len(), 2);
+        assert!(catalog.contains("VELOCITY"));
+        assert_eq!(
+            catalog.resolve("HIGH_RISK_DEVICE", Some("pt")).unwrap().message,
+            "Device check failed."
+        );
+    }
+}
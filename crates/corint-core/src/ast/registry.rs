@@ -3,6 +3,14 @@
 //! A Pipeline Registry defines the entry point routing for event processing.
 //! It provides a declarative, ordered list of pipeline matching rules that determine
 //! which pipeline should execute for a given event.
+//!
+//! A registry can also declare schedules: pipelines that run periodically
+//! (cron) over a set of entities selected from a datasource, instead of being
+//! triggered by incoming events.
+//!
+//! An entry can run a champion/challenger experiment: a share of the events
+//! it matches are also evaluated by a challenger pipeline, whose decision is
+//! recorded for comparison while the champion's decision is returned.
 
 use crate::ast::rule::WhenBlock;
 use serde::{Deserialize, Serialize};
@@ -16,6 +24,10 @@ pub struct PipelineRegistry {
 
     /// Ordered list of registry entries
     pub registry: Vec<RegistryEntry>,
+
+    /// Scheduled (cron-triggered) pipeline runs
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub schedules: Vec<ScheduleEntry>,
 }
 
 /// A single entry in the pipeline registry
@@ -26,6 +38,75 @@ pub struct RegistryEntry {
 
     /// When condition that determines if this entry matches the event
     pub when: WhenBlock,
+
+    /// Challenger pipeline evaluated alongside `pipeline` for part of the traffic
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub experiment: Option<Experiment>,
+}
+
+/// A champion/challenger experiment on a registry entry
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Experiment {
+    /// Experiment identifier, under which results are recorded
+    pub id: String,
+
+    /// Pipeline evaluated alongside the champion
+    pub challenger: String,
+
+    /// Share of the matched traffic (0 - 100) also sent to the challenger
+    pub traffic_percent: f64,
+
+    /// Event field the traffic is split on (e.g. `event.user.id`), so that
+    /// all events with the same value are in or out of the experiment
+    pub key: String,
+}
+
+/// A pipeline executed on a cron schedule for each entity returned by a query
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScheduleEntry {
+    /// Unique schedule identifier
+    pub id: String,
+
+    /// Pipeline ID to execute for each entity
+    pub pipeline: String,
+
+    /// Cron expression (e.g., "0 2 * * *" for daily at 02:00 UTC)
+    pub schedule: String,
+
+    /// Query selecting the entities to evaluate
+    pub entities: EntityQuery,
+
+    /// Number of entities evaluated concurrently
+    #[serde(default = "default_batch_size")]
+    pub batch_size: usize,
+
+    /// Human-readable description
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+/// Entity selection for a scheduled pipeline
+///
+/// Each returned row becomes the `event` data of one decision.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EntityQuery {
+    /// Datasource name (as configured for features)
+    pub datasource: String,
+
+    /// Table/entity to select from
+    pub entity: String,
+
+    /// Filter conditions, combined with AND (e.g., "last_login_at < '2024-01-01'")
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub when: Vec<String>,
+
+    /// Maximum number of entities per run
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<usize>,
+}
+
+fn default_batch_size() -> usize {
+    100
 }
 
 impl PipelineRegistry {
@@ -34,6 +115,7 @@ impl PipelineRegistry {
         Self {
             version: Some("0.1".to_string()),
             registry: Vec::new(),
+            schedules: Vec::new(),
         }
     }
 
@@ -54,6 +136,17 @@ impl PipelineRegistry {
         self.registry = entries;
         self
     }
+
+    /// Add a schedule
+    pub fn add_schedule(mut self, schedule: ScheduleEntry) -> Self {
+        self.schedules.push(schedule);
+        self
+    }
+
+    /// Find a schedule by ID
+    pub fn schedule(&self, id: &str) -> Option<&ScheduleEntry> {
+        self.schedules.iter().find(|s| s.id == id)
+    }
 }
 
 impl Default for PipelineRegistry {
@@ -65,7 +158,91 @@ impl Default for PipelineRegistry {
 impl RegistryEntry {
     /// Create a new registry entry
     pub fn new(pipeline: String, when: WhenBlock) -> Self {
-        Self { pipeline, when }
+        Self {
+            pipeline,
+            when,
+            experiment: None,
+        }
+    }
+
+    /// Run a champion/challenger experiment on the entry
+    pub fn with_experiment(mut self, experiment: Experiment) -> Self {
+        self.experiment = Some(experiment);
+        self
+    }
+}
+
+impl Experiment {
+    /// Send `traffic_percent` of the traffic, split on `key`, to `challenger`
+    pub fn new(
+        id: impl Into<String>,
+        challenger: impl Into<String>,
+        traffic_percent: f64,
+        key: impl Into<String>,
+    ) -> Self {
+        Self {
+            id: id.into(),
+            challenger: challenger.into(),
+            traffic_percent,
+            key: key.into(),
+        }
+    }
+
+    /// Whether events whose key field has `key_value` are sent to the challenger
+    ///
+    /// The split hashes the experiment ID with the key value (FNV-1a) into
+    /// one of 10,000 buckets, so it is stable across processes and restarts,
+    /// and independent between experiments.
+    pub fn includes(&self, key_value: &str) -> bool {
+        let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+        for byte in self.id.bytes().chain([b':']).chain(key_value.bytes()) {
+            hash ^= u64::from(byte);
+            hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+        }
+        ((hash % 10_000) as f64) < self.traffic_percent * 100.0
+    }
+}
+
+impl ScheduleEntry {
+    /// Create a new schedule
+    pub fn new(
+        id: impl Into<String>,
+        pipeline: impl Into<String>,
+        schedule: impl Into<String>,
+        entities: EntityQuery,
+    ) -> Self {
+        Self {
+            id: id.into(),
+            pipeline: pipeline.into(),
+            schedule: schedule.into(),
+            entities,
+            batch_size: default_batch_size(),
+            description: None,
+        }
+    }
+
+    /// Set the batch size
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size;
+        self
+    }
+}
+
+impl EntityQuery {
+    /// Create an entity query over a datasource table
+    pub fn new(datasource: impl Into<String>, entity: impl Into<String>) -> Self {
+        Self {
+            datasource: datasource.into(),
+            entity: entity.into(),
+            when: Vec::new(),
+            limit: None,
+        }
+    }
+
+    /// Add a filter condition
+    pub fn with_condition(mut self, condition: impl Into<String>) -> Self {
+        self.when.push(condition.into());
+        self
     }
 }
 
@@ -138,4 +315,44 @@ mod tests {
         let deserialized: PipelineRegistry = serde_json::from_str(&json).unwrap();
         assert_eq!(deserialized, registry);
     }
+
+    #[test]
+    fn test_experiment_traffic_split() {
+        let experiment = Experiment::new("new_velocity", "payment_v2", 20.0, "event.user.id");
+
+        let included = (0..10_000)
+            .filter(|i| experiment.includes(&format!("user_{}", i)))
+            .count();
+        assert!((1_800..2_200).contains(&included), "{} included", included);
+
+        // The same key always lands on the same side
+        let first = experiment.includes("user_42");
+        assert!((0..10).all(|_| experiment.includes("user_42") == first));
+
+        assert!(!Experiment::new("off", "p", 0.0, "k").includes("user_42"));
+        assert!(Experiment::new("all", "p", 100.0, "k").includes("user_42"));
+    }
+
+    #[test]
+    fn test_registry_schedules() {
+        let registry = PipelineRegistry::new().add_schedule(
+            ScheduleEntry::new(
+                "dormant_review",
+                "dormant_account_pipeline",
+                "0 2 * * *",
+                EntityQuery::new("postgres_main", "accounts")
+                    .with_condition("status == \"active\""),
+            )
+            .with_batch_size(50),
+        );
+
+        let schedule = registry.schedule("dormant_review").unwrap();
+        assert_eq!(schedule.pipeline, "dormant_account_pipeline");
+        assert_eq!(schedule.batch_size, 50);
+        assert!(registry.schedule("missing").is_none());
+
+        let json = serde_json::to_string(&registry).unwrap();
+        let deserialized: PipelineRegistry = serde_json::from_str(&json).unwrap();
+        assert_eq!(deserialized, registry);
+    }
 }
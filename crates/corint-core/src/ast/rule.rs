@@ -25,6 +25,19 @@ pub struct Rule {
     /// Score to add if rule is triggered
     pub score: i32,
 
+    /// Evaluation order within a ruleset: higher first, ties in ruleset order
+    /// (default: 0)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub priority: Option<i32>,
+
+    /// Start of the rule's effective window (RFC 3339, inclusive)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub effective_from: Option<String>,
+
+    /// End of the rule's effective window (RFC 3339, exclusive)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub effective_until: Option<String>,
+
     /// Optional metadata
     #[serde(skip_serializing_if = "Option::is_none")]
     pub metadata: Option<serde_json::Value>,
@@ -86,6 +99,9 @@ impl Rule {
             params: None,
             when,
             score,
+            priority: None,
+            effective_from: None,
+            effective_until: None,
             metadata: None,
         }
     }
@@ -102,6 +118,24 @@ impl Rule {
         self
     }
 
+    /// Set the evaluation priority within a ruleset
+    pub fn with_priority(mut self, priority: i32) -> Self {
+        self.priority = Some(priority);
+        self
+    }
+
+    /// Set the start of the effective window
+    pub fn with_effective_from(mut self, effective_from: String) -> Self {
+        self.effective_from = Some(effective_from);
+        self
+    }
+
+    /// Set the end of the effective window
+    pub fn with_effective_until(mut self, effective_until: String) -> Self {
+        self.effective_until = Some(effective_until);
+        self
+    }
+
     /// Set the metadata
     pub fn with_metadata(mut self, metadata: serde_json::Value) -> Self {
         self.metadata = Some(metadata);
@@ -5,6 +5,7 @@
 
 use crate::ast::Expression;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 /// A ruleset groups multiple rules and defines decision logic
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -29,9 +30,116 @@ pub struct Ruleset {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub description: Option<String>,
 
+    /// Start of the ruleset's effective window (RFC 3339, inclusive)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub effective_from: Option<String>,
+
+    /// End of the ruleset's effective window (RFC 3339, exclusive)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub effective_until: Option<String>,
+
     /// Optional metadata
     #[serde(skip_serializing_if = "Option::is_none")]
     pub metadata: Option<serde_json::Value>,
+
+    /// How the scores of the rules are combined (default: summed)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scoring: Option<ScoreAggregation>,
+
+    /// Stop evaluating rules once the score of the rules evaluated so far
+    /// reaches this value
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stop_on_score: Option<i32>,
+
+    /// Stop evaluating rules once the conclusion reaches this signal
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stop_on_signal: Option<Signal>,
+}
+
+/// How a ruleset combines the scores of its rules
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScoreStrategy {
+    /// Sum of the rule scores
+    #[default]
+    Sum,
+
+    /// Highest rule score
+    Max,
+
+    /// Sum of the rule scores, each multiplied by the weight of its rule
+    WeightedSum,
+}
+
+/// Score aggregation of a ruleset
+///
+/// The rule scores are combined by `strategy`, then capped to `min`/`max`.
+/// With `normalize`, the capped score is then scaled from `min..=max` (`min`
+/// defaulting to 0) to 0-100, so that rulesets with different score ranges
+/// can be compared.
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct ScoreAggregation {
+    /// How the rule scores are combined
+    #[serde(default)]
+    pub strategy: ScoreStrategy,
+
+    /// Weights of the rules for `weighted_sum`, by rule ID (default: 1.0)
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub weights: HashMap<String, f64>,
+
+    /// Lowest score of the ruleset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min: Option<i32>,
+
+    /// Highest score of the ruleset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max: Option<i32>,
+
+    /// Scale the score to 0-100 (requires `max`)
+    #[serde(default)]
+    pub normalize: bool,
+}
+
+impl ScoreAggregation {
+    /// Combine the scores of the rules, given as (rule ID, score)
+    pub fn aggregate(&self, scores: &[(&str, i32)]) -> i32 {
+        let combined = match self.strategy {
+            ScoreStrategy::Sum => scores.iter().map(|(_, score)| f64::from(*score)).sum(),
+            ScoreStrategy::Max => scores
+                .iter()
+                .map(|(_, score)| f64::from(*score))
+                .fold(None, |max: Option<f64>, score| Some(max.map_or(score, |m| m.max(score))))
+                .unwrap_or(0.0),
+            ScoreStrategy::WeightedSum => scores
+                .iter()
+                .map(|(rule_id, score)| {
+                    f64::from(*score) * self.weights.get(*rule_id).copied().unwrap_or(1.0)
+                })
+                .sum(),
+        };
+
+        let mut capped = combined;
+        if let Some(min) = self.min {
+            capped = capped.max(f64::from(min));
+        }
+        if let Some(max) = self.max {
+            capped = capped.min(f64::from(max));
+        }
+
+        let score = match (self.normalize, self.max) {
+            (true, Some(max)) => {
+                let min = f64::from(self.min.unwrap_or(0));
+                let range = f64::from(max) - min;
+                if range > 0.0 {
+                    (capped - min) / range * 100.0
+                } else {
+                    0.0
+                }
+            }
+            _ => capped,
+        };
+        score.round() as i32
+    }
 }
 
 /// A decision rule determines the signal and actions based on conditions
@@ -53,6 +161,10 @@ pub struct DecisionRule {
 
     /// Optional reason for this decision
     pub reason: Option<String>,
+
+    /// Optional reason code from the reason code catalog
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason_code: Option<String>,
 }
 
 /// Decision signal (the decision result)
@@ -86,7 +198,12 @@ impl Ruleset {
             rules: Vec::new(),
             conclusion: Vec::new(),
             description: None,
+            effective_from: None,
+            effective_until: None,
             metadata: None,
+            scoring: None,
+            stop_on_score: None,
+            stop_on_signal: None,
         }
     }
 
@@ -108,6 +225,36 @@ impl Ruleset {
         self
     }
 
+    /// Set the start of the effective window
+    pub fn with_effective_from(mut self, effective_from: String) -> Self {
+        self.effective_from = Some(effective_from);
+        self
+    }
+
+    /// Set the end of the effective window
+    pub fn with_effective_until(mut self, effective_until: String) -> Self {
+        self.effective_until = Some(effective_until);
+        self
+    }
+
+    /// Set how the scores of the rules are combined
+    pub fn with_scoring(mut self, scoring: ScoreAggregation) -> Self {
+        self.scoring = Some(scoring);
+        self
+    }
+
+    /// Stop evaluating rules once their score reaches `score`
+    pub fn with_stop_on_score(mut self, score: i32) -> Self {
+        self.stop_on_score = Some(score);
+        self
+    }
+
+    /// Stop evaluating rules once the conclusion reaches `signal`
+    pub fn with_stop_on_signal(mut self, signal: Signal) -> Self {
+        self.stop_on_signal = Some(signal);
+        self
+    }
+
     /// Add a rule ID
     pub fn add_rule(mut self, rule_id: String) -> Self {
         self.rules.push(rule_id);
@@ -142,6 +289,7 @@ impl DecisionRule {
             signal,
             actions: Vec::new(),
             reason: None,
+            reason_code: None,
         }
     }
 
@@ -153,6 +301,7 @@ impl DecisionRule {
             signal,
             actions: Vec::new(),
             reason: None,
+            reason_code: None,
         }
     }
 
@@ -168,6 +317,11 @@ impl DecisionRule {
         self
     }
 
+    /// Set the reason code
+    pub fn with_reason_code(mut self, reason_code: String) -> Self {
+        self.reason_code = Some(reason_code);
+        self
+    }
 }
 
 #[cfg(test)]
@@ -302,4 +456,47 @@ mod tests {
         let deserialized: Ruleset = serde_json::from_str(&json).unwrap();
         assert_eq!(deserialized, ruleset);
     }
+
+    #[test]
+    fn test_score_aggregation() {
+        let scores = [("velocity", 60), ("new_device", 30), ("geo_mismatch", 0)];
+
+        assert_eq!(ScoreAggregation::default().aggregate(&scores), 90);
+        assert_eq!(ScoreAggregation::default().aggregate(&[]), 0);
+
+        let max = ScoreAggregation {
+            strategy: ScoreStrategy::Max,
+            ..Default::default()
+        };
+        assert_eq!(max.aggregate(&scores), 60);
+        assert_eq!(max.aggregate(&[("refund", -20)]), -20);
+
+        let mut weighted = ScoreAggregation {
+            strategy: ScoreStrategy::WeightedSum,
+            ..Default::default()
+        };
+        weighted.weights.insert("velocity".to_string(), 0.5);
+        weighted.weights.insert("new_device".to_string(), 2.0);
+        assert_eq!(weighted.aggregate(&scores), 90);
+
+        let capped = ScoreAggregation {
+            max: Some(75),
+            ..Default::default()
+        };
+        assert_eq!(capped.aggregate(&scores), 75);
+
+        let normalized = ScoreAggregation {
+            max: Some(200),
+            normalize: true,
+            ..Default::default()
+        };
+        assert_eq!(normalized.aggregate(&scores), 45);
+        assert_eq!(normalized.aggregate(&[("velocity", 500)]), 100);
+
+        let scoring: ScoreAggregation =
+            serde_json::from_str(r#"{"strategy": "weighted_sum", "weights": {"velocity": 2}}"#)
+                .unwrap();
+        assert_eq!(scoring.strategy, ScoreStrategy::WeightedSum);
+        assert_eq!(scoring.aggregate(&scores), 150);
+    }
 }
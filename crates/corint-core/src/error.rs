@@ -16,6 +16,9 @@ pub enum CoreError {
 
     #[error("Invalid value: {0}")]
     InvalidValue(String),
+
+    #[error("Invalid program artifact: {0}")]
+    InvalidArtifact(String),
 }
 
 pub type Result<T> = std::result::Result<T, CoreError>;
@@ -0,0 +1,294 @@
+//! Binary format of compiled programs
+//!
+//! Programs compiled ahead of time are shipped as artifacts that runtimes
+//! load without the parser and compiler. An artifact holds one or more
+//! programs behind a versioned header:
+//!
+//! ```text
+//! magic "CRIR" | format version (u16 LE) | program count (varint) | programs
+//! ```
+//!
+//! Each program is encoded as the tree of its serde representation, with
+//! tagged values and varint lengths. The tree is self-describing, so untagged
+//! and defaulted fields decode as they do from JSON. Loaders reject artifacts
+//! of any other format version: compile them again with the matching
+//! compiler.
+
+use crate::error::{CoreError, Result};
+use crate::ir::Program;
+use serde_json::{Map, Number, Value as Tree};
+
+/// Magic bytes opening every artifact
+pub const MAGIC: &[u8; 4] = b"CRIR";
+
+/// Version of the format written, and the only one read
+pub const FORMAT_VERSION: u16 = 1;
+
+/// Deepest nesting of values decoded, bounding recursion on corrupt input
+const MAX_DEPTH: usize = 256;
+
+const TAG_NULL: u8 = 0;
+const TAG_FALSE: u8 = 1;
+const TAG_TRUE: u8 = 2;
+const TAG_UNSIGNED: u8 = 3;
+const TAG_SIGNED: u8 = 4;
+const TAG_FLOAT: u8 = 5;
+const TAG_STRING: u8 = 6;
+const TAG_ARRAY: u8 = 7;
+const TAG_OBJECT: u8 = 8;
+
+/// Encode programs into an artifact
+pub fn encode(programs: &[Program]) -> Result<Vec<u8>> {
+    let mut bytes = Vec::new();
+    bytes.extend_from_slice(MAGIC);
+    bytes.extend_from_slice(&FORMAT_VERSION.to_le_bytes());
+    write_varint(&mut bytes, programs.len() as u64);
+    for program in programs {
+        let tree = serde_json::to_value(program)
+            .map_err(|e| invalid(format!("cannot encode program: {}", e)))?;
+        write_tree(&mut bytes, &tree);
+    }
+    Ok(bytes)
+}
+
+/// Decode the programs of an artifact
+pub fn decode(bytes: &[u8]) -> Result<Vec<Program>> {
+    let mut reader = Reader { bytes, position: 0 };
+    if reader.take(MAGIC.len())? != MAGIC {
+        return Err(invalid("not a compiled program artifact".to_string()));
+    }
+    let version = u16::from_le_bytes(reader.take(2)?.try_into().expect("two bytes"));
+    if version != FORMAT_VERSION {
+        return Err(invalid(format!(
+            "unsupported format version {} (expected {})",
+            version, FORMAT_VERSION
+        )));
+    }
+
+    let count = reader.length()?;
+    let mut programs = Vec::with_capacity(count);
+    for _ in 0..count {
+        let tree = reader.tree(0)?;
+        let program = serde_json::from_value(tree)
+            .map_err(|e| invalid(format!("malformed program: {}", e)))?;
+        programs.push(program);
+    }
+    if reader.position != bytes.len() {
+        return Err(invalid("trailing bytes after the last program".to_string()));
+    }
+    Ok(programs)
+}
+
+fn invalid(message: String) -> CoreError {
+    CoreError::InvalidArtifact(message)
+}
+
+fn write_varint(bytes: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        bytes.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    bytes.push(value as u8);
+}
+
+fn write_string(bytes: &mut Vec<u8>, value: &str) {
+    write_varint(bytes, value.len() as u64);
+    bytes.extend_from_slice(value.as_bytes());
+}
+
+fn write_tree(bytes: &mut Vec<u8>, tree: &Tree) {
+    match tree {
+        Tree::Null => bytes.push(TAG_NULL),
+        Tree::Bool(false) => bytes.push(TAG_FALSE),
+        Tree::Bool(true) => bytes.push(TAG_TRUE),
+        Tree::Number(number) => {
+            if let Some(value) = number.as_u64() {
+                bytes.push(TAG_UNSIGNED);
+                write_varint(bytes, value);
+            } else if let Some(value) = number.as_i64() {
+                bytes.push(TAG_SIGNED);
+                // Zigzag, so small negative numbers stay short
+                write_varint(bytes, ((value << 1) ^ (value >> 63)) as u64);
+            } else {
+                bytes.push(TAG_FLOAT);
+                let value = number.as_f64().unwrap_or_default();
+                bytes.extend_from_slice(&value.to_le_bytes());
+            }
+        }
+        Tree::String(value) => {
+            bytes.push(TAG_STRING);
+            write_string(bytes, value);
+        }
+        Tree::Array(items) => {
+            bytes.push(TAG_ARRAY);
+            write_varint(bytes, items.len() as u64);
+            for item in items {
+                write_tree(bytes, item);
+            }
+        }
+        Tree::Object(fields) => {
+            bytes.push(TAG_OBJECT);
+            write_varint(bytes, fields.len() as u64);
+            for (key, value) in fields {
+                write_string(bytes, key);
+                write_tree(bytes, value);
+            }
+        }
+    }
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8]> {
+        let end = self
+            .position
+            .checked_add(len)
+            .filter(|end| *end <= self.bytes.len())
+            .ok_or_else(|| invalid("truncated artifact".to_string()))?;
+        let taken = &self.bytes[self.position..end];
+        self.position = end;
+        Ok(taken)
+    }
+
+    fn varint(&mut self) -> Result<u64> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = self.take(1)?[0];
+            value |= u64::from(byte & 0x7f) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(invalid("varint too long".to_string()))
+    }
+
+    /// A count of items, each taking at least one byte
+    fn length(&mut self) -> Result<usize> {
+        let len = self.varint()?;
+        usize::try_from(len)
+            .ok()
+            .filter(|len| *len <= self.bytes.len() - self.position)
+            .ok_or_else(|| invalid("truncated artifact".to_string()))
+    }
+
+    fn string(&mut self) -> Result<String> {
+        let len = self.length()?;
+        let bytes = self.take(len)?;
+        String::from_utf8(bytes.to_vec()).map_err(|_| invalid("invalid UTF-8 string".to_string()))
+    }
+
+    fn tree(&mut self, depth: usize) -> Result<Tree> {
+        if depth > MAX_DEPTH {
+            return Err(invalid("values nested too deeply".to_string()));
+        }
+        let tree = match self.take(1)?[0] {
+            TAG_NULL => Tree::Null,
+            TAG_FALSE => Tree::Bool(false),
+            TAG_TRUE => Tree::Bool(true),
+            TAG_UNSIGNED => Tree::from(self.varint()?),
+            TAG_SIGNED => {
+                let value = self.varint()?;
+                Tree::from((value >> 1) as i64 ^ -((value & 1) as i64))
+            }
+            TAG_FLOAT => {
+                let value = f64::from_le_bytes(self.take(8)?.try_into().expect("eight bytes"));
+                Number::from_f64(value)
+                    .map(Tree::Number)
+                    .ok_or_else(|| invalid("non-finite number".to_string()))?
+            }
+            TAG_STRING => Tree::String(self.string()?),
+            TAG_ARRAY => {
+                let len = self.length()?;
+                let mut items = Vec::with_capacity(len);
+                for _ in 0..len {
+                    items.push(self.tree(depth + 1)?);
+                }
+                Tree::Array(items)
+            }
+            TAG_OBJECT => {
+                let len = self.length()?;
+                let mut fields = Map::new();
+                for _ in 0..len {
+                    let key = self.string()?;
+                    fields.insert(key, self.tree(depth + 1)?);
+                }
+                Tree::Object(fields)
+            }
+            tag => return Err(invalid(format!("unknown value tag {}", tag))),
+        };
+        Ok(tree)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ast::Operator;
+    use crate::ir::{Instruction, ProgramMetadata};
+    use crate::Value;
+
+    fn program() -> Program {
+        Program::new(
+            vec![
+                Instruction::LoadField {
+                    path: vec!["event".to_string(), "amount".to_string()],
+                },
+                Instruction::LoadConst {
+                    value: Value::Number(1000.5),
+                },
+                Instruction::Compare { op: Operator::Gt },
+                Instruction::JumpIfFalse { offset: -3 },
+                Instruction::LoadConst {
+                    value: Value::Array(vec![Value::Null, Value::Bool(true), Value::Number(-2.0)]),
+                },
+                Instruction::AddScore { value: -40 },
+                Instruction::Return,
+            ],
+            ProgramMetadata::for_rule("large_amount".to_string())
+                .with_name("Large amount".to_string())
+                .with_custom(
+                    "conditions".to_string(),
+                    "event.amount > 1000.5".to_string(),
+                ),
+        )
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let programs = vec![program(), Program::new(vec![], ProgramMetadata::default())];
+
+        let bytes = encode(&programs).unwrap();
+
+        assert_eq!(&bytes[..4], MAGIC);
+        assert_eq!(decode(&bytes).unwrap(), programs);
+        assert!(bytes.len() < serde_json::to_vec(&programs).unwrap().len());
+    }
+
+    #[test]
+    fn test_rejects_other_versions_and_corrupt_artifacts() {
+        let bytes = encode(&[program()]).unwrap();
+
+        let mut newer = bytes.clone();
+        newer[4..6].copy_from_slice(&(FORMAT_VERSION + 1).to_le_bytes());
+        assert!(decode(&newer)
+            .unwrap_err()
+            .to_string()
+            .contains("unsupported format version 2"));
+
+        assert!(decode(b"{\"instructions\": []}").is_err());
+        assert!(decode(&bytes[..bytes.len() - 1]).is_err());
+        let mut trailing = bytes.clone();
+        trailing.push(TAG_NULL);
+        assert!(decode(&trailing).is_err());
+
+        // Counts larger than the artifact fail instead of allocating
+        let mut huge = MAGIC.to_vec();
+        huge.extend_from_slice(&FORMAT_VERSION.to_le_bytes());
+        write_varint(&mut huge, u64::MAX);
+        assert!(decode(&huge).is_err());
+    }
+}
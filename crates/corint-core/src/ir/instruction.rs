@@ -2,7 +2,7 @@
 //!
 //! Low-level instructions for the CORINT runtime execution engine.
 
-use crate::ast::{Expression, Operator, Signal};
+use crate::ast::{AggregateOp, EmitKind, Expression, Operator, QuantifierOp, ScoreAggregation, Signal};
 use crate::Value;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -118,6 +118,32 @@ pub enum Instruction {
         fallback: Option<Value>,
     },
 
+    /// Queue a side effect, dispatched once the decision is final
+    /// Pops one value per payload field, the last field on top
+    Emit {
+        /// Emit step ID
+        step_id: String,
+        /// Kind of side effect
+        kind: EmitKind,
+        /// Webhook URL, Kafka topic or case queue
+        target: String,
+        /// Payload field names, in the order their values were pushed
+        fields: Vec<String>,
+        /// Final signals the side effect is emitted for (all when empty)
+        signals: Vec<Signal>,
+    },
+
+    /// Call the handler of a custom step type, registered on the executor
+    /// Pops one value per argument, the last on top, and pushes the result
+    CallCustom {
+        /// Name the handler is registered under
+        handler: String,
+        /// Custom step ID
+        step_id: String,
+        /// Argument names, in the order their values were pushed
+        args: Vec<String>,
+    },
+
     // ===== Decision Making =====
     /// Set the score
     SetScore {
@@ -131,6 +157,14 @@ pub enum Instruction {
         value: i32,
     },
 
+    /// Replace the summed scores of a ruleset's rules with their aggregation
+    AggregateScore {
+        /// Rules whose scores are combined
+        rules: Vec<String>,
+        /// How the scores are combined
+        scoring: ScoreAggregation,
+    },
+
     /// Set the signal (decision result)
     SetSignal {
         /// Signal to emit (approve/decline/review/hold/pass)
@@ -143,6 +177,12 @@ pub enum Instruction {
         reason: String,
     },
 
+    /// Set the reason code for the decision (resolved against the reason catalog)
+    SetReasonCode {
+        /// Code defined in the reason code catalog
+        code: String,
+    },
+
     /// Set user-defined actions
     SetActions {
         /// List of action identifiers (e.g., ["KYC", "OTP", "BLOCK_DEVICE"])
@@ -204,6 +244,39 @@ pub enum Instruction {
         name: String,
     },
 
+    /// Declare a request-scoped variable, initialized to the top of stack
+    ///
+    /// The variable is stored under `vars.<name>`; declaring it twice in the
+    /// same execution is an error.
+    Declare {
+        /// Variable name (without the `vars.` prefix)
+        name: String,
+    },
+
+    /// Store top of stack to a variable declared earlier in the execution
+    Assign {
+        /// Variable name (without the `vars.` prefix)
+        name: String,
+    },
+
+    // ===== Shared Slots =====
+    /// Load the value of a shared slot computed earlier in the request
+    ///
+    /// If the slot has a value, pushes it and jumps by `offset`, past the
+    /// instructions computing it; otherwise falls through to compute it.
+    LoadSlot {
+        /// Slot name
+        slot: String,
+        /// Offset to jump when the slot has a value
+        offset: isize,
+    },
+
+    /// Keep the top of stack as the value of a shared slot, without popping it
+    StoreSlot {
+        /// Slot name
+        slot: String,
+    },
+
     // ===== List Operations =====
     /// Check if a value exists in a configured list
     /// Pops value from stack, pushes boolean result
@@ -213,6 +286,100 @@ pub enum Instruction {
         /// Negate the result (for "not in list")
         negate: bool,
     },
+
+    // ===== Array Operations =====
+    /// Evaluate a predicate against each element of an array
+    /// Pops the array from the stack, pushes boolean result
+    IterateArray {
+        /// Quantifier (any_of / all_of)
+        op: QuantifierOp,
+        /// Name the current element is bound to while the predicate runs
+        binding: String,
+        /// Predicate instructions, each run leaves one value on the stack
+        predicate: Vec<Instruction>,
+    },
+
+    /// Aggregate the elements of an array (count, sum, avg, min, max)
+    /// Pops the array from the stack, pushes the aggregate value
+    AggregateArray {
+        /// Aggregation function
+        op: AggregateOp,
+        /// Field path within each element (empty = the elements themselves)
+        path: Vec<String>,
+    },
+}
+
+impl Instruction {
+    /// Get the name of this instruction, without its operands
+    pub fn opcode(&self) -> &'static str {
+        match self {
+            Instruction::LoadField { .. } => "LoadField",
+            Instruction::LoadConst { .. } => "LoadConst",
+            Instruction::LoadResult { .. } => "LoadResult",
+            Instruction::BinaryOp { .. } => "BinaryOp",
+            Instruction::Compare { .. } => "Compare",
+            Instruction::UnaryOp { .. } => "UnaryOp",
+            Instruction::Jump { .. } => "Jump",
+            Instruction::JumpIfTrue { .. } => "JumpIfTrue",
+            Instruction::JumpIfFalse { .. } => "JumpIfFalse",
+            Instruction::Return => "Return",
+            Instruction::CheckEventType { .. } => "CheckEventType",
+            Instruction::CallFeature { .. } => "CallFeature",
+            Instruction::CallService { .. } => "CallService",
+            Instruction::CallExternal { .. } => "CallExternal",
+            Instruction::Emit { .. } => "Emit",
+            Instruction::CallCustom { .. } => "CallCustom",
+            Instruction::SetScore { .. } => "SetScore",
+            Instruction::AddScore { .. } => "AddScore",
+            Instruction::AggregateScore { .. } => "AggregateScore",
+            Instruction::SetSignal { .. } => "SetSignal",
+            Instruction::SetReason { .. } => "SetReason",
+            Instruction::SetReasonCode { .. } => "SetReasonCode",
+            Instruction::SetActions { .. } => "SetActions",
+            Instruction::MarkRuleTriggered { .. } => "MarkRuleTriggered",
+            Instruction::MarkBranchExecuted { .. } => "MarkBranchExecuted",
+            Instruction::MarkStepExecuted { .. } => "MarkStepExecuted",
+            Instruction::CallRuleset { .. } => "CallRuleset",
+            Instruction::Dup => "Dup",
+            Instruction::Pop => "Pop",
+            Instruction::Swap => "Swap",
+            Instruction::Store { .. } => "Store",
+            Instruction::Load { .. } => "Load",
+            Instruction::Declare { .. } => "Declare",
+            Instruction::Assign { .. } => "Assign",
+            Instruction::LoadSlot { .. } => "LoadSlot",
+            Instruction::StoreSlot { .. } => "StoreSlot",
+            Instruction::ListLookup { .. } => "ListLookup",
+            Instruction::IterateArray { .. } => "IterateArray",
+            Instruction::AggregateArray { .. } => "AggregateArray",
+        }
+    }
+
+    /// Whether this instruction may appear in the predicate of an `IterateArray`
+    ///
+    /// Predicates are evaluated once per element by a reduced interpreter that
+    /// only covers expressions over fields, variables and lists; the compiler
+    /// rejects quantifiers whose predicate needs anything else.
+    pub fn allowed_in_predicate(&self) -> bool {
+        matches!(
+            self,
+            Instruction::LoadField { .. }
+                | Instruction::LoadConst { .. }
+                | Instruction::Load { .. }
+                | Instruction::BinaryOp { .. }
+                | Instruction::Compare { .. }
+                | Instruction::UnaryOp { .. }
+                | Instruction::Jump { .. }
+                | Instruction::JumpIfTrue { .. }
+                | Instruction::JumpIfFalse { .. }
+                | Instruction::Dup
+                | Instruction::Pop
+                | Instruction::Swap
+                | Instruction::ListLookup { .. }
+                | Instruction::IterateArray { .. }
+                | Instruction::AggregateArray { .. }
+        )
+    }
 }
 
 /// Type of feature to extract
@@ -238,10 +405,28 @@ pub enum FeatureType {
 
     /// Percentile calculation
     Percentile {
-        /// Percentile value (0.0 - 1.0)
+        /// Percentile value (0 - 100)
         p: f64,
     },
 
+    /// Median (average of the two middle values for an even count)
+    Median,
+
+    /// Most frequent value
+    Mode,
+
+    /// Sum with each value weighted by `0.5^(age / half_life)`
+    DecayedSum {
+        /// Half-life in seconds
+        half_life_secs: u64,
+    },
+
+    /// Count with each event weighted by `0.5^(age / half_life)`
+    DecayedCount {
+        /// Half-life in seconds
+        half_life_secs: u64,
+    },
+
     /// Standard deviation
     StdDev,
 
@@ -300,6 +485,10 @@ impl FeatureType {
             FeatureType::Min => "min",
             FeatureType::Max => "max",
             FeatureType::Percentile { .. } => "percentile",
+            FeatureType::Median => "median",
+            FeatureType::Mode => "mode",
+            FeatureType::DecayedSum { .. } => "decayed_sum",
+            FeatureType::DecayedCount { .. } => "decayed_count",
             FeatureType::StdDev => "stddev",
             FeatureType::Variance => "variance",
         }
@@ -431,7 +620,8 @@ mod tests {
         assert_eq!(FeatureType::CountDistinct.name(), "count_distinct");
         assert_eq!(FeatureType::Sum.name(), "sum");
         assert_eq!(FeatureType::Avg.name(), "avg");
-        assert_eq!(FeatureType::Percentile { p: 0.95 }.name(), "percentile");
+        assert_eq!(FeatureType::Percentile { p: 95.0 }.name(), "percentile");
+        assert_eq!(FeatureType::Mode.name(), "mode");
 
         assert!(!FeatureType::Count.is_aggregate());
         assert!(FeatureType::Sum.is_aggregate());
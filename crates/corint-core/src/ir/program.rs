@@ -2,7 +2,8 @@
 //!
 //! A program is a sequence of IR instructions with associated metadata.
 
-use crate::ir::Instruction;
+use crate::error::{CoreError, Result};
+use crate::ir::{binary, Instruction};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
@@ -42,6 +43,56 @@ pub struct ProgramMetadata {
 
     /// Version of the compiler that generated this
     pub compiler_version: String,
+
+    /// Source conditions of instruction ranges, for traces and errors
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub source_map: Vec<SourceSpan>,
+}
+
+/// Source condition of a range of instructions
+///
+/// Spans are recorded by the code generator; `file` and `line` are filled in
+/// when the program is compiled from a file.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SourceSpan {
+    /// First instruction of the range
+    pub start: usize,
+
+    /// End of the range (exclusive)
+    pub end: usize,
+
+    /// Condition evaluated by the range, e.g. `event.amount > 1000`
+    pub text: String,
+
+    /// File the definition was compiled from
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+
+    /// Line of the condition in `file` (1-based)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub line: Option<usize>,
+}
+
+impl SourceSpan {
+    /// Create a span over instructions `start..end`
+    pub fn new(start: usize, end: usize, text: impl Into<String>) -> Self {
+        Self {
+            start,
+            end,
+            text: text.into(),
+            file: None,
+            line: None,
+        }
+    }
+
+    /// `file:line` of the condition, when known
+    pub fn location(&self) -> Option<String> {
+        match (&self.file, self.line) {
+            (Some(file), Some(line)) => Some(format!("{}:{}", file, line)),
+            (None, Some(line)) => Some(format!("line {}", line)),
+            _ => None,
+        }
+    }
 }
 
 impl Program {
@@ -86,6 +137,65 @@ impl Program {
     pub fn get_instruction(&self, index: usize) -> Option<&Instruction> {
         self.instructions.get(index)
     }
+
+    /// Innermost source span covering the instruction at `pc`
+    pub fn source_span(&self, pc: usize) -> Option<&SourceSpan> {
+        self.metadata
+            .source_map
+            .iter()
+            .filter(|span| span.start <= pc && pc < span.end)
+            .min_by_key(|span| span.end - span.start)
+    }
+
+    /// Names of the features loaded with `features.<name>`, in first-use order
+    pub fn feature_references(&self) -> Vec<String> {
+        let mut names = Vec::new();
+        let decision = self.decision_instructions.iter().flatten();
+        collect_feature_references(self.instructions.iter().chain(decision), &mut names);
+        names
+    }
+
+    /// Encode the program into a binary artifact (see [`crate::ir::binary`])
+    pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        binary::encode(std::slice::from_ref(self))
+    }
+
+    /// Load a program from a binary artifact holding exactly one
+    pub fn load_from_bytes(bytes: &[u8]) -> Result<Self> {
+        let mut programs = binary::decode(bytes)?;
+        if programs.len() != 1 {
+            return Err(CoreError::InvalidArtifact(format!(
+                "expected one program, found {}",
+                programs.len()
+            )));
+        }
+        Ok(programs.remove(0))
+    }
+
+    /// Load all programs of a binary artifact, such as one written by
+    /// `Compiler::compile_to_file`
+    pub fn load_all_from_bytes(bytes: &[u8]) -> Result<Vec<Self>> {
+        binary::decode(bytes)
+    }
+}
+
+fn collect_feature_references<'a>(
+    instructions: impl Iterator<Item = &'a Instruction>,
+    names: &mut Vec<String>,
+) {
+    for instruction in instructions {
+        match instruction {
+            Instruction::LoadField { path }
+                if path.len() == 2 && path[0] == "features" && !names.contains(&path[1]) =>
+            {
+                names.push(path[1].clone());
+            }
+            Instruction::IterateArray { predicate, .. } => {
+                collect_feature_references(predicate.iter(), names);
+            }
+            _ => {}
+        }
+    }
 }
 
 impl ProgramMetadata {
@@ -98,6 +208,7 @@ impl ProgramMetadata {
             description: None,
             custom: HashMap::new(),
             compiler_version: env!("CARGO_PKG_VERSION").to_string(),
+            source_map: Vec::new(),
         }
     }
 
@@ -110,6 +221,7 @@ impl ProgramMetadata {
             description: None,
             custom: HashMap::new(),
             compiler_version: env!("CARGO_PKG_VERSION").to_string(),
+            source_map: Vec::new(),
         }
     }
 
@@ -122,6 +234,7 @@ impl ProgramMetadata {
             description: None,
             custom: HashMap::new(),
             compiler_version: env!("CARGO_PKG_VERSION").to_string(),
+            source_map: Vec::new(),
         }
     }
 
@@ -142,6 +255,19 @@ impl ProgramMetadata {
         self.custom.insert(key, value);
         self
     }
+
+    /// Move the source map after instructions were removed or inserted
+    ///
+    /// `new_positions[pc]` is the new position of old instruction `pc`, with
+    /// one more entry for the end of the program. Spans left empty are dropped.
+    pub fn remap_source_map(&mut self, new_positions: &[usize]) {
+        let last = new_positions.len().saturating_sub(1);
+        for span in &mut self.source_map {
+            span.start = new_positions[span.start.min(last)];
+            span.end = new_positions[span.end.min(last)];
+        }
+        self.source_map.retain(|span| span.start < span.end);
+    }
 }
 
 impl Default for ProgramMetadata {
@@ -153,6 +279,7 @@ impl Default for ProgramMetadata {
             description: None,
             custom: HashMap::new(),
             compiler_version: env!("CARGO_PKG_VERSION").to_string(),
+            source_map: Vec::new(),
         }
     }
 }
@@ -295,6 +422,30 @@ mod tests {
         assert_eq!(deserialized, program);
     }
 
+    #[test]
+    fn test_program_bytes() {
+        let program = Program::new(
+            vec![
+                Instruction::LoadConst {
+                    value: Value::Number(42.0),
+                },
+                Instruction::Return,
+            ],
+            ProgramMetadata::for_rule("test_rule".to_string()),
+        );
+
+        let bytes = program.to_bytes().unwrap();
+        assert_eq!(Program::load_from_bytes(&bytes).unwrap(), program);
+        assert_eq!(
+            Program::load_all_from_bytes(&bytes).unwrap(),
+            vec![program.clone()]
+        );
+
+        // An artifact of several programs is not a single program
+        let both = binary::encode(&[program.clone(), program]).unwrap();
+        assert!(Program::load_from_bytes(&both).is_err());
+    }
+
     #[test]
     fn test_complex_program() {
         // Simulate a compiled rule program
@@ -336,4 +487,61 @@ mod tests {
         assert_eq!(program.instruction_count(), 8);
         assert_eq!(program.metadata.source_type, "rule");
     }
+
+    #[test]
+    fn test_feature_references() {
+        use crate::ast::QuantifierOp;
+
+        let load = |path: &[&str]| Instruction::LoadField {
+            path: path.iter().map(|p| p.to_string()).collect(),
+        };
+        let program = Program::new_with_decision(
+            vec![
+                load(&["features", "txn_count_24h"]),
+                load(&["event", "amount"]),
+                load(&["features", "txn_count_24h"]),
+                Instruction::IterateArray {
+                    op: QuantifierOp::AnyOf,
+                    binding: "item".to_string(),
+                    predicate: vec![load(&["features", "device_count_7d"])],
+                },
+            ],
+            ProgramMetadata::for_rule("test".to_string()),
+            vec![load(&["features", "risk_score"])],
+        );
+
+        assert_eq!(
+            program.feature_references(),
+            vec!["txn_count_24h", "device_count_7d", "risk_score"]
+        );
+    }
+
+    #[test]
+    fn test_source_map() {
+        let mut metadata = ProgramMetadata::for_rule("test".to_string());
+        metadata.source_map = vec![
+            SourceSpan::new(0, 4, "event.a > 1 && event.b < 2"),
+            SourceSpan::new(0, 2, "event.a > 1"),
+            SourceSpan::new(2, 4, "event.b < 2"),
+        ];
+        let mut program = Program::new(vec![Instruction::Return; 6], metadata);
+
+        assert_eq!(program.source_span(1).unwrap().text, "event.a > 1");
+        assert_eq!(program.source_span(3).unwrap().text, "event.b < 2");
+        assert!(program.source_span(4).is_none());
+
+        // Instructions 0 and 1 removed: the first condition's span is dropped
+        program.metadata.remap_source_map(&[0, 0, 0, 1, 2, 3, 4]);
+        assert_eq!(program.metadata.source_map.len(), 2);
+        assert_eq!(
+            program.metadata.source_map[1],
+            SourceSpan::new(0, 2, "event.b < 2")
+        );
+
+        let mut span = SourceSpan::new(0, 1, "event.a > 1");
+        assert_eq!(span.location(), None);
+        span.file = Some("rules/a.yaml".to_string());
+        span.line = Some(7);
+        assert_eq!(span.location().as_deref(), Some("rules/a.yaml:7"));
+    }
 }
@@ -0,0 +1,282 @@
+//! Decision table parser
+//!
+//! Parses YAML decision tables into DecisionTable AST nodes.
+
+use crate::error::{ParseError, Result};
+use crate::expression_parser::ExpressionParser;
+use crate::yaml_parser::YamlParser;
+use corint_core::ast::{DecisionTable, DecisionTableRow, Expression, HitPolicy, Operator};
+use corint_core::Value;
+use serde_yaml::Value as YamlValue;
+
+/// Operators a cell may start with to test its input with
+const CELL_OPERATORS: &[&str] = &[
+    "==",
+    "!=",
+    ">=",
+    "<=",
+    ">",
+    "<",
+    "in ",
+    "not in ",
+    "contains ",
+    "starts_with ",
+    "ends_with ",
+    "regex ",
+];
+
+/// Decision table parser
+pub struct DecisionTableParser;
+
+impl DecisionTableParser {
+    /// Parse a decision table from YAML string
+    pub fn parse(yaml_str: &str) -> Result<DecisionTable> {
+        let yaml = YamlParser::parse(yaml_str)?;
+        Self::parse_from_yaml(&yaml)
+    }
+
+    /// Parse a decision table from YAML value
+    ///
+    /// Expected format:
+    /// ```yaml
+    /// decision_table:
+    ///   id: shipping_risk
+    ///   name: Shipping Risk
+    ///   hit_policy: first            # first | priority | collect
+    ///   inputs:
+    ///     - event.country
+    ///     - event.amount
+    ///   rows:
+    ///     - id: sanctioned
+    ///       cells: ['in ["KP", "IR"]', -]
+    ///       score: 100
+    ///     - id: large_foreign
+    ///       cells: ['!= "US"', '> 1000']
+    ///       score: 60
+    /// ```
+    ///
+    /// A cell starting with an operator tests its input with it; `-` (or an
+    /// empty cell) matches any value; any other value is compared for
+    /// equality, so `US` in the country column means `event.country == "US"`.
+    pub fn parse_from_yaml(yaml: &YamlValue) -> Result<DecisionTable> {
+        let table_obj = yaml
+            .get("decision_table")
+            .ok_or_else(|| ParseError::MissingField {
+                field: "decision_table".to_string(),
+            })?;
+
+        let id = YamlParser::get_string(table_obj, "id")?;
+        let name = YamlParser::get_string(table_obj, "name")?;
+        let description = YamlParser::get_optional_string(table_obj, "description");
+
+        let hit_policy = match YamlParser::get_optional_string(table_obj, "hit_policy").as_deref() {
+            None | Some("first") => HitPolicy::First,
+            Some("priority") => HitPolicy::Priority,
+            Some("collect") => HitPolicy::Collect,
+            Some(other) => {
+                return Err(ParseError::InvalidValue {
+                    field: "decision_table.hit_policy".to_string(),
+                    message: format!(
+                        "Unknown hit policy '{}', expected one of: first, priority, collect",
+                        other
+                    ),
+                })
+            }
+        };
+
+        let input_sources: Vec<String> = YamlParser::get_array(table_obj, "inputs")
+            .map_err(|_| ParseError::MissingField {
+                field: "decision_table.inputs".to_string(),
+            })?
+            .iter()
+            .map(YamlParser::to_string)
+            .collect();
+        if input_sources.is_empty() {
+            return Err(ParseError::InvalidValue {
+                field: "decision_table.inputs".to_string(),
+                message: "A decision table needs at least one input".to_string(),
+            });
+        }
+        let inputs = input_sources
+            .iter()
+            .map(|source| ExpressionParser::parse(source))
+            .collect::<Result<Vec<_>>>()?;
+
+        let rows = YamlParser::get_array(table_obj, "rows")
+            .map_err(|_| ParseError::MissingField {
+                field: "decision_table.rows".to_string(),
+            })?
+            .iter()
+            .enumerate()
+            .map(|(idx, row)| Self::parse_row(row, idx, &input_sources, &inputs))
+            .collect::<Result<Vec<_>>>()?;
+
+        // Row IDs must be unique within a table
+        for (idx, row) in rows.iter().enumerate() {
+            if rows[..idx].iter().any(|r| r.id == row.id) {
+                return Err(ParseError::InvalidValue {
+                    field: format!("decision_table.rows[{}].id", idx),
+                    message: format!("Duplicate row ID '{}'", row.id),
+                });
+            }
+        }
+
+        let mut table = DecisionTable::new(id, name, inputs).with_hit_policy(hit_policy);
+        table.description = description;
+        table.rows = rows;
+        Ok(table)
+    }
+
+    /// Parse a row, with one cell per input
+    fn parse_row(
+        yaml: &YamlValue,
+        index: usize,
+        input_sources: &[String],
+        inputs: &[Expression],
+    ) -> Result<DecisionTableRow> {
+        let id = YamlParser::get_string(yaml, "id").map_err(|_| ParseError::MissingField {
+            field: format!("decision_table.rows[{}].id", index),
+        })?;
+        let score = YamlParser::get_i32(yaml, "score").map_err(|_| ParseError::MissingField {
+            field: format!("decision_table.rows[{}].score", index),
+        })?;
+        let priority = YamlParser::get_optional_i32(yaml, "priority").unwrap_or(0);
+
+        let cells = YamlParser::get_array(yaml, "cells").map_err(|_| ParseError::MissingField {
+            field: format!("decision_table.rows[{}].cells", index),
+        })?;
+        if cells.len() != inputs.len() {
+            return Err(ParseError::InvalidValue {
+                field: format!("decision_table.rows[{}].cells", index),
+                message: format!(
+                    "Row '{}' has {} cells, but the table has {} inputs",
+                    id,
+                    cells.len(),
+                    inputs.len()
+                ),
+            });
+        }
+
+        let cells = cells
+            .iter()
+            .zip(input_sources.iter().zip(inputs))
+            .map(|(cell, (source, input))| Self::parse_cell(cell, source, input))
+            .collect::<Result<Vec<_>>>()
+            .map_err(|e| ParseError::InvalidValue {
+                field: format!("decision_table.rows[{}].cells", index),
+                message: e.to_string(),
+            })?;
+
+        Ok(DecisionTableRow::new(id, cells, score).with_priority(priority))
+    }
+
+    /// Parse the condition of a cell over its input
+    fn parse_cell(
+        cell: &YamlValue,
+        source: &str,
+        input: &Expression,
+    ) -> Result<Option<Expression>> {
+        let value = match cell {
+            YamlValue::Null => return Ok(None),
+            YamlValue::Bool(b) => Value::Bool(*b),
+            YamlValue::Number(n) => Value::Number(n.as_f64().unwrap_or_default()),
+            YamlValue::String(s) => {
+                let text = s.trim();
+                if text.is_empty() || text == "-" {
+                    return Ok(None);
+                }
+                if CELL_OPERATORS.iter().any(|op| text.starts_with(op)) {
+                    return ExpressionParser::parse(&format!("{} {}", source, text)).map(Some);
+                }
+                Value::String(text.to_string())
+            }
+            _ => {
+                return Err(ParseError::InvalidValue {
+                    field: "cells".to_string(),
+                    message: format!("Expected a value or a test for input '{}'", source),
+                })
+            }
+        };
+
+        Ok(Some(Expression::binary(
+            input.clone(),
+            Operator::Eq,
+            Expression::literal(value),
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TABLE: &str = r#"
+decision_table:
+  id: shipping_risk
+  name: Shipping Risk
+  hit_policy: priority
+  inputs:
+    - event.country
+    - event.amount
+  rows:
+    - id: sanctioned
+      cells: ['in ["KP", "IR"]', -]
+      score: 100
+      priority: 10
+    - id: large_foreign
+      cells: ['!= "US"', '> 1000']
+      score: 60
+    - id: domestic
+      cells: [US, ""]
+      score: 0
+"#;
+
+    #[test]
+    fn test_parse_decision_table() {
+        let table = DecisionTableParser::parse(TABLE).unwrap();
+
+        assert_eq!(table.id, "shipping_risk");
+        assert_eq!(table.hit_policy, HitPolicy::Priority);
+        assert_eq!(table.inputs.len(), 2);
+        assert_eq!(table.rows.len(), 3);
+
+        let sanctioned = &table.rows[0];
+        assert_eq!(sanctioned.priority, 10);
+        assert!(matches!(
+            &sanctioned.cells[0],
+            Some(Expression::Binary {
+                op: Operator::In,
+                ..
+            })
+        ));
+        assert_eq!(sanctioned.cells[1], None);
+
+        assert_eq!(
+            table.rows[1].cells[1],
+            Some(ExpressionParser::parse("event.amount > 1000").unwrap())
+        );
+        assert_eq!(
+            table.rows[2].cells,
+            vec![
+                Some(ExpressionParser::parse(r#"event.country == "US""#).unwrap()),
+                None
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_decision_table_errors() {
+        // A row needs a cell per input
+        let yaml = TABLE.replace("cells: [US, \"\"]", "cells: [US]");
+        assert!(DecisionTableParser::parse(&yaml).is_err());
+
+        let yaml = TABLE.replace("id: domestic", "id: sanctioned");
+        assert!(DecisionTableParser::parse(&yaml).is_err());
+
+        let yaml = TABLE.replace("hit_policy: priority", "hit_policy: unique");
+        assert!(DecisionTableParser::parse(&yaml).is_err());
+
+        let yaml = TABLE.replace("'> 1000'", "{ above: 1000 }");
+        assert!(DecisionTableParser::parse(&yaml).is_err());
+    }
+}
@@ -7,13 +7,19 @@
 //! - Literals: `42`, `3.14`, `"string"`, `true`, `false`, `null`
 //! - Binary operators: `>`, `<`, `>=`, `<=`, `==`, `!=`, `+`, `-`, `*`, `/`, `&&`, `||`
 //! - Unary operators: `!`, `-`
-//! - Function calls: `count(user.logins)`, `sum(amounts, last_7d)`
+//! - Null checks: `user.email is null`, `user.email is not null`
+//! - Function calls: `count(user.logins)`, `sum(amounts, last_7d)`, `coalesce(user.tier, "basic")`
+//! - Array quantifiers: `any_of(event.items, item.price > 1000)`, `all_of(event.items, x, x.qty > 0)`
+//! - Array aggregates: `count(event.items)`, `sum(event.items[*].amount)`, `max(event.items[*].price)`
 //! - Parentheses for grouping: `(a + b) * c`
 
 use crate::error::{ParseError, Result};
-use corint_core::ast::{Expression, Operator, UnaryOperator};
+use corint_core::ast::{AggregateOp, Expression, Operator, QuantifierOp, UnaryOperator};
 use corint_core::Value;
 
+/// Element name used inside `any_of`/`all_of` predicates when none is given
+pub const DEFAULT_QUANTIFIER_BINDING: &str = "item";
+
 /// Expression parser
 pub struct ExpressionParser;
 
@@ -43,6 +49,20 @@ impl ExpressionParser {
             ));
         }
 
+        // Null checks bind tighter than logical operators only
+        if let Some(operand) = Self::strip_keyword_suffix(input, "is not null") {
+            return Ok(Expression::unary(
+                UnaryOperator::IsNotNull,
+                Self::parse_expression(operand)?,
+            ));
+        }
+        if let Some(operand) = Self::strip_keyword_suffix(input, "is null") {
+            return Ok(Expression::unary(
+                UnaryOperator::IsNull,
+                Self::parse_expression(operand)?,
+            ));
+        }
+
         // Try to parse as binary expression with keyword operators (contains, in, not in, etc.)
         // Note: "not in" must come before "in" to match correctly
         if let Some((left, op, right)) = Self::split_by_keyword_operator(
@@ -177,12 +197,26 @@ impl ExpressionParser {
                 let func_name = input[..paren_pos].trim();
                 let args_str = &input[paren_pos + 1..input.len() - 1];
 
-                let args = if args_str.trim().is_empty() {
+                let mut args = if args_str.trim().is_empty() {
                     Vec::new()
                 } else {
                     Self::parse_function_args(args_str)?
                 };
 
+                match func_name {
+                    "any_of" => return Self::parse_quantifier(QuantifierOp::AnyOf, args),
+                    "all_of" => return Self::parse_quantifier(QuantifierOp::AllOf, args),
+                    _ => {}
+                }
+
+                // Single-argument aggregates work on in-payload arrays; other
+                // arities (e.g. `sum(amounts, last_7d)`) stay plain function calls
+                if let Some(op) = AggregateOp::from_name(func_name) {
+                    if args.len() == 1 {
+                        return Self::parse_aggregate(op, args.remove(0));
+                    }
+                }
+
                 return Ok(Expression::function_call(func_name.to_string(), args));
             }
         }
@@ -240,6 +274,91 @@ impl ExpressionParser {
         )))
     }
 
+    /// Parse an array quantifier from its function arguments
+    ///
+    /// Accepts `(collection, predicate)` with the element bound to `item`, or
+    /// `(collection, binding, predicate)` with an explicit element name.
+    fn parse_quantifier(op: QuantifierOp, mut args: Vec<Expression>) -> Result<Expression> {
+        let binding = match args.len() {
+            2 => DEFAULT_QUANTIFIER_BINDING.to_string(),
+            3 => match args.remove(1) {
+                Expression::FieldAccess(path) if path.len() == 1 => path[0].clone(),
+                other => {
+                    return Err(ParseError::InvalidExpression(format!(
+                        "{}: element name must be a plain identifier, got {:?}",
+                        op.name(),
+                        other
+                    )))
+                }
+            },
+            n => {
+                return Err(ParseError::InvalidExpression(format!(
+                    "{} expects 2 or 3 arguments (collection, [name,] predicate), got {}",
+                    op.name(),
+                    n
+                )))
+            }
+        };
+
+        let predicate = args.pop().expect("argument count checked above");
+        let collection = args.pop().expect("argument count checked above");
+
+        Ok(Expression::quantifier(op, collection, binding, predicate))
+    }
+
+    /// Parse an array aggregate from its single argument
+    ///
+    /// `items[*].amount` aggregates the `amount` field of every element of
+    /// `items`; without a `[*]` segment the elements themselves are aggregated.
+    fn parse_aggregate(op: AggregateOp, arg: Expression) -> Result<Expression> {
+        let path = match arg {
+            Expression::FieldAccess(path) => path,
+            other => return Ok(Expression::aggregate(op, other, Vec::new())),
+        };
+
+        let wildcard_segments: Vec<usize> = path
+            .iter()
+            .enumerate()
+            .filter(|(_, segment)| segment.contains("[*]"))
+            .map(|(i, _)| i)
+            .collect();
+
+        match wildcard_segments.as_slice() {
+            [] => Ok(Expression::aggregate(
+                op,
+                Expression::field_access(path),
+                Vec::new(),
+            )),
+            [index] if path[*index].ends_with("[*]") => {
+                let mut collection = path[..=*index].to_vec();
+                let last = collection.last_mut().expect("wildcard segment exists");
+                last.truncate(last.len() - "[*]".len());
+                let element_path = path[index + 1..].to_vec();
+
+                Ok(Expression::aggregate(
+                    op,
+                    Expression::field_access(collection),
+                    element_path,
+                ))
+            }
+            _ => Err(ParseError::InvalidExpression(format!(
+                "{}: only a single trailing [*] per path segment is supported, got {}",
+                op.name(),
+                path.join(".")
+            ))),
+        }
+    }
+
+    /// Strip a trailing keyword operator such as `is null`, returning the operand before it
+    fn strip_keyword_suffix<'a>(input: &'a str, keyword: &str) -> Option<&'a str> {
+        let operand = input.strip_suffix(keyword)?;
+        if operand.ends_with(char::is_whitespace) && !operand.trim().is_empty() {
+            Some(operand.trim_end())
+        } else {
+            None
+        }
+    }
+
     /// Split input by binary operator (respecting parentheses and brackets)
     fn split_by_operator<'a>(
         input: &'a str,
@@ -584,10 +703,10 @@ mod tests {
 
     #[test]
     fn test_parse_function_call() {
-        let expr = ExpressionParser::parse("count(user.logins)").unwrap();
+        let expr = ExpressionParser::parse("lower(user.name)").unwrap();
 
         if let Expression::FunctionCall { name, args } = expr {
-            assert_eq!(name, "count");
+            assert_eq!(name, "lower");
             assert_eq!(args.len(), 1);
         } else {
             panic!("Expected function call");
@@ -656,6 +775,92 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_parse_any_of_quantifier() {
+        let expr = ExpressionParser::parse("any_of(event.items, item.price > 1000)").unwrap();
+
+        if let Expression::Quantifier {
+            op,
+            collection,
+            binding,
+            predicate,
+        } = expr
+        {
+            assert_eq!(op, QuantifierOp::AnyOf);
+            assert_eq!(binding, "item");
+            assert_eq!(
+                *collection,
+                Expression::field_access(vec!["event".to_string(), "items".to_string()])
+            );
+            assert!(matches!(*predicate, Expression::Binary { op: Operator::Gt, .. }));
+        } else {
+            panic!("Expected quantifier expression");
+        }
+    }
+
+    #[test]
+    fn test_parse_all_of_with_named_binding() {
+        let expr =
+            ExpressionParser::parse("all_of(event.addresses, addr, addr.country == \"US\")")
+                .unwrap();
+
+        if let Expression::Quantifier { op, binding, .. } = expr {
+            assert_eq!(op, QuantifierOp::AllOf);
+            assert_eq!(binding, "addr");
+        } else {
+            panic!("Expected quantifier expression");
+        }
+
+        // Quantifiers compose with other operators
+        let expr = ExpressionParser::parse("any_of(event.items, item.qty > 5) && event.amount > 10")
+            .unwrap();
+        assert!(matches!(expr, Expression::Binary { op: Operator::And, .. }));
+    }
+
+    #[test]
+    fn test_parse_quantifier_invalid_arity() {
+        assert!(ExpressionParser::parse("any_of(event.items)").is_err());
+        assert!(ExpressionParser::parse("all_of(event.items, a.b, a.b > 1)").is_err());
+    }
+
+    #[test]
+    fn test_parse_aggregate_with_wildcard_path() {
+        let expr = ExpressionParser::parse("sum(event.items[*].amount) > 5000").unwrap();
+
+        if let Expression::Binary { left, .. } = expr {
+            assert_eq!(
+                *left,
+                Expression::aggregate(
+                    AggregateOp::Sum,
+                    Expression::field_access(vec!["event".to_string(), "items".to_string()]),
+                    vec!["amount".to_string()],
+                )
+            );
+        } else {
+            panic!("Expected binary expression");
+        }
+    }
+
+    #[test]
+    fn test_parse_aggregate_without_wildcard() {
+        let expr = ExpressionParser::parse("count(event.items)").unwrap();
+
+        assert_eq!(
+            expr,
+            Expression::aggregate(
+                AggregateOp::Count,
+                Expression::field_access(vec!["event".to_string(), "items".to_string()]),
+                Vec::new(),
+            )
+        );
+
+        // Multi-argument forms remain function calls
+        let expr = ExpressionParser::parse("sum(amounts, last_7d)").unwrap();
+        assert!(matches!(expr, Expression::FunctionCall { .. }));
+
+        assert!(ExpressionParser::parse("max(event.orders[*].items[*].price)").is_err());
+    }
+
     #[test]
     fn test_parse_in_operator_with_array() {
         let expr = ExpressionParser::parse(r#"event.country in ["RU", "CN", "NK"]"#).unwrap();
@@ -673,4 +878,29 @@ mod tests {
             panic!("Expected binary expression");
         }
     }
+
+    #[test]
+    fn test_parse_null_checks() {
+        let email = Expression::field_access(vec!["user".to_string(), "email".to_string()]);
+
+        let expr = ExpressionParser::parse("user.email is null").unwrap();
+        assert_eq!(expr, Expression::unary(UnaryOperator::IsNull, email.clone()));
+
+        let expr = ExpressionParser::parse("user.email is not null && user.age > 18").unwrap();
+        if let Expression::Binary { left, op, .. } = &expr {
+            assert_eq!(*op, Operator::And);
+            assert_eq!(
+                *left.clone(),
+                Expression::unary(UnaryOperator::IsNotNull, email)
+            );
+        } else {
+            panic!("Expected binary expression");
+        }
+
+        let expr = ExpressionParser::parse(r#"coalesce(user.tier, "basic")"#).unwrap();
+        assert!(matches!(
+            expr,
+            Expression::FunctionCall { ref name, ref args } if name == "coalesce" && args.len() == 2
+        ));
+    }
 }
@@ -3,21 +3,25 @@
 //! This crate provides parsers for converting YAML configuration files
 //! into CORINT AST (Abstract Syntax Tree) structures.
 
+pub mod decision_table_parser;
 pub mod error;
 pub mod expression_parser;
 pub mod import_parser;
 pub mod pipeline;
 pub mod pipeline_parser;
+pub mod reason_code_parser;
 pub mod registry_parser;
 pub mod rule_parser;
 pub mod ruleset_parser;
 pub mod yaml_parser;
 
 // Re-export main parser types
+pub use decision_table_parser::DecisionTableParser;
 pub use error::{ParseError, Result};
 pub use expression_parser::ExpressionParser;
 pub use import_parser::ImportParser;
 pub use pipeline::PipelineParser;
+pub use reason_code_parser::ReasonCatalogParser;
 pub use registry_parser::RegistryParser;
 pub use rule_parser::RuleParser;
 pub use ruleset_parser::RulesetParser;
@@ -4,10 +4,12 @@
 
 use crate::error::{ParseError, Result};
 use crate::import_parser::ImportParser;
+use crate::ruleset_parser::RulesetParser;
 use crate::yaml_parser::YamlParser;
 use super::step_parser::{parse_new_step, parse_step, parse_when_block};
 use corint_core::ast::pipeline::{PipelineStep, StepNext, StepDetails};
-use corint_core::ast::{Pipeline, RdlDocument, Step};
+use corint_core::ast::{ErrorPolicy, Pipeline, PipelineOnError, RdlDocument, Step};
+use corint_core::types::{FieldType, Schema, SchemaField};
 use serde_yaml::Value as YamlValue;
 
 /// Pipeline parser
@@ -105,6 +107,21 @@ impl PipelineParser {
             None
         };
 
+        // Parse optional time budget for the whole pipeline
+        let timeout_ms = pipeline_obj.get("timeout_ms").and_then(|v| v.as_u64());
+
+        // Parse optional fallback decision when a step fails
+        let on_error = pipeline_obj
+            .get("on_error")
+            .map(Self::parse_on_error)
+            .transpose()?;
+
+        // Parse optional schema of the input event
+        let schema = pipeline_obj
+            .get("schema")
+            .map(|schema| Self::parse_schema(&id, schema))
+            .transpose()?;
+
         // Parse steps array
         let steps_array = pipeline_obj
             .get("steps")
@@ -136,12 +153,131 @@ impl PipelineParser {
             description,
             entry,
             when,
+            timeout_ms,
+            on_error,
+            schema,
             steps,
             decision,
             metadata,
         })
     }
 
+    /// Parse the declared schema of the input event
+    ///
+    /// Maps each field of the event to its type, either a type name
+    /// (`amount: number`) or a mapping with the `type` and, optionally,
+    /// `required`, `description`, `items` (arrays) and `fields` (objects).
+    fn parse_schema(id: &str, yaml: &YamlValue) -> Result<Schema> {
+        Ok(Schema::new(id.to_string()).with_fields(Self::parse_schema_fields("schema", yaml)?))
+    }
+
+    fn parse_schema_fields(
+        path: &str,
+        yaml: &YamlValue,
+    ) -> Result<std::collections::HashMap<String, SchemaField>> {
+        let mapping = yaml.as_mapping().ok_or_else(|| ParseError::InvalidValue {
+            field: path.to_string(),
+            message: "Expected a mapping of field names to types".to_string(),
+        })?;
+
+        let mut fields = std::collections::HashMap::new();
+        for (name, field) in mapping {
+            let name = name.as_str().ok_or_else(|| ParseError::InvalidValue {
+                field: path.to_string(),
+                message: "Field names must be strings".to_string(),
+            })?;
+            let path = format!("{}.{}", path, name);
+            let mut schema_field =
+                SchemaField::new(name.to_string(), Self::parse_field_type(&path, field)?);
+            schema_field.required =
+                YamlParser::get_optional_bool(field, "required").unwrap_or(false);
+            schema_field.description = YamlParser::get_optional_string(field, "description");
+            fields.insert(name.to_string(), schema_field);
+        }
+        Ok(fields)
+    }
+
+    fn parse_field_type(path: &str, yaml: &YamlValue) -> Result<FieldType> {
+        let type_name = match yaml.as_str() {
+            Some(type_name) => type_name.to_string(),
+            None => YamlParser::get_string(yaml, "type").map_err(|_| ParseError::InvalidValue {
+                field: path.to_string(),
+                message: "Expected a type name or a mapping with a 'type'".to_string(),
+            })?,
+        };
+
+        Ok(match type_name.as_str() {
+            "number" => FieldType::Number,
+            "string" => FieldType::String,
+            "boolean" => FieldType::Boolean,
+            "null" => FieldType::Null,
+            "any" => FieldType::Any,
+            "array" => FieldType::Array {
+                item_type: Box::new(match yaml.get("items") {
+                    Some(items) => Self::parse_field_type(&format!("{}.items", path), items)?,
+                    None => FieldType::Any,
+                }),
+            },
+            "object" => FieldType::Object {
+                schema: yaml
+                    .get("fields")
+                    .map(|fields| -> Result<Box<Schema>> {
+                        let name = path.rsplit('.').next().unwrap_or(path).to_string();
+                        Ok(Box::new(
+                            Schema::new(name).with_fields(Self::parse_schema_fields(path, fields)?),
+                        ))
+                    })
+                    .transpose()?,
+            },
+            _ => {
+                return Err(ParseError::InvalidValue {
+                    field: path.to_string(),
+                    message: format!(
+                        "Unknown type '{}', expected one of: number, string, boolean, array, object, any, null",
+                        type_name
+                    ),
+                })
+            }
+        })
+    }
+
+    /// Parse the `on_error` policy of a pipeline
+    ///
+    /// Either a policy name (`on_error: fail_closed`) or a mapping with the
+    /// policy and, for `fail_open`, the signal decided.
+    fn parse_on_error(yaml: &YamlValue) -> Result<PipelineOnError> {
+        let (policy, signal, reason_code) = match yaml.as_str() {
+            Some(policy) => (policy.to_string(), None, None),
+            None => (
+                YamlParser::get_string(yaml, "policy")?,
+                YamlParser::get_optional_string(yaml, "signal"),
+                YamlParser::get_optional_string(yaml, "reason_code"),
+            ),
+        };
+
+        let policy = serde_yaml::from_value(YamlValue::String(policy)).map_err(|_| {
+            ParseError::InvalidValue {
+                field: "on_error".to_string(),
+                message: "Expected one of: fail_open, fail_closed, review".to_string(),
+            }
+        })?;
+        let signal = signal
+            .map(|signal| RulesetParser::signal_from_str(&signal))
+            .transpose()?;
+        if signal.is_some() && policy != ErrorPolicy::FailOpen {
+            return Err(ParseError::InvalidValue {
+                field: "on_error.signal".to_string(),
+                message: "A signal can only be set for the fail_open policy".to_string(),
+            });
+        }
+
+        Ok(PipelineOnError {
+            policy,
+            signal,
+            reason_code,
+        })
+    }
+
     /// Parse legacy format pipeline (backward compatibility)
     fn parse_legacy_format(pipeline_obj: &YamlValue) -> Result<Pipeline> {
         // Parse optional id, name, description (for legacy format)
@@ -189,6 +325,8 @@ impl PipelineParser {
                     default: None,
                     next: Some(StepNext::StepId("end".to_string())),
                     when: None,
+                    else_branch: None,
+                    timeout_ms: None,
                     details: StepDetails::Ruleset { ruleset },
                 },
                 Step::Extract { id: _, features } => PipelineStep {
@@ -199,6 +337,8 @@ impl PipelineParser {
                     default: None,
                     next: Some(StepNext::StepId("end".to_string())),
                     when: None,
+                    else_branch: None,
+                    timeout_ms: None,
                     details: StepDetails::Extract {
                         features: Some(features),
                     },
@@ -217,6 +357,8 @@ impl PipelineParser {
                     default: None,
                     next: Some(StepNext::StepId("end".to_string())),
                     when: None,
+                    else_branch: None,
+                    timeout_ms: None,
                     details: StepDetails::Service {
                         service,
                         endpoint: None,
@@ -243,6 +385,8 @@ impl PipelineParser {
                     default: None,
                     next: Some(StepNext::StepId("end".to_string())),
                     when: None,
+                    else_branch: None,
+                    timeout_ms: None,
                     details: StepDetails::Api {
                         api_target: corint_core::ast::pipeline::ApiTarget::Single { api },
                         endpoint: Some(endpoint),
@@ -264,6 +408,8 @@ impl PipelineParser {
                         default: Some("end".to_string()),
                         next: None,
                         when: None,
+                        else_branch: None,
+                        timeout_ms: None,
                         details: StepDetails::Router {},
                     }
                 }
@@ -277,6 +423,8 @@ impl PipelineParser {
                         default: None,
                         next: Some(StepNext::StepId("end".to_string())),
                         when: None,
+                        else_branch: None,
+                        timeout_ms: None,
                         details: StepDetails::Router {},
                     }
                 }
@@ -305,6 +453,9 @@ impl PipelineParser {
             description,
             entry,
             when,
+            timeout_ms: None,
+            on_error: None,
+            schema: None,
             steps,
             decision: None,
             metadata: None,
@@ -362,6 +513,7 @@ impl PipelineParser {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use corint_core::ast::{EmitKind, Signal};
 
     #[test]
     fn test_parse_new_format_pipeline() {
@@ -404,6 +556,220 @@ pipeline:
         assert_eq!(step.default, Some("step3".to_string()));
     }
 
+    #[test]
+    fn test_parse_timeouts() {
+        let yaml = r#"
+pipeline:
+  id: test_pipeline
+  name: Test Pipeline
+  entry: enrich
+  timeout_ms: 800
+  steps:
+    - step:
+        id: enrich
+        name: Enrich
+        type: api
+        api: ipinfo
+        endpoint: lookup
+        timeout_ms: 250
+        next: score
+    - step:
+        id: score
+        name: Score
+        type: ruleset
+        ruleset: fraud_detection
+"#;
+
+        let pipeline = PipelineParser::parse(yaml).unwrap();
+
+        assert_eq!(pipeline.timeout_ms, Some(800));
+        assert_eq!(pipeline.steps[0].timeout_ms, Some(250));
+        assert_eq!(pipeline.steps[1].timeout_ms, None);
+    }
+
+    #[test]
+    fn test_parse_on_error() {
+        let pipeline = |on_error: &str| {
+            PipelineParser::parse(&format!(
+                r#"
+pipeline:
+  id: payment_pipeline
+  name: Payment Pipeline
+  entry: score
+  on_error: {}
+  steps:
+    - step:
+        id: score
+        name: Score
+        type: ruleset
+        ruleset: fraud_detection
+"#,
+                on_error
+            ))
+        };
+
+        let on_error = pipeline("fail_closed").unwrap().on_error.unwrap();
+        assert_eq!(on_error, PipelineOnError::new(ErrorPolicy::FailClosed));
+        assert_eq!(on_error.fallback_signal(), Signal::Decline);
+
+        let on_error = pipeline("{ policy: fail_open, signal: hold, reason_code: NO_SCORE }")
+            .unwrap()
+            .on_error
+            .unwrap();
+        assert_eq!(on_error.policy, ErrorPolicy::FailOpen);
+        assert_eq!(on_error.fallback_signal(), Signal::Hold);
+        assert_eq!(on_error.reason_code.as_deref(), Some("NO_SCORE"));
+
+        assert!(pipeline("retry").is_err());
+        assert!(pipeline("{ policy: review, signal: approve }").is_err());
+    }
+
+    #[test]
+    fn test_parse_schema() {
+        let yaml = r#"
+pipeline:
+  id: payment_pipeline
+  name: Payment Pipeline
+  entry: score
+  schema:
+    amount: { type: number, required: true }
+    currency: string
+    tags:
+      type: array
+      items: string
+    user:
+      type: object
+      fields:
+        age: number
+    device: object
+  steps:
+    - step:
+        id: score
+        name: Score
+        type: ruleset
+        ruleset: fraud_detection
+"#;
+
+        let schema = PipelineParser::parse(yaml).unwrap().schema.unwrap();
+        assert_eq!(schema.name, "payment_pipeline");
+        assert_eq!(schema.fields.len(), 5);
+        assert!(schema.is_required("amount"));
+        assert_eq!(
+            schema.get_field("amount").unwrap().field_type,
+            FieldType::Number
+        );
+        assert!(!schema.is_required("currency"));
+        assert_eq!(
+            schema.get_field("tags").unwrap().field_type,
+            FieldType::Array {
+                item_type: Box::new(FieldType::String)
+            }
+        );
+        let FieldType::Object { schema: Some(user) } =
+            &schema.get_field("user").unwrap().field_type
+        else {
+            panic!("expected an object with fields");
+        };
+        assert_eq!(user.get_field("age").unwrap().field_type, FieldType::Number);
+        assert_eq!(
+            schema.get_field("device").unwrap().field_type,
+            FieldType::Object { schema: None }
+        );
+
+        let invalid = yaml.replace("currency: string", "currency: text");
+        assert!(PipelineParser::parse(&invalid).is_err());
+    }
+
+    #[test]
+    fn test_parse_step_else() {
+        let yaml = r#"
+pipeline:
+  id: onboarding
+  name: Onboarding
+  entry: documents
+  steps:
+    - step:
+        id: documents
+        name: Document Check
+        type: ruleset
+        ruleset: document_checks
+        when: event.kyc_verified != true
+        else:
+          skip_to: score
+        next: score
+    - step:
+        id: score
+        name: Score
+        type: ruleset
+        ruleset: onboarding_rules
+"#;
+
+        let pipeline = PipelineParser::parse(yaml).unwrap();
+
+        let step = &pipeline.steps[0];
+        assert!(step.when.is_some());
+        assert_eq!(
+            step.else_branch.as_ref().map(|e| e.skip_to.as_str()),
+            Some("score")
+        );
+        assert_eq!(pipeline.steps[1].else_branch, None);
+
+        // An else branch needs a when condition and a target
+        let without_when = yaml.replace("        when: event.kyc_verified != true\n", "");
+        assert!(PipelineParser::parse(&without_when).is_err());
+        let without_target = yaml.replace("skip_to: score", "goto: score");
+        assert!(PipelineParser::parse(&without_target).is_err());
+    }
+
+    #[test]
+    fn test_parse_emit_step() {
+        let yaml = r#"
+pipeline:
+  id: payments
+  name: Payments
+  entry: score
+  steps:
+    - step:
+        id: score
+        name: Score
+        type: ruleset
+        ruleset: payment_rules
+        next: open_case
+    - step:
+        id: open_case
+        name: Open Case
+        type: emit
+        emit: case
+        target: fraud_review
+        payload:
+          user_id: event.user_id
+          amount: event.amount
+        signals: [decline, review]
+"#;
+
+        let pipeline = PipelineParser::parse(yaml).unwrap();
+
+        match &pipeline.steps[1].details {
+            StepDetails::Emit {
+                emit,
+                target,
+                payload,
+                signals,
+            } => {
+                assert_eq!(*emit, EmitKind::Case);
+                assert_eq!(target, "fraud_review");
+                assert_eq!(payload.as_ref().map(|p| p.len()), Some(2));
+                assert_eq!(signals, &vec![Signal::Decline, Signal::Review]);
+            }
+            other => panic!("Expected emit step, got {:?}", other),
+        }
+
+        let unknown_kind = yaml.replace("emit: case", "emit: email");
+        assert!(PipelineParser::parse(&unknown_kind).is_err());
+        let unknown_signal = yaml.replace("[decline, review]", "[decline, maybe]");
+        assert!(PipelineParser::parse(&unknown_signal).is_err());
+    }
+
     #[test]
     fn test_parse_ruleset_step() {
         let yaml = r#"
@@ -537,4 +903,149 @@ pipeline:
         assert_eq!(pipeline.steps.len(), 1);
         assert_eq!(pipeline.steps[0].step_type, "extract");
     }
+    #[test]
+    fn test_parse_set_step() {
+        let yaml = r#"
+pipeline:
+  id: test_pipeline
+  name: Test Pipeline
+  entry: derive
+  steps:
+    - step:
+        id: derive
+        name: Derive Variables
+        type: set
+        set:
+          risk_band: high
+          order_total: sum(event.items[*].amount)
+          is_large: event.amount > 1000
+          threshold: 500
+        next: end
+"#;
+
+        let pipeline = PipelineParser::parse(yaml).unwrap();
+
+        let step = &pipeline.steps[0];
+        assert_eq!(step.step_type, "set");
+        if let StepDetails::Set { set } = &step.details {
+            let names: Vec<_> = set.iter().map(|a| a.name.as_str()).collect();
+            assert_eq!(names, vec!["risk_band", "order_total", "is_large", "threshold"]);
+            assert_eq!(
+                set[0].value,
+                corint_core::ast::Expression::literal(corint_core::Value::String("high".to_string()))
+            );
+            assert!(matches!(set[1].value, corint_core::ast::Expression::Aggregate { .. }));
+            assert!(matches!(set[2].value, corint_core::ast::Expression::Binary { .. }));
+        } else {
+            panic!("Expected Set step");
+        }
+    }
+
+    #[test]
+    fn test_parse_let_and_assign_steps() {
+        let yaml = r#"
+pipeline:
+  id: test_pipeline
+  name: Test Pipeline
+  entry: normalize
+  steps:
+    - step:
+        id: normalize
+        name: Normalize Amount
+        type: let
+        let:
+          normalized_amount: event.amount / 100
+        next: cap
+    - step:
+        id: cap
+        name: Cap Amount
+        type: assign
+        when:
+          all:
+            - vars.normalized_amount > 50
+        assign:
+          normalized_amount: 50
+        next: end
+"#;
+
+        let pipeline = PipelineParser::parse(yaml).unwrap();
+
+        match &pipeline.steps[0].details {
+            StepDetails::Let { declare } => {
+                assert_eq!(declare[0].name, "normalized_amount");
+                assert!(matches!(declare[0].value, corint_core::ast::Expression::Binary { .. }));
+            }
+            other => panic!("Expected Let step, got {:?}", other),
+        }
+        match &pipeline.steps[1].details {
+            StepDetails::Assign { assign } => {
+                assert_eq!(assign[0].name, "normalized_amount");
+                assert_eq!(
+                    assign[0].value,
+                    corint_core::ast::Expression::literal(corint_core::Value::Number(50.0))
+                );
+            }
+            other => panic!("Expected Assign step, got {:?}", other),
+        }
+
+        let yaml = yaml.replace("assign:\n          normalized_amount", "set:\n          normalized_amount");
+        assert!(PipelineParser::parse(&yaml).is_err());
+    }
+
+    #[test]
+    fn test_parse_set_step_requires_mapping() {
+        let yaml = r#"
+pipeline:
+  id: test_pipeline
+  name: Test Pipeline
+  entry: derive
+  steps:
+    - step:
+        id: derive
+        name: Derive Variables
+        type: set
+        set: [risk_band]
+"#;
+
+        assert!(PipelineParser::parse(yaml).is_err());
+    }
+
+    #[test]
+    fn test_parse_custom_step() {
+        let yaml = r#"
+pipeline:
+  id: test_pipeline
+  name: Test Pipeline
+  entry: velocity
+  steps:
+    - step:
+        id: velocity
+        name: Geo Velocity
+        type: geo_velocity
+        window_hours: 24
+        fields: [ip_country, billing_country]
+        next: end
+"#;
+
+        let pipeline = PipelineParser::parse(yaml).unwrap();
+
+        let step = &pipeline.steps[0];
+        assert_eq!(step.step_type, "geo_velocity");
+        assert_eq!(step.next, Some(StepNext::StepId("end".to_string())));
+        match &step.details {
+            StepDetails::Custom { fields } => {
+                assert_eq!(fields.len(), 2);
+                assert_eq!(fields["window_hours"], serde_json::json!(24));
+                assert_eq!(
+                    fields["fields"],
+                    serde_json::json!(["ip_country", "billing_country"])
+                );
+            }
+            other => panic!("Expected Custom step, got {:?}", other),
+        }
+
+        // Built-in step types still reject unknown fields
+        let yaml = yaml.replace("type: geo_velocity", "type: rule\n        rule: check");
+        assert!(PipelineParser::parse(&yaml).is_err());
+    }
 }
@@ -5,10 +5,12 @@
 use crate::error::{ParseError, Result};
 use crate::expression_parser::ExpressionParser;
 use crate::rule_parser::RuleParser;
+use crate::ruleset_parser::RulesetParser;
 use crate::yaml_parser::YamlParser;
-use super::validation::get_valid_fields_for_step_type;
+use super::validation::{get_valid_fields_for_step_type, is_builtin_step_type, COMMON_STEP_FIELDS};
 use corint_core::ast::pipeline::{
-    ApiTarget, ErrorAction, ErrorHandling, PipelineStep, Route, StepDetails, StepNext,
+    ApiTarget, ErrorAction, ErrorHandling, PipelineStep, Route, StepDetails, StepElse, StepNext,
+    VariableAssignment,
 };
 use corint_core::ast::{
     Branch, FeatureDefinition, MergeStrategy, Step, WhenBlock,
@@ -58,6 +60,18 @@ pub(super) fn parse_new_step(yaml: &YamlValue) -> Result<PipelineStep> {
         None
     };
 
+    // Parse optional else branch of the when condition
+    let else_branch = step_obj.get("else").map(parse_step_else).transpose()?;
+    if else_branch.is_some() && when.is_none() {
+        return Err(ParseError::InvalidValue {
+            field: "else".to_string(),
+            message: format!("Step '{}' has an else branch but no when condition", id),
+        });
+    }
+
+    // Parse optional time budget
+    let timeout_ms = step_obj.get("timeout_ms").and_then(|v| v.as_u64());
+
     // Parse type-specific details based on step_type
     let details = parse_step_details(step_obj, &step_type)?;
 
@@ -69,12 +83,29 @@ pub(super) fn parse_new_step(yaml: &YamlValue) -> Result<PipelineStep> {
         default,
         next,
         when,
+        else_branch,
+        timeout_ms,
         details,
     })
 }
 
+/// Parse the else branch of a step's when condition (`else: { skip_to: <step> }`)
+fn parse_step_else(yaml: &YamlValue) -> Result<StepElse> {
+    let skip_to = YamlParser::get_string(yaml, "skip_to").map_err(|_| ParseError::InvalidValue {
+        field: "else".to_string(),
+        message: "Expected 'skip_to' with the ID of the step to continue with, or 'end'"
+            .to_string(),
+    })?;
+    Ok(StepElse { skip_to })
+}
+
 /// Parse step-specific details based on type
 pub(super) fn parse_step_details(step_obj: &YamlValue, step_type: &str) -> Result<StepDetails> {
+    // Steps of other types are left to the compiler plugin registered for them
+    if !is_builtin_step_type(step_type) {
+        return parse_custom_step(step_obj);
+    }
+
     // Validate fields strictly for this step type
     let valid_fields = get_valid_fields_for_step_type(step_type);
     YamlParser::validate_fields_strict(
@@ -154,6 +185,52 @@ pub(super) fn parse_step_details(step_obj: &YamlValue, step_type: &str) -> Resul
             Ok(StepDetails::Trigger { target, params })
         }
 
+        "emit" => {
+            let emit = step_obj
+                .get("emit")
+                .ok_or_else(|| ParseError::MissingField {
+                    field: "emit".to_string(),
+                })
+                .and_then(|v| {
+                    serde_yaml::from_value(v.clone()).map_err(|_| ParseError::InvalidValue {
+                        field: "emit".to_string(),
+                        message: "Expected one of: webhook, kafka, case".to_string(),
+                    })
+                })?;
+            let target = YamlParser::get_string(step_obj, "target")?;
+            let payload = parse_expression_map(step_obj, "payload")?;
+            let signals = YamlParser::get_optional_array(step_obj, "signals")
+                .map(|signals| {
+                    signals
+                        .iter()
+                        .map(|signal| RulesetParser::signal_from_str(&YamlParser::to_string(signal)))
+                        .collect::<Result<Vec<_>>>()
+                })
+                .transpose()?
+                .unwrap_or_default();
+            Ok(StepDetails::Emit {
+                emit,
+                target,
+                payload,
+                signals,
+            })
+        }
+
+        "set" => {
+            let set = parse_set_assignments(step_obj, "set")?;
+            Ok(StepDetails::Set { set })
+        }
+
+        "let" => {
+            let declare = parse_set_assignments(step_obj, "let")?;
+            Ok(StepDetails::Let { declare })
+        }
+
+        "assign" => {
+            let assign = parse_set_assignments(step_obj, "assign")?;
+            Ok(StepDetails::Assign { assign })
+        }
+
         "extract" => {
             let features = if let Some(features_array) =
                 step_obj.get("features").and_then(|v| v.as_sequence())
@@ -174,6 +251,30 @@ pub(super) fn parse_step_details(step_obj: &YamlValue, step_type: &str) -> Resul
     }
 }
 
+/// Keep the fields of a step of a custom type, other than the common ones
+fn parse_custom_step(step_obj: &YamlValue) -> Result<StepDetails> {
+    let mut fields = HashMap::new();
+    if let Some(mapping) = step_obj.as_mapping() {
+        for (key, value) in mapping {
+            let Some(key) = key.as_str() else { continue };
+            if COMMON_STEP_FIELDS.contains(&key) || key == "routes" || key == "default" {
+                continue;
+            }
+            let value = serde_json::to_value(value).map_err(|e| ParseError::InvalidValue {
+                field: key.to_string(),
+                message: e.to_string(),
+            })?;
+            fields.insert(key.to_string(), value);
+        }
+    }
+
+    if fields.is_empty() {
+        Ok(StepDetails::Unknown {})
+    } else {
+        Ok(StepDetails::Custom { fields })
+    }
+}
+
 /// Parse API target (single, any, all)
 pub(super) fn parse_api_target(step_obj: &YamlValue) -> Result<ApiTarget> {
     // Try single API
@@ -206,7 +307,15 @@ pub(super) fn parse_api_target(step_obj: &YamlValue) -> Result<ApiTarget> {
 
 /// Parse parameters as HashMap<String, Expression>
 pub(super) fn parse_params(step_obj: &YamlValue) -> Result<Option<HashMap<String, corint_core::ast::Expression>>> {
-    if let Some(params_obj) = step_obj.get("params").and_then(|v| v.as_mapping()) {
+    parse_expression_map(step_obj, "params")
+}
+
+/// Parse a mapping of names to expressions (step params, emit payload)
+fn parse_expression_map(
+    step_obj: &YamlValue,
+    field: &str,
+) -> Result<Option<HashMap<String, corint_core::ast::Expression>>> {
+    if let Some(params_obj) = step_obj.get(field).and_then(|v| v.as_mapping()) {
         let mut map = HashMap::new();
         for (key, value) in params_obj {
             if let Some(key_str) = key.as_str() {
@@ -238,6 +347,72 @@ pub(super) fn parse_params(step_obj: &YamlValue) -> Result<Option<HashMap<String
     }
 }
 
+/// Parse the assignments of a set, let or assign step from its `field`, preserving their order
+///
+/// String values are parsed as expressions (e.g. `event.amount * 2`); a bare
+/// word such as `high` is taken as a string literal.
+pub(super) fn parse_set_assignments(
+    step_obj: &YamlValue,
+    field: &str,
+) -> Result<Vec<VariableAssignment>> {
+    use corint_core::ast::Expression;
+    use corint_core::Value;
+
+    let set_obj = step_obj
+        .get(field)
+        .and_then(|v| v.as_mapping())
+        .ok_or_else(|| ParseError::InvalidValue {
+            field: field.to_string(),
+            message: format!(
+                "{} step requires a mapping of variable names to values",
+                field
+            ),
+        })?;
+
+    let mut assignments = Vec::new();
+    for (key, value) in set_obj {
+        let name = key
+            .as_str()
+            .filter(|k| !k.is_empty() && k.chars().all(|c| c.is_alphanumeric() || c == '_'))
+            .ok_or_else(|| ParseError::InvalidValue {
+                field: field.to_string(),
+                message: format!("Invalid variable name: {:?}", key),
+            })?
+            .to_string();
+
+        let value = if let Some(value_str) = value.as_str() {
+            match ExpressionParser::parse(value_str)? {
+                Expression::FieldAccess(path) if path.len() == 1 => {
+                    Expression::literal(Value::String(value_str.to_string()))
+                }
+                expr => expr,
+            }
+        } else if let Some(num) = value.as_f64() {
+            Expression::literal(Value::Number(num))
+        } else if let Some(bool_val) = value.as_bool() {
+            Expression::literal(Value::Bool(bool_val))
+        } else if value.is_null() {
+            Expression::literal(Value::Null)
+        } else {
+            return Err(ParseError::InvalidValue {
+                field: format!("{}.{}", field, name),
+                message: "value must be a scalar or an expression string".to_string(),
+            });
+        };
+
+        assignments.push(VariableAssignment { name, value });
+    }
+
+    if assignments.is_empty() {
+        return Err(ParseError::InvalidValue {
+            field: field.to_string(),
+            message: format!("{} step requires at least one assignment", field),
+        });
+    }
+
+    Ok(assignments)
+}
+
 /// Parse a route (next + when)
 pub(super) fn parse_route(yaml: &YamlValue) -> Result<Route> {
     let next = YamlParser::get_string(yaml, "next")?;
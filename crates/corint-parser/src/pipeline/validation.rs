@@ -3,7 +3,8 @@
 //! Defines valid fields for each step type and provides validation utilities.
 
 /// Common fields for all step types
-pub(super) const COMMON_STEP_FIELDS: &[&str] = &["id", "name", "type", "next", "when"];
+pub(super) const COMMON_STEP_FIELDS: &[&str] =
+    &["id", "name", "type", "next", "when", "else", "timeout_ms"];
 
 /// Fields specific to each step type (aligned with Pipeline DSL v2.0)
 pub(super) const FUNCTION_STEP_FIELDS: &[&str] = &["function", "params"];
@@ -14,10 +15,38 @@ pub(super) const API_STEP_FIELDS: &[&str] = &["api", "any", "all", "params", "en
 pub(super) const SERVICE_STEP_FIELDS: &[&str] = &["service", "query", "params"];
 pub(super) const ROUTER_STEP_FIELDS: &[&str] = &["routes", "default"];
 pub(super) const TRIGGER_STEP_FIELDS: &[&str] = &["target", "params"];
+pub(super) const EMIT_STEP_FIELDS: &[&str] = &["emit", "target", "payload", "signals"];
+pub(super) const SET_STEP_FIELDS: &[&str] = &["set"];
+pub(super) const LET_STEP_FIELDS: &[&str] = &["let"];
+pub(super) const ASSIGN_STEP_FIELDS: &[&str] = &["assign"];
 // Legacy step types (for backward compatibility only)
 pub(super) const EXTRACT_STEP_FIELDS: &[&str] = &["features"];
 pub(super) const REASON_STEP_FIELDS: &[&str] = &["provider", "model", "prompt", "output_schema"];
 
+/// Whether `step_type` is parsed by the parser, rather than by a compiler plugin
+///
+/// Types not listed here are kept as `StepDetails::Custom`, without validation
+/// of their fields.
+pub(super) fn is_builtin_step_type(step_type: &str) -> bool {
+    matches!(
+        step_type,
+        "router"
+            | "function"
+            | "rule"
+            | "ruleset"
+            | "pipeline"
+            | "service"
+            | "api"
+            | "trigger"
+            | "emit"
+            | "set"
+            | "let"
+            | "assign"
+            | "extract"
+            | "reason"
+    )
+}
+
 /// Helper function to combine common fields with type-specific fields
 ///
 /// Returns the list of valid field names for a given step type, including both
@@ -35,6 +64,10 @@ pub(super) fn get_valid_fields_for_step_type(step_type: &str) -> Vec<&'static st
         "service" => SERVICE_STEP_FIELDS,
         "api" => API_STEP_FIELDS,
         "trigger" => TRIGGER_STEP_FIELDS,
+        "emit" => EMIT_STEP_FIELDS,
+        "set" => SET_STEP_FIELDS,
+        "let" => LET_STEP_FIELDS,
+        "assign" => ASSIGN_STEP_FIELDS,
         // Legacy step types (backward compatibility)
         "extract" => EXTRACT_STEP_FIELDS,
         "reason" => REASON_STEP_FIELDS,
@@ -172,6 +172,9 @@ impl PipelineParser {
             description,
             entry,
             when,
+            timeout_ms: None,
+            on_error: None,
+            schema: None,
             steps,
             decision: None,
             metadata,
@@ -225,6 +228,8 @@ impl PipelineParser {
                     default: None,
                     next: Some(StepNext::StepId("end".to_string())),
                     when: None,
+                    else_branch: None,
+                    timeout_ms: None,
                     details: StepDetails::Ruleset { ruleset },
                 },
                 Step::Extract { id: _, features } => PipelineStep {
@@ -235,6 +240,8 @@ impl PipelineParser {
                     default: None,
                     next: Some(StepNext::StepId("end".to_string())),
                     when: None,
+                    else_branch: None,
+                    timeout_ms: None,
                     details: StepDetails::Extract {
                         features: Some(features),
                     },
@@ -253,6 +260,8 @@ impl PipelineParser {
                     default: None,
                     next: Some(StepNext::StepId("end".to_string())),
                     when: None,
+                    else_branch: None,
+                    timeout_ms: None,
                     details: StepDetails::Service {
                         service,
                         endpoint: None,
@@ -279,6 +288,8 @@ impl PipelineParser {
                     default: None,
                     next: Some(StepNext::StepId("end".to_string())),
                     when: None,
+                    else_branch: None,
+                    timeout_ms: None,
                     details: StepDetails::Api {
                         api_target: ApiTarget::Single { api },
                         endpoint: Some(endpoint),
@@ -300,6 +311,8 @@ impl PipelineParser {
                         default: Some("end".to_string()),
                         next: None,
                         when: None,
+                        else_branch: None,
+                        timeout_ms: None,
                         details: StepDetails::Router {},
                     }
                 }
@@ -313,6 +326,8 @@ impl PipelineParser {
                         default: None,
                         next: Some(StepNext::StepId("end".to_string())),
                         when: None,
+                        else_branch: None,
+                        timeout_ms: None,
                         details: StepDetails::Router {},
                     }
                 }
@@ -341,6 +356,9 @@ impl PipelineParser {
             description,
             entry,
             when,
+            timeout_ms: None,
+            on_error: None,
+            schema: None,
             steps,
             decision: None,
             metadata: None,
@@ -400,6 +418,8 @@ impl PipelineParser {
             default,
             next,
             when,
+            else_branch: None,
+            timeout_ms: None,
             details,
         })
     }
@@ -0,0 +1,210 @@
+//! Reason code catalog parser
+//!
+//! Parses YAML reason code catalogs into ReasonCatalog AST nodes.
+
+use crate::error::{ParseError, Result};
+use crate::yaml_parser::YamlParser;
+use corint_core::ast::{ReasonCatalog, ReasonCode, ReasonMessage};
+use serde_yaml::Value as YamlValue;
+
+/// Reason code catalog parser
+pub struct ReasonCatalogParser;
+
+impl ReasonCatalogParser {
+    /// Parse a reason code catalog from YAML string
+    pub fn parse(yaml_str: &str) -> Result<ReasonCatalog> {
+        let yaml = YamlParser::parse(yaml_str)?;
+        Self::parse_from_yaml(&yaml)
+    }
+
+    /// Parse a reason code catalog from YAML value
+    ///
+    /// Expected format:
+    /// ```yaml
+    /// reason_codes:
+    ///   default_locale: en
+    ///   codes:
+    ///     - code: HIGH_RISK_DEVICE
+    ///       messages:
+    ///         en:
+    ///           customer: We could not verify your device.
+    ///           internal: Device fingerprint on deny list
+    ///         es: No pudimos verificar su dispositivo.
+    /// ```
+    pub fn parse_from_yaml(yaml: &YamlValue) -> Result<ReasonCatalog> {
+        let catalog_obj = yaml
+            .get("reason_codes")
+            .ok_or_else(|| ParseError::MissingField {
+                field: "reason_codes".to_string(),
+            })?;
+
+        let default_locale = YamlParser::get_optional_string(catalog_obj, "default_locale")
+            .unwrap_or_else(|| "en".to_string());
+
+        let codes = YamlParser::get_array(catalog_obj, "codes")
+            .map_err(|_| ParseError::MissingField {
+                field: "reason_codes.codes".to_string(),
+            })?
+            .iter()
+            .enumerate()
+            .map(|(idx, v)| Self::parse_code(v, idx, &default_locale))
+            .collect::<Result<Vec<_>>>()?;
+
+        // Codes must be unique within a catalog
+        for (idx, code) in codes.iter().enumerate() {
+            if codes[..idx].iter().any(|c| c.code == code.code) {
+                return Err(ParseError::InvalidValue {
+                    field: format!("reason_codes.codes[{}].code", idx),
+                    message: format!("Duplicate reason code '{}'", code.code),
+                });
+            }
+        }
+
+        let mut catalog = ReasonCatalog::new().with_default_locale(default_locale);
+        catalog.codes = codes;
+        Ok(catalog)
+    }
+
+    /// Parse a single reason code definition
+    fn parse_code(yaml: &YamlValue, index: usize, default_locale: &str) -> Result<ReasonCode> {
+        let code = YamlParser::get_string(yaml, "code").map_err(|_| ParseError::MissingField {
+            field: format!("reason_codes.codes[{}].code", index),
+        })?;
+
+        if code.trim().is_empty() {
+            return Err(ParseError::InvalidValue {
+                field: format!("reason_codes.codes[{}].code", index),
+                message: "Reason code cannot be empty".to_string(),
+            });
+        }
+
+        let mut reason_code = ReasonCode::new(code.clone());
+        if let Some(description) = YamlParser::get_optional_string(yaml, "description") {
+            reason_code = reason_code.with_description(description);
+        }
+
+        let messages = YamlParser::get_object(yaml, "messages").map_err(|_| {
+            ParseError::MissingField {
+                field: format!("reason_codes.codes[{}].messages", index),
+            }
+        })?;
+
+        for (locale, message) in messages {
+            let locale = locale.as_str().ok_or_else(|| ParseError::InvalidValue {
+                field: format!("reason_codes.codes[{}].messages", index),
+                message: "Locale keys must be strings".to_string(),
+            })?;
+            let message = Self::parse_message(message).ok_or_else(|| ParseError::InvalidValue {
+                field: format!("reason_codes.codes[{}].messages.{}", index, locale),
+                message: "Expected a string or an object with a 'customer' field".to_string(),
+            })?;
+            reason_code = reason_code.with_message(locale.to_string(), message);
+        }
+
+        if !reason_code.messages.contains_key(default_locale) {
+            return Err(ParseError::InvalidValue {
+                field: format!("reason_codes.codes[{}].messages", index),
+                message: format!(
+                    "Reason code '{}' has no message for default locale '{}'",
+                    code, default_locale
+                ),
+            });
+        }
+
+        Ok(reason_code)
+    }
+
+    /// Parse a localized message
+    ///
+    /// A plain string is shorthand for a customer-facing message.
+    fn parse_message(yaml: &YamlValue) -> Option<ReasonMessage> {
+        if let Some(text) = yaml.as_str() {
+            return Some(ReasonMessage::new(text.to_string()));
+        }
+
+        let customer = YamlParser::get_optional_string(yaml, "customer")?;
+        let mut message = ReasonMessage::new(customer);
+        if let Some(internal) = YamlParser::get_optional_string(yaml, "internal") {
+            message = message.with_internal(internal);
+        }
+        Some(message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_reason_catalog() {
+        let yaml = r#"
+version: "0.1"
+
+reason_codes:
+  default_locale: en
+  codes:
+    - code: HIGH_RISK_DEVICE
+      description: Device matched a deny list
+      messages:
+        en:
+          customer: We could not verify your device.
+          internal: Device fingerprint on deny list
+        es: No pudimos verificar su dispositivo.
+    - code: VELOCITY_LIMIT
+      messages:
+        en: Too many attempts, please try again later.
+"#;
+
+        let catalog = ReasonCatalogParser::parse(yaml).unwrap();
+
+        assert_eq!(catalog.default_locale, "en");
+        assert_eq!(catalog.codes.len(), 2);
+
+        let device = catalog.get("HIGH_RISK_DEVICE").unwrap();
+        assert_eq!(
+            device.description.as_deref(),
+            Some("Device matched a deny list")
+        );
+        assert_eq!(
+            device.messages["en"].internal.as_deref(),
+            Some("Device fingerprint on deny list")
+        );
+        assert_eq!(
+            device.messages["es"].customer,
+            "No pudimos verificar su dispositivo."
+        );
+        assert!(catalog.contains("VELOCITY_LIMIT"));
+    }
+
+    #[test]
+    fn test_parse_reason_catalog_missing_default_locale_message() {
+        let yaml = r#"
+reason_codes:
+  default_locale: en
+  codes:
+    - code: HIGH_RISK_DEVICE
+      messages:
+        es: No pudimos verificar su dispositivo.
+"#;
+
+        let result = ReasonCatalogParser::parse(yaml);
+        assert!(matches!(result, Err(ParseError::InvalidValue { .. })));
+    }
+
+    #[test]
+    fn test_parse_reason_catalog_duplicate_code() {
+        let yaml = r#"
+reason_codes:
+  codes:
+    - code: VELOCITY_LIMIT
+      messages:
+        en: Too many attempts.
+    - code: VELOCITY_LIMIT
+      messages:
+        en: Slow down.
+"#;
+
+        let result = ReasonCatalogParser::parse(yaml);
+        assert!(matches!(result, Err(ParseError::InvalidValue { .. })));
+    }
+}
@@ -5,7 +5,7 @@
 use crate::error::{ParseError, Result};
 use crate::expression_parser::ExpressionParser;
 use crate::yaml_parser::YamlParser;
-use corint_core::ast::{PipelineRegistry, RegistryEntry, WhenBlock};
+use corint_core::ast::{Experiment, PipelineRegistry, RegistryEntry, ScheduleEntry, WhenBlock};
 use serde_yaml::Value as YamlValue;
 
 /// Registry parser
@@ -43,9 +43,53 @@ impl RegistryParser {
             registry = registry.with_version(ver);
         }
 
+        // Parse optional schedules
+        if let Some(schedules) = yaml.get("schedules").and_then(|v| v.as_sequence()) {
+            for (idx, v) in schedules.iter().enumerate() {
+                registry = registry.add_schedule(Self::parse_schedule(v, idx)?);
+            }
+        }
+
         Ok(registry)
     }
 
+    /// Parse a single schedule entry
+    fn parse_schedule(yaml: &YamlValue, index: usize) -> Result<ScheduleEntry> {
+        for field in ["id", "pipeline", "schedule", "entities"] {
+            if yaml.get(field).is_none() {
+                return Err(ParseError::MissingField {
+                    field: format!("schedules[{}].{}", index, field),
+                });
+            }
+        }
+
+        let schedule: ScheduleEntry =
+            serde_yaml::from_value(yaml.clone()).map_err(|e| ParseError::InvalidValue {
+                field: format!("schedules[{}]", index),
+                message: e.to_string(),
+            })?;
+
+        // Standard 5-field cron: minute hour day-of-month month day-of-week
+        if schedule.schedule.split_whitespace().count() != 5 {
+            return Err(ParseError::InvalidValue {
+                field: format!("schedules[{}].schedule", index),
+                message: format!(
+                    "Expected a 5-field cron expression, got '{}'",
+                    schedule.schedule
+                ),
+            });
+        }
+
+        if schedule.batch_size == 0 {
+            return Err(ParseError::InvalidValue {
+                field: format!("schedules[{}].batch_size", index),
+                message: "Batch size must be greater than zero".to_string(),
+            });
+        }
+
+        Ok(schedule)
+    }
+
     /// Parse a single registry entry
     fn parse_entry(yaml: &YamlValue, index: usize) -> Result<RegistryEntry> {
         // Get pipeline ID
@@ -61,7 +105,59 @@ impl RegistryParser {
 
         let when = Self::parse_when_block(when_obj)?;
 
-        Ok(RegistryEntry::new(pipeline, when))
+        let experiment = yaml
+            .get("experiment")
+            .map(|experiment| Self::parse_experiment(experiment, &pipeline, index))
+            .transpose()?;
+
+        let entry = RegistryEntry::new(pipeline, when);
+        Ok(match experiment {
+            Some(experiment) => entry.with_experiment(experiment),
+            None => entry,
+        })
+    }
+
+    /// Parse the champion/challenger experiment of a registry entry
+    fn parse_experiment(
+        yaml: &YamlValue,
+        pipeline: &str,
+        index: usize,
+    ) -> Result<Experiment> {
+        for field in ["id", "challenger", "traffic_percent", "key"] {
+            if yaml.get(field).is_none() {
+                return Err(ParseError::MissingField {
+                    field: format!("registry[{}].experiment.{}", index, field),
+                });
+            }
+        }
+
+        let experiment: Experiment =
+            serde_yaml::from_value(yaml.clone()).map_err(|e| ParseError::InvalidValue {
+                field: format!("registry[{}].experiment", index),
+                message: e.to_string(),
+            })?;
+
+        if !(0.0..=100.0).contains(&experiment.traffic_percent) {
+            return Err(ParseError::InvalidValue {
+                field: format!("registry[{}].experiment.traffic_percent", index),
+                message: format!(
+                    "Expected a percentage between 0 and 100, got {}",
+                    experiment.traffic_percent
+                ),
+            });
+        }
+
+        if experiment.challenger == pipeline {
+            return Err(ParseError::InvalidValue {
+                field: format!("registry[{}].experiment.challenger", index),
+                message: format!(
+                    "Challenger must differ from the champion pipeline '{}'",
+                    pipeline
+                ),
+            });
+        }
+
+        Ok(experiment)
     }
 
     /// Parse when block
@@ -404,4 +500,104 @@ registry:
         assert_eq!(deserialized.version, registry.version);
         assert_eq!(deserialized.registry.len(), registry.registry.len());
     }
+
+    #[test]
+    fn test_parse_registry_with_schedules() {
+        let yaml = r#"
+registry:
+  - pipeline: login_pipeline
+    when: event.type == "login"
+
+schedules:
+  - id: dormant_account_review
+    pipeline: dormant_account_pipeline
+    schedule: "0 2 * * *"
+    entities:
+      datasource: postgres_main
+      entity: accounts
+      when:
+        - status == "active"
+      limit: 10000
+    batch_size: 200
+"#;
+
+        let registry = RegistryParser::parse(yaml).unwrap();
+        assert_eq!(registry.schedules.len(), 1);
+
+        let schedule = registry.schedule("dormant_account_review").unwrap();
+        assert_eq!(schedule.pipeline, "dormant_account_pipeline");
+        assert_eq!(schedule.schedule, "0 2 * * *");
+        assert_eq!(schedule.entities.datasource, "postgres_main");
+        assert_eq!(schedule.entities.when.len(), 1);
+        assert_eq!(schedule.entities.limit, Some(10000));
+        assert_eq!(schedule.batch_size, 200);
+    }
+
+    #[test]
+    fn test_parse_registry_schedule_errors() {
+        let missing_entities = r#"
+registry: []
+schedules:
+  - id: nightly
+    pipeline: p
+    schedule: "0 2 * * *"
+"#;
+        assert!(matches!(
+            RegistryParser::parse(missing_entities),
+            Err(ParseError::MissingField { .. })
+        ));
+
+        let bad_cron = r#"
+registry: []
+schedules:
+  - id: nightly
+    pipeline: p
+    schedule: "daily"
+    entities:
+      datasource: db
+      entity: accounts
+"#;
+        assert!(matches!(
+            RegistryParser::parse(bad_cron),
+            Err(ParseError::InvalidValue { .. })
+        ));
+    }
+
+    #[test]
+    fn test_parse_registry_with_experiment() {
+        let yaml = r#"
+registry:
+  - pipeline: payment_pipeline
+    when: event.type == "payment"
+    experiment:
+      id: payment_v2_trial
+      challenger: payment_pipeline_v2
+      traffic_percent: 10
+      key: event.user.id
+"#;
+        let registry = RegistryParser::parse(yaml).unwrap();
+        let experiment = registry.registry[0].experiment.as_ref().unwrap();
+        assert_eq!(experiment.id, "payment_v2_trial");
+        assert_eq!(experiment.challenger, "payment_pipeline_v2");
+        assert_eq!(experiment.traffic_percent, 10.0);
+        assert_eq!(experiment.key, "event.user.id");
+
+        let too_much = yaml.replace("traffic_percent: 10", "traffic_percent: 150");
+        assert!(matches!(
+            RegistryParser::parse(&too_much),
+            Err(ParseError::InvalidValue { .. })
+        ));
+
+        let same_pipeline = yaml.replace("payment_pipeline_v2", "payment_pipeline");
+        assert!(matches!(
+            RegistryParser::parse(&same_pipeline),
+            Err(ParseError::InvalidValue { .. })
+        ));
+
+        let no_key = yaml.replace("      key: event.user.id\n", "");
+        assert!(matches!(
+            RegistryParser::parse(&no_key),
+            Err(ParseError::MissingField { .. })
+        ));
+    }
 }
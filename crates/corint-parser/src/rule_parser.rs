@@ -77,6 +77,12 @@ impl RuleParser {
         // Parse when block
         let when = Self::parse_when_block(rule_obj)?;
 
+        // Parse optional evaluation priority
+        let priority = YamlParser::get_optional_i32(rule_obj, "priority");
+
+        // Parse optional effective window
+        let (effective_from, effective_until) = YamlParser::get_effective_window(rule_obj)?;
+
         Ok(Rule {
             id,
             name,
@@ -84,6 +90,9 @@ impl RuleParser {
             params,
             when,
             score,
+            priority,
+            effective_from,
+            effective_until,
             metadata,
         })
     }
@@ -373,6 +382,37 @@ rule:
         assert_eq!(rule.when.conditions.as_ref().unwrap().len(), 3);
     }
 
+    #[test]
+    fn test_parse_rule_with_effective_window() {
+        let yaml = r#"
+rule:
+  id: holiday_promo
+  name: Holiday Promotion Abuse
+  effective_from: "2026-12-01T00:00:00Z"
+  effective_until: "2027-01-01T00:00:00Z"
+  when:
+    conditions:
+      - event.coupon_count > 3
+  score: 40
+"#;
+
+        let rule = RuleParser::parse(yaml).unwrap();
+        assert_eq!(rule.effective_from.as_deref(), Some("2026-12-01T00:00:00Z"));
+        assert_eq!(rule.effective_until.as_deref(), Some("2027-01-01T00:00:00Z"));
+
+        let invalid = yaml.replace("\"2026-12-01T00:00:00Z\"", "next week");
+        assert!(matches!(
+            RuleParser::parse(&invalid),
+            Err(ParseError::InvalidValue { ref field, .. }) if field == "effective_from"
+        ));
+
+        let reversed = yaml.replace("2027-01-01", "2026-11-01");
+        assert!(matches!(
+            RuleParser::parse(&reversed),
+            Err(ParseError::InvalidValue { ref field, .. }) if field == "effective_until"
+        ));
+    }
+
     #[test]
     fn test_parse_rule_without_event_type() {
         let yaml = r#"
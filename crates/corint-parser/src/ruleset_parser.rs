@@ -6,7 +6,7 @@ use crate::error::{ParseError, Result};
 use crate::expression_parser::ExpressionParser;
 use crate::import_parser::ImportParser;
 use crate::yaml_parser::YamlParser;
-use corint_core::ast::{DecisionRule, RdlDocument, Ruleset, Signal};
+use corint_core::ast::{DecisionRule, RdlDocument, Ruleset, ScoreAggregation, Signal};
 use serde_yaml::Value as YamlValue;
 
 /// Ruleset parser
@@ -78,11 +78,27 @@ impl RulesetParser {
         // Parse optional description
         let description = YamlParser::get_optional_string(ruleset_obj, "description");
 
+        // Parse optional effective window
+        let (effective_from, effective_until) = YamlParser::get_effective_window(ruleset_obj)?;
+
         // Parse optional metadata
         let metadata = ruleset_obj
             .get("metadata")
             .and_then(|v| serde_yaml::from_value(v.clone()).ok());
 
+        // Parse optional score aggregation
+        let scoring = ruleset_obj
+            .get("scoring")
+            .map(Self::parse_scoring)
+            .transpose()?;
+
+        // Parse optional early termination
+        let stop_on_score = YamlParser::get_optional_i32(ruleset_obj, "stop_on_score");
+        let stop_on_signal = YamlParser::get_optional_string(ruleset_obj, "stop_on_signal")
+            .or_else(|| YamlParser::get_optional_string(ruleset_obj, "stop_on_action"))
+            .map(|s| Self::signal_from_str(&s))
+            .transpose()?;
+
         // Parse conclusion (ruleset's decision rules)
         let conclusion = if let Some(logic_array) = ruleset_obj
             .get("conclusion")
@@ -103,10 +119,49 @@ impl RulesetParser {
             rules,
             conclusion,
             description,
+            effective_from,
+            effective_until,
             metadata,
+            scoring,
+            stop_on_score,
+            stop_on_signal,
         })
     }
 
+    /// Parse how the scores of the rules are combined
+    fn parse_scoring(yaml: &YamlValue) -> Result<ScoreAggregation> {
+        let scoring: ScoreAggregation =
+            serde_yaml::from_value(yaml.clone()).map_err(|e| ParseError::InvalidValue {
+                field: "scoring".to_string(),
+                message: e.to_string(),
+            })?;
+
+        if let (Some(min), Some(max)) = (scoring.min, scoring.max) {
+            if min >= max {
+                return Err(ParseError::InvalidValue {
+                    field: "scoring.min".to_string(),
+                    message: format!("Expected min below max ({}), got {}", max, min),
+                });
+            }
+        }
+
+        if scoring.normalize && scoring.max.is_none() {
+            return Err(ParseError::InvalidValue {
+                field: "scoring.normalize".to_string(),
+                message: "Normalizing to 0-100 requires a max score".to_string(),
+            });
+        }
+
+        if let Some((rule_id, weight)) = scoring.weights.iter().find(|(_, w)| !w.is_finite()) {
+            return Err(ParseError::InvalidValue {
+                field: format!("scoring.weights.{}", rule_id),
+                message: format!("Expected a finite weight, got {}", weight),
+            });
+        }
+
+        Ok(scoring)
+    }
+
     /// Parse a decision rule (public for template parser use)
     pub fn parse_decision_rule(yaml: &YamlValue) -> Result<DecisionRule> {
         // Parse when condition (optional)
@@ -126,12 +181,16 @@ impl RulesetParser {
         // Parse reason (optional)
         let reason = YamlParser::get_optional_string(yaml, "reason");
 
+        // Parse reason code (optional, references the reason code catalog)
+        let reason_code = YamlParser::get_optional_string(yaml, "reason_code");
+
         Ok(DecisionRule {
             condition,
             default,
             signal,
             actions,
             reason,
+            reason_code,
         })
     }
 
@@ -144,7 +203,12 @@ impl RulesetParser {
                 field: "signal (or action)".to_string(),
             })?;
 
-        match signal_str.as_str() {
+        Self::signal_from_str(&signal_str)
+    }
+
+    /// Convert a signal name to a signal
+    pub(crate) fn signal_from_str(signal_str: &str) -> Result<Signal> {
+        match signal_str {
             "approve" => Ok(Signal::Approve),
             "decline" => Ok(Signal::Decline),
             // Keep "deny" as alias for backward compatibility
@@ -319,4 +383,67 @@ ruleset:
         let result = RulesetParser::parse(yaml);
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_parse_ruleset_scoring() {
+        use corint_core::ast::ScoreStrategy;
+
+        let yaml = r#"
+ruleset:
+  id: payment_risk
+  rules:
+    - velocity
+    - new_device
+  scoring:
+    strategy: weighted_sum
+    weights:
+      velocity: 1.5
+    max: 200
+    normalize: true
+  conclusion:
+    - when: total_score >= 80
+      signal: decline
+"#;
+
+        let ruleset = RulesetParser::parse(yaml).unwrap();
+        let scoring = ruleset.scoring.unwrap();
+        assert_eq!(scoring.strategy, ScoreStrategy::WeightedSum);
+        assert_eq!(scoring.weights["velocity"], 1.5);
+        assert_eq!(scoring.max, Some(200));
+        assert!(scoring.normalize);
+
+        for scoring in [
+            "strategy: median",
+            "normalize: true",
+            "min: 100\n    max: 50",
+        ] {
+            let yaml = format!(
+                "ruleset:\n  id: bad\n  rules: []\n  scoring:\n    {}\n  conclusion: []\n",
+                scoring
+            );
+            assert!(RulesetParser::parse(&yaml).is_err(), "{}", scoring);
+        }
+    }
+
+    #[test]
+    fn test_parse_ruleset_early_termination() {
+        let yaml = r#"
+ruleset:
+  id: payment_risk
+  rules:
+    - velocity
+  stop_on_score: 100
+  stop_on_action: deny
+  conclusion:
+    - default: true
+      signal: approve
+"#;
+
+        let ruleset = RulesetParser::parse(yaml).unwrap();
+        assert_eq!(ruleset.stop_on_score, Some(100));
+        assert_eq!(ruleset.stop_on_signal, Some(Signal::Decline));
+
+        let yaml = "ruleset:\n  id: bad\n  rules: []\n  stop_on_signal: block\n";
+        assert!(RulesetParser::parse(yaml).is_err());
+    }
 }
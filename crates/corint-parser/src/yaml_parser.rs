@@ -206,6 +206,37 @@ impl YamlParser {
             .map(|s| s.to_string())
     }
 
+    /// Get the optional `effective_from` / `effective_until` window of a definition
+    ///
+    /// Both bounds must be RFC 3339 timestamps and `effective_from` must precede
+    /// `effective_until` when both are set.
+    pub fn get_effective_window(obj: &YamlValue) -> Result<(Option<String>, Option<String>)> {
+        let parse_bound = |field: &str| -> Result<Option<(String, chrono::DateTime<chrono::FixedOffset>)>> {
+            match Self::get_optional_string(obj, field) {
+                Some(value) => chrono::DateTime::parse_from_rfc3339(&value)
+                    .map(|at| Some((value, at)))
+                    .map_err(|e| ParseError::InvalidValue {
+                        field: field.to_string(),
+                        message: format!("expected an RFC 3339 timestamp: {}", e),
+                    }),
+                None => Ok(None),
+            }
+        };
+
+        let from = parse_bound("effective_from")?;
+        let until = parse_bound("effective_until")?;
+        if let (Some((_, from_at)), Some((_, until_at))) = (&from, &until) {
+            if from_at >= until_at {
+                return Err(ParseError::InvalidValue {
+                    field: "effective_until".to_string(),
+                    message: "must be later than effective_from".to_string(),
+                });
+            }
+        }
+
+        Ok((from.map(|(value, _)| value), until.map(|(value, _)| value)))
+    }
+
     /// Convert YAML mapping to HashMap<String, String>
     pub fn mapping_to_hashmap(mapping: &serde_yaml::Mapping) -> HashMap<String, String> {
         let mut map = HashMap::new();
@@ -22,12 +22,22 @@ CREATE INDEX IF NOT EXISTS idx_rulesets_updated_at ON rulesets(updated_at DESC);
 CREATE INDEX IF NOT EXISTS idx_rulesets_created_at ON rulesets(created_at DESC);
 
 -- Add foreign key constraint for extends (self-referential)
--- Note: We use ON DELETE SET NULL so deleting a parent doesn't cascade
-ALTER TABLE rulesets
-ADD CONSTRAINT fk_rulesets_extends
-FOREIGN KEY (extends)
-REFERENCES rulesets(id)
-ON DELETE SET NULL;
+-- Note: We use ON DELETE SET NULL so deleting a parent doesn't cascade.
+-- Skipped on schemas applied by hand up to 009, which drops this key.
+DO $$
+BEGIN
+    IF NOT EXISTS (SELECT 1 FROM pg_constraint WHERE conname = 'fk_rulesets_extends')
+       AND NOT EXISTS (
+           SELECT 1 FROM information_schema.columns
+           WHERE table_name = 'rulesets' AND column_name = 'status'
+       ) THEN
+        ALTER TABLE rulesets
+        ADD CONSTRAINT fk_rulesets_extends
+        FOREIGN KEY (extends)
+        REFERENCES rulesets(id)
+        ON DELETE SET NULL;
+    END IF;
+END $$;
 
 -- Add comments for documentation
 COMMENT ON TABLE rulesets IS 'Stores ruleset definitions in YAML format';
@@ -27,12 +27,18 @@ CREATE INDEX IF NOT EXISTS idx_audit_operation ON artifact_audit_log(operation);
 CREATE INDEX IF NOT EXISTS idx_audit_artifact_time
 ON artifact_audit_log(artifact_id, changed_at DESC);
 
--- Add check constraint for artifact_type
-ALTER TABLE artifact_audit_log
-ADD CONSTRAINT chk_artifact_type
-CHECK (artifact_type IN ('rule', 'ruleset', 'template', 'pipeline'));
+-- Add check constraint for artifact_type (kept if 008 already widened it)
+DO $$
+BEGIN
+    IF NOT EXISTS (SELECT 1 FROM pg_constraint WHERE conname = 'chk_artifact_type') THEN
+        ALTER TABLE artifact_audit_log
+        ADD CONSTRAINT chk_artifact_type
+        CHECK (artifact_type IN ('rule', 'ruleset', 'template', 'pipeline'));
+    END IF;
+END $$;
 
 -- Add check constraint for operation
+ALTER TABLE artifact_audit_log DROP CONSTRAINT IF EXISTS chk_operation;
 ALTER TABLE artifact_audit_log
 ADD CONSTRAINT chk_operation
 CHECK (operation IN ('create', 'update', 'delete'));
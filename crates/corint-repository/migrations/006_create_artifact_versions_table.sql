@@ -0,0 +1,33 @@
+-- Create artifact versions table
+-- Migration: 006_create_artifact_versions_table
+-- Description: Keep the content of every saved version so versions can be compared
+
+CREATE TABLE IF NOT EXISTS artifact_versions (
+    artifact_type VARCHAR(50) NOT NULL,  -- 'rule', 'ruleset', 'pipeline'
+    artifact_id VARCHAR(255) NOT NULL,
+    version INTEGER NOT NULL,
+    content TEXT NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    created_by VARCHAR(255),
+
+    PRIMARY KEY (artifact_type, artifact_id, version)
+);
+
+-- Create indexes for efficient querying
+CREATE INDEX IF NOT EXISTS idx_artifact_versions_lookup ON artifact_versions(artifact_id, version);
+CREATE INDEX IF NOT EXISTS idx_artifact_versions_created_at ON artifact_versions(created_at DESC);
+
+-- Add check constraint for artifact_type
+ALTER TABLE artifact_versions DROP CONSTRAINT IF EXISTS chk_artifact_versions_type;
+ALTER TABLE artifact_versions
+ADD CONSTRAINT chk_artifact_versions_type
+CHECK (artifact_type IN ('rule', 'ruleset', 'pipeline'));
+
+-- Add comments for documentation
+COMMENT ON TABLE artifact_versions IS 'Immutable history of every saved artifact version';
+COMMENT ON COLUMN artifact_versions.artifact_type IS 'Type of artifact: rule, ruleset, or pipeline';
+COMMENT ON COLUMN artifact_versions.artifact_id IS 'ID of the artifact';
+COMMENT ON COLUMN artifact_versions.version IS 'Version number at the time of saving';
+COMMENT ON COLUMN artifact_versions.content IS 'Full YAML content of this version';
+COMMENT ON COLUMN artifact_versions.created_at IS 'Timestamp when this version was saved';
+COMMENT ON COLUMN artifact_versions.created_by IS 'User or system that saved this version';
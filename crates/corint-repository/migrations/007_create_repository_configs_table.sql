@@ -0,0 +1,31 @@
+-- Create repository configs table
+-- Migration: 007_create_repository_configs_table
+-- Description: Store registry and config files (lists, features, API configs, reason codes)
+--              so whole repositories can be promoted between databases as bundles
+
+CREATE TABLE IF NOT EXISTS repository_configs (
+    config_type VARCHAR(50) NOT NULL,   -- 'registry', 'list', 'feature', 'api_config', 'reason_codes', 'masking_policy'
+    id VARCHAR(512) NOT NULL,
+    content TEXT NOT NULL,
+    version INTEGER NOT NULL DEFAULT 1,
+    updated_at TIMESTAMP NOT NULL DEFAULT NOW(),
+
+    PRIMARY KEY (config_type, id)
+);
+
+-- Create indexes for efficient querying
+CREATE INDEX IF NOT EXISTS idx_repository_configs_updated_at ON repository_configs(updated_at DESC);
+
+-- Add check constraint for config_type
+ALTER TABLE repository_configs DROP CONSTRAINT IF EXISTS chk_repository_configs_type;
+ALTER TABLE repository_configs
+ADD CONSTRAINT chk_repository_configs_type
+CHECK (config_type IN ('registry', 'list', 'feature', 'api_config', 'reason_codes', 'masking_policy'));
+
+-- Add comments for documentation
+COMMENT ON TABLE repository_configs IS 'Registry and config files imported from repository bundles';
+COMMENT ON COLUMN repository_configs.config_type IS 'Type of config: registry, list, feature, api_config, reason_codes, or masking_policy';
+COMMENT ON COLUMN repository_configs.id IS 'Config identifier (file path relative to its config directory, without extension)';
+COMMENT ON COLUMN repository_configs.content IS 'Full YAML content of the config file';
+COMMENT ON COLUMN repository_configs.version IS 'Version number, incremented on each import';
+COMMENT ON COLUMN repository_configs.updated_at IS 'Timestamp when the config was last imported';
@@ -0,0 +1,13 @@
+-- Extend audit log artifact types
+-- Migration: 008_extend_audit_log_artifact_types
+-- Description: Allow audit entries for registry and config artifacts imported from bundles
+
+ALTER TABLE artifact_audit_log
+DROP CONSTRAINT IF EXISTS chk_artifact_type;
+
+ALTER TABLE artifact_audit_log
+ADD CONSTRAINT chk_artifact_type
+CHECK (artifact_type IN (
+    'rule', 'ruleset', 'template', 'pipeline',
+    'registry', 'list', 'feature', 'api_config', 'reason_codes', 'masking_policy'
+));
@@ -0,0 +1,41 @@
+-- Add lifecycle status to definitions
+-- Migration: 009_add_artifact_status
+-- Description: Track draft/published/archived status so drafts can coexist with the live version
+
+ALTER TABLE rules ADD COLUMN IF NOT EXISTS status VARCHAR(20) NOT NULL DEFAULT 'published';
+ALTER TABLE rulesets ADD COLUMN IF NOT EXISTS status VARCHAR(20) NOT NULL DEFAULT 'published';
+ALTER TABLE pipelines ADD COLUMN IF NOT EXISTS status VARCHAR(20) NOT NULL DEFAULT 'published';
+
+-- One row per artifact and status: a draft and a published version may exist side by side.
+-- Ruleset IDs are no longer unique on their own, so `extends` can't be a foreign key;
+-- the compiler already reports missing parents (ExtendsNotFound).
+ALTER TABLE rulesets DROP CONSTRAINT IF EXISTS fk_rulesets_extends;
+ALTER TABLE rules DROP CONSTRAINT IF EXISTS rules_pkey;
+ALTER TABLE rules ADD PRIMARY KEY (id, status);
+ALTER TABLE rulesets DROP CONSTRAINT IF EXISTS rulesets_pkey;
+ALTER TABLE rulesets ADD PRIMARY KEY (id, status);
+ALTER TABLE pipelines DROP CONSTRAINT IF EXISTS pipelines_pkey;
+ALTER TABLE pipelines ADD PRIMARY KEY (id, status);
+
+ALTER TABLE rules DROP CONSTRAINT IF EXISTS chk_rules_status;
+ALTER TABLE rules
+ADD CONSTRAINT chk_rules_status
+CHECK (status IN ('draft', 'published', 'archived'));
+
+ALTER TABLE rulesets DROP CONSTRAINT IF EXISTS chk_rulesets_status;
+ALTER TABLE rulesets
+ADD CONSTRAINT chk_rulesets_status
+CHECK (status IN ('draft', 'published', 'archived'));
+
+ALTER TABLE pipelines DROP CONSTRAINT IF EXISTS chk_pipelines_status;
+ALTER TABLE pipelines
+ADD CONSTRAINT chk_pipelines_status
+CHECK (status IN ('draft', 'published', 'archived'));
+
+CREATE INDEX IF NOT EXISTS idx_rules_status ON rules(status);
+CREATE INDEX IF NOT EXISTS idx_rulesets_status ON rulesets(status);
+CREATE INDEX IF NOT EXISTS idx_pipelines_status ON pipelines(status);
+
+COMMENT ON COLUMN rules.status IS 'Lifecycle status: draft, published (served by load_rule) or archived';
+COMMENT ON COLUMN rulesets.status IS 'Lifecycle status: draft, published (served by load_ruleset) or archived';
+COMMENT ON COLUMN pipelines.status IS 'Lifecycle status: draft, published (served by load_pipeline) or archived';
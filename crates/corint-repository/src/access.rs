@@ -0,0 +1,778 @@
+//! Repository access control and audit trail
+//!
+//! [`AuthorizedRepository`] wraps any [`WritableRepository`] and requires an
+//! [`Actor`] for every write. Each save or delete is checked against an
+//! [`AccessPolicy`] (role grants scoped by artifact kind and ID pattern) and,
+//! when allowed, recorded in an [`AuditLog`] so every rule modification can be
+//! traced back to who changed what and when.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use corint_repository::{
+//!     AccessPolicy, Actor, AuditQuery, AuthorizedRepository, BundleArtifactKind, Grant,
+//!     FileSystemRepository, InMemoryAuditLog, Permission, Repository,
+//! };
+//! use std::sync::Arc;
+//!
+//! # #[tokio::main]
+//! # async fn main() -> anyhow::Result<()> {
+//! let policy = AccessPolicy::new()
+//!     .with_grant(Grant::new("admin", [Permission::Write, Permission::Delete]))
+//!     .with_grant(
+//!         Grant::new("fraud_analyst", [Permission::Write])
+//!             .for_kind(BundleArtifactKind::Rule)
+//!             .matching("fraud_*"),
+//!     );
+//!
+//! let repo = FileSystemRepository::new("repository")?;
+//! let mut repo = AuthorizedRepository::new(repo, policy, Arc::new(InMemoryAuditLog::new()));
+//!
+//! let analyst = Actor::new("alice").with_role("fraud_analyst");
+//! let (mut rule, _) = repo.load_rule("fraud_farm").await?;
+//! rule.score = 80;
+//! repo.save_rule(&analyst, &rule).await?;
+//!
+//! let history = repo.audit_log(&AuditQuery::new().for_artifact("fraud_farm")).await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use async_trait::async_trait;
+use corint_core::ast::{Pipeline, Rule, Ruleset};
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::{
+    bundle::{BundleArtifactKind, RepositoryBundle},
+    error::RepositoryError,
+    models::ArtifactStatus,
+    traits::{Repository, WritableRepository},
+    RepositoryResult,
+};
+
+/// Identity performing a repository change
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Actor {
+    /// User or service identifier recorded in the audit log
+    pub id: String,
+
+    /// Roles used to evaluate the access policy
+    #[serde(default)]
+    pub roles: Vec<String>,
+}
+
+impl Actor {
+    /// Create an actor without roles
+    pub fn new(id: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            roles: Vec::new(),
+        }
+    }
+
+    /// Add a role
+    pub fn with_role(mut self, role: impl Into<String>) -> Self {
+        self.roles.push(role.into());
+        self
+    }
+}
+
+/// Operation an actor may be granted on an artifact
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Permission {
+    /// Create or update artifacts
+    Write,
+    /// Delete artifacts
+    Delete,
+}
+
+/// Permissions granted to a role, optionally scoped to an artifact kind and ID pattern
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Grant {
+    /// Role receiving the grant
+    pub role: String,
+
+    /// Artifact kind the grant applies to (all kinds if not set)
+    #[serde(default)]
+    pub kind: Option<BundleArtifactKind>,
+
+    /// Artifact ID pattern; `*` matches any sequence of characters
+    #[serde(default = "default_pattern")]
+    pub pattern: String,
+
+    /// Granted permissions
+    pub permissions: Vec<Permission>,
+}
+
+fn default_pattern() -> String {
+    "*".to_string()
+}
+
+impl Grant {
+    /// Grant permissions on every artifact to a role
+    pub fn new(role: impl Into<String>, permissions: impl IntoIterator<Item = Permission>) -> Self {
+        Self {
+            role: role.into(),
+            kind: None,
+            pattern: default_pattern(),
+            permissions: permissions.into_iter().collect(),
+        }
+    }
+
+    /// Restrict the grant to one artifact kind
+    pub fn for_kind(mut self, kind: BundleArtifactKind) -> Self {
+        self.kind = Some(kind);
+        self
+    }
+
+    /// Restrict the grant to artifact IDs matching a pattern
+    pub fn matching(mut self, pattern: impl Into<String>) -> Self {
+        self.pattern = pattern.into();
+        self
+    }
+
+    /// Whether this grant allows `permission` on the artifact
+    fn allows(&self, kind: BundleArtifactKind, id: &str, permission: Permission) -> bool {
+        self.kind.is_none_or(|k| k == kind)
+            && self.permissions.contains(&permission)
+            && matches_pattern(&self.pattern, id)
+    }
+}
+
+/// Match an ID against a pattern where `*` matches any sequence of characters
+pub(crate) fn matches_pattern(pattern: &str, id: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = id.strip_prefix(first) else {
+        return false;
+    };
+
+    let parts: Vec<&str> = parts.collect();
+    let Some((last, middle)) = parts.split_last() else {
+        // No wildcard: exact match
+        return rest.is_empty();
+    };
+
+    for part in middle {
+        match rest.find(part) {
+            Some(pos) => rest = &rest[pos + part.len()..],
+            None => return false,
+        }
+    }
+    rest.ends_with(last)
+}
+
+/// Role-based access policy for repository writes
+///
+/// Access is denied unless at least one grant for one of the actor's roles
+/// allows the operation.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AccessPolicy {
+    /// Role grants
+    #[serde(default)]
+    pub grants: Vec<Grant>,
+}
+
+impl AccessPolicy {
+    /// Create an empty policy (denies everything)
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a grant
+    pub fn with_grant(mut self, grant: Grant) -> Self {
+        self.grants.push(grant);
+        self
+    }
+
+    /// Check whether an actor may perform `permission` on an artifact
+    pub fn check(
+        &self,
+        actor: &Actor,
+        kind: BundleArtifactKind,
+        id: &str,
+        permission: Permission,
+    ) -> RepositoryResult<()> {
+        let allowed = self
+            .grants
+            .iter()
+            .filter(|g| actor.roles.contains(&g.role))
+            .any(|g| g.allows(kind, id, permission));
+
+        if allowed {
+            Ok(())
+        } else {
+            Err(RepositoryError::PermissionDenied {
+                actor: actor.id.clone(),
+                action: format!("{:?}", permission).to_lowercase(),
+                artifact: format!("{}:{}", kind.as_str(), id),
+            })
+        }
+    }
+}
+
+/// Kind of change recorded in the audit log
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditOperation {
+    Create,
+    Update,
+    Delete,
+}
+
+impl AuditOperation {
+    /// Stable name used in storage backends
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Create => "create",
+            Self::Update => "update",
+            Self::Delete => "delete",
+        }
+    }
+
+    /// Parse an operation from its stable name
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "create" => Some(Self::Create),
+            "update" => Some(Self::Update),
+            "delete" => Some(Self::Delete),
+            _ => None,
+        }
+    }
+}
+
+/// A single audited repository change
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuditEntry {
+    /// Artifact kind ("rule", "ruleset", "pipeline", ...)
+    pub artifact_type: String,
+
+    /// Artifact ID
+    pub artifact_id: String,
+
+    /// Kind of change
+    pub operation: AuditOperation,
+
+    /// Actor who made the change
+    pub actor: String,
+
+    /// Time of the change (seconds since the Unix epoch)
+    pub changed_at: i64,
+
+    /// New content (not set for deletes)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content: Option<String>,
+}
+
+impl AuditEntry {
+    /// Create an entry timestamped now
+    pub fn new(
+        kind: BundleArtifactKind,
+        artifact_id: impl Into<String>,
+        operation: AuditOperation,
+        actor: impl Into<String>,
+    ) -> Self {
+        let changed_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs() as i64)
+            .unwrap_or_default();
+
+        Self {
+            artifact_type: kind.as_str().to_string(),
+            artifact_id: artifact_id.into(),
+            operation,
+            actor: actor.into(),
+            changed_at,
+            content: None,
+        }
+    }
+
+    /// Attach the new content
+    pub fn with_content(mut self, content: impl Into<String>) -> Self {
+        self.content = Some(content.into());
+        self
+    }
+}
+
+/// Filter for audit log queries
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuditQuery {
+    /// Only entries for this artifact kind
+    #[serde(default)]
+    pub artifact_type: Option<String>,
+
+    /// Only entries for this artifact ID
+    #[serde(default)]
+    pub artifact_id: Option<String>,
+
+    /// Only entries made by this actor
+    #[serde(default)]
+    pub actor: Option<String>,
+
+    /// Only entries at or after this time (seconds since the Unix epoch)
+    #[serde(default)]
+    pub since: Option<i64>,
+
+    /// Maximum number of entries to return (newest first)
+    #[serde(default)]
+    pub limit: Option<usize>,
+}
+
+impl AuditQuery {
+    /// Match every entry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only entries for one artifact ID
+    pub fn for_artifact(mut self, artifact_id: impl Into<String>) -> Self {
+        self.artifact_id = Some(artifact_id.into());
+        self
+    }
+
+    /// Only entries made by one actor
+    pub fn by_actor(mut self, actor: impl Into<String>) -> Self {
+        self.actor = Some(actor.into());
+        self
+    }
+
+    /// Limit the number of returned entries
+    pub fn with_limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Whether an entry matches this query (ignores `limit`)
+    pub fn matches(&self, entry: &AuditEntry) -> bool {
+        self.artifact_type
+            .as_ref()
+            .is_none_or(|t| *t == entry.artifact_type)
+            && self
+                .artifact_id
+                .as_ref()
+                .is_none_or(|id| *id == entry.artifact_id)
+            && self.actor.as_ref().is_none_or(|a| *a == entry.actor)
+            && self.since.is_none_or(|since| entry.changed_at >= since)
+    }
+}
+
+/// Storage for audit entries
+#[async_trait]
+pub trait AuditLog: Send + Sync {
+    /// Append an entry
+    async fn record(&self, entry: AuditEntry) -> RepositoryResult<()>;
+
+    /// Query entries, newest first
+    async fn query(&self, query: &AuditQuery) -> RepositoryResult<Vec<AuditEntry>>;
+}
+
+/// In-memory audit log (for tests and single-process deployments)
+#[derive(Debug, Default)]
+pub struct InMemoryAuditLog {
+    entries: Mutex<Vec<AuditEntry>>,
+}
+
+impl InMemoryAuditLog {
+    /// Create an empty audit log
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl AuditLog for InMemoryAuditLog {
+    async fn record(&self, entry: AuditEntry) -> RepositoryResult<()> {
+        self.entries.lock().unwrap().push(entry);
+        Ok(())
+    }
+
+    async fn query(&self, query: &AuditQuery) -> RepositoryResult<Vec<AuditEntry>> {
+        let entries = self.entries.lock().unwrap();
+        Ok(entries
+            .iter()
+            .rev()
+            .filter(|e| query.matches(e))
+            .take(query.limit.unwrap_or(usize::MAX))
+            .cloned()
+            .collect())
+    }
+}
+
+/// Writable repository wrapper that enforces an access policy and audits every change
+pub struct AuthorizedRepository<R> {
+    inner: R,
+    policy: AccessPolicy,
+    audit: Arc<dyn AuditLog>,
+}
+
+impl<R: WritableRepository> AuthorizedRepository<R> {
+    /// Wrap a repository
+    pub fn new(inner: R, policy: AccessPolicy, audit: Arc<dyn AuditLog>) -> Self {
+        Self {
+            inner,
+            policy,
+            audit,
+        }
+    }
+
+    /// Access the wrapped repository
+    pub fn inner(&self) -> &R {
+        &self.inner
+    }
+
+    /// Current access policy
+    pub fn policy(&self) -> &AccessPolicy {
+        &self.policy
+    }
+
+    /// Replace the access policy
+    pub fn set_policy(&mut self, policy: AccessPolicy) {
+        self.policy = policy;
+    }
+
+    /// Query the audit trail
+    pub async fn audit_log(&self, query: &AuditQuery) -> RepositoryResult<Vec<AuditEntry>> {
+        self.audit.query(query).await
+    }
+
+    /// Create or update depending on whether the artifact already exists
+    ///
+    /// Loads by kind rather than calling `exists`, since some backends only
+    /// resolve paths there.
+    async fn write_operation(&self, kind: BundleArtifactKind, id: &str) -> AuditOperation {
+        let exists = match kind {
+            BundleArtifactKind::Rule => self.inner.load_rule(id).await.is_ok(),
+            BundleArtifactKind::Ruleset => self.inner.load_ruleset(id).await.is_ok(),
+            BundleArtifactKind::Pipeline => self.inner.load_pipeline(id).await.is_ok(),
+            _ => true,
+        };
+
+        if exists {
+            AuditOperation::Update
+        } else {
+            AuditOperation::Create
+        }
+    }
+
+    /// Save a rule on behalf of an actor
+    pub async fn save_rule(&mut self, actor: &Actor, rule: &Rule) -> RepositoryResult<()> {
+        let kind = BundleArtifactKind::Rule;
+        self.policy
+            .check(actor, kind, &rule.id, Permission::Write)?;
+
+        let operation = self.write_operation(kind, &rule.id).await;
+        self.inner.save_rule(rule).await?;
+
+        let content = serde_yaml::to_string(rule)?;
+        self.audit
+            .record(AuditEntry::new(kind, &rule.id, operation, &actor.id).with_content(content))
+            .await
+    }
+
+    /// Save a ruleset on behalf of an actor
+    pub async fn save_ruleset(&mut self, actor: &Actor, ruleset: &Ruleset) -> RepositoryResult<()> {
+        let kind = BundleArtifactKind::Ruleset;
+        self.policy
+            .check(actor, kind, &ruleset.id, Permission::Write)?;
+
+        let operation = self.write_operation(kind, &ruleset.id).await;
+        self.inner.save_ruleset(ruleset).await?;
+
+        let content = serde_yaml::to_string(ruleset)?;
+        self.audit
+            .record(AuditEntry::new(kind, &ruleset.id, operation, &actor.id).with_content(content))
+            .await
+    }
+
+    /// Save a pipeline on behalf of an actor
+    pub async fn save_pipeline(
+        &mut self,
+        actor: &Actor,
+        pipeline: &Pipeline,
+    ) -> RepositoryResult<()> {
+        let kind = BundleArtifactKind::Pipeline;
+        self.policy
+            .check(actor, kind, &pipeline.id, Permission::Write)?;
+
+        let operation = self.write_operation(kind, &pipeline.id).await;
+        self.inner.save_pipeline(pipeline).await?;
+
+        let content = serde_yaml::to_string(pipeline)?;
+        self.audit
+            .record(AuditEntry::new(kind, &pipeline.id, operation, &actor.id).with_content(content))
+            .await
+    }
+
+    /// Delete a rule on behalf of an actor
+    pub async fn delete_rule(&self, actor: &Actor, identifier: &str) -> RepositoryResult<()> {
+        self.delete(actor, BundleArtifactKind::Rule, identifier)
+            .await
+    }
+
+    /// Delete a ruleset on behalf of an actor
+    pub async fn delete_ruleset(&self, actor: &Actor, identifier: &str) -> RepositoryResult<()> {
+        self.delete(actor, BundleArtifactKind::Ruleset, identifier)
+            .await
+    }
+
+    /// Delete a pipeline on behalf of an actor
+    pub async fn delete_pipeline(&self, actor: &Actor, identifier: &str) -> RepositoryResult<()> {
+        self.delete(actor, BundleArtifactKind::Pipeline, identifier)
+            .await
+    }
+
+    async fn delete(
+        &self,
+        actor: &Actor,
+        kind: BundleArtifactKind,
+        identifier: &str,
+    ) -> RepositoryResult<()> {
+        self.policy
+            .check(actor, kind, identifier, Permission::Delete)?;
+
+        match kind {
+            BundleArtifactKind::Rule => self.inner.delete_rule(identifier).await?,
+            BundleArtifactKind::Ruleset => self.inner.delete_ruleset(identifier).await?,
+            _ => self.inner.delete_pipeline(identifier).await?,
+        }
+
+        self.audit
+            .record(AuditEntry::new(
+                kind,
+                identifier,
+                AuditOperation::Delete,
+                &actor.id,
+            ))
+            .await
+    }
+
+    /// Save a draft on behalf of an actor
+    pub async fn save_draft(
+        &mut self,
+        actor: &Actor,
+        kind: BundleArtifactKind,
+        id: &str,
+        content: &str,
+    ) -> RepositoryResult<()> {
+        self.policy.check(actor, kind, id, Permission::Write)?;
+
+        let operation = if self.inner.load_draft(kind, id).await.is_ok() {
+            AuditOperation::Update
+        } else {
+            AuditOperation::Create
+        };
+        self.inner.save_draft(kind, id, content).await?;
+
+        self.audit
+            .record(AuditEntry::new(kind, id, operation, &actor.id).with_content(content))
+            .await
+    }
+
+    /// Change the lifecycle status of an artifact on behalf of an actor
+    ///
+    /// Publishing or archiving changes live traffic, so it needs write permission.
+    pub async fn set_status(
+        &mut self,
+        actor: &Actor,
+        kind: BundleArtifactKind,
+        identifier: &str,
+        status: ArtifactStatus,
+    ) -> RepositoryResult<()> {
+        self.policy
+            .check(actor, kind, identifier, Permission::Write)?;
+
+        self.inner.set_status(kind, identifier, status).await?;
+
+        self.audit
+            .record(
+                AuditEntry::new(kind, identifier, AuditOperation::Update, &actor.id)
+                    .with_content(format!("status: {}", status.as_str())),
+            )
+            .await
+    }
+
+    /// Import a bundle on behalf of an actor
+    ///
+    /// The actor needs write permission on every artifact in the bundle.
+    pub async fn import_bundle(
+        &mut self,
+        actor: &Actor,
+        bundle: &RepositoryBundle,
+        signing_key: Option<&[u8]>,
+    ) -> RepositoryResult<usize> {
+        for artifact in &bundle.artifacts {
+            self.policy
+                .check(actor, artifact.kind, &artifact.id, Permission::Write)?;
+        }
+
+        let mut operations = Vec::with_capacity(bundle.artifacts.len());
+        for artifact in &bundle.artifacts {
+            operations.push(self.write_operation(artifact.kind, &artifact.id).await);
+        }
+
+        let imported = self.inner.import_bundle(bundle, signing_key).await?;
+
+        for (artifact, operation) in bundle.artifacts.iter().zip(operations) {
+            self.audit
+                .record(
+                    AuditEntry::new(artifact.kind, &artifact.id, operation, &actor.id)
+                        .with_content(artifact.content.clone()),
+                )
+                .await?;
+        }
+
+        Ok(imported)
+    }
+}
+
+#[async_trait]
+impl<R: WritableRepository> Repository for AuthorizedRepository<R> {
+    async fn load_rule(&self, identifier: &str) -> RepositoryResult<(Rule, String)> {
+        self.inner.load_rule(identifier).await
+    }
+
+    async fn load_ruleset(&self, identifier: &str) -> RepositoryResult<(Ruleset, String)> {
+        self.inner.load_ruleset(identifier).await
+    }
+
+    async fn load_pipeline(&self, identifier: &str) -> RepositoryResult<(Pipeline, String)> {
+        self.inner.load_pipeline(identifier).await
+    }
+
+    async fn exists(&self, identifier: &str) -> RepositoryResult<bool> {
+        self.inner.exists(identifier).await
+    }
+
+    async fn list_rules(&self) -> RepositoryResult<Vec<String>> {
+        self.inner.list_rules().await
+    }
+
+    async fn list_rulesets(&self) -> RepositoryResult<Vec<String>> {
+        self.inner.list_rulesets().await
+    }
+
+    async fn list_pipelines(&self) -> RepositoryResult<Vec<String>> {
+        self.inner.list_pipelines().await
+    }
+
+    async fn load_registry(&self) -> RepositoryResult<String> {
+        self.inner.load_registry().await
+    }
+
+    async fn export_bundle(&self) -> RepositoryResult<RepositoryBundle> {
+        self.inner.export_bundle().await
+    }
+
+    async fn load_draft(
+        &self,
+        kind: BundleArtifactKind,
+        identifier: &str,
+    ) -> RepositoryResult<String> {
+        self.inner.load_draft(kind, identifier).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_matches_pattern() {
+        assert!(matches_pattern("*", "anything"));
+        assert!(matches_pattern("fraud_*", "fraud_farm"));
+        assert!(matches_pattern("*_check", "velocity_check"));
+        assert!(matches_pattern("fraud_*_v2", "fraud_farm_v2"));
+        assert!(matches_pattern("fraud_farm", "fraud_farm"));
+        assert!(!matches_pattern("fraud_farm", "fraud_farm_v2"));
+        assert!(!matches_pattern("fraud_*", "velocity_check"));
+    }
+
+    #[test]
+    fn test_access_policy_check() {
+        let policy = AccessPolicy::new()
+            .with_grant(Grant::new("admin", [Permission::Write, Permission::Delete]))
+            .with_grant(
+                Grant::new("fraud_analyst", [Permission::Write])
+                    .for_kind(BundleArtifactKind::Rule)
+                    .matching("fraud_*"),
+            );
+
+        let admin = Actor::new("root").with_role("admin");
+        let analyst = Actor::new("alice").with_role("fraud_analyst");
+        let nobody = Actor::new("mallory");
+
+        assert!(policy
+            .check(
+                &admin,
+                BundleArtifactKind::Pipeline,
+                "main",
+                Permission::Delete
+            )
+            .is_ok());
+        assert!(policy
+            .check(
+                &analyst,
+                BundleArtifactKind::Rule,
+                "fraud_farm",
+                Permission::Write
+            )
+            .is_ok());
+        assert!(policy
+            .check(
+                &analyst,
+                BundleArtifactKind::Rule,
+                "fraud_farm",
+                Permission::Delete
+            )
+            .is_err());
+        assert!(policy
+            .check(
+                &analyst,
+                BundleArtifactKind::Ruleset,
+                "fraud_rules",
+                Permission::Write
+            )
+            .is_err());
+        assert!(matches!(
+            policy.check(
+                &nobody,
+                BundleArtifactKind::Rule,
+                "fraud_farm",
+                Permission::Write
+            ),
+            Err(RepositoryError::PermissionDenied { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_in_memory_audit_log_query() {
+        let log = InMemoryAuditLog::new();
+        for (id, actor) in [("a", "alice"), ("b", "bob"), ("a", "bob")] {
+            log.record(AuditEntry::new(
+                BundleArtifactKind::Rule,
+                id,
+                AuditOperation::Update,
+                actor,
+            ))
+            .await
+            .unwrap();
+        }
+
+        let for_a = log
+            .query(&AuditQuery::new().for_artifact("a"))
+            .await
+            .unwrap();
+        assert_eq!(for_a.len(), 2);
+        assert_eq!(for_a[0].actor, "bob");
+
+        let by_bob = log
+            .query(&AuditQuery::new().by_actor("bob").with_limit(1))
+            .await
+            .unwrap();
+        assert_eq!(by_bob.len(), 1);
+        assert_eq!(by_bob[0].artifact_id, "a");
+    }
+}
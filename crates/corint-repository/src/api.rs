@@ -15,6 +15,8 @@
 //! - Configurable timeout (default: 30s)
 //! - Local caching of fetched artifacts
 //! - Automatic manifest-based discovery
+//! - Write operations (`WritableRepository`) via PUT/DELETE
+//! - Webhook-style cache invalidation pushed by a rule-management service
 //!
 //! # Usage
 //!
@@ -67,6 +69,35 @@
 //!
 //! Returns the YAML content for the requested artifact.
 //!
+//! ## PUT /pipelines/{id}.yaml
+//! ## PUT /rulesets/{id}.yaml
+//! ## PUT /rules/{id}.yaml
+//!
+//! Creates or replaces an artifact (body: YAML, `Content-Type: application/yaml`).
+//! Used by the [`WritableRepository`] implementation. Artifacts already listed in
+//! the manifest are written to their manifest URL.
+//!
+//! ## DELETE /pipelines/{id}.yaml
+//! ## DELETE /rulesets/{id}.yaml
+//! ## DELETE /rules/{id}.yaml
+//!
+//! Deletes an artifact. A 404 response is reported as `RepositoryError::NotFound`.
+//!
+//! # Webhook Invalidation
+//!
+//! A rule-management service can push changes instead of waiting for engines to
+//! poll. It sends an [`InvalidationNotice`] as JSON, signed with a shared secret:
+//!
+//! ```text
+//! X-Corint-Signature: sha256={hex(HMAC-SHA256(secret, body))}
+//!
+//! {"kind": "rule", "id": "high_amount"}
+//! ```
+//!
+//! [`InvalidationNotice::verify_signature`] checks the header and
+//! [`ApiRepository::invalidate`] evicts the cached artifact (or everything, when
+//! `kind`/`id` are omitted) and refreshes the manifest.
+//!
 //! # Authentication
 //!
 //! If an API key is provided, it's sent as a Bearer token:
@@ -81,13 +112,22 @@
 
 use async_trait::async_trait;
 use corint_core::ast::{Pipeline, Rule, Ruleset};
-use reqwest::Client;
-use serde::Deserialize;
+use hmac::{Hmac, Mac};
+use reqwest::{Client, RequestBuilder, StatusCode};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
 use std::collections::HashMap;
-use std::time::Duration;
+use std::sync::RwLock;
+use std::time::{Duration, Instant};
 
+use crate::bundle::{from_hex, BundleArtifactKind};
 use crate::error::{RepositoryError, RepositoryResult};
-use crate::traits::Repository;
+use crate::sync::{ArtifactDrift, DriftKind, SyncReport, SyncableRepository};
+use crate::traits::{Repository, WritableRepository};
+
+/// Header carrying the webhook signature
+pub const WEBHOOK_SIGNATURE_HEADER: &str = "X-Corint-Signature";
 
 /// HTTP API Repository
 ///
@@ -103,20 +143,22 @@ pub struct ApiRepository {
     api_key: Option<String>,
 
     /// Cached manifest from the API
-    manifest: ApiManifest,
+    manifest: RwLock<ApiManifest>,
 
-    /// In-memory cache of loaded artifacts
-    cache: HashMap<String, CachedArtifact>,
+    /// In-memory cache of loaded artifacts, keyed by `kind:id`
+    cache: RwLock<HashMap<String, CachedArtifact>>,
 }
 
 /// Cached artifact with its raw content
 #[derive(Clone)]
 struct CachedArtifact {
     content: String,
+    /// Version marker the content was loaded at
+    version: String,
 }
 
 /// API manifest response
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Default, Deserialize)]
 struct ApiManifest {
     /// Optional registry URL
     #[serde(default)]
@@ -136,6 +178,96 @@ struct ApiManifest {
     rules: Vec<ArtifactRef>,
 }
 
+impl ApiManifest {
+    /// Artifact references of a kind
+    fn artifacts(&self, kind: BundleArtifactKind) -> &[ArtifactRef] {
+        match kind {
+            BundleArtifactKind::Rule => &self.rules,
+            BundleArtifactKind::Ruleset => &self.rulesets,
+            BundleArtifactKind::Pipeline => &self.pipelines,
+            _ => &[],
+        }
+    }
+
+    /// Mutable artifact references of a definition kind
+    fn artifacts_mut(&mut self, kind: BundleArtifactKind) -> Option<&mut Vec<ArtifactRef>> {
+        match kind {
+            BundleArtifactKind::Rule => Some(&mut self.rules),
+            BundleArtifactKind::Ruleset => Some(&mut self.rulesets),
+            BundleArtifactKind::Pipeline => Some(&mut self.pipelines),
+            _ => None,
+        }
+    }
+}
+
+/// Cache invalidation pushed by a rule-management service
+///
+/// Without `kind` and `id`, every cached artifact is invalidated.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InvalidationNotice {
+    /// Artifact kind ("rule", "ruleset" or "pipeline")
+    #[serde(default)]
+    pub kind: Option<String>,
+
+    /// Artifact ID
+    #[serde(default)]
+    pub id: Option<String>,
+}
+
+impl InvalidationNotice {
+    /// Invalidate a single artifact
+    pub fn artifact(kind: BundleArtifactKind, id: impl Into<String>) -> Self {
+        Self {
+            kind: Some(kind.as_str().to_string()),
+            id: Some(id.into()),
+        }
+    }
+
+    /// Invalidate every cached artifact
+    pub fn all() -> Self {
+        Self::default()
+    }
+
+    /// Parse a notice from a webhook body (an empty body invalidates everything)
+    pub fn from_json(body: &[u8]) -> RepositoryResult<Self> {
+        if body.iter().all(u8::is_ascii_whitespace) {
+            return Ok(Self::all());
+        }
+        serde_json::from_slice(body).map_err(|e| {
+            RepositoryError::ParseError(format!("Invalid invalidation notice: {}", e))
+        })
+    }
+
+    /// Compute the signature header value for a webhook body
+    pub fn sign(secret: &[u8], body: &[u8]) -> String {
+        let mut mac = Self::mac(secret);
+        mac.update(body);
+        format!(
+            "sha256={}",
+            crate::bundle::to_hex(&mac.finalize().into_bytes())
+        )
+    }
+
+    /// Verify a `sha256=<hex>` signature header against the webhook body
+    pub fn verify_signature(secret: &[u8], body: &[u8], signature: &str) -> bool {
+        let Some(expected) = signature
+            .trim()
+            .strip_prefix("sha256=")
+            .and_then(from_hex)
+        else {
+            return false;
+        };
+
+        let mut mac = Self::mac(secret);
+        mac.update(body);
+        mac.verify_slice(&expected).is_ok()
+    }
+
+    fn mac(secret: &[u8]) -> Hmac<Sha256> {
+        Hmac::<Sha256>::new_from_slice(secret).expect("HMAC accepts any key length")
+    }
+}
+
 /// Reference to an artifact in the API
 #[derive(Debug, Clone, Deserialize)]
 struct ArtifactRef {
@@ -145,6 +277,10 @@ struct ArtifactRef {
     /// Full URL to fetch the artifact
     url: String,
 
+    /// Optional version marker, changed whenever the content changes
+    #[serde(default)]
+    version: Option<String>,
+
     /// Optional description
     #[serde(default)]
     #[allow(dead_code)]
@@ -181,11 +317,27 @@ impl ApiRepository {
                 RepositoryError::ApiError(format!("Failed to create HTTP client: {}", e))
             })?;
 
-        // Fetch manifest
+        let manifest = Self::fetch_manifest(&client, &base_url, api_key.as_deref()).await?;
+
+        Ok(Self {
+            client,
+            base_url,
+            api_key,
+            manifest: RwLock::new(manifest),
+            cache: RwLock::new(HashMap::new()),
+        })
+    }
+
+    /// Fetch the manifest from `{base_url}/manifest`
+    async fn fetch_manifest(
+        client: &Client,
+        base_url: &str,
+        api_key: Option<&str>,
+    ) -> RepositoryResult<ApiManifest> {
         let manifest_url = format!("{}/manifest", base_url);
         let mut request = client.get(&manifest_url);
 
-        if let Some(ref key) = api_key {
+        if let Some(key) = api_key {
             request = request.header("Authorization", format!("Bearer {}", key));
         }
 
@@ -201,29 +353,24 @@ impl ApiRepository {
             )));
         }
 
-        let manifest: ApiManifest = response
+        response
             .json()
             .await
-            .map_err(|e| RepositoryError::ApiError(format!("Failed to parse manifest: {}", e)))?;
+            .map_err(|e| RepositoryError::ApiError(format!("Failed to parse manifest: {}", e)))
+    }
 
-        Ok(Self {
-            client,
-            base_url,
-            api_key,
-            manifest,
-            cache: HashMap::new(),
-        })
+    /// Add the Bearer token, if configured
+    fn authorize(&self, request: RequestBuilder) -> RequestBuilder {
+        match self.api_key {
+            Some(ref key) => request.header("Authorization", format!("Bearer {}", key)),
+            None => request,
+        }
     }
 
     /// Fetch content from a URL
     async fn fetch_content(&self, url: &str) -> RepositoryResult<String> {
-        let mut request = self.client.get(url);
-
-        if let Some(ref key) = self.api_key {
-            request = request.header("Authorization", format!("Bearer {}", key));
-        }
-
-        let response = request
+        let response = self
+            .authorize(self.client.get(url))
             .send()
             .await
             .map_err(|e| RepositoryError::ApiError(format!("Failed to fetch {}: {}", url, e)))?;
@@ -242,98 +389,314 @@ impl ApiRepository {
             .map_err(|e| RepositoryError::ApiError(format!("Failed to read response body: {}", e)))
     }
 
-    /// Find artifact URL by ID
-    fn find_artifact_url(&self, id: &str, artifacts: &[ArtifactRef]) -> RepositoryResult<String> {
-        artifacts
+    /// Find an artifact reference by ID
+    fn find_artifact(&self, kind: BundleArtifactKind, id: &str) -> RepositoryResult<ArtifactRef> {
+        self.manifest
+            .read()
+            .unwrap()
+            .artifacts(kind)
             .iter()
             .find(|a| a.id == id)
-            .map(|a| a.url.clone())
+            .cloned()
             .ok_or_else(|| RepositoryError::NotFound {
                 path: format!("Artifact not found: {}", id),
             })
     }
-}
 
-#[async_trait]
-impl Repository for ApiRepository {
-    async fn load_rule(&self, identifier: &str) -> RepositoryResult<(Rule, String)> {
-        // Check cache first
-        if let Some(cached) = self.cache.get(identifier) {
-            let rule = serde_yaml::from_str(&cached.content)
-                .map_err(|e| RepositoryError::ParseError(format!("Failed to parse rule: {}", e)))?;
-            return Ok((rule, cached.content.clone()));
+    /// Find artifact URL by ID
+    fn find_artifact_url(&self, kind: BundleArtifactKind, id: &str) -> RepositoryResult<String> {
+        self.find_artifact(kind, id).map(|a| a.url)
+    }
+
+    /// URL an artifact is written to: its manifest URL, or `{base_url}/{kind}s/{id}.yaml`
+    fn write_url(&self, kind: BundleArtifactKind, id: &str) -> String {
+        self.find_artifact_url(kind, id)
+            .unwrap_or_else(|_| format!("{}/{}s/{}.yaml", self.base_url, kind.as_str(), id))
+    }
+
+    /// Cache key of an artifact
+    fn cache_key(kind: BundleArtifactKind, id: &str) -> String {
+        format!("{}:{}", kind.as_str(), id)
+    }
+
+    /// Load and parse an artifact, using the local cache when possible
+    async fn load_definition<T: DeserializeOwned>(
+        &self,
+        kind: BundleArtifactKind,
+        identifier: &str,
+    ) -> RepositoryResult<(T, String)> {
+        let key = Self::cache_key(kind, identifier);
+        let cached = self.cache.read().unwrap().get(&key).cloned();
+
+        let (content, version) = match cached {
+            Some(cached) => (cached.content, cached.version),
+            None => {
+                let artifact = self.find_artifact(kind, identifier)?;
+                let content = self.fetch_content(&artifact.url).await?;
+                let version = artifact
+                    .version
+                    .unwrap_or_else(|| content_marker(&content));
+                (content, version)
+            }
+        };
+
+        let definition = serde_yaml::from_str(&content).map_err(|e| {
+            RepositoryError::ParseError(format!("Failed to parse {}: {}", kind.as_str(), e))
+        })?;
+
+        self.cache.write().unwrap().insert(
+            key,
+            CachedArtifact {
+                content: content.clone(),
+                version,
+            },
+        );
+
+        Ok((definition, content))
+    }
+
+    /// Create or replace an artifact with PUT
+    async fn put_definition<T: Serialize>(
+        &self,
+        kind: BundleArtifactKind,
+        id: &str,
+        definition: &T,
+    ) -> RepositoryResult<()> {
+        if id.is_empty() {
+            return Err(RepositoryError::Other(format!(
+                "{} must have an id",
+                kind.as_str()
+            )));
         }
 
-        // Find URL in manifest
-        let url = self.find_artifact_url(identifier, &self.manifest.rules)?;
+        let content = serde_yaml::to_string(definition).map_err(|e| {
+            RepositoryError::Other(format!("Failed to serialize {}: {}", kind.as_str(), e))
+        })?;
+        let url = self.write_url(kind, id);
 
-        // Fetch content
-        let content = self.fetch_content(&url).await?;
+        let response = self
+            .authorize(self.client.put(&url))
+            .header("Content-Type", "application/yaml")
+            .body(content.clone())
+            .send()
+            .await
+            .map_err(|e| RepositoryError::ApiError(format!("Failed to write {}: {}", url, e)))?;
 
-        // Parse rule
-        let rule = serde_yaml::from_str(&content)
-            .map_err(|e| RepositoryError::ParseError(format!("Failed to parse rule: {}", e)))?;
+        if !response.status().is_success() {
+            return Err(RepositoryError::ApiError(format!(
+                "API returned error status {} for {}",
+                response.status(),
+                url
+            )));
+        }
 
-        Ok((rule, content))
+        if let Some(artifacts) = self.manifest.write().unwrap().artifacts_mut(kind) {
+            if !artifacts.iter().any(|a| a.id == id) {
+                artifacts.push(ArtifactRef {
+                    id: id.to_string(),
+                    url,
+                    version: None,
+                    description: None,
+                });
+            }
+        }
+        // The server assigns the new version; until the manifest is refreshed
+        // the content hash stands in, so the next sync re-checks the artifact
+        let version = content_marker(&content);
+        self.cache
+            .write()
+            .unwrap()
+            .insert(Self::cache_key(kind, id), CachedArtifact { content, version });
+
+        Ok(())
     }
 
-    async fn load_ruleset(&self, identifier: &str) -> RepositoryResult<(Ruleset, String)> {
-        // Check cache first
-        if let Some(cached) = self.cache.get(identifier) {
-            let ruleset = serde_yaml::from_str(&cached.content).map_err(|e| {
-                RepositoryError::ParseError(format!("Failed to parse ruleset: {}", e))
-            })?;
-            return Ok((ruleset, cached.content.clone()));
+    /// Delete an artifact with DELETE
+    async fn delete_definition(&self, kind: BundleArtifactKind, id: &str) -> RepositoryResult<()> {
+        let url = self.write_url(kind, id);
+
+        let response = self
+            .authorize(self.client.delete(&url))
+            .send()
+            .await
+            .map_err(|e| RepositoryError::ApiError(format!("Failed to delete {}: {}", url, e)))?;
+
+        if response.status() == StatusCode::NOT_FOUND {
+            return Err(RepositoryError::NotFound {
+                path: id.to_string(),
+            });
+        }
+        if !response.status().is_success() {
+            return Err(RepositoryError::ApiError(format!(
+                "API returned error status {} for {}",
+                response.status(),
+                url
+            )));
         }
 
-        // Find URL in manifest
-        let url = self.find_artifact_url(identifier, &self.manifest.rulesets)?;
+        if let Some(artifacts) = self.manifest.write().unwrap().artifacts_mut(kind) {
+            artifacts.retain(|a| a.id != id);
+        }
+        self.cache
+            .write()
+            .unwrap()
+            .remove(&Self::cache_key(kind, id));
 
-        // Fetch content
-        let content = self.fetch_content(&url).await?;
+        Ok(())
+    }
+
+    /// Re-fetch the manifest so added and removed artifacts become visible
+    pub async fn refresh_manifest(&self) -> RepositoryResult<()> {
+        let manifest =
+            Self::fetch_manifest(&self.client, &self.base_url, self.api_key.as_deref()).await?;
+        *self.manifest.write().unwrap() = manifest;
+        Ok(())
+    }
 
-        // Parse ruleset
-        let ruleset = serde_yaml::from_str(&content)
-            .map_err(|e| RepositoryError::ParseError(format!("Failed to parse ruleset: {}", e)))?;
+    /// Apply a webhook invalidation notice
+    ///
+    /// Evicts the named artifact from the local cache (or the whole cache when
+    /// the notice names no artifact) and refreshes the manifest.
+    pub async fn invalidate(&self, notice: &InvalidationNotice) -> RepositoryResult<()> {
+        match (notice.kind.as_deref(), notice.id.as_deref()) {
+            (Some(kind), Some(id)) => {
+                let kind = BundleArtifactKind::parse(kind).ok_or_else(|| {
+                    RepositoryError::Other(format!("Unknown artifact kind '{}'", kind))
+                })?;
+                self.cache
+                    .write()
+                    .unwrap()
+                    .remove(&Self::cache_key(kind, id));
+            }
+            _ => self.cache.write().unwrap().clear(),
+        }
 
-        Ok((ruleset, content))
+        self.refresh_manifest().await
     }
+}
 
-    async fn load_pipeline(&self, identifier: &str) -> RepositoryResult<(Pipeline, String)> {
-        // Check cache first
-        if let Some(cached) = self.cache.get(identifier) {
-            let pipeline = serde_yaml::from_str(&cached.content).map_err(|e| {
-                RepositoryError::ParseError(format!("Failed to parse pipeline: {}", e))
-            })?;
-            return Ok((pipeline, cached.content.clone()));
+/// Version marker of content the manifest gives no version for
+fn content_marker(content: &str) -> String {
+    use sha2::Digest;
+    crate::bundle::to_hex(&Sha256::digest(content.as_bytes()))
+}
+
+#[async_trait]
+impl SyncableRepository for ApiRepository {
+    async fn sync(&self) -> RepositoryResult<SyncReport> {
+        let start = Instant::now();
+        self.refresh_manifest().await?;
+
+        let cached: Vec<(String, String)> = self
+            .cache
+            .read()
+            .unwrap()
+            .iter()
+            .map(|(key, cached)| (key.clone(), cached.version.clone()))
+            .collect();
+
+        let mut report = SyncReport::default();
+        for (key, loaded_version) in cached {
+            let Some((kind, id)) = key
+                .split_once(':')
+                .and_then(|(kind, id)| Some((BundleArtifactKind::parse(kind)?, id)))
+            else {
+                continue;
+            };
+            report.checked += 1;
+
+            let mut drift = ArtifactDrift {
+                kind,
+                identifier: id.to_string(),
+                drift: DriftKind::Changed,
+                loaded_version: Some(loaded_version.clone()),
+                remote_version: None,
+            };
+
+            let artifact = match self.find_artifact(kind, id) {
+                Ok(artifact) => artifact,
+                Err(_) => {
+                    self.cache.write().unwrap().remove(&key);
+                    drift.drift = DriftKind::Removed;
+                    report.drifted.push(drift);
+                    continue;
+                }
+            };
+
+            // Without a manifest version, the content itself is compared
+            let remote_version = match artifact.version {
+                Some(version) => version,
+                None => match self.fetch_content(&artifact.url).await {
+                    Ok(content) => content_marker(&content),
+                    Err(e) => {
+                        report.errors.push(format!("{} {}: {}", kind.as_str(), id, e));
+                        continue;
+                    }
+                },
+            };
+            if remote_version == loaded_version {
+                continue;
+            }
+            drift.remote_version = Some(remote_version);
+            report.drifted.push(drift);
+
+            self.cache.write().unwrap().remove(&key);
+            let reloaded = match kind {
+                BundleArtifactKind::Rule => self.load_rule(id).await.map(|_| ()),
+                BundleArtifactKind::Ruleset => self.load_ruleset(id).await.map(|_| ()),
+                _ => self.load_pipeline(id).await.map(|_| ()),
+            };
+            match reloaded {
+                Ok(()) => report.refreshed += 1,
+                Err(e) => report.errors.push(format!("{} {}: {}", kind.as_str(), id, e)),
+            }
         }
 
-        // Find URL in manifest
-        let url = self.find_artifact_url(identifier, &self.manifest.pipelines)?;
+        report.elapsed = start.elapsed();
+        Ok(report)
+    }
+}
 
-        // Fetch content
-        let content = self.fetch_content(&url).await?;
+#[async_trait]
+impl Repository for ApiRepository {
+    async fn load_rule(&self, identifier: &str) -> RepositoryResult<(Rule, String)> {
+        self.load_definition(BundleArtifactKind::Rule, identifier)
+            .await
+    }
 
-        // Parse pipeline
-        let pipeline = serde_yaml::from_str(&content)
-            .map_err(|e| RepositoryError::ParseError(format!("Failed to parse pipeline: {}", e)))?;
+    async fn load_ruleset(&self, identifier: &str) -> RepositoryResult<(Ruleset, String)> {
+        self.load_definition(BundleArtifactKind::Ruleset, identifier)
+            .await
+    }
 
-        Ok((pipeline, content))
+    async fn load_pipeline(&self, identifier: &str) -> RepositoryResult<(Pipeline, String)> {
+        self.load_definition(BundleArtifactKind::Pipeline, identifier)
+            .await
     }
 
     async fn exists(&self, identifier: &str) -> RepositoryResult<bool> {
-        Ok(self.manifest.rules.iter().any(|r| r.id == identifier)
-            || self.manifest.rulesets.iter().any(|r| r.id == identifier)
-            || self.manifest.pipelines.iter().any(|p| p.id == identifier))
+        let manifest = self.manifest.read().unwrap();
+        Ok(manifest.rules.iter().any(|r| r.id == identifier)
+            || manifest.rulesets.iter().any(|r| r.id == identifier)
+            || manifest.pipelines.iter().any(|p| p.id == identifier))
     }
 
     async fn list_rules(&self) -> RepositoryResult<Vec<String>> {
-        Ok(self.manifest.rules.iter().map(|r| r.id.clone()).collect())
+        Ok(self
+            .manifest
+            .read()
+            .unwrap()
+            .rules
+            .iter()
+            .map(|r| r.id.clone())
+            .collect())
     }
 
     async fn list_rulesets(&self) -> RepositoryResult<Vec<String>> {
         Ok(self
             .manifest
+            .read()
+            .unwrap()
             .rulesets
             .iter()
             .map(|r| r.id.clone())
@@ -343,6 +706,8 @@ impl Repository for ApiRepository {
     async fn list_pipelines(&self) -> RepositoryResult<Vec<String>> {
         Ok(self
             .manifest
+            .read()
+            .unwrap()
             .pipelines
             .iter()
             .map(|p| p.id.clone())
@@ -374,3 +739,62 @@ impl Repository for ApiRepository {
             .map_err(|e| RepositoryError::Other(format!("Failed to read response: {}", e)))
     }
 }
+
+#[async_trait]
+impl WritableRepository for ApiRepository {
+    async fn save_rule(&mut self, rule: &Rule) -> RepositoryResult<()> {
+        self.put_definition(BundleArtifactKind::Rule, &rule.id, rule)
+            .await
+    }
+
+    async fn save_ruleset(&mut self, ruleset: &Ruleset) -> RepositoryResult<()> {
+        self.put_definition(BundleArtifactKind::Ruleset, &ruleset.id, ruleset)
+            .await
+    }
+
+    async fn save_pipeline(&mut self, pipeline: &Pipeline) -> RepositoryResult<()> {
+        self.put_definition(BundleArtifactKind::Pipeline, &pipeline.id, pipeline)
+            .await
+    }
+
+    async fn delete_rule(&self, identifier: &str) -> RepositoryResult<()> {
+        self.delete_definition(BundleArtifactKind::Rule, identifier)
+            .await
+    }
+
+    async fn delete_ruleset(&self, identifier: &str) -> RepositoryResult<()> {
+        self.delete_definition(BundleArtifactKind::Ruleset, identifier)
+            .await
+    }
+
+    async fn delete_pipeline(&self, identifier: &str) -> RepositoryResult<()> {
+        self.delete_definition(BundleArtifactKind::Pipeline, identifier)
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_invalidation_notice_signature() {
+        let body = br#"{"kind": "rule", "id": "high_amount"}"#;
+        let signature = InvalidationNotice::sign(b"secret", body);
+
+        assert!(InvalidationNotice::verify_signature(b"secret", body, &signature));
+        assert!(!InvalidationNotice::verify_signature(b"other", body, &signature));
+        assert!(!InvalidationNotice::verify_signature(b"secret", b"{}", &signature));
+        assert!(!InvalidationNotice::verify_signature(b"secret", body, "sha256=zz"));
+
+        let notice = InvalidationNotice::from_json(body).unwrap();
+        assert_eq!(
+            notice,
+            InvalidationNotice::artifact(BundleArtifactKind::Rule, "high_amount")
+        );
+        assert_eq!(
+            InvalidationNotice::from_json(b"").unwrap(),
+            InvalidationNotice::all()
+        );
+    }
+}
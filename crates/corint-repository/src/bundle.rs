@@ -0,0 +1,465 @@
+//! Repository bundles for promoting artifacts between repositories
+//!
+//! A bundle is a single JSON manifest holding the raw content of every
+//! exported artifact (rules, rulesets, pipelines, registry and config files)
+//! together with a SHA-256 hash per artifact, a digest over all hashes and an
+//! optional HMAC-SHA256 signature of that digest. Importing verifies the
+//! bundle and parses every definition before anything is written, so a
+//! tested rule pack can be moved from staging to production as one unit.
+
+use corint_core::ast::{Pipeline, Rule, Ruleset};
+use corint_parser::{
+    PipelineParser, ReasonCatalogParser, RegistryParser, RuleParser, RulesetParser,
+};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::{
+    error::{RepositoryError, RepositoryResult},
+    traits::Repository,
+};
+
+/// Current bundle format version
+pub const BUNDLE_FORMAT_VERSION: u32 = 1;
+
+/// Kind of artifact stored in a bundle
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BundleArtifactKind {
+    /// Pipeline registry (`registry.yaml`)
+    Registry,
+    /// Rule definition
+    Rule,
+    /// Ruleset definition
+    Ruleset,
+    /// Pipeline definition
+    Pipeline,
+    /// List configuration file (`configs/lists/`)
+    List,
+    /// Feature definition file (`configs/features/`)
+    Feature,
+    /// External API configuration file (`configs/apis/`)
+    ApiConfig,
+    /// Reason code catalog (`configs/reason_codes/`)
+    ReasonCodes,
+    /// PII masking policy (`configs/masking/`)
+    MaskingPolicy,
+}
+
+impl BundleArtifactKind {
+    /// Stable name used in manifests and storage backends
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Registry => "registry",
+            Self::Rule => "rule",
+            Self::Ruleset => "ruleset",
+            Self::Pipeline => "pipeline",
+            Self::List => "list",
+            Self::Feature => "feature",
+            Self::ApiConfig => "api_config",
+            Self::ReasonCodes => "reason_codes",
+            Self::MaskingPolicy => "masking_policy",
+        }
+    }
+
+    /// Parse a kind from its stable name
+    pub fn parse(name: &str) -> Option<Self> {
+        Self::config_kinds()
+            .iter()
+            .chain(&[Self::Registry, Self::Rule, Self::Ruleset, Self::Pipeline])
+            .copied()
+            .find(|kind| kind.as_str() == name)
+    }
+
+    /// Directory under `configs/` holding this kind of config file
+    pub fn config_dir(&self) -> Option<&'static str> {
+        match self {
+            Self::List => Some("lists"),
+            Self::Feature => Some("features"),
+            Self::ApiConfig => Some("apis"),
+            Self::ReasonCodes => Some("reason_codes"),
+            Self::MaskingPolicy => Some("masking"),
+            _ => None,
+        }
+    }
+
+    /// Kinds stored as plain config files
+    pub fn config_kinds() -> &'static [BundleArtifactKind] {
+        &[
+            Self::List,
+            Self::Feature,
+            Self::ApiConfig,
+            Self::ReasonCodes,
+            Self::MaskingPolicy,
+        ]
+    }
+
+    /// Whether this kind is a rule, ruleset or pipeline definition
+    pub fn is_definition(&self) -> bool {
+        matches!(self, Self::Rule | Self::Ruleset | Self::Pipeline)
+    }
+}
+
+/// A single artifact in a bundle
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BundleArtifact {
+    /// Artifact kind
+    pub kind: BundleArtifactKind,
+    /// Artifact ID (definition ID, or file stem for config files)
+    pub id: String,
+    /// Raw YAML content
+    pub content: String,
+    /// Hex-encoded SHA-256 of `content`
+    pub sha256: String,
+}
+
+/// A signed, self-verifying export of repository content
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RepositoryBundle {
+    /// Bundle format version
+    pub format_version: u32,
+    /// Creation time (seconds since the Unix epoch)
+    pub created_at: u64,
+    /// Artifacts, ordered by kind and ID
+    pub artifacts: Vec<BundleArtifact>,
+    /// Hex-encoded SHA-256 over every artifact's kind, ID and hash
+    pub digest: String,
+    /// Hex-encoded HMAC-SHA256 of `digest`, if the bundle was signed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<String>,
+}
+
+/// Parsed definitions of a verified bundle
+#[derive(Debug, Clone, Default)]
+pub struct BundleDefinitions {
+    /// Rules, in bundle order
+    pub rules: Vec<Rule>,
+    /// Rulesets, in bundle order
+    pub rulesets: Vec<Ruleset>,
+    /// Pipelines, in bundle order
+    pub pipelines: Vec<Pipeline>,
+}
+
+impl RepositoryBundle {
+    /// Create an empty, unsigned bundle
+    pub fn new() -> Self {
+        let created_at = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+
+        let mut bundle = Self {
+            format_version: BUNDLE_FORMAT_VERSION,
+            created_at,
+            artifacts: Vec::new(),
+            digest: String::new(),
+            signature: None,
+        };
+        bundle.digest = bundle.compute_digest();
+        bundle
+    }
+
+    /// Add (or replace) an artifact
+    ///
+    /// Updates the digest and drops any existing signature.
+    pub fn add_artifact(
+        &mut self,
+        kind: BundleArtifactKind,
+        id: impl Into<String>,
+        content: impl Into<String>,
+    ) {
+        let id = id.into();
+        let content = content.into();
+        let artifact = BundleArtifact {
+            kind,
+            sha256: sha256_hex(content.as_bytes()),
+            id,
+            content,
+        };
+
+        match self
+            .artifacts
+            .binary_search_by(|a| (a.kind, a.id.as_str()).cmp(&(kind, artifact.id.as_str())))
+        {
+            Ok(pos) => self.artifacts[pos] = artifact,
+            Err(pos) => self.artifacts.insert(pos, artifact),
+        }
+
+        self.digest = self.compute_digest();
+        self.signature = None;
+    }
+
+    /// Artifacts of a given kind
+    pub fn artifacts_of(&self, kind: BundleArtifactKind) -> impl Iterator<Item = &BundleArtifact> {
+        self.artifacts.iter().filter(move |a| a.kind == kind)
+    }
+
+    /// Sign the bundle digest with a shared key
+    pub fn sign(&mut self, key: &[u8]) {
+        let mut mac = Self::mac(key);
+        mac.update(self.digest.as_bytes());
+        self.signature = Some(to_hex(&mac.finalize().into_bytes()));
+    }
+
+    /// Verify artifact hashes, the digest and, when a key is given, the signature
+    ///
+    /// A bundle without a signature fails verification when a key is provided.
+    pub fn verify(&self, key: Option<&[u8]>) -> RepositoryResult<()> {
+        if self.format_version != BUNDLE_FORMAT_VERSION {
+            return Err(RepositoryError::InvalidBundle(format!(
+                "unsupported format version {}",
+                self.format_version
+            )));
+        }
+
+        for artifact in &self.artifacts {
+            if sha256_hex(artifact.content.as_bytes()) != artifact.sha256 {
+                return Err(RepositoryError::InvalidBundle(format!(
+                    "hash mismatch for {} '{}'",
+                    artifact.kind.as_str(),
+                    artifact.id
+                )));
+            }
+        }
+
+        if self.compute_digest() != self.digest {
+            return Err(RepositoryError::InvalidBundle(
+                "digest does not match artifacts".to_string(),
+            ));
+        }
+
+        if let Some(key) = key {
+            let signature = self.signature.as_deref().ok_or_else(|| {
+                RepositoryError::InvalidBundle("bundle is not signed".to_string())
+            })?;
+            let signature = from_hex(signature).ok_or_else(|| {
+                RepositoryError::InvalidBundle("malformed signature".to_string())
+            })?;
+            let mut mac = Self::mac(key);
+            mac.update(self.digest.as_bytes());
+            mac.verify_slice(&signature).map_err(|_| {
+                RepositoryError::InvalidBundle("signature verification failed".to_string())
+            })?;
+        }
+
+        Ok(())
+    }
+
+    /// Parse every artifact, returning the rule, ruleset and pipeline definitions
+    ///
+    /// Fails if any artifact does not parse or a definition's ID differs from
+    /// the ID it is stored under.
+    pub fn parse_definitions(&self) -> RepositoryResult<BundleDefinitions> {
+        let mut definitions = BundleDefinitions::default();
+
+        for artifact in &self.artifacts {
+            let defined_id = match artifact.kind {
+                BundleArtifactKind::Rule => {
+                    let rule = RuleParser::parse_with_imports(&artifact.content)?.definition;
+                    let id = rule.id.clone();
+                    definitions.rules.push(rule);
+                    Some(id)
+                }
+                BundleArtifactKind::Ruleset => {
+                    let ruleset = RulesetParser::parse_with_imports(&artifact.content)?.definition;
+                    let id = ruleset.id.clone();
+                    definitions.rulesets.push(ruleset);
+                    Some(id)
+                }
+                BundleArtifactKind::Pipeline => {
+                    let pipeline = PipelineParser::parse_with_imports(&artifact.content)?.definition;
+                    let id = pipeline.id.clone();
+                    definitions.pipelines.push(pipeline);
+                    Some(id)
+                }
+                BundleArtifactKind::Registry => {
+                    RegistryParser::parse(&artifact.content)?;
+                    None
+                }
+                BundleArtifactKind::ReasonCodes => {
+                    ReasonCatalogParser::parse(&artifact.content)?;
+                    None
+                }
+                BundleArtifactKind::List
+                | BundleArtifactKind::Feature
+                | BundleArtifactKind::ApiConfig
+                | BundleArtifactKind::MaskingPolicy => {
+                    serde_yaml::from_str::<serde_yaml::Value>(&artifact.content)?;
+                    None
+                }
+            };
+
+            if let Some(defined_id) = defined_id {
+                if defined_id != artifact.id {
+                    return Err(RepositoryError::InvalidBundle(format!(
+                        "{} stored as '{}' defines id '{}'",
+                        artifact.kind.as_str(),
+                        artifact.id,
+                        defined_id
+                    )));
+                }
+            }
+        }
+
+        Ok(definitions)
+    }
+
+    /// Serialize the bundle as pretty-printed JSON
+    pub fn to_json(&self) -> RepositoryResult<String> {
+        serde_json::to_string_pretty(self)
+            .map_err(|e| RepositoryError::ParseError(format!("Failed to serialize bundle: {}", e)))
+    }
+
+    /// Deserialize a bundle from JSON (does not verify it)
+    pub fn from_json(json: &str) -> RepositoryResult<Self> {
+        serde_json::from_str(json)
+            .map_err(|e| RepositoryError::InvalidBundle(format!("malformed manifest: {}", e)))
+    }
+
+    fn compute_digest(&self) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(format!("corint-bundle/{}\n", self.format_version));
+        for artifact in &self.artifacts {
+            hasher.update(format!(
+                "{}\t{}\t{}\n",
+                artifact.kind.as_str(),
+                artifact.id,
+                artifact.sha256
+            ));
+        }
+        to_hex(&hasher.finalize())
+    }
+
+    fn mac(key: &[u8]) -> Hmac<Sha256> {
+        // HMAC accepts keys of any length
+        Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts any key length")
+    }
+}
+
+impl Default for RepositoryBundle {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Export the rules, rulesets, pipelines and registry of any repository
+///
+/// Artifacts that fail to load are reported as errors rather than skipped,
+/// so an exported bundle is always complete.
+pub(crate) async fn export_definitions<R: Repository + ?Sized>(
+    repo: &R,
+) -> RepositoryResult<RepositoryBundle> {
+    let mut bundle = RepositoryBundle::new();
+
+    if let Ok(registry) = repo.load_registry().await {
+        bundle.add_artifact(BundleArtifactKind::Registry, "registry", registry);
+    }
+    for identifier in repo.list_rules().await? {
+        let (rule, content) = repo.load_rule(&identifier).await?;
+        bundle.add_artifact(BundleArtifactKind::Rule, rule.id, content);
+    }
+    for identifier in repo.list_rulesets().await? {
+        let (ruleset, content) = repo.load_ruleset(&identifier).await?;
+        bundle.add_artifact(BundleArtifactKind::Ruleset, ruleset.id, content);
+    }
+    for identifier in repo.list_pipelines().await? {
+        let (pipeline, content) = repo.load_pipeline(&identifier).await?;
+        bundle.add_artifact(BundleArtifactKind::Pipeline, pipeline.id, content);
+    }
+
+    Ok(bundle)
+}
+
+pub(crate) fn sha256_hex(bytes: &[u8]) -> String {
+    to_hex(&Sha256::digest(bytes))
+}
+
+pub(crate) fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+pub(crate) fn from_hex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RULE: &str = r#"
+rule:
+  id: high_amount
+  name: High Amount
+  when:
+    all:
+      - event.amount > 1000
+  score: 50
+"#;
+
+    fn bundle() -> RepositoryBundle {
+        let mut bundle = RepositoryBundle::new();
+        bundle.add_artifact(BundleArtifactKind::Rule, "high_amount", RULE);
+        bundle.add_artifact(
+            BundleArtifactKind::List,
+            "blocked_ips",
+            "lists:\n  - id: blocked_ips\n    backend: memory\n",
+        );
+        bundle
+    }
+
+    #[test]
+    fn test_signed_bundle_roundtrip() {
+        let mut bundle = bundle();
+        bundle.sign(b"promotion-key");
+
+        let restored = RepositoryBundle::from_json(&bundle.to_json().unwrap()).unwrap();
+        assert_eq!(restored, bundle);
+        restored.verify(Some(b"promotion-key")).unwrap();
+
+        let definitions = restored.parse_definitions().unwrap();
+        assert_eq!(definitions.rules.len(), 1);
+        assert_eq!(definitions.rules[0].id, "high_amount");
+    }
+
+    #[test]
+    fn test_verify_detects_tampering() {
+        let mut bundle = bundle();
+        bundle.sign(b"promotion-key");
+
+        assert!(bundle.verify(Some(b"other-key")).is_err());
+
+        let mut tampered = bundle.clone();
+        tampered.artifacts[0].content = RULE.replace("score: 50", "score: 0");
+        assert!(matches!(
+            tampered.verify(None),
+            Err(RepositoryError::InvalidBundle(_))
+        ));
+
+        // Re-hashing the artifact is caught by the digest
+        tampered.artifacts[0].sha256 = sha256_hex(tampered.artifacts[0].content.as_bytes());
+        assert!(tampered.verify(None).is_err());
+
+        let mut unsigned = bundle.clone();
+        unsigned.signature = None;
+        unsigned.verify(None).unwrap();
+        assert!(unsigned.verify(Some(b"promotion-key")).is_err());
+    }
+
+    #[test]
+    fn test_parse_definitions_rejects_mismatched_id() {
+        let mut bundle = RepositoryBundle::new();
+        bundle.add_artifact(BundleArtifactKind::Rule, "renamed", RULE);
+
+        assert!(matches!(
+            bundle.parse_definitions(),
+            Err(RepositoryError::InvalidBundle(_))
+        ));
+    }
+}
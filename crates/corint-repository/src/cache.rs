@@ -0,0 +1,405 @@
+//! Load coordination for artifact caches
+//!
+//! On a cold start, many requests ask for the same artifact at once. Without
+//! coordination every one of them misses the cache and queries the backing
+//! store. [`LoadCoordinator`] sits behind a repository's artifact caches and:
+//!
+//! - coalesces concurrent loads of the same key, so only one caller queries
+//!   the store while the others wait and then read its result from the cache
+//!   (single-flight);
+//! - remembers "not found" results for [`CacheConfig::negative_ttl`], so
+//!   repeated lookups of a missing artifact are answered without a query;
+//! - keeps each cache within [`CacheConfig::max_entries`], dropping expired
+//!   entries first and then the entry chosen by [`CacheConfig::eviction`].
+//!
+//! Errors other than "not found" are neither cached nor shared; each waiting
+//! caller retries the load in turn.
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex, Weak};
+use std::time::Instant;
+use tokio::sync::{Mutex as AsyncMutex, OwnedMutexGuard, RwLock};
+
+use crate::{
+    bundle::BundleArtifactKind,
+    error::RepositoryError,
+    models::{CacheConfig, CacheStats, CachedArtifact, EvictionPolicy},
+    RepositoryResult,
+};
+
+/// Cache of parsed artifacts with their raw content, keyed by identifier
+pub(crate) type ArtifactCache<T> = Arc<RwLock<HashMap<String, CachedArtifact<T>>>>;
+
+/// Negative cache and single-flight gate shared by a repository's artifact caches
+#[derive(Debug, Default)]
+pub(crate) struct LoadCoordinator {
+    /// Expiry of cached "not found" results, keyed by `<kind>:<identifier>`
+    missing: Mutex<HashMap<String, Instant>>,
+    /// Per-key gates of loads currently in progress
+    in_flight: Mutex<HashMap<String, Weak<AsyncMutex<()>>>>,
+}
+
+impl LoadCoordinator {
+    /// Load an artifact after a cache miss
+    ///
+    /// `load` runs at most once at a time per artifact. Its result is stored
+    /// in `cache`, or remembered as missing when it fails with a "not found"
+    /// error. When caching is disabled, `load` is called directly.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) async fn load<T, F, Fut>(
+        &self,
+        cache: &ArtifactCache<T>,
+        config: &Mutex<CacheConfig>,
+        stats: &Mutex<CacheStats>,
+        kind: &str,
+        identifier: &str,
+        load: F,
+    ) -> RepositoryResult<(T, String)>
+    where
+        T: Clone,
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = RepositoryResult<(T, String)>>,
+    {
+        let config = config.lock().unwrap().clone();
+        if !config.enabled {
+            return load().await;
+        }
+        let ttl = BundleArtifactKind::parse(kind)
+            .map_or(config.default_ttl, |kind| config.ttl_for(kind));
+
+        let key = format!("{}:{}", kind, identifier);
+        if self.is_missing(&key) {
+            return Err(not_found(identifier));
+        }
+
+        let _gate = self.acquire(&key).await;
+
+        // Another caller may have finished the same load while we waited
+        if let Some(cached) = cache.read().await.get(identifier) {
+            if !cached.is_expired() {
+                cached.touch();
+                return Ok((cached.data.clone(), cached.content.clone()));
+            }
+        }
+        if self.is_missing(&key) {
+            return Err(not_found(identifier));
+        }
+
+        match load().await {
+            Ok((data, content)) => {
+                let mut cache_write = cache.write().await;
+                let evicted = insert_bounded(
+                    &mut cache_write,
+                    identifier,
+                    CachedArtifact::new(data.clone(), content.clone(), ttl),
+                    &config,
+                );
+                let mut stats = stats.lock().unwrap();
+                stats.size = cache_write.len();
+                stats.evictions += evicted;
+                Ok((data, content))
+            }
+            Err(e) => {
+                if is_not_found(&e) && !config.negative_ttl.is_zero() {
+                    self.missing
+                        .lock()
+                        .unwrap()
+                        .insert(key, Instant::now() + config.negative_ttl);
+                }
+                Err(e)
+            }
+        }
+    }
+
+    /// Forget cached "not found" results for an identifier, of any kind
+    pub(crate) fn forget(&self, identifier: &str) {
+        self.missing
+            .lock()
+            .unwrap()
+            .retain(|key, _| key.split_once(':').map(|(_, id)| id) != Some(identifier));
+    }
+
+    /// Forget all cached "not found" results
+    pub(crate) fn clear(&self) {
+        self.missing.lock().unwrap().clear();
+    }
+
+    fn is_missing(&self, key: &str) -> bool {
+        let mut missing = self.missing.lock().unwrap();
+        match missing.get(key) {
+            Some(expires_at) if *expires_at > Instant::now() => true,
+            Some(_) => {
+                missing.remove(key);
+                false
+            }
+            None => false,
+        }
+    }
+
+    async fn acquire(&self, key: &str) -> OwnedMutexGuard<()> {
+        let gate = {
+            let mut in_flight = self.in_flight.lock().unwrap();
+            in_flight.retain(|_, gate| gate.strong_count() > 0);
+            match in_flight.get(key).and_then(Weak::upgrade) {
+                Some(gate) => gate,
+                None => {
+                    let gate = Arc::new(AsyncMutex::new(()));
+                    in_flight.insert(key.to_string(), Arc::downgrade(&gate));
+                    gate
+                }
+            }
+        };
+        gate.lock_owned().await
+    }
+}
+
+/// Insert an entry, evicting others to stay within `config.max_entries`
+///
+/// Expired entries go first; if the cache is still full, the least recently
+/// (LRU) or least frequently (LFU) used entry is dropped. Returns the number
+/// of evicted entries.
+fn insert_bounded<T>(
+    entries: &mut HashMap<String, CachedArtifact<T>>,
+    identifier: &str,
+    artifact: CachedArtifact<T>,
+    config: &CacheConfig,
+) -> u64 {
+    let Some(max_entries) = config.max_entries else {
+        entries.insert(identifier.to_string(), artifact);
+        return 0;
+    };
+
+    // Replacing an entry doesn't count as an eviction
+    entries.remove(identifier);
+    let before = entries.len();
+    if entries.len() >= max_entries {
+        entries.retain(|_, entry| !entry.is_expired());
+    }
+    while !entries.is_empty() && entries.len() >= max_entries {
+        let victim = match config.eviction {
+            EvictionPolicy::Lru => entries.iter().min_by_key(|(_, e)| e.last_used()),
+            EvictionPolicy::Lfu => entries
+                .iter()
+                .min_by_key(|(_, e)| (e.uses(), e.last_used())),
+        }
+        .map(|(key, _)| key.clone());
+        if let Some(victim) = victim {
+            entries.remove(&victim);
+        }
+    }
+
+    let evicted = (before - entries.len()) as u64;
+    if max_entries > 0 {
+        entries.insert(identifier.to_string(), artifact);
+    }
+    evicted
+}
+
+fn is_not_found(error: &RepositoryError) -> bool {
+    matches!(
+        error,
+        RepositoryError::NotFound { .. } | RepositoryError::IdNotFound { .. }
+    )
+}
+
+fn not_found(identifier: &str) -> RepositoryError {
+    RepositoryError::NotFound {
+        path: identifier.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    fn setup() -> (
+        LoadCoordinator,
+        ArtifactCache<String>,
+        Mutex<CacheConfig>,
+        Mutex<CacheStats>,
+    ) {
+        (
+            LoadCoordinator::default(),
+            Arc::new(RwLock::new(HashMap::new())),
+            Mutex::new(CacheConfig::default()),
+            Mutex::new(CacheStats::default()),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_loads_are_coalesced() {
+        let (loads, cache, config, stats) = setup();
+        let shared = Arc::new((loads, cache, config, stats, AtomicUsize::new(0)));
+
+        let tasks: Vec<_> = (0..16)
+            .map(|_| {
+                let shared = Arc::clone(&shared);
+                tokio::spawn(async move {
+                    let (loads, cache, config, stats, calls) = &*shared;
+                    loads
+                        .load(cache, config, stats, "rule", "fraud_check", || async {
+                            calls.fetch_add(1, Ordering::SeqCst);
+                            tokio::time::sleep(Duration::from_millis(20)).await;
+                            Ok(("rule".to_string(), "content".to_string()))
+                        })
+                        .await
+                })
+            })
+            .collect();
+
+        for task in tasks {
+            assert_eq!(task.await.unwrap().unwrap().0, "rule");
+        }
+        assert_eq!(shared.4.load(Ordering::SeqCst), 1);
+        assert_eq!(shared.3.lock().unwrap().size, 1);
+    }
+
+    #[tokio::test]
+    async fn test_not_found_is_cached() {
+        let (loads, cache, config, stats) = setup();
+        let calls = AtomicUsize::new(0);
+
+        let load = || async {
+            calls.fetch_add(1, Ordering::SeqCst);
+            Err::<(String, String), _>(not_found("missing"))
+        };
+
+        for _ in 0..3 {
+            let result = loads
+                .load(&cache, &config, &stats, "rule", "missing", load)
+                .await;
+            assert!(matches!(result, Err(RepositoryError::NotFound { .. })));
+        }
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        // Other kinds with the same identifier are looked up separately
+        let _ = loads
+            .load(&cache, &config, &stats, "ruleset", "missing", load)
+            .await;
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+        loads.forget("missing");
+        let _ = loads
+            .load(&cache, &config, &stats, "rule", "missing", load)
+            .await;
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_negative_cache_expires_and_can_be_disabled() {
+        let (loads, cache, config, stats) = setup();
+        *config.lock().unwrap() = CacheConfig::new().with_negative_ttl(Duration::ZERO);
+        let calls = AtomicUsize::new(0);
+
+        let load = || async {
+            calls.fetch_add(1, Ordering::SeqCst);
+            Err::<(String, String), _>(not_found("missing"))
+        };
+
+        let _ = loads
+            .load(&cache, &config, &stats, "rule", "missing", load)
+            .await;
+        let _ = loads
+            .load(&cache, &config, &stats, "rule", "missing", load)
+            .await;
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+        *config.lock().unwrap() = CacheConfig::new().with_negative_ttl(Duration::from_millis(10));
+        let _ = loads
+            .load(&cache, &config, &stats, "rule", "missing", load)
+            .await;
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        let _ = loads
+            .load(&cache, &config, &stats, "rule", "missing", load)
+            .await;
+        assert_eq!(calls.load(Ordering::SeqCst), 4);
+    }
+
+    async fn load_value(
+        loads: &LoadCoordinator,
+        cache: &ArtifactCache<String>,
+        config: &Mutex<CacheConfig>,
+        stats: &Mutex<CacheStats>,
+        identifier: &str,
+    ) {
+        loads
+            .load(cache, config, stats, "rule", identifier, || async {
+                Ok((identifier.to_string(), String::new()))
+            })
+            .await
+            .unwrap();
+    }
+
+    fn hit(cache: &ArtifactCache<String>, identifier: &str) {
+        cache.try_read().unwrap()[identifier].touch();
+    }
+
+    fn cached_ids(cache: &ArtifactCache<String>) -> Vec<String> {
+        let mut ids: Vec<String> = cache.try_read().unwrap().keys().cloned().collect();
+        ids.sort();
+        ids
+    }
+
+    #[tokio::test]
+    async fn test_lru_eviction() {
+        let (loads, cache, config, stats) = setup();
+        *config.lock().unwrap() = CacheConfig::new().with_max_entries(2);
+
+        load_value(&loads, &cache, &config, &stats, "a").await;
+        load_value(&loads, &cache, &config, &stats, "b").await;
+        hit(&cache, "a");
+        load_value(&loads, &cache, &config, &stats, "c").await;
+
+        assert_eq!(cached_ids(&cache), vec!["a", "c"]);
+        let stats = stats.lock().unwrap();
+        assert_eq!(stats.size, 2);
+        assert_eq!(stats.evictions, 1);
+    }
+
+    #[tokio::test]
+    async fn test_lfu_eviction() {
+        let (loads, cache, config, stats) = setup();
+        *config.lock().unwrap() = CacheConfig::new()
+            .with_max_entries(2)
+            .with_eviction(EvictionPolicy::Lfu);
+
+        load_value(&loads, &cache, &config, &stats, "a").await;
+        load_value(&loads, &cache, &config, &stats, "b").await;
+        hit(&cache, "a");
+        hit(&cache, "a");
+        hit(&cache, "b");
+        // "b" was used last but less often than "a"
+        load_value(&loads, &cache, &config, &stats, "c").await;
+
+        assert_eq!(cached_ids(&cache), vec!["a", "c"]);
+
+        // Zero entries disables storing altogether
+        *config.lock().unwrap() = CacheConfig::new().with_max_entries(0);
+        load_value(&loads, &cache, &config, &stats, "d").await;
+        assert!(cached_ids(&cache).is_empty());
+        assert_eq!(stats.lock().unwrap().evictions, 3);
+    }
+
+    #[tokio::test]
+    async fn test_entity_ttl() {
+        let (loads, cache, config, stats) = setup();
+        *config.lock().unwrap() = CacheConfig::new()
+            .with_entity_ttl(BundleArtifactKind::Rule, Duration::from_secs(30))
+            .with_entity_ttl(BundleArtifactKind::Pipeline, Duration::from_secs(3600));
+
+        load_value(&loads, &cache, &config, &stats, "a").await;
+        assert_eq!(
+            cache.read().await["a"].ttl,
+            Duration::from_secs(30)
+        );
+
+        let config = config.lock().unwrap().clone();
+        assert_eq!(
+            config.ttl_for(BundleArtifactKind::Ruleset),
+            config.default_ttl
+        );
+    }
+}
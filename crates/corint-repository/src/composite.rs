@@ -0,0 +1,269 @@
+//! Layered repository composition
+//!
+//! [`CompositeRepository`] chains several repositories and resolves every
+//! lookup against them in priority order. The typical setup layers a small
+//! set of environment-specific overrides over a shared rule library:
+//!
+//! ```no_run
+//! use corint_repository::{CompositeRepository, FileSystemRepository, Repository};
+//!
+//! # #[tokio::main]
+//! # async fn main() -> anyhow::Result<()> {
+//! let repo = CompositeRepository::new()
+//!     .with_layer("staging-overrides", FileSystemRepository::new("overrides/staging")?)
+//!     .with_layer("shared", FileSystemRepository::new("repository")?);
+//!
+//! // Served by the overrides if they define `fraud_farm`, otherwise by the shared library
+//! let (rule, _) = repo.load_rule("fraud_farm").await?;
+//!
+//! for layer in repo.layer_stats() {
+//!     println!("{}: served {} lookups", layer.name, layer.served);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Only "not found" errors fall through to the next layer; any other error
+//! from a layer is returned as-is, so a broken override is never silently
+//! replaced by the base definition.
+
+use async_trait::async_trait;
+use corint_core::ast::{Pipeline, Rule, Ruleset};
+use std::collections::HashSet;
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::{
+    bundle::BundleArtifactKind,
+    error::RepositoryError,
+    models::{CacheConfig, CacheStats},
+    traits::{CacheableRepository, Repository},
+    RepositoryResult,
+};
+
+/// Statistics for one layer of a [`CompositeRepository`]
+#[derive(Debug, Clone, Default)]
+pub struct LayerStats {
+    /// Layer name
+    pub name: String,
+
+    /// Lookups answered by this layer
+    pub served: u64,
+
+    /// Lookups that fell through this layer to a lower-priority one
+    pub fallthrough: u64,
+
+    /// Cache statistics reported by the layer's repository
+    pub cache: CacheStats,
+}
+
+struct RepositoryLayer {
+    name: String,
+    repository: Box<dyn CacheableRepository>,
+    served: AtomicU64,
+    fallthrough: AtomicU64,
+}
+
+/// Repository that resolves lookups across priority-ordered layers
+///
+/// Layers are consulted in the order they were added; the first layer that
+/// has an artifact wins.
+#[derive(Default)]
+pub struct CompositeRepository {
+    layers: Vec<RepositoryLayer>,
+}
+
+impl CompositeRepository {
+    /// Create a composite repository without layers
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a layer below all existing layers
+    pub fn with_layer(
+        mut self,
+        name: impl Into<String>,
+        repository: impl CacheableRepository + 'static,
+    ) -> Self {
+        self.layers.push(RepositoryLayer {
+            name: name.into(),
+            repository: Box::new(repository),
+            served: AtomicU64::new(0),
+            fallthrough: AtomicU64::new(0),
+        });
+        self
+    }
+
+    /// Layer names, highest priority first
+    pub fn layer_names(&self) -> Vec<&str> {
+        self.layers
+            .iter()
+            .map(|layer| layer.name.as_str())
+            .collect()
+    }
+
+    /// Per-layer lookup and cache statistics, highest priority first
+    pub fn layer_stats(&self) -> Vec<LayerStats> {
+        self.layers
+            .iter()
+            .map(|layer| LayerStats {
+                name: layer.name.clone(),
+                served: layer.served.load(Ordering::Relaxed),
+                fallthrough: layer.fallthrough.load(Ordering::Relaxed),
+                cache: layer.repository.cache_stats(),
+            })
+            .collect()
+    }
+
+    /// Name of the layer that provides an artifact, if any
+    pub async fn resolve_layer(&self, identifier: &str) -> RepositoryResult<Option<&str>> {
+        for layer in &self.layers {
+            if layer.repository.exists(identifier).await? {
+                return Ok(Some(&layer.name));
+            }
+        }
+        Ok(None)
+    }
+
+    async fn first_found<'a, T, F, Fut>(&'a self, what: &str, load: F) -> RepositoryResult<T>
+    where
+        F: Fn(&'a dyn CacheableRepository) -> Fut,
+        Fut: Future<Output = RepositoryResult<T>>,
+    {
+        for layer in &self.layers {
+            match load(layer.repository.as_ref()).await {
+                Ok(found) => {
+                    layer.served.fetch_add(1, Ordering::Relaxed);
+                    return Ok(found);
+                }
+                Err(e) if is_not_found(&e) => {
+                    layer.fallthrough.fetch_add(1, Ordering::Relaxed);
+                }
+                Err(e) => return Err(e),
+            }
+        }
+
+        Err(RepositoryError::NotFound {
+            path: what.to_string(),
+        })
+    }
+
+    async fn merged_ids<'a, F, Fut>(&'a self, list: F) -> RepositoryResult<Vec<String>>
+    where
+        F: Fn(&'a dyn CacheableRepository) -> Fut,
+        Fut: Future<Output = RepositoryResult<Vec<String>>>,
+    {
+        let mut seen = HashSet::new();
+        let mut ids = Vec::new();
+        for layer in &self.layers {
+            for id in list(layer.repository.as_ref()).await? {
+                if seen.insert(id.clone()) {
+                    ids.push(id);
+                }
+            }
+        }
+        Ok(ids)
+    }
+}
+
+fn is_not_found(error: &RepositoryError) -> bool {
+    matches!(
+        error,
+        RepositoryError::NotFound { .. } | RepositoryError::IdNotFound { .. }
+    )
+}
+
+#[async_trait]
+impl Repository for CompositeRepository {
+    async fn load_rule(&self, identifier: &str) -> RepositoryResult<(Rule, String)> {
+        self.first_found(identifier, |repo| repo.load_rule(identifier))
+            .await
+    }
+
+    async fn load_ruleset(&self, identifier: &str) -> RepositoryResult<(Ruleset, String)> {
+        self.first_found(identifier, |repo| repo.load_ruleset(identifier))
+            .await
+    }
+
+    async fn load_pipeline(&self, identifier: &str) -> RepositoryResult<(Pipeline, String)> {
+        self.first_found(identifier, |repo| repo.load_pipeline(identifier))
+            .await
+    }
+
+    async fn exists(&self, identifier: &str) -> RepositoryResult<bool> {
+        Ok(self.resolve_layer(identifier).await?.is_some())
+    }
+
+    async fn list_rules(&self) -> RepositoryResult<Vec<String>> {
+        self.merged_ids(|repo| repo.list_rules()).await
+    }
+
+    async fn list_rulesets(&self) -> RepositoryResult<Vec<String>> {
+        self.merged_ids(|repo| repo.list_rulesets()).await
+    }
+
+    async fn list_pipelines(&self) -> RepositoryResult<Vec<String>> {
+        self.merged_ids(|repo| repo.list_pipelines()).await
+    }
+
+    async fn load_registry(&self) -> RepositoryResult<String> {
+        self.first_found("registry", |repo| repo.load_registry())
+            .await
+    }
+
+    async fn load_draft(
+        &self,
+        kind: BundleArtifactKind,
+        identifier: &str,
+    ) -> RepositoryResult<String> {
+        let what = format!("{} draft '{}'", kind.as_str(), identifier);
+        self.first_found(&what, |repo| repo.load_draft(kind, identifier))
+            .await
+    }
+}
+
+impl CacheableRepository for CompositeRepository {
+    fn clear_cache(&mut self) {
+        for layer in &mut self.layers {
+            layer.repository.clear_cache();
+        }
+    }
+
+    fn clear_cache_entry(&mut self, identifier: &str) {
+        for layer in &mut self.layers {
+            layer.repository.clear_cache_entry(identifier);
+        }
+    }
+
+    /// Cache statistics summed over all layers
+    fn cache_stats(&self) -> CacheStats {
+        self.layers
+            .iter()
+            .map(|layer| layer.repository.cache_stats())
+            .fold(CacheStats::default(), |total, stats| CacheStats {
+                hits: total.hits + stats.hits,
+                misses: total.misses + stats.misses,
+                size: total.size + stats.size,
+                memory_bytes: total.memory_bytes + stats.memory_bytes,
+                evictions: total.evictions + stats.evictions,
+            })
+    }
+
+    fn set_cache_enabled(&mut self, enabled: bool) {
+        for layer in &mut self.layers {
+            layer.repository.set_cache_enabled(enabled);
+        }
+    }
+
+    fn is_cache_enabled(&self) -> bool {
+        self.layers
+            .iter()
+            .any(|layer| layer.repository.is_cache_enabled())
+    }
+
+    fn set_cache_config(&mut self, config: CacheConfig) {
+        for layer in &mut self.layers {
+            layer.repository.set_cache_config(config.clone());
+        }
+    }
+}
@@ -1,10 +1,12 @@
 //! Repository configuration types
 //!
 //! This module provides configuration types for different repository sources
-//! (file system, database, API, memory).
+//! (file system, database, API, Redis, MongoDB, memory).
 
 use serde::{Deserialize, Serialize};
 
+use crate::models::CacheConfig;
+
 /// Repository source type
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
@@ -15,6 +17,10 @@ pub enum RepositorySource {
     Database,
     /// Load from HTTP API
     Api,
+    /// Load from Redis
+    Redis,
+    /// Load from MongoDB
+    MongoDB,
     /// In-memory configuration (for testing or WASM with manual content)
     Memory,
 }
@@ -56,7 +62,7 @@ pub struct RepositoryConfig {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub base_path: Option<String>,
 
-    /// Database connection URL (required for Database source)
+    /// Database connection URL (required for Database and MongoDB sources)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub database_url: Option<String>,
 
@@ -67,6 +73,60 @@ pub struct RepositoryConfig {
     /// API key for authentication (optional for Api source)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub api_key: Option<String>,
+
+    /// Redis connection URL (required for Redis source)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub redis_url: Option<String>,
+
+    /// Checksum/signature verification of loaded content (disabled when unset)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub integrity: Option<IntegrityConfig>,
+
+    /// Artifact cache policy (each repository's default when unset)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache: Option<CacheConfig>,
+}
+
+/// Content integrity verification settings
+///
+/// When enabled, every registry, pipeline, rule and ruleset loaded from the
+/// repository must match the SHA-256 checksum recorded in the integrity
+/// manifest, and carry a valid Ed25519 signature if public keys are configured.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IntegrityConfig {
+    /// Path to the integrity manifest (JSON)
+    ///
+    /// Defaults to `integrity.json` in the repository root for file system sources.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub manifest_path: Option<String>,
+
+    /// Hex-encoded Ed25519 public keys trusted to sign artifacts
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub public_keys: Vec<String>,
+
+    /// Reject artifacts without a valid signature
+    #[serde(default)]
+    pub require_signatures: bool,
+}
+
+impl IntegrityConfig {
+    /// Checksum verification using the default manifest location
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the manifest path
+    pub fn with_manifest_path(mut self, path: impl Into<String>) -> Self {
+        self.manifest_path = Some(path.into());
+        self
+    }
+
+    /// Trust a hex-encoded Ed25519 public key and require signatures
+    pub fn with_public_key(mut self, key: impl Into<String>) -> Self {
+        self.public_keys.push(key.into());
+        self.require_signatures = true;
+        self
+    }
 }
 
 impl RepositoryConfig {
@@ -88,6 +148,9 @@ impl RepositoryConfig {
             database_url: None,
             api_url: None,
             api_key: None,
+            redis_url: None,
+            integrity: None,
+            cache: None,
         }
     }
 
@@ -109,6 +172,9 @@ impl RepositoryConfig {
             database_url: Some(url.into()),
             api_url: None,
             api_key: None,
+            redis_url: None,
+            integrity: None,
+            cache: None,
         }
     }
 
@@ -130,6 +196,57 @@ impl RepositoryConfig {
             database_url: None,
             api_url: Some(url.into()),
             api_key: None,
+            redis_url: None,
+            integrity: None,
+            cache: None,
+        }
+    }
+
+    /// Create a Redis repository configuration
+    ///
+    /// # Arguments
+    /// * `url` - Redis connection URL
+    ///
+    /// # Example
+    /// ```rust
+    /// use corint_repository::RepositoryConfig;
+    ///
+    /// let config = RepositoryConfig::redis("redis://localhost:6379");
+    /// ```
+    pub fn redis(url: impl Into<String>) -> Self {
+        Self {
+            source: RepositorySource::Redis,
+            base_path: None,
+            database_url: None,
+            api_url: None,
+            api_key: None,
+            redis_url: Some(url.into()),
+            integrity: None,
+            cache: None,
+        }
+    }
+
+    /// Create a MongoDB repository configuration
+    ///
+    /// # Arguments
+    /// * `url` - MongoDB connection string (the database name defaults to `corint`)
+    ///
+    /// # Example
+    /// ```rust
+    /// use corint_repository::RepositoryConfig;
+    ///
+    /// let config = RepositoryConfig::mongodb("mongodb://localhost:27017/corint");
+    /// ```
+    pub fn mongodb(url: impl Into<String>) -> Self {
+        Self {
+            source: RepositorySource::MongoDB,
+            base_path: None,
+            database_url: Some(url.into()),
+            api_url: None,
+            api_key: None,
+            redis_url: None,
+            integrity: None,
+            cache: None,
         }
     }
 
@@ -150,6 +267,9 @@ impl RepositoryConfig {
             database_url: None,
             api_url: None,
             api_key: None,
+            redis_url: None,
+            integrity: None,
+            cache: None,
         }
     }
 
@@ -170,6 +290,39 @@ impl RepositoryConfig {
         self
     }
 
+    /// Verify content checksums (and signatures) on load
+    ///
+    /// # Example
+    /// ```rust
+    /// use corint_repository::{IntegrityConfig, RepositoryConfig};
+    ///
+    /// let config = RepositoryConfig::file_system("repository")
+    ///     .with_integrity(IntegrityConfig::new().with_manifest_path("approved/integrity.json"));
+    /// ```
+    pub fn with_integrity(mut self, integrity: IntegrityConfig) -> Self {
+        self.integrity = Some(integrity);
+        self
+    }
+
+    /// Set the artifact cache policy
+    ///
+    /// # Example
+    /// ```rust
+    /// use corint_repository::{CacheConfig, EvictionPolicy, RepositoryConfig};
+    /// use std::time::Duration;
+    ///
+    /// let config = RepositoryConfig::redis("redis://localhost:6379").with_cache(
+    ///     CacheConfig::new()
+    ///         .with_max_entries(500)
+    ///         .with_eviction(EvictionPolicy::Lfu)
+    ///         .with_ttl(Duration::from_secs(60)),
+    /// );
+    /// ```
+    pub fn with_cache(mut self, cache: CacheConfig) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
     /// Validate the configuration
     ///
     /// Returns an error if required fields are missing for the selected source.
@@ -199,6 +352,22 @@ impl RepositoryConfig {
                     });
                 }
             }
+            RepositorySource::Redis => {
+                if self.redis_url.is_none() {
+                    return Err(ConfigError::MissingField {
+                        source: "Redis".to_string(),
+                        field: "redis_url".to_string(),
+                    });
+                }
+            }
+            RepositorySource::MongoDB => {
+                if self.database_url.is_none() {
+                    return Err(ConfigError::MissingField {
+                        source: "MongoDB".to_string(),
+                        field: "database_url".to_string(),
+                    });
+                }
+            }
             RepositorySource::Memory => {
                 // Memory source doesn't require any fields
             }
@@ -268,6 +437,36 @@ mod tests {
         assert!(config.validate().is_ok());
     }
 
+    #[test]
+    fn test_redis_config() {
+        let config = RepositoryConfig::redis("redis://localhost:6379");
+
+        assert_eq!(config.source, RepositorySource::Redis);
+        assert_eq!(config.redis_url, Some("redis://localhost:6379".to_string()));
+        assert!(config.validate().is_ok());
+
+        let missing = RepositoryConfig {
+            redis_url: None,
+            ..config
+        };
+        assert!(missing.validate().is_err());
+    }
+
+    #[test]
+    fn test_mongodb_config() {
+        let config = RepositoryConfig::mongodb("mongodb://localhost:27017/corint");
+
+        assert_eq!(config.source, RepositorySource::MongoDB);
+        assert_eq!(
+            config.database_url,
+            Some("mongodb://localhost:27017/corint".to_string())
+        );
+        assert!(config.validate().is_ok());
+
+        let parsed: RepositorySource = serde_json::from_str("\"mongodb\"").unwrap();
+        assert_eq!(parsed, RepositorySource::MongoDB);
+    }
+
     #[test]
     fn test_memory_config() {
         let config = RepositoryConfig::memory();
@@ -287,6 +486,9 @@ mod tests {
             database_url: None,
             api_url: None,
             api_key: None,
+            redis_url: None,
+            integrity: None,
+            cache: None,
         };
 
         assert!(config.validate().is_err());
@@ -300,6 +502,9 @@ mod tests {
             database_url: None,
             api_url: None,
             api_key: None,
+            redis_url: None,
+            integrity: None,
+            cache: None,
         };
 
         assert!(config.validate().is_err());
@@ -313,11 +518,56 @@ mod tests {
             database_url: None,
             api_url: None,
             api_key: None,
+            redis_url: None,
+            integrity: None,
+            cache: None,
         };
 
         assert!(config.validate().is_err());
     }
 
+    #[test]
+    fn test_cache_config_from_yaml() {
+        use crate::{BundleArtifactKind, EvictionPolicy};
+        use std::time::Duration;
+
+        let config: RepositoryConfig = serde_yaml::from_str(
+            r#"
+source: redis
+redis_url: redis://localhost:6379
+cache:
+  default_ttl: 120
+  entity_ttls:
+    pipeline: 3600
+    rule: 0.5
+  max_entries: 100
+  eviction: lfu
+"#,
+        )
+        .unwrap();
+
+        let cache = config.cache.unwrap();
+        assert_eq!(cache.default_ttl, Duration::from_secs(120));
+        assert_eq!(cache.max_entries, Some(100));
+        assert_eq!(cache.eviction, EvictionPolicy::Lfu);
+        assert!(cache.enabled);
+        assert_eq!(
+            cache.ttl_for(BundleArtifactKind::Pipeline),
+            Duration::from_secs(3600)
+        );
+        assert_eq!(
+            cache.ttl_for(BundleArtifactKind::Rule),
+            Duration::from_millis(500)
+        );
+        assert_eq!(
+            cache.ttl_for(BundleArtifactKind::Ruleset),
+            Duration::from_secs(120)
+        );
+
+        let yaml = serde_yaml::to_string(&cache).unwrap();
+        assert_eq!(serde_yaml::from_str::<CacheConfig>(&yaml).unwrap(), cache);
+    }
+
     #[test]
     fn test_default_source() {
         assert_eq!(RepositorySource::default(), RepositorySource::FileSystem);
@@ -3,6 +3,7 @@
 //! This module defines the content types loaded from a repository,
 //! including pipelines, rules, rulesets, and various configurations.
 
+use crate::bundle::BundleArtifactKind;
 use serde::{Deserialize, Serialize};
 
 /// All content loaded from a repository
@@ -34,6 +35,15 @@ pub struct RepositoryContent {
 
     /// List configurations
     pub list_configs: Vec<ListConfig>,
+
+    /// Reason code catalogs (id, yaml content)
+    pub reason_catalogs: Vec<(String, String)>,
+
+    /// PII masking policies (id, yaml content)
+    pub masking_policies: Vec<(String, String)>,
+
+    /// Entity key definitions (id, yaml content)
+    pub entity_definitions: Vec<(String, String)>,
 }
 
 impl RepositoryContent {
@@ -57,6 +67,22 @@ impl RepositoryContent {
         self.rulesets.push((id.into(), content.into()));
     }
 
+    /// Registry, pipelines, rules and rulesets as (kind, id, yaml)
+    ///
+    /// These are the definitions covered by integrity verification.
+    pub fn definitions(&self) -> impl Iterator<Item = (BundleArtifactKind, &str, &str)> {
+        let registry = self
+            .registry
+            .as_deref()
+            .map(|yaml| (BundleArtifactKind::Registry, "registry", yaml));
+
+        registry
+            .into_iter()
+            .chain(definitions_of(BundleArtifactKind::Pipeline, &self.pipelines))
+            .chain(definitions_of(BundleArtifactKind::Rule, &self.rules))
+            .chain(definitions_of(BundleArtifactKind::Ruleset, &self.rulesets))
+    }
+
     /// Merge another RepositoryContent into this one
     ///
     /// The other content is appended to existing content.
@@ -73,6 +99,9 @@ impl RepositoryContent {
         self.datasource_configs.extend(other.datasource_configs);
         self.feature_definitions.extend(other.feature_definitions);
         self.list_configs.extend(other.list_configs);
+        self.reason_catalogs.extend(other.reason_catalogs);
+        self.masking_policies.extend(other.masking_policies);
+        self.entity_definitions.extend(other.entity_definitions);
     }
 
     /// Check if the content is empty
@@ -85,6 +114,9 @@ impl RepositoryContent {
             && self.datasource_configs.is_empty()
             && self.feature_definitions.is_empty()
             && self.list_configs.is_empty()
+            && self.reason_catalogs.is_empty()
+            && self.masking_policies.is_empty()
+            && self.entity_definitions.is_empty()
     }
 
     /// Get total count of all artifacts
@@ -97,9 +129,21 @@ impl RepositoryContent {
             + self.datasource_configs.len()
             + self.feature_definitions.len()
             + self.list_configs.len()
+            + self.reason_catalogs.len()
+            + self.masking_policies.len()
+            + self.entity_definitions.len()
     }
 }
 
+fn definitions_of(
+    kind: BundleArtifactKind,
+    items: &[(String, String)],
+) -> impl Iterator<Item = (BundleArtifactKind, &str, &str)> {
+    items
+        .iter()
+        .map(move |(id, yaml)| (kind, id.as_str(), yaml.as_str()))
+}
+
 // Re-export API types defined in corint-runtime
 // Note: These types need to be available for repository loading,
 // but the actual implementation is in corint-runtime
@@ -338,6 +382,10 @@ pub struct ListConfig {
     /// Backend type (memory, postgresql, file)
     pub backend: String,
 
+    /// Match type: exact (default), cidr, pattern or bloom
+    #[serde(default, rename = "type")]
+    pub list_type: Option<String>,
+
     /// Table name (for database backend)
     #[serde(default)]
     pub table: Option<String>,
@@ -350,7 +398,7 @@ pub struct ListConfig {
     #[serde(default)]
     pub expiration_column: Option<String>,
 
-    /// File path (for file backend)
+    /// File path, URL or `s3://bucket/key` object (for file backend)
     #[serde(default)]
     pub path: Option<String>,
 
@@ -358,6 +406,14 @@ pub struct ListConfig {
     #[serde(default)]
     pub reload_interval: Option<u64>,
 
+    /// File format: txt, csv, json or parquet (for file backend)
+    #[serde(default)]
+    pub format: Option<String>,
+
+    /// Column holding the values (for csv, json and parquet files)
+    #[serde(default)]
+    pub column: Option<String>,
+
     /// Initial values (for memory backend)
     #[serde(default)]
     pub initial_values: Vec<String>,
@@ -425,11 +481,14 @@ mod tests {
             id: "blocklist".to_string(),
             description: Some("Email blocklist".to_string()),
             backend: "memory".to_string(),
+            list_type: None,
             table: None,
             value_column: None,
             expiration_column: None,
             path: None,
             reload_interval: None,
+            format: None,
+            column: None,
             initial_values: vec!["bad@example.com".to_string()],
         };
 
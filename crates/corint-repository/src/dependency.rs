@@ -0,0 +1,608 @@
+//! Dependency graph of repository artifacts
+//!
+//! [`Repository::dependency_graph`] walks everything a pipeline depends on:
+//! the rules, rulesets and sub-pipelines its steps include, the rules and
+//! parent rulesets of each ruleset, the files listed in `import:` sections,
+//! and the features (`features.<name>`) and lists (`list.<id>`) referenced
+//! by conditions. The result is a [`DependencyGraph`] that answers impact
+//! questions such as "which pipelines run this rule?" and "can this ruleset
+//! be deleted?".
+//!
+//! ```no_run
+//! use corint_repository::{BundleArtifactKind, DependencyNode, FileSystemRepository, Repository};
+//!
+//! # #[tokio::main]
+//! # async fn main() -> anyhow::Result<()> {
+//! let repo = FileSystemRepository::new("repository")?;
+//! let graph = repo.full_dependency_graph().await?;
+//!
+//! let rule = DependencyNode::new(BundleArtifactKind::Rule, "high_amount");
+//! for pipeline in graph.affected_pipelines(&rule) {
+//!     println!("{} runs {}", pipeline.id, rule.id);
+//! }
+//! if graph.can_delete(&rule) {
+//!     println!("{} is unused", rule.id);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`Repository::dependency_graph`]: crate::Repository::dependency_graph
+
+use corint_core::ast::pipeline::StepDetails;
+use corint_core::ast::{
+    Condition, ConditionGroup, Expression, Imports, Pipeline, Rule, Ruleset, WhenBlock,
+};
+use corint_parser::ImportParser;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+use std::fmt;
+
+use crate::{traits::Repository, BundleArtifactKind, RepositoryResult};
+
+/// Artifact in a dependency graph, identified by kind and ID
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+pub struct DependencyNode {
+    /// Artifact kind (pipeline, ruleset, rule, feature or list)
+    pub kind: BundleArtifactKind,
+
+    /// Artifact ID
+    pub id: String,
+}
+
+impl DependencyNode {
+    /// Create a node
+    pub fn new(kind: BundleArtifactKind, id: impl Into<String>) -> Self {
+        Self {
+            kind,
+            id: id.into(),
+        }
+    }
+}
+
+impl fmt::Display for DependencyNode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.kind.as_str(), self.id)
+    }
+}
+
+/// How one artifact depends on another
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DependencyRelation {
+    /// A pipeline step runs the rule, ruleset or sub-pipeline
+    Includes,
+
+    /// A ruleset lists the rule
+    Contains,
+
+    /// A ruleset extends the parent ruleset
+    Extends,
+
+    /// The artifact is listed in the `import:` section
+    Imports,
+
+    /// A condition or parameter references the feature or list
+    References,
+}
+
+/// Directed edge from a dependent artifact to its dependency
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+pub struct DependencyEdge {
+    /// Dependent artifact
+    pub from: DependencyNode,
+
+    /// Dependency
+    pub to: DependencyNode,
+
+    /// Kind of dependency
+    pub relation: DependencyRelation,
+}
+
+/// Referenced artifact that could not be loaded
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct UnresolvedDependency {
+    /// Node of the missing artifact, identified as referenced
+    pub node: DependencyNode,
+
+    /// Error message
+    pub error: String,
+}
+
+/// Dependency graph of one or more pipelines
+///
+/// Edges point from an artifact to what it depends on. Features and lists
+/// are leaves; they are identified by the name used in conditions and not
+/// loaded. Artifacts that fail to load are kept as nodes, so a dangling
+/// reference still shows up in impact analysis, and are listed in
+/// [`unresolved`](Self::unresolved).
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct DependencyGraph {
+    /// Pipelines the graph was built from
+    pub roots: Vec<DependencyNode>,
+
+    /// All artifacts in the graph, including the roots
+    pub nodes: BTreeSet<DependencyNode>,
+
+    /// Dependency edges
+    pub edges: BTreeSet<DependencyEdge>,
+
+    /// Referenced artifacts that could not be loaded
+    pub unresolved: Vec<UnresolvedDependency>,
+}
+
+impl DependencyGraph {
+    /// Whether the graph contains an artifact
+    pub fn contains(&self, node: &DependencyNode) -> bool {
+        self.nodes.contains(node)
+    }
+
+    /// Artifacts of one kind
+    pub fn nodes_of_kind(&self, kind: BundleArtifactKind) -> impl Iterator<Item = &DependencyNode> {
+        self.nodes.iter().filter(move |n| n.kind == kind)
+    }
+
+    /// Direct dependencies of an artifact
+    pub fn dependencies(&self, node: &DependencyNode) -> BTreeSet<&DependencyNode> {
+        self.edges
+            .iter()
+            .filter(|e| &e.from == node)
+            .map(|e| &e.to)
+            .collect()
+    }
+
+    /// Artifacts that depend directly on an artifact
+    pub fn dependents(&self, node: &DependencyNode) -> BTreeSet<&DependencyNode> {
+        self.edges
+            .iter()
+            .filter(|e| &e.to == node)
+            .map(|e| &e.from)
+            .collect()
+    }
+
+    /// Everything an artifact depends on, directly or transitively
+    pub fn transitive_dependencies(&self, node: &DependencyNode) -> BTreeSet<&DependencyNode> {
+        self.reachable(node, |n| self.dependencies(n))
+    }
+
+    /// Everything that depends on an artifact, directly or transitively
+    ///
+    /// This is the set of artifacts affected by a change to `node`.
+    pub fn impact(&self, node: &DependencyNode) -> BTreeSet<&DependencyNode> {
+        self.reachable(node, |n| self.dependents(n))
+    }
+
+    /// Pipelines that depend on an artifact, directly or transitively
+    pub fn affected_pipelines(&self, node: &DependencyNode) -> Vec<&DependencyNode> {
+        self.impact(node)
+            .into_iter()
+            .filter(|n| n.kind == BundleArtifactKind::Pipeline)
+            .collect()
+    }
+
+    /// Whether nothing in the graph depends on an artifact
+    ///
+    /// Only the pipelines the graph was built from are considered; use
+    /// [`Repository::full_dependency_graph`](crate::Repository::full_dependency_graph)
+    /// before deleting artifacts.
+    pub fn can_delete(&self, node: &DependencyNode) -> bool {
+        !self.edges.iter().any(|e| &e.to == node)
+    }
+
+    /// Artifacts ordered so that every dependency precedes its dependents
+    ///
+    /// Returns `None` when the graph has a cycle, e.g. two rulesets
+    /// extending each other.
+    pub fn topological_order(&self) -> Option<Vec<&DependencyNode>> {
+        let mut pending: BTreeMap<&DependencyNode, usize> =
+            self.nodes.iter().map(|n| (n, 0)).collect();
+        for edge in &self.edges {
+            *pending.entry(&edge.from).or_default() += 1;
+        }
+
+        let mut ready: VecDeque<&DependencyNode> = pending
+            .iter()
+            .filter(|(_, count)| **count == 0)
+            .map(|(n, _)| *n)
+            .collect();
+        let mut order = Vec::with_capacity(pending.len());
+        while let Some(node) = ready.pop_front() {
+            order.push(node);
+            for edge in self.edges.iter().filter(|e| &e.to == node) {
+                let count = pending.get_mut(&edge.from)?;
+                *count -= 1;
+                if *count == 0 {
+                    ready.push_back(&edge.from);
+                }
+            }
+        }
+
+        (order.len() == pending.len()).then_some(order)
+    }
+
+    fn reachable<'a, F>(&'a self, node: &DependencyNode, next: F) -> BTreeSet<&'a DependencyNode>
+    where
+        F: Fn(&DependencyNode) -> BTreeSet<&'a DependencyNode>,
+    {
+        let mut seen = BTreeSet::new();
+        let mut queue: VecDeque<&DependencyNode> = next(node).into_iter().collect();
+        while let Some(current) = queue.pop_front() {
+            if current != node && seen.insert(current) {
+                queue.extend(next(current));
+            }
+        }
+        seen
+    }
+}
+
+#[derive(Default)]
+struct GraphBuilder {
+    graph: DependencyGraph,
+    /// Node each attempted (kind, identifier) resolved to
+    resolved: BTreeMap<(BundleArtifactKind, String), DependencyNode>,
+    /// Referenced artifacts still to load, with the edge leading to them
+    queue: VecDeque<(
+        BundleArtifactKind,
+        String,
+        DependencyNode,
+        DependencyRelation,
+    )>,
+}
+
+impl GraphBuilder {
+    fn edge(&mut self, from: &DependencyNode, to: DependencyNode, relation: DependencyRelation) {
+        self.graph.nodes.insert(to.clone());
+        self.graph.edges.insert(DependencyEdge {
+            from: from.clone(),
+            to,
+            relation,
+        });
+    }
+
+    fn reference(
+        &mut self,
+        from: &DependencyNode,
+        kind: BundleArtifactKind,
+        identifier: &str,
+        relation: DependencyRelation,
+    ) {
+        self.queue
+            .push_back((kind, identifier.to_string(), from.clone(), relation));
+    }
+
+    fn imports(&mut self, from: &DependencyNode, content: &str) {
+        let Ok((Some(imports), _)) = ImportParser::parse_with_imports(content) else {
+            return;
+        };
+        let Imports {
+            rules,
+            rulesets,
+            pipelines,
+            ..
+        } = imports;
+        let listed = [
+            (BundleArtifactKind::Rule, rules),
+            (BundleArtifactKind::Ruleset, rulesets),
+            (BundleArtifactKind::Pipeline, pipelines),
+        ];
+        for (kind, paths) in listed {
+            for path in paths {
+                self.reference(from, kind, &path, DependencyRelation::Imports);
+            }
+        }
+    }
+
+    fn pipeline(&mut self, node: &DependencyNode, pipeline: &Pipeline) {
+        let mut refs = Vec::new();
+        if let Some(when) = &pipeline.when {
+            when_references(when, &mut refs);
+        }
+        for step in &pipeline.steps {
+            match &step.details {
+                StepDetails::Rule { rule } => self.reference(
+                    node,
+                    BundleArtifactKind::Rule,
+                    rule,
+                    DependencyRelation::Includes,
+                ),
+                StepDetails::Ruleset { ruleset } => self.reference(
+                    node,
+                    BundleArtifactKind::Ruleset,
+                    ruleset,
+                    DependencyRelation::Includes,
+                ),
+                StepDetails::SubPipeline { pipeline_id } => self.reference(
+                    node,
+                    BundleArtifactKind::Pipeline,
+                    pipeline_id,
+                    DependencyRelation::Includes,
+                ),
+                StepDetails::Function { params, .. }
+                | StepDetails::Service { params, .. }
+                | StepDetails::Api { params, .. } => {
+                    for expr in params.iter().flat_map(|p| p.values()) {
+                        expression_references(expr, &mut refs);
+                    }
+                }
+                _ => {}
+            }
+            if let Some(when) = &step.when {
+                when_references(when, &mut refs);
+            }
+            for route in step.routes.iter().flatten() {
+                when_references(&route.when, &mut refs);
+            }
+        }
+        for rule in pipeline.decision.iter().flatten() {
+            if let Some(when) = &rule.when {
+                when_references(when, &mut refs);
+            }
+        }
+        self.leaves(node, refs);
+    }
+
+    fn ruleset(&mut self, node: &DependencyNode, ruleset: &Ruleset) {
+        if let Some(parent) = &ruleset.extends {
+            self.reference(
+                node,
+                BundleArtifactKind::Ruleset,
+                parent,
+                DependencyRelation::Extends,
+            );
+        }
+        for rule in &ruleset.rules {
+            self.reference(
+                node,
+                BundleArtifactKind::Rule,
+                rule,
+                DependencyRelation::Contains,
+            );
+        }
+        let mut refs = Vec::new();
+        for rule in &ruleset.conclusion {
+            if let Some(condition) = &rule.condition {
+                expression_references(condition, &mut refs);
+            }
+        }
+        self.leaves(node, refs);
+    }
+
+    fn rule(&mut self, node: &DependencyNode, rule: &Rule) {
+        let mut refs = Vec::new();
+        when_references(&rule.when, &mut refs);
+        self.leaves(node, refs);
+    }
+
+    fn leaves(&mut self, from: &DependencyNode, refs: Vec<DependencyNode>) {
+        for leaf in refs {
+            self.edge(from, leaf, DependencyRelation::References);
+        }
+    }
+
+    /// Load one artifact and record its references, returning its node
+    async fn load<R: Repository + ?Sized>(
+        &mut self,
+        repo: &R,
+        kind: BundleArtifactKind,
+        identifier: &str,
+    ) -> RepositoryResult<DependencyNode> {
+        let key = (kind, identifier.to_string());
+        if let Some(node) = self.resolved.get(&key) {
+            return Ok(node.clone());
+        }
+
+        let node = match kind {
+            BundleArtifactKind::Pipeline => {
+                let (pipeline, content) = repo.load_pipeline(identifier).await?;
+                let node = DependencyNode::new(kind, &pipeline.id);
+                self.pipeline(&node, &pipeline);
+                self.imports(&node, &content);
+                node
+            }
+            BundleArtifactKind::Ruleset => {
+                let (ruleset, content) = repo.load_ruleset(identifier).await?;
+                let node = DependencyNode::new(kind, &ruleset.id);
+                self.ruleset(&node, &ruleset);
+                self.imports(&node, &content);
+                node
+            }
+            _ => {
+                let (rule, content) = repo.load_rule(identifier).await?;
+                let node = DependencyNode::new(kind, &rule.id);
+                self.rule(&node, &rule);
+                self.imports(&node, &content);
+                node
+            }
+        };
+
+        self.graph.nodes.insert(node.clone());
+        self.resolved.insert(key, node.clone());
+        Ok(node)
+    }
+
+    /// Load queued references until every reachable artifact is resolved
+    async fn drain<R: Repository + ?Sized>(&mut self, repo: &R) {
+        while let Some((kind, identifier, from, relation)) = self.queue.pop_front() {
+            let to = match self.load(repo, kind, &identifier).await {
+                Ok(node) => node,
+                Err(e) => {
+                    let node = DependencyNode::new(kind, &identifier);
+                    self.resolved.insert((kind, identifier), node.clone());
+                    self.graph.unresolved.push(UnresolvedDependency {
+                        node: node.clone(),
+                        error: e.to_string(),
+                    });
+                    node
+                }
+            };
+            self.edge(&from, to, relation);
+        }
+    }
+}
+
+/// Build the dependency graph of the given pipelines
+///
+/// Failing to load one of the pipelines fails the build; failures further
+/// down are recorded as unresolved dependencies.
+pub(crate) async fn build<R: Repository + ?Sized>(
+    repo: &R,
+    pipelines: &[String],
+) -> RepositoryResult<DependencyGraph> {
+    let mut builder = GraphBuilder::default();
+    for identifier in pipelines {
+        let node = builder
+            .load(repo, BundleArtifactKind::Pipeline, identifier)
+            .await?;
+        if !builder.graph.roots.contains(&node) {
+            builder.graph.roots.push(node);
+        }
+        builder.drain(repo).await;
+    }
+    Ok(builder.graph)
+}
+
+fn when_references(when: &WhenBlock, refs: &mut Vec<DependencyNode>) {
+    if let Some(group) = &when.condition_group {
+        group_references(group, refs);
+    }
+    for expr in when.conditions.iter().flatten() {
+        expression_references(expr, refs);
+    }
+}
+
+fn group_references(group: &ConditionGroup, refs: &mut Vec<DependencyNode>) {
+    let (ConditionGroup::All(conditions)
+    | ConditionGroup::Any(conditions)
+    | ConditionGroup::Not(conditions)) = group;
+    for condition in conditions {
+        match condition {
+            Condition::Expression(expr) => expression_references(expr, refs),
+            Condition::Group(group) => group_references(group, refs),
+        }
+    }
+}
+
+/// Collect the features and lists an expression references
+fn expression_references(expr: &Expression, refs: &mut Vec<DependencyNode>) {
+    match expr {
+        Expression::FieldAccess(path) if path.len() >= 2 && path[0] == "features" => {
+            refs.push(DependencyNode::new(BundleArtifactKind::Feature, &path[1]));
+        }
+        Expression::ListReference { list_id } => {
+            refs.push(DependencyNode::new(BundleArtifactKind::List, list_id));
+        }
+        Expression::Binary { left, right, .. } => {
+            expression_references(left, refs);
+            expression_references(right, refs);
+        }
+        Expression::Unary { operand, .. } => expression_references(operand, refs),
+        Expression::FunctionCall { args, .. } => {
+            for arg in args {
+                expression_references(arg, refs);
+            }
+        }
+        Expression::Ternary {
+            condition,
+            true_expr,
+            false_expr,
+        } => {
+            expression_references(condition, refs);
+            expression_references(true_expr, refs);
+            expression_references(false_expr, refs);
+        }
+        Expression::LogicalGroup { conditions, .. } => {
+            for condition in conditions {
+                expression_references(condition, refs);
+            }
+        }
+        Expression::Quantifier {
+            collection,
+            predicate,
+            ..
+        } => {
+            expression_references(collection, refs);
+            expression_references(predicate, refs);
+        }
+        Expression::Aggregate { collection, .. } => expression_references(collection, refs),
+        Expression::Literal(_) | Expression::FieldAccess(_) | Expression::ResultAccess { .. } => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node(kind: BundleArtifactKind, id: &str) -> DependencyNode {
+        DependencyNode::new(kind, id)
+    }
+
+    fn graph(edges: &[(DependencyNode, DependencyNode)]) -> DependencyGraph {
+        let mut graph = DependencyGraph::default();
+        for (from, to) in edges {
+            graph.nodes.insert(from.clone());
+            graph.nodes.insert(to.clone());
+            graph.edges.insert(DependencyEdge {
+                from: from.clone(),
+                to: to.clone(),
+                relation: DependencyRelation::Includes,
+            });
+        }
+        graph
+    }
+
+    #[test]
+    fn test_topological_order() {
+        let pipeline = node(BundleArtifactKind::Pipeline, "p");
+        let ruleset = node(BundleArtifactKind::Ruleset, "rs");
+        let rule = node(BundleArtifactKind::Rule, "r");
+        let feature = node(BundleArtifactKind::Feature, "f");
+        let g = graph(&[
+            (pipeline.clone(), ruleset.clone()),
+            (ruleset.clone(), rule.clone()),
+            (rule.clone(), feature.clone()),
+            (pipeline.clone(), rule.clone()),
+        ]);
+
+        let order = g.topological_order().unwrap();
+        let pos = |n: &DependencyNode| order.iter().position(|o| *o == n).unwrap();
+        assert!(pos(&feature) < pos(&rule));
+        assert!(pos(&rule) < pos(&ruleset));
+        assert!(pos(&ruleset) < pos(&pipeline));
+
+        let a = node(BundleArtifactKind::Ruleset, "a");
+        let b = node(BundleArtifactKind::Ruleset, "b");
+        let cyclic = graph(&[(a.clone(), b.clone()), (b.clone(), a.clone())]);
+        assert!(cyclic.topological_order().is_none());
+        assert_eq!(cyclic.impact(&a), BTreeSet::from([&b]));
+    }
+
+    #[test]
+    fn test_expression_references() {
+        let expr = Expression::Binary {
+            left: Box::new(Expression::FieldAccess(vec![
+                "features".to_string(),
+                "txn_count_24h".to_string(),
+            ])),
+            op: corint_core::ast::Operator::Gt,
+            right: Box::new(Expression::FunctionCall {
+                name: "max".to_string(),
+                args: vec![
+                    Expression::ListReference {
+                        list_id: "vip_users".to_string(),
+                    },
+                    Expression::FieldAccess(vec!["event".to_string(), "amount".to_string()]),
+                ],
+            }),
+        };
+
+        let mut refs = Vec::new();
+        expression_references(&expr, &mut refs);
+        assert_eq!(
+            refs,
+            vec![
+                node(BundleArtifactKind::Feature, "txn_count_24h"),
+                node(BundleArtifactKind::List, "vip_users"),
+            ]
+        );
+    }
+}
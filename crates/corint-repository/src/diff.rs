@@ -0,0 +1,305 @@
+//! Structural diffing of artifact versions
+//!
+//! Compares two versions of a rule, ruleset, or pipeline by their YAML
+//! structure rather than their text, so formatting-only edits produce no
+//! changes and every reported change points at a concrete field path
+//! (e.g. `rule.score` or `ruleset.conclusion[0].signal`).
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+
+use crate::error::{RepositoryError, RepositoryResult};
+
+/// Kind of change applied to a single field
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChangeKind {
+    /// Field exists only in the newer version
+    Added,
+    /// Field exists only in the older version
+    Removed,
+    /// Field exists in both versions with different values
+    Modified,
+}
+
+/// What part of the decision logic a change affects
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChangeCategory {
+    /// Trigger conditions (`when` blocks)
+    Condition,
+    /// Rule scores
+    Score,
+    /// Ruleset conclusions (decision logic)
+    Conclusion,
+    /// Everything else (names, descriptions, metadata, steps, ...)
+    Other,
+}
+
+/// A single field-level change between two versions
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FieldChange {
+    /// Dotted path to the changed field (e.g. `rule.when.all[1]`)
+    pub path: String,
+    /// Kind of change
+    pub kind: ChangeKind,
+    /// Value in the older version
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub before: Option<JsonValue>,
+    /// Value in the newer version
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub after: Option<JsonValue>,
+}
+
+impl FieldChange {
+    /// Classify the change by the first decision-relevant segment of its path
+    pub fn category(&self) -> ChangeCategory {
+        for segment in self.path.split(['.', '[']) {
+            match segment {
+                "when" => return ChangeCategory::Condition,
+                "score" => return ChangeCategory::Score,
+                "conclusion" => return ChangeCategory::Conclusion,
+                _ => {}
+            }
+        }
+        ChangeCategory::Other
+    }
+}
+
+/// Structural diff between two versions of the same artifact
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ArtifactDiff {
+    /// Artifact identifier
+    pub entity_id: String,
+    /// Older version number
+    pub from_version: i32,
+    /// Newer version number
+    pub to_version: i32,
+    /// Field-level changes, in document order
+    pub changes: Vec<FieldChange>,
+}
+
+impl ArtifactDiff {
+    /// Compute the structural diff between two YAML documents
+    pub fn between(
+        entity_id: &str,
+        from_version: i32,
+        from_content: &str,
+        to_version: i32,
+        to_content: &str,
+    ) -> RepositoryResult<Self> {
+        let before = yaml_to_json(from_content)?;
+        let after = yaml_to_json(to_content)?;
+
+        let mut changes = Vec::new();
+        diff_values("", &before, &after, &mut changes);
+
+        Ok(Self {
+            entity_id: entity_id.to_string(),
+            from_version,
+            to_version,
+            changes,
+        })
+    }
+
+    /// Whether the two versions are structurally identical
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// Changes affecting the given category
+    pub fn changes_in(&self, category: ChangeCategory) -> impl Iterator<Item = &FieldChange> {
+        self.changes
+            .iter()
+            .filter(move |change| change.category() == category)
+    }
+}
+
+/// Parse YAML content into JSON, merging multi-document files (imports + definition)
+fn yaml_to_json(content: &str) -> RepositoryResult<JsonValue> {
+    let mut merged = serde_json::Map::new();
+
+    for document in serde_yaml::Deserializer::from_str(content) {
+        let value = serde_yaml::Value::deserialize(document)?;
+        let json = serde_json::to_value(value)
+            .map_err(|e| RepositoryError::ParseError(format!("Unsupported YAML value: {}", e)))?;
+
+        match json {
+            JsonValue::Object(map) => merged.extend(map),
+            JsonValue::Null => {}
+            other => return Ok(other),
+        }
+    }
+
+    Ok(JsonValue::Object(merged))
+}
+
+fn child_path(parent: &str, key: &str) -> String {
+    if parent.is_empty() {
+        key.to_string()
+    } else {
+        format!("{}.{}", parent, key)
+    }
+}
+
+fn diff_values(path: &str, before: &JsonValue, after: &JsonValue, changes: &mut Vec<FieldChange>) {
+    match (before, after) {
+        (JsonValue::Object(old), JsonValue::Object(new)) => {
+            for (key, old_value) in old {
+                let field_path = child_path(path, key);
+                match new.get(key) {
+                    Some(new_value) => diff_values(&field_path, old_value, new_value, changes),
+                    None => changes.push(FieldChange {
+                        path: field_path,
+                        kind: ChangeKind::Removed,
+                        before: Some(old_value.clone()),
+                        after: None,
+                    }),
+                }
+            }
+            for (key, new_value) in new {
+                if !old.contains_key(key) {
+                    changes.push(FieldChange {
+                        path: child_path(path, key),
+                        kind: ChangeKind::Added,
+                        before: None,
+                        after: Some(new_value.clone()),
+                    });
+                }
+            }
+        }
+        (JsonValue::Array(old), JsonValue::Array(new)) => {
+            for index in 0..old.len().max(new.len()) {
+                let item_path = format!("{}[{}]", path, index);
+                match (old.get(index), new.get(index)) {
+                    (Some(old_item), Some(new_item)) => {
+                        diff_values(&item_path, old_item, new_item, changes)
+                    }
+                    (Some(old_item), None) => changes.push(FieldChange {
+                        path: item_path,
+                        kind: ChangeKind::Removed,
+                        before: Some(old_item.clone()),
+                        after: None,
+                    }),
+                    (None, Some(new_item)) => changes.push(FieldChange {
+                        path: item_path,
+                        kind: ChangeKind::Added,
+                        before: None,
+                        after: Some(new_item.clone()),
+                    }),
+                    (None, None) => {}
+                }
+            }
+        }
+        _ if before != after => changes.push(FieldChange {
+            path: path.to_string(),
+            kind: ChangeKind::Modified,
+            before: Some(before.clone()),
+            after: Some(after.clone()),
+        }),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RULE_V1: &str = r#"
+rule:
+  id: high_amount
+  name: High Amount
+  when:
+    all:
+      - event.amount > 1000
+  score: 50
+"#;
+
+    #[test]
+    fn test_identical_versions_ignore_formatting() {
+        let reformatted = "rule: {id: high_amount, name: High Amount, when: {all: [event.amount > 1000]}, score: 50}";
+
+        let diff = ArtifactDiff::between("high_amount", 1, RULE_V1, 2, reformatted).unwrap();
+
+        assert!(diff.is_empty());
+    }
+
+    #[test]
+    fn test_rule_condition_and_score_changes() {
+        let v2 = r#"
+rule:
+  id: high_amount
+  name: High Amount
+  when:
+    all:
+      - event.amount > 5000
+      - event.country == "US"
+  score: 80
+"#;
+
+        let diff = ArtifactDiff::between("high_amount", 1, RULE_V1, 2, v2).unwrap();
+
+        let conditions: Vec<_> = diff.changes_in(ChangeCategory::Condition).collect();
+        assert_eq!(conditions.len(), 2);
+        assert_eq!(conditions[0].path, "rule.when.all[0]");
+        assert_eq!(conditions[0].kind, ChangeKind::Modified);
+        assert_eq!(conditions[1].path, "rule.when.all[1]");
+        assert_eq!(conditions[1].kind, ChangeKind::Added);
+
+        let scores: Vec<_> = diff.changes_in(ChangeCategory::Score).collect();
+        assert_eq!(scores.len(), 1);
+        assert_eq!(scores[0].before, Some(serde_json::json!(50)));
+        assert_eq!(scores[0].after, Some(serde_json::json!(80)));
+    }
+
+    #[test]
+    fn test_ruleset_conclusion_changes() {
+        let v1 = r#"
+ruleset:
+  id: fraud
+  rules: [high_amount]
+  conclusion:
+    - when: total_score >= 100
+      signal: decline
+    - default: true
+      signal: approve
+"#;
+        let v2 = r#"
+ruleset:
+  id: fraud
+  description: Tightened thresholds
+  rules: [high_amount]
+  conclusion:
+    - when: total_score >= 100
+      signal: review
+    - default: true
+      signal: approve
+"#;
+
+        let diff = ArtifactDiff::between("fraud", 3, v1, 4, v2).unwrap();
+
+        assert_eq!(diff.changes.len(), 2);
+        let conclusion: Vec<_> = diff.changes_in(ChangeCategory::Conclusion).collect();
+        assert_eq!(conclusion.len(), 1);
+        assert_eq!(conclusion[0].path, "ruleset.conclusion[0].signal");
+
+        let other: Vec<_> = diff.changes_in(ChangeCategory::Other).collect();
+        assert_eq!(other[0].path, "ruleset.description");
+        assert_eq!(other[0].kind, ChangeKind::Added);
+    }
+
+    #[test]
+    fn test_multi_document_content() {
+        let v2 = format!(
+            "version: \"0.1\"\nimports:\n  rules: [shared.yaml]\n---{}",
+            RULE_V1
+        );
+
+        let diff = ArtifactDiff::between("high_amount", 1, RULE_V1, 2, &v2).unwrap();
+
+        let mut paths: Vec<_> = diff.changes.iter().map(|c| c.path.as_str()).collect();
+        paths.sort();
+        assert_eq!(paths, vec!["imports", "version"]);
+        assert!(diff.changes.iter().all(|c| c.kind == ChangeKind::Added));
+    }
+}
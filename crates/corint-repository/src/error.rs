@@ -45,6 +45,14 @@ pub enum RepositoryError {
     #[error("Artifact ID not found: {id}")]
     IdNotFound { id: String },
 
+    /// Actor is not allowed to perform an operation
+    #[error("Permission denied: '{actor}' may not {action} {artifact}")]
+    PermissionDenied {
+        actor: String,
+        action: String,
+        artifact: String,
+    },
+
     /// Database error (when database feature is enabled)
     #[cfg(feature = "postgres")]
     #[error("Database error: {0}")]
@@ -54,6 +62,33 @@ pub enum RepositoryError {
         sqlx::Error,
     ),
 
+    /// Schema migration error (when database feature is enabled)
+    #[cfg(feature = "postgres")]
+    #[error("Migration error: {0}")]
+    Migration(
+        #[from]
+        #[source]
+        sqlx::migrate::MigrateError,
+    ),
+
+    /// Redis error (when redis feature is enabled)
+    #[cfg(feature = "redis")]
+    #[error("Redis error: {0}")]
+    Redis(
+        #[from]
+        #[source]
+        redis::RedisError,
+    ),
+
+    /// MongoDB error (when mongodb feature is enabled)
+    #[cfg(feature = "mongodb")]
+    #[error("MongoDB error: {0}")]
+    Mongo(
+        #[from]
+        #[source]
+        mongodb::error::Error,
+    ),
+
     /// Cache error
     #[error("Cache error: {0}")]
     Cache(String),
@@ -70,6 +105,22 @@ pub enum RepositoryError {
     #[error("Configuration error: {0}")]
     Config(String),
 
+    /// Loaded content does not match the approved integrity manifest
+    #[error("Integrity check failed for {artifact}: {reason}")]
+    IntegrityViolation { artifact: String, reason: String },
+
+    /// Requested lifecycle status change is not allowed
+    #[error("Cannot change status of {artifact}: {reason}")]
+    InvalidStatusTransition { artifact: String, reason: String },
+
+    /// Bundle failed integrity or signature verification
+    #[error("Invalid bundle: {0}")]
+    InvalidBundle(String),
+
+    /// Environment variable or secret placeholder could not be resolved
+    #[error("Cannot resolve {placeholder}: {reason}")]
+    Interpolation { placeholder: String, reason: String },
+
     /// Generic error
     #[error("Repository error: {0}")]
     Other(String),
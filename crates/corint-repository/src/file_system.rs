@@ -4,17 +4,25 @@ use async_trait::async_trait;
 use corint_core::ast::{Pipeline, Rule, Ruleset};
 use corint_parser::{PipelineParser, RuleParser, RulesetParser};
 use path_absolutize::Absolutize;
+use serde::Deserialize;
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::{Arc, Mutex};
 use tokio::fs;
 use tokio::sync::RwLock;
 
-use crate::{error::RepositoryError, models::*, traits::*, CacheStats, RepositoryResult};
+use crate::{
+    bundle, cache::LoadCoordinator, error::RepositoryError, models::*, traits::*, yaml_writer,
+    BundleArtifact, BundleArtifactKind, CacheStats, RepositoryBundle, RepositoryResult,
+};
 
 /// File system based repository
 ///
-/// Loads artifacts from YAML files on disk with optional caching.
+/// Loads artifacts from YAML files on disk with optional caching. Saving an
+/// artifact rewrites the file that defines its ID (or creates
+/// `<type dir>/<id>.yaml`) with canonical YAML, keeping any leading
+/// `version`/`imports` document of the existing file.
 pub struct FileSystemRepository {
     /// Root path of the repository
     root_path: PathBuf,
@@ -28,6 +36,8 @@ pub struct FileSystemRepository {
     cache_config: Arc<Mutex<CacheConfig>>,
     /// Cache statistics
     stats: Arc<Mutex<CacheStats>>,
+    /// Negative cache and single-flight gate for cache misses
+    loads: Arc<LoadCoordinator>,
 }
 
 impl FileSystemRepository {
@@ -65,6 +75,7 @@ impl FileSystemRepository {
             pipeline_cache: Arc::new(RwLock::new(HashMap::new())),
             cache_config: Arc::new(Mutex::new(CacheConfig::default())),
             stats: Arc::new(Mutex::new(CacheStats::default())),
+            loads: Arc::new(LoadCoordinator::default()),
         })
     }
 
@@ -165,6 +176,7 @@ impl FileSystemRepository {
         if let Some(cached) = cache_read.get(identifier) {
             if !cached.is_expired() {
                 // Cache hit
+                cached.touch();
                 self.stats.lock().unwrap().hits += 1;
                 return Some((cached.data.clone(), cached.content.clone()));
             }
@@ -174,31 +186,6 @@ impl FileSystemRepository {
         self.stats.lock().unwrap().misses += 1;
         None
     }
-
-    /// Store in cache
-    async fn store_in_cache<T: Clone>(
-        &self,
-        cache: &Arc<RwLock<HashMap<String, CachedArtifact<T>>>>,
-        identifier: &str,
-        data: T,
-        content: String,
-    ) {
-        let (enabled, ttl) = {
-            let config = self.cache_config.lock().unwrap();
-            (config.enabled, config.default_ttl)
-        };
-
-        if !enabled {
-            return;
-        }
-
-        let cached = CachedArtifact::new(data, content, ttl);
-        let mut cache_write = cache.write().await;
-        cache_write.insert(identifier.to_string(), cached);
-
-        // Update stats
-        self.stats.lock().unwrap().size = cache_write.len();
-    }
 }
 
 #[async_trait]
@@ -209,35 +196,37 @@ impl Repository for FileSystemRepository {
             return Ok(cached);
         }
 
-        // Resolve path
-        let path = self.resolve_path(identifier);
-
-        // If path doesn't exist and identifier doesn't end with .yaml, try finding by ID
-        let path = if !path.exists() && !identifier.ends_with(".yaml") {
-            self.find_by_id(identifier, "rule").await?
-        } else {
-            path
-        };
-
-        // Load and parse
-        let content = fs::read_to_string(&path)
+        self.loads
+            .load(
+                &self.rule_cache,
+                &self.cache_config,
+                &self.stats,
+                "rule",
+                identifier,
+                || async {
+                    // Resolve path
+                    let path = self.resolve_path(identifier);
+
+                    // If path doesn't exist and identifier doesn't end with .yaml, try finding by ID
+                    let path = if !path.exists() && !identifier.ends_with(".yaml") {
+                        self.find_by_id(identifier, "rule").await?
+                    } else {
+                        path
+                    };
+
+                    // Load and parse
+                    let content = fs::read_to_string(&path)
+                        .await
+                        .map_err(|_| RepositoryError::NotFound {
+                            path: path.display().to_string(),
+                        })?;
+
+                    let doc = RuleParser::parse_with_imports(&content)?;
+
+                    Ok((doc.definition, content))
+                },
+            )
             .await
-            .map_err(|_| RepositoryError::NotFound {
-                path: path.display().to_string(),
-            })?;
-
-        let doc = RuleParser::parse_with_imports(&content)?;
-
-        // Store in cache
-        self.store_in_cache(
-            &self.rule_cache,
-            identifier,
-            doc.definition.clone(),
-            content.clone(),
-        )
-        .await;
-
-        Ok((doc.definition, content))
     }
 
     async fn load_ruleset(&self, identifier: &str) -> RepositoryResult<(Ruleset, String)> {
@@ -246,35 +235,37 @@ impl Repository for FileSystemRepository {
             return Ok(cached);
         }
 
-        // Resolve path
-        let path = self.resolve_path(identifier);
-
-        // If path doesn't exist and identifier doesn't end with .yaml, try finding by ID
-        let path = if !path.exists() && !identifier.ends_with(".yaml") {
-            self.find_by_id(identifier, "ruleset").await?
-        } else {
-            path
-        };
-
-        // Load and parse
-        let content = fs::read_to_string(&path)
+        self.loads
+            .load(
+                &self.ruleset_cache,
+                &self.cache_config,
+                &self.stats,
+                "ruleset",
+                identifier,
+                || async {
+                    // Resolve path
+                    let path = self.resolve_path(identifier);
+
+                    // If path doesn't exist and identifier doesn't end with .yaml, try finding by ID
+                    let path = if !path.exists() && !identifier.ends_with(".yaml") {
+                        self.find_by_id(identifier, "ruleset").await?
+                    } else {
+                        path
+                    };
+
+                    // Load and parse
+                    let content = fs::read_to_string(&path)
+                        .await
+                        .map_err(|_| RepositoryError::NotFound {
+                            path: path.display().to_string(),
+                        })?;
+
+                    let doc = RulesetParser::parse_with_imports(&content)?;
+
+                    Ok((doc.definition, content))
+                },
+            )
             .await
-            .map_err(|_| RepositoryError::NotFound {
-                path: path.display().to_string(),
-            })?;
-
-        let doc = RulesetParser::parse_with_imports(&content)?;
-
-        // Store in cache
-        self.store_in_cache(
-            &self.ruleset_cache,
-            identifier,
-            doc.definition.clone(),
-            content.clone(),
-        )
-        .await;
-
-        Ok((doc.definition, content))
     }
 
     async fn load_pipeline(&self, identifier: &str) -> RepositoryResult<(Pipeline, String)> {
@@ -283,35 +274,37 @@ impl Repository for FileSystemRepository {
             return Ok(cached);
         }
 
-        // Resolve path
-        let path = self.resolve_path(identifier);
-
-        // If path doesn't exist and identifier doesn't end with .yaml, try finding by ID
-        let path = if !path.exists() && !identifier.ends_with(".yaml") {
-            self.find_by_id(identifier, "pipeline").await?
-        } else {
-            path
-        };
-
-        // Load and parse
-        let content = fs::read_to_string(&path)
+        self.loads
+            .load(
+                &self.pipeline_cache,
+                &self.cache_config,
+                &self.stats,
+                "pipeline",
+                identifier,
+                || async {
+                    // Resolve path
+                    let path = self.resolve_path(identifier);
+
+                    // If path doesn't exist and identifier doesn't end with .yaml, try finding by ID
+                    let path = if !path.exists() && !identifier.ends_with(".yaml") {
+                        self.find_by_id(identifier, "pipeline").await?
+                    } else {
+                        path
+                    };
+
+                    // Load and parse
+                    let content = fs::read_to_string(&path)
+                        .await
+                        .map_err(|_| RepositoryError::NotFound {
+                            path: path.display().to_string(),
+                        })?;
+
+                    let doc = PipelineParser::parse_with_imports(&content)?;
+
+                    Ok((doc.definition, content))
+                },
+            )
             .await
-            .map_err(|_| RepositoryError::NotFound {
-                path: path.display().to_string(),
-            })?;
-
-        let doc = PipelineParser::parse_with_imports(&content)?;
-
-        // Store in cache
-        self.store_in_cache(
-            &self.pipeline_cache,
-            identifier,
-            doc.definition.clone(),
-            content.clone(),
-        )
-        .await;
-
-        Ok((doc.definition, content))
     }
 
     async fn exists(&self, identifier: &str) -> RepositoryResult<bool> {
@@ -344,6 +337,27 @@ impl Repository for FileSystemRepository {
 
         Ok(content)
     }
+
+    async fn export_bundle(&self) -> RepositoryResult<RepositoryBundle> {
+        let mut bundle = bundle::export_definitions(self).await?;
+
+        // Config files are exported verbatim, keyed by their path under the config directory
+        for &kind in BundleArtifactKind::config_kinds() {
+            let dir = format!("configs/{}", kind.config_dir().unwrap_or_default());
+            for relative in self.list_yaml_files(&dir).await? {
+                let path = self.root_path.join(&relative);
+                let id = path
+                    .strip_prefix(self.root_path.join(&dir))
+                    .unwrap_or(&path)
+                    .with_extension("")
+                    .display()
+                    .to_string();
+                bundle.add_artifact(kind, id, fs::read_to_string(&path).await?);
+            }
+        }
+
+        Ok(bundle)
+    }
 }
 
 impl FileSystemRepository {
@@ -396,6 +410,315 @@ impl FileSystemRepository {
     }
 }
 
+impl FileSystemRepository {
+    /// Directory that holds artifacts of the given type
+    fn artifact_dir(artifact_type: &str) -> &'static str {
+        match artifact_type {
+            "rule" => "library/rules",
+            "ruleset" => "library/rulesets",
+            _ => "pipelines",
+        }
+    }
+
+    /// Find the file that defines `id`, comparing parsed IDs rather than text
+    async fn find_definition_file(
+        &self,
+        id: &str,
+        artifact_type: &str,
+    ) -> RepositoryResult<Option<PathBuf>> {
+        let dir_path = self.root_path.join(Self::artifact_dir(artifact_type));
+        if !dir_path.exists() {
+            return Ok(None);
+        }
+
+        let mut files = Vec::new();
+        self.collect_yaml_files(&dir_path, &self.root_path, &mut files)
+            .await?;
+
+        for relative in files {
+            let path = self.root_path.join(relative);
+            let content = fs::read_to_string(&path).await?;
+            if defined_id(&content, artifact_type).as_deref() == Some(id) {
+                return Ok(Some(path));
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Path of a file given relative to the repository root, rejecting any
+    /// that is absolute or steps out of the root with `..`
+    fn contained_path(&self, relative: &str) -> RepositoryResult<PathBuf> {
+        let relative = Path::new(relative);
+        let contained = relative
+            .components()
+            .all(|c| matches!(c, std::path::Component::Normal(_)));
+        let path = self.root_path.join(relative);
+        if !contained || !path.starts_with(&self.root_path) {
+            return Err(RepositoryError::InvalidPath {
+                path: relative.to_path_buf(),
+            });
+        }
+        Ok(path)
+    }
+
+    /// File that defines `id`, or `<type dir>/<id>.yaml` for a new artifact
+    async fn definition_path(&self, id: &str, artifact_type: &str) -> RepositoryResult<PathBuf> {
+        if id.is_empty() || id.starts_with('.') || id.contains(['/', '\\']) {
+            return Err(RepositoryError::Other(format!(
+                "Invalid {} id for file system storage: '{}'",
+                artifact_type, id
+            )));
+        }
+
+        Ok(match self.find_definition_file(id, artifact_type).await? {
+            Some(path) => path,
+            None => self
+                .root_path
+                .join(Self::artifact_dir(artifact_type))
+                .join(format!("{}.yaml", id)),
+        })
+    }
+
+    /// Write a serialized artifact to the file that defines it (or a new file)
+    async fn write_artifact(
+        &self,
+        id: &str,
+        artifact_type: &str,
+        body: String,
+    ) -> RepositoryResult<PathBuf> {
+        let path = self.definition_path(id, artifact_type).await?;
+
+        let content = if path.exists() {
+            let existing = fs::read_to_string(&path).await?;
+            replace_definition(&existing, artifact_type, &body)?
+        } else {
+            body
+        };
+
+        write_atomically(&path, content.as_bytes()).await?;
+
+        Ok(path)
+    }
+
+    /// Write one bundle artifact verbatim to where this repository keeps it
+    async fn write_bundle_artifact(&self, artifact: &BundleArtifact) -> RepositoryResult<()> {
+        let path = match artifact.kind {
+            BundleArtifactKind::Registry => self.root_path.join("registry.yaml"),
+            BundleArtifactKind::Rule | BundleArtifactKind::Ruleset | BundleArtifactKind::Pipeline => {
+                self.definition_path(&artifact.id, artifact.kind.as_str())
+                    .await?
+            }
+            kind => {
+                let relative = Path::new(&artifact.id);
+                if artifact.id.is_empty()
+                    || !relative
+                        .components()
+                        .all(|c| matches!(c, std::path::Component::Normal(_)))
+                {
+                    return Err(RepositoryError::Other(format!(
+                        "Invalid {} id for file system storage: '{}'",
+                        kind.as_str(),
+                        artifact.id
+                    )));
+                }
+                self.root_path
+                    .join("configs")
+                    .join(kind.config_dir().unwrap_or_default())
+                    .join(format!("{}.yaml", artifact.id))
+            }
+        };
+
+        write_atomically(&path, artifact.content.as_bytes()).await?;
+
+        Ok(())
+    }
+
+    /// Delete the file holding an artifact, returning the artifact's ID
+    async fn delete_artifact(&self, identifier: &str, artifact_type: &str) -> RepositoryResult<String> {
+        let path = if identifier.ends_with(".yaml") || identifier.ends_with(".yml") {
+            self.contained_path(identifier)?
+        } else {
+            self.find_definition_file(identifier, artifact_type)
+                .await?
+                .ok_or_else(|| RepositoryError::IdNotFound {
+                    id: identifier.to_string(),
+                })?
+        };
+
+        let content = fs::read_to_string(&path)
+            .await
+            .map_err(|_| RepositoryError::NotFound {
+                path: path.display().to_string(),
+            })?;
+        // Never delete a file that does not hold an artifact of this type
+        let id = defined_id(&content, artifact_type).ok_or_else(|| {
+            RepositoryError::Other(format!(
+                "'{}' does not define a {}",
+                identifier, artifact_type
+            ))
+        })?;
+
+        fs::remove_file(&path).await?;
+
+        Ok(id)
+    }
+
+    /// Drop cache entries keyed by `identifier` or holding the artifact `id`
+    async fn evict<T>(
+        cache: &Arc<RwLock<HashMap<String, CachedArtifact<T>>>>,
+        identifier: &str,
+        id: &str,
+        id_of: impl Fn(&T) -> &str,
+    ) {
+        cache
+            .write()
+            .await
+            .retain(|key, cached| key != identifier && id_of(&cached.data) != id);
+    }
+}
+
+/// Replace the content of `path` in one step, so that a failed write never
+/// leaves a truncated file behind for loaders to read
+async fn write_atomically(path: &Path, content: &[u8]) -> RepositoryResult<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).await?;
+    }
+
+    let file_name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    // Unique per write, so concurrent saves of one artifact never share a temp file
+    static WRITES: AtomicU64 = AtomicU64::new(0);
+    let temp_path = path.with_file_name(format!(
+        ".{}.{}.{}.tmp",
+        file_name,
+        std::process::id(),
+        WRITES.fetch_add(1, Ordering::Relaxed)
+    ));
+
+    if let Err(e) = fs::write(&temp_path, content).await {
+        let _ = fs::remove_file(&temp_path).await;
+        return Err(e.into());
+    }
+    if let Err(e) = fs::rename(&temp_path, path).await {
+        let _ = fs::remove_file(&temp_path).await;
+        return Err(e.into());
+    }
+
+    Ok(())
+}
+
+/// ID of the `artifact_type` definition in (possibly multi-document) YAML content
+fn defined_id(content: &str, artifact_type: &str) -> Option<String> {
+    serde_yaml::Deserializer::from_str(content)
+        .filter_map(|document| serde_yaml::Value::deserialize(document).ok())
+        .find_map(|value| {
+            value
+                .get(artifact_type)?
+                .get("id")?
+                .as_str()
+                .map(str::to_string)
+        })
+}
+
+/// Swap the definition in existing content for a new one, keeping the rest
+/// of the file (`version`, `imports`, extra documents) as it was
+fn replace_definition(existing: &str, artifact_type: &str, body: &str) -> RepositoryResult<String> {
+    let replacement = serde_yaml::from_str::<serde_yaml::Value>(body)?
+        .get(artifact_type)
+        .cloned()
+        .unwrap_or_default();
+
+    let mut documents = Vec::new();
+    let mut replaced = false;
+    for document in serde_yaml::Deserializer::from_str(existing) {
+        let mut value = serde_yaml::Value::deserialize(document)?;
+        if value.is_null() {
+            continue;
+        }
+        if let Some(definition) = value.get_mut(artifact_type) {
+            *definition = replacement.clone();
+            replaced = true;
+        }
+        documents.push(serde_yaml::to_string(&value)?);
+    }
+    if !replaced {
+        documents.push(body.to_string());
+    }
+
+    Ok(documents.join("---\n"))
+}
+
+#[async_trait]
+impl WritableRepository for FileSystemRepository {
+    async fn save_rule(&mut self, rule: &Rule) -> RepositoryResult<()> {
+        let content = yaml_writer::rule_to_yaml(rule)?;
+        self.write_artifact(&rule.id, "rule", content).await?;
+        self.loads.clear();
+        Self::evict(&self.rule_cache, &rule.id, &rule.id, |r: &Rule| &r.id).await;
+        Ok(())
+    }
+
+    async fn save_ruleset(&mut self, ruleset: &Ruleset) -> RepositoryResult<()> {
+        let content = yaml_writer::ruleset_to_yaml(ruleset)?;
+        self.write_artifact(&ruleset.id, "ruleset", content).await?;
+        self.loads.clear();
+        Self::evict(&self.ruleset_cache, &ruleset.id, &ruleset.id, |r: &Ruleset| &r.id).await;
+        Ok(())
+    }
+
+    async fn save_pipeline(&mut self, pipeline: &Pipeline) -> RepositoryResult<()> {
+        let content = yaml_writer::pipeline_to_yaml(pipeline)?;
+        self.write_artifact(&pipeline.id, "pipeline", content).await?;
+        self.loads.clear();
+        Self::evict(&self.pipeline_cache, &pipeline.id, &pipeline.id, |p: &Pipeline| &p.id).await;
+        Ok(())
+    }
+
+    async fn delete_rule(&self, identifier: &str) -> RepositoryResult<()> {
+        let id = self.delete_artifact(identifier, "rule").await?;
+        Self::evict(&self.rule_cache, identifier, &id, |r: &Rule| &r.id).await;
+        Ok(())
+    }
+
+    async fn delete_ruleset(&self, identifier: &str) -> RepositoryResult<()> {
+        let id = self.delete_artifact(identifier, "ruleset").await?;
+        Self::evict(&self.ruleset_cache, identifier, &id, |r: &Ruleset| &r.id).await;
+        Ok(())
+    }
+
+    async fn delete_pipeline(&self, identifier: &str) -> RepositoryResult<()> {
+        let id = self.delete_artifact(identifier, "pipeline").await?;
+        Self::evict(&self.pipeline_cache, identifier, &id, |p: &Pipeline| &p.id).await;
+        Ok(())
+    }
+
+    /// Files are written verbatim, and only after the whole bundle has been
+    /// verified and parsed.
+    async fn import_bundle(
+        &mut self,
+        bundle: &RepositoryBundle,
+        signing_key: Option<&[u8]>,
+    ) -> RepositoryResult<usize> {
+        bundle.verify(signing_key)?;
+        bundle.parse_definitions()?;
+
+        for artifact in &bundle.artifacts {
+            self.write_bundle_artifact(artifact).await?;
+        }
+
+        self.rule_cache.write().await.clear();
+        self.ruleset_cache.write().await.clear();
+        self.pipeline_cache.write().await.clear();
+        self.loads.clear();
+        self.stats.lock().unwrap().size = 0;
+
+        Ok(bundle.artifacts.len())
+    }
+}
+
 #[async_trait]
 impl CacheableRepository for FileSystemRepository {
     fn clear_cache(&mut self) {
@@ -413,6 +736,7 @@ impl CacheableRepository for FileSystemRepository {
             pipeline_cache.write().await.clear();
         });
 
+        self.loads.clear();
         self.stats.lock().unwrap().size = 0;
     }
 
@@ -420,6 +744,7 @@ impl CacheableRepository for FileSystemRepository {
         let rule_cache = self.rule_cache.clone();
         let ruleset_cache = self.ruleset_cache.clone();
         let pipeline_cache = self.pipeline_cache.clone();
+        self.loads.forget(identifier);
         let id = identifier.to_string();
 
         tokio::spawn(async move {
@@ -440,4 +765,8 @@ impl CacheableRepository for FileSystemRepository {
     fn is_cache_enabled(&self) -> bool {
         self.cache_config.lock().unwrap().enabled
     }
+
+    fn set_cache_config(&mut self, config: CacheConfig) {
+        *self.cache_config.lock().unwrap() = config;
+    }
 }
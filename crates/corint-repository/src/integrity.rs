@@ -0,0 +1,233 @@
+//! Content integrity verification
+//!
+//! An [`IntegrityManifest`] records the SHA-256 checksum of every approved
+//! registry, pipeline, rule and ruleset, optionally with an Ed25519 signature
+//! per artifact. The loader checks loaded content against the manifest before
+//! anything is compiled, so production only runs rules that match the
+//! approved artifacts.
+//!
+//! Signatures cover `"<kind>:<id>:<sha256>"`, binding each checksum to the
+//! artifact it was approved for.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use corint_repository::{IntegrityManifest, RepositoryConfig, RepositoryLoader};
+//! use ed25519_dalek::SigningKey;
+//!
+//! # #[tokio::main]
+//! # async fn main() -> anyhow::Result<()> {
+//! let content = RepositoryLoader::new(RepositoryConfig::file_system("repository"))
+//!     .load_all()
+//!     .await?;
+//!
+//! let signing_key = SigningKey::from_bytes(&[7u8; 32]);
+//! let mut manifest = IntegrityManifest::from_content(&content);
+//! manifest.sign(&signing_key);
+//! std::fs::write("repository/integrity.json", manifest.to_json()?)?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::bundle::{from_hex, sha256_hex, to_hex, BundleArtifactKind};
+use crate::content::RepositoryContent;
+use crate::error::{RepositoryError, RepositoryResult};
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use serde::{Deserialize, Serialize};
+
+/// Checksum (and optional signature) of one approved artifact
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ManifestEntry {
+    /// Artifact kind
+    pub kind: BundleArtifactKind,
+
+    /// Artifact ID (`registry` for the registry)
+    pub id: String,
+
+    /// Hex-encoded SHA-256 of the raw YAML
+    pub sha256: String,
+
+    /// Hex-encoded Ed25519 signature
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<String>,
+}
+
+impl ManifestEntry {
+    /// Message covered by the signature
+    fn signing_message(&self) -> String {
+        format!("{}:{}:{}", self.kind.as_str(), self.id, self.sha256)
+    }
+}
+
+/// Approved checksums and signatures for repository content
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IntegrityManifest {
+    /// One entry per approved artifact
+    pub artifacts: Vec<ManifestEntry>,
+}
+
+impl IntegrityManifest {
+    /// Create an empty manifest
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record the checksums of all definitions in `content`
+    pub fn from_content(content: &RepositoryContent) -> Self {
+        let artifacts = content
+            .definitions()
+            .map(|(kind, id, yaml)| ManifestEntry {
+                kind,
+                id: id.to_string(),
+                sha256: sha256_hex(yaml.as_bytes()),
+                signature: None,
+            })
+            .collect();
+
+        Self { artifacts }
+    }
+
+    /// Sign every entry with an Ed25519 key
+    pub fn sign(&mut self, key: &SigningKey) {
+        for entry in &mut self.artifacts {
+            let signature = key.sign(entry.signing_message().as_bytes());
+            entry.signature = Some(to_hex(&signature.to_bytes()));
+        }
+    }
+
+    /// Find the entry for an artifact
+    pub fn entry(&self, kind: BundleArtifactKind, id: &str) -> Option<&ManifestEntry> {
+        self.artifacts
+            .iter()
+            .find(|entry| entry.kind == kind && entry.id == id)
+    }
+
+    /// Verify that every definition in `content` matches this manifest
+    ///
+    /// Artifacts missing from the manifest or with a different checksum are
+    /// rejected. Signatures are checked against `public_keys` (any key may
+    /// match) and are mandatory when `require_signatures` is set.
+    pub fn verify_content(
+        &self,
+        content: &RepositoryContent,
+        public_keys: &[VerifyingKey],
+        require_signatures: bool,
+    ) -> RepositoryResult<()> {
+        for (kind, id, yaml) in content.definitions() {
+            let artifact = format!("{} '{}'", kind.as_str(), id);
+            let violation = |reason: &str| RepositoryError::IntegrityViolation {
+                artifact: artifact.clone(),
+                reason: reason.to_string(),
+            };
+
+            let entry = self
+                .entry(kind, id)
+                .ok_or_else(|| violation("not listed in the integrity manifest"))?;
+
+            if sha256_hex(yaml.as_bytes()) != entry.sha256 {
+                return Err(violation("checksum mismatch"));
+            }
+
+            match &entry.signature {
+                Some(signature) if !public_keys.is_empty() => {
+                    let signature = from_hex(signature)
+                        .and_then(|bytes| Signature::from_slice(&bytes).ok())
+                        .ok_or_else(|| violation("malformed signature"))?;
+                    let message = entry.signing_message();
+                    if !public_keys
+                        .iter()
+                        .any(|key| key.verify(message.as_bytes(), &signature).is_ok())
+                    {
+                        return Err(violation("invalid signature"));
+                    }
+                }
+                _ if require_signatures => return Err(violation("missing signature")),
+                _ => {}
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Serialize the manifest to pretty-printed JSON
+    pub fn to_json(&self) -> RepositoryResult<String> {
+        serde_json::to_string_pretty(self)
+            .map_err(|e| RepositoryError::Other(format!("Failed to serialize manifest: {}", e)))
+    }
+
+    /// Deserialize a manifest from JSON
+    pub fn from_json(json: &str) -> RepositoryResult<Self> {
+        serde_json::from_str(json)
+            .map_err(|e| RepositoryError::Config(format!("Invalid integrity manifest: {}", e)))
+    }
+}
+
+/// Parse a hex-encoded Ed25519 public key
+pub fn parse_public_key(hex: &str) -> RepositoryResult<VerifyingKey> {
+    from_hex(hex.trim())
+        .and_then(|bytes| <[u8; 32]>::try_from(bytes).ok())
+        .and_then(|bytes| VerifyingKey::from_bytes(&bytes).ok())
+        .ok_or_else(|| RepositoryError::Config(format!("Invalid Ed25519 public key: {}", hex)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn content() -> RepositoryContent {
+        let mut content = RepositoryContent::new();
+        content.registry = Some("registry: []\n".to_string());
+        content.add_rule("high_amount", "rule:\n  id: high_amount\n");
+        content.add_pipeline("payment", "pipeline:\n  id: payment\n");
+        content
+    }
+
+    #[test]
+    fn test_checksum_verification() {
+        let manifest = IntegrityManifest::from_content(&content());
+        assert_eq!(manifest.artifacts.len(), 3);
+        assert!(manifest.verify_content(&content(), &[], false).is_ok());
+
+        let mut tampered = content();
+        tampered.rules[0].1.push_str("  score: 0\n");
+        let err = manifest.verify_content(&tampered, &[], false).unwrap_err();
+        assert!(err.to_string().contains("checksum mismatch"));
+
+        let mut extra = content();
+        extra.add_ruleset("unapproved", "ruleset:\n  id: unapproved\n");
+        assert!(manifest.verify_content(&extra, &[], false).is_err());
+
+        // Signatures are mandatory when required
+        assert!(manifest.verify_content(&content(), &[], true).is_err());
+    }
+
+    #[test]
+    fn test_signature_verification() {
+        let key = SigningKey::from_bytes(&[7u8; 32]);
+        let public_key = parse_public_key(&to_hex(key.verifying_key().as_bytes())).unwrap();
+
+        let mut manifest = IntegrityManifest::from_content(&content());
+        manifest.sign(&key);
+        assert!(manifest
+            .verify_content(&content(), &[public_key], true)
+            .is_ok());
+
+        let roundtrip = IntegrityManifest::from_json(&manifest.to_json().unwrap()).unwrap();
+        assert_eq!(roundtrip, manifest);
+
+        // A checksum re-computed for tampered content no longer matches the signature
+        let mut forged = manifest.clone();
+        forged.artifacts[0].sha256 = sha256_hex(b"forged");
+        let mut tampered = content();
+        tampered.registry = Some("forged".to_string());
+        let err = forged
+            .verify_content(&tampered, &[public_key], true)
+            .unwrap_err();
+        assert!(err.to_string().contains("invalid signature"));
+
+        let other_key = SigningKey::from_bytes(&[9u8; 32]).verifying_key();
+        assert!(manifest
+            .verify_content(&content(), &[other_key], true)
+            .is_err());
+    }
+}
@@ -7,6 +7,8 @@
 //!
 //! - **File System Repository**: Load from YAML files on disk
 //! - **PostgreSQL Repository**: Database-backed storage with versioning (Phase 4)
+//! - **Redis Repository**: Low-latency distribution with pub/sub cache invalidation
+//! - **MongoDB Repository**: Document storage with the same versioning and audit as PostgreSQL
 //! - **Caching**: Built-in TTL-based caching for performance
 //! - **Async API**: Non-blocking I/O operations with Tokio
 //! - **Inheritance**: Support for ruleset inheritance chains
@@ -158,22 +160,71 @@
 //! - [examples/database_repository.rs](https://github.com/corint/corint-decision/blob/main/examples/database_repository.rs) - PostgreSQL example
 //! - [QUICK_START_PHASE3.md](https://github.com/corint/corint-decision/blob/main/QUICK_START_PHASE3.md) - Phase 3 features guide
 
+pub mod access;
+pub mod bundle;
+mod cache;
+pub mod composite;
 pub mod config;
 pub mod content;
+pub mod dependency;
+pub mod diff;
 pub mod error;
 pub mod file_system;
+pub mod integrity;
 pub mod loader;
 pub mod models;
+pub mod preload;
+pub mod secrets;
+pub mod sync;
 pub mod traits;
+pub mod yaml_writer;
+
+#[cfg(feature = "postgres")]
+pub mod migrations;
 
 #[cfg(feature = "postgres")]
 pub mod postgres;
 
+#[cfg(feature = "redis")]
+pub mod redis;
+
+#[cfg(feature = "mongodb")]
+pub mod mongodb;
+
 #[cfg(feature = "api")]
 pub mod api;
 
+// Re-exports - Access control and audit
+pub use access::{
+    AccessPolicy, Actor, AuditEntry, AuditLog, AuditOperation, AuditQuery, AuthorizedRepository,
+    Grant, InMemoryAuditLog, Permission,
+};
+
+// Re-exports - Bundles
+pub use bundle::{
+    BundleArtifact, BundleArtifactKind, BundleDefinitions, RepositoryBundle,
+    BUNDLE_FORMAT_VERSION,
+};
+
+// Re-exports - Layered composition
+pub use composite::{CompositeRepository, LayerStats};
+
+// Re-exports - Cache warm-up
+pub use preload::{PreloadFailure, PreloadManifest};
+
+// Re-exports - Dependency graph
+pub use dependency::{
+    DependencyEdge, DependencyGraph, DependencyNode, DependencyRelation, UnresolvedDependency,
+};
+
+// Re-exports - Drift detection
+pub use sync::{
+    spawn_sync, ArtifactDrift, DriftKind, SyncHandle, SyncReport, SyncStats, SyncableRepository,
+};
+
 // Re-exports - Configuration
-pub use config::{ConfigError, RepositoryConfig, RepositorySource};
+pub use config::{ConfigError, IntegrityConfig, RepositoryConfig, RepositorySource};
+pub use integrity::{IntegrityManifest, ManifestEntry};
 
 // Re-exports - Content
 pub use content::{
@@ -181,9 +232,18 @@ pub use content::{
     ListConfig, PoolConfig, RepositoryContent, TimeWindow,
 };
 
+// Re-exports - Diff
+pub use diff::{ArtifactDiff, ChangeCategory, ChangeKind, FieldChange};
+
+// Re-exports - YAML writer
+pub use yaml_writer::{expression_to_dsl, pipeline_to_yaml, rule_to_yaml, ruleset_to_yaml};
+
 // Re-exports - Loader
 pub use loader::RepositoryLoader;
 
+// Re-exports - Placeholder interpolation
+pub use secrets::{Interpolator, SecretResolver, StaticSecretResolver};
+
 // Re-exports - Error
 pub use error::{RepositoryError, RepositoryResult};
 
@@ -193,7 +253,19 @@ pub use models::*;
 pub use traits::*;
 
 #[cfg(feature = "postgres")]
-pub use postgres::PostgresRepository;
+pub use migrations::{MigrationInfo, MigrationReport};
+
+#[cfg(feature = "postgres")]
+pub use postgres::{PostgresAuditLog, PostgresRepository};
+
+#[cfg(feature = "redis")]
+pub use self::redis::RedisRepository;
+
+#[cfg(feature = "mongodb")]
+pub use self::mongodb::MongoRepository;
+
+#[cfg(feature = "api")]
+pub use api::{ApiRepository, InvalidationNotice, WEBHOOK_SIGNATURE_HEADER};
 
 #[cfg(feature = "api")]
-pub use api::ApiRepository;
+pub use secrets::VaultSecretResolver;
@@ -3,14 +3,17 @@
 //! This module provides a unified interface for loading all repository content
 //! from different sources (file system, database, API).
 
-use crate::config::{RepositoryConfig, RepositorySource};
+use crate::config::{IntegrityConfig, RepositoryConfig, RepositorySource};
 use crate::content::{
     ApiConfig, ApiEndpoint, DataSourceConfig, FeatureCache, FeatureDefinition, FeatureFilter,
     ListConfig, PoolConfig, RepositoryContent, TimeWindow,
 };
 use crate::error::{RepositoryError, RepositoryResult};
-use crate::Repository;
-use std::path::Path;
+use crate::integrity::{parse_public_key, IntegrityManifest};
+use crate::secrets::{Interpolator, SecretResolver};
+use crate::{CacheableRepository, Repository};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
 /// Unified repository loader
 ///
@@ -27,12 +30,22 @@ use std::path::Path;
 /// ```
 pub struct RepositoryLoader {
     config: RepositoryConfig,
+    interpolator: Interpolator,
 }
 
 impl RepositoryLoader {
     /// Create a new repository loader
     pub fn new(config: RepositoryConfig) -> Self {
-        Self { config }
+        Self {
+            config,
+            interpolator: Interpolator::new(),
+        }
+    }
+
+    /// Resolve `${secret:...}` placeholders in loaded YAML with a secret resolver
+    pub fn with_secret_resolver(mut self, resolver: Arc<dyn SecretResolver>) -> Self {
+        self.interpolator = Interpolator::new().with_secret_resolver(resolver);
+        self
     }
 
     /// Load all content from the repository
@@ -46,17 +59,102 @@ impl RepositoryLoader {
     /// - Data source configs
     /// - Feature definitions
     /// - List configs
+    ///
+    /// When integrity verification is configured, the registry, pipelines,
+    /// rules and rulesets are checked against the manifest before returning.
+    ///
+    /// `${ENV_VAR}` and `${secret:...}` placeholders are resolved in all loaded
+    /// YAML (after integrity verification, which covers the files as written);
+    /// see [`crate::secrets`].
     pub async fn load_all(&self) -> RepositoryResult<RepositoryContent> {
         // Validate configuration
         self.config
             .validate()
             .map_err(|e| RepositoryError::Config(e.to_string()))?;
 
-        match self.config.source {
+        let content = match self.config.source {
             RepositorySource::FileSystem => self.load_from_filesystem().await,
             RepositorySource::Database => self.load_from_database().await,
             RepositorySource::Api => self.load_from_api().await,
+            RepositorySource::Redis => self.load_from_redis().await,
+            RepositorySource::MongoDB => self.load_from_mongodb().await,
             RepositorySource::Memory => Ok(RepositoryContent::default()),
+        }?;
+
+        if let Some(integrity) = &self.config.integrity {
+            self.verify_integrity(integrity, &content).await?;
+        }
+
+        self.interpolate_content(content).await
+    }
+
+    /// Resolve placeholders in the registry, pipelines, rules and rulesets
+    ///
+    /// Config files are resolved as they are read, before parsing.
+    async fn interpolate_content(
+        &self,
+        mut content: RepositoryContent,
+    ) -> RepositoryResult<RepositoryContent> {
+        if let Some(registry) = &content.registry {
+            content.registry = Some(self.interpolator.interpolate(registry).await?);
+        }
+        for documents in [
+            &mut content.pipelines,
+            &mut content.rules,
+            &mut content.rulesets,
+        ] {
+            for (_, yaml) in documents.iter_mut() {
+                *yaml = self.interpolator.interpolate(yaml).await?;
+            }
+        }
+        Ok(content)
+    }
+
+    /// Read a config file and resolve its placeholders
+    async fn read_config_file(&self, path: &Path) -> RepositoryResult<String> {
+        let content = tokio::fs::read_to_string(path)
+            .await
+            .map_err(|e| RepositoryError::Other(format!("Failed to read {:?}: {}", path, e)))?;
+        self.interpolator.interpolate(&content).await
+    }
+
+    /// Verify loaded content against the integrity manifest
+    async fn verify_integrity(
+        &self,
+        integrity: &IntegrityConfig,
+        content: &RepositoryContent,
+    ) -> RepositoryResult<()> {
+        let manifest_path = match (&integrity.manifest_path, &self.config.base_path) {
+            (Some(path), _) => PathBuf::from(path),
+            (None, Some(base)) if self.config.source == RepositorySource::FileSystem => {
+                Path::new(base).join("integrity.json")
+            }
+            _ => {
+                return Err(RepositoryError::Config(
+                    "integrity.manifest_path is required for this repository source".to_string(),
+                ))
+            }
+        };
+
+        let public_keys = integrity
+            .public_keys
+            .iter()
+            .map(|key| parse_public_key(key))
+            .collect::<RepositoryResult<Vec<_>>>()?;
+        if integrity.require_signatures && public_keys.is_empty() {
+            return Err(RepositoryError::Config(
+                "integrity.require_signatures needs at least one public key".to_string(),
+            ));
+        }
+
+        let manifest = tokio::fs::read_to_string(&manifest_path).await?;
+        IntegrityManifest::from_json(&manifest)?.verify_content(content, &public_keys, integrity.require_signatures)
+    }
+
+    /// Apply the configured cache policy to a repository
+    fn configure_cache(&self, repo: &mut impl CacheableRepository) {
+        if let Some(cache) = &self.config.cache {
+            repo.set_cache_config(cache.clone());
         }
     }
 
@@ -66,7 +164,8 @@ impl RepositoryLoader {
             RepositoryError::Config("base_path required for FileSystem source".to_string())
         })?;
 
-        let repo = crate::FileSystemRepository::new(base_path)?;
+        let mut repo = crate::FileSystemRepository::new(base_path)?;
+        self.configure_cache(&mut repo);
         let mut content = RepositoryContent::default();
 
         // 1. Load registry
@@ -107,22 +206,42 @@ impl RepositoryLoader {
         let configs_path = Path::new(base_path).join("configs");
         if configs_path.exists() {
             // Load API configs
-            content.api_configs = self.load_api_configs(&configs_path).await.unwrap_or_default();
+            content.api_configs =
+                skip_unless_unresolved(self.load_api_configs(&configs_path).await)?
+                    .unwrap_or_default();
 
             // Load datasource configs
-            content.datasource_configs = self
-                .load_datasource_configs(&configs_path)
-                .await
-                .unwrap_or_default();
+            content.datasource_configs =
+                skip_unless_unresolved(self.load_datasource_configs(&configs_path).await)?
+                    .unwrap_or_default();
 
             // Load feature definitions
-            content.feature_definitions = self
-                .load_feature_definitions(&configs_path)
-                .await
-                .unwrap_or_default();
+            content.feature_definitions =
+                skip_unless_unresolved(self.load_feature_definitions(&configs_path).await)?
+                    .unwrap_or_default();
 
             // Load list configs
-            content.list_configs = self.load_list_configs(&configs_path).await.unwrap_or_default();
+            content.list_configs =
+                skip_unless_unresolved(self.load_list_configs(&configs_path).await)?
+                    .unwrap_or_default();
+
+            // Load reason code catalogs
+            content.reason_catalogs = skip_unless_unresolved(
+                self.load_config_documents(&configs_path, "reason_codes")
+                    .await,
+            )?
+            .unwrap_or_default();
+
+            // Load PII masking policies
+            content.masking_policies =
+                skip_unless_unresolved(self.load_config_documents(&configs_path, "masking").await)?
+                    .unwrap_or_default();
+
+            // Load entity key definitions
+            content.entity_definitions = skip_unless_unresolved(
+                self.load_config_documents(&configs_path, "entities").await,
+            )?
+            .unwrap_or_default();
         }
 
         Ok(content)
@@ -147,7 +266,9 @@ impl RepositoryLoader {
                 .extension()
                 .map_or(false, |ext| ext == "yaml" || ext == "yml")
             {
-                if let Ok(config) = self.load_api_config_file(&path).await {
+                if let Some(config) =
+                    skip_unless_unresolved(self.load_api_config_file(&path).await)?
+                {
                     configs.push(config);
                 }
             }
@@ -158,9 +279,7 @@ impl RepositoryLoader {
 
     /// Load a single API config file
     async fn load_api_config_file(&self, path: &Path) -> RepositoryResult<ApiConfig> {
-        let content = tokio::fs::read_to_string(path).await.map_err(|e| {
-            RepositoryError::Other(format!("Failed to read {:?}: {}", path, e))
-        })?;
+        let content = self.read_config_file(path).await?;
 
         // Parse YAML directly into ApiConfig structure
         // serde will handle the deserialization based on our struct definition
@@ -193,7 +312,9 @@ impl RepositoryLoader {
                 .extension()
                 .map_or(false, |ext| ext == "yaml" || ext == "yml")
             {
-                if let Ok(config) = self.load_datasource_config_file(&path).await {
+                if let Some(config) =
+                    skip_unless_unresolved(self.load_datasource_config_file(&path).await)?
+                {
                     configs.push(config);
                 }
             }
@@ -204,9 +325,7 @@ impl RepositoryLoader {
 
     /// Load a single datasource config file
     async fn load_datasource_config_file(&self, path: &Path) -> RepositoryResult<DataSourceConfig> {
-        let content = tokio::fs::read_to_string(path).await.map_err(|e| {
-            RepositoryError::Other(format!("Failed to read {:?}: {}", path, e))
-        })?;
+        let content = self.read_config_file(path).await?;
 
         let yaml: serde_yaml::Value = serde_yaml::from_str(&content).map_err(|e| {
             RepositoryError::ParseError(format!("Failed to parse {:?}: {}", path, e))
@@ -279,7 +398,9 @@ impl RepositoryLoader {
                 .extension()
                 .map_or(false, |ext| ext == "yaml" || ext == "yml")
             {
-                if let Ok(defs) = self.load_feature_definitions_file(&path).await {
+                if let Some(defs) =
+                    skip_unless_unresolved(self.load_feature_definitions_file(&path).await)?
+                {
                     definitions.extend(defs);
                 }
             }
@@ -293,9 +414,7 @@ impl RepositoryLoader {
         &self,
         path: &Path,
     ) -> RepositoryResult<Vec<FeatureDefinition>> {
-        let content = tokio::fs::read_to_string(path).await.map_err(|e| {
-            RepositoryError::Other(format!("Failed to read {:?}: {}", path, e))
-        })?;
+        let content = self.read_config_file(path).await?;
 
         let yaml: serde_yaml::Value = serde_yaml::from_str(&content).map_err(|e| {
             RepositoryError::ParseError(format!("Failed to parse {:?}: {}", path, e))
@@ -402,7 +521,9 @@ impl RepositoryLoader {
                 .extension()
                 .map_or(false, |ext| ext == "yaml" || ext == "yml")
             {
-                if let Ok(list_configs) = self.load_list_configs_file(&path).await {
+                if let Some(list_configs) =
+                    skip_unless_unresolved(self.load_list_configs_file(&path).await)?
+                {
                     configs.extend(list_configs);
                 }
             }
@@ -411,11 +532,52 @@ impl RepositoryLoader {
         Ok(configs)
     }
 
+    /// Load raw YAML documents (id, content) from a configs/ subdirectory
+    ///
+    /// Used for reason code catalogs (configs/reason_codes/), masking
+    /// policies (configs/masking/) and entity definitions (configs/entities/),
+    /// which are parsed by the engine.
+    async fn load_config_documents(
+        &self,
+        configs_path: &Path,
+        dir: &str,
+    ) -> RepositoryResult<Vec<(String, String)>> {
+        let dir_path = configs_path.join(dir);
+        if !dir_path.exists() {
+            return Ok(Vec::new());
+        }
+
+        let mut documents = Vec::new();
+
+        let entries = std::fs::read_dir(&dir_path).map_err(|e| {
+            RepositoryError::Other(format!("Failed to read {} directory: {}", dir, e))
+        })?;
+
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path
+                .extension()
+                .is_some_and(|ext| ext == "yaml" || ext == "yml")
+            {
+                let id = path
+                    .file_stem()
+                    .and_then(|s| s.to_str())
+                    .unwrap_or_default()
+                    .to_string();
+                let yaml = self.read_config_file(&path).await?;
+                documents.push((id, yaml));
+            }
+        }
+
+        // Directory order is platform-dependent; keep merges deterministic
+        documents.sort_by(|a, b| a.0.cmp(&b.0));
+
+        Ok(documents)
+    }
+
     /// Load list configs from a single file
     async fn load_list_configs_file(&self, path: &Path) -> RepositoryResult<Vec<ListConfig>> {
-        let content = tokio::fs::read_to_string(path).await.map_err(|e| {
-            RepositoryError::Other(format!("Failed to read {:?}: {}", path, e))
-        })?;
+        let content = self.read_config_file(path).await?;
 
         let yaml: serde_yaml::Value = serde_yaml::from_str(&content).map_err(|e| {
             RepositoryError::ParseError(format!("Failed to parse {:?}: {}", path, e))
@@ -444,6 +606,8 @@ impl RepositoryLoader {
             .and_then(|v| v.as_str())
             .map(String::from);
 
+        let list_type = yaml.get("type").and_then(|v| v.as_str()).map(String::from);
+
         let table = yaml.get("table").and_then(|v| v.as_str()).map(String::from);
 
         let value_column = yaml
@@ -460,6 +624,10 @@ impl RepositoryLoader {
 
         let reload_interval = yaml.get("reload_interval").and_then(|v| v.as_u64());
 
+        let format = yaml.get("format").and_then(|v| v.as_str()).map(String::from);
+
+        let column = yaml.get("column").and_then(|v| v.as_str()).map(String::from);
+
         let initial_values = yaml
             .get("initial_values")
             .and_then(|v| v.as_sequence())
@@ -474,11 +642,14 @@ impl RepositoryLoader {
             id,
             description,
             backend,
+            list_type,
             table,
             value_column,
             expiration_column,
             path,
             reload_interval,
+            format,
+            column,
             initial_values,
         })
     }
@@ -490,7 +661,8 @@ impl RepositoryLoader {
             RepositoryError::Config("database_url required for Database source".to_string())
         })?;
 
-        let repo = crate::PostgresRepository::new(db_url).await?;
+        let mut repo = crate::PostgresRepository::new(db_url).await?;
+        self.configure_cache(&mut repo);
         let mut content = RepositoryContent::default();
 
         // Load pipelines
@@ -570,6 +742,115 @@ impl RepositoryLoader {
             "API source requires 'api' feature to be enabled".to_string(),
         ))
     }
+
+    /// Load content from Redis
+    #[cfg(feature = "redis")]
+    async fn load_from_redis(&self) -> RepositoryResult<RepositoryContent> {
+        let redis_url = self.config.redis_url.as_ref().ok_or_else(|| {
+            RepositoryError::Config("redis_url required for Redis source".to_string())
+        })?;
+
+        let mut repo = crate::RedisRepository::new(redis_url).await?;
+        self.configure_cache(&mut repo);
+        let mut content = RepositoryContent {
+            registry: repo.load_registry().await.ok(),
+            ..Default::default()
+        };
+
+        // Load pipelines
+        let pipeline_ids = repo.list_pipelines().await?;
+        for id in pipeline_ids {
+            if let Ok((_, yaml)) = repo.load_pipeline(&id).await {
+                content.pipelines.push((id, yaml));
+            }
+        }
+
+        // Load rules
+        let rule_ids = repo.list_rules().await?;
+        for id in rule_ids {
+            if let Ok((_, yaml)) = repo.load_rule(&id).await {
+                content.rules.push((id, yaml));
+            }
+        }
+
+        // Load rulesets
+        let ruleset_ids = repo.list_rulesets().await?;
+        for id in ruleset_ids {
+            if let Ok((_, yaml)) = repo.load_ruleset(&id).await {
+                content.rulesets.push((id, yaml));
+            }
+        }
+
+        Ok(content)
+    }
+
+    #[cfg(not(feature = "redis"))]
+    async fn load_from_redis(&self) -> RepositoryResult<RepositoryContent> {
+        Err(RepositoryError::Config(
+            "Redis source requires 'redis' feature to be enabled".to_string(),
+        ))
+    }
+
+    /// Load content from MongoDB
+    #[cfg(feature = "mongodb")]
+    async fn load_from_mongodb(&self) -> RepositoryResult<RepositoryContent> {
+        let db_url = self.config.database_url.as_ref().ok_or_else(|| {
+            RepositoryError::Config("database_url required for MongoDB source".to_string())
+        })?;
+
+        let mut repo = crate::MongoRepository::new(db_url).await?;
+        self.configure_cache(&mut repo);
+        let mut content = RepositoryContent {
+            registry: repo.load_registry().await.ok(),
+            ..Default::default()
+        };
+
+        // Load pipelines
+        let pipeline_ids = repo.list_pipelines().await?;
+        for id in pipeline_ids {
+            if let Ok((_, yaml)) = repo.load_pipeline(&id).await {
+                content.pipelines.push((id, yaml));
+            }
+        }
+
+        // Load rules
+        let rule_ids = repo.list_rules().await?;
+        for id in rule_ids {
+            if let Ok((_, yaml)) = repo.load_rule(&id).await {
+                content.rules.push((id, yaml));
+            }
+        }
+
+        // Load rulesets
+        let ruleset_ids = repo.list_rulesets().await?;
+        for id in ruleset_ids {
+            if let Ok((_, yaml)) = repo.load_ruleset(&id).await {
+                content.rulesets.push((id, yaml));
+            }
+        }
+
+        Ok(content)
+    }
+
+    #[cfg(not(feature = "mongodb"))]
+    async fn load_from_mongodb(&self) -> RepositoryResult<RepositoryContent> {
+        Err(RepositoryError::Config(
+            "MongoDB source requires 'mongodb' feature to be enabled".to_string(),
+        ))
+    }
+}
+
+/// Skip config content that failed to load, unless a placeholder is unresolved
+///
+/// A broken config file is skipped so it doesn't hide the others, but an
+/// unresolved secret must fail the load rather than silently drop e.g. the
+/// credentials of an API.
+fn skip_unless_unresolved<T>(result: RepositoryResult<T>) -> RepositoryResult<Option<T>> {
+    match result {
+        Ok(value) => Ok(Some(value)),
+        Err(e @ RepositoryError::Interpolation { .. }) => Err(e),
+        Err(_) => Ok(None),
+    }
 }
 
 #[cfg(test)]
@@ -593,10 +874,46 @@ mod tests {
             database_url: None,
             api_url: None,
             api_key: None,
+            redis_url: None,
+            integrity: None,
+            cache: None,
         };
         let loader = RepositoryLoader::new(config);
         let result = loader.load_all().await;
 
         assert!(result.is_err());
     }
+
+    #[tokio::test]
+    async fn test_filesystem_loader_resolves_secrets() {
+        use crate::StaticSecretResolver;
+
+        let dir = tempfile::tempdir().unwrap();
+        let apis = dir.path().join("configs/apis");
+        std::fs::create_dir_all(&apis).unwrap();
+        std::fs::write(
+            apis.join("ipinfo.yaml"),
+            "name: ipinfo\n\
+             base_url: \"${CORINT_TEST_LOADER_IPINFO_URL:-https://ipinfo.io}\"\n\
+             auth:\n  type: header\n  name: Authorization\n\
+             \x20 value: \"Bearer ${secret:apis/ipinfo#token}\"\n",
+        )
+        .unwrap();
+        let config = RepositoryConfig::file_system(dir.path().to_string_lossy());
+
+        // An unresolved secret fails the load instead of dropping the API
+        let result = RepositoryLoader::new(config.clone()).load_all().await;
+        assert!(matches!(result, Err(RepositoryError::Interpolation { .. })));
+
+        let secrets = StaticSecretResolver::new().with_secret("apis/ipinfo#token", "s3cr3t");
+        let content = RepositoryLoader::new(config)
+            .with_secret_resolver(Arc::new(secrets))
+            .load_all()
+            .await
+            .unwrap();
+
+        let api = &content.api_configs[0];
+        assert_eq!(api.base_url, "https://ipinfo.io");
+        assert_eq!(api.auth.as_ref().unwrap().value, "Bearer s3cr3t");
+    }
 }
@@ -0,0 +1,120 @@
+//! Embedded PostgreSQL schema migrations
+//!
+//! The SQL files in `crates/corint-repository/migrations/` are compiled into
+//! the crate and applied in order by [`PostgresRepository::new`], so a new
+//! database needs no hand-applied SQL and existing ones are upgraded when a
+//! release adds a migration.
+//!
+//! Applied versions are recorded in the `_sqlx_migrations` table and a
+//! Postgres advisory lock serializes instances that start at the same time.
+//! Every migration is safe to run on a schema that was applied by hand
+//! before, so existing databases adopt the migration table on first start.
+//!
+//! Deployments whose application user may not run DDL can apply migrations
+//! from a separate job with [`PostgresRepository::migrate_only`] (or
+//! `corint-server --migrate-only`) and connect with
+//! [`PostgresRepository::new_without_migrations`].
+//!
+//! [`PostgresRepository::new`]: crate::PostgresRepository::new
+//! [`PostgresRepository::migrate_only`]: crate::PostgresRepository::migrate_only
+//! [`PostgresRepository::new_without_migrations`]: crate::PostgresRepository::new_without_migrations
+
+use sqlx::migrate::{Migrate, Migrator};
+use sqlx::postgres::PgPool;
+use std::collections::HashSet;
+
+use crate::RepositoryResult;
+
+/// Migrations of the repository schema
+pub static MIGRATOR: Migrator = sqlx::migrate!("./migrations");
+
+/// A schema migration
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MigrationInfo {
+    /// Version (the numeric file name prefix)
+    pub version: i64,
+
+    /// Description (the rest of the file name)
+    pub description: String,
+}
+
+/// Result of applying migrations
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MigrationReport {
+    /// Migrations applied by this run, in order
+    pub applied: Vec<MigrationInfo>,
+
+    /// Latest applied version after the run
+    pub current_version: Option<i64>,
+}
+
+impl MigrationReport {
+    /// Whether the schema was already up to date
+    pub fn is_up_to_date(&self) -> bool {
+        self.applied.is_empty()
+    }
+}
+
+/// All embedded migrations, in order
+pub fn migrations() -> Vec<MigrationInfo> {
+    MIGRATOR.iter().map(info).collect()
+}
+
+/// Apply pending migrations
+pub async fn run(pool: &PgPool) -> RepositoryResult<MigrationReport> {
+    let before = applied_versions(pool).await?;
+    MIGRATOR.run(pool).await?;
+
+    let applied: Vec<MigrationInfo> = MIGRATOR
+        .iter()
+        .filter(|m| !before.contains(&m.version))
+        .map(info)
+        .collect();
+
+    Ok(MigrationReport {
+        applied,
+        current_version: MIGRATOR.iter().map(|m| m.version).max(),
+    })
+}
+
+/// Migrations not yet applied to the database
+pub async fn pending(pool: &PgPool) -> RepositoryResult<Vec<MigrationInfo>> {
+    let applied = applied_versions(pool).await?;
+    Ok(MIGRATOR
+        .iter()
+        .filter(|m| !applied.contains(&m.version))
+        .map(info)
+        .collect())
+}
+
+async fn applied_versions(pool: &PgPool) -> RepositoryResult<HashSet<i64>> {
+    let mut conn = pool.acquire().await?;
+    conn.ensure_migrations_table().await?;
+    Ok(conn
+        .list_applied_migrations()
+        .await?
+        .into_iter()
+        .map(|m| m.version)
+        .collect())
+}
+
+fn info(migration: &sqlx::migrate::Migration) -> MigrationInfo {
+    MigrationInfo {
+        version: migration.version,
+        description: migration.description.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_embedded_migrations() {
+        let migrations = migrations();
+        let versions: Vec<i64> = migrations.iter().map(|m| m.version).collect();
+        assert_eq!(versions, (1..=9).collect::<Vec<_>>());
+        assert_eq!(migrations[0].description, "create rules table");
+        assert_eq!(migrations[8].description, "add artifact status");
+    }
+}
@@ -1,7 +1,49 @@
 //! Data models for the repository layer
 
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::time::{Duration, Instant};
 
+use crate::bundle::BundleArtifactKind;
+
+/// Lifecycle status of a rule, ruleset or pipeline
+///
+/// Only published artifacts are served by `load_*`; drafts are reachable
+/// through [`Repository::load_draft`](crate::Repository::load_draft).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ArtifactStatus {
+    /// Work in progress, not visible to live traffic
+    Draft,
+    /// Live version
+    #[default]
+    Published,
+    /// Retired version kept for reference
+    Archived,
+}
+
+impl ArtifactStatus {
+    /// Stable name used in storage backends
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Draft => "draft",
+            Self::Published => "published",
+            Self::Archived => "archived",
+        }
+    }
+
+    /// Parse a status from its stable name
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "draft" => Some(Self::Draft),
+            "published" => Some(Self::Published),
+            "archived" => Some(Self::Archived),
+            _ => None,
+        }
+    }
+}
+
 /// Cache statistics
 #[derive(Debug, Clone, Default)]
 pub struct CacheStats {
@@ -13,6 +55,8 @@ pub struct CacheStats {
     pub size: usize,
     /// Total memory used (approximate, in bytes)
     pub memory_bytes: usize,
+    /// Number of entries evicted to stay within `max_entries`
+    pub evictions: u64,
 }
 
 impl CacheStats {
@@ -27,8 +71,11 @@ impl CacheStats {
     }
 }
 
+/// Logical clock ordering cache accesses for LRU eviction
+static ACCESS_CLOCK: AtomicU64 = AtomicU64::new(0);
+
 /// A cached artifact with TTL support
-#[derive(Debug, Clone)]
+#[derive(Debug)]
 pub(crate) struct CachedArtifact<T> {
     /// The cached data
     pub data: T,
@@ -38,6 +85,23 @@ pub(crate) struct CachedArtifact<T> {
     pub cached_at: Instant,
     /// Time-to-live duration
     pub ttl: Duration,
+    /// Access clock value of the last use (updated under a read lock)
+    last_used: AtomicU64,
+    /// Number of uses since the entry was cached
+    uses: AtomicU64,
+}
+
+impl<T: Clone> Clone for CachedArtifact<T> {
+    fn clone(&self) -> Self {
+        Self {
+            data: self.data.clone(),
+            content: self.content.clone(),
+            cached_at: self.cached_at,
+            ttl: self.ttl,
+            last_used: AtomicU64::new(self.last_used()),
+            uses: AtomicU64::new(self.uses()),
+        }
+    }
 }
 
 impl<T> CachedArtifact<T> {
@@ -48,6 +112,8 @@ impl<T> CachedArtifact<T> {
             content,
             cached_at: Instant::now(),
             ttl,
+            last_used: AtomicU64::new(ACCESS_CLOCK.fetch_add(1, Ordering::Relaxed)),
+            uses: AtomicU64::new(0),
         }
     }
 
@@ -56,6 +122,25 @@ impl<T> CachedArtifact<T> {
         self.cached_at.elapsed() > self.ttl
     }
 
+    /// Record a cache hit on this entry
+    pub fn touch(&self) {
+        self.last_used.store(
+            ACCESS_CLOCK.fetch_add(1, Ordering::Relaxed),
+            Ordering::Relaxed,
+        );
+        self.uses.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Access clock value of the last use; higher is more recent
+    pub fn last_used(&self) -> u64 {
+        self.last_used.load(Ordering::Relaxed)
+    }
+
+    /// Number of cache hits on this entry
+    pub fn uses(&self) -> u64 {
+        self.uses.load(Ordering::Relaxed)
+    }
+
     /// Get the age of this cached entry
     #[allow(dead_code)]
     pub fn age(&self) -> Duration {
@@ -63,17 +148,52 @@ impl<T> CachedArtifact<T> {
     }
 }
 
+/// Which entry is evicted when a cache is full
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EvictionPolicy {
+    /// Least recently used
+    #[default]
+    Lru,
+    /// Least frequently used (ties go to the least recently used)
+    Lfu,
+}
+
 /// Cache configuration
-#[derive(Debug, Clone)]
+///
+/// Durations are written in seconds when (de)serialized, e.g. in the `cache`
+/// section of a [`RepositoryConfig`](crate::RepositoryConfig):
+///
+/// ```yaml
+/// cache:
+///   default_ttl: 300
+///   entity_ttls:
+///     pipeline: 3600
+///     rule: 60
+///   max_entries: 5000
+///   eviction: lfu
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct CacheConfig {
     /// Whether caching is enabled
     pub enabled: bool,
     /// Default time-to-live for cache entries
+    #[serde(with = "duration_secs")]
     pub default_ttl: Duration,
-    /// Maximum number of entries to keep in cache
+    /// Time-to-live per artifact kind, overriding `default_ttl`
+    #[serde(with = "duration_secs_map", skip_serializing_if = "BTreeMap::is_empty")]
+    pub entity_ttls: BTreeMap<BundleArtifactKind, Duration>,
+    /// Maximum number of entries kept per artifact kind (rules, rulesets and
+    /// pipelines are cached separately); unbounded when unset
     pub max_entries: Option<usize>,
+    /// Which entry is evicted when `max_entries` is reached
+    pub eviction: EvictionPolicy,
     /// Maximum memory usage (in bytes)
     pub max_memory_bytes: Option<usize>,
+    /// How long a "not found" result is remembered (zero disables negative caching)
+    #[serde(with = "duration_secs")]
+    pub negative_ttl: Duration,
 }
 
 impl Default for CacheConfig {
@@ -81,8 +201,11 @@ impl Default for CacheConfig {
         Self {
             enabled: true,
             default_ttl: Duration::from_secs(300), // 5 minutes
+            entity_ttls: BTreeMap::new(),
             max_entries: Some(1000),
+            eviction: EvictionPolicy::default(),
             max_memory_bytes: Some(100 * 1024 * 1024), // 100 MB
+            negative_ttl: Duration::from_secs(5),
         }
     }
 }
@@ -118,12 +241,100 @@ impl CacheConfig {
         self.max_entries = None;
         self
     }
+
+    /// Set how long "not found" results are remembered
+    ///
+    /// A zero duration disables negative caching.
+    pub fn with_negative_ttl(mut self, ttl: Duration) -> Self {
+        self.negative_ttl = ttl;
+        self
+    }
+
+    /// Set the TTL of one artifact kind
+    pub fn with_entity_ttl(mut self, kind: BundleArtifactKind, ttl: Duration) -> Self {
+        self.entity_ttls.insert(kind, ttl);
+        self
+    }
+
+    /// Set the eviction policy
+    pub fn with_eviction(mut self, eviction: EvictionPolicy) -> Self {
+        self.eviction = eviction;
+        self
+    }
+
+    /// TTL of an artifact kind
+    pub fn ttl_for(&self, kind: BundleArtifactKind) -> Duration {
+        self.entity_ttls
+            .get(&kind)
+            .copied()
+            .unwrap_or(self.default_ttl)
+    }
+}
+
+/// Durations as (fractional) seconds
+mod duration_secs {
+    use serde::{Deserialize, Deserializer, Serializer};
+    use std::time::Duration;
+
+    pub fn serialize<S: Serializer>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_f64(duration.as_secs_f64())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Duration, D::Error> {
+        let secs = f64::deserialize(deserializer)?;
+        Duration::try_from_secs_f64(secs).map_err(serde::de::Error::custom)
+    }
+}
+
+/// Maps of durations as (fractional) seconds
+mod duration_secs_map {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use std::collections::BTreeMap;
+    use std::time::Duration;
+
+    use crate::bundle::BundleArtifactKind;
+
+    pub fn serialize<S: Serializer>(
+        map: &BTreeMap<BundleArtifactKind, Duration>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        map.iter()
+            .map(|(kind, duration)| (*kind, duration.as_secs_f64()))
+            .collect::<BTreeMap<_, _>>()
+            .serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<BTreeMap<BundleArtifactKind, Duration>, D::Error> {
+        BTreeMap::<BundleArtifactKind, f64>::deserialize(deserializer)?
+            .into_iter()
+            .map(|(kind, secs)| {
+                Duration::try_from_secs_f64(secs)
+                    .map(|duration| (kind, duration))
+                    .map_err(serde::de::Error::custom)
+            })
+            .collect()
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_artifact_status_names() {
+        for status in [
+            ArtifactStatus::Draft,
+            ArtifactStatus::Published,
+            ArtifactStatus::Archived,
+        ] {
+            assert_eq!(ArtifactStatus::parse(status.as_str()), Some(status));
+        }
+        assert_eq!(ArtifactStatus::default(), ArtifactStatus::Published);
+        assert_eq!(ArtifactStatus::parse("live"), None);
+    }
+
     #[test]
     fn test_cache_stats_hit_rate() {
         let stats = CacheStats {
@@ -131,6 +342,7 @@ mod tests {
             misses: 20,
             size: 100,
             memory_bytes: 1024,
+            evictions: 0,
         };
 
         assert_eq!(stats.hit_rate(), 0.8);
@@ -143,6 +355,7 @@ mod tests {
             misses: 0,
             size: 0,
             memory_bytes: 0,
+            evictions: 0,
         };
 
         assert_eq!(stats.hit_rate(), 0.0);
@@ -204,6 +417,7 @@ mod tests {
         assert_eq!(config.default_ttl, Duration::from_secs(300));
         assert_eq!(config.max_entries, Some(1000));
         assert_eq!(config.max_memory_bytes, Some(100 * 1024 * 1024));
+        assert_eq!(config.negative_ttl, Duration::from_secs(5));
     }
 
     #[test]
@@ -262,6 +476,7 @@ mod tests {
             misses: 0,
             size: 50,
             memory_bytes: 2048,
+            evictions: 0,
         };
 
         assert_eq!(stats.hit_rate(), 1.0);
@@ -274,6 +489,7 @@ mod tests {
             misses: 100,
             size: 50,
             memory_bytes: 2048,
+            evictions: 0,
         };
 
         assert_eq!(stats.hit_rate(), 0.0);
@@ -0,0 +1,642 @@
+//! MongoDB repository implementation
+//!
+//! Mirrors the PostgreSQL repository's storage model using collections instead
+//! of tables, so platforms that already keep their configuration in MongoDB can
+//! host CORINT artifacts without a separate Postgres instance.
+//!
+//! Collections (in the configured database):
+//!
+//! ```text
+//! rules, rulesets, pipelines   { _id, content, version, extends?, updated_at }
+//! artifact_versions            { artifact_type, artifact_id, version, content, created_at }
+//! artifact_audit_log           { artifact_type, artifact_id, operation, old/new content and version, changed_at }
+//! repository_configs           { config_type, id, content, version, updated_at }
+//! ```
+//!
+//! Every save bumps the artifact version, records the new content in
+//! `artifact_versions` and appends an `artifact_audit_log` entry, matching the
+//! versioning and audit semantics of `PostgresRepository`.
+
+use ::mongodb::bson::{doc, DateTime, Document};
+use ::mongodb::options::{FindOneAndUpdateOptions, FindOptions, ReturnDocument, UpdateOptions};
+use ::mongodb::{Client, ClientSession, Collection, Database};
+use async_trait::async_trait;
+use corint_core::ast::{Pipeline, Rule, Ruleset};
+use corint_parser::{PipelineParser, RuleParser, RulesetParser};
+use futures::TryStreamExt;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tokio::sync::RwLock;
+
+use crate::{
+    bundle::{self, BundleArtifactKind, RepositoryBundle},
+    cache::LoadCoordinator,
+    diff::ArtifactDiff,
+    error::RepositoryError,
+    models::*,
+    traits::*,
+    CacheStats, RepositoryResult,
+};
+
+/// Database used when the connection string does not name one
+pub const DEFAULT_DATABASE: &str = "corint";
+
+type Cache<T> = Arc<RwLock<HashMap<String, CachedArtifact<T>>>>;
+
+/// MongoDB repository
+///
+/// Stores and loads artifacts from MongoDB collections with caching support.
+pub struct MongoRepository {
+    /// MongoDB client (needed for sessions)
+    client: Client,
+    /// Database holding the repository collections
+    db: Database,
+    /// Cache for rules
+    rule_cache: Cache<Rule>,
+    /// Cache for rulesets
+    ruleset_cache: Cache<Ruleset>,
+    /// Cache for pipelines
+    pipeline_cache: Cache<Pipeline>,
+    /// Cache configuration
+    cache_config: Arc<Mutex<CacheConfig>>,
+    /// Cache statistics
+    stats: Arc<Mutex<CacheStats>>,
+    /// Negative cache and single-flight gate for cache misses
+    loads: Arc<LoadCoordinator>,
+}
+
+impl MongoRepository {
+    /// Connect to MongoDB
+    ///
+    /// Uses the database named in the connection string, or `corint` if none is given.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use corint_repository::MongoRepository;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> anyhow::Result<()> {
+    ///     let repo = MongoRepository::new("mongodb://localhost:27017/corint").await?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn new(uri: &str) -> RepositoryResult<Self> {
+        let client = Client::with_uri_str(uri).await?;
+        let db = client
+            .default_database()
+            .unwrap_or_else(|| client.database(DEFAULT_DATABASE));
+        Ok(Self::with_database(client, db))
+    }
+
+    /// Create a repository over an existing client and database
+    pub fn with_database(client: Client, db: Database) -> Self {
+        Self {
+            client,
+            db,
+            rule_cache: Arc::new(RwLock::new(HashMap::new())),
+            ruleset_cache: Arc::new(RwLock::new(HashMap::new())),
+            pipeline_cache: Arc::new(RwLock::new(HashMap::new())),
+            cache_config: Arc::new(Mutex::new(CacheConfig::default())),
+            stats: Arc::new(Mutex::new(CacheStats::default())),
+            loads: Arc::new(LoadCoordinator::default()),
+        }
+    }
+
+    /// Create a new MongoDB repository with custom cache configuration
+    pub async fn with_cache_config(uri: &str, config: CacheConfig) -> RepositoryResult<Self> {
+        let mut repo = Self::new(uri).await?;
+        repo.cache_config = Arc::new(Mutex::new(config));
+        Ok(repo)
+    }
+
+    /// Collection holding one artifact kind ("rule" -> "rules")
+    fn artifacts(&self, kind: &str) -> Collection<Document> {
+        self.db.collection(&format!("{}s", kind))
+    }
+
+    fn versions(&self) -> Collection<Document> {
+        self.db.collection("artifact_versions")
+    }
+
+    fn audit_log(&self) -> Collection<Document> {
+        self.db.collection("artifact_audit_log")
+    }
+
+    fn configs(&self) -> Collection<Document> {
+        self.db.collection("repository_configs")
+    }
+
+    /// Filter matching an artifact by ID or path
+    fn identifier_filter(identifier: &str) -> Document {
+        doc! { "$or": [{ "_id": identifier }, { "path": identifier }] }
+    }
+
+    /// Check cache and potentially load from cache
+    async fn check_cache<T: Clone>(
+        &self,
+        cache: &Cache<T>,
+        identifier: &str,
+    ) -> Option<(T, String)> {
+        let enabled = self.cache_config.lock().unwrap().enabled;
+        if !enabled {
+            return None;
+        }
+
+        let cache_read = cache.read().await;
+        if let Some(cached) = cache_read.get(identifier) {
+            if !cached.is_expired() {
+                cached.touch();
+                self.stats.lock().unwrap().hits += 1;
+                return Some((cached.data.clone(), cached.content.clone()));
+            }
+        }
+
+        self.stats.lock().unwrap().misses += 1;
+        None
+    }
+
+    /// Load raw content for an artifact
+    async fn load_content(&self, kind: &str, identifier: &str) -> RepositoryResult<String> {
+        let document = self
+            .artifacts(kind)
+            .find_one(Self::identifier_filter(identifier), None)
+            .await?
+            .ok_or_else(|| RepositoryError::NotFound {
+                path: identifier.to_string(),
+            })?;
+
+        get_string(&document, "content")
+    }
+
+    /// List identifiers of an artifact kind, most recently updated first
+    async fn list_ids(&self, kind: &str) -> RepositoryResult<Vec<String>> {
+        let options = FindOptions::builder()
+            .sort(doc! { "updated_at": -1 })
+            .projection(doc! { "_id": 1, "path": 1 })
+            .build();
+
+        let documents: Vec<Document> = self
+            .artifacts(kind)
+            .find(None, options)
+            .await?
+            .try_collect()
+            .await?;
+
+        Ok(documents
+            .iter()
+            .filter_map(|d| d.get_str("path").or_else(|_| d.get_str("_id")).ok())
+            .map(String::from)
+            .collect())
+    }
+
+    /// Upsert an artifact, bumping its version, and record history and audit entries
+    ///
+    /// Returns the new version number.
+    async fn store_content(
+        &self,
+        kind: &str,
+        id: &str,
+        content: &str,
+        extends: Option<&str>,
+        mut session: Option<&mut ClientSession>,
+    ) -> RepositoryResult<i32> {
+        let now = DateTime::now();
+        let mut set = doc! { "content": content, "updated_at": now };
+        if kind == "ruleset" {
+            set.insert("extends", extends);
+        }
+
+        let options = FindOneAndUpdateOptions::builder()
+            .upsert(true)
+            .return_document(ReturnDocument::Before)
+            .build();
+        let filter = doc! { "_id": id };
+        let update = doc! { "$set": set, "$inc": { "version": 1_i32 } };
+
+        let previous = match session.as_deref_mut() {
+            Some(s) => {
+                self.artifacts(kind)
+                    .find_one_and_update_with_session(filter, update, options, s)
+                    .await?
+            }
+            None => {
+                self.artifacts(kind)
+                    .find_one_and_update(filter, update, options)
+                    .await?
+            }
+        };
+
+        let old_version = previous.as_ref().and_then(|d| d.get_i32("version").ok());
+        let old_content = previous
+            .as_ref()
+            .and_then(|d| d.get_str("content").ok())
+            .map(String::from);
+        let version = old_version.unwrap_or(0) + 1;
+
+        let history = doc! {
+            "artifact_type": kind,
+            "artifact_id": id,
+            "version": version,
+            "content": content,
+            "created_at": now,
+        };
+        let audit = doc! {
+            "artifact_type": kind,
+            "artifact_id": id,
+            "operation": if previous.is_some() { "update" } else { "create" },
+            "old_content": old_content,
+            "new_content": content,
+            "old_version": old_version,
+            "new_version": version,
+            "changed_at": now,
+        };
+
+        match session {
+            Some(s) => {
+                self.versions()
+                    .insert_one_with_session(history, None, &mut *s)
+                    .await?;
+                self.audit_log()
+                    .insert_one_with_session(audit, None, s)
+                    .await?;
+            }
+            None => {
+                self.versions().insert_one(history, None).await?;
+                self.audit_log().insert_one(audit, None).await?;
+            }
+        }
+
+        Ok(version)
+    }
+
+    /// Remove an artifact and record an audit entry
+    async fn delete_content(&self, kind: &str, identifier: &str) -> RepositoryResult<()> {
+        let removed = self
+            .artifacts(kind)
+            .find_one_and_delete(Self::identifier_filter(identifier), None)
+            .await?;
+
+        if let Some(removed) = removed {
+            self.audit_log()
+                .insert_one(
+                    doc! {
+                        "artifact_type": kind,
+                        "artifact_id": removed.get_str("_id").unwrap_or(identifier),
+                        "operation": "delete",
+                        "old_content": removed.get_str("content").ok(),
+                        "old_version": removed.get_i32("version").ok(),
+                        "changed_at": DateTime::now(),
+                    },
+                    None,
+                )
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Load the content of a specific artifact version
+    ///
+    /// Falls back to the live collection for the current version, so artifacts
+    /// written before history was recorded can still be compared against.
+    async fn load_version_content(
+        &self,
+        kind: BundleArtifactKind,
+        entity_id: &str,
+        version: i32,
+    ) -> RepositoryResult<String> {
+        let history = self
+            .versions()
+            .find_one(
+                doc! { "artifact_type": kind.as_str(), "artifact_id": entity_id, "version": version },
+                None,
+            )
+            .await?;
+        if let Some(document) = history {
+            return get_string(&document, "content");
+        }
+
+        let mut filter = Self::identifier_filter(entity_id);
+        filter.insert("version", version);
+        if let Some(document) = self.artifacts(kind.as_str()).find_one(filter, None).await? {
+            return get_string(&document, "content");
+        }
+
+        Err(RepositoryError::NotFound {
+            path: format!("{} {}@v{}", kind.as_str(), entity_id, version),
+        })
+    }
+
+    /// Compute a structural diff between two stored versions of a rule, ruleset, or pipeline
+    pub async fn diff_versions(
+        &self,
+        kind: BundleArtifactKind,
+        entity_id: &str,
+        v1: i32,
+        v2: i32,
+    ) -> RepositoryResult<ArtifactDiff> {
+        let from_content = self.load_version_content(kind, entity_id, v1).await?;
+        let to_content = self.load_version_content(kind, entity_id, v2).await?;
+
+        ArtifactDiff::between(entity_id, v1, &from_content, v2, &to_content)
+    }
+}
+
+/// Read a required string field from a document
+fn get_string(document: &Document, key: &str) -> RepositoryResult<String> {
+    document
+        .get_str(key)
+        .map(String::from)
+        .map_err(|e| RepositoryError::Other(format!("Invalid '{}' field: {}", key, e)))
+}
+
+#[async_trait]
+impl Repository for MongoRepository {
+    async fn load_rule(&self, identifier: &str) -> RepositoryResult<(Rule, String)> {
+        if let Some(cached) = self.check_cache(&self.rule_cache, identifier).await {
+            return Ok(cached);
+        }
+
+        self.loads
+            .load(
+                &self.rule_cache,
+                &self.cache_config,
+                &self.stats,
+                "rule",
+                identifier,
+                || async {
+                    let content = self.load_content("rule", identifier).await?;
+                    let doc = RuleParser::parse_with_imports(&content)?;
+
+                    Ok((doc.definition, content))
+                },
+            )
+            .await
+    }
+
+    async fn load_ruleset(&self, identifier: &str) -> RepositoryResult<(Ruleset, String)> {
+        if let Some(cached) = self.check_cache(&self.ruleset_cache, identifier).await {
+            return Ok(cached);
+        }
+
+        self.loads
+            .load(
+                &self.ruleset_cache,
+                &self.cache_config,
+                &self.stats,
+                "ruleset",
+                identifier,
+                || async {
+                    let content = self.load_content("ruleset", identifier).await?;
+                    let doc = RulesetParser::parse_with_imports(&content)?;
+
+                    Ok((doc.definition, content))
+                },
+            )
+            .await
+    }
+
+    async fn load_pipeline(&self, identifier: &str) -> RepositoryResult<(Pipeline, String)> {
+        if let Some(cached) = self.check_cache(&self.pipeline_cache, identifier).await {
+            return Ok(cached);
+        }
+
+        self.loads
+            .load(
+                &self.pipeline_cache,
+                &self.cache_config,
+                &self.stats,
+                "pipeline",
+                identifier,
+                || async {
+                    let content = self.load_content("pipeline", identifier).await?;
+                    let doc = PipelineParser::parse_with_imports(&content)?;
+
+                    Ok((doc.definition, content))
+                },
+            )
+            .await
+    }
+
+    async fn exists(&self, identifier: &str) -> RepositoryResult<bool> {
+        for kind in ["rule", "ruleset", "pipeline"] {
+            let count = self
+                .artifacts(kind)
+                .count_documents(Self::identifier_filter(identifier), None)
+                .await?;
+            if count > 0 {
+                return Ok(true);
+            }
+        }
+
+        Ok(false)
+    }
+
+    async fn list_rules(&self) -> RepositoryResult<Vec<String>> {
+        self.list_ids("rule").await
+    }
+
+    async fn list_rulesets(&self) -> RepositoryResult<Vec<String>> {
+        self.list_ids("ruleset").await
+    }
+
+    async fn list_pipelines(&self) -> RepositoryResult<Vec<String>> {
+        self.list_ids("pipeline").await
+    }
+
+    async fn load_registry(&self) -> RepositoryResult<String> {
+        // A registry is only present once one has been imported from a bundle
+        let document = self
+            .configs()
+            .find_one(doc! { "config_type": "registry" }, None)
+            .await?
+            .ok_or_else(|| RepositoryError::NotFound {
+                path: "registry".to_string(),
+            })?;
+
+        get_string(&document, "content")
+    }
+
+    async fn export_bundle(&self) -> RepositoryResult<RepositoryBundle> {
+        let mut bundle = bundle::export_definitions(self).await?;
+
+        let documents: Vec<Document> = self
+            .configs()
+            .find(doc! { "config_type": { "$ne": "registry" } }, None)
+            .await?
+            .try_collect()
+            .await?;
+
+        for document in documents {
+            let config_type = get_string(&document, "config_type")?;
+            let kind = BundleArtifactKind::parse(&config_type).ok_or_else(|| {
+                RepositoryError::Other(format!("Unknown config type '{}'", config_type))
+            })?;
+            bundle.add_artifact(
+                kind,
+                get_string(&document, "id")?,
+                get_string(&document, "content")?,
+            );
+        }
+
+        Ok(bundle)
+    }
+}
+
+#[async_trait]
+impl CacheableRepository for MongoRepository {
+    fn clear_cache(&mut self) {
+        let rule_cache = Arc::clone(&self.rule_cache);
+        let ruleset_cache = Arc::clone(&self.ruleset_cache);
+        let pipeline_cache = Arc::clone(&self.pipeline_cache);
+
+        tokio::spawn(async move {
+            rule_cache.write().await.clear();
+            ruleset_cache.write().await.clear();
+            pipeline_cache.write().await.clear();
+        });
+
+        self.loads.clear();
+        self.stats.lock().unwrap().size = 0;
+    }
+
+    fn clear_cache_entry(&mut self, identifier: &str) {
+        let rule_cache = Arc::clone(&self.rule_cache);
+        let ruleset_cache = Arc::clone(&self.ruleset_cache);
+        let pipeline_cache = Arc::clone(&self.pipeline_cache);
+        self.loads.forget(identifier);
+        let id = identifier.to_string();
+
+        tokio::spawn(async move {
+            rule_cache.write().await.remove(&id);
+            ruleset_cache.write().await.remove(&id);
+            pipeline_cache.write().await.remove(&id);
+        });
+    }
+
+    fn cache_stats(&self) -> CacheStats {
+        self.stats.lock().unwrap().clone()
+    }
+
+    fn set_cache_enabled(&mut self, enabled: bool) {
+        self.cache_config.lock().unwrap().enabled = enabled;
+    }
+
+    fn is_cache_enabled(&self) -> bool {
+        self.cache_config.lock().unwrap().enabled
+    }
+
+    fn set_cache_config(&mut self, config: CacheConfig) {
+        *self.cache_config.lock().unwrap() = config;
+    }
+}
+
+#[async_trait]
+impl WritableRepository for MongoRepository {
+    async fn save_rule(&mut self, rule: &Rule) -> RepositoryResult<()> {
+        let content = serde_yaml::to_string(rule)
+            .map_err(|e| RepositoryError::Other(format!("Failed to serialize rule: {}", e)))?;
+
+        self.store_content("rule", &rule.id, &content, None, None)
+            .await?;
+        self.clear_cache_entry(&rule.id);
+
+        Ok(())
+    }
+
+    async fn save_ruleset(&mut self, ruleset: &Ruleset) -> RepositoryResult<()> {
+        let content = serde_yaml::to_string(ruleset)
+            .map_err(|e| RepositoryError::Other(format!("Failed to serialize ruleset: {}", e)))?;
+
+        self.store_content(
+            "ruleset",
+            &ruleset.id,
+            &content,
+            ruleset.extends.as_deref(),
+            None,
+        )
+        .await?;
+        self.clear_cache_entry(&ruleset.id);
+
+        Ok(())
+    }
+
+    async fn save_pipeline(&mut self, pipeline: &Pipeline) -> RepositoryResult<()> {
+        let content = serde_yaml::to_string(pipeline)
+            .map_err(|e| RepositoryError::Other(format!("Failed to serialize pipeline: {}", e)))?;
+
+        if pipeline.id.is_empty() {
+            return Err(RepositoryError::Other(
+                "Pipeline must have an id".to_string(),
+            ));
+        }
+
+        self.store_content("pipeline", &pipeline.id, &content, None, None)
+            .await?;
+        self.clear_cache_entry(&pipeline.id);
+
+        Ok(())
+    }
+
+    async fn delete_rule(&self, identifier: &str) -> RepositoryResult<()> {
+        self.delete_content("rule", identifier).await
+    }
+
+    async fn delete_ruleset(&self, identifier: &str) -> RepositoryResult<()> {
+        self.delete_content("ruleset", identifier).await
+    }
+
+    async fn delete_pipeline(&self, identifier: &str) -> RepositoryResult<()> {
+        self.delete_content("pipeline", identifier).await
+    }
+
+    async fn import_bundle(
+        &mut self,
+        bundle: &RepositoryBundle,
+        signing_key: Option<&[u8]>,
+    ) -> RepositoryResult<usize> {
+        bundle.verify(signing_key)?;
+        let definitions = bundle.parse_definitions()?;
+
+        // All artifacts are written in one transaction so a promotion either
+        // lands completely or not at all (requires a replica set or sharded cluster)
+        let mut session = self.client.start_session(None).await?;
+        session.start_transaction(None).await?;
+
+        for artifact in &bundle.artifacts {
+            if artifact.kind.is_definition() {
+                let extends = definitions
+                    .rulesets
+                    .iter()
+                    .find(|r| r.id == artifact.id)
+                    .and_then(|r| r.extends.clone());
+
+                self.store_content(
+                    artifact.kind.as_str(),
+                    &artifact.id,
+                    &artifact.content,
+                    extends.as_deref(),
+                    Some(&mut session),
+                )
+                .await?;
+            } else {
+                self.configs()
+                    .update_one_with_session(
+                        doc! { "config_type": artifact.kind.as_str(), "id": &artifact.id },
+                        doc! {
+                            "$set": { "content": &artifact.content, "updated_at": DateTime::now() },
+                            "$inc": { "version": 1_i32 },
+                        },
+                        UpdateOptions::builder().upsert(true).build(),
+                        &mut session,
+                    )
+                    .await?;
+            }
+        }
+
+        session.commit_transaction().await?;
+        self.clear_cache();
+
+        Ok(bundle.artifacts.len())
+    }
+}
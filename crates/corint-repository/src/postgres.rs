@@ -3,13 +3,25 @@
 use async_trait::async_trait;
 use corint_core::ast::{Pipeline, Rule, Ruleset};
 use corint_parser::{PipelineParser, RuleParser, RulesetParser};
-use sqlx::postgres::PgPool;
+use sqlx::postgres::{PgConnection, PgPool};
 use sqlx::Row;
 use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
+use std::time::Instant;
 use tokio::sync::RwLock;
 
-use crate::{error::RepositoryError, models::*, traits::*, CacheStats, RepositoryResult};
+use crate::{
+    access::{AuditEntry, AuditLog, AuditOperation, AuditQuery},
+    bundle::{self, BundleArtifactKind, RepositoryBundle},
+    cache::LoadCoordinator,
+    diff::ArtifactDiff,
+    error::RepositoryError,
+    migrations::{self, MigrationInfo, MigrationReport},
+    models::*,
+    sync::{ArtifactDrift, DriftKind, SyncReport, SyncableRepository},
+    traits::*,
+    CacheStats, RepositoryResult,
+};
 
 /// PostgreSQL database repository
 ///
@@ -27,6 +39,10 @@ pub struct PostgresRepository {
     cache_config: Arc<Mutex<CacheConfig>>,
     /// Cache statistics
     stats: Arc<Mutex<CacheStats>>,
+    /// Negative cache and single-flight gate for cache misses
+    loads: Arc<LoadCoordinator>,
+    /// Version of each cached artifact, keyed by `<kind>:<identifier>`
+    loaded_versions: Arc<Mutex<HashMap<String, i32>>>,
 }
 
 impl PostgresRepository {
@@ -47,15 +63,40 @@ impl PostgresRepository {
     /// ```
     pub async fn new(database_url: &str) -> RepositoryResult<Self> {
         let pool = PgPool::connect(database_url).await?;
+        migrations::run(&pool).await?;
+        Ok(Self::with_pool(pool))
+    }
 
-        Ok(Self {
-            pool,
-            rule_cache: Arc::new(RwLock::new(HashMap::new())),
-            ruleset_cache: Arc::new(RwLock::new(HashMap::new())),
-            pipeline_cache: Arc::new(RwLock::new(HashMap::new())),
-            cache_config: Arc::new(Mutex::new(CacheConfig::default())),
-            stats: Arc::new(Mutex::new(CacheStats::default())),
-        })
+    /// Create a new PostgreSQL repository without applying schema migrations
+    ///
+    /// For deployments that migrate the schema separately, e.g. with
+    /// [`PostgresRepository::migrate_only`].
+    pub async fn new_without_migrations(database_url: &str) -> RepositoryResult<Self> {
+        Ok(Self::with_pool(PgPool::connect(database_url).await?))
+    }
+
+    /// Apply pending schema migrations and return without creating a repository
+    ///
+    /// Meant for deploy jobs and init containers that upgrade the schema
+    /// before the application starts.
+    pub async fn migrate_only(database_url: &str) -> RepositoryResult<MigrationReport> {
+        let pool = PgPool::connect(database_url).await?;
+        let report = migrations::run(&pool).await;
+        pool.close().await;
+        report
+    }
+
+    /// Apply pending schema migrations to this repository's database
+    ///
+    /// Repositories created with [`PostgresRepository::with_pool`] are not
+    /// migrated automatically.
+    pub async fn migrate(&self) -> RepositoryResult<MigrationReport> {
+        migrations::run(&self.pool).await
+    }
+
+    /// Schema migrations not yet applied to this repository's database
+    pub async fn pending_migrations(&self) -> RepositoryResult<Vec<MigrationInfo>> {
+        migrations::pending(&self.pool).await
     }
 
     /// Create a new PostgreSQL repository with an existing pool
@@ -67,6 +108,8 @@ impl PostgresRepository {
             pipeline_cache: Arc::new(RwLock::new(HashMap::new())),
             cache_config: Arc::new(Mutex::new(CacheConfig::default())),
             stats: Arc::new(Mutex::new(CacheStats::default())),
+            loads: Arc::new(LoadCoordinator::default()),
+            loaded_versions: Arc::new(Mutex::new(HashMap::new())),
         }
     }
 
@@ -95,6 +138,7 @@ impl PostgresRepository {
         if let Some(cached) = cache_read.get(identifier) {
             if !cached.is_expired() {
                 // Cache hit
+                cached.touch();
                 self.stats.lock().unwrap().hits += 1;
                 return Some((cached.data.clone(), cached.content.clone()));
             }
@@ -105,161 +149,375 @@ impl PostgresRepository {
         None
     }
 
-    /// Store in cache
-    async fn store_in_cache<T: Clone>(
-        &self,
-        cache: &Arc<RwLock<HashMap<String, CachedArtifact<T>>>>,
-        identifier: &str,
-        data: T,
-        content: String,
-    ) {
-        let (enabled, ttl) = {
-            let config = self.cache_config.lock().unwrap();
-            (config.enabled, config.default_ttl)
-        };
-
-        if !enabled {
-            return;
-        }
-
-        let cached = CachedArtifact::new(data, content, ttl);
-        let mut cache_write = cache.write().await;
-        cache_write.insert(identifier.to_string(), cached);
+    /// Record a saved artifact in the version history table
+    ///
+    /// Runs on the connection of the save's transaction, so a version is
+    /// recorded exactly when the save commits.
+    async fn record_version(
+        conn: &mut PgConnection,
+        artifact_type: &str,
+        artifact_id: &str,
+        version: i32,
+        content: &str,
+    ) -> RepositoryResult<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO artifact_versions (artifact_type, artifact_id, version, content, created_at)
+            VALUES ($1, $2, $3, $4, NOW())
+            ON CONFLICT (artifact_type, artifact_id, version) DO NOTHING
+            "#,
+        )
+        .bind(artifact_type)
+        .bind(artifact_id)
+        .bind(version)
+        .bind(content)
+        .execute(conn)
+        .await?;
 
-        // Update stats
-        self.stats.lock().unwrap().size = cache_write.len();
+        Ok(())
     }
-}
 
-#[async_trait]
-impl Repository for PostgresRepository {
-    async fn load_rule(&self, identifier: &str) -> RepositoryResult<(Rule, String)> {
-        // Check cache first
-        if let Some(cached) = self.check_cache(&self.rule_cache, identifier).await {
-            return Ok(cached);
+    /// Table holding a kind of definition
+    fn definition_table(kind: BundleArtifactKind) -> RepositoryResult<&'static str> {
+        match kind {
+            BundleArtifactKind::Rule => Ok("rules"),
+            BundleArtifactKind::Ruleset => Ok("rulesets"),
+            BundleArtifactKind::Pipeline => Ok("pipelines"),
+            _ => Err(RepositoryError::Other(format!(
+                "{} artifacts have no lifecycle status",
+                kind.as_str()
+            ))),
         }
+    }
 
-        // Query database by ID or path
-        let row = sqlx::query(
+    /// Load the content of a specific artifact version
+    ///
+    /// Falls back to the live table for the current version, so artifacts saved
+    /// before the history table existed can still be compared against.
+    async fn load_version_content(
+        &self,
+        kind: BundleArtifactKind,
+        entity_id: &str,
+        version: i32,
+    ) -> RepositoryResult<String> {
+        let table = Self::definition_table(kind)?;
+        let row = sqlx::query(&format!(
             r#"
-            SELECT id, content, version, updated_at
-            FROM rules
-            WHERE id = $1 OR path = $1
-            ORDER BY version DESC
+            SELECT content, 0 AS source FROM artifact_versions
+            WHERE artifact_type = $3 AND artifact_id = $1 AND version = $2
+            UNION ALL
+            SELECT content, 1 AS source FROM {}
+            WHERE (id = $1 OR path = $1) AND version = $2 AND status <> 'draft'
+            ORDER BY source
             LIMIT 1
             "#,
-        )
-        .bind(identifier)
+            table
+        ))
+        .bind(entity_id)
+        .bind(version)
+        .bind(kind.as_str())
         .fetch_optional(&self.pool)
         .await?;
 
         let row = row.ok_or_else(|| RepositoryError::NotFound {
-            path: identifier.to_string(),
+            path: format!("{} {}@v{}", kind.as_str(), entity_id, version),
         })?;
 
-        let content: String = row.try_get("content")?;
+        Ok(row.try_get("content")?)
+    }
 
-        // Parse the YAML content
-        let doc = RuleParser::parse_with_imports(&content)?;
+    /// Compute a structural diff between two stored versions of a rule, ruleset, or pipeline
+    ///
+    /// Unlike a text diff, the result lists field-level changes (conditions, scores,
+    /// conclusions, ...) so reviewers can see what a change does before promoting it.
+    /// `kind` tells apart artifacts of different kinds that share an ID.
+    pub async fn diff_versions(
+        &self,
+        kind: BundleArtifactKind,
+        entity_id: &str,
+        v1: i32,
+        v2: i32,
+    ) -> RepositoryResult<ArtifactDiff> {
+        let from_content = self.load_version_content(kind, entity_id, v1).await?;
+        let to_content = self.load_version_content(kind, entity_id, v2).await?;
+
+        ArtifactDiff::between(entity_id, v1, &from_content, v2, &to_content)
+    }
 
-        // Store in cache
-        self.store_in_cache(
-            &self.rule_cache,
-            identifier,
-            doc.definition.clone(),
-            content.clone(),
-        )
-        .await;
+    /// Identifiers currently cached for a kind of definition
+    async fn cached_identifiers(&self, kind: BundleArtifactKind) -> Vec<String> {
+        match kind {
+            BundleArtifactKind::Rule => self.rule_cache.read().await.keys().cloned().collect(),
+            BundleArtifactKind::Ruleset => {
+                self.ruleset_cache.read().await.keys().cloned().collect()
+            }
+            _ => self.pipeline_cache.read().await.keys().cloned().collect(),
+        }
+    }
 
-        Ok((doc.definition, content))
+    /// Drop a cached definition and its recorded version
+    async fn evict(&self, kind: BundleArtifactKind, identifier: &str) {
+        match kind {
+            BundleArtifactKind::Rule => {
+                self.rule_cache.write().await.remove(identifier);
+            }
+            BundleArtifactKind::Ruleset => {
+                self.ruleset_cache.write().await.remove(identifier);
+            }
+            _ => {
+                self.pipeline_cache.write().await.remove(identifier);
+            }
+        }
+        self.loaded_versions
+            .lock()
+            .unwrap()
+            .remove(&format!("{}:{}", kind.as_str(), identifier));
     }
 
-    async fn load_ruleset(&self, identifier: &str) -> RepositoryResult<(Ruleset, String)> {
-        // Check cache first
-        if let Some(cached) = self.check_cache(&self.ruleset_cache, identifier).await {
-            return Ok(cached);
+    /// Compare the cached definitions of one kind against the published versions
+    async fn sync_kind(
+        &self,
+        kind: BundleArtifactKind,
+        report: &mut SyncReport,
+    ) -> RepositoryResult<()> {
+        let identifiers = self.cached_identifiers(kind).await;
+        if identifiers.is_empty() {
+            return Ok(());
         }
 
-        // Query database
-        let row = sqlx::query(
-            r#"
-            SELECT id, content, version, updated_at
-            FROM rulesets
-            WHERE id = $1 OR path = $1
-            ORDER BY version DESC
-            LIMIT 1
-            "#,
-        )
-        .bind(identifier)
-        .fetch_optional(&self.pool)
+        let table = Self::definition_table(kind)?;
+        let rows = sqlx::query(&format!(
+            "SELECT id, path, version FROM {} WHERE status = 'published'",
+            table
+        ))
+        .fetch_all(&self.pool)
         .await?;
 
-        let row = row.ok_or_else(|| RepositoryError::NotFound {
-            path: identifier.to_string(),
-        })?;
+        // Latest published version, reachable by ID or path
+        let mut published: HashMap<String, i32> = HashMap::new();
+        for row in rows {
+            let version: i32 = row.try_get("version")?;
+            let id: String = row.try_get("id")?;
+            let path: Option<String> = row.try_get("path")?;
+            for key in std::iter::once(id).chain(path) {
+                let entry = published.entry(key).or_insert(version);
+                *entry = (*entry).max(version);
+            }
+        }
 
-        let content: String = row.try_get("content")?;
+        for identifier in identifiers {
+            report.checked += 1;
+            let loaded = self
+                .loaded_versions
+                .lock()
+                .unwrap()
+                .get(&format!("{}:{}", kind.as_str(), identifier))
+                .copied();
+            let remote = published.get(&identifier).copied();
+            if remote.is_some() && remote == loaded {
+                continue;
+            }
 
-        // Parse the YAML content
-        let doc = RulesetParser::parse_with_imports(&content)?;
+            report.drifted.push(ArtifactDrift {
+                kind,
+                identifier: identifier.clone(),
+                drift: if remote.is_some() {
+                    DriftKind::Changed
+                } else {
+                    DriftKind::Removed
+                },
+                loaded_version: loaded.map(|v| v.to_string()),
+                remote_version: remote.map(|v| v.to_string()),
+            });
+            self.evict(kind, &identifier).await;
+            if remote.is_none() {
+                continue;
+            }
 
-        // Store in cache
-        self.store_in_cache(
-            &self.ruleset_cache,
-            identifier,
-            doc.definition.clone(),
-            content.clone(),
-        )
-        .await;
+            let reloaded = match kind {
+                BundleArtifactKind::Rule => self.load_rule(&identifier).await.map(|_| ()),
+                BundleArtifactKind::Ruleset => self.load_ruleset(&identifier).await.map(|_| ()),
+                _ => self.load_pipeline(&identifier).await.map(|_| ()),
+            };
+            match reloaded {
+                Ok(()) => report.refreshed += 1,
+                Err(e) => report
+                    .errors
+                    .push(format!("{} {}: {}", kind.as_str(), identifier, e)),
+            }
+        }
 
-        Ok((doc.definition, content))
+        Ok(())
     }
+}
 
-    async fn load_pipeline(&self, identifier: &str) -> RepositoryResult<(Pipeline, String)> {
+#[async_trait]
+impl SyncableRepository for PostgresRepository {
+    async fn sync(&self) -> RepositoryResult<SyncReport> {
+        let start = Instant::now();
+        let mut report = SyncReport::default();
+        for kind in [
+            BundleArtifactKind::Pipeline,
+            BundleArtifactKind::Ruleset,
+            BundleArtifactKind::Rule,
+        ] {
+            self.sync_kind(kind, &mut report).await?;
+        }
+        report.elapsed = start.elapsed();
+        Ok(report)
+    }
+}
+
+#[async_trait]
+impl Repository for PostgresRepository {
+    async fn load_rule(&self, identifier: &str) -> RepositoryResult<(Rule, String)> {
         // Check cache first
-        if let Some(cached) = self.check_cache(&self.pipeline_cache, identifier).await {
+        if let Some(cached) = self.check_cache(&self.rule_cache, identifier).await {
             return Ok(cached);
         }
 
-        // Query database
-        let row = sqlx::query(
-            r#"
-            SELECT id, content, version, updated_at
-            FROM pipelines
-            WHERE id = $1 OR path = $1
-            ORDER BY version DESC
-            LIMIT 1
-            "#,
-        )
-        .bind(identifier)
-        .fetch_optional(&self.pool)
-        .await?;
-
-        let row = row.ok_or_else(|| RepositoryError::NotFound {
-            path: identifier.to_string(),
-        })?;
+        self.loads
+            .load(
+                &self.rule_cache,
+                &self.cache_config,
+                &self.stats,
+                "rule",
+                identifier,
+                || async {
+                    // Query database by ID or path
+                    let row = sqlx::query(
+                        r#"
+                        SELECT id, content, version, updated_at
+                        FROM rules
+                        WHERE (id = $1 OR path = $1) AND status = 'published'
+                        ORDER BY version DESC
+                        LIMIT 1
+                        "#,
+                    )
+                    .bind(identifier)
+                    .fetch_optional(&self.pool)
+                    .await?;
+
+                    let row = row.ok_or_else(|| RepositoryError::NotFound {
+                        path: identifier.to_string(),
+                    })?;
+
+                    let content: String = row.try_get("content")?;
+                    let version: i32 = row.try_get("version")?;
+
+                    // Parse the YAML content
+                    let doc = RuleParser::parse_with_imports(&content)?;
+                    self.loaded_versions
+                        .lock()
+                        .unwrap()
+                        .insert(format!("rule:{}", identifier), version);
+
+                    Ok((doc.definition, content))
+                },
+            )
+            .await
+    }
 
-        let content: String = row.try_get("content")?;
+    async fn load_ruleset(&self, identifier: &str) -> RepositoryResult<(Ruleset, String)> {
+        // Check cache first
+        if let Some(cached) = self.check_cache(&self.ruleset_cache, identifier).await {
+            return Ok(cached);
+        }
 
-        // Parse the YAML content
-        let doc = PipelineParser::parse_with_imports(&content)?;
+        self.loads
+            .load(
+                &self.ruleset_cache,
+                &self.cache_config,
+                &self.stats,
+                "ruleset",
+                identifier,
+                || async {
+                    // Query database
+                    let row = sqlx::query(
+                        r#"
+                        SELECT id, content, version, updated_at
+                        FROM rulesets
+                        WHERE (id = $1 OR path = $1) AND status = 'published'
+                        ORDER BY version DESC
+                        LIMIT 1
+                        "#,
+                    )
+                    .bind(identifier)
+                    .fetch_optional(&self.pool)
+                    .await?;
+
+                    let row = row.ok_or_else(|| RepositoryError::NotFound {
+                        path: identifier.to_string(),
+                    })?;
+
+                    let content: String = row.try_get("content")?;
+                    let version: i32 = row.try_get("version")?;
+
+                    // Parse the YAML content
+                    let doc = RulesetParser::parse_with_imports(&content)?;
+                    self.loaded_versions
+                        .lock()
+                        .unwrap()
+                        .insert(format!("ruleset:{}", identifier), version);
+
+                    Ok((doc.definition, content))
+                },
+            )
+            .await
+    }
 
-        // Store in cache
-        self.store_in_cache(
-            &self.pipeline_cache,
-            identifier,
-            doc.definition.clone(),
-            content.clone(),
-        )
-        .await;
+    async fn load_pipeline(&self, identifier: &str) -> RepositoryResult<(Pipeline, String)> {
+        // Check cache first
+        if let Some(cached) = self.check_cache(&self.pipeline_cache, identifier).await {
+            return Ok(cached);
+        }
 
-        Ok((doc.definition, content))
+        self.loads
+            .load(
+                &self.pipeline_cache,
+                &self.cache_config,
+                &self.stats,
+                "pipeline",
+                identifier,
+                || async {
+                    // Query database
+                    let row = sqlx::query(
+                        r#"
+                        SELECT id, content, version, updated_at
+                        FROM pipelines
+                        WHERE (id = $1 OR path = $1) AND status = 'published'
+                        ORDER BY version DESC
+                        LIMIT 1
+                        "#,
+                    )
+                    .bind(identifier)
+                    .fetch_optional(&self.pool)
+                    .await?;
+
+                    let row = row.ok_or_else(|| RepositoryError::NotFound {
+                        path: identifier.to_string(),
+                    })?;
+
+                    let content: String = row.try_get("content")?;
+                    let version: i32 = row.try_get("version")?;
+
+                    // Parse the YAML content
+                    let doc = PipelineParser::parse_with_imports(&content)?;
+                    self.loaded_versions
+                        .lock()
+                        .unwrap()
+                        .insert(format!("pipeline:{}", identifier), version);
+
+                    Ok((doc.definition, content))
+                },
+            )
+            .await
     }
 
     async fn exists(&self, identifier: &str) -> RepositoryResult<bool> {
         // Check all tables for the identifier
         let rule_exists: bool = sqlx::query(
-            r#"SELECT EXISTS(SELECT 1 FROM rules WHERE id = $1 OR path = $1) as exists"#,
+            r#"SELECT EXISTS(SELECT 1 FROM rules WHERE (id = $1 OR path = $1) AND status = 'published') as exists"#,
         )
         .bind(identifier)
         .fetch_one(&self.pool)
@@ -271,7 +529,7 @@ impl Repository for PostgresRepository {
         }
 
         let ruleset_exists: bool = sqlx::query(
-            r#"SELECT EXISTS(SELECT 1 FROM rulesets WHERE id = $1 OR path = $1) as exists"#,
+            r#"SELECT EXISTS(SELECT 1 FROM rulesets WHERE (id = $1 OR path = $1) AND status = 'published') as exists"#,
         )
         .bind(identifier)
         .fetch_one(&self.pool)
@@ -283,7 +541,7 @@ impl Repository for PostgresRepository {
         }
 
         let pipeline_exists: bool = sqlx::query(
-            r#"SELECT EXISTS(SELECT 1 FROM pipelines WHERE id = $1 OR path = $1) as exists"#,
+            r#"SELECT EXISTS(SELECT 1 FROM pipelines WHERE (id = $1 OR path = $1) AND status = 'published') as exists"#,
         )
         .bind(identifier)
         .fetch_one(&self.pool)
@@ -298,6 +556,7 @@ impl Repository for PostgresRepository {
             r#"
             SELECT COALESCE(path, id) as identifier
             FROM rules
+            WHERE status = 'published'
             ORDER BY updated_at DESC
             "#,
         )
@@ -315,6 +574,7 @@ impl Repository for PostgresRepository {
             r#"
             SELECT COALESCE(path, id) as identifier
             FROM rulesets
+            WHERE status = 'published'
             ORDER BY updated_at DESC
             "#,
         )
@@ -332,6 +592,7 @@ impl Repository for PostgresRepository {
             r#"
             SELECT COALESCE(path, id) as identifier
             FROM pipelines
+            WHERE status = 'published'
             ORDER BY updated_at DESC
             "#,
         )
@@ -345,11 +606,63 @@ impl Repository for PostgresRepository {
     }
 
     async fn load_registry(&self) -> RepositoryResult<String> {
-        // PostgreSQL repository doesn't support registry yet
-        // Registry is typically stored in filesystem
-        Err(RepositoryError::Other(
-            "Registry is not supported in PostgreSQL repository. Use FileSystem repository for registry configuration.".to_string()
+        // A registry is only present once one has been imported from a bundle
+        let row = sqlx::query(
+            r#"SELECT content FROM repository_configs WHERE config_type = 'registry' ORDER BY id LIMIT 1"#,
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        match row {
+            Some(row) => Ok(row.try_get("content")?),
+            None => Err(RepositoryError::Other(
+                "Registry is not supported in PostgreSQL repository. Use FileSystem repository for registry configuration.".to_string()
+            )),
+        }
+    }
+
+    async fn export_bundle(&self) -> RepositoryResult<RepositoryBundle> {
+        let mut bundle = bundle::export_definitions(self).await?;
+
+        let rows = sqlx::query(
+            r#"SELECT config_type, id, content FROM repository_configs WHERE config_type <> 'registry'"#,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        for row in rows {
+            let config_type: String = row.try_get("config_type")?;
+            let kind = BundleArtifactKind::parse(&config_type).ok_or_else(|| {
+                RepositoryError::Other(format!("Unknown config type '{}'", config_type))
+            })?;
+            let id: String = row.try_get("id")?;
+            let content: String = row.try_get("content")?;
+            bundle.add_artifact(kind, id, content);
+        }
+
+        Ok(bundle)
+    }
+
+    async fn load_draft(
+        &self,
+        kind: BundleArtifactKind,
+        identifier: &str,
+    ) -> RepositoryResult<String> {
+        let table = Self::definition_table(kind)?;
+
+        let row = sqlx::query(&format!(
+            "SELECT content FROM {} WHERE (id = $1 OR path = $1) AND status = 'draft'",
+            table
         ))
+        .bind(identifier)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        let row = row.ok_or_else(|| RepositoryError::NotFound {
+            path: format!("{} draft '{}'", kind.as_str(), identifier),
+        })?;
+
+        Ok(row.try_get("content")?)
     }
 }
 
@@ -366,6 +679,8 @@ impl CacheableRepository for PostgresRepository {
             pipeline_cache.write().await.clear();
         });
 
+        self.loads.clear();
+        self.loaded_versions.lock().unwrap().clear();
         self.stats.lock().unwrap().size = 0;
     }
 
@@ -373,6 +688,7 @@ impl CacheableRepository for PostgresRepository {
         let rule_cache = Arc::clone(&self.rule_cache);
         let ruleset_cache = Arc::clone(&self.ruleset_cache);
         let pipeline_cache = Arc::clone(&self.pipeline_cache);
+        self.loads.forget(identifier);
         let id = identifier.to_string();
 
         tokio::spawn(async move {
@@ -393,6 +709,10 @@ impl CacheableRepository for PostgresRepository {
     fn is_cache_enabled(&self) -> bool {
         self.cache_config.lock().unwrap().enabled
     }
+
+    fn set_cache_config(&mut self, config: CacheConfig) {
+        *self.cache_config.lock().unwrap() = config;
+    }
 }
 
 #[async_trait]
@@ -401,18 +721,27 @@ impl WritableRepository for PostgresRepository {
         let content = serde_yaml::to_string(rule)
             .map_err(|e| RepositoryError::Other(format!("Failed to serialize rule: {}", e)))?;
 
-        sqlx::query(
+        // The definition and its history entry are written together
+        let mut tx = self.pool.begin().await?;
+        let version: i32 = sqlx::query(
             r#"
             INSERT INTO rules (id, content, version, updated_at)
-            VALUES ($1, $2, 1, NOW())
-            ON CONFLICT (id) DO UPDATE
-            SET content = $2, version = rules.version + 1, updated_at = NOW()
+            VALUES ($1, $2, (
+                SELECT COALESCE(MAX(version), 0) + 1 FROM rules WHERE id = $1 AND status <> 'draft'
+            ), NOW())
+            ON CONFLICT (id, status) DO UPDATE
+            SET content = $2, version = EXCLUDED.version, updated_at = NOW()
+            RETURNING version
             "#,
         )
         .bind(&rule.id)
         .bind(&content)
-        .execute(&self.pool)
-        .await?;
+        .fetch_one(&mut *tx)
+        .await?
+        .try_get("version")?;
+
+        Self::record_version(&mut tx, "rule", &rule.id, version, &content).await?;
+        tx.commit().await?;
 
         // Clear cache for this rule
         self.clear_cache_entry(&rule.id);
@@ -424,19 +753,28 @@ impl WritableRepository for PostgresRepository {
         let content = serde_yaml::to_string(ruleset)
             .map_err(|e| RepositoryError::Other(format!("Failed to serialize ruleset: {}", e)))?;
 
-        sqlx::query(
+        // The definition and its history entry are written together
+        let mut tx = self.pool.begin().await?;
+        let version: i32 = sqlx::query(
             r#"
             INSERT INTO rulesets (id, content, version, extends, updated_at)
-            VALUES ($1, $2, 1, $3, NOW())
-            ON CONFLICT (id) DO UPDATE
-            SET content = $2, version = rulesets.version + 1, extends = $3, updated_at = NOW()
+            VALUES ($1, $2, (
+                SELECT COALESCE(MAX(version), 0) + 1 FROM rulesets WHERE id = $1 AND status <> 'draft'
+            ), $3, NOW())
+            ON CONFLICT (id, status) DO UPDATE
+            SET content = $2, version = EXCLUDED.version, extends = $3, updated_at = NOW()
+            RETURNING version
             "#,
         )
         .bind(&ruleset.id)
         .bind(&content)
         .bind(&ruleset.extends)
-        .execute(&self.pool)
-        .await?;
+        .fetch_one(&mut *tx)
+        .await?
+        .try_get("version")?;
+
+        Self::record_version(&mut tx, "ruleset", &ruleset.id, version, &content).await?;
+        tx.commit().await?;
 
         // Clear cache for this ruleset
         self.clear_cache_entry(&ruleset.id);
@@ -448,23 +786,34 @@ impl WritableRepository for PostgresRepository {
         let content = serde_yaml::to_string(pipeline)
             .map_err(|e| RepositoryError::Other(format!("Failed to serialize pipeline: {}", e)))?;
 
-        let pipeline_id = pipeline
-            .id
-            .as_ref()
-            .ok_or_else(|| RepositoryError::Other("Pipeline must have an id".to_string()))?;
+        if pipeline.id.is_empty() {
+            return Err(RepositoryError::Other(
+                "Pipeline must have an id".to_string(),
+            ));
+        }
+        let pipeline_id = pipeline.id.as_str();
 
-        sqlx::query(
+        // The definition and its history entry are written together
+        let mut tx = self.pool.begin().await?;
+        let version: i32 = sqlx::query(
             r#"
             INSERT INTO pipelines (id, content, version, updated_at)
-            VALUES ($1, $2, 1, NOW())
-            ON CONFLICT (id) DO UPDATE
-            SET content = $2, version = pipelines.version + 1, updated_at = NOW()
+            VALUES ($1, $2, (
+                SELECT COALESCE(MAX(version), 0) + 1 FROM pipelines WHERE id = $1 AND status <> 'draft'
+            ), NOW())
+            ON CONFLICT (id, status) DO UPDATE
+            SET content = $2, version = EXCLUDED.version, updated_at = NOW()
+            RETURNING version
             "#,
         )
         .bind(pipeline_id)
         .bind(&content)
-        .execute(&self.pool)
-        .await?;
+        .fetch_one(&mut *tx)
+        .await?
+        .try_get("version")?;
+
+        Self::record_version(&mut tx, "pipeline", pipeline_id, version, &content).await?;
+        tx.commit().await?;
 
         // Clear cache for this pipeline
         self.clear_cache_entry(pipeline_id);
@@ -498,4 +847,374 @@ impl WritableRepository for PostgresRepository {
 
         Ok(())
     }
+
+    async fn import_bundle(
+        &mut self,
+        bundle: &RepositoryBundle,
+        signing_key: Option<&[u8]>,
+    ) -> RepositoryResult<usize> {
+        bundle.verify(signing_key)?;
+        let definitions = bundle.parse_definitions()?;
+
+        // All artifacts are written in one transaction so a promotion either
+        // lands completely or not at all
+        let mut tx = self.pool.begin().await?;
+
+        for artifact in &bundle.artifacts {
+            let version: i32 = match artifact.kind {
+                BundleArtifactKind::Rule => {
+                    sqlx::query(
+                        r#"
+                        INSERT INTO rules (id, content, version, updated_at)
+                        VALUES ($1, $2, (
+                            SELECT COALESCE(MAX(version), 0) + 1 FROM rules WHERE id = $1 AND status <> 'draft'
+                        ), NOW())
+                        ON CONFLICT (id, status) DO UPDATE
+                        SET content = $2, version = EXCLUDED.version, updated_at = NOW()
+                        RETURNING version
+                        "#,
+                    )
+                    .bind(&artifact.id)
+                    .bind(&artifact.content)
+                    .fetch_one(&mut *tx)
+                    .await?
+                    .try_get("version")?
+                }
+                BundleArtifactKind::Ruleset => {
+                    let extends = definitions
+                        .rulesets
+                        .iter()
+                        .find(|r| r.id == artifact.id)
+                        .and_then(|r| r.extends.clone());
+
+                    sqlx::query(
+                        r#"
+                        INSERT INTO rulesets (id, content, version, extends, updated_at)
+                        VALUES ($1, $2, (
+                            SELECT COALESCE(MAX(version), 0) + 1 FROM rulesets WHERE id = $1 AND status <> 'draft'
+                        ), $3, NOW())
+                        ON CONFLICT (id, status) DO UPDATE
+                        SET content = $2, version = EXCLUDED.version, extends = $3, updated_at = NOW()
+                        RETURNING version
+                        "#,
+                    )
+                    .bind(&artifact.id)
+                    .bind(&artifact.content)
+                    .bind(extends)
+                    .fetch_one(&mut *tx)
+                    .await?
+                    .try_get("version")?
+                }
+                BundleArtifactKind::Pipeline => {
+                    sqlx::query(
+                        r#"
+                        INSERT INTO pipelines (id, content, version, updated_at)
+                        VALUES ($1, $2, (
+                            SELECT COALESCE(MAX(version), 0) + 1 FROM pipelines WHERE id = $1 AND status <> 'draft'
+                        ), NOW())
+                        ON CONFLICT (id, status) DO UPDATE
+                        SET content = $2, version = EXCLUDED.version, updated_at = NOW()
+                        RETURNING version
+                        "#,
+                    )
+                    .bind(&artifact.id)
+                    .bind(&artifact.content)
+                    .fetch_one(&mut *tx)
+                    .await?
+                    .try_get("version")?
+                }
+                _ => {
+                    sqlx::query(
+                        r#"
+                        INSERT INTO repository_configs (config_type, id, content, version, updated_at)
+                        VALUES ($1, $2, $3, 1, NOW())
+                        ON CONFLICT (config_type, id) DO UPDATE
+                        SET content = $3, version = repository_configs.version + 1, updated_at = NOW()
+                        "#,
+                    )
+                    .bind(artifact.kind.as_str())
+                    .bind(&artifact.id)
+                    .bind(&artifact.content)
+                    .execute(&mut *tx)
+                    .await?;
+                    continue;
+                }
+            };
+
+            Self::record_version(
+                &mut tx,
+                artifact.kind.as_str(),
+                &artifact.id,
+                version,
+                &artifact.content,
+            )
+            .await?;
+        }
+
+        tx.commit().await?;
+        self.clear_cache();
+
+        Ok(bundle.artifacts.len())
+    }
+
+    async fn save_draft(
+        &mut self,
+        kind: BundleArtifactKind,
+        id: &str,
+        content: &str,
+    ) -> RepositoryResult<()> {
+        let table = Self::definition_table(kind)?;
+
+        // Drafts must parse and carry the ID they are saved under
+        let (parsed_id, extends) = match kind {
+            BundleArtifactKind::Rule => (RuleParser::parse_with_imports(content)?.definition.id, None),
+            BundleArtifactKind::Ruleset => {
+                let ruleset = RulesetParser::parse_with_imports(content)?.definition;
+                (ruleset.id, ruleset.extends)
+            }
+            _ => (PipelineParser::parse_with_imports(content)?.definition.id, None),
+        };
+        if parsed_id != id {
+            return Err(RepositoryError::Other(format!(
+                "Draft content defines '{}' but was saved as '{}'",
+                parsed_id, id
+            )));
+        }
+
+        sqlx::query(&format!(
+            r#"
+            INSERT INTO {table} (id, content, version, status, updated_at)
+            VALUES ($1, $2, 1, 'draft', NOW())
+            ON CONFLICT (id, status) DO UPDATE
+            SET content = $2, version = {table}.version + 1, updated_at = NOW()
+            "#,
+            table = table
+        ))
+        .bind(id)
+        .bind(content)
+        .execute(&self.pool)
+        .await?;
+
+        if kind == BundleArtifactKind::Ruleset {
+            sqlx::query(r#"UPDATE rulesets SET extends = $2 WHERE id = $1 AND status = 'draft'"#)
+                .bind(id)
+                .bind(extends)
+                .execute(&self.pool)
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    async fn set_status(
+        &mut self,
+        kind: BundleArtifactKind,
+        identifier: &str,
+        status: ArtifactStatus,
+    ) -> RepositoryResult<()> {
+        let table = Self::definition_table(kind)?;
+        let artifact = format!("{} '{}'", kind.as_str(), identifier);
+        let transition_error = |reason: &str| RepositoryError::InvalidStatusTransition {
+            artifact: artifact.clone(),
+            reason: reason.to_string(),
+        };
+
+        let mut tx = self.pool.begin().await?;
+
+        let rows = sqlx::query(&format!(
+            "SELECT id, status FROM {} WHERE id = $1 OR path = $1",
+            table
+        ))
+        .bind(identifier)
+        .fetch_all(&mut *tx)
+        .await?;
+        if rows.is_empty() {
+            return Err(RepositoryError::NotFound {
+                path: artifact.clone(),
+            });
+        }
+
+        let id: String = rows[0].try_get("id")?;
+        let mut current = Vec::with_capacity(rows.len());
+        for row in &rows {
+            let name: String = row.try_get("status")?;
+            current.extend(ArtifactStatus::parse(&name));
+        }
+        let has = |status: ArtifactStatus| current.contains(&status);
+
+        // Moves the published version (if any) to archived, replacing the old archive
+        let archive_published = [
+            format!(
+                "DELETE FROM {table} WHERE id = $1 AND status = 'archived' AND EXISTS \
+                 (SELECT 1 FROM {table} WHERE id = $1 AND status = 'published')",
+                table = table
+            ),
+            format!(
+                "UPDATE {} SET status = 'archived', updated_at = NOW() \
+                 WHERE id = $1 AND status = 'published'",
+                table
+            ),
+        ];
+
+        match status {
+            ArtifactStatus::Draft => {
+                return Err(transition_error("drafts are created with save_draft"));
+            }
+            ArtifactStatus::Archived => {
+                if !has(ArtifactStatus::Published) {
+                    return Err(transition_error("there is no published version"));
+                }
+                for statement in &archive_published {
+                    sqlx::query(statement).bind(&id).execute(&mut *tx).await?;
+                }
+            }
+            ArtifactStatus::Published if has(ArtifactStatus::Draft) => {
+                let version: i32 = sqlx::query(&format!(
+                    "SELECT COALESCE(MAX(version), 0) + 1 AS next_version FROM {} \
+                     WHERE id = $1 AND status <> 'draft'",
+                    table
+                ))
+                .bind(&id)
+                .fetch_one(&mut *tx)
+                .await?
+                .try_get("next_version")?;
+
+                for statement in &archive_published {
+                    sqlx::query(statement).bind(&id).execute(&mut *tx).await?;
+                }
+
+                let content: String = sqlx::query(&format!(
+                    "UPDATE {} SET status = 'published', version = $2, updated_at = NOW() \
+                     WHERE id = $1 AND status = 'draft' RETURNING content",
+                    table
+                ))
+                .bind(&id)
+                .bind(version)
+                .fetch_one(&mut *tx)
+                .await?
+                .try_get("content")?;
+
+                sqlx::query(
+                    r#"
+                    INSERT INTO artifact_versions (artifact_type, artifact_id, version, content, created_at)
+                    VALUES ($1, $2, $3, $4, NOW())
+                    ON CONFLICT (artifact_type, artifact_id, version) DO NOTHING
+                    "#,
+                )
+                .bind(kind.as_str())
+                .bind(&id)
+                .bind(version)
+                .bind(&content)
+                .execute(&mut *tx)
+                .await?;
+            }
+            ArtifactStatus::Published => {
+                if has(ArtifactStatus::Published) {
+                    return Err(transition_error("already published and there is no draft"));
+                }
+                if !has(ArtifactStatus::Archived) {
+                    return Err(transition_error("there is no draft or archived version"));
+                }
+                sqlx::query(&format!(
+                    "UPDATE {} SET status = 'published', updated_at = NOW() \
+                     WHERE id = $1 AND status = 'archived'",
+                    table
+                ))
+                .bind(&id)
+                .execute(&mut *tx)
+                .await?;
+            }
+        }
+
+        tx.commit().await?;
+
+        self.clear_cache_entry(identifier);
+        self.clear_cache_entry(&id);
+
+        Ok(())
+    }
+}
+
+/// Audit log stored in the `artifact_audit_log` table
+///
+/// Used with [`AuthorizedRepository`](crate::AuthorizedRepository) to record the
+/// actor behind every change in `changed_by`.
+#[derive(Clone)]
+pub struct PostgresAuditLog {
+    pool: PgPool,
+}
+
+impl PostgresAuditLog {
+    /// Connect to the database holding the audit table
+    pub async fn new(database_url: &str) -> RepositoryResult<Self> {
+        Ok(Self::with_pool(PgPool::connect(database_url).await?))
+    }
+
+    /// Create an audit log over an existing pool
+    pub fn with_pool(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl AuditLog for PostgresAuditLog {
+    async fn record(&self, entry: AuditEntry) -> RepositoryResult<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO artifact_audit_log (artifact_type, artifact_id, operation, new_content, changed_by, changed_at)
+            VALUES ($1, $2, $3, $4, $5, to_timestamp($6))
+            "#,
+        )
+        .bind(&entry.artifact_type)
+        .bind(&entry.artifact_id)
+        .bind(entry.operation.as_str())
+        .bind(&entry.content)
+        .bind(&entry.actor)
+        .bind(entry.changed_at as f64)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn query(&self, query: &AuditQuery) -> RepositoryResult<Vec<AuditEntry>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT artifact_type, artifact_id, operation, new_content, changed_by,
+                   EXTRACT(EPOCH FROM changed_at)::BIGINT AS changed_at
+            FROM artifact_audit_log
+            WHERE ($1::TEXT IS NULL OR artifact_type = $1)
+              AND ($2::TEXT IS NULL OR artifact_id = $2)
+              AND ($3::TEXT IS NULL OR changed_by = $3)
+              AND ($4::BIGINT IS NULL OR changed_at >= to_timestamp($4))
+            ORDER BY changed_at DESC, id DESC
+            LIMIT $5
+            "#,
+        )
+        .bind(&query.artifact_type)
+        .bind(&query.artifact_id)
+        .bind(&query.actor)
+        .bind(query.since)
+        .bind(query.limit.map(|l| l as i64))
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter()
+            .map(|row| {
+                let operation: String = row.try_get("operation")?;
+                Ok(AuditEntry {
+                    artifact_type: row.try_get("artifact_type")?,
+                    artifact_id: row.try_get("artifact_id")?,
+                    operation: AuditOperation::parse(&operation).ok_or_else(|| {
+                        RepositoryError::Other(format!("Unknown audit operation '{}'", operation))
+                    })?,
+                    actor: row
+                        .try_get::<Option<String>, _>("changed_by")?
+                        .unwrap_or_default(),
+                    changed_at: row.try_get("changed_at")?,
+                    content: row.try_get("new_content")?,
+                })
+            })
+            .collect()
+    }
 }
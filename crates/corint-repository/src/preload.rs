@@ -0,0 +1,211 @@
+//! Cache warm-up
+//!
+//! [`Repository::preload_all`] and [`Repository::warm_cache`] load and parse
+//! pipelines, rulesets and rules ahead of traffic, so the first production
+//! request doesn't pay for the I/O and parsing. Artifacts referenced by a
+//! preloaded pipeline or ruleset (rulesets, rules, sub-pipelines and parent
+//! rulesets) are loaded by ID as well, since that is how they are looked up
+//! when the pipeline runs.
+//!
+//! Artifacts that fail to load are reported in the manifest rather than
+//! aborting the warm-up; a broken rule should not keep the rest of the cache
+//! cold.
+
+use corint_core::ast::pipeline::StepDetails;
+use corint_core::ast::{Pipeline, Ruleset};
+use serde::Serialize;
+use std::collections::{BTreeSet, VecDeque};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use crate::{access::matches_pattern, traits::Repository, BundleArtifactKind, RepositoryResult};
+
+/// Artifact that could not be preloaded
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PreloadFailure {
+    /// Artifact kind
+    pub kind: BundleArtifactKind,
+
+    /// Identifier the load was attempted with
+    pub identifier: String,
+
+    /// Error message
+    pub error: String,
+}
+
+/// Manifest of the artifacts loaded by a warm-up
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct PreloadManifest {
+    /// IDs of preloaded pipelines
+    pub pipelines: Vec<String>,
+
+    /// IDs of preloaded rulesets
+    pub rulesets: Vec<String>,
+
+    /// IDs of preloaded rules
+    pub rules: Vec<String>,
+
+    /// Artifacts that failed to load
+    pub failed: Vec<PreloadFailure>,
+
+    /// Time the warm-up took
+    pub elapsed: Duration,
+}
+
+impl PreloadManifest {
+    /// Number of artifacts loaded
+    pub fn loaded_count(&self) -> usize {
+        self.pipelines.len() + self.rulesets.len() + self.rules.len()
+    }
+
+    /// Whether every selected artifact loaded successfully
+    pub fn is_complete(&self) -> bool {
+        self.failed.is_empty()
+    }
+}
+
+/// Whether a listed identifier is selected by the patterns
+///
+/// Patterns match either the full identifier or, for file paths, the file
+/// name without extension; no patterns select everything.
+fn selected(patterns: &[&str], identifier: &str) -> bool {
+    let stem = Path::new(identifier)
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or(identifier);
+    patterns.is_empty()
+        || patterns
+            .iter()
+            .any(|p| matches_pattern(p, identifier) || matches_pattern(p, stem))
+}
+
+#[derive(Default)]
+struct Warmup {
+    manifest: PreloadManifest,
+    /// Artifacts recorded in the manifest, by ID
+    recorded: BTreeSet<(BundleArtifactKind, String)>,
+    /// Identifiers a load was already attempted with
+    attempted: BTreeSet<(BundleArtifactKind, String)>,
+    /// Referenced artifacts still to load, by ID
+    queue: VecDeque<(BundleArtifactKind, String)>,
+}
+
+impl Warmup {
+    fn fail(&mut self, kind: BundleArtifactKind, identifier: &str, error: impl ToString) {
+        self.manifest.failed.push(PreloadFailure {
+            kind,
+            identifier: identifier.to_string(),
+            error: error.to_string(),
+        });
+    }
+
+    fn record(&mut self, kind: BundleArtifactKind, id: String) {
+        if !self.recorded.insert((kind, id.clone())) {
+            return;
+        }
+        match kind {
+            BundleArtifactKind::Pipeline => self.manifest.pipelines.push(id),
+            BundleArtifactKind::Ruleset => self.manifest.rulesets.push(id),
+            _ => self.manifest.rules.push(id),
+        }
+    }
+
+    fn reference(&mut self, kind: BundleArtifactKind, id: &str) {
+        self.queue.push_back((kind, id.to_string()));
+    }
+
+    fn pipeline_references(&mut self, pipeline: &Pipeline) {
+        for step in &pipeline.steps {
+            match &step.details {
+                StepDetails::Rule { rule } => self.reference(BundleArtifactKind::Rule, rule),
+                StepDetails::Ruleset { ruleset } => {
+                    self.reference(BundleArtifactKind::Ruleset, ruleset)
+                }
+                StepDetails::SubPipeline { pipeline_id } => {
+                    self.reference(BundleArtifactKind::Pipeline, pipeline_id)
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn ruleset_references(&mut self, ruleset: &Ruleset) {
+        if let Some(parent) = &ruleset.extends {
+            self.reference(BundleArtifactKind::Ruleset, parent);
+        }
+        for rule in &ruleset.rules {
+            self.reference(BundleArtifactKind::Rule, rule);
+        }
+    }
+
+    /// Load one artifact unless already attempted, queueing its references
+    ///
+    /// Artifacts listed by path are also queued by ID, which warms the cache
+    /// key used when they are referenced.
+    async fn load<R: Repository + ?Sized>(
+        &mut self,
+        repo: &R,
+        kind: BundleArtifactKind,
+        identifier: &str,
+    ) {
+        if !self.attempted.insert((kind, identifier.to_string())) {
+            return;
+        }
+
+        let id = match kind {
+            BundleArtifactKind::Pipeline => match repo.load_pipeline(identifier).await {
+                Ok((pipeline, _)) => {
+                    self.pipeline_references(&pipeline);
+                    pipeline.id
+                }
+                Err(e) => return self.fail(kind, identifier, e),
+            },
+            BundleArtifactKind::Ruleset => match repo.load_ruleset(identifier).await {
+                Ok((ruleset, _)) => {
+                    self.ruleset_references(&ruleset);
+                    ruleset.id
+                }
+                Err(e) => return self.fail(kind, identifier, e),
+            },
+            _ => match repo.load_rule(identifier).await {
+                Ok((rule, _)) => rule.id,
+                Err(e) => return self.fail(kind, identifier, e),
+            },
+        };
+
+        if id != identifier {
+            self.reference(kind, &id);
+        }
+        self.record(kind, id);
+    }
+}
+
+/// Load the selected artifacts and everything they reference
+pub(crate) async fn warm<R: Repository + ?Sized>(
+    repo: &R,
+    patterns: &[&str],
+) -> RepositoryResult<PreloadManifest> {
+    let start = Instant::now();
+    let mut warmup = Warmup::default();
+
+    let listed = [
+        (BundleArtifactKind::Pipeline, repo.list_pipelines().await?),
+        (BundleArtifactKind::Ruleset, repo.list_rulesets().await?),
+        (BundleArtifactKind::Rule, repo.list_rules().await?),
+    ];
+    for (kind, identifiers) in listed {
+        for identifier in identifiers {
+            if selected(patterns, &identifier) {
+                warmup.load(repo, kind, &identifier).await;
+            }
+        }
+    }
+
+    while let Some((kind, id)) = warmup.queue.pop_front() {
+        warmup.load(repo, kind, &id).await;
+    }
+
+    let mut manifest = warmup.manifest;
+    manifest.elapsed = start.elapsed();
+    Ok(manifest)
+}
@@ -0,0 +1,557 @@
+//! Redis-backed repository implementation
+//!
+//! Stores artifact content under plain Redis keys so many decision nodes can
+//! load rules with a single round trip. Every write publishes an invalidation
+//! message; each repository instance subscribes to the channel and evicts the
+//! affected cache entries, so updates reach all nodes within seconds without
+//! waiting for cache TTLs or querying the system of record.
+//!
+//! Key layout (with the default `corint` prefix):
+//!
+//! ```text
+//! corint:rule:<id>        rule YAML
+//! corint:ruleset:<id>     ruleset YAML
+//! corint:pipeline:<id>    pipeline YAML
+//! corint:rules            set of rule IDs (likewise rulesets, pipelines)
+//! corint:registry         registry YAML
+//! corint:invalidate       pub/sub channel ("<kind>:<id>" or "*")
+//! ```
+
+use ::redis::aio::{ConnectionManager, PubSub};
+use ::redis::AsyncCommands;
+use async_trait::async_trait;
+use corint_core::ast::{Pipeline, Rule, Ruleset};
+use corint_parser::{PipelineParser, RuleParser, RulesetParser};
+use futures::StreamExt;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::RwLock;
+use tokio::task::JoinHandle;
+
+use crate::{
+    bundle::{BundleArtifactKind, RepositoryBundle},
+    cache::LoadCoordinator,
+    error::RepositoryError,
+    models::*,
+    traits::*,
+    yaml_writer, CacheStats, RepositoryResult,
+};
+
+/// Default prefix for all keys and the invalidation channel
+pub const DEFAULT_KEY_PREFIX: &str = "corint";
+
+/// Delay before the first attempt to resubscribe after losing the invalidation channel
+const RESUBSCRIBE_INITIAL_BACKOFF: Duration = Duration::from_millis(100);
+
+/// Longest delay between attempts to resubscribe
+const RESUBSCRIBE_MAX_BACKOFF: Duration = Duration::from_secs(5);
+
+type Cache<T> = Arc<RwLock<HashMap<String, CachedArtifact<T>>>>;
+
+/// Redis-backed repository
+///
+/// Loads artifacts from Redis keys with local caching. Cache entries are
+/// evicted as soon as an invalidation message is received from any writer.
+pub struct RedisRepository {
+    /// Connection used for commands
+    conn: ConnectionManager,
+    /// Prefix for keys and the invalidation channel
+    key_prefix: String,
+    /// Cache for rules
+    rule_cache: Cache<Rule>,
+    /// Cache for rulesets
+    ruleset_cache: Cache<Ruleset>,
+    /// Cache for pipelines
+    pipeline_cache: Cache<Pipeline>,
+    /// Cache configuration
+    cache_config: Arc<Mutex<CacheConfig>>,
+    /// Cache statistics
+    stats: Arc<Mutex<CacheStats>>,
+    /// Negative cache and single-flight gate for cache misses
+    loads: Arc<LoadCoordinator>,
+    /// Task evicting cache entries on invalidation messages, aborted on drop
+    invalidation_listener: Option<JoinHandle<()>>,
+}
+
+impl Drop for RedisRepository {
+    fn drop(&mut self) {
+        if let Some(listener) = &self.invalidation_listener {
+            listener.abort();
+        }
+    }
+}
+
+/// Caches the invalidation listener evicts from
+struct InvalidationTargets {
+    rule_cache: Cache<Rule>,
+    ruleset_cache: Cache<Ruleset>,
+    pipeline_cache: Cache<Pipeline>,
+    stats: Arc<Mutex<CacheStats>>,
+    loads: Arc<LoadCoordinator>,
+}
+
+impl InvalidationTargets {
+    /// Evict what an invalidation message names: `<kind>:<id>`, or anything else
+    /// for every entry
+    async fn apply(&self, payload: &str) {
+        match payload.split_once(':') {
+            Some((_, id)) => self.loads.forget(id),
+            None => self.loads.clear(),
+        }
+
+        match payload.split_once(':') {
+            Some(("rule", id)) => {
+                self.rule_cache.write().await.remove(id);
+            }
+            Some(("ruleset", id)) => {
+                self.ruleset_cache.write().await.remove(id);
+            }
+            Some(("pipeline", id)) => {
+                self.pipeline_cache.write().await.remove(id);
+            }
+            _ => {
+                self.rule_cache.write().await.clear();
+                self.ruleset_cache.write().await.clear();
+                self.pipeline_cache.write().await.clear();
+                self.stats.lock().unwrap().size = 0;
+            }
+        }
+    }
+}
+
+impl RedisRepository {
+    /// Connect to Redis and subscribe to invalidations using the default key prefix
+    ///
+    /// # Example
+    /// ```no_run
+    /// use corint_repository::RedisRepository;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> anyhow::Result<()> {
+    ///     let repo = RedisRepository::new("redis://localhost:6379").await?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn new(redis_url: &str) -> RepositoryResult<Self> {
+        Self::with_key_prefix(redis_url, DEFAULT_KEY_PREFIX).await
+    }
+
+    /// Connect to Redis using a custom key prefix
+    ///
+    /// Use distinct prefixes to host several environments in one Redis instance.
+    pub async fn with_key_prefix(redis_url: &str, key_prefix: &str) -> RepositoryResult<Self> {
+        let client = ::redis::Client::open(redis_url)?;
+        let conn = client.get_connection_manager().await?;
+
+        let mut repo = Self {
+            conn,
+            key_prefix: key_prefix.to_string(),
+            rule_cache: Arc::new(RwLock::new(HashMap::new())),
+            ruleset_cache: Arc::new(RwLock::new(HashMap::new())),
+            pipeline_cache: Arc::new(RwLock::new(HashMap::new())),
+            cache_config: Arc::new(Mutex::new(CacheConfig::default())),
+            stats: Arc::new(Mutex::new(CacheStats::default())),
+            loads: Arc::new(LoadCoordinator::default()),
+            invalidation_listener: None,
+        };
+
+        repo.invalidation_listener = Some(repo.spawn_invalidation_listener(client).await?);
+
+        Ok(repo)
+    }
+
+    /// Set custom cache configuration
+    pub fn with_cache_config(self, config: CacheConfig) -> Self {
+        *self.cache_config.lock().unwrap() = config;
+        self
+    }
+
+    /// Key holding an artifact's content
+    fn artifact_key(&self, kind: &str, id: &str) -> String {
+        format!("{}:{}:{}", self.key_prefix, kind, id)
+    }
+
+    /// Key holding the set of IDs for an artifact kind
+    fn index_key(&self, kind: &str) -> String {
+        format!("{}:{}s", self.key_prefix, kind)
+    }
+
+    /// Pub/sub channel for invalidation messages
+    fn channel(&self) -> String {
+        format!("{}:invalidate", self.key_prefix)
+    }
+
+    /// Subscribe to the invalidation channel and evict cache entries on each message
+    ///
+    /// When the subscription drops, the listener resubscribes with backoff and
+    /// clears all caches, since invalidations published while it was
+    /// disconnected are lost.
+    async fn spawn_invalidation_listener(
+        &self,
+        client: ::redis::Client,
+    ) -> RepositoryResult<JoinHandle<()>> {
+        let channel = self.channel();
+        let mut pubsub = Self::subscribe(&client, &channel).await?;
+
+        let targets = InvalidationTargets {
+            rule_cache: Arc::clone(&self.rule_cache),
+            ruleset_cache: Arc::clone(&self.ruleset_cache),
+            pipeline_cache: Arc::clone(&self.pipeline_cache),
+            stats: Arc::clone(&self.stats),
+            loads: Arc::clone(&self.loads),
+        };
+
+        Ok(tokio::spawn(async move {
+            loop {
+                let mut messages = pubsub.into_on_message();
+                while let Some(msg) = messages.next().await {
+                    if let Ok(payload) = msg.get_payload::<String>() {
+                        targets.apply(&payload).await;
+                    }
+                }
+                drop(messages);
+
+                let mut backoff = RESUBSCRIBE_INITIAL_BACKOFF;
+                pubsub = loop {
+                    tokio::time::sleep(backoff).await;
+                    match Self::subscribe(&client, &channel).await {
+                        Ok(pubsub) => break pubsub,
+                        Err(_) => backoff = (backoff * 2).min(RESUBSCRIBE_MAX_BACKOFF),
+                    }
+                };
+                targets.apply("*").await;
+            }
+        }))
+    }
+
+    /// Open a pub/sub connection subscribed to `channel`
+    async fn subscribe(client: &::redis::Client, channel: &str) -> RepositoryResult<PubSub> {
+        let mut pubsub = client.get_async_connection().await?.into_pubsub();
+        pubsub.subscribe(channel).await?;
+        Ok(pubsub)
+    }
+
+    /// Check cache and potentially load from cache
+    async fn check_cache<T: Clone>(
+        &self,
+        cache: &Cache<T>,
+        identifier: &str,
+    ) -> Option<(T, String)> {
+        let enabled = self.cache_config.lock().unwrap().enabled;
+        if !enabled {
+            return None;
+        }
+
+        let cache_read = cache.read().await;
+        if let Some(cached) = cache_read.get(identifier) {
+            if !cached.is_expired() {
+                cached.touch();
+                self.stats.lock().unwrap().hits += 1;
+                return Some((cached.data.clone(), cached.content.clone()));
+            }
+        }
+
+        self.stats.lock().unwrap().misses += 1;
+        None
+    }
+
+    /// Load raw content for an artifact
+    async fn load_content(&self, kind: &str, identifier: &str) -> RepositoryResult<String> {
+        let content: Option<String> = self
+            .conn
+            .clone()
+            .get(self.artifact_key(kind, identifier))
+            .await?;
+
+        content.ok_or_else(|| RepositoryError::NotFound {
+            path: identifier.to_string(),
+        })
+    }
+
+    /// List IDs of an artifact kind, sorted
+    async fn list_ids(&self, kind: &str) -> RepositoryResult<Vec<String>> {
+        let mut ids: Vec<String> = self.conn.clone().smembers(self.index_key(kind)).await?;
+        ids.sort();
+        Ok(ids)
+    }
+
+    /// Store artifact content, update the index and notify all subscribers
+    async fn store_content(&self, kind: &str, id: &str, content: &str) -> RepositoryResult<()> {
+        ::redis::pipe()
+            .atomic()
+            .set(self.artifact_key(kind, id), content)
+            .ignore()
+            .sadd(self.index_key(kind), id)
+            .ignore()
+            .publish(self.channel(), format!("{}:{}", kind, id))
+            .ignore()
+            .query_async::<_, ()>(&mut self.conn.clone())
+            .await?;
+
+        Ok(())
+    }
+
+    /// Remove an artifact, update the index and notify all subscribers
+    async fn delete_content(&self, kind: &str, id: &str) -> RepositoryResult<()> {
+        let (removed,): (i64,) = ::redis::pipe()
+            .atomic()
+            .del(self.artifact_key(kind, id))
+            .srem(self.index_key(kind), id)
+            .ignore()
+            .publish(self.channel(), format!("{}:{}", kind, id))
+            .ignore()
+            .query_async(&mut self.conn.clone())
+            .await?;
+
+        if removed == 0 {
+            return Err(RepositoryError::NotFound {
+                path: id.to_string(),
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Publish a full invalidation so every node drops its cache
+    pub async fn invalidate_all(&self) -> RepositoryResult<()> {
+        self.conn
+            .clone()
+            .publish::<_, _, ()>(self.channel(), "*")
+            .await?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Repository for RedisRepository {
+    async fn load_rule(&self, identifier: &str) -> RepositoryResult<(Rule, String)> {
+        if let Some(cached) = self.check_cache(&self.rule_cache, identifier).await {
+            return Ok(cached);
+        }
+
+        self.loads
+            .load(
+                &self.rule_cache,
+                &self.cache_config,
+                &self.stats,
+                "rule",
+                identifier,
+                || async {
+                    let content = self.load_content("rule", identifier).await?;
+                    let doc = RuleParser::parse_with_imports(&content)?;
+
+                    Ok((doc.definition, content))
+                },
+            )
+            .await
+    }
+
+    async fn load_ruleset(&self, identifier: &str) -> RepositoryResult<(Ruleset, String)> {
+        if let Some(cached) = self.check_cache(&self.ruleset_cache, identifier).await {
+            return Ok(cached);
+        }
+
+        self.loads
+            .load(
+                &self.ruleset_cache,
+                &self.cache_config,
+                &self.stats,
+                "ruleset",
+                identifier,
+                || async {
+                    let content = self.load_content("ruleset", identifier).await?;
+                    let doc = RulesetParser::parse_with_imports(&content)?;
+
+                    Ok((doc.definition, content))
+                },
+            )
+            .await
+    }
+
+    async fn load_pipeline(&self, identifier: &str) -> RepositoryResult<(Pipeline, String)> {
+        if let Some(cached) = self.check_cache(&self.pipeline_cache, identifier).await {
+            return Ok(cached);
+        }
+
+        self.loads
+            .load(
+                &self.pipeline_cache,
+                &self.cache_config,
+                &self.stats,
+                "pipeline",
+                identifier,
+                || async {
+                    let content = self.load_content("pipeline", identifier).await?;
+                    let doc = PipelineParser::parse_with_imports(&content)?;
+
+                    Ok((doc.definition, content))
+                },
+            )
+            .await
+    }
+
+    async fn exists(&self, identifier: &str) -> RepositoryResult<bool> {
+        let count: i64 = self
+            .conn
+            .clone()
+            .exists(vec![
+                self.artifact_key("rule", identifier),
+                self.artifact_key("ruleset", identifier),
+                self.artifact_key("pipeline", identifier),
+            ])
+            .await?;
+
+        Ok(count > 0)
+    }
+
+    async fn list_rules(&self) -> RepositoryResult<Vec<String>> {
+        self.list_ids("rule").await
+    }
+
+    async fn list_rulesets(&self) -> RepositoryResult<Vec<String>> {
+        self.list_ids("ruleset").await
+    }
+
+    async fn list_pipelines(&self) -> RepositoryResult<Vec<String>> {
+        self.list_ids("pipeline").await
+    }
+
+    async fn load_registry(&self) -> RepositoryResult<String> {
+        let content: Option<String> = self
+            .conn
+            .clone()
+            .get(format!("{}:registry", self.key_prefix))
+            .await?;
+
+        content.ok_or_else(|| RepositoryError::NotFound {
+            path: "registry".to_string(),
+        })
+    }
+}
+
+#[async_trait]
+impl CacheableRepository for RedisRepository {
+    fn clear_cache(&mut self) {
+        let rule_cache = Arc::clone(&self.rule_cache);
+        let ruleset_cache = Arc::clone(&self.ruleset_cache);
+        let pipeline_cache = Arc::clone(&self.pipeline_cache);
+
+        tokio::spawn(async move {
+            rule_cache.write().await.clear();
+            ruleset_cache.write().await.clear();
+            pipeline_cache.write().await.clear();
+        });
+
+        self.loads.clear();
+        self.stats.lock().unwrap().size = 0;
+    }
+
+    fn clear_cache_entry(&mut self, identifier: &str) {
+        let rule_cache = Arc::clone(&self.rule_cache);
+        let ruleset_cache = Arc::clone(&self.ruleset_cache);
+        let pipeline_cache = Arc::clone(&self.pipeline_cache);
+        self.loads.forget(identifier);
+        let id = identifier.to_string();
+
+        tokio::spawn(async move {
+            rule_cache.write().await.remove(&id);
+            ruleset_cache.write().await.remove(&id);
+            pipeline_cache.write().await.remove(&id);
+        });
+    }
+
+    fn cache_stats(&self) -> CacheStats {
+        self.stats.lock().unwrap().clone()
+    }
+
+    fn set_cache_enabled(&mut self, enabled: bool) {
+        self.cache_config.lock().unwrap().enabled = enabled;
+    }
+
+    fn is_cache_enabled(&self) -> bool {
+        self.cache_config.lock().unwrap().enabled
+    }
+
+    fn set_cache_config(&mut self, config: CacheConfig) {
+        *self.cache_config.lock().unwrap() = config;
+    }
+}
+
+#[async_trait]
+impl WritableRepository for RedisRepository {
+    async fn save_rule(&mut self, rule: &Rule) -> RepositoryResult<()> {
+        let content = yaml_writer::rule_to_yaml(rule)?;
+        self.store_content("rule", &rule.id, &content).await
+    }
+
+    async fn save_ruleset(&mut self, ruleset: &Ruleset) -> RepositoryResult<()> {
+        let content = yaml_writer::ruleset_to_yaml(ruleset)?;
+        self.store_content("ruleset", &ruleset.id, &content).await
+    }
+
+    async fn save_pipeline(&mut self, pipeline: &Pipeline) -> RepositoryResult<()> {
+        if pipeline.id.is_empty() {
+            return Err(RepositoryError::Other(
+                "Pipeline must have an id".to_string(),
+            ));
+        }
+
+        let content = yaml_writer::pipeline_to_yaml(pipeline)?;
+        self.store_content("pipeline", &pipeline.id, &content).await
+    }
+
+    async fn delete_rule(&self, identifier: &str) -> RepositoryResult<()> {
+        self.delete_content("rule", identifier).await
+    }
+
+    async fn delete_ruleset(&self, identifier: &str) -> RepositoryResult<()> {
+        self.delete_content("ruleset", identifier).await
+    }
+
+    async fn delete_pipeline(&self, identifier: &str) -> RepositoryResult<()> {
+        self.delete_content("pipeline", identifier).await
+    }
+
+    async fn import_bundle(
+        &mut self,
+        bundle: &RepositoryBundle,
+        signing_key: Option<&[u8]>,
+    ) -> RepositoryResult<usize> {
+        bundle.verify(signing_key)?;
+        bundle.parse_definitions()?;
+
+        if let Some(artifact) = bundle
+            .artifacts
+            .iter()
+            .find(|a| !a.kind.is_definition() && a.kind != BundleArtifactKind::Registry)
+        {
+            return Err(RepositoryError::Other(format!(
+                "Redis repository cannot import {} artifacts ('{}')",
+                artifact.kind.as_str(),
+                artifact.id
+            )));
+        }
+
+        // Apply the whole bundle in one MULTI/EXEC so nodes never observe a
+        // half-promoted rule pack, then invalidate every cache at once
+        let mut pipe = ::redis::pipe();
+        pipe.atomic();
+        for artifact in &bundle.artifacts {
+            let kind = artifact.kind.as_str();
+            if artifact.kind == BundleArtifactKind::Registry {
+                pipe.set(format!("{}:registry", self.key_prefix), &artifact.content)
+                    .ignore();
+            } else {
+                pipe.set(self.artifact_key(kind, &artifact.id), &artifact.content)
+                    .ignore()
+                    .sadd(self.index_key(kind), &artifact.id)
+                    .ignore();
+            }
+        }
+        pipe.publish(self.channel(), "*").ignore();
+        pipe.query_async::<_, ()>(&mut self.conn.clone()).await?;
+
+        Ok(bundle.artifacts.len())
+    }
+}
@@ -0,0 +1,326 @@
+//! Environment variable and secret interpolation
+//!
+//! Repository YAML may reference values that must not live in rule files,
+//! such as API keys and connection passwords. The [`RepositoryLoader`]
+//! replaces these placeholders when it loads the content:
+//!
+//! - `${NAME}` — the environment variable `NAME`
+//! - `${NAME:-fallback}` — the environment variable `NAME`, or `fallback` when unset
+//! - `${secret:reference}` — a secret looked up by the configured [`SecretResolver`]
+//! - `$${...}` — a literal `${...}`
+//!
+//! Only upper-case names are treated as environment variables, so runtime
+//! placeholders such as `${event.user.id}` or `${user_id}` in request
+//! templates are left untouched, as are full-line YAML comments. A placeholder
+//! that cannot be resolved fails the load instead of leaving the literal text
+//! in place.
+//!
+//! Secret backends plug in through [`SecretResolver`]; [`VaultSecretResolver`]
+//! (feature `api`) reads HashiCorp Vault KV v2 secrets, and other stores such
+//! as AWS Secrets Manager can be added by implementing the trait:
+//!
+//! ```yaml
+//! auth:
+//!   type: header
+//!   name: Authorization
+//!   value: "Bearer ${secret:apis/ipinfo#token}"
+//! ```
+//!
+//! [`RepositoryLoader`]: crate::RepositoryLoader
+
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+use crate::{error::RepositoryError, RepositoryResult};
+
+/// Source of secret values referenced by `${secret:...}` placeholders
+#[async_trait]
+pub trait SecretResolver: Send + Sync {
+    /// Resolve a secret reference (the text after `secret:`)
+    async fn resolve(&self, reference: &str) -> RepositoryResult<String>;
+}
+
+/// Secret resolver backed by a fixed map, for tests and local development
+#[derive(Debug, Clone, Default)]
+pub struct StaticSecretResolver {
+    secrets: HashMap<String, String>,
+}
+
+impl StaticSecretResolver {
+    /// Create an empty resolver
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a secret value
+    pub fn with_secret(mut self, reference: impl Into<String>, value: impl Into<String>) -> Self {
+        self.secrets.insert(reference.into(), value.into());
+        self
+    }
+}
+
+#[async_trait]
+impl SecretResolver for StaticSecretResolver {
+    async fn resolve(&self, reference: &str) -> RepositoryResult<String> {
+        self.secrets
+            .get(reference)
+            .cloned()
+            .ok_or_else(|| RepositoryError::NotFound {
+                path: format!("secret:{}", reference),
+            })
+    }
+}
+
+/// Secret resolver reading HashiCorp Vault KV version 2 secrets
+///
+/// References take the form `path#key`, e.g. `${secret:apis/ipinfo#token}`
+/// reads the `token` field of the secret at `<mount>/data/apis/ipinfo`. The
+/// key defaults to `value` when omitted.
+#[cfg(feature = "api")]
+pub struct VaultSecretResolver {
+    client: reqwest::Client,
+    address: String,
+    token: String,
+    mount: String,
+}
+
+#[cfg(feature = "api")]
+impl VaultSecretResolver {
+    /// Create a resolver for the Vault server at `address` (e.g. `https://vault:8200`)
+    pub fn new(address: impl Into<String>, token: impl Into<String>) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            address: address.into().trim_end_matches('/').to_string(),
+            token: token.into(),
+            mount: "secret".to_string(),
+        }
+    }
+
+    /// Create a resolver from the standard `VAULT_ADDR` and `VAULT_TOKEN` variables
+    pub fn from_env() -> RepositoryResult<Self> {
+        let var = |name: &str| {
+            std::env::var(name).map_err(|_| RepositoryError::Config(format!("{} is not set", name)))
+        };
+        Ok(Self::new(var("VAULT_ADDR")?, var("VAULT_TOKEN")?))
+    }
+
+    /// Set the KV secrets engine mount (default: `secret`)
+    pub fn with_mount(mut self, mount: impl Into<String>) -> Self {
+        self.mount = mount.into();
+        self
+    }
+}
+
+#[cfg(feature = "api")]
+#[async_trait]
+impl SecretResolver for VaultSecretResolver {
+    async fn resolve(&self, reference: &str) -> RepositoryResult<String> {
+        let (path, key) = reference.split_once('#').unwrap_or((reference, "value"));
+        let url = format!("{}/v1/{}/data/{}", self.address, self.mount, path);
+
+        let response = self
+            .client
+            .get(&url)
+            .header("X-Vault-Token", &self.token)
+            .send()
+            .await
+            .map_err(|e| RepositoryError::ApiError(format!("Vault request failed: {}", e)))?;
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Err(RepositoryError::NotFound {
+                path: format!("secret:{}", path),
+            });
+        }
+        if !response.status().is_success() {
+            return Err(RepositoryError::ApiError(format!(
+                "Vault returned {} for secret '{}'",
+                response.status(),
+                path
+            )));
+        }
+
+        let body: serde_json::Value = response
+            .json()
+            .await
+            .map_err(|e| RepositoryError::ParseError(format!("Invalid Vault response: {}", e)))?;
+        match &body["data"]["data"][key] {
+            serde_json::Value::String(value) => Ok(value.clone()),
+            serde_json::Value::Null => Err(RepositoryError::NotFound {
+                path: format!("secret:{}#{}", path, key),
+            }),
+            other => Ok(other.to_string()),
+        }
+    }
+}
+
+/// Replaces environment variable and secret placeholders in YAML text
+///
+/// Resolved secrets are cached for the lifetime of the interpolator, so a
+/// secret referenced by several files is fetched once per load.
+#[derive(Default)]
+pub struct Interpolator {
+    secrets: Option<Arc<dyn SecretResolver>>,
+    resolved: Mutex<HashMap<String, String>>,
+}
+
+impl Interpolator {
+    /// Create an interpolator resolving environment variables only
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Resolve `${secret:...}` placeholders with a secret resolver
+    pub fn with_secret_resolver(mut self, resolver: Arc<dyn SecretResolver>) -> Self {
+        self.secrets = Some(resolver);
+        self
+    }
+
+    /// Replace the placeholders in `text`
+    pub async fn interpolate(&self, text: &str) -> RepositoryResult<String> {
+        if !text.contains("${") {
+            return Ok(text.to_string());
+        }
+
+        let mut output = String::with_capacity(text.len());
+        for line in text.split_inclusive('\n') {
+            if line.trim_start().starts_with('#') {
+                output.push_str(line);
+            } else {
+                self.interpolate_line(line, &mut output).await?;
+            }
+        }
+        Ok(output)
+    }
+
+    async fn interpolate_line(&self, line: &str, output: &mut String) -> RepositoryResult<()> {
+        let mut rest = line;
+        while let Some(start) = rest.find("${") {
+            // `$${` escapes a literal `${`
+            if rest[..start].ends_with('$') {
+                output.push_str(&rest[..start - 1]);
+                output.push_str("${");
+                rest = &rest[start + 2..];
+                continue;
+            }
+
+            output.push_str(&rest[..start]);
+            let Some(len) = rest[start + 2..].find('}') else {
+                output.push_str(&rest[start..]);
+                return Ok(());
+            };
+            let inner = &rest[start + 2..start + 2 + len];
+            match self.resolve(inner).await? {
+                Some(value) => output.push_str(&value),
+                None => output.push_str(&rest[start..start + 3 + len]),
+            }
+            rest = &rest[start + 3 + len..];
+        }
+        output.push_str(rest);
+        Ok(())
+    }
+
+    /// Resolve a placeholder body, or `None` when it is not ours to resolve
+    async fn resolve(&self, inner: &str) -> RepositoryResult<Option<String>> {
+        let unresolved = |reason: String| RepositoryError::Interpolation {
+            placeholder: format!("${{{}}}", inner),
+            reason,
+        };
+
+        if let Some(reference) = inner.strip_prefix("secret:") {
+            let reference = reference.trim();
+            if let Some(value) = self.resolved.lock().await.get(reference) {
+                return Ok(Some(value.clone()));
+            }
+            let resolver = self
+                .secrets
+                .as_ref()
+                .ok_or_else(|| unresolved("no secret resolver is configured".to_string()))?;
+            let value = resolver
+                .resolve(reference)
+                .await
+                .map_err(|e| unresolved(e.to_string()))?;
+            self.resolved
+                .lock()
+                .await
+                .insert(reference.to_string(), value.clone());
+            return Ok(Some(value));
+        }
+
+        let (name, fallback) = match inner.split_once(":-") {
+            Some((name, fallback)) => (name, Some(fallback)),
+            None => (inner, None),
+        };
+        if !is_env_name(name) {
+            return Ok(None);
+        }
+        match (std::env::var(name), fallback) {
+            (Ok(value), _) => Ok(Some(value)),
+            (Err(_), Some(fallback)) => Ok(Some(fallback.to_string())),
+            (Err(_), None) => Err(unresolved(format!(
+                "environment variable {} is not set",
+                name
+            ))),
+        }
+    }
+}
+
+/// Whether a name is an environment variable name (`[A-Z_][A-Z0-9_]*`)
+fn is_env_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    matches!(chars.next(), Some(c) if c.is_ascii_uppercase() || c == '_')
+        && chars.all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_env_placeholders() {
+        std::env::set_var("CORINT_TEST_INTERP_KEY", "abc123");
+        let interpolator = Interpolator::new();
+
+        let yaml = "# Set ${CORINT_TEST_INTERP_MISSING} first\n\
+                    key: \"${CORINT_TEST_INTERP_KEY}\"\n\
+                    region: ${CORINT_TEST_INTERP_REGION:-eu-west-1}\n\
+                    body: '{\"user\": \"${event.user.id}\", \"raw\": \"$${NOT_EXPANDED}\"}'\n";
+        let resolved = interpolator.interpolate(yaml).await.unwrap();
+
+        assert_eq!(
+            resolved,
+            "# Set ${CORINT_TEST_INTERP_MISSING} first\n\
+             key: \"abc123\"\n\
+             region: eu-west-1\n\
+             body: '{\"user\": \"${event.user.id}\", \"raw\": \"${NOT_EXPANDED}\"}'\n"
+        );
+
+        let err = interpolator
+            .interpolate("key: ${CORINT_TEST_INTERP_MISSING}")
+            .await
+            .unwrap_err();
+        assert!(matches!(err, RepositoryError::Interpolation { .. }));
+        assert!(err.to_string().contains("CORINT_TEST_INTERP_MISSING"));
+    }
+
+    #[tokio::test]
+    async fn test_secret_placeholders() {
+        let yaml = "value: \"Bearer ${secret:apis/ipinfo#token}\"";
+
+        let err = Interpolator::new().interpolate(yaml).await.unwrap_err();
+        assert!(err.to_string().contains("no secret resolver"));
+
+        let resolver = StaticSecretResolver::new().with_secret("apis/ipinfo#token", "s3cr3t");
+        let interpolator = Interpolator::new().with_secret_resolver(Arc::new(resolver));
+        assert_eq!(
+            interpolator.interpolate(yaml).await.unwrap(),
+            "value: \"Bearer s3cr3t\""
+        );
+
+        let err = interpolator
+            .interpolate("value: ${secret:apis/unknown}")
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("secret:apis/unknown"));
+    }
+}
@@ -0,0 +1,314 @@
+//! Scheduled repository sync with drift detection
+//!
+//! Remote repositories change underneath a running engine: artifacts are
+//! edited or removed by a rule-management service, and a missed webhook or
+//! pub/sub message leaves the local cache serving stale content.
+//!
+//! A [`SyncableRepository`] compares the version markers of the remote store
+//! (manifest versions for the HTTP API, the `version` column for PostgreSQL)
+//! against those of the artifacts it has loaded. Artifacts whose marker
+//! differs are reloaded, and artifacts that no longer exist are evicted; both
+//! are reported as drift in a [`SyncReport`].
+//!
+//! [`spawn_sync`] runs the check on a fixed interval in the background and
+//! hands every result to an observer, e.g. to record metrics:
+//!
+//! ```no_run
+//! # #[cfg(feature = "api")]
+//! # async fn example() -> anyhow::Result<()> {
+//! use corint_repository::{spawn_sync, ApiRepository};
+//! use std::sync::Arc;
+//! use std::time::Duration;
+//!
+//! let repo = Arc::new(ApiRepository::new("https://api.example.com/rules", None::<String>).await?);
+//!
+//! let sync = spawn_sync(repo, Duration::from_secs(60), |result| {
+//!     if let Ok(report) = result {
+//!         println!("{} artifacts drifted", report.drifted.len());
+//!     }
+//! });
+//!
+//! // Later: cumulative statistics, and stop the task
+//! println!("{:?}", sync.stats());
+//! sync.stop();
+//! # Ok(())
+//! # }
+//! ```
+
+use async_trait::async_trait;
+use serde::Serialize;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+use tokio::task::JoinHandle;
+
+use crate::{error::RepositoryError, traits::Repository, BundleArtifactKind, RepositoryResult};
+
+/// How a loaded artifact differs from the remote store
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DriftKind {
+    /// The remote version differs from the loaded one
+    Changed,
+
+    /// The artifact no longer exists remotely
+    Removed,
+}
+
+/// Loaded artifact that drifted from the remote store
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ArtifactDrift {
+    /// Artifact kind
+    pub kind: BundleArtifactKind,
+
+    /// Identifier the artifact was loaded with
+    pub identifier: String,
+
+    /// How the artifact drifted
+    pub drift: DriftKind,
+
+    /// Version marker of the loaded content, if known
+    pub loaded_version: Option<String>,
+
+    /// Version marker of the remote content (`None` when removed)
+    pub remote_version: Option<String>,
+}
+
+/// Outcome of one sync run
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct SyncReport {
+    /// Number of loaded artifacts compared against the remote store
+    pub checked: usize,
+
+    /// Artifacts that drifted
+    pub drifted: Vec<ArtifactDrift>,
+
+    /// Number of changed artifacts reloaded successfully
+    pub refreshed: usize,
+
+    /// Errors for artifacts that could not be checked or reloaded
+    pub errors: Vec<String>,
+
+    /// Time the run took
+    pub elapsed: Duration,
+}
+
+impl SyncReport {
+    /// Whether any loaded artifact drifted
+    pub fn has_drift(&self) -> bool {
+        !self.drifted.is_empty()
+    }
+
+    /// Number of drifted artifacts of a kind of drift
+    pub fn count(&self, drift: DriftKind) -> usize {
+        self.drifted.iter().filter(|d| d.drift == drift).count()
+    }
+}
+
+/// Repository that can detect and repair drift from its remote store
+#[async_trait]
+pub trait SyncableRepository: Repository {
+    /// Compare remote version markers against loaded content
+    ///
+    /// Changed artifacts are reloaded and removed artifacts evicted. Only
+    /// artifacts that were loaded are checked; a failure to reach the remote
+    /// store fails the whole run, while failures of single artifacts are
+    /// collected in [`SyncReport::errors`].
+    async fn sync(&self) -> RepositoryResult<SyncReport>;
+}
+
+/// Cumulative statistics of a background sync task
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct SyncStats {
+    /// Completed runs
+    pub runs: u64,
+
+    /// Runs that failed entirely
+    pub failed_runs: u64,
+
+    /// Drifted artifacts detected across all runs
+    pub drifted: u64,
+
+    /// Artifacts refreshed across all runs
+    pub refreshed: u64,
+
+    /// When the last run finished
+    pub last_run: Option<SystemTime>,
+
+    /// Report of the last successful run
+    pub last_report: Option<SyncReport>,
+}
+
+impl SyncStats {
+    fn record(&mut self, result: &RepositoryResult<SyncReport>) {
+        self.runs += 1;
+        self.last_run = Some(SystemTime::now());
+        match result {
+            Ok(report) => {
+                self.drifted += report.drifted.len() as u64;
+                self.refreshed += report.refreshed as u64;
+                self.last_report = Some(report.clone());
+            }
+            Err(_) => self.failed_runs += 1,
+        }
+    }
+}
+
+/// Handle of a background sync task
+///
+/// The task is stopped when the handle is dropped.
+#[derive(Debug)]
+pub struct SyncHandle {
+    task: JoinHandle<()>,
+    stats: Arc<Mutex<SyncStats>>,
+}
+
+impl SyncHandle {
+    /// Statistics of the runs so far
+    pub fn stats(&self) -> SyncStats {
+        self.stats.lock().unwrap().clone()
+    }
+
+    /// Stop the task
+    pub fn stop(self) {
+        self.task.abort();
+    }
+}
+
+impl Drop for SyncHandle {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+/// Sync a repository every `interval` in the background
+///
+/// The first run starts after one interval. `observer` receives the result
+/// of every run. Must be called from within a Tokio runtime.
+pub fn spawn_sync<R, F>(repo: Arc<R>, interval: Duration, observer: F) -> SyncHandle
+where
+    R: SyncableRepository + ?Sized + 'static,
+    F: Fn(Result<&SyncReport, &RepositoryError>) + Send + Sync + 'static,
+{
+    let stats = Arc::new(Mutex::new(SyncStats::default()));
+    let task_stats = Arc::clone(&stats);
+
+    let task = tokio::spawn(async move {
+        let mut ticker = tokio::time::interval_at(tokio::time::Instant::now() + interval, interval);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            ticker.tick().await;
+            let result = repo.sync().await;
+            task_stats.lock().unwrap().record(&result);
+            observer(result.as_ref());
+        }
+    });
+
+    SyncHandle { task, stats }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use corint_core::ast::{Pipeline, Rule, Ruleset};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Repository reporting one changed rule per sync; the second sync fails
+    struct DriftingRepository {
+        syncs: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl Repository for DriftingRepository {
+        async fn load_rule(&self, identifier: &str) -> RepositoryResult<(Rule, String)> {
+            Err(RepositoryError::NotFound {
+                path: identifier.to_string(),
+            })
+        }
+
+        async fn load_ruleset(&self, identifier: &str) -> RepositoryResult<(Ruleset, String)> {
+            Err(RepositoryError::NotFound {
+                path: identifier.to_string(),
+            })
+        }
+
+        async fn load_pipeline(&self, identifier: &str) -> RepositoryResult<(Pipeline, String)> {
+            Err(RepositoryError::NotFound {
+                path: identifier.to_string(),
+            })
+        }
+
+        async fn exists(&self, _identifier: &str) -> RepositoryResult<bool> {
+            Ok(false)
+        }
+
+        async fn list_rules(&self) -> RepositoryResult<Vec<String>> {
+            Ok(vec![])
+        }
+
+        async fn list_rulesets(&self) -> RepositoryResult<Vec<String>> {
+            Ok(vec![])
+        }
+
+        async fn list_pipelines(&self) -> RepositoryResult<Vec<String>> {
+            Ok(vec![])
+        }
+
+        async fn load_registry(&self) -> RepositoryResult<String> {
+            Ok(String::new())
+        }
+    }
+
+    #[async_trait]
+    impl SyncableRepository for DriftingRepository {
+        async fn sync(&self) -> RepositoryResult<SyncReport> {
+            let n = self.syncs.fetch_add(1, Ordering::SeqCst);
+            if n == 1 {
+                return Err(RepositoryError::ApiError("unreachable".to_string()));
+            }
+            Ok(SyncReport {
+                checked: 2,
+                drifted: vec![ArtifactDrift {
+                    kind: BundleArtifactKind::Rule,
+                    identifier: "high_amount".to_string(),
+                    drift: DriftKind::Changed,
+                    loaded_version: Some("1".to_string()),
+                    remote_version: Some("2".to_string()),
+                }],
+                refreshed: 1,
+                errors: vec![],
+                elapsed: Duration::ZERO,
+            })
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_sync_runs_on_interval() {
+        let repo = Arc::new(DriftingRepository {
+            syncs: AtomicUsize::new(0),
+        });
+        let observed = Arc::new(AtomicUsize::new(0));
+        let seen = Arc::clone(&observed);
+
+        let handle = spawn_sync(Arc::clone(&repo), Duration::from_secs(60), move |_| {
+            seen.fetch_add(1, Ordering::SeqCst);
+        });
+
+        tokio::time::sleep(Duration::from_secs(30)).await;
+        assert_eq!(repo.syncs.load(Ordering::SeqCst), 0);
+
+        tokio::time::sleep(Duration::from_secs(151)).await;
+        assert_eq!(repo.syncs.load(Ordering::SeqCst), 3);
+        assert_eq!(observed.load(Ordering::SeqCst), 3);
+
+        let stats = handle.stats();
+        assert_eq!(stats.runs, 3);
+        assert_eq!(stats.failed_runs, 1);
+        assert_eq!(stats.drifted, 2);
+        assert_eq!(stats.refreshed, 2);
+        assert_eq!(stats.last_report.unwrap().count(DriftKind::Changed), 1);
+
+        handle.stop();
+        tokio::time::sleep(Duration::from_secs(600)).await;
+        assert_eq!(repo.syncs.load(Ordering::SeqCst), 3);
+    }
+}
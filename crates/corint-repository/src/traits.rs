@@ -53,7 +53,10 @@
 use async_trait::async_trait;
 use corint_core::ast::{Pipeline, Rule, Ruleset};
 
-use crate::{CacheStats, RepositoryResult};
+use crate::{
+    bundle, dependency, preload, ArtifactStatus, BundleArtifactKind, CacheConfig, CacheStats,
+    DependencyGraph, PreloadManifest, RepositoryBundle, RepositoryError, RepositoryResult,
+};
 
 /// Core repository trait for loading decision artifacts
 ///
@@ -192,6 +195,115 @@ pub trait Repository: Send + Sync {
     /// # }
     /// ```
     async fn load_registry(&self) -> RepositoryResult<String>;
+
+    /// Export repository content as a single bundle
+    ///
+    /// The default implementation exports rules, rulesets, pipelines and the
+    /// registry (if any). Backends that also store config files (lists,
+    /// features, API configs, reason codes) include them as well.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use corint_repository::{Repository, FileSystemRepository};
+    /// # #[tokio::main]
+    /// # async fn main() -> anyhow::Result<()> {
+    /// let repo = FileSystemRepository::new("repository")?;
+    ///
+    /// let mut bundle = repo.export_bundle().await?;
+    /// bundle.sign(b"shared-promotion-key");
+    /// std::fs::write("rules-bundle.json", bundle.to_json()?)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    async fn export_bundle(&self) -> RepositoryResult<RepositoryBundle> {
+        bundle::export_definitions(self).await
+    }
+
+    /// Load and cache every pipeline, ruleset and rule
+    ///
+    /// Call at startup so the first request doesn't pay for loading and
+    /// parsing. Artifacts that fail to load are listed in the manifest
+    /// instead of failing the warm-up.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use corint_repository::{Repository, FileSystemRepository};
+    /// # #[tokio::main]
+    /// # async fn main() -> anyhow::Result<()> {
+    /// let repo = FileSystemRepository::new("repository")?;
+    ///
+    /// let manifest = repo.preload_all().await?;
+    /// println!(
+    ///     "Preloaded {} artifacts in {:?}",
+    ///     manifest.loaded_count(),
+    ///     manifest.elapsed
+    /// );
+    /// for failure in &manifest.failed {
+    ///     eprintln!("{} {}: {}", failure.kind.as_str(), failure.identifier, failure.error);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    async fn preload_all(&self) -> RepositoryResult<PreloadManifest> {
+        preload::warm(self, &[]).await
+    }
+
+    /// Load and cache the artifacts matching any of the patterns
+    ///
+    /// Patterns match listed identifiers (IDs, or paths for the file system)
+    /// or their file names without extension; `*` matches any characters.
+    /// Rulesets, rules and sub-pipelines referenced by a matching artifact
+    /// are loaded as well.
+    async fn warm_cache(&self, patterns: &[&str]) -> RepositoryResult<PreloadManifest> {
+        preload::warm(self, patterns).await
+    }
+
+    /// Build the graph of everything a pipeline depends on
+    ///
+    /// Walks pipeline steps, ruleset rules and parents, `import:` sections
+    /// and the features and lists referenced by conditions. Referenced
+    /// artifacts that fail to load are recorded in
+    /// [`DependencyGraph::unresolved`] rather than failing the walk.
+    ///
+    /// # Errors
+    /// Returns an error if the pipeline itself cannot be loaded.
+    async fn dependency_graph(&self, pipeline_id: &str) -> RepositoryResult<DependencyGraph> {
+        dependency::build(self, &[pipeline_id.to_string()]).await
+    }
+
+    /// Build the dependency graph of every pipeline in the repository
+    ///
+    /// Use this graph for safe-deletion checks: an artifact is unused when
+    /// [`DependencyGraph::can_delete`] holds for it here.
+    async fn full_dependency_graph(&self) -> RepositoryResult<DependencyGraph> {
+        let pipelines = self.list_pipelines().await?;
+        dependency::build(self, &pipelines).await
+    }
+
+    /// Load the draft of a rule, ruleset or pipeline
+    ///
+    /// Drafts are never returned by `load_rule`, `load_ruleset` or
+    /// `load_pipeline`, so analysts can iterate on them without affecting
+    /// live traffic.
+    ///
+    /// # Returns
+    /// The raw YAML content of the draft
+    ///
+    /// # Errors
+    /// The default implementation reports every draft as not found, which
+    /// is correct for backends without a lifecycle (e.g. the file system,
+    /// where every artifact is published).
+    async fn load_draft(
+        &self,
+        kind: BundleArtifactKind,
+        identifier: &str,
+    ) -> RepositoryResult<String> {
+        Err(RepositoryError::NotFound {
+            path: format!("{} draft '{}'", kind.as_str(), identifier),
+        })
+    }
 }
 
 /// Extension trait for repositories that support caching
@@ -253,6 +365,12 @@ pub trait CacheableRepository: Repository {
 
     /// Check if caching is enabled
     fn is_cache_enabled(&self) -> bool;
+
+    /// Replace the cache policy (TTLs, size limit, eviction)
+    ///
+    /// Cached entries are kept; a smaller `max_entries` takes effect on the
+    /// next insert.
+    fn set_cache_config(&mut self, config: CacheConfig);
 }
 
 /// Extension trait for repositories that support write operations
@@ -284,6 +402,8 @@ pub trait CacheableRepository: Repository {
 ///     params: None,
 ///     when: WhenBlock { event_type: None, conditions: vec![] },
 ///     score: 50,
+///     effective_from: None,
+///     effective_until: None,
 ///     metadata: None,
 /// };
 ///
@@ -349,4 +469,85 @@ pub trait WritableRepository: Repository {
     /// # Arguments
     /// * `identifier` - The pipeline identifier (path or ID)
     async fn delete_pipeline(&self, identifier: &str) -> RepositoryResult<()>;
+
+    /// Import a bundle exported by [`Repository::export_bundle`]
+    ///
+    /// The bundle's hashes (and signature, when `signing_key` is given) are
+    /// verified and every artifact is parsed before anything is written.
+    /// Returns the number of imported artifacts.
+    ///
+    /// The default implementation saves rules, rulesets and pipelines one by
+    /// one and rejects bundles containing other artifact kinds.
+    /// [`PostgresRepository`](crate::PostgresRepository) overrides it to
+    /// import everything in a single transaction.
+    async fn import_bundle(
+        &mut self,
+        bundle: &RepositoryBundle,
+        signing_key: Option<&[u8]>,
+    ) -> RepositoryResult<usize> {
+        bundle.verify(signing_key)?;
+        let definitions = bundle.parse_definitions()?;
+
+        if let Some(artifact) = bundle.artifacts.iter().find(|a| !a.kind.is_definition()) {
+            return Err(crate::RepositoryError::Other(format!(
+                "This repository cannot import {} artifacts ('{}')",
+                artifact.kind.as_str(),
+                artifact.id
+            )));
+        }
+
+        for rule in &definitions.rules {
+            self.save_rule(rule).await?;
+        }
+        for ruleset in &definitions.rulesets {
+            self.save_ruleset(ruleset).await?;
+        }
+        for pipeline in &definitions.pipelines {
+            self.save_pipeline(pipeline).await?;
+        }
+
+        Ok(bundle.artifacts.len())
+    }
+
+    /// Save the draft of a rule, ruleset or pipeline
+    ///
+    /// The published version (if any) keeps serving traffic until the draft
+    /// is promoted with [`set_status`](Self::set_status).
+    ///
+    /// # Arguments
+    /// * `kind` - Rule, ruleset or pipeline
+    /// * `id` - The artifact ID
+    /// * `content` - Full YAML content of the draft
+    async fn save_draft(
+        &mut self,
+        kind: BundleArtifactKind,
+        id: &str,
+        content: &str,
+    ) -> RepositoryResult<()> {
+        let _ = (id, content);
+        Err(RepositoryError::Other(format!(
+            "This repository does not support {} drafts",
+            kind.as_str()
+        )))
+    }
+
+    /// Change the lifecycle status of a rule, ruleset or pipeline
+    ///
+    /// - `Published` promotes the draft (or restores the archived version
+    ///   when there is no draft); the previously published version is archived.
+    /// - `Archived` retires the published version.
+    /// - `Draft` is not a valid target; use [`save_draft`](Self::save_draft).
+    async fn set_status(
+        &mut self,
+        kind: BundleArtifactKind,
+        identifier: &str,
+        status: ArtifactStatus,
+    ) -> RepositoryResult<()> {
+        let _ = status;
+        Err(RepositoryError::Other(format!(
+            "This repository does not support lifecycle changes for {} '{}'",
+            kind.as_str(),
+            identifier
+        )))
+    }
 }
@@ -0,0 +1,761 @@
+//! Canonical YAML serialization of artifacts
+//!
+//! Turns parsed rules, rulesets, and pipelines back into the DSL documents the
+//! parsers accept: conditions become expression strings (`event.amount > 1000`)
+//! rather than serde's tagged AST, fields are emitted in a fixed order, and map
+//! keys are sorted so the same artifact always produces the same text.
+
+use corint_core::ast::pipeline::{
+    ApiTarget, PipelineDecisionRule, PipelineStep, StepDetails, StepNext, VariableAssignment,
+};
+use corint_core::ast::rule::{Condition, ConditionGroup};
+use corint_core::ast::{
+    Expression, LogicalGroupOp, Operator, Pipeline, Rule, Ruleset, Signal, UnaryOperator, WhenBlock,
+};
+use corint_core::types::{FieldType, SchemaField};
+use corint_core::Value;
+use corint_parser::expression_parser::DEFAULT_QUANTIFIER_BINDING;
+use serde_yaml::{Mapping, Value as YamlValue};
+use std::collections::{BTreeMap, HashMap};
+
+use crate::RepositoryResult;
+
+/// Serialize a rule as a `rule:` document
+pub fn rule_to_yaml(rule: &Rule) -> RepositoryResult<String> {
+    let mut map = Mapping::new();
+    insert(&mut map, "id", rule.id.clone());
+    insert(&mut map, "name", rule.name.clone());
+    insert_opt(&mut map, "description", rule.description.clone());
+    if let Some(params) = &rule.params {
+        let sorted: BTreeMap<_, _> = params.values.iter().collect();
+        insert(&mut map, "params", serde_yaml::to_value(sorted)?);
+    }
+    insert(&mut map, "when", when_block_to_yaml(&rule.when));
+    insert(&mut map, "score", rule.score);
+    if let Some(metadata) = &rule.metadata {
+        insert(&mut map, "metadata", serde_yaml::to_value(metadata)?);
+    }
+
+    document("rule", map)
+}
+
+/// Serialize a ruleset as a `ruleset:` document
+pub fn ruleset_to_yaml(ruleset: &Ruleset) -> RepositoryResult<String> {
+    let mut map = Mapping::new();
+    insert(&mut map, "id", ruleset.id.clone());
+    insert_opt(&mut map, "name", ruleset.name.clone());
+    insert_opt(&mut map, "extends", ruleset.extends.clone());
+    insert_opt(&mut map, "description", ruleset.description.clone());
+    insert(&mut map, "rules", ruleset.rules.clone());
+
+    let conclusion: Vec<YamlValue> = ruleset
+        .conclusion
+        .iter()
+        .map(|rule| {
+            let mut entry = Mapping::new();
+            if let Some(condition) = &rule.condition {
+                insert(&mut entry, "when", expression_to_dsl(condition));
+            }
+            if rule.default {
+                insert(&mut entry, "default", true);
+            }
+            insert(&mut entry, "signal", signal_name(&rule.signal));
+            if !rule.actions.is_empty() {
+                insert(&mut entry, "actions", rule.actions.clone());
+            }
+            insert_opt(&mut entry, "reason", rule.reason.clone());
+            insert_opt(&mut entry, "reason_code", rule.reason_code.clone());
+            YamlValue::Mapping(entry)
+        })
+        .collect();
+    insert(&mut map, "conclusion", conclusion);
+
+    if let Some(metadata) = &ruleset.metadata {
+        insert(&mut map, "metadata", serde_yaml::to_value(metadata)?);
+    }
+
+    document("ruleset", map)
+}
+
+/// Serialize a pipeline as a `pipeline:` document (step-based format)
+pub fn pipeline_to_yaml(pipeline: &Pipeline) -> RepositoryResult<String> {
+    let mut map = Mapping::new();
+    insert(&mut map, "id", pipeline.id.clone());
+    insert(&mut map, "name", pipeline.name.clone());
+    insert_opt(&mut map, "description", pipeline.description.clone());
+    insert(&mut map, "entry", pipeline.entry.clone());
+    if let Some(when) = &pipeline.when {
+        insert(&mut map, "when", when_block_to_yaml(when));
+    }
+    if let Some(schema) = &pipeline.schema {
+        insert(&mut map, "schema", schema_fields_to_yaml(&schema.fields));
+    }
+
+    let steps: Vec<YamlValue> = pipeline.steps.iter().map(step_to_yaml).collect();
+    insert(&mut map, "steps", steps);
+
+    if let Some(decision) = &pipeline.decision {
+        let rules: Vec<YamlValue> = decision.iter().map(decision_rule_to_yaml).collect();
+        insert(&mut map, "decision", rules);
+    }
+    if let Some(metadata) = &pipeline.metadata {
+        let sorted: BTreeMap<_, _> = metadata.iter().collect();
+        insert(&mut map, "metadata", serde_yaml::to_value(sorted)?);
+    }
+
+    document("pipeline", map)
+}
+
+/// Render an expression in DSL syntax, parenthesizing only where the parser needs it
+pub fn expression_to_dsl(expr: &Expression) -> String {
+    match expr {
+        Expression::Literal(value) => literal_to_dsl(value),
+        Expression::FieldAccess(path) => path.join("."),
+        Expression::Binary { left, op, right } => {
+            let precedence = operator_precedence(op);
+            // Operators are left-associative, so an equal-precedence operand
+            // only needs parentheses on the right-hand side
+            let left_str = operand_to_dsl(left, |p| p < precedence);
+            let right_str = match right.as_ref() {
+                Expression::ListReference { list_id } => format!("list.{}", list_id),
+                other => operand_to_dsl(other, |p| p <= precedence),
+            };
+            format!("{} {} {}", left_str, operator_symbol(op), right_str)
+        }
+        Expression::Unary { op, operand } => {
+            let symbol = match op {
+                UnaryOperator::Not => "!",
+                UnaryOperator::Negate => "-",
+                UnaryOperator::IsNull => {
+                    return format!("{} is null", operand_to_dsl(operand, |p| p <= 1))
+                }
+                UnaryOperator::IsNotNull => {
+                    return format!("{} is not null", operand_to_dsl(operand, |p| p <= 1))
+                }
+            };
+            format!("{}{}", symbol, operand_to_dsl(operand, |_| true))
+        }
+        Expression::FunctionCall { name, args } => {
+            let args: Vec<String> = args.iter().map(expression_to_dsl).collect();
+            format!("{}({})", name, args.join(", "))
+        }
+        Expression::Ternary {
+            condition,
+            true_expr,
+            false_expr,
+        } => format!(
+            "{} ? {} : {}",
+            expression_to_dsl(condition),
+            expression_to_dsl(true_expr),
+            expression_to_dsl(false_expr)
+        ),
+        Expression::LogicalGroup { op, conditions } => {
+            let separator = match op {
+                LogicalGroupOp::Any => " || ",
+                LogicalGroupOp::All => " && ",
+            };
+            let parts: Vec<String> = conditions
+                .iter()
+                .map(|c| operand_to_dsl(c, |p| p > 0))
+                .collect();
+            format!("({})", parts.join(separator))
+        }
+        Expression::ListReference { list_id } => format!("list.{}", list_id),
+        Expression::ResultAccess { ruleset_id, field } => match ruleset_id {
+            Some(id) => format!("result.{}.{}", id, field),
+            None => format!("result.{}", field),
+        },
+        Expression::Quantifier {
+            op,
+            collection,
+            binding,
+            predicate,
+        } => {
+            if binding == DEFAULT_QUANTIFIER_BINDING {
+                format!(
+                    "{}({}, {})",
+                    op.name(),
+                    expression_to_dsl(collection),
+                    expression_to_dsl(predicate)
+                )
+            } else {
+                format!(
+                    "{}({}, {}, {})",
+                    op.name(),
+                    expression_to_dsl(collection),
+                    binding,
+                    expression_to_dsl(predicate)
+                )
+            }
+        }
+        Expression::Aggregate {
+            op,
+            collection,
+            path,
+        } => {
+            let collection = expression_to_dsl(collection);
+            if path.is_empty() {
+                format!("{}({})", op.name(), collection)
+            } else {
+                format!("{}({}[*].{})", op.name(), collection, path.join("."))
+            }
+        }
+    }
+}
+
+/// Wrap a YAML mapping under its document key and serialize it
+fn document(key: &str, body: Mapping) -> RepositoryResult<String> {
+    let mut root = Mapping::new();
+    root.insert(YamlValue::from(key), YamlValue::Mapping(body));
+    Ok(serde_yaml::to_string(&root)?)
+}
+
+fn insert(map: &mut Mapping, key: &str, value: impl Into<YamlValue>) {
+    map.insert(YamlValue::from(key), value.into());
+}
+
+fn insert_opt(map: &mut Mapping, key: &str, value: Option<impl Into<YamlValue>>) {
+    if let Some(value) = value {
+        insert(map, key, value);
+    }
+}
+
+/// Fields of an event schema, sorted; a bare type name when that says it all
+fn schema_fields_to_yaml(fields: &HashMap<String, SchemaField>) -> Mapping {
+    let sorted: BTreeMap<_, _> = fields.iter().collect();
+    let mut map = Mapping::new();
+    for (name, field) in sorted {
+        let mut entry = field_type_to_yaml(&field.field_type);
+        if field.required || field.description.is_some() {
+            if let YamlValue::String(type_name) = entry {
+                let mut mapping = Mapping::new();
+                insert(&mut mapping, "type", type_name);
+                entry = YamlValue::Mapping(mapping);
+            }
+            if let YamlValue::Mapping(mapping) = &mut entry {
+                if field.required {
+                    insert(mapping, "required", true);
+                }
+                insert_opt(mapping, "description", field.description.clone());
+            }
+        }
+        insert(&mut map, name, entry);
+    }
+    map
+}
+
+fn field_type_to_yaml(field_type: &FieldType) -> YamlValue {
+    let type_name = match field_type {
+        FieldType::Null => "null",
+        FieldType::Boolean => "boolean",
+        FieldType::Number => "number",
+        FieldType::String => "string",
+        FieldType::Any => "any",
+        FieldType::Array { item_type } => {
+            let mut map = Mapping::new();
+            insert(&mut map, "type", "array");
+            insert(&mut map, "items", field_type_to_yaml(item_type));
+            return YamlValue::Mapping(map);
+        }
+        FieldType::Object {
+            schema: Some(schema),
+        } => {
+            let mut map = Mapping::new();
+            insert(&mut map, "type", "object");
+            insert(&mut map, "fields", schema_fields_to_yaml(&schema.fields));
+            return YamlValue::Mapping(map);
+        }
+        FieldType::Object { schema: None } => "object",
+    };
+    YamlValue::from(type_name)
+}
+
+fn when_block_to_yaml(when: &WhenBlock) -> YamlValue {
+    let mut map = Mapping::new();
+    insert_opt(&mut map, "event_type", when.event_type.clone());
+    if let Some(group) = &when.condition_group {
+        let (key, conditions) = condition_group_parts(group);
+        insert(&mut map, key, conditions);
+    }
+    if let Some(conditions) = &when.conditions {
+        let conditions: Vec<YamlValue> = conditions.iter().map(condition_expr_to_yaml).collect();
+        insert(&mut map, "conditions", conditions);
+    }
+    YamlValue::Mapping(map)
+}
+
+fn condition_group_parts(group: &ConditionGroup) -> (&'static str, Vec<YamlValue>) {
+    let (key, conditions) = match group {
+        ConditionGroup::All(conditions) => ("all", conditions),
+        ConditionGroup::Any(conditions) => ("any", conditions),
+        ConditionGroup::Not(conditions) => ("not", conditions),
+    };
+    let conditions = conditions
+        .iter()
+        .map(|condition| match condition {
+            Condition::Expression(expr) => condition_expr_to_yaml(expr),
+            Condition::Group(group) => {
+                let (key, conditions) = condition_group_parts(group);
+                let mut map = Mapping::new();
+                insert(&mut map, key, conditions);
+                YamlValue::Mapping(map)
+            }
+        })
+        .collect();
+    (key, conditions)
+}
+
+/// Logical groups nested in condition lists are written as `any:`/`all:` mappings
+fn condition_expr_to_yaml(expr: &Expression) -> YamlValue {
+    match expr {
+        Expression::LogicalGroup { op, conditions } => {
+            let key = match op {
+                LogicalGroupOp::Any => "any",
+                LogicalGroupOp::All => "all",
+            };
+            let conditions: Vec<YamlValue> =
+                conditions.iter().map(condition_expr_to_yaml).collect();
+            let mut map = Mapping::new();
+            insert(&mut map, key, conditions);
+            YamlValue::Mapping(map)
+        }
+        other => YamlValue::from(expression_to_dsl(other)),
+    }
+}
+
+fn step_to_yaml(step: &PipelineStep) -> YamlValue {
+    let mut map = Mapping::new();
+    insert(&mut map, "id", step.id.clone());
+    insert(&mut map, "name", step.name.clone());
+    insert(&mut map, "type", step.step_type.clone());
+    if let Some(when) = &step.when {
+        insert(&mut map, "when", when_block_to_yaml(when));
+    }
+
+    match &step.details {
+        StepDetails::Function { function, params } => {
+            insert(&mut map, "function", function.clone());
+            insert_params(&mut map, params);
+        }
+        StepDetails::Rule { rule } => insert(&mut map, "rule", rule.clone()),
+        StepDetails::Ruleset { ruleset } => insert(&mut map, "ruleset", ruleset.clone()),
+        StepDetails::SubPipeline { pipeline_id } => {
+            insert(&mut map, "pipeline", pipeline_id.clone())
+        }
+        StepDetails::Service {
+            service,
+            endpoint,
+            method,
+            topic,
+            query,
+            params,
+            output,
+        } => {
+            insert(&mut map, "service", service.clone());
+            insert_opt(&mut map, "endpoint", endpoint.clone());
+            insert_opt(&mut map, "method", method.clone());
+            insert_opt(&mut map, "topic", topic.clone());
+            insert_opt(&mut map, "query", query.clone());
+            insert_params(&mut map, params);
+            insert_opt(&mut map, "output", output.clone());
+        }
+        StepDetails::Api {
+            api_target,
+            endpoint,
+            params,
+            output,
+            timeout,
+            on_error,
+            min_success,
+        } => {
+            match api_target {
+                ApiTarget::Single { api } => insert(&mut map, "api", api.clone()),
+                ApiTarget::Any { any } => insert(&mut map, "any", any.clone()),
+                ApiTarget::All { all } => insert(&mut map, "all", all.clone()),
+            }
+            insert_opt(&mut map, "endpoint", endpoint.clone());
+            insert_params(&mut map, params);
+            insert_opt(&mut map, "output", output.clone());
+            insert_opt(&mut map, "timeout", *timeout);
+            insert_opt(&mut map, "on_error", on_error.clone());
+            insert_opt(&mut map, "min_success", min_success.map(|n| n as u64));
+        }
+        StepDetails::Emit {
+            emit,
+            target,
+            payload,
+            signals,
+        } => {
+            insert(&mut map, "emit", emit.as_str());
+            insert(&mut map, "target", target.clone());
+            insert_expression_map(&mut map, "payload", payload);
+            if !signals.is_empty() {
+                let signals: Vec<String> = signals
+                    .iter()
+                    .map(|signal| format!("{:?}", signal).to_lowercase())
+                    .collect();
+                insert(&mut map, "signals", signals);
+            }
+        }
+        StepDetails::Trigger { target, params } => {
+            insert(&mut map, "target", target.clone());
+            insert_params(&mut map, params);
+        }
+        StepDetails::Set { set } => insert(&mut map, "set", assignments_to_yaml(set)),
+        StepDetails::Let { declare } => insert(&mut map, "let", assignments_to_yaml(declare)),
+        StepDetails::Assign { assign } => insert(&mut map, "assign", assignments_to_yaml(assign)),
+        StepDetails::Extract { features } => {
+            if let Some(features) = features {
+                let features: Vec<YamlValue> = features
+                    .iter()
+                    .map(|feature| {
+                        let mut entry = Mapping::new();
+                        insert(&mut entry, "name", feature.name.clone());
+                        insert(&mut entry, "value", expression_to_dsl(&feature.value));
+                        YamlValue::Mapping(entry)
+                    })
+                    .collect();
+                insert(&mut map, "features", features);
+            }
+        }
+        StepDetails::Custom { fields } => {
+            for (key, value) in fields.iter().collect::<BTreeMap<_, _>>() {
+                if let Ok(value) = serde_yaml::to_value(value) {
+                    insert(&mut map, key, value);
+                }
+            }
+        }
+        StepDetails::Router {} | StepDetails::Unknown {} => {}
+    }
+
+    if let Some(routes) = &step.routes {
+        let routes: Vec<YamlValue> = routes
+            .iter()
+            .map(|route| {
+                let mut entry = Mapping::new();
+                insert(&mut entry, "next", route.next.clone());
+                insert(&mut entry, "when", when_block_to_yaml(&route.when));
+                YamlValue::Mapping(entry)
+            })
+            .collect();
+        insert(&mut map, "routes", routes);
+    }
+    insert_opt(&mut map, "default", step.default.clone());
+    if let Some(StepNext::StepId(next)) = &step.next {
+        insert(&mut map, "next", next.clone());
+    }
+
+    let mut wrapper = Mapping::new();
+    insert(&mut wrapper, "step", map);
+    YamlValue::Mapping(wrapper)
+}
+
+fn decision_rule_to_yaml(rule: &PipelineDecisionRule) -> YamlValue {
+    let mut map = Mapping::new();
+    if let Some(when) = &rule.when {
+        insert(&mut map, "when", when_block_to_yaml(when));
+    }
+    if rule.default {
+        insert(&mut map, "default", true);
+    }
+    insert(&mut map, "result", rule.result.clone());
+    if !rule.actions.is_empty() {
+        insert(&mut map, "actions", rule.actions.clone());
+    }
+    insert_opt(&mut map, "reason", rule.reason.clone());
+    YamlValue::Mapping(map)
+}
+
+/// Step params, sorted by name for stable output
+fn insert_params(map: &mut Mapping, params: &Option<HashMap<String, Expression>>) {
+    insert_expression_map(map, "params", params);
+}
+
+/// Variable assignments of a set, let or assign step, in order
+fn assignments_to_yaml(assignments: &[VariableAssignment]) -> Mapping {
+    let mut map = Mapping::new();
+    for assignment in assignments {
+        // A bare word is read back as a string literal, anything else as an expression
+        let value = value_expr_to_yaml(&assignment.value, |s| {
+            !s.is_empty() && s.chars().all(|c| c.is_alphanumeric() || c == '_')
+        });
+        insert(&mut map, &assignment.name, value);
+    }
+    map
+}
+
+/// Named expressions (step params, emit payload), sorted by name for stable output
+fn insert_expression_map(
+    map: &mut Mapping,
+    key: &str,
+    params: &Option<HashMap<String, Expression>>,
+) {
+    if let Some(params) = params {
+        let sorted: BTreeMap<_, _> = params.iter().collect();
+        let mut params_map = Mapping::new();
+        for (name, value) in sorted {
+            // Param strings containing '.' are read back as field paths
+            insert(
+                &mut params_map,
+                name,
+                value_expr_to_yaml(value, |s| !s.contains('.')),
+            );
+        }
+        insert(map, key, params_map);
+    }
+}
+
+/// Scalar literals are written as plain YAML values, everything else as DSL
+///
+/// `plain_string` tells whether a string literal survives being written
+/// unquoted; otherwise it is emitted as a quoted DSL string.
+fn value_expr_to_yaml(expr: &Expression, plain_string: impl Fn(&str) -> bool) -> YamlValue {
+    match expr {
+        Expression::Literal(Value::String(s)) if plain_string(s) => YamlValue::from(s.clone()),
+        Expression::Literal(Value::Number(n)) => number_to_yaml(*n),
+        Expression::Literal(Value::Bool(b)) => YamlValue::from(*b),
+        Expression::Literal(Value::Null) => YamlValue::Null,
+        other => YamlValue::from(expression_to_dsl(other)),
+    }
+}
+
+fn number_to_yaml(n: f64) -> YamlValue {
+    if n.fract() == 0.0 && n.abs() < i64::MAX as f64 {
+        YamlValue::from(n as i64)
+    } else {
+        YamlValue::from(n)
+    }
+}
+
+fn operand_to_dsl(expr: &Expression, needs_parens: impl Fn(u8) -> bool) -> String {
+    let rendered = expression_to_dsl(expr);
+    match expr {
+        Expression::Binary { op, .. } if needs_parens(operator_precedence(op)) => {
+            format!("({})", rendered)
+        }
+        Expression::Ternary { .. } => format!("({})", rendered),
+        // Null checks bind tighter than `&&`/`||` only
+        Expression::Unary {
+            op: UnaryOperator::IsNull | UnaryOperator::IsNotNull,
+            ..
+        } if needs_parens(2) => format!("({})", rendered),
+        _ => rendered,
+    }
+}
+
+/// Binding strength of each operator, mirroring the split order in the expression parser
+fn operator_precedence(op: &Operator) -> u8 {
+    match op {
+        Operator::And | Operator::Or => 1,
+        Operator::Contains
+        | Operator::StartsWith
+        | Operator::EndsWith
+        | Operator::Regex
+        | Operator::In
+        | Operator::NotIn
+        | Operator::InList
+        | Operator::NotInList => 2,
+        Operator::Eq | Operator::Ne | Operator::Lt | Operator::Gt | Operator::Le | Operator::Ge => {
+            3
+        }
+        Operator::Add | Operator::Sub => 4,
+        Operator::Mul | Operator::Div | Operator::Mod => 5,
+    }
+}
+
+fn operator_symbol(op: &Operator) -> &'static str {
+    match op {
+        Operator::Eq => "==",
+        Operator::Ne => "!=",
+        Operator::Lt => "<",
+        Operator::Gt => ">",
+        Operator::Le => "<=",
+        Operator::Ge => ">=",
+        Operator::Add => "+",
+        Operator::Sub => "-",
+        Operator::Mul => "*",
+        Operator::Div => "/",
+        Operator::Mod => "%",
+        Operator::And => "&&",
+        Operator::Or => "||",
+        Operator::Contains => "contains",
+        Operator::StartsWith => "starts_with",
+        Operator::EndsWith => "ends_with",
+        Operator::Regex => "regex",
+        Operator::In | Operator::InList => "in",
+        Operator::NotIn | Operator::NotInList => "not in",
+    }
+}
+
+fn literal_to_dsl(value: &Value) -> String {
+    match value {
+        Value::Null => "null".to_string(),
+        Value::Bool(b) => b.to_string(),
+        Value::Number(n) => {
+            if n.fract() == 0.0 && n.abs() < i64::MAX as f64 {
+                format!("{}", *n as i64)
+            } else {
+                n.to_string()
+            }
+        }
+        Value::String(s) => format!("\"{}\"", s),
+        Value::Array(items) => {
+            let items: Vec<String> = items.iter().map(literal_to_dsl).collect();
+            format!("[{}]", items.join(", "))
+        }
+        Value::Object(_) => serde_json::to_string(value).unwrap_or_else(|_| "{}".to_string()),
+    }
+}
+
+fn signal_name(signal: &Signal) -> &'static str {
+    match signal {
+        Signal::Approve => "approve",
+        Signal::Decline => "decline",
+        Signal::Review => "review",
+        Signal::Hold => "hold",
+        Signal::Pass => "pass",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use corint_parser::{ExpressionParser, PipelineParser, RuleParser, RulesetParser};
+
+    fn assert_expression_roundtrip(source: &str) {
+        let parsed = ExpressionParser::parse(source).unwrap();
+        let rendered = expression_to_dsl(&parsed);
+        assert_eq!(
+            ExpressionParser::parse(&rendered).unwrap(),
+            parsed,
+            "{} rendered as {}",
+            source,
+            rendered
+        );
+    }
+
+    #[test]
+    fn test_expression_roundtrip() {
+        assert_expression_roundtrip("event.amount > 1000");
+        assert_expression_roundtrip("(event.a + event.b) * 2 >= 10");
+        assert_expression_roundtrip("event.a - (event.b - event.c)");
+        assert_expression_roundtrip("event.a > 1 && (event.b < 2 || event.c == \"x\")");
+        assert_expression_roundtrip("event.ip in list.blocked_ips");
+        assert_expression_roundtrip("event.country not in [\"US\", \"CA\"]");
+        assert_expression_roundtrip("!(event.verified == true)");
+        assert_expression_roundtrip("any_of(event.items, item.price > 100)");
+        assert_expression_roundtrip("all_of(event.addresses, addr, addr.country == \"US\")");
+        assert_expression_roundtrip("sum(event.items[*].amount) > 500");
+        assert_expression_roundtrip("result.fraud_rules.total_score >= 80");
+    }
+
+    #[test]
+    fn test_rule_roundtrip() {
+        let source = r#"
+rule:
+  id: high_amount
+  name: High Amount
+  description: Large transaction
+  when:
+    event_type: transaction
+    all:
+      - event.amount > 1000
+      - any:
+          - event.country == "NG"
+          - event.ip in list.blocked_ips
+  score: 50
+"#;
+        let rule = RuleParser::parse(source).unwrap();
+
+        let yaml = rule_to_yaml(&rule).unwrap();
+
+        assert_eq!(RuleParser::parse(&yaml).unwrap(), rule);
+        assert!(yaml.contains("- event.amount > 1000"));
+    }
+
+    #[test]
+    fn test_ruleset_roundtrip() {
+        let source = r#"
+ruleset:
+  id: fraud_rules
+  name: Fraud Rules
+  rules: [high_amount, new_device]
+  conclusion:
+    - when: total_score >= 100
+      signal: decline
+      actions: [BLOCK_CARD]
+      reason: Score too high
+    - default: true
+      signal: approve
+"#;
+        let ruleset = RulesetParser::parse(source).unwrap();
+
+        let yaml = ruleset_to_yaml(&ruleset).unwrap();
+
+        assert_eq!(RulesetParser::parse(&yaml).unwrap(), ruleset);
+    }
+
+    #[test]
+    fn test_pipeline_roundtrip() {
+        let source = r#"
+pipeline:
+  id: fraud_pipeline
+  name: Fraud Pipeline
+  entry: route
+  schema:
+    amount: { type: number, required: true }
+    user_id: string
+    items:
+      type: array
+      items: { type: object, fields: { price: number } }
+  steps:
+    - step:
+        id: route
+        name: Route
+        type: router
+        routes:
+          - next: fraud_check
+            when:
+              all:
+                - event.amount > 100
+        default: assign
+    - step:
+        id: assign
+        name: Assign
+        type: set
+        set:
+          risk_band: high
+          limit: event.amount * 2
+        next: fraud_check
+    - step:
+        id: fraud_check
+        name: Fraud Check
+        type: ruleset
+        ruleset: fraud_rules
+        next: open_case
+    - step:
+        id: open_case
+        name: Open Case
+        type: emit
+        emit: case
+        target: fraud_review
+        payload:
+          user_id: event.user_id
+          priority: high
+        signals: [decline, review]
+  decision:
+    - when:
+        all:
+          - results.fraud_rules.signal == "decline"
+      result: decline
+      reason: Fraud detected
+    - default: true
+      result: approve
+"#;
+        let pipeline = PipelineParser::parse(source).unwrap();
+
+        let yaml = pipeline_to_yaml(&pipeline).unwrap();
+
+        assert_eq!(PipelineParser::parse(&yaml).unwrap(), pipeline);
+    }
+}
@@ -1,6 +1,12 @@
 //! Integration tests for FileSystemRepository
 
-use corint_repository::{FileSystemRepository, Repository};
+use corint_repository::{
+    AccessPolicy, Actor, AuditOperation, AuditQuery, AuthorizedRepository, BundleArtifactKind,
+    FileSystemRepository, Grant, InMemoryAuditLog, IntegrityConfig, IntegrityManifest,
+    Permission, Repository, RepositoryBundle, RepositoryConfig, RepositoryError,
+    RepositoryLoader, WritableRepository,
+};
+use std::sync::Arc;
 use tempfile::TempDir;
 use tokio::fs;
 
@@ -176,3 +182,211 @@ async fn test_exists() {
 
     assert!(!not_exists);
 }
+
+#[tokio::test]
+async fn test_save_new_rule_creates_file() {
+    let (temp, mut repo) = create_test_repo().await;
+
+    let (mut rule, _) = repo.load_rule("test_fraud_rule").await.unwrap();
+    rule.id = "generated_rule".to_string();
+    rule.score = 75;
+
+    repo.save_rule(&rule).await.expect("Failed to save rule");
+
+    assert!(temp.path().join("library/rules/generated_rule.yaml").exists());
+    let (loaded, _) = repo.load_rule("generated_rule").await.unwrap();
+    assert_eq!(loaded, rule);
+}
+
+#[tokio::test]
+async fn test_save_existing_rule_rewrites_in_place() {
+    let (temp, mut repo) = create_test_repo().await;
+    let path = temp.path().join("library/rules/fraud/test_fraud_rule.yaml");
+
+    // Populate the cache so the save has to invalidate it
+    let (mut rule, _) = repo.load_rule("test_fraud_rule").await.unwrap();
+    rule.score = 90;
+
+    repo.save_rule(&rule).await.expect("Failed to save rule");
+
+    let content = fs::read_to_string(&path).await.unwrap();
+    assert!(content.contains("version:"));
+    assert!(!temp.path().join("library/rules/test_fraud_rule.yaml").exists());
+
+    let (loaded, _) = repo.load_rule("test_fraud_rule").await.unwrap();
+    assert_eq!(loaded.score, 90);
+}
+
+#[tokio::test]
+async fn test_concurrent_saves_of_one_rule() {
+    let (temp, mut repo) = create_test_repo().await;
+    let mut other = FileSystemRepository::new(temp.path()).unwrap();
+
+    let (mut rule, _) = repo.load_rule("test_fraud_rule").await.unwrap();
+    let mut other_rule = rule.clone();
+    rule.score = 60;
+    other_rule.score = 70;
+
+    for _ in 0..20 {
+        let (first, second) = tokio::join!(repo.save_rule(&rule), other.save_rule(&other_rule));
+        first.expect("Failed to save rule");
+        second.expect("Failed to save rule");
+    }
+
+    let (loaded, _) = repo.load_rule("test_fraud_rule").await.unwrap();
+    assert!(loaded.score == 60 || loaded.score == 70);
+}
+
+#[tokio::test]
+async fn test_delete_rule() {
+    let (temp, repo) = create_test_repo().await;
+
+    repo.delete_rule("test_fraud_rule")
+        .await
+        .expect("Failed to delete rule");
+
+    assert!(!temp
+        .path()
+        .join("library/rules/fraud/test_fraud_rule.yaml")
+        .exists());
+    assert!(repo.delete_rule("test_fraud_rule").await.is_err());
+}
+
+#[tokio::test]
+async fn test_delete_rule_rejects_paths_outside_repository() {
+    let (temp, repo) = create_test_repo().await;
+    let outside = TempDir::new().unwrap();
+    let victim = outside.path().join("victim.yaml");
+    fs::write(&victim, "rule:\n  id: victim\n").await.unwrap();
+
+    let escaping = format!(
+        "../{}/victim.yaml",
+        outside.path().file_name().unwrap().to_string_lossy()
+    );
+    assert!(repo.delete_rule(&escaping).await.is_err());
+    assert!(repo.delete_rule(victim.to_str().unwrap()).await.is_err());
+    assert!(repo.delete_rule("pipelines/../registry.yaml").await.is_err());
+    assert!(victim.exists());
+
+    // A file that holds no rule is not deleted either
+    assert!(repo
+        .delete_rule("library/rulesets/test_ruleset.yaml")
+        .await
+        .is_err());
+    assert!(temp
+        .path()
+        .join("library/rulesets/test_ruleset.yaml")
+        .exists());
+}
+
+#[tokio::test]
+async fn test_bundle_export_import_roundtrip() {
+    let (source_dir, source) = create_test_repo().await;
+    fs::create_dir_all(source_dir.path().join("configs/lists"))
+        .await
+        .unwrap();
+    fs::write(
+        source_dir.path().join("configs/lists/blocked_ips.yaml"),
+        "lists:\n  - id: blocked_ips\n    backend: memory\n",
+    )
+    .await
+    .unwrap();
+
+    let mut bundle = source.export_bundle().await.unwrap();
+    bundle.sign(b"promotion-key");
+    let bundle = RepositoryBundle::from_json(&bundle.to_json().unwrap()).unwrap();
+
+    let target_dir = TempDir::new().unwrap();
+    let mut target = FileSystemRepository::new(target_dir.path()).unwrap();
+
+    assert!(target.import_bundle(&bundle, Some(b"wrong-key")).await.is_err());
+    let imported = target
+        .import_bundle(&bundle, Some(b"promotion-key"))
+        .await
+        .unwrap();
+
+    assert_eq!(imported, bundle.artifacts.len());
+    let (rule, _) = target.load_rule("test_fraud_rule").await.unwrap();
+    assert_eq!(rule.score, 50);
+    assert!(target.load_ruleset("test_ruleset").await.is_ok());
+    assert!(target_dir
+        .path()
+        .join("configs/lists/blocked_ips.yaml")
+        .exists());
+}
+
+#[tokio::test]
+async fn test_authorized_repository_enforces_policy_and_audits() {
+    let (temp, repo) = create_test_repo().await;
+    let policy = AccessPolicy::new().with_grant(
+        Grant::new("fraud_analyst", [Permission::Write])
+            .for_kind(BundleArtifactKind::Rule)
+            .matching("test_fraud_*"),
+    );
+    let mut repo = AuthorizedRepository::new(repo, policy, Arc::new(InMemoryAuditLog::new()));
+
+    let analyst = Actor::new("alice").with_role("fraud_analyst");
+    let (mut rule, _) = repo.load_rule("test_fraud_rule").await.unwrap();
+    rule.score = 90;
+    repo.save_rule(&analyst, &rule).await.unwrap();
+
+    // Analysts may not delete, and outsiders may not write
+    let denied = repo.delete_rule(&analyst, "test_fraud_rule").await;
+    assert!(matches!(denied, Err(RepositoryError::PermissionDenied { .. })));
+    rule.id = "unrelated_rule".to_string();
+    assert!(repo.save_rule(&Actor::new("mallory"), &rule).await.is_err());
+    assert!(!temp.path().join("library/rules/unrelated_rule.yaml").exists());
+
+    let entries = repo
+        .audit_log(&AuditQuery::new().for_artifact("test_fraud_rule"))
+        .await
+        .unwrap();
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].actor, "alice");
+    assert_eq!(entries[0].operation, AuditOperation::Update);
+    assert!(entries[0].content.as_deref().unwrap().contains("90"));
+}
+
+#[tokio::test]
+async fn test_loader_verifies_integrity_manifest() {
+    let (temp, _repo) = create_test_repo().await;
+    let base = temp.path().to_str().unwrap().to_string();
+
+    let content = RepositoryLoader::new(RepositoryConfig::file_system(&base))
+        .load_all()
+        .await
+        .unwrap();
+
+    let signing_key = ed25519_dalek::SigningKey::from_bytes(&[3u8; 32]);
+    let mut manifest = IntegrityManifest::from_content(&content);
+    manifest.sign(&signing_key);
+    fs::write(temp.path().join("integrity.json"), manifest.to_json().unwrap())
+        .await
+        .unwrap();
+
+    let public_key: String = signing_key
+        .verifying_key()
+        .as_bytes()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect();
+    let config = RepositoryConfig::file_system(&base)
+        .with_integrity(IntegrityConfig::new().with_public_key(public_key));
+
+    let verified = RepositoryLoader::new(config.clone()).load_all().await.unwrap();
+    assert_eq!(verified.rules.len(), content.rules.len());
+
+    // Tampering with an approved rule is rejected before compilation
+    fs::write(
+        temp.path().join("library/rules/fraud/test_fraud_rule.yaml"),
+        "rule:\n  id: test_fraud_rule\n  name: Tampered\n  when:\n    conditions:\n      - amount > 0\n  score: 0\n",
+    )
+    .await
+    .unwrap();
+
+    let result = RepositoryLoader::new(config).load_all().await;
+    assert!(matches!(
+        result,
+        Err(RepositoryError::IntegrityViolation { .. })
+    ));
+}
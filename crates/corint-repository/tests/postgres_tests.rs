@@ -1,7 +1,8 @@
 //! Integration tests for PostgresRepository
 //!
 //! These tests require a PostgreSQL database to be running.
-//! Set the DATABASE_URL environment variable to run these tests:
+//! Set the DATABASE_URL environment variable to run these tests; the schema
+//! is created by the embedded migrations:
 //!
 //! ```bash
 //! export DATABASE_URL="postgresql://localhost/corint_test"
@@ -12,7 +13,8 @@
 mod postgres_tests {
     use corint_core::ast::{Expression, Rule, WhenBlock};
     use corint_repository::{
-        CacheableRepository, PostgresRepository, Repository, WritableRepository,
+        ArtifactStatus, BundleArtifactKind, CacheableRepository, PostgresRepository, Repository,
+        RepositoryError, WritableRepository,
     };
     use sqlx::postgres::PgPool;
 
@@ -31,6 +33,10 @@ mod postgres_tests {
             .await
             .expect("Failed to connect to test database. Make sure PostgreSQL is running and DATABASE_URL is set correctly.");
 
+        corint_repository::migrations::run(&pool)
+            .await
+            .expect("Failed to apply migrations");
+
         // Clean up existing test data
         sqlx::query("TRUNCATE TABLE rules, rulesets, pipelines CASCADE")
             .execute(&pool)
@@ -52,13 +58,17 @@ mod postgres_tests {
             params: None,
             when: WhenBlock {
                 event_type: None,
-                conditions: vec![Expression::Binary {
+                condition_group: None,
+                conditions: Some(vec![Expression::Binary {
                     op: Operator::Gt,
                     left: Box::new(Expression::FieldAccess(vec!["amount".to_string()])),
                     right: Box::new(Expression::Literal(Value::Number(1000.0))),
-                }],
+                }]),
             },
             score: 50,
+            priority: None,
+            effective_from: None,
+            effective_until: None,
             metadata: None,
         }
     }
@@ -108,6 +118,31 @@ mod postgres_tests {
         assert_eq!(loaded_rule.score, 75);
     }
 
+    #[tokio::test]
+    #[ignore] // Requires database setup
+    async fn test_migrations_are_idempotent() {
+        let db_url = get_database_url();
+
+        // The first run may apply migrations; a second one finds nothing to do
+        let first = PostgresRepository::migrate_only(&db_url)
+            .await
+            .expect("Failed to apply migrations");
+        let latest = corint_repository::migrations::migrations()
+            .last()
+            .map(|migration| migration.version);
+        assert_eq!(first.current_version, latest);
+
+        let second = PostgresRepository::migrate_only(&db_url)
+            .await
+            .expect("Failed to re-run migrations");
+        assert!(second.is_up_to_date());
+
+        let repo = PostgresRepository::new_without_migrations(&db_url)
+            .await
+            .expect("Failed to connect");
+        assert!(repo.pending_migrations().await.unwrap().is_empty());
+    }
+
     #[tokio::test]
     #[ignore] // Requires database setup
     async fn test_rule_not_found() {
@@ -261,4 +296,74 @@ mod postgres_tests {
         repo.set_cache_enabled(true);
         assert!(repo.is_cache_enabled());
     }
+
+    #[tokio::test]
+    #[ignore] // Requires database setup
+    async fn test_draft_lifecycle() {
+        let pool = setup_test_db().await;
+        let mut repo = PostgresRepository::with_pool(pool);
+        repo.set_cache_enabled(false);
+
+        let rule = create_test_rule("test_rule_draft");
+        repo.save_rule(&rule).await.expect("Failed to save rule");
+
+        // A draft does not affect the published version
+        let draft = r#"
+rule:
+  id: test_rule_draft
+  name: Draft Rule
+  when:
+    conditions:
+      - amount > 5000
+  score: 90
+"#;
+        repo.save_draft(BundleArtifactKind::Rule, "test_rule_draft", draft)
+            .await
+            .expect("Failed to save draft");
+
+        let (live, _) = repo.load_rule("test_rule_draft").await.unwrap();
+        assert_eq!(live.score, 50);
+        let loaded_draft = repo
+            .load_draft(BundleArtifactKind::Rule, "test_rule_draft")
+            .await
+            .unwrap();
+        assert_eq!(loaded_draft, draft);
+
+        // Publishing promotes the draft and archives the old version
+        repo.set_status(
+            BundleArtifactKind::Rule,
+            "test_rule_draft",
+            ArtifactStatus::Published,
+        )
+        .await
+        .expect("Failed to publish draft");
+
+        let (live, _) = repo.load_rule("test_rule_draft").await.unwrap();
+        assert_eq!(live.score, 90);
+        assert!(repo
+            .load_draft(BundleArtifactKind::Rule, "test_rule_draft")
+            .await
+            .is_err());
+
+        // Archiving removes the rule from live traffic
+        repo.set_status(
+            BundleArtifactKind::Rule,
+            "test_rule_draft",
+            ArtifactStatus::Archived,
+        )
+        .await
+        .expect("Failed to archive rule");
+        assert!(repo.load_rule("test_rule_draft").await.is_err());
+        assert!(!repo.list_rules().await.unwrap().contains(&"test_rule_draft".to_string()));
+
+        assert!(matches!(
+            repo.set_status(
+                BundleArtifactKind::Rule,
+                "test_rule_draft",
+                ArtifactStatus::Draft,
+            )
+            .await,
+            Err(RepositoryError::InvalidStatusTransition { .. })
+        ));
+    }
 }
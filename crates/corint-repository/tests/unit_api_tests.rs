@@ -455,3 +455,170 @@ async fn test_empty_manifest() {
         .expect("Failed to check exists");
     assert!(!exists);
 }
+
+#[tokio::test]
+async fn test_save_and_delete_rule() {
+    use corint_repository::WritableRepository;
+
+    let mut server = Server::new_async().await;
+    let _m = create_mock_manifest(&mut server);
+
+    let put_mock = server
+        .mock("PUT", "/rules/new_rule.yaml")
+        .match_header("Authorization", "Bearer write_key")
+        .match_header("content-type", "application/yaml")
+        .with_status(201)
+        .expect(1)
+        .create();
+    let delete_mock = server
+        .mock("DELETE", "/rules/new_rule.yaml")
+        .with_status(204)
+        .expect(1)
+        .create();
+
+    let mut repo = ApiRepository::new(&server.url(), Some("write_key"))
+        .await
+        .expect("Failed to create repository");
+
+    let rule = corint_core::ast::Rule::new(
+        "new_rule".to_string(),
+        "New Rule".to_string(),
+        corint_core::ast::WhenBlock::new(),
+        40,
+    );
+    repo.save_rule(&rule).await.expect("Failed to save rule");
+    put_mock.assert();
+
+    // Saved rules are listed and served from the local cache
+    assert!(repo.exists("new_rule").await.unwrap());
+    let (loaded, _) = repo.load_rule("new_rule").await.unwrap();
+    assert_eq!(loaded.name, "New Rule");
+
+    repo.delete_rule("new_rule").await.expect("Failed to delete rule");
+    delete_mock.assert();
+    assert!(!repo.exists("new_rule").await.unwrap());
+}
+
+#[tokio::test]
+async fn test_delete_missing_rule() {
+    use corint_repository::{RepositoryError, WritableRepository};
+
+    let mut server = Server::new_async().await;
+    let _m = create_mock_manifest(&mut server);
+    let _delete_mock = server
+        .mock("DELETE", "/rules/test_rule.yaml")
+        .with_status(404)
+        .create();
+
+    let repo = ApiRepository::new(&server.url(), None::<String>)
+        .await
+        .expect("Failed to create repository");
+
+    let result = repo.delete_rule("test_rule").await;
+    assert!(matches!(result, Err(RepositoryError::NotFound { .. })));
+}
+
+#[tokio::test]
+async fn test_invalidate_refetches_artifact() {
+    use corint_repository::InvalidationNotice;
+
+    let mut server = Server::new_async().await;
+    let manifest_mock = create_mock_manifest(&mut server).expect(2);
+
+    let rule_yaml = |score: i32| {
+        format!(
+            "id: test_rule\nname: Test Rule\nwhen:\n  conditions: []\nscore: {}\n",
+            score
+        )
+    };
+    let first = server
+        .mock("GET", "/rules/test_rule.yaml")
+        .with_status(200)
+        .with_body(rule_yaml(50))
+        .expect(1)
+        .create();
+
+    let repo = ApiRepository::new(&server.url(), None::<String>)
+        .await
+        .expect("Failed to create repository");
+
+    // Second load is served from the cache
+    assert_eq!(repo.load_rule("test_rule").await.unwrap().0.score, 50);
+    assert_eq!(repo.load_rule("test_rule").await.unwrap().0.score, 50);
+    first.assert();
+    first.remove();
+
+    let _second = server
+        .mock("GET", "/rules/test_rule.yaml")
+        .with_status(200)
+        .with_body(rule_yaml(80))
+        .create();
+
+    let notice = InvalidationNotice::from_json(br#"{"kind": "rule", "id": "test_rule"}"#).unwrap();
+    repo.invalidate(&notice).await.expect("Failed to invalidate");
+    manifest_mock.assert();
+
+    assert_eq!(repo.load_rule("test_rule").await.unwrap().0.score, 80);
+}
+
+#[tokio::test]
+async fn test_sync_refreshes_drifted_artifacts() {
+    use corint_repository::{DriftKind, SyncableRepository};
+
+    let mut server = Server::new_async().await;
+    let manifest_mock = create_mock_manifest(&mut server);
+
+    let rule_yaml = |score: i32| {
+        format!(
+            "id: test_rule\nname: Test Rule\nwhen:\n  conditions: []\nscore: {}\n",
+            score
+        )
+    };
+    let first = server
+        .mock("GET", "/rules/test_rule.yaml")
+        .with_status(200)
+        .with_body(rule_yaml(50))
+        .create();
+    let _ruleset_mock = server
+        .mock("GET", "/rulesets/test_ruleset.yaml")
+        .with_status(200)
+        .with_body("id: test_ruleset\nname: Test Ruleset\nrules:\n  - test_rule\nconclusion: []\n")
+        .create();
+
+    let repo = ApiRepository::new(&server.url(), None::<String>)
+        .await
+        .expect("Failed to create repository");
+    repo.load_rule("test_rule").await.unwrap();
+    repo.load_ruleset("test_ruleset").await.unwrap();
+
+    // Nothing changed remotely
+    let report = repo.sync().await.expect("Failed to sync");
+    assert_eq!(report.checked, 2);
+    assert!(!report.has_drift());
+
+    // The rule is edited and the ruleset deleted behind the cache's back
+    first.remove();
+    manifest_mock.remove();
+    let _rule_mock = server
+        .mock("GET", "/rules/test_rule.yaml")
+        .with_status(200)
+        .with_body(rule_yaml(80))
+        .create();
+    let base_url = server.url();
+    let _manifest_mock = server
+        .mock("GET", "/manifest")
+        .with_status(200)
+        .with_body(format!(
+            r#"{{"rules": [{{"id": "test_rule", "url": "{base_url}/rules/test_rule.yaml"}}]}}"#
+        ))
+        .create();
+
+    let report = repo.sync().await.expect("Failed to sync");
+    assert_eq!(report.count(DriftKind::Changed), 1);
+    assert_eq!(report.count(DriftKind::Removed), 1);
+    assert_eq!(report.refreshed, 1);
+    assert!(report.errors.is_empty());
+
+    assert_eq!(repo.load_rule("test_rule").await.unwrap().0.score, 80);
+    assert!(repo.load_ruleset("test_ruleset").await.is_err());
+}
@@ -0,0 +1,119 @@
+//! Unit tests for CompositeRepository layering
+
+use corint_repository::{
+    CacheableRepository, CompositeRepository, FileSystemRepository, Repository,
+};
+use tempfile::TempDir;
+use tokio::fs;
+
+fn rule_yaml(id: &str, score: i32) -> String {
+    format!(
+        r#"version: "0.1"
+
+rule:
+  id: {id}
+  name: {id}
+  when:
+    conditions:
+      - amount > 1000
+  score: {score}
+"#
+    )
+}
+
+/// Create a file system repository containing the given rules
+async fn create_layer(rules: &[(&str, i32)]) -> (TempDir, FileSystemRepository) {
+    let temp_dir = TempDir::new().unwrap();
+    let rules_dir = temp_dir.path().join("library/rules");
+    fs::create_dir_all(&rules_dir).await.unwrap();
+
+    for (id, score) in rules {
+        fs::write(
+            rules_dir.join(format!("{}.yaml", id)),
+            rule_yaml(id, *score),
+        )
+        .await
+        .unwrap();
+    }
+
+    let repo = FileSystemRepository::new(temp_dir.path()).unwrap();
+    (temp_dir, repo)
+}
+
+async fn create_composite() -> (TempDir, TempDir, CompositeRepository) {
+    let (override_dir, overrides) = create_layer(&[("fraud_check", 90)]).await;
+    let (base_dir, base) = create_layer(&[("fraud_check", 50), ("kyc_check", 30)]).await;
+
+    let repo = CompositeRepository::new()
+        .with_layer("overrides", overrides)
+        .with_layer("base", base);
+    (override_dir, base_dir, repo)
+}
+
+#[tokio::test]
+async fn test_override_layer_takes_priority() {
+    let (_o, _b, repo) = create_composite().await;
+
+    let (rule, _) = repo.load_rule("fraud_check").await.unwrap();
+    assert_eq!(rule.score, 90);
+
+    let (rule, _) = repo.load_rule("kyc_check").await.unwrap();
+    assert_eq!(rule.score, 30);
+
+    assert_eq!(
+        repo.resolve_layer("library/rules/fraud_check.yaml")
+            .await
+            .unwrap(),
+        Some("overrides")
+    );
+    assert_eq!(
+        repo.resolve_layer("library/rules/kyc_check.yaml")
+            .await
+            .unwrap(),
+        Some("base")
+    );
+}
+
+#[tokio::test]
+async fn test_missing_artifact_in_all_layers() {
+    let (_o, _b, repo) = create_composite().await;
+
+    assert!(repo.load_rule("unknown_rule").await.is_err());
+    assert!(!repo.exists("unknown_rule").await.unwrap());
+    assert!(repo.load_registry().await.is_err());
+}
+
+#[tokio::test]
+async fn test_list_merges_layers() {
+    let (_o, _b, repo) = create_composite().await;
+
+    let mut rules = repo.list_rules().await.unwrap();
+    rules.sort();
+    assert_eq!(rules.len(), 2);
+    assert!(rules.iter().any(|r| r.contains("fraud_check")));
+    assert!(rules.iter().any(|r| r.contains("kyc_check")));
+}
+
+#[tokio::test]
+async fn test_layer_stats() {
+    let (_o, _b, repo) = create_composite().await;
+
+    repo.load_rule("fraud_check").await.unwrap();
+    repo.load_rule("kyc_check").await.unwrap();
+    repo.load_rule("kyc_check").await.unwrap();
+
+    assert_eq!(repo.layer_names(), vec!["overrides", "base"]);
+
+    let stats = repo.layer_stats();
+    assert_eq!(stats[0].served, 1);
+    assert_eq!(stats[0].fallthrough, 2);
+    assert_eq!(stats[1].served, 2);
+    assert_eq!(stats[1].fallthrough, 0);
+
+    // The base layer cached kyc_check on first load
+    assert_eq!(stats[1].cache.hits, 1);
+    assert_eq!(
+        repo.cache_stats().hits,
+        stats[0].cache.hits + stats[1].cache.hits
+    );
+}
@@ -0,0 +1,195 @@
+//! Unit tests for the repository dependency graph
+
+use corint_repository::{
+    BundleArtifactKind, DependencyNode, DependencyRelation, FileSystemRepository, Repository,
+};
+use tempfile::TempDir;
+use tokio::fs;
+
+const VELOCITY_RULE: &str = r#"version: "0.1"
+
+rule:
+  id: velocity_check
+  name: Velocity check
+  when:
+    all:
+      - features.txn_count_24h > 10
+      - any:
+          - event.user.id in list.watchlist
+  score: 60
+"#;
+
+const AMOUNT_RULE: &str = r#"version: "0.1"
+
+rule:
+  id: high_amount
+  name: High amount
+  when:
+    conditions:
+      - event.amount > 1000
+  score: 40
+"#;
+
+const UNUSED_RULE: &str = r#"version: "0.1"
+
+rule:
+  id: unused_rule
+  name: Unused rule
+  when:
+    conditions:
+      - event.amount > 5
+  score: 1
+"#;
+
+const BASE_RULESET: &str = r#"version: "0.1"
+
+ruleset:
+  id: base_rules
+  name: Base rules
+  rules:
+    - high_amount
+  conclusion:
+    - default: true
+      signal: approve
+"#;
+
+const PAYMENT_RULESET: &str = r#"version: "0.1"
+
+import:
+  rules:
+    - library/rules/velocity_check.yaml
+
+---
+
+ruleset:
+  id: payment_rules
+  name: Payment rules
+  extends: base_rules
+  rules:
+    - velocity_check
+    - missing_rule
+  conclusion:
+    - when: total_score >= 100
+      signal: decline
+    - default: true
+      signal: approve
+"#;
+
+const PAYMENT_PIPELINE: &str = r#"version: "0.1"
+
+pipeline:
+  id: payment_pipeline
+  name: Payment pipeline
+  entry: check
+  steps:
+    - step:
+        id: check
+        name: Check
+        type: ruleset
+        ruleset: payment_rules
+        next: end
+"#;
+
+async fn create_test_repo() -> (TempDir, FileSystemRepository) {
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path();
+
+    for dir in ["library/rules", "library/rulesets", "pipelines"] {
+        fs::create_dir_all(root.join(dir)).await.unwrap();
+    }
+
+    let files = [
+        ("library/rules/velocity_check.yaml", VELOCITY_RULE),
+        ("library/rules/high_amount.yaml", AMOUNT_RULE),
+        ("library/rules/unused_rule.yaml", UNUSED_RULE),
+        ("library/rulesets/base_rules.yaml", BASE_RULESET),
+        ("library/rulesets/payment_rules.yaml", PAYMENT_RULESET),
+        ("pipelines/payment_pipeline.yaml", PAYMENT_PIPELINE),
+    ];
+    for (path, content) in files {
+        fs::write(root.join(path), content).await.unwrap();
+    }
+
+    let repo = FileSystemRepository::new(root).unwrap();
+    (temp_dir, repo)
+}
+
+fn node(kind: BundleArtifactKind, id: &str) -> DependencyNode {
+    DependencyNode::new(kind, id)
+}
+
+#[tokio::test]
+async fn test_dependency_graph_of_pipeline() {
+    let (_temp, repo) = create_test_repo().await;
+
+    let graph = repo.dependency_graph("payment_pipeline").await.unwrap();
+    let pipeline = node(BundleArtifactKind::Pipeline, "payment_pipeline");
+    let ruleset = node(BundleArtifactKind::Ruleset, "payment_rules");
+    let velocity = node(BundleArtifactKind::Rule, "velocity_check");
+
+    assert_eq!(graph.roots, vec![pipeline.clone()]);
+
+    let deps: Vec<String> = graph
+        .transitive_dependencies(&pipeline)
+        .into_iter()
+        .map(|n| n.to_string())
+        .collect();
+    assert_eq!(
+        deps,
+        vec![
+            "rule:high_amount",
+            "rule:missing_rule",
+            "rule:velocity_check",
+            "ruleset:base_rules",
+            "ruleset:payment_rules",
+            "list:watchlist",
+            "feature:txn_count_24h",
+        ]
+    );
+
+    // The imported file resolves to the same node as the ID reference
+    let relations: Vec<DependencyRelation> = graph
+        .edges
+        .iter()
+        .filter(|e| e.from == ruleset && e.to == velocity)
+        .map(|e| e.relation)
+        .collect();
+    assert_eq!(
+        relations,
+        vec![DependencyRelation::Contains, DependencyRelation::Imports]
+    );
+
+    assert_eq!(graph.unresolved.len(), 1);
+    assert_eq!(graph.unresolved[0].node.id, "missing_rule");
+    assert!(graph.topological_order().is_some());
+}
+
+#[tokio::test]
+async fn test_impact_and_safe_deletion() {
+    let (_temp, repo) = create_test_repo().await;
+
+    let graph = repo.full_dependency_graph().await.unwrap();
+    let feature = node(BundleArtifactKind::Feature, "txn_count_24h");
+    let base = node(BundleArtifactKind::Ruleset, "base_rules");
+
+    let affected: Vec<&str> = graph
+        .affected_pipelines(&feature)
+        .into_iter()
+        .map(|n| n.id.as_str())
+        .collect();
+    assert_eq!(affected, vec!["payment_pipeline"]);
+    assert!(graph
+        .impact(&base)
+        .contains(&node(BundleArtifactKind::Ruleset, "payment_rules")));
+
+    assert!(!graph.can_delete(&base));
+    assert!(graph.can_delete(&node(BundleArtifactKind::Rule, "unused_rule")));
+    assert!(!graph.contains(&node(BundleArtifactKind::Rule, "unused_rule")));
+}
+
+#[tokio::test]
+async fn test_dependency_graph_of_missing_pipeline() {
+    let (_temp, repo) = create_test_repo().await;
+
+    assert!(repo.dependency_graph("no_such_pipeline").await.is_err());
+}
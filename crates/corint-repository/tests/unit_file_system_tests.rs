@@ -342,6 +342,36 @@ async fn test_cache_enable_disable() {
     );
 }
 
+#[tokio::test]
+async fn test_not_found_is_cached_until_cleared() {
+    let (temp, mut repo) = create_test_repo().await;
+
+    assert!(repo.load_rule("new_rule").await.is_err());
+
+    fs::write(
+        temp.path().join("library/rules/new_rule.yaml"),
+        r#"version: "0.1"
+
+rule:
+  id: new_rule
+  name: New Rule
+  when:
+    conditions:
+      - amount > 10
+  score: 10
+"#,
+    )
+    .await
+    .unwrap();
+
+    // The earlier "not found" is still remembered
+    assert!(repo.load_rule("new_rule").await.is_err());
+
+    repo.clear_cache_entry("new_rule");
+    let (rule, _) = repo.load_rule("new_rule").await.unwrap();
+    assert_eq!(rule.id, "new_rule");
+}
+
 // ==================================================
 // List Operations Tests
 // ==================================================
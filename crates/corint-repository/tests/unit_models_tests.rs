@@ -21,6 +21,7 @@ fn test_cache_stats_hit_rate_zero_requests() {
         misses: 0,
         size: 0,
         memory_bytes: 0,
+        evictions: 0,
     };
 
     assert_eq!(stats.hit_rate(), 0.0);
@@ -33,6 +34,7 @@ fn test_cache_stats_hit_rate_all_hits() {
         misses: 0,
         size: 50,
         memory_bytes: 10000,
+        evictions: 0,
     };
 
     assert_eq!(stats.hit_rate(), 1.0);
@@ -45,6 +47,7 @@ fn test_cache_stats_hit_rate_all_misses() {
         misses: 100,
         size: 50,
         memory_bytes: 10000,
+        evictions: 0,
     };
 
     assert_eq!(stats.hit_rate(), 0.0);
@@ -57,6 +60,7 @@ fn test_cache_stats_hit_rate_mixed() {
         misses: 25,
         size: 50,
         memory_bytes: 10000,
+        evictions: 0,
     };
 
     assert_eq!(stats.hit_rate(), 0.75);
@@ -69,6 +73,7 @@ fn test_cache_stats_hit_rate_precision() {
         misses: 3,
         size: 2,
         memory_bytes: 1000,
+        evictions: 0,
     };
 
     // 2/5 = 0.4
@@ -0,0 +1,177 @@
+//! Unit tests for repository preload and cache warm-up
+
+use corint_repository::{
+    BundleArtifactKind, CacheableRepository, FileSystemRepository, Repository,
+};
+use tempfile::TempDir;
+use tokio::fs;
+
+fn rule_yaml(id: &str) -> String {
+    format!(
+        r#"version: "0.1"
+
+rule:
+  id: {id}
+  name: {id}
+  when:
+    conditions:
+      - amount > 1000
+  score: 50
+"#
+    )
+}
+
+fn ruleset_yaml(id: &str, rules: &[&str]) -> String {
+    let rules: String = rules.iter().map(|r| format!("    - {}\n", r)).collect();
+    format!(
+        r#"version: "0.1"
+
+ruleset:
+  id: {id}
+  name: {id}
+  rules:
+{rules}  decision_logic:
+    - default: true
+      action: approve
+"#
+    )
+}
+
+fn pipeline_yaml(id: &str, ruleset: &str) -> String {
+    format!(
+        r#"version: "0.1"
+
+pipeline:
+  id: {id}
+  name: {id}
+  entry: check
+  steps:
+    - step:
+        id: check
+        name: Check
+        type: ruleset
+        ruleset: {ruleset}
+        next: end
+"#
+    )
+}
+
+/// Create a repository with two pipelines, each running its own ruleset
+async fn create_test_repo() -> (TempDir, FileSystemRepository) {
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path();
+
+    for dir in ["library/rules", "library/rulesets", "pipelines"] {
+        fs::create_dir_all(root.join(dir)).await.unwrap();
+    }
+
+    let files = [
+        ("library/rules/fraud_check.yaml", rule_yaml("fraud_check")),
+        ("library/rules/kyc_check.yaml", rule_yaml("kyc_check")),
+        ("library/rules/login_check.yaml", rule_yaml("login_check")),
+        (
+            "library/rulesets/payment_rules.yaml",
+            ruleset_yaml("payment_rules", &["fraud_check", "kyc_check"]),
+        ),
+        (
+            "library/rulesets/login_rules.yaml",
+            ruleset_yaml("login_rules", &["login_check"]),
+        ),
+        (
+            "pipelines/payment_pipeline.yaml",
+            pipeline_yaml("payment_pipeline", "payment_rules"),
+        ),
+        (
+            "pipelines/login_pipeline.yaml",
+            pipeline_yaml("login_pipeline", "login_rules"),
+        ),
+    ];
+    for (path, content) in files {
+        fs::write(root.join(path), content).await.unwrap();
+    }
+
+    let repo = FileSystemRepository::new(root).unwrap();
+    (temp_dir, repo)
+}
+
+fn sorted(mut ids: Vec<String>) -> Vec<String> {
+    ids.sort();
+    ids
+}
+
+#[tokio::test]
+async fn test_preload_all_loads_everything() {
+    let (_temp, repo) = create_test_repo().await;
+
+    let manifest = repo.preload_all().await.unwrap();
+
+    assert!(manifest.is_complete());
+    assert_eq!(
+        sorted(manifest.pipelines),
+        vec!["login_pipeline", "payment_pipeline"]
+    );
+    assert_eq!(
+        sorted(manifest.rulesets),
+        vec!["login_rules", "payment_rules"]
+    );
+    assert_eq!(
+        sorted(manifest.rules),
+        vec!["fraud_check", "kyc_check", "login_check"]
+    );
+
+    // Lookups by ID, as done when a pipeline runs, are served from the cache
+    let misses = repo.cache_stats().misses;
+    repo.load_pipeline("payment_pipeline").await.unwrap();
+    repo.load_ruleset("payment_rules").await.unwrap();
+    repo.load_rule("fraud_check").await.unwrap();
+    assert_eq!(repo.cache_stats().misses, misses);
+}
+
+#[tokio::test]
+async fn test_warm_cache_follows_references() {
+    let (_temp, repo) = create_test_repo().await;
+
+    let manifest = repo.warm_cache(&["payment_pipeline"]).await.unwrap();
+
+    assert_eq!(manifest.pipelines, vec!["payment_pipeline"]);
+    assert_eq!(manifest.rulesets, vec!["payment_rules"]);
+    assert_eq!(
+        sorted(manifest.rules.clone()),
+        vec!["fraud_check", "kyc_check"]
+    );
+    assert_eq!(manifest.loaded_count(), 4);
+}
+
+#[tokio::test]
+async fn test_broken_artifacts_are_reported() {
+    let (temp, repo) = create_test_repo().await;
+    fs::write(
+        temp.path().join("library/rules/broken.yaml"),
+        "rule: [not a rule",
+    )
+    .await
+    .unwrap();
+    fs::write(
+        temp.path().join("pipelines/orphan_pipeline.yaml"),
+        pipeline_yaml("orphan_pipeline", "missing_rules"),
+    )
+    .await
+    .unwrap();
+
+    let manifest = repo.preload_all().await.unwrap();
+
+    assert!(!manifest.is_complete());
+    assert_eq!(manifest.failed.len(), 2);
+    assert!(manifest
+        .failed
+        .iter()
+        .any(|f| f.kind == BundleArtifactKind::Rule && f.identifier.contains("broken")));
+    assert!(manifest
+        .failed
+        .iter()
+        .any(|f| f.kind == BundleArtifactKind::Ruleset && f.identifier == "missing_rules"));
+
+    // The rest of the repository is still warmed
+    assert_eq!(manifest.pipelines.len(), 3);
+    assert_eq!(manifest.rules.len(), 3);
+}
@@ -39,6 +39,10 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         pool_size: 10,
         timeout_ms: 5000,
         pooling_enabled: true,
+        concurrency: None,
+        circuit_breaker: None,
+        retry: None,
+        cache: None,
     };
 
     println!("Connecting to Redis at {}", "redis://127.0.0.1:6379");
@@ -0,0 +1,486 @@
+//! Circuit breakers for downstream dependencies
+//!
+//! A vendor API or data source that starts failing or timing out slows down
+//! every decision that calls it. A [`CircuitBreaker`] tracks the outcome of
+//! the most recent calls to one dependency and, once the failure rate
+//! crosses a threshold, opens: calls are rejected immediately with
+//! [`RuntimeError::CircuitOpen`], and fall back like any other failed call,
+//! without waiting on the dependency. After a cool-down the breaker lets a
+//! few probe calls through (half-open) and closes again once they succeed,
+//! or reopens on the first failure.
+//!
+//! Breakers count the times they open and the calls they reject in the
+//! `circuit_breaker_opened_total` and `circuit_breaker_rejected_total`
+//! metrics, labeled by dependency, once attached to a metrics collector.
+//!
+//! Breakers are configured per data source or API; an API keeps a separate
+//! breaker for each endpoint:
+//!
+//! ```yaml
+//! circuit_breaker:
+//!   failure_rate_threshold: 0.5
+//!   minimum_calls: 20
+//!   window_size: 50
+//!   open_duration_ms: 30000
+//!   half_open_probes: 3
+//! ```
+
+use crate::error::{Result, RuntimeError};
+use crate::observability::MetricsCollector;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+/// Circuit breaker configuration
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct CircuitBreakerConfig {
+    /// Failure rate (0.0 - 1.0) over the window at which the circuit opens (default: 0.5)
+    #[serde(default = "default_failure_rate_threshold")]
+    pub failure_rate_threshold: f64,
+
+    /// Calls the window must hold before the failure rate is acted on (default: 20)
+    #[serde(default = "default_minimum_calls")]
+    pub minimum_calls: usize,
+
+    /// Number of most recent calls the failure rate is computed over (default: 50)
+    #[serde(default = "default_window_size")]
+    pub window_size: usize,
+
+    /// How long the circuit stays open before probing, in milliseconds (default: 30000)
+    #[serde(default = "default_open_duration_ms")]
+    pub open_duration_ms: u64,
+
+    /// Probe calls let through while half-open, all of which must succeed to close (default: 3)
+    #[serde(default = "default_half_open_probes")]
+    pub half_open_probes: usize,
+}
+
+fn default_failure_rate_threshold() -> f64 {
+    0.5
+}
+
+fn default_minimum_calls() -> usize {
+    20
+}
+
+fn default_window_size() -> usize {
+    50
+}
+
+fn default_open_duration_ms() -> u64 {
+    30_000
+}
+
+fn default_half_open_probes() -> usize {
+    3
+}
+
+impl Default for CircuitBreakerConfig {
+    fn default() -> Self {
+        Self {
+            failure_rate_threshold: default_failure_rate_threshold(),
+            minimum_calls: default_minimum_calls(),
+            window_size: default_window_size(),
+            open_duration_ms: default_open_duration_ms(),
+            half_open_probes: default_half_open_probes(),
+        }
+    }
+}
+
+impl CircuitBreakerConfig {
+    /// Set the failure rate at which the circuit opens
+    pub fn with_failure_rate_threshold(mut self, failure_rate_threshold: f64) -> Self {
+        self.failure_rate_threshold = failure_rate_threshold;
+        self
+    }
+
+    /// Set the number of calls needed before the failure rate is acted on
+    pub fn with_minimum_calls(mut self, minimum_calls: usize) -> Self {
+        self.minimum_calls = minimum_calls;
+        self
+    }
+
+    /// Set the number of calls the failure rate is computed over
+    pub fn with_window_size(mut self, window_size: usize) -> Self {
+        self.window_size = window_size;
+        self
+    }
+
+    /// Set how long the circuit stays open before probing
+    pub fn with_open_duration_ms(mut self, open_duration_ms: u64) -> Self {
+        self.open_duration_ms = open_duration_ms;
+        self
+    }
+
+    /// Set the number of probe calls let through while half-open
+    pub fn with_half_open_probes(mut self, half_open_probes: usize) -> Self {
+        self.half_open_probes = half_open_probes;
+        self
+    }
+}
+
+/// State of a circuit
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CircuitState {
+    /// Calls go through
+    Closed,
+
+    /// Calls are rejected
+    Open,
+
+    /// A few probe calls go through to test the dependency
+    HalfOpen,
+}
+
+/// Point-in-time statistics of a circuit breaker
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CircuitBreakerStats {
+    /// Name of the protected data source or API endpoint
+    pub name: String,
+
+    /// Current state
+    pub state: CircuitState,
+
+    /// Failure rate over the calls in the window (0.0 - 1.0)
+    pub failure_rate: f64,
+
+    /// Calls in the window
+    pub window_calls: usize,
+
+    /// Times the circuit opened
+    pub opened: u64,
+
+    /// Calls rejected while the circuit was open
+    pub rejected: u64,
+}
+
+#[derive(Debug)]
+struct BreakerState {
+    state: CircuitState,
+
+    /// Outcomes of the most recent calls while closed, `true` for failures
+    outcomes: VecDeque<bool>,
+
+    opened_at: Option<Instant>,
+    probes_in_flight: usize,
+    probe_successes: usize,
+}
+
+impl BreakerState {
+    fn failure_rate(&self) -> f64 {
+        if self.outcomes.is_empty() {
+            0.0
+        } else {
+            self.outcomes.iter().filter(|failed| **failed).count() as f64
+                / self.outcomes.len() as f64
+        }
+    }
+}
+
+/// Circuit breaker for calls to one dependency
+pub struct CircuitBreaker {
+    name: String,
+    config: CircuitBreakerConfig,
+    state: Mutex<BreakerState>,
+    opened: AtomicU64,
+    rejected: AtomicU64,
+    metrics: OnceLock<Arc<MetricsCollector>>,
+}
+
+impl std::fmt::Debug for CircuitBreaker {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CircuitBreaker")
+            .field("name", &self.name)
+            .field("config", &self.config)
+            .field("state", &self.state)
+            .finish_non_exhaustive()
+    }
+}
+
+impl CircuitBreaker {
+    /// Create a closed breaker for the named dependency
+    pub fn new(name: impl Into<String>, config: CircuitBreakerConfig) -> Self {
+        Self {
+            name: name.into(),
+            config,
+            state: Mutex::new(BreakerState {
+                state: CircuitState::Closed,
+                outcomes: VecDeque::new(),
+                opened_at: None,
+                probes_in_flight: 0,
+                probe_successes: 0,
+            }),
+            opened: AtomicU64::new(0),
+            rejected: AtomicU64::new(0),
+            metrics: OnceLock::new(),
+        }
+    }
+
+    /// Count openings and rejections in `metrics`
+    ///
+    /// Only the first collector attached is used.
+    pub fn attach_metrics(&self, metrics: Arc<MetricsCollector>) {
+        let _ = self.metrics.set(metrics);
+    }
+
+    /// Ask to make a call
+    ///
+    /// Fails with [`RuntimeError::CircuitOpen`] while the circuit is open, or
+    /// half-open with all probes in flight. The outcome of the call is
+    /// reported through the returned permit; a permit dropped without an
+    /// outcome, e.g. for an abandoned call, does not count.
+    pub fn try_acquire(&self) -> Result<CircuitPermit<'_>> {
+        let mut state = self.state.lock().unwrap();
+        if state.state == CircuitState::Open {
+            let cooled_down = state.opened_at.is_none_or(|opened_at| {
+                opened_at.elapsed() >= Duration::from_millis(self.config.open_duration_ms)
+            });
+            if cooled_down {
+                tracing::info!("Circuit of '{}' is half-open, probing", self.name);
+                state.state = CircuitState::HalfOpen;
+                state.probes_in_flight = 0;
+                state.probe_successes = 0;
+            }
+        }
+
+        let probe = match state.state {
+            CircuitState::Closed => false,
+            CircuitState::HalfOpen if state.probes_in_flight < self.config.half_open_probes => {
+                state.probes_in_flight += 1;
+                true
+            }
+            _ => {
+                self.rejected.fetch_add(1, Ordering::Relaxed);
+                self.count("circuit_breaker_rejected_total");
+                return Err(RuntimeError::CircuitOpen(format!(
+                    "'{}' is failing, calls are suspended",
+                    self.name
+                )));
+            }
+        };
+        Ok(CircuitPermit {
+            breaker: self,
+            probe,
+            done: false,
+        })
+    }
+
+    /// Run `call` through the breaker, counting any error as a failure
+    pub async fn call<T, F>(&self, call: F) -> Result<T>
+    where
+        F: Future<Output = Result<T>>,
+    {
+        let permit = self.try_acquire()?;
+        let result = call.await;
+        permit.record(result.is_ok());
+        result
+    }
+
+    /// Name of the protected dependency
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Current state
+    pub fn state(&self) -> CircuitState {
+        self.state.lock().unwrap().state
+    }
+
+    /// Current statistics
+    pub fn stats(&self) -> CircuitBreakerStats {
+        let state = self.state.lock().unwrap();
+        CircuitBreakerStats {
+            name: self.name.clone(),
+            state: state.state,
+            failure_rate: state.failure_rate(),
+            window_calls: state.outcomes.len(),
+            opened: self.opened.load(Ordering::Relaxed),
+            rejected: self.rejected.load(Ordering::Relaxed),
+        }
+    }
+
+    fn open(&self, state: &mut BreakerState) {
+        state.state = CircuitState::Open;
+        state.opened_at = Some(Instant::now());
+        state.outcomes.clear();
+        self.opened.fetch_add(1, Ordering::Relaxed);
+        self.count("circuit_breaker_opened_total");
+    }
+
+    fn count(&self, metric: &str) {
+        if let Some(metrics) = self.metrics.get() {
+            metrics
+                .labeled_counter(metric, &[("dependency", self.name.as_str())])
+                .inc();
+        }
+    }
+
+    fn complete(&self, probe: bool, success: Option<bool>) {
+        let mut state = self.state.lock().unwrap();
+        if probe {
+            state.probes_in_flight = state.probes_in_flight.saturating_sub(1);
+        }
+
+        match (state.state, success) {
+            (_, None) => {}
+            (CircuitState::Closed, Some(success)) => {
+                state.outcomes.push_back(!success);
+                while state.outcomes.len() > self.config.window_size.max(1) {
+                    state.outcomes.pop_front();
+                }
+                let failure_rate = state.failure_rate();
+                if !success
+                    && state.outcomes.len() >= self.config.minimum_calls
+                    && failure_rate >= self.config.failure_rate_threshold
+                {
+                    tracing::warn!(
+                        "Opening circuit of '{}': {:.0}% of the last {} calls failed",
+                        self.name,
+                        failure_rate * 100.0,
+                        state.outcomes.len()
+                    );
+                    self.open(&mut state);
+                }
+            }
+            // Only probes decide how a half-open circuit goes
+            (CircuitState::HalfOpen, Some(true)) if probe => {
+                state.probe_successes += 1;
+                if state.probe_successes >= self.config.half_open_probes {
+                    tracing::info!("Closing circuit of '{}', probes succeeded", self.name);
+                    state.state = CircuitState::Closed;
+                    state.outcomes.clear();
+                }
+            }
+            (CircuitState::HalfOpen, Some(false)) if probe => {
+                tracing::warn!("Reopening circuit of '{}', probe failed", self.name);
+                self.open(&mut state);
+            }
+            // Calls started before the circuit opened
+            _ => {}
+        }
+    }
+}
+
+/// Permission to make one call through a [`CircuitBreaker`]
+#[derive(Debug)]
+pub struct CircuitPermit<'a> {
+    breaker: &'a CircuitBreaker,
+    probe: bool,
+    done: bool,
+}
+
+impl CircuitPermit<'_> {
+    /// Report whether the call succeeded
+    pub fn record(mut self, success: bool) {
+        self.done = true;
+        self.breaker.complete(self.probe, Some(success));
+    }
+}
+
+impl Drop for CircuitPermit<'_> {
+    fn drop(&mut self) {
+        if !self.done {
+            self.breaker.complete(self.probe, None);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fail() -> Result<()> {
+        Err(RuntimeError::ExternalCallFailed("boom".to_string()))
+    }
+
+    #[tokio::test]
+    async fn test_breaker_opens_probes_and_closes() {
+        let breaker = CircuitBreaker::new(
+            "ipinfo::lookup",
+            CircuitBreakerConfig::default()
+                .with_minimum_calls(4)
+                .with_window_size(4)
+                .with_open_duration_ms(20)
+                .with_half_open_probes(2),
+        );
+
+        // Failures below the minimum number of calls keep the circuit closed
+        for _ in 0..3 {
+            assert!(breaker.call(async { fail() }).await.is_err());
+        }
+        assert_eq!(breaker.state(), CircuitState::Closed);
+        breaker.call(async { fail() }).await.unwrap_err();
+        assert_eq!(breaker.state(), CircuitState::Open);
+
+        // Open: rejected without calling
+        let err = breaker.call(async { Ok(()) }).await.unwrap_err();
+        assert!(matches!(err, RuntimeError::CircuitOpen(_)));
+
+        // Half-open after the cool-down: a failed probe reopens the circuit
+        tokio::time::sleep(Duration::from_millis(30)).await;
+        breaker.call(async { fail() }).await.unwrap_err();
+        assert_eq!(breaker.state(), CircuitState::Open);
+
+        // Only two probes at a time, and both must succeed to close
+        tokio::time::sleep(Duration::from_millis(30)).await;
+        let first = breaker.try_acquire().unwrap();
+        let second = breaker.try_acquire().unwrap();
+        assert!(breaker.try_acquire().is_err());
+        first.record(true);
+        assert_eq!(breaker.state(), CircuitState::HalfOpen);
+        second.record(true);
+        assert_eq!(breaker.state(), CircuitState::Closed);
+
+        let stats = breaker.stats();
+        assert_eq!(stats.opened, 2);
+        assert_eq!(stats.rejected, 2);
+        assert_eq!(stats.window_calls, 0);
+    }
+
+    #[test]
+    fn test_breaker_metrics() {
+        let metrics = Arc::new(MetricsCollector::new());
+        let breaker = CircuitBreaker::new(
+            "risk_db",
+            CircuitBreakerConfig::default().with_minimum_calls(1),
+        );
+        breaker.attach_metrics(Arc::clone(&metrics));
+        breaker.try_acquire().unwrap().record(false);
+        assert!(breaker.try_acquire().is_err());
+
+        let counter = |name: &str| metrics.labeled_counter(name, &[("dependency", "risk_db")]).get();
+        assert_eq!(counter("circuit_breaker_opened_total"), 1);
+        assert_eq!(counter("circuit_breaker_rejected_total"), 1);
+    }
+
+    #[test]
+    fn test_breaker_failure_rate() {
+        let breaker = CircuitBreaker::new(
+            "risk_db",
+            CircuitBreakerConfig::default()
+                .with_minimum_calls(4)
+                .with_window_size(4),
+        );
+        for success in [true, true, false, true, false] {
+            breaker.try_acquire().unwrap().record(success);
+        }
+        // The window holds the last four calls, half of them failed
+        assert_eq!(breaker.state(), CircuitState::Open);
+
+        // An abandoned call does not count
+        let breaker = CircuitBreaker::new("risk_db", CircuitBreakerConfig::default());
+        drop(breaker.try_acquire().unwrap());
+        assert_eq!(breaker.stats().window_calls, 0);
+    }
+
+    #[test]
+    fn test_breaker_config_defaults() {
+        let config: CircuitBreakerConfig = serde_yaml::from_str("minimum_calls: 5").unwrap();
+        assert_eq!(config, CircuitBreakerConfig::default().with_minimum_calls(5));
+        assert_eq!(config.half_open_probes, 3);
+    }
+}
@@ -0,0 +1,131 @@
+//! Injectable clock and effective-window evaluation
+//!
+//! Rules and rulesets may declare an `effective_from` / `effective_until`
+//! window. The engine evaluates those windows against a [`Clock`] so that
+//! tests and replays can pin the current time.
+
+use chrono::{DateTime, Utc};
+use corint_core::ir::ProgramMetadata;
+use std::fmt::Debug;
+
+/// Source of the current time
+pub trait Clock: Debug + Send + Sync {
+    /// Current UTC time
+    fn now(&self) -> DateTime<Utc>;
+}
+
+/// Clock backed by the system time
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+/// Clock that always returns the same instant
+#[derive(Debug, Clone, Copy)]
+pub struct FixedClock {
+    now: DateTime<Utc>,
+}
+
+impl FixedClock {
+    /// Create a clock pinned to `now`
+    pub fn new(now: DateTime<Utc>) -> Self {
+        Self { now }
+    }
+}
+
+impl Clock for FixedClock {
+    fn now(&self) -> DateTime<Utc> {
+        self.now
+    }
+}
+
+/// Whether a program is inside its effective window
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EffectiveStatus {
+    /// No window, or the window contains the current time
+    Active,
+    /// `effective_from` is still in the future
+    NotYetEffective,
+    /// `effective_until` has passed
+    Expired,
+}
+
+impl EffectiveStatus {
+    /// Evaluate the `effective_from` / `effective_until` metadata of a program
+    ///
+    /// `effective_from` is inclusive and `effective_until` exclusive. Bounds
+    /// that are not valid RFC 3339 timestamps are ignored; the parser rejects
+    /// them before they reach a compiled program.
+    pub fn of(metadata: &ProgramMetadata, now: DateTime<Utc>) -> Self {
+        let bound = |key: &str| {
+            metadata
+                .custom
+                .get(key)
+                .and_then(|value| DateTime::parse_from_rfc3339(value).ok())
+                .map(|at| at.with_timezone(&Utc))
+        };
+
+        if bound("effective_from").is_some_and(|from| now < from) {
+            EffectiveStatus::NotYetEffective
+        } else if bound("effective_until").is_some_and(|until| now >= until) {
+            EffectiveStatus::Expired
+        } else {
+            EffectiveStatus::Active
+        }
+    }
+
+    /// Whether the program should run
+    pub fn is_active(&self) -> bool {
+        matches!(self, EffectiveStatus::Active)
+    }
+
+    /// Reason reported in traces when the program is skipped
+    pub fn skip_reason(&self) -> Option<&'static str> {
+        match self {
+            EffectiveStatus::Active => None,
+            EffectiveStatus::NotYetEffective => Some("not_yet_effective"),
+            EffectiveStatus::Expired => Some("expired"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(value: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(value)
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    #[test]
+    fn test_effective_status() {
+        let metadata = ProgramMetadata::for_rule("promo".to_string())
+            .with_custom(
+                "effective_from".to_string(),
+                "2026-12-01T00:00:00Z".to_string(),
+            )
+            .with_custom(
+                "effective_until".to_string(),
+                "2027-01-01T00:00:00+00:00".to_string(),
+            );
+
+        let status = |now: &str| EffectiveStatus::of(&metadata, FixedClock::new(at(now)).now());
+        assert_eq!(
+            status("2026-11-30T23:59:59Z"),
+            EffectiveStatus::NotYetEffective
+        );
+        assert_eq!(status("2026-12-01T00:00:00Z"), EffectiveStatus::Active);
+        assert_eq!(status("2026-12-31T23:59:59Z"), EffectiveStatus::Active);
+        assert_eq!(status("2027-01-01T00:00:00Z"), EffectiveStatus::Expired);
+        assert_eq!(EffectiveStatus::Expired.skip_reason(), Some("expired"));
+
+        let unbounded = ProgramMetadata::for_rule("always".to_string());
+        assert!(EffectiveStatus::of(&unbounded, SystemClock.now()).is_active());
+    }
+}
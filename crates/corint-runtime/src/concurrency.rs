@@ -0,0 +1,230 @@
+//! Concurrency limits for downstream dependencies
+//!
+//! A burst of decisions fans out into data source queries and external API
+//! calls. A [`ConcurrencyLimiter`] caps how many of those run at once against
+//! one dependency; further calls queue for a free slot until the queue
+//! deadline passes and then fail fast with
+//! [`RuntimeError::ConcurrencyLimitReached`], instead of piling up on a
+//! struggling database.
+//!
+//! Limits are configured per data source or API:
+//!
+//! ```yaml
+//! concurrency:
+//!   max_concurrent: 20
+//!   queue_timeout_ms: 50
+//! ```
+
+use crate::error::{Result, RuntimeError};
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+/// Concurrency limit configuration
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConcurrencyLimit {
+    /// Maximum number of calls in flight at once
+    pub max_concurrent: usize,
+
+    /// How long a call may wait for a free slot, in milliseconds (default: 100)
+    #[serde(default = "default_queue_timeout_ms")]
+    pub queue_timeout_ms: u64,
+}
+
+fn default_queue_timeout_ms() -> u64 {
+    100
+}
+
+impl ConcurrencyLimit {
+    /// Allow `max_concurrent` calls in flight with the default queue deadline
+    pub fn new(max_concurrent: usize) -> Self {
+        Self {
+            max_concurrent,
+            queue_timeout_ms: default_queue_timeout_ms(),
+        }
+    }
+
+    /// Set the queue deadline
+    pub fn with_queue_timeout_ms(mut self, queue_timeout_ms: u64) -> Self {
+        self.queue_timeout_ms = queue_timeout_ms;
+        self
+    }
+}
+
+/// Point-in-time saturation statistics of a limiter
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ConcurrencyStats {
+    /// Name of the limited data source or API
+    pub name: String,
+
+    /// Configured maximum number of calls in flight
+    pub max_concurrent: usize,
+
+    /// Calls currently in flight
+    pub in_flight: usize,
+
+    /// Calls currently waiting for a slot
+    pub waiting: usize,
+
+    /// Calls that obtained a slot
+    pub acquired: u64,
+
+    /// Calls rejected after waiting past the queue deadline
+    pub rejected: u64,
+
+    /// Total time calls spent waiting for a slot, in milliseconds
+    pub total_wait_ms: u64,
+}
+
+impl ConcurrencyStats {
+    /// Fraction of slots in use (0.0 - 1.0)
+    pub fn saturation(&self) -> f64 {
+        if self.max_concurrent == 0 {
+            0.0
+        } else {
+            self.in_flight as f64 / self.max_concurrent as f64
+        }
+    }
+
+    /// Average wait for a slot, in milliseconds
+    pub fn avg_wait_ms(&self) -> f64 {
+        if self.acquired == 0 {
+            0.0
+        } else {
+            self.total_wait_ms as f64 / self.acquired as f64
+        }
+    }
+}
+
+/// Semaphore-based limiter for calls to one dependency
+#[derive(Debug)]
+pub struct ConcurrencyLimiter {
+    name: String,
+    limit: ConcurrencyLimit,
+    semaphore: Arc<Semaphore>,
+    waiting: AtomicUsize,
+    acquired: AtomicU64,
+    rejected: AtomicU64,
+    total_wait_ms: AtomicU64,
+}
+
+impl ConcurrencyLimiter {
+    /// Create a limiter for the named dependency
+    pub fn new(name: impl Into<String>, limit: ConcurrencyLimit) -> Self {
+        Self {
+            name: name.into(),
+            limit,
+            semaphore: Arc::new(Semaphore::new(limit.max_concurrent)),
+            waiting: AtomicUsize::new(0),
+            acquired: AtomicU64::new(0),
+            rejected: AtomicU64::new(0),
+            total_wait_ms: AtomicU64::new(0),
+        }
+    }
+
+    /// Wait for a free slot
+    ///
+    /// The slot is released when the returned permit is dropped. Fails with
+    /// [`RuntimeError::ConcurrencyLimitReached`] when no slot frees up before
+    /// the queue deadline.
+    pub async fn acquire(&self) -> Result<OwnedSemaphorePermit> {
+        let start = Instant::now();
+        self.waiting.fetch_add(1, Ordering::Relaxed);
+        let permit = tokio::time::timeout(
+            Duration::from_millis(self.limit.queue_timeout_ms),
+            Arc::clone(&self.semaphore).acquire_owned(),
+        )
+        .await;
+        self.waiting.fetch_sub(1, Ordering::Relaxed);
+
+        match permit {
+            Ok(Ok(permit)) => {
+                self.acquired.fetch_add(1, Ordering::Relaxed);
+                self.total_wait_ms
+                    .fetch_add(start.elapsed().as_millis() as u64, Ordering::Relaxed);
+                Ok(permit)
+            }
+            _ => {
+                self.rejected.fetch_add(1, Ordering::Relaxed);
+                tracing::warn!(
+                    "Concurrency limit of {} reached for '{}' after waiting {}ms",
+                    self.limit.max_concurrent,
+                    self.name,
+                    self.limit.queue_timeout_ms
+                );
+                Err(RuntimeError::ConcurrencyLimitReached(format!(
+                    "'{}' has {} calls in flight (queue timeout {}ms)",
+                    self.name, self.limit.max_concurrent, self.limit.queue_timeout_ms
+                )))
+            }
+        }
+    }
+
+    /// Name of the limited dependency
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Configured limit
+    pub fn limit(&self) -> ConcurrencyLimit {
+        self.limit
+    }
+
+    /// Current saturation statistics
+    pub fn stats(&self) -> ConcurrencyStats {
+        ConcurrencyStats {
+            name: self.name.clone(),
+            max_concurrent: self.limit.max_concurrent,
+            in_flight: self.limit.max_concurrent - self.semaphore.available_permits(),
+            waiting: self.waiting.load(Ordering::Relaxed),
+            acquired: self.acquired.load(Ordering::Relaxed),
+            rejected: self.rejected.load(Ordering::Relaxed),
+            total_wait_ms: self.total_wait_ms.load(Ordering::Relaxed),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_limiter_queues_and_rejects() {
+        let limiter = ConcurrencyLimiter::new(
+            "risk_db",
+            ConcurrencyLimit::new(2).with_queue_timeout_ms(20),
+        );
+
+        let first = limiter.acquire().await.unwrap();
+        let _second = limiter.acquire().await.unwrap();
+        assert_eq!(limiter.stats().in_flight, 2);
+        assert_eq!(limiter.stats().saturation(), 1.0);
+
+        // No slot frees up before the deadline
+        let err = limiter.acquire().await.unwrap_err();
+        assert!(matches!(err, RuntimeError::ConcurrencyLimitReached(_)));
+
+        // A slot freed while waiting is handed to the queued call
+        let release = async {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+            drop(first);
+        };
+        let (third, _) = tokio::join!(limiter.acquire(), release);
+        assert!(third.is_ok());
+
+        let stats = limiter.stats();
+        assert_eq!(stats.acquired, 3);
+        assert_eq!(stats.rejected, 1);
+        assert_eq!(stats.waiting, 0);
+        assert_eq!(stats.in_flight, 2);
+    }
+
+    #[test]
+    fn test_limit_config_defaults() {
+        let limit: ConcurrencyLimit = serde_yaml::from_str("max_concurrent: 8").unwrap();
+        assert_eq!(limit, ConcurrencyLimit::new(8));
+        assert_eq!(limit.queue_timeout_ms, 100);
+    }
+}
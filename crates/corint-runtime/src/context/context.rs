@@ -7,7 +7,7 @@ use crate::error::{Result, RuntimeError};
 use crate::result::{DecisionResult, ExecutionResult};
 use corint_core::ast::Signal;
 use corint_core::Value;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 /// Input structure for creating ExecutionContext with multi-namespace support
 #[derive(Debug, Clone, Default)]
@@ -24,6 +24,8 @@ pub struct ContextInput {
     pub llm: Option<HashMap<String, Value>>,
     /// Simple variables and intermediate calculations (optional)
     pub vars: Option<HashMap<String, Value>>,
+    /// Tenant the request is made for, whose quota it counts against (optional)
+    pub tenant: Option<String>,
 }
 
 impl ContextInput {
@@ -36,6 +38,7 @@ impl ContextInput {
             service: None,
             llm: None,
             vars: None,
+            tenant: None,
         }
     }
 
@@ -68,6 +71,12 @@ impl ContextInput {
         self.vars = Some(vars);
         self
     }
+
+    /// Builder method to set the tenant
+    pub fn with_tenant(mut self, tenant: impl Into<String>) -> Self {
+        self.tenant = Some(tenant.into());
+        self
+    }
 }
 
 /// Execution context for running IR programs with flattened namespace architecture
@@ -104,6 +113,12 @@ pub struct ExecutionContext {
 
     /// Execution result (accumulated state)
     pub result: ExecutionResult,
+
+    /// Element bindings introduced by array quantifiers (innermost last)
+    pub bindings: Vec<(String, Value)>,
+
+    /// Variables declared with `let` in this execution, the only ones `assign` may update
+    pub declared: HashSet<String>,
 }
 
 impl ExecutionContext {
@@ -123,6 +138,8 @@ impl ExecutionContext {
             sys: super::system_vars::build_system_vars(),
             env: super::env_vars::load_environment_vars(),
             result: ExecutionResult::new(),
+            bindings: Vec::new(),
+            declared: HashSet::new(),
         })
     }
 
@@ -178,6 +195,8 @@ impl ExecutionContext {
             sys: super::system_vars::build_system_vars(),
             env: super::env_vars::load_environment_vars(),
             result,
+            bindings: Vec::new(),
+            declared: HashSet::new(),
         })
     }
 
@@ -208,6 +227,27 @@ impl ExecutionContext {
         self.vars.insert(name.to_string(), value);
     }
 
+    /// Declare a variable with its initial value
+    ///
+    /// A declaration shadows a variable of the same name carried in from an
+    /// earlier program, but not one declared in this execution.
+    pub fn declare_var(&mut self, name: &str, value: Value) -> Result<()> {
+        if !self.declared.insert(name.to_string()) {
+            return Err(RuntimeError::VariableAlreadyDeclared(name.to_string()));
+        }
+        self.store_var(name, value);
+        Ok(())
+    }
+
+    /// Update a variable declared in this execution
+    pub fn assign_var(&mut self, name: &str, value: Value) -> Result<()> {
+        if !self.declared.contains(name) {
+            return Err(RuntimeError::UndeclaredVariable(name.to_string()));
+        }
+        self.store_var(name, value);
+        Ok(())
+    }
+
     // ========== Field Lookup (supports all 8 namespaces) ==========
 
     /// Load a field value from any namespace
@@ -225,12 +265,25 @@ impl ExecutionContext {
     /// Returns Value::Null if field is not found (graceful handling)
     pub fn load_field(&self, path: &[String]) -> Result<Value> {
         if path.is_empty() {
-            return Err(RuntimeError::FieldNotFound("empty path".to_string()));
+            return Err(RuntimeError::MissingField {
+                path: String::new(),
+            });
         }
 
         let namespace = &path[0];
         let remaining_path = &path[1..];
 
+        // Element bindings (e.g., `item` inside any_of) shadow everything else
+        if let Some((_, bound)) = self.bindings.iter().rev().find(|(name, _)| name == namespace) {
+            if remaining_path.is_empty() {
+                return Ok(bound.clone());
+            }
+            return match bound {
+                Value::Object(map) => super::field_lookup::get_nested_value(map, remaining_path),
+                _ => Ok(Value::Null),
+            };
+        }
+
         // Route to appropriate namespace
         let namespace_data = match namespace.as_str() {
             "event" => Some(&self.event),
@@ -316,6 +369,18 @@ impl ExecutionContext {
         Ok(current.clone())
     }
 
+    // ========== Element Bindings ==========
+
+    /// Bind a name to a value for the duration of a predicate evaluation
+    pub fn push_binding(&mut self, name: String, value: Value) {
+        self.bindings.push((name, value));
+    }
+
+    /// Remove the innermost binding
+    pub fn pop_binding(&mut self) {
+        self.bindings.pop();
+    }
+
     // ========== Stack Operations ==========
 
     /// Push a value onto the stack
@@ -362,7 +427,9 @@ impl ExecutionContext {
         self.result
             .load_variable(name)
             .cloned()
-            .ok_or_else(|| RuntimeError::FieldNotFound(name.to_string()))
+            .ok_or_else(|| RuntimeError::MissingField {
+                path: name.to_string(),
+            })
     }
 
     /// Store a value in a namespace at a nested path
@@ -446,6 +513,11 @@ impl ExecutionContext {
         self.result.explicit_explanation = Some(reason);
     }
 
+    /// Set the reason code
+    pub fn set_reason_code(&mut self, code: String) {
+        self.result.reason_code = Some(code);
+    }
+
     /// Set user-defined actions
     pub fn set_actions(&mut self, actions: Vec<String>) {
         self.result.actions = actions;
@@ -505,6 +577,7 @@ impl ExecutionContext {
             score: self.result.score,
             triggered_rules: self.result.triggered_rules,
             explanation,
+            reason_code: self.result.reason_code,
             context,
         }
     }
@@ -599,6 +672,27 @@ mod tests {
         assert_eq!(ctx.stack_depth(), 0);
     }
 
+    #[test]
+    fn test_element_bindings_shadow_fields() {
+        let mut ctx = ExecutionContext::from_event(create_test_event()).unwrap();
+
+        let mut item = HashMap::new();
+        item.insert("price".to_string(), Value::Number(1200.0));
+        ctx.push_binding("user".to_string(), Value::Object(item));
+
+        // Binding takes precedence over the event field of the same name
+        let price = ctx
+            .load_field(&["user".to_string(), "price".to_string()])
+            .unwrap();
+        assert_eq!(price, Value::Number(1200.0));
+
+        ctx.pop_binding();
+        let age = ctx
+            .load_field(&["user".to_string(), "age".to_string()])
+            .unwrap();
+        assert_eq!(age, Value::Number(25.0));
+    }
+
     #[test]
     fn test_namespace_storage() {
         let mut ctx = ExecutionContext::from_event(HashMap::new()).unwrap();
@@ -618,6 +712,33 @@ mod tests {
         assert_eq!(ctx.vars.len(), 1);
     }
 
+    #[test]
+    fn test_declared_variables() {
+        let mut ctx = ExecutionContext::from_event(HashMap::new()).unwrap();
+
+        // A variable carried in from an earlier program can be redeclared once
+        ctx.store_var("normalized_amount", Value::Number(1.0));
+        assert!(matches!(
+            ctx.assign_var("normalized_amount", Value::Number(2.0)),
+            Err(RuntimeError::UndeclaredVariable(_))
+        ));
+
+        ctx.declare_var("normalized_amount", Value::Number(10.0))
+            .unwrap();
+        ctx.assign_var("normalized_amount", Value::Number(20.0))
+            .unwrap();
+        assert_eq!(
+            ctx.load_field(&["vars".to_string(), "normalized_amount".to_string()])
+                .unwrap(),
+            Value::Number(20.0)
+        );
+
+        assert!(matches!(
+            ctx.declare_var("normalized_amount", Value::Number(30.0)),
+            Err(RuntimeError::VariableAlreadyDeclared(_))
+        ));
+    }
+
     #[test]
     fn test_namespace_field_lookup() {
         let mut ctx = ExecutionContext::from_event(HashMap::new()).unwrap();
@@ -0,0 +1,36 @@
+//! Handlers of custom step types
+//!
+//! Step types specific to an organization are compiled by a `StepPlugin`
+//! registered on the compiler, into `CallCustom` instructions that call a
+//! handler registered by name with
+//! [`PipelineExecutor::with_custom_handler`](crate::engine::PipelineExecutor::with_custom_handler).
+
+use crate::error::Result;
+use async_trait::async_trait;
+use corint_core::Value;
+use std::collections::HashMap;
+
+/// Call of a custom step handler
+#[derive(Debug, Clone, PartialEq)]
+pub struct CustomCall {
+    /// Custom step ID
+    pub step_id: String,
+
+    /// Arguments, evaluated when the step runs
+    pub args: HashMap<String, Value>,
+}
+
+impl CustomCall {
+    /// Argument `name`, or `Null` when the step did not pass it
+    pub fn arg(&self, name: &str) -> &Value {
+        self.args.get(name).unwrap_or(&Value::Null)
+    }
+}
+
+/// Handler of the `CallCustom` instructions of a custom step type
+#[async_trait]
+pub trait CustomStepHandler: Send + Sync {
+    /// Run the step; the value returned is pushed for the instructions that
+    /// follow the call (usually a `Store` into the step's output variable)
+    async fn call(&self, call: CustomCall) -> Result<Value>;
+}
@@ -1,10 +1,15 @@
 //! Feature Caching Layer
 //!
 //! Caching strategies for feature computation results.
+//!
+//! With the `stale_while_revalidate` strategy an entry stays usable for
+//! `stale_ttl` seconds after it expires: the stale value is returned at once
+//! and the query is re-run in the background, so a slow data source doesn't
+//! show up in decision latency as long as entries are read within the window.
 
 use corint_core::Value;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::time::{Duration, SystemTime};
 
 /// Cache strategy configuration
@@ -24,6 +29,33 @@ pub struct CacheStrategy {
     /// Strategy type
     #[serde(default)]
     pub strategy: CacheStrategyType,
+
+    /// Seconds an expired entry is still served while it is refreshed
+    /// (`stale_while_revalidate` only)
+    #[serde(default = "default_stale_ttl")]
+    pub stale_ttl: u64,
+}
+
+impl Default for CacheStrategy {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            ttl: default_ttl(),
+            key_template: None,
+            strategy: CacheStrategyType::default(),
+            stale_ttl: default_stale_ttl(),
+        }
+    }
+}
+
+impl CacheStrategy {
+    /// How long an expired entry may be served, given the strategy
+    pub fn stale_window(&self) -> Duration {
+        match self.strategy {
+            CacheStrategyType::StaleWhileRevalidate => Duration::from_secs(self.stale_ttl),
+            _ => Duration::ZERO,
+        }
+    }
 }
 
 /// Cache strategy types
@@ -39,6 +71,20 @@ pub enum CacheStrategyType {
 
     /// Refresh - update in background
     Refresh,
+
+    /// Serve expired entries while refreshing them in the background
+    #[serde(rename = "stale_while_revalidate")]
+    StaleWhileRevalidate,
+}
+
+/// Whether a cache hit is within its TTL
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Freshness {
+    /// Within the TTL
+    Fresh,
+
+    /// Expired, but within the stale window; should be refreshed
+    Stale,
 }
 
 /// Cached result wrapper
@@ -52,6 +98,9 @@ pub struct CachedResult {
 
     /// TTL duration
     pub ttl: Duration,
+
+    /// How long after the TTL the entry may still be served stale
+    pub stale_ttl: Duration,
 }
 
 impl CachedResult {
@@ -61,9 +110,16 @@ impl CachedResult {
             value,
             cached_at: SystemTime::now(),
             ttl,
+            stale_ttl: Duration::ZERO,
         }
     }
 
+    /// Allow serving the entry for `stale_ttl` after it expires
+    pub fn with_stale_ttl(mut self, stale_ttl: Duration) -> Self {
+        self.stale_ttl = stale_ttl;
+        self
+    }
+
     /// Check if the cache entry is still valid
     pub fn is_valid(&self) -> bool {
         match self.cached_at.elapsed() {
@@ -79,11 +135,26 @@ impl CachedResult {
             .ok()
             .and_then(|elapsed| self.ttl.checked_sub(elapsed))
     }
+
+    /// Freshness of the entry, or `None` once it can't be served at all
+    pub fn freshness(&self) -> Option<Freshness> {
+        let elapsed = self.cached_at.elapsed().ok()?;
+        if elapsed < self.ttl {
+            Some(Freshness::Fresh)
+        } else if elapsed < self.ttl + self.stale_ttl {
+            Some(Freshness::Stale)
+        } else {
+            None
+        }
+    }
 }
 
 /// In-memory cache for feature results
 pub struct FeatureCache {
     cache: HashMap<String, CachedResult>,
+
+    /// Keys with a background refresh in flight
+    refreshing: HashSet<String>,
 }
 
 impl FeatureCache {
@@ -91,6 +162,7 @@ impl FeatureCache {
     pub fn new() -> Self {
         Self {
             cache: HashMap::new(),
+            refreshing: HashSet::new(),
         }
     }
 
@@ -105,14 +177,48 @@ impl FeatureCache {
         })
     }
 
+    /// Get a cached value that is fresh or within its stale window
+    pub fn get_with_freshness(&self, key: &str) -> Option<(&HashMap<String, Value>, Freshness)> {
+        let entry = self.cache.get(key)?;
+        entry.freshness().map(|freshness| (&entry.value, freshness))
+    }
+
     /// Set a cached value
     pub fn set(&mut self, key: String, value: HashMap<String, Value>, ttl: Duration) {
         self.cache.insert(key, CachedResult::new(value, ttl));
     }
 
-    /// Remove expired entries
+    /// Set a cached value that may be served stale for `stale_ttl` after it
+    /// expires
+    pub fn set_with_stale_ttl(
+        &mut self,
+        key: String,
+        value: HashMap<String, Value>,
+        ttl: Duration,
+        stale_ttl: Duration,
+    ) {
+        self.cache
+            .insert(key, CachedResult::new(value, ttl).with_stale_ttl(stale_ttl));
+    }
+
+    /// Claim the background refresh of `key`; `false` if one is running
+    pub fn start_refresh(&mut self, key: &str) -> bool {
+        self.refreshing.insert(key.to_string())
+    }
+
+    /// Release the refresh claimed with [`start_refresh`](Self::start_refresh)
+    pub fn finish_refresh(&mut self, key: &str) {
+        self.refreshing.remove(key);
+    }
+
+    /// Remove a cached value
+    pub fn remove(&mut self, key: &str) {
+        self.cache.remove(key);
+    }
+
+    /// Remove entries that can no longer be served
     pub fn cleanup(&mut self) {
-        self.cache.retain(|_, entry| entry.is_valid());
+        self.cache.retain(|_, entry| entry.freshness().is_some());
     }
 
     /// Clear all entries
@@ -140,6 +246,10 @@ fn default_ttl() -> u64 {
     300 // 5 minutes
 }
 
+fn default_stale_ttl() -> u64 {
+    60
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -188,4 +298,44 @@ mod tests {
         assert_eq!(cache.size(), 1);
         assert!(cache.get("valid").is_some());
     }
+
+    #[test]
+    fn test_stale_entries() {
+        let mut cache = FeatureCache::new();
+        let value = HashMap::from([("count".to_string(), Value::Number(3.0))]);
+
+        cache.set_with_stale_ttl(
+            "swr".to_string(),
+            value.clone(),
+            Duration::ZERO,
+            Duration::from_secs(60),
+        );
+        cache.set("plain".to_string(), value, Duration::ZERO);
+
+        // Expired for get(), but still served stale
+        assert!(cache.get("swr").is_none());
+        let (cached, freshness) = cache.get_with_freshness("swr").unwrap();
+        assert_eq!(cached["count"], Value::Number(3.0));
+        assert_eq!(freshness, Freshness::Stale);
+        assert!(cache.get_with_freshness("plain").is_none());
+
+        cache.cleanup();
+        assert_eq!(cache.size(), 1);
+
+        assert!(cache.start_refresh("swr"));
+        assert!(!cache.start_refresh("swr"));
+        cache.finish_refresh("swr");
+        assert!(cache.start_refresh("swr"));
+    }
+
+    #[test]
+    fn test_parse_strategy() {
+        let strategy: CacheStrategy =
+            serde_yaml::from_str("strategy: stale_while_revalidate\nttl: 30\n").unwrap();
+        assert_eq!(strategy.ttl, 30);
+        assert_eq!(strategy.stale_window(), Duration::from_secs(60));
+
+        let lazy: CacheStrategy = serde_yaml::from_str("ttl: 30").unwrap();
+        assert_eq!(lazy.stale_window(), Duration::ZERO);
+    }
 }
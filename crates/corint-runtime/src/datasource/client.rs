@@ -2,14 +2,22 @@
 //!
 //! Provides a unified interface for accessing different data sources.
 
-use super::cache::FeatureCache;
+use super::cache::{FeatureCache, Freshness};
 use super::config::{DataSourceConfig, DataSourceType};
 use super::query::{Query, QueryResult};
+use crate::circuit_breaker::{CircuitBreaker, CircuitBreakerStats};
+use crate::concurrency::{ConcurrencyLimiter, ConcurrencyStats};
 use crate::error::{Result, RuntimeError};
+use crate::observability::MetricsCollector;
+use crate::replay;
 use corint_core::Value;
 use std::collections::HashMap;
+use std::future::Future;
 use std::sync::{Arc, Mutex};
-use std::time::Instant;
+use std::time::{Duration, Instant};
+
+/// Longest time a feature store value is cached
+const FEATURE_CACHE_TTL: Duration = Duration::from_secs(300);
 
 
 /// Unified data source client
@@ -21,39 +29,84 @@ pub struct DataSourceClient {
     cache: Arc<Mutex<FeatureCache>>,
 
     /// Underlying client implementation
-    client: Box<dyn DataSourceImpl>,
+    client: Arc<dyn DataSourceImpl>,
+
+    /// Cap on concurrent queries, if configured
+    limiter: Option<Arc<ConcurrencyLimiter>>,
+
+    /// Circuit breaker, if configured
+    breaker: Option<Arc<CircuitBreaker>>,
 }
 
 impl DataSourceClient {
     /// Create a new data source client
     pub async fn new(config: DataSourceConfig) -> Result<Self> {
-        let client: Box<dyn DataSourceImpl> = match &config.source_type {
+        let client: Arc<dyn DataSourceImpl> = match &config.source_type {
             DataSourceType::FeatureStore(fs_config) => {
-                Box::new(FeatureStoreClient::new(fs_config.clone()).await?)
+                Arc::new(FeatureStoreClient::new(fs_config.clone()).await?)
             }
             DataSourceType::OLAP(olap_config) => {
-                Box::new(OLAPClient::new(olap_config.clone()).await?)
+                Arc::new(
+                OLAPClient::new(olap_config.clone(), config.pool_size, config.timeout_ms).await?,
+            )
             }
             DataSourceType::SQL(sql_config) => {
-                Box::new(SQLClient::new(sql_config.clone(), config.pool_size).await?)
+                Arc::new(
+                    SQLClient::new(sql_config.clone(), config.pool_size, config.timeout_ms).await?,
+                )
             }
         };
 
+        let limiter = config
+            .concurrency
+            .map(|limit| Arc::new(ConcurrencyLimiter::new(config.name.clone(), limit)));
+        let breaker = config
+            .circuit_breaker
+            .map(|breaker| Arc::new(CircuitBreaker::new(config.name.clone(), breaker)));
+
         Ok(Self {
             config,
             cache: Arc::new(Mutex::new(FeatureCache::new())),
             client,
+            limiter,
+            breaker,
         })
     }
 
     /// Execute a query
+    ///
+    /// While a [`ReplaySession`](crate::replay::ReplaySession) is active, the
+    /// result is recorded or replayed.
     pub async fn query(&self, query: Query) -> Result<QueryResult> {
-        // Check cache first
         let cache_key = self.generate_cache_key(&query);
-        if let Some(cached_value) = self.cache.lock().unwrap().get(&cache_key) {
-            tracing::debug!("Cache hit for key: {}", cache_key);
+        replay::intercept(&self.dependency(), &cache_key, || {
+            self.query_cached(cache_key.clone(), query)
+        })
+        .await
+    }
+
+    async fn query_cached(&self, cache_key: String, query: Query) -> Result<QueryResult> {
+        let strategy = self.config.cache.clone().unwrap_or_default();
+        if !strategy.enabled {
+            return self.execute_uncached(query).await;
+        }
+        let ttl = Duration::from_secs(query.cache_ttl.unwrap_or(strategy.ttl));
+        let stale_ttl = strategy.stale_window();
+
+        // Check cache first
+        let cached = self
+            .cache
+            .lock()
+            .unwrap()
+            .get_with_freshness(&cache_key)
+            .map(|(row, freshness)| (row.clone(), freshness));
+        if let Some((row, freshness)) = cached {
+            tracing::debug!("Cache hit for key: {} ({:?})", cache_key, freshness);
+            if freshness == Freshness::Stale {
+                self.refresh_in_background(cache_key, query, ttl, stale_ttl);
+            }
             return Ok(QueryResult {
-                rows: vec![cached_value.clone()],
+                rows: vec![row],
                 execution_time_ms: 0,
                 source: self.config.name.clone(),
                 from_cache: true,
@@ -62,16 +115,22 @@ impl DataSourceClient {
 
         // Execute query
         let start = Instant::now();
-        let result = self.client.execute(query.clone()).await?;
+        let result = self
+            .guarded(|| async {
+                let _permit = self.acquire_slot().await?;
+                self.client.execute(query.clone()).await
+            })
+            .await?;
         let execution_time_ms = start.elapsed().as_millis() as u64;
 
         // Cache result if applicable
-        if !result.rows.is_empty() {
-            if let Some(row) = result.rows.first() {
-                self.cache.lock().unwrap().set(
+        if let Some(row) = result.rows.first() {
+            if !ttl.is_zero() {
+                self.cache.lock().unwrap().set_with_stale_ttl(
                     cache_key,
                     row.clone(),
-                    std::time::Duration::from_secs(300),
+                    ttl,
+                    stale_ttl,
                 );
             }
         }
@@ -84,35 +143,191 @@ impl DataSourceClient {
         })
     }
 
+    /// Execute a query without consulting or populating the cache
+    ///
+    /// Used for multi-row selections (e.g., scheduled entity queries) where the
+    /// single-row feature cache does not apply.
+    pub async fn query_uncached(&self, query: Query) -> Result<QueryResult> {
+        let key = format!("uncached:{}", self.generate_cache_key(&query));
+        replay::intercept(&self.dependency(), &key, || self.execute_uncached(query)).await
+    }
+
+    async fn execute_uncached(&self, query: Query) -> Result<QueryResult> {
+        let start = Instant::now();
+        let result = self
+            .guarded(|| async {
+                let _permit = self.acquire_slot().await?;
+                self.client.execute(query.clone()).await
+            })
+            .await?;
+
+        Ok(QueryResult {
+            rows: result.rows,
+            execution_time_ms: start.elapsed().as_millis() as u64,
+            source: self.config.name.clone(),
+            from_cache: false,
+        })
+    }
+
+    /// Re-run a query whose cached result is stale, without waiting for it
+    ///
+    /// At most one refresh per cache key runs at a time. If the refresh fails
+    /// the stale entry is kept until its stale window ends.
+    fn refresh_in_background(
+        &self,
+        cache_key: String,
+        query: Query,
+        ttl: Duration,
+        stale_ttl: Duration,
+    ) {
+        if !self.cache.lock().unwrap().start_refresh(&cache_key) {
+            return;
+        }
+
+        let client = Arc::clone(&self.client);
+        let cache = Arc::clone(&self.cache);
+        let limiter = self.limiter.clone();
+        let breaker = self.breaker.clone();
+        let name = self.config.name.clone();
+        tokio::spawn(async move {
+            let refresh = async {
+                let _permit = match &limiter {
+                    Some(limiter) => Some(limiter.acquire().await?),
+                    None => None,
+                };
+                client.execute(query).await
+            };
+            let result = match &breaker {
+                Some(breaker) => breaker.call(refresh).await,
+                None => refresh.await,
+            };
+
+            let mut cache = cache.lock().unwrap();
+            match result {
+                Ok(result) => {
+                    if let Some(row) = result.rows.first() {
+                        cache.set_with_stale_ttl(cache_key.clone(), row.clone(), ttl, stale_ttl);
+                    }
+                }
+                Err(e) => tracing::warn!("Background refresh on {} failed: {}", name, e),
+            }
+            cache.finish_refresh(&cache_key);
+        });
+    }
+
     /// Get a feature from feature store
     pub async fn get_feature(&self, feature_name: &str, entity_key: &str) -> Result<Option<Value>> {
-        let cache_key = format!("feature:{}:{}", feature_name, entity_key);
+        let cache_key = feature_cache_key(feature_name, entity_key);
+        replay::intercept(&self.dependency(), &cache_key, || {
+            self.get_feature_cached(feature_name, entity_key, cache_key.clone())
+        })
+        .await
+    }
 
+    async fn get_feature_cached(
+        &self,
+        feature_name: &str,
+        entity_key: &str,
+        cache_key: String,
+    ) -> Result<Option<Value>> {
         // Check cache
         if let Some(cached) = self.cache.lock().unwrap().get(&cache_key) {
             return Ok(cached.get("value").cloned());
         }
 
         // Get from feature store
-        if let Some(fs_client) = self.client.as_feature_store() {
-            let value = fs_client.get_feature(feature_name, entity_key).await?;
-
-            // Cache the result
-            if let Some(ref val) = value {
-                let mut row = HashMap::new();
-                row.insert("value".to_string(), val.clone());
-                self.cache
-                    .lock()
-                    .unwrap()
-                    .set(cache_key, row, std::time::Duration::from_secs(300));
+        let fs_client = self.feature_store()?;
+        let stored = self
+            .guarded(|| async {
+                let _permit = self.acquire_slot().await?;
+                fs_client.get_feature(feature_name, entity_key).await
+            })
+            .await?;
+        Ok(self.cache_feature(cache_key, stored))
+    }
+
+    /// Store a feature value in a feature store, in the layout lookups read
+    pub async fn set_feature(&self, feature_name: &str, entity_key: &str, value: &Value) -> Result<()> {
+        let fs_client = self.feature_store()?;
+        self.guarded(|| async {
+            let _permit = self.acquire_slot().await?;
+            fs_client.set_feature(feature_name, entity_key, value).await
+        })
+        .await?;
+        self.cache
+            .lock()
+            .unwrap()
+            .remove(&feature_cache_key(feature_name, entity_key));
+        Ok(())
+    }
+
+    /// Get several features from a feature store in one round trip
+    ///
+    /// `requests` are `(feature_name, entity_key)` pairs; values are returned
+    /// in the same order, `None` for missing ones. Cached values are not
+    /// fetched again.
+    pub async fn get_features(&self, requests: &[(String, String)]) -> Result<Vec<Option<Value>>> {
+        // Recorded and replayed calls are matched one feature at a time
+        if replay::is_active() {
+            let mut values = Vec::with_capacity(requests.len());
+            for (feature_name, entity_key) in requests {
+                values.push(self.get_feature(feature_name, entity_key).await?);
             }
+            return Ok(values);
+        }
+
+        let fs_client = self.feature_store()?;
+        let mut values = vec![None; requests.len()];
+        let mut missing = Vec::new();
+        {
+            let cache = self.cache.lock().unwrap();
+            for (i, (feature_name, entity_key)) in requests.iter().enumerate() {
+                match cache.get(&feature_cache_key(feature_name, entity_key)) {
+                    Some(cached) => values[i] = cached.get("value").cloned(),
+                    None => missing.push(i),
+                }
+            }
+        }
+        if missing.is_empty() {
+            return Ok(values);
+        }
+
+        let batch: Vec<(&str, &str)> = missing
+            .iter()
+            .map(|&i| (requests[i].0.as_str(), requests[i].1.as_str()))
+            .collect();
+        let fetched = self
+            .guarded(|| async {
+                let _permit = self.acquire_slot().await?;
+                fs_client.get_features(&batch).await
+            })
+            .await?;
+
+        for (i, stored) in missing.into_iter().zip(fetched) {
+            let (feature_name, entity_key) = &requests[i];
+            values[i] = self.cache_feature(feature_cache_key(feature_name, entity_key), stored);
+        }
+        Ok(values)
+    }
+
+    fn feature_store(&self) -> Result<&dyn FeatureStoreOps> {
+        self.client.as_feature_store().ok_or_else(|| {
+            RuntimeError::RuntimeError("Data source is not a feature store".to_string())
+        })
+    }
 
-            Ok(value)
-        } else {
-            Err(RuntimeError::RuntimeError(
-                "Data source is not a feature store".to_string(),
-            ))
+    /// Cache a stored feature no longer than its remaining lifetime
+    fn cache_feature(&self, cache_key: String, stored: Option<StoredFeature>) -> Option<Value> {
+        let stored = stored?;
+        let ttl = stored
+            .ttl
+            .map_or(FEATURE_CACHE_TTL, |ttl| ttl.min(FEATURE_CACHE_TTL));
+        if !ttl.is_zero() {
+            let mut row = HashMap::new();
+            row.insert("value".to_string(), stored.value.clone());
+            self.cache.lock().unwrap().set(cache_key, row, ttl);
         }
+        Some(stored.value)
     }
 
     /// Generate cache key for a query
@@ -124,12 +339,16 @@ impl DataSourceClient {
             serde_json::to_string(&query.filters).unwrap_or_default(),
         ];
 
-        // Include time window in cache key
+        // Include time window in cache key; calendar periods by their
+        // current range, so cached results expire at the period boundary
         if let Some(ref time_window) = query.time_window {
+            let window_type = time_window
+                .window_type
+                .resolve(chrono::Utc::now().timestamp());
             key_parts.push(format!(
                 "window:{}:{}",
                 time_window.time_field,
-                serde_json::to_string(&time_window.window_type).unwrap_or_default()
+                serde_json::to_string(&window_type).unwrap_or_default()
             ));
         }
 
@@ -146,6 +365,20 @@ impl DataSourceClient {
             key_parts.push(format!("group_by:{}", query.group_by.join(",")));
         }
 
+        // Include post-aggregation filters and ordering in cache key
+        if !query.having.is_empty() {
+            key_parts.push(format!(
+                "having:{}",
+                serde_json::to_string(&query.having).unwrap_or_default()
+            ));
+        }
+        if !query.order_by.is_empty() {
+            key_parts.push(format!(
+                "order_by:{}",
+                serde_json::to_string(&query.order_by).unwrap_or_default()
+            ));
+        }
+
         // Include limit in cache key
         if let Some(limit) = query.limit {
             key_parts.push(format!("limit:{}", limit));
@@ -158,6 +391,91 @@ impl DataSourceClient {
     pub fn name(&self) -> &str {
         &self.config.name
     }
+
+    /// Whether this is a feature store, read by key rather than queried
+    pub fn is_feature_store(&self) -> bool {
+        self.client.as_feature_store().is_some()
+    }
+
+    /// Dependency name under which calls are recorded and replayed
+    fn dependency(&self) -> String {
+        format!("datasource:{}", self.config.name)
+    }
+
+    /// Saturation statistics, if a concurrency limit is configured
+    pub fn concurrency_stats(&self) -> Option<ConcurrencyStats> {
+        self.limiter.as_deref().map(ConcurrencyLimiter::stats)
+    }
+
+    /// Count circuit breaker openings and rejections in `metrics`
+    pub fn attach_metrics(&self, metrics: Arc<MetricsCollector>) {
+        if let Some(breaker) = &self.breaker {
+            breaker.attach_metrics(metrics);
+        }
+    }
+
+    /// Circuit breaker statistics, if a circuit breaker is configured
+    pub fn circuit_breaker_stats(&self) -> Option<CircuitBreakerStats> {
+        self.breaker.as_deref().map(CircuitBreaker::stats)
+    }
+
+    /// Run a call to the data source through its circuit breaker and retry
+    /// policy, if configured
+    ///
+    /// Every attempt counts against the circuit breaker. Queries rejected for
+    /// lack of a concurrency slot count as failures: the data source is not
+    /// keeping up.
+    ///
+    /// Timeouts and failures of the data source itself are reported as
+    /// [`RuntimeError::DataSourceTimeout`] and
+    /// [`RuntimeError::DataSourceUnavailable`], naming the data source.
+    async fn guarded<T, F, Fut>(&self, mut call: F) -> Result<T>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let breaker = self.breaker.as_deref();
+        let mut attempt = || {
+            let call = call();
+            async move {
+                match breaker {
+                    Some(breaker) => breaker.call(call).await,
+                    None => call.await,
+                }
+            }
+        };
+        let result = match &self.config.retry {
+            Some(policy) => policy.run(&self.config.name, attempt).await,
+            None => attempt().await,
+        };
+        result.map_err(|e| self.classify_error(e))
+    }
+
+    /// Attribute a timeout or failure of a call to this data source
+    fn classify_error(&self, error: RuntimeError) -> RuntimeError {
+        let datasource = self.config.name.clone();
+        match error {
+            RuntimeError::Timeout(message) => RuntimeError::DataSourceTimeout {
+                datasource,
+                message,
+            },
+            RuntimeError::ExternalCallFailed(message) | RuntimeError::RuntimeError(message) => {
+                RuntimeError::DataSourceUnavailable {
+                    datasource,
+                    message,
+                }
+            }
+            other => other,
+        }
+    }
+
+    /// Wait for a query slot when a concurrency limit is configured
+    async fn acquire_slot(&self) -> Result<Option<tokio::sync::OwnedSemaphorePermit>> {
+        match &self.limiter {
+            Some(limiter) => limiter.acquire().await.map(Some),
+            None => Ok(None),
+        }
+    }
 }
 
 /// Trait for data source implementations
@@ -172,14 +490,220 @@ pub(super) trait DataSourceImpl: Send + Sync {
     }
 }
 
+/// A feature value read from a feature store
+#[derive(Debug, Clone, PartialEq)]
+pub(super) struct StoredFeature {
+    pub value: Value,
+
+    /// Remaining lifetime in the store, if the value expires
+    pub ttl: Option<Duration>,
+}
+
 /// Feature store operations
 #[async_trait::async_trait]
 pub(super) trait FeatureStoreOps: Send + Sync {
+    /// Get several feature values by `(feature_name, entity_key)`, in request order
+    async fn get_features(&self, requests: &[(&str, &str)]) -> Result<Vec<Option<StoredFeature>>>;
+
     /// Get a feature value
-    async fn get_feature(&self, feature_name: &str, entity_key: &str) -> Result<Option<Value>>;
+    async fn get_feature(
+        &self,
+        feature_name: &str,
+        entity_key: &str,
+    ) -> Result<Option<StoredFeature>> {
+        Ok(self
+            .get_features(&[(feature_name, entity_key)])
+            .await?
+            .pop()
+            .flatten())
+    }
+
+    /// Store a feature value
+    async fn set_feature(&self, feature_name: &str, entity_key: &str, value: &Value) -> Result<()> {
+        let _ = (feature_name, entity_key, value);
+        Err(RuntimeError::RuntimeError(
+            "Feature store does not accept writes".to_string(),
+        ))
+    }
+}
+
+fn feature_cache_key(feature_name: &str, entity_key: &str) -> String {
+    format!("feature:{}:{}", feature_name, entity_key)
 }
 
 use super::feature_store::FeatureStoreClient;
 use super::olap::OLAPClient;
 use super::sql::SQLClient;
 
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::datasource::cache::{CacheStrategy, CacheStrategyType};
+    use crate::datasource::config::{SQLConfig, SQLProvider};
+    use crate::datasource::query::QueryType;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Counts executions and returns the count so far
+    struct CountingSource(AtomicUsize);
+
+    #[async_trait::async_trait]
+    impl DataSourceImpl for CountingSource {
+        async fn execute(&self, _query: Query) -> Result<QueryResult> {
+            let n = self.0.fetch_add(1, Ordering::SeqCst) + 1;
+            Ok(QueryResult {
+                rows: vec![HashMap::from([("count".to_string(), Value::Number(n as f64))])],
+                execution_time_ms: 0,
+                source: "events".to_string(),
+                from_cache: false,
+            })
+        }
+    }
+
+    /// Answers every query with `rows` after `delay`, or fails without rows
+    struct StaticSource {
+        rows: Option<Vec<HashMap<String, Value>>>,
+        delay: Duration,
+    }
+
+    #[async_trait::async_trait]
+    impl DataSourceImpl for StaticSource {
+        async fn execute(&self, _query: Query) -> Result<QueryResult> {
+            tokio::time::sleep(self.delay).await;
+            let rows = self
+                .rows
+                .clone()
+                .ok_or_else(|| RuntimeError::RuntimeError("connection refused".to_string()))?;
+            Ok(QueryResult {
+                rows,
+                execution_time_ms: 0,
+                source: "static".to_string(),
+                from_cache: false,
+            })
+        }
+    }
+
+    /// An uncached client for tests elsewhere in the crate
+    pub(crate) fn static_client(
+        name: &str,
+        rows: Option<Vec<HashMap<String, Value>>>,
+        delay: Duration,
+    ) -> DataSourceClient {
+        let (mut client, _) = counting_client(CacheStrategy {
+            enabled: false,
+            ..CacheStrategy::default()
+        });
+        client.config.name = name.to_string();
+        client.client = Arc::new(StaticSource { rows, delay });
+        client
+    }
+
+    fn counting_client(cache: CacheStrategy) -> (DataSourceClient, Arc<CountingSource>) {
+        let source = Arc::new(CountingSource(AtomicUsize::new(0)));
+        let config = DataSourceConfig {
+            name: "events".to_string(),
+            source_type: DataSourceType::SQL(SQLConfig {
+                provider: SQLProvider::SQLite,
+                connection_string: ":memory:".to_string(),
+                database: "events".to_string(),
+                events_table: "events".to_string(),
+                options: HashMap::new(),
+            }),
+            pool_size: 1,
+            timeout_ms: 1000,
+            pooling_enabled: true,
+            concurrency: None,
+            circuit_breaker: None,
+            retry: None,
+            cache: Some(cache),
+        };
+        let client = DataSourceClient {
+            config,
+            cache: Arc::new(Mutex::new(FeatureCache::new())),
+            client: source.clone(),
+            limiter: None,
+            breaker: None,
+        };
+        (client, source)
+    }
+
+    fn query() -> Query {
+        Query {
+            query_type: QueryType::Count,
+            entity: "events".to_string(),
+            filters: vec![],
+            time_window: None,
+            aggregations: vec![],
+            group_by: vec![],
+            having: Vec::new(),
+            order_by: Vec::new(),
+            limit: None,
+            cache_ttl: None,
+        }
+    }
+
+    fn count(result: &QueryResult) -> &Value {
+        &result.rows[0]["count"]
+    }
+
+    #[tokio::test]
+    async fn test_stale_while_revalidate() {
+        let (client, source) = counting_client(CacheStrategy {
+            strategy: CacheStrategyType::StaleWhileRevalidate,
+            ..CacheStrategy::default()
+        });
+
+        // An entry that expired but is within its stale window
+        let key = client.generate_cache_key(&query());
+        client.cache.lock().unwrap().set_with_stale_ttl(
+            key,
+            HashMap::from([("count".to_string(), Value::Number(0.0))]),
+            Duration::ZERO,
+            Duration::from_secs(60),
+        );
+
+        let stale = client.query(query()).await.unwrap();
+        assert!(stale.from_cache);
+        assert_eq!(count(&stale), &Value::Number(0.0));
+
+        // The refresh ran in the background and replaced the entry
+        for _ in 0..100 {
+            if source.0.load(Ordering::SeqCst) == 1 {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        let fresh = client.query(query()).await.unwrap();
+        assert!(fresh.from_cache);
+        assert_eq!(count(&fresh), &Value::Number(1.0));
+        assert_eq!(source.0.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_query_cache_ttl_override() {
+        let (client, source) = counting_client(CacheStrategy::default());
+
+        // A zero TTL disables caching for this query only
+        let uncached = Query {
+            cache_ttl: Some(0),
+            ..query()
+        };
+        client.query(uncached.clone()).await.unwrap();
+        client.query(uncached).await.unwrap();
+        assert_eq!(source.0.load(Ordering::SeqCst), 2);
+
+        client.query(query()).await.unwrap();
+        let cached = client.query(query()).await.unwrap();
+        assert!(cached.from_cache);
+        assert_eq!(source.0.load(Ordering::SeqCst), 3);
+
+        let (client, source) = counting_client(CacheStrategy {
+            enabled: false,
+            ..CacheStrategy::default()
+        });
+        client.query(query()).await.unwrap();
+        client.query(query()).await.unwrap();
+        assert_eq!(source.0.load(Ordering::SeqCst), 2);
+    }
+}
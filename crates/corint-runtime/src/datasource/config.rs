@@ -2,6 +2,10 @@
 //!
 //! Configuration structures for different types of data sources.
 
+use super::cache::CacheStrategy;
+use crate::circuit_breaker::CircuitBreakerConfig;
+use crate::concurrency::ConcurrencyLimit;
+use crate::retry::RetryPolicy;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
@@ -26,6 +30,22 @@ pub struct DataSourceConfig {
     /// Enable connection pooling
     #[serde(default = "default_true")]
     pub pooling_enabled: bool,
+
+    /// Optional cap on concurrent queries against this data source
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub concurrency: Option<ConcurrencyLimit>,
+
+    /// Optional circuit breaker suspending queries while the data source is failing
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub circuit_breaker: Option<CircuitBreakerConfig>,
+
+    /// Optional retry policy for failed queries (default: a single attempt)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retry: Option<RetryPolicy>,
+
+    /// Caching of query results (default: lazy, 5 minute TTL)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache: Option<CacheStrategy>,
 }
 
 /// Data source type enumeration
@@ -149,6 +169,12 @@ pub enum SQLProvider {
 
     /// SQLite (for testing)
     SQLite,
+
+    /// Google BigQuery (`bigquery` feature)
+    BigQuery,
+
+    /// Snowflake (`snowflake` feature)
+    Snowflake,
 }
 
 // Default value functions
@@ -1,22 +1,131 @@
 //! Feature Store Client Implementation
 //!
 //! Provides feature store connectivity for Redis, Feast, and HTTP-based feature stores.
+//!
+//! Redis keys are built from a template with `{namespace}`, `{feature}` and
+//! `{key}` (entity key) placeholders. The layout is set in `options` for all
+//! features, or as `features.<feature>.<option>` for a single feature:
+//!
+//! | Option | Meaning |
+//! |--------|---------|
+//! | `key_template` | Redis key (default: `{namespace}:{feature}:{key}`) |
+//! | `layout` | `string` (GET), `hash` (HGET of `field`) or `zset` (member with the highest score) |
+//! | `field` | Hash field (default: `{feature}`) |
+//! | `max_age_secs` | For `zset`: ignore members scored (unix seconds) longer ago |
+//!
+//! Values are cached no longer than their remaining Redis TTL (or, for
+//! `zset`, until they exceed `max_age_secs`), and batched lookups are sent in
+//! one pipeline with a single MGET for all `string` keys.
 
 use super::config::{FeatureStoreConfig, FeatureStoreProvider};
 use super::query::{FilterOperator, Query, QueryResult, QueryType};
 use crate::error::{Result, RuntimeError};
 use corint_core::Value;
 use std::collections::HashMap;
+#[cfg(feature = "redis")]
+use std::time::Duration;
 
 // Import the traits from client module
-use super::client::{DataSourceImpl, FeatureStoreOps};
+use super::client::{DataSourceImpl, FeatureStoreOps, StoredFeature};
 
 #[cfg(feature = "redis")]
 use redis::aio::ConnectionManager;
 
+/// How a feature is stored in Redis
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+enum RedisLayout {
+    /// Plain key read with GET (MGET when batched)
+    #[default]
+    String,
+
+    /// Hash field read with HGET
+    Hash,
+
+    /// Sorted set whose member with the highest score is the value
+    Zset,
+}
+
+impl std::str::FromStr for RedisLayout {
+    type Err = RuntimeError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "string" => Ok(Self::String),
+            "hash" => Ok(Self::Hash),
+            "zset" => Ok(Self::Zset),
+            _ => Err(RuntimeError::RuntimeError(format!(
+                "Unknown Redis layout '{}' (expected string, hash or zset)",
+                s
+            ))),
+        }
+    }
+}
+
+/// Layout options; unset ones fall back to the datasource defaults
+#[derive(Debug, Clone, Default)]
+struct LayoutOptions {
+    key_template: Option<String>,
+    layout: Option<RedisLayout>,
+    field: Option<String>,
+    max_age_secs: Option<u64>,
+}
+
+impl LayoutOptions {
+    /// Parse the layout options of a datasource, returning the defaults and
+    /// the per-feature overrides
+    fn parse(options: &HashMap<String, String>) -> Result<(Self, HashMap<String, Self>)> {
+        let mut defaults = Self::default();
+        let mut features: HashMap<String, Self> = HashMap::new();
+
+        for (name, value) in options {
+            if let Some(rest) = name.strip_prefix("features.") {
+                let (feature, option) = rest.rsplit_once('.').unwrap_or((rest, ""));
+                if !features.entry(feature.to_string()).or_default().set(option, value)? {
+                    return Err(RuntimeError::RuntimeError(format!(
+                        "Unknown feature store option '{}'",
+                        name
+                    )));
+                }
+            } else {
+                // Other options (pool sizes, timeouts) are not layout related
+                defaults.set(name, value)?;
+            }
+        }
+
+        Ok((defaults, features))
+    }
+
+    /// Set an option by name; returns false for unknown names
+    fn set(&mut self, name: &str, value: &str) -> Result<bool> {
+        match name {
+            "key_template" => self.key_template = Some(value.to_string()),
+            "layout" => self.layout = Some(value.parse()?),
+            "field" => self.field = Some(value.to_string()),
+            "max_age_secs" => {
+                self.max_age_secs = Some(value.parse().map_err(|_| {
+                    RuntimeError::RuntimeError(format!("Invalid max_age_secs: {}", value))
+                })?)
+            }
+            _ => return Ok(false),
+        }
+        Ok(true)
+    }
+}
+
+/// Where and how a feature value is read
+#[derive(Debug, Clone, PartialEq)]
+struct FeatureLocation {
+    key: String,
+    layout: RedisLayout,
+    field: String,
+    max_age_secs: Option<u64>,
+}
+
 /// Feature Store Client
 pub(super) struct FeatureStoreClient {
     config: FeatureStoreConfig,
+    layout: LayoutOptions,
+    feature_layouts: HashMap<String, LayoutOptions>,
     #[cfg(feature = "redis")]
     redis_conn: Option<ConnectionManager>,
 }
@@ -25,6 +134,8 @@ impl FeatureStoreClient {
     pub(super) async fn new(config: FeatureStoreConfig) -> Result<Self> {
         tracing::info!("Initializing feature store client: {:?}", config.provider);
 
+        let (layout, feature_layouts) = LayoutOptions::parse(&config.options)?;
+
         #[cfg(feature = "redis")]
         let redis_conn = if matches!(config.provider, FeatureStoreProvider::Redis) {
             Self::init_redis_connection(&config.connection_string).await.ok()
@@ -32,11 +143,10 @@ impl FeatureStoreClient {
             None
         };
 
-        #[cfg(not(feature = "redis"))]
-        let _config_check = &config; // Avoid unused variable warning
-
         Ok(Self {
             config,
+            layout,
+            feature_layouts,
             #[cfg(feature = "redis")]
             redis_conn,
         })
@@ -72,90 +182,76 @@ impl FeatureStoreClient {
         ))
     }
 
-    /// Get feature from Redis
-    async fn get_redis_feature(
-        &self,
-        feature_name: &str,
-        entity_key: &str,
-    ) -> Result<Option<Value>> {
-        #[cfg(feature = "redis")]
-        {
-            use redis::AsyncCommands;
-
-            let redis_key = if self.config.namespace.is_empty() {
-                format!("{}:{}", feature_name, entity_key)
+    /// Resolve the Redis key and layout of a feature
+    fn location(&self, feature_name: &str, entity_key: &str) -> FeatureLocation {
+        let custom = self.feature_layouts.get(feature_name);
+        let key_template = custom
+            .and_then(|c| c.key_template.as_deref())
+            .or(self.layout.key_template.as_deref())
+            .unwrap_or(if self.config.namespace.is_empty() {
+                "{feature}:{key}"
             } else {
-                format!("{}:{}:{}", self.config.namespace, feature_name, entity_key)
-            };
+                "{namespace}:{feature}:{key}"
+            });
+        let field = custom
+            .and_then(|c| c.field.as_deref())
+            .or(self.layout.field.as_deref())
+            .unwrap_or("{feature}");
+
+        let render = |template: &str| {
+            template
+                .replace("{namespace}", &self.config.namespace)
+                .replace("{feature}", feature_name)
+                .replace("{key}", entity_key)
+        };
 
-            tracing::debug!("Fetching Redis key: {}", redis_key);
+        FeatureLocation {
+            key: render(key_template),
+            layout: custom
+                .and_then(|c| c.layout)
+                .or(self.layout.layout)
+                .unwrap_or_default(),
+            field: render(field),
+            max_age_secs: custom
+                .and_then(|c| c.max_age_secs)
+                .or(self.layout.max_age_secs),
+        }
+    }
+
+    /// Get features from Redis in one round trip
+    async fn get_redis_features(
+        &self,
+        requests: &[(&str, &str)],
+    ) -> Result<Vec<Option<StoredFeature>>> {
+        let locations: Vec<FeatureLocation> = requests
+            .iter()
+            .map(|(feature, key)| self.location(feature, key))
+            .collect();
 
+        tracing::debug!("Fetching Redis features: {:?}", locations);
+
+        #[cfg(feature = "redis")]
+        {
             let Some(ref conn) = self.redis_conn else {
                 tracing::warn!("Redis connection not initialized, returning None");
-                return Ok(None);
+                return Ok(requests.iter().map(|_| None).collect());
             };
 
             let mut conn = conn.clone();
+            let now = unix_now();
+            let replies: Vec<redis::Value> = build_pipeline(&locations, now)
+                .query_async(&mut conn)
+                .await
+                .map_err(|e| {
+                    tracing::error!("Redis error while fetching features: {}", e);
+                    RuntimeError::RuntimeError(format!("Redis error: {}", e))
+                })?;
 
-            // Try to get the value from Redis
-            match conn.get::<_, Option<String>>(&redis_key).await {
-                Ok(Some(value_str)) => {
-                    tracing::debug!("Found value in Redis for key {}: {}", redis_key, value_str);
-
-                    // Parse the value - try to detect the type
-                    let value = if let Ok(num) = value_str.parse::<f64>() {
-                        Value::Number(num)
-                    } else if let Ok(b) = value_str.parse::<bool>() {
-                        Value::Bool(b)
-                    } else if let Ok(json_val) = serde_json::from_str::<serde_json::Value>(&value_str) {
-                        // Try to parse as JSON
-                        match json_val {
-                            serde_json::Value::Number(n) => {
-                                Value::Number(n.as_f64().unwrap_or(0.0))
-                            }
-                            serde_json::Value::String(s) => Value::String(s),
-                            serde_json::Value::Bool(b) => Value::Bool(b),
-                            serde_json::Value::Null => Value::Null,
-                            serde_json::Value::Array(arr) => {
-                                // Convert JSON array to Value array
-                                let converted: Vec<Value> = arr
-                                    .into_iter()
-                                    .filter_map(|v| serde_json::from_value(v).ok())
-                                    .collect();
-                                Value::Array(converted)
-                            }
-                            serde_json::Value::Object(obj) => {
-                                // Convert JSON object to Value object
-                                let converted: HashMap<String, Value> = obj
-                                    .into_iter()
-                                    .filter_map(|(k, v)| {
-                                        serde_json::from_value(v).ok().map(|val| (k, val))
-                                    })
-                                    .collect();
-                                Value::Object(converted)
-                            }
-                        }
-                    } else {
-                        // Default to string
-                        Value::String(value_str)
-                    };
-
-                    Ok(Some(value))
-                }
-                Ok(None) => {
-                    tracing::debug!("Key not found in Redis: {}", redis_key);
-                    Ok(None)
-                }
-                Err(e) => {
-                    tracing::error!("Redis error while fetching key {}: {}", redis_key, e);
-                    Err(RuntimeError::RuntimeError(format!("Redis error: {}", e)))
-                }
-            }
+            decode_replies(&locations, replies, now)
         }
 
         #[cfg(not(feature = "redis"))]
         {
-            let _ = (feature_name, entity_key);
             Err(RuntimeError::RuntimeError(
                 "Redis feature is not enabled. Please compile with --features redis".to_string(),
             ))
@@ -163,7 +259,6 @@ impl FeatureStoreClient {
     }
 
     /// Set feature to Redis
-    #[allow(dead_code)]
     async fn set_redis_feature(
         &self,
         feature_name: &str,
@@ -172,15 +267,9 @@ impl FeatureStoreClient {
     ) -> Result<()> {
         #[cfg(feature = "redis")]
         {
-            use redis::AsyncCommands;
+            let location = self.location(feature_name, entity_key);
 
-            let redis_key = if self.config.namespace.is_empty() {
-                format!("{}:{}", feature_name, entity_key)
-            } else {
-                format!("{}:{}:{}", self.config.namespace, feature_name, entity_key)
-            };
-
-            tracing::debug!("Setting Redis key: {} = {:?}", redis_key, value);
+            tracing::debug!("Setting Redis feature: {:?} = {:?}", location, value);
 
             let Some(ref conn) = self.redis_conn else {
                 return Err(RuntimeError::RuntimeError(
@@ -205,11 +294,25 @@ impl FeatureStoreClient {
 
             // Set with TTL
             let ttl = self.config.default_ttl;
-            conn.set_ex::<_, _, ()>(&redis_key, value_str, ttl)
+            let mut pipe = redis::pipe();
+            match location.layout {
+                RedisLayout::String => {
+                    pipe.cmd("SET").arg(&location.key).arg(value_str).arg("EX").arg(ttl);
+                }
+                RedisLayout::Hash => {
+                    pipe.cmd("HSET").arg(&location.key).arg(&location.field).arg(value_str);
+                    pipe.cmd("EXPIRE").arg(&location.key).arg(ttl);
+                }
+                RedisLayout::Zset => {
+                    pipe.cmd("ZADD").arg(&location.key).arg(unix_now()).arg(value_str);
+                    pipe.cmd("EXPIRE").arg(&location.key).arg(ttl);
+                }
+            }
+            pipe.query_async::<_, ()>(&mut conn)
                 .await
                 .map_err(|e| RuntimeError::RuntimeError(format!("Redis SET error: {}", e)))?;
 
-            tracing::debug!("Successfully set Redis key {} with TTL {}s", redis_key, ttl);
+            tracing::debug!("Successfully set Redis key {} with TTL {}s", location.key, ttl);
             Ok(())
         }
 
@@ -223,6 +326,169 @@ impl FeatureStoreClient {
     }
 }
 
+/// Pipeline reading every location: one MGET for all string keys, then
+/// per location its PTTL, HGET + PTTL, or ZREVRANGEBYSCORE
+#[cfg(feature = "redis")]
+fn build_pipeline(locations: &[FeatureLocation], now: f64) -> redis::Pipeline {
+    let mut pipe = redis::pipe();
+
+    let string_keys: Vec<&str> = locations
+        .iter()
+        .filter(|l| l.layout == RedisLayout::String)
+        .map(|l| l.key.as_str())
+        .collect();
+    if !string_keys.is_empty() {
+        pipe.cmd("MGET").arg(&string_keys);
+    }
+
+    for location in locations {
+        match location.layout {
+            RedisLayout::String => {
+                pipe.cmd("PTTL").arg(&location.key);
+            }
+            RedisLayout::Hash => {
+                pipe.cmd("HGET").arg(&location.key).arg(&location.field);
+                pipe.cmd("PTTL").arg(&location.key);
+            }
+            RedisLayout::Zset => {
+                let min = location
+                    .max_age_secs
+                    .map_or("-inf".to_string(), |age| (now - age as f64).to_string());
+                pipe.cmd("ZREVRANGEBYSCORE")
+                    .arg(&location.key)
+                    .arg("+inf")
+                    .arg(min)
+                    .arg("WITHSCORES")
+                    .arg("LIMIT")
+                    .arg(0)
+                    .arg(1);
+            }
+        }
+    }
+
+    pipe
+}
+
+/// Decode the replies of [`build_pipeline`], in location order
+#[cfg(feature = "redis")]
+fn decode_replies(
+    locations: &[FeatureLocation],
+    replies: Vec<redis::Value>,
+    now: f64,
+) -> Result<Vec<Option<StoredFeature>>> {
+    let mut replies = replies.into_iter();
+    let has_strings = locations.iter().any(|l| l.layout == RedisLayout::String);
+    let mut strings = if has_strings {
+        decode::<Vec<Option<String>>>(&mut replies)?.into_iter()
+    } else {
+        Vec::new().into_iter()
+    };
+
+    let mut features = Vec::with_capacity(locations.len());
+    for location in locations {
+        let feature = match location.layout {
+            RedisLayout::String => {
+                let raw = strings.next().flatten();
+                let ttl = decode_pttl(&mut replies)?;
+                raw.map(|raw| StoredFeature {
+                    value: parse_value(raw),
+                    ttl,
+                })
+            }
+            RedisLayout::Hash => {
+                let raw = decode::<Option<String>>(&mut replies)?;
+                let ttl = decode_pttl(&mut replies)?;
+                raw.map(|raw| StoredFeature {
+                    value: parse_value(raw),
+                    ttl,
+                })
+            }
+            RedisLayout::Zset => {
+                let reply = decode::<Vec<String>>(&mut replies)?;
+                match reply.as_slice() {
+                    [member, score, ..] => {
+                        let score: f64 = score.parse().map_err(|_| {
+                            RuntimeError::RuntimeError(format!("Invalid zset score: {}", score))
+                        })?;
+                        let ttl = location.max_age_secs.map(|age| {
+                            Duration::from_secs_f64((score + age as f64 - now).max(0.0))
+                        });
+                        Some(StoredFeature {
+                            value: parse_value(member.clone()),
+                            ttl,
+                        })
+                    }
+                    _ => None,
+                }
+            }
+        };
+        features.push(feature);
+    }
+
+    Ok(features)
+}
+
+#[cfg(feature = "redis")]
+fn decode<T: redis::FromRedisValue>(replies: &mut impl Iterator<Item = redis::Value>) -> Result<T> {
+    let reply = replies.next().ok_or_else(|| {
+        RuntimeError::RuntimeError("Incomplete Redis pipeline reply".to_string())
+    })?;
+    redis::from_redis_value(&reply)
+        .map_err(|e| RuntimeError::RuntimeError(format!("Unexpected Redis reply: {}", e)))
+}
+
+/// Remaining lifetime from a PTTL reply (-1: no expiry, -2: no key)
+#[cfg(feature = "redis")]
+fn decode_pttl(replies: &mut impl Iterator<Item = redis::Value>) -> Result<Option<Duration>> {
+    let millis = decode::<i64>(replies)?;
+    Ok((millis >= 0).then(|| Duration::from_millis(millis as u64)))
+}
+
+#[cfg(feature = "redis")]
+fn unix_now() -> f64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs_f64())
+        .unwrap_or_default()
+}
+
+/// Parse a stored value - numbers, booleans and JSON are detected
+#[cfg_attr(not(feature = "redis"), allow(dead_code))]
+fn parse_value(value_str: String) -> Value {
+    if let Ok(num) = value_str.parse::<f64>() {
+        Value::Number(num)
+    } else if let Ok(b) = value_str.parse::<bool>() {
+        Value::Bool(b)
+    } else if let Ok(json_val) = serde_json::from_str::<serde_json::Value>(&value_str) {
+        // Try to parse as JSON
+        match json_val {
+            serde_json::Value::Number(n) => Value::Number(n.as_f64().unwrap_or(0.0)),
+            serde_json::Value::String(s) => Value::String(s),
+            serde_json::Value::Bool(b) => Value::Bool(b),
+            serde_json::Value::Null => Value::Null,
+            serde_json::Value::Array(arr) => {
+                // Convert JSON array to Value array
+                let converted: Vec<Value> = arr
+                    .into_iter()
+                    .filter_map(|v| serde_json::from_value(v).ok())
+                    .collect();
+                Value::Array(converted)
+            }
+            serde_json::Value::Object(obj) => {
+                // Convert JSON object to Value object
+                let converted: HashMap<String, Value> = obj
+                    .into_iter()
+                    .filter_map(|(k, v)| serde_json::from_value(v).ok().map(|val| (k, val)))
+                    .collect();
+                Value::Object(converted)
+            }
+        }
+    } else {
+        // Default to string
+        Value::String(value_str)
+    }
+}
+
 #[async_trait::async_trait]
 impl DataSourceImpl for FeatureStoreClient {
     async fn execute(&self, query: Query) -> Result<QueryResult> {
@@ -240,9 +506,9 @@ impl DataSourceImpl for FeatureStoreClient {
                 let value = self.get_feature(feature_name, &entity_key).await?;
 
                 // Convert to QueryResult format
-                let row = if let Some(val) = value {
+                let row = if let Some(stored) = value {
                     let mut map = HashMap::new();
-                    map.insert("value".to_string(), val);
+                    map.insert("value".to_string(), stored.value);
                     vec![map]
                 } else {
                     vec![]
@@ -268,13 +534,11 @@ impl DataSourceImpl for FeatureStoreClient {
 
 #[async_trait::async_trait]
 impl FeatureStoreOps for FeatureStoreClient {
-    async fn get_feature(&self, feature_name: &str, entity_key: &str) -> Result<Option<Value>> {
-        tracing::debug!("Getting feature {} for entity {}", feature_name, entity_key);
+    async fn get_features(&self, requests: &[(&str, &str)]) -> Result<Vec<Option<StoredFeature>>> {
+        tracing::debug!("Getting {} features", requests.len());
 
         match self.config.provider {
-            FeatureStoreProvider::Redis => {
-                self.get_redis_feature(feature_name, entity_key).await
-            }
+            FeatureStoreProvider::Redis => self.get_redis_features(requests).await,
             FeatureStoreProvider::Feast => Err(RuntimeError::RuntimeError(
                 "Feast not yet implemented".to_string(),
             )),
@@ -283,6 +547,18 @@ impl FeatureStoreOps for FeatureStoreClient {
             )),
         }
     }
+
+    async fn set_feature(&self, feature_name: &str, entity_key: &str, value: &Value) -> Result<()> {
+        match self.config.provider {
+            FeatureStoreProvider::Redis => {
+                self.set_redis_feature(feature_name, entity_key, value).await
+            }
+            _ => Err(RuntimeError::RuntimeError(format!(
+                "{:?} feature store does not accept writes",
+                self.config.provider
+            ))),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -413,4 +689,124 @@ default_ttl: 7200
         assert_eq!(config.namespace, "user_features");
         assert_eq!(config.default_ttl, 7200);
     }
+
+    async fn http_client(options: &[(&str, &str)]) -> Result<FeatureStoreClient> {
+        FeatureStoreClient::new(FeatureStoreConfig {
+            provider: FeatureStoreProvider::Http,
+            connection_string: "http://localhost".to_string(),
+            namespace: "risk".to_string(),
+            default_ttl: 3600,
+            options: options
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+        })
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_feature_locations() {
+        let client = http_client(&[
+            ("max_connections", "20"),
+            ("key_template", "{namespace}:user:{key}"),
+            ("layout", "hash"),
+            ("features.user_30d_chargeback_count.layout", "zset"),
+            ("features.user_30d_chargeback_count.key_template", "cb:{key}"),
+            ("features.user_30d_chargeback_count.max_age_secs", "86400"),
+        ])
+        .await
+        .unwrap();
+
+        assert_eq!(
+            client.location("risk_score", "u1"),
+            FeatureLocation {
+                key: "risk:user:u1".to_string(),
+                layout: RedisLayout::Hash,
+                field: "risk_score".to_string(),
+                max_age_secs: None,
+            }
+        );
+        assert_eq!(
+            client.location("user_30d_chargeback_count", "u1"),
+            FeatureLocation {
+                key: "cb:u1".to_string(),
+                layout: RedisLayout::Zset,
+                field: "user_30d_chargeback_count".to_string(),
+                max_age_secs: Some(86400),
+            }
+        );
+
+        // Defaults keep the namespace:feature:key scheme
+        let client = http_client(&[]).await.unwrap();
+        let location = client.location("risk_score", "u1");
+        assert_eq!(location.key, "risk:risk_score:u1");
+        assert_eq!(location.layout, RedisLayout::String);
+
+        assert!(http_client(&[("layout", "list")]).await.is_err());
+        assert!(http_client(&[("features.risk_score.ttl", "5")]).await.is_err());
+    }
+
+    #[cfg(feature = "redis")]
+    #[test]
+    fn test_pipeline_batches_string_keys() {
+        let location = |key: &str, layout| FeatureLocation {
+            key: key.to_string(),
+            layout,
+            field: "score".to_string(),
+            max_age_secs: Some(60),
+        };
+        let locations = vec![
+            location("a", RedisLayout::String),
+            location("h", RedisLayout::Hash),
+            location("b", RedisLayout::String),
+            location("z", RedisLayout::Zset),
+            location("c", RedisLayout::String),
+        ];
+
+        let packed = String::from_utf8(build_pipeline(&locations, 1000.0).get_packed_pipeline())
+            .unwrap();
+        assert_eq!(packed.matches("MGET").count(), 1);
+        assert_eq!(packed.matches("PTTL").count(), 4);
+        assert!(packed.contains("$3\r\n940\r\n"));
+
+        let data = |s: &str| redis::Value::Data(s.as_bytes().to_vec());
+        let replies = vec![
+            redis::Value::Bulk(vec![data("0.5"), redis::Value::Nil, data("true")]),
+            redis::Value::Int(-1),
+            data("{\"level\":\"high\"}"),
+            redis::Value::Int(30_000),
+            redis::Value::Int(-2),
+            redis::Value::Bulk(vec![data("7"), data("990")]),
+            redis::Value::Int(500_000),
+        ];
+
+        let features = decode_replies(&locations, replies, 1000.0).unwrap();
+        assert_eq!(
+            features[0],
+            Some(StoredFeature {
+                value: Value::Number(0.5),
+                ttl: None
+            })
+        );
+        let hash = features[1].as_ref().unwrap();
+        assert!(matches!(hash.value, Value::Object(_)));
+        assert_eq!(hash.ttl, Some(Duration::from_secs(30)));
+        assert_eq!(features[2], None);
+        assert_eq!(
+            features[3],
+            Some(StoredFeature {
+                value: Value::Number(7.0),
+                ttl: Some(Duration::from_secs(50))
+            })
+        );
+        assert_eq!(
+            features[4],
+            Some(StoredFeature {
+                value: Value::Bool(true),
+                ttl: Some(Duration::from_secs(500))
+            })
+        );
+
+        assert!(decode_replies(&locations, vec![], 1000.0).is_err());
+    }
 }
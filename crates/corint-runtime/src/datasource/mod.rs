@@ -13,13 +13,14 @@ mod feature_store;
 mod olap;
 mod sql;
 
-pub use cache::{CacheStrategy, CachedResult};
+pub use cache::{CacheStrategy, CacheStrategyType, CachedResult, Freshness};
 pub use client::DataSourceClient;
 pub use config::{
     DataSourceConfig, DataSourceType, FeatureStoreConfig, OLAPConfig, OLAPProvider, SQLConfig,
     SQLProvider,
 };
 pub use query::{
-    Aggregation, AggregationType, Filter, FilterOperator, Query, QueryResult, QueryType,
-    RelativeWindow, TimeUnit, TimeWindow, TimeWindowType,
+    Aggregation, AggregationType, Filter, FilterOperator, OrderBy, Query, QueryResult, QueryType,
+    RelativeWindow, SessionWindow, TimeUnit, TimeWindow, TimeWindowType, TumblingWindow,
+    WindowAlignment,
 };
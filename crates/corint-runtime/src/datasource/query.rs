@@ -2,6 +2,7 @@
 //!
 //! Unified query interface for different data sources.
 
+use chrono::{DateTime, Datelike, Months, NaiveDateTime, Timelike};
 use corint_core::Value;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -30,8 +31,20 @@ pub struct Query {
     #[serde(default)]
     pub group_by: Vec<String>,
 
+    /// Conditions on aggregation outputs or group keys, applied after grouping (HAVING)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub having: Vec<Filter>,
+
+    /// Result ordering; with `limit` this selects the top N groups
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub order_by: Vec<OrderBy>,
+
     /// Limit number of results
     pub limit: Option<usize>,
+
+    /// Seconds to cache the result, overriding the data source's cache TTL
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_ttl: Option<u64>,
 }
 
 /// Query type enumeration
@@ -83,6 +96,35 @@ pub enum FilterOperator {
     Regex, // Regex match
 }
 
+/// Sort key of a query
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrderBy {
+    /// Aggregation output name or group by field
+    pub field: String,
+
+    /// Sort in descending order
+    #[serde(default)]
+    pub descending: bool,
+}
+
+impl OrderBy {
+    /// Largest values first
+    pub fn desc(field: impl Into<String>) -> Self {
+        Self {
+            field: field.into(),
+            descending: true,
+        }
+    }
+
+    /// Smallest values first
+    pub fn asc(field: impl Into<String>) -> Self {
+        Self {
+            field: field.into(),
+            descending: false,
+        }
+    }
+}
+
 /// Time window specification
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TimeWindow {
@@ -99,7 +141,7 @@ pub struct TimeWindow {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(untagged)]
 pub enum TimeWindowType {
-    /// Relative window (e.g., last_24h, last_7d)
+    /// Sliding window ending now (e.g., last_24h, last_7d)
     Relative(RelativeWindow),
 
     /// Absolute time range
@@ -107,6 +149,133 @@ pub enum TimeWindowType {
         start: i64, // Unix timestamp
         end: i64,   // Unix timestamp
     },
+
+    /// Calendar-aligned period (e.g., this calendar day, previous hour)
+    Tumbling(TumblingWindow),
+
+    /// Current session: events since the last gap in activity longer than
+    /// `session_gap`, or none once the session has timed out
+    Session(SessionWindow),
+}
+
+impl TimeWindowType {
+    /// Parse a window name: a sliding duration (`24h`, `last_7d`), a calendar
+    /// period (`this_hour`, `today`, `yesterday`, `this_week`, `this_month`)
+    /// or a session gap (`session_30m`)
+    pub fn parse(s: &str) -> Option<Self> {
+        let tumbling = |align, periods_ago| {
+            Some(TimeWindowType::Tumbling(TumblingWindow {
+                align,
+                periods_ago,
+                utc_offset_minutes: 0,
+            }))
+        };
+        match s {
+            "this_hour" => tumbling(WindowAlignment::Hour, 0),
+            "today" | "this_day" => tumbling(WindowAlignment::Day, 0),
+            "yesterday" => tumbling(WindowAlignment::Day, 1),
+            "this_week" => tumbling(WindowAlignment::Week, 0),
+            "this_month" => tumbling(WindowAlignment::Month, 0),
+            _ => match s.strip_prefix("session_") {
+                Some(gap) => Some(TimeWindowType::Session(SessionWindow {
+                    session_gap: RelativeWindow::parse_duration(gap)?,
+                })),
+                None => RelativeWindow::from_string(s).map(TimeWindowType::Relative),
+            },
+        }
+    }
+
+    /// The window with calendar periods fixed to absolute ranges at `now`
+    /// (seconds since epoch)
+    pub fn resolve(&self, now: i64) -> TimeWindowType {
+        match self {
+            TimeWindowType::Tumbling(tumbling) => {
+                let (start, end) = tumbling.bounds(now);
+                TimeWindowType::Absolute { start, end }
+            }
+            other => other.clone(),
+        }
+    }
+}
+
+/// Calendar boundary a tumbling window is aligned to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WindowAlignment {
+    Hour,
+    Day,
+    /// ISO weeks, starting on Monday
+    Week,
+    Month,
+}
+
+/// Tumbling window covering one calendar period
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TumblingWindow {
+    /// Period length and alignment
+    pub align: WindowAlignment,
+
+    /// 0 for the current period, 1 for the previous one, ...
+    #[serde(default)]
+    pub periods_ago: u32,
+
+    /// Offset of the calendar's time zone from UTC, in minutes
+    #[serde(default)]
+    pub utc_offset_minutes: i32,
+}
+
+impl TumblingWindow {
+    /// Start and end (exclusive) of the period in seconds since epoch
+    pub fn bounds(&self, now: i64) -> (i64, i64) {
+        let offset = self.utc_offset_minutes as i64 * 60;
+        let local = DateTime::from_timestamp(now + offset, 0)
+            .unwrap_or_default()
+            .naive_utc();
+        let date = local.date();
+        let ago = self.periods_ago as i64;
+
+        let (start, end): (NaiveDateTime, NaiveDateTime) = match self.align {
+            WindowAlignment::Hour => {
+                let start = date.and_hms_opt(local.hour(), 0, 0).unwrap_or_default()
+                    - chrono::Duration::hours(ago);
+                (start, start + chrono::Duration::hours(1))
+            }
+            WindowAlignment::Day => {
+                let start =
+                    date.and_hms_opt(0, 0, 0).unwrap_or_default() - chrono::Duration::days(ago);
+                (start, start + chrono::Duration::days(1))
+            }
+            WindowAlignment::Week => {
+                let monday =
+                    date - chrono::Duration::days(date.weekday().num_days_from_monday() as i64);
+                let start =
+                    monday.and_hms_opt(0, 0, 0).unwrap_or_default() - chrono::Duration::weeks(ago);
+                (start, start + chrono::Duration::weeks(1))
+            }
+            WindowAlignment::Month => {
+                let first = date.with_day(1).unwrap_or(date);
+                let start = first
+                    .checked_sub_months(Months::new(self.periods_ago))
+                    .unwrap_or(first);
+                let end = start.checked_add_months(Months::new(1)).unwrap_or(start);
+                (
+                    start.and_hms_opt(0, 0, 0).unwrap_or_default(),
+                    end.and_hms_opt(0, 0, 0).unwrap_or_default(),
+                )
+            }
+        };
+        (
+            start.and_utc().timestamp() - offset,
+            end.and_utc().timestamp() - offset,
+        )
+    }
+}
+
+/// Session window
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionWindow {
+    /// Inactivity that ends a session
+    pub session_gap: RelativeWindow,
 }
 
 /// Relative time window
@@ -152,7 +321,7 @@ impl RelativeWindow {
         }
     }
 
-    fn parse_duration(s: &str) -> Option<Self> {
+    pub(crate) fn parse_duration(s: &str) -> Option<Self> {
         let len = s.len();
         if len < 2 {
             return None;
@@ -207,6 +376,18 @@ pub enum AggregationType {
     Median,
     Stddev,
     Percentile { p: u8 },
+    /// Most frequent value
+    Mode,
+    /// Sum with each value weighted by `0.5^(age / half_life)`
+    DecayedSum {
+        half_life_secs: u64,
+        time_field: String,
+    },
+    /// Count with each event weighted by `0.5^(age / half_life)`
+    DecayedCount {
+        half_life_secs: u64,
+        time_field: String,
+    },
 }
 
 /// Query result
@@ -249,6 +430,57 @@ mod tests {
         assert!(matches!(window.unit, TimeUnit::Minutes));
     }
 
+    #[test]
+    fn test_tumbling_window_bounds() {
+        // Wednesday 2024-01-31T22:30:00Z
+        let now = 1_706_740_200;
+        let bounds = |window: &str| match TimeWindowType::parse(window).unwrap().resolve(now) {
+            TimeWindowType::Absolute { start, end } => (start, end),
+            other => panic!("unexpected window {:?}", other),
+        };
+        let day = 86_400;
+        let jan_31 = 1_706_659_200;
+
+        assert_eq!(bounds("this_hour"), (now - 1800, now + 1800));
+        assert_eq!(bounds("today"), (jan_31, jan_31 + day));
+        assert_eq!(bounds("yesterday"), (jan_31 - day, jan_31));
+        assert_eq!(bounds("this_week"), (jan_31 - 2 * day, jan_31 + 5 * day));
+        assert_eq!(bounds("this_month"), (jan_31 - 30 * day, jan_31 + day));
+
+        // In UTC+2 it is already February 1st
+        let window = TumblingWindow {
+            align: WindowAlignment::Month,
+            periods_ago: 0,
+            utc_offset_minutes: 120,
+        };
+        assert_eq!(
+            window.bounds(now),
+            (jan_31 + day - 7200, jan_31 + 30 * day - 7200)
+        );
+
+        assert!(matches!(
+            TimeWindowType::parse("24h"),
+            Some(TimeWindowType::Relative(_))
+        ));
+        match TimeWindowType::parse("session_30m") {
+            Some(TimeWindowType::Session(session)) => {
+                assert_eq!(session.session_gap.to_seconds(), 1800)
+            }
+            other => panic!("unexpected window {:?}", other),
+        }
+
+        let window: TimeWindow =
+            serde_json::from_str(r#"{"align": "day", "utc_offset_minutes": -300}"#).unwrap();
+        assert!(matches!(
+            window.window_type,
+            TimeWindowType::Tumbling(TumblingWindow {
+                align: WindowAlignment::Day,
+                utc_offset_minutes: -300,
+                ..
+            })
+        ));
+    }
+
     #[test]
     fn test_query_serialization() {
         let query = Query {
@@ -272,7 +504,10 @@ mod tests {
                 output_name: "unique_devices".to_string(),
             }],
             group_by: vec![],
+            having: Vec::new(),
+            order_by: Vec::new(),
             limit: None,
+            cache_ttl: None,
         };
 
         let json = serde_json::to_string_pretty(&query).unwrap();
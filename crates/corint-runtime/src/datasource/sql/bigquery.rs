@@ -0,0 +1,461 @@
+//! BigQuery client over the REST API
+//!
+//! Queries are submitted with `jobs.query`. When BigQuery doesn't finish
+//! within the request, the job is polled with `jobs.getQueryResults` until it
+//! completes or the timeout elapses, and further result pages are fetched by
+//! page token.
+//!
+//! `connection_string` is the project (`bigquery://my-project`), `database`
+//! the default dataset for unqualified table names. Requests are authorized
+//! with a service-account key: a JWT signed with the key is exchanged for an
+//! OAuth access token, which is reused until shortly before it expires.
+//!
+//! | Option | Meaning |
+//! |--------|---------|
+//! | `credentials_path` | Service-account key file (default: `$GOOGLE_APPLICATION_CREDENTIALS`) |
+//! | `credentials_json` | Service-account key JSON, instead of a file |
+//! | `location` | Job location, e.g. `US` or `europe-west1` |
+//! | `timeout_ms` | Overall query timeout (default: the datasource `timeout_ms`) |
+//! | `poll_interval_ms` | Wait between polls of a running job (default: 500) |
+//! | `endpoint` | API root (default: `https://bigquery.googleapis.com`) |
+
+use super::super::config::SQLConfig;
+use super::warehouse::{self, CachedToken, JwtSigner};
+use super::Warehouse;
+use crate::error::{Result, RuntimeError};
+use corint_core::Value;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+
+const DEFAULT_ENDPOINT: &str = "https://bigquery.googleapis.com";
+const SCOPE: &str = "https://www.googleapis.com/auth/bigquery";
+const JWT_BEARER_GRANT: &str = "urn:ietf:params:oauth:grant-type:jwt-bearer";
+
+/// Longest single wait BigQuery is asked for before it answers
+const MAX_WAIT: Duration = Duration::from_secs(10);
+
+/// Service-account key file
+#[derive(Deserialize)]
+struct ServiceAccountKey {
+    client_email: String,
+    private_key: String,
+    #[serde(default = "default_token_uri")]
+    token_uri: String,
+    #[serde(default)]
+    project_id: Option<String>,
+}
+
+fn default_token_uri() -> String {
+    "https://oauth2.googleapis.com/token".to_string()
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    expires_in: u64,
+}
+
+/// Response of `jobs.query` and `jobs.getQueryResults`
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct QueryResponse {
+    #[serde(default)]
+    job_complete: bool,
+    job_reference: Option<JobReference>,
+    schema: Option<TableSchema>,
+    #[serde(default)]
+    rows: Vec<TableRow>,
+    page_token: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct JobReference {
+    job_id: String,
+    location: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TableSchema {
+    #[serde(default)]
+    fields: Vec<FieldSchema>,
+}
+
+#[derive(Debug, Deserialize)]
+struct FieldSchema {
+    name: String,
+    #[serde(rename = "type")]
+    field_type: String,
+    #[serde(default)]
+    mode: Option<String>,
+    #[serde(default)]
+    fields: Vec<FieldSchema>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TableRow {
+    #[serde(default)]
+    f: Vec<Cell>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Cell {
+    #[serde(default)]
+    v: serde_json::Value,
+}
+
+/// BigQuery client
+pub(super) struct BigQueryClient {
+    http: reqwest::Client,
+    endpoint: String,
+    project: String,
+    dataset: String,
+    location: Option<String>,
+    client_email: String,
+    token_uri: String,
+    signer: JwtSigner,
+    token: Mutex<Option<CachedToken>>,
+    timeout: Duration,
+    poll_interval: Duration,
+}
+
+impl BigQueryClient {
+    pub(super) fn new(config: &SQLConfig, pool_size: u32, timeout_ms: u64) -> Result<Self> {
+        let credentials = match warehouse::read_secret(
+            config,
+            "credentials_json",
+            "credentials_path",
+        )? {
+            Some(credentials) => credentials,
+            None => {
+                let path = std::env::var("GOOGLE_APPLICATION_CREDENTIALS").map_err(|_| {
+                    RuntimeError::RuntimeError(
+                        "BigQuery requires credentials_path, credentials_json or GOOGLE_APPLICATION_CREDENTIALS"
+                            .to_string(),
+                    )
+                })?;
+                std::fs::read_to_string(&path).map_err(|e| {
+                    RuntimeError::RuntimeError(format!("Failed to read {}: {}", path, e))
+                })?
+            }
+        };
+        let key: ServiceAccountKey = serde_json::from_str(&credentials).map_err(|e| {
+            RuntimeError::RuntimeError(format!("Invalid BigQuery service-account key: {}", e))
+        })?;
+
+        let project = config
+            .connection_string
+            .trim_start_matches("bigquery://")
+            .trim_end_matches('/')
+            .to_string();
+        let project = if project.is_empty() {
+            key.project_id.clone().ok_or_else(|| {
+                RuntimeError::RuntimeError(
+                    "BigQuery connection_string must name the project".to_string(),
+                )
+            })?
+        } else {
+            project
+        };
+        let (timeout, poll_interval) = warehouse::polling(config, timeout_ms)?;
+
+        tracing::info!(
+            "✓ BigQuery client created (project: {}, dataset: {})",
+            project,
+            config.database
+        );
+
+        Ok(Self {
+            http: warehouse::http_client(pool_size)?,
+            endpoint: config
+                .options
+                .get("endpoint")
+                .map(|e| e.trim_end_matches('/').to_string())
+                .unwrap_or_else(|| DEFAULT_ENDPOINT.to_string()),
+            project,
+            dataset: config.database.clone(),
+            location: config.options.get("location").cloned(),
+            client_email: key.client_email,
+            token_uri: key.token_uri,
+            signer: JwtSigner::from_pem(&key.private_key)?,
+            token: Mutex::new(None),
+            timeout,
+            poll_interval,
+        })
+    }
+
+    /// OAuth access token for the service account
+    async fn access_token(&self) -> Result<String> {
+        let mut token = self.token.lock().await;
+        if let Some(value) = token.as_ref().and_then(CachedToken::fresh) {
+            return Ok(value.to_string());
+        }
+
+        let now = warehouse::unix_now();
+        let assertion = self.signer.sign(&serde_json::json!({
+            "iss": self.client_email,
+            "scope": SCOPE,
+            "aud": self.token_uri,
+            "iat": now,
+            "exp": now + 3600,
+        }));
+        let response = self
+            .http
+            .post(&self.token_uri)
+            .form(&[("grant_type", JWT_BEARER_GRANT), ("assertion", &assertion)])
+            .send()
+            .await
+            .map_err(|e| {
+                RuntimeError::RuntimeError(format!("BigQuery token request failed: {}", e))
+            })?;
+        let response: TokenResponse = warehouse::error_for_status("BigQuery token", response)
+            .await?
+            .json()
+            .await
+            .map_err(|e| {
+                RuntimeError::RuntimeError(format!("Invalid BigQuery token response: {}", e))
+            })?;
+
+        let value = response.access_token.clone();
+        *token = Some(CachedToken::new(
+            response.access_token,
+            Duration::from_secs(response.expires_in),
+        ));
+        Ok(value)
+    }
+
+    async fn send(&self, request: reqwest::RequestBuilder) -> Result<QueryResponse> {
+        let response = request
+            .bearer_auth(self.access_token().await?)
+            .send()
+            .await
+            .map_err(|e| RuntimeError::RuntimeError(format!("BigQuery request failed: {}", e)))?;
+        warehouse::error_for_status("BigQuery", response)
+            .await?
+            .json()
+            .await
+            .map_err(|e| RuntimeError::RuntimeError(format!("Invalid BigQuery response: {}", e)))
+    }
+
+    /// Poll a job for completion or fetch a page of its results
+    async fn get_results(
+        &self,
+        job: &JobReference,
+        page_token: Option<&str>,
+        deadline: Instant,
+    ) -> Result<QueryResponse> {
+        let mut params = vec![("timeoutMs", wait_ms(deadline).to_string())];
+        if let Some(location) = job.location.as_ref().or(self.location.as_ref()) {
+            params.push(("location", location.clone()));
+        }
+        if let Some(page_token) = page_token {
+            params.push(("pageToken", page_token.to_string()));
+        }
+        let url = format!(
+            "{}/bigquery/v2/projects/{}/queries/{}",
+            self.endpoint, self.project, job.job_id
+        );
+        self.send(self.http.get(url).query(&params)).await
+    }
+}
+
+#[async_trait::async_trait]
+impl Warehouse for BigQueryClient {
+    async fn query(&self, sql: &str) -> Result<Vec<HashMap<String, Value>>> {
+        let deadline = Instant::now() + self.timeout;
+
+        let mut body = serde_json::json!({
+            "query": sql,
+            "useLegacySql": false,
+            "timeoutMs": wait_ms(deadline),
+            "defaultDataset": {"projectId": self.project, "datasetId": self.dataset},
+        });
+        if let Some(location) = &self.location {
+            body["location"] = serde_json::Value::String(location.clone());
+        }
+        let url = format!(
+            "{}/bigquery/v2/projects/{}/queries",
+            self.endpoint, self.project
+        );
+        let mut page = self.send(self.http.post(url).json(&body)).await?;
+
+        let job = page.job_reference.take().ok_or_else(|| {
+            RuntimeError::RuntimeError("BigQuery response has no job reference".to_string())
+        })?;
+        while !page.job_complete {
+            if Instant::now() + self.poll_interval >= deadline {
+                return Err(RuntimeError::RuntimeError(format!(
+                    "BigQuery job {} did not finish within {}ms",
+                    job.job_id,
+                    self.timeout.as_millis()
+                )));
+            }
+            tracing::debug!("Waiting for BigQuery job {}", job.job_id);
+            tokio::time::sleep(self.poll_interval).await;
+            page = self.get_results(&job, None, deadline).await?;
+        }
+
+        let schema = page.schema.take().map(|s| s.fields).unwrap_or_default();
+        let mut rows: Vec<_> = page
+            .rows
+            .iter()
+            .map(|row| convert_row(&schema, row))
+            .collect();
+        while let Some(page_token) = page.page_token.take() {
+            page = self.get_results(&job, Some(&page_token), deadline).await?;
+            rows.extend(page.rows.iter().map(|row| convert_row(&schema, row)));
+        }
+
+        Ok(rows)
+    }
+}
+
+/// How long to ask BigQuery to wait before answering
+fn wait_ms(deadline: Instant) -> u64 {
+    deadline
+        .saturating_duration_since(Instant::now())
+        .min(MAX_WAIT)
+        .as_millis() as u64
+}
+
+fn convert_row(schema: &[FieldSchema], row: &TableRow) -> HashMap<String, Value> {
+    schema
+        .iter()
+        .zip(&row.f)
+        .map(|(field, cell)| (field.name.clone(), convert_cell(field, &cell.v, true)))
+        .collect()
+}
+
+/// Convert a cell; all scalars arrive as strings, records as `{"f": [...]}`
+/// and repeated fields as `[{"v": ...}]`
+fn convert_cell(field: &FieldSchema, value: &serde_json::Value, expand_repeated: bool) -> Value {
+    if value.is_null() {
+        return Value::Null;
+    }
+    if expand_repeated && field.mode.as_deref() == Some("REPEATED") {
+        return Value::Array(
+            value
+                .as_array()
+                .into_iter()
+                .flatten()
+                .map(|item| convert_cell(field, &item["v"], false))
+                .collect(),
+        );
+    }
+
+    match field.field_type.as_str() {
+        "RECORD" | "STRUCT" => Value::Object(
+            field
+                .fields
+                .iter()
+                .zip(value["f"].as_array().into_iter().flatten())
+                .map(|(field, cell)| (field.name.clone(), convert_cell(field, &cell["v"], true)))
+                .collect(),
+        ),
+        _ => {
+            let Some(text) = value.as_str() else {
+                return Value::Null;
+            };
+            match field.field_type.as_str() {
+                "INTEGER" | "INT64" | "FLOAT" | "FLOAT64" | "NUMERIC" | "BIGNUMERIC" => text
+                    .parse()
+                    .map(Value::Number)
+                    .unwrap_or_else(|_| Value::String(text.to_string())),
+                "BOOLEAN" | "BOOL" => Value::Bool(text == "true"),
+                "TIMESTAMP" => Value::String(
+                    warehouse::epoch_seconds_to_rfc3339(text).unwrap_or_else(|| text.to_string()),
+                ),
+                "JSON" => {
+                    serde_json::from_str(text).unwrap_or_else(|_| Value::String(text.to_string()))
+                }
+                _ => Value::String(text.to_string()),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::warehouse::tests::{serve, TEST_KEY};
+    use super::super::SQLProvider;
+    use super::*;
+
+    #[tokio::test]
+    async fn test_query_polls_and_pages() {
+        let schema = r#"{"fields": [
+            {"name": "user_id", "type": "STRING"},
+            {"name": "txn_count", "type": "INTEGER"},
+            {"name": "last_seen", "type": "TIMESTAMP"},
+            {"name": "tags", "type": "STRING", "mode": "REPEATED"},
+            {"name": "device", "type": "RECORD", "fields": [{"name": "trusted", "type": "BOOLEAN"}]}
+        ]}"#;
+        let (addr, server) = serve(vec![
+            (200, r#"{"access_token": "ya29.token", "expires_in": 3599}"#.to_string()),
+            (200, r#"{"jobComplete": false, "jobReference": {"jobId": "job_1", "location": "EU"}}"#.to_string()),
+            (200, format!(
+                r#"{{"jobComplete": true, "schema": {}, "pageToken": "p2", "rows": [
+                    {{"f": [{{"v": "007"}}, {{"v": "12"}}, {{"v": "1.7041536E9"}},
+                            {{"v": [{{"v": "vip"}}]}}, {{"v": {{"f": [{{"v": "true"}}]}}}}]}}
+                ]}}"#,
+                schema
+            )),
+            (200, format!(
+                r#"{{"jobComplete": true, "schema": {}, "rows": [
+                    {{"f": [{{"v": "008"}}, {{"v": "0"}}, {{"v": null}}, {{"v": []}}, {{"v": null}}]}}
+                ]}}"#,
+                schema
+            )),
+        ])
+        .await;
+
+        let credentials = serde_json::json!({
+            "client_email": "corint@acme.iam.gserviceaccount.com",
+            "private_key": TEST_KEY,
+            "token_uri": format!("{}/token", addr),
+        });
+        let config = SQLConfig {
+            provider: SQLProvider::BigQuery,
+            connection_string: "bigquery://acme-risk".to_string(),
+            database: "events".to_string(),
+            events_table: "events".to_string(),
+            options: HashMap::from([
+                ("credentials_json".to_string(), credentials.to_string()),
+                ("endpoint".to_string(), addr.clone()),
+                ("poll_interval_ms".to_string(), "1".to_string()),
+            ]),
+        };
+        let client = BigQueryClient::new(&config, 1, 5000).unwrap();
+
+        let rows = client.query("SELECT 1").await.unwrap();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0]["user_id"], Value::String("007".to_string()));
+        assert_eq!(rows[0]["txn_count"], Value::Number(12.0));
+        assert_eq!(
+            rows[0]["last_seen"],
+            Value::String("2024-01-02T00:00:00+00:00".to_string())
+        );
+        assert_eq!(
+            rows[0]["tags"],
+            Value::Array(vec![Value::String("vip".to_string())])
+        );
+        assert_eq!(
+            rows[0]["device"],
+            Value::Object(HashMap::from([("trusted".to_string(), Value::Bool(true))]))
+        );
+        assert_eq!(rows[1]["last_seen"], Value::Null);
+        assert_eq!(rows[1]["tags"], Value::Array(vec![]));
+
+        let requests = server.await.unwrap();
+        assert!(requests[0].starts_with("POST /token"));
+        assert!(requests[0]
+            .contains("grant_type=urn%3Aietf%3Aparams%3Aoauth%3Agrant-type%3Ajwt-bearer"));
+        assert!(requests[1].starts_with("POST /bigquery/v2/projects/acme-risk/queries "));
+        assert!(requests[1].contains("authorization: Bearer ya29.token"));
+        assert!(requests[1].contains(r#""datasetId":"events""#));
+        assert!(requests[2].starts_with("GET /bigquery/v2/projects/acme-risk/queries/job_1?"));
+        assert!(requests[2].contains("location=EU"));
+        assert!(requests[3].contains("pageToken=p2"));
+    }
+}
@@ -1,10 +1,20 @@
 //! SQL Database Client Implementation
 //!
-//! Provides SQL database connectivity for PostgreSQL, SQLite, and MySQL.
+//! Provides SQL database connectivity for PostgreSQL, SQLite, and MySQL, and
+//! for the BigQuery and Snowflake warehouses (`bigquery` and `snowflake`
+//! features), which are queried over their REST APIs.
+
+#[cfg(feature = "bigquery")]
+mod bigquery;
+#[cfg(feature = "snowflake")]
+mod snowflake;
+#[cfg(any(feature = "bigquery", feature = "snowflake"))]
+mod warehouse;
 
 use super::config::{SQLConfig, SQLProvider};
 use super::query::{
-    Aggregation, AggregationType, Filter, FilterOperator, Query, QueryResult, TimeWindowType,
+    Aggregation, AggregationType, Filter, FilterOperator, Query, QueryResult, TimeWindow,
+    TimeWindowType,
 };
 use crate::error::{Result, RuntimeError};
 use corint_core::Value;
@@ -19,9 +29,17 @@ use sqlx::{Column, Row};
 // Import the DataSourceImpl trait from client module
 use super::client::DataSourceImpl;
 
+/// Cloud warehouse queried over HTTP
+#[async_trait::async_trait]
+trait Warehouse: Send + Sync {
+    /// Run a statement to completion and return all result rows
+    async fn query(&self, sql: &str) -> Result<Vec<HashMap<String, Value>>>;
+}
+
 /// SQL Database Client
 pub(super) struct SQLClient {
     config: SQLConfig,
+    warehouse: Option<Box<dyn Warehouse>>,
     #[cfg(feature = "sqlx")]
     pg_pool: Option<sqlx::PgPool>,
     #[cfg(feature = "sqlx")]
@@ -30,9 +48,11 @@ pub(super) struct SQLClient {
 
 impl SQLClient {
     #[cfg_attr(not(feature = "sqlx"), allow(unused_variables))]
-    pub(super) async fn new(config: SQLConfig, pool_size: u32) -> Result<Self> {
+    pub(super) async fn new(config: SQLConfig, pool_size: u32, timeout_ms: u64) -> Result<Self> {
         tracing::info!("Initializing SQL client: {:?}", config.provider);
 
+        let warehouse = connect_warehouse(&config, pool_size, timeout_ms)?;
+
         #[cfg(feature = "sqlx")]
         {
             let mut pg_pool = None;
@@ -112,10 +132,12 @@ impl SQLClient {
                     // MySQL not yet implemented
                     tracing::warn!("MySQL provider not yet implemented");
                 }
+                SQLProvider::BigQuery | SQLProvider::Snowflake => {}
             }
 
             Ok(Self {
                 config,
+                warehouse,
                 pg_pool,
                 sqlite_pool,
             })
@@ -123,11 +145,35 @@ impl SQLClient {
 
         #[cfg(not(feature = "sqlx"))]
         {
-            Ok(Self { config })
+            Ok(Self { config, warehouse })
         }
     }
 }
 
+/// Client for a warehouse provider, if the provider is one and its feature
+/// is enabled
+#[cfg_attr(
+    not(any(feature = "bigquery", feature = "snowflake")),
+    allow(unused_variables)
+)]
+fn connect_warehouse(
+    config: &SQLConfig,
+    pool_size: u32,
+    timeout_ms: u64,
+) -> Result<Option<Box<dyn Warehouse>>> {
+    Ok(match config.provider {
+        #[cfg(feature = "bigquery")]
+        SQLProvider::BigQuery => Some(Box::new(bigquery::BigQueryClient::new(
+            config, pool_size, timeout_ms,
+        )?)),
+        #[cfg(feature = "snowflake")]
+        SQLProvider::Snowflake => Some(Box::new(snowflake::SnowflakeClient::new(
+            config, pool_size, timeout_ms,
+        )?)),
+        _ => None,
+    })
+}
+
 #[async_trait::async_trait]
 impl DataSourceImpl for SQLClient {
     async fn execute(&self, query: Query) -> Result<QueryResult> {
@@ -144,6 +190,8 @@ impl DataSourceImpl for SQLClient {
             SQLProvider::MySQL => Err(RuntimeError::RuntimeError(
                 "MySQL not yet implemented".to_string(),
             )),
+            SQLProvider::BigQuery => self.execute_warehouse(&sql, "bigquery").await,
+            SQLProvider::Snowflake => self.execute_warehouse(&sql, "snowflake").await,
         }
     }
 }
@@ -153,80 +201,36 @@ impl SQLClient {
     fn build_sql(&self, query: &Query) -> Result<String> {
         let mut sql = String::new();
 
-        // Build SELECT clause
+        // Build SELECT clause; grouped queries also return the group keys
         sql.push_str("SELECT ");
         if query.aggregations.is_empty() {
             sql.push('*');
         } else {
-            let agg_clauses: Vec<String> = query
-                .aggregations
+            let columns: Vec<String> = query
+                .group_by
                 .iter()
-                .map(|agg| self.build_aggregation(agg))
-                .collect();
-            sql.push_str(&agg_clauses.join(", "));
+                .map(|field| self.group_column(field))
+                .map(Ok)
+                .chain(query.aggregations.iter().map(|agg| self.build_aggregation(agg)))
+                .collect::<Result<_>>()?;
+            sql.push_str(&columns.join(", "));
         }
 
         // FROM clause
         sql.push_str(&format!(" FROM {}", query.entity));
 
-        // WHERE clause
-        if !query.filters.is_empty() {
-            sql.push_str(" WHERE ");
-            let filter_clauses: Vec<String> = query
-                .filters
-                .iter()
-                .map(|f| self.build_filter(f))
-                .collect::<Result<Vec<_>>>()?;
-            sql.push_str(&filter_clauses.join(" AND "));
-        }
-
-        // Time window
+        // WHERE clause: filters and time window
+        let mut conditions = query
+            .filters
+            .iter()
+            .map(|f| self.build_filter(f))
+            .collect::<Result<Vec<_>>>()?;
         if let Some(ref time_window) = query.time_window {
-            if !query.filters.is_empty() {
-                sql.push_str(" AND ");
-            } else {
-                sql.push_str(" WHERE ");
-            }
-
-            match &time_window.window_type {
-                TimeWindowType::Relative(rel) => {
-                    let seconds = rel.to_seconds();
-                    match self.config.provider {
-                        SQLProvider::SQLite => {
-                            // SQLite uses datetime() function
-                            sql.push_str(&format!(
-                                "{} >= datetime('now', '-{} seconds')",
-                                time_window.time_field, seconds
-                            ));
-                        }
-                        _ => {
-                            // PostgreSQL and others use INTERVAL
-                            sql.push_str(&format!(
-                                "{} >= NOW() - INTERVAL '{} seconds'",
-                                time_window.time_field, seconds
-                            ));
-                        }
-                    }
-                }
-                TimeWindowType::Absolute { start, end } => {
-                    match self.config.provider {
-                        SQLProvider::SQLite => {
-                            // SQLite uses datetime() function with unix timestamp
-                            sql.push_str(&format!(
-                                "{} >= datetime({}, 'unixepoch') AND {} < datetime({}, 'unixepoch')",
-                                time_window.time_field, start, time_window.time_field, end
-                            ));
-                        }
-                        _ => {
-                            // PostgreSQL uses TO_TIMESTAMP
-                            sql.push_str(&format!(
-                                "{} >= TO_TIMESTAMP({}) AND {} < TO_TIMESTAMP({})",
-                                time_window.time_field, start, time_window.time_field, end
-                            ));
-                        }
-                    }
-                }
-            }
+            conditions.push(self.build_time_window(time_window, &query.entity, &conditions));
+        }
+        if !conditions.is_empty() {
+            sql.push_str(" WHERE ");
+            sql.push_str(&conditions.join(" AND "));
         }
 
         // GROUP BY clause
@@ -234,6 +238,35 @@ impl SQLClient {
             sql.push_str(&format!(" GROUP BY {}", query.group_by.join(", ")));
         }
 
+        // HAVING clause; aggregation outputs are repeated as expressions since
+        // PostgreSQL can't refer to select aliases there
+        if !query.having.is_empty() {
+            let conditions = query
+                .having
+                .iter()
+                .map(|f| {
+                    self.build_filter(&Filter {
+                        field: self.output_expr(query, &f.field)?,
+                        ..f.clone()
+                    })
+                })
+                .collect::<Result<Vec<_>>>()?;
+            sql.push_str(&format!(" HAVING {}", conditions.join(" AND ")));
+        }
+
+        // ORDER BY clause
+        if !query.order_by.is_empty() {
+            let keys: Vec<String> = query
+                .order_by
+                .iter()
+                .map(|o| {
+                    let direction = if o.descending { "DESC" } else { "ASC" };
+                    Ok(format!("{} {}", self.output_expr(query, &o.field)?, direction))
+                })
+                .collect::<Result<_>>()?;
+            sql.push_str(&format!(" ORDER BY {}", keys.join(", ")));
+        }
+
         // LIMIT clause
         if let Some(limit) = query.limit {
             sql.push_str(&format!(" LIMIT {}", limit));
@@ -242,8 +275,35 @@ impl SQLClient {
         Ok(sql)
     }
 
+    /// Select a group by field under its own name
+    fn group_column(&self, field: &str) -> String {
+        match self.config.provider {
+            // Snowflake upper-cases unquoted column names
+            SQLProvider::Snowflake => format!("{} AS \"{}\"", field, field),
+            _ => field.to_string(),
+        }
+    }
+
+    /// Expression for an aggregation output name, or the field itself
+    fn output_expr(&self, query: &Query, field: &str) -> Result<String> {
+        match query.aggregations.iter().find(|agg| agg.output_name == field) {
+            Some(agg) => self.aggregation_expr(agg),
+            None => Ok(field.to_string()),
+        }
+    }
+
     /// Build aggregation clause
-    fn build_aggregation(&self, agg: &Aggregation) -> String {
+    fn build_aggregation(&self, agg: &Aggregation) -> Result<String> {
+        let expr = self.aggregation_expr(agg)?;
+        Ok(match self.config.provider {
+            // Snowflake upper-cases unquoted aliases
+            SQLProvider::Snowflake => format!("{} AS \"{}\"", expr, agg.output_name),
+            _ => format!("{} AS {}", expr, agg.output_name),
+        })
+    }
+
+    /// Aggregation expression without its alias
+    fn aggregation_expr(&self, agg: &Aggregation) -> Result<String> {
         let field = agg.field.as_deref().unwrap_or("*");
 
         // For PostgreSQL/SQLite, if field contains JSON access, wrap it with type cast for numeric aggregations
@@ -253,8 +313,10 @@ impl SQLClient {
                 | AggregationType::Avg
                 | AggregationType::Min
                 | AggregationType::Max
+                | AggregationType::Median
                 | AggregationType::Stddev
                 | AggregationType::Percentile { .. }
+                | AggregationType::DecayedSum { .. }
         ) && (field.contains("->>")
             || field == "amount"
             || field.starts_with("attributes"));
@@ -348,6 +410,9 @@ impl SQLClient {
                         format!("CAST({} AS REAL)", field)
                     }
                 }
+                SQLProvider::BigQuery | SQLProvider::Snowflake => {
+                    self.warehouse_field_expr(field, true)
+                }
                 _ => field.to_string(),
             }
         } else {
@@ -406,11 +471,14 @@ impl SQLClient {
                         field.to_string()
                     }
                 }
+                SQLProvider::BigQuery | SQLProvider::Snowflake => {
+                    self.warehouse_field_expr(field, false)
+                }
                 _ => field.to_string(),
             }
         };
 
-        let expr = match agg.agg_type {
+        let expr = match &agg.agg_type {
             AggregationType::Count => format!("COUNT({})", field_expr),
             AggregationType::CountDistinct => {
                 format!("COUNT(DISTINCT {})", field_expr)
@@ -421,6 +489,10 @@ impl SQLClient {
             AggregationType::Max => format!("MAX({})", field_expr),
             AggregationType::Median => {
                 match self.config.provider {
+                    // BigQuery only has PERCENTILE_CONT as a window function
+                    SQLProvider::BigQuery => {
+                        format!("APPROX_QUANTILES({}, 2)[OFFSET(1)]", field_expr)
+                    }
                     SQLProvider::SQLite => {
                         // SQLite doesn't have PERCENTILE_CONT, use a workaround
                         format!(
@@ -437,14 +509,21 @@ impl SQLClient {
                 }
             }
             AggregationType::Stddev => match self.config.provider {
-                SQLProvider::SQLite => format!("STDEV({})", field_expr),
+                // SQLite has no STDDEV aggregate; expand E[x²] - E[x]²
+                SQLProvider::SQLite => format!(
+                    "SQRT(MAX(AVG({0} * {0}) - AVG({0}) * AVG({0}), 0))",
+                    field_expr
+                ),
                 _ => format!("STDDEV_POP({})", field_expr),
             },
             AggregationType::Percentile { p } => {
                 match self.config.provider {
+                    SQLProvider::BigQuery => {
+                        format!("APPROX_QUANTILES({}, 100)[OFFSET({})]", field_expr, p)
+                    }
                     SQLProvider::SQLite => {
                         // SQLite doesn't have PERCENTILE_CONT, use a workaround
-                        let percentile = p as f64 / 100.0;
+                        let percentile = *p as f64 / 100.0;
                         format!(
                             "(SELECT {} FROM (SELECT {} FROM (SELECT {} ORDER BY {}) LIMIT 1 OFFSET (SELECT CAST(COUNT(*) * {} AS INTEGER) FROM (SELECT {})))",
                             field_expr, field_expr, field_expr, field_expr, percentile, field_expr
@@ -453,15 +532,173 @@ impl SQLClient {
                     _ => {
                         format!(
                             "PERCENTILE_CONT({}) WITHIN GROUP (ORDER BY {})",
-                            p as f64 / 100.0,
+                            *p as f64 / 100.0,
                             field_expr
                         )
                     }
                 }
             }
+            AggregationType::Mode => match self.config.provider {
+                SQLProvider::PostgreSQL => {
+                    format!("MODE() WITHIN GROUP (ORDER BY {})", field_expr)
+                }
+                SQLProvider::BigQuery => {
+                    format!("APPROX_TOP_COUNT({}, 1)[OFFSET(0)].value", field_expr)
+                }
+                SQLProvider::Snowflake => format!("MODE({})", field_expr),
+                _ => {
+                    return Err(RuntimeError::RuntimeError(format!(
+                        "{:?} has no mode aggregate; group by {} and order by count instead",
+                        self.config.provider, field
+                    )))
+                }
+            },
+            AggregationType::DecayedSum { half_life_secs, time_field } => format!(
+                "COALESCE(SUM({} * {}), 0)",
+                field_expr,
+                self.decay_weight(time_field, *half_life_secs)
+            ),
+            AggregationType::DecayedCount { half_life_secs, time_field } => format!(
+                "COALESCE(SUM({}), 0)",
+                self.decay_weight(time_field, *half_life_secs)
+            ),
+        };
+
+        Ok(expr)
+    }
+
+    /// Weight `0.5^(age / half_life)` of a row by the age of its timestamp
+    fn decay_weight(&self, time_field: &str, half_life_secs: u64) -> String {
+        let age = match self.config.provider {
+            SQLProvider::SQLite => {
+                format!("(julianday('now') - julianday({})) * 86400", time_field)
+            }
+            SQLProvider::BigQuery => {
+                format!("TIMESTAMP_DIFF(CURRENT_TIMESTAMP(), {}, SECOND)", time_field)
+            }
+            SQLProvider::Snowflake => {
+                format!("DATEDIFF(second, {}, CURRENT_TIMESTAMP())", time_field)
+            }
+            _ => format!("EXTRACT(EPOCH FROM (NOW() - {}))", time_field),
         };
+        format!(
+            "EXP(-{} * {} / {})",
+            std::f64::consts::LN_2,
+            age,
+            half_life_secs.max(1)
+        )
+    }
 
-        format!("{} AS {}", expr, agg.output_name)
+    /// Field expression for BigQuery and Snowflake
+    ///
+    /// Dot notation reads from a JSON column: `attributes.device.os` becomes
+    /// `JSON_VALUE(attributes, '$.device.os')` (BigQuery) or
+    /// `attributes:device.os` (Snowflake VARIANT). With `numeric` set the value
+    /// is cast to a float.
+    fn warehouse_field_expr(&self, field: &str, numeric: bool) -> String {
+        let bigquery = matches!(self.config.provider, SQLProvider::BigQuery);
+        match field.split_once('.') {
+            Some((column, path)) if !field.starts_with("${") => match (bigquery, numeric) {
+                (true, true) => format!("CAST(JSON_VALUE({}, '$.{}') AS FLOAT64)", column, path),
+                (true, false) => format!("JSON_VALUE({}, '$.{}')", column, path),
+                (false, true) => format!("{}:{}::FLOAT", column, path),
+                (false, false) => format!("{}:{}::STRING", column, path),
+            },
+            _ if numeric && field != "*" => {
+                format!("CAST({} AS {})", field, if bigquery { "FLOAT64" } else { "FLOAT" })
+            }
+            _ => field.to_string(),
+        }
+    }
+
+    /// Build the time window condition; session windows look at the events
+    /// matching `filters`
+    fn build_time_window(
+        &self,
+        time_window: &TimeWindow,
+        entity: &str,
+        filters: &[String],
+    ) -> String {
+        let field = &time_window.time_field;
+        match &time_window.window_type {
+            TimeWindowType::Relative(rel) => {
+                let seconds = rel.to_seconds();
+                match self.config.provider {
+                    // SQLite uses datetime() function
+                    SQLProvider::SQLite => {
+                        format!("{} >= datetime('now', '-{} seconds')", field, seconds)
+                    }
+                    SQLProvider::BigQuery => format!(
+                        "{} >= TIMESTAMP_SUB(CURRENT_TIMESTAMP(), INTERVAL {} SECOND)",
+                        field, seconds
+                    ),
+                    SQLProvider::Snowflake => format!(
+                        "{} >= DATEADD(second, -{}, CURRENT_TIMESTAMP())",
+                        field, seconds
+                    ),
+                    // PostgreSQL and others use INTERVAL
+                    _ => format!("{} >= NOW() - INTERVAL '{} seconds'", field, seconds),
+                }
+            }
+            TimeWindowType::Absolute { start, end } => self.absolute_window(field, *start, *end),
+            TimeWindowType::Tumbling(tumbling) => {
+                let (start, end) = tumbling.bounds(chrono::Utc::now().timestamp());
+                self.absolute_window(field, start, end)
+            }
+            TimeWindowType::Session(session) => {
+                let gap = session.session_gap.to_seconds();
+                let (now, gap_exceeded) = match self.config.provider {
+                    SQLProvider::SQLite => (
+                        "datetime('now')".to_string(),
+                        format!("(julianday(s.ts) - julianday(s.prev)) * 86400 > {}", gap),
+                    ),
+                    SQLProvider::BigQuery => (
+                        "CURRENT_TIMESTAMP()".to_string(),
+                        format!("TIMESTAMP_DIFF(s.ts, s.prev, SECOND) > {}", gap),
+                    ),
+                    SQLProvider::Snowflake => (
+                        "CURRENT_TIMESTAMP()".to_string(),
+                        format!("DATEDIFF(second, s.prev, s.ts) > {}", gap),
+                    ),
+                    _ => (
+                        "NOW()".to_string(),
+                        format!("s.ts - s.prev > INTERVAL '{} seconds'", gap),
+                    ),
+                };
+                let filters = if filters.is_empty() {
+                    String::new()
+                } else {
+                    format!(" WHERE {}", filters.join(" AND "))
+                };
+                // The session starts at the latest event following a gap; now
+                // is included so a session that timed out starts anew
+                format!(
+                    "{field} >= (SELECT MAX(s.ts) FROM (SELECT e.ts, LAG(e.ts) OVER (ORDER BY e.ts) AS prev \
+                     FROM (SELECT {field} AS ts FROM {entity}{filters} UNION ALL SELECT {now}) e) s \
+                     WHERE s.prev IS NULL OR {gap_exceeded})"
+                )
+            }
+        }
+    }
+
+    /// Condition for `start <= field < end` (seconds since epoch)
+    fn absolute_window(&self, field: &str, start: i64, end: i64) -> String {
+        match self.config.provider {
+            // SQLite uses datetime() function with unix timestamp
+            SQLProvider::SQLite => format!(
+                "{} >= datetime({}, 'unixepoch') AND {} < datetime({}, 'unixepoch')",
+                field, start, field, end
+            ),
+            SQLProvider::BigQuery => format!(
+                "{} >= TIMESTAMP_SECONDS({}) AND {} < TIMESTAMP_SECONDS({})",
+                field, start, field, end
+            ),
+            // PostgreSQL and Snowflake use TO_TIMESTAMP
+            _ => format!(
+                "{} >= TO_TIMESTAMP({}) AND {} < TO_TIMESTAMP({})",
+                field, start, field, end
+            ),
+        }
     }
 
     /// Build filter clause
@@ -504,9 +741,14 @@ impl SQLClient {
             FilterOperator::Like => {
                 format!("{} LIKE {}", filter.field, value_str)
             }
-            FilterOperator::Regex => {
-                format!("{} ~ {}", filter.field, value_str) // PostgreSQL regex operator
-            }
+            FilterOperator::Regex => match self.config.provider {
+                SQLProvider::BigQuery => format!("REGEXP_CONTAINS({}, {})", filter.field, value_str),
+                // REGEXP_LIKE would have to match the whole value
+                SQLProvider::Snowflake => {
+                    format!("REGEXP_INSTR({}, {}) > 0", filter.field, value_str)
+                }
+                _ => format!("{} ~ {}", filter.field, value_str), // PostgreSQL regex operator
+            },
         };
 
         Ok(expr)
@@ -518,7 +760,14 @@ impl SQLClient {
             Value::Null => Ok("NULL".to_string()),
             Value::Bool(b) => Ok(b.to_string().to_uppercase()),
             Value::Number(n) => Ok(n.to_string()),
-            Value::String(s) => Ok(format!("'{}'", s.replace('\'', "''"))), // SQL escape
+            Value::String(s) => match self.config.provider {
+                // Backslash is an escape character in BigQuery and Snowflake strings
+                SQLProvider::BigQuery | SQLProvider::Snowflake => Ok(format!(
+                    "'{}'",
+                    s.replace('\\', "\\\\").replace('\'', "\\'")
+                )),
+                _ => Ok(format!("'{}'", s.replace('\'', "''"))), // SQL escape
+            },
             Value::Array(_) => Err(RuntimeError::RuntimeError(
                 "Arrays should be handled by IN/NOT IN operator".to_string(),
             )),
@@ -528,6 +777,28 @@ impl SQLClient {
         }
     }
 
+    /// Execute query on BigQuery or Snowflake
+    async fn execute_warehouse(&self, sql: &str, feature: &str) -> Result<QueryResult> {
+        tracing::info!("Executing {:?} query: {}", self.config.provider, sql);
+
+        let warehouse = self.warehouse.as_ref().ok_or_else(|| {
+            RuntimeError::RuntimeError(format!(
+                "{:?} queries require '{}' feature to be enabled",
+                self.config.provider, feature
+            ))
+        })?;
+
+        let start = Instant::now();
+        let rows = warehouse.query(sql).await?;
+
+        Ok(QueryResult {
+            rows,
+            execution_time_ms: start.elapsed().as_millis() as u64,
+            source: self.config.database.clone(),
+            from_cache: false,
+        })
+    }
+
     /// Execute query on PostgreSQL
     async fn execute_postgresql(&self, sql: &str) -> Result<QueryResult> {
         tracing::info!("Executing PostgreSQL query: {}", sql);
@@ -806,3 +1077,244 @@ impl SQLClient {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::datasource::query::{OrderBy, QueryType, RelativeWindow, TimeUnit, TimeWindow};
+
+    fn client(provider: SQLProvider) -> SQLClient {
+        SQLClient {
+            config: SQLConfig {
+                provider,
+                connection_string: String::new(),
+                database: "risk".to_string(),
+                events_table: "events".to_string(),
+                options: HashMap::new(),
+            },
+            warehouse: None,
+            #[cfg(feature = "sqlx")]
+            pg_pool: None,
+            #[cfg(feature = "sqlx")]
+            sqlite_pool: None,
+        }
+    }
+
+    fn query() -> Query {
+        Query {
+            query_type: QueryType::Aggregate,
+            entity: "events".to_string(),
+            filters: vec![
+                Filter {
+                    field: "user_id".to_string(),
+                    operator: FilterOperator::Eq,
+                    value: Value::String(r"o'brien\".to_string()),
+                },
+                Filter {
+                    field: "email".to_string(),
+                    operator: FilterOperator::Regex,
+                    value: Value::String("@example".to_string()),
+                },
+            ],
+            time_window: Some(TimeWindow {
+                window_type: TimeWindowType::Relative(RelativeWindow {
+                    value: 1,
+                    unit: TimeUnit::Hours,
+                }),
+                time_field: "event_timestamp".to_string(),
+            }),
+            aggregations: vec![
+                Aggregation {
+                    agg_type: AggregationType::Sum,
+                    field: Some("attributes.amount".to_string()),
+                    output_name: "total".to_string(),
+                },
+                Aggregation {
+                    agg_type: AggregationType::Percentile { p: 95 },
+                    field: Some("amount".to_string()),
+                    output_name: "p95".to_string(),
+                },
+            ],
+            group_by: vec![],
+            having: Vec::new(),
+            order_by: Vec::new(),
+            limit: None,
+            cache_ttl: None,
+        }
+    }
+
+    #[test]
+    fn test_build_bigquery_sql() {
+        let sql = client(SQLProvider::BigQuery).build_sql(&query()).unwrap();
+        assert_eq!(
+            sql,
+            "SELECT SUM(CAST(JSON_VALUE(attributes, '$.amount') AS FLOAT64)) AS total, \
+             APPROX_QUANTILES(CAST(amount AS FLOAT64), 100)[OFFSET(95)] AS p95 \
+             FROM events WHERE user_id = 'o\\'brien\\\\' AND REGEXP_CONTAINS(email, '@example') \
+             AND event_timestamp >= TIMESTAMP_SUB(CURRENT_TIMESTAMP(), INTERVAL 3600 SECOND)"
+        );
+    }
+
+    #[test]
+    fn test_build_snowflake_sql() {
+        let sql = client(SQLProvider::Snowflake).build_sql(&query()).unwrap();
+        assert_eq!(
+            sql,
+            "SELECT SUM(attributes:amount::FLOAT) AS \"total\", \
+             PERCENTILE_CONT(0.95) WITHIN GROUP (ORDER BY CAST(amount AS FLOAT)) AS \"p95\" \
+             FROM events WHERE user_id = 'o\\'brien\\\\' AND REGEXP_INSTR(email, '@example') > 0 \
+             AND event_timestamp >= DATEADD(second, -3600, CURRENT_TIMESTAMP())"
+        );
+    }
+
+    #[test]
+    fn test_build_top_n_sql() {
+        let mut query = query();
+        query.filters.truncate(1);
+        query.time_window = None;
+        query.aggregations.truncate(1);
+        query.group_by = vec!["device_id".to_string()];
+        query.having = vec![Filter {
+            field: "total".to_string(),
+            operator: FilterOperator::Gt,
+            value: Value::Number(100.0),
+        }];
+        query.order_by = vec![OrderBy::desc("total"), OrderBy::asc("device_id")];
+        query.limit = Some(3);
+
+        assert_eq!(
+            client(SQLProvider::PostgreSQL).build_sql(&query).unwrap(),
+            "SELECT device_id, SUM((attributes->>'amount')::numeric) AS total FROM events \
+             WHERE user_id = 'o''brien\\' GROUP BY device_id \
+             HAVING SUM((attributes->>'amount')::numeric) > 100 \
+             ORDER BY SUM((attributes->>'amount')::numeric) DESC, device_id ASC LIMIT 3"
+        );
+        assert_eq!(
+            client(SQLProvider::Snowflake).build_sql(&query).unwrap(),
+            "SELECT device_id AS \"device_id\", SUM(attributes:amount::FLOAT) AS \"total\" \
+             FROM events WHERE user_id = 'o\\'brien\\\\' GROUP BY device_id \
+             HAVING SUM(attributes:amount::FLOAT) > 100 \
+             ORDER BY SUM(attributes:amount::FLOAT) DESC, device_id ASC LIMIT 3"
+        );
+    }
+
+    #[test]
+    fn test_build_session_window_sql() {
+        let mut query = query();
+        query.filters.truncate(1);
+        query.aggregations = vec![Aggregation {
+            agg_type: AggregationType::Count,
+            field: None,
+            output_name: "count".to_string(),
+        }];
+        query.time_window = Some(TimeWindow {
+            window_type: TimeWindowType::parse("session_30m").unwrap(),
+            time_field: "created_at".to_string(),
+        });
+
+        assert_eq!(
+            client(SQLProvider::PostgreSQL).build_sql(&query).unwrap(),
+            "SELECT COUNT(*) AS count FROM events WHERE user_id = 'o''brien\\' \
+             AND created_at >= (SELECT MAX(s.ts) FROM (SELECT e.ts, LAG(e.ts) OVER (ORDER BY e.ts) AS prev \
+             FROM (SELECT created_at AS ts FROM events WHERE user_id = 'o''brien\\' UNION ALL SELECT NOW()) e) s \
+             WHERE s.prev IS NULL OR s.ts - s.prev > INTERVAL '1800 seconds')"
+        );
+        assert!(client(SQLProvider::BigQuery)
+            .build_sql(&query)
+            .unwrap()
+            .contains("UNION ALL SELECT CURRENT_TIMESTAMP()) e) s WHERE s.prev IS NULL OR TIMESTAMP_DIFF(s.ts, s.prev, SECOND) > 1800)"));
+
+        query.time_window = Some(TimeWindow {
+            window_type: TimeWindowType::parse("today").unwrap(),
+            time_field: "created_at".to_string(),
+        });
+        assert!(client(SQLProvider::SQLite)
+            .build_sql(&query)
+            .unwrap()
+            .contains("created_at >= datetime("));
+    }
+
+    #[test]
+    fn test_build_distribution_sql() {
+        let mut query = query();
+        query.filters.truncate(1);
+        query.time_window = None;
+        query.aggregations = vec![
+            Aggregation {
+                agg_type: AggregationType::Median,
+                field: Some("attributes.amount".to_string()),
+                output_name: "median".to_string(),
+            },
+            Aggregation {
+                agg_type: AggregationType::Mode,
+                field: Some("device_id".to_string()),
+                output_name: "mode".to_string(),
+            },
+        ];
+
+        assert_eq!(
+            client(SQLProvider::PostgreSQL).build_sql(&query).unwrap(),
+            "SELECT PERCENTILE_CONT(0.5) WITHIN GROUP (ORDER BY (attributes->>'amount')::numeric) AS median, \
+             MODE() WITHIN GROUP (ORDER BY device_id) AS mode FROM events WHERE user_id = 'o''brien\\'"
+        );
+        assert!(client(SQLProvider::BigQuery)
+            .build_sql(&query)
+            .unwrap()
+            .contains("APPROX_TOP_COUNT(device_id, 1)[OFFSET(0)].value AS mode"));
+        assert!(client(SQLProvider::SQLite).build_sql(&query).is_err());
+
+        query.aggregations = vec![Aggregation {
+            agg_type: AggregationType::Stddev,
+            field: Some("amount".to_string()),
+            output_name: "stddev".to_string(),
+        }];
+        assert!(client(SQLProvider::SQLite).build_sql(&query).unwrap().starts_with(
+            "SELECT SQRT(MAX(AVG(CAST(amount AS REAL) * CAST(amount AS REAL)) - \
+             AVG(CAST(amount AS REAL)) * AVG(CAST(amount AS REAL)), 0)) AS stddev"
+        ));
+    }
+
+    #[test]
+    fn test_build_decayed_sql() {
+        let mut query = query();
+        query.filters.truncate(1);
+        query.time_window = None;
+        query.aggregations = vec![
+            Aggregation {
+                agg_type: AggregationType::DecayedSum {
+                    half_life_secs: 3600,
+                    time_field: "created_at".to_string(),
+                },
+                field: Some("amount".to_string()),
+                output_name: "decayed_sum".to_string(),
+            },
+            Aggregation {
+                agg_type: AggregationType::DecayedCount {
+                    half_life_secs: 3600,
+                    time_field: "created_at".to_string(),
+                },
+                field: None,
+                output_name: "decayed_count".to_string(),
+            },
+        ];
+
+        assert_eq!(
+            client(SQLProvider::PostgreSQL).build_sql(&query).unwrap(),
+            "SELECT COALESCE(SUM(CAST(amount AS numeric) * \
+             EXP(-0.6931471805599453 * EXTRACT(EPOCH FROM (NOW() - created_at)) / 3600)), 0) AS decayed_sum, \
+             COALESCE(SUM(EXP(-0.6931471805599453 * EXTRACT(EPOCH FROM (NOW() - created_at)) / 3600)), 0) \
+             AS decayed_count FROM events WHERE user_id = 'o''brien\\'"
+        );
+        assert!(client(SQLProvider::SQLite)
+            .build_sql(&query)
+            .unwrap()
+            .contains("EXP(-0.6931471805599453 * (julianday('now') - julianday(created_at)) * 86400 / 3600)"));
+    }
+
+    #[tokio::test]
+    async fn test_warehouse_requires_feature() {
+        let client = client(SQLProvider::Snowflake);
+        let err = client.execute(query()).await.unwrap_err();
+        assert!(err.to_string().contains("'snowflake' feature"));
+    }
+}
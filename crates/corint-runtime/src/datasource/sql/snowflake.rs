@@ -0,0 +1,389 @@
+//! Snowflake client over the SQL API
+//!
+//! Statements are submitted to `/api/v2/statements` with key-pair
+//! authentication. When a statement doesn't finish within the request,
+//! Snowflake answers `202 Accepted` with a statement handle, which is polled
+//! until the statement completes or the timeout elapses. Results larger than
+//! one partition are fetched partition by partition.
+//!
+//! `connection_string` is the account URL
+//! (`https://<account>.snowflakecomputing.com`), `database` the session
+//! database.
+//!
+//! | Option | Meaning |
+//! |--------|---------|
+//! | `user` | User the public key is registered for (required) |
+//! | `private_key_path` | Unencrypted PKCS#8 or PKCS#1 PEM key file |
+//! | `private_key` | PEM key, instead of a file |
+//! | `account` | Account identifier (default: the first label of the URL host) |
+//! | `warehouse`, `role`, `schema` | Session warehouse, role and schema |
+//! | `timeout_ms` | Overall statement timeout (default: the datasource `timeout_ms`) |
+//! | `poll_interval_ms` | Wait between polls of a running statement (default: 500) |
+
+use super::super::config::SQLConfig;
+use super::warehouse::{self, CachedToken, JwtSigner};
+use super::Warehouse;
+use crate::error::{Result, RuntimeError};
+use chrono::{Days, NaiveDate};
+use corint_core::Value;
+use reqwest::StatusCode;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+
+/// Lifetime of a key-pair JWT (Snowflake accepts at most one hour)
+const JWT_LIFETIME: Duration = Duration::from_secs(3600);
+
+/// Response of a finished statement, or of one result partition
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ResultSet {
+    #[serde(default)]
+    statement_handle: Option<String>,
+    result_set_meta_data: Option<ResultSetMetaData>,
+    #[serde(default)]
+    data: Vec<Vec<Option<String>>>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ResultSetMetaData {
+    #[serde(default)]
+    row_type: Vec<Column>,
+    #[serde(default)]
+    partition_info: Vec<serde_json::Value>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Column {
+    name: String,
+    #[serde(rename = "type")]
+    column_type: String,
+}
+
+/// Response of a statement that is still running
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct StatementStatus {
+    statement_handle: String,
+}
+
+/// Snowflake client
+pub(super) struct SnowflakeClient {
+    http: reqwest::Client,
+    base_url: String,
+    /// `ACCOUNT.USER`, the JWT subject
+    qualified_user: String,
+    signer: JwtSigner,
+    token: Mutex<Option<CachedToken>>,
+    context: serde_json::Map<String, serde_json::Value>,
+    timeout: Duration,
+    poll_interval: Duration,
+}
+
+impl SnowflakeClient {
+    pub(super) fn new(config: &SQLConfig, pool_size: u32, timeout_ms: u64) -> Result<Self> {
+        let base_url = config.connection_string.trim_end_matches('/').to_string();
+        let user = config.options.get("user").ok_or_else(|| {
+            RuntimeError::RuntimeError("Snowflake requires the user option".to_string())
+        })?;
+        let account = match config.options.get("account") {
+            Some(account) => account.clone(),
+            None => reqwest::Url::parse(&base_url)
+                .ok()
+                .and_then(|url| url.host_str().map(str::to_string))
+                .and_then(|host| host.split('.').next().map(str::to_string))
+                .ok_or_else(|| {
+                    RuntimeError::RuntimeError(format!(
+                        "Cannot derive the Snowflake account from {}; set the account option",
+                        base_url
+                    ))
+                })?,
+        };
+        let key = warehouse::read_secret(config, "private_key", "private_key_path")?.ok_or_else(
+            || {
+                RuntimeError::RuntimeError(
+                    "Snowflake requires private_key_path or private_key".to_string(),
+                )
+            },
+        )?;
+        let (timeout, poll_interval) = warehouse::polling(config, timeout_ms)?;
+
+        let mut context = serde_json::Map::new();
+        context.insert("database".to_string(), config.database.clone().into());
+        for name in ["warehouse", "role", "schema"] {
+            if let Some(value) = config.options.get(name) {
+                context.insert(name.to_string(), value.clone().into());
+            }
+        }
+
+        tracing::info!(
+            "✓ Snowflake client created (account: {}, database: {})",
+            account,
+            config.database
+        );
+
+        Ok(Self {
+            http: warehouse::http_client(pool_size)?,
+            base_url,
+            qualified_user: qualified_user(&account, user),
+            signer: JwtSigner::from_pem(&key)?,
+            token: Mutex::new(None),
+            context,
+            timeout,
+            poll_interval,
+        })
+    }
+
+    /// Key-pair JWT, reused until shortly before it expires
+    async fn jwt(&self) -> String {
+        let mut token = self.token.lock().await;
+        if let Some(value) = token.as_ref().and_then(CachedToken::fresh) {
+            return value.to_string();
+        }
+
+        let now = warehouse::unix_now();
+        let value = self.signer.sign(&serde_json::json!({
+            "iss": format!("{}.{}", self.qualified_user, self.signer.public_key_fingerprint()),
+            "sub": self.qualified_user,
+            "iat": now,
+            "exp": now + JWT_LIFETIME.as_secs(),
+        }));
+        *token = Some(CachedToken::new(value.clone(), JWT_LIFETIME));
+        value
+    }
+
+    /// Send a request; `Ok(Err(handle))` while the statement is running
+    async fn send(
+        &self,
+        request: reqwest::RequestBuilder,
+    ) -> Result<std::result::Result<ResultSet, String>> {
+        let response = request
+            .bearer_auth(self.jwt().await)
+            .header("X-Snowflake-Authorization-Token-Type", "KEYPAIR_JWT")
+            .header(reqwest::header::ACCEPT, "application/json")
+            .send()
+            .await
+            .map_err(|e| RuntimeError::RuntimeError(format!("Snowflake request failed: {}", e)))?;
+
+        let accepted = response.status() == StatusCode::ACCEPTED;
+        let response = warehouse::error_for_status("Snowflake", response).await?;
+        let invalid = |e: reqwest::Error| {
+            RuntimeError::RuntimeError(format!("Invalid Snowflake response: {}", e))
+        };
+        if accepted {
+            let status: StatementStatus = response.json().await.map_err(invalid)?;
+            Ok(Err(status.statement_handle))
+        } else {
+            Ok(Ok(response.json().await.map_err(invalid)?))
+        }
+    }
+
+    fn statement_url(&self, handle: &str) -> String {
+        format!("{}/api/v2/statements/{}", self.base_url, handle)
+    }
+}
+
+#[async_trait::async_trait]
+impl Warehouse for SnowflakeClient {
+    async fn query(&self, sql: &str) -> Result<Vec<HashMap<String, Value>>> {
+        let deadline = Instant::now() + self.timeout;
+
+        let mut body = self.context.clone();
+        body.insert("statement".to_string(), sql.into());
+        body.insert("timeout".to_string(), self.timeout.as_secs().max(1).into());
+        let request = self
+            .http
+            .post(format!("{}/api/v2/statements", self.base_url))
+            .query(&[("requestId", uuid::Uuid::new_v4().to_string())])
+            .json(&body);
+
+        let mut response = self.send(request).await?;
+        let result = loop {
+            match response {
+                Ok(result) => break result,
+                Err(handle) => {
+                    if Instant::now() + self.poll_interval >= deadline {
+                        return Err(RuntimeError::RuntimeError(format!(
+                            "Snowflake statement {} did not finish within {}ms",
+                            handle,
+                            self.timeout.as_millis()
+                        )));
+                    }
+                    tracing::debug!("Waiting for Snowflake statement {}", handle);
+                    tokio::time::sleep(self.poll_interval).await;
+                    response = self
+                        .send(self.http.get(self.statement_url(&handle)))
+                        .await?;
+                }
+            }
+        };
+
+        let metadata = result.result_set_meta_data.ok_or_else(|| {
+            RuntimeError::RuntimeError("Snowflake response has no result metadata".to_string())
+        })?;
+        let mut rows: Vec<_> = result
+            .data
+            .iter()
+            .map(|row| convert_row(&metadata.row_type, row))
+            .collect();
+
+        if metadata.partition_info.len() > 1 {
+            let handle = result.statement_handle.ok_or_else(|| {
+                RuntimeError::RuntimeError("Snowflake response has no statement handle".to_string())
+            })?;
+            for partition in 1..metadata.partition_info.len() {
+                let request = self
+                    .http
+                    .get(self.statement_url(&handle))
+                    .query(&[("partition", partition)]);
+                let Ok(page) = self.send(request).await? else {
+                    return Err(RuntimeError::RuntimeError(format!(
+                        "Snowflake partition {} of {} is not ready",
+                        partition, handle
+                    )));
+                };
+                rows.extend(
+                    page.data
+                        .iter()
+                        .map(|row| convert_row(&metadata.row_type, row)),
+                );
+            }
+        }
+
+        Ok(rows)
+    }
+}
+
+/// `ACCOUNT.USER` as Snowflake expects it in key-pair JWTs
+///
+/// The account is upper-cased and loses any region suffix
+/// (`xy12345.us-east-1` becomes `XY12345`).
+fn qualified_user(account: &str, user: &str) -> String {
+    let account = account.split('.').next().unwrap_or(account);
+    format!("{}.{}", account.to_uppercase(), user.to_uppercase())
+}
+
+fn convert_row(columns: &[Column], row: &[Option<String>]) -> HashMap<String, Value> {
+    columns
+        .iter()
+        .zip(row)
+        .map(|(column, value)| {
+            let value = match value {
+                Some(text) => convert_value(&column.column_type, text),
+                None => Value::Null,
+            };
+            (column.name.clone(), value)
+        })
+        .collect()
+}
+
+/// Convert a value; the SQL API returns every value as a string
+fn convert_value(column_type: &str, text: &str) -> Value {
+    let string = || Value::String(text.to_string());
+    match column_type {
+        "fixed" | "real" => text.parse().map(Value::Number).unwrap_or_else(|_| string()),
+        "boolean" => Value::Bool(text == "true"),
+        // Days since the epoch
+        "date" => text
+            .parse::<u64>()
+            .ok()
+            .and_then(|days| NaiveDate::from_ymd_opt(1970, 1, 1)?.checked_add_days(Days::new(days)))
+            .map(|date| Value::String(date.to_string()))
+            .unwrap_or_else(string),
+        // Seconds since the epoch; TIMESTAMP_TZ adds the offset after a space
+        "timestamp_ltz" | "timestamp_ntz" | "timestamp_tz" => text
+            .split(' ')
+            .next()
+            .and_then(warehouse::epoch_seconds_to_rfc3339)
+            .map(Value::String)
+            .unwrap_or_else(string),
+        "variant" | "object" | "array" => serde_json::from_str(text).unwrap_or_else(|_| string()),
+        _ => string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::warehouse::tests::{serve, TEST_KEY};
+    use super::super::SQLProvider;
+    use super::*;
+
+    #[test]
+    fn test_qualified_user() {
+        assert_eq!(
+            qualified_user("xy12345.us-east-1", "analyst"),
+            "XY12345.ANALYST"
+        );
+        assert_eq!(qualified_user("acme-risk", "Corint"), "ACME-RISK.CORINT");
+    }
+
+    #[tokio::test]
+    async fn test_query_polls_and_reads_partitions() {
+        let row_type = r#"[
+            {"name": "txn_count", "type": "fixed", "scale": 0},
+            {"name": "last_seen", "type": "timestamp_tz"},
+            {"name": "first_day", "type": "date"},
+            {"name": "attributes", "type": "variant"},
+            {"name": "country", "type": "text"}
+        ]"#;
+        let (addr, server) = serve(vec![
+            (202, r#"{"statementHandle": "01b2-stmt", "message": "Asynchronous execution in progress."}"#.to_string()),
+            (
+                200,
+                format!(
+                    r#"{{"statementHandle": "01b2-stmt",
+                        "resultSetMetaData": {{"numRows": 2, "rowType": {}, "partitionInfo": [{{"rowCount": 1}}, {{"rowCount": 1}}]}},
+                        "data": [["12", "1704153600.000000000 1440", "19724", "{{\"ip\": \"10.0.0.1\"}}", "US"]]}}"#,
+                    row_type
+                ),
+            ),
+            (200, r#"{"data": [["0", null, null, null, "DE"]]}"#.to_string()),
+        ])
+        .await;
+
+        let config = SQLConfig {
+            provider: SQLProvider::Snowflake,
+            connection_string: addr.clone(),
+            database: "RISK".to_string(),
+            events_table: "events".to_string(),
+            options: HashMap::from([
+                ("account".to_string(), "acme".to_string()),
+                ("user".to_string(), "corint".to_string()),
+                ("private_key".to_string(), TEST_KEY.to_string()),
+                ("warehouse".to_string(), "BACKFILL_WH".to_string()),
+                ("poll_interval_ms".to_string(), "1".to_string()),
+            ]),
+        };
+        let client = SnowflakeClient::new(&config, 1, 5000).unwrap();
+
+        let rows = client.query("SELECT 1").await.unwrap();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0]["txn_count"], Value::Number(12.0));
+        assert_eq!(
+            rows[0]["last_seen"],
+            Value::String("2024-01-02T00:00:00+00:00".to_string())
+        );
+        assert_eq!(
+            rows[0]["first_day"],
+            Value::String("2024-01-02".to_string())
+        );
+        assert_eq!(
+            rows[0]["attributes"],
+            Value::Object(HashMap::from([(
+                "ip".to_string(),
+                Value::String("10.0.0.1".to_string())
+            )]))
+        );
+        assert_eq!(rows[1]["country"], Value::String("DE".to_string()));
+        assert_eq!(rows[1]["last_seen"], Value::Null);
+
+        let requests = server.await.unwrap();
+        assert!(requests[0].starts_with("POST /api/v2/statements?requestId="));
+        assert!(requests[0].contains("x-snowflake-authorization-token-type: KEYPAIR_JWT"));
+        assert!(requests[0].contains(r#""warehouse":"BACKFILL_WH""#));
+        assert!(requests[1].starts_with("GET /api/v2/statements/01b2-stmt "));
+        assert!(requests[2].starts_with("GET /api/v2/statements/01b2-stmt?partition=1 "));
+    }
+}
@@ -0,0 +1,302 @@
+//! Deterministic replay of decisions from their captured inputs
+//!
+//! Where [`crate::replay`] feeds back the raw responses of data sources and
+//! APIs, this module works one level up: it captures the values a program
+//! actually consumed — the event, every feature value, the result of every
+//! external API and service call (after fallbacks) and the outcome of every
+//! list lookup — so that a disputed decision can be re-executed with exactly
+//! those values, whatever the state of the data sources, lists or feature
+//! definitions today.
+//!
+//! An [`InputCapture`] is bound to the task executing a decision with
+//! [`InputCapture::scope`]:
+//!
+//! - a capturing session records each input as the program consumes it; the
+//!   resulting [`CapturedInputs`] are attached to the execution trace and the
+//!   persisted decision record;
+//! - a pinned session answers each input from captured inputs instead. No
+//!   data source, API, service or list is reached, and an input that was not
+//!   captured fails the execution with [`RuntimeError::Replay`] rather than
+//!   being fetched live.
+
+use crate::error::{Result, RuntimeError};
+use chrono::{DateTime, Utc};
+use corint_core::Value;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+
+tokio::task_local! {
+    static CURRENT_CAPTURE: Arc<InputCapture>;
+}
+
+/// Result of one external API, service or storage call consumed by a program
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CapturedCall {
+    /// Called target (e.g. `api:ipinfo::lookup`, `service:kyc.verify`)
+    pub target: String,
+
+    /// Value the program received
+    pub value: Value,
+}
+
+/// Outcome of one list lookup
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CapturedLookup {
+    /// List looked up
+    pub list_id: String,
+
+    /// Value looked up
+    pub value: Value,
+
+    /// Whether the list contained the value (before any negation)
+    pub contains: bool,
+}
+
+/// Every input a decision consumed
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CapturedInputs {
+    /// Request ID of the decision
+    pub request_id: String,
+
+    /// When the decision was made; replays evaluate effective windows at this time
+    pub captured_at: DateTime<Utc>,
+
+    /// Event data of the request
+    pub event: HashMap<String, Value>,
+
+    /// Feature values, by feature name
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub features: BTreeMap<String, Value>,
+
+    /// Call results in the order they were consumed
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub calls: Vec<CapturedCall>,
+
+    /// List lookup outcomes in the order they were made
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub list_lookups: Vec<CapturedLookup>,
+}
+
+impl CapturedInputs {
+    /// Inputs of a request, captured now
+    pub fn new(request_id: impl Into<String>, event: HashMap<String, Value>) -> Self {
+        Self {
+            request_id: request_id.into(),
+            captured_at: Utc::now(),
+            event,
+            features: BTreeMap::new(),
+            calls: Vec::new(),
+            list_lookups: Vec::new(),
+        }
+    }
+
+    /// Set when the decision was made
+    pub fn with_captured_at(mut self, captured_at: DateTime<Utc>) -> Self {
+        self.captured_at = captured_at;
+        self
+    }
+}
+
+/// Whether a session records inputs or answers them from a capture
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CaptureMode {
+    Capture,
+    Pinned,
+}
+
+/// Input capture or pinning state of one decision
+#[derive(Debug)]
+pub struct InputCapture {
+    mode: CaptureMode,
+    inputs: Mutex<CapturedInputs>,
+    pending_calls: Mutex<HashMap<String, VecDeque<Value>>>,
+}
+
+impl InputCapture {
+    /// Start capturing inputs, on top of the request's `inputs`
+    pub fn capture(inputs: CapturedInputs) -> Self {
+        Self {
+            mode: CaptureMode::Capture,
+            inputs: Mutex::new(inputs),
+            pending_calls: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Answer inputs from `inputs` instead of fetching them
+    pub fn pin(inputs: CapturedInputs) -> Self {
+        let mut pending_calls: HashMap<String, VecDeque<Value>> = HashMap::new();
+        for call in &inputs.calls {
+            pending_calls
+                .entry(call.target.clone())
+                .or_default()
+                .push_back(call.value.clone());
+        }
+        Self {
+            mode: CaptureMode::Pinned,
+            inputs: Mutex::new(inputs),
+            pending_calls: Mutex::new(pending_calls),
+        }
+    }
+
+    /// Whether inputs are answered from a capture
+    pub fn is_pinned(&self) -> bool {
+        self.mode == CaptureMode::Pinned
+    }
+
+    /// When the captured decision was made
+    pub fn captured_at(&self) -> DateTime<Utc> {
+        self.inputs.lock().unwrap().captured_at
+    }
+
+    /// Inputs captured so far, or the pinned inputs
+    pub fn inputs(&self) -> CapturedInputs {
+        self.inputs.lock().unwrap().clone()
+    }
+
+    /// Run `future` with this session active
+    pub async fn scope<F: Future>(self: Arc<Self>, future: F) -> F::Output {
+        CURRENT_CAPTURE.scope(self, future).await
+    }
+
+    /// Pinned value of a feature
+    pub(crate) fn pinned_feature(&self, name: &str) -> Result<Value> {
+        let inputs = self.inputs.lock().unwrap();
+        inputs.features.get(name).cloned().ok_or_else(|| {
+            RuntimeError::Replay(format!(
+                "Feature '{}' was not captured in request {}",
+                name, inputs.request_id
+            ))
+        })
+    }
+
+    /// Record the value of a feature consumed by the program
+    pub(crate) fn record_feature(&self, name: &str, value: &Value) {
+        if !self.is_pinned() {
+            self.inputs
+                .lock()
+                .unwrap()
+                .features
+                .insert(name.to_string(), value.clone());
+        }
+    }
+
+    /// Next pinned result of a call to `target`
+    pub(crate) fn pinned_call(&self, target: &str) -> Result<Value> {
+        self.pending_calls
+            .lock()
+            .unwrap()
+            .get_mut(target)
+            .and_then(VecDeque::pop_front)
+            .ok_or_else(|| {
+                RuntimeError::Replay(format!(
+                    "No captured result left for {} in request {}",
+                    target,
+                    self.inputs.lock().unwrap().request_id
+                ))
+            })
+    }
+
+    /// Record the result of a call consumed by the program
+    pub(crate) fn record_call(&self, target: &str, value: &Value) {
+        if !self.is_pinned() {
+            self.inputs.lock().unwrap().calls.push(CapturedCall {
+                target: target.to_string(),
+                value: value.clone(),
+            });
+        }
+    }
+
+    /// Pinned outcome of looking `value` up in `list_id`
+    pub(crate) fn pinned_lookup(&self, list_id: &str, value: &Value) -> Result<bool> {
+        let inputs = self.inputs.lock().unwrap();
+        inputs
+            .list_lookups
+            .iter()
+            .find(|lookup| lookup.list_id == list_id && lookup.value == *value)
+            .map(|lookup| lookup.contains)
+            .ok_or_else(|| {
+                RuntimeError::Replay(format!(
+                    "Lookup of {:?} in list '{}' was not captured in request {}",
+                    value, list_id, inputs.request_id
+                ))
+            })
+    }
+
+    /// Record the outcome of a list lookup made by the program
+    pub(crate) fn record_lookup(&self, list_id: &str, value: &Value, contains: bool) {
+        if !self.is_pinned() {
+            self.inputs
+                .lock()
+                .unwrap()
+                .list_lookups
+                .push(CapturedLookup {
+                    list_id: list_id.to_string(),
+                    value: value.clone(),
+                    contains,
+                });
+        }
+    }
+}
+
+/// Capture or pinning session bound to the current task, if any
+pub fn current_capture() -> Option<Arc<InputCapture>> {
+    CURRENT_CAPTURE.try_with(Arc::clone).ok()
+}
+
+/// Pinned session bound to the current task, if any
+pub(crate) fn current_pinned() -> Option<Arc<InputCapture>> {
+    current_capture().filter(|capture| capture.is_pinned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_capture_then_pin() {
+        let mut event = HashMap::new();
+        event.insert("amount".to_string(), Value::Number(120.0));
+        let capture = InputCapture::capture(CapturedInputs::new("req_1", event));
+        capture.record_feature("txn_count_1h", &Value::Number(3.0));
+        capture.record_call("api:ipinfo::lookup", &Value::String("US".to_string()));
+        capture.record_call("api:ipinfo::lookup", &Value::String("FR".to_string()));
+        capture.record_lookup("blocked_ips", &Value::String("1.2.3.4".to_string()), true);
+
+        let inputs = capture.inputs();
+        assert_eq!(inputs.calls.len(), 2);
+        let json = serde_json::to_string(&inputs).unwrap();
+        let inputs: CapturedInputs = serde_json::from_str(&json).unwrap();
+
+        let pinned = InputCapture::pin(inputs);
+        assert!(pinned.is_pinned());
+        assert_eq!(
+            pinned.pinned_feature("txn_count_1h").unwrap(),
+            Value::Number(3.0)
+        );
+        assert!(matches!(
+            pinned.pinned_feature("other"),
+            Err(RuntimeError::Replay(_))
+        ));
+        assert_eq!(
+            pinned.pinned_call("api:ipinfo::lookup").unwrap(),
+            Value::String("US".to_string())
+        );
+        assert_eq!(
+            pinned.pinned_call("api:ipinfo::lookup").unwrap(),
+            Value::String("FR".to_string())
+        );
+        assert!(pinned.pinned_call("api:ipinfo::lookup").is_err());
+        assert!(pinned
+            .pinned_lookup("blocked_ips", &Value::String("1.2.3.4".to_string()))
+            .unwrap());
+        assert!(pinned
+            .pinned_lookup("blocked_ips", &Value::String("5.6.7.8".to_string()))
+            .is_err());
+
+        // Pinned sessions do not record
+        pinned.record_feature("late", &Value::Null);
+        assert!(!pinned.inputs().features.contains_key("late"));
+    }
+}
@@ -0,0 +1,564 @@
+//! Fire-and-forget side effects of decisions
+//!
+//! An `emit` pipeline step calls a webhook, publishes a Kafka message or
+//! opens a case. The step only evaluates its payload: the side effect is
+//! queued in the execution context and dispatched by an [`EmitDispatcher`]
+//! once the decision is final, on a background task, so a slow or failing
+//! receiver never delays the decision.
+//!
+//! Each [`Emission`] is delivered by the [`EmitSink`] registered for its
+//! kind, retried with the dispatcher's [`RetryPolicy`], and moved to a
+//! bounded dead-letter buffer once it cannot be delivered. The oldest dead
+//! letters are dropped when the buffer is full.
+//!
+//! ```yaml
+//! - step:
+//!     id: open_case
+//!     name: Open Review Case
+//!     type: emit
+//!     emit: case
+//!     target: fraud_review
+//!     payload:
+//!       user_id: event.user_id
+//!       amount: event.amount
+//!     signals: [decline, review]
+//! ```
+
+use crate::error::{Result, RuntimeError};
+use crate::result::DecisionResult;
+use crate::retry::RetryPolicy;
+use crate::service::MqClient;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use corint_core::ast::{EmitKind, Signal};
+use corint_core::Value;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicU32, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::Notify;
+
+/// Context variable emit steps queue their side effects in
+pub const QUEUED_EMITS_VARIABLE: &str = "__queued_emits__";
+
+/// Side effect queued by an emit step, waiting for the final decision
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct QueuedEmit {
+    /// Emit step ID
+    pub step_id: String,
+
+    /// Kind of side effect
+    pub kind: EmitKind,
+
+    /// Webhook URL, Kafka topic or case queue
+    pub target: String,
+
+    /// Payload evaluated when the step ran
+    pub payload: HashMap<String, Value>,
+
+    /// Final signals the side effect is emitted for (all when empty)
+    #[serde(default)]
+    pub signals: Vec<Signal>,
+}
+
+impl QueuedEmit {
+    /// Side effects queued in the variables of an execution, in step order
+    pub fn from_context(variables: &HashMap<String, Value>) -> Vec<QueuedEmit> {
+        let Some(Value::Array(queued)) = variables.get(QUEUED_EMITS_VARIABLE) else {
+            return Vec::new();
+        };
+        queued
+            .iter()
+            .filter_map(|record| match record {
+                Value::String(json) => serde_json::from_str(json).ok(),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Whether the side effect is emitted for a decision with `signal`
+    pub fn emits_for(&self, signal: Option<&Signal>) -> bool {
+        self.signals.is_empty() || signal.is_some_and(|signal| self.signals.contains(signal))
+    }
+}
+
+/// Side effect of a final decision, as delivered to a sink
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Emission {
+    /// Request the decision was made for
+    pub request_id: String,
+
+    /// Emit step ID
+    pub step_id: String,
+
+    /// Kind of side effect
+    pub kind: EmitKind,
+
+    /// Webhook URL, Kafka topic or case queue
+    pub target: String,
+
+    /// Payload evaluated by the emit step
+    pub payload: HashMap<String, Value>,
+
+    /// Final signal of the decision
+    pub signal: Option<Signal>,
+
+    /// Final score of the decision
+    pub score: i32,
+
+    /// Rules triggered by the decision
+    pub triggered_rules: Vec<String>,
+
+    /// User-defined actions of the decision
+    pub actions: Vec<String>,
+}
+
+impl Emission {
+    /// Side effect of `queued` for the final decision `result`
+    pub fn new(request_id: String, queued: QueuedEmit, result: &DecisionResult) -> Self {
+        Self {
+            request_id,
+            step_id: queued.step_id,
+            kind: queued.kind,
+            target: queued.target,
+            payload: queued.payload,
+            signal: result.signal.clone(),
+            score: result.score,
+            triggered_rules: result.triggered_rules.clone(),
+            actions: result.actions.clone(),
+        }
+    }
+}
+
+/// Delivers emissions of one kind
+#[async_trait]
+pub trait EmitSink: Send + Sync {
+    /// Deliver an emission, failing if it may need to be retried
+    async fn deliver(&self, emission: &Emission) -> Result<()>;
+}
+
+/// Posts emissions as JSON to their target URL
+pub struct WebhookSink {
+    client: reqwest::Client,
+}
+
+impl WebhookSink {
+    /// Create a sink whose requests time out after `timeout`
+    pub fn new(timeout: Duration) -> Self {
+        Self {
+            client: reqwest::Client::builder()
+                .timeout(timeout)
+                .build()
+                .unwrap_or_default(),
+        }
+    }
+}
+
+#[async_trait]
+impl EmitSink for WebhookSink {
+    async fn deliver(&self, emission: &Emission) -> Result<()> {
+        let response = self
+            .client
+            .post(&emission.target)
+            .json(emission)
+            .send()
+            .await
+            .map_err(|e| {
+                if e.is_timeout() {
+                    RuntimeError::Timeout(format!("Webhook {}: {}", emission.target, e))
+                } else {
+                    RuntimeError::ExternalCallFailed(format!("Webhook {}: {}", emission.target, e))
+                }
+            })?;
+
+        if !response.status().is_success() {
+            return Err(RuntimeError::ExternalCallFailed(format!(
+                "Webhook {} returned {}",
+                emission.target,
+                response.status()
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Publishes emissions to their target topic, keyed by request ID
+pub struct MqSink {
+    client: Arc<dyn MqClient>,
+}
+
+impl MqSink {
+    /// Create a sink publishing through `client`
+    pub fn new(client: Arc<dyn MqClient>) -> Self {
+        Self { client }
+    }
+}
+
+#[async_trait]
+impl EmitSink for MqSink {
+    async fn deliver(&self, emission: &Emission) -> Result<()> {
+        let value = serde_json::to_value(emission)
+            .and_then(serde_json::from_value)
+            .map_err(|e| RuntimeError::RuntimeError(format!("Failed to encode emission: {}", e)))?;
+        self.client
+            .publish(
+                emission.target.clone(),
+                Some(emission.request_id.clone()),
+                value,
+            )
+            .await
+    }
+}
+
+/// Emission that could not be delivered
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DeadLetter {
+    /// The undelivered emission
+    pub emission: Emission,
+
+    /// Error of the last delivery attempt
+    pub error: String,
+
+    /// Delivery attempts made
+    pub attempts: u32,
+
+    /// When the emission was given up on
+    pub failed_at: DateTime<Utc>,
+}
+
+/// Delivery statistics of a dispatcher
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct EmitStats {
+    /// Emissions dispatched
+    pub dispatched: u64,
+
+    /// Emissions delivered
+    pub delivered: u64,
+
+    /// Delivery attempts retried
+    pub retried: u64,
+
+    /// Emissions moved to the dead-letter buffer
+    pub dead_lettered: u64,
+
+    /// Dead letters dropped from the full buffer
+    pub dropped: u64,
+
+    /// Emissions currently being delivered
+    pub in_flight: usize,
+}
+
+/// Delivers the side effects of final decisions in the background
+pub struct EmitDispatcher {
+    sinks: HashMap<EmitKind, Arc<dyn EmitSink>>,
+    retry: RetryPolicy,
+    dead_letter_capacity: usize,
+    dead_letters: Mutex<VecDeque<DeadLetter>>,
+    dispatched: AtomicU64,
+    delivered: AtomicU64,
+    retried: AtomicU64,
+    dead_lettered: AtomicU64,
+    dropped: AtomicU64,
+    in_flight: AtomicUsize,
+    idle: Notify,
+}
+
+impl std::fmt::Debug for EmitDispatcher {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EmitDispatcher")
+            .field("sinks", &self.sinks.keys().collect::<Vec<_>>())
+            .field("retry", &self.retry)
+            .field("dead_letter_capacity", &self.dead_letter_capacity)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Default for EmitDispatcher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl EmitDispatcher {
+    /// Create a dispatcher without sinks, keeping up to 1000 dead letters
+    pub fn new() -> Self {
+        Self {
+            sinks: HashMap::new(),
+            retry: RetryPolicy::default(),
+            dead_letter_capacity: 1000,
+            dead_letters: Mutex::new(VecDeque::new()),
+            dispatched: AtomicU64::new(0),
+            delivered: AtomicU64::new(0),
+            retried: AtomicU64::new(0),
+            dead_lettered: AtomicU64::new(0),
+            dropped: AtomicU64::new(0),
+            in_flight: AtomicUsize::new(0),
+            idle: Notify::new(),
+        }
+    }
+
+    /// Deliver emissions of `kind` with `sink`
+    pub fn with_sink(mut self, kind: EmitKind, sink: Arc<dyn EmitSink>) -> Self {
+        self.sinks.insert(kind, sink);
+        self
+    }
+
+    /// Set the retry policy of deliveries
+    pub fn with_retry_policy(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Set the number of dead letters kept
+    pub fn with_dead_letter_capacity(mut self, capacity: usize) -> Self {
+        self.dead_letter_capacity = capacity;
+        self
+    }
+
+    /// Deliver `emissions` in the background
+    ///
+    /// Must be called within a Tokio runtime.
+    pub fn dispatch(self: &Arc<Self>, emissions: Vec<Emission>) {
+        for emission in emissions {
+            self.dispatched.fetch_add(1, Ordering::Relaxed);
+            self.in_flight.fetch_add(1, Ordering::SeqCst);
+            let dispatcher = Arc::clone(self);
+            tokio::spawn(async move {
+                dispatcher.deliver(emission).await;
+                if dispatcher.in_flight.fetch_sub(1, Ordering::SeqCst) == 1 {
+                    dispatcher.idle.notify_waiters();
+                }
+            });
+        }
+    }
+
+    /// Wait until every dispatched emission is delivered or dead-lettered
+    pub async fn wait_idle(&self) {
+        loop {
+            let notified = self.idle.notified();
+            tokio::pin!(notified);
+            notified.as_mut().enable();
+            if self.in_flight.load(Ordering::SeqCst) == 0 {
+                return;
+            }
+            notified.await;
+        }
+    }
+
+    /// Dead letters currently buffered, oldest first
+    pub fn dead_letters(&self) -> Vec<DeadLetter> {
+        self.dead_letters.lock().unwrap().iter().cloned().collect()
+    }
+
+    /// Remove and return the buffered dead letters, oldest first
+    ///
+    /// Their emissions can be dispatched again once the receiver recovers.
+    pub fn take_dead_letters(&self) -> Vec<DeadLetter> {
+        self.dead_letters.lock().unwrap().drain(..).collect()
+    }
+
+    /// Current delivery statistics
+    pub fn stats(&self) -> EmitStats {
+        EmitStats {
+            dispatched: self.dispatched.load(Ordering::Relaxed),
+            delivered: self.delivered.load(Ordering::Relaxed),
+            retried: self.retried.load(Ordering::Relaxed),
+            dead_lettered: self.dead_lettered.load(Ordering::Relaxed),
+            dropped: self.dropped.load(Ordering::Relaxed),
+            in_flight: self.in_flight.load(Ordering::SeqCst),
+        }
+    }
+
+    async fn deliver(&self, emission: Emission) {
+        let Some(sink) = self.sinks.get(&emission.kind) else {
+            let error = format!("No sink registered for {} emissions", emission.kind.as_str());
+            self.dead_letter(emission, error, 0);
+            return;
+        };
+
+        let attempts = AtomicU32::new(0);
+        let dependency = format!("{} {}", emission.kind.as_str(), emission.target);
+        let outcome = self
+            .retry
+            .run(&dependency, || {
+                attempts.fetch_add(1, Ordering::Relaxed);
+                sink.deliver(&emission)
+            })
+            .await;
+
+        let attempts = attempts.into_inner();
+        self.retried
+            .fetch_add(u64::from(attempts.saturating_sub(1)), Ordering::Relaxed);
+        match outcome {
+            Ok(()) => {
+                self.delivered.fetch_add(1, Ordering::Relaxed);
+            }
+            Err(e) => self.dead_letter(emission, e.to_string(), attempts),
+        }
+    }
+
+    fn dead_letter(&self, emission: Emission, error: String, attempts: u32) {
+        tracing::warn!(
+            "Giving up on {} emission of step {} (request {}) after {} attempts: {}",
+            emission.kind.as_str(),
+            emission.step_id,
+            emission.request_id,
+            attempts,
+            error
+        );
+        self.dead_lettered.fetch_add(1, Ordering::Relaxed);
+
+        let mut dead_letters = self.dead_letters.lock().unwrap();
+        if self.dead_letter_capacity == 0 {
+            self.dropped.fetch_add(1, Ordering::Relaxed);
+            return;
+        }
+        if dead_letters.len() >= self.dead_letter_capacity {
+            dead_letters.pop_front();
+            self.dropped.fetch_add(1, Ordering::Relaxed);
+        }
+        dead_letters.push_back(DeadLetter {
+            emission,
+            error,
+            attempts,
+            failed_at: Utc::now(),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::service::MockMqClient;
+
+    /// Sink failing its first `failures` deliveries
+    struct FlakySink {
+        failures: u32,
+        calls: AtomicU32,
+    }
+
+    #[async_trait]
+    impl EmitSink for FlakySink {
+        async fn deliver(&self, _emission: &Emission) -> Result<()> {
+            if self.calls.fetch_add(1, Ordering::SeqCst) < self.failures {
+                return Err(RuntimeError::ExternalCallFailed("unavailable".to_string()));
+            }
+            Ok(())
+        }
+    }
+
+    fn queued(kind: EmitKind, step_id: &str) -> QueuedEmit {
+        QueuedEmit {
+            step_id: step_id.to_string(),
+            kind,
+            target: "fraud_review".to_string(),
+            payload: HashMap::from([("user_id".to_string(), Value::String("u1".to_string()))]),
+            signals: Vec::new(),
+        }
+    }
+
+    fn emission(kind: EmitKind, step_id: &str) -> Emission {
+        Emission::new(
+            "req_1".to_string(),
+            queued(kind, step_id),
+            &DecisionResult::new(Signal::Decline, 80),
+        )
+    }
+
+    #[test]
+    fn test_queued_emit_signals() {
+        let mut queued = queued(EmitKind::Case, "open_case");
+        assert!(queued.emits_for(None));
+
+        queued.signals = vec![Signal::Decline, Signal::Review];
+        assert!(queued.emits_for(Some(&Signal::Review)));
+        assert!(!queued.emits_for(Some(&Signal::Approve)));
+        assert!(!queued.emits_for(None));
+
+        let variables = HashMap::from([(
+            QUEUED_EMITS_VARIABLE.to_string(),
+            Value::Array(vec![Value::String(serde_json::to_string(&queued).unwrap())]),
+        )]);
+        assert_eq!(QueuedEmit::from_context(&variables), vec![queued]);
+        assert!(QueuedEmit::from_context(&HashMap::new()).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_dispatcher_retries_then_delivers() {
+        let sink = Arc::new(FlakySink {
+            failures: 2,
+            calls: AtomicU32::new(0),
+        });
+        let dispatcher = Arc::new(
+            EmitDispatcher::new()
+                .with_sink(EmitKind::Case, sink.clone())
+                .with_retry_policy(RetryPolicy::new(3).with_backoff(1, 1)),
+        );
+
+        dispatcher.dispatch(vec![emission(EmitKind::Case, "open_case")]);
+        dispatcher.wait_idle().await;
+
+        assert_eq!(sink.calls.load(Ordering::SeqCst), 3);
+        let stats = dispatcher.stats();
+        assert_eq!(stats.delivered, 1);
+        assert_eq!(stats.retried, 2);
+        assert_eq!(stats.in_flight, 0);
+        assert!(dispatcher.dead_letters().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_dispatcher_dead_letters() {
+        let sink = Arc::new(FlakySink {
+            failures: u32::MAX,
+            calls: AtomicU32::new(0),
+        });
+        let dispatcher = Arc::new(
+            EmitDispatcher::new()
+                .with_sink(EmitKind::Webhook, sink)
+                .with_retry_policy(RetryPolicy::new(2).with_backoff(1, 1))
+                .with_dead_letter_capacity(2),
+        );
+
+        // Kafka has no sink: dead-lettered without any attempt
+        dispatcher.dispatch(vec![emission(EmitKind::Kafka, "publish")]);
+        dispatcher.wait_idle().await;
+        dispatcher.dispatch(vec![
+            emission(EmitKind::Webhook, "notify_a"),
+            emission(EmitKind::Webhook, "notify_b"),
+        ]);
+        dispatcher.wait_idle().await;
+
+        let stats = dispatcher.stats();
+        assert_eq!(stats.dispatched, 3);
+        assert_eq!(stats.dead_lettered, 3);
+        assert_eq!(stats.dropped, 1);
+
+        let mut dead_letters = dispatcher.take_dead_letters();
+        assert!(dispatcher.dead_letters().is_empty());
+        dead_letters.sort_by(|a, b| a.emission.step_id.cmp(&b.emission.step_id));
+        assert_eq!(dead_letters.len(), 2);
+        assert_eq!(dead_letters[0].emission.step_id, "notify_a");
+        assert_eq!(dead_letters[0].attempts, 2);
+        assert!(dead_letters[0].error.contains("unavailable"));
+    }
+
+    #[tokio::test]
+    async fn test_mq_sink_publishes_to_target() {
+        let client = Arc::new(MockMqClient::new());
+        let sink = MqSink::new(client.clone());
+
+        sink.deliver(&emission(EmitKind::Kafka, "publish"))
+            .await
+            .unwrap();
+
+        let published = client.get_published_messages();
+        assert_eq!(published.len(), 1);
+        assert_eq!(published[0].topic, "fraud_review");
+        assert_eq!(published[0].key.as_deref(), Some("req_1"));
+        let Value::Object(message) = &published[0].value else {
+            panic!("expected an object message");
+        };
+        assert_eq!(message.get("score"), Some(&Value::Number(80.0)));
+    }
+}
@@ -0,0 +1,227 @@
+//! Time budgets of a decision
+//!
+//! A decision can be given a timeout as a whole, each pipeline and each of
+//! its steps can set their own with `timeout_ms`, and every external call is
+//! bounded by its endpoint timeout. A scope that runs out of time does not
+//! fail the decision: the work left in it is skipped, calls that time out
+//! fall back to their fallback value, and the decision is made from the
+//! results gathered so far.
+//!
+//! An [`ExecutionBudget`] is bound to the task making a decision. It holds
+//! the deadline of the decision and of the pipeline being executed, which
+//! also bounds the rulesets and rules the pipeline routes to, and collects a
+//! [`TimeoutEvent`] for every scope that ran out of time, so that the
+//! response can be marked as degraded.
+
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+tokio::task_local! {
+    static CURRENT_BUDGET: Arc<ExecutionBudget>;
+}
+
+/// What ran out of time
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TimeoutScope {
+    /// The whole decision
+    Decision,
+
+    /// A pipeline, including the rulesets it routes to
+    Pipeline,
+
+    /// A pipeline step
+    Step,
+
+    /// A single external API or service call
+    Call,
+}
+
+impl TimeoutScope {
+    /// Scope name, as used in metric labels
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Decision => "decision",
+            Self::Pipeline => "pipeline",
+            Self::Step => "step",
+            Self::Call => "call",
+        }
+    }
+}
+
+/// A scope that ran out of time
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TimeoutEvent {
+    /// What ran out of time
+    pub scope: TimeoutScope,
+
+    /// Pipeline or step ID, or `api::endpoint` of a call; none for the decision
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+
+    /// Time budget of the scope
+    pub budget_ms: u64,
+
+    /// Time spent in the scope when it was cut short
+    pub elapsed_ms: u64,
+}
+
+/// Deadline of one scope
+#[derive(Debug, Clone)]
+pub struct Deadline {
+    scope: TimeoutScope,
+    id: Option<String>,
+    budget: Duration,
+    started: Instant,
+}
+
+impl Deadline {
+    /// Deadline `budget_ms` milliseconds from now
+    pub fn new(scope: TimeoutScope, id: Option<String>, budget_ms: u64) -> Self {
+        Self {
+            scope,
+            id,
+            budget: Duration::from_millis(budget_ms),
+            started: Instant::now(),
+        }
+    }
+
+    /// Scope the deadline applies to
+    pub fn scope(&self) -> TimeoutScope {
+        self.scope
+    }
+
+    /// Time left before the deadline
+    pub fn remaining(&self) -> Duration {
+        self.budget.saturating_sub(self.started.elapsed())
+    }
+
+    /// Whether the deadline has passed
+    pub fn is_expired(&self) -> bool {
+        self.started.elapsed() >= self.budget
+    }
+
+    /// Event recording that the scope ran out of time
+    pub fn event(&self) -> TimeoutEvent {
+        TimeoutEvent {
+            scope: self.scope,
+            id: self.id.clone(),
+            budget_ms: self.budget.as_millis() as u64,
+            elapsed_ms: self.started.elapsed().as_millis() as u64,
+        }
+    }
+
+    /// The deadline among `deadlines` with the least time left
+    pub fn earliest<'a>(deadlines: impl IntoIterator<Item = &'a Deadline>) -> Option<&'a Deadline> {
+        deadlines
+            .into_iter()
+            .min_by_key(|deadline| deadline.remaining())
+    }
+}
+
+/// Time budget of one decision
+#[derive(Debug, Default)]
+pub struct ExecutionBudget {
+    decision: Option<Deadline>,
+    pipeline: Mutex<Option<Deadline>>,
+    timeouts: Mutex<Vec<TimeoutEvent>>,
+}
+
+impl ExecutionBudget {
+    /// Budget of a decision that must be made within `timeout`, if set
+    pub fn new(timeout: Option<Duration>) -> Self {
+        Self {
+            decision: timeout.map(|timeout| Deadline {
+                scope: TimeoutScope::Decision,
+                id: None,
+                budget: timeout,
+                started: Instant::now(),
+            }),
+            ..Default::default()
+        }
+    }
+
+    /// Start executing `pipeline_id`, within `timeout_ms` if set
+    pub fn enter_pipeline(&self, pipeline_id: &str, timeout_ms: Option<u64>) {
+        *self.pipeline.lock().unwrap() = timeout_ms.map(|timeout_ms| {
+            Deadline::new(
+                TimeoutScope::Pipeline,
+                Some(pipeline_id.to_string()),
+                timeout_ms,
+            )
+        });
+    }
+
+    /// Deadlines in force: the decision's and the current pipeline's
+    pub fn deadlines(&self) -> Vec<Deadline> {
+        self.decision
+            .iter()
+            .cloned()
+            .chain(self.pipeline.lock().unwrap().clone())
+            .collect()
+    }
+
+    /// Record that a scope ran out of time
+    ///
+    /// A scope is recorded once, however much work its timeout cut short.
+    pub fn record(&self, event: TimeoutEvent) {
+        let mut timeouts = self.timeouts.lock().unwrap();
+        if !timeouts
+            .iter()
+            .any(|t| t.scope == event.scope && t.id == event.id)
+        {
+            timeouts.push(event);
+        }
+    }
+
+    /// Scopes that ran out of time, in the order they did
+    pub fn timeouts(&self) -> Vec<TimeoutEvent> {
+        self.timeouts.lock().unwrap().clone()
+    }
+
+    /// Whether the decision was made from partial results
+    pub fn is_degraded(&self) -> bool {
+        !self.timeouts.lock().unwrap().is_empty()
+    }
+
+    /// Run `future` with this budget active
+    pub async fn scope<F: Future>(self: Arc<Self>, future: F) -> F::Output {
+        CURRENT_BUDGET.scope(self, future).await
+    }
+}
+
+/// Budget bound to the current task, if any
+pub fn current_budget() -> Option<Arc<ExecutionBudget>> {
+    CURRENT_BUDGET.try_with(Arc::clone).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_execution_budget() {
+        let budget = ExecutionBudget::new(Some(Duration::from_secs(60)));
+        assert_eq!(budget.deadlines().len(), 1);
+
+        budget.enter_pipeline("login", Some(0));
+        let deadlines = budget.deadlines();
+        let earliest = Deadline::earliest(&deadlines).unwrap();
+        assert_eq!(earliest.scope(), TimeoutScope::Pipeline);
+        assert!(earliest.is_expired());
+        assert!(!budget.is_degraded());
+
+        budget.record(earliest.event());
+        budget.record(earliest.event());
+        assert!(budget.is_degraded());
+        assert_eq!(budget.timeouts().len(), 1);
+        assert_eq!(budget.timeouts()[0].id.as_deref(), Some("login"));
+
+        // Without a timeout the pipeline has no deadline
+        budget.enter_pipeline("signup", None);
+        assert_eq!(budget.deadlines().len(), 1);
+        assert!(ExecutionBudget::new(None).deadlines().is_empty());
+    }
+}
@@ -0,0 +1,150 @@
+//! Execution limits of a program
+//!
+//! Compiled programs only jump forward, but a malformed or pathological
+//! program (a backward jump, a huge generated ruleset) could otherwise keep
+//! a worker busy indefinitely. Unlike the time budgets of a decision, which
+//! degrade it gracefully, these limits are a safety net: a program that
+//! executes more than `max_instructions` instructions, or runs longer than
+//! `max_wall_clock_ms`, is aborted with [`RuntimeError::ExecutionLimitExceeded`].
+
+use crate::error::{Result, RuntimeError};
+use corint_core::ir::{Instruction, ProgramMetadata};
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+
+/// Limits on the execution of a single program
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExecutionLimits {
+    /// Instructions a program may execute, including its decision logic
+    /// (default: 1,000,000)
+    #[serde(default = "default_max_instructions")]
+    pub max_instructions: u64,
+
+    /// Time a program may run, in milliseconds, including the external calls
+    /// it waits on (default: 30,000; none for no limit)
+    #[serde(default = "default_max_wall_clock_ms")]
+    pub max_wall_clock_ms: Option<u64>,
+}
+
+fn default_max_instructions() -> u64 {
+    1_000_000
+}
+
+fn default_max_wall_clock_ms() -> Option<u64> {
+    Some(30_000)
+}
+
+impl Default for ExecutionLimits {
+    fn default() -> Self {
+        Self {
+            max_instructions: default_max_instructions(),
+            max_wall_clock_ms: default_max_wall_clock_ms(),
+        }
+    }
+}
+
+impl ExecutionLimits {
+    /// Set the number of instructions a program may execute
+    pub fn with_max_instructions(mut self, max_instructions: u64) -> Self {
+        self.max_instructions = max_instructions;
+        self
+    }
+
+    /// Set the time a program may run, `None` for no limit
+    pub fn with_max_wall_clock(mut self, max_wall_clock: Option<Duration>) -> Self {
+        self.max_wall_clock_ms = max_wall_clock.map(|limit| limit.as_millis() as u64);
+        self
+    }
+}
+
+/// Counts the instructions of one program execution against its limits
+pub(crate) struct InstructionGuard<'a> {
+    limits: &'a ExecutionLimits,
+    metadata: &'a ProgramMetadata,
+    started: Instant,
+    executed: u64,
+}
+
+impl<'a> InstructionGuard<'a> {
+    /// Start counting an execution of the program described by `metadata`
+    pub(crate) fn new(limits: &'a ExecutionLimits, metadata: &'a ProgramMetadata) -> Self {
+        Self {
+            limits,
+            metadata,
+            started: Instant::now(),
+            executed: 0,
+        }
+    }
+
+    /// Account for `instruction`, about to execute at `pc`
+    ///
+    /// Fails when it would exceed a limit; the error carries the program,
+    /// the instruction and how far the execution got.
+    pub(crate) fn check(&mut self, pc: usize, instruction: &Instruction) -> Result<()> {
+        self.executed += 1;
+
+        let exceeded = if self.executed > self.limits.max_instructions {
+            Some(format!(
+                "more than {} instructions executed",
+                self.limits.max_instructions
+            ))
+        } else {
+            self.limits
+                .max_wall_clock_ms
+                .filter(|&limit| self.started.elapsed() > Duration::from_millis(limit))
+                .map(|limit| format!("ran longer than {}ms", limit))
+        };
+
+        match exceeded {
+            Some(limit) => Err(RuntimeError::ExecutionLimitExceeded {
+                program: format!("{}:{}", self.metadata.source_type, self.metadata.source_id),
+                limit,
+                pc,
+                instruction: format!("{:?}", instruction),
+                executed: self.executed - 1,
+                elapsed_ms: self.started.elapsed().as_millis() as u64,
+            }),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_instruction_guard() {
+        let limits = ExecutionLimits::default().with_max_instructions(3);
+        let metadata = ProgramMetadata::for_rule("loop_rule".to_string());
+        let mut guard = InstructionGuard::new(&limits, &metadata);
+
+        for pc in 0..3 {
+            guard.check(pc, &Instruction::Return).unwrap();
+        }
+        match guard.check(3, &Instruction::Jump { offset: -3 }) {
+            Err(RuntimeError::ExecutionLimitExceeded {
+                program,
+                pc,
+                instruction,
+                executed,
+                ..
+            }) => {
+                assert_eq!(program, "rule:loop_rule");
+                assert_eq!(pc, 3);
+                assert_eq!(instruction, "Jump { offset: -3 }");
+                assert_eq!(executed, 3);
+            }
+            other => panic!("expected ExecutionLimitExceeded, got {:?}", other),
+        }
+
+        let limits = ExecutionLimits::default().with_max_wall_clock(Some(Duration::ZERO));
+        let mut guard = InstructionGuard::new(&limits, &metadata);
+        std::thread::sleep(Duration::from_millis(2));
+        assert!(guard.check(0, &Instruction::Return).is_err());
+
+        let limits: ExecutionLimits = serde_yaml::from_str("max_instructions: 500").unwrap();
+        assert_eq!(limits.max_instructions, 500);
+        assert_eq!(limits.max_wall_clock_ms, Some(30_000));
+    }
+}
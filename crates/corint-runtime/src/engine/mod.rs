@@ -2,8 +2,16 @@
 //!
 //! Provides executors for running IR programs.
 
+pub mod budget;
+mod limits;
+pub mod null_semantics;
+pub mod observer;
 mod operators;
 pub mod pipeline_executor;
 
 // Re-export for convenience
+pub use budget::{ExecutionBudget, TimeoutEvent, TimeoutScope};
+pub use limits::ExecutionLimits;
+pub use null_semantics::NullSemantics;
+pub use observer::{ExecutionObserver, InstructionEvent, ProgramSection, StepEvent};
 pub use pipeline_executor::PipelineExecutor;
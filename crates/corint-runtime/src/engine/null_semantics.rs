@@ -0,0 +1,35 @@
+//! Semantics of conditions over missing values
+//!
+//! By default a comparison involving `Null` is false, so `user.age > 18` and
+//! `user.age <= 18` are both false when the age is missing, and negating one
+//! makes it true. With [`NullSemantics::ThreeValued`], comparisons, list
+//! lookups and quantifiers involving `Null` evaluate to Unknown (`Null`
+//! itself) instead, and `&&`, `||` and `!` follow Kleene logic: `false && x`
+//! is false and `true || x` is true whatever `x` is, and any other
+//! combination with Unknown is Unknown. A rule whose condition is Unknown does
+//! not trigger, negated or not.
+//!
+//! Missing values are checked explicitly with `x is null` / `x is not null`
+//! and defaulted with `coalesce(x, default)`, which behave the same under both
+//! semantics.
+
+use serde::{Deserialize, Serialize};
+
+/// How comparisons and logical operators treat `Null`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NullSemantics {
+    /// Comparisons involving `Null` are false (default)
+    #[default]
+    TwoValued,
+
+    /// Comparisons involving `Null` are Unknown, combined with Kleene logic
+    ThreeValued,
+}
+
+impl NullSemantics {
+    /// Whether conditions over `Null` evaluate to Unknown
+    pub fn is_three_valued(&self) -> bool {
+        matches!(self, NullSemantics::ThreeValued)
+    }
+}
@@ -0,0 +1,200 @@
+//! Execution observers
+//!
+//! An [`ExecutionObserver`] attached to a [`PipelineExecutor`] is called
+//! before and after every instruction it executes, and around every pipeline
+//! step, with the execution context as it stands at that point. This is the
+//! extension point for interactive debuggers, profilers and coverage tools:
+//! an observer can inspect the stack and namespaces, time instructions, or
+//! hold an instruction back (e.g. at a breakpoint) until it returns.
+//!
+//! A step starts at its `MarkStepExecuted` instruction and ends where the
+//! next step starts or the program ends. After-callbacks are only made for
+//! instructions and steps that complete: an instruction that fails the
+//! execution is not followed by `after_instruction`.
+//!
+//! [`PipelineExecutor`]: super::PipelineExecutor
+
+use crate::context::ExecutionContext;
+use async_trait::async_trait;
+use corint_core::ir::{Instruction, ProgramMetadata};
+
+/// Instruction sequence of a program an instruction belongs to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProgramSection {
+    /// Main instructions
+    Main,
+
+    /// Decision logic, executed after the main instructions
+    Decision,
+}
+
+/// An instruction about to execute, or just executed
+#[derive(Debug, Clone, Copy)]
+pub struct InstructionEvent<'a> {
+    /// Program being executed
+    pub program: &'a ProgramMetadata,
+
+    /// Instruction sequence the instruction belongs to
+    pub section: ProgramSection,
+
+    /// Position of the instruction in its section
+    pub pc: usize,
+
+    /// The instruction
+    pub instruction: &'a Instruction,
+
+    /// Execution context before or after the instruction
+    pub context: &'a ExecutionContext,
+}
+
+/// A pipeline step starting or ending
+#[derive(Debug, Clone, Copy)]
+pub struct StepEvent<'a> {
+    /// Pipeline being executed
+    pub program: &'a ProgramMetadata,
+
+    /// Step ID
+    pub step_id: &'a str,
+
+    /// Step execution continues with, if any
+    pub next_step_id: Option<&'a str>,
+
+    /// Execution context at the start or end of the step
+    pub context: &'a ExecutionContext,
+}
+
+/// Callbacks around the instructions and steps of an execution
+///
+/// Every callback does nothing by default. Callbacks are awaited in line, so
+/// the execution does not proceed until they return.
+#[async_trait]
+pub trait ExecutionObserver: Send + Sync {
+    /// Called before an instruction executes
+    async fn before_instruction(&self, _event: &InstructionEvent<'_>) {}
+
+    /// Called after an instruction executed
+    async fn after_instruction(&self, _event: &InstructionEvent<'_>) {}
+
+    /// Called when a pipeline step starts, before its first instruction
+    async fn before_step(&self, _event: &StepEvent<'_>) {}
+
+    /// Called when a pipeline step ends
+    async fn after_step(&self, _event: &StepEvent<'_>) {}
+}
+
+/// Reports the instructions and steps of one section to an observer
+pub(crate) struct ObserverCursor<'a> {
+    observer: Option<&'a dyn ExecutionObserver>,
+    program: &'a ProgramMetadata,
+    section: ProgramSection,
+    pending: Option<(usize, &'a Instruction)>,
+    step: Option<(&'a str, Option<&'a str>)>,
+}
+
+impl<'a> ObserverCursor<'a> {
+    /// Start reporting a section of the program described by `program`
+    pub(crate) fn new(
+        observer: Option<&'a dyn ExecutionObserver>,
+        program: &'a ProgramMetadata,
+        section: ProgramSection,
+    ) -> Self {
+        Self {
+            observer,
+            program,
+            section,
+            pending: None,
+            step: None,
+        }
+    }
+
+    /// Report `instruction`, about to execute at `pc`
+    ///
+    /// Completes the previous instruction, and the current step when
+    /// `instruction` starts the next one.
+    pub(crate) async fn enter(
+        &mut self,
+        pc: usize,
+        instruction: &'a Instruction,
+        context: &ExecutionContext,
+    ) {
+        let Some(observer) = self.observer else {
+            return;
+        };
+        self.complete_instruction(observer, context).await;
+
+        if let Instruction::MarkStepExecuted {
+            step_id,
+            next_step_id,
+            ..
+        } = instruction
+        {
+            self.complete_step(observer, context).await;
+            let step = (step_id.as_str(), next_step_id.as_deref());
+            observer.before_step(&self.step_event(step, context)).await;
+            self.step = Some(step);
+        }
+
+        observer
+            .before_instruction(&self.instruction_event(pc, instruction, context))
+            .await;
+        self.pending = Some((pc, instruction));
+    }
+
+    /// Complete the last instruction and step of the section
+    pub(crate) async fn finish(&mut self, context: &ExecutionContext) {
+        if let Some(observer) = self.observer {
+            self.complete_instruction(observer, context).await;
+            self.complete_step(observer, context).await;
+        }
+    }
+
+    async fn complete_instruction(
+        &mut self,
+        observer: &dyn ExecutionObserver,
+        context: &ExecutionContext,
+    ) {
+        if let Some((pc, instruction)) = self.pending.take() {
+            observer
+                .after_instruction(&self.instruction_event(pc, instruction, context))
+                .await;
+        }
+    }
+
+    async fn complete_step(
+        &mut self,
+        observer: &dyn ExecutionObserver,
+        context: &ExecutionContext,
+    ) {
+        if let Some(step) = self.step.take() {
+            observer.after_step(&self.step_event(step, context)).await;
+        }
+    }
+
+    fn instruction_event<'e>(
+        &'e self,
+        pc: usize,
+        instruction: &'e Instruction,
+        context: &'e ExecutionContext,
+    ) -> InstructionEvent<'e> {
+        InstructionEvent {
+            program: self.program,
+            section: self.section,
+            pc,
+            instruction,
+            context,
+        }
+    }
+
+    fn step_event<'e>(
+        &'e self,
+        (step_id, next_step_id): (&'e str, Option<&'e str>),
+        context: &'e ExecutionContext,
+    ) -> StepEvent<'e> {
+        StepEvent {
+            program: self.program,
+            step_id,
+            next_step_id,
+            context,
+        }
+    }
+}
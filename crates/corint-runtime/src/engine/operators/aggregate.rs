@@ -0,0 +1,65 @@
+//! Array aggregate execution
+
+use crate::error::{Result, RuntimeError};
+use corint_core::ast::AggregateOp;
+use corint_core::Value;
+
+/// Execute an aggregate over array elements
+///
+/// Missing (Null) values are skipped. `count` counts non-null values; the
+/// numeric aggregates return Null when there is nothing to aggregate, except
+/// `sum`, which returns 0.
+pub(crate) fn execute_aggregate(
+    op: &AggregateOp,
+    elements: &[Value],
+    path: &[String],
+) -> Result<Value> {
+    let values = elements
+        .iter()
+        .map(|element| element_value(element, path))
+        .filter(|value| !matches!(value, Value::Null));
+
+    if *op == AggregateOp::Count {
+        return Ok(Value::Number(values.count() as f64));
+    }
+
+    let mut numbers = Vec::new();
+    for value in values {
+        match value {
+            Value::Number(n) => numbers.push(*n),
+            other => {
+                return Err(RuntimeError::TypeMismatch {
+                    expr: op.name().to_string(),
+                    message: format!("requires numeric values, got {:?}", other),
+                })
+            }
+        }
+    }
+
+    let result = match op {
+        AggregateOp::Sum => Some(numbers.iter().sum()),
+        AggregateOp::Avg if !numbers.is_empty() => {
+            Some(numbers.iter().sum::<f64>() / numbers.len() as f64)
+        }
+        AggregateOp::Min => numbers.iter().copied().reduce(f64::min),
+        AggregateOp::Max => numbers.iter().copied().reduce(f64::max),
+        _ => None,
+    };
+
+    Ok(result.map(Value::Number).unwrap_or(Value::Null))
+}
+
+/// Follow a field path within a single element, returning Null if it is missing
+fn element_value<'a>(element: &'a Value, path: &[String]) -> &'a Value {
+    let mut current = element;
+    for key in path {
+        current = match current {
+            Value::Object(map) => match map.get(key) {
+                Some(value) => value,
+                None => return &Value::Null,
+            },
+            _ => return &Value::Null,
+        };
+    }
+    current
+}
@@ -0,0 +1,39 @@
+//! Three-valued logical operator execution
+
+use crate::error::{Result, RuntimeError};
+use corint_core::ast::Operator;
+use corint_core::Value;
+
+/// Execute `&&` or `||` with Kleene logic, `Null` standing for Unknown
+pub(crate) fn execute_kleene_op(left: &Value, op: &Operator, right: &Value) -> Result<Value> {
+    let operand = |value: &Value| match value {
+        Value::Bool(b) => Ok(Some(*b)),
+        Value::Null => Ok(None),
+        other => Err(RuntimeError::InvalidOperation(format!(
+            "Cannot apply {:?} to {:?} and {:?}: {:?} is not a boolean",
+            op, left, right, other
+        ))),
+    };
+    let (l, r) = (operand(left)?, operand(right)?);
+
+    let result = match op {
+        Operator::And => match (l, r) {
+            (Some(false), _) | (_, Some(false)) => Some(false),
+            (Some(true), Some(true)) => Some(true),
+            _ => None,
+        },
+        Operator::Or => match (l, r) {
+            (Some(true), _) | (_, Some(true)) => Some(true),
+            (Some(false), Some(false)) => Some(false),
+            _ => None,
+        },
+        _ => {
+            return Err(RuntimeError::InvalidOperation(format!(
+                "{:?} is not a logical operator",
+                op
+            )))
+        }
+    };
+
+    Ok(result.map(Value::Bool).unwrap_or(Value::Null))
+}
@@ -2,10 +2,14 @@
 //!
 //! This module contains the implementation of operator evaluation for the IR executor.
 
+mod aggregate;
 mod binary;
 mod comparison;
+mod logic;
 mod unary;
 
+pub(crate) use aggregate::execute_aggregate;
 pub(crate) use binary::execute_binary_op;
 pub(crate) use comparison::execute_compare;
+pub(crate) use logic::execute_kleene_op;
 pub(crate) use unary::execute_unary_op;
@@ -9,6 +9,8 @@ pub(crate) fn execute_unary_op(operand: &Value, op: &UnaryOperator) -> Result<Va
     match (op, operand) {
         (UnaryOperator::Not, Value::Bool(b)) => Ok(Value::Bool(!b)),
         (UnaryOperator::Negate, Value::Number(n)) => Ok(Value::Number(-n)),
+        (UnaryOperator::IsNull, value) => Ok(Value::Bool(matches!(value, Value::Null))),
+        (UnaryOperator::IsNotNull, value) => Ok(Value::Bool(!matches!(value, Value::Null))),
         _ => Err(RuntimeError::InvalidOperation(format!(
             "Cannot apply {:?} to {:?}",
             op, operand
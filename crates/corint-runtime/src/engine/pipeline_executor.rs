@@ -6,21 +6,43 @@
 #[path = "tests/mod.rs"]
 mod tests;
 
+use super::budget::{self, Deadline, ExecutionBudget, TimeoutEvent, TimeoutScope};
+use super::limits::{ExecutionLimits, InstructionGuard};
+use super::null_semantics::NullSemantics;
+use super::observer::{ExecutionObserver, ObserverCursor, ProgramSection};
 use super::operators;
+use crate::circuit_breaker::CircuitBreakerStats;
 use crate::context::ExecutionContext;
+use crate::custom_step::{CustomCall, CustomStepHandler};
+use crate::decision_replay::{CapturedInputs, InputCapture};
+use crate::emit::{QueuedEmit, QUEUED_EMITS_VARIABLE};
 use crate::error::{Result, RuntimeError};
 use crate::external_api::ExternalApiClient;
 use crate::feature::{FeatureExecutor, FeatureExtractor};
-use crate::observability::{Metrics, MetricsCollector};
+use crate::lists::ListHit;
+use crate::memo::{self, MemoKey};
+use crate::observability::profile::InstructionTimer;
+use crate::observability::{Metrics, MetricsCollector, ProfileScope};
 use crate::result::{DecisionResult, ExecutionResult};
 use crate::service::ServiceClient;
 use crate::storage::Storage;
-use corint_core::ir::{FeatureType, Instruction, Program};
+use crate::tenant::{self, TenantPermit, TenantQuotas, TenantStats};
+use corint_core::ast::{AggregateOp, Operator, QuantifierOp, UnaryOperator};
+use corint_core::ir::{FeatureType, Instruction, Program, ProgramMetadata};
 use corint_core::Value;
 use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
 use std::sync::Arc;
 use std::time::Instant;
 
+/// Default maximum number of array elements an aggregate, or an `any_of`/`all_of`
+/// together with the quantifiers nested in its predicate, may visit
+pub const DEFAULT_MAX_ITERATIONS: usize = 1000;
+
+/// Default number of events of a batch executed concurrently
+pub const DEFAULT_BATCH_CONCURRENCY: usize = 8;
+
 /// Pipeline executor for async IR execution
 pub struct PipelineExecutor {
     feature_extractor: Option<Arc<FeatureExtractor>>,
@@ -29,6 +51,14 @@ pub struct PipelineExecutor {
     external_api_client: Arc<ExternalApiClient>,
     list_service: Option<Arc<crate::lists::ListService>>,
     metrics: Arc<MetricsCollector>,
+    max_iterations: usize,
+    limits: ExecutionLimits,
+    observer: Option<Arc<dyn ExecutionObserver>>,
+    profiling: bool,
+    null_semantics: NullSemantics,
+    batch_concurrency: usize,
+    tenant_quotas: Option<Arc<TenantQuotas>>,
+    custom_handlers: HashMap<String, Arc<dyn CustomStepHandler>>,
 }
 
 impl PipelineExecutor {
@@ -41,6 +71,14 @@ impl PipelineExecutor {
             external_api_client: Arc::new(ExternalApiClient::new()),
             list_service: None,
             metrics: Arc::new(MetricsCollector::new()),
+            max_iterations: DEFAULT_MAX_ITERATIONS,
+            limits: ExecutionLimits::default(),
+            observer: None,
+            profiling: false,
+            null_semantics: NullSemantics::default(),
+            batch_concurrency: DEFAULT_BATCH_CONCURRENCY,
+            tenant_quotas: None,
+            custom_handlers: HashMap::new(),
         }
     }
 
@@ -53,11 +91,20 @@ impl PipelineExecutor {
             external_api_client: Arc::new(ExternalApiClient::new()),
             list_service: None,
             metrics: Arc::new(MetricsCollector::new()),
+            max_iterations: DEFAULT_MAX_ITERATIONS,
+            limits: ExecutionLimits::default(),
+            observer: None,
+            profiling: false,
+            null_semantics: NullSemantics::default(),
+            batch_concurrency: DEFAULT_BATCH_CONCURRENCY,
+            tenant_quotas: None,
+            custom_handlers: HashMap::new(),
         }
     }
 
     /// Set feature executor for lazy feature calculation
     pub fn with_feature_executor(mut self, executor: Arc<FeatureExecutor>) -> Self {
+        executor.attach_metrics(Arc::clone(&self.metrics));
         self.feature_executor = Some(executor);
         self
     }
@@ -70,6 +117,7 @@ impl PipelineExecutor {
 
     /// Set external API client
     pub fn with_external_api_client(mut self, client: Arc<ExternalApiClient>) -> Self {
+        client.attach_metrics(Arc::clone(&self.metrics));
         self.external_api_client = client;
         self
     }
@@ -80,11 +128,115 @@ impl PipelineExecutor {
         self
     }
 
+    /// Set the maximum number of array elements an aggregate, or an outermost
+    /// `any_of`/`all_of` including its nested quantifiers, may visit
+    pub fn with_max_iterations(mut self, max_iterations: usize) -> Self {
+        self.max_iterations = max_iterations;
+        self
+    }
+
+    /// Set the instruction and wall-clock limits of a program execution
+    pub fn with_execution_limits(mut self, limits: ExecutionLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Set an observer called around every instruction and step executed
+    pub fn with_observer(mut self, observer: Arc<dyn ExecutionObserver>) -> Self {
+        self.observer = Some(observer);
+        self
+    }
+
+    /// Time instructions, programs and feature loads into the profile of the metrics collector
+    ///
+    /// Off by default: it adds a clock read to every instruction.
+    pub fn with_profiling(mut self, enabled: bool) -> Self {
+        self.profiling = enabled;
+        self
+    }
+
+    /// Set how comparisons and logical operators treat `Null`
+    pub fn with_null_semantics(mut self, semantics: NullSemantics) -> Self {
+        self.null_semantics = semantics;
+        self
+    }
+
+    /// Set the number of events of a batch executed concurrently
+    pub fn with_batch_concurrency(mut self, concurrency: usize) -> Self {
+        self.batch_concurrency = concurrency.max(1);
+        self
+    }
+
+    /// Admit the executions of each tenant under its quota
+    pub fn with_tenant_quotas(mut self, quotas: Arc<TenantQuotas>) -> Self {
+        self.tenant_quotas = Some(quotas);
+        self
+    }
+
+    /// Register the handler of the `CallCustom` instructions naming `name`,
+    /// compiled from the steps of a custom type
+    pub fn with_custom_handler(
+        mut self,
+        name: impl Into<String>,
+        handler: Arc<dyn CustomStepHandler>,
+    ) -> Self {
+        self.custom_handlers.insert(name.into(), handler);
+        self
+    }
+
     /// Get metrics collector
     pub fn metrics(&self) -> Arc<MetricsCollector> {
         Arc::clone(&self.metrics)
     }
 
+    /// Circuit breaker statistics of the external API endpoints and data sources
+    pub fn circuit_breaker_stats(&self) -> Vec<CircuitBreakerStats> {
+        let mut stats = self.external_api_client.circuit_breaker_stats();
+        if let Some(executor) = &self.feature_executor {
+            stats.extend(executor.circuit_breaker_stats());
+        }
+        stats
+    }
+
+    /// Quota statistics of the tenants that executed programs
+    pub fn tenant_stats(&self) -> Vec<TenantStats> {
+        self.tenant_quotas
+            .as_deref()
+            .map(TenantQuotas::stats)
+            .unwrap_or_default()
+    }
+
+    /// Admit a decision of `tenant` under its quota
+    ///
+    /// Programs executed within [`TenantPermit::scope`] for the same tenant
+    /// are not admitted again, so a decision that executes a pipeline and the
+    /// rulesets and rules it routes to counts once. Programs executed outside
+    /// it for an input with a tenant are admitted one by one.
+    pub async fn admit_tenant(&self, tenant: &str) -> Result<TenantPermit> {
+        let admitted = match &self.tenant_quotas {
+            Some(quotas) => quotas.admit(tenant).await,
+            None => Ok(TenantPermit::unlimited(tenant)),
+        };
+        let label = match &self.tenant_quotas {
+            Some(quotas) => quotas.config().metric_label(tenant),
+            None => "default",
+        };
+        match &admitted {
+            Ok(_) => self
+                .metrics
+                .labeled_counter("tenant_admitted_total", &[("tenant", label)])
+                .inc(),
+            Err(e) => self
+                .metrics
+                .labeled_counter(
+                    "tenant_rejected_total",
+                    &[("tenant", label), ("reason", e.code())],
+                )
+                .inc(),
+        }
+        admitted
+    }
+
     /// Execute an IR program with the given event data
     pub async fn execute(
         &self,
@@ -96,26 +248,166 @@ impl PipelineExecutor {
             .await
     }
 
+    /// Re-execute an IR program with the inputs captured from a past execution
+    ///
+    /// Features, call results and list lookups are answered from `inputs`;
+    /// nothing is fetched, so the program decides exactly as it did then,
+    /// unless the program itself changed. An input the program consumes that
+    /// was not captured fails the replay.
+    pub async fn replay(
+        &self,
+        program: &Program,
+        inputs: &CapturedInputs,
+    ) -> Result<DecisionResult> {
+        let capture = Arc::new(InputCapture::pin(inputs.clone()));
+        capture
+            .scope(self.execute(program, inputs.event.clone()))
+            .await
+    }
+
+    /// Execute an IR program for each of many events, for backfill and scoring jobs
+    ///
+    /// The lookup features the program loads are fetched for all events with
+    /// one query per datasource before any event is executed. Events are then
+    /// executed concurrently, each as its own request with its own
+    /// [`RequestMemo`](crate::memo::RequestMemo); one event failing does not
+    /// fail the others. Results are in the order of `inputs`.
+    pub async fn execute_batch(
+        &self,
+        program: &Program,
+        inputs: Vec<crate::ContextInput>,
+    ) -> Vec<Result<DecisionResult>> {
+        use futures::StreamExt;
+
+        if let Some(executor) = &self.feature_executor {
+            let features: Vec<String> = program
+                .feature_references()
+                .into_iter()
+                .filter(|name| executor.has_feature(name))
+                .collect();
+            let events: Vec<_> = inputs.iter().map(|input| &input.event).collect();
+            if !features.is_empty() {
+                if let Err(e) = executor.prefetch_features(&features, &events).await {
+                    tracing::warn!("Failed to prefetch features for a batch: {}", e);
+                }
+            }
+        }
+
+        futures::stream::iter(inputs)
+            .map(|input| {
+                Arc::new(memo::RequestMemo::new())
+                    .scope(self.execute_with_result(program, input, ExecutionResult::new()))
+            })
+            .buffered(self.batch_concurrency)
+            .collect()
+            .await
+    }
+
     /// Execute an IR program with the given context input and existing result state
+    ///
+    /// An error raised evaluating a condition names the condition, and the
+    /// file and line it is defined at when the program's source map has them.
     pub async fn execute_with_result(
         &self,
         program: &Program,
         context_input: crate::ContextInput,
         existing_result: ExecutionResult,
     ) -> Result<DecisionResult> {
+        let mut failed_at = None;
+        self.run_program(program, context_input, existing_result, &mut failed_at)
+            .await
+            .map_err(|e| match failed_at {
+                Some(pc) => e.at_instruction(program, pc),
+                None => e,
+            })
+    }
+
+    /// Execute the instructions of a program, keeping in `failed_at` the
+    /// position of the main instruction being executed
+    async fn run_program(
+        &self,
+        program: &Program,
+        context_input: crate::ContextInput,
+        existing_result: ExecutionResult,
+        failed_at: &mut Option<usize>,
+    ) -> Result<DecisionResult> {
+        let _tenant_permit = match &context_input.tenant {
+            Some(tenant) if tenant::current_tenant().as_deref() != Some(tenant.as_str()) => {
+                Some(self.admit_tenant(tenant).await?)
+            }
+            _ => None,
+        };
         let start_time = Instant::now();
         self.metrics.counter("executions_total").inc();
 
+        let initial_score = existing_result.score;
         let mut ctx = ExecutionContext::with_result(context_input, existing_result)?;
         let mut pc = 0; // Program Counter
 
+        // Deadlines of the request and the pipeline; a pipeline's also bounds
+        // the rulesets and rules it routes to, executed after it
+        let budget = budget::current_budget();
+        let pipeline_timeout = program
+            .metadata
+            .custom
+            .get("timeout_ms")
+            .and_then(|ms| ms.parse::<u64>().ok());
+        let mut deadlines = Vec::new();
+        if program.metadata.source_type == "pipeline" {
+            match &budget {
+                Some(budget) => {
+                    budget.enter_pipeline(&program.metadata.source_id, pipeline_timeout)
+                }
+                None => deadlines.extend(pipeline_timeout.map(|ms| {
+                    Deadline::new(
+                        TimeoutScope::Pipeline,
+                        Some(program.metadata.source_id.clone()),
+                        ms,
+                    )
+                })),
+            }
+        }
+        if let Some(budget) = &budget {
+            deadlines.extend(budget.deadlines());
+        }
+        let step_timeouts = Self::step_timeouts(&program.metadata);
+        let mut step_deadline: Option<Deadline> = None;
+        let mut guard = InstructionGuard::new(&self.limits, &program.metadata);
+        let mut cursor = ObserverCursor::new(
+            self.observer.as_deref(),
+            &program.metadata,
+            ProgramSection::Main,
+        );
+        let mut timer = InstructionTimer::new(self.profiling);
+
         tracing::debug!("Program has {} instructions", program.instructions.len());
         for (i, inst) in program.instructions.iter().enumerate() {
             tracing::trace!("  [{}]: {:?}", i, inst);
         }
         while pc < program.instructions.len() {
             let instruction = &program.instructions[pc];
+            *failed_at = Some(pc);
             tracing::trace!("Executing pc={}: {:?}", pc, instruction);
+            self.check_limits(&mut guard, pc, instruction)?;
+            cursor.enter(pc, instruction, &ctx).await;
+            timer.enter(instruction.opcode());
+
+            // Once out of time, skip the rest of the program, but still make
+            // the decision from what has been gathered
+            let checkpoint = pc == 0
+                || matches!(
+                    instruction,
+                    Instruction::MarkStepExecuted { .. }
+                        | Instruction::CallService { .. }
+                        | Instruction::CallExternal { .. }
+                        | Instruction::CallCustom { .. }
+                );
+            if checkpoint {
+                if let Some(expired) = deadlines.iter().find(|d| d.is_expired()) {
+                    self.record_timeout(budget.as_deref(), expired.event());
+                    break;
+                }
+            }
 
             match instruction {
                 Instruction::LoadField { path } => {
@@ -169,7 +461,7 @@ impl PipelineExecutor {
                 Instruction::BinaryOp { op } => {
                     let right = ctx.pop()?;
                     let left = ctx.pop()?;
-                    let result = operators::execute_binary_op(&left, op, &right)?;
+                    let result = self.binary_op(&left, op, &right)?;
                     ctx.push(result);
                     pc += 1;
                 }
@@ -178,15 +470,15 @@ impl PipelineExecutor {
                     let right = ctx.pop()?;
                     let left = ctx.pop()?;
                     tracing::trace!("Compare {:?} {:?} {:?}", left, op, right);
-                    let result = operators::execute_compare(&left, op, &right)?;
-                    tracing::trace!("Compare result: {}", result);
-                    ctx.push(Value::Bool(result));
+                    let result = self.compare(&left, op, &right)?;
+                    tracing::trace!("Compare result: {:?}", result);
+                    ctx.push(result);
                     pc += 1;
                 }
 
                 Instruction::UnaryOp { op } => {
                     let operand = ctx.pop()?;
-                    let result = operators::execute_unary_op(&operand, op)?;
+                    let result = self.unary_op(&operand, op)?;
                     ctx.push(result);
                     pc += 1;
                 }
@@ -310,6 +602,31 @@ impl PipelineExecutor {
                     pc += 1;
                 }
 
+                Instruction::AggregateScore { rules, scoring } => {
+                    let scores: Vec<(&str, i32)> = rules
+                        .iter()
+                        .filter_map(|rule_id| {
+                            match ctx.load_variable(&format!("__rule_score__.{}", rule_id)) {
+                                Ok(Value::Number(score)) => Some((rule_id.as_str(), score as i32)),
+                                _ => None,
+                            }
+                        })
+                        .collect();
+                    let summed: i32 = scores.iter().map(|(_, score)| score).sum();
+                    let aggregated = scoring.aggregate(&scores);
+                    tracing::debug!(
+                        "AggregateScore: {:?} of {:?} = {} (summed: {})",
+                        scoring.strategy,
+                        scores,
+                        aggregated,
+                        summed
+                    );
+
+                    // Rules of earlier rulesets keep contributing to the score
+                    ctx.set_score(ctx.result.score - summed + aggregated);
+                    pc += 1;
+                }
+
                 Instruction::SetSignal { signal } => {
                     tracing::debug!("SetSignal called with signal: {:?}", signal);
                     ctx.set_signal(signal.clone());
@@ -326,6 +643,12 @@ impl PipelineExecutor {
                     pc += 1;
                 }
 
+                Instruction::SetReasonCode { code } => {
+                    tracing::debug!("SetReasonCode called with code: {}", code);
+                    ctx.set_reason_code(code.clone());
+                    pc += 1;
+                }
+
                 Instruction::SetActions { actions } => {
                     tracing::debug!("SetActions called with actions: {:?}", actions);
                     ctx.set_actions(actions.clone());
@@ -394,6 +717,95 @@ impl PipelineExecutor {
                         route_index,
                         is_default_route
                     );
+                    step_deadline = step_timeouts.get(step_id).map(|ms| {
+                        Deadline::new(TimeoutScope::Step, Some(step_id.clone()), *ms)
+                    });
+                    pc += 1;
+                }
+
+                Instruction::Emit {
+                    step_id,
+                    kind,
+                    target,
+                    fields,
+                    signals,
+                } => {
+                    // Queue the side effect; it is dispatched once the decision is final
+                    let mut values = Vec::with_capacity(fields.len());
+                    for _ in fields {
+                        values.push(ctx.pop()?);
+                    }
+                    let queued = QueuedEmit {
+                        step_id: step_id.clone(),
+                        kind: *kind,
+                        target: target.clone(),
+                        payload: fields.iter().cloned().zip(values.into_iter().rev()).collect(),
+                        signals: signals.clone(),
+                    };
+
+                    let mut queued_emits = match ctx.load_variable(QUEUED_EMITS_VARIABLE) {
+                        Ok(Value::Array(arr)) => arr,
+                        _ => Vec::new(),
+                    };
+                    queued_emits.push(Value::String(
+                        serde_json::to_string(&queued).map_err(|e| {
+                            RuntimeError::RuntimeError(format!(
+                                "Failed to queue emit of step {}: {}",
+                                step_id, e
+                            ))
+                        })?,
+                    ));
+                    ctx.store_variable(
+                        QUEUED_EMITS_VARIABLE.to_string(),
+                        Value::Array(queued_emits),
+                    );
+
+                    tracing::debug!("Emit queued: {} -> {:?} {}", step_id, kind, target);
+                    pc += 1;
+                }
+
+                Instruction::CallCustom {
+                    handler,
+                    step_id,
+                    args,
+                } => {
+                    let mut values = Vec::with_capacity(args.len());
+                    for _ in args {
+                        values.push(ctx.pop()?);
+                    }
+                    let target = format!("custom:{}.{}", handler, step_id);
+                    if let Some(capture) = crate::decision_replay::current_pinned() {
+                        ctx.push(capture.pinned_call(&target)?);
+                        pc += 1;
+                        continue;
+                    }
+
+                    let custom = self.custom_handlers.get(handler).ok_or_else(|| {
+                        RuntimeError::InvalidOperation(format!(
+                            "No handler registered for custom step type '{}'",
+                            handler
+                        ))
+                    })?;
+                    let call = CustomCall {
+                        step_id: step_id.clone(),
+                        args: args.iter().cloned().zip(values.into_iter().rev()).collect(),
+                    };
+
+                    let custom_start = Instant::now();
+                    let deadline = Deadline::earliest(deadlines.iter().chain(&step_deadline));
+                    let value = match Self::within(deadline, custom.call(call)).await {
+                        Some(result) => result?,
+                        None => {
+                            if let Some(deadline) = deadline {
+                                self.record_timeout(budget.as_deref(), deadline.event());
+                            }
+                            Value::Null
+                        }
+                    };
+                    self.metrics
+                        .record_execution_time("custom_call", custom_start.elapsed());
+                    Self::capture_call(&target, &value);
+                    ctx.push(value);
                     pc += 1;
                 }
 
@@ -431,19 +843,41 @@ impl PipelineExecutor {
                     // Pop the value to check from the stack
                     let value = ctx.pop()?;
 
-                    // Use configured list service if available, otherwise fall back to empty in-memory
-                    let contains = if let Some(ref list_service) = self.list_service {
-                        list_service.contains(list_id, &value).await?
-                    } else {
-                        tracing::warn!("List service not configured, treating all lists as empty");
-                        false
-                    };
+                    let result = self
+                        .list_membership(&program.metadata, list_id, &value, *negate)
+                        .await?;
 
-                    // Apply negation if needed
-                    let result = if *negate { !contains } else { contains };
+                    // Push the boolean (or Unknown) result onto the stack
+                    ctx.push(result);
+                    pc += 1;
+                }
 
-                    // Push the boolean result onto the stack
-                    ctx.push(Value::Bool(result));
+                Instruction::IterateArray {
+                    op,
+                    binding,
+                    predicate,
+                } => {
+                    let collection = ctx.pop()?;
+                    let mut iterations = self.max_iterations;
+                    let result = self
+                        .evaluate_quantifier(
+                            &mut ctx,
+                            &program.metadata,
+                            *op,
+                            binding,
+                            predicate,
+                            collection,
+                            &mut iterations,
+                            &mut guard,
+                        )
+                        .await?;
+                    ctx.push(result);
+                    pc += 1;
+                }
+
+                Instruction::AggregateArray { op, path } => {
+                    let collection = ctx.pop()?;
+                    ctx.push(self.evaluate_aggregate(*op, path, collection)?);
                     pc += 1;
                 }
 
@@ -489,6 +923,38 @@ impl PipelineExecutor {
                     pc += 1;
                 }
 
+                Instruction::Declare { name } => {
+                    let value = ctx.pop()?;
+                    ctx.declare_var(name, value)?;
+                    pc += 1;
+                }
+
+                Instruction::Assign { name } => {
+                    let value = ctx.pop()?;
+                    ctx.assign_var(name, value)?;
+                    pc += 1;
+                }
+
+                // Shared slots, computed once per request by the first program reaching them
+                Instruction::LoadSlot { slot, offset } => {
+                    let memo = memo::current_memo();
+                    let key = MemoKey::Slot(slot.clone());
+                    match self.memoized(memo.as_deref(), &key) {
+                        Some(value) => {
+                            ctx.push(value);
+                            pc = (pc as isize + offset) as usize;
+                        }
+                        None => pc += 1,
+                    }
+                }
+
+                Instruction::StoreSlot { slot } => {
+                    if let Some(memo) = memo::current_memo() {
+                        memo.insert(MemoKey::Slot(slot.clone()), ctx.peek()?.clone());
+                    }
+                    pc += 1;
+                }
+
                 // Feature extraction
                 Instruction::CallFeature {
                     feature_type,
@@ -496,16 +962,35 @@ impl PipelineExecutor {
                     filter: _,
                     time_window,
                 } => {
-                    let value = if let Some(ref extractor) = self.feature_extractor {
-                        // Real feature extraction with storage
-                        // TODO: Convert filter expression to EventFilter
-                        extractor
-                            .extract(feature_type, field, time_window, None)
-                            .await?
-                    } else {
-                        // Fallback: return placeholder
-                        Self::placeholder_feature(feature_type)
+                    let target = format!("extract:{:?}:{}", feature_type, field.join("."));
+                    if let Some(capture) = crate::decision_replay::current_pinned() {
+                        ctx.push(capture.pinned_call(&target)?);
+                        pc += 1;
+                        continue;
+                    }
+
+                    let memo = memo::current_memo();
+                    let key = MemoKey::Extract(format!("{}:{:?}", target, time_window));
+                    let value = match self.memoized(memo.as_deref(), &key) {
+                        Some(value) => value,
+                        None => {
+                            let value = if let Some(ref extractor) = self.feature_extractor {
+                                // Real feature extraction with storage
+                                // TODO: Convert filter expression to EventFilter
+                                extractor
+                                    .extract(feature_type, field, time_window, None)
+                                    .await?
+                            } else {
+                                // Fallback: return placeholder
+                                Self::placeholder_feature(feature_type)
+                            };
+                            if let Some(memo) = &memo {
+                                memo.insert(key, value.clone());
+                            }
+                            value
+                        }
                     };
+                    Self::capture_call(&target, &value);
                     ctx.push(value);
                     pc += 1;
                 }
@@ -516,6 +1001,13 @@ impl PipelineExecutor {
                     operation,
                     params,
                 } => {
+                    let target = format!("service:{}.{}", service, operation);
+                    if let Some(capture) = crate::decision_replay::current_pinned() {
+                        ctx.push(capture.pinned_call(&target)?);
+                        pc += 1;
+                        continue;
+                    }
+
                     let service_start = Instant::now();
                     let value = if let Some(ref client) = self.service_client {
                         use crate::service::ServiceRequest;
@@ -524,21 +1016,30 @@ impl PipelineExecutor {
                         for (k, v) in params {
                             request = request.with_param(k.clone(), v.clone());
                         }
-                        match client.call(request).await {
-                            Ok(response) => {
+                        let deadline = Deadline::earliest(deadlines.iter().chain(&step_deadline));
+                        match Self::within(deadline, client.call(request)).await {
+                            Some(Ok(response)) => {
                                 self.metrics.counter("service_calls_success").inc();
                                 response.data
                             }
-                            Err(e) => {
+                            Some(Err(e)) => {
                                 self.metrics.counter("service_calls_error").inc();
                                 Value::String(format!("Service Error: {}", e))
                             }
+                            None => {
+                                self.metrics.counter("service_calls_error").inc();
+                                if let Some(deadline) = deadline {
+                                    self.record_timeout(budget.as_deref(), deadline.event());
+                                }
+                                Value::Null
+                            }
                         }
                     } else {
                         Value::Null
                     };
                     self.metrics
                         .record_execution_time("service_call", service_start.elapsed());
+                    Self::capture_call(&target, &value);
                     ctx.push(value);
                     pc += 1;
                 }
@@ -551,19 +1052,47 @@ impl PipelineExecutor {
                     timeout,
                     fallback,
                 } => {
+                    let target = format!("api:{}::{}", api, endpoint);
+                    if let Some(capture) = crate::decision_replay::current_pinned() {
+                        ctx.push(capture.pinned_call(&target)?);
+                        pc += 1;
+                        continue;
+                    }
+
                     let api_start = Instant::now();
 
                     // Call external API using the generic client
-                    let value = match self
+                    let deadline = Deadline::earliest(deadlines.iter().chain(&step_deadline));
+                    let call = self
                         .external_api_client
-                        .call(api, endpoint, params, *timeout, &ctx)
-                        .await
-                    {
-                        Ok(result) => {
+                        .call(api, endpoint, params, *timeout, &ctx);
+                    let value = match Self::within(deadline, call).await {
+                        Some(Ok(result)) => {
                             tracing::debug!("External API {}::{} succeeded", api, endpoint);
                             result
                         }
-                        Err(e) => {
+                        None => {
+                            if let Some(deadline) = deadline {
+                                self.record_timeout(budget.as_deref(), deadline.event());
+                            }
+                            fallback.clone().unwrap_or(Value::Null)
+                        }
+                        Some(Err(e)) => {
+                            if matches!(e, RuntimeError::Timeout(_)) {
+                                let budget_ms = self
+                                    .external_api_client
+                                    .effective_timeout_ms(api, endpoint, *timeout)
+                                    .unwrap_or_default();
+                                self.record_timeout(
+                                    budget.as_deref(),
+                                    TimeoutEvent {
+                                        scope: TimeoutScope::Call,
+                                        id: Some(format!("{}::{}", api, endpoint)),
+                                        budget_ms,
+                                        elapsed_ms: api_start.elapsed().as_millis() as u64,
+                                    },
+                                );
+                            }
                             tracing::warn!(
                                 "External API {}::{} failed: {}, using fallback",
                                 api,
@@ -580,12 +1109,17 @@ impl PipelineExecutor {
 
                     self.metrics
                         .record_execution_time("external_api_call", api_start.elapsed());
+                    Self::capture_call(&target, &value);
                     ctx.push(value);
                     pc += 1;
                 }
             }
         }
 
+        *failed_at = None;
+        cursor.finish(&ctx).await;
+        timer.complete();
+
         // Execute decision logic if present
         if let Some(ref decision_instructions) = program.decision_instructions {
             tracing::debug!(
@@ -593,10 +1127,18 @@ impl PipelineExecutor {
                 decision_instructions.len()
             );
 
+            let mut cursor = ObserverCursor::new(
+                self.observer.as_deref(),
+                &program.metadata,
+                ProgramSection::Decision,
+            );
             let mut decision_pc = 0;
             while decision_pc < decision_instructions.len() {
                 let instruction = &decision_instructions[decision_pc];
                 tracing::trace!("Decision pc={}: {:?}", decision_pc, instruction);
+                self.check_limits(&mut guard, decision_pc, instruction)?;
+                cursor.enter(decision_pc, instruction, &ctx).await;
+                timer.enter(instruction.opcode());
 
                 match instruction {
                     Instruction::LoadField { path } => {
@@ -650,8 +1192,8 @@ impl PipelineExecutor {
                     Instruction::Compare { op } => {
                         let right = ctx.pop()?;
                         let left = ctx.pop()?;
-                        let result = operators::execute_compare(&left, op, &right)?;
-                        ctx.push(Value::Bool(result));
+                        let result = self.compare(&left, op, &right)?;
+                        ctx.push(result);
                         decision_pc += 1;
                     }
 
@@ -680,6 +1222,12 @@ impl PipelineExecutor {
                         decision_pc += 1;
                     }
 
+                    Instruction::SetReasonCode { code } => {
+                        tracing::debug!("Decision: SetReasonCode {}", code);
+                        ctx.set_reason_code(code.clone());
+                        decision_pc += 1;
+                    }
+
                     Instruction::SetActions { actions } => {
                         tracing::debug!("Decision: SetActions {:?}", actions);
                         ctx.set_actions(actions.clone());
@@ -698,15 +1246,375 @@ impl PipelineExecutor {
                     }
                 }
             }
+            cursor.finish(&ctx).await;
+        }
+
+        // Keep what each rule scored, for rulesets aggregating their rule scores
+        if program.metadata.source_type == "rule" {
+            ctx.store_variable(
+                format!("__rule_score__.{}", program.metadata.source_id),
+                Value::Number(f64::from(ctx.result.score - initial_score)),
+            );
         }
 
         let duration = start_time.elapsed();
         self.metrics
             .record_execution_time("program_execution", duration);
+        if self.profiling {
+            let profile = self.metrics.profile();
+            timer.flush(profile);
+            if let Some(scope) = ProfileScope::for_source_type(&program.metadata.source_type) {
+                profile.record(scope, &program.metadata.source_id, duration);
+            }
+        }
 
         Ok(ctx.into_decision_result())
     }
 
+    /// Result of `key` memoized earlier in the request, if any
+    fn memoized(&self, memo: Option<&memo::RequestMemo>, key: &MemoKey) -> Option<Value> {
+        let value = memo?.get(key)?;
+        self.metrics
+            .labeled_counter("memo_hits_total", &[("kind", key.kind())])
+            .inc();
+        Some(value)
+    }
+
+    /// Record a call result consumed by the program, when capturing inputs
+    fn capture_call(target: &str, value: &Value) {
+        if let Some(capture) = crate::decision_replay::current_capture() {
+            capture.record_call(target, value);
+        }
+    }
+
+    /// Abort a program about to exceed its execution limits
+    fn check_limits(
+        &self,
+        guard: &mut InstructionGuard<'_>,
+        pc: usize,
+        instruction: &Instruction,
+    ) -> Result<()> {
+        guard.check(pc, instruction).inspect_err(|e| {
+            self.metrics.counter("execution_limit_exceeded_total").inc();
+            tracing::error!("Aborted runaway program: {}", e);
+        })
+    }
+
+    /// Step timeouts in milliseconds, by step ID, from the program metadata
+    ///
+    /// Set by the compiler as `step_timeouts`, a comma-separated list of
+    /// `step_id=ms` pairs.
+    fn step_timeouts(metadata: &ProgramMetadata) -> HashMap<String, u64> {
+        metadata
+            .custom
+            .get("step_timeouts")
+            .map(|timeouts| {
+                timeouts
+                    .split(',')
+                    .filter_map(|pair| {
+                        let (step_id, ms) = pair.split_once('=')?;
+                        Some((step_id.to_string(), ms.parse().ok()?))
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Await `future` until `deadline`, if any; `None` if the deadline passed first
+    async fn within<F: Future>(deadline: Option<&Deadline>, future: F) -> Option<F::Output> {
+        match deadline {
+            Some(deadline) if deadline.is_expired() => None,
+            Some(deadline) => tokio::time::timeout(deadline.remaining(), future).await.ok(),
+            None => Some(future.await),
+        }
+    }
+
+    /// Log, count and, when a budget is active, record that a scope ran out of time
+    fn record_timeout(&self, budget: Option<&ExecutionBudget>, event: TimeoutEvent) {
+        tracing::warn!(
+            "{} {} timed out after {}ms (budget {}ms), continuing with partial results",
+            event.scope.as_str(),
+            event.id.as_deref().unwrap_or("decision"),
+            event.elapsed_ms,
+            event.budget_ms
+        );
+        self.metrics
+            .labeled_counter("timeouts_total", &[("scope", event.scope.as_str())])
+            .inc();
+        if let Some(budget) = budget {
+            budget.record(event);
+        }
+    }
+
+    /// Check list membership, applying negation for "not in list"
+    ///
+    /// Counts lookups, hits and misses per list and, when the request audits
+    /// list hits, records a hit for the program `source`.
+    async fn lookup_list(
+        &self,
+        source: &ProgramMetadata,
+        list_id: &str,
+        value: &Value,
+        negate: bool,
+    ) -> Result<bool> {
+        // Replayed decisions use the outcome the original lookup had
+        let capture = crate::decision_replay::current_capture();
+        if let Some(capture) = capture.as_ref().filter(|capture| capture.is_pinned()) {
+            let contains = capture.pinned_lookup(list_id, value)?;
+            return Ok(if negate { !contains } else { contains });
+        }
+
+        // Use configured list service if available, otherwise fall back to empty in-memory
+        let contains = if let Some(ref list_service) = self.list_service {
+            // Replays and backtests look lists up as of the original event time
+            let scope = crate::lists::history::current_scope();
+            let as_of = scope.as_ref().and_then(|scope| scope.as_of());
+
+            // Looked up once per request, however many rules consult the list
+            let memo = memo::current_memo();
+            let key = MemoKey::list_lookup(list_id, value);
+            let contains = match self.memoized(memo.as_deref(), &key) {
+                Some(Value::Bool(contains)) => contains,
+                _ => {
+                    let contains = match as_of {
+                        Some(at) => list_service.contains_at(list_id, value, at).await?,
+                        None => list_service.contains(list_id, value).await?,
+                    };
+
+                    let labels = [("list_id", list_id)];
+                    self.metrics.labeled_counter("list_lookups_total", &labels).inc();
+                    let outcome = if contains { "list_hits_total" } else { "list_misses_total" };
+                    self.metrics.labeled_counter(outcome, &labels).inc();
+                    if let Some(memo) = &memo {
+                        memo.insert(key, Value::Bool(contains));
+                    }
+                    contains
+                }
+            };
+
+            if let Some(scope) = scope {
+                let version = match as_of {
+                    Some(at) => list_service.version_at(list_id, at),
+                    None => list_service.version(list_id),
+                };
+                scope.record(list_id, version);
+                if contains && scope.audits_hits() {
+                    scope.record_hit(ListHit {
+                        list_id: list_id.to_string(),
+                        value: value.clone(),
+                        source_type: source.source_type.clone(),
+                        source_id: source.source_id.clone(),
+                        negated: negate,
+                        version,
+                        at: as_of.unwrap_or_else(chrono::Utc::now),
+                    });
+                }
+            }
+            contains
+        } else {
+            tracing::warn!("List service not configured, treating all lists as empty");
+            false
+        };
+        if let Some(capture) = capture {
+            capture.record_lookup(list_id, value, contains);
+        }
+
+        Ok(if negate { !contains } else { contains })
+    }
+
+    /// Unwrap an array operand of at most `limit` elements
+    ///
+    /// A missing (Null) collection behaves like an empty array.
+    fn array_elements(&self, name: &str, collection: Value, limit: usize) -> Result<Vec<Value>> {
+        let elements = match collection {
+            Value::Array(arr) => arr,
+            Value::Null => Vec::new(),
+            other => {
+                return Err(RuntimeError::TypeMismatch {
+                    expr: name.to_string(),
+                    message: format!("requires an array, got {:?}", other),
+                })
+            }
+        };
+
+        if elements.len() > limit {
+            return Err(RuntimeError::InvalidOperation(format!(
+                "{} over {} elements exceeds the iteration limit of {}",
+                name,
+                elements.len(),
+                self.max_iterations
+            )));
+        }
+
+        Ok(elements)
+    }
+
+    /// Evaluate a count/sum/avg/min/max aggregate over the elements of an array
+    fn evaluate_aggregate(
+        &self,
+        op: AggregateOp,
+        path: &[String],
+        collection: Value,
+    ) -> Result<Value> {
+        let elements = self.array_elements(op.name(), collection, self.max_iterations)?;
+        operators::execute_aggregate(&op, &elements, path)
+    }
+
+    /// Evaluate an `any_of`/`all_of` predicate over the elements of an array
+    ///
+    /// `iterations` is what is left of the iteration limit, shared with the
+    /// quantifiers nested in the predicate so that nesting does not multiply it.
+    /// Predicate instructions count against the program's execution limits.
+    #[allow(clippy::too_many_arguments)]
+    async fn evaluate_quantifier(
+        &self,
+        ctx: &mut ExecutionContext,
+        source: &ProgramMetadata,
+        op: QuantifierOp,
+        binding: &str,
+        predicate: &[Instruction],
+        collection: Value,
+        iterations: &mut usize,
+        guard: &mut InstructionGuard<'_>,
+    ) -> Result<Value> {
+        let three_valued = self.null_semantics.is_three_valued();
+        if three_valued && matches!(collection, Value::Null) {
+            return Ok(Value::Null);
+        }
+        let elements = self.array_elements(op.name(), collection, *iterations)?;
+        *iterations -= elements.len();
+
+        // Under three-valued semantics, an Unknown predicate makes the
+        // result Unknown unless another element decides it
+        let mut unknown = false;
+        for element in elements {
+            ctx.push_binding(binding.to_string(), element);
+            let outcome = self
+                .execute_predicate(ctx, source, predicate, iterations, guard)
+                .await;
+            ctx.pop_binding();
+
+            let outcome = outcome?;
+            if three_valued && matches!(outcome, Value::Null) {
+                unknown = true;
+                continue;
+            }
+
+            let matched = Self::is_truthy(&outcome);
+            match op {
+                QuantifierOp::AnyOf if matched => return Ok(Value::Bool(true)),
+                QuantifierOp::AllOf if !matched => return Ok(Value::Bool(false)),
+                _ => {}
+            }
+        }
+
+        if unknown {
+            Ok(Value::Null)
+        } else {
+            Ok(Value::Bool(matches!(op, QuantifierOp::AllOf)))
+        }
+    }
+
+    /// Run predicate instructions and return the value they leave on the stack
+    ///
+    /// The compiler only emits instructions [allowed in
+    /// predicates](Instruction::allowed_in_predicate) here.
+    fn execute_predicate<'a>(
+        &'a self,
+        ctx: &'a mut ExecutionContext,
+        source: &'a ProgramMetadata,
+        instructions: &'a [Instruction],
+        iterations: &'a mut usize,
+        guard: &'a mut InstructionGuard<'_>,
+    ) -> Pin<Box<dyn Future<Output = Result<Value>> + Send + 'a>> {
+        Box::pin(async move {
+            let mut pc = 0;
+            while pc < instructions.len() {
+                let instruction = &instructions[pc];
+                self.check_limits(guard, pc, instruction)?;
+
+                match instruction {
+                    Instruction::LoadField { path } => {
+                        let value = self.handle_load_field(ctx, path).await?;
+                        ctx.push(value);
+                    }
+                    Instruction::LoadConst { value } => ctx.push(value.clone()),
+                    Instruction::Load { name } => {
+                        let value = ctx.load_variable(name)?;
+                        ctx.push(value);
+                    }
+                    Instruction::BinaryOp { op } => {
+                        let right = ctx.pop()?;
+                        let left = ctx.pop()?;
+                        ctx.push(self.binary_op(&left, op, &right)?);
+                    }
+                    Instruction::Compare { op } => {
+                        let right = ctx.pop()?;
+                        let left = ctx.pop()?;
+                        ctx.push(self.compare(&left, op, &right)?);
+                    }
+                    Instruction::UnaryOp { op } => {
+                        let operand = ctx.pop()?;
+                        ctx.push(self.unary_op(&operand, op)?);
+                    }
+                    Instruction::Jump { offset } => {
+                        pc = (pc as isize + offset) as usize;
+                        continue;
+                    }
+                    Instruction::JumpIfTrue { offset } => {
+                        if Self::is_truthy(&ctx.pop()?) {
+                            pc = (pc as isize + offset) as usize;
+                            continue;
+                        }
+                    }
+                    Instruction::JumpIfFalse { offset } => {
+                        if !Self::is_truthy(&ctx.pop()?) {
+                            pc = (pc as isize + offset) as usize;
+                            continue;
+                        }
+                    }
+                    Instruction::Dup => ctx.dup()?,
+                    Instruction::Pop => {
+                        ctx.pop()?;
+                    }
+                    Instruction::Swap => ctx.swap()?,
+                    Instruction::ListLookup { list_id, negate } => {
+                        let value = ctx.pop()?;
+                        let result = self.list_membership(source, list_id, &value, *negate).await?;
+                        ctx.push(result);
+                    }
+                    Instruction::IterateArray {
+                        op,
+                        binding,
+                        predicate,
+                    } => {
+                        let collection = ctx.pop()?;
+                        let result = self
+                            .evaluate_quantifier(
+                                ctx, source, *op, binding, predicate, collection, iterations,
+                                guard,
+                            )
+                            .await?;
+                        ctx.push(result);
+                    }
+                    Instruction::AggregateArray { op, path } => {
+                        let collection = ctx.pop()?;
+                        ctx.push(self.evaluate_aggregate(*op, path, collection)?);
+                    }
+                    other => {
+                        return Err(RuntimeError::RuntimeError(format!(
+                            "Unsupported instruction in array predicate: {:?}",
+                            other
+                        )));
+                    }
+                }
+                pc += 1;
+            }
+
+            ctx.pop()
+        })
+    }
+
     /// Placeholder feature value for when storage is not available
     fn placeholder_feature(feature_type: &FeatureType) -> Value {
         match feature_type {
@@ -717,9 +1625,58 @@ impl PipelineExecutor {
             | FeatureType::Min
             | FeatureType::Max
             | FeatureType::Percentile { .. }
+            | FeatureType::Median
+            | FeatureType::DecayedSum { .. }
+            | FeatureType::DecayedCount { .. }
             | FeatureType::StdDev
             | FeatureType::Variance => Value::Number(0.0),
+            FeatureType::Mode => Value::Null,
+        }
+    }
+
+    /// Execute a comparison, Unknown (`Null`) over `Null` under three-valued semantics
+    fn compare(&self, left: &Value, op: &Operator, right: &Value) -> Result<Value> {
+        if self.null_semantics.is_three_valued()
+            && (matches!(left, Value::Null) || matches!(right, Value::Null))
+        {
+            return Ok(Value::Null);
+        }
+        Ok(Value::Bool(operators::execute_compare(left, op, right)?))
+    }
+
+    /// Execute a binary operation, `&&`/`||` with Kleene logic under three-valued semantics
+    fn binary_op(&self, left: &Value, op: &Operator, right: &Value) -> Result<Value> {
+        if self.null_semantics.is_three_valued() && op.is_logical() {
+            return operators::execute_kleene_op(left, op, right);
+        }
+        operators::execute_binary_op(left, op, right)
+    }
+
+    /// Execute a unary operation, `!Null` being Unknown under three-valued semantics
+    fn unary_op(&self, operand: &Value, op: &UnaryOperator) -> Result<Value> {
+        if self.null_semantics.is_three_valued()
+            && *op == UnaryOperator::Not
+            && matches!(operand, Value::Null)
+        {
+            return Ok(Value::Null);
         }
+        operators::execute_unary_op(operand, op)
+    }
+
+    /// Look a value up in a list, Unknown for `Null` under three-valued semantics
+    async fn list_membership(
+        &self,
+        source: &ProgramMetadata,
+        list_id: &str,
+        value: &Value,
+        negate: bool,
+    ) -> Result<Value> {
+        if self.null_semantics.is_three_valued() && matches!(value, Value::Null) {
+            return Ok(Value::Null);
+        }
+        Ok(Value::Bool(
+            self.lookup_list(source, list_id, value, negate).await?,
+        ))
     }
 
     /// Check if a value is truthy
@@ -764,59 +1721,97 @@ impl PipelineExecutor {
             // Explicit feature access: features.xxx
             let feature_name = &path[1];
 
-            // First, check if the feature value was pre-provided in the request
-            if let Some(existing_value) = ctx.features.get(feature_name) {
+            // Replayed decisions use the value the original decision consumed
+            let capture = crate::decision_replay::current_capture();
+            if let Some(capture) = capture.as_ref().filter(|capture| capture.is_pinned()) {
+                return capture.pinned_feature(feature_name);
+            }
+
+            let started = Instant::now();
+            let value = self.load_feature(ctx, feature_name).await;
+            if self.profiling {
+                self.metrics
+                    .profile()
+                    .record(ProfileScope::Feature, feature_name, started.elapsed());
+            }
+            if let (Some(capture), Ok(value)) = (&capture, &value) {
+                capture.record_feature(feature_name, value);
+            }
+            value
+        } else {
+            // Regular field access: event_data, variables, or special fields
+            ctx.load_field(path)
+        }
+    }
+
+    /// Load a feature value, pre-provided in the request or calculated on demand
+    async fn load_feature(&self, ctx: &mut ExecutionContext, feature_name: &str) -> Result<Value> {
+        // First, check if the feature value was pre-provided in the request
+        if let Some(existing_value) = ctx.features.get(feature_name) {
+            tracing::debug!(
+                "Using pre-provided feature '{}': {:?}",
+                feature_name,
+                existing_value
+            );
+            Ok(existing_value.clone())
+        } else if let Some(ref feature_executor) = self.feature_executor {
+            // Feature not pre-provided, try to calculate it
+            if feature_executor.has_feature(feature_name) {
+                // Calculated once per request, however many rules load it
+                let memo = memo::current_memo();
+                let key = MemoKey::Feature(feature_name.to_string());
+                if let Some(feature_value) = self.memoized(memo.as_deref(), &key) {
+                    ctx.store_feature(feature_name, feature_value.clone());
+                    return Ok(feature_value);
+                }
+
                 tracing::debug!(
-                    "Using pre-provided feature '{}': {:?}",
-                    feature_name,
-                    existing_value
+                    "Calculating feature '{}' via FeatureExtractor",
+                    feature_name
                 );
-                Ok(existing_value.clone())
-            } else if let Some(ref feature_executor) = self.feature_executor {
-                // Feature not pre-provided, try to calculate it
-                if feature_executor.has_feature(feature_name) {
-                    tracing::debug!(
-                        "Calculating feature '{}' via FeatureExtractor",
-                        feature_name
-                    );
 
-                    // Calculate feature on-demand
-                    match feature_executor.execute_feature(feature_name, ctx).await {
-                        Ok(feature_value) => {
-                            // Store the result in features namespace
-                            ctx.store_feature(feature_name, feature_value.clone());
-                            tracing::debug!(
-                                "Feature '{}' calculated: {:?}",
-                                feature_name,
-                                feature_value
-                            );
-                            Ok(feature_value)
-                        }
-                        Err(e) => {
-                            tracing::warn!(
-                                "Failed to calculate feature '{}': {}",
-                                feature_name,
-                                e
-                            );
-                            Ok(Value::Null)
-                        }
+                // Calculate feature on-demand
+                let feature_value = match feature_executor.execute_feature(feature_name, ctx).await
+                {
+                    Ok(feature_value) => {
+                        tracing::debug!(
+                            "Feature '{}' calculated: {:?}",
+                            feature_name,
+                            feature_value
+                        );
+                        feature_value
+                    }
+                    Err(e) if e.is::<crate::feature::MissingFeatureError>() => {
+                        tracing::debug!("Feature '{}' is missing: {}", feature_name, e);
+                        return Err(RuntimeError::MissingField {
+                            path: feature_name.to_string(),
+                        });
                     }
-                } else {
-                    Err(RuntimeError::FieldNotFound(format!(
-                        "Feature '{}' not found in pre-provided features or FeatureExtractor",
-                        feature_name
-                    )))
+                    Err(e) => {
+                        tracing::warn!(
+                            "Failed to calculate feature '{}': {}",
+                            feature_name,
+                            e
+                        );
+                        Value::Null
+                    }
+                };
+                if let Some(memo) = memo {
+                    memo.insert(key, feature_value.clone());
                 }
+                // Store the result in features namespace
+                ctx.store_feature(feature_name, feature_value.clone());
+                Ok(feature_value)
             } else {
-                // No pre-provided value and no feature executor
-                Err(RuntimeError::FieldNotFound(format!(
-                    "Feature '{}' not found: no pre-provided value and FeatureExtractor not available",
-                    feature_name
-                )))
+                Err(RuntimeError::MissingField {
+                    path: format!("features.{}", feature_name),
+                })
             }
         } else {
-            // Regular field access: event_data, variables, or special fields
-            ctx.load_field(path)
+            // No pre-provided value and no feature executor
+            Err(RuntimeError::MissingField {
+                path: format!("features.{}", feature_name),
+            })
         }
     }
 }
@@ -156,6 +156,45 @@ async fn test_division_by_zero() {
     ));
 }
 
+#[tokio::test]
+async fn test_division_by_zero_located_in_condition() {
+    use corint_core::ir::SourceSpan;
+
+    let executor = PipelineExecutor::new();
+
+    let instructions = vec![
+        Instruction::LoadField {
+            path: vec!["event".to_string(), "amount".to_string()],
+        },
+        Instruction::LoadField {
+            path: vec!["event".to_string(), "count".to_string()],
+        },
+        Instruction::BinaryOp {
+            op: corint_core::ast::Operator::Div,
+        },
+        Instruction::Return,
+    ];
+    let mut metadata = ProgramMetadata::for_rule("avg_amount".to_string());
+    let mut span = SourceSpan::new(0, 3, "event.amount / event.count");
+    span.file = Some("rules/avg_amount.yaml".to_string());
+    span.line = Some(6);
+    metadata.source_map.push(span);
+    let program = Program::new(instructions, metadata);
+
+    let mut event = HashMap::new();
+    event.insert("amount".to_string(), Value::Number(100.0));
+    event.insert("count".to_string(), Value::Number(0.0));
+    let error = executor.execute(&program, event).await.unwrap_err();
+
+    assert_eq!(error.code(), "DIVISION_BY_ZERO");
+    assert!(matches!(
+        &error,
+        crate::error::RuntimeError::InCondition { condition, location, .. }
+            if condition == "event.amount / event.count"
+                && location == "rule 'avg_amount' at rules/avg_amount.yaml:6"
+    ));
+}
+
 #[tokio::test]
 async fn test_metrics_collection() {
     let executor = PipelineExecutor::new();
@@ -173,3 +212,487 @@ async fn test_metrics_collection() {
     let duration_hist = metrics.histogram("program_execution_duration");
     assert_eq!(duration_hist.count(), 1);
 }
+
+#[tokio::test]
+async fn test_profiling() {
+    use crate::observability::ProfileScope;
+    use crate::ContextInput;
+
+    let instructions = vec![
+        Instruction::LoadField {
+            path: vec!["features".to_string(), "txn_count_1h".to_string()],
+        },
+        Instruction::LoadConst {
+            value: Value::Number(5.0),
+        },
+        Instruction::Compare {
+            op: corint_core::ast::Operator::Gt,
+        },
+        Instruction::JumpIfFalse { offset: 2 },
+        Instruction::SetScore { value: 80 },
+        Instruction::Return,
+    ];
+    let program = Program::new(
+        instructions,
+        ProgramMetadata::for_rule("velocity".to_string()),
+    );
+    let mut features = HashMap::new();
+    features.insert("txn_count_1h".to_string(), Value::Number(9.0));
+    let input = || ContextInput::new(HashMap::new()).with_features(features.clone());
+
+    let executor = PipelineExecutor::new().with_profiling(true);
+    for _ in 0..3 {
+        executor
+            .execute_with_result(&program, input(), Default::default())
+            .await
+            .unwrap();
+    }
+
+    let report = executor.metrics().profile_report();
+    assert_eq!(report.get(ProfileScope::Rule, "velocity").unwrap().calls, 3);
+    assert_eq!(
+        report.get(ProfileScope::Feature, "txn_count_1h").unwrap().calls,
+        3
+    );
+    assert_eq!(
+        report.get(ProfileScope::Instruction, "LoadField").unwrap().calls,
+        3
+    );
+    assert_eq!(report.get(ProfileScope::Instruction, "Return").unwrap().calls, 3);
+
+    // Profiling is off by default
+    let executor = PipelineExecutor::new();
+    executor
+        .execute_with_result(&program, input(), Default::default())
+        .await
+        .unwrap();
+    assert!(executor.metrics().profile_report().entries.is_empty());
+}
+
+fn quantifier_program(op: corint_core::ast::QuantifierOp) -> Program {
+    let instructions = vec![
+        Instruction::LoadField {
+            path: vec!["event".to_string(), "items".to_string()],
+        },
+        Instruction::IterateArray {
+            op,
+            binding: "item".to_string(),
+            predicate: vec![
+                Instruction::LoadField {
+                    path: vec!["item".to_string(), "price".to_string()],
+                },
+                Instruction::LoadConst {
+                    value: Value::Number(1000.0),
+                },
+                Instruction::Compare {
+                    op: corint_core::ast::Operator::Gt,
+                },
+            ],
+        },
+        Instruction::JumpIfFalse { offset: 2 },
+        Instruction::SetScore { value: 100 },
+        Instruction::Return,
+    ];
+
+    Program::new(instructions, ProgramMetadata::for_rule("test".to_string()))
+}
+
+fn items_event(prices: &[f64]) -> HashMap<String, Value> {
+    let items = prices
+        .iter()
+        .map(|price| {
+            let mut item = HashMap::new();
+            item.insert("price".to_string(), Value::Number(*price));
+            Value::Object(item)
+        })
+        .collect();
+
+    let mut event = HashMap::new();
+    event.insert("items".to_string(), Value::Array(items));
+    event
+}
+
+#[tokio::test]
+async fn test_iterate_array_quantifiers() {
+    use corint_core::ast::QuantifierOp;
+
+    let executor = PipelineExecutor::new();
+
+    let any_of = quantifier_program(QuantifierOp::AnyOf);
+    let all_of = quantifier_program(QuantifierOp::AllOf);
+
+    let mixed = items_event(&[50.0, 1500.0]);
+    let result = executor.execute(&any_of, mixed.clone()).await.unwrap();
+    assert_eq!(result.score, 100);
+    let result = executor.execute(&all_of, mixed).await.unwrap();
+    assert_eq!(result.score, 0);
+
+    let expensive = items_event(&[1200.0, 1500.0]);
+    let result = executor.execute(&all_of, expensive).await.unwrap();
+    assert_eq!(result.score, 100);
+
+    // Empty arrays: any_of is false, all_of is vacuously true
+    let result = executor.execute(&any_of, items_event(&[])).await.unwrap();
+    assert_eq!(result.score, 0);
+    let result = executor.execute(&all_of, items_event(&[])).await.unwrap();
+    assert_eq!(result.score, 100);
+}
+
+#[tokio::test]
+async fn test_iterate_array_limit() {
+    let executor = PipelineExecutor::new().with_max_iterations(2);
+    let program = quantifier_program(corint_core::ast::QuantifierOp::AnyOf);
+
+    let result = executor
+        .execute(&program, items_event(&[1.0, 2.0, 3.0]))
+        .await;
+
+    assert!(matches!(
+        result.unwrap_err(),
+        crate::error::RuntimeError::InvalidOperation(_)
+    ));
+}
+
+/// `any_of(event.orders, order, any_of(order.items, item, item.price > 1000))`
+fn nested_quantifier_program() -> Program {
+    use corint_core::ast::QuantifierOp;
+
+    let mut inner = quantifier_program(QuantifierOp::AnyOf).instructions;
+    inner.truncate(2);
+    inner[0] = Instruction::LoadField {
+        path: vec!["order".to_string(), "items".to_string()],
+    };
+
+    let instructions = vec![
+        Instruction::LoadField {
+            path: vec!["event".to_string(), "orders".to_string()],
+        },
+        Instruction::IterateArray {
+            op: QuantifierOp::AnyOf,
+            binding: "order".to_string(),
+            predicate: inner,
+        },
+        Instruction::JumpIfFalse { offset: 2 },
+        Instruction::SetScore { value: 100 },
+        Instruction::Return,
+    ];
+    Program::new(instructions, ProgramMetadata::for_rule("test".to_string()))
+}
+
+fn orders_event(orders: usize, prices: &[f64]) -> HashMap<String, Value> {
+    let order = Value::Object(items_event(prices));
+    let mut event = HashMap::new();
+    event.insert("orders".to_string(), Value::Array(vec![order; orders]));
+    event
+}
+
+#[tokio::test]
+async fn test_nested_quantifiers_share_iteration_limit() {
+    let program = nested_quantifier_program();
+
+    // 2 orders and 2 x 3 items: each array is within the limit, their total is not
+    let executor = PipelineExecutor::new().with_max_iterations(5);
+    let result = executor
+        .execute(&program, orders_event(2, &[1.0, 2.0, 3.0]))
+        .await;
+    assert!(matches!(
+        result.unwrap_err(),
+        crate::error::RuntimeError::InvalidOperation(_)
+    ));
+
+    let executor = PipelineExecutor::new().with_max_iterations(8);
+    let result = executor
+        .execute(&program, orders_event(2, &[1.0, 2.0, 3.0]))
+        .await
+        .unwrap();
+    assert_eq!(result.score, 0);
+    let result = executor
+        .execute(&program, orders_event(2, &[1.0, 2000.0]))
+        .await
+        .unwrap();
+    assert_eq!(result.score, 100);
+}
+
+#[tokio::test]
+async fn test_nested_quantifiers_count_against_instruction_limit() {
+    use crate::engine::ExecutionLimits;
+    use crate::error::RuntimeError;
+
+    let program = nested_quantifier_program();
+    let event = || orders_event(10, &[1.0, 2.0, 3.0]);
+
+    // 4 instructions in the program, 11 in the predicates of each order
+    let executor = PipelineExecutor::new()
+        .with_execution_limits(ExecutionLimits::default().with_max_instructions(50));
+    match executor.execute(&program, event()).await {
+        Err(RuntimeError::ExecutionLimitExceeded { executed, .. }) => {
+            assert_eq!(executed, 50);
+        }
+        other => panic!("expected ExecutionLimitExceeded, got {:?}", other),
+    }
+
+    let executor = PipelineExecutor::new()
+        .with_execution_limits(ExecutionLimits::default().with_max_instructions(114));
+    assert_eq!(executor.execute(&program, event()).await.unwrap().score, 0);
+}
+
+#[tokio::test]
+async fn test_runaway_program_is_aborted() {
+    use crate::engine::ExecutionLimits;
+    use crate::error::RuntimeError;
+
+    // Adds to the score forever
+    let instructions = vec![
+        Instruction::AddScore { value: 1 },
+        Instruction::Jump { offset: -1 },
+    ];
+    let program = Program::new(
+        instructions,
+        ProgramMetadata::for_ruleset("loop_rules".to_string()),
+    );
+
+    let executor = PipelineExecutor::new()
+        .with_execution_limits(ExecutionLimits::default().with_max_instructions(1_000));
+    match executor.execute(&program, HashMap::new()).await {
+        Err(RuntimeError::ExecutionLimitExceeded {
+            program, executed, ..
+        }) => {
+            assert_eq!(program, "ruleset:loop_rules");
+            assert_eq!(executed, 1_000);
+        }
+        other => panic!("expected ExecutionLimitExceeded, got {:?}", other),
+    }
+    assert_eq!(
+        executor
+            .metrics()
+            .counter("execution_limit_exceeded_total")
+            .get(),
+        1
+    );
+}
+
+#[tokio::test]
+async fn test_observer_sees_instructions_and_steps() {
+    use crate::engine::{ExecutionObserver, InstructionEvent, StepEvent};
+    use async_trait::async_trait;
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Default)]
+    struct Recorder {
+        events: Mutex<Vec<String>>,
+    }
+
+    #[async_trait]
+    impl ExecutionObserver for Recorder {
+        async fn before_instruction(&self, event: &InstructionEvent<'_>) {
+            self.events
+                .lock()
+                .unwrap()
+                .push(format!("before {}", event.pc));
+        }
+
+        async fn after_instruction(&self, event: &InstructionEvent<'_>) {
+            self.events.lock().unwrap().push(format!(
+                "after {} (score {})",
+                event.pc, event.context.result.score
+            ));
+        }
+
+        async fn before_step(&self, event: &StepEvent<'_>) {
+            self.events
+                .lock()
+                .unwrap()
+                .push(format!("enter {}", event.step_id));
+        }
+
+        async fn after_step(&self, event: &StepEvent<'_>) {
+            self.events
+                .lock()
+                .unwrap()
+                .push(format!("leave {}", event.step_id));
+        }
+    }
+
+    let mark = |step_id: &str, next_step_id: Option<&str>| Instruction::MarkStepExecuted {
+        step_id: step_id.to_string(),
+        next_step_id: next_step_id.map(str::to_string),
+        route_index: None,
+        is_default_route: false,
+    };
+    let instructions = vec![
+        mark("screen", Some("score")),
+        mark("score", None),
+        Instruction::SetScore { value: 10 },
+        Instruction::Return,
+    ];
+    let program = Program::new(
+        instructions,
+        ProgramMetadata::for_pipeline("observed".to_string()),
+    );
+
+    let recorder = Arc::new(Recorder::default());
+    let executor = PipelineExecutor::new().with_observer(recorder.clone());
+    let result = executor.execute(&program, HashMap::new()).await.unwrap();
+    assert_eq!(result.score, 10);
+
+    assert_eq!(
+        *recorder.events.lock().unwrap(),
+        vec![
+            "enter screen",
+            "before 0",
+            "after 0 (score 0)",
+            "leave screen",
+            "enter score",
+            "before 1",
+            "after 1 (score 0)",
+            "before 2",
+            "after 2 (score 10)",
+            "before 3",
+            "after 3 (score 10)",
+            "leave score",
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_aggregate_score_keeps_earlier_rulesets() {
+    use crate::result::ExecutionResult;
+    use crate::ContextInput;
+    use corint_core::ast::{ScoreAggregation, ScoreStrategy};
+
+    let executor = PipelineExecutor::new();
+
+    // A rule of an earlier ruleset, then the two rules of this one
+    let mut result = ExecutionResult::new();
+    for (rule_id, score) in [("earlier", 20), ("velocity", 60), ("new_device", 30)] {
+        let program = Program::new(
+            vec![Instruction::AddScore { value: score }, Instruction::Return],
+            ProgramMetadata::for_rule(rule_id.to_string()),
+        );
+        let rule_result = executor
+            .execute_with_result(&program, ContextInput::new(HashMap::new()), result.clone())
+            .await
+            .unwrap();
+        result.score = rule_result.score;
+        result.variables.extend(rule_result.context);
+    }
+    assert_eq!(result.score, 110);
+
+    let program = Program::new(
+        vec![
+            Instruction::AggregateScore {
+                rules: vec!["velocity".to_string(), "new_device".to_string()],
+                scoring: ScoreAggregation {
+                    strategy: ScoreStrategy::Max,
+                    ..Default::default()
+                },
+            },
+            Instruction::Return,
+        ],
+        ProgramMetadata::for_ruleset("capped".to_string()),
+    );
+    let ruleset_result = executor
+        .execute_with_result(&program, ContextInput::new(HashMap::new()), result)
+        .await
+        .unwrap();
+    assert_eq!(ruleset_result.score, 80);
+}
+
+#[tokio::test]
+async fn test_execute_batch() {
+    use crate::ContextInput;
+    use corint_core::ast::Operator;
+
+    let executor = PipelineExecutor::new().with_batch_concurrency(2);
+
+    // Score 80 when amount / units > 100
+    let field = |name: &str| Instruction::LoadField {
+        path: vec!["event".to_string(), name.to_string()],
+    };
+    let instructions = vec![
+        field("amount"),
+        field("units"),
+        Instruction::BinaryOp { op: Operator::Div },
+        Instruction::LoadConst {
+            value: Value::Number(100.0),
+        },
+        Instruction::Compare { op: Operator::Gt },
+        Instruction::JumpIfFalse { offset: 2 },
+        Instruction::SetScore { value: 80 },
+        Instruction::Return,
+    ];
+    let program = Program::new(instructions, ProgramMetadata::for_rule("test".to_string()));
+
+    let inputs = [(5000.0, 2.0), (100.0, 0.0), (150.0, 3.0)]
+        .into_iter()
+        .map(|(amount, units)| {
+            let mut event = HashMap::new();
+            event.insert("amount".to_string(), Value::Number(amount));
+            event.insert("units".to_string(), Value::Number(units));
+            ContextInput::new(event)
+        })
+        .collect();
+
+    let results = executor.execute_batch(&program, inputs).await;
+    assert_eq!(results.len(), 3);
+    assert_eq!(results[0].as_ref().unwrap().score, 80);
+    assert!(matches!(
+        results[1],
+        Err(crate::error::RuntimeError::DivisionByZero)
+    ));
+    assert_eq!(results[2].as_ref().unwrap().score, 0);
+}
+
+#[tokio::test]
+async fn test_tenant_quotas() {
+    use crate::concurrency::ConcurrencyLimit;
+    use crate::result::ExecutionResult;
+    use crate::tenant::{TenantQuota, TenantQuotaConfig, TenantQuotas};
+    use crate::ContextInput;
+    use std::sync::Arc;
+
+    let quotas = TenantQuotaConfig::new().with_default(
+        TenantQuota::new().with_concurrency(ConcurrencyLimit::new(1).with_queue_timeout_ms(10)),
+    );
+    let executor = PipelineExecutor::new().with_tenant_quotas(Arc::new(TenantQuotas::new(quotas)));
+    let program = Program::new(
+        vec![Instruction::SetScore { value: 10 }, Instruction::Return],
+        ProgramMetadata::for_rule("test".to_string()),
+    );
+    let input = |tenant: &str| ContextInput::new(HashMap::new()).with_tenant(tenant);
+    let execute = |tenant: &str| {
+        executor.execute_with_result(&program, input(tenant), ExecutionResult::new())
+    };
+
+    // A decision of the tenant holds its only slot
+    let permit = executor.admit_tenant("acme").await.unwrap();
+    assert!(matches!(
+        execute("acme").await,
+        Err(crate::error::RuntimeError::ConcurrencyLimitReached(_))
+    ));
+    assert_eq!(execute("globex").await.unwrap().score, 10);
+
+    // Programs executed within the decision are not admitted again
+    let result = permit.scope(execute("acme")).await;
+    assert_eq!(result.unwrap().score, 10);
+    assert_eq!(execute("acme").await.unwrap().score, 10);
+
+    // Tenants on the default quota are counted together
+    let metrics = executor.metrics();
+    assert_eq!(
+        metrics
+            .labeled_counter("tenant_admitted_total", &[("tenant", "default")])
+            .get(),
+        3
+    );
+    assert_eq!(
+        metrics
+            .labeled_counter(
+                "tenant_rejected_total",
+                &[("tenant", "default"), ("reason", "CONCURRENCY_LIMIT_REACHED")]
+            )
+            .get(),
+        1
+    );
+    assert_eq!(executor.tenant_stats().len(), 2);
+}
@@ -67,3 +67,87 @@ async fn test_feature_extraction_with_storage() {
     // Sum of 10, 20, 30, 40, 50 = 150
     assert_eq!(result.score, 0); // Score is separate from stack value
 }
+
+#[tokio::test]
+async fn test_replay_from_captured_inputs() {
+    use crate::decision_replay::{CapturedInputs, InputCapture};
+    use crate::error::RuntimeError;
+    use crate::result::ExecutionResult;
+    use crate::ContextInput;
+
+    // Scores 80 when the feature exceeds 5 and the sum of amounts is positive
+    let instructions = vec![
+        Instruction::LoadField {
+            path: vec!["features".to_string(), "txn_count_1h".to_string()],
+        },
+        Instruction::LoadConst {
+            value: Value::Number(5.0),
+        },
+        Instruction::Compare {
+            op: corint_core::ast::Operator::Gt,
+        },
+        Instruction::JumpIfFalse { offset: 7 },
+        Instruction::CallFeature {
+            feature_type: FeatureType::Sum,
+            field: vec!["amount".to_string()],
+            filter: None,
+            time_window: TimeWindow::Last1Hour,
+        },
+        Instruction::LoadConst {
+            value: Value::Number(0.0),
+        },
+        Instruction::Compare {
+            op: corint_core::ast::Operator::Gt,
+        },
+        Instruction::JumpIfFalse { offset: 2 },
+        Instruction::AddScore { value: 80 },
+        Instruction::Return,
+        Instruction::Return,
+    ];
+    let program = Program::new(instructions, ProgramMetadata::for_rule("velocity".to_string()));
+
+    let mut storage = InMemoryStorage::new();
+    let now = chrono::Utc::now().timestamp();
+    let mut data = HashMap::new();
+    data.insert("amount".to_string(), Value::Number(250.0));
+    storage.add_event(Event {
+        timestamp: now - 10,
+        data,
+    });
+    let executor = PipelineExecutor::with_storage(Arc::new(storage));
+
+    let mut event = HashMap::new();
+    event.insert("user_id".to_string(), Value::String("u1".to_string()));
+    let mut features = HashMap::new();
+    features.insert("txn_count_1h".to_string(), Value::Number(9.0));
+    let capture = Arc::new(InputCapture::capture(CapturedInputs::new(
+        "req_1",
+        event.clone(),
+    )));
+    let input = ContextInput::new(event).with_features(features);
+    let original = Arc::clone(&capture)
+        .scope(executor.execute_with_result(&program, input, ExecutionResult::new()))
+        .await
+        .unwrap();
+    assert_eq!(original.score, 80);
+
+    let inputs = capture.inputs();
+    assert_eq!(inputs.features["txn_count_1h"], Value::Number(9.0));
+    assert_eq!(inputs.calls.len(), 1);
+    assert_eq!(inputs.calls[0].value, Value::Number(250.0));
+
+    // Without the storage the events came from, the replay still matches
+    let replayed = PipelineExecutor::new()
+        .replay(&program, &inputs)
+        .await
+        .unwrap();
+    assert_eq!(replayed.score, original.score);
+    assert_eq!(replayed.signal, original.signal);
+
+    let mut partial = inputs.clone();
+    partial.calls.clear();
+    assert!(matches!(
+        PipelineExecutor::new().replay(&program, &partial).await,
+        Err(RuntimeError::Replay(_))
+    ));
+}
@@ -1,8 +1,17 @@
 //! Integration tests for Service clients in pipeline executor
 
-use crate::engine::PipelineExecutor;
+use crate::custom_step::{CustomCall, CustomStepHandler};
+use crate::engine::{ExecutionBudget, PipelineExecutor, TimeoutScope};
+use crate::error::Result;
 use crate::service::http::MockHttpClient;
+use crate::service::{ServiceClient, ServiceRequest, ServiceResponse};
+use async_trait::async_trait;
+use corint_core::ast::Signal;
+use corint_core::ir::{Instruction, Program, ProgramMetadata};
+use corint_core::Value;
+use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::Duration;
 
 #[tokio::test]
 async fn test_service_integration() {
@@ -11,3 +20,157 @@ async fn test_service_integration() {
 
     assert!(executor.service_client.is_some());
 }
+
+/// Service answering after a delay
+struct SlowService(Duration);
+
+#[async_trait]
+impl ServiceClient for SlowService {
+    async fn call(&self, _request: ServiceRequest) -> Result<ServiceResponse> {
+        tokio::time::sleep(self.0).await;
+        Ok(ServiceResponse::new(Value::String("slow".to_string())))
+    }
+
+    fn name(&self) -> &str {
+        "slow"
+    }
+}
+
+fn step(step_id: &str) -> Instruction {
+    Instruction::MarkStepExecuted {
+        step_id: step_id.to_string(),
+        next_step_id: None,
+        route_index: None,
+        is_default_route: false,
+    }
+}
+
+/// Pipeline calling the slow service in step `enrich`, then scoring in step `score`
+fn slow_pipeline(metadata: ProgramMetadata) -> Program {
+    let instructions = vec![
+        step("enrich"),
+        Instruction::CallService {
+            service: "slow".to_string(),
+            operation: "get".to_string(),
+            params: HashMap::new(),
+        },
+        Instruction::Store {
+            name: "enriched".to_string(),
+        },
+        step("score"),
+        Instruction::SetScore { value: 10 },
+        Instruction::Return,
+    ];
+    let decision = vec![
+        Instruction::SetSignal {
+            signal: Signal::Review,
+        },
+        Instruction::Return,
+    ];
+    Program::new_with_decision(instructions, metadata, decision)
+}
+
+#[tokio::test]
+async fn test_step_and_pipeline_timeouts() {
+    let executor = PipelineExecutor::new()
+        .with_service_client(Arc::new(SlowService(Duration::from_millis(200))));
+
+    // The step times out, the pipeline goes on without the service response
+    let program = slow_pipeline(
+        ProgramMetadata::for_pipeline("login".to_string())
+            .with_custom("step_timeouts".to_string(), "enrich=20".to_string()),
+    );
+    let budget = Arc::new(ExecutionBudget::new(None));
+    let result = Arc::clone(&budget)
+        .scope(executor.execute(&program, HashMap::new()))
+        .await
+        .unwrap();
+    assert_eq!(result.score, 10);
+    assert_eq!(result.context.get("enriched"), Some(&Value::Null));
+    let timeouts = budget.timeouts();
+    assert_eq!(timeouts.len(), 1);
+    assert_eq!(timeouts[0].scope, TimeoutScope::Step);
+    assert_eq!(timeouts[0].id.as_deref(), Some("enrich"));
+
+    // The pipeline times out, its remaining steps are skipped but the decision is made
+    let program = slow_pipeline(
+        ProgramMetadata::for_pipeline("login".to_string())
+            .with_custom("timeout_ms".to_string(), "20".to_string()),
+    );
+    let budget = Arc::new(ExecutionBudget::new(None));
+    let result = Arc::clone(&budget)
+        .scope(executor.execute(&program, HashMap::new()))
+        .await
+        .unwrap();
+    assert_eq!(result.score, 0);
+    assert_eq!(result.signal, Some(Signal::Review));
+    assert!(budget.is_degraded());
+    assert_eq!(budget.timeouts()[0].scope, TimeoutScope::Pipeline);
+
+    // Rules routed to by the timed out pipeline are skipped
+    let rule = Program::new(
+        vec![Instruction::SetScore { value: 50 }, Instruction::Return],
+        ProgramMetadata::for_rule("high_amount".to_string()),
+    );
+    let result = budget
+        .scope(executor.execute(&rule, HashMap::new()))
+        .await
+        .unwrap();
+    assert_eq!(result.score, 0);
+}
+
+/// Handler of the `geo_velocity` custom step type
+struct GeoVelocity;
+
+#[async_trait]
+impl CustomStepHandler for GeoVelocity {
+    async fn call(&self, call: CustomCall) -> Result<Value> {
+        match (call.arg("distance_km"), call.arg("hours")) {
+            (Value::Number(distance), Value::Number(hours)) => Ok(Value::Number(distance / hours)),
+            _ => Ok(Value::Null),
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_custom_step_handler() {
+    let instructions = vec![
+        step("velocity"),
+        Instruction::LoadField {
+            path: vec!["event".to_string(), "distance_km".to_string()],
+        },
+        Instruction::LoadConst {
+            value: Value::Number(2.0),
+        },
+        Instruction::CallCustom {
+            handler: "geo_velocity".to_string(),
+            step_id: "velocity".to_string(),
+            args: vec!["distance_km".to_string(), "hours".to_string()],
+        },
+        Instruction::Store {
+            name: "geo_velocity".to_string(),
+        },
+        Instruction::Return,
+    ];
+    let program = Program::new(
+        instructions,
+        ProgramMetadata::for_pipeline("login".to_string()),
+    );
+    let mut event = HashMap::new();
+    event.insert("distance_km".to_string(), Value::Number(900.0));
+
+    let executor =
+        PipelineExecutor::new().with_custom_handler("geo_velocity", Arc::new(GeoVelocity));
+    let result = executor.execute(&program, event.clone()).await.unwrap();
+    assert_eq!(
+        result.context.get("geo_velocity"),
+        Some(&Value::Number(450.0))
+    );
+
+    // Without a handler for the step type, the pipeline fails
+    let error = PipelineExecutor::new()
+        .execute(&program, event)
+        .await
+        .unwrap_err();
+    assert!(error.to_string().contains("geo_velocity"));
+}
@@ -1,7 +1,7 @@
 //! Operator tests for pipeline executor
 
-use crate::engine::PipelineExecutor;
-use corint_core::ast::{Operator, UnaryOperator};
+use crate::engine::{NullSemantics, PipelineExecutor};
+use corint_core::ast::{AggregateOp, Operator, UnaryOperator};
 use corint_core::ir::{Instruction, Program, ProgramMetadata};
 use corint_core::Value;
 use std::collections::HashMap;
@@ -597,3 +597,189 @@ async fn test_greater_than_or_equal() {
     let result = executor.execute(&program, HashMap::new()).await.unwrap();
     assert_eq!(result.score, 100); // 15 >= 10
 }
+
+// ===========================================
+// Array aggregate tests
+// ===========================================
+
+/// Build `<op>(event.items[*].amount) <cmp> threshold` that sets score 100 when true
+fn aggregate_program(op: AggregateOp, cmp: Operator, threshold: f64) -> Program {
+    let instructions = vec![
+        Instruction::LoadField {
+            path: vec!["event".to_string(), "items".to_string()],
+        },
+        Instruction::AggregateArray {
+            op,
+            path: vec!["amount".to_string()],
+        },
+        Instruction::LoadConst {
+            value: Value::Number(threshold),
+        },
+        Instruction::Compare { op: cmp },
+        Instruction::JumpIfFalse { offset: 2 },
+        Instruction::SetScore { value: 100 },
+        Instruction::Return,
+    ];
+
+    Program::new(instructions, ProgramMetadata::for_rule("test".to_string()))
+}
+
+fn items_with_amounts(amounts: &[Value]) -> HashMap<String, Value> {
+    let items = amounts
+        .iter()
+        .map(|amount| {
+            let mut item = HashMap::new();
+            item.insert("amount".to_string(), amount.clone());
+            Value::Object(item)
+        })
+        .collect();
+
+    let mut event = HashMap::new();
+    event.insert("items".to_string(), Value::Array(items));
+    event
+}
+
+#[tokio::test]
+async fn test_array_aggregates() {
+    let executor = PipelineExecutor::new();
+    let event = items_with_amounts(&[
+        Value::Number(1000.0),
+        Value::Number(4500.0),
+        Value::Null,
+    ]);
+
+    let cases = [
+        (AggregateOp::Sum, 5500.0),
+        (AggregateOp::Count, 2.0),
+        (AggregateOp::Max, 4500.0),
+        (AggregateOp::Min, 1000.0),
+        (AggregateOp::Avg, 2750.0),
+    ];
+
+    for (op, expected) in cases {
+        let program = aggregate_program(op, Operator::Eq, expected);
+        let result = executor.execute(&program, event.clone()).await.unwrap();
+        assert_eq!(result.score, 100, "{} should be {}", op.name(), expected);
+    }
+}
+
+#[tokio::test]
+async fn test_array_aggregate_missing_array() {
+    let executor = PipelineExecutor::new();
+
+    // A missing array sums to 0
+    let program = aggregate_program(AggregateOp::Sum, Operator::Eq, 0.0);
+    let result = executor.execute(&program, HashMap::new()).await.unwrap();
+    assert_eq!(result.score, 100);
+}
+
+#[tokio::test]
+async fn test_array_aggregate_non_numeric() {
+    let executor = PipelineExecutor::new();
+    let event = items_with_amounts(&[Value::String("ten".to_string())]);
+
+    let program = aggregate_program(AggregateOp::Sum, Operator::Gt, 0.0);
+    let result = executor.execute(&program, event).await;
+    assert!(matches!(
+        result.unwrap_err(),
+        crate::error::RuntimeError::TypeMismatch { .. }
+    ));
+}
+
+// ===========================================
+// Null semantics tests
+// ===========================================
+
+/// Build a rule that sets score 100 when the condition instructions leave a truthy value
+fn condition_program(mut condition: Vec<Instruction>) -> Program {
+    condition.extend([
+        Instruction::JumpIfFalse { offset: 2 },
+        Instruction::SetScore { value: 100 },
+        Instruction::Return,
+    ]);
+    Program::new(condition, ProgramMetadata::for_rule("test".to_string()))
+}
+
+/// `event.age > 18`
+fn age_over_18() -> Vec<Instruction> {
+    vec![
+        Instruction::LoadField {
+            path: vec!["event".to_string(), "age".to_string()],
+        },
+        Instruction::LoadConst {
+            value: Value::Number(18.0),
+        },
+        Instruction::Compare { op: Operator::Gt },
+    ]
+}
+
+#[tokio::test]
+async fn test_negated_comparison_over_null() {
+    // !(event.age > 18) with the age missing
+    let mut condition = age_over_18();
+    condition.push(Instruction::UnaryOp {
+        op: UnaryOperator::Not,
+    });
+    let program = condition_program(condition);
+
+    let two_valued = PipelineExecutor::new();
+    let result = two_valued.execute(&program, HashMap::new()).await.unwrap();
+    assert_eq!(result.score, 100); // false, negated
+
+    let three_valued = PipelineExecutor::new().with_null_semantics(NullSemantics::ThreeValued);
+    let result = three_valued.execute(&program, HashMap::new()).await.unwrap();
+    assert_eq!(result.score, 0); // Unknown, negated, is still Unknown
+}
+
+#[tokio::test]
+async fn test_kleene_logic_over_null() {
+    let executor = PipelineExecutor::new().with_null_semantics(NullSemantics::ThreeValued);
+
+    // Unknown || true is true
+    let mut condition = age_over_18();
+    condition.push(Instruction::LoadConst {
+        value: Value::Bool(true),
+    });
+    condition.push(Instruction::BinaryOp { op: Operator::Or });
+    let result = executor
+        .execute(&condition_program(condition), HashMap::new())
+        .await
+        .unwrap();
+    assert_eq!(result.score, 100);
+
+    // !(Unknown && false) is true
+    let mut condition = age_over_18();
+    condition.push(Instruction::LoadConst {
+        value: Value::Bool(false),
+    });
+    condition.push(Instruction::BinaryOp { op: Operator::And });
+    condition.push(Instruction::UnaryOp {
+        op: UnaryOperator::Not,
+    });
+    let result = executor
+        .execute(&condition_program(condition), HashMap::new())
+        .await
+        .unwrap();
+    assert_eq!(result.score, 100);
+}
+
+#[tokio::test]
+async fn test_is_null() {
+    let executor = PipelineExecutor::new().with_null_semantics(NullSemantics::ThreeValued);
+    let program = condition_program(vec![
+        Instruction::LoadField {
+            path: vec!["event".to_string(), "age".to_string()],
+        },
+        Instruction::UnaryOp {
+            op: UnaryOperator::IsNull,
+        },
+    ]);
+
+    let result = executor.execute(&program, HashMap::new()).await.unwrap();
+    assert_eq!(result.score, 100);
+
+    let mut event = HashMap::new();
+    event.insert("age".to_string(), Value::Number(30.0));
+    let result = executor.execute(&program, event).await.unwrap();
+    assert_eq!(result.score, 0);
+}
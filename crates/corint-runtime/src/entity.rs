@@ -0,0 +1,398 @@
+//! Entity key resolution
+//!
+//! Features, velocity counters and deduplication all group events by an
+//! entity such as a user, a device or a card used at a merchant. Entity
+//! definitions declare how the key for such an entity is derived from an
+//! event, so every module that needs the key builds it the same way.
+//!
+//! Definitions live in the repository under `configs/entities/`:
+//!
+//! ```yaml
+//! entities:
+//!   - name: card_merchant
+//!     description: Card used at a merchant
+//!     key: "${event.card_hash}:${event.merchant_id}"
+//!     normalize: [trim, lowercase]
+//!   - name: email
+//!     key: "${event.user.email}"
+//!     normalize: [trim, lowercase, hash]
+//! ```
+//!
+//! Normalization is applied, in order, to each value substituted into the
+//! key template; literal parts of the template are kept as written.
+
+use crate::error::{Result, RuntimeError};
+use corint_core::Value;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+
+/// Prefix used to reference an entity key from a feature `dimension_value`
+pub const ENTITY_REFERENCE_PREFIX: &str = "entity.";
+
+/// Declarative entity key definition
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EntityDefinition {
+    /// Entity name (e.g. "card_merchant")
+    pub name: String,
+
+    /// Optional description
+    #[serde(default)]
+    pub description: Option<String>,
+
+    /// Key template with `${path}` placeholders (e.g. "${event.card_hash}:${event.merchant_id}")
+    pub key: String,
+
+    /// Normalization steps applied to each substituted value
+    #[serde(default)]
+    pub normalize: Vec<Normalization>,
+}
+
+/// Normalization step applied to an entity key component
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Normalization {
+    /// Strip leading and trailing whitespace
+    Trim,
+
+    /// Convert to lowercase
+    Lowercase,
+
+    /// Convert to uppercase
+    Uppercase,
+
+    /// Keep only ASCII digits (e.g. phone or card numbers)
+    DigitsOnly,
+
+    /// Replace the value with its SHA-256 hex digest
+    Hash,
+}
+
+impl Normalization {
+    /// Apply this step to a value
+    pub fn apply(&self, value: &str) -> String {
+        match self {
+            Normalization::Trim => value.trim().to_string(),
+            Normalization::Lowercase => value.to_lowercase(),
+            Normalization::Uppercase => value.to_uppercase(),
+            Normalization::DigitsOnly => value.chars().filter(|c| c.is_ascii_digit()).collect(),
+            Normalization::Hash => Sha256::digest(value.as_bytes())
+                .iter()
+                .map(|b| format!("{:02x}", b))
+                .collect(),
+        }
+    }
+}
+
+impl EntityDefinition {
+    /// Create a definition from a name and key template
+    pub fn new(name: impl Into<String>, key: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            description: None,
+            key: key.into(),
+            normalize: Vec::new(),
+        }
+    }
+
+    /// Add a normalization step
+    pub fn with_normalization(mut self, step: Normalization) -> Self {
+        self.normalize.push(step);
+        self
+    }
+
+    /// Validate the key template
+    pub fn validate(&self) -> Result<()> {
+        if self.name.is_empty() {
+            return Err(RuntimeError::InvalidValue(
+                "Entity name cannot be empty".to_string(),
+            ));
+        }
+        let placeholders = placeholders(&self.key)
+            .map_err(|e| RuntimeError::InvalidValue(format!("Entity '{}': {}", self.name, e)))?;
+        if placeholders.is_empty() {
+            return Err(RuntimeError::InvalidValue(format!(
+                "Entity '{}' key template has no ${{...}} placeholders",
+                self.name
+            )));
+        }
+        Ok(())
+    }
+
+    /// Derive the entity key from event data
+    ///
+    /// Fails when a placeholder is missing or null, so that events lacking
+    /// part of a composite key never share a key with each other.
+    pub fn resolve(&self, context: &HashMap<String, Value>) -> Result<String> {
+        let mut key = String::with_capacity(self.key.len());
+        let mut rest = self.key.as_str();
+
+        while let Some(start) = rest.find("${") {
+            let end = rest[start..]
+                .find('}')
+                .map(|offset| start + offset)
+                .ok_or_else(|| {
+                    RuntimeError::InvalidValue(format!(
+                        "Entity '{}' key template has an unclosed placeholder",
+                        self.name
+                    ))
+                })?;
+            let path = &rest[start + 2..end];
+            let value = lookup(context, path).ok_or_else(|| {
+                tracing::debug!("Entity '{}' key field '{}' is missing", self.name, path);
+                RuntimeError::MissingField {
+                    path: path.to_string(),
+                }
+            })?;
+
+            key.push_str(&rest[..start]);
+            key.push_str(&self.normalize_value(&component(value)));
+            rest = &rest[end + 1..];
+        }
+        key.push_str(rest);
+
+        Ok(key)
+    }
+
+    fn normalize_value(&self, value: &str) -> String {
+        self.normalize
+            .iter()
+            .fold(value.to_string(), |value, step| step.apply(&value))
+    }
+}
+
+#[derive(Deserialize)]
+struct EntityDocument {
+    entities: Vec<EntityDefinition>,
+}
+
+/// Registry of entity definitions by name
+#[derive(Debug, Clone, Default)]
+pub struct EntityRegistry {
+    entities: HashMap<String, EntityDefinition>,
+}
+
+impl EntityRegistry {
+    /// Create an empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parse definitions from a YAML document with an `entities` root
+    pub fn from_yaml(yaml: &str) -> Result<Self> {
+        let document: EntityDocument = serde_yaml::from_str(yaml).map_err(|e| {
+            RuntimeError::InvalidValue(format!("Invalid entity definitions: {}", e))
+        })?;
+
+        let mut registry = Self::new();
+        for entity in document.entities {
+            registry.register(entity)?;
+        }
+        Ok(registry)
+    }
+
+    /// Parse and merge definitions from (id, yaml) documents
+    ///
+    /// Later documents override earlier definitions with the same name.
+    pub fn from_documents(documents: &[(String, String)]) -> Result<Self> {
+        let mut registry = Self::new();
+        for (id, yaml) in documents {
+            let parsed = Self::from_yaml(yaml).map_err(|e| {
+                RuntimeError::InvalidValue(format!("Entity document '{}': {}", id, e))
+            })?;
+            registry.entities.extend(parsed.entities);
+        }
+        Ok(registry)
+    }
+
+    /// Register a definition, replacing any with the same name
+    pub fn register(&mut self, entity: EntityDefinition) -> Result<()> {
+        entity.validate()?;
+        self.entities.insert(entity.name.clone(), entity);
+        Ok(())
+    }
+
+    /// Get a definition by name
+    pub fn get(&self, name: &str) -> Option<&EntityDefinition> {
+        self.entities.get(name)
+    }
+
+    /// Number of registered definitions
+    pub fn len(&self) -> usize {
+        self.entities.len()
+    }
+
+    /// Check whether no definitions are registered
+    pub fn is_empty(&self) -> bool {
+        self.entities.is_empty()
+    }
+
+    /// Derive the key of a named entity from event data
+    pub fn resolve_key(&self, name: &str, context: &HashMap<String, Value>) -> Result<String> {
+        self.get(name)
+            .ok_or_else(|| RuntimeError::InvalidValue(format!("Unknown entity '{}'", name)))?
+            .resolve(context)
+    }
+
+    /// Resolve a feature dimension template that references an entity
+    ///
+    /// `entity.card_merchant` and `${entity.card_merchant}` reference the
+    /// `card_merchant` entity. Returns `None` when the template is not an
+    /// entity reference.
+    pub fn resolve_reference(
+        &self,
+        template: &str,
+        context: &HashMap<String, Value>,
+    ) -> Option<Result<String>> {
+        let inner = template
+            .trim()
+            .strip_prefix("${")
+            .and_then(|t| t.strip_suffix('}'))
+            .unwrap_or(template.trim());
+        let name = inner.strip_prefix(ENTITY_REFERENCE_PREFIX)?;
+        Some(self.resolve_key(name, context))
+    }
+}
+
+/// Look up a placeholder path in event data
+///
+/// A leading `event.` namespace is optional. Nested objects are followed
+/// first; otherwise the last path segment is looked up directly, matching
+/// how feature templates resolve fields.
+fn lookup<'a>(context: &'a HashMap<String, Value>, path: &str) -> Option<&'a Value> {
+    let path = path.strip_prefix("event.").unwrap_or(path);
+    let mut segments = path.split('.');
+    let first = segments.next()?;
+
+    let nested = segments.try_fold(context.get(first)?, |value, segment| match value {
+        Value::Object(map) => map.get(segment),
+        _ => None,
+    });
+
+    nested
+        .or_else(|| path.rsplit('.').next().and_then(|last| context.get(last)))
+        .filter(|value| !matches!(value, Value::Null))
+}
+
+fn component(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Number(n) if n.fract() == 0.0 => format!("{}", *n as i64),
+        Value::Number(n) => n.to_string(),
+        Value::Bool(b) => b.to_string(),
+        other => serde_json::to_string(other).unwrap_or_default(),
+    }
+}
+
+fn placeholders(template: &str) -> std::result::Result<Vec<&str>, String> {
+    let mut found = Vec::new();
+    let mut rest = template;
+    while let Some(start) = rest.find("${") {
+        let end = rest[start..]
+            .find('}')
+            .ok_or_else(|| "key template has an unclosed placeholder".to_string())?;
+        found.push(&rest[start + 2..start + end]);
+        rest = &rest[start + end + 1..];
+    }
+    Ok(found)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn registry() -> EntityRegistry {
+        EntityRegistry::from_yaml(
+            r#"
+entities:
+  - name: card_merchant
+    key: "${event.card_hash}:${event.merchant_id}"
+    normalize: [trim, lowercase]
+  - name: phone
+    key: "${event.user.phone}"
+    normalize: [digits_only, hash]
+"#,
+        )
+        .unwrap()
+    }
+
+    fn event(pairs: &[(&str, Value)]) -> HashMap<String, Value> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn test_composite_key_normalization() {
+        let registry = registry();
+        let context = event(&[
+            ("card_hash", Value::String(" ABC123 ".to_string())),
+            ("merchant_id", Value::Number(42.0)),
+        ]);
+        assert_eq!(
+            registry.resolve_key("card_merchant", &context).unwrap(),
+            "abc123:42"
+        );
+
+        let user = HashMap::from([(
+            "phone".to_string(),
+            Value::String("+1 (555) 010-0000".to_string()),
+        )]);
+        let hashed = registry
+            .resolve_key("phone", &event(&[("user", Value::Object(user))]))
+            .unwrap();
+        assert_eq!(hashed, Normalization::Hash.apply("15550100000"));
+    }
+
+    #[test]
+    fn test_missing_component_is_an_error() {
+        let registry = registry();
+        let context = event(&[
+            ("card_hash", Value::String("abc".to_string())),
+            ("merchant_id", Value::Null),
+        ]);
+        assert!(registry.resolve_key("card_merchant", &context).is_err());
+        assert!(registry.resolve_key("unknown", &context).is_err());
+    }
+
+    #[test]
+    fn test_entity_reference() {
+        let registry = registry();
+        let context = event(&[
+            ("card_hash", Value::String("abc".to_string())),
+            ("merchant_id", Value::String("M1".to_string())),
+        ]);
+        assert_eq!(
+            registry
+                .resolve_reference("${entity.card_merchant}", &context)
+                .unwrap()
+                .unwrap(),
+            "abc:m1"
+        );
+        assert_eq!(
+            registry
+                .resolve_reference("entity.card_merchant", &context)
+                .unwrap()
+                .unwrap(),
+            "abc:m1"
+        );
+        assert!(registry
+            .resolve_reference("${event.user_id}", &context)
+            .is_none());
+    }
+
+    #[test]
+    fn test_invalid_definitions() {
+        assert!(EntityDefinition::new("static", "no-placeholders")
+            .validate()
+            .is_err());
+        assert!(EntityDefinition::new("open", "${event.user_id")
+            .validate()
+            .is_err());
+        assert!(EntityDefinition::new("user", "${event.user_id}")
+            .with_normalization(Normalization::Lowercase)
+            .validate()
+            .is_ok());
+    }
+}
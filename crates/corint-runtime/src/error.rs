@@ -1,7 +1,46 @@
 //! Runtime error types
+//!
+//! Every [`RuntimeError`] has a stable, machine-readable [`code`] and an
+//! [`ErrorCategory`], so callers can tell a missing field in the event from
+//! an unavailable data source without matching on error messages. Codes are
+//! part of the public contract: they are returned by the SDK and the server
+//! and are never renamed.
+//!
+//! [`code`]: RuntimeError::code
 
+use corint_core::ir::Program;
+use serde::Serialize;
 use thiserror::Error;
 
+/// Broad class of a runtime error
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCategory {
+    /// The event or features do not have the fields or types the rules expect
+    Input,
+
+    /// A program cannot be executed as compiled, or exceeded its limits
+    Execution,
+
+    /// A data source, list backend or external API failed or is throttled
+    Dependency,
+
+    /// Failure inside the engine itself
+    Internal,
+}
+
+impl ErrorCategory {
+    /// Get the name of this category
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ErrorCategory::Input => "input",
+            ErrorCategory::Execution => "execution",
+            ErrorCategory::Dependency => "dependency",
+            ErrorCategory::Internal => "internal",
+        }
+    }
+}
+
 /// Runtime error
 #[derive(Error, Debug)]
 pub enum RuntimeError {
@@ -9,13 +48,21 @@ pub enum RuntimeError {
     #[error("Stack underflow")]
     StackUnderflow,
 
-    /// Type error
-    #[error("Type error: {0}")]
-    TypeError(String),
+    /// An operand does not have the type an expression requires
+    #[error("Type mismatch in {expr}: {message}")]
+    TypeMismatch {
+        /// Expression or operation that was evaluated, e.g. `Add` or `sum`
+        expr: String,
+        /// What was expected and what was found
+        message: String,
+    },
 
-    /// Field not found
-    #[error("Field not found: {0}")]
-    FieldNotFound(String),
+    /// A required field or feature is missing
+    #[error("Missing field: {path}")]
+    MissingField {
+        /// Path of the field, or name of the feature
+        path: String,
+    },
 
     /// Invalid operation
     #[error("Invalid operation: {0}")]
@@ -25,6 +72,14 @@ pub enum RuntimeError {
     #[error("Division by zero")]
     DivisionByZero,
 
+    /// A `let` of a variable already declared in the execution
+    #[error("Variable already declared: {0}")]
+    VariableAlreadyDeclared(String),
+
+    /// An `assign` to a variable no `let` declared
+    #[error("Undeclared variable: {0}")]
+    UndeclaredVariable(String),
+
     /// Program counter out of bounds
     #[error("Program counter out of bounds: {0}")]
     PCOutOfBounds(usize),
@@ -33,6 +88,77 @@ pub enum RuntimeError {
     #[error("External API call failed: {0}")]
     ExternalCallFailed(String),
 
+    /// No concurrency slot freed up for a data source or API before the queue deadline
+    #[error("Concurrency limit reached: {0}")]
+    ConcurrencyLimitReached(String),
+
+    /// An external API endpoint is called faster than its rate limit allows
+    #[error("Rate limited: {0}")]
+    RateLimited(String),
+
+    /// A data source did not answer in time
+    #[error("Data source '{datasource}' timed out: {message}")]
+    DataSourceTimeout {
+        /// Name of the data source
+        datasource: String,
+        /// Underlying error
+        message: String,
+    },
+
+    /// A data source could not be reached or failed to answer
+    #[error("Data source '{datasource}' unavailable: {message}")]
+    DataSourceUnavailable {
+        /// Name of the data source
+        datasource: String,
+        /// Underlying error
+        message: String,
+    },
+
+    /// The backend of a list could not be queried
+    #[error("List '{list_id}' backend unavailable: {message}")]
+    ListBackendUnavailable {
+        /// ID of the list
+        list_id: String,
+        /// Underlying error
+        message: String,
+    },
+
+    /// Calls to a failing data source or API are suspended by its circuit breaker
+    #[error("Circuit open: {0}")]
+    CircuitOpen(String),
+
+    /// A call or execution ran past its time budget
+    #[error("Timeout: {0}")]
+    Timeout(String),
+
+    /// A program was aborted for exceeding its instruction or wall-clock limit
+    #[error(
+        "Execution limit exceeded in {program}: {limit} (pc={pc}, instruction={instruction}, \
+         {executed} instructions in {elapsed_ms}ms)"
+    )]
+    ExecutionLimitExceeded {
+        /// Program source type and ID, e.g. `ruleset:payment_rules`
+        program: String,
+        /// Limit that was exceeded
+        limit: String,
+        /// Program counter of the instruction that was about to execute
+        pc: usize,
+        /// Instruction that was about to execute
+        instruction: String,
+        /// Instructions executed before the program was aborted
+        executed: u64,
+        /// Time the program ran, in milliseconds
+        elapsed_ms: u64,
+    },
+
+    /// Recording or replay of external dependencies failed
+    #[error("Replay error: {0}")]
+    Replay(String),
+
+    /// Exporting the decision log failed
+    #[error("Export error: {0}")]
+    Export(String),
+
     /// Reserved field in event data
     #[error("Reserved field '{field}': {reason}")]
     ReservedField {
@@ -57,7 +183,183 @@ pub enum RuntimeError {
     /// Generic runtime error
     #[error("Runtime error: {0}")]
     RuntimeError(String),
+
+    /// An error evaluating a condition, located where the condition is defined
+    #[error("{source} (in `{condition}`, {location})")]
+    InCondition {
+        /// Condition being evaluated, e.g. `event.amount / event.count > 10`
+        condition: String,
+        /// Program of the condition, and its `file:line` when known
+        location: String,
+        /// Underlying error
+        source: Box<RuntimeError>,
+    },
+}
+
+impl RuntimeError {
+    /// Stable machine-readable code of this error
+    pub fn code(&self) -> &'static str {
+        match self {
+            RuntimeError::StackUnderflow => "STACK_UNDERFLOW",
+            RuntimeError::TypeMismatch { .. } => "TYPE_MISMATCH",
+            RuntimeError::MissingField { .. } => "MISSING_FIELD",
+            RuntimeError::InvalidOperation(_) => "INVALID_OPERATION",
+            RuntimeError::DivisionByZero => "DIVISION_BY_ZERO",
+            RuntimeError::VariableAlreadyDeclared(_) => "VARIABLE_ALREADY_DECLARED",
+            RuntimeError::UndeclaredVariable(_) => "UNDECLARED_VARIABLE",
+            RuntimeError::PCOutOfBounds(_) => "PC_OUT_OF_BOUNDS",
+            RuntimeError::ExternalCallFailed(_) => "EXTERNAL_CALL_FAILED",
+            RuntimeError::ConcurrencyLimitReached(_) => "CONCURRENCY_LIMIT_REACHED",
+            RuntimeError::RateLimited(_) => "RATE_LIMITED",
+            RuntimeError::DataSourceTimeout { .. } => "DATASOURCE_TIMEOUT",
+            RuntimeError::DataSourceUnavailable { .. } => "DATASOURCE_UNAVAILABLE",
+            RuntimeError::ListBackendUnavailable { .. } => "LIST_BACKEND_UNAVAILABLE",
+            RuntimeError::CircuitOpen(_) => "CIRCUIT_OPEN",
+            RuntimeError::Timeout(_) => "TIMEOUT",
+            RuntimeError::ExecutionLimitExceeded { .. } => "EXECUTION_LIMIT_EXCEEDED",
+            RuntimeError::Replay(_) => "REPLAY_ERROR",
+            RuntimeError::Export(_) => "EXPORT_ERROR",
+            RuntimeError::ReservedField { .. } => "RESERVED_FIELD",
+            RuntimeError::CoreError(_) => "CORE_ERROR",
+            RuntimeError::InvalidValue(_) => "INVALID_VALUE",
+            RuntimeError::RuntimeError(_) => "RUNTIME_ERROR",
+            RuntimeError::InCondition { source, .. } => source.code(),
+        }
+    }
+
+    /// Broad class of this error
+    pub fn category(&self) -> ErrorCategory {
+        match self {
+            RuntimeError::TypeMismatch { .. }
+            | RuntimeError::MissingField { .. }
+            | RuntimeError::ReservedField { .. }
+            | RuntimeError::InvalidValue(_) => ErrorCategory::Input,
+            RuntimeError::StackUnderflow
+            | RuntimeError::InvalidOperation(_)
+            | RuntimeError::DivisionByZero
+            | RuntimeError::VariableAlreadyDeclared(_)
+            | RuntimeError::UndeclaredVariable(_)
+            | RuntimeError::PCOutOfBounds(_)
+            | RuntimeError::ExecutionLimitExceeded { .. } => ErrorCategory::Execution,
+            RuntimeError::ExternalCallFailed(_)
+            | RuntimeError::ConcurrencyLimitReached(_)
+            | RuntimeError::RateLimited(_)
+            | RuntimeError::DataSourceTimeout { .. }
+            | RuntimeError::DataSourceUnavailable { .. }
+            | RuntimeError::ListBackendUnavailable { .. }
+            | RuntimeError::CircuitOpen(_)
+            | RuntimeError::Timeout(_) => ErrorCategory::Dependency,
+            RuntimeError::Replay(_)
+            | RuntimeError::Export(_)
+            | RuntimeError::CoreError(_)
+            | RuntimeError::RuntimeError(_) => ErrorCategory::Internal,
+            RuntimeError::InCondition { source, .. } => source.category(),
+        }
+    }
+
+    /// Locate an error raised at instruction `pc` of `program` in the
+    /// condition it was compiled from, using the program's source map
+    ///
+    /// Only input and execution errors are wrapped; failures of dependencies
+    /// keep their variant for the fallbacks and retries that match on it.
+    pub fn at_instruction(self, program: &Program, pc: usize) -> Self {
+        let located = matches!(
+            self,
+            RuntimeError::InCondition { .. } | RuntimeError::ExecutionLimitExceeded { .. }
+        );
+        if located
+            || !matches!(
+                self.category(),
+                ErrorCategory::Input | ErrorCategory::Execution
+            )
+        {
+            return self;
+        }
+        let Some(span) = program.source_span(pc) else {
+            return self;
+        };
+
+        let mut location = format!(
+            "{} '{}'",
+            program.metadata.source_type, program.metadata.source_id
+        );
+        if let Some(file_line) = span.location() {
+            location.push_str(" at ");
+            location.push_str(&file_line);
+        }
+        RuntimeError::InCondition {
+            condition: span.text.clone(),
+            location,
+            source: Box::new(self),
+        }
+    }
 }
 
 /// Result type for runtime operations
 pub type Result<T> = std::result::Result<T, RuntimeError>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_codes_and_categories() {
+        let error = RuntimeError::TypeMismatch {
+            expr: "sum".to_string(),
+            message: "requires numeric values, got String(\"ten\")".to_string(),
+        };
+        assert_eq!(error.code(), "TYPE_MISMATCH");
+        assert_eq!(error.category(), ErrorCategory::Input);
+        assert!(error.to_string().starts_with("Type mismatch in sum"));
+
+        let error = RuntimeError::DataSourceTimeout {
+            datasource: "clickhouse".to_string(),
+            message: "operation timed out".to_string(),
+        };
+        assert_eq!(error.code(), "DATASOURCE_TIMEOUT");
+        assert_eq!(error.category(), ErrorCategory::Dependency);
+
+        let error = RuntimeError::ListBackendUnavailable {
+            list_id: "blocked_ips".to_string(),
+            message: "connection refused".to_string(),
+        };
+        assert_eq!(error.code(), "LIST_BACKEND_UNAVAILABLE");
+        assert_eq!(error.category().as_str(), "dependency");
+
+        assert_eq!(
+            RuntimeError::DivisionByZero.category(),
+            ErrorCategory::Execution
+        );
+        assert_eq!(
+            RuntimeError::RuntimeError("boom".to_string()).category(),
+            ErrorCategory::Internal
+        );
+    }
+
+    #[test]
+    fn test_error_at_instruction() {
+        use corint_core::ir::{Instruction, ProgramMetadata, SourceSpan};
+
+        let mut metadata = ProgramMetadata::for_rule("avg_amount".to_string());
+        let mut span = SourceSpan::new(0, 4, "event.amount / event.count > 10");
+        span.file = Some("rules/avg_amount.yaml".to_string());
+        span.line = Some(6);
+        metadata.source_map.push(span);
+        let program = Program::new(vec![Instruction::Return; 6], metadata);
+
+        let error = RuntimeError::DivisionByZero.at_instruction(&program, 2);
+        assert_eq!(error.code(), "DIVISION_BY_ZERO");
+        assert_eq!(error.category(), ErrorCategory::Execution);
+        assert_eq!(
+            error.to_string(),
+            "Division by zero (in `event.amount / event.count > 10`, \
+             rule 'avg_amount' at rules/avg_amount.yaml:6)"
+        );
+
+        // Outside of any condition, and for dependency failures, unchanged
+        let error = RuntimeError::DivisionByZero.at_instruction(&program, 5);
+        assert!(matches!(error, RuntimeError::DivisionByZero));
+        let error = RuntimeError::Timeout("slow".to_string()).at_instruction(&program, 2);
+        assert!(matches!(error, RuntimeError::Timeout(_)));
+    }
+}
@@ -15,15 +15,35 @@ pub struct Executor;
 
 impl Executor {
     /// Execute an IR program with the given event data
+    ///
+    /// An error raised evaluating a condition names the condition, and where
+    /// it is defined when the program's source map has it.
     pub async fn execute(
         program: &Program,
         event_data: HashMap<String, Value>,
+    ) -> Result<ExecutionResult> {
+        let mut failed_at = None;
+        Self::run(program, event_data, &mut failed_at)
+            .await
+            .map_err(|e| match failed_at {
+                Some(pc) => e.at_instruction(program, pc),
+                None => e,
+            })
+    }
+
+    /// Execute the instructions of a program, keeping in `failed_at` the
+    /// position of the instruction being executed
+    async fn run(
+        program: &Program,
+        event_data: HashMap<String, Value>,
+        failed_at: &mut Option<usize>,
     ) -> Result<ExecutionResult> {
         let mut ctx = ExecutionContext::from_event(event_data)?;
         let mut pc = 0; // Program Counter
 
         while pc < program.instructions.len() {
             let instruction = &program.instructions[pc];
+            *failed_at = Some(pc);
 
             match instruction {
                 Instruction::LoadField { path } => {
@@ -152,10 +172,19 @@ impl Executor {
 
                     // For Phase 1 MVP, use a simple in-memory list service
                     // TODO: In Phase 2/3, integrate ListService into ExecutionContext
-                    let list_service = crate::lists::ListService::new_with_memory();
-
-                    // Check if value exists in the list
-                    let contains = list_service.contains(list_id, &value).await?;
+                    #[cfg(feature = "native")]
+                    let contains = crate::lists::ListService::new_with_memory()
+                        .contains(list_id, &value)
+                        .await?;
+
+                    // Without list backends every list is empty, as lists
+                    // missing from the service above
+                    #[cfg(not(feature = "native"))]
+                    let contains = {
+                        tracing::warn!("List '{}' not configured, treating as empty", list_id);
+                        let _ = value;
+                        false
+                    };
 
                     // Apply negation if needed
                     let result = if *negate { !contains } else { contains };
@@ -200,10 +229,10 @@ impl Executor {
                     }
                     Operator::Mod => a % b,
                     _ => {
-                        return Err(RuntimeError::TypeError(format!(
-                            "Invalid operation {:?} for numbers",
-                            op
-                        )))
+                        return Err(RuntimeError::TypeMismatch {
+                            expr: format!("{:?}", op),
+                            message: "not defined for numbers".to_string(),
+                        })
                     }
                 };
                 Ok(Value::Number(result))
@@ -214,19 +243,19 @@ impl Executor {
                     Operator::And => *a && *b,
                     Operator::Or => *a || *b,
                     _ => {
-                        return Err(RuntimeError::TypeError(format!(
-                            "Invalid operation {:?} for booleans",
-                            op
-                        )))
+                        return Err(RuntimeError::TypeMismatch {
+                            expr: format!("{:?}", op),
+                            message: "not defined for booleans".to_string(),
+                        })
                     }
                 };
                 Ok(Value::Bool(result))
             }
 
-            _ => Err(RuntimeError::TypeError(format!(
-                "Type mismatch for operation {:?}",
-                op
-            ))),
+            _ => Err(RuntimeError::TypeMismatch {
+                expr: format!("{:?}", op),
+                message: format!("incompatible operands {:?} and {:?}", left, right),
+            }),
         }
     }
 
@@ -242,10 +271,10 @@ impl Executor {
                     Operator::Le => a <= b,
                     Operator::Ge => a >= b,
                     _ => {
-                        return Err(RuntimeError::TypeError(format!(
-                            "Invalid comparison {:?}",
-                            op
-                        )))
+                        return Err(RuntimeError::TypeMismatch {
+                            expr: format!("{:?}", op),
+                            message: "not a comparison of numbers".to_string(),
+                        })
                     }
                 };
                 Ok(result)
@@ -256,10 +285,10 @@ impl Executor {
                     Operator::Eq => a == b,
                     Operator::Ne => a != b,
                     _ => {
-                        return Err(RuntimeError::TypeError(format!(
-                            "Invalid string comparison {:?}",
-                            op
-                        )))
+                        return Err(RuntimeError::TypeMismatch {
+                            expr: format!("{:?}", op),
+                            message: "not a comparison of strings".to_string(),
+                        })
                     }
                 };
                 Ok(result)
@@ -270,10 +299,10 @@ impl Executor {
                     Operator::Eq => a == b,
                     Operator::Ne => a != b,
                     _ => {
-                        return Err(RuntimeError::TypeError(format!(
-                            "Invalid boolean comparison {:?}",
-                            op
-                        )))
+                        return Err(RuntimeError::TypeMismatch {
+                            expr: format!("{:?}", op),
+                            message: "not a comparison of booleans".to_string(),
+                        })
                     }
                 };
                 Ok(result)
@@ -288,10 +317,12 @@ impl Executor {
         match (operand, op) {
             (Value::Bool(b), UnaryOperator::Not) => Ok(Value::Bool(!b)),
             (Value::Number(n), UnaryOperator::Negate) => Ok(Value::Number(-n)),
-            _ => Err(RuntimeError::TypeError(format!(
-                "Invalid unary operation {:?} for value type",
-                op
-            ))),
+            (value, UnaryOperator::IsNull) => Ok(Value::Bool(matches!(value, Value::Null))),
+            (value, UnaryOperator::IsNotNull) => Ok(Value::Bool(!matches!(value, Value::Null))),
+            _ => Err(RuntimeError::TypeMismatch {
+                expr: format!("{:?}", op),
+                message: format!("not defined for {:?}", operand),
+            }),
         }
     }
 
@@ -2,12 +2,19 @@
 //!
 //! Provides a generic, configurable system for calling external APIs.
 
+use crate::circuit_breaker::{CircuitBreaker, CircuitBreakerConfig, CircuitBreakerStats};
+use crate::concurrency::{ConcurrencyLimit, ConcurrencyLimiter, ConcurrencyStats};
 use crate::context::ExecutionContext;
 use crate::error::{Result, RuntimeError};
+use crate::observability::MetricsCollector;
+use crate::rate_limit::{RateLimit, RateLimitStats, RateLimiter};
+use crate::replay;
+use crate::retry::{RetryCondition, RetryPolicy};
 use corint_core::Value;
 use reqwest::header::HeaderMap;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, OnceLock};
 use std::time::Duration;
 
 /// External API configuration
@@ -30,6 +37,18 @@ pub struct ApiConfig {
     /// Endpoint definitions (as a map: endpoint_name -> endpoint_config)
     #[serde(default)]
     pub endpoints: HashMap<String, ApiEndpoint>,
+
+    /// Optional cap on concurrent calls to this API (across all endpoints)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub concurrency: Option<ConcurrencyLimit>,
+
+    /// Optional circuit breaker, tracked separately for each endpoint
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub circuit_breaker: Option<CircuitBreakerConfig>,
+
+    /// Optional retry policy for all endpoints (default: a single attempt)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retry: Option<RetryPolicy>,
 }
 
 fn default_api_timeout() -> u64 {
@@ -79,6 +98,14 @@ pub struct ApiEndpoint {
     /// Response handling configuration
     #[serde(skip_serializing_if = "Option::is_none")]
     pub response: Option<ApiResponse>,
+
+    /// Optional retry policy for this endpoint (overrides API default)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retry: Option<RetryPolicy>,
+
+    /// Optional cap on the calls per second to this endpoint, retries included
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rate_limit: Option<RateLimit>,
 }
 
 /// Response handling configuration
@@ -121,6 +148,14 @@ impl HttpMethod {
 pub struct ExternalApiClient {
     /// API configurations by name
     configs: HashMap<String, ApiConfig>,
+    /// Concurrency limiters for APIs that configure a limit
+    limiters: HashMap<String, ConcurrencyLimiter>,
+    /// Circuit breakers by `api::endpoint`, for APIs that configure one
+    breakers: HashMap<String, CircuitBreaker>,
+    /// Rate limiters by `api::endpoint`, for endpoints that configure a limit
+    rate_limiters: HashMap<String, RateLimiter>,
+    /// Metrics the circuit breakers count openings and rejections in
+    metrics: OnceLock<Arc<MetricsCollector>>,
     /// HTTP client
     client: reqwest::Client,
 }
@@ -173,6 +208,10 @@ impl ExternalApiClient {
     pub fn new() -> Self {
         Self {
             configs: HashMap::new(),
+            limiters: HashMap::new(),
+            breakers: HashMap::new(),
+            rate_limiters: HashMap::new(),
+            metrics: OnceLock::new(),
             client: build_http_client(Duration::from_secs(10), None).unwrap_or_else(|err| {
                 panic!("Failed to create HTTP client: {}", err);
             }),
@@ -181,10 +220,107 @@ impl ExternalApiClient {
 
     /// Register an API configuration
     pub fn register_api(&mut self, config: ApiConfig) {
+        match config.concurrency {
+            Some(limit) => {
+                self.limiters.insert(
+                    config.name.clone(),
+                    ConcurrencyLimiter::new(config.name.clone(), limit),
+                );
+            }
+            None => {
+                self.limiters.remove(&config.name);
+            }
+        }
+
+        let prefix = format!("{}::", config.name);
+        self.breakers.retain(|key, _| !key.starts_with(&prefix));
+        self.rate_limiters.retain(|key, _| !key.starts_with(&prefix));
+        for (endpoint_name, endpoint) in &config.endpoints {
+            if let Some(limit) = endpoint.rate_limit {
+                let key = Self::breaker_key(&config.name, endpoint_name);
+                self.rate_limiters
+                    .insert(key.clone(), RateLimiter::new(key, limit));
+            }
+        }
+        if let Some(breaker) = config.circuit_breaker {
+            for endpoint_name in config.endpoints.keys() {
+                let key = Self::breaker_key(&config.name, endpoint_name);
+                let breaker = CircuitBreaker::new(key.clone(), breaker);
+                if let Some(metrics) = self.metrics.get() {
+                    breaker.attach_metrics(Arc::clone(metrics));
+                }
+                self.breakers.insert(key, breaker);
+            }
+        }
         self.configs.insert(config.name.clone(), config);
     }
 
+    /// Saturation statistics of all APIs with a concurrency limit
+    pub fn concurrency_stats(&self) -> Vec<ConcurrencyStats> {
+        self.limiters.values().map(ConcurrencyLimiter::stats).collect()
+    }
+
+    /// Statistics of all endpoints with a rate limit
+    pub fn rate_limit_stats(&self) -> Vec<RateLimitStats> {
+        self.rate_limiters.values().map(RateLimiter::stats).collect()
+    }
+
+    /// Circuit breaker statistics of all endpoints of APIs with a circuit breaker
+    pub fn circuit_breaker_stats(&self) -> Vec<CircuitBreakerStats> {
+        self.breakers.values().map(CircuitBreaker::stats).collect()
+    }
+
+    /// Count circuit breaker openings and rejections in `metrics`
+    ///
+    /// Only the first collector attached is used.
+    pub fn attach_metrics(&self, metrics: Arc<MetricsCollector>) {
+        if self.metrics.set(Arc::clone(&metrics)).is_ok() {
+            for breaker in self.breakers.values() {
+                breaker.attach_metrics(Arc::clone(&metrics));
+            }
+        }
+    }
+
+    fn breaker_key(api_name: &str, endpoint_name: &str) -> String {
+        format!("{}::{}", api_name, endpoint_name)
+    }
+
+    /// Timeout in milliseconds of a call to `api_name::endpoint_name`
+    ///
+    /// `timeout` is the one set on the call, if any, which takes precedence
+    /// over the endpoint and API timeouts. `None` for an unknown endpoint.
+    pub fn effective_timeout_ms(
+        &self,
+        api_name: &str,
+        endpoint_name: &str,
+        timeout: Option<u64>,
+    ) -> Option<u64> {
+        let api_config = self.configs.get(api_name)?;
+        let endpoint = api_config.endpoints.get(endpoint_name)?;
+        Some(Self::effective_timeout(api_config, endpoint, timeout))
+    }
+
+    fn effective_timeout(
+        api_config: &ApiConfig,
+        endpoint: &ApiEndpoint,
+        timeout: Option<u64>,
+    ) -> u64 {
+        timeout.or(endpoint.timeout_ms).unwrap_or(api_config.timeout_ms)
+    }
+
+    /// Error of a request that got no response, telling timeouts apart
+    fn request_error(e: reqwest::Error) -> RuntimeError {
+        if e.is_timeout() {
+            RuntimeError::Timeout(format!("HTTP request timed out: {}", e))
+        } else {
+            RuntimeError::ExternalCallFailed(format!("HTTP request failed: {}", e))
+        }
+    }
+
     /// Call an external API endpoint
+    ///
+    /// While a [`ReplaySession`](crate::replay::ReplaySession) is active, the
+    /// response is recorded or replayed, keyed by the call parameters.
     pub async fn call(
         &self,
         api_name: &str,
@@ -192,6 +328,23 @@ impl ExternalApiClient {
         params: &HashMap<String, Value>,
         timeout: Option<u64>,
         ctx: &ExecutionContext,
+    ) -> Result<Value> {
+        let dependency = format!("api:{}::{}", api_name, endpoint_name);
+        let key = serde_json::to_string(&params.iter().collect::<BTreeMap<_, _>>())
+            .unwrap_or_default();
+        replay::intercept(&dependency, &key, || {
+            self.call_live(api_name, endpoint_name, params, timeout, ctx)
+        })
+        .await
+    }
+
+    async fn call_live(
+        &self,
+        api_name: &str,
+        endpoint_name: &str,
+        params: &HashMap<String, Value>,
+        timeout: Option<u64>,
+        ctx: &ExecutionContext,
     ) -> Result<Value> {
         // Get API configuration
         let api_config = self.configs.get(api_name).ok_or_else(|| {
@@ -207,9 +360,7 @@ impl ExternalApiClient {
         })?;
 
         // Determine effective timeout (priority: param > endpoint > API > default)
-        let effective_timeout = timeout
-            .or(endpoint.timeout_ms)
-            .unwrap_or(api_config.timeout_ms);
+        let effective_timeout = Self::effective_timeout(api_config, endpoint, timeout);
 
         // Build the complete URL
         let url = self.build_url(api_config, endpoint, params, ctx)?;
@@ -242,34 +393,124 @@ impl ExternalApiClient {
             RuntimeError::ExternalCallFailed(format!("Invalid HTTP method: {}", endpoint.method))
         })?;
 
-        // Make HTTP request based on method
-        let response = match method {
-            HttpMethod::GET => client.get(&url).send().await.map_err(|e| {
-                RuntimeError::ExternalCallFailed(format!("HTTP request failed: {}", e))
-            })?,
-            HttpMethod::POST | HttpMethod::PUT | HttpMethod::PATCH => {
-                let mut request = match method {
-                    HttpMethod::POST => client.post(&url),
-                    HttpMethod::PUT => client.put(&url),
-                    HttpMethod::PATCH => client.patch(&url),
-                    _ => unreachable!(),
-                };
+        // Process request body if present
+        let body = match (&endpoint.request_body, method) {
+            (Some(body_template), HttpMethod::POST | HttpMethod::PUT | HttpMethod::PATCH) => {
+                Some(self.substitute_body_template(body_template, &endpoint.params, params, ctx)?)
+            }
+            _ => None,
+        };
 
-                // Process request body if present
-                if let Some(body_template) = &endpoint.request_body {
-                    let body = self.substitute_body_template(body_template, &endpoint.params, params, ctx)?;
-                    request = request
-                        .header("Content-Type", "application/json")
-                        .body(body);
+        let fallback = endpoint.response.as_ref().and_then(|r| r.fallback.as_ref());
+        let breaker = self.breakers.get(&Self::breaker_key(api_name, endpoint_name));
+        let retry = endpoint.retry.as_ref().or(api_config.retry.as_ref());
+        let mut attempt = 1;
+        let response = loop {
+            // A failing endpoint is not called while its circuit is open
+            let circuit = match breaker {
+                Some(breaker) => match breaker.try_acquire() {
+                    Ok(permit) => Some(permit),
+                    Err(e) => {
+                        if let Some(fallback) = fallback {
+                            tracing::warn!(
+                                "Circuit of external API {}::{} is open, using endpoint fallback",
+                                api_name,
+                                endpoint_name
+                            );
+                            return Self::json_to_value(fallback.clone());
+                        }
+                        return Err(e);
+                    }
+                },
+                None => None,
+            };
+
+            // Stay within the endpoint's rate limit; a throttled call degrades to the endpoint fallback
+            if let Some(limiter) = self.rate_limiters.get(&Self::breaker_key(api_name, endpoint_name)) {
+                if let Err(e) = limiter.acquire().await {
+                    if let Some(metrics) = self.metrics.get() {
+                        metrics
+                            .labeled_counter(
+                                "external_api_rate_limited_total",
+                                &[("endpoint", limiter.name())],
+                            )
+                            .inc();
+                    }
+                    if let Some(fallback) = fallback {
+                        tracing::warn!(
+                            "External API {}::{} is rate limited, using endpoint fallback",
+                            api_name,
+                            endpoint_name
+                        );
+                        return Self::json_to_value(fallback.clone());
+                    }
+                    return Err(e);
                 }
+            }
 
-                request.send().await.map_err(|e| {
-                    RuntimeError::ExternalCallFailed(format!("HTTP request failed: {}", e))
-                })?
+            // Wait for a concurrency slot; a saturated API degrades to the endpoint fallback
+            let permit = match self.limiters.get(api_name) {
+                Some(limiter) => match limiter.acquire().await {
+                    Ok(permit) => Some(permit),
+                    Err(e) => {
+                        if let Some(fallback) = fallback {
+                            tracing::warn!(
+                                "External API {}::{} is saturated, using endpoint fallback",
+                                api_name,
+                                endpoint_name
+                            );
+                            return Self::json_to_value(fallback.clone());
+                        }
+                        return Err(e);
+                    }
+                },
+                None => None,
+            };
+
+            // Make HTTP request based on method
+            let request = match method {
+                HttpMethod::GET => client.get(&url),
+                HttpMethod::POST => client.post(&url),
+                HttpMethod::PUT => client.put(&url),
+                HttpMethod::PATCH => client.patch(&url),
+                HttpMethod::DELETE => client.delete(&url),
+            };
+            let request = match &body {
+                Some(body) => request
+                    .header("Content-Type", "application/json")
+                    .body(body.clone()),
+                None => request,
+            };
+            let sent = request.send().await;
+            drop(permit);
+
+            // Server errors, throttling and failed requests count against the
+            // circuit and may be retried, other responses do not
+            let failure = match &sent {
+                Ok(response) => RetryCondition::of_status(response.status().as_u16()),
+                Err(e) if e.is_timeout() => Some(RetryCondition::Timeout),
+                Err(_) => Some(RetryCondition::Connection),
+            };
+            if let Some(circuit) = circuit {
+                circuit.record(failure.is_none());
             }
-            HttpMethod::DELETE => client.delete(&url).send().await.map_err(|e| {
-                RuntimeError::ExternalCallFailed(format!("HTTP request failed: {}", e))
-            })?,
+
+            let Some(delay) = retry.and_then(|retry| retry.next_delay(attempt, failure)) else {
+                break sent.map_err(Self::request_error)?;
+            };
+            tracing::warn!(
+                "External API {}::{} failed on attempt {} ({}), retrying in {:?}",
+                api_name,
+                endpoint_name,
+                attempt,
+                match &sent {
+                    Ok(response) => format!("status {}", response.status()),
+                    Err(e) => e.to_string(),
+                },
+                delay
+            );
+            tokio::time::sleep(delay).await;
+            attempt += 1;
         };
 
         // Check if response is successful
@@ -432,10 +673,10 @@ impl ExternalApiClient {
             }
             serde_json::Value::Bool(b) => Ok(Value::Bool(*b)),
             serde_json::Value::Null => Ok(Value::Null),
-            _ => Err(RuntimeError::TypeError(format!(
-                "Unsupported parameter type: {:?}",
-                value
-            ))),
+            _ => Err(RuntimeError::TypeMismatch {
+                expr: "API parameter".to_string(),
+                message: format!("unsupported type {:?}", value),
+            }),
         }
     }
 
@@ -531,9 +772,10 @@ impl ExternalApiClient {
             Value::Number(n) => Ok(n.to_string()),
             Value::Bool(b) => Ok(b.to_string()),
             Value::Null => Ok(String::new()),
-            _ => Err(RuntimeError::TypeError(
-                "Cannot convert complex value to string for URL parameter".to_string(),
-            )),
+            _ => Err(RuntimeError::TypeMismatch {
+                expr: "URL parameter".to_string(),
+                message: "cannot convert a complex value to a string".to_string(),
+            }),
         }
     }
 
@@ -580,6 +822,7 @@ pub fn load_api_config(yaml_content: &str) -> Result<ApiConfig> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::circuit_breaker::CircuitState;
 
     #[test]
     fn test_build_url_with_path_params() {
@@ -597,6 +840,8 @@ mod tests {
                 query_params: vec![],
                 request_body: None,
                 response: None,
+                retry: None,
+                rate_limit: None,
             },
         );
 
@@ -606,6 +851,9 @@ mod tests {
             auth: None,
             timeout_ms: 10000,
             endpoints,
+            concurrency: None,
+            circuit_breaker: None,
+            retry: None,
         };
 
         let endpoint = api_config.endpoints.get("get_user").unwrap();
@@ -640,6 +888,8 @@ mod tests {
                 query_params: vec!["token".to_string(), "format".to_string()],
                 request_body: None,
                 response: None,
+                retry: None,
+                rate_limit: None,
             },
         );
 
@@ -649,6 +899,9 @@ mod tests {
             auth: None,
             timeout_ms: 10000,
             endpoints,
+            concurrency: None,
+            circuit_breaker: None,
+            retry: None,
         };
 
         let endpoint = api_config.endpoints.get("get_data").unwrap();
@@ -669,4 +922,286 @@ mod tests {
         assert!(url.contains("token=abc123"));
         assert!(url.contains("format=json"));
     }
+
+    #[tokio::test]
+    async fn test_saturated_api_uses_fallback() {
+        let endpoint = |fallback: Option<serde_json::Value>| ApiEndpoint {
+            method: "GET".to_string(),
+            path: "/score".to_string(),
+            timeout_ms: None,
+            params: HashMap::new(),
+            query_params: vec![],
+            request_body: None,
+            response: Some(ApiResponse {
+                mapping: HashMap::new(),
+                fallback,
+            }),
+            retry: None,
+            rate_limit: None,
+        };
+
+        let mut endpoints = HashMap::new();
+        endpoints.insert("score".to_string(), endpoint(Some(serde_json::json!({"score": 0}))));
+        endpoints.insert("score_strict".to_string(), endpoint(None));
+
+        // No slots at all, so every call is rejected after the queue deadline
+        let mut client = ExternalApiClient::new();
+        client.register_api(ApiConfig {
+            name: "risk_api".to_string(),
+            base_url: "http://127.0.0.1:9".to_string(),
+            auth: None,
+            timeout_ms: 1000,
+            endpoints,
+            concurrency: Some(ConcurrencyLimit::new(0).with_queue_timeout_ms(1)),
+            circuit_breaker: None,
+            retry: None,
+        });
+        let ctx = ExecutionContext::from_event(HashMap::new()).unwrap();
+
+        let value = client
+            .call("risk_api", "score", &HashMap::new(), None, &ctx)
+            .await
+            .unwrap();
+        match value {
+            Value::Object(map) => assert_eq!(map.get("score"), Some(&Value::Number(0.0))),
+            other => panic!("expected fallback object, got {:?}", other),
+        }
+
+        let err = client
+            .call("risk_api", "score_strict", &HashMap::new(), None, &ctx)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, RuntimeError::ConcurrencyLimitReached(_)));
+
+        let stats = client.concurrency_stats();
+        assert_eq!(stats.len(), 1);
+        assert_eq!(stats[0].rejected, 2);
+    }
+
+    #[tokio::test]
+    async fn test_rate_limited_endpoint_uses_fallback() {
+        let endpoint = |fallback: Option<serde_json::Value>| ApiEndpoint {
+            method: "GET".to_string(),
+            path: "/score".to_string(),
+            timeout_ms: None,
+            params: HashMap::new(),
+            query_params: vec![],
+            request_body: None,
+            response: Some(ApiResponse {
+                mapping: HashMap::new(),
+                fallback,
+            }),
+            retry: None,
+            rate_limit: Some(
+                RateLimit::new(0.001).with_on_limit(crate::rate_limit::RateLimitBehavior::Reject),
+            ),
+        };
+
+        let mut endpoints = HashMap::new();
+        endpoints.insert("score".to_string(), endpoint(Some(serde_json::json!({"score": 0}))));
+        endpoints.insert("score_strict".to_string(), endpoint(None));
+
+        // Each endpoint allows one call, which fails as nothing listens on the discard port
+        let mut client = ExternalApiClient::new();
+        client.register_api(ApiConfig {
+            name: "risk_api".to_string(),
+            base_url: "http://127.0.0.1:9".to_string(),
+            auth: None,
+            timeout_ms: 1000,
+            endpoints,
+            concurrency: None,
+            circuit_breaker: None,
+            retry: None,
+        });
+        let ctx = ExecutionContext::from_event(HashMap::new()).unwrap();
+
+        for endpoint_name in ["score", "score_strict"] {
+            let err = client
+                .call("risk_api", endpoint_name, &HashMap::new(), None, &ctx)
+                .await
+                .unwrap_err();
+            assert!(!matches!(err, RuntimeError::RateLimited(_)));
+        }
+
+        let value = client
+            .call("risk_api", "score", &HashMap::new(), None, &ctx)
+            .await
+            .unwrap();
+        match value {
+            Value::Object(map) => assert_eq!(map.get("score"), Some(&Value::Number(0.0))),
+            other => panic!("expected fallback object, got {:?}", other),
+        }
+
+        let err = client
+            .call("risk_api", "score_strict", &HashMap::new(), None, &ctx)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, RuntimeError::RateLimited(_)));
+
+        let stats = client.rate_limit_stats();
+        assert_eq!(stats.len(), 2);
+        assert!(stats.iter().all(|s| s.acquired == 1 && s.rejected == 1));
+    }
+
+    #[tokio::test]
+    async fn test_open_circuit_skips_calls() {
+        let mut endpoints = HashMap::new();
+        endpoints.insert(
+            "score".to_string(),
+            ApiEndpoint {
+                method: "GET".to_string(),
+                path: "/score".to_string(),
+                timeout_ms: None,
+                params: HashMap::new(),
+                query_params: vec![],
+                request_body: None,
+                response: None,
+                retry: None,
+                rate_limit: None,
+            },
+        );
+
+        // Nothing listens on the discard port, so every call fails
+        let mut client = ExternalApiClient::new();
+        client.register_api(ApiConfig {
+            name: "risk_api".to_string(),
+            base_url: "http://127.0.0.1:9".to_string(),
+            auth: None,
+            timeout_ms: 1000,
+            endpoints,
+            concurrency: None,
+            circuit_breaker: Some(CircuitBreakerConfig::default().with_minimum_calls(2)),
+            retry: None,
+        });
+        let ctx = ExecutionContext::from_event(HashMap::new()).unwrap();
+
+        for _ in 0..2 {
+            let err = client
+                .call("risk_api", "score", &HashMap::new(), None, &ctx)
+                .await
+                .unwrap_err();
+            assert!(!matches!(err, RuntimeError::CircuitOpen(_)));
+        }
+        let err = client
+            .call("risk_api", "score", &HashMap::new(), None, &ctx)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, RuntimeError::CircuitOpen(_)));
+
+        let stats = client.circuit_breaker_stats();
+        assert_eq!(stats.len(), 1);
+        assert_eq!(stats[0].name, "risk_api::score");
+        assert_eq!(stats[0].state, CircuitState::Open);
+        assert_eq!(stats[0].rejected, 1);
+    }
+
+    #[tokio::test]
+    async fn test_failed_calls_are_retried() {
+        let endpoint = |retry: Option<RetryPolicy>| ApiEndpoint {
+            method: "GET".to_string(),
+            path: "/score".to_string(),
+            timeout_ms: None,
+            params: HashMap::new(),
+            query_params: vec![],
+            request_body: None,
+            response: None,
+            retry,
+            rate_limit: None,
+        };
+
+        let mut endpoints = HashMap::new();
+        endpoints.insert("score".to_string(), endpoint(None));
+        endpoints.insert(
+            "score_once".to_string(),
+            endpoint(Some(RetryPolicy::new(1))),
+        );
+
+        // Nothing listens on the discard port; each attempt counts against the circuit
+        let mut client = ExternalApiClient::new();
+        client.register_api(ApiConfig {
+            name: "risk_api".to_string(),
+            base_url: "http://127.0.0.1:9".to_string(),
+            auth: None,
+            timeout_ms: 1000,
+            endpoints,
+            concurrency: None,
+            circuit_breaker: Some(CircuitBreakerConfig::default()),
+            retry: Some(RetryPolicy::new(3).with_backoff(1, 1)),
+        });
+        let ctx = ExecutionContext::from_event(HashMap::new()).unwrap();
+
+        for endpoint_name in ["score", "score_once"] {
+            let err = client
+                .call("risk_api", endpoint_name, &HashMap::new(), None, &ctx)
+                .await
+                .unwrap_err();
+            assert!(matches!(err, RuntimeError::ExternalCallFailed(_)));
+        }
+
+        let stats = client.circuit_breaker_stats();
+        let calls = |name: &str| {
+            stats
+                .iter()
+                .find(|stats| stats.name == name)
+                .map(|stats| stats.window_calls)
+        };
+        assert_eq!(calls("risk_api::score"), Some(3));
+        assert_eq!(calls("risk_api::score_once"), Some(1));
+    }
+
+    #[tokio::test]
+    async fn test_replayed_call_skips_http() {
+        use crate::replay::{RecordedCall, RecordedResponse, Recording, ReplaySession};
+        use std::sync::Arc;
+
+        let mut endpoints = HashMap::new();
+        endpoints.insert(
+            "score".to_string(),
+            ApiEndpoint {
+                method: "GET".to_string(),
+                path: "/score".to_string(),
+                timeout_ms: None,
+                params: HashMap::new(),
+                query_params: vec![],
+                request_body: None,
+                response: None,
+                retry: None,
+                rate_limit: None,
+            },
+        );
+
+        // Nothing listens on the discard port, so a real call would fail
+        let mut client = ExternalApiClient::new();
+        client.register_api(ApiConfig {
+            name: "risk_api".to_string(),
+            base_url: "http://127.0.0.1:9".to_string(),
+            auth: None,
+            timeout_ms: 1000,
+            endpoints,
+            concurrency: None,
+            circuit_breaker: None,
+            retry: None,
+        });
+        let ctx = ExecutionContext::from_event(HashMap::new()).unwrap();
+
+        let recording = Recording {
+            request_id: "req_1".to_string(),
+            recorded_at: chrono::Utc::now(),
+            calls: vec![RecordedCall {
+                dependency: "api:risk_api::score".to_string(),
+                key: "{}".to_string(),
+                response: RecordedResponse::Ok(serde_json::json!({"score": 87})),
+            }],
+        };
+        let session = Arc::new(ReplaySession::replay(recording));
+
+        let value = session
+            .scope(client.call("risk_api", "score", &HashMap::new(), None, &ctx))
+            .await
+            .unwrap();
+        match value {
+            Value::Object(map) => assert_eq!(map.get("score"), Some(&Value::Number(87.0))),
+            other => panic!("expected recorded object, got {:?}", other),
+        }
+    }
 }
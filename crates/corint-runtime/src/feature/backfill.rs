@@ -0,0 +1,552 @@
+//! Offline feature computation
+//!
+//! Computes the feature definitions used in decisions for every event of a
+//! historical range read from [`Storage`], to build ML training datasets or to
+//! fill a feature store. Each event becomes one [`BackfillRow`] written to a
+//! [`BackfillSink`]:
+//!
+//! - [`FileBackfillSink`] writes one CSV or Parquet (feature `parquet`) file
+//!   per batch, with an `event_timestamp` column, the selected `event.<field>`
+//!   columns and one `feature.<name>` column per feature;
+//! - [`FeatureStoreBackfillSink`] stores the values in a feature store under
+//!   each event's entity key, in the layout lookup features read.
+//!
+//! Features are computed by a [`FeatureExecutor`] as in a decision: windows
+//! end when the backfill runs, not at each event's timestamp, so features over
+//! event history describe the history at backfill time.
+
+use crate::context::ExecutionContext;
+use crate::datasource::DataSourceClient;
+use crate::feature::executor::{FeatureExecutor, FetchStatus};
+use crate::feature::expression::ExpressionEvaluator;
+use crate::result::{io_error, write_atomically};
+use crate::storage::{Event, EventFilter, Storage, TimeRange};
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use corint_core::Value;
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tracing::{info, warn};
+
+/// What to backfill
+#[derive(Debug, Clone, PartialEq)]
+pub struct BackfillConfig {
+    /// Events to compute the features for, in seconds since epoch (end exclusive)
+    pub time_range: TimeRange,
+
+    /// Only events of this type
+    pub event_type: Option<String>,
+
+    /// Features to compute; every enabled feature when empty
+    pub features: Vec<String>,
+
+    /// Length of the slices the range is read from storage in (default: 1 hour)
+    pub slice: Duration,
+
+    /// Maximum number of rows per sink write (default: 10000)
+    pub batch_size: usize,
+
+    /// Events computed concurrently (default: 8)
+    pub concurrency: usize,
+}
+
+impl BackfillConfig {
+    /// Backfill every enabled feature for the events in `time_range`
+    pub fn new(time_range: TimeRange) -> Self {
+        Self {
+            time_range,
+            event_type: None,
+            features: Vec::new(),
+            slice: Duration::from_secs(3600),
+            batch_size: 10_000,
+            concurrency: 8,
+        }
+    }
+
+    /// Only backfill events of this type
+    pub fn with_event_type(mut self, event_type: impl Into<String>) -> Self {
+        self.event_type = Some(event_type.into());
+        self
+    }
+
+    /// Set the features to compute
+    pub fn with_features(mut self, features: Vec<String>) -> Self {
+        self.features = features;
+        self
+    }
+
+    /// Set the length of the slices read from storage
+    pub fn with_slice(mut self, slice: Duration) -> Self {
+        self.slice = slice.max(Duration::from_secs(1));
+        self
+    }
+
+    /// Set the maximum number of rows per sink write
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    /// Set how many events are computed concurrently
+    pub fn with_concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+}
+
+/// Features computed for one event
+#[derive(Debug, Clone, PartialEq)]
+pub struct BackfillRow {
+    /// Event time in seconds since epoch
+    pub timestamp: i64,
+
+    /// The event's fields
+    pub event: HashMap<String, Value>,
+
+    /// Feature values in the configured order; `Null` when not computed
+    pub features: Vec<(String, Value)>,
+}
+
+/// Destination of backfilled rows
+#[async_trait]
+pub trait BackfillSink: Send + Sync {
+    /// Write a batch of rows, in event time order
+    async fn write(&self, rows: &[BackfillRow]) -> Result<()>;
+}
+
+/// Outcome of a backfill run
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct BackfillSummary {
+    /// Events read from storage
+    pub events: u64,
+
+    /// Rows written to the sink
+    pub rows: u64,
+
+    /// Feature values that failed or timed out
+    pub failed_values: u64,
+}
+
+/// Computes features over historical events
+pub struct FeatureBackfill {
+    executor: Arc<FeatureExecutor>,
+    storage: Arc<dyn Storage>,
+    config: BackfillConfig,
+}
+
+impl FeatureBackfill {
+    /// Create a backfill computing features with `executor` over `storage`
+    pub fn new(
+        executor: Arc<FeatureExecutor>,
+        storage: Arc<dyn Storage>,
+        config: BackfillConfig,
+    ) -> Self {
+        Self {
+            executor,
+            storage,
+            config,
+        }
+    }
+
+    /// Compute the features for every event in the range and write them to `sink`
+    pub async fn run(&self, sink: &dyn BackfillSink) -> Result<BackfillSummary> {
+        let features = if self.config.features.is_empty() {
+            self.executor.feature_names()
+        } else {
+            self.config.features.clone()
+        };
+        if let Some(name) = features
+            .iter()
+            .find(|name| !self.executor.has_feature(name))
+        {
+            anyhow::bail!("Feature '{}' not found", name);
+        }
+
+        let (start, end) = self.config.time_range;
+        let slice = self.config.slice.as_secs().max(1) as i64;
+        let mut summary = BackfillSummary::default();
+        let mut batch = Vec::new();
+
+        let mut slice_start = start;
+        while slice_start < end {
+            let slice_end = slice_start.saturating_add(slice).min(end);
+            let filter = self
+                .config
+                .event_type
+                .clone()
+                .map(|event_type| EventFilter::new().with_event_type(event_type));
+            let mut events = self
+                .storage
+                .query_events((slice_start, slice_end), filter)
+                .await
+                .with_context(|| format!("Failed to read events {}..{}", slice_start, slice_end))?;
+            events.sort_by_key(|event| event.timestamp);
+
+            let mut rows = futures::stream::iter(events)
+                .map(|event| self.compute_row(event, &features))
+                .buffered(self.config.concurrency.max(1));
+            while let Some((row, failed)) = rows.next().await {
+                summary.events += 1;
+                summary.failed_values += failed;
+                batch.push(row);
+                if batch.len() >= self.config.batch_size {
+                    sink.write(&batch).await?;
+                    summary.rows += batch.len() as u64;
+                    batch.clear();
+                }
+            }
+
+            slice_start = slice_end;
+        }
+
+        if !batch.is_empty() {
+            sink.write(&batch).await?;
+            summary.rows += batch.len() as u64;
+        }
+
+        info!(
+            "Backfilled {} features for {} events ({} values failed)",
+            features.len(),
+            summary.events,
+            summary.failed_values
+        );
+        Ok(summary)
+    }
+
+    /// Compute the features of one event, with the number of failed values
+    async fn compute_row(&self, event: Event, features: &[String]) -> (BackfillRow, u64) {
+        let fetches = match ExecutionContext::from_event(event.data.clone()) {
+            Ok(context) => self.executor.fetch_features(features, &context).await,
+            Err(e) => Err(e.into()),
+        };
+
+        let (values, failed) = match fetches {
+            Ok(fetches) => {
+                let mut failed = 0;
+                let mut values: HashMap<String, Value> = HashMap::new();
+                for fetch in fetches {
+                    if features.contains(&fetch.feature) {
+                        if fetch.status != FetchStatus::Ok {
+                            failed += 1;
+                        }
+                        values.insert(fetch.feature, fetch.value);
+                    }
+                }
+                (values, failed)
+            }
+            Err(e) => {
+                warn!(
+                    "Failed to compute features for event at {}: {}",
+                    event.timestamp, e
+                );
+                (HashMap::new(), features.len() as u64)
+            }
+        };
+
+        let row = BackfillRow {
+            timestamp: event.timestamp,
+            features: features
+                .iter()
+                .map(|name| {
+                    (
+                        name.clone(),
+                        values.get(name).cloned().unwrap_or(Value::Null),
+                    )
+                })
+                .collect(),
+            event: event.data,
+        };
+        (row, failed)
+    }
+}
+
+/// Dataset file format of a [`FileBackfillSink`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BackfillFormat {
+    /// Comma-separated values with a header row
+    #[default]
+    Csv,
+
+    /// Apache Parquet (requires the `parquet` feature)
+    Parquet,
+}
+
+impl BackfillFormat {
+    /// File extension of the format
+    pub fn extension(&self) -> &'static str {
+        match self {
+            BackfillFormat::Csv => "csv",
+            BackfillFormat::Parquet => "parquet",
+        }
+    }
+}
+
+/// Writes backfilled rows to dataset files, one file per batch
+pub struct FileBackfillSink {
+    format: BackfillFormat,
+    output_dir: PathBuf,
+    event_fields: Vec<String>,
+    files: Mutex<Vec<PathBuf>>,
+}
+
+impl FileBackfillSink {
+    /// Write files in `format` to `output_dir`, created if missing
+    pub fn new(format: BackfillFormat, output_dir: impl Into<PathBuf>) -> Result<Self> {
+        #[cfg(not(feature = "parquet"))]
+        if format == BackfillFormat::Parquet {
+            anyhow::bail!("Parquet output requires the 'parquet' feature");
+        }
+
+        let output_dir = output_dir.into();
+        std::fs::create_dir_all(&output_dir).map_err(|e| io_error(&output_dir, e))?;
+        Ok(Self {
+            format,
+            output_dir,
+            event_fields: Vec::new(),
+            files: Mutex::new(Vec::new()),
+        })
+    }
+
+    /// Copy these event fields into `event.<field>` columns, e.g. labels or keys
+    pub fn with_event_fields(mut self, event_fields: Vec<String>) -> Self {
+        self.event_fields = event_fields;
+        self
+    }
+
+    /// Files written so far
+    pub fn files(&self) -> Vec<PathBuf> {
+        self.files.lock().unwrap().clone()
+    }
+
+    /// Flatten a row into ordered `(column, value)` pairs
+    fn columns(&self, row: &BackfillRow) -> Vec<(String, JsonValue)> {
+        let mut columns = vec![("event_timestamp".to_string(), row.timestamp.into())];
+        for field in &self.event_fields {
+            let value = row.event.get(field).map_or(JsonValue::Null, json_value);
+            columns.push((format!("event.{}", field), value));
+        }
+        for (name, value) in &row.features {
+            columns.push((format!("feature.{}", name), json_value(value)));
+        }
+        columns
+    }
+}
+
+#[async_trait]
+impl BackfillSink for FileBackfillSink {
+    async fn write(&self, rows: &[BackfillRow]) -> Result<()> {
+        let rows: Vec<_> = rows.iter().map(|row| self.columns(row)).collect();
+        let data = match self.format {
+            BackfillFormat::Csv => encode_csv(&rows)?,
+            #[cfg(feature = "parquet")]
+            BackfillFormat::Parquet => crate::result::encode_parquet(&rows)?,
+            #[cfg(not(feature = "parquet"))]
+            BackfillFormat::Parquet => unreachable!("rejected in new"),
+        };
+
+        let mut files = self.files.lock().unwrap();
+        let path = self.output_dir.join(format!(
+            "features-{:06}.{}",
+            files.len() + 1,
+            self.format.extension()
+        ));
+        write_atomically(&path, &data)?;
+        info!("Wrote {} backfilled rows to {:?}", rows.len(), path);
+        files.push(path);
+        Ok(())
+    }
+}
+
+/// Stores backfilled values in a feature store
+///
+/// Each feature value is stored under the feature name and the event's entity
+/// key; `null` values are skipped. Rows are written in event time order, so
+/// the store ends up with each key's value for its latest event.
+pub struct FeatureStoreBackfillSink {
+    datasource: Arc<DataSourceClient>,
+    key: String,
+}
+
+impl FeatureStoreBackfillSink {
+    /// Store values in `datasource` under the entity key rendered from `key`
+    /// (e.g., `${event.user_id}`)
+    pub fn new(datasource: Arc<DataSourceClient>, key: impl Into<String>) -> Self {
+        Self {
+            datasource,
+            key: key.into(),
+        }
+    }
+}
+
+#[async_trait]
+impl BackfillSink for FeatureStoreBackfillSink {
+    async fn write(&self, rows: &[BackfillRow]) -> Result<()> {
+        for row in rows {
+            let key = match ExpressionEvaluator::substitute_template(&self.key, &row.event) {
+                Ok(key) if !key.is_empty() => key,
+                _ => {
+                    warn!("Skipping event at {} without an entity key", row.timestamp);
+                    continue;
+                }
+            };
+            for (name, value) in &row.features {
+                if *value != Value::Null {
+                    self.datasource
+                        .set_feature(name, &key, value)
+                        .await
+                        .with_context(|| {
+                            format!("Failed to store feature '{}' for '{}'", name, key)
+                        })?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Convert a value for a dataset column; nested values become JSON text
+fn json_value(value: &Value) -> JsonValue {
+    match serde_json::to_value(value) {
+        Ok(value @ (JsonValue::Array(_) | JsonValue::Object(_))) => value.to_string().into(),
+        Ok(value) => value,
+        Err(_) => JsonValue::Null,
+    }
+}
+
+/// Encode rows as CSV, with the union of the rows' columns as header
+fn encode_csv(rows: &[Vec<(String, JsonValue)>]) -> Result<Vec<u8>> {
+    let mut header: Vec<&str> = Vec::new();
+    for row in rows {
+        for (name, _) in row {
+            if !header.contains(&name.as_str()) {
+                header.push(name);
+            }
+        }
+    }
+
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    writer
+        .write_record(&header)
+        .context("Failed to encode CSV")?;
+    for row in rows {
+        let values: HashMap<&str, &JsonValue> = row.iter().map(|(k, v)| (k.as_str(), v)).collect();
+        let record = header.iter().map(|name| match values.get(name) {
+            None | Some(JsonValue::Null) => String::new(),
+            Some(JsonValue::String(s)) => s.clone(),
+            Some(JsonValue::Number(n)) => match n.as_f64() {
+                Some(f) if f.fract() == 0.0 && f.abs() < 1e15 => format!("{}", f as i64),
+                _ => n.to_string(),
+            },
+            Some(other) => other.to_string(),
+        });
+        writer
+            .write_record(record)
+            .context("Failed to encode CSV")?;
+    }
+    writer.into_inner().context("Failed to encode CSV")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::datasource::client::tests::static_client;
+    use crate::feature::FeatureDefinition;
+    use crate::storage::InMemoryStorage;
+
+    fn event(timestamp: i64, event_type: &str, user_id: Option<&str>) -> Event {
+        let mut data = HashMap::from([(
+            "event_type".to_string(),
+            Value::String(event_type.to_string()),
+        )]);
+        if let Some(user_id) = user_id {
+            data.insert("user_id".to_string(), Value::String(user_id.to_string()));
+        }
+        Event { timestamp, data }
+    }
+
+    #[tokio::test]
+    async fn test_backfill_to_csv() {
+        let count = vec![HashMap::from([("count".to_string(), Value::Number(3.0))])];
+        let mut executor = FeatureExecutor::new();
+        executor.add_datasource(
+            "events",
+            static_client("events", Some(count), Duration::ZERO),
+        );
+        let features: Vec<FeatureDefinition> = serde_yaml::from_str(
+            r#"
+- name: txn_count
+  type: aggregation
+  method: count
+  datasource: events
+  entity: events
+  dimension: user_id
+  dimension_value: "${event.user_id}"
+- name: unused
+  type: aggregation
+  method: count
+  datasource: events
+  entity: events
+  dimension: user_id
+  dimension_value: "${event.user_id}"
+"#,
+        )
+        .unwrap();
+        executor.register_features(features).unwrap();
+
+        let mut storage = InMemoryStorage::new();
+        storage.add_events(vec![
+            event(250, "transaction", Some("u2")),
+            event(100, "transaction", Some("u1")),
+            event(150, "login", Some("u1")),
+            event(200, "transaction", None),
+            event(400, "transaction", Some("u3")),
+        ]);
+
+        let config = BackfillConfig::new((0, 300))
+            .with_event_type("transaction")
+            .with_features(vec!["txn_count".to_string()])
+            .with_slice(Duration::from_secs(100))
+            .with_batch_size(2);
+        let backfill = FeatureBackfill::new(Arc::new(executor), Arc::new(storage), config);
+
+        let dir = tempfile::tempdir().unwrap();
+        let sink = FileBackfillSink::new(BackfillFormat::Csv, dir.path())
+            .unwrap()
+            .with_event_fields(vec!["user_id".to_string()]);
+        let summary = backfill.run(&sink).await.unwrap();
+        assert_eq!(
+            summary,
+            BackfillSummary {
+                events: 3,
+                rows: 3,
+                failed_values: 1,
+            }
+        );
+
+        let files = sink.files();
+        assert_eq!(files.len(), 2);
+        assert!(files[0].ends_with("features-000001.csv"));
+        assert_eq!(
+            std::fs::read_to_string(&files[0]).unwrap(),
+            "event_timestamp,event.user_id,feature.txn_count\n100,u1,3\n200,,\n"
+        );
+        assert_eq!(
+            std::fs::read_to_string(&files[1]).unwrap(),
+            "event_timestamp,event.user_id,feature.txn_count\n250,u2,3\n"
+        );
+
+        let config = BackfillConfig::new((0, 300)).with_features(vec!["missing".to_string()]);
+        let backfill = FeatureBackfill::new(
+            Arc::new(FeatureExecutor::new()),
+            Arc::new(InMemoryStorage::new()),
+            config,
+        );
+        assert!(backfill.run(&sink).await.is_err());
+    }
+}
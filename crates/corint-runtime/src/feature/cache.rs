@@ -216,6 +216,13 @@ impl CacheManager {
             Operator::Max(op) => op.params.cache.as_ref(),
             Operator::Min(op) => op.params.cache.as_ref(),
             Operator::CountDistinct(op) => op.params.cache.as_ref(),
+            Operator::Percentile(op) => op.params.cache.as_ref(),
+            Operator::Stddev(op) => op.params.cache.as_ref(),
+            Operator::Median(op) => op.params.cache.as_ref(),
+            Operator::Mode(op) => op.params.cache.as_ref(),
+            Operator::CountDistinctRatio(op) => op.params.cache.as_ref(),
+            Operator::Entropy(op) => op.params.cache.as_ref(),
+            Operator::GeoVelocity(op) => op.params.cache.as_ref(),
             Operator::CrossDimensionCount(_) => None, // No cache support for this operator
             Operator::FirstSeen(_) => None,           // No cache support for this operator
             Operator::LastSeen(_) => None,            // No cache support for this operator
@@ -0,0 +1,452 @@
+//! Incremental counters for high-QPS velocity features
+//!
+//! Counter features such as "failed logins for this user in the last hour"
+//! read a running total from a [`CounterStore`] instead of scanning event
+//! history on every decision. Callers increment a named counter per entity
+//! key as events happen, and the store keeps the totals in fixed-size time
+//! buckets so that any window up to the retention can be read back.
+//!
+//! A window covers every bucket that overlaps it, so reads are accurate to
+//! one bucket: with the default one-minute buckets, a one-hour read may
+//! include up to a minute of older increments.
+
+use crate::datasource::RelativeWindow;
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+#[cfg(feature = "redis")]
+use redis::aio::ConnectionManager;
+
+/// Default bucket size in seconds
+pub const DEFAULT_BUCKET_SECS: u64 = 60;
+
+/// Default retention in seconds (7 days)
+pub const DEFAULT_RETENTION_SECS: u64 = 7 * 86400;
+
+/// Bucketing of counter increments over time
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CounterBuckets {
+    /// Bucket size in seconds; the resolution of windowed reads
+    pub bucket_secs: u64,
+
+    /// How long increments are kept; the longest window that can be read
+    pub retention_secs: u64,
+}
+
+impl Default for CounterBuckets {
+    fn default() -> Self {
+        Self {
+            bucket_secs: DEFAULT_BUCKET_SECS,
+            retention_secs: DEFAULT_RETENTION_SECS,
+        }
+    }
+}
+
+impl CounterBuckets {
+    /// Start of the bucket holding `ts` (seconds since epoch)
+    pub fn bucket_of(&self, ts: i64) -> i64 {
+        let size = self.bucket_secs.max(1) as i64;
+        ts.div_euclid(size) * size
+    }
+
+    /// Starts of the buckets overlapping `window` up to `now`, oldest first
+    pub fn window_buckets(&self, window: &RelativeWindow, now: i64) -> Result<Vec<i64>> {
+        let window_secs = window.to_seconds();
+        if window_secs > self.retention_secs {
+            anyhow::bail!(
+                "Counter window of {}s exceeds the retention of {}s",
+                window_secs,
+                self.retention_secs
+            );
+        }
+        let first = self.bucket_of(now - window_secs as i64);
+        let last = self.bucket_of(now);
+        Ok((first..=last)
+            .step_by(self.bucket_secs.max(1) as usize)
+            .collect())
+    }
+
+    fn oldest_retained(&self, now: i64) -> i64 {
+        self.bucket_of(now - self.retention_secs as i64)
+    }
+}
+
+/// Store of named counters keyed by entity
+///
+/// Only [`increment_at`](CounterStore::increment_at) and
+/// [`read`](CounterStore::read) are required.
+#[async_trait]
+pub trait CounterStore: Send + Sync {
+    /// Add `amount` to `counter` for `key` at a given time
+    async fn increment_at(
+        &self,
+        counter: &str,
+        key: &str,
+        amount: f64,
+        at: DateTime<Utc>,
+    ) -> Result<()>;
+
+    /// Add `amount` to `counter` for `key` now
+    async fn increment(&self, counter: &str, key: &str, amount: f64) -> Result<()> {
+        self.increment_at(counter, key, amount, Utc::now()).await
+    }
+
+    /// Total of `counter` for `key` over the window ending now
+    async fn read(&self, counter: &str, key: &str, window: &RelativeWindow) -> Result<f64>;
+}
+
+/// Counters of one entity key, by bucket start
+type BucketTotals = BTreeMap<i64, f64>;
+
+#[derive(Serialize, Deserialize)]
+struct SnapshotEntry {
+    counter: String,
+    key: String,
+    buckets: BucketTotals,
+}
+
+/// In-process counter store, optionally persisted to a JSON snapshot file
+///
+/// With [`with_persistence`](Self::with_persistence) the counters are loaded
+/// from the snapshot on creation and written back by
+/// [`persist`](Self::persist), which
+/// [`spawn_persistence`](Self::spawn_persistence) calls in the background.
+#[derive(Default)]
+pub struct InMemoryCounterStore {
+    buckets: CounterBuckets,
+    counters: RwLock<HashMap<(String, String), BucketTotals>>,
+    snapshot_path: Option<PathBuf>,
+}
+
+impl InMemoryCounterStore {
+    /// Create an empty store
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the bucket size and retention
+    pub fn with_buckets(mut self, buckets: CounterBuckets) -> Self {
+        self.buckets = buckets;
+        self
+    }
+
+    /// Persist to a snapshot file, loading it first if it exists
+    pub fn with_persistence(mut self, path: impl Into<PathBuf>) -> Result<Self> {
+        let path = path.into();
+        if path.exists() {
+            let entries = load_snapshot(&path)?;
+            let counters = self.counters.get_mut().unwrap();
+            for entry in entries {
+                counters.insert((entry.counter, entry.key), entry.buckets);
+            }
+        }
+        self.snapshot_path = Some(path);
+        Ok(self)
+    }
+
+    /// Drop buckets older than the retention
+    pub fn prune(&self) {
+        let oldest = self.buckets.oldest_retained(Utc::now().timestamp());
+        let mut counters = self.counters.write().unwrap();
+        for totals in counters.values_mut() {
+            *totals = totals.split_off(&oldest);
+        }
+        counters.retain(|_, totals| !totals.is_empty());
+    }
+
+    /// Write the counters to the snapshot file, if persistence is enabled
+    pub fn persist(&self) -> Result<()> {
+        let Some(path) = &self.snapshot_path else {
+            return Ok(());
+        };
+
+        let entries: Vec<SnapshotEntry> = self
+            .counters
+            .read()
+            .unwrap()
+            .iter()
+            .map(|((counter, key), buckets)| SnapshotEntry {
+                counter: counter.clone(),
+                key: key.clone(),
+                buckets: buckets.clone(),
+            })
+            .collect();
+
+        // Write then rename so a crash never leaves a partial snapshot
+        let tmp = path.with_extension("tmp");
+        std::fs::write(&tmp, serde_json::to_vec(&entries)?)
+            .with_context(|| format!("Failed to write counter snapshot {}", tmp.display()))?;
+        std::fs::rename(&tmp, path)
+            .with_context(|| format!("Failed to replace counter snapshot {}", path.display()))?;
+        Ok(())
+    }
+
+    /// Prune and persist every `interval` until the store is dropped
+    pub fn spawn_persistence(self: &Arc<Self>, interval: Duration) -> tokio::task::JoinHandle<()> {
+        let store = Arc::downgrade(self);
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            ticker.tick().await;
+            loop {
+                ticker.tick().await;
+                let Some(store) = store.upgrade() else {
+                    break;
+                };
+                store.prune();
+                if let Err(e) = store.persist() {
+                    tracing::warn!("Failed to persist counters: {:#}", e);
+                }
+            }
+        })
+    }
+}
+
+fn load_snapshot(path: &Path) -> Result<Vec<SnapshotEntry>> {
+    let bytes = std::fs::read(path)
+        .with_context(|| format!("Failed to read counter snapshot {}", path.display()))?;
+    serde_json::from_slice(&bytes)
+        .with_context(|| format!("Invalid counter snapshot {}", path.display()))
+}
+
+#[async_trait]
+impl CounterStore for InMemoryCounterStore {
+    async fn increment_at(
+        &self,
+        counter: &str,
+        key: &str,
+        amount: f64,
+        at: DateTime<Utc>,
+    ) -> Result<()> {
+        let bucket = self.buckets.bucket_of(at.timestamp());
+        let mut counters = self.counters.write().unwrap();
+        *counters
+            .entry((counter.to_string(), key.to_string()))
+            .or_default()
+            .entry(bucket)
+            .or_default() += amount;
+        Ok(())
+    }
+
+    async fn read(&self, counter: &str, key: &str, window: &RelativeWindow) -> Result<f64> {
+        let buckets = self
+            .buckets
+            .window_buckets(window, Utc::now().timestamp())?;
+        let (Some(first), Some(last)) = (buckets.first(), buckets.last()) else {
+            return Ok(0.0);
+        };
+
+        let counters = self.counters.read().unwrap();
+        Ok(counters
+            .get(&(counter.to_string(), key.to_string()))
+            .map_or(0.0, |totals| {
+                totals.range(first..=last).map(|(_, v)| v).sum()
+            }))
+    }
+}
+
+/// Counter store backed by Redis, with one expiring key per bucket
+///
+/// Keys are `{prefix}:{counter}:{key}:{bucket_start}`, incremented with
+/// `INCRBYFLOAT` and read back with a single `MGET`, so the cost of a read
+/// grows with the number of buckets in the window.
+#[cfg(feature = "redis")]
+pub struct RedisCounterStore {
+    conn: ConnectionManager,
+    prefix: String,
+    buckets: CounterBuckets,
+}
+
+#[cfg(feature = "redis")]
+impl RedisCounterStore {
+    /// Create a store over an existing connection
+    pub fn new(conn: ConnectionManager) -> Self {
+        Self {
+            conn,
+            prefix: "counter".to_string(),
+            buckets: CounterBuckets::default(),
+        }
+    }
+
+    /// Connect to a Redis URL
+    pub async fn connect(url: &str) -> Result<Self> {
+        let client = redis::Client::open(url)
+            .with_context(|| format!("Failed to create Redis client for {}", url))?;
+        let conn = ConnectionManager::new(client)
+            .await
+            .with_context(|| format!("Failed to connect to Redis at {}", url))?;
+        Ok(Self::new(conn))
+    }
+
+    /// Set the key prefix (default "counter")
+    pub fn with_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.prefix = prefix.into();
+        self
+    }
+
+    /// Set the bucket size and retention
+    pub fn with_buckets(mut self, buckets: CounterBuckets) -> Self {
+        self.buckets = buckets;
+        self
+    }
+
+    fn bucket_key(&self, counter: &str, key: &str, bucket: i64) -> String {
+        format!("{}:{}:{}:{}", self.prefix, counter, key, bucket)
+    }
+}
+
+#[cfg(feature = "redis")]
+#[async_trait]
+impl CounterStore for RedisCounterStore {
+    async fn increment_at(
+        &self,
+        counter: &str,
+        key: &str,
+        amount: f64,
+        at: DateTime<Utc>,
+    ) -> Result<()> {
+        let bucket = self.buckets.bucket_of(at.timestamp());
+        let redis_key = self.bucket_key(counter, key, bucket);
+        // Keep each bucket until the last window that can overlap it has passed
+        let ttl = self.buckets.retention_secs + self.buckets.bucket_secs;
+
+        let mut conn = self.conn.clone();
+        redis::pipe()
+            .atomic()
+            .cmd("INCRBYFLOAT")
+            .arg(&redis_key)
+            .arg(amount)
+            .ignore()
+            .cmd("EXPIRE")
+            .arg(&redis_key)
+            .arg(ttl)
+            .ignore()
+            .query_async::<_, ()>(&mut conn)
+            .await
+            .with_context(|| format!("Failed to increment counter '{}'", counter))
+    }
+
+    async fn read(&self, counter: &str, key: &str, window: &RelativeWindow) -> Result<f64> {
+        let keys: Vec<String> = self
+            .buckets
+            .window_buckets(window, Utc::now().timestamp())?
+            .into_iter()
+            .map(|bucket| self.bucket_key(counter, key, bucket))
+            .collect();
+        if keys.is_empty() {
+            return Ok(0.0);
+        }
+
+        let mut conn = self.conn.clone();
+        let totals: Vec<Option<f64>> =
+            redis::cmd("MGET")
+                .arg(&keys)
+                .query_async(&mut conn)
+                .await
+                .with_context(|| format!("Failed to read counter '{}'", counter))?;
+        Ok(totals.into_iter().flatten().sum())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::datasource::TimeUnit;
+
+    fn window(value: u64, unit: TimeUnit) -> RelativeWindow {
+        RelativeWindow { value, unit }
+    }
+
+    #[test]
+    fn test_window_buckets() {
+        let buckets = CounterBuckets::default();
+        assert_eq!(buckets.bucket_of(125), 120);
+        assert_eq!(buckets.bucket_of(-1), -60);
+
+        // Five minutes ending mid-bucket overlaps six buckets
+        let starts = buckets
+            .window_buckets(&window(5, TimeUnit::Minutes), 1_000)
+            .unwrap();
+        assert_eq!(starts, vec![660, 720, 780, 840, 900, 960]);
+
+        assert!(buckets
+            .window_buckets(&window(2, TimeUnit::Weeks), 1_000)
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_in_memory_counters() {
+        let store = InMemoryCounterStore::new();
+        let now = Utc::now();
+
+        store.increment("login_failures", "u1", 1.0).await.unwrap();
+        store.increment("login_failures", "u1", 2.0).await.unwrap();
+        store
+            .increment_at(
+                "login_failures",
+                "u1",
+                5.0,
+                now - chrono::Duration::hours(3),
+            )
+            .await
+            .unwrap();
+        store.increment("login_failures", "u2", 1.0).await.unwrap();
+
+        let hour = window(1, TimeUnit::Hours);
+        assert_eq!(
+            store.read("login_failures", "u1", &hour).await.unwrap(),
+            3.0
+        );
+        assert_eq!(
+            store
+                .read("login_failures", "u1", &window(1, TimeUnit::Days))
+                .await
+                .unwrap(),
+            8.0
+        );
+        assert_eq!(
+            store.read("login_failures", "u3", &hour).await.unwrap(),
+            0.0
+        );
+        assert_eq!(store.read("payments", "u1", &hour).await.unwrap(), 0.0);
+
+        // Increments past the retention are pruned
+        store
+            .increment_at(
+                "login_failures",
+                "u1",
+                7.0,
+                now - chrono::Duration::days(30),
+            )
+            .await
+            .unwrap();
+        store.prune();
+        assert_eq!(
+            store.counters.read().unwrap()[&("login_failures".to_string(), "u1".to_string())].len(),
+            2
+        );
+    }
+
+    #[tokio::test]
+    async fn test_counter_persistence() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("counters.json");
+        let hour = window(1, TimeUnit::Hours);
+
+        let store = InMemoryCounterStore::new().with_persistence(&path).unwrap();
+        store.increment("payments", "u1", 42.5).await.unwrap();
+        store.persist().unwrap();
+
+        let restored = InMemoryCounterStore::new().with_persistence(&path).unwrap();
+        assert_eq!(restored.read("payments", "u1", &hour).await.unwrap(), 42.5);
+
+        std::fs::write(&path, "not json").unwrap();
+        assert!(InMemoryCounterStore::new().with_persistence(&path).is_err());
+    }
+}
@@ -36,6 +36,9 @@ pub enum FeatureType {
 
     /// Lookup features - retrieve pre-computed values
     Lookup,
+
+    /// Counter features - read incrementally maintained counters
+    Counter,
 }
 
 /// Aggregation methods
@@ -60,22 +63,31 @@ pub enum AggregationMethod {
     /// Count unique values
     Distinct,
 
-    /// Standard deviation (planned)
+    /// Unique values divided by matching events
+    CountDistinctRatio,
+
+    /// Standard deviation
     Stddev,
 
     /// Variance (planned)
     Variance,
 
-    /// Nth percentile value (planned)
+    /// Nth percentile value
     Percentile,
 
-    /// Median value (planned)
+    /// Median value
     Median,
 
-    /// Most frequent value (planned)
+    /// Most frequent value
     Mode,
 
-    /// Shannon entropy (planned)
+    /// Sum weighted by event age, halving every `half_life`
+    DecayedSum,
+
+    /// Count weighted by event age, halving every `half_life`
+    DecayedCount,
+
+    /// Shannon entropy in bits
     Entropy,
 }
 
@@ -146,6 +158,12 @@ pub enum GraphMethod {
 
     /// Distance between entities in graph
     NetworkDistance,
+
+    /// Count distinct linked entities of one type (e.g. users per device)
+    LinkedEntityCount,
+
+    /// Number of distinct entities of any type linked to a node
+    NodeDegree,
 }
 
 /// Expression methods
@@ -231,6 +249,14 @@ pub struct AggregationConfig {
     /// Percentile value (for percentile method)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub percentile: Option<u8>,
+
+    /// Half-life of event weights (e.g., "1h", "7d"; for decayed methods)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub half_life: Option<String>,
+
+    /// Seconds to cache the result, overriding the datasource cache TTL
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cache_ttl: Option<u64>,
 }
 
 /// State feature configuration
@@ -307,11 +333,20 @@ pub struct SequenceConfig {
 /// Graph feature configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GraphConfig {
+    /// Graph store or data source name
     pub datasource: String,
+
+    /// Node type (e.g., device_id)
     pub dimension: String,
+
+    /// Template for the node value (e.g., "${event.device_id}")
     pub dimension_value: String,
-    pub dimension2: String,
 
+    /// Linked or intermediate entity type (e.g., user_id); not needed for node_degree
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dimension2: Option<String>,
+
+    /// Template for the second node value (two-node methods)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub dimension_value2: Option<String>,
 
@@ -349,6 +384,68 @@ pub struct LookupConfig {
     pub fallback: Option<Value>,
 }
 
+/// Counter feature configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CounterConfig {
+    /// Counter store name
+    pub datasource: String,
+
+    /// Counter name (e.g., login_failures)
+    pub counter: String,
+
+    /// Template for the counter key (e.g., "${event.user_id}")
+    pub dimension_value: String,
+
+    /// Window to total the counter over (e.g., 1h)
+    pub window: String,
+}
+
+/// One datasource in a feature's fallback chain
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SourceHop {
+    /// Data source name
+    pub datasource: String,
+
+    /// Key template for feature store hops (defaults to the lookup key)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key: Option<String>,
+
+    /// Move on to the next source after this many milliseconds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_ms: Option<u64>,
+}
+
+/// What a feature evaluates to when its value is missing
+///
+/// A value is missing when computing it fails, times out, or gives `null`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MissingPolicy {
+    /// The feature's `default` value
+    Default,
+
+    /// The mean of the values computed so far, or `default` before the first
+    Mean,
+
+    /// Fail the evaluation reading the feature
+    FailClosed,
+
+    /// `null`, so conditions on the feature do not match
+    FailOpen,
+}
+
+impl MissingPolicy {
+    /// Policy name used in traces
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MissingPolicy::Default => "default",
+            MissingPolicy::Mean => "mean",
+            MissingPolicy::FailClosed => "fail_closed",
+            MissingPolicy::FailOpen => "fail_open",
+        }
+    }
+}
+
 /// Feature definition following DSL v0.2
 #[derive(Debug, Clone, Serialize)]
 pub struct FeatureDefinition {
@@ -389,6 +486,22 @@ pub struct FeatureDefinition {
     #[serde(skip, default)]
     pub lookup: Option<LookupConfig>,
 
+    /// Counter-specific configuration
+    #[serde(flatten, default)]
+    pub counter: Option<CounterConfig>,
+
+    /// Datasources tried in order until one has a value, instead of `datasource`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sources: Vec<SourceHop>,
+
+    /// Value used when every source fails or the value is missing
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default: Option<Value>,
+
+    /// What the feature evaluates to when its value is missing
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub on_missing: Option<MissingPolicy>,
+
     /// Human-readable description
     #[serde(default, skip_serializing_if = "String::is_empty")]
     pub description: String,
@@ -424,7 +537,7 @@ impl<'de> Deserialize<'de> for FeatureDefinition {
             .ok_or_else(|| serde::de::Error::custom("Missing or invalid 'type' field"))?;
         
         // Deserialize based on type
-        match feature_type {
+        let mut definition = match feature_type {
             FeatureType::Lookup => {
                 // For lookup, manually extract fields (avoid recursion)
                 let name = value.get("name").and_then(|v| v.as_str())
@@ -451,6 +564,10 @@ impl<'de> Deserialize<'de> for FeatureDefinition {
                     graph: None,
                     expression: None,
                     lookup: None, // Will be set in post-processing
+                    counter: None,
+                    sources: Vec::new(),
+                    default: None,
+                    on_missing: None,
                     description,
                     dependencies,
                     tags,
@@ -488,6 +605,10 @@ impl<'de> Deserialize<'de> for FeatureDefinition {
                     graph: None,
                     expression: None,
                     lookup: None,
+                    counter: None,
+                    sources: Vec::new(),
+                    default: None,
+                    on_missing: None,
                     description,
                     dependencies,
                     tags,
@@ -524,6 +645,10 @@ impl<'de> Deserialize<'de> for FeatureDefinition {
                     graph: None,
                     expression: None,
                     lookup: None,
+                    counter: None,
+                    sources: Vec::new(),
+                    default: None,
+                    on_missing: None,
                     description,
                     dependencies,
                     tags,
@@ -560,6 +685,10 @@ impl<'de> Deserialize<'de> for FeatureDefinition {
                     graph: None,
                     expression: None,
                     lookup: None,
+                    counter: None,
+                    sources: Vec::new(),
+                    default: None,
+                    on_missing: None,
                     description,
                     dependencies,
                     tags,
@@ -596,6 +725,10 @@ impl<'de> Deserialize<'de> for FeatureDefinition {
                     graph: Some(graph_config),
                     expression: None,
                     lookup: None,
+                    counter: None,
+                    sources: Vec::new(),
+                    default: None,
+                    on_missing: None,
                     description,
                     dependencies,
                     tags,
@@ -632,6 +765,10 @@ impl<'de> Deserialize<'de> for FeatureDefinition {
                     graph: None,
                     expression: Some(expr_config),
                     lookup: None,
+                    counter: None,
+                    sources: Vec::new(),
+                    default: None,
+                    on_missing: None,
                     description,
                     dependencies,
                     tags,
@@ -639,7 +776,66 @@ impl<'de> Deserialize<'de> for FeatureDefinition {
                     version,
                 })
             }
+            FeatureType::Counter => {
+                // For counter, deserialize CounterConfig explicitly
+                let counter_config: CounterConfig = serde_yaml::from_value(value.clone())
+                    .map_err(|e| serde::de::Error::custom(format!("Failed to deserialize counter config: {}", e)))?;
+                
+                let name = value.get("name").and_then(|v| v.as_str())
+                    .ok_or_else(|| serde::de::Error::missing_field("name"))?
+                    .to_string();
+                let method = value.get("method").and_then(|v| v.as_str()).map(|s| s.to_string());
+                let description = value.get("description").and_then(|v| v.as_str()).unwrap_or("").to_string();
+                let dependencies = value.get("dependencies")
+                    .and_then(|v| Vec::<String>::deserialize(v).ok())
+                    .unwrap_or_default();
+                let tags = value.get("tags")
+                    .and_then(|v| Vec::<String>::deserialize(v).ok())
+                    .unwrap_or_default();
+                let enabled = value.get("enabled").and_then(|v| v.as_bool()).unwrap_or(true);
+                let version = value.get("version").and_then(|v| v.as_str()).unwrap_or("1.0").to_string();
+                
+                Ok(FeatureDefinition {
+                    name,
+                    feature_type,
+                    method,
+                    aggregation: None,
+                    state: None,
+                    sequence: None,
+                    graph: None,
+                    expression: None,
+                    lookup: None,
+                    counter: Some(counter_config),
+                    sources: Vec::new(),
+                    default: None,
+                    on_missing: None,
+                    description,
+                    dependencies,
+                    tags,
+                    enabled,
+                    version,
+                })
+            }
+        }?;
+
+        if let Some(sources) = value.get("sources") {
+            definition.sources = Vec::<SourceHop>::deserialize(sources)
+                .map_err(|e| serde::de::Error::custom(format!("Invalid sources: {}", e)))?;
+        }
+        if let Some(default) = value.get("default") {
+            definition.default = Some(
+                Value::deserialize(default)
+                    .map_err(|e| serde::de::Error::custom(format!("Invalid default: {}", e)))?,
+            );
         }
+        if let Some(on_missing) = value.get("on_missing") {
+            definition.on_missing = Some(
+                MissingPolicy::deserialize(on_missing)
+                    .map_err(|e| serde::de::Error::custom(format!("Invalid on_missing: {}", e)))?,
+            );
+        }
+
+        Ok(definition)
     }
 }
 
@@ -714,12 +910,18 @@ impl FeatureDefinition {
                         timestamp_field: None,
                         when: None,
                         percentile: None,
+                        half_life: None,
+                        cache_ttl: None,
                     }),
                     state: None,
                     sequence: None,
                     graph: None,
                     expression: None,
                     lookup: None,
+                    counter: None,
+                    sources: Vec::new(),
+                    default: None,
+                    on_missing: None,
                     description: String::new(),
                     dependencies: Vec::new(),
                     tags: Vec::new(),
@@ -743,12 +945,18 @@ impl FeatureDefinition {
                         timestamp_field: None,
                         when: None,
                         percentile: None,
+                        half_life: None,
+                        cache_ttl: None,
                     }),
                     state: None,
                     sequence: None,
                     graph: None,
                     expression: None,
                     lookup: None,
+                    counter: None,
+                    sources: Vec::new(),
+                    default: None,
+                    on_missing: None,
                     description: String::new(),
                     dependencies: Vec::new(),
                     tags: Vec::new(),
@@ -791,15 +999,43 @@ impl FeatureDefinition {
                 // Expression method is optional - defaults to "expression" if not specified
                 // This avoids redundancy since expression type always uses expression method
             }
-            FeatureType::Lookup => {
-                // Lookup does not require method
+            FeatureType::Lookup | FeatureType::Counter => {
+                // Lookup and counter features do not require method
                 // Config fields are validated during execution
             }
         }
 
+        // Expression, graph and counter features don't read from datasources
+        if !self.sources.is_empty()
+            && matches!(
+                self.feature_type,
+                FeatureType::Expression | FeatureType::Graph | FeatureType::Counter
+            )
+        {
+            return Err(format!(
+                "Feature '{}': sources are not supported for {:?} features",
+                self.name, self.feature_type
+            ));
+        }
+
+        if self.on_missing == Some(MissingPolicy::Default) && self.default.is_none() {
+            return Err(format!(
+                "Feature '{}': on_missing: default requires a default value",
+                self.name
+            ));
+        }
+
         Ok(())
     }
 
+    /// Policy applied when the value is missing
+    ///
+    /// A feature with a `default` but no `on_missing` uses the default.
+    pub fn missing_policy(&self) -> Option<MissingPolicy> {
+        self.on_missing
+            .or_else(|| self.default.as_ref().map(|_| MissingPolicy::Default))
+    }
+
     /// Check if this feature is enabled
     pub fn is_enabled(&self) -> bool {
         self.enabled
@@ -934,12 +1170,18 @@ mod tests {
                 timestamp_field: None,
                 when: None,
                 percentile: None,
+                half_life: None,
+                cache_ttl: None,
             }),
             state: None,
             sequence: None,
             graph: None,
             expression: None,
             lookup: None,
+            counter: None,
+            sources: Vec::new(),
+            default: None,
+            on_missing: None,
             description: String::new(),
             dependencies: Vec::new(),
             tags: Vec::new(),
@@ -967,6 +1209,10 @@ mod tests {
                 key: "user_risk_score:{event.user_id}".to_string(),
                 fallback: Some(Value::Number(50.0)),
             }),
+            counter: None,
+            sources: Vec::new(),
+            default: None,
+            on_missing: None,
             description: String::new(),
             dependencies: Vec::new(),
             tags: Vec::new(),
@@ -995,6 +1241,10 @@ mod tests {
                 output: None,
             }),
             lookup: None,
+            counter: None,
+            sources: Vec::new(),
+            default: None,
+            on_missing: None,
             description: String::new(),
             dependencies: Vec::new(),
             tags: Vec::new(),
@@ -1025,6 +1275,10 @@ mod tests {
                 output: None,
             }),
             lookup: None,
+            counter: None,
+            sources: Vec::new(),
+            default: None,
+            on_missing: None,
             description: "Transaction velocity ratio".to_string(),
             dependencies: Vec::new(),
             tags: Vec::new(),
@@ -1055,12 +1309,18 @@ mod tests {
                 timestamp_field: None,
                 when: None,
                 percentile: None,
+                half_life: None,
+                cache_ttl: None,
             }),
             state: None,
             sequence: None,
             graph: None,
             expression: None,
             lookup: None,
+            counter: None,
+            sources: Vec::new(),
+            default: None,
+            on_missing: None,
             description: String::new(),
             dependencies: Vec::new(),
             tags: Vec::new(),
@@ -1155,4 +1415,61 @@ features:
         assert_eq!(state.unit, Some("days".to_string()));
         assert_eq!(state.timestamp_field, Some("event_timestamp".to_string()));
     }
+
+    #[test]
+    fn test_counter_feature_from_yaml() {
+        let yaml = r#"
+features:
+  - name: login_failures_1h
+    type: counter
+    datasource: velocity_counters
+    counter: login_failures
+    dimension_value: "${event.user_id}"
+    window: 1h
+"#;
+
+        let collection: FeatureCollection = serde_yaml::from_str(yaml).expect("Failed to parse YAML");
+        let feature = &collection.features[0];
+        assert_eq!(feature.feature_type, FeatureType::Counter);
+        assert!(feature.validate().is_ok());
+
+        let counter = feature.counter.as_ref().expect("Counter config should be populated");
+        assert_eq!(counter.datasource, "velocity_counters");
+        assert_eq!(counter.counter, "login_failures");
+        assert_eq!(counter.window, "1h");
+    }
+
+    #[test]
+    fn test_missing_policy_from_yaml() {
+        let yaml = r#"
+features:
+  - name: avg_amount_30d
+    type: aggregation
+    method: avg
+    datasource: postgresql_events
+    entity: events
+    dimension: user_id
+    dimension_value: "${event.user_id}"
+    field: amount
+    window: 30d
+    on_missing: mean
+  - name: user_risk_score
+    type: expression
+    expression: "1.0"
+    default: 0.5
+  - name: kyc_level
+    type: expression
+    expression: "1.0"
+    on_missing: default
+"#;
+
+        let collection: FeatureCollection = serde_yaml::from_str(yaml).expect("Failed to parse YAML");
+        assert_eq!(collection.features[0].missing_policy(), Some(MissingPolicy::Mean));
+        assert_eq!(collection.features[1].missing_policy(), Some(MissingPolicy::Default));
+        assert_eq!(collection.features[1].default, Some(Value::Number(0.5)));
+
+        // The default policy needs a default value
+        let err = collection.features[2].validate().unwrap_err();
+        assert!(err.contains("requires a default value"), "{}", err);
+    }
 }
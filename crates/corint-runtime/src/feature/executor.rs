@@ -6,25 +6,125 @@
 //! - Handles batch feature execution
 //! - Manages feature dependencies
 
+use crate::circuit_breaker::CircuitBreakerStats;
 use crate::context::ExecutionContext;
 use crate::datasource::DataSourceClient;
+use crate::entity::EntityRegistry;
 use crate::feature::cache::CacheManager;
-use crate::feature::definition::FeatureDefinition;
+use crate::feature::definition::{FeatureDefinition, MissingPolicy, SourceHop};
 use crate::feature::expression::ExpressionEvaluator;
-use crate::feature::operator::{CacheBackend, Operator};
+use crate::feature::counter::CounterStore;
+use crate::feature::graph::{GraphNode, GraphStore, SqlAdjacencyGraphStore};
+use crate::feature::operator::{grouped_entropy, CacheBackend, Operator};
+use crate::observability::MetricsCollector;
 use anyhow::{Context as AnyhowContext, Result};
 use corint_core::condition::ConditionParser;
 use corint_core::Value;
 use futures::future;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::future::Future;
 use std::pin::Pin;
-use std::sync::Arc;
-use tracing::{debug, warn};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::Duration;
+use tracing::{debug, info, warn};
 
 // Re-export CacheStats for public API
 pub use crate::feature::cache::CacheStats;
 
+/// Time limits for [`FeatureExecutor::fetch_features`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FetchLimits {
+    /// Longest a single feature may take once its dependencies are computed
+    pub feature_timeout: Option<Duration>,
+
+    /// Longest the whole fetch may take; features not done by then time out
+    pub total_budget: Option<Duration>,
+}
+
+/// Outcome of fetching one feature
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FetchStatus {
+    /// The feature was computed
+    Ok,
+
+    /// Computing the feature or one of its dependencies failed
+    Failed,
+
+    /// The feature timeout or the total budget ran out
+    TimedOut,
+}
+
+impl FetchStatus {
+    /// Status name used in traces
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FetchStatus::Ok => "ok",
+            FetchStatus::Failed => "failed",
+            FetchStatus::TimedOut => "timed_out",
+        }
+    }
+}
+
+/// One feature fetched by [`FeatureExecutor::fetch_features`]
+#[derive(Debug, Clone)]
+pub struct FeatureFetch {
+    /// Feature name
+    pub feature: String,
+
+    /// Computed value, or the value its missing-value policy gave instead
+    pub value: Value,
+
+    /// Fetch outcome
+    pub status: FetchStatus,
+
+    /// Time spent on this feature, excluding its dependencies
+    pub latency: Duration,
+
+    /// Why the fetch did not succeed
+    pub error: Option<String>,
+
+    /// Missing-value policy applied, when the value was missing
+    pub imputed: Option<MissingPolicy>,
+}
+
+/// Features added, changed and removed by [`FeatureExecutor::replace_features`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FeatureChanges {
+    /// Registry version after the replacement
+    pub registry_version: u64,
+
+    /// Features that were not registered before
+    pub added: Vec<String>,
+
+    /// Features whose definition changed
+    pub updated: Vec<String>,
+
+    /// Features that are no longer registered
+    pub removed: Vec<String>,
+}
+
+impl FeatureChanges {
+    /// Whether any feature was added, changed or removed
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.updated.is_empty() && self.removed.is_empty()
+    }
+}
+
+/// Feature definitions by name
+type FeatureSet = HashMap<String, FeatureDefinition>;
+
+/// A feature whose missing-value policy is fail-closed has no value
+#[derive(Debug, thiserror::Error)]
+#[error("Feature '{feature}' is missing and fails closed: {reason}")]
+pub struct MissingFeatureError {
+    /// Feature name
+    pub feature: String,
+
+    /// Why the value is missing
+    pub reason: String,
+}
+
 /// Feature executor that handles feature computation and caching
 pub struct FeatureExecutor {
     /// Cache manager (handles L1/L2 caching and statistics)
@@ -33,8 +133,26 @@ pub struct FeatureExecutor {
     /// Data source clients for feature computation
     datasources: HashMap<String, Arc<DataSourceClient>>,
 
-    /// Feature definitions registry
-    features: HashMap<String, FeatureDefinition>,
+    /// Feature definitions registry, replaced as a whole on reload
+    features: RwLock<Arc<FeatureSet>>,
+
+    /// Incremented each time the feature definitions are replaced
+    registry_version: AtomicU64,
+
+    /// Entity definitions referenced by `dimension_value: entity.<name>`
+    entities: EntityRegistry,
+
+    /// Graph stores for graph features, by datasource name
+    graph_stores: HashMap<String, Arc<dyn GraphStore>>,
+
+    /// Counter stores for counter features, by datasource name
+    counter_stores: HashMap<String, Arc<dyn CounterStore>>,
+
+    /// Time limits for fetch_features
+    fetch_limits: FetchLimits,
+
+    /// Count and sum of the values computed per mean-filled feature
+    observed_values: Mutex<HashMap<String, (u64, f64)>>,
 }
 
 impl FeatureExecutor {
@@ -43,7 +161,13 @@ impl FeatureExecutor {
         Self {
             cache_manager: CacheManager::new(),
             datasources: HashMap::new(),
-            features: HashMap::new(),
+            features: RwLock::new(Arc::new(HashMap::new())),
+            registry_version: AtomicU64::new(0),
+            entities: EntityRegistry::new(),
+            graph_stores: HashMap::new(),
+            counter_stores: HashMap::new(),
+            fetch_limits: FetchLimits::default(),
+            observed_values: Mutex::new(HashMap::new()),
         }
     }
 
@@ -53,19 +177,100 @@ impl FeatureExecutor {
         self
     }
 
+    /// Set the entity definitions used to derive dimension keys
+    pub fn with_entities(mut self, entities: EntityRegistry) -> Self {
+        self.entities = entities;
+        self
+    }
+
+    /// Set the time limits for [`fetch_features`](Self::fetch_features)
+    pub fn with_fetch_limits(mut self, limits: FetchLimits) -> Self {
+        self.fetch_limits = limits;
+        self
+    }
+
+    /// Time limits for [`fetch_features`](Self::fetch_features)
+    pub fn fetch_limits(&self) -> FetchLimits {
+        self.fetch_limits
+    }
+
+    /// Entity definitions used to derive dimension keys
+    pub fn entities(&self) -> &EntityRegistry {
+        &self.entities
+    }
+
     /// Add a data source client
     pub fn add_datasource(&mut self, name: impl Into<String>, client: DataSourceClient) {
         self.datasources.insert(name.into(), Arc::new(client));
     }
 
+    /// Count circuit breaker openings and rejections of all data sources in `metrics`
+    pub fn attach_metrics(&self, metrics: Arc<MetricsCollector>) {
+        for datasource in self.datasources.values() {
+            datasource.attach_metrics(Arc::clone(&metrics));
+        }
+    }
+
+    /// Circuit breaker statistics of all data sources with a circuit breaker
+    pub fn circuit_breaker_stats(&self) -> Vec<CircuitBreakerStats> {
+        self.datasources
+            .values()
+            .filter_map(|datasource| datasource.circuit_breaker_stats())
+            .collect()
+    }
+
+    /// Add a graph store used by graph features with this datasource name
+    ///
+    /// Graph features whose datasource has no registered graph store fall back
+    /// to a [`SqlAdjacencyGraphStore`] over the data source client of that name.
+    pub fn add_graph_store(&mut self, name: impl Into<String>, store: Arc<dyn GraphStore>) {
+        self.graph_stores.insert(name.into(), store);
+    }
+
+    /// Add a counter store used by counter features with this datasource name
+    pub fn add_counter_store(&mut self, name: impl Into<String>, store: Arc<dyn CounterStore>) {
+        self.counter_stores.insert(name.into(), store);
+    }
+
+    /// Increment a counter read by counter features
+    ///
+    /// Counters are not derived from events; the caller increments them as
+    /// events happen, e.g. `login_failures` by user after each failed login.
+    pub async fn increment_counter(
+        &self,
+        store: &str,
+        counter: &str,
+        key: &str,
+        amount: f64,
+    ) -> Result<()> {
+        let store = self.counter_stores.get(store)
+            .with_context(|| format!("Counter store '{}' not found", store))?;
+        store.increment(counter, key, amount).await
+    }
+
     /// Register a feature definition
+    ///
+    /// Expression features without declared dependencies depend on the
+    /// features their expression references.
     pub fn register_feature(&mut self, feature: FeatureDefinition) -> Result<()> {
+        let feature = Self::prepare_feature(feature)?;
+        let features = self.features.get_mut().unwrap_or_else(|e| e.into_inner());
+        Arc::make_mut(features).insert(feature.name.clone(), feature);
+        Ok(())
+    }
+
+    /// Fill in expression dependencies and validate a feature
+    fn prepare_feature(mut feature: FeatureDefinition) -> Result<FeatureDefinition> {
+        if feature.dependencies.is_empty() {
+            if let Some(expr) = feature.expression.as_ref().and_then(|e| e.expression.as_ref()) {
+                feature.dependencies = ExpressionEvaluator::extract_dependencies(expr);
+            }
+        }
+
         feature
             .validate()
             .map_err(|e| anyhow::anyhow!("Failed to validate feature '{}': {}", feature.name, e))?;
-
-        self.features.insert(feature.name.clone(), feature);
-        Ok(())
+        Ok(feature)
     }
 
     /// Register multiple features
@@ -76,9 +281,115 @@ impl FeatureExecutor {
         Ok(())
     }
 
+    /// Replace every registered feature at once
+    ///
+    /// The new definitions are validated together, including their
+    /// dependencies, before any of them is used; on error the current ones
+    /// stay in place. Datasources, stores and caches are kept, except cached
+    /// values of changed and removed features. Executions already running
+    /// finish with the definitions they started with.
+    pub async fn replace_features(&self, features: Vec<FeatureDefinition>) -> Result<FeatureChanges> {
+        let mut new_features = FeatureSet::new();
+        for feature in features {
+            let feature = Self::prepare_feature(feature)?;
+            if new_features.contains_key(&feature.name) {
+                anyhow::bail!("Feature '{}' is defined more than once", feature.name);
+            }
+            new_features.insert(feature.name.clone(), feature);
+        }
+        for feature in new_features.values() {
+            if let Some(dep) = feature.dependencies.iter().find(|dep| !new_features.contains_key(*dep)) {
+                anyhow::bail!("Feature '{}' depends on unknown feature '{}'", feature.name, dep);
+            }
+        }
+        let names: Vec<String> = new_features.keys().cloned().collect();
+        dependency_levels(&new_features, &names)?;
+
+        let new_features = Arc::new(new_features);
+        let old_features = {
+            let mut features = self.features.write().unwrap_or_else(|e| e.into_inner());
+            std::mem::replace(&mut *features, new_features.clone())
+        };
+        let registry_version = self.registry_version.fetch_add(1, Ordering::SeqCst) + 1;
+
+        let mut changes = FeatureChanges {
+            registry_version,
+            ..Default::default()
+        };
+        for (name, feature) in new_features.iter() {
+            match old_features.get(name) {
+                None => changes.added.push(name.clone()),
+                Some(old) if !same_definition(old, feature) => {
+                    if old.version == feature.version {
+                        warn!(
+                            "Feature '{}' changed without a version change (version {})",
+                            name, feature.version
+                        );
+                    } else {
+                        debug!("Feature '{}' updated from version {} to {}", name, old.version, feature.version);
+                    }
+                    changes.updated.push(name.clone());
+                }
+                Some(_) => {}
+            }
+        }
+        changes.removed = old_features
+            .keys()
+            .filter(|name| !new_features.contains_key(*name))
+            .cloned()
+            .collect();
+        changes.added.sort();
+        changes.updated.sort();
+        changes.removed.sort();
+
+        if !changes.updated.is_empty() || !changes.removed.is_empty() {
+            self.cache_manager.clear_cache().await;
+            let mut observed = self.observed_values.lock().unwrap_or_else(|e| e.into_inner());
+            for name in changes.updated.iter().chain(&changes.removed) {
+                observed.remove(name);
+            }
+        }
+
+        info!(
+            "Feature registry version {}: {} added, {} updated, {} removed",
+            registry_version,
+            changes.added.len(),
+            changes.updated.len(),
+            changes.removed.len()
+        );
+        Ok(changes)
+    }
+
+    /// Number of times the feature definitions have been replaced
+    pub fn registry_version(&self) -> u64 {
+        self.registry_version.load(Ordering::SeqCst)
+    }
+
+    /// Version of a registered feature
+    pub fn feature_version(&self, feature_name: &str) -> Option<String> {
+        self.features().get(feature_name).map(|f| f.version.clone())
+    }
+
+    /// The registered feature definitions
+    fn features(&self) -> Arc<FeatureSet> {
+        self.features.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Names of the enabled features, sorted
+    pub fn feature_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self
+            .features()
+            .values()
+            .filter(|f| f.is_enabled())
+            .map(|f| f.name.clone())
+            .collect();
+        names.sort();
+        names
+    }
+
     /// Check if a feature is registered
     pub fn has_feature(&self, feature_name: &str) -> bool {
-        self.features.contains_key(feature_name)
+        self.features().contains_key(feature_name)
     }
 
     /// Execute a single feature by name
@@ -96,14 +407,34 @@ impl FeatureExecutor {
         feature_name: &str,
         context: &ExecutionContext,
     ) -> Result<Value> {
-        use std::time::Instant;
-        let start_time = Instant::now();
-
-        let feature = self
-            .features
+        let features = self.features();
+        let feature = features
             .get(feature_name)
             .with_context(|| format!("Feature '{}' not found", feature_name))?;
 
+        if feature.dependencies.is_empty() || !feature.is_enabled() {
+            let result = self.execute_resolved(feature, context, HashMap::new()).await;
+            return self.resolve_missing(feature, result).map(|(value, _)| value);
+        }
+
+        // Compute each transitive dependency once, level by level
+        let mut values = self
+            .execute_levels(&features, &[feature_name.to_string()], context)
+            .await?;
+        Ok(values.remove(feature_name).unwrap_or(Value::Null))
+    }
+
+    /// Execute a feature whose dependencies are already computed
+    async fn execute_resolved(
+        &self,
+        feature: &FeatureDefinition,
+        context: &ExecutionContext,
+        dep_values: HashMap<String, Value>,
+    ) -> Result<Value> {
+        use std::time::Instant;
+        let start_time = Instant::now();
+        let feature_name = feature.name.as_str();
+
         if !feature.is_enabled() {
             return Ok(Value::Null);
         }
@@ -111,45 +442,13 @@ impl FeatureExecutor {
         // Build context map from ExecutionContext (use event namespace)
         let context_map = context.event.clone();
 
-        // Check dependencies first - compute them in parallel
-        let dep_start = Instant::now();
-        let dep_values = if !feature.dependencies.is_empty() {
-            debug!(
-                "Computing {} dependencies in parallel for feature '{}': {:?}",
-                feature.dependencies.len(),
-                feature_name,
-                feature.dependencies
-            );
-
-            // Create futures for all dependencies
-            let dep_futures: Vec<_> = feature
-                .dependencies
-                .iter()
-                .map(|dep_name| {
-                    let dep_name_clone = dep_name.clone();
-                    async move {
-                        let value = self.execute_feature_impl(&dep_name_clone, context).await?;
-                        Ok::<(String, Value), anyhow::Error>((dep_name_clone, value))
-                    }
-                })
-                .collect();
-
-            // Execute all dependency computations in parallel
-            let results = future::try_join_all(dep_futures).await?;
-            let dep_elapsed = dep_start.elapsed();
-            debug!(
-                "Feature '{}' dependencies computed in {:?}",
-                feature_name, dep_elapsed
-            );
-
-            // Convert results to HashMap
-            results.into_iter().collect::<HashMap<String, Value>>()
-        } else {
-            HashMap::new()
-        };
-
-        // Try to get from cache
-        if let Some(cache_config) = self.cache_manager.get_cache_config(feature) {
+        // Try to get from cache, unless the request's data source calls are
+        // being recorded or replayed
+        let cache_config = self
+            .cache_manager
+            .get_cache_config(feature)
+            .filter(|_| !crate::replay::is_active());
+        if let Some(cache_config) = cache_config {
             let cache_key = self.cache_manager.build_cache_key(feature_name, &context_map);
 
             // L1 cache check
@@ -234,55 +533,420 @@ impl FeatureExecutor {
     }
 
     /// Execute multiple features in batch
+    ///
+    /// The features and their transitive dependencies are grouped into
+    /// dependency levels; the features of a level run concurrently once every
+    /// earlier level has completed. The result holds the dependencies too.
     pub async fn execute_features(
         &self,
         feature_names: &[String],
         context: &ExecutionContext,
+    ) -> Result<HashMap<String, Value>> {
+        self.execute_levels(&self.features(), feature_names, context).await
+    }
+
+    /// Execute features and their dependencies from one set of definitions
+    async fn execute_levels(
+        &self,
+        features: &FeatureSet,
+        feature_names: &[String],
+        context: &ExecutionContext,
     ) -> Result<HashMap<String, Value>> {
         use std::time::Instant;
         let batch_start = Instant::now();
 
-        let mut results = HashMap::new();
+        let levels = dependency_levels(features, feature_names)?;
+        let total: usize = levels.iter().map(Vec::len).sum();
 
-        // Sort features by dependency order
-        let sorted_features = self.sort_by_dependencies(feature_names)?;
-
-        debug!(
-            "Executing {} features sequentially in dependency order",
-            sorted_features.len()
-        );
-
-        for (idx, feature_name) in sorted_features.iter().enumerate() {
-            let feature_start = Instant::now();
-            let value = self.execute_feature_impl(feature_name, context).await?;
-            let feature_elapsed = feature_start.elapsed();
+        // Fetch lookup features with one round trip per feature store
+        self.prefetch_lookups(features, &levels.concat(), &[&context.event])
+            .await;
 
+        let mut results: HashMap<String, Value> = HashMap::new();
+        for (depth, level) in levels.iter().enumerate() {
             debug!(
-                "[{}/{}] Feature '{}' completed in {}ms",
-                idx + 1,
-                sorted_features.len(),
-                feature_name,
-                feature_elapsed.as_millis()
+                "Executing {} features concurrently at dependency level {}",
+                level.len(),
+                depth
             );
 
-            results.insert(feature_name.clone(), value);
+            let computed = &results;
+            let level_futures = level.iter().map(|feature_name| async move {
+                let feature_start = Instant::now();
+                let feature = features
+                    .get(feature_name)
+                    .with_context(|| format!("Feature '{}' not found", feature_name))?;
+                let dep_values = feature
+                    .dependencies
+                    .iter()
+                    .map(|dep| (dep.clone(), computed.get(dep).cloned().unwrap_or(Value::Null)))
+                    .collect();
+                let result = self.execute_resolved(feature, context, dep_values).await;
+                let (value, _) = self.resolve_missing(feature, result)?;
+
+                debug!(
+                    "Feature '{}' completed in {}ms",
+                    feature_name,
+                    feature_start.elapsed().as_millis()
+                );
+                Ok::<(String, Value), anyhow::Error>((feature_name.clone(), value))
+            });
+            let values = future::try_join_all(level_futures).await?;
+            results.extend(values);
         }
 
         let batch_elapsed = batch_start.elapsed();
         debug!(
-            "Batch execution of {} features completed in {}ms (avg: {}ms/feature)",
-            sorted_features.len(),
-            batch_elapsed.as_millis(),
-            if sorted_features.is_empty() { 0 } else { batch_elapsed.as_millis() / sorted_features.len() as u128 }
+            "Batch execution of {} features in {} levels completed in {}ms",
+            total,
+            levels.len(),
+            batch_elapsed.as_millis()
         );
 
         Ok(results)
     }
 
+    /// Fetch features concurrently within the executor's [`FetchLimits`]
+    ///
+    /// Like [`execute_features`](Self::execute_features), but a feature that
+    /// fails or runs out of time does not fail the batch: it is reported with
+    /// a `Null` value, and so are the features depending on it. Every feature
+    /// fetched, dependencies included, is reported in execution order.
+    ///
+    /// Missing values are replaced according to each feature's
+    /// [`MissingPolicy`]; a fail-closed feature without a value fails the
+    /// fetch with a [`MissingFeatureError`].
+    pub async fn fetch_features(
+        &self,
+        feature_names: &[String],
+        context: &ExecutionContext,
+    ) -> Result<Vec<FeatureFetch>> {
+        use tokio::time::Instant;
+
+        let features = self.features();
+        let levels = dependency_levels(&features, feature_names)?;
+        let deadline = self.fetch_limits.total_budget.map(|budget| Instant::now() + budget);
+
+        let all_features = levels.concat();
+        let events = [&context.event];
+        let prefetch = self.prefetch_lookups(&features, &all_features, &events);
+        match deadline {
+            Some(deadline) => {
+                let _ = tokio::time::timeout_at(deadline, prefetch).await;
+            }
+            None => prefetch.await,
+        }
+
+        let mut computed: HashMap<String, Value> = HashMap::new();
+        let mut fetches = Vec::new();
+        for level in &levels {
+            let level_futures = level
+                .iter()
+                .map(|feature_name| {
+                    self.fetch_feature(features.get(feature_name), feature_name, context, &computed, deadline)
+                });
+            let level_fetches = future::join_all(level_futures).await;
+
+            for fetch in &level_fetches {
+                if fetch.imputed == Some(MissingPolicy::FailClosed) {
+                    return Err(MissingFeatureError {
+                        feature: fetch.feature.clone(),
+                        reason: fetch.error.clone().unwrap_or_default(),
+                    }
+                    .into());
+                }
+                if fetch.status == FetchStatus::Ok || fetch.imputed.is_some() {
+                    computed.insert(fetch.feature.clone(), fetch.value.clone());
+                }
+            }
+            fetches.extend(level_fetches);
+        }
+
+        Ok(fetches)
+    }
+
+    /// Fetch one feature whose dependencies have been fetched
+    async fn fetch_feature(
+        &self,
+        feature: Option<&FeatureDefinition>,
+        feature_name: &str,
+        context: &ExecutionContext,
+        computed: &HashMap<String, Value>,
+        deadline: Option<tokio::time::Instant>,
+    ) -> FeatureFetch {
+        let start = tokio::time::Instant::now();
+        let limit = match (self.fetch_limits.feature_timeout.map(|t| start + t), deadline) {
+            (Some(timeout), Some(deadline)) => Some(timeout.min(deadline)),
+            (timeout, deadline) => timeout.or(deadline),
+        };
+
+        let Some(feature) = feature else {
+            let error = format!("Feature '{}' not found", feature_name);
+            warn!("Failed to fetch feature '{}': {}", feature_name, error);
+            return FeatureFetch {
+                feature: feature_name.to_string(),
+                value: Value::Null,
+                status: FetchStatus::Failed,
+                latency: start.elapsed(),
+                error: Some(error),
+                imputed: None,
+            };
+        };
+
+        let result = match feature.dependencies.iter().find(|dep| !computed.contains_key(*dep)) {
+            Some(dep) => Err((FetchStatus::Failed, format!("Dependency '{}' was not fetched", dep))),
+            None if limit.is_some_and(|limit| limit <= start) => {
+                Err((FetchStatus::TimedOut, "Feature budget exhausted".to_string()))
+            }
+            None => {
+                let dep_values = feature
+                    .dependencies
+                    .iter()
+                    .map(|dep| (dep.clone(), computed[dep].clone()))
+                    .collect();
+                let execution = self.execute_resolved(feature, context, dep_values);
+                let result = match limit {
+                    Some(limit) => tokio::time::timeout_at(limit, execution).await,
+                    None => Ok(execution.await),
+                };
+                match result {
+                    Ok(Ok(value)) => Ok(value),
+                    Ok(Err(e)) => Err((FetchStatus::Failed, e.to_string())),
+                    Err(_) => Err((
+                        FetchStatus::TimedOut,
+                        format!("Timed out after {}ms", start.elapsed().as_millis()),
+                    )),
+                }
+            }
+        };
+
+        let (mut status, mut error) = match &result {
+            Ok(_) => (FetchStatus::Ok, None),
+            Err((status, error)) => (*status, Some(error.clone())),
+        };
+        if let Some(ref error) = error {
+            warn!("Failed to fetch feature '{}': {}", feature_name, error);
+        }
+
+        let result = result.map_err(|(_, error)| anyhow::anyhow!(error));
+        let (value, imputed) = match self.resolve_missing(feature, result) {
+            Ok(resolved) => resolved,
+            Err(e) if e.is::<MissingFeatureError>() => {
+                if status == FetchStatus::Ok {
+                    status = FetchStatus::Failed;
+                    error = Some("Feature has no value".to_string());
+                }
+                (Value::Null, Some(MissingPolicy::FailClosed))
+            }
+            Err(_) => (Value::Null, None),
+        };
+
+        FeatureFetch {
+            feature: feature_name.to_string(),
+            value,
+            status,
+            latency: start.elapsed(),
+            error,
+            imputed,
+        }
+    }
+
+    /// Apply a feature's missing-value policy to its computed value
+    ///
+    /// Returns the value and, when the computed one was missing, the policy
+    /// applied. Features without a policy keep their value or error.
+    fn resolve_missing(
+        &self,
+        feature: &FeatureDefinition,
+        result: Result<Value>,
+    ) -> Result<(Value, Option<MissingPolicy>)> {
+        let policy = match feature.missing_policy() {
+            Some(policy) if feature.is_enabled() => policy,
+            _ => return result.map(|value| (value, None)),
+        };
+
+        let reason = match result {
+            Ok(Value::Null) => "no value".to_string(),
+            Ok(value) => {
+                if policy == MissingPolicy::Mean {
+                    self.observe_value(&feature.name, &value);
+                }
+                return Ok((value, None));
+            }
+            Err(e) => format!("{:#}", e),
+        };
+        debug!(
+            "Feature '{}' is missing ({}), applying {} policy",
+            feature.name,
+            reason,
+            policy.as_str()
+        );
+
+        let value = match policy {
+            MissingPolicy::Default => feature.default.clone().unwrap_or(Value::Null),
+            MissingPolicy::Mean => self
+                .observed_mean(&feature.name)
+                .map(Value::Number)
+                .or_else(|| feature.default.clone())
+                .unwrap_or(Value::Null),
+            MissingPolicy::FailClosed => {
+                return Err(MissingFeatureError {
+                    feature: feature.name.clone(),
+                    reason,
+                }
+                .into())
+            }
+            MissingPolicy::FailOpen => Value::Null,
+        };
+        Ok((value, Some(policy)))
+    }
+
+    /// Record a computed value of a mean-filled feature
+    fn observe_value(&self, feature_name: &str, value: &Value) {
+        if let Value::Number(n) = value {
+            let mut observed = self.observed_values.lock().unwrap_or_else(|e| e.into_inner());
+            let (count, sum) = observed.entry(feature_name.to_string()).or_insert((0, 0.0));
+            *count += 1;
+            *sum += n;
+        }
+    }
+
+    /// Mean of the values computed so far for a feature
+    fn observed_mean(&self, feature_name: &str) -> Option<f64> {
+        let observed = self.observed_values.lock().unwrap_or_else(|e| e.into_inner());
+        observed
+            .get(feature_name)
+            .filter(|(count, _)| *count > 0)
+            .map(|(count, sum)| sum / *count as f64)
+    }
+
+    /// Warm the datasource caches for the features of many events
+    ///
+    /// The lookup features among `feature_names` and their dependencies are
+    /// fetched for every event with one round trip per datasource, so that
+    /// computing them event by event afterwards is answered from the caches.
+    pub async fn prefetch_features(
+        &self,
+        feature_names: &[String],
+        events: &[&HashMap<String, Value>],
+    ) -> Result<()> {
+        let features = self.features();
+        let levels = dependency_levels(&features, feature_names)?;
+        self.prefetch_lookups(&features, &levels.concat(), events)
+            .await;
+        Ok(())
+    }
+
+    /// Warm the datasource caches for lookup features, one batch per datasource
+    ///
+    /// Failures are only logged; the features are then fetched one by one.
+    async fn prefetch_lookups(
+        &self,
+        features: &FeatureSet,
+        feature_names: &[String],
+        events: &[&HashMap<String, Value>],
+    ) {
+        // Recorded and replayed lookups are matched one feature at a time
+        if crate::replay::is_active() {
+            return;
+        }
+
+        let mut batches: HashMap<String, Vec<(String, String)>> = HashMap::new();
+        let mut seen = HashSet::new();
+        for name in feature_names {
+            let Some(feature) = features.get(name) else {
+                continue;
+            };
+            let Some(lookup) = feature.lookup.as_ref() else {
+                continue;
+            };
+            if !feature.is_enabled() || !feature.dependencies.is_empty() {
+                continue;
+            }
+            for event in events {
+                let Ok(key) = ExpressionEvaluator::substitute_template(&lookup.key, event) else {
+                    continue;
+                };
+                if seen.insert((name.as_str(), key.clone())) {
+                    batches
+                        .entry(self.get_datasource_name(feature))
+                        .or_default()
+                        .push((name.clone(), key));
+                }
+            }
+        }
+
+        for (datasource_name, requests) in batches {
+            if requests.len() < 2 {
+                continue;
+            }
+            let Some(datasource) = self.datasources.get(&datasource_name) else {
+                continue;
+            };
+            if let Err(e) = datasource.get_features(&requests).await {
+                warn!(
+                    "Batched lookup of {} features from '{}' failed: {}",
+                    requests.len(),
+                    datasource_name,
+                    e
+                );
+            }
+        }
+    }
+
     /// Execute all registered features
     pub async fn execute_all(&self, context: &ExecutionContext) -> Result<HashMap<String, Value>> {
-        let feature_names: Vec<String> = self.features.keys().cloned().collect();
-        self.execute_features(&feature_names, context).await
+        let features = self.features();
+        let feature_names: Vec<String> = features.keys().cloned().collect();
+        self.execute_levels(&features, &feature_names, context).await
+    }
+
+    /// Select the entities for a scheduled pipeline run
+    ///
+    /// Conditions use the same syntax as feature `when` filters and are combined with AND.
+    pub async fn query_entities(
+        &self,
+        query: &corint_core::ast::EntityQuery,
+    ) -> Result<Vec<HashMap<String, Value>>> {
+        use crate::datasource::query::{Query, QueryType};
+        use crate::feature::definition::WhenCondition;
+
+        let datasource = self
+            .datasources
+            .get(&query.datasource)
+            .ok_or_else(|| anyhow::anyhow!("Datasource '{}' not found", query.datasource))?;
+
+        let when = (!query.when.is_empty()).then(|| WhenCondition::Complex {
+            all: Some(query.when.clone()),
+            any: None,
+        });
+        let filters = self.build_filters(&when, &HashMap::new())?;
+
+        // A dropped filter would widen the selection, so every condition must parse
+        if filters.len() != query.when.len() {
+            return Err(anyhow::anyhow!(
+                "Invalid entity filter for '{}': {:?}",
+                query.entity,
+                query.when
+            ));
+        }
+
+        let result = datasource
+            .query_uncached(Query {
+                query_type: QueryType::RawEvents,
+                entity: query.entity.clone(),
+                filters,
+                time_window: None,
+                aggregations: Vec::new(),
+                group_by: Vec::new(),
+                having: Vec::new(),
+                order_by: Vec::new(),
+                limit: query.limit,
+                cache_ttl: None,
+            })
+            .await?;
+
+        Ok(result.rows)
     }
 
     /// Compute a feature value (no caching)
@@ -312,6 +976,21 @@ impl FeatureExecutor {
             return Ok(result);
         }
 
+        // Graph features resolve their own graph store
+        if feature.feature_type == crate::feature::definition::FeatureType::Graph {
+            return self.execute_graph(feature, context).await;
+        }
+
+        // Counter features read their own counter store
+        if feature.feature_type == crate::feature::definition::FeatureType::Counter {
+            return self.execute_counter(feature, context).await;
+        }
+
+        // Fallback chains try their datasources in order
+        if !feature.sources.is_empty() {
+            return self.compute_from_sources(feature, context).await;
+        }
+
         // Determine data source for other feature types
         let datasource_name = self.get_datasource_name(feature);
         let datasource = self
@@ -339,6 +1018,77 @@ impl FeatureExecutor {
         Ok(result)
     }
 
+    /// Compute a feature from the first of its sources that has a value
+    ///
+    /// A source is skipped when it fails, exceeds its `timeout_ms` or, for a
+    /// feature store, has no value for the key. When all are skipped the
+    /// feature's `default` is used; without one the last error is returned.
+    async fn compute_from_sources(
+        &self,
+        feature: &FeatureDefinition,
+        context: &HashMap<String, Value>,
+    ) -> Result<Value> {
+        let mut last_error = None;
+        for hop in &feature.sources {
+            let attempt = self.compute_from_source(feature, hop, context);
+            let result = match hop.timeout_ms {
+                Some(timeout_ms) => tokio::time::timeout(Duration::from_millis(timeout_ms), attempt)
+                    .await
+                    .unwrap_or_else(|_| Err(anyhow::anyhow!("timed out after {}ms", timeout_ms))),
+                None => attempt.await,
+            };
+
+            match result {
+                Ok(Some(value)) => return Ok(value),
+                Ok(None) => {
+                    debug!("Feature '{}' not found in '{}'", feature.name, hop.datasource);
+                }
+                Err(e) => {
+                    warn!(
+                        "Feature '{}' source '{}' failed: {}",
+                        feature.name, hop.datasource, e
+                    );
+                    last_error = Some(e);
+                }
+            }
+        }
+
+        match (&feature.default, last_error) {
+            (Some(default), _) => Ok(default.clone()),
+            (None, Some(e)) => Err(e.context(format!("All sources of feature '{}' failed", feature.name))),
+            (None, None) => Ok(Value::Null),
+        }
+    }
+
+    /// Compute a feature from one source; `None` if a feature store has no value
+    async fn compute_from_source(
+        &self,
+        feature: &FeatureDefinition,
+        hop: &SourceHop,
+        context: &HashMap<String, Value>,
+    ) -> Result<Option<Value>> {
+        let datasource = self
+            .datasources
+            .get(&hop.datasource)
+            .with_context(|| format!("Data source '{}' not found", hop.datasource))?;
+
+        if !datasource.is_feature_store() {
+            return self
+                .execute_feature_by_type(feature, datasource, context)
+                .await
+                .map(Some);
+        }
+
+        // Feature stores hold the pre-computed value under the feature name
+        let template = hop
+            .key
+            .as_deref()
+            .or_else(|| feature.lookup.as_ref().map(|c| c.key.as_str()))
+            .with_context(|| format!("Feature store source '{}' needs a key", hop.datasource))?;
+        let key = ExpressionEvaluator::substitute_template(template, context)?;
+        Ok(datasource.get_feature(&feature.name, &key).await?)
+    }
+
     /// Execute a feature based on its type
     async fn execute_feature_by_type(
         &self,
@@ -359,7 +1109,7 @@ impl FeatureExecutor {
                 self.execute_sequence(feature, datasource, context).await
             }
             FeatureType::Graph => {
-                self.execute_graph(feature, datasource, context).await
+                self.execute_graph(feature, context).await
             }
             FeatureType::Expression => {
                 // Expression features are handled directly in compute_feature
@@ -372,6 +1122,17 @@ impl FeatureExecutor {
             FeatureType::Lookup => {
                 self.execute_lookup(feature, datasource, context).await
             }
+            FeatureType::Counter => {
+                self.execute_counter(feature, context).await
+            }
+        }
+    }
+
+    /// Resolve a dimension value, deriving entity keys from entity definitions
+    fn resolve_dimension_value(&self, template: &str, context: &HashMap<String, Value>) -> Result<String> {
+        match self.entities.resolve_reference(template, context) {
+            Some(key) => Ok(key?),
+            None => ExpressionEvaluator::substitute_template(template, context),
         }
     }
 
@@ -382,7 +1143,7 @@ impl FeatureExecutor {
         datasource: &DataSourceClient,
         context: &HashMap<String, Value>,
     ) -> Result<Value> {
-        use crate::datasource::query::{Query, QueryType, Aggregation, AggregationType, Filter, FilterOperator, TimeWindow, TimeWindowType, RelativeWindow};
+        use crate::datasource::query::{Query, QueryType, Aggregation, AggregationType, Filter, FilterOperator, OrderBy, RelativeWindow, TimeWindow, TimeWindowType};
 
         let config = feature.aggregation.as_ref()
             .ok_or_else(|| anyhow::anyhow!("Missing aggregation config for feature '{}'", feature.name))?;
@@ -395,15 +1156,15 @@ impl FeatureExecutor {
 
         // Build time window if specified
         let time_window = config.window.as_ref().and_then(|w| {
-            RelativeWindow::from_string(w).map(|relative| TimeWindow {
-                window_type: TimeWindowType::Relative(relative),
+            TimeWindowType::parse(w).map(|window_type| TimeWindow {
+                window_type,
                 time_field: config.timestamp_field.clone()
                     .unwrap_or_else(|| "event_timestamp".to_string()),
             })
         });
 
-        // Substitute dimension_value template with context values
-        let dimension_value = ExpressionEvaluator::substitute_template(&config.dimension_value, context)?;
+        // Derive the entity key, or substitute the dimension_value template with context values
+        let dimension_value = self.resolve_dimension_value(&config.dimension_value, context)?;
 
         // Add dimension filter to constrain the query
         let mut all_filters = filters;
@@ -486,38 +1247,136 @@ impl FeatureExecutor {
                     output_name: "median".to_string(),
                 }])
             }
-            "percentile" => {
+            "percentile" | "p50" | "p95" | "p99" => {
                 let field = config.field.clone()
                     .ok_or_else(|| anyhow::anyhow!("Field required for percentile aggregation"))?;
-                let p = config.percentile.unwrap_or(50);
+                let p = match method.as_str() {
+                    "p50" => 50,
+                    "p95" => 95,
+                    "p99" => 99,
+                    _ => config.percentile.unwrap_or(50),
+                };
+                if p > 100 {
+                    return Err(anyhow::anyhow!("Percentile must be between 0 and 100, got {}", p));
+                }
                 (QueryType::Aggregate, vec![Aggregation {
                     agg_type: AggregationType::Percentile { p },
                     field: Some(field),
                     output_name: "percentile".to_string(),
                 }])
             }
+            "decayed_sum" | "decayed_count" => {
+                let half_life = config.half_life.as_deref()
+                    .ok_or_else(|| anyhow::anyhow!("half_life required for {} aggregation", method))?;
+                let half_life_secs = RelativeWindow::from_string(half_life)
+                    .map(|window| window.to_seconds())
+                    .filter(|secs| *secs > 0)
+                    .ok_or_else(|| anyhow::anyhow!("Invalid half_life: {}", half_life))?;
+                let time_field = config.timestamp_field.clone()
+                    .unwrap_or_else(|| "event_timestamp".to_string());
+                let agg_type = if method == "decayed_sum" {
+                    AggregationType::DecayedSum { half_life_secs, time_field }
+                } else {
+                    AggregationType::DecayedCount { half_life_secs, time_field }
+                };
+                let field = if method == "decayed_sum" {
+                    Some(config.field.clone()
+                        .ok_or_else(|| anyhow::anyhow!("Field required for decayed_sum aggregation"))?)
+                } else {
+                    None
+                };
+                (QueryType::Aggregate, vec![Aggregation {
+                    agg_type,
+                    field,
+                    output_name: method.clone(),
+                }])
+            }
+            "count_distinct_ratio" => {
+                let field = config.field.clone()
+                    .ok_or_else(|| anyhow::anyhow!("Field required for count_distinct_ratio aggregation"))?;
+                (QueryType::Aggregate, vec![
+                    Aggregation {
+                        agg_type: AggregationType::CountDistinct,
+                        field: Some(field),
+                        output_name: "distinct_count".to_string(),
+                    },
+                    Aggregation {
+                        agg_type: AggregationType::Count,
+                        field: None,
+                        output_name: "count".to_string(),
+                    },
+                ])
+            }
+            "mode" | "entropy" => {
+                // Counted per value below; the top group is the most frequent
+                if config.field.is_none() {
+                    return Err(anyhow::anyhow!("Field required for {} aggregation", method));
+                }
+                (QueryType::Aggregate, vec![Aggregation {
+                    agg_type: AggregationType::Count,
+                    field: None,
+                    output_name: "count".to_string(),
+                }])
+            }
             _ => {
                 return Err(anyhow::anyhow!("Unsupported aggregation method: {}", method));
             }
         };
 
         // Build the query - datasource-agnostic
-        let query = Query {
+        let mut query = Query {
             query_type,
             entity: config.entity.clone(),
             filters: all_filters,
             time_window,
             aggregations,
             group_by: vec![],
+            having: Vec::new(),
+            order_by: Vec::new(),
             limit: None,
+            cache_ttl: config.cache_ttl,
         };
 
+        // Mode is the top value by count; ties go to the smallest value
+        if let ("mode", Some(field)) = (method.as_str(), &config.field) {
+            query.group_by = vec![field.clone()];
+            query.order_by = vec![OrderBy::desc("count"), OrderBy::asc(field.clone())];
+            query.limit = Some(1);
+        }
+
+        // Entropy is taken over the events per value
+        if let ("entropy", Some(field)) = (method.as_str(), &config.field) {
+            query.group_by = vec![field.clone()];
+        }
+
         // Execute the query - DataSourceClient handles SQL generation based on provider
         let result = datasource.query(query).await
             .map_err(|e| anyhow::anyhow!("Query execution failed: {}", e))?;
 
+        if method == "entropy" {
+            return Ok(Value::Number(grouped_entropy(&result.rows)));
+        }
+
         // Extract the result value
         if let Some(row) = result.rows.first() {
+            // For mode, the group key is the only column besides the count
+            if method == "mode" {
+                return Ok(row
+                    .iter()
+                    .find(|(column, _)| column.as_str() != "count")
+                    .map(|(_, value)| value.clone())
+                    .unwrap_or(Value::Null));
+            }
+
+            if method == "count_distinct_ratio" {
+                return Ok(match (row.get("distinct_count"), row.get("count")) {
+                    (Some(Value::Number(distinct)), Some(Value::Number(total))) if *total > 0.0 => {
+                        Value::Number(distinct / total)
+                    }
+                    _ => Value::Number(0.0),
+                });
+            }
+
             // Get the first aggregation output
             let output_key = match method.as_str() {
                 "count" => "count",
@@ -528,7 +1387,9 @@ impl FeatureExecutor {
                 "distinct" => "distinct_count",
                 "stddev" => "stddev",
                 "median" => "median",
-                "percentile" => "percentile",
+                "percentile" | "p50" | "p95" | "p99" => "percentile",
+                "decayed_sum" => "decayed_sum",
+                "decayed_count" => "decayed_count",
                 _ => "value",
             };
 
@@ -536,7 +1397,8 @@ impl FeatureExecutor {
         } else {
             // No results - return appropriate default
             Ok(match method.as_str() {
-                "count" | "distinct" | "sum" => Value::Number(0.0),
+                "count" | "distinct" | "sum" | "decayed_sum" | "decayed_count"
+                | "count_distinct_ratio" => Value::Number(0.0),
                 _ => Value::Null,
             })
         }
@@ -696,7 +1558,10 @@ impl FeatureExecutor {
                 let operator = TimeSinceOperator {
                     entity: config.entity.clone(),
                     dimension: config.dimension.clone(),
-                    dimension_value: config.dimension_value.clone(),
+                    dimension_value: match self.entities.resolve_reference(&config.dimension_value, context) {
+                        Some(key) => key?,
+                        None => config.dimension_value.clone(),
+                    },
                     filters,
                     unit,
                     timestamp_field,
@@ -745,10 +1610,83 @@ impl FeatureExecutor {
     async fn execute_graph(
         &self,
         feature: &FeatureDefinition,
-        _datasource: &DataSourceClient,
-        _context: &HashMap<String, Value>,
+        context: &HashMap<String, Value>,
+    ) -> Result<Value> {
+        use crate::datasource::RelativeWindow;
+        use crate::feature::definition::GraphMethod;
+
+        let config = feature.graph.as_ref()
+            .ok_or_else(|| anyhow::anyhow!("Missing graph config for feature '{}'", feature.name))?;
+
+        let method_name = feature.method.as_ref()
+            .ok_or_else(|| anyhow::anyhow!("Missing method for graph feature '{}'", feature.name))?;
+        let method: GraphMethod = serde_yaml::from_value(serde_yaml::Value::String(method_name.clone()))
+            .map_err(|_| anyhow::anyhow!("Unknown graph method '{}' for feature '{}'", method_name, feature.name))?;
+
+        let store: Arc<dyn GraphStore> = match self.graph_stores.get(&config.datasource) {
+            Some(store) => store.clone(),
+            None => {
+                let client = self.datasources.get(&config.datasource)
+                    .with_context(|| format!("Graph store '{}' not found", config.datasource))?;
+                Arc::new(SqlAdjacencyGraphStore::new(client.clone()))
+            }
+        };
+
+        let window = match config.window.as_deref() {
+            Some(w) => Some(RelativeWindow::from_string(w)
+                .ok_or_else(|| anyhow::anyhow!("Invalid window '{}' for feature '{}'", w, feature.name))?),
+            None => None,
+        };
+
+        let node = GraphNode::new(
+            config.dimension.clone(),
+            self.resolve_dimension_value(&config.dimension_value, context)?,
+        );
+        let linked_type = || config.dimension2.as_deref()
+            .ok_or_else(|| anyhow::anyhow!("Missing dimension2 for graph feature '{}'", feature.name));
+
+        let count = match method {
+            GraphMethod::LinkedEntityCount => {
+                store.linked_count(&node, linked_type()?, window.as_ref()).await?
+            }
+            GraphMethod::NodeDegree => store.degree(&node, window.as_ref()).await?,
+            GraphMethod::SharedEntityCount => {
+                let template = config.dimension_value2.as_deref()
+                    .ok_or_else(|| anyhow::anyhow!("Missing dimension_value2 for graph feature '{}'", feature.name))?;
+                let other = GraphNode::new(
+                    config.dimension.clone(),
+                    self.resolve_dimension_value(template, context)?,
+                );
+                store.shared_count(&node, &other, linked_type()?, window.as_ref()).await?
+            }
+            GraphMethod::GraphCentrality | GraphMethod::CommunitySize | GraphMethod::NetworkDistance => {
+                return Err(anyhow::anyhow!(
+                    "Graph method '{}' not yet implemented: {}", method_name, feature.name
+                ));
+            }
+        };
+
+        Ok(Value::Number(count as f64))
+    }
+
+    /// Execute counter feature - total of a counter over the window
+    async fn execute_counter(
+        &self,
+        feature: &FeatureDefinition,
+        context: &HashMap<String, Value>,
     ) -> Result<Value> {
-        Err(anyhow::anyhow!("Graph features not yet implemented: {}", feature.name))
+        use crate::datasource::RelativeWindow;
+
+        let config = feature.counter.as_ref()
+            .ok_or_else(|| anyhow::anyhow!("Missing counter config for feature '{}'", feature.name))?;
+        let store = self.counter_stores.get(&config.datasource)
+            .with_context(|| format!("Counter store '{}' not found", config.datasource))?;
+        let window = RelativeWindow::from_string(&config.window)
+            .ok_or_else(|| anyhow::anyhow!("Invalid window '{}' for feature '{}'", config.window, feature.name))?;
+
+        let key = self.resolve_dimension_value(&config.dimension_value, context)?;
+        let total = store.read(&config.counter, &key, &window).await?;
+        Ok(Value::Number(total))
     }
 
     /// Execute expression feature - computes from other features
@@ -849,6 +1787,11 @@ impl FeatureExecutor {
                     .map(|c| c.datasource.clone())
                     .unwrap_or_else(|| "default".to_string())
             }
+            FeatureType::Counter => {
+                feature.counter.as_ref()
+                    .map(|c| c.datasource.clone())
+                    .unwrap_or_else(|| "default".to_string())
+            }
             FeatureType::Expression => {
                 "default".to_string() // Expression features don't need datasource
             }
@@ -894,6 +1837,41 @@ impl FeatureExecutor {
                 .datasource
                 .clone()
                 .unwrap_or_else(|| "default".to_string()),
+            Operator::Percentile(op) => op
+                .params
+                .datasource
+                .clone()
+                .unwrap_or_else(|| "default".to_string()),
+            Operator::Stddev(op) => op
+                .params
+                .datasource
+                .clone()
+                .unwrap_or_else(|| "default".to_string()),
+            Operator::Median(op) => op
+                .params
+                .datasource
+                .clone()
+                .unwrap_or_else(|| "default".to_string()),
+            Operator::Mode(op) => op
+                .params
+                .datasource
+                .clone()
+                .unwrap_or_else(|| "default".to_string()),
+            Operator::CountDistinctRatio(op) => op
+                .params
+                .datasource
+                .clone()
+                .unwrap_or_else(|| "default".to_string()),
+            Operator::Entropy(op) => op
+                .params
+                .datasource
+                .clone()
+                .unwrap_or_else(|| "default".to_string()),
+            Operator::GeoVelocity(op) => op
+                .params
+                .datasource
+                .clone()
+                .unwrap_or_else(|| "default".to_string()),
             Operator::Velocity(op) => op
                 .params
                 .datasource
@@ -905,50 +1883,6 @@ impl FeatureExecutor {
         }
     }
 
-    /// Sort features by dependency order (topological sort)
-    fn sort_by_dependencies(&self, feature_names: &[String]) -> Result<Vec<String>> {
-        let mut sorted = Vec::new();
-        let mut visited = std::collections::HashSet::new();
-        let mut visiting = std::collections::HashSet::new();
-
-        for name in feature_names {
-            self.visit_feature(name, &mut sorted, &mut visited, &mut visiting)?;
-        }
-
-        Ok(sorted)
-    }
-
-    /// Visit a feature in dependency graph (for topological sort)
-    fn visit_feature(
-        &self,
-        name: &str,
-        sorted: &mut Vec<String>,
-        visited: &mut std::collections::HashSet<String>,
-        visiting: &mut std::collections::HashSet<String>,
-    ) -> Result<()> {
-        if visited.contains(name) {
-            return Ok(());
-        }
-
-        if visiting.contains(name) {
-            return Err(anyhow::anyhow!("Circular dependency detected: {}", name));
-        }
-
-        visiting.insert(name.to_string());
-
-        if let Some(feature) = self.features.get(name) {
-            for dep in &feature.dependencies {
-                self.visit_feature(dep, sorted, visited, visiting)?;
-            }
-        }
-
-        visiting.remove(name);
-        visited.insert(name.to_string());
-        sorted.push(name.to_string());
-
-        Ok(())
-    }
-
     /// Clear L1 cache
     pub async fn clear_cache(&self) {
         self.cache_manager.clear_cache().await;
@@ -992,6 +1926,67 @@ impl Default for FeatureExecutor {
     }
 }
 
+/// Group features and their transitive dependencies into dependency levels
+///
+/// Level 0 holds features without dependencies and every other feature is
+/// one level above its deepest dependency, so the features of a level never
+/// depend on each other.
+fn dependency_levels(features: &FeatureSet, feature_names: &[String]) -> Result<Vec<Vec<String>>> {
+    let mut depths = HashMap::new();
+    let mut order = Vec::new();
+    let mut visiting = std::collections::HashSet::new();
+
+    for name in feature_names {
+        visit_feature(features, name, &mut depths, &mut order, &mut visiting)?;
+    }
+
+    let mut levels: Vec<Vec<String>> = Vec::new();
+    for name in order {
+        let depth = depths[&name];
+        if levels.len() <= depth {
+            levels.resize_with(depth + 1, Vec::new);
+        }
+        levels[depth].push(name);
+    }
+
+    Ok(levels)
+}
+
+/// Visit a feature in the dependency graph, returning its level
+fn visit_feature(
+    features: &FeatureSet,
+    name: &str,
+    depths: &mut HashMap<String, usize>,
+    order: &mut Vec<String>,
+    visiting: &mut std::collections::HashSet<String>,
+) -> Result<usize> {
+    if let Some(depth) = depths.get(name) {
+        return Ok(*depth);
+    }
+
+    if !visiting.insert(name.to_string()) {
+        return Err(anyhow::anyhow!("Circular dependency detected: {}", name));
+    }
+
+    let mut depth = 0;
+    if let Some(feature) = features.get(name) {
+        for dep in &feature.dependencies {
+            depth = depth.max(visit_feature(features, dep, depths, order, visiting)? + 1);
+        }
+    }
+
+    visiting.remove(name);
+    depths.insert(name.to_string(), depth);
+    order.push(name.to_string());
+
+    Ok(depth)
+}
+
+/// Whether two definitions of a feature are the same
+fn same_definition(a: &FeatureDefinition, b: &FeatureDefinition) -> bool {
+    serde_json::to_value(a).ok() == serde_json::to_value(b).ok()
+}
+
 /// Parse window string (e.g., "24h", "7d", "30d") to WindowConfig
 #[allow(dead_code)]
 fn parse_window(window_str: &str) -> Option<crate::feature::operator::WindowConfig> {
@@ -1138,7 +2133,7 @@ mod tests {
         let executor = FeatureExecutor::new();
         assert!(!executor.is_stats_enabled());
         assert_eq!(executor.datasources.len(), 0);
-        assert_eq!(executor.features.len(), 0);
+        assert_eq!(executor.features().len(), 0);
     }
 
     #[test]
@@ -1354,6 +2349,655 @@ mod tests {
         assert_eq!(result, "count_42_value");
     }
 
+    #[test]
+    fn test_resolve_dimension_value_with_entity() {
+        use crate::entity::{EntityDefinition, Normalization};
+
+        let mut entities = EntityRegistry::new();
+        entities
+            .register(
+                EntityDefinition::new("card_merchant", "${event.card_hash}:${event.merchant_id}")
+                    .with_normalization(Normalization::Lowercase),
+            )
+            .unwrap();
+        let executor = FeatureExecutor::new().with_entities(entities);
+
+        let mut context = HashMap::new();
+        context.insert("user_id".to_string(), Value::String("user123".to_string()));
+        context.insert("card_hash".to_string(), Value::String("AbC".to_string()));
+        context.insert("merchant_id".to_string(), Value::String("M1".to_string()));
+
+        let key = executor.resolve_dimension_value("entity.card_merchant", &context).unwrap();
+        assert_eq!(key, "abc:m1");
+
+        // Plain templates still resolve against the context
+        let user = executor.resolve_dimension_value("${event.user_id}", &context).unwrap();
+        assert_eq!(user, "user123");
+
+        context.remove("merchant_id");
+        assert!(executor.resolve_dimension_value("entity.card_merchant", &context).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_graph_feature_with_graph_store() {
+        use crate::feature::graph::InMemoryGraphStore;
+
+        let store = Arc::new(InMemoryGraphStore::new());
+        let device = GraphNode::new("device_id", "dev_1");
+        store.link(device.clone(), GraphNode::new("user_id", "u1"));
+        store.link(device.clone(), GraphNode::new("user_id", "u2"));
+        store.link(device.clone(), GraphNode::new("ip", "10.0.0.1"));
+
+        let features: Vec<FeatureDefinition> = serde_yaml::from_str(
+            r#"
+- name: users_per_device_30d
+  type: graph
+  method: linked_entity_count
+  datasource: link_graph
+  dimension: device_id
+  dimension_value: "${event.device_id}"
+  dimension2: user_id
+  window: 30d
+- name: device_degree
+  type: graph
+  method: node_degree
+  datasource: link_graph
+  dimension: device_id
+  dimension_value: "${event.device_id}"
+- name: device_centrality
+  type: graph
+  method: graph_centrality
+  datasource: link_graph
+  dimension: device_id
+  dimension_value: "${event.device_id}"
+"#,
+        )
+        .unwrap();
+
+        let mut executor = FeatureExecutor::new();
+        executor.add_graph_store("link_graph", store);
+        executor.register_features(features).unwrap();
+
+        let mut event = HashMap::new();
+        event.insert("device_id".to_string(), Value::String("dev_1".to_string()));
+        let context = ExecutionContext::from_event(event).unwrap();
+
+        let users = executor.execute_feature("users_per_device_30d", &context).await.unwrap();
+        assert_eq!(users, Value::Number(2.0));
+
+        let degree = executor.execute_feature("device_degree", &context).await.unwrap();
+        assert_eq!(degree, Value::Number(3.0));
+
+        assert!(executor.execute_feature("device_centrality", &context).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_counter_feature() {
+        use crate::feature::counter::InMemoryCounterStore;
+
+        let features: Vec<FeatureDefinition> = serde_yaml::from_str(
+            r#"
+- name: login_failures_1h
+  type: counter
+  datasource: velocity_counters
+  counter: login_failures
+  dimension_value: "${event.user_id}"
+  window: 1h
+- name: login_failures_missing_store
+  type: counter
+  datasource: unknown_counters
+  counter: login_failures
+  dimension_value: "${event.user_id}"
+  window: 1h
+"#,
+        )
+        .unwrap();
+
+        let mut executor = FeatureExecutor::new();
+        executor.add_counter_store("velocity_counters", Arc::new(InMemoryCounterStore::new()));
+        executor.register_features(features).unwrap();
+
+        for _ in 0..3 {
+            executor
+                .increment_counter("velocity_counters", "login_failures", "u1", 1.0)
+                .await
+                .unwrap();
+        }
+        executor
+            .increment_counter("velocity_counters", "login_failures", "u2", 1.0)
+            .await
+            .unwrap();
+        assert!(executor
+            .increment_counter("unknown_counters", "login_failures", "u1", 1.0)
+            .await
+            .is_err());
+
+        let mut event = HashMap::new();
+        event.insert("user_id".to_string(), Value::String("u1".to_string()));
+        let context = ExecutionContext::from_event(event).unwrap();
+
+        let failures = executor.execute_feature("login_failures_1h", &context).await.unwrap();
+        assert_eq!(failures, Value::Number(3.0));
+        assert!(executor
+            .execute_feature("login_failures_missing_store", &context)
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_dependency_levels() {
+        use crate::feature::counter::InMemoryCounterStore;
+
+        let features: Vec<FeatureDefinition> = serde_yaml::from_str(
+            r#"
+- name: failures_1h
+  type: counter
+  datasource: counters
+  counter: failures
+  dimension_value: "${event.user_id}"
+  window: 1h
+- name: logins_1h
+  type: counter
+  datasource: counters
+  counter: logins
+  dimension_value: "${event.user_id}"
+  window: 1h
+- name: attempts_1h
+  type: expression
+  expression: "failures_1h + logins_1h"
+- name: double_attempts_1h
+  type: expression
+  expression: "attempts_1h * 2"
+- name: attempts_per_login_1h
+  type: expression
+  expression: "attempts_1h / logins_1h"
+- name: loop_a
+  type: expression
+  expression: "loop_b + 1"
+- name: loop_b
+  type: expression
+  expression: "loop_a + 1"
+"#,
+        )
+        .unwrap();
+
+        let store = Arc::new(InMemoryCounterStore::new());
+        let mut executor = FeatureExecutor::new();
+        executor.add_counter_store("counters", store.clone());
+        executor.register_features(features).unwrap();
+
+        // Dependencies come from the expressions
+        let names = vec![
+            "attempts_per_login_1h".to_string(),
+            "double_attempts_1h".to_string(),
+        ];
+        assert_eq!(
+            dependency_levels(&executor.features(), &names).unwrap(),
+            vec![
+                vec!["failures_1h".to_string(), "logins_1h".to_string()],
+                vec!["attempts_1h".to_string()],
+                vec!["attempts_per_login_1h".to_string(), "double_attempts_1h".to_string()],
+            ]
+        );
+
+        store.increment("failures", "u1", 3.0).await.unwrap();
+        store.increment("logins", "u1", 1.0).await.unwrap();
+        let event = HashMap::from([("user_id".to_string(), Value::String("u1".to_string()))]);
+        let context = ExecutionContext::from_event(event).unwrap();
+
+        let values = executor.execute_features(&names, &context).await.unwrap();
+        assert_eq!(values["attempts_1h"], Value::Number(4.0));
+        assert_eq!(values["double_attempts_1h"], Value::Number(8.0));
+        assert_eq!(values["attempts_per_login_1h"], Value::Number(4.0));
+
+        let value = executor.execute_feature("double_attempts_1h", &context).await.unwrap();
+        assert_eq!(value, Value::Number(8.0));
+
+        let err = executor.execute_feature("loop_a", &context).await.unwrap_err();
+        assert!(err.to_string().contains("Circular dependency"));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_features_with_limits() {
+        use crate::datasource::client::tests::static_client;
+
+        let count = |n: f64| Some(vec![HashMap::from([("count".to_string(), Value::Number(n))])]);
+        let features: Vec<FeatureDefinition> = serde_yaml::from_str(
+            r#"
+- name: fast_count
+  type: aggregation
+  method: count
+  datasource: fast
+  entity: events
+  dimension: user_id
+  dimension_value: "${event.user_id}"
+- name: slow_count
+  type: aggregation
+  method: count
+  datasource: slow
+  entity: events
+  dimension: user_id
+  dimension_value: "${event.user_id}"
+- name: slow_ratio
+  type: expression
+  expression: "fast_count / slow_count"
+"#,
+        )
+        .unwrap();
+
+        let build = |limits: FetchLimits| {
+            let mut executor = FeatureExecutor::new().with_fetch_limits(limits);
+            executor.add_datasource("fast", static_client("fast", count(2.0), Duration::ZERO));
+            executor.add_datasource("slow", static_client("slow", count(4.0), Duration::from_millis(300)));
+            executor.register_features(features.clone()).unwrap();
+            executor
+        };
+        let event = HashMap::from([("user_id".to_string(), Value::String("u1".to_string()))]);
+        let context = ExecutionContext::from_event(event).unwrap();
+        let names = vec!["fast_count".to_string(), "slow_ratio".to_string()];
+        let status = |fetches: &[FeatureFetch], name: &str| {
+            fetches.iter().find(|f| f.feature == name).map(|f| (f.status, f.value.clone()))
+        };
+
+        // Without limits every feature is fetched
+        let fetches = build(FetchLimits::default()).fetch_features(&names, &context).await.unwrap();
+        assert_eq!(fetches.len(), 3);
+        assert_eq!(status(&fetches, "slow_ratio"), Some((FetchStatus::Ok, Value::Number(0.5))));
+
+        // A slow feature times out alone; its dependents are not computed
+        let limits = FetchLimits {
+            feature_timeout: Some(Duration::from_millis(50)),
+            total_budget: None,
+        };
+        let fetches = build(limits).fetch_features(&names, &context).await.unwrap();
+        assert_eq!(status(&fetches, "fast_count"), Some((FetchStatus::Ok, Value::Number(2.0))));
+        assert_eq!(status(&fetches, "slow_count"), Some((FetchStatus::TimedOut, Value::Null)));
+        assert_eq!(status(&fetches, "slow_ratio"), Some((FetchStatus::Failed, Value::Null)));
+        let slow = fetches.iter().find(|f| f.feature == "slow_count").unwrap();
+        assert!(slow.latency < Duration::from_millis(250));
+
+        // The total budget bounds the whole fetch
+        let limits = FetchLimits {
+            feature_timeout: None,
+            total_budget: Some(Duration::from_millis(50)),
+        };
+        let start = std::time::Instant::now();
+        let fetches = build(limits).fetch_features(&names, &context).await.unwrap();
+        assert!(start.elapsed() < Duration::from_millis(250));
+        assert_eq!(status(&fetches, "slow_count"), Some((FetchStatus::TimedOut, Value::Null)));
+    }
+
+    #[tokio::test]
+    async fn test_replace_features() {
+        use crate::datasource::client::tests::static_client;
+
+        let count = |n: f64| Some(vec![HashMap::from([("count".to_string(), Value::Number(n))])]);
+        let mut executor = FeatureExecutor::new();
+        executor.add_datasource("events", static_client("events", count(3.0), Duration::ZERO));
+        let parse = |yaml: &str| serde_yaml::from_str::<Vec<FeatureDefinition>>(yaml).unwrap();
+        executor
+            .register_features(parse(
+                r#"
+- name: txn_count
+  type: aggregation
+  method: count
+  datasource: events
+  entity: events
+  dimension: user_id
+  dimension_value: "${event.user_id}"
+- name: doubled
+  type: expression
+  expression: "txn_count * 2"
+"#,
+            ))
+            .unwrap();
+
+        let context = ExecutionContext::from_event(HashMap::from([(
+            "user_id".to_string(),
+            Value::String("u1".to_string()),
+        )]))
+        .unwrap();
+        assert_eq!(executor.execute_feature("doubled", &context).await.unwrap(), Value::Number(6.0));
+        assert_eq!(executor.registry_version(), 0);
+
+        // Swapped in through a shared reference, keeping the datasources
+        let executor = Arc::new(executor);
+        let changes = executor
+            .replace_features(parse(
+                r#"
+- name: txn_count
+  type: aggregation
+  method: count
+  datasource: events
+  entity: events
+  dimension: user_id
+  dimension_value: "${event.user_id}"
+- name: doubled
+  type: expression
+  expression: "txn_count * 3"
+  version: "2.0"
+- name: tripled
+  type: expression
+  expression: "txn_count * 3"
+"#,
+            ))
+            .await
+            .unwrap();
+        assert_eq!(changes.registry_version, 1);
+        assert_eq!(changes.added, vec!["tripled".to_string()]);
+        assert_eq!(changes.updated, vec!["doubled".to_string()]);
+        assert!(changes.removed.is_empty());
+        assert_eq!(executor.feature_version("doubled").as_deref(), Some("2.0"));
+        assert_eq!(executor.execute_feature("doubled", &context).await.unwrap(), Value::Number(9.0));
+
+        // Invalid definitions leave the current ones in place
+        let err = executor
+            .replace_features(parse(
+                r#"
+- name: doubled
+  type: expression
+  expression: "txn_count * 2"
+"#,
+            ))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("unknown feature 'txn_count'"), "{}", err);
+        assert_eq!(executor.registry_version(), 1);
+        assert!(executor.has_feature("tripled"));
+
+        let changes = executor.replace_features(Vec::new()).await.unwrap();
+        assert_eq!(changes.removed.len(), 3);
+        assert!(!executor.has_feature("doubled"));
+    }
+
+    #[tokio::test]
+    async fn test_missing_value_policies() {
+        use crate::datasource::client::tests::static_client;
+
+        let count = |n: f64| Some(vec![HashMap::from([("count".to_string(), Value::Number(n))])]);
+        let mut executor = FeatureExecutor::new();
+        executor.add_datasource("events", static_client("events", count(3.0), Duration::ZERO));
+        executor.add_datasource("down", static_client("down", None, Duration::ZERO));
+
+        let feature = |name: &str, datasource: &str, policy: &str| {
+            format!(
+                r#"
+- name: {}
+  type: aggregation
+  method: count
+  datasource: {}
+  entity: events
+  dimension: user_id
+  dimension_value: "${{event.user_id}}"
+  {}"#,
+                name, datasource, policy
+            )
+        };
+        let yaml = [
+            feature("with_default", "down", "default: 0"),
+            feature("with_mean", "events", "on_missing: mean\n  default: 1"),
+            feature("fail_closed", "down", "on_missing: fail_closed"),
+            feature("fail_open", "down", "on_missing: fail_open"),
+            feature("no_policy", "down", ""),
+        ]
+        .concat();
+        let features: Vec<FeatureDefinition> = serde_yaml::from_str(&yaml).unwrap();
+        executor.register_features(features).unwrap();
+
+        let with_user = ExecutionContext::from_event(HashMap::from([(
+            "user_id".to_string(),
+            Value::String("u1".to_string()),
+        )]))
+        .unwrap();
+        let without_user = ExecutionContext::from_event(HashMap::new()).unwrap();
+
+        assert_eq!(executor.execute_feature("with_default", &with_user).await.unwrap(), Value::Number(0.0));
+        assert_eq!(executor.execute_feature("fail_open", &with_user).await.unwrap(), Value::Null);
+        assert!(executor.execute_feature("no_policy", &with_user).await.is_err());
+        let err = executor.execute_feature("fail_closed", &with_user).await.unwrap_err();
+        assert!(err.is::<MissingFeatureError>(), "{}", err);
+
+        // The mean falls back to the default until a value has been computed
+        assert_eq!(executor.execute_feature("with_mean", &without_user).await.unwrap(), Value::Number(1.0));
+        assert_eq!(executor.execute_feature("with_mean", &with_user).await.unwrap(), Value::Number(3.0));
+        assert_eq!(executor.execute_feature("with_mean", &without_user).await.unwrap(), Value::Number(3.0));
+
+        // Fetches record the policy applied
+        let names = vec!["with_default".to_string(), "with_mean".to_string()];
+        let fetches = executor.fetch_features(&names, &without_user).await.unwrap();
+        assert!(fetches.iter().all(|f| f.status == FetchStatus::Failed));
+        assert_eq!(fetches[0].value, Value::Number(0.0));
+        assert_eq!(fetches[0].imputed, Some(MissingPolicy::Default));
+        assert_eq!(fetches[1].value, Value::Number(3.0));
+        assert_eq!(fetches[1].imputed, Some(MissingPolicy::Mean));
+
+        let names = vec!["with_default".to_string(), "fail_closed".to_string()];
+        let err = executor.fetch_features(&names, &with_user).await.unwrap_err();
+        assert!(err.is::<MissingFeatureError>(), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn test_feature_source_fallback() {
+        use crate::datasource::client::tests::static_client;
+        use std::time::Duration;
+
+        let count = |n: f64| Some(vec![HashMap::from([("count".to_string(), Value::Number(n))])]);
+        let mut executor = FeatureExecutor::new();
+        executor.add_datasource("slow", static_client("slow", count(1.0), Duration::from_secs(5)));
+        executor.add_datasource("down", static_client("down", None, Duration::ZERO));
+        executor.add_datasource("events", static_client("events", count(3.0), Duration::ZERO));
+
+        let features: Vec<FeatureDefinition> = serde_yaml::from_str(
+            r#"
+- name: cnt_userid_txn_24h
+  type: aggregation
+  method: count
+  datasource: events
+  entity: events
+  dimension: user_id
+  dimension_value: "${event.user_id}"
+  window: 24h
+  sources:
+    - datasource: slow
+      timeout_ms: 20
+    - datasource: down
+    - datasource: events
+- name: cnt_userid_login_24h
+  type: aggregation
+  method: count
+  datasource: events
+  entity: events
+  dimension: user_id
+  dimension_value: "${event.user_id}"
+  window: 24h
+  sources:
+    - datasource: down
+  default: 0
+- name: cnt_userid_refund_24h
+  type: aggregation
+  method: count
+  datasource: events
+  entity: events
+  dimension: user_id
+  dimension_value: "${event.user_id}"
+  window: 24h
+  sources:
+    - datasource: down
+"#,
+        )
+        .unwrap();
+        assert_eq!(features[0].sources[0].timeout_ms, Some(20));
+        executor.register_features(features).unwrap();
+
+        let event = HashMap::from([("user_id".to_string(), Value::String("u1".to_string()))]);
+        let context = ExecutionContext::from_event(event).unwrap();
+
+        let value = executor.execute_feature("cnt_userid_txn_24h", &context).await.unwrap();
+        assert_eq!(value, Value::Number(3.0));
+
+        let value = executor.execute_feature("cnt_userid_login_24h", &context).await.unwrap();
+        assert_eq!(value, Value::Number(0.0));
+
+        let err = executor.execute_feature("cnt_userid_refund_24h", &context).await.unwrap_err();
+        assert!(format!("{:#}", err).contains("connection refused"));
+    }
+
+    #[tokio::test]
+    async fn test_mode_aggregation() {
+        use crate::datasource::client::tests::static_client;
+        use std::time::Duration;
+
+        let top = HashMap::from([
+            ("device_id".to_string(), Value::String("dev_1".to_string())),
+            ("count".to_string(), Value::Number(4.0)),
+        ]);
+        let mut executor = FeatureExecutor::new();
+        executor.add_datasource("events", static_client("events", Some(vec![top]), Duration::ZERO));
+        executor.add_datasource("empty", static_client("empty", Some(vec![]), Duration::ZERO));
+
+        let features: Vec<FeatureDefinition> = serde_yaml::from_str(
+            r#"
+- name: mode_userid_device_24h
+  type: aggregation
+  method: mode
+  datasource: events
+  entity: events
+  dimension: user_id
+  dimension_value: "${event.user_id}"
+  field: device_id
+  window: 24h
+- name: mode_userid_country_24h
+  type: aggregation
+  method: mode
+  datasource: empty
+  entity: events
+  dimension: user_id
+  dimension_value: "${event.user_id}"
+  field: country
+  window: 24h
+"#,
+        )
+        .unwrap();
+        executor.register_features(features).unwrap();
+
+        let event = HashMap::from([("user_id".to_string(), Value::String("u1".to_string()))]);
+        let context = ExecutionContext::from_event(event).unwrap();
+
+        let value = executor.execute_feature("mode_userid_device_24h", &context).await.unwrap();
+        assert_eq!(value, Value::String("dev_1".to_string()));
+
+        let value = executor.execute_feature("mode_userid_country_24h", &context).await.unwrap();
+        assert_eq!(value, Value::Null);
+    }
+
+    #[tokio::test]
+    async fn test_distribution_aggregations() {
+        use crate::datasource::client::tests::static_client;
+        use std::time::Duration;
+
+        let group = |device: Value, count: f64| {
+            HashMap::from([
+                ("device_id".to_string(), device),
+                ("count".to_string(), Value::Number(count)),
+            ])
+        };
+        // Two devices used equally often, plus events without a device
+        let groups = vec![
+            group(Value::String("dev_1".to_string()), 2.0),
+            group(Value::String("dev_2".to_string()), 2.0),
+            group(Value::Null, 5.0),
+        ];
+        let totals = HashMap::from([
+            ("distinct_count".to_string(), Value::Number(3.0)),
+            ("count".to_string(), Value::Number(12.0)),
+        ]);
+        let mut executor = FeatureExecutor::new();
+        executor.add_datasource("groups", static_client("groups", Some(groups), Duration::ZERO));
+        executor.add_datasource("totals", static_client("totals", Some(vec![totals]), Duration::ZERO));
+
+        let features: Vec<FeatureDefinition> = serde_yaml::from_str(
+            r#"
+- name: entropy_userid_device_7d
+  type: aggregation
+  method: entropy
+  datasource: groups
+  entity: events
+  dimension: user_id
+  dimension_value: "${event.user_id}"
+  field: device_id
+  window: 7d
+- name: ratio_userid_device_7d
+  type: aggregation
+  method: count_distinct_ratio
+  datasource: totals
+  entity: events
+  dimension: user_id
+  dimension_value: "${event.user_id}"
+  field: device_id
+  window: 7d
+"#,
+        )
+        .unwrap();
+        executor.register_features(features).unwrap();
+
+        let event = HashMap::from([("user_id".to_string(), Value::String("u1".to_string()))]);
+        let context = ExecutionContext::from_event(event).unwrap();
+
+        let value = executor.execute_feature("entropy_userid_device_7d", &context).await.unwrap();
+        assert_eq!(value, Value::Number(1.0));
+
+        let value = executor.execute_feature("ratio_userid_device_7d", &context).await.unwrap();
+        assert_eq!(value, Value::Number(0.25));
+    }
+
+    #[tokio::test]
+    async fn test_decayed_aggregation() {
+        use crate::datasource::client::tests::static_client;
+        use std::time::Duration;
+
+        let row = HashMap::from([("decayed_sum".to_string(), Value::Number(12.5))]);
+        let mut executor = FeatureExecutor::new();
+        executor.add_datasource("events", static_client("events", Some(vec![row]), Duration::ZERO));
+
+        let features: Vec<FeatureDefinition> = serde_yaml::from_str(
+            r#"
+- name: dsum_userid_txn_amt
+  type: aggregation
+  method: decayed_sum
+  datasource: events
+  entity: events
+  dimension: user_id
+  dimension_value: "${event.user_id}"
+  field: amount
+  half_life: 1h
+  window: 24h
+- name: dcnt_userid_txn
+  type: aggregation
+  method: decayed_count
+  datasource: events
+  entity: events
+  dimension: user_id
+  dimension_value: "${event.user_id}"
+  window: 24h
+"#,
+        )
+        .unwrap();
+        assert_eq!(
+            features[0].aggregation.as_ref().unwrap().half_life.as_deref(),
+            Some("1h")
+        );
+        executor.register_features(features).unwrap();
+
+        let event = HashMap::from([("user_id".to_string(), Value::String("u1".to_string()))]);
+        let context = ExecutionContext::from_event(event).unwrap();
+
+        let value = executor.execute_feature("dsum_userid_txn_amt", &context).await.unwrap();
+        assert_eq!(value, Value::Number(12.5));
+
+        let err = executor.execute_feature("dcnt_userid_txn", &context).await.unwrap_err();
+        assert!(format!("{:#}", err).contains("half_life required"));
+    }
+
     #[tokio::test]
     async fn test_cache_overwrite() {
         let executor = FeatureExecutor::new();
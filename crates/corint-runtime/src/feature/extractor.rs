@@ -6,7 +6,8 @@ use crate::error::{Result, RuntimeError};
 use crate::storage::{Event, EventFilter, Storage, TimeRange};
 use corint_core::ir::{FeatureType, TimeWindow};
 use corint_core::Value;
-use std::collections::HashSet;
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 
 /// Feature extractor for computing statistical features
@@ -29,7 +30,13 @@ impl FeatureExtractor {
         filter: Option<EventFilter>,
     ) -> Result<Value> {
         let time_range = self.calculate_time_range(time_window);
-        let events = self.storage.query_events(time_range, filter).await?;
+
+        // Only the aggregated field is read
+        let mut filter = filter.unwrap_or_default();
+        if filter.fields.is_none() {
+            filter.fields = Some(field.first().cloned().into_iter().collect());
+        }
+        let events = self.storage.query_events(time_range, Some(filter)).await?;
 
         match feature_type {
             FeatureType::Count => self.compute_count(&events),
@@ -39,6 +46,14 @@ impl FeatureExtractor {
             FeatureType::Min => self.compute_min(&events, field),
             FeatureType::Max => self.compute_max(&events, field),
             FeatureType::Percentile { p } => self.compute_percentile(&events, field, *p),
+            FeatureType::Median => self.compute_median(&events, field),
+            FeatureType::Mode => self.compute_mode(&events, field),
+            FeatureType::DecayedSum { half_life_secs } => {
+                self.compute_decayed(&events, Some(field), *half_life_secs, time_range.1)
+            }
+            FeatureType::DecayedCount { half_life_secs } => {
+                self.compute_decayed(&events, None, *half_life_secs, time_range.1)
+            }
             FeatureType::StdDev => self.compute_stddev(&events, field),
             FeatureType::Variance => self.compute_variance(&events, field),
         }
@@ -149,26 +164,81 @@ impl FeatureExtractor {
             ));
         }
 
-        let mut values: Vec<f64> = Vec::new();
+        let values = self.sorted_values(events, field);
+        if values.is_empty() {
+            return Ok(Value::Number(0.0));
+        }
+
+        let index = (p / 100.0 * (values.len() - 1) as f64).round() as usize;
+        Ok(Value::Number(values[index]))
+    }
+
+    fn compute_median(&self, events: &[Event], field: &[String]) -> Result<Value> {
+        let values = self.sorted_values(events, field);
+        if values.is_empty() {
+            return Ok(Value::Number(0.0));
+        }
+
+        let mid = values.len() / 2;
+        let median = if values.len().is_multiple_of(2) {
+            (values[mid - 1] + values[mid]) / 2.0
+        } else {
+            values[mid]
+        };
+        Ok(Value::Number(median))
+    }
+
+    /// Most frequent value of any type; ties go to the smallest value
+    fn compute_mode(&self, events: &[Event], field: &[String]) -> Result<Value> {
+        let mut counts: HashMap<String, (Value, usize)> = HashMap::new();
 
         for event in events {
-            if let Some(Value::Number(n)) = self.get_field_value(&event.data, field) {
-                // Filter out NaN and Infinity values
-                if n.is_finite() {
-                    values.push(n);
+            match self.get_field_value(&event.data, field) {
+                None | Some(Value::Null) => {}
+                Some(value) => {
+                    counts.entry(format!("{:?}", value)).or_insert((value, 0)).1 += 1;
                 }
             }
         }
 
-        if values.is_empty() {
-            return Ok(Value::Number(0.0));
+        let mode = counts
+            .into_values()
+            .max_by(|(a, a_count), (b, b_count)| {
+                a_count.cmp(b_count).then_with(|| compare_values(b, a))
+            })
+            .map(|(value, _)| value);
+        Ok(mode.unwrap_or(Value::Null))
+    }
+
+    /// Sum of the field (or 1 per event) weighted by `0.5^(age / half_life)`
+    fn compute_decayed(
+        &self,
+        events: &[Event],
+        field: Option<&[String]>,
+        half_life_secs: u64,
+        now: i64,
+    ) -> Result<Value> {
+        if half_life_secs == 0 {
+            return Err(RuntimeError::InvalidOperation(
+                "Half-life must be positive".to_string(),
+            ));
         }
 
-        // Safe sort: NaN values have been filtered out above
-        values.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+        let mut total = 0.0;
 
-        let index = (p / 100.0 * (values.len() - 1) as f64).round() as usize;
-        Ok(Value::Number(values[index]))
+        for event in events {
+            let value = match field {
+                Some(field) => match self.get_field_value(&event.data, field) {
+                    Some(Value::Number(n)) => n,
+                    _ => continue,
+                },
+                None => 1.0,
+            };
+            let age = (now - event.timestamp).max(0) as f64;
+            total += value * 0.5f64.powf(age / half_life_secs as f64);
+        }
+
+        Ok(Value::Number(total))
     }
 
     fn compute_stddev(&self, events: &[Event], field: &[String]) -> Result<Value> {
@@ -203,6 +273,24 @@ impl FeatureExtractor {
         Ok(Value::Number(variance))
     }
 
+    /// Finite numeric values of the field in ascending order
+    fn sorted_values(&self, events: &[Event], field: &[String]) -> Vec<f64> {
+        let mut values: Vec<f64> = Vec::new();
+
+        for event in events {
+            if let Some(Value::Number(n)) = self.get_field_value(&event.data, field) {
+                // Filter out NaN and Infinity values
+                if n.is_finite() {
+                    values.push(n);
+                }
+            }
+        }
+
+        // Safe sort: NaN values have been filtered out above
+        values.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+        values
+    }
+
     /// Get field value from event data using path
     fn get_field_value(
         &self,
@@ -228,6 +316,17 @@ impl FeatureExtractor {
     }
 }
 
+/// Order numbers and strings by value; other values only by their type
+fn compare_values(a: &Value, b: &Value) -> Ordering {
+    match (a, b) {
+        (Value::Number(a), Value::Number(b)) => a.partial_cmp(b).unwrap_or(Ordering::Equal),
+        (Value::String(a), Value::String(b)) => a.cmp(b),
+        (Value::Number(_), _) => Ordering::Less,
+        (_, Value::Number(_)) => Ordering::Greater,
+        _ => Ordering::Equal,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -528,6 +627,100 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_extract_median_mode() {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs() as i64;
+
+        let events = vec![
+            create_test_event(now - 400, 40.0),
+            create_test_event(now - 300, 10.0),
+            create_test_event(now - 200, 40.0),
+            create_test_event(now - 150, 10.0),
+            create_test_event(now - 100, 25.0),
+            create_test_event(now - 50, 30.0),
+        ];
+
+        let storage = create_storage_with_events(events);
+        let extractor = FeatureExtractor::new(storage);
+        let field = ["amount".to_string()];
+        let window = TimeWindow::Custom { seconds: 1000 };
+
+        // Sorted [10, 10, 25, 30, 40, 40]: the middle values are averaged
+        let median = extractor
+            .extract(&FeatureType::Median, &field, &window, None)
+            .await
+            .unwrap();
+        assert_eq!(median, Value::Number(27.5));
+
+        // 10 and 40 occur twice each; the smaller one wins
+        let mode = extractor
+            .extract(&FeatureType::Mode, &field, &window, None)
+            .await
+            .unwrap();
+        assert_eq!(mode, Value::Number(10.0));
+
+        let p95 = extractor
+            .extract(&FeatureType::Percentile { p: 95.0 }, &field, &window, None)
+            .await
+            .unwrap();
+        assert_eq!(p95, Value::Number(40.0));
+    }
+
+    #[tokio::test]
+    async fn test_extract_decayed() {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs() as i64;
+
+        // Ages of about zero, one and two half-lives
+        let events = vec![
+            create_test_event(now - 1, 40.0),
+            create_test_event(now - 3601, 40.0),
+            create_test_event(now - 7201, 40.0),
+        ];
+
+        let storage = create_storage_with_events(events);
+        let extractor = FeatureExtractor::new(storage);
+        let field = ["amount".to_string()];
+        let window = TimeWindow::Last24Hours;
+
+        let sum = extractor
+            .extract(
+                &FeatureType::DecayedSum {
+                    half_life_secs: 3600,
+                },
+                &field,
+                &window,
+                None,
+            )
+            .await
+            .unwrap();
+        let count = extractor
+            .extract(
+                &FeatureType::DecayedCount {
+                    half_life_secs: 3600,
+                },
+                &field,
+                &window,
+                None,
+            )
+            .await
+            .unwrap();
+
+        // A second may pass between inserting and extracting
+        match (sum, count) {
+            (Value::Number(sum), Value::Number(count)) => {
+                assert!((sum - 70.0).abs() < 0.1, "sum {}", sum);
+                assert!((count - 1.75).abs() < 0.01, "count {}", count);
+            }
+            other => panic!("Expected numbers, got {:?}", other),
+        }
+    }
+
     #[tokio::test]
     async fn test_calculate_time_range() {
         let storage = Arc::new(InMemoryStorage::new());
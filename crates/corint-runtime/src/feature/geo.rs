@@ -0,0 +1,158 @@
+//! Geo primitives
+//!
+//! Great-circle distances between coordinates and the speed needed to travel
+//! between two located events, for "impossible travel" features.
+
+use corint_core::Value;
+
+/// Mean Earth radius in kilometers
+pub const EARTH_RADIUS_KM: f64 = 6371.0088;
+
+/// A latitude/longitude pair in degrees
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GeoPoint {
+    /// Latitude (-90 to 90)
+    pub lat: f64,
+
+    /// Longitude (-180 to 180)
+    pub lon: f64,
+}
+
+impl GeoPoint {
+    /// Create a point; `None` when a coordinate is out of range
+    pub fn new(lat: f64, lon: f64) -> Option<Self> {
+        ((-90.0..=90.0).contains(&lat) && (-180.0..=180.0).contains(&lon))
+            .then_some(Self { lat, lon })
+    }
+
+    /// Create a point from numbers or numeric strings
+    pub fn from_values(lat: &Value, lon: &Value) -> Option<Self> {
+        Self::new(coordinate(lat)?, coordinate(lon)?)
+    }
+
+    /// Great-circle distance to another point in kilometers
+    pub fn distance_km(&self, other: &GeoPoint) -> f64 {
+        haversine_km(self.lat, self.lon, other.lat, other.lon)
+    }
+}
+
+/// Great-circle distance in kilometers between two coordinates in degrees
+pub fn haversine_km(lat1: f64, lon1: f64, lat2: f64, lon2: f64) -> f64 {
+    let d_lat = (lat2 - lat1).to_radians();
+    let d_lon = (lon2 - lon1).to_radians();
+    let a = (d_lat / 2.0).sin().powi(2)
+        + lat1.to_radians().cos() * lat2.to_radians().cos() * (d_lon / 2.0).sin().powi(2);
+    2.0 * EARTH_RADIUS_KM * a.sqrt().min(1.0).asin()
+}
+
+/// Speed in km/h needed to get from one point to another
+///
+/// Timestamps are seconds since epoch, in either order. Events less than a
+/// second apart count as one second, so distinct places at the same time
+/// give a very high but finite speed.
+pub fn velocity_kmh(from: GeoPoint, from_ts: i64, to: GeoPoint, to_ts: i64) -> f64 {
+    let hours = (to_ts - from_ts).unsigned_abs().max(1) as f64 / 3600.0;
+    from.distance_km(&to) / hours
+}
+
+fn coordinate(value: &Value) -> Option<f64> {
+    match value {
+        Value::Number(n) => Some(*n),
+        Value::String(s) => s.trim().parse().ok(),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_haversine_distance() {
+        let london = GeoPoint::new(51.5074, -0.1278).unwrap();
+        let paris = GeoPoint::new(48.8566, 2.3522).unwrap();
+        assert!((london.distance_km(&paris) - 343.6).abs() < 1.0);
+        assert_eq!(london.distance_km(&london), 0.0);
+
+        // Antipodes are half the circumference apart
+        let d = haversine_km(0.0, 0.0, 0.0, 180.0);
+        assert!((d - std::f64::consts::PI * EARTH_RADIUS_KM).abs() < 1e-6);
+
+        assert!(GeoPoint::new(91.0, 0.0).is_none());
+        assert_eq!(
+            GeoPoint::from_values(&Value::String("48.8566".into()), &Value::Number(2.3522)),
+            Some(paris)
+        );
+        assert!(GeoPoint::from_values(&Value::Null, &Value::Number(2.0)).is_none());
+    }
+
+    #[test]
+    fn test_velocity() {
+        let london = GeoPoint::new(51.5074, -0.1278).unwrap();
+        let paris = GeoPoint::new(48.8566, 2.3522).unwrap();
+
+        // About 343 km in two hours, in either order
+        let speed = velocity_kmh(london, 1_000, paris, 1_000 + 7200);
+        assert!((speed - 171.8).abs() < 1.0);
+        assert_eq!(velocity_kmh(paris, 1_000 + 7200, london, 1_000), speed);
+
+        // Simultaneous events in different places
+        assert!(velocity_kmh(london, 5, paris, 5) > 1_000_000.0);
+    }
+
+    #[tokio::test]
+    async fn test_geo_velocity_operator() {
+        use crate::datasource::client::tests::static_client;
+        use crate::feature::operator::GeoVelocityOperator;
+        use std::collections::HashMap;
+        use std::time::Duration;
+
+        let row = |ts: &str, lat: f64, lon: f64| {
+            HashMap::from([
+                ("event_timestamp".to_string(), Value::String(ts.to_string())),
+                ("lat".to_string(), Value::Number(lat)),
+                ("lon".to_string(), Value::Number(lon)),
+                ("count".to_string(), Value::Number(1.0)),
+            ])
+        };
+        // Latest first: Paris one hour after London
+        let rows = vec![
+            row("2024-03-01 11:00:00", 48.8566, 2.3522),
+            row("2024-03-01T10:00:00Z", 51.5074, -0.1278),
+        ];
+        let datasource = static_client("events", Some(rows), Duration::ZERO);
+
+        let mut operator: GeoVelocityOperator = serde_json::from_value(serde_json::json!({
+            "entity": "events",
+            "dimension": "user_id",
+            "dimension_value": "${event.user_id}",
+            "latitude_field": "lat",
+            "longitude_field": "lon"
+        }))
+        .unwrap();
+        let context = HashMap::from([
+            ("user_id".to_string(), Value::String("u1".to_string())),
+            ("latitude".to_string(), Value::Number(48.8566)),
+            ("longitude".to_string(), Value::String("2.3522".to_string())),
+        ]);
+
+        match operator.execute(&datasource, &context).await.unwrap() {
+            Value::Number(speed) => assert!((speed - 343.6).abs() < 1.0, "speed {}", speed),
+            other => panic!("Expected a speed, got {:?}", other),
+        }
+
+        // From the current event: no distance to the latest stored one
+        operator.current_latitude = Some("${event.latitude}".to_string());
+        operator.current_longitude = Some("${event.longitude}".to_string());
+        assert_eq!(
+            operator.execute(&datasource, &context).await.unwrap(),
+            Value::Number(0.0)
+        );
+
+        let empty = static_client("events", Some(vec![]), Duration::ZERO);
+        assert_eq!(
+            operator.execute(&empty, &context).await.unwrap(),
+            Value::Null
+        );
+    }
+}
@@ -0,0 +1,364 @@
+//! Graph store clients for relationship features
+//!
+//! Graph features answer questions such as "how many distinct users were
+//! linked to this device in the last 30 days" or "what is the degree of this
+//! email node". They are computed against a [`GraphStore`], which the feature
+//! executor resolves by the feature's `datasource` name.
+//!
+//! [`SqlAdjacencyGraphStore`] keeps links in an adjacency table of an existing
+//! SQL or OLAP data source. Each link is stored once per direction:
+//!
+//! ```sql
+//! CREATE TABLE entity_links (
+//!     src_type        TEXT NOT NULL,   -- e.g. 'device_id'
+//!     src_id          TEXT NOT NULL,
+//!     dst_type        TEXT NOT NULL,   -- e.g. 'user_id'
+//!     dst_id          TEXT NOT NULL,
+//!     event_timestamp TIMESTAMPTZ NOT NULL
+//! );
+//! ```
+
+use crate::datasource::{
+    Aggregation, AggregationType, DataSourceClient, Filter, FilterOperator, Query, QueryType,
+    RelativeWindow, TimeWindow, TimeWindowType,
+};
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use corint_core::Value;
+use std::collections::HashSet;
+use std::sync::{Arc, RwLock};
+
+/// Default adjacency table for [`SqlAdjacencyGraphStore`]
+pub const DEFAULT_LINK_TABLE: &str = "entity_links";
+
+/// Default cap on neighbors fetched for a single node
+pub const DEFAULT_MAX_NEIGHBORS: usize = 10_000;
+
+/// A typed node in the entity graph (e.g. `device_id = "dev_1"`)
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct GraphNode {
+    /// Node type, usually the event field name (e.g. "device_id")
+    pub node_type: String,
+
+    /// Node identifier
+    pub id: String,
+}
+
+impl GraphNode {
+    /// Create a node
+    pub fn new(node_type: impl Into<String>, id: impl Into<String>) -> Self {
+        Self {
+            node_type: node_type.into(),
+            id: id.into(),
+        }
+    }
+}
+
+/// Client for a store of links between entities
+///
+/// Only [`neighbors`](GraphStore::neighbors) is required; the counting
+/// methods default to counting neighbors and may be overridden by stores
+/// that can push the count down.
+#[async_trait]
+pub trait GraphStore: Send + Sync {
+    /// Distinct nodes linked to `node` within the window, optionally only of `neighbor_type`
+    async fn neighbors(
+        &self,
+        node: &GraphNode,
+        neighbor_type: Option<&str>,
+        window: Option<&RelativeWindow>,
+    ) -> Result<Vec<GraphNode>>;
+
+    /// Number of distinct `neighbor_type` nodes linked to `node`
+    async fn linked_count(
+        &self,
+        node: &GraphNode,
+        neighbor_type: &str,
+        window: Option<&RelativeWindow>,
+    ) -> Result<u64> {
+        Ok(self
+            .neighbors(node, Some(neighbor_type), window)
+            .await?
+            .len() as u64)
+    }
+
+    /// Number of distinct nodes of any type linked to `node`
+    async fn degree(&self, node: &GraphNode, window: Option<&RelativeWindow>) -> Result<u64> {
+        Ok(self.neighbors(node, None, window).await?.len() as u64)
+    }
+
+    /// Number of `via_type` nodes linked to both `a` and `b`
+    async fn shared_count(
+        &self,
+        a: &GraphNode,
+        b: &GraphNode,
+        via_type: &str,
+        window: Option<&RelativeWindow>,
+    ) -> Result<u64> {
+        let linked_to_a: HashSet<GraphNode> = self
+            .neighbors(a, Some(via_type), window)
+            .await?
+            .into_iter()
+            .collect();
+        let shared = self
+            .neighbors(b, Some(via_type), window)
+            .await?
+            .into_iter()
+            .filter(|node| linked_to_a.contains(node))
+            .count();
+        Ok(shared as u64)
+    }
+}
+
+/// Graph store backed by an adjacency table in a SQL or OLAP data source
+pub struct SqlAdjacencyGraphStore {
+    client: Arc<DataSourceClient>,
+    table: String,
+    time_field: String,
+    max_neighbors: usize,
+}
+
+impl SqlAdjacencyGraphStore {
+    /// Create a store over the default `entity_links` table
+    pub fn new(client: Arc<DataSourceClient>) -> Self {
+        Self {
+            client,
+            table: DEFAULT_LINK_TABLE.to_string(),
+            time_field: "event_timestamp".to_string(),
+            max_neighbors: DEFAULT_MAX_NEIGHBORS,
+        }
+    }
+
+    /// Use a different adjacency table
+    pub fn with_table(mut self, table: impl Into<String>) -> Self {
+        self.table = table.into();
+        self
+    }
+
+    /// Use a different timestamp column for windowed queries
+    pub fn with_time_field(mut self, time_field: impl Into<String>) -> Self {
+        self.time_field = time_field.into();
+        self
+    }
+
+    /// Cap the number of neighbors fetched for a single node
+    pub fn with_max_neighbors(mut self, max_neighbors: usize) -> Self {
+        self.max_neighbors = max_neighbors;
+        self
+    }
+
+    fn filters(&self, node: &GraphNode, neighbor_type: Option<&str>) -> Vec<Filter> {
+        let eq = |field: &str, value: &str| Filter {
+            field: field.to_string(),
+            operator: FilterOperator::Eq,
+            value: Value::String(value.to_string()),
+        };
+
+        let mut filters = vec![eq("src_type", &node.node_type), eq("src_id", &node.id)];
+        if let Some(neighbor_type) = neighbor_type {
+            filters.push(eq("dst_type", neighbor_type));
+        }
+        filters
+    }
+
+    fn time_window(&self, window: Option<&RelativeWindow>) -> Option<TimeWindow> {
+        window.map(|window| TimeWindow {
+            window_type: TimeWindowType::Relative(window.clone()),
+            time_field: self.time_field.clone(),
+        })
+    }
+}
+
+#[async_trait]
+impl GraphStore for SqlAdjacencyGraphStore {
+    async fn neighbors(
+        &self,
+        node: &GraphNode,
+        neighbor_type: Option<&str>,
+        window: Option<&RelativeWindow>,
+    ) -> Result<Vec<GraphNode>> {
+        let query = Query {
+            query_type: QueryType::RawEvents,
+            entity: self.table.clone(),
+            filters: self.filters(node, neighbor_type),
+            time_window: self.time_window(window),
+            aggregations: vec![],
+            group_by: vec![],
+            having: Vec::new(),
+            order_by: Vec::new(),
+            limit: Some(self.max_neighbors),
+            cache_ttl: None,
+        };
+
+        let result = self.client.query(query).await?;
+
+        let mut seen = HashSet::new();
+        let neighbors = result
+            .rows
+            .iter()
+            .filter_map(|row| match (row.get("dst_type"), row.get("dst_id")) {
+                (Some(Value::String(node_type)), Some(Value::String(id))) => {
+                    Some(GraphNode::new(node_type.clone(), id.clone()))
+                }
+                _ => None,
+            })
+            .filter(|neighbor| seen.insert(neighbor.clone()))
+            .collect();
+        Ok(neighbors)
+    }
+
+    async fn linked_count(
+        &self,
+        node: &GraphNode,
+        neighbor_type: &str,
+        window: Option<&RelativeWindow>,
+    ) -> Result<u64> {
+        let query = Query {
+            query_type: QueryType::CountDistinct,
+            entity: self.table.clone(),
+            filters: self.filters(node, Some(neighbor_type)),
+            time_window: self.time_window(window),
+            aggregations: vec![Aggregation {
+                agg_type: AggregationType::CountDistinct,
+                field: Some("dst_id".to_string()),
+                output_name: "count_distinct".to_string(),
+            }],
+            group_by: vec![],
+            having: Vec::new(),
+            order_by: Vec::new(),
+            limit: None,
+            cache_ttl: None,
+        };
+
+        let result = self.client.query(query).await?;
+        let count = match result
+            .rows
+            .first()
+            .and_then(|row| row.get("count_distinct"))
+        {
+            Some(Value::Number(n)) => *n as u64,
+            _ => 0,
+        };
+        Ok(count)
+    }
+}
+
+#[derive(Debug, Clone)]
+struct StoredLink {
+    from: GraphNode,
+    to: GraphNode,
+    at: DateTime<Utc>,
+}
+
+/// In-memory graph store, for tests and local development
+#[derive(Debug, Default)]
+pub struct InMemoryGraphStore {
+    links: RwLock<Vec<StoredLink>>,
+}
+
+impl InMemoryGraphStore {
+    /// Create an empty store
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Link two nodes now
+    pub fn link(&self, a: GraphNode, b: GraphNode) {
+        self.link_at(a, b, Utc::now());
+    }
+
+    /// Link two nodes at a given time (links are undirected)
+    pub fn link_at(&self, a: GraphNode, b: GraphNode, at: DateTime<Utc>) {
+        let mut links = self.links.write().unwrap();
+        links.push(StoredLink {
+            from: a.clone(),
+            to: b.clone(),
+            at,
+        });
+        links.push(StoredLink { from: b, to: a, at });
+    }
+}
+
+#[async_trait]
+impl GraphStore for InMemoryGraphStore {
+    async fn neighbors(
+        &self,
+        node: &GraphNode,
+        neighbor_type: Option<&str>,
+        window: Option<&RelativeWindow>,
+    ) -> Result<Vec<GraphNode>> {
+        let since = window.map(|w| Utc::now() - chrono::Duration::seconds(w.to_seconds() as i64));
+
+        let mut seen = HashSet::new();
+        let neighbors = self
+            .links
+            .read()
+            .unwrap()
+            .iter()
+            .filter(|link| link.from == *node)
+            .filter(|link| neighbor_type.is_none_or(|t| link.to.node_type == t))
+            .filter(|link| since.is_none_or(|since| link.at >= since))
+            .map(|link| link.to.clone())
+            .filter(|neighbor| seen.insert(neighbor.clone()))
+            .collect();
+        Ok(neighbors)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::datasource::TimeUnit;
+
+    #[tokio::test]
+    async fn test_in_memory_graph_counts() {
+        let store = InMemoryGraphStore::new();
+        let device = GraphNode::new("device_id", "dev_1");
+        let email = GraphNode::new("email", "a@example.com");
+
+        store.link(device.clone(), GraphNode::new("user_id", "u1"));
+        store.link(device.clone(), GraphNode::new("user_id", "u2"));
+        store.link(device.clone(), GraphNode::new("user_id", "u2"));
+        store.link(device.clone(), GraphNode::new("ip", "10.0.0.1"));
+        store.link_at(
+            device.clone(),
+            GraphNode::new("user_id", "u_old"),
+            Utc::now() - chrono::Duration::days(45),
+        );
+        store.link(email.clone(), GraphNode::new("user_id", "u1"));
+        store.link(email.clone(), GraphNode::new("user_id", "u3"));
+
+        let last_30d = RelativeWindow {
+            value: 30,
+            unit: TimeUnit::Days,
+        };
+
+        assert_eq!(
+            store.linked_count(&device, "user_id", None).await.unwrap(),
+            3
+        );
+        assert_eq!(
+            store
+                .linked_count(&device, "user_id", Some(&last_30d))
+                .await
+                .unwrap(),
+            2
+        );
+        assert_eq!(store.degree(&device, Some(&last_30d)).await.unwrap(), 3);
+        assert_eq!(store.degree(&email, None).await.unwrap(), 2);
+
+        // Users are linked in both directions
+        let u1 = GraphNode::new("user_id", "u1");
+        assert_eq!(store.degree(&u1, None).await.unwrap(), 2);
+
+        // The device and the email share user u1
+        assert_eq!(
+            store
+                .shared_count(&device, &email, "user_id", None)
+                .await
+                .unwrap(),
+            1
+        );
+    }
+}
@@ -6,19 +6,35 @@
 //! - Feature definitions and registry
 //! - Feature execution engine with caching
 //! - Pipeline integration
+//! - Offline backfill over historical events
 
 mod cache;
 mod expression;
 
+pub mod backfill;
+pub mod counter;
 pub mod definition;
 pub mod executor;
 pub mod extractor;
+pub mod geo;
+pub mod graph;
 pub mod operator;
 pub mod registry;
 
-pub use definition::{FeatureDefinition, FeatureType};
-pub use executor::FeatureExecutor;
+pub use backfill::{
+    BackfillConfig, BackfillFormat, BackfillRow, BackfillSink, BackfillSummary, FeatureBackfill,
+    FeatureStoreBackfillSink, FileBackfillSink,
+};
+pub use counter::{CounterBuckets, CounterStore, InMemoryCounterStore};
+#[cfg(feature = "redis")]
+pub use counter::RedisCounterStore;
+pub use definition::{FeatureDefinition, FeatureType, MissingPolicy};
+pub use executor::{
+    FeatureChanges, FeatureExecutor, FeatureFetch, FetchLimits, FetchStatus, MissingFeatureError,
+};
 pub use extractor::FeatureExtractor;
+pub use geo::{haversine_km, velocity_kmh, GeoPoint};
+pub use graph::{GraphNode, GraphStore, InMemoryGraphStore, SqlAdjacencyGraphStore};
 pub use operator::{
     CacheBackend, CacheConfig, FilterConfig, FilterOp, Operator, OperatorParams, WindowConfig,
     WindowUnit,
@@ -5,11 +5,13 @@
 //! **Note:** In DSL v0.2, the concept of "operators" has been renamed to "methods" and integrated into a type-based
 //! feature system (see `definition.rs`). This module will be removed once all code is migrated.
 
+use super::geo::{velocity_kmh, GeoPoint};
 use crate::datasource::{
-    Aggregation, AggregationType, DataSourceClient, Filter, FilterOperator, Query, QueryType,
-    TimeUnit, TimeWindow, TimeWindowType,
+    Aggregation, AggregationType, DataSourceClient, Filter, FilterOperator, OrderBy, Query,
+    QueryType, TimeUnit, TimeWindow, TimeWindowType,
 };
 use crate::error::{Result, RuntimeError};
+use chrono::{DateTime, NaiveDateTime, Utc};
 use corint_core::Value;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -36,6 +38,27 @@ pub enum Operator {
     /// Count distinct values
     CountDistinct(CountDistinctOperator),
 
+    /// Nth percentile of numeric field values
+    Percentile(PercentileOperator),
+
+    /// Population standard deviation of numeric field values
+    Stddev(StddevOperator),
+
+    /// Median of numeric field values
+    Median(MedianOperator),
+
+    /// Most frequent field value
+    Mode(ModeOperator),
+
+    /// Distinct values per event (e.g., devices per login)
+    CountDistinctRatio(CountDistinctRatioOperator),
+
+    /// Shannon entropy of a field's value distribution
+    Entropy(EntropyOperator),
+
+    /// Travel speed between the latest located events (impossible travel)
+    GeoVelocity(GeoVelocityOperator),
+
     /// Cross-dimension count (e.g., devices per IP)
     CrossDimensionCount(CrossDimensionCountOperator),
 
@@ -197,7 +220,10 @@ impl CountOperator {
             time_window: build_time_window(&self.params)?,
             aggregations: vec![],
             group_by: vec![],
+            having: Vec::new(),
+            order_by: Vec::new(),
             limit: None,
+            cache_ttl: None,
         };
 
         let result = datasource.query(query).await?;
@@ -245,7 +271,10 @@ impl SumOperator {
                 output_name: "sum".to_string(),
             }],
             group_by: vec![],
+            having: Vec::new(),
+            order_by: Vec::new(),
             limit: None,
+            cache_ttl: None,
         };
 
         let result = datasource.query(query).await?;
@@ -292,7 +321,10 @@ impl AvgOperator {
                 output_name: "avg".to_string(),
             }],
             group_by: vec![],
+            having: Vec::new(),
+            order_by: Vec::new(),
             limit: None,
+            cache_ttl: None,
         };
 
         let result = datasource.query(query).await?;
@@ -339,7 +371,10 @@ impl MaxOperator {
                 output_name: "max".to_string(),
             }],
             group_by: vec![],
+            having: Vec::new(),
+            order_by: Vec::new(),
             limit: None,
+            cache_ttl: None,
         };
 
         let result = datasource.query(query).await?;
@@ -386,7 +421,10 @@ impl MinOperator {
                 output_name: "min".to_string(),
             }],
             group_by: vec![],
+            having: Vec::new(),
+            order_by: Vec::new(),
             limit: None,
+            cache_ttl: None,
         };
 
         let result = datasource.query(query).await?;
@@ -433,7 +471,10 @@ impl CountDistinctOperator {
                 output_name: "count_distinct".to_string(),
             }],
             group_by: vec![],
+            having: Vec::new(),
+            order_by: Vec::new(),
             limit: None,
+            cache_ttl: None,
         };
 
         let result = datasource.query(query).await?;
@@ -448,6 +489,306 @@ impl CountDistinctOperator {
     }
 }
 
+// ============================================================================
+// Percentile Operator
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PercentileOperator {
+    #[serde(flatten)]
+    pub params: OperatorParams,
+
+    /// Field to compute the percentile of
+    pub field: String,
+
+    /// Percentile between 0 and 100 (e.g., 95 for p95)
+    #[serde(default = "default_percentile")]
+    pub percentile: u8,
+}
+
+fn default_percentile() -> u8 {
+    50
+}
+
+impl PercentileOperator {
+    pub async fn execute(
+        &self,
+        datasource: &DataSourceClient,
+        context: &HashMap<String, Value>,
+    ) -> Result<Value> {
+        if self.percentile > 100 {
+            return Err(RuntimeError::InvalidValue(format!(
+                "Percentile must be between 0 and 100, got {}",
+                self.percentile
+            )));
+        }
+
+        execute_field_aggregation(
+            &self.params,
+            AggregationType::Percentile { p: self.percentile },
+            &self.field,
+            datasource,
+            context,
+        )
+        .await
+    }
+}
+
+// ============================================================================
+// Stddev Operator
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StddevOperator {
+    #[serde(flatten)]
+    pub params: OperatorParams,
+
+    /// Field to compute the standard deviation of
+    pub field: String,
+}
+
+impl StddevOperator {
+    pub async fn execute(
+        &self,
+        datasource: &DataSourceClient,
+        context: &HashMap<String, Value>,
+    ) -> Result<Value> {
+        execute_field_aggregation(
+            &self.params,
+            AggregationType::Stddev,
+            &self.field,
+            datasource,
+            context,
+        )
+        .await
+    }
+}
+
+// ============================================================================
+// Median Operator
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MedianOperator {
+    #[serde(flatten)]
+    pub params: OperatorParams,
+
+    /// Field to compute the median of
+    pub field: String,
+}
+
+impl MedianOperator {
+    pub async fn execute(
+        &self,
+        datasource: &DataSourceClient,
+        context: &HashMap<String, Value>,
+    ) -> Result<Value> {
+        execute_field_aggregation(
+            &self.params,
+            AggregationType::Median,
+            &self.field,
+            datasource,
+            context,
+        )
+        .await
+    }
+}
+
+// ============================================================================
+// Mode Operator
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModeOperator {
+    #[serde(flatten)]
+    pub params: OperatorParams,
+
+    /// Field to find the most frequent value of
+    pub field: String,
+}
+
+impl ModeOperator {
+    pub async fn execute(
+        &self,
+        datasource: &DataSourceClient,
+        context: &HashMap<String, Value>,
+    ) -> Result<Value> {
+        execute_field_aggregation(
+            &self.params,
+            AggregationType::Mode,
+            &self.field,
+            datasource,
+            context,
+        )
+        .await
+    }
+}
+
+// ============================================================================
+// CountDistinctRatio Operator
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CountDistinctRatioOperator {
+    #[serde(flatten)]
+    pub params: OperatorParams,
+
+    /// Field to count distinct values of
+    pub distinct_field: String,
+}
+
+impl CountDistinctRatioOperator {
+    /// Distinct values divided by matching events, 0 without events
+    pub async fn execute(
+        &self,
+        datasource: &DataSourceClient,
+        context: &HashMap<String, Value>,
+    ) -> Result<Value> {
+        let dimension_value = resolve_template(&self.params.dimension_value, context)?;
+
+        let query = Query {
+            query_type: QueryType::Aggregate,
+            entity: self.params.entity.clone(),
+            filters: build_filters(&self.params, &dimension_value, context)?,
+            time_window: build_time_window(&self.params)?,
+            aggregations: vec![
+                Aggregation {
+                    agg_type: AggregationType::CountDistinct,
+                    field: Some(self.distinct_field.clone()),
+                    output_name: "count_distinct".to_string(),
+                },
+                Aggregation {
+                    agg_type: AggregationType::Count,
+                    field: None,
+                    output_name: "count".to_string(),
+                },
+            ],
+            group_by: vec![],
+            having: Vec::new(),
+            order_by: Vec::new(),
+            limit: None,
+            cache_ttl: None,
+        };
+
+        let result = datasource.query(query).await?;
+
+        if let Some(row) = result.rows.first() {
+            if let (Some(Value::Number(distinct)), Some(Value::Number(total))) =
+                (row.get("count_distinct"), row.get("count"))
+            {
+                if *total > 0.0 {
+                    return Ok(Value::Number(distinct / total));
+                }
+            }
+        }
+
+        Ok(Value::Number(0.0))
+    }
+}
+
+// ============================================================================
+// Entropy Operator
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EntropyOperator {
+    #[serde(flatten)]
+    pub params: OperatorParams,
+
+    /// Field whose value distribution is measured
+    pub field: String,
+}
+
+impl EntropyOperator {
+    /// Entropy in bits over the events per value; 0 for a single value
+    pub async fn execute(
+        &self,
+        datasource: &DataSourceClient,
+        context: &HashMap<String, Value>,
+    ) -> Result<Value> {
+        let dimension_value = resolve_template(&self.params.dimension_value, context)?;
+
+        let query = Query {
+            query_type: QueryType::Aggregate,
+            entity: self.params.entity.clone(),
+            filters: build_filters(&self.params, &dimension_value, context)?,
+            time_window: build_time_window(&self.params)?,
+            aggregations: vec![Aggregation {
+                agg_type: AggregationType::Count,
+                field: None,
+                output_name: "count".to_string(),
+            }],
+            group_by: vec![self.field.clone()],
+            having: Vec::new(),
+            order_by: Vec::new(),
+            limit: None,
+            cache_ttl: None,
+        };
+
+        let result = datasource.query(query).await?;
+
+        Ok(Value::Number(grouped_entropy(&result.rows)))
+    }
+}
+
+/// Shannon entropy in bits of grouped counts
+///
+/// Each row holds a `count` and the group key; groups with a null key are
+/// left out.
+pub(crate) fn grouped_entropy(rows: &[HashMap<String, Value>]) -> f64 {
+    let counts: Vec<f64> = rows
+        .iter()
+        .filter(|row| {
+            row.iter()
+                .any(|(column, value)| column != "count" && *value != Value::Null)
+        })
+        .filter_map(|row| match row.get("count") {
+            Some(Value::Number(n)) if *n > 0.0 => Some(*n),
+            _ => None,
+        })
+        .collect();
+
+    let total: f64 = counts.iter().sum();
+    counts.iter().map(|n| n / total * (total / n).log2()).sum()
+}
+
+/// Run a single aggregation over a field; null when there is no result
+async fn execute_field_aggregation(
+    params: &OperatorParams,
+    agg_type: AggregationType,
+    field: &str,
+    datasource: &DataSourceClient,
+    context: &HashMap<String, Value>,
+) -> Result<Value> {
+    let dimension_value = resolve_template(&params.dimension_value, context)?;
+
+    let query = Query {
+        query_type: QueryType::Aggregate,
+        entity: params.entity.clone(),
+        filters: build_filters(params, &dimension_value, context)?,
+        time_window: build_time_window(params)?,
+        aggregations: vec![Aggregation {
+            agg_type,
+            field: Some(field.to_string()),
+            output_name: "value".to_string(),
+        }],
+        group_by: vec![],
+        having: Vec::new(),
+        order_by: Vec::new(),
+        limit: None,
+        cache_ttl: None,
+    };
+
+    let result = datasource.query(query).await?;
+
+    Ok(result
+        .rows
+        .first()
+        .and_then(|row| row.get("value"))
+        .cloned()
+        .unwrap_or(Value::Null))
+}
+
 // ============================================================================
 // CrossDimensionCount Operator
 // ============================================================================
@@ -518,7 +859,10 @@ impl CrossDimensionCountOperator {
                 output_name: "count".to_string(),
             }],
             group_by: vec![],
+            having: Vec::new(),
+            order_by: Vec::new(),
             limit: None,
+            cache_ttl: None,
         };
 
         let result = datasource.query(query).await?;
@@ -567,7 +911,10 @@ impl FirstSeenOperator {
                 output_name: "first_seen".to_string(),
             }],
             group_by: vec![],
+            having: Vec::new(),
+            order_by: Vec::new(),
             limit: None,
+            cache_ttl: None,
         };
 
         let result = datasource.query(query).await?;
@@ -629,7 +976,10 @@ impl LastSeenOperator {
                 output_name: "last_seen".to_string(),
             }],
             group_by: vec![],
+            having: Vec::new(),
+            order_by: Vec::new(),
             limit: None,
+            cache_ttl: None,
         };
 
         let result = datasource.query(query).await?;
@@ -701,7 +1051,10 @@ impl TimeSinceOperator {
                 output_name: "first_timestamp".to_string(),
             }],
             group_by: vec![],
+            having: Vec::new(),
+            order_by: Vec::new(),
             limit: None,
+            cache_ttl: None,
         };
 
         let result = datasource.query(query).await?;
@@ -709,44 +1062,12 @@ impl TimeSinceOperator {
         if let Some(row) = result.rows.first() {
             if let Some(Value::String(timestamp_str)) = row.get("first_timestamp") {
                 // Parse the timestamp and calculate elapsed time
-                use chrono::{DateTime, NaiveDateTime, Utc};
-
-                // Try parsing as ISO 8601 with timezone first
-                let first_time = if let Ok(dt) = timestamp_str.parse::<DateTime<Utc>>() {
-                    dt
-                } else if let Ok(dt) = DateTime::parse_from_str(timestamp_str, "%Y-%m-%d %H:%M:%S%.f%:z") {
-                    // PostgreSQL timestamptz: "YYYY-MM-DD HH:MM:SS.mmm+00:00"
-                    dt.with_timezone(&Utc)
-                } else if let Ok(dt) = DateTime::parse_from_str(timestamp_str, "%Y-%m-%d %H:%M:%S%.f%z") {
-                    // PostgreSQL timestamptz: "YYYY-MM-DD HH:MM:SS.mmm+00"
-                    dt.with_timezone(&Utc)
-                } else if let Ok(dt) = DateTime::parse_from_str(timestamp_str, "%Y-%m-%d %H:%M:%S%:z") {
-                    // PostgreSQL timestamptz: "YYYY-MM-DD HH:MM:SS+00:00"
-                    dt.with_timezone(&Utc)
-                } else if let Ok(dt) = DateTime::parse_from_str(timestamp_str, "%Y-%m-%d %H:%M:%S%z") {
-                    // PostgreSQL timestamptz: "YYYY-MM-DD HH:MM:SS+00"
-                    dt.with_timezone(&Utc)
-                } else if let Ok(naive_dt) = NaiveDateTime::parse_from_str(timestamp_str, "%Y-%m-%d %H:%M:%S%.f") {
-                    // ClickHouse DateTime64 format: "YYYY-MM-DD HH:MM:SS.mmm" (no timezone)
-                    // Also matches SQLite format: "YYYY-MM-DD HH:MM:SS"
-                    // Assume UTC timezone
-                    DateTime::from_naive_utc_and_offset(naive_dt, Utc)
-                } else if let Ok(naive_dt) = NaiveDateTime::parse_from_str(timestamp_str, "%Y-%m-%d %H:%M:%S") {
-                    // SQLite returns format: "YYYY-MM-DD HH:MM:SS" (no timezone)
-                    // Assume UTC timezone
-                    DateTime::from_naive_utc_and_offset(naive_dt, Utc)
-                } else if let Ok(naive_dt) = NaiveDateTime::parse_from_str(timestamp_str, "%Y-%m-%dT%H:%M:%S%.f") {
-                    // ISO format with milliseconds but no timezone
-                    DateTime::from_naive_utc_and_offset(naive_dt, Utc)
-                } else if let Ok(naive_dt) = NaiveDateTime::parse_from_str(timestamp_str, "%Y-%m-%dT%H:%M:%S") {
-                    // ISO format without timezone
-                    DateTime::from_naive_utc_and_offset(naive_dt, Utc)
-                } else {
-                    return Err(RuntimeError::InvalidValue(format!(
+                let first_time = parse_timestamp_str(timestamp_str).ok_or_else(|| {
+                    RuntimeError::InvalidValue(format!(
                         "Failed to parse timestamp '{}' as DateTime<Utc> or NaiveDateTime",
                         timestamp_str
-                    )));
-                };
+                    ))
+                })?;
 
                 let now = Utc::now();
                 let duration = now.signed_duration_since(first_time);
@@ -773,6 +1094,147 @@ impl TimeSinceOperator {
     }
 }
 
+/// Parse a timestamp as returned by the SQL and OLAP data sources
+fn parse_timestamp_str(timestamp_str: &str) -> Option<DateTime<Utc>> {
+    // Try parsing as ISO 8601 with timezone first
+    if let Ok(dt) = timestamp_str.parse::<DateTime<Utc>>() {
+        Some(dt)
+    } else if let Ok(dt) = DateTime::parse_from_str(timestamp_str, "%Y-%m-%d %H:%M:%S%.f%:z") {
+        // PostgreSQL timestamptz: "YYYY-MM-DD HH:MM:SS.mmm+00:00"
+        Some(dt.with_timezone(&Utc))
+    } else if let Ok(dt) = DateTime::parse_from_str(timestamp_str, "%Y-%m-%d %H:%M:%S%.f%z") {
+        // PostgreSQL timestamptz: "YYYY-MM-DD HH:MM:SS.mmm+00"
+        Some(dt.with_timezone(&Utc))
+    } else if let Ok(dt) = DateTime::parse_from_str(timestamp_str, "%Y-%m-%d %H:%M:%S%:z") {
+        // PostgreSQL timestamptz: "YYYY-MM-DD HH:MM:SS+00:00"
+        Some(dt.with_timezone(&Utc))
+    } else if let Ok(dt) = DateTime::parse_from_str(timestamp_str, "%Y-%m-%d %H:%M:%S%z") {
+        // PostgreSQL timestamptz: "YYYY-MM-DD HH:MM:SS+00"
+        Some(dt.with_timezone(&Utc))
+    } else if let Ok(naive_dt) = NaiveDateTime::parse_from_str(timestamp_str, "%Y-%m-%d %H:%M:%S%.f") {
+        // ClickHouse DateTime64 format: "YYYY-MM-DD HH:MM:SS.mmm" (no timezone)
+        // Also matches SQLite format: "YYYY-MM-DD HH:MM:SS"
+        // Assume UTC timezone
+        Some(DateTime::from_naive_utc_and_offset(naive_dt, Utc))
+    } else if let Ok(naive_dt) = NaiveDateTime::parse_from_str(timestamp_str, "%Y-%m-%d %H:%M:%S") {
+        // SQLite returns format: "YYYY-MM-DD HH:MM:SS" (no timezone)
+        // Assume UTC timezone
+        Some(DateTime::from_naive_utc_and_offset(naive_dt, Utc))
+    } else if let Ok(naive_dt) = NaiveDateTime::parse_from_str(timestamp_str, "%Y-%m-%dT%H:%M:%S%.f") {
+        // ISO format with milliseconds but no timezone
+        Some(DateTime::from_naive_utc_and_offset(naive_dt, Utc))
+    } else if let Ok(naive_dt) = NaiveDateTime::parse_from_str(timestamp_str, "%Y-%m-%dT%H:%M:%S") {
+        // ISO format without timezone
+        Some(DateTime::from_naive_utc_and_offset(naive_dt, Utc))
+    } else {
+        None
+    }
+}
+
+// ============================================================================
+// GeoVelocity Operator
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GeoVelocityOperator {
+    #[serde(flatten)]
+    pub params: OperatorParams,
+
+    /// Stored latitude field
+    pub latitude_field: String,
+
+    /// Stored longitude field
+    pub longitude_field: String,
+
+    /// Timestamp field name (defaults to "event_timestamp")
+    #[serde(default = "default_timestamp_field")]
+    pub timestamp_field: String,
+
+    /// Latitude of the current event (supports template like "${event.latitude}");
+    /// without it the two latest stored events are compared
+    #[serde(default)]
+    pub current_latitude: Option<String>,
+
+    /// Longitude of the current event
+    #[serde(default)]
+    pub current_longitude: Option<String>,
+}
+
+impl GeoVelocityOperator {
+    /// Speed in km/h from the previous located event; null without one
+    pub async fn execute(
+        &self,
+        datasource: &DataSourceClient,
+        context: &HashMap<String, Value>,
+    ) -> Result<Value> {
+        let current = match (&self.current_latitude, &self.current_longitude) {
+            (Some(lat), Some(lon)) => {
+                let lat = Value::String(resolve_template(lat, context)?);
+                let lon = Value::String(resolve_template(lon, context)?);
+                match GeoPoint::from_values(&lat, &lon) {
+                    Some(point) => Some((point, Utc::now().timestamp())),
+                    // The current event has no usable location
+                    None => return Ok(Value::Null),
+                }
+            }
+            (None, None) => None,
+            _ => {
+                return Err(RuntimeError::InvalidValue(
+                    "current_latitude and current_longitude must be set together".to_string(),
+                ))
+            }
+        };
+
+        let dimension_value = resolve_template(&self.params.dimension_value, context)?;
+
+        // Latest distinct (time, place) rows of the dimension
+        let query = Query {
+            query_type: QueryType::Aggregate,
+            entity: self.params.entity.clone(),
+            filters: build_filters(&self.params, &dimension_value, context)?,
+            time_window: build_time_window(&self.params)?,
+            aggregations: vec![Aggregation {
+                agg_type: AggregationType::Count,
+                field: None,
+                output_name: "count".to_string(),
+            }],
+            group_by: vec![
+                self.timestamp_field.clone(),
+                self.latitude_field.clone(),
+                self.longitude_field.clone(),
+            ],
+            having: Vec::new(),
+            order_by: vec![OrderBy::desc(self.timestamp_field.clone())],
+            limit: Some(if current.is_some() { 1 } else { 2 }),
+            cache_ttl: None,
+        };
+
+        let result = datasource.query(query).await?;
+
+        let mut points = current.into_iter().chain(result.rows.iter().filter_map(|row| {
+            let timestamp = match row.get(&self.timestamp_field)? {
+                // Anything past the year 5000 in seconds is taken as milliseconds
+                Value::Number(n) if *n > 1e11 => (*n / 1000.0) as i64,
+                Value::Number(n) => *n as i64,
+                Value::String(s) => parse_timestamp_str(s)?.timestamp(),
+                _ => return None,
+            };
+            let point = GeoPoint::from_values(
+                row.get(&self.latitude_field)?,
+                row.get(&self.longitude_field)?,
+            )?;
+            Some((point, timestamp))
+        }));
+
+        match (points.next(), points.next()) {
+            (Some((to, to_ts)), Some((from, from_ts))) => {
+                Ok(Value::Number(velocity_kmh(from, from_ts, to, to_ts)))
+            }
+            _ => Ok(Value::Null),
+        }
+    }
+}
+
 // ============================================================================
 // FeatureStoreLookup Operator
 // ============================================================================
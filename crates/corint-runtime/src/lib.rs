@@ -3,38 +3,138 @@
 //! This crate provides the runtime execution engine that executes
 //! compiled IR programs.
 
+#[cfg(feature = "native")]
+pub mod circuit_breaker;
+#[cfg(feature = "native")]
+pub mod clock;
+#[cfg(feature = "native")]
+pub mod concurrency;
 pub mod context;
+#[cfg(feature = "native")]
+pub mod custom_step;
+#[cfg(feature = "native")]
 pub mod datasource;
+#[cfg(feature = "native")]
+pub mod decision_replay;
+#[cfg(feature = "native")]
+pub mod emit;
+#[cfg(feature = "native")]
 pub mod engine;
+#[cfg(feature = "native")]
+pub mod entity;
 pub mod error;
 pub mod executor;
+#[cfg(feature = "native")]
 pub mod external_api;
+#[cfg(feature = "native")]
 pub mod feature;
+#[cfg(feature = "native")]
 pub mod lists;
+#[cfg(feature = "native")]
+pub mod memo;
+#[cfg(feature = "native")]
 pub mod observability;
+#[cfg(feature = "native")]
+pub mod rate_limit;
+#[cfg(feature = "native")]
+pub mod replay;
 pub mod result;
+#[cfg(feature = "native")]
+pub mod retry;
+#[cfg(feature = "native")]
+pub mod schedule;
+#[cfg(feature = "native")]
 pub mod service;
+#[cfg(feature = "native")]
 pub mod storage;
+#[cfg(feature = "native")]
+pub mod tenant;
 pub mod validation;
 
 // Re-export main types
+#[cfg(feature = "native")]
+pub use circuit_breaker::{CircuitBreaker, CircuitBreakerConfig, CircuitBreakerStats, CircuitState};
+#[cfg(feature = "native")]
+pub use clock::{Clock, EffectiveStatus, FixedClock, SystemClock};
+#[cfg(feature = "native")]
+pub use concurrency::{ConcurrencyLimit, ConcurrencyLimiter, ConcurrencyStats};
 pub use context::{ContextInput, ExecutionContext};
+#[cfg(feature = "native")]
+pub use custom_step::{CustomCall, CustomStepHandler};
+#[cfg(feature = "native")]
 pub use datasource::{
     Aggregation, AggregationType, DataSourceClient, DataSourceConfig, DataSourceType, Filter,
     FilterOperator, Query, QueryResult, QueryType, RelativeWindow, TimeUnit, TimeWindow,
     TimeWindowType,
 };
-pub use engine::PipelineExecutor;
-pub use error::{Result, RuntimeError};
+#[cfg(feature = "native")]
+pub use decision_replay::{CapturedCall, CapturedInputs, CapturedLookup, InputCapture};
+#[cfg(feature = "native")]
+pub use emit::{
+    DeadLetter, EmitDispatcher, EmitSink, EmitStats, Emission, MqSink, QueuedEmit, WebhookSink,
+};
+#[cfg(feature = "native")]
+pub use engine::{
+    ExecutionBudget, ExecutionLimits, ExecutionObserver, InstructionEvent, NullSemantics,
+    PipelineExecutor, ProgramSection, StepEvent, TimeoutEvent, TimeoutScope,
+};
+#[cfg(feature = "native")]
+pub use entity::{EntityDefinition, EntityRegistry, Normalization};
+pub use error::{ErrorCategory, Result, RuntimeError};
 pub use executor::Executor;
+#[cfg(feature = "native")]
 pub use external_api::{ApiAuth, ApiConfig, ApiEndpoint, ApiResponse, ExternalApiClient, HttpMethod};
+#[cfg(feature = "native")]
 pub use feature::FeatureExtractor;
-pub use lists::{ListBackend, ListService, MemoryBackend};
-pub use observability::{Metrics, MetricsCollector};
+#[cfg(feature = "native")]
+pub use lists::{ListBackend, ListHit, ListLookupScope, ListService, MemoryBackend};
+#[cfg(feature = "native")]
+pub use memo::{MemoKey, MemoStats, RequestMemo};
+#[cfg(feature = "native")]
+pub use observability::{
+    ExecutionProfile, Metrics, MetricsCollector, ProfileEntry, ProfileReport, ProfileScope,
+};
+#[cfg(feature = "native")]
+pub use rate_limit::{RateLimit, RateLimitBehavior, RateLimitStats, RateLimiter};
+#[cfg(feature = "native")]
+pub use replay::{RecordReplayConfig, RecordReplayMode, Recording, ReplaySession};
+pub use result::{DecisionResult, ExecutionResult};
+#[cfg(feature = "native")]
+pub use result::{
+    rule_precision, summarize_experiment, ConclusionTrace, ConditionTrace, DecisionExporter,
+    DecisionLogSource, DecisionRecord, DecisionResultWriter, ErrorTrace, ExecutionTrace,
+    ExperimentRecord, ExperimentStore, ExperimentSummary, ExportCheckpoint, ExportConfig,
+    ExportFormat, ExportSummary, FeatureTrace, InMemoryDecisionLog,
+    InMemoryExperimentStore, InMemoryOutcomeStore, LabeledDecision, ListTrace, PersistedDecision,
+    MaskingPolicy, MaskingRule, MaskingStrategy, OutcomeLabel, OutcomeQuery, OutcomeRecord,
+    OutcomeStore, PipelineTrace, PurgeSummary, RetentionConfig, ReviewQueueQuery,
+    RuleExecutionRecord, RulePrecision, RuleTrace, RulesetTrace, SampledDecision, SamplingConfig,
+    SamplingRule, StepTrace, VariantDecision,
+};
+#[cfg(feature = "sqlx")]
 pub use result::{
-    ConclusionTrace, ConditionTrace, DecisionRecord, DecisionResult, DecisionResultWriter,
-    ExecutionResult, ExecutionTrace, PipelineTrace, RuleExecutionRecord, RuleTrace, RulesetTrace,
-    StepTrace,
+    DecisionRetention, DecisionReviewQueue, PostgresDecisionLog, PostgresExperimentStore,
+    PostgresOutcomeStore,
 };
+#[cfg(feature = "native")]
+pub use retry::{RetryCondition, RetryPolicy};
+#[cfg(feature = "native")]
+pub use schedule::{CronSchedule, ScheduleRunSummary};
+#[cfg(feature = "native")]
 pub use service::{ServiceClient, ServiceRequest, ServiceResponse};
-pub use storage::{Event, EventFilter, InMemoryStorage, Storage, TimeRange};
+#[cfg(feature = "native")]
+pub use storage::{
+    Event, EventFilter, EventIngestor, EventStream, InMemoryRetention, InMemoryStorage,
+    IngestConfig, IngestStats, Storage, StreamMessage, StreamOffset, TimeRange,
+};
+#[cfg(feature = "native")]
+pub use tenant::{
+    TenantPermit, TenantQuota, TenantQuotaConfig, TenantQuotas, TenantStats, IDLE_TENANT_TTL,
+    MAX_DEFAULT_TENANTS,
+};
+#[cfg(feature = "clickhouse")]
+pub use storage::{
+    ClickHouseStorage, ClickHouseStorageConfig, EventColumn, EventColumnType, PartitionGranularity,
+};
+#[cfg(feature = "sqlx")]
+pub use storage::{Partitioning, PostgresStorage, PostgresStorageConfig};
@@ -0,0 +1,269 @@
+//! Bloom filter list backend
+
+use super::ListBackend;
+use crate::error::{Result, RuntimeError};
+use crate::lists::bloom::BloomFilter;
+use crate::lists::source::{ListFileOptions, ListSource};
+use corint_core::Value;
+use std::sync::{Arc, Weak};
+use tokio::sync::{Mutex, RwLock};
+use tokio::time::{interval, Duration};
+
+/// Default false-positive rate of Bloom filter lists
+pub const DEFAULT_FALSE_POSITIVE_RATE: f64 = 0.001;
+
+/// Bloom filter list backend
+///
+/// Builds a [`BloomFilter`] from a list file (any source and format of
+/// [`FileBackend`](super::FileBackend)) instead of keeping the entries, for
+/// lists too large to hold in memory, such as breached-credential hashes.
+/// Lookups are approximate: a listed value always matches, other values
+/// match with the configured false-positive rate. With a fallback backend,
+/// positives are confirmed by an exact lookup there, so only values that
+/// pass the filter reach the slower backend.
+///
+/// The list is read-only; with a reload interval a changed file is rebuilt
+/// in the background and swapped in.
+pub struct BloomBackend {
+    /// Source, parsing options and filter, shared with the reload task
+    state: Arc<BloomListState>,
+
+    /// List ID and backend confirming positives
+    fallback: Option<(String, Box<dyn ListBackend>)>,
+}
+
+struct BloomListState {
+    /// Where the list file is read from
+    source: ListSource,
+
+    /// How entries are parsed
+    options: ListFileOptions,
+
+    /// Target false-positive rate of the filter
+    false_positive_rate: f64,
+
+    /// HTTP client for remote sources
+    client: reqwest::Client,
+
+    /// Filter built from the loaded file
+    filter: RwLock<Arc<BloomFilter>>,
+
+    /// Version of the loaded file; also serializes concurrent loads
+    version: Mutex<Option<String>>,
+}
+
+impl BloomBackend {
+    /// Create a Bloom filter backend for a list file
+    pub fn from_source(
+        source: ListSource,
+        options: ListFileOptions,
+        false_positive_rate: f64,
+    ) -> Self {
+        Self {
+            state: Arc::new(BloomListState {
+                source,
+                options,
+                false_positive_rate,
+                client: reqwest::Client::new(),
+                filter: RwLock::new(Arc::new(BloomFilter::new(0, false_positive_rate))),
+                version: Mutex::new(None),
+            }),
+            fallback: None,
+        }
+    }
+
+    /// Confirm positives with an exact lookup of list `list_id` in `backend`
+    pub fn with_fallback(
+        mut self,
+        list_id: impl Into<String>,
+        backend: Box<dyn ListBackend>,
+    ) -> Self {
+        self.fallback = Some((list_id.into(), backend));
+        self
+    }
+
+    /// Check the source for changes every `reload_interval_secs` seconds
+    ///
+    /// The background task stops when the backend is dropped.
+    pub fn with_reload(self, reload_interval_secs: u64) -> Self {
+        let state: Weak<BloomListState> = Arc::downgrade(&self.state);
+        tokio::spawn(async move {
+            let mut interval_timer = interval(Duration::from_secs(reload_interval_secs.max(1)));
+            // The first tick completes immediately; the initial load is explicit
+            interval_timer.tick().await;
+            loop {
+                interval_timer.tick().await;
+                let Some(state) = state.upgrade() else {
+                    break;
+                };
+                if let Err(e) = state.refresh(false).await {
+                    tracing::warn!("Failed to reload Bloom filter list {}: {}", state.source, e);
+                }
+            }
+        });
+        self
+    }
+
+    /// Build the filter immediately (called during initialization)
+    pub async fn load(&self) -> Result<()> {
+        self.state.refresh(true).await.map(|_| ())
+    }
+
+    /// Rebuild the filter if the source changed since the last load
+    ///
+    /// Returns whether the filter was replaced.
+    pub async fn refresh(&self) -> Result<bool> {
+        self.state.refresh(false).await
+    }
+
+    /// The current filter
+    pub async fn filter(&self) -> Arc<BloomFilter> {
+        self.state.filter.read().await.clone()
+    }
+}
+
+impl BloomListState {
+    async fn refresh(&self, force: bool) -> Result<bool> {
+        let mut version = self.version.lock().await;
+        let known = if force { None } else { version.as_deref() };
+
+        let Some(fetched) = self.source.fetch(&self.client, known).await? else {
+            return Ok(false);
+        };
+        // Hashing hundreds of millions of entries is CPU-bound; keep it off the runtime
+        let options = self.options.clone();
+        let false_positive_rate = self.false_positive_rate;
+        let data = fetched.data;
+        let filter = tokio::task::spawn_blocking(move || -> Result<BloomFilter> {
+            // Count first so the filter is sized for the file
+            let mut count = 0;
+            options.for_each(&data, |_| count += 1)?;
+            let mut filter = BloomFilter::new(count, false_positive_rate);
+            options.for_each(&data, |value| filter.insert(value))?;
+            Ok(filter)
+        })
+        .await
+        .map_err(|e| {
+            RuntimeError::InvalidOperation(format!("Failed to build Bloom filter: {}", e))
+        })??;
+
+        tracing::debug!(
+            "Built Bloom filter of {} entries ({} bytes) from list file {}",
+            filter.len(),
+            filter.size_bytes(),
+            self.source
+        );
+        *self.filter.write().await = Arc::new(filter);
+        *version = Some(fetched.version);
+        Ok(true)
+    }
+}
+
+#[async_trait::async_trait]
+impl ListBackend for BloomBackend {
+    async fn contains(&self, _list_id: &str, value: &Value) -> Result<bool> {
+        // Convert value to string for matching
+        let search_str = match value {
+            Value::String(s) => s.clone(),
+            Value::Number(n) => n.to_string(),
+            Value::Bool(b) => b.to_string(),
+            Value::Null => {
+                // Null values are never in a list
+                return Ok(false);
+            }
+            _ => return Err(RuntimeError::InvalidValue(format!(
+                "Bloom filter backend only supports string, number, and boolean values, got: {:?}",
+                value
+            ))),
+        };
+
+        if !self.state.filter.read().await.contains(&search_str) {
+            return Ok(false);
+        }
+        match &self.fallback {
+            Some((list_id, backend)) => backend.contains(list_id, value).await,
+            None => Ok(true),
+        }
+    }
+
+    async fn add(&mut self, _list_id: &str, _value: Value) -> Result<()> {
+        Err(RuntimeError::InvalidOperation(
+            "Bloom filter backend is read-only".to_string(),
+        ))
+    }
+
+    async fn remove(&mut self, _list_id: &str, _value: &Value) -> Result<()> {
+        Err(RuntimeError::InvalidOperation(
+            "Bloom filter backend is read-only".to_string(),
+        ))
+    }
+
+    async fn get_all(&self, _list_id: &str) -> Result<Vec<Value>> {
+        // The filter does not keep its entries
+        Err(RuntimeError::InvalidOperation(
+            "Bloom filter backend cannot list its entries".to_string(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lists::MemoryBackend;
+    use std::io::Write;
+    use tempfile::NamedTempFile;
+
+    #[tokio::test]
+    async fn test_bloom_backend() {
+        let mut file = NamedTempFile::new().unwrap();
+        for i in 0..1000 {
+            writeln!(file, "hash-{}", i).unwrap();
+        }
+
+        let source = ListSource::Local(file.path().to_path_buf());
+        let backend = BloomBackend::from_source(source.clone(), ListFileOptions::default(), 0.01);
+        backend.load().await.unwrap();
+        assert_eq!(backend.filter().await.len(), 1000);
+
+        let value = |s: String| Value::String(s);
+        for i in 0..1000 {
+            assert!(backend
+                .contains("breached", &value(format!("hash-{}", i)))
+                .await
+                .unwrap());
+        }
+        let mut false_positives = 0;
+        for i in 0..1000 {
+            false_positives += backend
+                .contains("breached", &value(format!("other-{}", i)))
+                .await
+                .unwrap() as usize;
+        }
+        assert!(false_positives < 30, "{} false positives", false_positives);
+        assert!(backend.get_all("breached").await.is_err());
+
+        // With a fallback only exact matches remain
+        let mut exact = MemoryBackend::new();
+        exact
+            .add("exact", value("hash-1".to_string()))
+            .await
+            .unwrap();
+        let backend = BloomBackend::from_source(source, ListFileOptions::default(), 0.5)
+            .with_fallback("exact", Box::new(exact));
+        backend.load().await.unwrap();
+        assert!(backend
+            .contains("breached", &value("hash-1".to_string()))
+            .await
+            .unwrap());
+        assert!(!backend
+            .contains("breached", &value("hash-2".to_string()))
+            .await
+            .unwrap());
+        for i in 0..1000 {
+            assert!(!backend
+                .contains("breached", &value(format!("other-{}", i)))
+                .await
+                .unwrap());
+        }
+    }
+}
@@ -0,0 +1,327 @@
+//! IP network list backend
+
+use super::{ListBackend, ListEntry};
+use crate::error::{Result, RuntimeError};
+use crate::lists::cidr::{CidrTrie, IpNetwork};
+use chrono::{DateTime, Utc};
+use corint_core::Value;
+use std::net::IpAddr;
+use std::sync::{Arc, Weak};
+use tokio::sync::{Mutex, RwLock};
+use tokio::time::{interval, Duration};
+
+/// IP network list backend
+///
+/// Keeps the entries of another backend (networks, addresses or ranges, see
+/// [`crate::lists::cidr`]) and answers `contains` for an IP address by
+/// longest-prefix match in a radix trie built from them. Entries added or
+/// removed through this backend update the trie at once; with a refresh
+/// interval it is also rebuilt in the background, for entries changed in
+/// the underlying store. The trie is rebuilt when its first entry expires.
+pub struct CidrBackend {
+    /// Underlying entries and their trie, shared with the refresh task
+    state: Arc<CidrListState>,
+}
+
+struct CidrListState {
+    /// List the entries belong to
+    list_id: String,
+
+    /// Backend holding the entries
+    inner: RwLock<Box<dyn ListBackend>>,
+
+    /// Networks of the entries
+    trie: RwLock<CidrSnapshot>,
+
+    /// Serializes rebuilds
+    rebuilding: Mutex<()>,
+}
+
+#[derive(Default)]
+struct CidrSnapshot {
+    trie: CidrTrie,
+
+    /// When the first entry in the trie expires
+    next_expiry: Option<DateTime<Utc>>,
+}
+
+impl CidrSnapshot {
+    fn insert(&mut self, networks: Vec<IpNetwork>, expires_at: Option<DateTime<Utc>>) {
+        for network in networks {
+            self.trie.insert(network);
+        }
+        if let Some(expires_at) = expires_at {
+            self.next_expiry = Some(
+                self.next_expiry
+                    .map_or(expires_at, |next| next.min(expires_at)),
+            );
+        }
+    }
+
+    fn is_stale(&self, now: DateTime<Utc>) -> bool {
+        self.next_expiry.is_some_and(|next| next <= now)
+    }
+}
+
+impl CidrBackend {
+    /// Build the trie for `list_id` from the entries of `inner`
+    pub async fn load(list_id: impl Into<String>, inner: Box<dyn ListBackend>) -> Result<Self> {
+        let state = Arc::new(CidrListState {
+            list_id: list_id.into(),
+            inner: RwLock::new(inner),
+            trie: RwLock::new(CidrSnapshot::default()),
+            rebuilding: Mutex::new(()),
+        });
+        state.rebuild().await?;
+        Ok(Self { state })
+    }
+
+    /// Rebuild the trie from the underlying entries every `refresh_interval_secs` seconds
+    ///
+    /// The background task stops when the backend is dropped.
+    pub fn with_refresh(self, refresh_interval_secs: u64) -> Self {
+        let state: Weak<CidrListState> = Arc::downgrade(&self.state);
+        tokio::spawn(async move {
+            let mut interval_timer = interval(Duration::from_secs(refresh_interval_secs.max(1)));
+            interval_timer.tick().await;
+            loop {
+                interval_timer.tick().await;
+                let Some(state) = state.upgrade() else {
+                    break;
+                };
+                if let Err(e) = state.rebuild().await {
+                    tracing::warn!("Failed to refresh CIDR list '{}': {}", state.list_id, e);
+                }
+            }
+        });
+        self
+    }
+
+    /// Number of networks in the trie
+    pub async fn len(&self) -> usize {
+        self.state.trie.read().await.trie.len()
+    }
+
+    /// Check if the trie is empty
+    pub async fn is_empty(&self) -> bool {
+        self.state.trie.read().await.trie.is_empty()
+    }
+
+    /// Most specific network of the list containing `ip`
+    pub async fn longest_match(&self, ip: IpAddr) -> Result<Option<IpNetwork>> {
+        self.state.rebuild_if_stale().await?;
+        Ok(self.state.trie.read().await.trie.longest_match(ip))
+    }
+
+    /// Parse an entry to add, which must be a network, address or range
+    fn parse_entry(value: &Value) -> Result<Vec<IpNetwork>> {
+        match value {
+            Value::String(s) => IpNetwork::parse_all(s),
+            _ => Err(RuntimeError::InvalidValue(format!(
+                "CIDR list entries must be networks, addresses or ranges, got: {:?}",
+                value
+            ))),
+        }
+    }
+}
+
+impl CidrListState {
+    /// Rebuild the trie if one of its entries expired
+    async fn rebuild_if_stale(&self) -> Result<()> {
+        if !self.trie.read().await.is_stale(Utc::now()) {
+            return Ok(());
+        }
+        let _rebuilding = self.rebuilding.lock().await;
+        if self.trie.read().await.is_stale(Utc::now()) {
+            self.rebuild_locked().await?;
+        }
+        Ok(())
+    }
+
+    /// Replace the trie with one built from the current entries
+    async fn rebuild(&self) -> Result<()> {
+        let _rebuilding = self.rebuilding.lock().await;
+        self.rebuild_locked().await
+    }
+
+    async fn rebuild_locked(&self) -> Result<()> {
+        let entries = self.inner.read().await.get_entries(&self.list_id).await?;
+
+        let mut snapshot = CidrSnapshot::default();
+        let mut invalid = 0;
+        for entry in entries {
+            match CidrBackend::parse_entry(&entry.value) {
+                Ok(networks) => snapshot.insert(networks, entry.expires_at),
+                Err(e) => {
+                    invalid += 1;
+                    tracing::debug!("Skipping entry of CIDR list '{}': {}", self.list_id, e);
+                }
+            }
+        }
+        if invalid > 0 {
+            tracing::warn!(
+                "Skipped {} invalid entries of CIDR list '{}'",
+                invalid,
+                self.list_id
+            );
+        }
+
+        tracing::debug!(
+            "Built CIDR list '{}' with {} networks",
+            self.list_id,
+            snapshot.trie.len()
+        );
+        *self.trie.write().await = snapshot;
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl ListBackend for CidrBackend {
+    async fn contains(&self, _list_id: &str, value: &Value) -> Result<bool> {
+        // Values that are not IP addresses are never in the list
+        let Value::String(s) = value else {
+            return Ok(false);
+        };
+        match s.trim().parse::<IpAddr>() {
+            Ok(ip) => {
+                self.state.rebuild_if_stale().await?;
+                Ok(self.state.trie.read().await.trie.contains(ip))
+            }
+            Err(_) => Ok(false),
+        }
+    }
+
+    async fn add(&mut self, list_id: &str, value: Value) -> Result<()> {
+        let networks = Self::parse_entry(&value)?;
+        self.state.inner.write().await.add(list_id, value).await?;
+
+        self.state.trie.write().await.insert(networks, None);
+        Ok(())
+    }
+
+    async fn remove(&mut self, list_id: &str, value: &Value) -> Result<()> {
+        self.state
+            .inner
+            .write()
+            .await
+            .remove(list_id, value)
+            .await?;
+
+        // Other entries may overlap the removed one, so rebuild from all of them
+        self.state.rebuild().await
+    }
+
+    async fn get_all(&self, list_id: &str) -> Result<Vec<Value>> {
+        self.state.inner.read().await.get_all(list_id).await
+    }
+
+    async fn add_entry(&mut self, list_id: &str, entry: ListEntry) -> Result<()> {
+        let networks = Self::parse_entry(&entry.value)?;
+        let expires_at = entry.expires_at;
+        self.state
+            .inner
+            .write()
+            .await
+            .add_entry(list_id, entry)
+            .await?;
+
+        // The trie is rebuilt from the entries when the earliest expiry passes,
+        // so a replaced entry's expiry needs no special handling
+        self.state.trie.write().await.insert(networks, expires_at);
+        Ok(())
+    }
+
+    async fn get_entries(&self, list_id: &str) -> Result<Vec<ListEntry>> {
+        self.state.inner.read().await.get_entries(list_id).await
+    }
+
+    async fn purge_expired(&mut self, list_id: &str) -> Result<usize> {
+        self.state.inner.write().await.purge_expired(list_id).await
+    }
+
+    fn matches_entry(&self, entry: &Value, value: &Value) -> bool {
+        let (Ok(networks), Value::String(s)) = (Self::parse_entry(entry), value) else {
+            return false;
+        };
+        s.trim()
+            .parse::<IpAddr>()
+            .is_ok_and(|ip| networks.iter().any(|network| network.contains(ip)))
+    }
+
+    fn matches_exactly(&self) -> bool {
+        false
+    }
+
+    fn validate_entry(&self, value: &Value) -> Result<()> {
+        Self::parse_entry(value).map(|_| ())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lists::MemoryBackend;
+
+    fn ip(s: &str) -> Value {
+        Value::String(s.to_string())
+    }
+
+    #[tokio::test]
+    async fn test_cidr_backend() {
+        let mut entries = MemoryBackend::new();
+        entries.add("ips", ip("10.0.0.0/8")).await.unwrap();
+        entries
+            .add("ips", ip("192.0.2.10-192.0.2.20"))
+            .await
+            .unwrap();
+        entries.add("ips", ip("not a network")).await.unwrap();
+
+        let mut backend = CidrBackend::load("ips", Box::new(entries)).await.unwrap();
+        assert_eq!(backend.len().await, 5);
+        assert!(backend.contains("ips", &ip("10.20.30.40")).await.unwrap());
+        assert!(backend.contains("ips", &ip("192.0.2.15")).await.unwrap());
+        assert!(!backend.contains("ips", &ip("192.0.2.21")).await.unwrap());
+        assert!(!backend.contains("ips", &ip("example.com")).await.unwrap());
+        assert!(!backend.contains("ips", &Value::Number(10.0)).await.unwrap());
+
+        backend.add("ips", ip("2001:db8::/32")).await.unwrap();
+        assert!(backend.contains("ips", &ip("2001:db8::42")).await.unwrap());
+        assert!(backend.add("ips", ip("2001:db8::/129")).await.is_err());
+
+        backend.add("ips", ip("10.1.0.0/16")).await.unwrap();
+        backend.remove("ips", &ip("10.0.0.0/8")).await.unwrap();
+        assert!(backend.contains("ips", &ip("10.1.2.3")).await.unwrap());
+        assert!(!backend.contains("ips", &ip("10.2.2.3")).await.unwrap());
+        assert_eq!(backend.get_all("ips").await.unwrap().len(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_cidr_backend_expiry() {
+        let mut backend = CidrBackend::load("ips", Box::new(MemoryBackend::new()))
+            .await
+            .unwrap();
+
+        backend
+            .add_entry(
+                "ips",
+                ListEntry::new(ip("198.51.100.0/24"))
+                    .with_ttl(std::time::Duration::from_secs(3600)),
+            )
+            .await
+            .unwrap();
+        backend
+            .add_entry(
+                "ips",
+                ListEntry::new(ip("203.0.113.0/24"))
+                    .with_expires_at(Utc::now() - chrono::Duration::seconds(1)),
+            )
+            .await
+            .unwrap();
+
+        assert!(backend.contains("ips", &ip("198.51.100.9")).await.unwrap());
+        assert!(!backend.contains("ips", &ip("203.0.113.9")).await.unwrap());
+        assert_eq!(backend.len().await, 1);
+        assert_eq!(backend.purge_expired("ips").await.unwrap(), 1);
+    }
+}
@@ -2,97 +2,143 @@
 
 use super::ListBackend;
 use crate::error::{Result, RuntimeError};
+use crate::lists::source::{ListFileOptions, ListSource};
 use corint_core::Value;
 use std::collections::HashSet;
 use std::path::PathBuf;
-use std::sync::Arc;
-use tokio::fs;
-use tokio::sync::RwLock;
+use std::sync::{Arc, Weak};
+use tokio::sync::{Mutex, RwLock};
 use tokio::time::{interval, Duration};
 
 /// File-based list backend
 ///
-/// Loads list entries from a text file (one entry per line).
-/// Supports automatic reloading at specified intervals.
+/// Loads list entries from a local file, URL or S3 object in text, CSV, JSON
+/// or Parquet format (see [`crate::lists::source`]). With a reload interval
+/// the source is polled in the background and the entries are swapped in
+/// when it changed, so lookups never see a partially loaded list.
 pub struct FileBackend {
-    /// Path to the list file
-    file_path: PathBuf,
-
-    /// Cached entries (list_id -> set of values)
-    entries: Arc<RwLock<HashSet<String>>>,
+    /// Source, parsing options and loaded entries, shared with the reload task
+    state: Arc<FileListState>,
 
     /// Whether automatic reloading is enabled
     #[allow(dead_code)]
     reload_enabled: bool,
 }
 
+struct FileListState {
+    /// Where the list file is read from
+    source: ListSource,
+
+    /// How entries are parsed
+    options: ListFileOptions,
+
+    /// HTTP client for remote sources
+    client: reqwest::Client,
+
+    /// Cached entries (list_id -> set of values)
+    entries: RwLock<HashSet<String>>,
+
+    /// Version of the loaded file; also serializes concurrent loads
+    version: Mutex<Option<String>>,
+}
+
 impl FileBackend {
-    /// Create a new file backend
+    /// Create a new file backend for a local text file
     pub fn new(file_path: PathBuf) -> Self {
+        Self::from_source(ListSource::Local(file_path), ListFileOptions::default())
+    }
+
+    /// Create a new file backend for a local text file with auto-reload
+    pub fn new_with_reload(file_path: PathBuf, reload_interval_secs: u64) -> Self {
+        Self::new(file_path).with_reload(reload_interval_secs)
+    }
+
+    /// Create a new file backend for any source and format
+    pub fn from_source(source: ListSource, options: ListFileOptions) -> Self {
         Self {
-            file_path,
-            entries: Arc::new(RwLock::new(HashSet::new())),
+            state: Arc::new(FileListState {
+                source,
+                options,
+                client: reqwest::Client::new(),
+                entries: RwLock::new(HashSet::new()),
+                version: Mutex::new(None),
+            }),
             reload_enabled: false,
         }
     }
 
-    /// Create a new file backend with auto-reload
-    pub fn new_with_reload(file_path: PathBuf, reload_interval_secs: u64) -> Self {
-        let backend = Self {
-            file_path: file_path.clone(),
-            entries: Arc::new(RwLock::new(HashSet::new())),
-            reload_enabled: true,
-        };
-
-        // Start background reload task
-        let entries = Arc::clone(&backend.entries);
+    /// Check the source for changes every `reload_interval_secs` seconds
+    ///
+    /// The background task stops when the backend is dropped.
+    pub fn with_reload(mut self, reload_interval_secs: u64) -> Self {
+        let state: Weak<FileListState> = Arc::downgrade(&self.state);
         tokio::spawn(async move {
-            let mut interval_timer = interval(Duration::from_secs(reload_interval_secs));
+            let mut interval_timer = interval(Duration::from_secs(reload_interval_secs.max(1)));
+            // The first tick completes immediately; the initial load is explicit
+            interval_timer.tick().await;
             loop {
                 interval_timer.tick().await;
-                if let Ok(loaded) = Self::load_from_file(&file_path).await {
-                    let mut entries_write = entries.write().await;
-                    *entries_write = loaded;
+                let Some(state) = state.upgrade() else {
+                    break;
+                };
+                if let Err(e) = state.refresh(false).await {
+                    tracing::warn!("Failed to reload list file {}: {}", state.source, e);
                 }
             }
         });
 
-        backend
-    }
-
-    /// Load entries from file
-    async fn load_from_file(path: &PathBuf) -> Result<HashSet<String>> {
-        let content = fs::read_to_string(path).await.map_err(|e| {
-            RuntimeError::InvalidOperation(format!("Failed to read list file: {}", e))
-        })?;
-
-        let mut entries = HashSet::new();
-        for line in content.lines() {
-            let trimmed = line.trim();
-            if !trimmed.is_empty() && !trimmed.starts_with('#') {
-                entries.insert(trimmed.to_string());
-            }
-        }
-
-        Ok(entries)
+        self.reload_enabled = true;
+        self
     }
 
     /// Load entries immediately (called during initialization)
     pub async fn load(&self) -> Result<()> {
-        let loaded = Self::load_from_file(&self.file_path).await?;
-        let mut entries = self.entries.write().await;
-        *entries = loaded;
-        Ok(())
+        self.state.refresh(true).await.map(|_| ())
+    }
+
+    /// Reload entries if the source changed since the last load
+    ///
+    /// Returns whether the entries were replaced.
+    pub async fn refresh(&self) -> Result<bool> {
+        self.state.refresh(false).await
     }
 
     /// Get the number of entries
     pub async fn len(&self) -> usize {
-        self.entries.read().await.len()
+        self.state.entries.read().await.len()
     }
 
     /// Check if the list is empty
     pub async fn is_empty(&self) -> bool {
-        self.entries.read().await.is_empty()
+        self.state.entries.read().await.is_empty()
+    }
+}
+
+impl FileListState {
+    async fn refresh(&self, force: bool) -> Result<bool> {
+        let mut version = self.version.lock().await;
+        let known = if force { None } else { version.as_deref() };
+
+        let Some(fetched) = self.source.fetch(&self.client, known).await? else {
+            return Ok(false);
+        };
+        // Parsing millions of entries is CPU-bound; keep it off the runtime
+        let options = self.options.clone();
+        let data = fetched.data;
+        let loaded = tokio::task::spawn_blocking(move || options.parse(&data))
+            .await
+            .map_err(|e| {
+                RuntimeError::InvalidOperation(format!("Failed to parse list file: {}", e))
+            })??;
+
+        tracing::debug!(
+            "Loaded {} entries from list file {}",
+            loaded.len(),
+            self.source
+        );
+        *self.entries.write().await = loaded;
+        *version = Some(fetched.version);
+        Ok(true)
     }
 }
 
@@ -116,7 +162,7 @@ impl ListBackend for FileBackend {
             }
         };
 
-        let entries = self.entries.read().await;
+        let entries = self.state.entries.read().await;
         Ok(entries.contains(&search_str))
     }
 
@@ -136,7 +182,7 @@ impl ListBackend for FileBackend {
     }
 
     async fn get_all(&self, _list_id: &str) -> Result<Vec<Value>> {
-        let entries = self.entries.read().await;
+        let entries = self.state.entries.read().await;
         Ok(entries
             .iter()
             .map(|s| Value::String(s.clone()))
@@ -222,4 +268,36 @@ mod tests {
             .await;
         assert!(result.is_err());
     }
+
+    #[tokio::test]
+    async fn test_file_backend_csv_refresh() {
+        use crate::lists::source::ListFileFormat;
+
+        let mut temp_file = NamedTempFile::new().unwrap();
+        writeln!(temp_file, "ip,reason").unwrap();
+        writeln!(temp_file, "1.2.3.4,botnet").unwrap();
+        temp_file.flush().unwrap();
+
+        let backend = FileBackend::from_source(
+            ListSource::Local(temp_file.path().to_path_buf()),
+            ListFileOptions::new(ListFileFormat::Csv).with_column("ip"),
+        );
+        backend.load().await.unwrap();
+        assert_eq!(backend.len().await, 1);
+
+        // Unchanged file is not reloaded
+        assert!(!backend.refresh().await.unwrap());
+
+        std::fs::write(temp_file.path(), "ip,reason\n1.2.3.4,botnet\n5.6.7.8,tor\n").unwrap();
+        assert!(backend.refresh().await.unwrap());
+        assert!(backend
+            .contains("test", &Value::String("5.6.7.8".to_string()))
+            .await
+            .unwrap());
+
+        // A broken file keeps the entries loaded before
+        std::fs::write(temp_file.path(), "reason\nbotnet\n").unwrap();
+        assert!(backend.refresh().await.is_err());
+        assert_eq!(backend.len().await, 2);
+    }
 }
@@ -2,20 +2,34 @@
 //!
 //! Simple memory-based list storage for testing and development.
 
-use super::ListBackend;
+use super::{ListBackend, ListEntry};
 use crate::error::{Result, RuntimeError};
+use chrono::{DateTime, Utc};
 use corint_core::Value;
-use std::collections::{HashMap, HashSet};
+use std::collections::HashMap;
 use tokio::sync::RwLock;
 
 /// In-memory list backend
 ///
-/// Stores list data in memory using HashSets.
+/// Stores list data in memory using HashMaps.
 /// This is suitable for testing and development, but not for production
 /// as data is lost when the process restarts.
 pub struct MemoryBackend {
-    /// Map of list_id -> set of values
-    lists: RwLock<HashMap<String, HashSet<String>>>,
+    /// Map of list_id -> value -> entry metadata
+    lists: RwLock<HashMap<String, HashMap<String, EntryMetadata>>>,
+}
+
+/// Expiry and reason of a stored value
+#[derive(Debug, Clone, Default)]
+struct EntryMetadata {
+    expires_at: Option<DateTime<Utc>>,
+    reason: Option<String>,
+}
+
+impl EntryMetadata {
+    fn is_live(&self, now: DateTime<Utc>) -> bool {
+        self.expires_at.is_none_or(|expires_at| expires_at > now)
+    }
 }
 
 impl MemoryBackend {
@@ -41,6 +55,28 @@ impl MemoryBackend {
             }
         }
     }
+
+    /// Convert a stored string back to a Value
+    fn key_to_value(s: &str) -> Value {
+        // Try to parse as number first
+        if let Ok(n) = s.parse::<f64>() {
+            return Value::Number(n);
+        }
+        // Try to parse as boolean
+        if let Ok(b) = s.parse::<bool>() {
+            return Value::Bool(b);
+        }
+        // Check for null
+        if s == "null" {
+            return Value::Null;
+        }
+        // Try to parse as JSON (for arrays/objects)
+        if let Ok(v) = serde_json::from_str::<Value>(s) {
+            return v;
+        }
+        // Default to string
+        Value::String(s.to_string())
+    }
 }
 
 impl Default for MemoryBackend {
@@ -56,7 +92,9 @@ impl ListBackend for MemoryBackend {
         let lists = self.lists.read().await;
 
         if let Some(list) = lists.get(list_id) {
-            Ok(list.contains(&key))
+            Ok(list
+                .get(&key)
+                .is_some_and(|entry| entry.is_live(Utc::now())))
         } else {
             Ok(false)
         }
@@ -68,8 +106,9 @@ impl ListBackend for MemoryBackend {
 
         lists
             .entry(list_id.to_string())
-            .or_insert_with(HashSet::new)
-            .insert(key);
+            .or_default()
+            .entry(key)
+            .or_default();
 
         Ok(())
     }
@@ -89,29 +128,12 @@ impl ListBackend for MemoryBackend {
         let lists = self.lists.read().await;
 
         if let Some(list) = lists.get(list_id) {
-            // Convert all strings back to Values
+            // Convert all unexpired strings back to Values
+            let now = Utc::now();
             let values: Vec<Value> = list
                 .iter()
-                .map(|s| {
-                    // Try to parse as number first
-                    if let Ok(n) = s.parse::<f64>() {
-                        return Value::Number(n);
-                    }
-                    // Try to parse as boolean
-                    if let Ok(b) = s.parse::<bool>() {
-                        return Value::Bool(b);
-                    }
-                    // Check for null
-                    if s == "null" {
-                        return Value::Null;
-                    }
-                    // Try to parse as JSON (for arrays/objects)
-                    if let Ok(v) = serde_json::from_str::<Value>(s) {
-                        return v;
-                    }
-                    // Default to string
-                    Value::String(s.clone())
-                })
+                .filter(|(_, entry)| entry.is_live(now))
+                .map(|(s, _)| Self::key_to_value(s))
                 .collect();
 
             Ok(values)
@@ -119,6 +141,50 @@ impl ListBackend for MemoryBackend {
             Ok(Vec::new())
         }
     }
+
+    async fn add_entry(&mut self, list_id: &str, entry: ListEntry) -> Result<()> {
+        let key = Self::value_to_key(&entry.value)?;
+        let mut lists = self.lists.write().await;
+
+        lists.entry(list_id.to_string()).or_default().insert(
+            key,
+            EntryMetadata {
+                expires_at: entry.expires_at,
+                reason: entry.reason,
+            },
+        );
+
+        Ok(())
+    }
+
+    async fn get_entries(&self, list_id: &str) -> Result<Vec<ListEntry>> {
+        let lists = self.lists.read().await;
+        let now = Utc::now();
+
+        Ok(lists
+            .get(list_id)
+            .into_iter()
+            .flatten()
+            .filter(|(_, entry)| entry.is_live(now))
+            .map(|(s, entry)| ListEntry {
+                value: Self::key_to_value(s),
+                expires_at: entry.expires_at,
+                reason: entry.reason.clone(),
+            })
+            .collect())
+    }
+
+    async fn purge_expired(&mut self, list_id: &str) -> Result<usize> {
+        let mut lists = self.lists.write().await;
+        let Some(list) = lists.get_mut(list_id) else {
+            return Ok(0);
+        };
+
+        let now = Utc::now();
+        let before = list.len();
+        list.retain(|_, entry| entry.is_live(now));
+        Ok(before - list.len())
+    }
 }
 
 #[cfg(test)]
@@ -218,4 +284,62 @@ mod tests {
         let values = backend.get_all("test_list").await.unwrap();
         assert_eq!(values.len(), 2);
     }
+
+    #[tokio::test]
+    async fn test_memory_backend_expiry() {
+        let mut backend = MemoryBackend::new();
+        let value = |s: &str| Value::String(s.to_string());
+
+        backend.add("blocked", value("permanent")).await.unwrap();
+        backend
+            .add_entry(
+                "blocked",
+                ListEntry::new(value("temporary"))
+                    .with_ttl(std::time::Duration::from_secs(3600))
+                    .with_reason("chargeback"),
+            )
+            .await
+            .unwrap();
+        backend
+            .add_entry(
+                "blocked",
+                ListEntry::new(value("expired"))
+                    .with_expires_at(Utc::now() - chrono::Duration::seconds(1)),
+            )
+            .await
+            .unwrap();
+
+        assert!(backend
+            .contains("blocked", &value("permanent"))
+            .await
+            .unwrap());
+        assert!(backend
+            .contains("blocked", &value("temporary"))
+            .await
+            .unwrap());
+        assert!(!backend
+            .contains("blocked", &value("expired"))
+            .await
+            .unwrap());
+        assert_eq!(backend.get_all("blocked").await.unwrap().len(), 2);
+
+        let entries = backend.get_entries("blocked").await.unwrap();
+        let temporary = entries
+            .iter()
+            .find(|e| e.value == value("temporary"))
+            .unwrap();
+        assert_eq!(temporary.reason.as_deref(), Some("chargeback"));
+        assert!(temporary.expires_at.is_some());
+
+        assert_eq!(backend.purge_expired("blocked").await.unwrap(), 1);
+        assert_eq!(backend.purge_expired("blocked").await.unwrap(), 0);
+
+        // Re-adding an entry replaces its expiry
+        backend
+            .add_entry("blocked", ListEntry::new(value("temporary")))
+            .await
+            .unwrap();
+        let entries = backend.get_entries("blocked").await.unwrap();
+        assert!(entries.iter().all(|e| e.expires_at.is_none()));
+    }
 }
@@ -2,18 +2,77 @@
 //!
 //! Backends for storing and querying list data.
 
+pub mod bloom;
+pub mod cidr;
 pub mod file;
 mod memory;
+pub mod pattern;
 pub mod postgresql;
 pub mod sqlite;
 
+pub use bloom::BloomBackend;
+pub use cidr::CidrBackend;
 pub use file::FileBackend;
 pub use memory::MemoryBackend;
+pub use pattern::PatternBackend;
 pub use postgresql::PostgresBackend;
 pub use sqlite::SqliteBackend;
 
-use crate::error::Result;
+use super::ListService;
+use crate::error::{Result, RuntimeError};
+use chrono::{DateTime, Utc};
 use corint_core::Value;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// A list value with optional expiry and reason metadata
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ListEntry {
+    /// Listed value
+    pub value: Value,
+
+    /// When the entry stops matching; never when `None`
+    #[serde(default)]
+    pub expires_at: Option<DateTime<Utc>>,
+
+    /// Why the value was listed
+    #[serde(default)]
+    pub reason: Option<String>,
+}
+
+impl ListEntry {
+    /// Create a permanent entry
+    pub fn new(value: Value) -> Self {
+        Self {
+            value,
+            expires_at: None,
+            reason: None,
+        }
+    }
+
+    /// Expire the entry `ttl` from now
+    pub fn with_ttl(self, ttl: Duration) -> Self {
+        let ttl = chrono::Duration::from_std(ttl).unwrap_or(chrono::Duration::MAX);
+        self.with_expires_at(Utc::now().checked_add_signed(ttl).unwrap_or(DateTime::<Utc>::MAX_UTC))
+    }
+
+    /// Expire the entry at `expires_at`
+    pub fn with_expires_at(mut self, expires_at: DateTime<Utc>) -> Self {
+        self.expires_at = Some(expires_at);
+        self
+    }
+
+    /// Record why the value was listed
+    pub fn with_reason(mut self, reason: impl Into<String>) -> Self {
+        self.reason = Some(reason.into());
+        self
+    }
+
+    /// Whether the entry has expired at `now`
+    pub fn is_expired_at(&self, now: DateTime<Utc>) -> bool {
+        self.expires_at.is_some_and(|expires_at| expires_at <= now)
+    }
+}
 
 /// Trait for list storage backends
 #[async_trait::async_trait]
@@ -29,4 +88,47 @@ pub trait ListBackend: Send + Sync {
 
     /// Get all values in a list (for future management API)
     async fn get_all(&self, list_id: &str) -> Result<Vec<Value>>;
+
+    /// Add an entry with its expiry and reason, replacing those of an existing entry
+    ///
+    /// Backends that cannot store the metadata only accept plain entries.
+    async fn add_entry(&mut self, list_id: &str, entry: ListEntry) -> Result<()> {
+        if entry.expires_at.is_some() || entry.reason.is_some() {
+            return Err(RuntimeError::InvalidOperation(
+                "List backend does not store entry expiry or reason".to_string(),
+            ));
+        }
+        self.add(list_id, entry.value).await
+    }
+
+    /// Get the unexpired entries of a list with their metadata
+    async fn get_entries(&self, list_id: &str) -> Result<Vec<ListEntry>> {
+        let values = self.get_all(list_id).await?;
+        Ok(values.into_iter().map(ListEntry::new).collect())
+    }
+
+    /// Delete expired entries, returning how many were deleted
+    ///
+    /// Expired entries never match; this only reclaims their storage.
+    async fn purge_expired(&mut self, _list_id: &str) -> Result<usize> {
+        Ok(0)
+    }
+
+    /// Whether the entry `entry` matches `value` in a lookup
+    ///
+    /// Used to evaluate lookups against past entries of the list. Entries
+    /// match values with the same string form unless overridden.
+    fn matches_entry(&self, entry: &Value, value: &Value) -> bool {
+        ListService::entry_key(entry) == ListService::entry_key(value)
+    }
+
+    /// Whether a value only matches the entry with the same string form
+    fn matches_exactly(&self) -> bool {
+        true
+    }
+
+    /// Check that `value` can be added as an entry, without adding it
+    fn validate_entry(&self, _value: &Value) -> Result<()> {
+        Ok(())
+    }
 }
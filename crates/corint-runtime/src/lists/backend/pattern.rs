@@ -0,0 +1,330 @@
+//! Pattern list backend
+
+use super::{ListBackend, ListEntry};
+use crate::error::{Result, RuntimeError};
+use crate::lists::pattern::{ListPattern, PatternSet};
+use chrono::{DateTime, Utc};
+use corint_core::Value;
+use regex::Regex;
+use std::sync::{Arc, Weak};
+use tokio::sync::{Mutex, RwLock};
+use tokio::time::{interval, Duration};
+
+/// Pattern list backend
+///
+/// Keeps the entries of another backend (globs or regular expressions, see
+/// [`crate::lists::pattern`]) and answers `contains` by matching the value
+/// against all of them, compiled into one [`PatternSet`]. The set is
+/// recompiled when entries are added or removed through this backend, when
+/// its first entry expires and, with a refresh interval, in the background
+/// for entries changed in the underlying store.
+pub struct PatternBackend {
+    /// Underlying entries and their compiled patterns, shared with the refresh task
+    state: Arc<PatternListState>,
+}
+
+struct PatternListState {
+    /// List the entries belong to
+    list_id: String,
+
+    /// Backend holding the entries
+    inner: RwLock<Box<dyn ListBackend>>,
+
+    /// Compiled patterns of the entries
+    patterns: RwLock<PatternSnapshot>,
+
+    /// Serializes rebuilds
+    rebuilding: Mutex<()>,
+}
+
+#[derive(Default)]
+struct PatternSnapshot {
+    set: PatternSet,
+
+    /// When the first entry in the set expires
+    next_expiry: Option<DateTime<Utc>>,
+}
+
+impl PatternSnapshot {
+    fn is_stale(&self, now: DateTime<Utc>) -> bool {
+        self.next_expiry.is_some_and(|next| next <= now)
+    }
+}
+
+impl PatternBackend {
+    /// Compile the patterns of `list_id` from the entries of `inner`
+    pub async fn load(list_id: impl Into<String>, inner: Box<dyn ListBackend>) -> Result<Self> {
+        let state = Arc::new(PatternListState {
+            list_id: list_id.into(),
+            inner: RwLock::new(inner),
+            patterns: RwLock::new(PatternSnapshot::default()),
+            rebuilding: Mutex::new(()),
+        });
+        state.rebuild().await?;
+        Ok(Self { state })
+    }
+
+    /// Recompile the patterns from the underlying entries every `refresh_interval_secs` seconds
+    ///
+    /// The background task stops when the backend is dropped.
+    pub fn with_refresh(self, refresh_interval_secs: u64) -> Self {
+        let state: Weak<PatternListState> = Arc::downgrade(&self.state);
+        tokio::spawn(async move {
+            let mut interval_timer = interval(Duration::from_secs(refresh_interval_secs.max(1)));
+            interval_timer.tick().await;
+            loop {
+                interval_timer.tick().await;
+                let Some(state) = state.upgrade() else {
+                    break;
+                };
+                if let Err(e) = state.rebuild().await {
+                    tracing::warn!("Failed to refresh pattern list '{}': {}", state.list_id, e);
+                }
+            }
+        });
+        self
+    }
+
+    /// Number of compiled patterns
+    pub async fn len(&self) -> usize {
+        self.state.patterns.read().await.set.len()
+    }
+
+    /// Check if there are no compiled patterns
+    pub async fn is_empty(&self) -> bool {
+        self.state.patterns.read().await.set.is_empty()
+    }
+
+    /// Patterns of the list matching `value`
+    pub async fn matching_patterns(&self, value: &str) -> Result<Vec<ListPattern>> {
+        self.state.rebuild_if_stale().await?;
+        let snapshot = self.state.patterns.read().await;
+        Ok(snapshot.set.matches(value).into_iter().cloned().collect())
+    }
+
+    /// Parse an entry to add, which must be a valid pattern
+    fn parse_entry(value: &Value) -> Result<ListPattern> {
+        match value {
+            Value::String(s) => s.parse(),
+            // Some backends read digit-only entries back as numbers
+            Value::Number(n) => n.to_string().parse(),
+            _ => Err(RuntimeError::InvalidValue(format!(
+                "Pattern list entries must be strings, got: {:?}",
+                value
+            ))),
+        }
+    }
+}
+
+impl PatternListState {
+    /// Rebuild the set if one of its entries expired
+    async fn rebuild_if_stale(&self) -> Result<()> {
+        if !self.patterns.read().await.is_stale(Utc::now()) {
+            return Ok(());
+        }
+        let _rebuilding = self.rebuilding.lock().await;
+        if self.patterns.read().await.is_stale(Utc::now()) {
+            self.rebuild_locked().await?;
+        }
+        Ok(())
+    }
+
+    /// Replace the set with one compiled from the current entries
+    async fn rebuild(&self) -> Result<()> {
+        let _rebuilding = self.rebuilding.lock().await;
+        self.rebuild_locked().await
+    }
+
+    async fn rebuild_locked(&self) -> Result<()> {
+        let entries = self.inner.read().await.get_entries(&self.list_id).await?;
+
+        let mut patterns = Vec::with_capacity(entries.len());
+        let mut next_expiry: Option<DateTime<Utc>> = None;
+        let mut invalid = 0;
+        for entry in entries {
+            match PatternBackend::parse_entry(&entry.value) {
+                Ok(pattern) => {
+                    patterns.push(pattern);
+                    if let Some(expires_at) = entry.expires_at {
+                        next_expiry =
+                            Some(next_expiry.map_or(expires_at, |next| next.min(expires_at)));
+                    }
+                }
+                Err(e) => {
+                    invalid += 1;
+                    tracing::debug!("Skipping entry of pattern list '{}': {}", self.list_id, e);
+                }
+            }
+        }
+        if invalid > 0 {
+            tracing::warn!(
+                "Skipped {} invalid entries of pattern list '{}'",
+                invalid,
+                self.list_id
+            );
+        }
+
+        let set = PatternSet::new(patterns)?;
+        tracing::debug!(
+            "Compiled pattern list '{}' with {} patterns",
+            self.list_id,
+            set.len()
+        );
+        *self.patterns.write().await = PatternSnapshot { set, next_expiry };
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl ListBackend for PatternBackend {
+    async fn contains(&self, _list_id: &str, value: &Value) -> Result<bool> {
+        let search_str = match value {
+            Value::String(s) => s.clone(),
+            Value::Number(n) => n.to_string(),
+            Value::Bool(b) => b.to_string(),
+            // Null and compound values never match a pattern
+            _ => return Ok(false),
+        };
+
+        self.state.rebuild_if_stale().await?;
+        Ok(self.state.patterns.read().await.set.is_match(&search_str))
+    }
+
+    async fn add(&mut self, list_id: &str, value: Value) -> Result<()> {
+        Self::parse_entry(&value)?;
+        self.state.inner.write().await.add(list_id, value).await?;
+        self.state.rebuild().await
+    }
+
+    async fn remove(&mut self, list_id: &str, value: &Value) -> Result<()> {
+        self.state
+            .inner
+            .write()
+            .await
+            .remove(list_id, value)
+            .await?;
+        self.state.rebuild().await
+    }
+
+    async fn get_all(&self, list_id: &str) -> Result<Vec<Value>> {
+        self.state.inner.read().await.get_all(list_id).await
+    }
+
+    async fn add_entry(&mut self, list_id: &str, entry: ListEntry) -> Result<()> {
+        Self::parse_entry(&entry.value)?;
+        self.state
+            .inner
+            .write()
+            .await
+            .add_entry(list_id, entry)
+            .await?;
+        self.state.rebuild().await
+    }
+
+    async fn get_entries(&self, list_id: &str) -> Result<Vec<ListEntry>> {
+        self.state.inner.read().await.get_entries(list_id).await
+    }
+
+    async fn purge_expired(&mut self, list_id: &str) -> Result<usize> {
+        self.state.inner.write().await.purge_expired(list_id).await
+    }
+
+    fn matches_entry(&self, entry: &Value, value: &Value) -> bool {
+        let value = match value {
+            Value::String(s) => s.clone(),
+            Value::Number(n) => n.to_string(),
+            Value::Bool(b) => b.to_string(),
+            _ => return false,
+        };
+        Self::parse_entry(entry)
+            .ok()
+            .and_then(|pattern| Regex::new(&pattern.to_regex()).ok())
+            .is_some_and(|regex| regex.is_match(&value))
+    }
+
+    fn matches_exactly(&self) -> bool {
+        false
+    }
+
+    fn validate_entry(&self, value: &Value) -> Result<()> {
+        Self::parse_entry(value).map(|_| ())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lists::MemoryBackend;
+
+    fn s(value: &str) -> Value {
+        Value::String(value.to_string())
+    }
+
+    #[tokio::test]
+    async fn test_pattern_backend() {
+        let mut entries = MemoryBackend::new();
+        entries.add("domains", s("*@*.temp-mail.*")).await.unwrap();
+        entries.add("domains", s("/(unclosed/")).await.unwrap();
+
+        let mut backend = PatternBackend::load("domains", Box::new(entries))
+            .await
+            .unwrap();
+        assert_eq!(backend.len().await, 1);
+        assert!(backend
+            .contains("domains", &s("jo@x.temp-mail.io"))
+            .await
+            .unwrap());
+        assert!(!backend
+            .contains("domains", &s("jo@example.com"))
+            .await
+            .unwrap());
+        assert!(!backend.contains("domains", &Value::Null).await.unwrap());
+
+        backend
+            .add("domains", s("/@(mailinator|guerrillamail)\\.com$/"))
+            .await
+            .unwrap();
+        assert!(backend
+            .contains("domains", &s("jo@mailinator.com"))
+            .await
+            .unwrap());
+        assert!(backend.add("domains", s("/[z-a]/")).await.is_err());
+        assert_eq!(
+            backend
+                .matching_patterns("jo@mailinator.com")
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+
+        backend
+            .remove("domains", &s("*@*.temp-mail.*"))
+            .await
+            .unwrap();
+        assert!(!backend
+            .contains("domains", &s("jo@x.temp-mail.io"))
+            .await
+            .unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_pattern_backend_expiry() {
+        let mut backend = PatternBackend::load("domains", Box::new(MemoryBackend::new()))
+            .await
+            .unwrap();
+        backend
+            .add_entry(
+                "domains",
+                ListEntry::new(s("*.example"))
+                    .with_expires_at(Utc::now() + chrono::Duration::milliseconds(50)),
+            )
+            .await
+            .unwrap();
+        assert!(backend.contains("domains", &s("a.example")).await.unwrap());
+
+        tokio::time::sleep(std::time::Duration::from_millis(80)).await;
+        assert!(!backend.contains("domains", &s("a.example")).await.unwrap());
+        assert!(backend.is_empty().await);
+    }
+}
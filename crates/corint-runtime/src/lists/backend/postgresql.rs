@@ -5,6 +5,8 @@ use crate::error::{Result, RuntimeError};
 use corint_core::Value;
 use std::sync::Arc;
 
+#[cfg(feature = "sqlx")]
+use super::ListEntry;
 #[cfg(feature = "sqlx")]
 use sqlx::{PgPool, Row};
 
@@ -12,6 +14,7 @@ use sqlx::{PgPool, Row};
 ///
 /// Stores list entries in a PostgreSQL database.
 /// Default table: `list_entries` with columns (list_id, value, created_at, expires_at)
+/// and, when configured, a reason column.
 pub struct PostgresBackend {
     /// Database connection pool
     #[cfg(feature = "sqlx")]
@@ -25,6 +28,9 @@ pub struct PostgresBackend {
 
     /// Optional expiration column
     expiration_column: Option<String>,
+
+    /// Optional column for the reason an entry was added
+    reason_column: Option<String>,
 }
 
 impl PostgresBackend {
@@ -36,6 +42,7 @@ impl PostgresBackend {
             table: "list_entries".to_string(),
             value_column: "value".to_string(),
             expiration_column: Some("expires_at".to_string()),
+            reason_column: None,
         }
     }
 
@@ -52,9 +59,16 @@ impl PostgresBackend {
             table,
             value_column,
             expiration_column,
+            reason_column: None,
         }
     }
 
+    /// Store the reason entries were added in this column
+    pub fn with_reason_column(mut self, reason_column: Option<String>) -> Self {
+        self.reason_column = reason_column;
+        self
+    }
+
     /// Placeholder for when sqlx feature is disabled
     #[cfg(not(feature = "sqlx"))]
     pub fn new() -> Self {
@@ -62,10 +76,30 @@ impl PostgresBackend {
             table: "list_entries".to_string(),
             value_column: "value".to_string(),
             expiration_column: Some("expires_at".to_string()),
+            reason_column: None,
+        }
+    }
+
+    /// Check that the table has columns for an entry's metadata
+    #[cfg(feature = "sqlx")]
+    fn check_entry_columns(&self, entry: &ListEntry) -> Result<()> {
+        if entry.expires_at.is_some() && self.expiration_column.is_none() {
+            return Err(RuntimeError::InvalidOperation(format!(
+                "List table '{}' has no expiration column",
+                self.table
+            )));
+        }
+        if entry.reason.is_some() && self.reason_column.is_none() {
+            return Err(RuntimeError::InvalidOperation(format!(
+                "List table '{}' has no reason column",
+                self.table
+            )));
         }
+        Ok(())
     }
 
     /// Convert Value to string for SQL queries
+    #[cfg(feature = "sqlx")]
     fn value_to_string(value: &Value) -> Result<String> {
         match value {
             Value::String(s) => Ok(s.clone()),
@@ -103,7 +137,10 @@ impl ListBackend for PostgresBackend {
             .bind(&value_str)
             .fetch_optional(&*self.pool)
             .await
-            .map_err(|e| RuntimeError::InvalidOperation(format!("Database query failed: {}", e)))?;
+            .map_err(|e| RuntimeError::ListBackendUnavailable {
+                list_id: list_id.to_string(),
+                message: format!("Database query failed: {}", e),
+            })?;
 
         Ok(result.is_some())
     }
@@ -174,6 +211,107 @@ impl ListBackend for PostgresBackend {
 
         Ok(values)
     }
+
+    async fn add_entry(&mut self, list_id: &str, entry: ListEntry) -> Result<()> {
+        self.check_entry_columns(&entry)?;
+        let value_str = Self::value_to_string(&entry.value)?;
+
+        // Configured metadata columns replace those of an existing entry
+        let mut columns = vec![self.value_column.clone()];
+        let mut params = vec!["$2".to_string()];
+        let mut updates = Vec::new();
+        let metadata = [
+            (&self.expiration_column, "::timestamptz"),
+            (&self.reason_column, ""),
+        ];
+        for (column, cast) in metadata {
+            if let Some(column) = column {
+                params.push(format!("${}{}", params.len() + 2, cast));
+                updates.push(format!("{} = EXCLUDED.{}", column, column));
+                columns.push(column.clone());
+            }
+        }
+        let on_conflict = if updates.is_empty() {
+            "DO NOTHING".to_string()
+        } else {
+            format!("DO UPDATE SET {}", updates.join(", "))
+        };
+
+        let query = format!(
+            "INSERT INTO {} (list_id, {}, created_at) VALUES ($1, {}, NOW())
+             ON CONFLICT (list_id, {}) {}",
+            self.table,
+            columns.join(", "),
+            params.join(", "),
+            self.value_column,
+            on_conflict
+        );
+
+        let mut insert = sqlx::query(&query).bind(list_id).bind(&value_str);
+        if self.expiration_column.is_some() {
+            insert = insert.bind(entry.expires_at);
+        }
+        if self.reason_column.is_some() {
+            insert = insert.bind(entry.reason);
+        }
+        insert
+            .execute(&*self.pool)
+            .await
+            .map_err(|e| RuntimeError::InvalidOperation(format!("Database insert failed: {}", e)))?;
+
+        Ok(())
+    }
+
+    async fn get_entries(&self, list_id: &str) -> Result<Vec<ListEntry>> {
+        let (expires, live) = match &self.expiration_column {
+            Some(exp_col) => (
+                format!("{}::timestamptz", exp_col),
+                format!("AND ({} IS NULL OR {} > NOW())", exp_col, exp_col),
+            ),
+            None => ("NULL::timestamptz".to_string(), String::new()),
+        };
+        let reason = self.reason_column.as_deref().unwrap_or("NULL::text");
+        let query = format!(
+            "SELECT {}, {}, {} FROM {} WHERE list_id = $1 {}",
+            self.value_column, expires, reason, self.table, live
+        );
+
+        let rows = sqlx::query(&query)
+            .bind(list_id)
+            .fetch_all(&*self.pool)
+            .await
+            .map_err(|e| RuntimeError::InvalidOperation(format!("Database query failed: {}", e)))?;
+
+        rows.iter()
+            .map(|row| {
+                Ok(ListEntry {
+                    value: Value::String(row.try_get(0)?),
+                    expires_at: row.try_get(1)?,
+                    reason: row.try_get(2)?,
+                })
+            })
+            .collect::<std::result::Result<_, sqlx::Error>>()
+            .map_err(|e| RuntimeError::InvalidOperation(format!("Failed to read entry: {}", e)))
+    }
+
+    async fn purge_expired(&mut self, list_id: &str) -> Result<usize> {
+        let Some(exp_col) = &self.expiration_column else {
+            return Ok(0);
+        };
+
+        let query = format!(
+            "DELETE FROM {} WHERE list_id = $1 AND {} <= NOW()",
+            self.table, exp_col
+        );
+
+        let result = sqlx::query(&query)
+            .bind(list_id)
+            .execute(&*self.pool)
+            .await
+            .map_err(|e| RuntimeError::InvalidOperation(format!("Database delete failed: {}", e)))?;
+
+        Ok(result.rows_affected() as usize)
+    }
 }
 
 #[cfg(not(feature = "sqlx"))]
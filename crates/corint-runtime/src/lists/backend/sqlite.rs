@@ -7,6 +7,10 @@ use std::path::PathBuf;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
+#[cfg(feature = "sqlx")]
+use super::ListEntry;
+#[cfg(feature = "sqlx")]
+use chrono::NaiveDateTime;
 #[cfg(feature = "sqlx")]
 use sqlx::{Row, SqlitePool};
 
@@ -14,6 +18,7 @@ use sqlx::{Row, SqlitePool};
 ///
 /// Stores list entries in a SQLite database.
 /// Default table: `list_entries` with columns (list_id, value, created_at, expires_at)
+/// and, when configured, a reason column.
 pub struct SqliteBackend {
     /// Database file path
     db_path: PathBuf,
@@ -30,6 +35,9 @@ pub struct SqliteBackend {
 
     /// Optional expiration column
     expiration_column: Option<String>,
+
+    /// Optional column for the reason an entry was added
+    reason_column: Option<String>,
 }
 
 impl SqliteBackend {
@@ -42,6 +50,7 @@ impl SqliteBackend {
             table: "list_entries".to_string(),
             value_column: "value".to_string(),
             expiration_column: Some("expires_at".to_string()),
+            reason_column: None,
         }
     }
 
@@ -59,9 +68,34 @@ impl SqliteBackend {
             table,
             value_column,
             expiration_column,
+            reason_column: None,
         }
     }
 
+    /// Store the reason entries were added in this column
+    pub fn with_reason_column(mut self, reason_column: Option<String>) -> Self {
+        self.reason_column = reason_column;
+        self
+    }
+
+    /// Check that the table has columns for an entry's metadata
+    #[cfg(feature = "sqlx")]
+    fn check_entry_columns(&self, entry: &ListEntry) -> Result<()> {
+        if entry.expires_at.is_some() && self.expiration_column.is_none() {
+            return Err(RuntimeError::InvalidOperation(format!(
+                "List table '{}' has no expiration column",
+                self.table
+            )));
+        }
+        if entry.reason.is_some() && self.reason_column.is_none() {
+            return Err(RuntimeError::InvalidOperation(format!(
+                "List table '{}' has no reason column",
+                self.table
+            )));
+        }
+        Ok(())
+    }
+
     /// Get or create connection pool
     #[cfg(feature = "sqlx")]
     async fn get_pool(&self) -> Result<SqlitePool> {
@@ -92,6 +126,7 @@ impl SqliteBackend {
     }
 
     /// Convert Value to string for SQL queries
+    #[cfg(feature = "sqlx")]
     fn value_to_string(value: &Value) -> Result<String> {
         match value {
             Value::String(s) => Ok(s.clone()),
@@ -132,7 +167,10 @@ impl ListBackend for SqliteBackend {
             .bind(&value_str)
             .fetch_optional(&pool)
             .await
-            .map_err(|e| RuntimeError::InvalidOperation(format!("SQLite query failed: {}", e)))?;
+            .map_err(|e| RuntimeError::ListBackendUnavailable {
+                list_id: list_id.to_string(),
+                message: format!("SQLite query failed: {}", e),
+            })?;
 
         Ok(result.is_some())
     }
@@ -207,6 +245,119 @@ impl ListBackend for SqliteBackend {
 
         Ok(values)
     }
+
+    async fn add_entry(&mut self, list_id: &str, entry: ListEntry) -> Result<()> {
+        self.check_entry_columns(&entry)?;
+        let value_str = Self::value_to_string(&entry.value)?;
+        let pool = self.get_pool().await?;
+
+        // Configured metadata columns replace those of an existing entry
+        let mut columns = vec![self.value_column.clone()];
+        let mut params = vec!["?2".to_string()];
+        let mut updates = Vec::new();
+        for column in [&self.expiration_column, &self.reason_column].into_iter().flatten() {
+            params.push(format!("?{}", params.len() + 2));
+            updates.push(format!("{} = excluded.{}", column, column));
+            columns.push(column.clone());
+        }
+        let on_conflict = if updates.is_empty() {
+            "DO NOTHING".to_string()
+        } else {
+            format!("DO UPDATE SET {}", updates.join(", "))
+        };
+
+        let query = format!(
+            "INSERT INTO {} (list_id, {}, created_at) VALUES (?1, {}, datetime('now'))
+             ON CONFLICT (list_id, {}) {}",
+            self.table,
+            columns.join(", "),
+            params.join(", "),
+            self.value_column,
+            on_conflict
+        );
+
+        // Stored in the format of datetime('now') so comparisons stay textual
+        let mut insert = sqlx::query(&query).bind(list_id).bind(&value_str);
+        if self.expiration_column.is_some() {
+            insert = insert.bind(
+                entry
+                    .expires_at
+                    .map(|expires_at| expires_at.format("%Y-%m-%d %H:%M:%S").to_string()),
+            );
+        }
+        if self.reason_column.is_some() {
+            insert = insert.bind(entry.reason);
+        }
+        insert
+            .execute(&pool)
+            .await
+            .map_err(|e| RuntimeError::InvalidOperation(format!("SQLite insert failed: {}", e)))?;
+
+        Ok(())
+    }
+
+    async fn get_entries(&self, list_id: &str) -> Result<Vec<ListEntry>> {
+        let pool = self.get_pool().await?;
+
+        let (expires, live) = match &self.expiration_column {
+            Some(exp_col) => (
+                format!("datetime({})", exp_col),
+                format!(
+                    "AND ({} IS NULL OR datetime({}) > datetime('now'))",
+                    exp_col, exp_col
+                ),
+            ),
+            None => ("NULL".to_string(), String::new()),
+        };
+        let reason = self.reason_column.as_deref().unwrap_or("NULL");
+        let query = format!(
+            "SELECT {}, {}, {} FROM {} WHERE list_id = ?1 {}",
+            self.value_column, expires, reason, self.table, live
+        );
+
+        let rows = sqlx::query(&query)
+            .bind(list_id)
+            .fetch_all(&pool)
+            .await
+            .map_err(|e| RuntimeError::InvalidOperation(format!("SQLite query failed: {}", e)))?;
+
+        let mut entries = Vec::new();
+        for row in rows {
+            let read_error = |e: sqlx::Error| {
+                RuntimeError::InvalidOperation(format!("Failed to read entry: {}", e))
+            };
+            let expires_at: Option<String> = row.try_get(1).map_err(read_error)?;
+            entries.push(ListEntry {
+                value: Value::String(row.try_get(0).map_err(read_error)?),
+                expires_at: expires_at
+                    .and_then(|s| NaiveDateTime::parse_from_str(&s, "%Y-%m-%d %H:%M:%S").ok())
+                    .map(|naive| naive.and_utc()),
+                reason: row.try_get(2).map_err(read_error)?,
+            });
+        }
+
+        Ok(entries)
+    }
+
+    async fn purge_expired(&mut self, list_id: &str) -> Result<usize> {
+        let Some(exp_col) = &self.expiration_column else {
+            return Ok(0);
+        };
+        let pool = self.get_pool().await?;
+
+        let query = format!(
+            "DELETE FROM {} WHERE list_id = ?1 AND datetime({}) <= datetime('now')",
+            self.table, exp_col
+        );
+
+        let result = sqlx::query(&query)
+            .bind(list_id)
+            .execute(&pool)
+            .await
+            .map_err(|e| RuntimeError::InvalidOperation(format!("SQLite delete failed: {}", e)))?;
+
+        Ok(result.rows_affected() as usize)
+    }
 }
 
 #[cfg(not(feature = "sqlx"))]
@@ -257,6 +408,7 @@ mod tests {
                 value TEXT NOT NULL,
                 created_at TEXT DEFAULT CURRENT_TIMESTAMP,
                 expires_at TEXT,
+                reason TEXT,
                 UNIQUE(list_id, value)
             )",
         )
@@ -385,4 +537,50 @@ mod tests {
         let values = backend.get_all(test_list).await.unwrap();
         assert_eq!(values.len(), 3);
     }
+
+    #[tokio::test]
+    async fn test_sqlite_backend_entry_metadata() {
+        let (_temp_file, path) = setup_test_db().await;
+        let mut backend = SqliteBackend::new(path).with_reason_column(Some("reason".to_string()));
+        let value = |s: &str| Value::String(s.to_string());
+
+        let test_list = "blocked_users";
+        backend
+            .add_entry(
+                test_list,
+                ListEntry::new(value("temporary"))
+                    .with_ttl(std::time::Duration::from_secs(3600))
+                    .with_reason("chargeback"),
+            )
+            .await
+            .unwrap();
+        backend
+            .add_entry(
+                test_list,
+                ListEntry::new(value("expired"))
+                    .with_expires_at(chrono::Utc::now() - chrono::Duration::seconds(5)),
+            )
+            .await
+            .unwrap();
+
+        assert!(backend.contains(test_list, &value("temporary")).await.unwrap());
+        assert!(!backend.contains(test_list, &value("expired")).await.unwrap());
+
+        let entries = backend.get_entries(test_list).await.unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].reason.as_deref(), Some("chargeback"));
+        assert!(entries[0].expires_at.unwrap() > chrono::Utc::now());
+
+        assert_eq!(backend.purge_expired(test_list).await.unwrap(), 1);
+        assert_eq!(backend.purge_expired(test_list).await.unwrap(), 0);
+
+        // Re-adding an entry replaces its expiry and reason
+        backend
+            .add_entry(test_list, ListEntry::new(value("temporary")))
+            .await
+            .unwrap();
+        let entries = backend.get_entries(test_list).await.unwrap();
+        assert_eq!(entries[0].expires_at, None);
+        assert_eq!(entries[0].reason, None);
+    }
 }
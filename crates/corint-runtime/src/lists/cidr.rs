@@ -0,0 +1,406 @@
+//! IP networks and longest-prefix matching
+//!
+//! Lists of `type: cidr` hold networks (`10.0.0.0/8`, `2001:db8::/32`),
+//! single addresses or address ranges (`192.0.2.10-192.0.2.20`), and match
+//! an IP address against them with a binary radix trie.
+
+use crate::error::{Result, RuntimeError};
+use std::fmt;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::str::FromStr;
+
+/// An IPv4 or IPv6 network
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct IpNetwork {
+    addr: IpAddr,
+    prefix_len: u8,
+}
+
+impl IpNetwork {
+    /// Create a network, clearing the host bits of `addr`
+    pub fn new(addr: IpAddr, prefix_len: u8) -> Result<Self> {
+        // Networks of IPv4-mapped IPv6 addresses match like their IPv4 form
+        if let IpAddr::V6(v6) = addr {
+            if let (Some(v4), 96..) = (v6.to_ipv4_mapped(), prefix_len) {
+                return IpNetwork::new(IpAddr::V4(v4), prefix_len - 96);
+            }
+        }
+        let width = address_width(&addr);
+        if prefix_len > width {
+            return Err(RuntimeError::InvalidValue(format!(
+                "Invalid prefix length /{} for {}",
+                prefix_len, addr
+            )));
+        }
+        Ok(Self {
+            addr: from_key(key(&addr) & prefix_mask(prefix_len), addr.is_ipv4()),
+            prefix_len,
+        })
+    }
+
+    /// Network address
+    pub fn addr(&self) -> IpAddr {
+        self.addr
+    }
+
+    /// Number of leading bits that identify the network
+    pub fn prefix_len(&self) -> u8 {
+        self.prefix_len
+    }
+
+    /// Whether `ip` belongs to this network
+    pub fn contains(&self, ip: IpAddr) -> bool {
+        let ip = normalize(ip);
+        ip.is_ipv4() == self.addr.is_ipv4()
+            && key(&ip) & prefix_mask(self.prefix_len) == key(&self.addr)
+    }
+
+    /// Parse a network, a single address or an inclusive `start-end` range
+    ///
+    /// A range is split into the fewest networks that cover it exactly.
+    pub fn parse_all(s: &str) -> Result<Vec<IpNetwork>> {
+        let Some((start, end)) = s.split_once('-') else {
+            return Ok(vec![s.parse()?]);
+        };
+        let (start, end) = (parse_ip(start)?, parse_ip(end)?);
+        if start.is_ipv4() != end.is_ipv4() || to_bits(&start) > to_bits(&end) {
+            return Err(RuntimeError::InvalidValue(format!(
+                "Invalid IP range '{}'",
+                s
+            )));
+        }
+
+        let width = address_width(&start);
+        let (mut next, end) = (to_bits(&start), to_bits(&end));
+        let mut networks = Vec::new();
+        loop {
+            // Largest aligned block starting at `next` that does not pass `end`
+            let mut host_bits = (next.trailing_zeros() as u8).min(width);
+            while host_bits > 0
+                && next
+                    .checked_add(block_span(host_bits))
+                    .is_none_or(|last| last > end)
+            {
+                host_bits -= 1;
+            }
+            networks.push(IpNetwork {
+                addr: from_bits(next, start.is_ipv4()),
+                prefix_len: width - host_bits,
+            });
+            let last = next + block_span(host_bits);
+            if last >= end {
+                return Ok(networks);
+            }
+            next = last + 1;
+        }
+    }
+}
+
+impl FromStr for IpNetwork {
+    type Err = RuntimeError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().split_once('/') {
+            Some((addr, prefix_len)) => {
+                let prefix_len = prefix_len
+                    .trim()
+                    .parse()
+                    .map_err(|_| RuntimeError::InvalidValue(format!("Invalid network '{}'", s)))?;
+                IpNetwork::new(parse_ip(addr)?, prefix_len)
+            }
+            None => {
+                let addr = parse_ip(s)?;
+                IpNetwork::new(addr, address_width(&addr))
+            }
+        }
+    }
+}
+
+impl fmt::Display for IpNetwork {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.addr, self.prefix_len)
+    }
+}
+
+/// Binary radix trie of IP networks
+///
+/// IPv4 and IPv6 networks are kept in separate trees; IPv4-mapped IPv6
+/// addresses (`::ffff:192.0.2.1`) are looked up as IPv4.
+#[derive(Debug, Clone)]
+pub struct CidrTrie {
+    /// Node arena; nodes 0 and 1 are the IPv4 and IPv6 roots
+    nodes: Vec<TrieNode>,
+    len: usize,
+}
+
+#[derive(Debug, Clone, Default)]
+struct TrieNode {
+    /// Child indexes for a 0 and a 1 bit; 0 means none
+    children: [u32; 2],
+    /// Whether a network ends at this node
+    terminal: bool,
+}
+
+impl CidrTrie {
+    /// Create an empty trie
+    pub fn new() -> Self {
+        Self {
+            nodes: vec![TrieNode::default(), TrieNode::default()],
+            len: 0,
+        }
+    }
+
+    /// Number of networks
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Whether the trie holds no networks
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Add a network, returning whether it was new
+    pub fn insert(&mut self, network: IpNetwork) -> bool {
+        let bits = key(&network.addr);
+        let mut node = root(&network.addr);
+        for depth in 0..network.prefix_len {
+            let bit = bit_at(bits, depth);
+            node = match self.nodes[node].children[bit] {
+                0 => {
+                    self.nodes.push(TrieNode::default());
+                    let child = self.nodes.len() - 1;
+                    self.nodes[node].children[bit] = child as u32;
+                    child
+                }
+                child => child as usize,
+            };
+        }
+        let added = !self.nodes[node].terminal;
+        self.nodes[node].terminal = true;
+        self.len += added as usize;
+        added
+    }
+
+    /// Remove a network, returning whether it was present
+    ///
+    /// Addresses in networks nested in or covering it still match those.
+    pub fn remove(&mut self, network: &IpNetwork) -> bool {
+        let bits = key(&network.addr);
+        let mut node = root(&network.addr);
+        for depth in 0..network.prefix_len {
+            node = match self.nodes[node].children[bit_at(bits, depth)] {
+                0 => return false,
+                child => child as usize,
+            };
+        }
+        let removed = self.nodes[node].terminal;
+        self.nodes[node].terminal = false;
+        self.len -= removed as usize;
+        removed
+    }
+
+    /// Most specific network containing `ip`
+    pub fn longest_match(&self, ip: IpAddr) -> Option<IpNetwork> {
+        let ip = normalize(ip);
+        let bits = key(&ip);
+        let mut node = root(&ip);
+        let mut matched = self.nodes[node].terminal.then_some(0);
+        for depth in 0..address_width(&ip) {
+            node = match self.nodes[node].children[bit_at(bits, depth)] {
+                0 => break,
+                child => child as usize,
+            };
+            if self.nodes[node].terminal {
+                matched = Some(depth + 1);
+            }
+        }
+        matched.map(|prefix_len| IpNetwork {
+            addr: from_key(bits & prefix_mask(prefix_len), ip.is_ipv4()),
+            prefix_len,
+        })
+    }
+
+    /// Whether any network contains `ip`
+    pub fn contains(&self, ip: IpAddr) -> bool {
+        self.longest_match(ip).is_some()
+    }
+}
+
+impl Default for CidrTrie {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Parse an IP address, accepting surrounding whitespace
+fn parse_ip(s: &str) -> Result<IpAddr> {
+    s.trim()
+        .parse()
+        .map_err(|_| RuntimeError::InvalidValue(format!("Invalid IP address '{}'", s.trim())))
+}
+
+/// Treat IPv4-mapped IPv6 addresses as IPv4
+fn normalize(ip: IpAddr) -> IpAddr {
+    match ip {
+        IpAddr::V6(v6) => v6.to_ipv4_mapped().map_or(ip, IpAddr::V4),
+        IpAddr::V4(_) => ip,
+    }
+}
+
+fn address_width(ip: &IpAddr) -> u8 {
+    if ip.is_ipv4() {
+        32
+    } else {
+        128
+    }
+}
+
+fn root(ip: &IpAddr) -> usize {
+    if ip.is_ipv4() {
+        0
+    } else {
+        1
+    }
+}
+
+/// Address as an integer, for range arithmetic
+fn to_bits(ip: &IpAddr) -> u128 {
+    match ip {
+        IpAddr::V4(v4) => u32::from(*v4) as u128,
+        IpAddr::V6(v6) => u128::from(*v6),
+    }
+}
+
+fn from_bits(bits: u128, ipv4: bool) -> IpAddr {
+    if ipv4 {
+        IpAddr::V4(Ipv4Addr::from(bits as u32))
+    } else {
+        IpAddr::V6(Ipv6Addr::from(bits))
+    }
+}
+
+/// Address as trie key: its bits aligned to the most significant bit
+fn key(ip: &IpAddr) -> u128 {
+    to_bits(ip) << (128 - address_width(ip) as u32)
+}
+
+fn from_key(key: u128, ipv4: bool) -> IpAddr {
+    if ipv4 {
+        from_bits(key >> 96, true)
+    } else {
+        from_bits(key, false)
+    }
+}
+
+/// Key mask of the leading `prefix_len` bits
+fn prefix_mask(prefix_len: u8) -> u128 {
+    match prefix_len {
+        0 => 0,
+        n => !0u128 << (128 - n as u32),
+    }
+}
+
+/// Bit `depth` of a key, counted from the most significant bit
+fn bit_at(bits: u128, depth: u8) -> usize {
+    ((bits >> (127 - depth as u32)) & 1) as usize
+}
+
+/// Distance from the first to the last address of a block with `host_bits` host bits
+fn block_span(host_bits: u8) -> u128 {
+    if host_bits >= 128 {
+        u128::MAX
+    } else {
+        (1 << host_bits) - 1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ip(s: &str) -> IpAddr {
+        s.parse().unwrap()
+    }
+
+    fn net(s: &str) -> IpNetwork {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_parse_network() {
+        assert_eq!(net("10.1.2.3/8").to_string(), "10.0.0.0/8");
+        assert_eq!(net(" 192.0.2.1 ").to_string(), "192.0.2.1/32");
+        assert_eq!(net("2001:db8::1/32").to_string(), "2001:db8::/32");
+        assert_eq!(net("::ffff:192.0.2.0/120").to_string(), "192.0.2.0/24");
+        assert!("10.0.0.0/33".parse::<IpNetwork>().is_err());
+        assert!("not-an-ip".parse::<IpNetwork>().is_err());
+        assert!(net("10.0.0.0/8").contains(ip("10.255.0.1")));
+        assert!(!net("10.0.0.0/8").contains(ip("11.0.0.1")));
+    }
+
+    #[test]
+    fn test_parse_range() {
+        let networks: Vec<String> = IpNetwork::parse_all("192.0.2.10 - 192.0.2.20")
+            .unwrap()
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        assert_eq!(
+            networks,
+            [
+                "192.0.2.10/31",
+                "192.0.2.12/30",
+                "192.0.2.16/30",
+                "192.0.2.20/32"
+            ]
+        );
+        assert_eq!(
+            IpNetwork::parse_all("0.0.0.0-255.255.255.255").unwrap(),
+            vec![net("0.0.0.0/0")]
+        );
+        assert_eq!(
+            IpNetwork::parse_all("::-ffff:ffff:ffff:ffff:ffff:ffff:ffff:ffff").unwrap(),
+            vec![net("::/0")]
+        );
+        assert!(IpNetwork::parse_all("192.0.2.20-192.0.2.10").is_err());
+        assert!(IpNetwork::parse_all("192.0.2.1-::1").is_err());
+    }
+
+    #[test]
+    fn test_longest_match() {
+        let mut trie = CidrTrie::new();
+        assert!(trie.insert(net("10.0.0.0/8")));
+        assert!(trie.insert(net("10.1.0.0/16")));
+        assert!(trie.insert(net("192.0.2.7")));
+        assert!(trie.insert(net("2001:db8::/32")));
+        assert!(!trie.insert(net("10.0.0.0/8")));
+        assert_eq!(trie.len(), 4);
+
+        assert_eq!(trie.longest_match(ip("10.1.2.3")), Some(net("10.1.0.0/16")));
+        assert_eq!(trie.longest_match(ip("10.2.2.3")), Some(net("10.0.0.0/8")));
+        assert_eq!(
+            trie.longest_match(ip("192.0.2.7")),
+            Some(net("192.0.2.7/32"))
+        );
+        assert_eq!(
+            trie.longest_match(ip("::ffff:10.9.9.9")),
+            Some(net("10.0.0.0/8"))
+        );
+        assert_eq!(
+            trie.longest_match(ip("2001:db8:1::1")),
+            Some(net("2001:db8::/32"))
+        );
+        assert!(!trie.contains(ip("192.0.2.8")));
+        assert!(!trie.contains(ip("2001:db9::1")));
+        // IPv4 networks do not match IPv6 addresses with the same leading bits
+        assert!(!trie.contains(ip("a00::1")));
+
+        assert!(trie.remove(&net("10.1.0.0/16")));
+        assert!(!trie.remove(&net("10.1.0.0/16")));
+        assert_eq!(trie.longest_match(ip("10.1.2.3")), Some(net("10.0.0.0/8")));
+
+        trie.insert(net("0.0.0.0/0"));
+        assert_eq!(trie.longest_match(ip("8.8.8.8")), Some(net("0.0.0.0/0")));
+    }
+}
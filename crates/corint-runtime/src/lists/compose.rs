@@ -0,0 +1,403 @@
+//! Lists composed of other lists
+//!
+//! A composed list has no entries of its own. Its definition combines other
+//! lists with set operators, evaluated left to right, `&` before `+`/`-`:
+//!
+//! | operator | meaning                                            |
+//! |----------|----------------------------------------------------|
+//! | `a + b`  | union: values in `a` or in `b`                     |
+//! | `a & b`  | intersection: values in both `a` and `b`           |
+//! | `a - b`  | difference: values in `a` but not in `b`           |
+//!
+//! with parentheses for grouping, e.g.
+//! `vendor_feed + internal_blocks - allowlist`. Compositions may reference
+//! other composed lists; these are inlined when the compositions are
+//! resolved at load time, which also rejects cycles and unknown lists, so a
+//! lookup only consults lists with entries.
+
+use crate::error::{Result, RuntimeError};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::str::FromStr;
+
+/// Set expression over lists
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ListComposition {
+    /// Values in the list with this ID
+    List(String),
+
+    /// Values in either operand
+    Union(Box<ListComposition>, Box<ListComposition>),
+
+    /// Values in both operands
+    Intersection(Box<ListComposition>, Box<ListComposition>),
+
+    /// Values in the first operand but not the second
+    Difference(Box<ListComposition>, Box<ListComposition>),
+}
+
+impl ListComposition {
+    /// IDs of the lists referenced, in order of first appearance
+    pub fn lists(&self) -> Vec<&str> {
+        let mut lists = Vec::new();
+        self.collect_lists(&mut lists);
+        lists
+    }
+
+    fn collect_lists<'a>(&'a self, lists: &mut Vec<&'a str>) {
+        match self {
+            Self::List(list_id) => {
+                if !lists.contains(&list_id.as_str()) {
+                    lists.push(list_id);
+                }
+            }
+            Self::Union(a, b) | Self::Intersection(a, b) | Self::Difference(a, b) => {
+                a.collect_lists(lists);
+                b.collect_lists(lists);
+            }
+        }
+    }
+
+    /// Whether a value is in the composed list, given whether it is in each referenced list
+    pub fn evaluate(&self, contains: &impl Fn(&str) -> bool) -> bool {
+        match self {
+            Self::List(list_id) => contains(list_id),
+            Self::Union(a, b) => a.evaluate(contains) || b.evaluate(contains),
+            Self::Intersection(a, b) => a.evaluate(contains) && b.evaluate(contains),
+            Self::Difference(a, b) => a.evaluate(contains) && !b.evaluate(contains),
+        }
+    }
+
+    /// Resolve compositions against the lists with entries
+    ///
+    /// References to other composed lists are replaced by their definitions.
+    /// Compositions that reference an unknown list, directly or through
+    /// another composition, that are part of a cycle or that reuse the ID of
+    /// a list with entries are returned as errors, by list ID.
+    pub fn resolve_all(
+        compositions: &HashMap<String, ListComposition>,
+        lists: &HashSet<String>,
+    ) -> (
+        HashMap<String, ListComposition>,
+        Vec<(String, RuntimeError)>,
+    ) {
+        let mut resolved = HashMap::new();
+        let mut errors = Vec::new();
+        for (list_id, composition) in compositions {
+            let result = if lists.contains(list_id) {
+                Err(RuntimeError::InvalidOperation(format!(
+                    "Composed list '{}' has the ID of an existing list",
+                    list_id
+                )))
+            } else {
+                composition.resolve(compositions, lists, &mut vec![list_id.as_str()])
+            };
+            match result {
+                Ok(composition) => {
+                    resolved.insert(list_id.clone(), composition);
+                }
+                Err(e) => errors.push((list_id.clone(), e)),
+            }
+        }
+        errors.sort_by(|a, b| a.0.cmp(&b.0));
+        (resolved, errors)
+    }
+
+    fn resolve<'a>(
+        &'a self,
+        compositions: &'a HashMap<String, ListComposition>,
+        lists: &HashSet<String>,
+        path: &mut Vec<&'a str>,
+    ) -> Result<ListComposition> {
+        let resolve_pair = |a: &'a Self, b: &'a Self, path: &mut Vec<&'a str>| {
+            Ok::<_, RuntimeError>((
+                Box::new(a.resolve(compositions, lists, path)?),
+                Box::new(b.resolve(compositions, lists, path)?),
+            ))
+        };
+        match self {
+            Self::List(list_id) if lists.contains(list_id) => Ok(self.clone()),
+            Self::List(list_id) => {
+                let Some(composition) = compositions.get(list_id) else {
+                    return Err(RuntimeError::InvalidOperation(format!(
+                        "Composed list '{}' references unknown list '{}'",
+                        path[0], list_id
+                    )));
+                };
+                if path.contains(&list_id.as_str()) {
+                    path.push(list_id);
+                    return Err(RuntimeError::InvalidOperation(format!(
+                        "Composed list '{}' references itself: {}",
+                        path[0],
+                        path.join(" -> ")
+                    )));
+                }
+                path.push(list_id);
+                let resolved = composition.resolve(compositions, lists, path)?;
+                path.pop();
+                Ok(resolved)
+            }
+            Self::Union(a, b) => {
+                let (a, b) = resolve_pair(a, b, path)?;
+                Ok(Self::Union(a, b))
+            }
+            Self::Intersection(a, b) => {
+                let (a, b) = resolve_pair(a, b, path)?;
+                Ok(Self::Intersection(a, b))
+            }
+            Self::Difference(a, b) => {
+                let (a, b) = resolve_pair(a, b, path)?;
+                Ok(Self::Difference(a, b))
+            }
+        }
+    }
+}
+
+impl FromStr for ListComposition {
+    type Err = RuntimeError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let tokens = tokenize(s)?;
+        let mut parser = Parser {
+            expression: s,
+            tokens: &tokens,
+            pos: 0,
+        };
+        let composition = parser.union()?;
+        match parser.tokens.get(parser.pos) {
+            None => Ok(composition),
+            Some(token) => Err(invalid(s, &format!("unexpected '{}'", token))),
+        }
+    }
+}
+
+impl fmt::Display for ListComposition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Parenthesize operands that would otherwise bind differently
+        let operand = |f: &mut fmt::Formatter<'_>, c: &Self, grouped: bool| {
+            if grouped {
+                write!(f, "({})", c)
+            } else {
+                write!(f, "{}", c)
+            }
+        };
+        let is_sum = |c: &Self| matches!(c, Self::Union(..) | Self::Difference(..));
+        let (a, operator, b) = match self {
+            Self::List(list_id) => return write!(f, "{}", list_id),
+            Self::Union(a, b) => (a, '+', b),
+            Self::Intersection(a, b) => (a, '&', b),
+            Self::Difference(a, b) => (a, '-', b),
+        };
+        if operator == '&' {
+            operand(f, a, is_sum(a))?;
+            write!(f, " & ")?;
+            operand(f, b, !matches!(**b, Self::List(_)))
+        } else {
+            operand(f, a, false)?;
+            write!(f, " {} ", operator)?;
+            operand(f, b, is_sum(b))
+        }
+    }
+}
+
+fn invalid(expression: &str, reason: &str) -> RuntimeError {
+    RuntimeError::InvalidValue(format!(
+        "Invalid list composition '{}': {}",
+        expression.trim(),
+        reason
+    ))
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    List(String),
+    Operator(char),
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::List(list_id) => write!(f, "{}", list_id),
+            Self::Operator(c) => write!(f, "{}", c),
+        }
+    }
+}
+
+fn tokenize(s: &str) -> Result<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut chars = s.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        match c {
+            c if c.is_whitespace() => {
+                chars.next();
+            }
+            '+' | '-' | '&' | '(' | ')' => {
+                tokens.push(Token::Operator(c));
+                chars.next();
+            }
+            c if c.is_alphanumeric() || c == '_' => {
+                let mut list_id = String::new();
+                while let Some(&c) = chars.peek() {
+                    if !(c.is_alphanumeric() || matches!(c, '_' | '.')) {
+                        break;
+                    }
+                    list_id.push(c);
+                    chars.next();
+                }
+                tokens.push(Token::List(list_id));
+            }
+            c => return Err(invalid(s, &format!("unexpected '{}'", c))),
+        }
+    }
+    if tokens.is_empty() {
+        return Err(invalid(s, "no lists"));
+    }
+    Ok(tokens)
+}
+
+/// Recursive descent parser over the tokens of `expression`
+struct Parser<'a> {
+    expression: &'a str,
+    tokens: &'a [Token],
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn next_operator(&mut self, operators: &[char]) -> Option<char> {
+        match self.tokens.get(self.pos) {
+            Some(Token::Operator(c)) if operators.contains(c) => {
+                self.pos += 1;
+                Some(*c)
+            }
+            _ => None,
+        }
+    }
+
+    fn union(&mut self) -> Result<ListComposition> {
+        let mut composition = self.intersection()?;
+        while let Some(operator) = self.next_operator(&['+', '-']) {
+            let (a, b) = (Box::new(composition), Box::new(self.intersection()?));
+            composition = if operator == '+' {
+                ListComposition::Union(a, b)
+            } else {
+                ListComposition::Difference(a, b)
+            };
+        }
+        Ok(composition)
+    }
+
+    fn intersection(&mut self) -> Result<ListComposition> {
+        let mut composition = self.operand()?;
+        while self.next_operator(&['&']).is_some() {
+            composition =
+                ListComposition::Intersection(Box::new(composition), Box::new(self.operand()?));
+        }
+        Ok(composition)
+    }
+
+    fn operand(&mut self) -> Result<ListComposition> {
+        let token = self
+            .tokens
+            .get(self.pos)
+            .ok_or_else(|| invalid(self.expression, "unexpected end of expression"))?;
+        self.pos += 1;
+        match token {
+            Token::List(list_id) => Ok(ListComposition::List(list_id.clone())),
+            Token::Operator('(') => {
+                let composition = self.union()?;
+                if self.next_operator(&[')']).is_none() {
+                    return Err(invalid(self.expression, "missing ')'"));
+                }
+                Ok(composition)
+            }
+            token => Err(invalid(self.expression, &format!("unexpected '{}'", token))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(s: &str) -> ListComposition {
+        s.parse().unwrap()
+    }
+
+    fn list(list_id: &str) -> Box<ListComposition> {
+        Box::new(ListComposition::List(list_id.to_string()))
+    }
+
+    #[test]
+    fn test_parse_composition() {
+        assert_eq!(
+            parse("vendor_feed + internal_blocks - allowlist"),
+            ListComposition::Difference(
+                Box::new(ListComposition::Union(
+                    list("vendor_feed"),
+                    list("internal_blocks")
+                )),
+                list("allowlist")
+            )
+        );
+        // & binds tighter than + and -
+        assert_eq!(
+            parse("a + b & c"),
+            ListComposition::Union(
+                list("a"),
+                Box::new(ListComposition::Intersection(list("b"), list("c")))
+            )
+        );
+        assert_eq!(parse("(a + b) & c").to_string(), "(a + b) & c");
+        assert_eq!(parse("a - (b - c)").to_string(), "a - (b - c)");
+        assert_eq!(parse("a - b - c").to_string(), "a - b - c");
+        assert_eq!(parse("a - b - c").lists(), vec!["a", "b", "c"]);
+
+        for invalid in ["", "a +", "+ a", "a b", "(a + b", "a + b)", "a | b"] {
+            let error = invalid.parse::<ListComposition>().unwrap_err().to_string();
+            assert!(
+                error.contains("Invalid list composition"),
+                "{}: {}",
+                invalid,
+                error
+            );
+        }
+    }
+
+    #[test]
+    fn test_evaluate_composition() {
+        let composition = parse("vendor_feed + internal_blocks - allowlist");
+        let members =
+            |lists: &'static [&'static str]| move |list_id: &str| lists.contains(&list_id);
+        assert!(composition.evaluate(&members(&["vendor_feed"])));
+        assert!(composition.evaluate(&members(&["internal_blocks"])));
+        assert!(!composition.evaluate(&members(&["internal_blocks", "allowlist"])));
+        assert!(!composition.evaluate(&members(&[])));
+        assert!(parse("a & b").evaluate(&members(&["a", "b"])));
+        assert!(!parse("a & b").evaluate(&members(&["a"])));
+    }
+
+    #[test]
+    fn test_resolve_compositions() {
+        let lists: HashSet<String> = ["a", "b", "c"].iter().map(|s| s.to_string()).collect();
+        let compositions: HashMap<String, ListComposition> = [
+            ("ab", "a + b"),
+            ("abc", "ab - c"),
+            ("loop1", "a + loop2"),
+            ("loop2", "loop1 & b"),
+            ("dangling", "a + missing"),
+            ("a", "b + c"),
+        ]
+        .iter()
+        .map(|(id, s)| (id.to_string(), parse(s)))
+        .collect();
+
+        let (resolved, errors) = ListComposition::resolve_all(&compositions, &lists);
+        assert_eq!(resolved["abc"], parse("(a + b) - c"));
+        assert_eq!(resolved.len(), 2);
+
+        let failed: Vec<&str> = errors.iter().map(|(id, _)| id.as_str()).collect();
+        assert_eq!(failed, vec!["a", "dangling", "loop1", "loop2"]);
+        assert!(errors[2].1.to_string().contains("loop1 -> loop2 -> loop1"));
+        assert!(errors[1].1.to_string().contains("unknown list 'missing'"));
+    }
+}
@@ -1,5 +1,7 @@
 //! List configuration structures
 
+use super::source::{ListFileFormat, ListFileOptions};
+use crate::error::Result;
 use serde::{Deserialize, Serialize};
 
 /// List configuration
@@ -20,6 +22,10 @@ pub struct ListConfig {
     #[serde(default)]
     pub backend: Option<ListBackendType>,
 
+    /// How values are matched against the entries
+    #[serde(default, rename = "type")]
+    pub list_type: ListType,
+
     /// PostgreSQL configuration (when backend = postgresql)
     #[serde(flatten)]
     pub postgres_config: Option<PostgresListConfig>,
@@ -43,6 +49,23 @@ pub struct ListConfig {
     /// Cache TTL in seconds (optional)
     #[serde(default)]
     pub cache_ttl: Option<u64>,
+
+    /// Target false-positive rate of a Bloom filter list (default 0.001)
+    #[serde(default)]
+    pub false_positive_rate: Option<f64>,
+
+    /// List confirming the positives of a Bloom filter list with an exact lookup
+    #[serde(default)]
+    pub fallback: Option<Box<ListConfig>>,
+
+    /// Scheduled synchronization from an external source
+    #[serde(default)]
+    pub sync: Option<ListSyncConfig>,
+
+    /// Set expression over other lists (e.g. `vendor_feed + internal_blocks - allowlist`)
+    /// defining a list without entries of its own
+    #[serde(default)]
+    pub compose: Option<String>,
 }
 
 /// Backend type
@@ -57,6 +80,24 @@ pub enum ListBackendType {
     Sqlite,
 }
 
+/// How list entries match values
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ListType {
+    /// Values equal to an entry
+    #[default]
+    Exact,
+
+    /// IP addresses within a network, address or range entry
+    Cidr,
+
+    /// Values matching a glob or regular expression entry
+    Pattern,
+
+    /// Values probably in a file list, checked with a Bloom filter
+    Bloom,
+}
+
 /// PostgreSQL list configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PostgresListConfig {
@@ -72,6 +113,10 @@ pub struct PostgresListConfig {
     #[serde(default)]
     pub expiration_column: Option<String>,
 
+    /// Column for the reason an entry was added (optional)
+    #[serde(default)]
+    pub reason_column: Option<String>,
+
     /// Match type (exact, prefix, regex)
     #[serde(default)]
     pub match_type: Option<String>,
@@ -95,6 +140,10 @@ pub struct SqliteListConfig {
     /// Expiration column name (optional, defaults to "expires_at")
     #[serde(default)]
     pub expiration_column: Option<String>,
+
+    /// Column for the reason an entry was added (optional)
+    #[serde(default)]
+    pub reason_column: Option<String>,
 }
 
 /// Redis list configuration
@@ -112,21 +161,97 @@ pub struct RedisListConfig {
 /// File list configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FileListConfig {
-    /// Path to the list file
+    /// Path to the list file: a local path, an `http(s)://` URL or an
+    /// `s3://bucket/key` object
     #[serde(default)]
     pub path: Option<String>,
 
-    /// Reload interval in seconds
+    /// Reload interval in seconds; the file is only re-read when it changed
     #[serde(default)]
     pub reload_interval: Option<u64>,
 
-    /// File format (txt, csv, json)
+    /// File format (txt, csv, json, parquet, or auto to use the extension)
     #[serde(default = "default_file_format")]
     pub format: String,
+
+    /// Column holding the values (csv, json objects, parquet)
+    #[serde(default)]
+    pub column: Option<String>,
+
+    /// Whether a CSV file starts with a header row (default true)
+    #[serde(default)]
+    pub header: Option<bool>,
+
+    /// AWS region of an S3 list file
+    #[serde(default)]
+    pub region: Option<String>,
+
+    /// Endpoint of an S3-compatible store (MinIO, R2, ...)
+    #[serde(default)]
+    pub endpoint: Option<String>,
 }
 
 fn default_file_format() -> String {
-    "txt".to_string()
+    "auto".to_string()
+}
+
+/// List sync configuration
+///
+/// Entries are pulled either from a file (`path`) or from the first column
+/// of a query against a datasource (`datasource` and `query`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ListSyncConfig {
+    /// List file to pull: a local path, an `http(s)://` URL or an
+    /// `s3://bucket/key` object
+    #[serde(default)]
+    pub path: Option<String>,
+
+    /// File format (txt, csv, json, parquet, or auto to use the extension)
+    #[serde(default = "default_file_format")]
+    pub format: String,
+
+    /// Column holding the values (csv, json objects, parquet)
+    #[serde(default)]
+    pub column: Option<String>,
+
+    /// Whether a CSV file starts with a header row (default true)
+    #[serde(default)]
+    pub header: Option<bool>,
+
+    /// AWS region of an S3 list file
+    #[serde(default)]
+    pub region: Option<String>,
+
+    /// Endpoint of an S3-compatible store (MinIO, R2, ...)
+    #[serde(default)]
+    pub endpoint: Option<String>,
+
+    /// Datasource the query runs against (sqlite or postgresql)
+    #[serde(default)]
+    pub datasource: Option<String>,
+
+    /// Query whose first column holds the entries
+    #[serde(default)]
+    pub query: Option<String>,
+
+    /// Seconds between syncs (default 3600)
+    #[serde(default)]
+    pub interval: Option<u64>,
+
+    /// Apply a source without entries instead of keeping the current ones
+    #[serde(default)]
+    pub allow_empty: bool,
+}
+
+impl ListSyncConfig {
+    /// Get the parsing options of the sync file
+    pub fn file_options(&self) -> Result<ListFileOptions> {
+        let path = self.path.as_deref().unwrap_or_default();
+        let mut options = ListFileOptions::new(ListFileFormat::resolve(&self.format, path)?)
+            .with_header(self.header.unwrap_or(true));
+        options.column = self.column.clone();
+        Ok(options)
+    }
 }
 
 /// Container for multiple list configurations
@@ -159,6 +284,12 @@ impl ListConfig {
             .or_else(|| self.postgres_config.as_ref().and_then(|c| c.expiration_column.clone()))
     }
 
+    /// Get the reason column name (generic, tries to get from any backend config)
+    pub fn reason_column(&self) -> Option<String> {
+        self.sqlite_config.as_ref().and_then(|c| c.reason_column.clone())
+            .or_else(|| self.postgres_config.as_ref().and_then(|c| c.reason_column.clone()))
+    }
+
     /// Get the default table name for PostgreSQL backend
     pub fn postgres_table(&self) -> String {
         self.postgres_config
@@ -195,6 +326,25 @@ impl ListConfig {
             .and_then(|c| c.reload_interval)
     }
 
+    /// Get how often a CIDR or pattern list's matcher is rebuilt from its backend (in seconds)
+    ///
+    /// File lists are rebuilt at their reload interval, other backends at the cache TTL.
+    pub fn index_refresh_interval(&self) -> Option<u64> {
+        self.file_reload_interval().or(self.cache_ttl)
+    }
+
+    /// Get the parsing options for File backend
+    pub fn file_options(&self) -> Result<ListFileOptions> {
+        let Some(config) = &self.file_config else {
+            return Ok(ListFileOptions::default());
+        };
+        let path = config.path.as_deref().unwrap_or_default();
+        let mut options = ListFileOptions::new(ListFileFormat::resolve(&config.format, path)?)
+            .with_header(config.header.unwrap_or(true));
+        options.column = config.column.clone();
+        Ok(options)
+    }
+
     /// Get the database path for SQLite backend
     pub fn sqlite_db_path(&self) -> Option<String> {
         self.sqlite_config.as_ref().and_then(|c| c.db_path.clone())
@@ -243,9 +393,65 @@ initial_values:
         let config: ListConfig = serde_yaml::from_str(yaml).unwrap();
         assert_eq!(config.id, "test_list");
         assert_eq!(config.backend, Some(ListBackendType::Memory));
+        assert_eq!(config.list_type, ListType::Exact);
         assert_eq!(config.initial_values.len(), 2);
     }
 
+    #[test]
+    fn test_parse_cidr_list() {
+        let yaml = r#"
+id: datacenter_ips
+type: cidr
+backend: file
+path: "configs/lists/data/datacenters.txt"
+reload_interval: 600
+"#;
+
+        let config: ListConfig = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(config.list_type, ListType::Cidr);
+        assert_eq!(config.index_refresh_interval(), Some(600));
+    }
+
+    #[test]
+    fn test_parse_bloom_list() {
+        let yaml = r#"
+id: breached_passwords
+type: bloom
+backend: file
+path: "s3://risk-data/lists/breached_sha1.txt"
+false_positive_rate: 0.0001
+fallback:
+  id: breached_passwords
+  backend: postgresql
+  table: breached_hashes
+"#;
+
+        let config: ListConfig = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(config.list_type, ListType::Bloom);
+        assert_eq!(config.false_positive_rate, Some(0.0001));
+        let fallback = config.fallback.as_ref().unwrap();
+        assert_eq!(fallback.backend, Some(ListBackendType::PostgreSQL));
+        assert_eq!(fallback.postgres_table(), "breached_hashes");
+    }
+
+    #[test]
+    fn test_parse_synced_list() {
+        let yaml = r#"
+id: tor_exit_nodes
+backend: memory
+sync:
+  path: "https://check.torproject.org/torbulkexitlist"
+  format: txt
+  interval: 1800
+"#;
+
+        let config: ListConfig = serde_yaml::from_str(yaml).unwrap();
+        let sync = config.sync.as_ref().unwrap();
+        assert_eq!(sync.interval, Some(1800));
+        assert!(!sync.allow_empty);
+        assert_eq!(sync.file_options().unwrap(), ListFileOptions::default());
+    }
+
     #[test]
     fn test_parse_postgresql_list() {
         let yaml = r#"
@@ -279,6 +485,27 @@ reload_interval: 3600
             Some("repository/configs/lists/data/countries.txt".to_string())
         );
         assert_eq!(config.file_reload_interval(), Some(3600));
+        assert_eq!(config.file_options().unwrap(), ListFileOptions::default());
+    }
+
+    #[test]
+    fn test_parse_bulk_file_list() {
+        let yaml = r#"
+id: blocked_cards
+backend: file
+path: "s3://risk-data/lists/blocked_cards.csv"
+column: card_hash
+region: eu-west-1
+reload_interval: 300
+"#;
+
+        let config: ListConfig = serde_yaml::from_str(yaml).unwrap();
+        let file_config = config.file_config.as_ref().unwrap();
+        assert_eq!(file_config.region.as_deref(), Some("eu-west-1"));
+        assert_eq!(
+            config.file_options().unwrap(),
+            ListFileOptions::new(ListFileFormat::Csv).with_column("card_hash")
+        );
     }
 
     #[test]
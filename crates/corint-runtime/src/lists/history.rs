@@ -0,0 +1,345 @@
+//! List versions and point-in-time lookups
+//!
+//! Every change made to a list through the [`ListService`] (additions,
+//! removals and syncs) is appended to the list's history and bumps its
+//! version. From the history, [`ListService::contains_at`] answers whether
+//! a value was listed at an earlier time, so that replayed and backtested
+//! decisions see the lists as they were when the original event happened.
+//!
+//! The history is kept in memory and capped per list; a lookup older than
+//! the oldest retained change falls back to the current entries. Changes
+//! made to the underlying store directly (another process writing the
+//! database, a reloaded list file) are not recorded.
+//!
+//! A [`ListLookupScope`] is bound to the task executing a request. It holds
+//! the time lists are looked up at, if not now, and collects the version of
+//! every list consulted, for the decision trace, and optionally a
+//! [`ListHit`] for every lookup that found its value, for auditing.
+//!
+//! [`ListService`]: super::ListService
+//! [`ListService::contains_at`]: super::ListService::contains_at
+
+use super::service::ListService;
+use chrono::{DateTime, Utc};
+use corint_core::Value;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+
+/// Default number of changes kept per list
+pub const DEFAULT_HISTORY_LIMIT: usize = 10_000;
+
+tokio::task_local! {
+    static CURRENT_SCOPE: Arc<ListLookupScope>;
+}
+
+/// Kind of change made to a list
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ListChange {
+    /// The value was listed, until `expires_at` if set
+    Added {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        expires_at: Option<DateTime<Utc>>,
+    },
+
+    /// The value was removed
+    Removed,
+}
+
+/// One recorded change of a list
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ListMutation {
+    /// List version after the change
+    pub version: u64,
+
+    /// When the change was made
+    pub at: DateTime<Utc>,
+
+    /// Changed value
+    pub value: Value,
+
+    /// What happened to the value
+    #[serde(flatten)]
+    pub change: ListChange,
+}
+
+/// Changes of one list, oldest first
+#[derive(Debug, Clone)]
+pub struct ListHistory {
+    version: u64,
+    mutations: VecDeque<ListMutation>,
+    limit: usize,
+
+    /// Time of the newest change dropped to respect `limit`
+    truncated_at: Option<DateTime<Utc>>,
+}
+
+impl ListHistory {
+    /// Create an empty history keeping at most `limit` changes
+    pub fn new(limit: usize) -> Self {
+        Self {
+            version: 0,
+            mutations: VecDeque::new(),
+            limit: limit.max(1),
+            truncated_at: None,
+        }
+    }
+
+    /// Current version: the number of changes made since the list was loaded
+    pub fn version(&self) -> u64 {
+        self.version
+    }
+
+    /// Retained changes, oldest first
+    pub fn mutations(&self) -> impl Iterator<Item = &ListMutation> {
+        self.mutations.iter()
+    }
+
+    /// Whether every change made after `at` is retained
+    pub fn covers(&self, at: DateTime<Utc>) -> bool {
+        self.truncated_at
+            .is_none_or(|truncated_at| truncated_at <= at)
+    }
+
+    /// Version of the list at `at`
+    ///
+    /// Changes dropped from the history are assumed to be older than `at`.
+    pub fn version_at(&self, at: DateTime<Utc>) -> u64 {
+        let later = self
+            .mutations
+            .iter()
+            .rev()
+            .take_while(|m| m.at > at)
+            .count();
+        self.version - later as u64
+    }
+
+    /// Append a change, returning the new version
+    pub fn record(&mut self, value: Value, change: ListChange, at: DateTime<Utc>) -> u64 {
+        self.version += 1;
+        self.mutations.push_back(ListMutation {
+            version: self.version,
+            at,
+            value,
+            change,
+        });
+        while self.mutations.len() > self.limit {
+            if let Some(dropped) = self.mutations.pop_front() {
+                self.truncated_at = Some(dropped.at);
+            }
+        }
+        self.version
+    }
+
+    /// Whether the values for which `matches` holds were listed at `at`
+    ///
+    /// Covers the values whose state at `at` may differ from their current
+    /// one: those changed after `at` and those set to expire after it.
+    /// Keyed by [`ListService::entry_key`]. A value last changed at or before
+    /// `at` was listed if it was added and not yet expired; a value only
+    /// changed after `at` was listed if its first later change removed it.
+    pub fn listed_at(
+        &self,
+        at: DateTime<Utc>,
+        matches: impl Fn(&Value) -> bool,
+    ) -> HashMap<String, bool> {
+        // Last change at or before `at` and first change after it, per value
+        let mut changes: HashMap<String, (Option<&ListChange>, Option<&ListChange>)> =
+            HashMap::new();
+        let mut unmatched = HashSet::new();
+        for mutation in &self.mutations {
+            let key = ListService::entry_key(&mutation.value);
+            if unmatched.contains(&key) {
+                continue;
+            }
+            if !changes.contains_key(&key) && !matches(&mutation.value) {
+                unmatched.insert(key);
+                continue;
+            }
+            let (before, after) = changes.entry(key).or_default();
+            if mutation.at <= at {
+                *before = Some(&mutation.change);
+            } else if after.is_none() {
+                *after = Some(&mutation.change);
+            }
+        }
+
+        changes
+            .into_iter()
+            .filter_map(|(key, (before, after))| {
+                let listed = match (before, after) {
+                    (Some(ListChange::Added { expires_at }), after) => {
+                        if after.is_none() && expires_at.is_none() {
+                            return None;
+                        }
+                        expires_at.is_none_or(|expires_at| expires_at > at)
+                    }
+                    (Some(ListChange::Removed), Some(_)) => false,
+                    (None, Some(after)) => *after == ListChange::Removed,
+                    _ => return None,
+                };
+                Some((key, listed))
+            })
+            .collect()
+    }
+}
+
+impl Default for ListHistory {
+    fn default() -> Self {
+        Self::new(DEFAULT_HISTORY_LIMIT)
+    }
+}
+
+/// A lookup that found its value in a list
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ListHit {
+    /// The list ID
+    pub list_id: String,
+
+    /// Value found in the list
+    pub value: Value,
+
+    /// Type of the program that looked the value up ("rule", "ruleset" or "pipeline")
+    pub source_type: String,
+
+    /// ID of the rule, ruleset or pipeline that looked the value up
+    pub source_id: String,
+
+    /// Whether the condition was `not in list`, so the hit made it false
+    #[serde(default)]
+    pub negated: bool,
+
+    /// List version the lookup saw
+    pub version: u64,
+
+    /// Time the list was looked up at
+    pub at: DateTime<Utc>,
+}
+
+/// List lookups made for one request
+#[derive(Debug, Default)]
+pub struct ListLookupScope {
+    as_of: Option<DateTime<Utc>>,
+    consulted: Mutex<BTreeMap<String, u64>>,
+    audit_hits: bool,
+    hits: Mutex<Vec<ListHit>>,
+}
+
+impl ListLookupScope {
+    /// Look lists up at `as_of`, or at the current time when `None`
+    pub fn new(as_of: Option<DateTime<Utc>>) -> Self {
+        Self {
+            as_of,
+            ..Default::default()
+        }
+    }
+
+    /// Collect a [`ListHit`] for every lookup that finds its value
+    pub fn with_hit_audit(mut self, audit_hits: bool) -> Self {
+        self.audit_hits = audit_hits;
+        self
+    }
+
+    /// Whether hits are collected
+    pub fn audits_hits(&self) -> bool {
+        self.audit_hits
+    }
+
+    /// Record a hit, if hits are collected
+    pub fn record_hit(&self, hit: ListHit) {
+        if self.audit_hits {
+            self.hits.lock().unwrap().push(hit);
+        }
+    }
+
+    /// Hits collected so far, in lookup order
+    pub fn hits(&self) -> Vec<ListHit> {
+        self.hits.lock().unwrap().clone()
+    }
+
+    /// Time lists are looked up at, if not now
+    pub fn as_of(&self) -> Option<DateTime<Utc>> {
+        self.as_of
+    }
+
+    /// Record that version `version` of `list_id` was consulted
+    pub fn record(&self, list_id: &str, version: u64) {
+        self.consulted
+            .lock()
+            .unwrap()
+            .insert(list_id.to_string(), version);
+    }
+
+    /// Consulted lists and their versions, by list ID
+    pub fn consulted(&self) -> Vec<(String, u64)> {
+        self.consulted
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(list_id, version)| (list_id.clone(), *version))
+            .collect()
+    }
+
+    /// Run `future` with this scope active
+    pub async fn scope<F: Future>(self: Arc<Self>, future: F) -> F::Output {
+        CURRENT_SCOPE.scope(self, future).await
+    }
+}
+
+/// Lookup scope bound to the current task, if any
+pub fn current_scope() -> Option<Arc<ListLookupScope>> {
+    CURRENT_SCOPE.try_with(Arc::clone).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn s(value: &str) -> Value {
+        Value::String(value.to_string())
+    }
+
+    #[test]
+    fn test_list_history() {
+        let t = |secs: i64| DateTime::<Utc>::from_timestamp(1_700_000_000 + secs, 0).unwrap();
+        let added = ListChange::Added { expires_at: None };
+
+        let mut history = ListHistory::new(3);
+        assert_eq!(history.record(s("u1"), added.clone(), t(10)), 1);
+        history.record(
+            s("u2"),
+            ListChange::Added {
+                expires_at: Some(t(25)),
+            },
+            t(10),
+        );
+        history.record(s("u1"), ListChange::Removed, t(20));
+        assert_eq!(history.version(), 3);
+        assert_eq!(history.version_at(t(15)), 2);
+        assert_eq!(history.version_at(t(5)), 0);
+
+        let listed = |at, value: &str| {
+            let key = value.to_string();
+            history
+                .listed_at(at, |v| ListService::entry_key(v) == key)
+                .get(value)
+                .copied()
+        };
+        assert_eq!(listed(t(15), "u1"), Some(true));
+        assert_eq!(listed(t(5), "u1"), Some(false));
+        // Not changed since
+        assert_eq!(listed(t(20), "u1"), None);
+        assert_eq!(listed(t(5), "u2"), Some(false));
+        // Listed until it expired
+        assert_eq!(listed(t(20), "u2"), Some(true));
+        assert_eq!(listed(t(25), "u2"), Some(false));
+
+        // Only the last three changes are kept
+        history.record(s("u3"), added, t(30));
+        assert_eq!(history.mutations().count(), 3);
+        assert!(history.covers(t(10)));
+        assert!(!history.covers(t(5)));
+    }
+}
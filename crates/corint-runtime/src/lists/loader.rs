@@ -1,12 +1,23 @@
 //! List configuration loader
 
-use super::backend::{FileBackend, ListBackend, MemoryBackend, SqliteBackend};
-use super::config::{ListBackendType, ListConfig, ListsConfig};
+use super::backend::bloom::DEFAULT_FALSE_POSITIVE_RATE;
+use super::backend::{
+    BloomBackend, CidrBackend, FileBackend, ListBackend, MemoryBackend, PatternBackend, SqliteBackend,
+};
+use super::compose::ListComposition;
+use super::config::{ListBackendType, ListConfig, ListType, ListsConfig};
+use super::service::ListService;
+use super::source::ListSource;
+use super::sync::{ListSync, ListSyncSource};
+use super::transfer::{
+    self, ListFieldMapping, ListImportError, ListImportOptions, ListImportReport, ListTransferFormat,
+};
 use crate::error::{Result, RuntimeError};
 use corint_core::Value;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::fs;
 
 #[cfg(feature = "sqlx")]
@@ -87,11 +98,200 @@ impl ListLoader {
     pub async fn load_all(&self) -> Result<HashMap<String, Box<dyn ListBackend>>> {
         let mut backends = HashMap::new();
 
+        for path in self.config_files().await? {
+            match self.load_from_file(&path).await {
+                Ok(mut file_backends) => {
+                    backends.extend(file_backends.drain());
+                }
+                Err(e) => {
+                    tracing::error!("Failed to load list config from {:?}: {}", path, e);
+                }
+            }
+        }
+
+        tracing::info!("Loaded {} list backends", backends.len());
+        Ok(backends)
+    }
+
+    /// Load the syncs of all lists with a `sync` section
+    ///
+    /// Lists whose sync is misconfigured are logged and skipped.
+    pub async fn load_syncs(&self) -> Result<Vec<ListSync>> {
+        let mut syncs = Vec::new();
+
+        for path in self.config_files().await? {
+            let configs = match Self::read_configs(&path).await {
+                Ok(configs) => configs,
+                Err(e) => {
+                    tracing::error!("Failed to load list config from {:?}: {}", path, e);
+                    continue;
+                }
+            };
+            for config in configs {
+                match self.create_sync(&config) {
+                    Ok(Some(sync)) => syncs.push(sync),
+                    Ok(None) => {}
+                    Err(e) => {
+                        tracing::error!("Failed to create sync for list '{}': {}", config.id, e);
+                    }
+                }
+            }
+        }
+
+        Ok(syncs)
+    }
+
+    /// Load the definitions of all lists with a `compose` expression
+    ///
+    /// Definitions that fail to parse are logged and skipped. The lists they
+    /// reference are resolved by [`ListService::with_compositions`].
+    pub async fn load_compositions(&self) -> Result<HashMap<String, ListComposition>> {
+        let mut compositions = HashMap::new();
+
+        for path in self.config_files().await? {
+            let configs = match Self::read_configs(&path).await {
+                Ok(configs) => configs,
+                Err(e) => {
+                    tracing::error!("Failed to load list config from {:?}: {}", path, e);
+                    continue;
+                }
+            };
+            for config in configs {
+                let Some(expression) = &config.compose else {
+                    continue;
+                };
+                match expression.parse::<ListComposition>() {
+                    Ok(composition) => {
+                        compositions.insert(config.id, composition);
+                    }
+                    Err(e) => {
+                        tracing::error!("Failed to load composed list '{}': {}", config.id, e);
+                    }
+                }
+            }
+        }
+
+        Ok(compositions)
+    }
+
+    /// Import the entries of a CSV or JSON Lines file into list `list_id`
+    ///
+    /// Every row is validated, including against the list's backend, before
+    /// the list is changed; if any row is invalid, or on a dry run, the list
+    /// is left untouched and the report lists the rejected rows.
+    pub async fn import_entries(
+        &self,
+        service: &ListService,
+        list_id: &str,
+        data: &[u8],
+        options: &ListImportOptions,
+    ) -> Result<ListImportReport> {
+        let (entries, mut errors) = transfer::parse_entries(data, options.format, &options.mapping)?;
+        let rows = entries.len() + errors.len();
+        for (line, entry) in &entries {
+            if let Err(e) = service.validate_entry(list_id, &entry.value).await {
+                errors.push(ListImportError {
+                    line: *line,
+                    message: e.to_string(),
+                });
+            }
+        }
+        errors.sort_by_key(|error| error.line);
+
+        let mut report = ListImportReport {
+            list_id: list_id.to_string(),
+            rows,
+            errors,
+            applied: false,
+            imported: 0,
+            removed: 0,
+        };
+        if !report.is_valid() || options.dry_run {
+            return Ok(report);
+        }
+
+        if options.replace {
+            let values: HashSet<String> = entries
+                .iter()
+                .map(|(_, entry)| ListService::entry_key(&entry.value))
+                .collect();
+            (_, report.removed) = service.replace_all(list_id, &values).await?;
+        }
+        for (_, entry) in entries {
+            report.imported += 1;
+            // Values added by the replace still need their metadata
+            if options.replace && entry.expires_at.is_none() && entry.reason.is_none() {
+                continue;
+            }
+            service.add_entry(list_id, entry).await?;
+        }
+        report.applied = true;
+
+        tracing::info!(
+            "Imported {} entries into list '{}' ({} removed)",
+            report.imported,
+            list_id,
+            report.removed
+        );
+        Ok(report)
+    }
+
+    /// Import a list file, relative to the base directory, into list `list_id`
+    pub async fn import_file<P: AsRef<Path>>(
+        &self,
+        service: &ListService,
+        list_id: &str,
+        path: P,
+        options: &ListImportOptions,
+    ) -> Result<ListImportReport> {
+        let path = self.base_dir.join(path);
+        let data = fs::read(&path)
+            .await
+            .map_err(|e| RuntimeError::InvalidOperation(format!("Failed to read list file {:?}: {}", path, e)))?;
+        self.import_entries(service, list_id, &data, options).await
+    }
+
+    /// Export the unexpired entries of list `list_id` as a CSV or JSON Lines file
+    pub async fn export_entries(
+        &self,
+        service: &ListService,
+        list_id: &str,
+        format: ListTransferFormat,
+        mapping: &ListFieldMapping,
+    ) -> Result<Vec<u8>> {
+        let entries = service.get_entries(list_id).await?;
+        transfer::write_entries(&entries, format, mapping)
+    }
+
+    /// Export list `list_id` to a file relative to the base directory
+    ///
+    /// Returns the number of entries written.
+    pub async fn export_file<P: AsRef<Path>>(
+        &self,
+        service: &ListService,
+        list_id: &str,
+        path: P,
+        format: ListTransferFormat,
+        mapping: &ListFieldMapping,
+    ) -> Result<usize> {
+        let path = self.base_dir.join(path);
+        let entries = service.get_entries(list_id).await?;
+        let data = transfer::write_entries(&entries, format, mapping)?;
+        fs::write(&path, data)
+            .await
+            .map_err(|e| RuntimeError::InvalidOperation(format!("Failed to write list file {:?}: {}", path, e)))?;
+        Ok(entries.len())
+    }
+
+    /// YAML files in the lists directory
+    async fn config_files(&self) -> Result<Vec<PathBuf>> {
+        let mut files = Vec::new();
+
         // Find all YAML files in the lists directory
         let lists_dir = self.base_dir.join("configs/lists");
         if !lists_dir.exists() {
             tracing::warn!("Lists directory does not exist: {:?}", lists_dir);
-            return Ok(backends);
+            return Ok(files);
         }
 
         let mut entries = fs::read_dir(&lists_dir)
@@ -107,47 +307,47 @@ impl ListLoader {
             if path.extension().and_then(|s| s.to_str()) == Some("yaml")
                 || path.extension().and_then(|s| s.to_str()) == Some("yml")
             {
-                match self.load_from_file(&path).await {
-                    Ok(mut file_backends) => {
-                        backends.extend(file_backends.drain());
-                    }
-                    Err(e) => {
-                        tracing::error!("Failed to load list config from {:?}: {}", path, e);
-                    }
-                }
+                files.push(path);
             }
         }
 
-        tracing::info!("Loaded {} list backends", backends.len());
-        Ok(backends)
+        Ok(files)
     }
 
     /// Load list configurations from a single file
     async fn load_from_file(&self, path: &Path) -> Result<HashMap<String, Box<dyn ListBackend>>> {
+        let mut backends = HashMap::new();
+        for config in Self::read_configs(path).await? {
+            // Composed lists have no backend; see `load_compositions`
+            if config.compose.is_some() {
+                continue;
+            }
+            match self.create_backend(config).await {
+                Ok(backend) => {
+                    backends.insert(backend.0, backend.1);
+                }
+                Err(e) => {
+                    tracing::error!("Failed to create backend: {}", e);
+                }
+            }
+        }
+        Ok(backends)
+    }
+
+    /// Parse the list configurations of a file, holding one list or a `lists` array
+    async fn read_configs(path: &Path) -> Result<Vec<ListConfig>> {
         let content = fs::read_to_string(path)
             .await
             .map_err(|e| RuntimeError::InvalidOperation(format!("Failed to read file: {}", e)))?;
 
         // Try parsing as ListsConfig first (multiple lists)
         if let Ok(lists_config) = serde_yaml::from_str::<ListsConfig>(&content) {
-            let mut backends = HashMap::new();
-            for config in lists_config.lists {
-                match self.create_backend(config).await {
-                    Ok(backend) => {
-                        backends.insert(backend.0, backend.1);
-                    }
-                    Err(e) => {
-                        tracing::error!("Failed to create backend: {}", e);
-                    }
-                }
-            }
-            return Ok(backends);
+            return Ok(lists_config.lists);
         }
 
         // Try parsing as single ListConfig
         if let Ok(config) = serde_yaml::from_str::<ListConfig>(&content) {
-            let backend = self.create_backend(config).await?;
-            return Ok(vec![backend].into_iter().collect());
+            return Ok(vec![config]);
         }
 
         Err(RuntimeError::InvalidOperation(format!(
@@ -156,8 +356,124 @@ impl ListLoader {
         )))
     }
 
-    /// Create a backend from configuration
+    /// Create the sync of a list, if it has a `sync` section
+    fn create_sync(&self, config: &ListConfig) -> Result<Option<ListSync>> {
+        let Some(sync_config) = &config.sync else {
+            return Ok(None);
+        };
+
+        let source = match (&sync_config.path, &sync_config.datasource, &sync_config.query) {
+            (Some(path), None, None) => ListSyncSource::File {
+                source: ListSource::parse(path, &self.base_dir)?
+                    .with_s3_options(sync_config.region.clone(), sync_config.endpoint.clone()),
+                options: sync_config.file_options()?,
+            },
+            (None, Some(datasource_name), Some(query)) => {
+                let datasource = self.datasources.get(datasource_name).ok_or_else(|| {
+                    RuntimeError::InvalidOperation(format!(
+                        "Datasource '{}' not found for list '{}'",
+                        datasource_name, config.id
+                    ))
+                })?;
+                ListSyncSource::Sql {
+                    provider: datasource.provider.clone(),
+                    connection_string: datasource.connection_string.clone(),
+                    query: query.clone(),
+                }
+            }
+            _ => {
+                return Err(RuntimeError::InvalidOperation(format!(
+                    "Sync of list '{}' needs either 'path' or 'datasource' and 'query'",
+                    config.id
+                )))
+            }
+        };
+
+        let mut sync =
+            ListSync::new(config.id.clone(), source).with_allow_empty(sync_config.allow_empty);
+        if let Some(interval) = sync_config.interval {
+            sync = sync.with_interval(Duration::from_secs(interval.max(1)));
+        }
+        Ok(Some(sync))
+    }
+
+    /// Create a backend from configuration, matching values as the list type requires
     async fn create_backend(&self, config: ListConfig) -> Result<(String, Box<dyn ListBackend>)> {
+        match config.list_type {
+            ListType::Exact => self.create_entry_backend(config).await,
+            ListType::Cidr => {
+                let refresh_interval = config.index_refresh_interval();
+                let (list_id, backend) = self.create_entry_backend(config).await?;
+                let mut backend = CidrBackend::load(list_id.clone(), backend).await?;
+                if let Some(refresh_interval) = refresh_interval {
+                    backend = backend.with_refresh(refresh_interval);
+                }
+                Ok((list_id, Box::new(backend)))
+            }
+            ListType::Pattern => {
+                let refresh_interval = config.index_refresh_interval();
+                let (list_id, backend) = self.create_entry_backend(config).await?;
+                let mut backend = PatternBackend::load(list_id.clone(), backend).await?;
+                if let Some(refresh_interval) = refresh_interval {
+                    backend = backend.with_refresh(refresh_interval);
+                }
+                Ok((list_id, Box::new(backend)))
+            }
+            ListType::Bloom => self.create_bloom_backend(config).await,
+        }
+    }
+
+    /// Create a Bloom filter backend for a file list, with its fallback list
+    async fn create_bloom_backend(&self, config: ListConfig) -> Result<(String, Box<dyn ListBackend>)> {
+        if config.backend != Some(ListBackendType::File) {
+            return Err(RuntimeError::InvalidOperation(format!(
+                "Bloom filter list '{}' requires the file backend",
+                config.id
+            )));
+        }
+
+        let false_positive_rate = config
+            .false_positive_rate
+            .unwrap_or(DEFAULT_FALSE_POSITIVE_RATE);
+        if !(false_positive_rate > 0.0 && false_positive_rate < 1.0) {
+            return Err(RuntimeError::InvalidOperation(format!(
+                "Bloom filter list '{}' needs a false_positive_rate between 0 and 1",
+                config.id
+            )));
+        }
+
+        let mut backend = BloomBackend::from_source(
+            self.file_source(&config)?,
+            config.file_options()?,
+            false_positive_rate,
+        );
+        if let Some(fallback) = config.fallback.clone() {
+            // Boxed: the fallback is created like any other list
+            let (fallback_id, fallback_backend) = Box::pin(self.create_backend(*fallback)).await?;
+            backend = backend.with_fallback(fallback_id, fallback_backend);
+        }
+        if let Some(reload_interval) = config.file_reload_interval() {
+            backend = backend.with_reload(reload_interval);
+        }
+
+        backend.load().await?;
+        Ok((config.id, Box::new(backend)))
+    }
+
+    /// Source of a file list; local paths are resolved relative to repository root
+    fn file_source(&self, config: &ListConfig) -> Result<ListSource> {
+        let file_path = config
+            .file_path()
+            .ok_or_else(|| RuntimeError::InvalidOperation("File backend requires 'path' field".to_string()))?;
+
+        Ok(ListSource::parse(&file_path, &self.base_dir)?.with_s3_options(
+            config.file_config.as_ref().and_then(|c| c.region.clone()),
+            config.file_config.as_ref().and_then(|c| c.endpoint.clone()),
+        ))
+    }
+
+    /// Create the backend holding a list's entries
+    async fn create_entry_backend(&self, config: ListConfig) -> Result<(String, Box<dyn ListBackend>)> {
         let list_id = config.id.clone();
 
         // Check if using datasource reference
@@ -186,22 +502,12 @@ impl ListLoader {
             }
 
             ListBackendType::File => {
-                let file_path = config
-                    .file_path()
-                    .ok_or_else(|| RuntimeError::InvalidOperation("File backend requires 'path' field".to_string()))?;
+                let source = self.file_source(&config)?;
 
-                // Resolve path relative to repository root
-                let full_path = if Path::new(&file_path).is_absolute() {
-                    PathBuf::from(file_path)
-                } else {
-                    self.base_dir.join(&file_path)
-                };
-
-                let backend = if let Some(reload_interval) = config.file_reload_interval() {
-                    FileBackend::new_with_reload(full_path, reload_interval)
-                } else {
-                    FileBackend::new(full_path)
-                };
+                let mut backend = FileBackend::from_source(source, config.file_options()?);
+                if let Some(reload_interval) = config.file_reload_interval() {
+                    backend = backend.with_reload(reload_interval);
+                }
 
                 // Load initial data
                 backend.load().await?;
@@ -223,7 +529,8 @@ impl ListLoader {
                     )
                 } else {
                     PostgresBackend::new(Arc::clone(pool))
-                };
+                }
+                .with_reason_column(config.reason_column());
 
                 Box::new(backend)
             }
@@ -269,7 +576,8 @@ impl ListLoader {
                     config.sqlite_table(),
                     config.sqlite_value_column(),
                     config.sqlite_expiration_column(),
-                );
+                )
+                .with_reason_column(config.reason_column());
 
                 Box::new(backend)
             }
@@ -315,7 +623,8 @@ impl ListLoader {
                     table,
                     value_column,
                     expiration_column,
-                );
+                )
+                .with_reason_column(config.reason_column());
 
                 Box::new(backend)
             }
@@ -340,7 +649,8 @@ impl ListLoader {
                     table,
                     value_column,
                     expiration_column,
-                );
+                )
+                .with_reason_column(config.reason_column());
 
                 Box::new(backend)
             }
@@ -368,6 +678,7 @@ impl ListLoader {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::lists::ListService;
     use tempfile::TempDir;
 
     #[tokio::test]
@@ -401,6 +712,191 @@ lists:
             .unwrap());
     }
 
+    #[tokio::test]
+    async fn test_load_cidr_list() {
+        let temp_dir = TempDir::new().unwrap();
+        let lists_dir = temp_dir.path().join("configs/lists");
+        fs::create_dir_all(&lists_dir).await.unwrap();
+
+        let yaml = r#"
+id: datacenter_ips
+type: cidr
+backend: memory
+initial_values:
+  - "203.0.113.0/24"
+  - "2001:db8::/32"
+"#;
+        fs::write(lists_dir.join("cidr.yaml"), yaml).await.unwrap();
+
+        let loader = ListLoader::new(temp_dir.path());
+        let backends = loader.load_all().await.unwrap();
+
+        let backend = backends.get("datacenter_ips").unwrap();
+        assert!(backend
+            .contains("datacenter_ips", &Value::String("203.0.113.77".to_string()))
+            .await
+            .unwrap());
+        assert!(!backend
+            .contains("datacenter_ips", &Value::String("203.0.114.1".to_string()))
+            .await
+            .unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_import_and_export_entries() {
+        let temp_dir = TempDir::new().unwrap();
+        let lists_dir = temp_dir.path().join("configs/lists");
+        fs::create_dir_all(&lists_dir).await.unwrap();
+
+        let yaml = r#"
+id: datacenter_ips
+type: cidr
+backend: memory
+initial_values:
+  - "198.51.100.0/24"
+"#;
+        fs::write(lists_dir.join("cidr.yaml"), yaml).await.unwrap();
+
+        let loader = ListLoader::new(temp_dir.path());
+        let service = ListService::new_with_backends(loader.load_all().await.unwrap());
+        let ip = |s: &str| Value::String(s.to_string());
+
+        // An invalid network rejects the whole file
+        let csv = "network,note\n203.0.113.0/24,hosting\n203.0.113.0/33,typo\n";
+        let mapping = ListFieldMapping::default()
+            .with_value("network")
+            .with_reason("note");
+        let options = ListImportOptions::new(ListTransferFormat::Csv)
+            .with_mapping(mapping.clone())
+            .with_replace(true);
+        let report = loader
+            .import_entries(&service, "datacenter_ips", csv.as_bytes(), &options)
+            .await
+            .unwrap();
+        assert_eq!(report.rows, 2);
+        assert!(!report.applied);
+        assert_eq!(report.errors.len(), 1);
+        assert_eq!(report.errors[0].line, 3);
+
+        // A dry run validates without importing
+        let csv = "network,note\n203.0.113.0/24,hosting\n";
+        let dry_run = options.clone().with_dry_run(true);
+        let report = loader
+            .import_entries(&service, "datacenter_ips", csv.as_bytes(), &dry_run)
+            .await
+            .unwrap();
+        assert!(report.is_valid());
+        assert!(!report.applied);
+        assert!(!service.contains("datacenter_ips", &ip("203.0.113.9")).await.unwrap());
+
+        fs::write(temp_dir.path().join("hosting.csv"), csv).await.unwrap();
+        let report = loader
+            .import_file(&service, "datacenter_ips", "hosting.csv", &options)
+            .await
+            .unwrap();
+        assert!(report.applied);
+        assert_eq!((report.imported, report.removed), (1, 1));
+        assert!(service.contains("datacenter_ips", &ip("203.0.113.9")).await.unwrap());
+        assert!(!service.contains("datacenter_ips", &ip("198.51.100.9")).await.unwrap());
+
+        let exported = loader
+            .export_entries(&service, "datacenter_ips", ListTransferFormat::Csv, &mapping)
+            .await
+            .unwrap();
+        assert_eq!(
+            String::from_utf8(exported).unwrap(),
+            "network,expires_at,note\n203.0.113.0/24,,hosting\n"
+        );
+        let count = loader
+            .export_file(
+                &service,
+                "datacenter_ips",
+                "export.jsonl",
+                ListTransferFormat::Jsonl,
+                &ListFieldMapping::default(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(count, 1);
+        assert_eq!(
+            fs::read_to_string(temp_dir.path().join("export.jsonl")).await.unwrap(),
+            "{\"reason\":\"hosting\",\"value\":\"203.0.113.0/24\"}\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_load_pattern_list() {
+        let temp_dir = TempDir::new().unwrap();
+        let lists_dir = temp_dir.path().join("configs/lists");
+        fs::create_dir_all(&lists_dir).await.unwrap();
+
+        let yaml = r#"
+id: disposable_emails
+type: pattern
+backend: memory
+initial_values:
+  - "*@*.temp-mail.*"
+  - "/@(mailinator|guerrillamail)\\.com$/"
+"#;
+        fs::write(lists_dir.join("pattern.yaml"), yaml).await.unwrap();
+
+        let loader = ListLoader::new(temp_dir.path());
+        let backends = loader.load_all().await.unwrap();
+
+        let backend = backends.get("disposable_emails").unwrap();
+        let email = |s: &str| Value::String(s.to_string());
+        assert!(backend
+            .contains("disposable_emails", &email("a@x.temp-mail.org"))
+            .await
+            .unwrap());
+        assert!(backend
+            .contains("disposable_emails", &email("a@mailinator.com"))
+            .await
+            .unwrap());
+        assert!(!backend
+            .contains("disposable_emails", &email("a@example.com"))
+            .await
+            .unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_load_bloom_list() {
+        let temp_dir = TempDir::new().unwrap();
+        let lists_dir = temp_dir.path().join("configs/lists");
+        fs::create_dir_all(lists_dir.join("data")).await.unwrap();
+        fs::write(lists_dir.join("data/hashes.txt"), "h1\nh2\nh3\n")
+            .await
+            .unwrap();
+
+        let yaml = r#"
+lists:
+  - id: breached
+    type: bloom
+    backend: file
+    path: "configs/lists/data/hashes.txt"
+    false_positive_rate: 0.01
+    fallback:
+      id: breached_exact
+      backend: memory
+      initial_values: ["h1", "h2"]
+  - id: invalid_bloom
+    type: bloom
+    backend: memory
+"#;
+        fs::write(lists_dir.join("bloom.yaml"), yaml).await.unwrap();
+
+        let loader = ListLoader::new(temp_dir.path());
+        let backends = loader.load_all().await.unwrap();
+        assert!(!backends.contains_key("invalid_bloom"));
+
+        let backend = backends.get("breached").unwrap();
+        let value = |s: &str| Value::String(s.to_string());
+        assert!(backend.contains("breached", &value("h1")).await.unwrap());
+        // Not confirmed by the fallback list
+        assert!(!backend.contains("breached", &value("h3")).await.unwrap());
+        assert!(!backend.contains("breached", &value("h4")).await.unwrap());
+    }
+
     #[tokio::test]
     async fn test_load_file_list() {
         let temp_dir = TempDir::new().unwrap();
@@ -435,4 +931,99 @@ path: "configs/lists/data/values.txt"
             .await
             .unwrap());
     }
+
+    #[tokio::test]
+    async fn test_load_synced_list() {
+        let temp_dir = TempDir::new().unwrap();
+        let lists_dir = temp_dir.path().join("configs/lists");
+        fs::create_dir_all(lists_dir.join("data")).await.unwrap();
+        fs::write(lists_dir.join("data/tor.txt"), "1.2.3.4\n5.6.7.8\n")
+            .await
+            .unwrap();
+
+        let yaml = r#"
+lists:
+  - id: tor_exit_nodes
+    backend: memory
+    sync:
+      path: "configs/lists/data/tor.txt"
+      interval: 600
+  - id: static_list
+    backend: memory
+  - id: broken_sync
+    backend: memory
+    sync:
+      interval: 600
+"#;
+        fs::write(lists_dir.join("lists.yaml"), yaml).await.unwrap();
+
+        let loader = ListLoader::new(temp_dir.path());
+        let service = ListService::new_with_backends(loader.load_all().await.unwrap());
+        let syncs = loader.load_syncs().await.unwrap();
+        assert_eq!(syncs.len(), 1);
+        assert_eq!(syncs[0].list_id(), "tor_exit_nodes");
+        assert_eq!(syncs[0].interval(), Duration::from_secs(600));
+
+        let report = syncs[0].run(&service).await.unwrap().unwrap();
+        assert_eq!(report.added, 2);
+        assert!(service
+            .contains("tor_exit_nodes", &Value::String("5.6.7.8".to_string()))
+            .await
+            .unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_load_composed_lists() {
+        let temp_dir = TempDir::new().unwrap();
+        let lists_dir = temp_dir.path().join("configs/lists");
+        fs::create_dir_all(&lists_dir).await.unwrap();
+
+        let yaml = r#"
+lists:
+  - id: vendor_feed
+    backend: memory
+    initial_values: ["10.0.0.1", "10.0.0.2"]
+  - id: internal_blocks
+    type: cidr
+    backend: memory
+    initial_values: ["192.168.0.0/16"]
+  - id: allowlist
+    backend: memory
+    initial_values: ["10.0.0.2", "192.168.1.1"]
+  - id: global_blocklist
+    compose: "vendor_feed + internal_blocks - allowlist"
+  - id: partner_blocklist
+    compose: "global_blocklist & vendor_feed"
+  - id: broken
+    compose: "vendor_feed + missing"
+"#;
+        fs::write(lists_dir.join("lists.yaml"), yaml).await.unwrap();
+
+        let loader = ListLoader::new(temp_dir.path());
+        let compositions = loader.load_compositions().await.unwrap();
+        assert_eq!(compositions.len(), 3);
+        let service = ListService::new_with_backends(loader.load_all().await.unwrap())
+            .with_compositions(compositions)
+            .await;
+        let ip = |s: &str| Value::String(s.to_string());
+
+        assert!(service.has_list("global_blocklist").await);
+        assert!(!service.has_list("broken").await);
+        assert!(service.contains("global_blocklist", &ip("10.0.0.1")).await.unwrap());
+        assert!(service.contains("global_blocklist", &ip("192.168.7.7")).await.unwrap());
+        assert!(!service.contains("global_blocklist", &ip("10.0.0.2")).await.unwrap());
+        assert!(!service.contains("global_blocklist", &ip("192.168.1.1")).await.unwrap());
+        assert!(!service.contains("partner_blocklist", &ip("192.168.7.7")).await.unwrap());
+        assert_eq!(
+            service.get_all("partner_blocklist").await.unwrap(),
+            vec![ip("10.0.0.1")]
+        );
+
+        // Composed lists follow their members and cannot be changed directly
+        service.remove("allowlist", &ip("10.0.0.2")).await.unwrap();
+        assert!(service.contains("global_blocklist", &ip("10.0.0.2")).await.unwrap());
+        assert_eq!(service.version("global_blocklist"), 1);
+        let error = service.add("global_blocklist", ip("1.1.1.1")).await.unwrap_err();
+        assert!(error.to_string().contains("composed of other lists"));
+    }
 }
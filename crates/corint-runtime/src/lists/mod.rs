@@ -1,17 +1,42 @@
 //! List management module
 //!
 //! Provides functionality for managing and querying blocklists, allowlists,
-//! and watchlists.
+//! and watchlists, with exact, IP network (CIDR), wildcard/regex pattern or
+//! approximate (Bloom filter) matching, composing lists from other lists,
+//! keeping them in sync with external sources, importing and exporting
+//! them as CSV or JSON Lines, and looking them up as they were at an
+//! earlier time.
 
 pub mod backend;
+pub mod bloom;
+pub mod cidr;
+pub mod compose;
 pub mod config;
+pub mod history;
 pub mod loader;
+pub mod pattern;
 pub mod service;
+pub mod source;
+pub mod sync;
+pub mod transfer;
 
 #[cfg(test)]
 mod tests;
 
-pub use backend::{FileBackend, ListBackend, MemoryBackend, PostgresBackend};
-pub use config::{ListBackendType, ListConfig, ListsConfig};
+pub use backend::{
+    BloomBackend, CidrBackend, FileBackend, ListBackend, ListEntry, MemoryBackend, PatternBackend,
+    PostgresBackend,
+};
+pub use bloom::BloomFilter;
+pub use cidr::{CidrTrie, IpNetwork};
+pub use compose::ListComposition;
+pub use config::{ListBackendType, ListConfig, ListType, ListsConfig};
+pub use history::{ListChange, ListHistory, ListHit, ListLookupScope, ListMutation};
 pub use loader::ListLoader;
+pub use pattern::{ListPattern, PatternSet};
 pub use service::ListService;
+pub use source::{ListFileFormat, ListFileOptions, ListSource, S3Location};
+pub use sync::{ListSync, ListSyncReport, ListSyncSource, ListSyncStatus};
+pub use transfer::{
+    ListFieldMapping, ListImportError, ListImportOptions, ListImportReport, ListTransferFormat,
+};
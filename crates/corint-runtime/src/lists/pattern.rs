@@ -0,0 +1,186 @@
+//! Wildcard and regular expression list entries
+//!
+//! Entries of `pattern` lists are globs unless written between slashes:
+//!
+//! | entry             | meaning                                          |
+//! |-------------------|--------------------------------------------------|
+//! | `*@*.temp-mail.*` | `*` is any run of characters; case is ignored    |
+//! | `user_??`         | `?` is a single character                        |
+//! | `/^acct_\d{6}$/`  | regular expression, matched as written           |
+//!
+//! A regular expression matches anywhere in the value unless anchored; add
+//! `(?i)` to ignore case. All patterns of a list are compiled into a single
+//! [`regex::RegexSet`], so a lookup is one pass over the value regardless of
+//! the number of entries.
+
+use crate::error::{Result, RuntimeError};
+use regex::{Regex, RegexSet};
+use std::fmt;
+use std::str::FromStr;
+
+/// A pattern entry
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ListPattern {
+    /// Wildcard pattern matching the whole value, ignoring case
+    Glob(String),
+
+    /// Regular expression
+    Regex(String),
+}
+
+impl ListPattern {
+    /// Regular expression equivalent to the pattern
+    pub fn to_regex(&self) -> String {
+        match self {
+            Self::Glob(glob) => {
+                let mut regex = String::from("(?is)^");
+                for c in glob.chars() {
+                    match c {
+                        '*' => regex.push_str(".*"),
+                        '?' => regex.push('.'),
+                        c => regex.push_str(&regex::escape(c.encode_utf8(&mut [0; 4]))),
+                    }
+                }
+                regex.push('$');
+                regex
+            }
+            Self::Regex(regex) => regex.clone(),
+        }
+    }
+}
+
+impl FromStr for ListPattern {
+    type Err = RuntimeError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let s = s.trim();
+        if s.is_empty() {
+            return Err(RuntimeError::InvalidValue(
+                "List pattern must not be empty".to_string(),
+            ));
+        }
+
+        let pattern = match s
+            .strip_prefix('/')
+            .and_then(|rest| rest.strip_suffix('/'))
+            .filter(|regex| !regex.is_empty())
+        {
+            Some(regex) => Self::Regex(regex.to_string()),
+            None => Self::Glob(s.to_string()),
+        };
+        Regex::new(&pattern.to_regex()).map_err(|e| {
+            RuntimeError::InvalidValue(format!("Invalid list pattern '{}': {}", s, e))
+        })?;
+        Ok(pattern)
+    }
+}
+
+impl fmt::Display for ListPattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Glob(glob) => write!(f, "{}", glob),
+            Self::Regex(regex) => write!(f, "/{}/", regex),
+        }
+    }
+}
+
+/// Compiled patterns of a list
+#[derive(Debug, Clone)]
+pub struct PatternSet {
+    set: RegexSet,
+    patterns: Vec<ListPattern>,
+}
+
+impl PatternSet {
+    /// Compile `patterns` into one matcher
+    pub fn new(patterns: Vec<ListPattern>) -> Result<Self> {
+        let set = RegexSet::new(patterns.iter().map(ListPattern::to_regex)).map_err(|e| {
+            RuntimeError::InvalidOperation(format!("Failed to compile list patterns: {}", e))
+        })?;
+        Ok(Self { set, patterns })
+    }
+
+    /// Whether any pattern matches `value`
+    pub fn is_match(&self, value: &str) -> bool {
+        self.set.is_match(value)
+    }
+
+    /// Patterns matching `value`
+    pub fn matches(&self, value: &str) -> Vec<&ListPattern> {
+        self.set
+            .matches(value)
+            .into_iter()
+            .map(|i| &self.patterns[i])
+            .collect()
+    }
+
+    /// Number of patterns
+    pub fn len(&self) -> usize {
+        self.patterns.len()
+    }
+
+    /// Check if there are no patterns
+    pub fn is_empty(&self) -> bool {
+        self.patterns.is_empty()
+    }
+}
+
+impl Default for PatternSet {
+    fn default() -> Self {
+        Self {
+            set: RegexSet::empty(),
+            patterns: Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn set(entries: &[&str]) -> PatternSet {
+        PatternSet::new(entries.iter().map(|e| e.parse().unwrap()).collect()).unwrap()
+    }
+
+    #[test]
+    fn test_parse_pattern() {
+        assert_eq!(
+            "*.temp-mail.*".parse::<ListPattern>().unwrap(),
+            ListPattern::Glob("*.temp-mail.*".to_string())
+        );
+        assert_eq!(
+            " /^acct_\\d+$/ ".parse::<ListPattern>().unwrap(),
+            ListPattern::Regex("^acct_\\d+$".to_string())
+        );
+        // A lone slash is a glob
+        assert_eq!(
+            "/".parse::<ListPattern>().unwrap(),
+            ListPattern::Glob("/".to_string())
+        );
+        assert!("/(unclosed/".parse::<ListPattern>().is_err());
+        assert!("".parse::<ListPattern>().is_err());
+        assert_eq!("/^a$/".parse::<ListPattern>().unwrap().to_string(), "/^a$/");
+    }
+
+    #[test]
+    fn test_pattern_set() {
+        let patterns = set(&["*@*.temp-mail.*", "user_??", "/^acct_\\d{6}$/", "a.b"]);
+        assert_eq!(patterns.len(), 4);
+
+        assert!(patterns.is_match("Bob@inbox.Temp-Mail.org"));
+        assert!(!patterns.is_match("bob@temp-mail.org"));
+        assert!(patterns.is_match("user_42"));
+        assert!(!patterns.is_match("user_420"));
+        assert!(patterns.is_match("acct_123456"));
+        assert!(!patterns.is_match("ACCT_123456"));
+        // Glob characters other than * and ? are literal
+        assert!(patterns.is_match("a.b"));
+        assert!(!patterns.is_match("axb"));
+
+        assert_eq!(
+            patterns.matches("user_ab"),
+            vec![&ListPattern::Glob("user_??".to_string())]
+        );
+        assert!(!PatternSet::default().is_match("anything"));
+    }
+}
@@ -1,35 +1,123 @@
 //! List service for managing lists
 
-use super::backend::ListBackend;
+use super::backend::{ListBackend, ListEntry};
+use super::compose::ListComposition;
+use super::history::{ListChange, ListHistory, DEFAULT_HISTORY_LIMIT};
+use super::sync::ListSync;
 use crate::error::{Result, RuntimeError};
+use chrono::{DateTime, Utc};
 use corint_core::Value;
-use std::collections::HashMap;
-use std::sync::Arc;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex, Weak};
+use std::time::Duration;
 use tokio::sync::RwLock;
 
+type Backends = RwLock<HashMap<String, Box<dyn ListBackend>>>;
+
 /// List service that manages list lookups across multiple backends
 pub struct ListService {
     /// Map of list_id to backend
-    backends: Arc<RwLock<HashMap<String, Box<dyn ListBackend>>>>,
+    backends: Arc<Backends>,
+
+    /// Changes made to each list through the service, by list ID
+    history: Arc<Mutex<HashMap<String, ListHistory>>>,
+
+    /// Number of changes kept per list
+    history_limit: usize,
+
+    /// Resolved definitions of the lists composed of other lists, by list ID
+    compositions: Arc<Mutex<HashMap<String, ListComposition>>>,
+}
+
+/// Service handle of a background task, which does not keep the service alive
+struct WeakListService {
+    backends: Weak<Backends>,
+    history: Weak<Mutex<HashMap<String, ListHistory>>>,
+    history_limit: usize,
+    compositions: Weak<Mutex<HashMap<String, ListComposition>>>,
+}
+
+impl WeakListService {
+    fn upgrade(&self) -> Option<ListService> {
+        Some(ListService {
+            backends: self.backends.upgrade()?,
+            history: self.history.upgrade()?,
+            history_limit: self.history_limit,
+            compositions: self.compositions.upgrade()?,
+        })
+    }
 }
 
 impl ListService {
     /// Create a new list service with memory backend
     pub fn new_with_memory() -> Self {
-        Self {
-            backends: Arc::new(RwLock::new(HashMap::new())),
-        }
+        Self::new_with_backends(HashMap::new())
     }
 
     /// Create a new list service with multiple backends
     pub fn new_with_backends(backends: HashMap<String, Box<dyn ListBackend>>) -> Self {
         Self {
             backends: Arc::new(RwLock::new(backends)),
+            history: Arc::new(Mutex::new(HashMap::new())),
+            history_limit: DEFAULT_HISTORY_LIMIT,
+            compositions: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Set the number of changes kept per list for point-in-time lookups
+    pub fn with_history_limit(mut self, history_limit: usize) -> Self {
+        self.history_limit = history_limit;
+        self
+    }
+
+    /// Add lists composed of the lists of the service, by list ID
+    ///
+    /// Compositions are resolved against the lists loaded so far and the
+    /// other compositions; a composition referencing an unknown list, part
+    /// of a cycle or reusing the ID of a list is logged and skipped.
+    pub async fn with_compositions(self, compositions: HashMap<String, ListComposition>) -> Self {
+        let lists: HashSet<String> = self.backends.read().await.keys().cloned().collect();
+        let (resolved, errors) = ListComposition::resolve_all(&compositions, &lists);
+        for (list_id, e) in errors {
+            tracing::error!("Failed to compose list '{}': {}", list_id, e);
         }
+        self.compositions.lock().unwrap().extend(resolved);
+        self
+    }
+
+    /// Resolved definition of a list composed of other lists
+    pub fn composition(&self, list_id: &str) -> Option<ListComposition> {
+        self.compositions.lock().unwrap().get(list_id).cloned()
     }
 
     /// Check if a value exists in a list
     pub async fn contains(&self, list_id: &str, value: &Value) -> Result<bool> {
+        match self.composition(list_id) {
+            Some(composition) => self.contains_composed(&composition, value, None).await,
+            None => self.contains_entry(list_id, value).await,
+        }
+    }
+
+    /// Check if a value is in a composed list, now or at `at`
+    async fn contains_composed(
+        &self,
+        composition: &ListComposition,
+        value: &Value,
+        at: Option<DateTime<Utc>>,
+    ) -> Result<bool> {
+        let mut members = HashMap::new();
+        for list_id in composition.lists() {
+            let listed = match at {
+                Some(at) => self.contains_entry_at(list_id, value, at).await?,
+                None => self.contains_entry(list_id, value).await?,
+            };
+            members.insert(list_id, listed);
+        }
+        Ok(composition.evaluate(&|list_id| members.get(list_id).copied().unwrap_or(false)))
+    }
+
+    /// Check if a value exists in a list with entries
+    async fn contains_entry(&self, list_id: &str, value: &Value) -> Result<bool> {
         let backends = self.backends.read().await;
 
         let backend = backends.get(list_id).ok_or_else(|| {
@@ -45,30 +133,141 @@ impl ListService {
         }
     }
 
+    /// Check if a value was in a list at `at`
+    ///
+    /// Entries changed through the service since `at` are evaluated as they
+    /// were at that time, from the list's history; all other entries as they
+    /// are now. When the history no longer reaches back to `at`, the current
+    /// entries are used.
+    pub async fn contains_at(
+        &self,
+        list_id: &str,
+        value: &Value,
+        at: DateTime<Utc>,
+    ) -> Result<bool> {
+        match self.composition(list_id) {
+            Some(composition) => self.contains_composed(&composition, value, Some(at)).await,
+            None => self.contains_entry_at(list_id, value, at).await,
+        }
+    }
+
+    /// Check if a value was in a list with entries at `at`
+    async fn contains_entry_at(
+        &self,
+        list_id: &str,
+        value: &Value,
+        at: DateTime<Utc>,
+    ) -> Result<bool> {
+        let backends = self.backends.read().await;
+        let Some(backend) = backends.get(list_id) else {
+            tracing::warn!("List '{}' not configured, treating as empty", list_id);
+            return Ok(false);
+        };
+
+        let listed = match self.history.lock().unwrap().get(list_id) {
+            Some(history) if history.covers(at) => {
+                history.listed_at(at, |entry| backend.matches_entry(entry, value))
+            }
+            Some(_) => {
+                tracing::warn!(
+                    "History of list '{}' does not reach back to {}, using its current entries",
+                    list_id,
+                    at
+                );
+                HashMap::new()
+            }
+            None => HashMap::new(),
+        };
+        if listed.is_empty() {
+            return backend.contains(list_id, value).await;
+        }
+        if listed.values().any(|listed| *listed) {
+            return Ok(true);
+        }
+
+        // Otherwise only a matching entry unchanged since `at` lists the value
+        if backend.matches_exactly() || !backend.contains(list_id, value).await? {
+            return Ok(false);
+        }
+        Ok(backend.get_entries(list_id).await?.iter().any(|entry| {
+            !listed.contains_key(&Self::entry_key(&entry.value))
+                && backend.matches_entry(&entry.value, value)
+        }))
+    }
+
+    /// Current version of a list: the number of changes made through the service
+    ///
+    /// The version of a composed list is the sum of its members' versions.
+    pub fn version(&self, list_id: &str) -> u64 {
+        self.versions(list_id, ListHistory::version)
+    }
+
+    /// Version of a list at `at`
+    pub fn version_at(&self, list_id: &str, at: DateTime<Utc>) -> u64 {
+        self.versions(list_id, |history| history.version_at(at))
+    }
+
+    fn versions(&self, list_id: &str, version: impl Fn(&ListHistory) -> u64) -> u64 {
+        let members = match self.composition(list_id) {
+            Some(composition) => composition.lists().into_iter().map(str::to_string).collect(),
+            None => vec![list_id.to_string()],
+        };
+        let history = self.history.lock().unwrap();
+        members
+            .iter()
+            .filter_map(|list_id| history.get(list_id))
+            .map(version)
+            .sum()
+    }
+
+    /// Recorded changes of a list, oldest first
+    pub fn history(&self, list_id: &str) -> Option<ListHistory> {
+        self.history.lock().unwrap().get(list_id).cloned()
+    }
+
+    /// Append a change of a list to its history
+    fn record(&self, list_id: &str, value: Value, change: ListChange) {
+        self.history
+            .lock()
+            .unwrap()
+            .entry(list_id.to_string())
+            .or_insert_with(|| ListHistory::new(self.history_limit))
+            .record(value, change, Utc::now());
+    }
+
     /// Add a value to a list
     pub async fn add(&self, list_id: &str, value: Value) -> Result<()> {
         let mut backends = self.backends.write().await;
 
-        let backend = backends.get_mut(list_id).ok_or_else(|| {
-            RuntimeError::InvalidOperation(format!("List '{}' not found", list_id))
-        })?;
+        let backend = backends
+            .get_mut(list_id)
+            .ok_or_else(|| self.missing_list(list_id))?;
 
-        backend.add(list_id, value).await
+        backend.add(list_id, value.clone()).await?;
+        self.record(list_id, value, ListChange::Added { expires_at: None });
+        Ok(())
     }
 
     /// Remove a value from a list
     pub async fn remove(&self, list_id: &str, value: &Value) -> Result<()> {
         let mut backends = self.backends.write().await;
 
-        let backend = backends.get_mut(list_id).ok_or_else(|| {
-            RuntimeError::InvalidOperation(format!("List '{}' not found", list_id))
-        })?;
+        let backend = backends
+            .get_mut(list_id)
+            .ok_or_else(|| self.missing_list(list_id))?;
 
-        backend.remove(list_id, value).await
+        backend.remove(list_id, value).await?;
+        self.record(list_id, value.clone(), ListChange::Removed);
+        Ok(())
     }
 
     /// Get all values in a list
     pub async fn get_all(&self, list_id: &str) -> Result<Vec<Value>> {
+        if let Some(composition) = self.composition(list_id) {
+            let entries = self.composed_entries(&composition).await?;
+            return Ok(entries.into_iter().map(|entry| entry.value).collect());
+        }
+
         let backends = self.backends.read().await;
 
         let backend = backends.get(list_id).ok_or_else(|| {
@@ -78,16 +277,219 @@ impl ListService {
         backend.get_all(list_id).await
     }
 
-    /// Get list of all configured list IDs
+    /// Add an entry with expiry and reason metadata to a list
+    pub async fn add_entry(&self, list_id: &str, entry: ListEntry) -> Result<()> {
+        let mut backends = self.backends.write().await;
+
+        let backend = backends
+            .get_mut(list_id)
+            .ok_or_else(|| self.missing_list(list_id))?;
+
+        let (value, expires_at) = (entry.value.clone(), entry.expires_at);
+        backend.add_entry(list_id, entry).await?;
+        self.record(list_id, value, ListChange::Added { expires_at });
+        Ok(())
+    }
+
+    /// Replace the values of a list with `entries`
+    ///
+    /// Only the difference is written to the backend, under the write lock, so
+    /// lookups see either the old or the new values. Returns how many values
+    /// were added and removed.
+    pub async fn replace_all(
+        &self,
+        list_id: &str,
+        entries: &HashSet<String>,
+    ) -> Result<(usize, usize)> {
+        let mut backends = self.backends.write().await;
+
+        let backend = backends
+            .get_mut(list_id)
+            .ok_or_else(|| self.missing_list(list_id))?;
+
+        let current: HashMap<String, Value> = backend
+            .get_all(list_id)
+            .await?
+            .into_iter()
+            .map(|value| (Self::entry_key(&value), value))
+            .collect();
+        let removed: Vec<&Value> = current
+            .iter()
+            .filter(|(key, _)| !entries.contains(*key))
+            .map(|(_, value)| value)
+            .collect();
+        let added: Vec<&String> = entries
+            .iter()
+            .filter(|entry| !current.contains_key(*entry))
+            .collect();
+
+        for value in &removed {
+            backend.remove(list_id, value).await?;
+            self.record(list_id, (*value).clone(), ListChange::Removed);
+        }
+        for entry in &added {
+            let value = Value::String((*entry).clone());
+            backend.add(list_id, value.clone()).await?;
+            self.record(list_id, value, ListChange::Added { expires_at: None });
+        }
+        Ok((added.len(), removed.len()))
+    }
+
+    /// Check that `value` can be added to a list, without adding it
+    pub async fn validate_entry(&self, list_id: &str, value: &Value) -> Result<()> {
+        let backends = self.backends.read().await;
+
+        let backend = backends
+            .get(list_id)
+            .ok_or_else(|| self.missing_list(list_id))?;
+
+        backend.validate_entry(value)
+    }
+
+    /// String form of a list value, as entries from files and queries are read
+    pub fn entry_key(value: &Value) -> String {
+        match value {
+            Value::String(s) => s.clone(),
+            Value::Number(n) => n.to_string(),
+            Value::Bool(b) => b.to_string(),
+            other => serde_json::to_string(other).unwrap_or_default(),
+        }
+    }
+
+    /// Get the unexpired entries of a list with their metadata
+    ///
+    /// The entries of a composed list are the entries of its members that
+    /// the composition contains, the first of each value.
+    pub async fn get_entries(&self, list_id: &str) -> Result<Vec<ListEntry>> {
+        if let Some(composition) = self.composition(list_id) {
+            return self.composed_entries(&composition).await;
+        }
+        self.backend_entries(list_id).await
+    }
+
+    async fn backend_entries(&self, list_id: &str) -> Result<Vec<ListEntry>> {
+        let backends = self.backends.read().await;
+
+        let backend = backends.get(list_id).ok_or_else(|| {
+            RuntimeError::InvalidOperation(format!("List '{}' not found", list_id))
+        })?;
+
+        backend.get_entries(list_id).await
+    }
+
+    async fn composed_entries(&self, composition: &ListComposition) -> Result<Vec<ListEntry>> {
+        let mut seen = HashSet::new();
+        let mut entries = Vec::new();
+        for list_id in composition.lists() {
+            for entry in self.backend_entries(list_id).await? {
+                if !seen.insert(Self::entry_key(&entry.value)) {
+                    continue;
+                }
+                if self.contains_composed(composition, &entry.value, None).await? {
+                    entries.push(entry);
+                }
+            }
+        }
+        Ok(entries)
+    }
+
+    /// Error for a change of a list without a backend
+    fn missing_list(&self, list_id: &str) -> RuntimeError {
+        if self.composition(list_id).is_some() {
+            return RuntimeError::InvalidOperation(format!(
+                "List '{}' is composed of other lists and cannot be changed directly",
+                list_id
+            ));
+        }
+        RuntimeError::InvalidOperation(format!("List '{}' not found", list_id))
+    }
+
+    /// Delete expired entries from every list, returning how many were deleted
+    ///
+    /// A list that fails to purge is logged and skipped.
+    pub async fn purge_expired(&self) -> usize {
+        let mut purged = 0;
+        for list_id in self.list_ids().await {
+            let mut backends = self.backends.write().await;
+            let Some(backend) = backends.get_mut(&list_id) else {
+                continue;
+            };
+            match backend.purge_expired(&list_id).await {
+                Ok(count) => purged += count,
+                Err(e) => {
+                    tracing::warn!("Failed to purge expired entries of list '{}': {}", list_id, e)
+                }
+            }
+        }
+        purged
+    }
+
+    /// Purge expired entries every `interval` until the service is dropped
+    pub fn spawn_expiry_sweep(&self, interval: Duration) -> tokio::task::JoinHandle<()> {
+        let service = self.downgrade();
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            ticker.tick().await;
+            loop {
+                ticker.tick().await;
+                let Some(service) = service.upgrade() else {
+                    break;
+                };
+                let purged = service.purge_expired().await;
+                if purged > 0 {
+                    tracing::debug!("Purged {} expired list entries", purged);
+                }
+            }
+        })
+    }
+
+    /// Run `sync` now and then at its interval until the service is dropped
+    pub fn spawn_sync(&self, sync: Arc<ListSync>) -> tokio::task::JoinHandle<()> {
+        let service = self.downgrade();
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(sync.interval());
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            loop {
+                ticker.tick().await;
+                let Some(service) = service.upgrade() else {
+                    break;
+                };
+                match sync.run(&service).await {
+                    Ok(Some(report)) if report.added + report.removed > 0 => tracing::info!(
+                        "Synced list '{}' to version {}: {} added, {} removed",
+                        sync.list_id(),
+                        report.version,
+                        report.added,
+                        report.removed
+                    ),
+                    Ok(_) => {}
+                    Err(e) => tracing::warn!("Failed to sync list '{}': {}", sync.list_id(), e),
+                }
+            }
+        })
+    }
+
+    fn downgrade(&self) -> WeakListService {
+        WeakListService {
+            backends: Arc::downgrade(&self.backends),
+            history: Arc::downgrade(&self.history),
+            history_limit: self.history_limit,
+            compositions: Arc::downgrade(&self.compositions),
+        }
+    }
+
+    /// Get list of all configured list IDs, including composed lists
     pub async fn list_ids(&self) -> Vec<String> {
         let backends = self.backends.read().await;
-        backends.keys().cloned().collect()
+        let compositions = self.compositions.lock().unwrap();
+        backends.keys().chain(compositions.keys()).cloned().collect()
     }
 
     /// Check if a list is configured
     pub async fn has_list(&self, list_id: &str) -> bool {
         let backends = self.backends.read().await;
-        backends.contains_key(list_id)
+        backends.contains_key(list_id) || self.composition(list_id).is_some()
     }
 }
 
@@ -95,6 +497,9 @@ impl Clone for ListService {
     fn clone(&self) -> Self {
         Self {
             backends: Arc::clone(&self.backends),
+            history: Arc::clone(&self.history),
+            history_limit: self.history_limit,
+            compositions: Arc::clone(&self.compositions),
         }
     }
 }
@@ -160,4 +565,84 @@ mod tests {
         assert!(list_ids.contains(&"list1".to_string()));
         assert!(list_ids.contains(&"list2".to_string()));
     }
+
+    #[tokio::test]
+    async fn test_list_service_entry_expiry() {
+        let mut backends: HashMap<String, Box<dyn ListBackend>> = HashMap::new();
+        backends.insert("blocked".to_string(), Box::new(MemoryBackend::new()));
+        let service = ListService::new_with_backends(backends);
+        let user = |s: &str| Value::String(s.to_string());
+
+        service
+            .add_entry(
+                "blocked",
+                ListEntry::new(user("u1"))
+                    .with_ttl(Duration::from_secs(86400))
+                    .with_reason("manual review"),
+            )
+            .await
+            .unwrap();
+        service
+            .add_entry(
+                "blocked",
+                ListEntry::new(user("u2"))
+                    .with_expires_at(chrono::Utc::now() - chrono::Duration::seconds(1)),
+            )
+            .await
+            .unwrap();
+        assert!(service.add_entry("missing", ListEntry::new(user("u3"))).await.is_err());
+
+        assert!(service.contains("blocked", &user("u1")).await.unwrap());
+        assert!(!service.contains("blocked", &user("u2")).await.unwrap());
+        let entries = service.get_entries("blocked").await.unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].reason.as_deref(), Some("manual review"));
+
+        assert_eq!(service.purge_expired().await, 1);
+        assert_eq!(service.purge_expired().await, 0);
+    }
+
+    #[tokio::test]
+    async fn test_list_service_contains_at() {
+        let mut backends: HashMap<String, Box<dyn ListBackend>> = HashMap::new();
+        backends.insert("blocked".to_string(), Box::new(MemoryBackend::new()));
+        let service = ListService::new_with_backends(backends);
+        let user = |s: &str| Value::String(s.to_string());
+
+        service.add("blocked", user("u1")).await.unwrap();
+        let before = chrono::Utc::now();
+        tokio::time::sleep(Duration::from_millis(5)).await;
+        service.add("blocked", user("u2")).await.unwrap();
+        service.remove("blocked", &user("u1")).await.unwrap();
+        assert_eq!(service.version("blocked"), 3);
+        assert_eq!(service.version_at("blocked", before), 1);
+
+        assert!(service.contains_at("blocked", &user("u1"), before).await.unwrap());
+        assert!(!service.contains_at("blocked", &user("u2"), before).await.unwrap());
+        assert!(!service.contains("blocked", &user("u1")).await.unwrap());
+        let now = chrono::Utc::now();
+        assert!(service.contains_at("blocked", &user("u2"), now).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_list_service_contains_at_cidr() {
+        let backend = crate::lists::CidrBackend::load("ips", Box::new(MemoryBackend::new()))
+            .await
+            .unwrap();
+        let mut backends: HashMap<String, Box<dyn ListBackend>> = HashMap::new();
+        backends.insert("ips".to_string(), Box::new(backend));
+        let service = ListService::new_with_backends(backends);
+        let ip = |s: &str| Value::String(s.to_string());
+
+        service.add("ips", ip("10.0.0.0/8")).await.unwrap();
+        let before = chrono::Utc::now();
+        tokio::time::sleep(Duration::from_millis(5)).await;
+        service.add("ips", ip("10.1.0.0/16")).await.unwrap();
+        service.add("ips", ip("192.0.2.0/24")).await.unwrap();
+
+        // Still listed by the older network
+        assert!(service.contains_at("ips", &ip("10.1.2.3"), before).await.unwrap());
+        assert!(!service.contains_at("ips", &ip("192.0.2.1"), before).await.unwrap());
+        assert!(service.contains("ips", &ip("192.0.2.1")).await.unwrap());
+    }
 }
@@ -0,0 +1,403 @@
+//! Scheduled list synchronization from external sources
+//!
+//! A synchronized list is pulled periodically from a list file (local path,
+//! HTTP(S) URL or S3 object, in any format of [`crate::lists::source`]) or
+//! from the first column of a SQL query. Each pull is compared with the
+//! list's current entries and only the difference is applied, under the
+//! [`ListService`] write lock, so lookups see either the old or the new
+//! contents. Unchanged sources (same ETag, file version or query result)
+//! are skipped.
+
+use super::service::ListService;
+use super::source::{ListFileOptions, ListSource};
+use crate::error::{Result, RuntimeError};
+use chrono::{DateTime, Utc};
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
+use std::fmt;
+use std::time::Duration;
+use tokio::sync::Mutex;
+
+/// Default time between syncs
+pub const DEFAULT_SYNC_INTERVAL: Duration = Duration::from_secs(3600);
+
+/// Where a synchronized list is pulled from
+#[derive(Debug, Clone)]
+pub enum ListSyncSource {
+    /// List file
+    File {
+        source: ListSource,
+        options: ListFileOptions,
+    },
+
+    /// First column of the rows returned by a query
+    Sql {
+        /// Database provider (sqlite or postgresql)
+        provider: String,
+        /// Connection string
+        connection_string: String,
+        /// Query returning the entries
+        query: String,
+    },
+}
+
+impl ListSyncSource {
+    /// Fetch the entries unless their version equals `known_version`
+    async fn fetch(
+        &self,
+        client: &reqwest::Client,
+        known_version: Option<&str>,
+    ) -> Result<Option<(HashSet<String>, String)>> {
+        match self {
+            Self::File { source, options } => {
+                let Some(fetched) = source.fetch(client, known_version).await? else {
+                    return Ok(None);
+                };
+                Ok(Some((options.parse(&fetched.data)?, fetched.version)))
+            }
+            Self::Sql {
+                provider,
+                connection_string,
+                query,
+            } => {
+                let entries = query_entries(provider, connection_string, query).await?;
+
+                // Query results carry no version; hash them in a stable order
+                let mut sorted: Vec<&String> = entries.iter().collect();
+                sorted.sort();
+                let mut hasher = Sha256::new();
+                for entry in sorted {
+                    hasher.update(entry.as_bytes());
+                    hasher.update(b"\n");
+                }
+                let version = format!("sha256:{}", hex::encode(hasher.finalize()));
+                if known_version == Some(version.as_str()) {
+                    return Ok(None);
+                }
+                Ok(Some((entries, version)))
+            }
+        }
+    }
+}
+
+impl fmt::Display for ListSyncSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::File { source, .. } => write!(f, "{}", source),
+            Self::Sql { provider, .. } => write!(f, "{} query", provider),
+        }
+    }
+}
+
+#[cfg(feature = "sqlx")]
+async fn query_entries(
+    provider: &str,
+    connection_string: &str,
+    query: &str,
+) -> Result<HashSet<String>> {
+    use sqlx::Row;
+
+    let query_error = |e: sqlx::Error| {
+        RuntimeError::InvalidOperation(format!("Failed to query list entries: {}", e))
+    };
+
+    let mut entries = HashSet::new();
+    match provider {
+        "sqlite" => {
+            let path = connection_string
+                .strip_prefix("sqlite://")
+                .or_else(|| connection_string.strip_prefix("sqlite:"))
+                .unwrap_or(connection_string);
+            let pool = sqlx::SqlitePool::connect(&format!("sqlite:{}", path))
+                .await
+                .map_err(query_error)?;
+            let rows = sqlx::query(query)
+                .fetch_all(&pool)
+                .await
+                .map_err(query_error)?;
+            pool.close().await;
+            for row in rows {
+                let value = row
+                    .try_get::<Option<String>, _>(0)
+                    .or_else(|_| {
+                        row.try_get::<Option<i64>, _>(0)
+                            .map(|v| v.map(|v| v.to_string()))
+                    })
+                    .map_err(query_error)?;
+                entries.extend(value.filter(|v| !v.trim().is_empty()));
+            }
+        }
+        "postgresql" => {
+            let pool = sqlx::PgPool::connect(connection_string)
+                .await
+                .map_err(query_error)?;
+            let rows = sqlx::query(query)
+                .fetch_all(&pool)
+                .await
+                .map_err(query_error)?;
+            pool.close().await;
+            for row in rows {
+                let value = row
+                    .try_get::<Option<String>, _>(0)
+                    .or_else(|_| {
+                        row.try_get::<Option<i64>, _>(0)
+                            .map(|v| v.map(|v| v.to_string()))
+                    })
+                    .map_err(query_error)?;
+                entries.extend(value.filter(|v| !v.trim().is_empty()));
+            }
+        }
+        provider => {
+            return Err(RuntimeError::InvalidOperation(format!(
+                "Unsupported list sync provider '{}'. Supported: sqlite, postgresql",
+                provider
+            )))
+        }
+    }
+    Ok(entries)
+}
+
+#[cfg(not(feature = "sqlx"))]
+async fn query_entries(
+    _provider: &str,
+    _connection_string: &str,
+    _query: &str,
+) -> Result<HashSet<String>> {
+    Err(RuntimeError::InvalidOperation(
+        "SQL list sync requires the 'sqlx' feature".to_string(),
+    ))
+}
+
+/// State of a list sync
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ListSyncStatus {
+    /// Number of syncs that changed the list
+    pub version: u64,
+
+    /// Version of the source content last applied
+    pub source_version: Option<String>,
+
+    /// Number of entries in the source content last applied
+    pub entries: usize,
+
+    /// When the source was last checked
+    pub last_checked: Option<DateTime<Utc>>,
+
+    /// When the list was last changed by a sync
+    pub last_changed: Option<DateTime<Utc>>,
+
+    /// Error of the last sync, if it failed
+    pub last_error: Option<String>,
+}
+
+/// Changes applied by a sync
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ListSyncReport {
+    /// Sync version after the changes
+    pub version: u64,
+
+    /// Number of entries added
+    pub added: usize,
+
+    /// Number of entries removed
+    pub removed: usize,
+}
+
+/// Periodic synchronization of one list from an external source
+pub struct ListSync {
+    list_id: String,
+    source: ListSyncSource,
+    interval: Duration,
+    allow_empty: bool,
+    client: reqwest::Client,
+
+    /// Status; also serializes concurrent syncs
+    status: Mutex<ListSyncStatus>,
+}
+
+impl ListSync {
+    /// Sync list `list_id` from `source` every hour
+    pub fn new(list_id: impl Into<String>, source: ListSyncSource) -> Self {
+        Self {
+            list_id: list_id.into(),
+            source,
+            interval: DEFAULT_SYNC_INTERVAL,
+            allow_empty: false,
+            client: reqwest::Client::new(),
+            status: Mutex::new(ListSyncStatus::default()),
+        }
+    }
+
+    /// Set the time between syncs
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Apply a source without entries instead of rejecting it
+    ///
+    /// By default an empty source, such as a truncated feed download, leaves
+    /// the list unchanged.
+    pub fn with_allow_empty(mut self, allow_empty: bool) -> Self {
+        self.allow_empty = allow_empty;
+        self
+    }
+
+    /// ID of the synchronized list
+    pub fn list_id(&self) -> &str {
+        &self.list_id
+    }
+
+    /// Time between syncs
+    pub fn interval(&self) -> Duration {
+        self.interval
+    }
+
+    /// Current status
+    pub async fn status(&self) -> ListSyncStatus {
+        self.status.lock().await.clone()
+    }
+
+    /// Pull the source and apply its changes to the list in `service`
+    ///
+    /// Returns `None` when the source is unchanged since the last sync. A
+    /// failed sync is retried in full by the next one.
+    pub async fn run(&self, service: &ListService) -> Result<Option<ListSyncReport>> {
+        let mut status = self.status.lock().await;
+        status.last_checked = Some(Utc::now());
+
+        let result = self.apply(&mut status, service).await;
+        status.last_error = result.as_ref().err().map(|e| e.to_string());
+        result
+    }
+
+    async fn apply(
+        &self,
+        status: &mut ListSyncStatus,
+        service: &ListService,
+    ) -> Result<Option<ListSyncReport>> {
+        let Some((entries, source_version)) = self
+            .source
+            .fetch(&self.client, status.source_version.as_deref())
+            .await?
+        else {
+            return Ok(None);
+        };
+        if entries.is_empty() && !self.allow_empty {
+            return Err(RuntimeError::InvalidOperation(format!(
+                "Sync source {} of list '{}' has no entries; keeping the current entries",
+                self.source, self.list_id
+            )));
+        }
+
+        let (added, removed) = service.replace_all(&self.list_id, &entries).await?;
+        if added + removed > 0 {
+            status.version += 1;
+            status.last_changed = status.last_checked;
+        }
+        status.source_version = Some(source_version);
+        status.entries = entries.len();
+        Ok(Some(ListSyncReport {
+            version: status.version,
+            added,
+            removed,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lists::backend::ListBackend;
+    use crate::lists::MemoryBackend;
+    use corint_core::Value;
+    use std::collections::HashMap;
+    use tempfile::NamedTempFile;
+
+    #[tokio::test]
+    async fn test_list_sync_from_file() {
+        let file = NamedTempFile::new().unwrap();
+        std::fs::write(file.path(), "1.2.3.4\n5.6.7.8\n").unwrap();
+
+        let mut backend = MemoryBackend::new();
+        backend
+            .add("tor_exits", Value::String("9.9.9.9".to_string()))
+            .await
+            .unwrap();
+        let mut backends: HashMap<String, Box<dyn ListBackend>> = HashMap::new();
+        backends.insert("tor_exits".to_string(), Box::new(backend));
+        let service = ListService::new_with_backends(backends);
+
+        let sync = ListSync::new(
+            "tor_exits",
+            ListSyncSource::File {
+                source: ListSource::Local(file.path().to_path_buf()),
+                options: ListFileOptions::default(),
+            },
+        );
+        let report = sync.run(&service).await.unwrap().unwrap();
+        assert_eq!(
+            report,
+            ListSyncReport {
+                version: 1,
+                added: 2,
+                removed: 1
+            }
+        );
+        let ip = |s: &str| Value::String(s.to_string());
+        assert!(service.contains("tor_exits", &ip("5.6.7.8")).await.unwrap());
+        assert!(!service.contains("tor_exits", &ip("9.9.9.9")).await.unwrap());
+
+        // Unchanged file is skipped
+        assert_eq!(sync.run(&service).await.unwrap(), None);
+
+        // An empty file is rejected and reported
+        std::fs::write(file.path(), "# feed unavailable\n").unwrap();
+        assert!(sync.run(&service).await.is_err());
+        let status = sync.status().await;
+        assert_eq!(status.version, 1);
+        assert_eq!(status.entries, 2);
+        assert!(status.last_error.is_some());
+        assert!(service.contains("tor_exits", &ip("1.2.3.4")).await.unwrap());
+
+        std::fs::write(file.path(), "1.2.3.4\n").unwrap();
+        let report = sync.run(&service).await.unwrap().unwrap();
+        assert_eq!((report.version, report.added, report.removed), (2, 0, 1));
+        assert_eq!(sync.status().await.last_error, None);
+    }
+
+    #[cfg(feature = "sqlx")]
+    #[tokio::test]
+    async fn test_list_sync_from_query() {
+        let file = NamedTempFile::new().unwrap();
+        let db_url = format!("sqlite:{}", file.path().display());
+        let pool = sqlx::SqlitePool::connect(&db_url).await.unwrap();
+        sqlx::query("CREATE TABLE chargebacks (account_id TEXT, status TEXT)")
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query("INSERT INTO chargebacks VALUES ('a1', 'confirmed'), ('a2', 'open')")
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let mut backends: HashMap<String, Box<dyn ListBackend>> = HashMap::new();
+        backends.insert("chargebacks".to_string(), Box::new(MemoryBackend::new()));
+        let service = ListService::new_with_backends(backends);
+
+        let sync = ListSync::new(
+            "chargebacks",
+            ListSyncSource::Sql {
+                provider: "sqlite".to_string(),
+                connection_string: db_url,
+                query: "SELECT account_id FROM chargebacks WHERE status = 'confirmed'".to_string(),
+            },
+        );
+        assert_eq!(sync.run(&service).await.unwrap().unwrap().added, 1);
+        assert_eq!(sync.run(&service).await.unwrap(), None);
+        assert_eq!(
+            service.get_all("chargebacks").await.unwrap(),
+            vec![Value::String("a1".to_string())]
+        );
+    }
+}
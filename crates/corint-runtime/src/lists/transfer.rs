@@ -0,0 +1,544 @@
+//! List import and export in CSV and JSON Lines
+//!
+//! Lists are exchanged with spreadsheets and scripts as files holding one
+//! entry per row, with its value and optionally its expiry and reason:
+//!
+//! ```text
+//! value,expires_at,reason
+//! acct_1001,2026-12-31T00:00:00Z,chargeback
+//! acct_1002,,manual review
+//! ```
+//!
+//! JSON Lines files hold one object per line with the same fields, or a bare
+//! value. The column or field names are set by a [`ListFieldMapping`].
+//!
+//! Every row of an import is validated before anything is written: a row
+//! without a value, with an invalid or past expiry, repeating an earlier
+//! value, or rejected by the list's backend (e.g. an invalid network in a
+//! CIDR list) makes the whole import fail with a report of the bad rows. A
+//! dry run only validates. See [`ListLoader::import_entries`].
+//!
+//! [`ListLoader::import_entries`]: super::ListLoader::import_entries
+
+use super::backend::ListEntry;
+use super::service::ListService;
+use crate::error::{Result, RuntimeError};
+use chrono::{DateTime, Utc};
+use corint_core::Value;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Format of a list import or export file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ListTransferFormat {
+    /// Comma-separated values with a header row
+    Csv,
+    /// One JSON object or value per line
+    Jsonl,
+}
+
+impl ListTransferFormat {
+    /// Parse a format name; `auto` picks the format from the file extension
+    pub fn resolve(name: &str, path: &str) -> Result<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "csv" => Ok(Self::Csv),
+            "jsonl" | "ndjson" => Ok(Self::Jsonl),
+            "auto" | "" => {
+                match Path::new(path)
+                    .extension()
+                    .and_then(|e| e.to_str())
+                    .map(|e| e.to_ascii_lowercase())
+                    .as_deref()
+                {
+                    Some("csv") => Ok(Self::Csv),
+                    Some("jsonl") | Some("ndjson") => Ok(Self::Jsonl),
+                    _ => Err(RuntimeError::InvalidOperation(format!(
+                        "Cannot tell the list file format of '{}' (expected .csv or .jsonl)",
+                        path
+                    ))),
+                }
+            }
+            other => Err(RuntimeError::InvalidOperation(format!(
+                "Unsupported list transfer format '{}' (expected csv or jsonl)",
+                other
+            ))),
+        }
+    }
+}
+
+/// Names of the columns (CSV) or fields (JSON Lines) holding entry data
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ListFieldMapping {
+    /// Listed value
+    #[serde(default = "default_value_field")]
+    pub value: String,
+
+    /// Expiry as an RFC 3339 timestamp; empty for permanent entries
+    #[serde(default = "default_expires_at_field")]
+    pub expires_at: String,
+
+    /// Why the value was listed
+    #[serde(default = "default_reason_field")]
+    pub reason: String,
+}
+
+fn default_value_field() -> String {
+    "value".to_string()
+}
+
+fn default_expires_at_field() -> String {
+    "expires_at".to_string()
+}
+
+fn default_reason_field() -> String {
+    "reason".to_string()
+}
+
+impl Default for ListFieldMapping {
+    fn default() -> Self {
+        Self {
+            value: default_value_field(),
+            expires_at: default_expires_at_field(),
+            reason: default_reason_field(),
+        }
+    }
+}
+
+impl ListFieldMapping {
+    /// Read values from `field`
+    pub fn with_value(mut self, field: impl Into<String>) -> Self {
+        self.value = field.into();
+        self
+    }
+
+    /// Read expiries from `field`
+    pub fn with_expires_at(mut self, field: impl Into<String>) -> Self {
+        self.expires_at = field.into();
+        self
+    }
+
+    /// Read reasons from `field`
+    pub fn with_reason(mut self, field: impl Into<String>) -> Self {
+        self.reason = field.into();
+        self
+    }
+}
+
+/// How a list file is imported
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ListImportOptions {
+    /// File format
+    pub format: ListTransferFormat,
+
+    /// Column or field names
+    pub mapping: ListFieldMapping,
+
+    /// Only validate the file, without changing the list
+    pub dry_run: bool,
+
+    /// Remove the entries that are not in the file
+    pub replace: bool,
+}
+
+impl ListImportOptions {
+    /// Add the entries of a file in `format` to the list
+    pub fn new(format: ListTransferFormat) -> Self {
+        Self {
+            format,
+            mapping: ListFieldMapping::default(),
+            dry_run: false,
+            replace: false,
+        }
+    }
+
+    /// Set the column or field names
+    pub fn with_mapping(mut self, mapping: ListFieldMapping) -> Self {
+        self.mapping = mapping;
+        self
+    }
+
+    /// Only validate the file
+    pub fn with_dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    /// Make the list hold exactly the entries of the file
+    pub fn with_replace(mut self, replace: bool) -> Self {
+        self.replace = replace;
+        self
+    }
+}
+
+/// A row rejected by import validation
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ListImportError {
+    /// Line of the file, starting at 1 (the CSV header is line 1)
+    pub line: usize,
+
+    /// Why the row was rejected
+    pub message: String,
+}
+
+/// Outcome of a list import
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ListImportReport {
+    /// The list ID
+    pub list_id: String,
+
+    /// Number of entry rows in the file
+    pub rows: usize,
+
+    /// Rows rejected by validation; nothing is written unless empty
+    pub errors: Vec<ListImportError>,
+
+    /// Whether the list was changed (false for dry runs and invalid files)
+    pub applied: bool,
+
+    /// Number of entries written
+    pub imported: usize,
+
+    /// Number of entries removed because they were not in the file
+    pub removed: usize,
+}
+
+impl ListImportReport {
+    /// Whether every row passed validation
+    pub fn is_valid(&self) -> bool {
+        self.errors.is_empty()
+    }
+}
+
+/// Entries of a list file with the line they were read from
+pub type LineEntries = Vec<(usize, ListEntry)>;
+
+/// Entries of a list file, by line, and the rows that could not be read
+///
+/// Rows are only checked on their own here (value present, expiry valid and
+/// in the future, value not repeated); backend checks are left to the caller.
+pub fn parse_entries(
+    data: &[u8],
+    format: ListTransferFormat,
+    mapping: &ListFieldMapping,
+) -> Result<(LineEntries, Vec<ListImportError>)> {
+    let rows = match format {
+        ListTransferFormat::Csv => read_csv_rows(data, mapping)?,
+        ListTransferFormat::Jsonl => read_jsonl_rows(data, mapping),
+    };
+
+    let now = Utc::now();
+    let mut entries = Vec::new();
+    let mut errors = Vec::new();
+    let mut first_line: HashMap<String, usize> = HashMap::new();
+    for (line, row) in rows {
+        let entry = row.and_then(|row| row.into_entry(now));
+        let result = entry.and_then(|entry| {
+            let key = ListService::entry_key(&entry.value);
+            match first_line.get(&key) {
+                Some(first) => Err(format!(
+                    "Duplicate value '{}' (first on line {})",
+                    key, first
+                )),
+                None => {
+                    first_line.insert(key, line);
+                    Ok(entry)
+                }
+            }
+        });
+        match result {
+            Ok(entry) => entries.push((line, entry)),
+            Err(message) => errors.push(ListImportError { line, message }),
+        }
+    }
+    Ok((entries, errors))
+}
+
+/// Write entries as a list file that [`parse_entries`] reads back
+pub fn write_entries(
+    entries: &[ListEntry],
+    format: ListTransferFormat,
+    mapping: &ListFieldMapping,
+) -> Result<Vec<u8>> {
+    let expires_at = |entry: &ListEntry| {
+        entry
+            .expires_at
+            .map(|at| at.to_rfc3339_opts(chrono::SecondsFormat::Secs, true))
+    };
+
+    match format {
+        ListTransferFormat::Csv => {
+            let csv_error = |e: csv::Error| {
+                RuntimeError::InvalidOperation(format!("Failed to write CSV list file: {}", e))
+            };
+            let mut writer = csv::Writer::from_writer(Vec::new());
+            writer
+                .write_record([&mapping.value, &mapping.expires_at, &mapping.reason])
+                .map_err(csv_error)?;
+            for entry in entries {
+                writer
+                    .write_record([
+                        ListService::entry_key(&entry.value),
+                        expires_at(entry).unwrap_or_default(),
+                        entry.reason.clone().unwrap_or_default(),
+                    ])
+                    .map_err(csv_error)?;
+            }
+            writer.into_inner().map_err(|e| {
+                RuntimeError::InvalidOperation(format!("Failed to write CSV list file: {}", e))
+            })
+        }
+        ListTransferFormat::Jsonl => {
+            let mut data = Vec::new();
+            for entry in entries {
+                let mut object = serde_json::Map::new();
+                object.insert(
+                    mapping.value.clone(),
+                    serde_json::to_value(&entry.value).unwrap_or_default(),
+                );
+                if let Some(expires_at) = expires_at(entry) {
+                    object.insert(mapping.expires_at.clone(), expires_at.into());
+                }
+                if let Some(reason) = &entry.reason {
+                    object.insert(mapping.reason.clone(), reason.clone().into());
+                }
+                serde_json::to_writer(&mut data, &object).map_err(|e| {
+                    RuntimeError::InvalidOperation(format!(
+                        "Failed to write JSON Lines list file: {}",
+                        e
+                    ))
+                })?;
+                data.push(b'\n');
+            }
+            Ok(data)
+        }
+    }
+}
+
+/// Fields of one row, before validation
+struct Row {
+    value: Option<Value>,
+    expires_at: Option<String>,
+    reason: Option<String>,
+}
+
+impl Row {
+    fn into_entry(self, now: DateTime<Utc>) -> std::result::Result<ListEntry, String> {
+        let value = match self.value {
+            Some(Value::String(s)) if s.trim().is_empty() => None,
+            Some(Value::String(s)) => Some(Value::String(s.trim().to_string())),
+            other => other,
+        };
+        let Some(value) = value else {
+            return Err("Missing value".to_string());
+        };
+
+        let mut entry = ListEntry::new(value);
+        if let Some(expires_at) = self.expires_at.filter(|s| !s.trim().is_empty()) {
+            let expires_at = DateTime::parse_from_rfc3339(expires_at.trim())
+                .map_err(|e| format!("Invalid expires_at '{}': {}", expires_at, e))?
+                .with_timezone(&Utc);
+            if expires_at <= now {
+                return Err(format!("Entry already expired at {}", expires_at));
+            }
+            entry = entry.with_expires_at(expires_at);
+        }
+        if let Some(reason) = self.reason.filter(|s| !s.trim().is_empty()) {
+            entry = entry.with_reason(reason.trim());
+        }
+        Ok(entry)
+    }
+}
+
+type Rows = Vec<(usize, std::result::Result<Row, String>)>;
+
+fn read_csv_rows(data: &[u8], mapping: &ListFieldMapping) -> Result<Rows> {
+    let mut reader = csv::ReaderBuilder::new()
+        .flexible(true)
+        .comment(Some(b'#'))
+        .from_reader(data);
+    let headers = reader.headers().map_err(|e| {
+        RuntimeError::InvalidOperation(format!("Failed to read CSV list file header: {}", e))
+    })?;
+    let column = |name: &str| headers.iter().position(|h| h.trim() == name);
+    let value_column = column(&mapping.value).ok_or_else(|| {
+        RuntimeError::InvalidOperation(format!("CSV list file has no column '{}'", mapping.value))
+    })?;
+    let (expires_at_column, reason_column) = (column(&mapping.expires_at), column(&mapping.reason));
+
+    let mut rows = Vec::new();
+    for record in reader.records() {
+        let row = match record {
+            Ok(record) => {
+                let line = record.position().map_or(0, |p| p.line() as usize);
+                let field =
+                    |index: Option<usize>| index.and_then(|i| record.get(i)).map(str::to_string);
+                let row = Row {
+                    value: field(Some(value_column)).map(Value::String),
+                    expires_at: field(expires_at_column),
+                    reason: field(reason_column),
+                };
+                (line, Ok(row))
+            }
+            Err(e) => {
+                let line = e.position().map_or(0, |p| p.line() as usize);
+                (line, Err(format!("Unreadable row: {}", e)))
+            }
+        };
+        rows.push(row);
+    }
+    Ok(rows)
+}
+
+fn read_jsonl_rows(data: &[u8], mapping: &ListFieldMapping) -> Rows {
+    let text = String::from_utf8_lossy(data);
+    let mut rows = Vec::new();
+    for (index, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        let text_field = |object: &serde_json::Map<String, serde_json::Value>, name: &str| {
+            match object.get(name) {
+                None | Some(serde_json::Value::Null) => Ok(None),
+                Some(serde_json::Value::String(s)) => Ok(Some(s.clone())),
+                Some(other) => Err(format!("Field '{}' must be a string, got {}", name, other)),
+            }
+        };
+        let row = serde_json::from_str::<serde_json::Value>(line)
+            .map_err(|e| format!("Invalid JSON: {}", e))
+            .and_then(|json| match json {
+                serde_json::Value::Object(object) => Ok(Row {
+                    value: object
+                        .get(&mapping.value)
+                        .map(scalar_value)
+                        .transpose()?
+                        .flatten(),
+                    expires_at: text_field(&object, &mapping.expires_at)?,
+                    reason: text_field(&object, &mapping.reason)?,
+                }),
+                json => Ok(Row {
+                    value: scalar_value(&json)?,
+                    expires_at: None,
+                    reason: None,
+                }),
+            });
+        rows.push((index + 1, row));
+    }
+    rows
+}
+
+/// Entry value of a JSON value; `None` for null
+fn scalar_value(json: &serde_json::Value) -> std::result::Result<Option<Value>, String> {
+    match json {
+        serde_json::Value::Null => Ok(None),
+        serde_json::Value::Bool(b) => Ok(Some(Value::Bool(*b))),
+        serde_json::Value::Number(n) => Ok(n.as_f64().map(Value::Number)),
+        serde_json::Value::String(s) => Ok(Some(Value::String(s.clone()))),
+        _ => Err("Value must be a string, number or boolean".to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_csv_entries() {
+        let data = "\
+account,until,note
+acct_1,2999-01-01T00:00:00Z,chargeback
+acct_2,,
+,2999-01-01T00:00:00Z,no value
+acct_3,tomorrow,
+acct_1,,again
+acct_4,2000-01-01T00:00:00Z,
+";
+        let mapping = ListFieldMapping::default()
+            .with_value("account")
+            .with_expires_at("until")
+            .with_reason("note");
+        let (entries, errors) =
+            parse_entries(data.as_bytes(), ListTransferFormat::Csv, &mapping).unwrap();
+
+        assert_eq!(entries.len(), 2);
+        let (line, entry) = &entries[0];
+        assert_eq!(*line, 2);
+        assert_eq!(entry.value, Value::String("acct_1".to_string()));
+        assert_eq!(entry.reason.as_deref(), Some("chargeback"));
+        assert!(entry.expires_at.is_some());
+        assert_eq!(
+            entries[1].1,
+            ListEntry::new(Value::String("acct_2".to_string()))
+        );
+
+        let lines: Vec<usize> = errors.iter().map(|e| e.line).collect();
+        assert_eq!(lines, vec![4, 5, 6, 7]);
+        assert!(errors[2].message.contains("first on line 2"));
+
+        let missing = parse_entries(
+            data.as_bytes(),
+            ListTransferFormat::Csv,
+            &ListFieldMapping::default(),
+        );
+        assert!(missing.is_err());
+    }
+
+    #[test]
+    fn test_jsonl_round_trip() {
+        let entries = vec![
+            ListEntry::new(Value::String("a@example.com".to_string()))
+                .with_expires_at("2999-01-01T00:00:00Z".parse().unwrap())
+                .with_reason("fraud ring"),
+            ListEntry::new(Value::Number(42.0)),
+        ];
+        let mapping = ListFieldMapping::default();
+        let data = write_entries(&entries, ListTransferFormat::Jsonl, &mapping).unwrap();
+        let (parsed, errors) = parse_entries(&data, ListTransferFormat::Jsonl, &mapping).unwrap();
+        assert!(errors.is_empty());
+        assert_eq!(
+            parsed.into_iter().map(|(_, e)| e).collect::<Vec<_>>(),
+            entries
+        );
+
+        let data = b"\"bare\"\n{\"value\": [1]}\nnot json\n\n{\"reason\": \"x\"}\n";
+        let (parsed, errors) = parse_entries(data, ListTransferFormat::Jsonl, &mapping).unwrap();
+        assert_eq!(parsed.len(), 1);
+        let lines: Vec<usize> = errors.iter().map(|e| e.line).collect();
+        assert_eq!(lines, vec![2, 3, 5]);
+    }
+
+    #[test]
+    fn test_csv_round_trip() {
+        let entries = vec![
+            ListEntry::new(Value::String("1.2.3.4".to_string())).with_reason("scanner, repeat"),
+            ListEntry::new(Value::String("5.6.7.8".to_string()))
+                .with_expires_at("2999-01-01T00:00:00Z".parse().unwrap()),
+        ];
+        let mapping = ListFieldMapping::default();
+        let data = write_entries(&entries, ListTransferFormat::Csv, &mapping).unwrap();
+        assert!(String::from_utf8_lossy(&data).starts_with("value,expires_at,reason\n"));
+        let (parsed, errors) = parse_entries(&data, ListTransferFormat::Csv, &mapping).unwrap();
+        assert!(errors.is_empty());
+        assert_eq!(
+            parsed.into_iter().map(|(_, e)| e).collect::<Vec<_>>(),
+            entries
+        );
+    }
+
+    #[test]
+    fn test_resolve_transfer_format() {
+        assert_eq!(
+            ListTransferFormat::resolve("auto", "export/blocked.JSONL").unwrap(),
+            ListTransferFormat::Jsonl
+        );
+        assert_eq!(
+            ListTransferFormat::resolve("csv", "blocked.txt").unwrap(),
+            ListTransferFormat::Csv
+        );
+        assert!(ListTransferFormat::resolve("auto", "blocked.txt").is_err());
+        assert!(ListTransferFormat::resolve("xml", "blocked.xml").is_err());
+    }
+}
@@ -0,0 +1,161 @@
+//! Memoization of pure lookups within a request
+//!
+//! Each rule of a ruleset is a separate program with its own execution
+//! context, so a feature or list referenced by ten rules would be computed or
+//! looked up ten times per decision. A [`RequestMemo`] bound to the task
+//! executing a decision with [`RequestMemo::scope`] keeps the results of the
+//! lookups that cannot change within it, and the executor answers repeated
+//! ones from it:
+//!
+//! - feature values computed on demand, including `Null` for features that
+//!   failed to compute, so a failing data source is not retried by every rule;
+//! - list lookup outcomes, by list and value;
+//! - storage feature extractions (`CallFeature`), by feature type and field;
+//! - conditions the compiler found in several rules of a ruleset, by the
+//!   shared slot it hoisted them into (`LoadSlot` / `StoreSlot`).
+//!
+//! External API and service calls are not memoized: they are not assumed to
+//! be free of side effects. Every program still sees the lookups it made, so
+//! list hits are audited and inputs captured per rule as before.
+
+use corint_core::Value;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+tokio::task_local! {
+    static CURRENT_MEMO: Arc<RequestMemo>;
+}
+
+/// A memoized lookup
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum MemoKey {
+    /// Value of a feature
+    Feature(String),
+
+    /// Whether a list contains a value, by list ID and JSON value
+    ListLookup { list_id: String, value: String },
+
+    /// Storage feature extraction, by extraction target
+    Extract(String),
+
+    /// Condition shared by several programs, by slot name
+    Slot(String),
+}
+
+impl MemoKey {
+    /// Key of the lookup of `value` in `list_id`
+    pub fn list_lookup(list_id: &str, value: &Value) -> Self {
+        MemoKey::ListLookup {
+            list_id: list_id.to_string(),
+            value: serde_json::to_string(value).unwrap_or_default(),
+        }
+    }
+
+    /// Kind of lookup, as a metric label
+    pub fn kind(&self) -> &'static str {
+        match self {
+            MemoKey::Feature(_) => "feature",
+            MemoKey::ListLookup { .. } => "list_lookup",
+            MemoKey::Extract(_) => "extract",
+            MemoKey::Slot(_) => "slot",
+        }
+    }
+}
+
+/// Lookup results of one request
+#[derive(Debug, Default)]
+pub struct RequestMemo {
+    values: Mutex<HashMap<MemoKey, Value>>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+/// Hits and misses of a [`RequestMemo`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MemoStats {
+    /// Lookups answered from the memo
+    pub hits: u64,
+
+    /// Lookups made, then memoized
+    pub misses: u64,
+}
+
+impl RequestMemo {
+    /// Create an empty memo
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Memoized result of `key`, counting a hit or a miss
+    pub fn get(&self, key: &MemoKey) -> Option<Value> {
+        let value = self.values.lock().unwrap().get(key).cloned();
+        let counter = if value.is_some() {
+            &self.hits
+        } else {
+            &self.misses
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+        value
+    }
+
+    /// Whether `key` is memoized, without counting a hit or a miss
+    pub fn contains(&self, key: &MemoKey) -> bool {
+        self.values.lock().unwrap().contains_key(key)
+    }
+
+    /// Memoize the result of `key`
+    pub fn insert(&self, key: MemoKey, value: Value) {
+        self.values.lock().unwrap().insert(key, value);
+    }
+
+    /// Hits and misses so far
+    pub fn stats(&self) -> MemoStats {
+        MemoStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Run `future` with this memo active
+    pub async fn scope<F: Future>(self: Arc<Self>, future: F) -> F::Output {
+        CURRENT_MEMO.scope(self, future).await
+    }
+}
+
+/// Memo bound to the current task, if any
+pub fn current_memo() -> Option<Arc<RequestMemo>> {
+    CURRENT_MEMO.try_with(Arc::clone).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_request_memo() {
+        assert!(current_memo().is_none());
+
+        let memo = Arc::new(RequestMemo::new());
+        Arc::clone(&memo)
+            .scope(async {
+                let memo = current_memo().unwrap();
+                let key = MemoKey::list_lookup("blocked_ips", &Value::String("1.2.3.4".into()));
+                assert_eq!(memo.get(&key), None);
+                memo.insert(key.clone(), Value::Bool(true));
+                assert_eq!(memo.get(&key), Some(Value::Bool(true)));
+                assert_eq!(
+                    memo.get(&MemoKey::list_lookup(
+                        "blocked_ips",
+                        &Value::String("5.6.7.8".into())
+                    )),
+                    None
+                );
+            })
+            .await;
+
+        assert_eq!(memo.stats(), MemoStats { hits: 1, misses: 2 });
+        assert!(!memo.contains(&MemoKey::Feature("txn_count_1h".into())));
+    }
+}
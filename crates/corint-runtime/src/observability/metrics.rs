@@ -1,5 +1,6 @@
 //! Metrics collection and reporting
 
+use super::profile::{ExecutionProfile, ProfileReport};
 use std::collections::HashMap;
 use std::sync::{Arc, RwLock};
 use std::time::Duration;
@@ -160,6 +161,7 @@ pub trait Metrics: Send + Sync {
 pub struct MetricsCollector {
     counters: Arc<RwLock<HashMap<String, Arc<Counter>>>>,
     histograms: Arc<RwLock<HashMap<String, Arc<Histogram>>>>,
+    profile: Arc<ExecutionProfile>,
 }
 
 impl MetricsCollector {
@@ -168,6 +170,7 @@ impl MetricsCollector {
         Self {
             counters: Arc::new(RwLock::new(HashMap::new())),
             histograms: Arc::new(RwLock::new(HashMap::new())),
+            profile: Arc::new(ExecutionProfile::new()),
         }
     }
 
@@ -181,6 +184,44 @@ impl MetricsCollector {
         self.histograms.read().unwrap().keys().cloned().collect()
     }
 
+    /// Get a counter of `name` for one set of labels
+    ///
+    /// Each label set is a separate counter, registered as
+    /// `name{key="value",...}`, e.g. `list_hits_total{list_id="sanctions"}`.
+    pub fn labeled_counter(&self, name: &str, labels: &[(&str, &str)]) -> Arc<Counter> {
+        let key = format!(
+            "{}{{{}}}",
+            name,
+            labels
+                .iter()
+                .map(|(key, value)| format!("{}=\"{}\"", key, value))
+                .collect::<Vec<_>>()
+                .join(",")
+        );
+        self.counters
+            .write()
+            .unwrap()
+            .entry(key)
+            .or_insert_with(|| {
+                let labels = labels
+                    .iter()
+                    .map(|(key, value)| (key.to_string(), value.to_string()))
+                    .collect();
+                Arc::new(Counter::new(name.to_string()).with_labels(labels))
+            })
+            .clone()
+    }
+
+    /// Get the execution profile, filled by executors with profiling enabled
+    pub fn profile(&self) -> &ExecutionProfile {
+        &self.profile
+    }
+
+    /// Get the execution profile, hottest instructions, rules and features first
+    pub fn profile_report(&self) -> ProfileReport {
+        self.profile.report()
+    }
+
     /// Reset all metrics
     pub fn reset_all(&self) {
         for counter in self.counters.read().unwrap().values() {
@@ -189,6 +230,7 @@ impl MetricsCollector {
         for histogram in self.histograms.read().unwrap().values() {
             histogram.reset();
         }
+        self.profile.reset();
     }
 }
 
@@ -307,6 +349,29 @@ mod tests {
         assert_eq!(histogram.count(), 1);
     }
 
+    #[test]
+    fn test_labeled_counter() {
+        let collector = MetricsCollector::new();
+
+        collector
+            .labeled_counter("list_hits_total", &[("list_id", "sanctions")])
+            .inc();
+        collector
+            .labeled_counter("list_hits_total", &[("list_id", "sanctions")])
+            .inc();
+        collector
+            .labeled_counter("list_hits_total", &[("list_id", "vip")])
+            .inc();
+
+        let counter = collector.labeled_counter("list_hits_total", &[("list_id", "sanctions")]);
+        assert_eq!(counter.get(), 2);
+        assert_eq!(counter.name(), "list_hits_total");
+        assert_eq!(counter.labels()["list_id"], "sanctions");
+        assert!(collector
+            .counter_names()
+            .contains(&"list_hits_total{list_id=\"vip\"}".to_string()));
+    }
+
     #[test]
     fn test_record_error() {
         let collector = MetricsCollector::new();
@@ -324,10 +389,16 @@ mod tests {
 
         collector.counter("c1").inc();
         collector.histogram("h1").observe(10.0);
+        collector.profile().record(
+            crate::observability::ProfileScope::Rule,
+            "r1",
+            Duration::from_millis(1),
+        );
 
         collector.reset_all();
 
         assert_eq!(collector.counter("c1").get(), 0);
         assert_eq!(collector.histogram("h1").count(), 0);
+        assert!(collector.profile_report().entries.is_empty());
     }
 }
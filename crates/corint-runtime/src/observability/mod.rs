@@ -5,9 +5,12 @@
 //! This module includes:
 //! - Custom metrics collection (Counter, Histogram)
 //! - Custom distributed tracing
+//! - Execution profiling of instructions, rules and features
 
 pub mod metrics;
+pub mod profile;
 pub mod tracing;
 
 pub use metrics::{Counter, Histogram, Metrics, MetricsCollector};
+pub use profile::{ExecutionProfile, ProfileEntry, ProfileReport, ProfileScope};
 pub use tracing::{Span, SpanContext, Tracer};
@@ -0,0 +1,342 @@
+//! Execution profiling
+//!
+//! A `PipelineExecutor` with profiling enabled times every instruction it
+//! executes, every rule, ruleset and pipeline program, and every feature it
+//! loads, into the [`ExecutionProfile`] of its metrics collector. The profile
+//! keeps call counts and total and maximum time per name over all executions
+//! since it was last reset, and its [`ProfileReport`] ranks them, so the rules
+//! and features that dominate latency can be found without an external
+//! profiler.
+//!
+//! A program's time covers its own instructions only: the rules of a ruleset
+//! are separate programs, profiled on their own. Executions that fail are not
+//! profiled.
+
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// What a profile entry times
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProfileScope {
+    /// Instructions of one opcode, e.g. `CallExternal`
+    Instruction,
+
+    /// Loads of one feature, computed or pre-provided
+    Feature,
+
+    /// Executions of one rule
+    Rule,
+
+    /// Executions of one ruleset's decision logic
+    Ruleset,
+
+    /// Executions of one pipeline
+    Pipeline,
+}
+
+impl ProfileScope {
+    /// Scope of programs of a source type, if they are profiled
+    pub fn for_source_type(source_type: &str) -> Option<Self> {
+        match source_type {
+            "rule" => Some(ProfileScope::Rule),
+            "ruleset" => Some(ProfileScope::Ruleset),
+            "pipeline" => Some(ProfileScope::Pipeline),
+            _ => None,
+        }
+    }
+
+    /// Get the name of this scope
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ProfileScope::Instruction => "instruction",
+            ProfileScope::Feature => "feature",
+            ProfileScope::Rule => "rule",
+            ProfileScope::Ruleset => "ruleset",
+            ProfileScope::Pipeline => "pipeline",
+        }
+    }
+}
+
+/// Calls and time of one name
+#[derive(Debug, Clone, Copy, Default)]
+struct Timing {
+    calls: u64,
+    total: Duration,
+    max: Duration,
+}
+
+impl Timing {
+    fn record(&mut self, calls: u64, total: Duration, max: Duration) {
+        self.calls += calls;
+        self.total += total;
+        self.max = self.max.max(max);
+    }
+}
+
+/// Aggregated calls and time, by scope and name
+#[derive(Debug, Default)]
+pub struct ExecutionProfile {
+    timings: Mutex<HashMap<ProfileScope, HashMap<String, Timing>>>,
+}
+
+impl ExecutionProfile {
+    /// Create an empty profile
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record one call of `name` that took `elapsed`
+    pub fn record(&self, scope: ProfileScope, name: &str, elapsed: Duration) {
+        self.record_timing(scope, name, 1, elapsed, elapsed);
+    }
+
+    fn record_timing(
+        &self,
+        scope: ProfileScope,
+        name: &str,
+        calls: u64,
+        total: Duration,
+        max: Duration,
+    ) {
+        let mut timings = self.timings.lock().unwrap();
+        let names = timings.entry(scope).or_default();
+        match names.get_mut(name) {
+            Some(timing) => timing.record(calls, total, max),
+            None => {
+                let mut timing = Timing::default();
+                timing.record(calls, total, max);
+                names.insert(name.to_string(), timing);
+            }
+        }
+    }
+
+    /// Snapshot of the profile, hottest entries first
+    pub fn report(&self) -> ProfileReport {
+        let timings = self.timings.lock().unwrap();
+        let mut entries: Vec<ProfileEntry> = timings
+            .iter()
+            .flat_map(|(scope, names)| {
+                names.iter().map(|(name, timing)| ProfileEntry {
+                    scope: *scope,
+                    name: name.clone(),
+                    calls: timing.calls,
+                    total: timing.total,
+                    max: timing.max,
+                })
+            })
+            .collect();
+        entries.sort_by(|a, b| {
+            b.total
+                .cmp(&a.total)
+                .then_with(|| a.scope.cmp(&b.scope))
+                .then_with(|| a.name.cmp(&b.name))
+        });
+        ProfileReport { entries }
+    }
+
+    /// Forget everything recorded
+    pub fn reset(&self) {
+        self.timings.lock().unwrap().clear();
+    }
+}
+
+/// Calls and time of one instruction opcode, rule, ruleset, pipeline or feature
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ProfileEntry {
+    /// What is timed
+    pub scope: ProfileScope,
+
+    /// Opcode, or rule, ruleset, pipeline or feature ID
+    pub name: String,
+
+    /// Number of calls
+    pub calls: u64,
+
+    /// Time of all calls
+    pub total: Duration,
+
+    /// Time of the slowest call
+    pub max: Duration,
+}
+
+impl ProfileEntry {
+    /// Average time of a call
+    pub fn mean(&self) -> Duration {
+        if self.calls == 0 {
+            Duration::ZERO
+        } else {
+            self.total / self.calls as u32
+        }
+    }
+}
+
+/// Snapshot of an [`ExecutionProfile`]
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ProfileReport {
+    /// Entries of all scopes, by total time, highest first
+    pub entries: Vec<ProfileEntry>,
+}
+
+impl ProfileReport {
+    /// The entry of `name` in `scope`, if it was called
+    pub fn get(&self, scope: ProfileScope, name: &str) -> Option<&ProfileEntry> {
+        self.entries
+            .iter()
+            .find(|entry| entry.scope == scope && entry.name == name)
+    }
+
+    /// The `limit` entries of `scope` with the highest total time
+    pub fn hottest(&self, scope: ProfileScope, limit: usize) -> Vec<&ProfileEntry> {
+        self.entries
+            .iter()
+            .filter(|entry| entry.scope == scope)
+            .take(limit)
+            .collect()
+    }
+}
+
+impl fmt::Display for ProfileReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{:<12} {:<40} {:>10} {:>12} {:>12} {:>12}",
+            "scope", "name", "calls", "total_ms", "mean_us", "max_us"
+        )?;
+        for entry in &self.entries {
+            writeln!(
+                f,
+                "{:<12} {:<40} {:>10} {:>12.3} {:>12.1} {:>12.1}",
+                entry.scope.as_str(),
+                entry.name,
+                entry.calls,
+                entry.total.as_secs_f64() * 1e3,
+                entry.mean().as_secs_f64() * 1e6,
+                entry.max.as_secs_f64() * 1e6,
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Times the instructions of one program execution
+///
+/// Samples are kept per execution and merged into the profile once, when it
+/// completes, rather than locking the profile for every instruction.
+pub(crate) struct InstructionTimer {
+    enabled: bool,
+    pending: Option<(&'static str, Instant)>,
+    timings: HashMap<&'static str, Timing>,
+}
+
+impl InstructionTimer {
+    /// Start timing, if `enabled`
+    pub(crate) fn new(enabled: bool) -> Self {
+        Self {
+            enabled,
+            pending: None,
+            timings: HashMap::new(),
+        }
+    }
+
+    /// Stop timing the previous instruction and start timing `opcode`
+    pub(crate) fn enter(&mut self, opcode: &'static str) {
+        if self.enabled {
+            self.complete();
+            self.pending = Some((opcode, Instant::now()));
+        }
+    }
+
+    /// Stop timing the last instruction
+    pub(crate) fn complete(&mut self) {
+        if let Some((opcode, started)) = self.pending.take() {
+            let elapsed = started.elapsed();
+            self.timings
+                .entry(opcode)
+                .or_default()
+                .record(1, elapsed, elapsed);
+        }
+    }
+
+    /// Merge the instructions timed into `profile`
+    pub(crate) fn flush(mut self, profile: &ExecutionProfile) {
+        self.complete();
+        for (opcode, timing) in self.timings {
+            profile.record_timing(
+                ProfileScope::Instruction,
+                opcode,
+                timing.calls,
+                timing.total,
+                timing.max,
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_profile_report() {
+        let profile = ExecutionProfile::new();
+        profile.record(ProfileScope::Rule, "velocity", Duration::from_millis(3));
+        profile.record(ProfileScope::Rule, "velocity", Duration::from_millis(5));
+        profile.record(ProfileScope::Rule, "amount", Duration::from_millis(1));
+        profile.record(
+            ProfileScope::Feature,
+            "txn_count_24h",
+            Duration::from_millis(20),
+        );
+
+        let report = profile.report();
+        assert_eq!(report.entries[0].name, "txn_count_24h");
+
+        let velocity = report.get(ProfileScope::Rule, "velocity").unwrap();
+        assert_eq!(velocity.calls, 2);
+        assert_eq!(velocity.total, Duration::from_millis(8));
+        assert_eq!(velocity.max, Duration::from_millis(5));
+        assert_eq!(velocity.mean(), Duration::from_millis(4));
+
+        let rules: Vec<_> = report
+            .hottest(ProfileScope::Rule, 5)
+            .iter()
+            .map(|entry| entry.name.as_str())
+            .collect();
+        assert_eq!(rules, vec!["velocity", "amount"]);
+        assert!(report.to_string().contains("txn_count_24h"));
+
+        profile.reset();
+        assert!(profile.report().entries.is_empty());
+    }
+
+    #[test]
+    fn test_instruction_timer() {
+        let profile = ExecutionProfile::new();
+        let mut timer = InstructionTimer::new(true);
+        timer.enter("LoadField");
+        timer.enter("Compare");
+        timer.enter("LoadField");
+        timer.flush(&profile);
+
+        let report = profile.report();
+        let load_field = report.get(ProfileScope::Instruction, "LoadField").unwrap();
+        assert_eq!(load_field.calls, 2);
+        assert_eq!(
+            report
+                .get(ProfileScope::Instruction, "Compare")
+                .unwrap()
+                .calls,
+            1
+        );
+
+        let mut timer = InstructionTimer::new(false);
+        timer.enter("Return");
+        timer.flush(&profile);
+        assert!(report.get(ProfileScope::Instruction, "Return").is_none());
+        assert_eq!(profile.report().entries.len(), 2);
+    }
+}
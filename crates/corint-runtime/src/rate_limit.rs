@@ -0,0 +1,293 @@
+//! Rate limits for external API endpoints
+//!
+//! Vendor contracts cap how many calls per second an endpoint may receive. A
+//! [`RateLimiter`] enforces such a cap with a token bucket: tokens refill at
+//! `tokens_per_sec` up to `burst`, and every call takes one. A call that
+//! finds the bucket empty either waits for its token, up to `max_wait_ms`, or
+//! fails fast with [`RuntimeError::RateLimited`], depending on the configured
+//! [`RateLimitBehavior`].
+//!
+//! Limits are configured per endpoint:
+//!
+//! ```yaml
+//! rate_limit:
+//!   tokens_per_sec: 50
+//!   burst: 10
+//!   on_limit: queue
+//!   max_wait_ms: 200
+//! ```
+
+use crate::error::{Result, RuntimeError};
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// What a call does when the rate limit is reached
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RateLimitBehavior {
+    /// Wait for the next token, up to the maximum wait (default)
+    #[default]
+    Queue,
+
+    /// Fail immediately
+    Reject,
+}
+
+/// Rate limit configuration
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct RateLimit {
+    /// Sustained number of calls per second
+    pub tokens_per_sec: f64,
+
+    /// Calls that may be made at once after a quiet period (default: 1)
+    #[serde(default = "default_burst")]
+    pub burst: u32,
+
+    /// What a call does when the limit is reached (default: queue)
+    #[serde(default)]
+    pub on_limit: RateLimitBehavior,
+
+    /// How long a queued call may wait for its token, in milliseconds (default: 100)
+    #[serde(default = "default_max_wait_ms")]
+    pub max_wait_ms: u64,
+}
+
+fn default_burst() -> u32 {
+    1
+}
+
+fn default_max_wait_ms() -> u64 {
+    100
+}
+
+impl RateLimit {
+    /// Allow `tokens_per_sec` calls per second, queueing with the default wait
+    pub fn new(tokens_per_sec: f64) -> Self {
+        Self {
+            tokens_per_sec,
+            burst: default_burst(),
+            on_limit: RateLimitBehavior::default(),
+            max_wait_ms: default_max_wait_ms(),
+        }
+    }
+
+    /// Set the burst size
+    pub fn with_burst(mut self, burst: u32) -> Self {
+        self.burst = burst;
+        self
+    }
+
+    /// Set what a call does when the limit is reached
+    pub fn with_on_limit(mut self, on_limit: RateLimitBehavior) -> Self {
+        self.on_limit = on_limit;
+        self
+    }
+
+    /// Set the maximum wait of a queued call
+    pub fn with_max_wait_ms(mut self, max_wait_ms: u64) -> Self {
+        self.max_wait_ms = max_wait_ms;
+        self
+    }
+}
+
+/// Point-in-time statistics of a rate limiter
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct RateLimitStats {
+    /// Name of the limited endpoint
+    pub name: String,
+
+    /// Configured calls per second
+    pub tokens_per_sec: f64,
+
+    /// Calls that obtained a token
+    pub acquired: u64,
+
+    /// Calls that had to wait for their token
+    pub queued: u64,
+
+    /// Calls rejected by the limit
+    pub rejected: u64,
+
+    /// Total time calls spent waiting for a token, in milliseconds
+    pub total_wait_ms: u64,
+}
+
+/// Tokens left and when they were last refilled
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+/// Token bucket limiting the calls to one endpoint
+#[derive(Debug)]
+pub struct RateLimiter {
+    name: String,
+    limit: RateLimit,
+    bucket: Mutex<Bucket>,
+    acquired: AtomicU64,
+    queued: AtomicU64,
+    rejected: AtomicU64,
+    total_wait_ms: AtomicU64,
+}
+
+impl RateLimiter {
+    /// Create a limiter for the named endpoint, with a full bucket
+    pub fn new(name: impl Into<String>, limit: RateLimit) -> Self {
+        Self {
+            name: name.into(),
+            limit,
+            bucket: Mutex::new(Bucket {
+                tokens: limit.burst.max(1) as f64,
+                refilled_at: Instant::now(),
+            }),
+            acquired: AtomicU64::new(0),
+            queued: AtomicU64::new(0),
+            rejected: AtomicU64::new(0),
+            total_wait_ms: AtomicU64::new(0),
+        }
+    }
+
+    /// Take a token, waiting for it if the limit queues calls
+    ///
+    /// Fails with [`RuntimeError::RateLimited`] when the bucket is empty and
+    /// the limit rejects calls, or when the token would come later than the
+    /// maximum wait. A rejected call takes no token.
+    pub async fn acquire(&self) -> Result<()> {
+        let wait = self.reserve()?;
+        if !wait.is_zero() {
+            self.queued.fetch_add(1, Ordering::Relaxed);
+            self.total_wait_ms
+                .fetch_add(wait.as_millis() as u64, Ordering::Relaxed);
+            tokio::time::sleep(wait).await;
+        }
+        self.acquired.fetch_add(1, Ordering::Relaxed);
+        Ok(())
+    }
+
+    /// Take the next token, with how long until it is available
+    ///
+    /// Queued calls take their tokens in advance, so the bucket goes below
+    /// zero while calls are waiting and each waits for its own token.
+    fn reserve(&self) -> Result<Duration> {
+        let rate = self.limit.tokens_per_sec.max(f64::MIN_POSITIVE);
+        let mut bucket = self.bucket.lock().unwrap();
+        let now = Instant::now();
+        let elapsed = now.duration_since(bucket.refilled_at).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * rate).min(self.limit.burst.max(1) as f64);
+        bucket.refilled_at = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            return Ok(Duration::ZERO);
+        }
+
+        let wait = Duration::from_secs_f64((1.0 - bucket.tokens) / rate);
+        let max_wait = Duration::from_millis(self.limit.max_wait_ms);
+        if self.limit.on_limit == RateLimitBehavior::Queue && wait <= max_wait {
+            bucket.tokens -= 1.0;
+            return Ok(wait);
+        }
+        drop(bucket);
+
+        self.rejected.fetch_add(1, Ordering::Relaxed);
+        tracing::warn!(
+            "Rate limit of {}/s reached for '{}'",
+            self.limit.tokens_per_sec,
+            self.name
+        );
+        Err(RuntimeError::RateLimited(format!(
+            "'{}' allows {} calls per second (next token in {}ms)",
+            self.name,
+            self.limit.tokens_per_sec,
+            wait.as_millis()
+        )))
+    }
+
+    /// Name of the limited endpoint
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Configured limit
+    pub fn limit(&self) -> RateLimit {
+        self.limit
+    }
+
+    /// Current statistics
+    pub fn stats(&self) -> RateLimitStats {
+        RateLimitStats {
+            name: self.name.clone(),
+            tokens_per_sec: self.limit.tokens_per_sec,
+            acquired: self.acquired.load(Ordering::Relaxed),
+            queued: self.queued.load(Ordering::Relaxed),
+            rejected: self.rejected.load(Ordering::Relaxed),
+            total_wait_ms: self.total_wait_ms.load(Ordering::Relaxed),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(start_paused = true)]
+    async fn test_limiter_queues_within_max_wait() {
+        let limiter = RateLimiter::new(
+            "vendor::score",
+            RateLimit::new(10.0).with_burst(2).with_max_wait_ms(150),
+        );
+
+        // The burst goes through at once, then calls are spaced by 100ms
+        let start = Instant::now();
+        limiter.acquire().await.unwrap();
+        limiter.acquire().await.unwrap();
+        assert_eq!(start.elapsed(), Duration::ZERO);
+        limiter.acquire().await.unwrap();
+        assert_eq!(start.elapsed(), Duration::from_millis(100));
+
+        // A token further away than the maximum wait is not waited for
+        let (queued, rejected) = tokio::join!(limiter.acquire(), async {
+            tokio::task::yield_now().await;
+            limiter.acquire().await
+        });
+        assert!(queued.is_ok());
+        assert!(matches!(rejected, Err(RuntimeError::RateLimited(_))));
+
+        let stats = limiter.stats();
+        assert_eq!(stats.acquired, 4);
+        assert_eq!(stats.queued, 2);
+        assert_eq!(stats.rejected, 1);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_limiter_rejects_without_queueing() {
+        let limiter = RateLimiter::new(
+            "vendor::score",
+            RateLimit::new(5.0).with_on_limit(RateLimitBehavior::Reject),
+        );
+
+        limiter.acquire().await.unwrap();
+        let err = limiter.acquire().await.unwrap_err();
+        assert!(matches!(err, RuntimeError::RateLimited(_)));
+
+        tokio::time::advance(Duration::from_millis(200)).await;
+        assert!(limiter.acquire().await.is_ok());
+        assert_eq!(limiter.stats().rejected, 1);
+    }
+
+    #[test]
+    fn test_rate_limit_config_defaults() {
+        let limit: RateLimit = serde_yaml::from_str("tokens_per_sec: 20").unwrap();
+        assert_eq!(limit, RateLimit::new(20.0));
+        assert_eq!(limit.burst, 1);
+        assert_eq!(limit.on_limit, RateLimitBehavior::Queue);
+
+        let limit: RateLimit =
+            serde_yaml::from_str("tokens_per_sec: 20\nburst: 5\non_limit: reject").unwrap();
+        assert_eq!(limit.on_limit, RateLimitBehavior::Reject);
+    }
+}
@@ -0,0 +1,432 @@
+//! Record and replay of external dependencies
+//!
+//! To reproduce a production decision locally, the responses of every external
+//! API call and data source query made for a request are recorded, keyed by
+//! the request ID, and later fed back instead of making real calls.
+//!
+//! A [`ReplaySession`] is bound to the task executing a request with
+//! [`ReplaySession::scope`]. While a session is active, [`ExternalApiClient`]
+//! and [`DataSourceClient`] route their calls through it:
+//!
+//! - in [`RecordReplayMode::Record`] the real call is made and its response
+//!   (or error) appended to the session's [`Recording`];
+//! - in [`RecordReplayMode::Replay`] the next recorded response for the same
+//!   dependency and call key is returned and no real call is made. A call
+//!   that was not recorded fails with [`RuntimeError::Replay`].
+//!
+//! Feature caches are bypassed while a session is active, so every data
+//! source query a decision depends on is recorded and replayed.
+//!
+//! [`ExternalApiClient`]: crate::external_api::ExternalApiClient
+//! [`DataSourceClient`]: crate::datasource::DataSourceClient
+
+use crate::error::{Result, RuntimeError};
+use chrono::{DateTime, Utc};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+tokio::task_local! {
+    static CURRENT_SESSION: Arc<ReplaySession>;
+}
+
+/// Whether external dependencies are recorded or replayed
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RecordReplayMode {
+    /// Real calls, nothing recorded (default)
+    #[default]
+    Off,
+
+    /// Real calls, responses recorded per request
+    Record,
+
+    /// Recorded responses fed back instead of real calls
+    Replay,
+}
+
+/// Record/replay configuration
+///
+/// ```yaml
+/// record_replay:
+///   mode: record
+///   directory: /var/lib/corint/recordings
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordReplayConfig {
+    /// Record, replay or neither
+    #[serde(default)]
+    pub mode: RecordReplayMode,
+
+    /// Directory holding one `<request_id>.json` recording per request
+    #[serde(default = "default_directory")]
+    pub directory: PathBuf,
+}
+
+fn default_directory() -> PathBuf {
+    PathBuf::from("recordings")
+}
+
+impl Default for RecordReplayConfig {
+    fn default() -> Self {
+        Self {
+            mode: RecordReplayMode::Off,
+            directory: default_directory(),
+        }
+    }
+}
+
+impl RecordReplayConfig {
+    /// Record responses into `directory`
+    pub fn record(directory: impl Into<PathBuf>) -> Self {
+        Self {
+            mode: RecordReplayMode::Record,
+            directory: directory.into(),
+        }
+    }
+
+    /// Replay responses from `directory`
+    pub fn replay(directory: impl Into<PathBuf>) -> Self {
+        Self {
+            mode: RecordReplayMode::Replay,
+            directory: directory.into(),
+        }
+    }
+
+    /// Whether recording or replay is enabled
+    pub fn is_enabled(&self) -> bool {
+        self.mode != RecordReplayMode::Off
+    }
+
+    /// Path of the recording for a request
+    ///
+    /// Characters other than ASCII letters, digits, `-`, `_` and `.` are
+    /// replaced so that any request ID maps to a file inside `directory`.
+    pub fn recording_path(&self, request_id: &str) -> PathBuf {
+        let file_name: String = request_id
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.') {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        self.directory
+            .join(format!("{}.json", file_name.trim_start_matches('.')))
+    }
+}
+
+/// Recorded outcome of a call
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RecordedResponse {
+    /// Successful response
+    Ok(serde_json::Value),
+
+    /// Error message of a failed call
+    Error(String),
+}
+
+/// One recorded call to an external dependency
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordedCall {
+    /// Called dependency (e.g. `api:ipinfo::lookup`, `datasource:risk_db`)
+    pub dependency: String,
+
+    /// Call key identifying the request made to the dependency
+    pub key: String,
+
+    /// Response the dependency returned
+    pub response: RecordedResponse,
+}
+
+/// All calls to external dependencies made for one request
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Recording {
+    /// Request ID
+    pub request_id: String,
+
+    /// When the request was recorded
+    pub recorded_at: DateTime<Utc>,
+
+    /// Calls in the order they completed
+    pub calls: Vec<RecordedCall>,
+}
+
+impl Recording {
+    /// Read a recording from a JSON file
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path).map_err(|e| {
+            RuntimeError::Replay(format!("Cannot read recording {}: {}", path.display(), e))
+        })?;
+        serde_json::from_str(&content).map_err(|e| {
+            RuntimeError::Replay(format!("Invalid recording {}: {}", path.display(), e))
+        })
+    }
+
+    /// Write the recording to a JSON file, creating its directory if needed
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        let write = || -> std::io::Result<()> {
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            let content = serde_json::to_string_pretty(self)?;
+            std::fs::write(path, content)
+        };
+        write().map_err(|e| {
+            RuntimeError::Replay(format!("Cannot write recording {}: {}", path.display(), e))
+        })
+    }
+}
+
+/// Recording or replay state of one request
+#[derive(Debug)]
+pub struct ReplaySession {
+    mode: RecordReplayMode,
+    request_id: String,
+    recorded_at: Option<DateTime<Utc>>,
+    recorded: Mutex<Vec<RecordedCall>>,
+    pending: Mutex<HashMap<(String, String), VecDeque<RecordedResponse>>>,
+}
+
+impl ReplaySession {
+    /// Start recording the calls made for a request
+    pub fn record(request_id: impl Into<String>) -> Self {
+        Self {
+            mode: RecordReplayMode::Record,
+            request_id: request_id.into(),
+            recorded_at: None,
+            recorded: Mutex::new(Vec::new()),
+            pending: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Feed back the calls of a recording
+    pub fn replay(recording: Recording) -> Self {
+        let mut pending: HashMap<(String, String), VecDeque<RecordedResponse>> = HashMap::new();
+        for call in recording.calls {
+            pending
+                .entry((call.dependency, call.key))
+                .or_default()
+                .push_back(call.response);
+        }
+        Self {
+            mode: RecordReplayMode::Replay,
+            request_id: recording.request_id,
+            recorded_at: Some(recording.recorded_at),
+            recorded: Mutex::new(Vec::new()),
+            pending: Mutex::new(pending),
+        }
+    }
+
+    /// Session mode
+    pub fn mode(&self) -> RecordReplayMode {
+        self.mode
+    }
+
+    /// Request ID the session belongs to
+    pub fn request_id(&self) -> &str {
+        &self.request_id
+    }
+
+    /// When the replayed request was recorded; `None` while recording
+    pub fn recorded_at(&self) -> Option<DateTime<Utc>> {
+        self.recorded_at
+    }
+
+    /// Calls recorded so far
+    pub fn recording(&self) -> Recording {
+        Recording {
+            request_id: self.request_id.clone(),
+            recorded_at: Utc::now(),
+            calls: self.recorded.lock().unwrap().clone(),
+        }
+    }
+
+    /// Number of recorded responses a replay has not consumed
+    ///
+    /// A non-zero count after a replayed request means the decision made
+    /// fewer calls than in production, e.g. because the rules changed.
+    pub fn unused_calls(&self) -> usize {
+        self.pending
+            .lock()
+            .unwrap()
+            .values()
+            .map(VecDeque::len)
+            .sum()
+    }
+
+    /// Run `future` with this session active
+    pub async fn scope<F: Future>(self: Arc<Self>, future: F) -> F::Output {
+        CURRENT_SESSION.scope(self, future).await
+    }
+
+    fn push(&self, dependency: &str, key: &str, response: RecordedResponse) {
+        self.recorded.lock().unwrap().push(RecordedCall {
+            dependency: dependency.to_string(),
+            key: key.to_string(),
+            response,
+        });
+    }
+
+    fn take(&self, dependency: &str, key: &str) -> Option<RecordedResponse> {
+        self.pending
+            .lock()
+            .unwrap()
+            .get_mut(&(dependency.to_string(), key.to_string()))
+            .and_then(VecDeque::pop_front)
+    }
+}
+
+/// Session bound to the current task, if any
+pub fn current_session() -> Option<Arc<ReplaySession>> {
+    CURRENT_SESSION.try_with(Arc::clone).ok()
+}
+
+/// Whether the current task records or replays its calls
+pub(crate) fn is_active() -> bool {
+    CURRENT_SESSION.try_with(|_| ()).is_ok()
+}
+
+/// Make a call to an external dependency through the current session
+///
+/// Without an active session `call` is simply awaited. Replayed errors are
+/// returned as [`RuntimeError::ExternalCallFailed`] with the recorded message.
+pub(crate) async fn intercept<T, F, Fut>(dependency: &str, key: &str, call: F) -> Result<T>
+where
+    T: Serialize + DeserializeOwned,
+    F: FnOnce() -> Fut,
+    Fut: Future<Output = Result<T>>,
+{
+    let Some(session) = current_session() else {
+        return call().await;
+    };
+
+    match session.mode {
+        RecordReplayMode::Replay => match session.take(dependency, key) {
+            Some(RecordedResponse::Ok(value)) => serde_json::from_value(value).map_err(|e| {
+                RuntimeError::Replay(format!(
+                    "Recorded response of {} does not match the expected type: {}",
+                    dependency, e
+                ))
+            }),
+            Some(RecordedResponse::Error(message)) => {
+                Err(RuntimeError::ExternalCallFailed(message))
+            }
+            None => Err(RuntimeError::Replay(format!(
+                "No recorded response for {} ({}) in request {}",
+                dependency, key, session.request_id
+            ))),
+        },
+        RecordReplayMode::Record => {
+            let result = call().await;
+            let response = match &result {
+                Ok(value) => serde_json::to_value(value)
+                    .map(RecordedResponse::Ok)
+                    .unwrap_or_else(|e| RecordedResponse::Error(e.to_string())),
+                Err(e) => RecordedResponse::Error(e.to_string()),
+            };
+            session.push(dependency, key, response);
+            result
+        }
+        RecordReplayMode::Off => call().await,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    async fn lookup(calls: &AtomicUsize, ip: &str) -> Result<String> {
+        intercept("api:ipinfo::lookup", ip, || async {
+            let n = calls.fetch_add(1, Ordering::SeqCst);
+            if ip == "bad" {
+                Err(RuntimeError::ExternalCallFailed("HTTP 500".to_string()))
+            } else {
+                Ok(format!("{}#{}", ip, n))
+            }
+        })
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_record_then_replay() {
+        let calls = AtomicUsize::new(0);
+        let session = Arc::new(ReplaySession::record("req_1"));
+
+        let recorded = Arc::clone(&session)
+            .scope(async {
+                (
+                    lookup(&calls, "1.2.3.4").await.unwrap(),
+                    lookup(&calls, "1.2.3.4").await.unwrap(),
+                    lookup(&calls, "bad").await.is_err(),
+                )
+            })
+            .await;
+        assert_eq!(
+            recorded,
+            ("1.2.3.4#0".to_string(), "1.2.3.4#1".to_string(), true)
+        );
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+
+        let recording = session.recording();
+        assert_eq!(recording.calls.len(), 3);
+
+        // Replay returns the recorded responses in order without calling out
+        let replay = Arc::new(ReplaySession::replay(recording));
+        let replayed = Arc::clone(&replay)
+            .scope(async {
+                (
+                    lookup(&calls, "1.2.3.4").await.unwrap(),
+                    lookup(&calls, "1.2.3.4").await.unwrap(),
+                    lookup(&calls, "bad").await.unwrap_err().to_string(),
+                )
+            })
+            .await;
+        assert_eq!(replayed.0, "1.2.3.4#0");
+        assert_eq!(replayed.1, "1.2.3.4#1");
+        assert!(replayed.2.contains("HTTP 500"));
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+        assert_eq!(replay.unused_calls(), 0);
+
+        // Calls that were not recorded fail instead of reaching the dependency
+        let err = replay.scope(lookup(&calls, "5.6.7.8")).await.unwrap_err();
+        assert!(matches!(err, RuntimeError::Replay(_)));
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_no_session_calls_through() {
+        let calls = AtomicUsize::new(0);
+        assert!(!is_active());
+        assert_eq!(lookup(&calls, "1.2.3.4").await.unwrap(), "1.2.3.4#0");
+    }
+
+    #[test]
+    fn test_recording_file_round_trip() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let config = RecordReplayConfig::record(dir.path());
+        let path = config.recording_path("req/../1");
+        assert_eq!(path, dir.path().join("req_.._1.json"));
+
+        let recording = Recording {
+            request_id: "req/../1".to_string(),
+            recorded_at: Utc::now(),
+            calls: vec![RecordedCall {
+                dependency: "datasource:risk_db".to_string(),
+                key: "query:risk_db:events".to_string(),
+                response: RecordedResponse::Ok(serde_json::json!({"rows": []})),
+            }],
+        };
+        recording.save(&path).unwrap();
+        assert_eq!(Recording::load(&path).unwrap(), recording);
+    }
+}
@@ -0,0 +1,342 @@
+//! Champion/challenger experiment results
+//!
+//! When a registry entry runs an experiment, the events sent to its
+//! challenger are decided by both pipelines. The champion's decision is
+//! returned and persisted as usual; both decisions are recorded side by side
+//! in the `experiment_results` table, keyed by the request ID, so that the
+//! challenger can be compared with the champion before it is promoted.
+
+use crate::error::Result;
+#[cfg(feature = "sqlx")]
+use crate::error::RuntimeError;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+
+/// Decision of one pipeline in an experiment
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VariantDecision {
+    /// Pipeline that made the decision
+    pub pipeline_id: String,
+
+    /// Decision signal (e.g. "approve", "decline"), if one was reached
+    pub decision: Option<String>,
+
+    /// Risk score
+    pub score: i32,
+
+    /// Rules triggered by the decision
+    pub triggered_rules: Vec<String>,
+
+    /// Processing time in milliseconds
+    pub processing_time_ms: u64,
+}
+
+/// Champion and challenger decisions of one request
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExperimentRecord {
+    /// Request ID of the decision (links to risk_decisions)
+    pub request_id: String,
+
+    /// Experiment ID
+    pub experiment_id: String,
+
+    /// Decision returned to the caller
+    pub champion: VariantDecision,
+
+    /// Decision of the challenger pipeline
+    pub challenger: VariantDecision,
+
+    /// When the decisions were made
+    pub recorded_at: DateTime<Utc>,
+}
+
+impl ExperimentRecord {
+    /// Record the decisions of a request made now
+    pub fn new(
+        request_id: impl Into<String>,
+        experiment_id: impl Into<String>,
+        champion: VariantDecision,
+        challenger: VariantDecision,
+    ) -> Self {
+        Self {
+            request_id: request_id.into(),
+            experiment_id: experiment_id.into(),
+            champion,
+            challenger,
+            recorded_at: Utc::now(),
+        }
+    }
+
+    /// Whether both pipelines reached the same decision
+    pub fn agrees(&self) -> bool {
+        self.champion.decision == self.challenger.decision
+    }
+}
+
+/// Comparison of the champion and challenger over recorded requests
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ExperimentSummary {
+    /// Experiment ID
+    pub experiment_id: String,
+
+    /// Requests decided by both pipelines
+    pub requests: u64,
+
+    /// Requests on which both pipelines reached the same decision
+    pub agreed: u64,
+
+    /// Number of requests per (champion decision, challenger decision),
+    /// `none` standing for no decision
+    pub transitions: BTreeMap<String, BTreeMap<String, u64>>,
+
+    /// Average champion score
+    pub champion_avg_score: f64,
+
+    /// Average challenger score
+    pub challenger_avg_score: f64,
+}
+
+impl ExperimentSummary {
+    /// Share of requests on which both pipelines agreed (None without requests)
+    pub fn agreement_rate(&self) -> Option<f64> {
+        (self.requests > 0).then(|| self.agreed as f64 / self.requests as f64)
+    }
+}
+
+/// Compare the champion and challenger over the records of `experiment_id`
+pub fn summarize_experiment(
+    experiment_id: &str,
+    records: &[ExperimentRecord],
+) -> ExperimentSummary {
+    let mut summary = ExperimentSummary {
+        experiment_id: experiment_id.to_string(),
+        ..Default::default()
+    };
+    let mut champion_score = 0i64;
+    let mut challenger_score = 0i64;
+
+    for record in records.iter().filter(|r| r.experiment_id == experiment_id) {
+        summary.requests += 1;
+        if record.agrees() {
+            summary.agreed += 1;
+        }
+        let decision = |variant: &VariantDecision| {
+            variant
+                .decision
+                .clone()
+                .unwrap_or_else(|| "none".to_string())
+        };
+        *summary
+            .transitions
+            .entry(decision(&record.champion))
+            .or_default()
+            .entry(decision(&record.challenger))
+            .or_default() += 1;
+        champion_score += i64::from(record.champion.score);
+        challenger_score += i64::from(record.challenger.score);
+    }
+
+    if summary.requests > 0 {
+        summary.champion_avg_score = champion_score as f64 / summary.requests as f64;
+        summary.challenger_avg_score = challenger_score as f64 / summary.requests as f64;
+    }
+    summary
+}
+
+/// Storage for experiment results
+#[async_trait]
+pub trait ExperimentStore: Send + Sync {
+    /// Record the decisions of a request
+    async fn record_experiment(&self, record: ExperimentRecord) -> Result<()>;
+
+    /// Load the records of an experiment, oldest first
+    async fn experiment_records(&self, experiment_id: &str) -> Result<Vec<ExperimentRecord>>;
+}
+
+/// In-memory experiment store (for tests and single-process deployments)
+#[derive(Debug, Default)]
+pub struct InMemoryExperimentStore {
+    records: Mutex<Vec<ExperimentRecord>>,
+}
+
+impl InMemoryExperimentStore {
+    /// Create an empty store
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl ExperimentStore for InMemoryExperimentStore {
+    async fn record_experiment(&self, record: ExperimentRecord) -> Result<()> {
+        self.records.lock().unwrap().push(record);
+        Ok(())
+    }
+
+    async fn experiment_records(&self, experiment_id: &str) -> Result<Vec<ExperimentRecord>> {
+        Ok(self
+            .records
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|record| record.experiment_id == experiment_id)
+            .cloned()
+            .collect())
+    }
+}
+
+/// Experiment store backed by the decision database
+#[cfg(feature = "sqlx")]
+#[derive(Clone)]
+pub struct PostgresExperimentStore {
+    pool: sqlx::PgPool,
+}
+
+#[cfg(feature = "sqlx")]
+impl PostgresExperimentStore {
+    /// Create a store over the decision database pool
+    pub fn new(pool: sqlx::PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[cfg(feature = "sqlx")]
+#[async_trait]
+impl ExperimentStore for PostgresExperimentStore {
+    async fn record_experiment(&self, record: ExperimentRecord) -> Result<()> {
+        let variant = |variant: &VariantDecision| {
+            serde_json::to_value(variant).map_err(|e| {
+                RuntimeError::RuntimeError(format!("Failed to serialize experiment result: {}", e))
+            })
+        };
+
+        sqlx::query(
+            r#"
+            INSERT INTO experiment_results (
+                request_id, experiment_id, champion_pipeline_id, challenger_pipeline_id,
+                champion_decision, challenger_decision, champion, challenger, recorded_at
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            ON CONFLICT (request_id, experiment_id) DO UPDATE SET
+                champion_decision = EXCLUDED.champion_decision,
+                challenger_decision = EXCLUDED.challenger_decision,
+                champion = EXCLUDED.champion,
+                challenger = EXCLUDED.challenger,
+                recorded_at = EXCLUDED.recorded_at
+            "#,
+        )
+        .bind(&record.request_id)
+        .bind(&record.experiment_id)
+        .bind(&record.champion.pipeline_id)
+        .bind(&record.challenger.pipeline_id)
+        .bind(record.champion.decision.as_deref())
+        .bind(record.challenger.decision.as_deref())
+        .bind(variant(&record.champion)?)
+        .bind(variant(&record.challenger)?)
+        .bind(record.recorded_at)
+        .execute(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        Ok(())
+    }
+
+    async fn experiment_records(&self, experiment_id: &str) -> Result<Vec<ExperimentRecord>> {
+        use sqlx::Row;
+
+        let rows = sqlx::query(
+            r#"
+            SELECT request_id, champion, challenger, recorded_at
+            FROM experiment_results
+            WHERE experiment_id = $1
+            ORDER BY recorded_at
+            "#,
+        )
+        .bind(experiment_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        let variant = |value: serde_json::Value| {
+            serde_json::from_value::<VariantDecision>(value).map_err(|e| {
+                RuntimeError::RuntimeError(format!(
+                    "Failed to deserialize experiment result: {}",
+                    e
+                ))
+            })
+        };
+        rows.into_iter()
+            .map(|row| {
+                Ok(ExperimentRecord {
+                    request_id: row.try_get("request_id").map_err(db_error)?,
+                    experiment_id: experiment_id.to_string(),
+                    champion: variant(row.try_get("champion").map_err(db_error)?)?,
+                    challenger: variant(row.try_get("challenger").map_err(db_error)?)?,
+                    recorded_at: row.try_get("recorded_at").map_err(db_error)?,
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(feature = "sqlx")]
+fn db_error(e: sqlx::Error) -> RuntimeError {
+    RuntimeError::RuntimeError(format!("Experiment database error: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn variant(pipeline_id: &str, decision: Option<&str>, score: i32) -> VariantDecision {
+        VariantDecision {
+            pipeline_id: pipeline_id.to_string(),
+            decision: decision.map(str::to_string),
+            score,
+            triggered_rules: Vec::new(),
+            processing_time_ms: 1,
+        }
+    }
+
+    fn record(request_id: &str, champion: &str, challenger: Option<&str>) -> ExperimentRecord {
+        ExperimentRecord::new(
+            request_id,
+            "payment_v2_trial",
+            variant("payment", Some(champion), 40),
+            variant("payment_v2", challenger, 60),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_experiment_store_and_summary() {
+        let store = InMemoryExperimentStore::new();
+        for record in [
+            record("r1", "approve", Some("approve")),
+            record("r2", "approve", Some("decline")),
+            record("r3", "decline", None),
+        ] {
+            store.record_experiment(record).await.unwrap();
+        }
+        assert!(store.experiment_records("other").await.unwrap().is_empty());
+
+        let records = store.experiment_records("payment_v2_trial").await.unwrap();
+        assert_eq!(records.len(), 3);
+        assert!(records[0].agrees());
+        assert!(!records[1].agrees());
+
+        let summary = summarize_experiment("payment_v2_trial", &records);
+        assert_eq!(summary.requests, 3);
+        assert_eq!(summary.agreed, 1);
+        assert_eq!(summary.transitions["approve"]["decline"], 1);
+        assert_eq!(summary.transitions["decline"]["none"], 1);
+        assert_eq!(summary.champion_avg_score, 40.0);
+        assert_eq!(summary.challenger_avg_score, 60.0);
+        assert!((summary.agreement_rate().unwrap() - 1.0 / 3.0).abs() < 1e-9);
+        assert_eq!(
+            summarize_experiment("other", &records).agreement_rate(),
+            None
+        );
+    }
+}
@@ -0,0 +1,662 @@
+//! Decision log export
+//!
+//! Turns persisted decisions into analysis-ready datasets for the backtester
+//! and external BI tools. Every decision becomes one flat row:
+//!
+//! - decision columns: `id`, `request_id`, `event_id`, `pipeline_id`,
+//!   `created_at`, `risk_score`, `decision`, `decision_reason`,
+//!   `processing_time_ms`, `sampled`, `triggered_rule_count`, `triggered_rules`
+//! - one `rule.<rule_id>` column per triggered rule, holding its score
+//!   (empty when the rule did not trigger)
+//! - one `feature.<name>` column per feature value, when enabled
+//!
+//! Rows are written as JSON Lines, or as Parquet with the `parquet` feature.
+//! Each export run writes the decisions persisted since the last run, one
+//! file per batch, and advances a checkpoint stored next to the files after
+//! every file, so an interrupted export resumes where it stopped.
+
+use crate::error::{Result, RuntimeError};
+use crate::result::DecisionRecord;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+/// File name of the export checkpoint in the output directory
+pub const CHECKPOINT_FILE: &str = ".export-checkpoint.json";
+
+/// A decision record as persisted, with its position in the decision log
+#[derive(Debug, Clone)]
+pub struct PersistedDecision {
+    /// Monotonic ID assigned when the decision was persisted
+    pub id: i64,
+
+    /// When the decision was persisted
+    pub created_at: DateTime<Utc>,
+
+    /// The decision record
+    pub record: DecisionRecord,
+}
+
+/// Source of persisted decisions
+#[async_trait]
+pub trait DecisionLogSource: Send + Sync {
+    /// Fetch up to `limit` decisions with an ID greater than `after_id`, in ID order
+    async fn fetch_after(&self, after_id: i64, limit: usize) -> Result<Vec<PersistedDecision>>;
+}
+
+/// In-memory decision log, for tests and embedded use
+#[derive(Debug, Default)]
+pub struct InMemoryDecisionLog {
+    decisions: Mutex<Vec<PersistedDecision>>,
+}
+
+impl InMemoryDecisionLog {
+    /// Create an empty log
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append a decision record, returning its ID
+    pub fn append(&self, record: DecisionRecord) -> i64 {
+        let mut decisions = self.decisions.lock().unwrap();
+        let id = decisions.len() as i64 + 1;
+        decisions.push(PersistedDecision {
+            id,
+            created_at: Utc::now(),
+            record,
+        });
+        id
+    }
+}
+
+#[async_trait]
+impl DecisionLogSource for InMemoryDecisionLog {
+    async fn fetch_after(&self, after_id: i64, limit: usize) -> Result<Vec<PersistedDecision>> {
+        Ok(self
+            .decisions
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|d| d.id > after_id)
+            .take(limit)
+            .cloned()
+            .collect())
+    }
+}
+
+/// Decision log in the `risk_decisions` table
+#[cfg(feature = "sqlx")]
+pub struct PostgresDecisionLog {
+    pool: sqlx::PgPool,
+}
+
+#[cfg(feature = "sqlx")]
+impl PostgresDecisionLog {
+    /// Create a source over the decision database pool
+    pub fn new(pool: sqlx::PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[cfg(feature = "sqlx")]
+#[async_trait]
+impl DecisionLogSource for PostgresDecisionLog {
+    async fn fetch_after(&self, after_id: i64, limit: usize) -> Result<Vec<PersistedDecision>> {
+        use sqlx::Row;
+
+        let db_error =
+            |e: sqlx::Error| RuntimeError::Export(format!("Decision log database error: {}", e));
+
+        let rows = sqlx::query(
+            r#"
+            SELECT id, request_id, event_id, pipeline_id, risk_score::INT AS risk_score,
+                   decision, decision_reason, triggered_rules, rule_scores, feature_values,
+                   processing_time_ms, sampled, captured_inputs, created_at
+            FROM risk_decisions
+            WHERE id > $1
+            ORDER BY id
+            LIMIT $2
+            "#,
+        )
+        .bind(after_id)
+        .bind(limit as i64)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        rows.into_iter()
+            .map(|row| {
+                let decision: String = row.try_get("decision").map_err(db_error)?;
+                let decision = serde_json::from_value(serde_json::json!({ "type": decision }))
+                    .map_err(|e| RuntimeError::Export(format!("Unknown decision: {}", e)))?;
+                let rule_scores: Option<JsonValue> =
+                    row.try_get("rule_scores").map_err(db_error)?;
+                let feature_values: Option<JsonValue> =
+                    row.try_get("feature_values").map_err(db_error)?;
+                let processing_time_ms: Option<i32> =
+                    row.try_get("processing_time_ms").map_err(db_error)?;
+                let captured_inputs: Option<JsonValue> =
+                    row.try_get("captured_inputs").map_err(db_error)?;
+
+                Ok(PersistedDecision {
+                    id: row.try_get("id").map_err(db_error)?,
+                    created_at: row.try_get("created_at").map_err(db_error)?,
+                    record: DecisionRecord {
+                        request_id: row.try_get("request_id").map_err(db_error)?,
+                        event_id: row.try_get("event_id").map_err(db_error)?,
+                        pipeline_id: row.try_get("pipeline_id").map_err(db_error)?,
+                        risk_score: row.try_get("risk_score").map_err(db_error)?,
+                        decision,
+                        decision_reason: row.try_get("decision_reason").map_err(db_error)?,
+                        triggered_rules: row
+                            .try_get::<Option<Vec<String>>, _>("triggered_rules")
+                            .map_err(db_error)?
+                            .unwrap_or_default(),
+                        rule_scores: rule_scores
+                            .and_then(|v| serde_json::from_value(v).ok())
+                            .unwrap_or_default(),
+                        feature_values: feature_values.and_then(|v| serde_json::from_value(v).ok()),
+                        processing_time_ms: processing_time_ms.unwrap_or_default().max(0) as u64,
+                        rule_executions: Vec::new(),
+                        sampled: row.try_get("sampled").map_err(db_error)?,
+                        captured_inputs: captured_inputs
+                            .and_then(|v| serde_json::from_value(v).ok()),
+                    },
+                })
+            })
+            .collect()
+    }
+}
+
+/// Dataset file format
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    /// One JSON object per line
+    #[default]
+    Jsonl,
+
+    /// Apache Parquet (requires the `parquet` feature)
+    Parquet,
+}
+
+impl ExportFormat {
+    /// File extension of the format
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Jsonl => "jsonl",
+            ExportFormat::Parquet => "parquet",
+        }
+    }
+}
+
+/// Export configuration
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExportConfig {
+    /// Dataset file format
+    #[serde(default)]
+    pub format: ExportFormat,
+
+    /// Directory the dataset files and checkpoint are written to
+    pub output_dir: PathBuf,
+
+    /// Maximum number of decisions per file (default: 10000)
+    #[serde(default = "default_batch_size")]
+    pub batch_size: usize,
+
+    /// Whether to add `feature.<name>` columns (default: true)
+    #[serde(default = "default_include_features")]
+    pub include_features: bool,
+}
+
+fn default_batch_size() -> usize {
+    10_000
+}
+
+fn default_include_features() -> bool {
+    true
+}
+
+impl ExportConfig {
+    /// Export to `output_dir` in the given format
+    pub fn new(format: ExportFormat, output_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            format,
+            output_dir: output_dir.into(),
+            batch_size: default_batch_size(),
+            include_features: default_include_features(),
+        }
+    }
+
+    /// Set the maximum number of decisions per file
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    /// Set whether to add feature value columns
+    pub fn with_features(mut self, include_features: bool) -> Self {
+        self.include_features = include_features;
+        self
+    }
+}
+
+/// Position of the export in the decision log
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ExportCheckpoint {
+    /// ID of the last exported decision
+    pub last_id: i64,
+
+    /// Total number of decisions exported
+    pub exported: u64,
+
+    /// When the checkpoint was last advanced
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+impl ExportCheckpoint {
+    /// Load a checkpoint, starting from the beginning when the file doesn't exist
+    pub fn load(path: &Path) -> Result<Self> {
+        match std::fs::read_to_string(path) {
+            Ok(json) => serde_json::from_str(&json)
+                .map_err(|e| RuntimeError::Export(format!("Invalid checkpoint {:?}: {}", path, e))),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(io_error(path, e)),
+        }
+    }
+
+    /// Save the checkpoint atomically
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| RuntimeError::Export(format!("Failed to serialize checkpoint: {}", e)))?;
+        write_atomically(path, json.as_bytes())
+    }
+}
+
+/// Outcome of an export run
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ExportSummary {
+    /// Files written by this run
+    pub files: Vec<PathBuf>,
+
+    /// Decisions exported by this run
+    pub records: u64,
+
+    /// Checkpoint after this run
+    pub checkpoint: ExportCheckpoint,
+}
+
+/// Exports persisted decisions to dataset files
+pub struct DecisionExporter {
+    source: Arc<dyn DecisionLogSource>,
+    config: ExportConfig,
+}
+
+impl DecisionExporter {
+    /// Create an exporter reading from `source`
+    pub fn new(source: Arc<dyn DecisionLogSource>, config: ExportConfig) -> Self {
+        Self { source, config }
+    }
+
+    /// Path of the checkpoint file
+    pub fn checkpoint_path(&self) -> PathBuf {
+        self.config.output_dir.join(CHECKPOINT_FILE)
+    }
+
+    /// Export the decisions persisted since the last run
+    pub async fn export(&self) -> Result<ExportSummary> {
+        #[cfg(not(feature = "parquet"))]
+        if self.config.format == ExportFormat::Parquet {
+            return Err(RuntimeError::Export(
+                "Parquet export requires the 'parquet' feature".to_string(),
+            ));
+        }
+
+        std::fs::create_dir_all(&self.config.output_dir)
+            .map_err(|e| io_error(&self.config.output_dir, e))?;
+        let checkpoint_path = self.checkpoint_path();
+        let mut summary = ExportSummary {
+            checkpoint: ExportCheckpoint::load(&checkpoint_path)?,
+            ..Default::default()
+        };
+
+        loop {
+            let batch = self
+                .source
+                .fetch_after(summary.checkpoint.last_id, self.config.batch_size)
+                .await?;
+            let (Some(first), Some(last)) = (batch.first(), batch.last()) else {
+                break;
+            };
+
+            let path = self.config.output_dir.join(format!(
+                "decisions-{:012}-{:012}.{}",
+                first.id,
+                last.id,
+                self.config.format.extension()
+            ));
+            let rows: Vec<_> = batch
+                .iter()
+                .map(|d| flatten_decision(d, self.config.include_features))
+                .collect();
+            let data = match self.config.format {
+                ExportFormat::Jsonl => encode_jsonl(&rows)?,
+                #[cfg(feature = "parquet")]
+                ExportFormat::Parquet => encode_parquet(&rows)?,
+                #[cfg(not(feature = "parquet"))]
+                ExportFormat::Parquet => unreachable!("rejected above"),
+            };
+            write_atomically(&path, &data)?;
+
+            // Advance only after the file is in place
+            summary.checkpoint.last_id = last.id;
+            summary.checkpoint.exported += batch.len() as u64;
+            summary.checkpoint.updated_at = Some(Utc::now());
+            summary.checkpoint.save(&checkpoint_path)?;
+
+            tracing::info!("Exported {} decisions to {:?}", batch.len(), path);
+            summary.records += batch.len() as u64;
+            summary.files.push(path);
+
+            if batch.len() < self.config.batch_size {
+                break;
+            }
+        }
+
+        Ok(summary)
+    }
+}
+
+/// Flatten a decision into ordered `(column, value)` pairs
+///
+/// Triggered rules become `rule.<rule_id>` score columns and feature values
+/// `feature.<name>` columns; nested feature values are encoded as JSON text.
+pub fn flatten_decision(
+    decision: &PersistedDecision,
+    include_features: bool,
+) -> Vec<(String, JsonValue)> {
+    let record = &decision.record;
+    let signal = serde_json::to_value(&record.decision)
+        .ok()
+        .and_then(|v| v.get("type").cloned())
+        .unwrap_or(JsonValue::Null);
+
+    let mut row = vec![
+        ("id".to_string(), decision.id.into()),
+        ("request_id".to_string(), record.request_id.clone().into()),
+        ("event_id".to_string(), record.event_id.clone().into()),
+        ("pipeline_id".to_string(), record.pipeline_id.clone().into()),
+        (
+            "created_at".to_string(),
+            decision.created_at.to_rfc3339().into(),
+        ),
+        ("risk_score".to_string(), record.risk_score.into()),
+        ("decision".to_string(), signal),
+        (
+            "decision_reason".to_string(),
+            record.decision_reason.clone().into(),
+        ),
+        (
+            "processing_time_ms".to_string(),
+            record.processing_time_ms.into(),
+        ),
+        ("sampled".to_string(), record.sampled.into()),
+        (
+            "triggered_rule_count".to_string(),
+            record.triggered_rules.len().into(),
+        ),
+        (
+            "triggered_rules".to_string(),
+            record.triggered_rules.join(",").into(),
+        ),
+    ];
+
+    let rules: BTreeSet<&String> = record.triggered_rules.iter().collect();
+    for rule in rules {
+        let score = record.rule_scores.get(rule).copied().unwrap_or(0);
+        row.push((format!("rule.{}", rule), score.into()));
+    }
+
+    if include_features {
+        if let Some(features) = &record.feature_values {
+            let mut names: Vec<&String> = features.keys().collect();
+            names.sort();
+            for name in names {
+                let value = match serde_json::to_value(&features[name]) {
+                    Ok(value @ (JsonValue::Array(_) | JsonValue::Object(_))) => {
+                        value.to_string().into()
+                    }
+                    Ok(value) => value,
+                    Err(_) => JsonValue::Null,
+                };
+                row.push((format!("feature.{}", name), value));
+            }
+        }
+    }
+
+    row
+}
+
+/// Encode rows as JSON Lines
+fn encode_jsonl(rows: &[Vec<(String, JsonValue)>]) -> Result<Vec<u8>> {
+    let mut data = Vec::new();
+    for row in rows {
+        let object: serde_json::Map<String, JsonValue> = row.iter().cloned().collect();
+        serde_json::to_writer(&mut data, &object)
+            .map_err(|e| RuntimeError::Export(format!("Failed to encode row: {}", e)))?;
+        data.push(b'\n');
+    }
+    Ok(data)
+}
+
+/// Encode rows as a Parquet file
+///
+/// The schema is the union of the batch's columns. Column types are inferred
+/// from the values: integer, float, boolean, or string for anything else.
+#[cfg(feature = "parquet")]
+pub(crate) fn encode_parquet(rows: &[Vec<(String, JsonValue)>]) -> Result<Vec<u8>> {
+    use arrow_array::{ArrayRef, BooleanArray, Float64Array, Int64Array, RecordBatch, StringArray};
+    use arrow_schema::{DataType, Field, Schema};
+    use parquet::arrow::ArrowWriter;
+    use std::collections::HashMap;
+
+    let parquet_error = |e: &dyn std::fmt::Display| {
+        RuntimeError::Export(format!("Failed to encode Parquet: {}", e))
+    };
+
+    // Decision columns keep their order; rule and feature columns follow sorted
+    let mut columns: Vec<String> = Vec::new();
+    let mut dynamic: BTreeSet<String> = BTreeSet::new();
+    for row in rows {
+        for (name, _) in row {
+            if name.starts_with("rule.") || name.starts_with("feature.") {
+                dynamic.insert(name.clone());
+            } else if !columns.contains(name) {
+                columns.push(name.clone());
+            }
+        }
+    }
+    columns.extend(dynamic);
+
+    let lookups: Vec<HashMap<&str, &JsonValue>> = rows
+        .iter()
+        .map(|row| row.iter().map(|(k, v)| (k.as_str(), v)).collect())
+        .collect();
+
+    let mut fields = Vec::new();
+    let mut arrays: Vec<ArrayRef> = Vec::new();
+    for name in &columns {
+        let values: Vec<Option<&JsonValue>> = lookups
+            .iter()
+            .map(|row| row.get(name.as_str()).copied().filter(|v| !v.is_null()))
+            .collect();
+        let present = || values.iter().flatten();
+
+        let (data_type, array): (DataType, ArrayRef) = if present().all(|v| v.is_i64()) {
+            let array: Int64Array = values.iter().map(|v| v.and_then(|v| v.as_i64())).collect();
+            (DataType::Int64, Arc::new(array))
+        } else if present().all(|v| v.is_number()) {
+            let array: Float64Array = values.iter().map(|v| v.and_then(|v| v.as_f64())).collect();
+            (DataType::Float64, Arc::new(array))
+        } else if present().all(|v| v.is_boolean()) {
+            let array: BooleanArray = values.iter().map(|v| v.and_then(|v| v.as_bool())).collect();
+            (DataType::Boolean, Arc::new(array))
+        } else {
+            let array: StringArray = values
+                .iter()
+                .map(|v| {
+                    v.map(|v| match v {
+                        JsonValue::String(s) => s.clone(),
+                        other => other.to_string(),
+                    })
+                })
+                .collect();
+            (DataType::Utf8, Arc::new(array))
+        };
+        fields.push(Field::new(name, data_type, true));
+        arrays.push(array);
+    }
+
+    let schema = Arc::new(Schema::new(fields));
+    let batch = RecordBatch::try_new(Arc::clone(&schema), arrays).map_err(|e| parquet_error(&e))?;
+
+    let mut data = Vec::new();
+    let mut writer =
+        ArrowWriter::try_new(&mut data, schema, None).map_err(|e| parquet_error(&e))?;
+    writer.write(&batch).map_err(|e| parquet_error(&e))?;
+    writer.close().map_err(|e| parquet_error(&e))?;
+    Ok(data)
+}
+
+/// Write a file via a temporary file and rename, so readers never see partial data
+pub(crate) fn write_atomically(path: &Path, data: &[u8]) -> Result<()> {
+    let tmp = path.with_extension("tmp");
+    std::fs::write(&tmp, data).map_err(|e| io_error(&tmp, e))?;
+    std::fs::rename(&tmp, path).map_err(|e| io_error(path, e))
+}
+
+pub(crate) fn io_error(path: &Path, e: std::io::Error) -> RuntimeError {
+    RuntimeError::Export(format!("{:?}: {}", path, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use corint_core::ast::Signal;
+    use corint_core::Value;
+    use std::collections::HashMap;
+
+    fn record(request_id: &str, triggered: &[(&str, i32)]) -> DecisionRecord {
+        DecisionRecord {
+            request_id: request_id.to_string(),
+            event_id: None,
+            pipeline_id: "payment".to_string(),
+            risk_score: triggered.iter().map(|(_, score)| score).sum(),
+            decision: Signal::Review,
+            decision_reason: None,
+            triggered_rules: triggered.iter().map(|(id, _)| id.to_string()).collect(),
+            rule_scores: triggered
+                .iter()
+                .map(|(id, score)| (id.to_string(), *score))
+                .collect(),
+            feature_values: Some(HashMap::from([
+                ("txn_count_1h".to_string(), Value::Number(4.0)),
+                ("country".to_string(), Value::String("DE".to_string())),
+            ])),
+            processing_time_ms: 3,
+            rule_executions: Vec::new(),
+            sampled: false,
+            captured_inputs: None,
+        }
+    }
+
+    #[test]
+    fn test_flatten_decision() {
+        let decision = PersistedDecision {
+            id: 7,
+            created_at: Utc::now(),
+            record: record("req-1", &[("velocity", 40), ("new_device", 25)]),
+        };
+
+        let row: HashMap<String, JsonValue> =
+            flatten_decision(&decision, true).into_iter().collect();
+        assert_eq!(row["decision"], "review");
+        assert_eq!(row["triggered_rule_count"], 2);
+        assert_eq!(row["rule.velocity"], 40);
+        assert_eq!(row["rule.new_device"], 25);
+        assert_eq!(row["feature.country"], "DE");
+
+        let row = flatten_decision(&decision, false);
+        assert!(!row.iter().any(|(name, _)| name.starts_with("feature.")));
+    }
+
+    #[tokio::test]
+    async fn test_export_resumes_from_checkpoint() {
+        let dir = tempfile::tempdir().unwrap();
+        let log = Arc::new(InMemoryDecisionLog::new());
+        for i in 0..5 {
+            log.append(record(&format!("req-{}", i), &[("velocity", 40)]));
+        }
+
+        let config = ExportConfig::new(ExportFormat::Jsonl, dir.path()).with_batch_size(2);
+        let exporter = DecisionExporter::new(log.clone(), config);
+
+        let summary = exporter.export().await.unwrap();
+        assert_eq!(summary.records, 5);
+        assert_eq!(summary.files.len(), 3);
+        assert_eq!(summary.checkpoint.last_id, 5);
+
+        let first = std::fs::read_to_string(&summary.files[0]).unwrap();
+        let lines: Vec<JsonValue> = first
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["request_id"], "req-0");
+        assert_eq!(lines[1]["rule.velocity"], 40);
+
+        // Nothing new: no files, checkpoint unchanged
+        let summary = exporter.export().await.unwrap();
+        assert_eq!(summary.records, 0);
+        assert_eq!(summary.checkpoint.exported, 5);
+
+        log.append(record("req-5", &[]));
+        let summary = exporter.export().await.unwrap();
+        assert_eq!(summary.records, 1);
+        assert!(summary.files[0].ends_with("decisions-000000000006-000000000006.jsonl"));
+        assert_eq!(
+            ExportCheckpoint::load(&exporter.checkpoint_path())
+                .unwrap()
+                .exported,
+            6
+        );
+    }
+
+    #[cfg(feature = "parquet")]
+    #[tokio::test]
+    async fn test_export_parquet() {
+        use parquet::file::reader::{FileReader, SerializedFileReader};
+
+        let dir = tempfile::tempdir().unwrap();
+        let log = Arc::new(InMemoryDecisionLog::new());
+        log.append(record("req-0", &[("velocity", 40)]));
+        log.append(record("req-1", &[("new_device", 25)]));
+
+        let exporter =
+            DecisionExporter::new(log, ExportConfig::new(ExportFormat::Parquet, dir.path()));
+        let summary = exporter.export().await.unwrap();
+
+        let reader =
+            SerializedFileReader::new(std::fs::File::open(&summary.files[0]).unwrap()).unwrap();
+        assert_eq!(reader.metadata().file_metadata().num_rows(), 2);
+        let schema = reader.metadata().file_metadata().schema_descr();
+        let columns: Vec<&str> = schema.columns().iter().map(|c| c.name()).collect();
+        assert!(columns.contains(&"rule.velocity"));
+        assert!(columns.contains(&"rule.new_device"));
+        assert!(columns.contains(&"feature.txn_count_1h"));
+    }
+}
@@ -0,0 +1,457 @@
+//! PII masking for persisted decisions and returned traces
+//!
+//! A masking policy maps field patterns to a masking strategy (hash, truncate
+//! or redact). It is applied to decision records before they are queued for
+//! persistence and to execution traces before they leave the engine, so raw
+//! emails, card numbers and IP addresses never reach the database or API
+//! consumers.
+//!
+//! Policies live in the repository under `configs/masking/` and carry a
+//! version so persisted records can be traced back to the policy in force:
+//!
+//! ```yaml
+//! masking_policy:
+//!   version: "2024-06-01"
+//!   salt: corint
+//!   rules:
+//!     - field: email
+//!       strategy: hash
+//!     - field: "*card_number"
+//!       strategy: truncate
+//!       keep: 4
+//!     - field: event.device.ip
+//!       strategy: redact
+//! ```
+
+use crate::decision_replay::CapturedInputs;
+use crate::error::{Result, RuntimeError};
+use crate::result::{ConditionTrace, DecisionRecord, ExecutionTrace};
+use corint_core::Value;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+
+/// Replacement text for redacted values
+pub const REDACTED: &str = "[REDACTED]";
+
+/// Masking policy applied to persisted records and traces
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MaskingPolicy {
+    /// Policy version, recorded alongside masked data
+    #[serde(default)]
+    pub version: Option<String>,
+
+    /// Salt mixed into hashed values so hashes cannot be matched against
+    /// precomputed tables
+    #[serde(default)]
+    pub salt: Option<String>,
+
+    /// Masking rules, evaluated in order; the first matching rule wins
+    #[serde(default)]
+    pub rules: Vec<MaskingRule>,
+}
+
+/// A single field pattern and the strategy used to mask it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MaskingRule {
+    /// Field pattern matched against the end of a dotted field path.
+    ///
+    /// `email` matches `event.user.email` and the feature `email`;
+    /// `user.email` only matches paths ending in `user.email`. `*` matches
+    /// any run of characters within a segment (e.g. `*_ip`).
+    pub field: String,
+
+    /// How matching values are masked
+    #[serde(flatten)]
+    pub strategy: MaskingStrategy,
+}
+
+/// How a matched value is masked
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "strategy", rename_all = "snake_case")]
+pub enum MaskingStrategy {
+    /// Replace the value with a salted SHA-256 hash, so equal values stay
+    /// comparable without being readable
+    Hash,
+
+    /// Keep only the last `keep` characters (e.g. the last four PAN digits)
+    Truncate {
+        /// Number of trailing characters to keep
+        #[serde(default = "default_keep")]
+        keep: usize,
+    },
+
+    /// Replace the value entirely
+    Redact,
+}
+
+fn default_keep() -> usize {
+    4
+}
+
+#[derive(Deserialize)]
+struct MaskingPolicyDocument {
+    masking_policy: MaskingPolicy,
+}
+
+impl MaskingPolicy {
+    /// Create an empty policy that masks nothing
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parse a policy from a YAML document with a `masking_policy` root
+    pub fn from_yaml(yaml: &str) -> Result<Self> {
+        let document: MaskingPolicyDocument = serde_yaml::from_str(yaml).map_err(|e| {
+            RuntimeError::InvalidValue(format!("Invalid masking policy: {}", e))
+        })?;
+        Ok(document.masking_policy)
+    }
+
+    /// Set the policy version
+    pub fn with_version(mut self, version: impl Into<String>) -> Self {
+        self.version = Some(version.into());
+        self
+    }
+
+    /// Add a masking rule
+    pub fn add_rule(mut self, field: impl Into<String>, strategy: MaskingStrategy) -> Self {
+        self.rules.push(MaskingRule {
+            field: field.into(),
+            strategy,
+        });
+        self
+    }
+
+    /// Check whether the policy has no rules
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Merge another policy into this one.
+    ///
+    /// Rules from `other` take precedence; its version and salt replace
+    /// these when set.
+    pub fn merge(&mut self, other: MaskingPolicy) {
+        if other.version.is_some() {
+            self.version = other.version;
+        }
+        if other.salt.is_some() {
+            self.salt = other.salt;
+        }
+        let mut rules = other.rules;
+        rules.append(&mut self.rules);
+        self.rules = rules;
+    }
+
+    /// Find the strategy for a dotted field path
+    pub fn strategy_for(&self, path: &str) -> Option<&MaskingStrategy> {
+        self.rules
+            .iter()
+            .find(|rule| path_matches(&rule.field, path))
+            .map(|rule| &rule.strategy)
+    }
+
+    /// Mask a single value with the given strategy
+    pub fn mask_value(&self, strategy: &MaskingStrategy, value: &Value) -> Value {
+        match value {
+            Value::Null => Value::Null,
+            Value::Array(items) => Value::Array(
+                items
+                    .iter()
+                    .map(|item| self.mask_value(strategy, item))
+                    .collect(),
+            ),
+            Value::Object(map) => Value::Object(
+                map.iter()
+                    .map(|(k, v)| (k.clone(), self.mask_value(strategy, v)))
+                    .collect(),
+            ),
+            Value::String(s) => Value::String(self.mask_text(strategy, s)),
+            // Card numbers and similar identifiers often arrive as numbers
+            Value::Number(n) if n.fract() == 0.0 => {
+                Value::String(self.mask_text(strategy, &format!("{}", *n as i64)))
+            }
+            Value::Number(n) => Value::String(self.mask_text(strategy, &n.to_string())),
+            Value::Bool(b) => Value::String(self.mask_text(strategy, &b.to_string())),
+        }
+    }
+
+    fn mask_text(&self, strategy: &MaskingStrategy, text: &str) -> String {
+        match strategy {
+            MaskingStrategy::Hash => {
+                let mut hasher = Sha256::new();
+                if let Some(salt) = &self.salt {
+                    hasher.update(salt.as_bytes());
+                }
+                hasher.update(text.as_bytes());
+                let digest = hasher.finalize();
+                let hex: String = digest[..8].iter().map(|b| format!("{:02x}", b)).collect();
+                format!("sha256:{}", hex)
+            }
+            MaskingStrategy::Truncate { keep } => {
+                let chars: Vec<char> = text.chars().collect();
+                let kept = chars.len().min(*keep);
+                let masked = chars.len() - kept;
+                "*".repeat(masked) + &chars[masked..].iter().collect::<String>()
+            }
+            MaskingStrategy::Redact => REDACTED.to_string(),
+        }
+    }
+
+    /// Mask matching fields in a map, recursing into nested objects
+    pub fn mask_map(&self, prefix: &str, map: &mut HashMap<String, Value>) {
+        for (key, value) in map.iter_mut() {
+            let path = if prefix.is_empty() {
+                key.clone()
+            } else {
+                format!("{}.{}", prefix, key)
+            };
+            if let Some(strategy) = self.strategy_for(&path) {
+                *value = self.mask_value(strategy, value);
+            } else if let Value::Object(nested) = value {
+                self.mask_map(&path, nested);
+            }
+        }
+    }
+
+    /// Mask feature values in a decision record and its rule executions
+    pub fn mask_record(&self, record: &mut DecisionRecord) {
+        if self.is_empty() {
+            return;
+        }
+        if let Some(features) = record.feature_values.as_mut() {
+            self.mask_map("", features);
+        }
+        for rule_exec in &mut record.rule_executions {
+            if let Some(features) = rule_exec.feature_values.as_mut() {
+                self.mask_map("", features);
+            }
+        }
+        if let Some(inputs) = record.captured_inputs.as_mut() {
+            self.mask_inputs(inputs);
+        }
+    }
+
+    /// Mask the event and feature values of captured inputs
+    ///
+    /// A replay of masked inputs only reproduces the decision if its rules
+    /// do not depend on the masked values.
+    pub fn mask_inputs(&self, inputs: &mut CapturedInputs) {
+        self.mask_map("event", &mut inputs.event);
+        for (name, value) in inputs.features.iter_mut() {
+            if let Some(strategy) = self.strategy_for(name) {
+                *value = self.mask_value(strategy, value);
+            }
+        }
+    }
+
+    /// Mask operand values captured in an execution trace
+    pub fn mask_trace(&self, trace: &mut ExecutionTrace) {
+        if self.is_empty() {
+            return;
+        }
+        if let Some(inputs) = trace.inputs.as_mut() {
+            self.mask_inputs(inputs);
+        }
+        let Some(pipeline) = trace.pipeline.as_mut() else {
+            return;
+        };
+
+        let conditions = pipeline
+            .when_conditions
+            .iter_mut()
+            .chain(pipeline.branch_conditions.iter_mut())
+            .chain(pipeline.steps.iter_mut().flat_map(|s| s.conditions.iter_mut()))
+            .chain(
+                pipeline
+                    .rulesets
+                    .iter_mut()
+                    .flat_map(|rs| rs.rules.iter_mut())
+                    .flat_map(|r| r.conditions.iter_mut()),
+            );
+        for condition in conditions {
+            self.mask_condition(condition);
+        }
+    }
+
+    fn mask_condition(&self, condition: &mut ConditionTrace) {
+        if let Some(nested) = condition.nested.as_mut() {
+            for child in nested {
+                self.mask_condition(child);
+            }
+        }
+
+        // Operands are identified by the field paths at either end of the
+        // condition expression (e.g. `event.user.email == event.saved_email`)
+        let tokens: Vec<&str> = condition.expression.split_whitespace().collect();
+        let (Some(left), Some(right)) = (tokens.first(), tokens.last()) else {
+            return;
+        };
+
+        for (operand, value) in [
+            (*left, &mut condition.left_value),
+            (*right, &mut condition.right_value),
+        ] {
+            if let (Some(strategy), Some(v)) = (self.strategy_for(operand), value.as_mut()) {
+                *v = self.mask_value(strategy, v);
+            }
+        }
+    }
+}
+
+/// Check whether a field pattern matches the end of a dotted path
+fn path_matches(pattern: &str, path: &str) -> bool {
+    let pattern: Vec<&str> = pattern.split('.').collect();
+    let path: Vec<&str> = path.split('.').collect();
+    if pattern.len() > path.len() {
+        return false;
+    }
+    pattern
+        .iter()
+        .zip(&path[path.len() - pattern.len()..])
+        .all(|(p, s)| segment_matches(p, s))
+}
+
+/// Match a single path segment against a pattern with `*` wildcards
+fn segment_matches(pattern: &str, segment: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = segment.strip_prefix(first) else {
+        return false;
+    };
+
+    let parts: Vec<&str> = parts.collect();
+    let Some((last, middle)) = parts.split_last() else {
+        // No wildcard: the whole segment must match
+        return rest.is_empty();
+    };
+
+    for part in middle {
+        match rest.find(part) {
+            Some(idx) => rest = &rest[idx + part.len()..],
+            None => return false,
+        }
+    }
+    rest.ends_with(last)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::result::{PipelineTrace, RuleTrace, RulesetTrace};
+    use corint_core::ast::Signal;
+
+    fn policy() -> MaskingPolicy {
+        MaskingPolicy::from_yaml(
+            r#"
+masking_policy:
+  version: "2024-06-01"
+  salt: test
+  rules:
+    - field: email
+      strategy: hash
+    - field: "*card_number"
+      strategy: truncate
+    - field: device.ip
+      strategy: redact
+"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_path_patterns() {
+        assert!(path_matches("email", "event.user.email"));
+        assert!(path_matches("user.email", "event.user.email"));
+        assert!(!path_matches("account.email", "event.user.email"));
+        assert!(path_matches("*card_number", "event.payment.card_number"));
+        assert!(path_matches("*_ip", "login_ip"));
+        assert!(!path_matches("*_ip", "zip"));
+        assert!(path_matches("a*c*e", "abcde"));
+    }
+
+    #[test]
+    fn test_mask_strategies() {
+        let policy = policy();
+        assert_eq!(policy.version.as_deref(), Some("2024-06-01"));
+
+        let pan = policy.mask_value(
+            &MaskingStrategy::Truncate { keep: 4 },
+            &Value::Number(4111111111111111.0),
+        );
+        assert_eq!(pan, Value::String("************1111".to_string()));
+
+        let email = Value::String("alice@example.com".to_string());
+        let hashed = policy.mask_value(&MaskingStrategy::Hash, &email);
+        assert_eq!(hashed, policy.mask_value(&MaskingStrategy::Hash, &email));
+        assert!(matches!(&hashed, Value::String(s) if s.starts_with("sha256:")));
+
+        assert_eq!(
+            policy.mask_value(&MaskingStrategy::Redact, &Value::Bool(true)),
+            Value::String(REDACTED.to_string())
+        );
+    }
+
+    #[test]
+    fn test_mask_record_and_trace() {
+        let policy = policy();
+
+        let mut features = HashMap::new();
+        features.insert("email".to_string(), Value::String("a@b.co".to_string()));
+        features.insert("txn_count".to_string(), Value::Number(3.0));
+        let mut device = HashMap::new();
+        device.insert("ip".to_string(), Value::String("10.0.0.1".to_string()));
+        features.insert("device".to_string(), Value::Object(device));
+
+        let mut record = DecisionRecord {
+            request_id: "req-1".to_string(),
+            event_id: None,
+            pipeline_id: "p".to_string(),
+            risk_score: 0,
+            decision: Signal::Approve,
+            decision_reason: None,
+            triggered_rules: Vec::new(),
+            rule_scores: HashMap::new(),
+            feature_values: Some(features),
+            processing_time_ms: 1,
+            rule_executions: Vec::new(),
+            sampled: false,
+            captured_inputs: None,
+        };
+        policy.mask_record(&mut record);
+
+        let features = record.feature_values.unwrap();
+        assert!(matches!(&features["email"], Value::String(s) if s.starts_with("sha256:")));
+        assert_eq!(features["txn_count"], Value::Number(3.0));
+        let Value::Object(device) = &features["device"] else {
+            panic!("device should remain an object");
+        };
+        assert_eq!(device["ip"], Value::String(REDACTED.to_string()));
+
+        let condition = ConditionTrace::binary(
+            "event.payment.card_number == event.saved_card".to_string(),
+            Value::String("4111111111111111".to_string()),
+            "==",
+            Value::String("4000000000000002".to_string()),
+            false,
+        );
+        let mut trace = ExecutionTrace::new().with_pipeline(
+            PipelineTrace::new("p".to_string()).add_ruleset(
+                RulesetTrace::new("rs".to_string())
+                    .add_rule(RuleTrace::new("r".to_string()).add_condition(condition)),
+            ),
+        );
+        policy.mask_trace(&mut trace);
+
+        let condition = &trace.pipeline.unwrap().rulesets[0].rules[0].conditions[0];
+        assert_eq!(
+            condition.left_value,
+            Some(Value::String("************1111".to_string()))
+        );
+        assert_eq!(
+            condition.right_value,
+            Some(Value::String("4000000000000002".to_string()))
+        );
+    }
+}
@@ -1,13 +1,64 @@
 //! Execution result types and persistence
 
+#[cfg(feature = "native")]
+mod experiment;
+#[cfg(feature = "native")]
+mod export;
+#[cfg(feature = "native")]
+mod masking;
+#[cfg(feature = "native")]
+mod outcome;
+#[cfg(feature = "native")]
 mod persistence;
 #[allow(clippy::module_inception)]
 mod result;
+#[cfg(feature = "native")]
+mod retention;
+#[cfg(feature = "native")]
+mod sampling;
+#[cfg(feature = "native")]
 mod trace;
 
+#[cfg(feature = "sqlx")]
+pub use experiment::PostgresExperimentStore;
+#[cfg(feature = "native")]
+pub use experiment::{
+    summarize_experiment, ExperimentRecord, ExperimentStore, ExperimentSummary,
+    InMemoryExperimentStore, VariantDecision,
+};
+#[cfg(feature = "sqlx")]
+pub use export::PostgresDecisionLog;
+#[cfg(feature = "native")]
+pub use export::{
+    flatten_decision, DecisionExporter, DecisionLogSource, ExportCheckpoint, ExportConfig,
+    ExportFormat, ExportSummary, InMemoryDecisionLog, PersistedDecision,
+};
+#[cfg(feature = "parquet")]
+pub(crate) use export::encode_parquet;
+#[cfg(feature = "native")]
+pub(crate) use export::{io_error, write_atomically};
+#[cfg(feature = "native")]
+pub use masking::{MaskingPolicy, MaskingRule, MaskingStrategy};
+#[cfg(feature = "sqlx")]
+pub use outcome::PostgresOutcomeStore;
+#[cfg(feature = "native")]
+pub use outcome::{
+    rule_precision, InMemoryOutcomeStore, LabeledDecision, OutcomeLabel, OutcomeQuery,
+    OutcomeRecord, OutcomeStore, RulePrecision,
+};
+#[cfg(feature = "native")]
 pub use persistence::{DecisionRecord, DecisionResultWriter, RuleExecutionRecord};
 pub use result::{DecisionResult, ExecutionResult};
+#[cfg(feature = "sqlx")]
+pub use retention::DecisionRetention;
+#[cfg(feature = "native")]
+pub use retention::{PurgeSummary, RetentionConfig};
+#[cfg(feature = "sqlx")]
+pub use sampling::DecisionReviewQueue;
+#[cfg(feature = "native")]
+pub use sampling::{ReviewQueueQuery, SampledDecision, SamplingConfig, SamplingRule};
+#[cfg(feature = "native")]
 pub use trace::{
-    ConclusionTrace, ConditionTrace, ExecutionTrace, PipelineTrace, RuleTrace,
-    RulesetTrace, StepTrace,
+    ConclusionTrace, ConditionTrace, ErrorTrace, ExecutionTrace, FeatureTrace, ListTrace,
+    PipelineTrace, RuleTrace, RulesetTrace, StepTrace,
 };
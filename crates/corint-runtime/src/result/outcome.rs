@@ -0,0 +1,399 @@
+//! Decision outcome feedback
+//!
+//! Final outcomes (chargebacks, confirmed fraud, false positives) usually arrive
+//! days or weeks after a decision. They are recorded against the decision's
+//! `request_id` in the `decision_outcomes` table so that persisted decisions can
+//! be joined with their labels to measure rule precision.
+
+use crate::error::Result;
+#[cfg(feature = "sqlx")]
+use crate::error::RuntimeError;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use corint_core::Value;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Mutex;
+
+/// Final outcome of a decided request
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OutcomeLabel {
+    /// Fraud confirmed by investigation
+    ConfirmedFraud,
+    /// Chargeback received from the issuer
+    Chargeback,
+    /// Flagged by the engine but turned out legitimate
+    FalsePositive,
+    /// Legitimate activity
+    Legitimate,
+}
+
+impl OutcomeLabel {
+    /// Label name as stored in the database
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OutcomeLabel::ConfirmedFraud => "confirmed_fraud",
+            OutcomeLabel::Chargeback => "chargeback",
+            OutcomeLabel::FalsePositive => "false_positive",
+            OutcomeLabel::Legitimate => "legitimate",
+        }
+    }
+
+    /// Parse a label name
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "confirmed_fraud" => Some(OutcomeLabel::ConfirmedFraud),
+            "chargeback" => Some(OutcomeLabel::Chargeback),
+            "false_positive" => Some(OutcomeLabel::FalsePositive),
+            "legitimate" => Some(OutcomeLabel::Legitimate),
+            _ => None,
+        }
+    }
+
+    /// Whether the label marks the request as fraudulent
+    pub fn is_fraud(&self) -> bool {
+        matches!(
+            self,
+            OutcomeLabel::ConfirmedFraud | OutcomeLabel::Chargeback
+        )
+    }
+}
+
+/// Outcome attached to a past decision
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OutcomeRecord {
+    /// Request ID of the decision (links to risk_decisions)
+    pub request_id: String,
+
+    /// Outcome label
+    pub label: OutcomeLabel,
+
+    /// Free-form details (case ID, chargeback reason code, amount, ...)
+    #[serde(default)]
+    pub metadata: HashMap<String, Value>,
+
+    /// When the outcome was recorded
+    pub recorded_at: DateTime<Utc>,
+}
+
+impl OutcomeRecord {
+    /// Create an outcome recorded now
+    pub fn new(request_id: impl Into<String>, label: OutcomeLabel) -> Self {
+        Self {
+            request_id: request_id.into(),
+            label,
+            metadata: HashMap::new(),
+            recorded_at: Utc::now(),
+        }
+    }
+
+    /// Attach metadata
+    pub fn with_metadata(mut self, metadata: HashMap<String, Value>) -> Self {
+        self.metadata = metadata;
+        self
+    }
+}
+
+/// Persisted decision joined with its outcome
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LabeledDecision {
+    /// Request ID
+    pub request_id: String,
+
+    /// Pipeline that made the decision
+    pub pipeline_id: String,
+
+    /// Decision signal (e.g. "approve", "decline")
+    pub decision: String,
+
+    /// Risk score
+    pub risk_score: i32,
+
+    /// Rules triggered by the decision
+    pub triggered_rules: Vec<String>,
+
+    /// Outcome label
+    pub label: OutcomeLabel,
+}
+
+/// Filter for loading labeled decisions
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct OutcomeQuery {
+    /// Only decisions made by this pipeline
+    #[serde(default)]
+    pub pipeline_id: Option<String>,
+
+    /// Only outcomes recorded at or after this time
+    #[serde(default)]
+    pub since: Option<DateTime<Utc>>,
+
+    /// Maximum number of rows to return
+    #[serde(default)]
+    pub limit: Option<usize>,
+}
+
+/// Precision of a single rule against recorded outcomes
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RulePrecision {
+    /// Rule ID
+    pub rule_id: String,
+
+    /// Labeled decisions on which the rule triggered
+    pub triggered: u64,
+
+    /// Of those, decisions labeled as fraud
+    pub fraud: u64,
+}
+
+impl RulePrecision {
+    /// Share of labeled triggers that were fraud (None when the rule never triggered)
+    pub fn precision(&self) -> Option<f64> {
+        (self.triggered > 0).then(|| self.fraud as f64 / self.triggered as f64)
+    }
+}
+
+/// Compute per-rule precision over labeled decisions, ordered by rule ID
+pub fn rule_precision(decisions: &[LabeledDecision]) -> Vec<RulePrecision> {
+    let mut by_rule: BTreeMap<&str, RulePrecision> = BTreeMap::new();
+
+    for decision in decisions {
+        for rule_id in &decision.triggered_rules {
+            let entry = by_rule
+                .entry(rule_id.as_str())
+                .or_insert_with(|| RulePrecision {
+                    rule_id: rule_id.clone(),
+                    triggered: 0,
+                    fraud: 0,
+                });
+            entry.triggered += 1;
+            if decision.label.is_fraud() {
+                entry.fraud += 1;
+            }
+        }
+    }
+
+    by_rule.into_values().collect()
+}
+
+/// Storage for decision outcomes
+#[async_trait]
+pub trait OutcomeStore: Send + Sync {
+    /// Record (or replace) the outcome of a decision
+    async fn record_outcome(&self, outcome: OutcomeRecord) -> Result<()>;
+
+    /// Load the outcome recorded for a request
+    async fn get_outcome(&self, request_id: &str) -> Result<Option<OutcomeRecord>>;
+}
+
+/// In-memory outcome store (for tests and single-process deployments)
+#[derive(Debug, Default)]
+pub struct InMemoryOutcomeStore {
+    outcomes: Mutex<HashMap<String, OutcomeRecord>>,
+}
+
+impl InMemoryOutcomeStore {
+    /// Create an empty store
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl OutcomeStore for InMemoryOutcomeStore {
+    async fn record_outcome(&self, outcome: OutcomeRecord) -> Result<()> {
+        self.outcomes
+            .lock()
+            .unwrap()
+            .insert(outcome.request_id.clone(), outcome);
+        Ok(())
+    }
+
+    async fn get_outcome(&self, request_id: &str) -> Result<Option<OutcomeRecord>> {
+        Ok(self.outcomes.lock().unwrap().get(request_id).cloned())
+    }
+}
+
+/// Outcome store backed by the decision database
+#[cfg(feature = "sqlx")]
+#[derive(Clone)]
+pub struct PostgresOutcomeStore {
+    pool: sqlx::PgPool,
+}
+
+#[cfg(feature = "sqlx")]
+impl PostgresOutcomeStore {
+    /// Create a store over the decision database pool
+    pub fn new(pool: sqlx::PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Load persisted decisions joined with their outcomes, newest outcome first
+    pub async fn labeled_decisions(&self, query: &OutcomeQuery) -> Result<Vec<LabeledDecision>> {
+        use sqlx::Row;
+
+        let rows = sqlx::query(
+            r#"
+            SELECT d.request_id, d.pipeline_id, d.decision, d.risk_score::INT AS risk_score,
+                   d.triggered_rules, o.label
+            FROM decision_outcomes o
+            JOIN risk_decisions d ON d.request_id = o.request_id
+            WHERE ($1::TEXT IS NULL OR d.pipeline_id = $1)
+              AND ($2::TIMESTAMPTZ IS NULL OR o.recorded_at >= $2)
+            ORDER BY o.recorded_at DESC
+            LIMIT $3
+            "#,
+        )
+        .bind(query.pipeline_id.as_deref())
+        .bind(query.since)
+        .bind(query.limit.map(|limit| limit as i64).unwrap_or(i64::MAX))
+        .fetch_all(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        rows.into_iter()
+            .map(|row| {
+                let label: String = row.try_get("label").map_err(db_error)?;
+                Ok(LabeledDecision {
+                    request_id: row.try_get("request_id").map_err(db_error)?,
+                    pipeline_id: row.try_get("pipeline_id").map_err(db_error)?,
+                    decision: row.try_get("decision").map_err(db_error)?,
+                    risk_score: row.try_get("risk_score").map_err(db_error)?,
+                    triggered_rules: row
+                        .try_get::<Option<Vec<String>>, _>("triggered_rules")
+                        .map_err(db_error)?
+                        .unwrap_or_default(),
+                    label: OutcomeLabel::parse(&label).ok_or_else(|| {
+                        RuntimeError::InvalidValue(format!("Unknown outcome label '{}'", label))
+                    })?,
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(feature = "sqlx")]
+#[async_trait]
+impl OutcomeStore for PostgresOutcomeStore {
+    async fn record_outcome(&self, outcome: OutcomeRecord) -> Result<()> {
+        let metadata = serde_json::to_value(&outcome.metadata).map_err(|e| {
+            RuntimeError::RuntimeError(format!("Failed to serialize outcome metadata: {}", e))
+        })?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO decision_outcomes (request_id, label, metadata, recorded_at)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (request_id) DO UPDATE SET
+                label = EXCLUDED.label,
+                metadata = EXCLUDED.metadata,
+                recorded_at = EXCLUDED.recorded_at
+            "#,
+        )
+        .bind(&outcome.request_id)
+        .bind(outcome.label.as_str())
+        .bind(&metadata)
+        .bind(outcome.recorded_at)
+        .execute(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        Ok(())
+    }
+
+    async fn get_outcome(&self, request_id: &str) -> Result<Option<OutcomeRecord>> {
+        use sqlx::Row;
+
+        let row = sqlx::query(
+            "SELECT label, metadata, recorded_at FROM decision_outcomes WHERE request_id = $1",
+        )
+        .bind(request_id)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        let Some(row) = row else {
+            return Ok(None);
+        };
+
+        let label: String = row.try_get("label").map_err(db_error)?;
+        let metadata: Option<serde_json::Value> = row.try_get("metadata").map_err(db_error)?;
+        Ok(Some(OutcomeRecord {
+            request_id: request_id.to_string(),
+            label: OutcomeLabel::parse(&label).ok_or_else(|| {
+                RuntimeError::InvalidValue(format!("Unknown outcome label '{}'", label))
+            })?,
+            metadata: metadata
+                .map(serde_json::from_value)
+                .transpose()
+                .map_err(|e| {
+                    RuntimeError::RuntimeError(format!(
+                        "Failed to deserialize outcome metadata: {}",
+                        e
+                    ))
+                })?
+                .unwrap_or_default(),
+            recorded_at: row.try_get("recorded_at").map_err(db_error)?,
+        }))
+    }
+}
+
+#[cfg(feature = "sqlx")]
+fn db_error(e: sqlx::Error) -> RuntimeError {
+    RuntimeError::RuntimeError(format!("Outcome database error: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn labeled(request_id: &str, rules: &[&str], label: OutcomeLabel) -> LabeledDecision {
+        LabeledDecision {
+            request_id: request_id.to_string(),
+            pipeline_id: "payments".to_string(),
+            decision: "decline".to_string(),
+            risk_score: 80,
+            triggered_rules: rules.iter().map(|r| r.to_string()).collect(),
+            label,
+        }
+    }
+
+    #[test]
+    fn test_rule_precision() {
+        let decisions = vec![
+            labeled("r1", &["velocity", "new_device"], OutcomeLabel::Chargeback),
+            labeled("r2", &["velocity"], OutcomeLabel::FalsePositive),
+            labeled("r3", &["new_device"], OutcomeLabel::ConfirmedFraud),
+        ];
+
+        let precision = rule_precision(&decisions);
+        assert_eq!(precision.len(), 2);
+        assert_eq!(precision[0].rule_id, "new_device");
+        assert_eq!(precision[0].precision(), Some(1.0));
+        assert_eq!(precision[1].rule_id, "velocity");
+        assert_eq!(precision[1].precision(), Some(0.5));
+
+        assert_eq!(
+            OutcomeLabel::parse("chargeback"),
+            Some(OutcomeLabel::Chargeback)
+        );
+        assert_eq!(OutcomeLabel::parse("refund"), None);
+    }
+
+    #[tokio::test]
+    async fn test_in_memory_store_replaces_outcome() {
+        let store = InMemoryOutcomeStore::new();
+        store
+            .record_outcome(OutcomeRecord::new("req-1", OutcomeLabel::FalsePositive))
+            .await
+            .unwrap();
+        store
+            .record_outcome(OutcomeRecord::new("req-1", OutcomeLabel::Chargeback))
+            .await
+            .unwrap();
+
+        let outcome = store.get_outcome("req-1").await.unwrap().unwrap();
+        assert_eq!(outcome.label, OutcomeLabel::Chargeback);
+        assert!(store.get_outcome("req-2").await.unwrap().is_none());
+    }
+}
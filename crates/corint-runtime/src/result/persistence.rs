@@ -8,13 +8,18 @@
 //! Tables:
 //! - risk_decisions: Main decision results
 //! - rule_executions: Individual rule execution logs
+//!
+//! Decisions selected by the sampling configuration are flagged `sampled`
+//! and surface in the review queue.
 
+use crate::decision_replay::CapturedInputs;
 use crate::error::{Result, RuntimeError};
-use crate::result::DecisionResult;
+use crate::result::{DecisionResult, MaskingPolicy};
 use corint_core::ast::Signal;
 use corint_core::Value;
 use serde_json;
 use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
 use tokio::sync::mpsc;
 
 /// Rule execution record for persistence
@@ -53,6 +58,9 @@ pub struct RuleExecutionRecord {
 
     /// Condition group JSON for new all/any format
     pub condition_group_json: Option<String>,
+
+    /// Why the rule was not evaluated (e.g. "expired"), if it was skipped
+    pub skip_reason: Option<String>,
 }
 
 /// Decision result record for persistence
@@ -90,12 +98,21 @@ pub struct DecisionRecord {
 
     /// Rule execution records
     pub rule_executions: Vec<RuleExecutionRecord>,
+
+    /// Whether the decision was sampled for quality review
+    pub sampled: bool,
+
+    /// Inputs the decision consumed, to replay it (if input capture is enabled)
+    pub captured_inputs: Option<CapturedInputs>,
 }
 
 /// Async decision result writer that queues writes to avoid blocking decision execution
 pub struct DecisionResultWriter {
     /// Channel sender for queuing decision records
     sender: mpsc::UnboundedSender<DecisionRecord>,
+
+    /// PII masking policy applied to records before they are queued
+    masking_policy: RwLock<Arc<MaskingPolicy>>,
 }
 
 impl DecisionResultWriter {
@@ -113,18 +130,33 @@ impl DecisionResultWriter {
 
         tracing::info!("DecisionResultWriter created, background task spawned");
 
-        Self { sender }
+        Self {
+            sender,
+            masking_policy: RwLock::new(Arc::new(MaskingPolicy::new())),
+        }
     }
 
     /// Create a new decision result writer without database (no-op)
     #[cfg(not(feature = "sqlx"))]
     pub fn new() -> Self {
         let (sender, _receiver) = mpsc::unbounded_channel();
-        Self { sender }
+        Self {
+            sender,
+            masking_policy: RwLock::new(Arc::new(MaskingPolicy::new())),
+        }
+    }
+
+    /// Replace the masking policy applied to subsequently written records
+    pub fn set_masking_policy(&self, policy: Arc<MaskingPolicy>) {
+        *self.masking_policy.write().unwrap() = policy;
     }
 
     /// Write a decision result record asynchronously
-    pub fn write_decision(&self, record: DecisionRecord) -> Result<()> {
+    ///
+    /// Fields matched by the masking policy are masked before the record is queued.
+    pub fn write_decision(&self, mut record: DecisionRecord) -> Result<()> {
+        self.masking_policy.read().unwrap().mask_record(&mut record);
+
         self.sender.send(record).map_err(|e| {
             RuntimeError::RuntimeError(format!("Failed to queue decision record: {}", e))
         })
@@ -198,12 +230,22 @@ impl DecisionResultWriter {
             None
         };
 
+        let captured_inputs_json = record
+            .captured_inputs
+            .as_ref()
+            .map(serde_json::to_value)
+            .transpose()
+            .map_err(|e| {
+                RuntimeError::RuntimeError(format!("Failed to serialize captured_inputs: {}", e))
+            })?;
+
         let insert_result = sqlx::query(
             r#"
             INSERT INTO risk_decisions (
                 request_id, event_id, pipeline_id, risk_score, decision, decision_reason,
-                triggered_rules, rule_scores, feature_values, processing_time_ms
-            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+                triggered_rules, rule_scores, feature_values, processing_time_ms, sampled,
+                captured_inputs
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
             ON CONFLICT (request_id) DO UPDATE SET
                 event_id = EXCLUDED.event_id,
                 pipeline_id = EXCLUDED.pipeline_id,
@@ -213,7 +255,9 @@ impl DecisionResultWriter {
                 triggered_rules = EXCLUDED.triggered_rules,
                 rule_scores = EXCLUDED.rule_scores,
                 feature_values = EXCLUDED.feature_values,
-                processing_time_ms = EXCLUDED.processing_time_ms
+                processing_time_ms = EXCLUDED.processing_time_ms,
+                sampled = EXCLUDED.sampled,
+                captured_inputs = EXCLUDED.captured_inputs
             "#,
         )
         .bind(&record.request_id)
@@ -226,6 +270,8 @@ impl DecisionResultWriter {
         .bind(&rule_scores_json)
         .bind(feature_values_json.as_ref())
         .bind(record.processing_time_ms as i32)
+        .bind(record.sampled)
+        .bind(captured_inputs_json.as_ref())
         .execute(&mut *tx)
         .await;
 
@@ -260,6 +306,11 @@ impl DecisionResultWriter {
 
         // 2. Insert rule execution records
         for rule_exec in &record.rule_executions {
+            // Skipped rules were never evaluated; they only appear in traces
+            if rule_exec.skip_reason.is_some() {
+                continue;
+            }
+
             let feature_values_json = if let Some(ref fv) = rule_exec.feature_values {
                 Some(serde_json::to_value(fv).map_err(|e| {
                     RuntimeError::RuntimeError(format!(
@@ -355,13 +406,19 @@ impl Default for DecisionResultWriter {
     fn default() -> Self {
         // Create a no-op writer if no pool is provided
         let (sender, _receiver) = mpsc::unbounded_channel();
-        Self { sender }
+        Self {
+            sender,
+            masking_policy: RwLock::new(Arc::new(MaskingPolicy::new())),
+        }
     }
 
     #[cfg(not(feature = "sqlx"))]
     fn default() -> Self {
         let (sender, _receiver) = mpsc::unbounded_channel();
-        Self { sender }
+        Self {
+            sender,
+            masking_policy: RwLock::new(Arc::new(MaskingPolicy::new())),
+        }
     }
 }
 
@@ -411,6 +468,8 @@ impl DecisionRecord {
             feature_values,
             processing_time_ms,
             rule_executions,
+            sampled: false,
+            captured_inputs: None,
         }
     }
 }
@@ -24,6 +24,10 @@ pub struct DecisionResult {
     /// Explanation/reason for the decision
     pub explanation: String,
 
+    /// Reason code from the reason code catalog, if the decision set one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason_code: Option<String>,
+
     /// Additional context data
     pub context: HashMap<String, Value>,
 }
@@ -46,6 +50,9 @@ pub struct ExecutionResult {
     /// Explicit explanation/reason (overrides auto-generated explanation)
     pub explicit_explanation: Option<String>,
 
+    /// Reason code set by the decision logic
+    pub reason_code: Option<String>,
+
     /// Variables stored during execution
     pub variables: HashMap<String, Value>,
 }
@@ -59,6 +66,7 @@ impl DecisionResult {
             score,
             triggered_rules: Vec::new(),
             explanation: String::new(),
+            reason_code: None,
             context: HashMap::new(),
         }
     }
@@ -95,6 +103,7 @@ impl ExecutionResult {
             signal: None,
             actions: Vec::new(),
             explicit_explanation: None,
+            reason_code: None,
             variables: HashMap::new(),
         }
     }
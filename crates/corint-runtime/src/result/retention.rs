@@ -0,0 +1,409 @@
+//! Decision data retention and subject purge
+//!
+//! Supports data-protection requirements for persisted decisions:
+//! - Time-based retention (TTL per record type)
+//! - Erasure of a single subject's decision history (`purge_by_entity`)
+//!
+//! Every subject purge writes an entry to the `decision_purge_audit` table.
+//! The purged value itself is never stored, only its SHA-256 hash.
+
+#[cfg(any(feature = "sqlx", test))]
+use crate::error::{Result, RuntimeError};
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "sqlx")]
+use sha2::{Digest, Sha256};
+
+/// Retention configuration for persisted decision data
+///
+/// Each TTL is expressed in days. A missing TTL keeps records indefinitely.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct RetentionConfig {
+    /// TTL for rows in `risk_decisions`
+    #[serde(default)]
+    pub risk_decisions_ttl_days: Option<u32>,
+
+    /// TTL for rows in `rule_executions`
+    #[serde(default)]
+    pub rule_executions_ttl_days: Option<u32>,
+
+    /// TTL for rows in `events`
+    #[serde(default)]
+    pub events_ttl_days: Option<u32>,
+
+    /// Interval between expired-record sweeps, in seconds
+    #[serde(default = "default_purge_interval_secs")]
+    pub purge_interval_secs: u64,
+}
+
+fn default_purge_interval_secs() -> u64 {
+    3600
+}
+
+impl Default for RetentionConfig {
+    fn default() -> Self {
+        Self {
+            risk_decisions_ttl_days: None,
+            rule_executions_ttl_days: None,
+            events_ttl_days: None,
+            purge_interval_secs: default_purge_interval_secs(),
+        }
+    }
+}
+
+impl RetentionConfig {
+    /// Create an empty retention config (keep everything)
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the TTL for decision records
+    pub fn with_risk_decisions_ttl_days(mut self, days: u32) -> Self {
+        self.risk_decisions_ttl_days = Some(days);
+        self
+    }
+
+    /// Set the TTL for rule execution records
+    pub fn with_rule_executions_ttl_days(mut self, days: u32) -> Self {
+        self.rule_executions_ttl_days = Some(days);
+        self
+    }
+
+    /// Set the TTL for raw event records
+    pub fn with_events_ttl_days(mut self, days: u32) -> Self {
+        self.events_ttl_days = Some(days);
+        self
+    }
+
+    /// Whether any TTL is configured
+    pub fn is_enabled(&self) -> bool {
+        self.risk_decisions_ttl_days.is_some()
+            || self.rule_executions_ttl_days.is_some()
+            || self.events_ttl_days.is_some()
+    }
+
+    /// Table/TTL pairs in deletion order (children before parents)
+    #[cfg(any(feature = "sqlx", test))]
+    fn table_ttls(&self) -> Vec<(&'static str, &'static str, u32)> {
+        [
+            (
+                "rule_executions",
+                "created_at",
+                self.rule_executions_ttl_days,
+            ),
+            ("risk_decisions", "created_at", self.risk_decisions_ttl_days),
+            ("events", "event_timestamp", self.events_ttl_days),
+        ]
+        .into_iter()
+        .filter_map(|(table, column, ttl)| ttl.map(|days| (table, column, days)))
+        .collect()
+    }
+}
+
+/// Number of rows removed by a purge, per record type
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct PurgeSummary {
+    /// Rows deleted from `risk_decisions`
+    pub risk_decisions: u64,
+
+    /// Rows deleted from `rule_executions`
+    pub rule_executions: u64,
+
+    /// Rows deleted from `events`
+    pub events: u64,
+
+    /// Rows deleted from `decision_outcomes`
+    #[serde(default)]
+    pub decision_outcomes: u64,
+
+    /// Rows deleted from `experiment_results`
+    #[serde(default)]
+    pub experiment_results: u64,
+}
+
+impl PurgeSummary {
+    /// Total number of rows deleted
+    pub fn total(&self) -> u64 {
+        self.risk_decisions
+            + self.rule_executions
+            + self.events
+            + self.decision_outcomes
+            + self.experiment_results
+    }
+}
+
+/// Where an entity field lives in the `events` table
+#[cfg(any(feature = "sqlx", test))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum EventField {
+    /// A dedicated column
+    Column(&'static str),
+    /// A path inside the `attributes` JSONB column
+    Attribute(Vec<String>),
+}
+
+/// Where an entity field lives in the `risk_decisions` table
+#[cfg(any(feature = "sqlx", test))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum DecisionField {
+    /// A dedicated column
+    Column(&'static str),
+    /// A path inside the `feature_values` JSONB column
+    Feature(Vec<String>),
+}
+
+/// Resolve an entity field (e.g. `user_id`, `event.email`) to table locations
+///
+/// Column names are taken from a fixed list so that they are never
+/// interpolated from user input; other fields are bound as JSONB paths.
+#[cfg(any(feature = "sqlx", test))]
+fn resolve_field(field: &str) -> Result<(EventField, DecisionField)> {
+    let field = field.strip_prefix("event.").unwrap_or(field).trim();
+    if field.is_empty() || field.split('.').any(|segment| segment.is_empty()) {
+        return Err(RuntimeError::InvalidValue(format!(
+            "Invalid entity field '{}'",
+            field
+        )));
+    }
+
+    let path: Vec<String> = field.split('.').map(String::from).collect();
+    let resolved = match field {
+        "request_id" => (
+            EventField::Column("request_id"),
+            DecisionField::Column("request_id"),
+        ),
+        "event_id" => (
+            EventField::Column("event_id"),
+            DecisionField::Column("event_id"),
+        ),
+        "user_id" => (EventField::Column("user_id"), DecisionField::Feature(path)),
+        "device_id" => (
+            EventField::Column("device_id"),
+            DecisionField::Feature(path),
+        ),
+        "ip_address" => (
+            EventField::Column("host(ip_address)"),
+            DecisionField::Feature(path),
+        ),
+        _ => (
+            EventField::Attribute(path.clone()),
+            DecisionField::Feature(path),
+        ),
+    };
+    Ok(resolved)
+}
+
+/// Hash a purged value for the audit trail
+#[cfg(feature = "sqlx")]
+fn hash_value(value: &str) -> String {
+    let digest = Sha256::digest(value.as_bytes());
+    digest.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Retention and purge operations on persisted decision data
+#[cfg(feature = "sqlx")]
+#[derive(Clone)]
+pub struct DecisionRetention {
+    pool: sqlx::PgPool,
+}
+
+#[cfg(feature = "sqlx")]
+impl DecisionRetention {
+    /// Create a retention service over the decision database pool
+    pub fn new(pool: sqlx::PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Delete every decision, rule execution, outcome and event linked to an entity
+    ///
+    /// Records are matched when `field` equals `value` on the raw event or in the
+    /// decision's feature values. Deletion and the audit entry share one transaction.
+    pub async fn purge_by_entity(
+        &self,
+        field: &str,
+        value: &str,
+        actor: &str,
+        reason: Option<&str>,
+    ) -> Result<PurgeSummary> {
+        let (event_field, decision_field) = resolve_field(field)?;
+
+        let event_condition = match &event_field {
+            EventField::Column(column) => format!("{} = $1", column),
+            EventField::Attribute(_) => "attributes #>> $2 = $1".to_string(),
+        };
+        let decision_condition = match &decision_field {
+            DecisionField::Column(column) => format!("{} = $1", column),
+            DecisionField::Feature(_) => "feature_values #>> $2 = $1".to_string(),
+        };
+        let path = match (&event_field, &decision_field) {
+            (EventField::Attribute(path), _) | (_, DecisionField::Feature(path)) => {
+                Some(path.clone())
+            }
+            _ => None,
+        };
+
+        let mut tx = self.pool.begin().await.map_err(db_error)?;
+
+        let sql = format!(
+            "SELECT request_id FROM events WHERE {} \
+             UNION SELECT request_id FROM risk_decisions WHERE {}",
+            event_condition, decision_condition
+        );
+        let mut query = sqlx::query_scalar::<_, String>(&sql).bind(value);
+        if let Some(path) = &path {
+            query = query.bind(path);
+        }
+        let request_ids = query.fetch_all(&mut *tx).await.map_err(db_error)?;
+
+        let mut summary = PurgeSummary::default();
+        if !request_ids.is_empty() {
+            summary.rule_executions =
+                sqlx::query("DELETE FROM rule_executions WHERE request_id = ANY($1)")
+                    .bind(&request_ids)
+                    .execute(&mut *tx)
+                    .await
+                    .map_err(db_error)?
+                    .rows_affected();
+            summary.risk_decisions =
+                sqlx::query("DELETE FROM risk_decisions WHERE request_id = ANY($1)")
+                    .bind(&request_ids)
+                    .execute(&mut *tx)
+                    .await
+                    .map_err(db_error)?
+                    .rows_affected();
+            summary.decision_outcomes =
+                sqlx::query("DELETE FROM decision_outcomes WHERE request_id = ANY($1)")
+                    .bind(&request_ids)
+                    .execute(&mut *tx)
+                    .await
+                    .map_err(db_error)?
+                    .rows_affected();
+            summary.experiment_results =
+                sqlx::query("DELETE FROM experiment_results WHERE request_id = ANY($1)")
+                    .bind(&request_ids)
+                    .execute(&mut *tx)
+                    .await
+                    .map_err(db_error)?
+                    .rows_affected();
+            summary.events = sqlx::query("DELETE FROM events WHERE request_id = ANY($1)")
+                .bind(&request_ids)
+                .execute(&mut *tx)
+                .await
+                .map_err(db_error)?
+                .rows_affected();
+        }
+
+        sqlx::query(
+            r#"
+            INSERT INTO decision_purge_audit (
+                actor, entity_field, entity_value_hash, reason,
+                risk_decisions_deleted, rule_executions_deleted, events_deleted
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7)
+            "#,
+        )
+        .bind(actor)
+        .bind(field)
+        .bind(hash_value(value))
+        .bind(reason)
+        .bind(summary.risk_decisions as i64)
+        .bind(summary.rule_executions as i64)
+        .bind(summary.events as i64)
+        .execute(&mut *tx)
+        .await
+        .map_err(db_error)?;
+
+        tx.commit().await.map_err(db_error)?;
+
+        tracing::info!(
+            "Purged {} records for entity field '{}' (actor: {})",
+            summary.total(),
+            field,
+            actor
+        );
+
+        Ok(summary)
+    }
+
+    /// Delete records older than their configured TTL
+    pub async fn purge_expired(&self, config: &RetentionConfig) -> Result<PurgeSummary> {
+        let mut summary = PurgeSummary::default();
+
+        for (table, column, days) in config.table_ttls() {
+            let deleted = sqlx::query(&format!(
+                "DELETE FROM {} WHERE {} < NOW() - make_interval(days => $1)",
+                table, column
+            ))
+            .bind(days as i32)
+            .execute(&self.pool)
+            .await
+            .map_err(db_error)?
+            .rows_affected();
+
+            match table {
+                "rule_executions" => summary.rule_executions = deleted,
+                "risk_decisions" => summary.risk_decisions = deleted,
+                _ => summary.events = deleted,
+            }
+        }
+
+        if summary.total() > 0 {
+            tracing::info!(
+                "Retention sweep removed {} expired records",
+                summary.total()
+            );
+        }
+
+        Ok(summary)
+    }
+}
+
+#[cfg(feature = "sqlx")]
+fn db_error(e: sqlx::Error) -> RuntimeError {
+    RuntimeError::RuntimeError(format!("Retention database error: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_field() {
+        let (event, decision) = resolve_field("user_id").unwrap();
+        assert_eq!(event, EventField::Column("user_id"));
+        assert_eq!(
+            decision,
+            DecisionField::Feature(vec!["user_id".to_string()])
+        );
+
+        let (event, decision) = resolve_field("event.customer.email").unwrap();
+        assert_eq!(
+            event,
+            EventField::Attribute(vec!["customer".to_string(), "email".to_string()])
+        );
+        assert!(matches!(decision, DecisionField::Feature(_)));
+
+        assert!(resolve_field("").is_err());
+        assert!(resolve_field("customer..email").is_err());
+    }
+
+    #[test]
+    fn test_retention_config_ttls() {
+        let config: RetentionConfig = serde_yaml::from_str(
+            r#"
+risk_decisions_ttl_days: 365
+events_ttl_days: 90
+"#,
+        )
+        .unwrap();
+
+        assert!(config.is_enabled());
+        assert_eq!(config.purge_interval_secs, 3600);
+        assert_eq!(
+            config.table_ttls(),
+            vec![
+                ("risk_decisions", "created_at", 365),
+                ("events", "event_timestamp", 90)
+            ]
+        );
+        assert!(!RetentionConfig::new().is_enabled());
+    }
+}
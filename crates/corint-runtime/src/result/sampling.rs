@@ -0,0 +1,276 @@
+//! Decision sampling for quality review
+//!
+//! A fraction of decisions is flagged as `sampled` when persisted, so that
+//! analysts can review them manually. Sampled decisions that have no recorded
+//! outcome yet form the review queue.
+
+use crate::result::DecisionRecord;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Sampling rule applied to decisions matching a signal and score range
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SamplingRule {
+    /// Decision signal to match (e.g. "approve", "decline"); any signal when unset
+    #[serde(default)]
+    pub signal: Option<String>,
+
+    /// Minimum risk score (inclusive) to match
+    #[serde(default)]
+    pub min_score: Option<i32>,
+
+    /// Maximum risk score (inclusive) to match
+    #[serde(default)]
+    pub max_score: Option<i32>,
+
+    /// Fraction of matching decisions to sample (0.0 - 1.0)
+    pub rate: f64,
+}
+
+impl SamplingRule {
+    /// Create a rule sampling `rate` of all decisions
+    pub fn new(rate: f64) -> Self {
+        Self {
+            signal: None,
+            min_score: None,
+            max_score: None,
+            rate,
+        }
+    }
+
+    /// Only match decisions with this signal
+    pub fn with_signal(mut self, signal: impl Into<String>) -> Self {
+        self.signal = Some(signal.into());
+        self
+    }
+
+    /// Only match decisions scoring at least `score`
+    pub fn with_min_score(mut self, score: i32) -> Self {
+        self.min_score = Some(score);
+        self
+    }
+
+    /// Only match decisions scoring at most `score`
+    pub fn with_max_score(mut self, score: i32) -> Self {
+        self.max_score = Some(score);
+        self
+    }
+
+    fn matches(&self, signal: &str, score: i32) -> bool {
+        self.signal
+            .as_deref()
+            .is_none_or(|expected| expected.eq_ignore_ascii_case(signal))
+            && self.min_score.is_none_or(|min| score >= min)
+            && self.max_score.is_none_or(|max| score <= max)
+    }
+}
+
+/// Sampling configuration
+///
+/// When several rules match a decision, the highest rate applies.
+///
+/// ```yaml
+/// sampling:
+///   rules:
+///     - signal: approve
+///       rate: 0.01
+///     - signal: decline
+///       min_score: 90
+///       rate: 1.0
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SamplingConfig {
+    /// Sampling rules (no rules disables sampling)
+    #[serde(default)]
+    pub rules: Vec<SamplingRule>,
+}
+
+impl SamplingConfig {
+    /// Create an empty configuration (nothing is sampled)
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a sampling rule
+    pub fn with_rule(mut self, rule: SamplingRule) -> Self {
+        self.rules.push(rule);
+        self
+    }
+
+    /// Whether any sampling rule is configured
+    pub fn is_enabled(&self) -> bool {
+        !self.rules.is_empty()
+    }
+
+    /// Sampling rate for a decision (0.0 when no rule matches)
+    pub fn rate_for(&self, signal: &str, score: i32) -> f64 {
+        self.rules
+            .iter()
+            .filter(|rule| rule.matches(signal, score))
+            .map(|rule| rule.rate)
+            .fold(0.0, f64::max)
+    }
+
+    /// Decide whether to sample a record, given a uniform random `roll` in [0, 1)
+    pub fn should_sample(&self, record: &DecisionRecord, roll: f64) -> bool {
+        let signal = format!("{:?}", record.decision).to_lowercase();
+        roll < self.rate_for(&signal, record.risk_score)
+    }
+}
+
+/// Filter for the review queue
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ReviewQueueQuery {
+    /// Only decisions made by this pipeline
+    #[serde(default)]
+    pub pipeline_id: Option<String>,
+
+    /// Only decisions with this signal (e.g. "decline")
+    #[serde(default)]
+    pub decision: Option<String>,
+
+    /// Only decisions made at or after this time
+    #[serde(default)]
+    pub since: Option<DateTime<Utc>>,
+
+    /// Also return sampled decisions that already have an outcome
+    #[serde(default)]
+    pub include_labeled: bool,
+
+    /// Maximum number of decisions to return (default: 100)
+    #[serde(default)]
+    pub limit: Option<usize>,
+}
+
+/// Sampled decision awaiting review
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SampledDecision {
+    /// Request ID
+    pub request_id: String,
+
+    /// Optional business event ID
+    pub event_id: Option<String>,
+
+    /// Pipeline that made the decision
+    pub pipeline_id: String,
+
+    /// Risk score
+    pub risk_score: i32,
+
+    /// Decision signal
+    pub decision: String,
+
+    /// Decision reason/explanation
+    pub decision_reason: Option<String>,
+
+    /// Triggered rule IDs
+    pub triggered_rules: Vec<String>,
+
+    /// When the decision was made
+    pub created_at: DateTime<Utc>,
+}
+
+/// Review queue over sampled decisions in the decision database
+#[cfg(feature = "sqlx")]
+#[derive(Clone)]
+pub struct DecisionReviewQueue {
+    pool: sqlx::PgPool,
+}
+
+#[cfg(feature = "sqlx")]
+impl DecisionReviewQueue {
+    /// Create a review queue over the decision database pool
+    pub fn new(pool: sqlx::PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Fetch sampled decisions, newest first
+    ///
+    /// Decisions with a recorded outcome are excluded unless `include_labeled` is set.
+    pub async fn fetch(
+        &self,
+        query: &ReviewQueueQuery,
+    ) -> crate::error::Result<Vec<SampledDecision>> {
+        use sqlx::Row;
+
+        let rows = sqlx::query(
+            r#"
+            SELECT d.request_id, d.event_id, d.pipeline_id, d.risk_score::INT AS risk_score,
+                   d.decision, d.decision_reason, d.triggered_rules, d.created_at
+            FROM risk_decisions d
+            WHERE d.sampled
+              AND ($1::TEXT IS NULL OR d.pipeline_id = $1)
+              AND ($2::TEXT IS NULL OR d.decision = $2)
+              AND ($3::TIMESTAMPTZ IS NULL OR d.created_at >= $3)
+              AND ($4 OR NOT EXISTS (
+                  SELECT 1 FROM decision_outcomes o WHERE o.request_id = d.request_id
+              ))
+            ORDER BY d.created_at DESC
+            LIMIT $5
+            "#,
+        )
+        .bind(query.pipeline_id.as_deref())
+        .bind(query.decision.as_deref())
+        .bind(query.since)
+        .bind(query.include_labeled)
+        .bind(query.limit.unwrap_or(100) as i64)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        rows.into_iter()
+            .map(|row| {
+                Ok(SampledDecision {
+                    request_id: row.try_get("request_id").map_err(db_error)?,
+                    event_id: row.try_get("event_id").map_err(db_error)?,
+                    pipeline_id: row.try_get("pipeline_id").map_err(db_error)?,
+                    risk_score: row.try_get("risk_score").map_err(db_error)?,
+                    decision: row.try_get("decision").map_err(db_error)?,
+                    decision_reason: row.try_get("decision_reason").map_err(db_error)?,
+                    triggered_rules: row
+                        .try_get::<Option<Vec<String>>, _>("triggered_rules")
+                        .map_err(db_error)?
+                        .unwrap_or_default(),
+                    created_at: row.try_get("created_at").map_err(db_error)?,
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(feature = "sqlx")]
+fn db_error(e: sqlx::Error) -> crate::error::RuntimeError {
+    crate::error::RuntimeError::RuntimeError(format!("Review queue database error: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sampling_rates() {
+        let config: SamplingConfig = serde_yaml::from_str(
+            r#"
+rules:
+  - signal: approve
+    rate: 0.01
+  - signal: decline
+    min_score: 90
+    rate: 1.0
+"#,
+        )
+        .unwrap();
+
+        assert!(config.is_enabled());
+        assert_eq!(config.rate_for("approve", 10), 0.01);
+        assert_eq!(config.rate_for("decline", 95), 1.0);
+        assert_eq!(config.rate_for("decline", 60), 0.0);
+        assert_eq!(config.rate_for("review", 95), 0.0);
+
+        let overlapping = config.with_rule(SamplingRule::new(0.05).with_min_score(50));
+        assert_eq!(overlapping.rate_for("approve", 60), 0.05);
+        assert_eq!(overlapping.rate_for("approve", 10), 0.01);
+
+        assert_eq!(SamplingConfig::new().rate_for("decline", 100), 0.0);
+    }
+}
@@ -3,6 +3,10 @@
 //! These structures capture detailed information about rule evaluation,
 //! condition matching, and decision logic execution.
 
+use crate::decision_replay::CapturedInputs;
+use crate::engine::TimeoutEvent;
+use chrono::{DateTime, Utc};
+use corint_core::ast::{ErrorPolicy, Signal};
 use corint_core::Value;
 use serde::{Deserialize, Serialize};
 
@@ -34,6 +38,11 @@ pub struct ConditionTrace {
     /// The logical group type if this is a group ("any" or "all")
     #[serde(skip_serializing_if = "Option::is_none")]
     pub group_type: Option<String>,
+
+    /// Whether the condition was Unknown because it involved a missing value
+    /// (three-valued null semantics only); `result` is false when it is
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub unknown: bool,
 }
 
 impl ConditionTrace {
@@ -47,6 +56,7 @@ impl ConditionTrace {
             result,
             nested: None,
             group_type: None,
+            unknown: false,
         }
     }
 
@@ -66,6 +76,7 @@ impl ConditionTrace {
             result,
             nested: None,
             group_type: None,
+            unknown: false,
         }
     }
 
@@ -79,6 +90,7 @@ impl ConditionTrace {
             result,
             nested: Some(nested),
             group_type: Some(group_type.to_string()),
+            unknown: false,
         }
     }
 }
@@ -106,6 +118,14 @@ pub struct RuleTrace {
     /// Execution time in milliseconds
     #[serde(skip_serializing_if = "Option::is_none")]
     pub execution_time_ms: Option<u64>,
+
+    /// Why the rule was skipped instead of evaluated (e.g. "expired")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub skipped: Option<String>,
+
+    /// Where the rule's conditions are defined (`file:line` of the first)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
 }
 
 impl RuleTrace {
@@ -118,9 +138,18 @@ impl RuleTrace {
             score: None,
             conditions: Vec::new(),
             execution_time_ms: None,
+            skipped: None,
+            source: None,
         }
     }
 
+    /// Mark the rule as skipped with a reason
+    pub fn set_skipped(mut self, reason: &str) -> Self {
+        self.triggered = false;
+        self.skipped = Some(reason.to_string());
+        self
+    }
+
     /// Set the rule as triggered with a score
     pub fn set_triggered(mut self, score: i32) -> Self {
         self.triggered = true;
@@ -161,6 +190,10 @@ pub struct ConclusionTrace {
     /// The total_score at the time this conclusion rule was evaluated (only for matched rules)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub total_score: Option<i32>,
+
+    /// Where the condition is defined (`file:line`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
 }
 
 impl ConclusionTrace {
@@ -172,6 +205,7 @@ impl ConclusionTrace {
             signal: None,
             reason: None,
             total_score: None,
+            source: None,
         }
     }
 
@@ -183,6 +217,7 @@ impl ConclusionTrace {
             signal: Some(signal.to_string()),
             reason: reason.map(|s| s.to_string()),
             total_score: None,
+            source: None,
         }
     }
 
@@ -194,6 +229,7 @@ impl ConclusionTrace {
             signal: Some(signal.to_string()),
             reason: reason.map(|s| s.to_string()),
             total_score: Some(total_score),
+            source: None,
         }
     }
 }
@@ -330,6 +366,66 @@ impl StepTrace {
     }
 }
 
+/// Trace of a feature fetched for a pipeline step
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeatureTrace {
+    /// The feature name
+    pub feature: String,
+
+    /// Fetch outcome ("ok", "failed" or "timed_out")
+    pub status: String,
+
+    /// Time spent fetching the feature in milliseconds
+    pub latency_ms: u64,
+
+    /// Why the fetch did not succeed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+
+    /// Missing-value policy that replaced a missing value (e.g., "default", "mean")
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub imputed: Option<String>,
+}
+
+impl From<&crate::feature::FeatureFetch> for FeatureTrace {
+    fn from(fetch: &crate::feature::FeatureFetch) -> Self {
+        Self {
+            feature: fetch.feature.clone(),
+            status: fetch.status.as_str().to_string(),
+            latency_ms: fetch.latency.as_millis() as u64,
+            error: fetch.error.clone(),
+            imputed: fetch.imputed.map(|policy| policy.as_str().to_string()),
+        }
+    }
+}
+
+/// Version of a list consulted by a decision
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ListTrace {
+    /// The list ID
+    pub list_id: String,
+
+    /// List version the lookups saw
+    pub version: u64,
+
+    /// Time the list was looked up at, when not the decision time (replays and backtests)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub as_of: Option<DateTime<Utc>>,
+}
+
+/// Step failure that a pipeline's `on_error` policy decided
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ErrorTrace {
+    /// The policy applied
+    pub policy: ErrorPolicy,
+
+    /// Signal of the fallback decision
+    pub signal: Signal,
+
+    /// The error raised
+    pub message: String,
+}
+
 /// Trace of a pipeline execution
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PipelineTrace {
@@ -355,6 +451,22 @@ pub struct PipelineTrace {
     /// Ruleset execution traces
     pub rulesets: Vec<RulesetTrace>,
 
+    /// Features fetched ahead of the steps that use them
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub features: Vec<FeatureTrace>,
+
+    /// Lists consulted by the decision
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub lists: Vec<ListTrace>,
+
+    /// Scopes that ran out of time, leaving the decision to partial results
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub timeouts: Vec<TimeoutEvent>,
+
+    /// Step failure decided by the pipeline's `on_error` policy
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<ErrorTrace>,
+
     /// Final conclusion evaluation traces
     #[serde(skip_serializing_if = "Vec::is_empty")]
     pub final_conclusion: Vec<ConclusionTrace>,
@@ -370,6 +482,10 @@ impl PipelineTrace {
             executed_branch: None,
             branch_conditions: Vec::new(),
             rulesets: Vec::new(),
+            features: Vec::new(),
+            lists: Vec::new(),
+            timeouts: Vec::new(),
+            error: None,
             final_conclusion: Vec::new(),
         }
     }
@@ -423,6 +539,10 @@ pub struct ExecutionTrace {
     /// Number of rules triggered (internal use only, not serialized)
     #[serde(skip)]
     pub rules_triggered: usize,
+
+    /// Inputs the decision consumed, to replay it (only present if input capture is enabled)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub inputs: Option<CapturedInputs>,
 }
 
 impl ExecutionTrace {
@@ -433,6 +553,7 @@ impl ExecutionTrace {
             total_time_ms: 0,
             rules_evaluated: 0,
             rules_triggered: 0,
+            inputs: None,
         }
     }
 
@@ -452,6 +573,12 @@ impl ExecutionTrace {
         self.total_time_ms = ms;
         self
     }
+
+    /// Attach the inputs the decision consumed
+    pub fn with_inputs(mut self, inputs: CapturedInputs) -> Self {
+        self.inputs = Some(inputs);
+        self
+    }
 }
 
 impl Default for ExecutionTrace {
@@ -0,0 +1,373 @@
+//! Retry policies for downstream dependencies
+//!
+//! Calls to data sources, external APIs and services are made once by
+//! default. A [`RetryPolicy`] retries calls that failed in a way worth
+//! retrying, classified as a [`RetryCondition`], up to `max_attempts` calls
+//! in total. The wait between attempts grows exponentially from
+//! `initial_backoff_ms` up to `max_backoff_ms`, and part of it is randomized
+//! (`jitter`) so that calls failing together do not retry in lockstep.
+//!
+//! A retry is not attempted when its backoff would outlast the time budget
+//! of the decision being made (see [`crate::engine::budget`]), and calls
+//! rejected by a concurrency limit or an open circuit are never retried.
+//!
+//! Policies are configured per data source, API or API endpoint:
+//!
+//! ```yaml
+//! retry:
+//!   max_attempts: 3
+//!   initial_backoff_ms: 50
+//!   max_backoff_ms: 1000
+//!   multiplier: 2.0
+//!   jitter: 0.5
+//!   retry_on: [timeout, connection, server_error, throttled]
+//! ```
+
+use crate::engine::budget;
+use crate::error::{Result, RuntimeError};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use std::time::Duration;
+
+/// Kind of failure a call can be retried on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RetryCondition {
+    /// The call timed out
+    Timeout,
+
+    /// The request could not be sent or got no response
+    Connection,
+
+    /// HTTP 5xx response
+    ServerError,
+
+    /// HTTP 429 response
+    Throttled,
+
+    /// Any other failure, such as a failed data source query or service call
+    Error,
+}
+
+impl RetryCondition {
+    /// Classify a failed call, `None` if it must not be retried
+    pub fn of_error(error: &RuntimeError) -> Option<Self> {
+        match error {
+            RuntimeError::Timeout(_) | RuntimeError::DataSourceTimeout { .. } => {
+                Some(Self::Timeout)
+            }
+            RuntimeError::ExternalCallFailed(_)
+            | RuntimeError::DataSourceUnavailable { .. }
+            | RuntimeError::ListBackendUnavailable { .. } => Some(Self::Connection),
+            // Retrying a rejected call only adds load to a struggling dependency
+            RuntimeError::ConcurrencyLimitReached(_)
+            | RuntimeError::RateLimited(_)
+            | RuntimeError::CircuitOpen(_)
+            | RuntimeError::Replay(_) => None,
+            _ => Some(Self::Error),
+        }
+    }
+
+    /// Classify an HTTP response status, `None` if it is not a failure worth retrying
+    pub fn of_status(status: u16) -> Option<Self> {
+        match status {
+            429 => Some(Self::Throttled),
+            500..=599 => Some(Self::ServerError),
+            _ => None,
+        }
+    }
+}
+
+/// Retry policy configuration
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RetryPolicy {
+    /// Calls made in total, including the first one (default: 3)
+    #[serde(default = "default_max_attempts")]
+    pub max_attempts: u32,
+
+    /// Wait before the first retry, in milliseconds (default: 50)
+    #[serde(default = "default_initial_backoff_ms")]
+    pub initial_backoff_ms: u64,
+
+    /// Longest wait between attempts, in milliseconds (default: 1000)
+    #[serde(default = "default_max_backoff_ms")]
+    pub max_backoff_ms: u64,
+
+    /// Factor the wait grows by after each retry (default: 2.0)
+    #[serde(default = "default_multiplier")]
+    pub multiplier: f64,
+
+    /// Fraction (0.0 - 1.0) of each wait that is randomized (default: 0.5)
+    #[serde(default = "default_jitter")]
+    pub jitter: f64,
+
+    /// Failures that are retried (default: all of them)
+    #[serde(default = "default_retry_on")]
+    pub retry_on: Vec<RetryCondition>,
+}
+
+fn default_max_attempts() -> u32 {
+    3
+}
+
+fn default_initial_backoff_ms() -> u64 {
+    50
+}
+
+fn default_max_backoff_ms() -> u64 {
+    1000
+}
+
+fn default_multiplier() -> f64 {
+    2.0
+}
+
+fn default_jitter() -> f64 {
+    0.5
+}
+
+fn default_retry_on() -> Vec<RetryCondition> {
+    vec![
+        RetryCondition::Timeout,
+        RetryCondition::Connection,
+        RetryCondition::ServerError,
+        RetryCondition::Throttled,
+        RetryCondition::Error,
+    ]
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self::new(default_max_attempts())
+    }
+}
+
+impl RetryPolicy {
+    /// Make up to `max_attempts` calls with the default backoff
+    pub fn new(max_attempts: u32) -> Self {
+        Self {
+            max_attempts,
+            initial_backoff_ms: default_initial_backoff_ms(),
+            max_backoff_ms: default_max_backoff_ms(),
+            multiplier: default_multiplier(),
+            jitter: default_jitter(),
+            retry_on: default_retry_on(),
+        }
+    }
+
+    /// Set the first and the longest wait between attempts
+    pub fn with_backoff(mut self, initial_backoff_ms: u64, max_backoff_ms: u64) -> Self {
+        self.initial_backoff_ms = initial_backoff_ms;
+        self.max_backoff_ms = max_backoff_ms;
+        self
+    }
+
+    /// Set the factor the wait grows by
+    pub fn with_multiplier(mut self, multiplier: f64) -> Self {
+        self.multiplier = multiplier;
+        self
+    }
+
+    /// Set the randomized fraction of each wait
+    pub fn with_jitter(mut self, jitter: f64) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// Set the failures that are retried
+    pub fn with_retry_on(mut self, retry_on: Vec<RetryCondition>) -> Self {
+        self.retry_on = retry_on;
+        self
+    }
+
+    /// Whether failures of this kind are retried
+    pub fn retries(&self, condition: RetryCondition) -> bool {
+        self.retry_on.contains(&condition)
+    }
+
+    /// Wait before retry number `retry` (1 for the first), without jitter
+    pub fn backoff(&self, retry: u32) -> Duration {
+        let exponent = retry.saturating_sub(1).min(32) as i32;
+        let backoff = self.initial_backoff_ms as f64 * self.multiplier.max(1.0).powi(exponent);
+        Duration::from_millis(backoff.min(self.max_backoff_ms as f64) as u64)
+    }
+
+    /// Wait before retrying a call that failed on attempt `attempt`
+    ///
+    /// `None` if the call must not be retried: it did not fail in a way the
+    /// policy retries, it was the last attempt, or the wait would outlast
+    /// the budget of the current decision.
+    pub fn next_delay(&self, attempt: u32, condition: Option<RetryCondition>) -> Option<Duration> {
+        let condition = condition?;
+        if attempt >= self.max_attempts || !self.retries(condition) {
+            return None;
+        }
+
+        let backoff = self.backoff(attempt);
+        let jitter = self.jitter.clamp(0.0, 1.0);
+        let delay = if jitter > 0.0 {
+            backoff.mul_f64(1.0 - jitter * rand::thread_rng().gen::<f64>())
+        } else {
+            backoff
+        };
+
+        let remaining = budget::current_budget().and_then(|budget| {
+            budget
+                .deadlines()
+                .iter()
+                .map(|deadline| deadline.remaining())
+                .min()
+        });
+        match remaining {
+            Some(remaining) if remaining <= delay => None,
+            _ => Some(delay),
+        }
+    }
+
+    /// Make `call` until it succeeds or must not be retried
+    ///
+    /// `dependency` names the data source or service called, for logging.
+    pub async fn run<T, F, Fut>(&self, dependency: &str, mut call: F) -> Result<T>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let mut attempt = 1;
+        loop {
+            match call().await {
+                Ok(value) => return Ok(value),
+                Err(e) => {
+                    let Some(delay) = self.next_delay(attempt, RetryCondition::of_error(&e)) else {
+                        return Err(e);
+                    };
+                    tracing::warn!(
+                        "Call to {} failed on attempt {}/{}, retrying in {:?}: {}",
+                        dependency,
+                        attempt,
+                        self.max_attempts,
+                        delay,
+                        e
+                    );
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::ExecutionBudget;
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::sync::Arc;
+
+    #[test]
+    fn test_retry_policy_backoff() {
+        let policy = RetryPolicy::new(5).with_backoff(100, 300).with_jitter(0.0);
+        assert_eq!(policy.backoff(1), Duration::from_millis(100));
+        assert_eq!(policy.backoff(2), Duration::from_millis(200));
+        assert_eq!(policy.backoff(3), Duration::from_millis(300));
+        assert_eq!(policy.backoff(40), Duration::from_millis(300));
+
+        let jittered = RetryPolicy::new(5).with_backoff(100, 100);
+        for _ in 0..20 {
+            let delay = jittered
+                .next_delay(1, Some(RetryCondition::Timeout))
+                .unwrap();
+            assert!(delay >= Duration::from_millis(50) && delay <= Duration::from_millis(100));
+        }
+
+        let policy = policy.with_retry_on(vec![RetryCondition::Timeout]);
+        assert!(policy
+            .next_delay(1, Some(RetryCondition::Timeout))
+            .is_some());
+        assert!(policy.next_delay(1, Some(RetryCondition::Error)).is_none());
+        assert!(policy.next_delay(1, None).is_none());
+        assert!(policy
+            .next_delay(5, Some(RetryCondition::Timeout))
+            .is_none());
+    }
+
+    #[test]
+    fn test_retry_condition_classification() {
+        assert_eq!(
+            RetryCondition::of_status(429),
+            Some(RetryCondition::Throttled)
+        );
+        assert_eq!(
+            RetryCondition::of_status(503),
+            Some(RetryCondition::ServerError)
+        );
+        assert_eq!(RetryCondition::of_status(404), None);
+        assert_eq!(
+            RetryCondition::of_error(&RuntimeError::Timeout("slow".to_string())),
+            Some(RetryCondition::Timeout)
+        );
+        assert_eq!(
+            RetryCondition::of_error(&RuntimeError::CircuitOpen("db".to_string())),
+            None
+        );
+
+        let policy: RetryPolicy =
+            serde_yaml::from_str("max_attempts: 2\nretry_on: [timeout, throttled]").unwrap();
+        assert_eq!(policy.max_attempts, 2);
+        assert_eq!(policy.initial_backoff_ms, 50);
+        assert!(policy.retries(RetryCondition::Throttled));
+        assert!(!policy.retries(RetryCondition::ServerError));
+    }
+
+    #[tokio::test]
+    async fn test_retry_policy_run() {
+        let policy = RetryPolicy::new(3).with_backoff(1, 1);
+        let calls = AtomicU32::new(0);
+        let value = policy
+            .run("db", || async {
+                match calls.fetch_add(1, Ordering::SeqCst) {
+                    0 => Err(RuntimeError::Timeout("slow".to_string())),
+                    _ => Ok(7),
+                }
+            })
+            .await
+            .unwrap();
+        assert_eq!(value, 7);
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+        // Gives up after the last attempt
+        calls.store(0, Ordering::SeqCst);
+        let result: Result<()> = policy
+            .run("db", || async {
+                calls.fetch_add(1, Ordering::SeqCst);
+                Err(RuntimeError::RuntimeError("down".to_string()))
+            })
+            .await;
+        assert!(result.is_err());
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+
+        // Rejected calls are not retried
+        calls.store(0, Ordering::SeqCst);
+        let result: Result<()> = policy
+            .run("db", || async {
+                calls.fetch_add(1, Ordering::SeqCst);
+                Err(RuntimeError::ConcurrencyLimitReached("db".to_string()))
+            })
+            .await;
+        assert!(result.is_err());
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_retry_respects_decision_budget() {
+        let policy = RetryPolicy::new(3).with_backoff(500, 500).with_jitter(0.0);
+        let budget = Arc::new(ExecutionBudget::new(Some(Duration::from_millis(100))));
+        let delay = budget
+            .scope(async { policy.next_delay(1, Some(RetryCondition::Timeout)) })
+            .await;
+        assert!(delay.is_none());
+        assert!(policy
+            .next_delay(1, Some(RetryCondition::Timeout))
+            .is_some());
+    }
+}
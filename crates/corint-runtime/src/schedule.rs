@@ -0,0 +1,98 @@
+//! Cron schedules for scheduled pipelines
+//!
+//! Schedules are declared in the pipeline registry (`schedules:`) with a
+//! standard 5-field cron expression, evaluated in UTC at minute granularity.
+
+use crate::error::{Result, RuntimeError};
+use chrono::{DateTime, Timelike, Utc};
+use croner::Cron;
+use serde::{Deserialize, Serialize};
+
+/// A parsed cron expression
+#[derive(Debug, Clone)]
+pub struct CronSchedule {
+    expression: String,
+    cron: Cron,
+}
+
+impl CronSchedule {
+    /// Parse a 5-field cron expression (e.g., "0 2 * * *")
+    pub fn parse(expression: &str) -> Result<Self> {
+        let cron = Cron::new(expression).parse().map_err(|e| {
+            RuntimeError::InvalidValue(format!("Invalid cron expression '{}': {}", expression, e))
+        })?;
+
+        Ok(Self {
+            expression: expression.to_string(),
+            cron,
+        })
+    }
+
+    /// The original expression
+    pub fn expression(&self) -> &str {
+        &self.expression
+    }
+
+    /// Whether the schedule fires in the minute containing `at`
+    pub fn matches(&self, at: DateTime<Utc>) -> bool {
+        let minute = at
+            .with_second(0)
+            .and_then(|t| t.with_nanosecond(0))
+            .unwrap_or(at);
+        self.cron.is_time_matching(&minute).unwrap_or(false)
+    }
+
+    /// The next firing time strictly after `after`
+    pub fn next_after(&self, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        self.cron.find_next_occurrence(&after, false).ok()
+    }
+}
+
+/// Outcome of one scheduled pipeline run
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ScheduleRunSummary {
+    /// Schedule ID
+    pub schedule_id: String,
+
+    /// Pipeline executed for each entity
+    pub pipeline: String,
+
+    /// Number of entities returned by the entity query
+    pub entities: usize,
+
+    /// Number of batches executed
+    pub batches: usize,
+
+    /// Decisions completed successfully
+    pub succeeded: usize,
+
+    /// Decisions that returned an error
+    pub failed: usize,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_cron_schedule_matches_and_next() {
+        let schedule = CronSchedule::parse("0 2 * * *").unwrap();
+        assert_eq!(schedule.expression(), "0 2 * * *");
+
+        let at = Utc.with_ymd_and_hms(2024, 3, 10, 2, 0, 42).unwrap();
+        assert!(schedule.matches(at));
+        assert!(!schedule.matches(Utc.with_ymd_and_hms(2024, 3, 10, 2, 1, 0).unwrap()));
+
+        assert_eq!(
+            schedule.next_after(at),
+            Some(Utc.with_ymd_and_hms(2024, 3, 11, 2, 0, 0).unwrap())
+        );
+    }
+
+    #[test]
+    fn test_cron_schedule_invalid() {
+        assert!(CronSchedule::parse("not a cron").is_err());
+        assert!(CronSchedule::parse("61 * * * *").is_err());
+    }
+}
@@ -13,6 +13,7 @@ pub mod grpc;
 pub mod http;
 pub mod mq;
 pub mod redis;
+pub mod retry;
 
 pub use client::{ServiceClient, ServiceRequest, ServiceResponse};
 pub use database::{DatabaseClient, DatabaseQuery, MockDatabaseClient};
@@ -20,3 +21,4 @@ pub use grpc::{GrpcClient, MockGrpcClient};
 pub use http::{HttpClient, HttpMethod, MockHttpClient};
 pub use mq::{MockMqClient, MqClient, MqDriver, MqMessage, PublishedMessage};
 pub use redis::{MockRedisClient, RedisClient, RedisCommand};
+pub use retry::RetryingServiceClient;
@@ -0,0 +1,134 @@
+//! Retrying service client
+//!
+//! Wraps any [`ServiceClient`] so that failed calls are retried by the
+//! [`RetryPolicy`] of the service operation, as configured under `retry` in
+//! the service or endpoint definition.
+
+use super::client::{ServiceClient, ServiceRequest, ServiceResponse};
+use crate::error::Result;
+use crate::retry::RetryPolicy;
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Service client retrying failed calls
+pub struct RetryingServiceClient {
+    /// Client making the calls
+    inner: Arc<dyn ServiceClient>,
+
+    /// Policy of operations without one of their own
+    default_policy: Option<RetryPolicy>,
+
+    /// Policies of single operations, keyed by `service.operation`
+    operation_policies: HashMap<String, RetryPolicy>,
+}
+
+impl RetryingServiceClient {
+    /// Retry every call made through `inner` by `policy`
+    pub fn new(inner: Arc<dyn ServiceClient>, policy: RetryPolicy) -> Self {
+        Self {
+            inner,
+            default_policy: Some(policy),
+            operation_policies: HashMap::new(),
+        }
+    }
+
+    /// Retry only the operations given a policy with [`Self::with_operation_policy`]
+    pub fn per_operation(inner: Arc<dyn ServiceClient>) -> Self {
+        Self {
+            inner,
+            default_policy: None,
+            operation_policies: HashMap::new(),
+        }
+    }
+
+    /// Retry calls to `service.operation` by `policy` instead of the default one
+    pub fn with_operation_policy(
+        mut self,
+        service: &str,
+        operation: &str,
+        policy: RetryPolicy,
+    ) -> Self {
+        self.operation_policies
+            .insert(format!("{}.{}", service, operation), policy);
+        self
+    }
+
+    /// Policy of a call, if it is retried
+    fn policy(&self, request: &ServiceRequest) -> Option<&RetryPolicy> {
+        self.operation_policies
+            .get(&format!("{}.{}", request.service, request.operation))
+            .or(self.default_policy.as_ref())
+    }
+}
+
+#[async_trait]
+impl ServiceClient for RetryingServiceClient {
+    async fn call(&self, request: ServiceRequest) -> Result<ServiceResponse> {
+        match self.policy(&request) {
+            Some(policy) => {
+                let dependency = format!("{}.{}", request.service, request.operation);
+                policy
+                    .run(&dependency, || self.inner.call(request.clone()))
+                    .await
+            }
+            None => self.inner.call(request).await,
+        }
+    }
+
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::RuntimeError;
+    use corint_core::Value;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    /// Fails the first `failures` calls
+    struct FlakyService {
+        failures: u32,
+        calls: AtomicU32,
+    }
+
+    #[async_trait]
+    impl ServiceClient for FlakyService {
+        async fn call(&self, _request: ServiceRequest) -> Result<ServiceResponse> {
+            if self.calls.fetch_add(1, Ordering::SeqCst) < self.failures {
+                return Err(RuntimeError::Timeout("kyc".to_string()));
+            }
+            Ok(ServiceResponse::new(Value::Bool(true)))
+        }
+
+        fn name(&self) -> &str {
+            "flaky"
+        }
+    }
+
+    #[tokio::test]
+    async fn test_retrying_service_client() {
+        let flaky = Arc::new(FlakyService {
+            failures: 2,
+            calls: AtomicU32::new(0),
+        });
+        let client = RetryingServiceClient::per_operation(flaky.clone()).with_operation_policy(
+            "kyc",
+            "verify",
+            RetryPolicy::new(3).with_backoff(1, 1),
+        );
+
+        // Operations without a policy are called once
+        let request = ServiceRequest::new("kyc".to_string(), "status".to_string());
+        assert!(client.call(request).await.is_err());
+        assert_eq!(flaky.calls.load(Ordering::SeqCst), 1);
+
+        let request = ServiceRequest::new("kyc".to_string(), "verify".to_string());
+        let response = client.call(request).await.unwrap();
+        assert_eq!(response.data, Value::Bool(true));
+        assert_eq!(flaky.calls.load(Ordering::SeqCst), 3);
+        assert_eq!(client.name(), "flaky");
+    }
+}
@@ -0,0 +1,652 @@
+//! ClickHouse event storage
+//!
+//! Events live in a `MergeTree` table partitioned by day or month and sorted
+//! by `(event_type, event_timestamp)`, so a time range only reads the matching
+//! partitions and granules. Fields listed in `columns` are stored in their own
+//! (bloom-filter indexed) columns; the rest of the payload is kept as JSON in
+//! `data`. When a filter names the fields it needs, only those columns are
+//! read and `data` is skipped if every field has its own column.
+
+use super::{Event, EventFilter, Storage, TimeRange};
+use crate::error::{Result, RuntimeError};
+use async_trait::async_trait;
+use chrono::DateTime;
+use corint_core::Value;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// Columns every event table has
+const RESERVED_COLUMNS: [&str; 3] = ["event_timestamp", "event_type", "data"];
+
+/// Type of a payload field stored in its own column
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EventColumnType {
+    String,
+    Number,
+    Bool,
+}
+
+impl EventColumnType {
+    fn sql_type(self) -> &'static str {
+        match self {
+            EventColumnType::String => "Nullable(String)",
+            EventColumnType::Number => "Nullable(Float64)",
+            EventColumnType::Bool => "Nullable(Bool)",
+        }
+    }
+
+    fn accepts(self, value: &Value) -> bool {
+        matches!(
+            (self, value),
+            (EventColumnType::String, Value::String(_))
+                | (EventColumnType::Number, Value::Number(_))
+                | (EventColumnType::Bool, Value::Bool(_))
+        )
+    }
+}
+
+/// Payload field stored in its own column
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventColumn {
+    pub name: String,
+
+    #[serde(rename = "type")]
+    pub column_type: EventColumnType,
+}
+
+/// Time span of one table partition
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PartitionGranularity {
+    Day,
+    #[default]
+    Month,
+}
+
+/// ClickHouse event storage configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClickHouseStorageConfig {
+    /// HTTP interface, e.g. `http://localhost:8123`
+    pub url: String,
+
+    #[serde(default = "default_database")]
+    pub database: String,
+
+    #[serde(default = "default_table")]
+    pub table: String,
+
+    #[serde(default)]
+    pub user: Option<String>,
+
+    #[serde(default)]
+    pub password: Option<String>,
+
+    /// Payload fields stored in their own columns, e.g. `user_id`
+    #[serde(default)]
+    pub columns: Vec<EventColumn>,
+
+    #[serde(default)]
+    pub partition: PartitionGranularity,
+
+    /// Days after which ClickHouse deletes events
+    #[serde(default)]
+    pub ttl_days: Option<u32>,
+
+    /// Request timeout in milliseconds
+    #[serde(default = "default_timeout_ms")]
+    pub timeout_ms: u64,
+}
+
+fn default_database() -> String {
+    "default".to_string()
+}
+
+fn default_table() -> String {
+    "event_history".to_string()
+}
+
+fn default_timeout_ms() -> u64 {
+    30_000
+}
+
+impl ClickHouseStorageConfig {
+    /// Configuration for the server at `url`
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            database: default_database(),
+            table: default_table(),
+            user: None,
+            password: None,
+            columns: Vec::new(),
+            partition: PartitionGranularity::default(),
+            ttl_days: None,
+            timeout_ms: default_timeout_ms(),
+        }
+    }
+
+    pub fn with_database(mut self, database: impl Into<String>) -> Self {
+        self.database = database.into();
+        self
+    }
+
+    pub fn with_table(mut self, table: impl Into<String>) -> Self {
+        self.table = table.into();
+        self
+    }
+
+    pub fn with_credentials(
+        mut self,
+        user: impl Into<String>,
+        password: impl Into<String>,
+    ) -> Self {
+        self.user = Some(user.into());
+        self.password = Some(password.into());
+        self
+    }
+
+    /// Store a payload field in its own column
+    pub fn with_column(mut self, name: impl Into<String>, column_type: EventColumnType) -> Self {
+        self.columns.push(EventColumn {
+            name: name.into(),
+            column_type,
+        });
+        self
+    }
+
+    pub fn with_partition(mut self, partition: PartitionGranularity) -> Self {
+        self.partition = partition;
+        self
+    }
+
+    pub fn with_ttl_days(mut self, days: u32) -> Self {
+        self.ttl_days = Some(days);
+        self
+    }
+
+    fn column(&self, name: &str) -> Option<&EventColumn> {
+        self.columns.iter().find(|column| column.name == name)
+    }
+
+    /// Statement creating the event table
+    fn create_table_sql(&self) -> Result<String> {
+        let mut definitions = vec![
+            "`event_timestamp` DateTime64(3, 'UTC')".to_string(),
+            "`event_type` LowCardinality(String)".to_string(),
+        ];
+        for column in &self.columns {
+            definitions.push(format!(
+                "{} {}",
+                quote_identifier(&column.name)?,
+                column.column_type.sql_type()
+            ));
+        }
+        definitions.push("`data` String CODEC(ZSTD(3))".to_string());
+        for column in &self.columns {
+            definitions.push(format!(
+                "INDEX {} {} TYPE bloom_filter GRANULARITY 4",
+                quote_identifier(&format!("idx_{}", column.name))?,
+                quote_identifier(&column.name)?
+            ));
+        }
+
+        let mut sql = format!(
+            "CREATE TABLE IF NOT EXISTS {} ({}) ENGINE = MergeTree \
+             PARTITION BY {}(event_timestamp) ORDER BY (event_type, event_timestamp)",
+            quote_identifier(&self.table)?,
+            definitions.join(", "),
+            match self.partition {
+                PartitionGranularity::Day => "toYYYYMMDD",
+                PartitionGranularity::Month => "toYYYYMM",
+            }
+        );
+        if let Some(days) = self.ttl_days {
+            sql.push_str(&format!(
+                " TTL toDateTime(event_timestamp) + INTERVAL {} DAY",
+                days
+            ));
+        }
+        Ok(sql)
+    }
+}
+
+/// Select statement for a query and the filters applied after reading
+struct Select {
+    sql: String,
+
+    /// Filters that can't be expressed on the stored columns
+    residual: Vec<(String, Value)>,
+}
+
+/// Event storage backed by ClickHouse
+pub struct ClickHouseStorage {
+    config: ClickHouseStorageConfig,
+    http_client: reqwest::Client,
+
+    /// Endpoint including database and output format
+    url: reqwest::Url,
+}
+
+impl ClickHouseStorage {
+    /// Create a storage; call [`create_table`](Self::create_table) unless the
+    /// table already exists
+    pub fn new(config: ClickHouseStorageConfig) -> Result<Self> {
+        quote_identifier(&config.table)?;
+        for column in &config.columns {
+            quote_identifier(&column.name)?;
+            if RESERVED_COLUMNS.contains(&column.name.as_str()) {
+                return Err(RuntimeError::InvalidValue(format!(
+                    "Event column '{}' is reserved",
+                    column.name
+                )));
+            }
+        }
+
+        let mut url = reqwest::Url::parse(&config.url).map_err(|e| {
+            RuntimeError::RuntimeError(format!("Invalid ClickHouse URL {}: {}", config.url, e))
+        })?;
+        url.query_pairs_mut()
+            .append_pair("database", &config.database)
+            .append_pair("default_format", "JSONEachRow")
+            .append_pair("output_format_json_quote_64bit_integers", "0");
+
+        let http_client = reqwest::Client::builder()
+            .timeout(Duration::from_millis(config.timeout_ms.max(1)))
+            .tcp_nodelay(true)
+            .build()
+            .map_err(|e| {
+                RuntimeError::RuntimeError(format!("Failed to create HTTP client: {}", e))
+            })?;
+
+        Ok(Self {
+            config,
+            http_client,
+            url,
+        })
+    }
+
+    /// Create the event table if missing
+    pub async fn create_table(&self) -> Result<()> {
+        self.execute(self.config.create_table_sql()?).await?;
+        Ok(())
+    }
+
+    /// Send a statement and return the response body
+    async fn execute(&self, body: String) -> Result<String> {
+        let mut request = self.http_client.post(self.url.clone()).body(body);
+        if let Some(user) = &self.config.user {
+            request = request.header("X-ClickHouse-User", user);
+        }
+        if let Some(password) = &self.config.password {
+            request = request.header("X-ClickHouse-Key", password);
+        }
+        let response = request
+            .send()
+            .await
+            .map_err(|e| RuntimeError::RuntimeError(format!("ClickHouse request failed: {}", e)))?;
+
+        let status = response.status();
+        let body = response.text().await.map_err(|e| {
+            RuntimeError::RuntimeError(format!("Failed to read ClickHouse response: {}", e))
+        })?;
+        if !status.is_success() {
+            return Err(RuntimeError::RuntimeError(format!(
+                "ClickHouse query failed with status {}: {}",
+                status,
+                body.trim()
+            )));
+        }
+        Ok(body)
+    }
+
+    fn build_select(&self, time_range: TimeRange, filter: Option<&EventFilter>) -> Result<Select> {
+        let mut conditions = vec![
+            format!(
+                "event_timestamp >= toDateTime64({}, 3, 'UTC')",
+                time_range.0
+            ),
+            format!("event_timestamp < toDateTime64({}, 3, 'UTC')", time_range.1),
+        ];
+        let mut residual = Vec::new();
+
+        if let Some(filter) = filter {
+            if let Some(event_type) = &filter.event_type {
+                conditions.push(format!("event_type = {}", quote_string(event_type)));
+            }
+
+            let mut field_filters: Vec<_> = filter.field_filters.iter().collect();
+            field_filters.sort_by(|a, b| a.0.cmp(b.0));
+            for (field, value) in field_filters {
+                match (self.config.column(field), value) {
+                    (Some(column), value) if column.column_type.accepts(value) => {
+                        conditions.push(format!(
+                            "{} = {}",
+                            quote_identifier(field)?,
+                            format_literal(value)
+                        ));
+                    }
+                    // Values of another type than the column are kept in `data`
+                    (Some(_), _) => residual.push((field.clone(), value.clone())),
+                    (None, Value::String(_) | Value::Number(_) | Value::Bool(_)) => {
+                        let extract = match value {
+                            Value::String(_) => "JSONExtractString",
+                            Value::Number(_) => "JSONExtractFloat",
+                            _ => "JSONExtractBool",
+                        };
+                        let key = quote_string(field);
+                        conditions.push(format!(
+                            "JSONHas(data, {key}) AND {extract}(data, {key}) = {}",
+                            format_literal(value)
+                        ));
+                    }
+                    (None, _) => residual.push((field.clone(), value.clone())),
+                }
+            }
+        }
+
+        let wanted = filter.and_then(|filter| filter.fields.as_ref());
+        let mut columns = vec![
+            "toUnixTimestamp(event_timestamp) AS ts".to_string(),
+            "event_type".to_string(),
+        ];
+        for column in &self.config.columns {
+            if wanted.is_none_or(|fields| fields.contains(&column.name)) {
+                columns.push(quote_identifier(&column.name)?);
+            }
+        }
+        let needs_data = !residual.is_empty()
+            || wanted.is_none_or(|fields| {
+                fields
+                    .iter()
+                    .any(|field| field != "event_type" && self.config.column(field).is_none())
+            });
+        if needs_data {
+            columns.push("data".to_string());
+        }
+
+        Ok(Select {
+            sql: format!(
+                "SELECT {} FROM {} WHERE {} ORDER BY event_timestamp",
+                columns.join(", "),
+                quote_identifier(&self.config.table)?,
+                conditions.join(" AND ")
+            ),
+            residual,
+        })
+    }
+
+    /// Read one JSONEachRow line into an event
+    fn parse_row(&self, line: &str) -> Result<Event> {
+        let invalid = |e: serde_json::Error| {
+            RuntimeError::RuntimeError(format!("Invalid ClickHouse row: {}", e))
+        };
+        let row: serde_json::Map<String, serde_json::Value> =
+            serde_json::from_str(line).map_err(invalid)?;
+
+        let timestamp = row.get("ts").and_then(|ts| ts.as_i64()).ok_or_else(|| {
+            RuntimeError::RuntimeError("ClickHouse row without timestamp".to_string())
+        })?;
+
+        let mut data: HashMap<String, Value> = match row.get("data").and_then(|d| d.as_str()) {
+            Some(json) if !json.is_empty() => serde_json::from_str(json).map_err(invalid)?,
+            _ => HashMap::new(),
+        };
+        for (name, value) in row {
+            let name = match name.as_str() {
+                "ts" | "data" => continue,
+                "event_type" if value.as_str() == Some("") => continue,
+                _ => name,
+            };
+            if !value.is_null() {
+                data.insert(name, serde_json::from_value(value).map_err(invalid)?);
+            }
+        }
+
+        Ok(Event { timestamp, data })
+    }
+
+    /// INSERT statement followed by the rows
+    fn build_insert(&self, events: &[Event]) -> Result<String> {
+        let mut columns = vec!["event_timestamp", "event_type"];
+        columns.extend(self.config.columns.iter().map(|c| c.name.as_str()));
+        columns.push("data");
+        let columns: Vec<String> = columns
+            .into_iter()
+            .map(quote_identifier)
+            .collect::<Result<_>>()?;
+
+        let mut body = format!(
+            "INSERT INTO {} ({}) FORMAT JSONEachRow\n",
+            quote_identifier(&self.config.table)?,
+            columns.join(", ")
+        );
+        for event in events {
+            let mut data = event.data.clone();
+            let mut row = serde_json::Map::new();
+            let timestamp = DateTime::from_timestamp(event.timestamp, 0).ok_or_else(|| {
+                RuntimeError::InvalidValue(format!("Invalid event timestamp {}", event.timestamp))
+            })?;
+            row.insert(
+                "event_timestamp".to_string(),
+                timestamp.format("%Y-%m-%d %H:%M:%S").to_string().into(),
+            );
+            let event_type = match data.remove("event_type") {
+                Some(Value::String(event_type)) => event_type,
+                Some(other) => {
+                    // Only string types fit the column
+                    data.insert("event_type".to_string(), other);
+                    String::new()
+                }
+                None => String::new(),
+            };
+            row.insert("event_type".to_string(), event_type.into());
+            for column in &self.config.columns {
+                let value = match data.get(&column.name) {
+                    Some(value) if column.column_type.accepts(value) => {
+                        to_json(&data.remove(&column.name))?
+                    }
+                    _ => serde_json::Value::Null,
+                };
+                row.insert(column.name.clone(), value);
+            }
+            let data = if data.is_empty() {
+                String::new()
+            } else {
+                to_json(&data)?.to_string()
+            };
+            row.insert("data".to_string(), data.into());
+
+            body.push_str(&serde_json::Value::Object(row).to_string());
+            body.push('\n');
+        }
+        Ok(body)
+    }
+}
+
+#[async_trait]
+impl Storage for ClickHouseStorage {
+    async fn query_events(
+        &self,
+        time_range: TimeRange,
+        filter: Option<EventFilter>,
+    ) -> Result<Vec<Event>> {
+        let select = self.build_select(time_range, filter.as_ref())?;
+        tracing::debug!("Querying ClickHouse events: {}", select.sql);
+        let body = self.execute(select.sql).await?;
+
+        let wanted = filter.and_then(|filter| filter.fields);
+        let mut events = Vec::new();
+        for line in body.lines().filter(|line| !line.trim().is_empty()) {
+            let mut event = self.parse_row(line)?;
+            if !select
+                .residual
+                .iter()
+                .all(|(field, value)| event.data.get(field) == Some(value))
+            {
+                continue;
+            }
+            if let Some(fields) = &wanted {
+                event
+                    .data
+                    .retain(|name, _| name == "event_type" || fields.contains(name));
+            }
+            events.push(event);
+        }
+        Ok(events)
+    }
+
+    async fn insert_events_batch(&self, events: Vec<Event>) -> Result<()> {
+        if events.is_empty() {
+            return Ok(());
+        }
+        self.execute(self.build_insert(&events)?).await?;
+        Ok(())
+    }
+}
+
+fn quote_identifier(name: &str) -> Result<String> {
+    let valid = name
+        .chars()
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if !valid {
+        return Err(RuntimeError::InvalidValue(format!(
+            "Invalid ClickHouse identifier: {:?}",
+            name
+        )));
+    }
+    Ok(format!("`{}`", name))
+}
+
+/// Quote a string literal; ClickHouse treats backslashes as escapes
+fn quote_string(s: &str) -> String {
+    format!("'{}'", s.replace('\\', "\\\\").replace('\'', "\\'"))
+}
+
+/// Literal for a string, number or bool
+fn format_literal(value: &Value) -> String {
+    match value {
+        Value::String(s) => quote_string(s),
+        Value::Number(n) => n.to_string(),
+        Value::Bool(b) => b.to_string(),
+        _ => "NULL".to_string(),
+    }
+}
+
+fn to_json<T: Serialize>(value: &T) -> Result<serde_json::Value> {
+    serde_json::to_value(value)
+        .map_err(|e| RuntimeError::InvalidValue(format!("Invalid event data: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn storage() -> ClickHouseStorage {
+        ClickHouseStorage::new(
+            ClickHouseStorageConfig::new("http://localhost:8123")
+                .with_column("user_id", EventColumnType::String)
+                .with_column("amount", EventColumnType::Number)
+                .with_ttl_days(90),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_create_table_sql() {
+        assert_eq!(
+            storage().config.create_table_sql().unwrap(),
+            "CREATE TABLE IF NOT EXISTS `event_history` (\
+             `event_timestamp` DateTime64(3, 'UTC'), `event_type` LowCardinality(String), \
+             `user_id` Nullable(String), `amount` Nullable(Float64), `data` String CODEC(ZSTD(3)), \
+             INDEX `idx_user_id` `user_id` TYPE bloom_filter GRANULARITY 4, \
+             INDEX `idx_amount` `amount` TYPE bloom_filter GRANULARITY 4) ENGINE = MergeTree \
+             PARTITION BY toYYYYMM(event_timestamp) ORDER BY (event_type, event_timestamp) \
+             TTL toDateTime(event_timestamp) + INTERVAL 90 DAY"
+        );
+
+        let reserved = ClickHouseStorageConfig::new("http://localhost:8123")
+            .with_column("data", EventColumnType::String);
+        assert!(ClickHouseStorage::new(reserved).is_err());
+    }
+
+    #[test]
+    fn test_select_prunes_columns() {
+        let storage = storage();
+
+        // Only the aggregated column is read
+        let filter = EventFilter::new()
+            .with_event_type("payment".to_string())
+            .with_field_filter("user_id".to_string(), Value::String("u'1".to_string()))
+            .with_fields(vec!["amount".to_string()]);
+        let select = storage.build_select((0, 100), Some(&filter)).unwrap();
+        assert_eq!(
+            select.sql,
+            "SELECT toUnixTimestamp(event_timestamp) AS ts, event_type, `amount` \
+             FROM `event_history` WHERE event_timestamp >= toDateTime64(0, 3, 'UTC') \
+             AND event_timestamp < toDateTime64(100, 3, 'UTC') AND event_type = 'payment' \
+             AND `user_id` = 'u\\'1' ORDER BY event_timestamp"
+        );
+        assert!(select.residual.is_empty());
+
+        // Other fields come from the JSON payload
+        let filter = EventFilter::new()
+            .with_field_filter("country".to_string(), Value::String("US".to_string()))
+            .with_field_filter("tags".to_string(), Value::Array(vec![]));
+        let select = storage.build_select((0, 100), Some(&filter)).unwrap();
+        assert!(select
+            .sql
+            .contains("JSONHas(data, 'country') AND JSONExtractString(data, 'country') = 'US'"));
+        assert!(select.sql.contains("`user_id`, `amount`, data FROM"));
+        assert_eq!(
+            select.residual,
+            vec![("tags".to_string(), Value::Array(vec![]))]
+        );
+    }
+
+    #[test]
+    fn test_insert_and_parse_roundtrip() {
+        let storage = storage();
+        let mut data = HashMap::new();
+        data.insert(
+            "event_type".to_string(),
+            Value::String("payment".to_string()),
+        );
+        data.insert("user_id".to_string(), Value::String("u1".to_string()));
+        data.insert("amount".to_string(), Value::String("n/a".to_string()));
+        let event = Event {
+            timestamp: 1_704_153_600,
+            data: data.clone(),
+        };
+
+        let body = storage.build_insert(&[event]).unwrap();
+        let (statement, row) = body.split_once('\n').unwrap();
+        assert_eq!(
+            statement,
+            "INSERT INTO `event_history` (`event_timestamp`, `event_type`, `user_id`, `amount`, `data`) FORMAT JSONEachRow"
+        );
+        let row: serde_json::Value = serde_json::from_str(row.trim()).unwrap();
+        assert_eq!(
+            row,
+            serde_json::json!({
+                "event_timestamp": "2024-01-02 00:00:00",
+                "event_type": "payment",
+                "user_id": "u1",
+                // Not a number, so kept in the payload
+                "amount": null,
+                "data": "{\"amount\":\"n/a\"}",
+            })
+        );
+
+        let parsed = storage
+            .parse_row(
+                r#"{"ts":1704153600,"event_type":"payment","user_id":"u1","amount":null,"data":"{\"amount\":\"n/a\"}"}"#,
+            )
+            .unwrap();
+        assert_eq!(parsed.timestamp, 1_704_153_600);
+        assert_eq!(parsed.data, data);
+    }
+}
@@ -0,0 +1,505 @@
+//! Streaming event ingestion
+//!
+//! An [`EventIngestor`] reads messages from an [`EventStream`] such as a
+//! Kafka topic, parses their JSON payloads into events and writes them to a
+//! [`Storage`] in batches. Offsets are committed only after a batch has been
+//! stored, so after a crash or restart at most the batch in flight is
+//! delivered again.
+//!
+//! A batch is written before more messages are read. When the storage slows
+//! down or fails, consumption pauses and the backlog stays in the stream
+//! rather than in memory.
+
+use super::{Event, Storage};
+use crate::error::{Result, RuntimeError};
+use async_trait::async_trait;
+use chrono::DateTime;
+use corint_core::Value;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::time::Instant;
+use tracing::{debug, warn};
+
+/// A message read from a stream partition
+#[derive(Debug, Clone)]
+pub struct StreamMessage {
+    /// Topic the message was read from
+    pub topic: String,
+
+    /// Partition within the topic
+    pub partition: i32,
+
+    /// Offset within the partition
+    pub offset: i64,
+
+    /// Message timestamp in milliseconds since epoch, if the stream has one
+    pub timestamp_ms: Option<i64>,
+
+    /// JSON payload (`None` for tombstones)
+    pub payload: Option<Vec<u8>>,
+}
+
+/// Last processed offset of a partition
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StreamOffset {
+    /// Topic name
+    pub topic: String,
+
+    /// Partition within the topic
+    pub partition: i32,
+
+    /// Offset of the last processed message
+    pub offset: i64,
+}
+
+/// Source of event messages with committable offsets
+#[async_trait]
+pub trait EventStream: Send {
+    /// Read the next message; `None` once the stream has ended
+    ///
+    /// Must be cancel safe: the ingestor stops waiting for a message to flush
+    /// a batch or shut down.
+    async fn next_message(&mut self) -> Option<Result<StreamMessage>>;
+
+    /// Record the given messages, and all before them, as processed
+    async fn commit(&mut self, offsets: &[StreamOffset]) -> Result<()>;
+}
+
+/// Ingestion settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IngestConfig {
+    /// Maximum number of events written at once (default: 500)
+    #[serde(default = "default_batch_size")]
+    pub batch_size: usize,
+
+    /// Longest time an event waits for its batch to fill up (default: 1000)
+    #[serde(default = "default_flush_interval_ms")]
+    pub flush_interval_ms: u64,
+
+    /// Payload field with the event time, as Unix seconds, milliseconds or an
+    /// RFC 3339 string (default: `event_timestamp`). Without it the message
+    /// timestamp is used.
+    #[serde(default = "default_timestamp_field")]
+    pub timestamp_field: String,
+
+    /// Attempts to write a batch before ingestion stops (default: 10)
+    #[serde(default = "default_max_write_attempts")]
+    pub max_write_attempts: u32,
+}
+
+fn default_batch_size() -> usize {
+    500
+}
+
+fn default_flush_interval_ms() -> u64 {
+    1000
+}
+
+fn default_timestamp_field() -> String {
+    "event_timestamp".to_string()
+}
+
+fn default_max_write_attempts() -> u32 {
+    10
+}
+
+impl Default for IngestConfig {
+    fn default() -> Self {
+        Self {
+            batch_size: default_batch_size(),
+            flush_interval_ms: default_flush_interval_ms(),
+            timestamp_field: default_timestamp_field(),
+            max_write_attempts: default_max_write_attempts(),
+        }
+    }
+}
+
+impl IngestConfig {
+    /// Set the maximum number of events per write
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    /// Set the longest time an event waits for its batch to fill up
+    pub fn with_flush_interval(mut self, interval: Duration) -> Self {
+        self.flush_interval_ms = interval.as_millis() as u64;
+        self
+    }
+
+    /// Set the payload field holding the event time
+    pub fn with_timestamp_field(mut self, field: impl Into<String>) -> Self {
+        self.timestamp_field = field.into();
+        self
+    }
+}
+
+/// Counters of an ingestion run
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IngestStats {
+    /// Events written to storage
+    pub ingested: u64,
+
+    /// Messages that weren't valid events and were skipped
+    pub skipped: u64,
+
+    /// Batches written
+    pub batches: u64,
+
+    /// Failed batch writes that were retried
+    pub write_retries: u64,
+}
+
+/// Writes events from a stream into a storage backend
+pub struct EventIngestor {
+    storage: Arc<dyn Storage>,
+    config: IngestConfig,
+}
+
+/// Events read since the last commit
+#[derive(Default)]
+struct Batch {
+    events: Vec<Event>,
+    offsets: HashMap<(String, i32), i64>,
+    deadline: Option<Instant>,
+}
+
+impl EventIngestor {
+    /// Ingest into `storage` with the default settings
+    pub fn new(storage: Arc<dyn Storage>) -> Self {
+        Self {
+            storage,
+            config: IngestConfig::default(),
+        }
+    }
+
+    /// Use the given settings
+    pub fn with_config(mut self, config: IngestConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Consume `stream` until it ends or `shutdown` completes
+    ///
+    /// Events read before shutdown are written and committed before
+    /// returning. Fails when a batch can't be written or committed; its
+    /// offsets are then left uncommitted.
+    pub async fn run<S>(
+        &self,
+        stream: &mut S,
+        shutdown: impl Future<Output = ()>,
+    ) -> Result<IngestStats>
+    where
+        S: EventStream + ?Sized,
+    {
+        let mut stats = IngestStats::default();
+        let mut batch = Batch::default();
+        tokio::pin!(shutdown);
+
+        loop {
+            let deadline = batch.deadline;
+            tokio::select! {
+                _ = &mut shutdown => break,
+                _ = sleep_until(deadline), if deadline.is_some() => {
+                    self.flush(stream, &mut batch, &mut stats).await?;
+                }
+                message = stream.next_message() => match message {
+                    None => break,
+                    Some(Err(e)) => warn!("Failed to read from event stream: {}", e),
+                    Some(Ok(message)) => {
+                        self.add(&mut batch, message, &mut stats);
+                        if batch.events.len() >= self.config.batch_size {
+                            self.flush(stream, &mut batch, &mut stats).await?;
+                        }
+                    }
+                },
+            }
+        }
+
+        self.flush(stream, &mut batch, &mut stats).await?;
+        Ok(stats)
+    }
+
+    /// Parse a JSON object payload into an event
+    pub fn parse_event(&self, payload: &[u8], timestamp_ms: Option<i64>) -> Result<Event> {
+        let data: HashMap<String, Value> = serde_json::from_slice(payload).map_err(|e| {
+            RuntimeError::InvalidValue(format!("Event payload is not a JSON object: {}", e))
+        })?;
+
+        let timestamp = match data.get(&self.config.timestamp_field) {
+            Some(value) => parse_timestamp(value).ok_or_else(|| {
+                RuntimeError::InvalidValue(format!(
+                    "Invalid {}: {:?}",
+                    self.config.timestamp_field, value
+                ))
+            })?,
+            None => timestamp_ms
+                .map(|ms| ms / 1000)
+                .unwrap_or_else(|| chrono::Utc::now().timestamp()),
+        };
+
+        Ok(Event { timestamp, data })
+    }
+
+    /// Add a message to the batch; invalid ones are only counted
+    fn add(&self, batch: &mut Batch, message: StreamMessage, stats: &mut IngestStats) {
+        let parsed = match &message.payload {
+            Some(payload) => self.parse_event(payload, message.timestamp_ms),
+            None => Err(RuntimeError::InvalidValue("Empty message".to_string())),
+        };
+        match parsed {
+            Ok(event) => batch.events.push(event),
+            Err(e) => {
+                warn!(
+                    "Skipping message {}/{}@{}: {}",
+                    message.topic, message.partition, message.offset, e
+                );
+                stats.skipped += 1;
+            }
+        }
+
+        batch
+            .offsets
+            .insert((message.topic, message.partition), message.offset);
+        batch.deadline.get_or_insert_with(|| {
+            Instant::now() + Duration::from_millis(self.config.flush_interval_ms)
+        });
+    }
+
+    /// Write the batch, retrying with backoff, then commit its offsets
+    async fn flush<S>(
+        &self,
+        stream: &mut S,
+        batch: &mut Batch,
+        stats: &mut IngestStats,
+    ) -> Result<()>
+    where
+        S: EventStream + ?Sized,
+    {
+        let Batch {
+            events, offsets, ..
+        } = std::mem::take(batch);
+        if offsets.is_empty() {
+            return Ok(());
+        }
+
+        if !events.is_empty() {
+            let count = events.len() as u64;
+            let mut backoff = Duration::from_millis(100);
+            let mut attempt = 1;
+            while let Err(e) = self.storage.insert_events_batch(events.clone()).await {
+                if attempt >= self.config.max_write_attempts {
+                    return Err(RuntimeError::RuntimeError(format!(
+                        "Failed to store {} events after {} attempts: {}",
+                        count, attempt, e
+                    )));
+                }
+                warn!(
+                    "Failed to store {} events, retrying in {:?}: {}",
+                    count, backoff, e
+                );
+                stats.write_retries += 1;
+                tokio::time::sleep(backoff).await;
+                backoff = (backoff * 2).min(Duration::from_secs(5));
+                attempt += 1;
+            }
+            stats.ingested += count;
+            stats.batches += 1;
+            debug!("Stored {} streamed events", count);
+        }
+
+        let offsets: Vec<StreamOffset> = offsets
+            .into_iter()
+            .map(|((topic, partition), offset)| StreamOffset {
+                topic,
+                partition,
+                offset,
+            })
+            .collect();
+        stream.commit(&offsets).await
+    }
+}
+
+/// Sleep until the deadline, or forever without one
+async fn sleep_until(deadline: Option<Instant>) {
+    match deadline {
+        Some(deadline) => tokio::time::sleep_until(deadline).await,
+        None => std::future::pending().await,
+    }
+}
+
+/// Unix seconds from seconds, milliseconds or an RFC 3339 string
+fn parse_timestamp(value: &Value) -> Option<i64> {
+    match value {
+        // Anything past the year 5000 in seconds is taken as milliseconds
+        Value::Number(n) if *n > 1e11 => Some((*n / 1000.0) as i64),
+        Value::Number(n) => Some(*n as i64),
+        Value::String(s) => DateTime::parse_from_rfc3339(s)
+            .ok()
+            .map(|dt| dt.timestamp()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::{EventFilter, InMemoryStorage, TimeRange};
+    use std::collections::VecDeque;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    /// Replays messages, then either ends or stays open
+    struct TestStream {
+        messages: VecDeque<StreamMessage>,
+        end: bool,
+        commits: Vec<Vec<StreamOffset>>,
+    }
+
+    impl TestStream {
+        fn new(payloads: &[&str], end: bool) -> Self {
+            let messages = payloads
+                .iter()
+                .enumerate()
+                .map(|(i, payload)| StreamMessage {
+                    topic: "events".to_string(),
+                    partition: (i % 2) as i32,
+                    offset: i as i64,
+                    timestamp_ms: Some(1_700_000_000_000),
+                    payload: Some(payload.as_bytes().to_vec()),
+                })
+                .collect();
+            Self {
+                messages,
+                end,
+                commits: Vec::new(),
+            }
+        }
+
+        fn committed(&self) -> Vec<(i32, i64)> {
+            let mut offsets: Vec<(i32, i64)> = self
+                .commits
+                .iter()
+                .flatten()
+                .map(|o| (o.partition, o.offset))
+                .collect();
+            offsets.sort();
+            offsets
+        }
+    }
+
+    #[async_trait]
+    impl EventStream for TestStream {
+        async fn next_message(&mut self) -> Option<Result<StreamMessage>> {
+            match self.messages.pop_front() {
+                Some(message) => Some(Ok(message)),
+                None if self.end => None,
+                None => std::future::pending().await,
+            }
+        }
+
+        async fn commit(&mut self, offsets: &[StreamOffset]) -> Result<()> {
+            self.commits.push(offsets.to_vec());
+            Ok(())
+        }
+    }
+
+    /// Fails the first writes, then stores in memory
+    struct FlakyStorage {
+        failures: AtomicU32,
+        inner: InMemoryStorage,
+    }
+
+    #[async_trait]
+    impl Storage for FlakyStorage {
+        async fn query_events(
+            &self,
+            time_range: TimeRange,
+            filter: Option<EventFilter>,
+        ) -> Result<Vec<Event>> {
+            self.inner.query_events(time_range, filter).await
+        }
+
+        async fn insert_events_batch(&self, events: Vec<Event>) -> Result<()> {
+            if self.failures.load(Ordering::SeqCst) > 0 {
+                self.failures.fetch_sub(1, Ordering::SeqCst);
+                return Err(RuntimeError::RuntimeError("disk full".to_string()));
+            }
+            self.inner.insert_events_batch(events).await
+        }
+    }
+
+    #[tokio::test]
+    async fn test_ingest_batches_and_commits() {
+        let storage = Arc::new(InMemoryStorage::new());
+        let ingestor = EventIngestor::new(storage.clone())
+            .with_config(IngestConfig::default().with_batch_size(2));
+        let mut stream = TestStream::new(
+            &[
+                r#"{"event_type": "login", "user_id": "u1", "event_timestamp": 1700000100}"#,
+                r#"{"event_type": "login", "user_id": "u2", "event_timestamp": "2023-11-14T22:15:00Z"}"#,
+                r#"not json"#,
+                r#"{"event_type": "payment", "user_id": "u1"}"#,
+            ],
+            true,
+        );
+
+        let stats = ingestor
+            .run(&mut stream, std::future::pending())
+            .await
+            .unwrap();
+        assert_eq!(
+            stats,
+            IngestStats {
+                ingested: 3,
+                skipped: 1,
+                batches: 2,
+                write_retries: 0,
+            }
+        );
+        assert_eq!(stream.commits.len(), 2);
+        assert_eq!(stream.committed(), vec![(0, 0), (0, 2), (1, 1), (1, 3)]);
+
+        let events = storage.query_events((0, i64::MAX), None).await.unwrap();
+        let timestamps: Vec<i64> = events.iter().map(|e| e.timestamp).collect();
+        assert_eq!(
+            timestamps,
+            vec![1_700_000_100, 1_700_000_100, 1_700_000_000]
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_ingest_flushes_on_interval_and_retries() {
+        let storage = Arc::new(FlakyStorage {
+            failures: AtomicU32::new(2),
+            inner: InMemoryStorage::new(),
+        });
+        let ingestor = EventIngestor::new(storage.clone())
+            .with_config(IngestConfig::default().with_flush_interval(Duration::from_millis(200)));
+        let mut stream = TestStream::new(&[r#"{"event_type": "login"}"#], false);
+
+        let stats = ingestor
+            .run(&mut stream, tokio::time::sleep(Duration::from_secs(1)))
+            .await
+            .unwrap();
+        assert_eq!(stats.ingested, 1);
+        assert_eq!(stats.write_retries, 2);
+        assert_eq!(stream.committed(), vec![(0, 0)]);
+        assert_eq!(storage.inner.len(), 1);
+
+        // A batch that can't be stored is not committed
+        let storage = Arc::new(FlakyStorage {
+            failures: AtomicU32::new(u32::MAX),
+            inner: InMemoryStorage::new(),
+        });
+        let ingestor = EventIngestor::new(storage);
+        let mut stream = TestStream::new(&[r#"{"event_type": "login"}"#], true);
+        assert!(ingestor
+            .run(&mut stream, std::future::pending())
+            .await
+            .is_err());
+        assert!(stream.commits.is_empty());
+    }
+}
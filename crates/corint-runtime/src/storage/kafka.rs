@@ -0,0 +1,102 @@
+//! Kafka event stream
+//!
+//! Consumes topics as part of a consumer group. Auto-commit is disabled so
+//! offsets only advance once the [`EventIngestor`](super::EventIngestor) has
+//! stored the events.
+
+use super::ingest::{EventStream, StreamMessage, StreamOffset};
+use crate::error::{Result, RuntimeError};
+use async_trait::async_trait;
+use rdkafka::config::ClientConfig;
+use rdkafka::consumer::{CommitMode, Consumer, StreamConsumer};
+use rdkafka::{Message, Offset, TopicPartitionList};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Kafka consumer configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KafkaConfig {
+    /// Comma-separated bootstrap servers
+    pub brokers: String,
+
+    /// Consumer group id
+    pub group_id: String,
+
+    /// Topics to consume
+    pub topics: Vec<String>,
+
+    /// Additional librdkafka settings (e.g. `auto.offset.reset`,
+    /// `security.protocol`, `queued.max.messages.kbytes`)
+    #[serde(default)]
+    pub options: HashMap<String, String>,
+}
+
+/// Event stream reading from Kafka topics
+pub struct KafkaEventStream {
+    consumer: StreamConsumer,
+}
+
+impl KafkaEventStream {
+    /// Create a consumer and subscribe to the configured topics
+    pub fn new(config: &KafkaConfig) -> Result<Self> {
+        let mut client_config = ClientConfig::new();
+        client_config
+            .set("bootstrap.servers", &config.brokers)
+            .set("group.id", &config.group_id)
+            .set("auto.offset.reset", "earliest");
+        for (key, value) in &config.options {
+            client_config.set(key, value);
+        }
+        // Offsets are committed by the ingestor after events are stored
+        client_config.set("enable.auto.commit", "false");
+
+        let consumer: StreamConsumer = client_config.create().map_err(|e| {
+            RuntimeError::RuntimeError(format!("Failed to create Kafka consumer: {}", e))
+        })?;
+        let topics: Vec<&str> = config.topics.iter().map(String::as_str).collect();
+        consumer.subscribe(&topics).map_err(|e| {
+            RuntimeError::RuntimeError(format!("Failed to subscribe to {:?}: {}", topics, e))
+        })?;
+
+        Ok(Self { consumer })
+    }
+}
+
+#[async_trait]
+impl EventStream for KafkaEventStream {
+    async fn next_message(&mut self) -> Option<Result<StreamMessage>> {
+        let message = match self.consumer.recv().await {
+            Ok(message) => message,
+            Err(e) => {
+                return Some(Err(RuntimeError::RuntimeError(format!(
+                    "Kafka error: {}",
+                    e
+                ))))
+            }
+        };
+
+        Some(Ok(StreamMessage {
+            topic: message.topic().to_string(),
+            partition: message.partition(),
+            offset: message.offset(),
+            timestamp_ms: message.timestamp().to_millis(),
+            payload: message.payload().map(<[u8]>::to_vec),
+        }))
+    }
+
+    async fn commit(&mut self, offsets: &[StreamOffset]) -> Result<()> {
+        let mut list = TopicPartitionList::new();
+        for offset in offsets {
+            // Kafka stores the next offset to read
+            list.add_partition_offset(
+                &offset.topic,
+                offset.partition,
+                Offset::Offset(offset.offset + 1),
+            )
+            .map_err(|e| RuntimeError::RuntimeError(format!("Invalid offset: {}", e)))?;
+        }
+        self.consumer
+            .commit(&list, CommitMode::Async)
+            .map_err(|e| RuntimeError::RuntimeError(format!("Failed to commit offsets: {}", e)))
+    }
+}
@@ -1,11 +1,36 @@
 //! Storage layer for historical data queries
 //!
 //! Provides async interfaces for querying event history to support feature extraction.
-
-use crate::error::Result;
+//! Events can be streamed in with an [`EventIngestor`], e.g. from Kafka, and
+//! kept in PostgreSQL/TimescaleDB with `PostgresStorage` (feature `sqlx`) or
+//! ClickHouse with `ClickHouseStorage` (feature `clickhouse`).
+
+#[cfg(feature = "clickhouse")]
+mod clickhouse;
+mod ingest;
+#[cfg(feature = "kafka")]
+mod kafka;
+#[cfg(feature = "sqlx")]
+mod postgres;
+
+#[cfg(feature = "clickhouse")]
+pub use clickhouse::{
+    ClickHouseStorage, ClickHouseStorageConfig, EventColumn, EventColumnType, PartitionGranularity,
+};
+pub use ingest::{
+    EventIngestor, EventStream, IngestConfig, IngestStats, StreamMessage, StreamOffset,
+};
+#[cfg(feature = "kafka")]
+pub use kafka::{KafkaConfig, KafkaEventStream};
+#[cfg(feature = "sqlx")]
+pub use postgres::{Partitioning, PostgresStorage, PostgresStorageConfig};
+
+use crate::error::{Result, RuntimeError};
 use async_trait::async_trait;
 use corint_core::Value;
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 /// Time range for queries (start, end) in seconds since epoch
 pub type TimeRange = (i64, i64);
@@ -26,6 +51,21 @@ pub trait Storage: Send + Sync {
         time_range: TimeRange,
         filter: Option<EventFilter>,
     ) -> Result<Vec<Event>>;
+
+    /// Store a new event
+    async fn insert_event(&self, event: Event) -> Result<()> {
+        self.insert_events_batch(vec![event]).await
+    }
+
+    /// Store new events in one write
+    ///
+    /// Backends that are only read from keep the default, which fails.
+    async fn insert_events_batch(&self, events: Vec<Event>) -> Result<()> {
+        let _ = events;
+        Err(RuntimeError::RuntimeError(
+            "Storage does not accept new events".to_string(),
+        ))
+    }
 }
 
 /// Filter for event queries
@@ -36,6 +76,10 @@ pub struct EventFilter {
 
     /// Additional field filters
     pub field_filters: HashMap<String, Value>,
+
+    /// Top-level fields the caller reads (`None` for all). Column stores use
+    /// this to skip columns; other backends may return more fields.
+    pub fields: Option<Vec<String>>,
 }
 
 impl EventFilter {
@@ -44,6 +88,7 @@ impl EventFilter {
         Self {
             event_type: None,
             field_filters: HashMap::new(),
+            fields: None,
         }
     }
 
@@ -59,6 +104,12 @@ impl EventFilter {
         self
     }
 
+    /// Only read the given fields
+    pub fn with_fields(mut self, fields: Vec<String>) -> Self {
+        self.fields = Some(fields);
+        self
+    }
+
     /// Check if an event matches this filter
     pub fn matches(&self, event: &Event) -> bool {
         // Check event type
@@ -93,25 +144,205 @@ impl Default for EventFilter {
     }
 }
 
-/// In-memory storage implementation for testing
+/// Limits on the events an [`InMemoryStorage`] keeps
+///
+/// When a limit is reached the oldest inserted events are evicted first.
+#[derive(Debug, Clone, Default)]
+pub struct InMemoryRetention {
+    /// Maximum number of events
+    pub max_events: Option<usize>,
+
+    /// Events older than this are removed by [`InMemoryStorage::compact`]
+    pub max_age: Option<Duration>,
+
+    /// Maximum number of events per `event_type`
+    pub event_type_quotas: HashMap<String, usize>,
+}
+
+impl InMemoryRetention {
+    /// No limits
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Keep at most `max_events` events
+    pub fn with_max_events(mut self, max_events: usize) -> Self {
+        self.max_events = Some(max_events);
+        self
+    }
+
+    /// Drop events older than `max_age` when compacting
+    pub fn with_max_age(mut self, max_age: Duration) -> Self {
+        self.max_age = Some(max_age);
+        self
+    }
+
+    /// Keep at most `quota` events of `event_type`
+    pub fn with_event_type_quota(mut self, event_type: impl Into<String>, quota: usize) -> Self {
+        self.event_type_quotas.insert(event_type.into(), quota);
+        self
+    }
+}
+
+/// Events in insertion order with counts of the types that have a quota
+#[derive(Default)]
+struct EventBuffer {
+    events: VecDeque<Event>,
+    type_counts: HashMap<String, usize>,
+}
+
+impl EventBuffer {
+    fn push(&mut self, event: Event, retention: &InMemoryRetention) {
+        if let Some(event_type) = event_type(&event) {
+            if let Some(&quota) = retention.event_type_quotas.get(event_type) {
+                let count = self.type_counts.entry(event_type.to_string()).or_default();
+                *count += 1;
+                if *count > quota {
+                    let event_type = event_type.to_string();
+                    self.evict_oldest_of(&event_type);
+                }
+            }
+        }
+        self.events.push_back(event);
+
+        if let Some(max_events) = retention.max_events {
+            while self.events.len() > max_events {
+                if let Some(evicted) = self.events.pop_front() {
+                    self.uncount(&evicted);
+                }
+            }
+        }
+    }
+
+    fn evict_oldest_of(&mut self, evicted_type: &str) {
+        let position = self
+            .events
+            .iter()
+            .position(|event| event_type(event) == Some(evicted_type));
+        if let Some(evicted) = position.and_then(|i| self.events.remove(i)) {
+            self.uncount(&evicted);
+        }
+    }
+
+    fn uncount(&mut self, event: &Event) {
+        if let Some(count) = event_type(event).and_then(|t| self.type_counts.get_mut(t)) {
+            *count -= 1;
+        }
+    }
+
+    /// Remove events older than `cutoff`, returning how many were removed
+    fn remove_before(&mut self, cutoff: i64) -> usize {
+        let before = self.events.len();
+        let mut removed = Vec::new();
+        self.events.retain(|event| {
+            let keep = event.timestamp >= cutoff;
+            if !keep {
+                removed.push(event_type(event).map(str::to_string));
+            }
+            keep
+        });
+        for event_type in removed.into_iter().flatten() {
+            if let Some(count) = self.type_counts.get_mut(&event_type) {
+                *count -= 1;
+            }
+        }
+        before - self.events.len()
+    }
+}
+
+fn event_type(event: &Event) -> Option<&str> {
+    match event.data.get("event_type") {
+        Some(Value::String(event_type)) => Some(event_type),
+        _ => None,
+    }
+}
+
+/// In-memory storage for tests and embedded engines
+///
+/// Unbounded by default; with an [`InMemoryRetention`] it acts as a ring
+/// buffer and [`spawn_compaction`](Self::spawn_compaction) removes expired
+/// events in the background.
 pub struct InMemoryStorage {
-    events: Vec<Event>,
+    events: RwLock<EventBuffer>,
+    retention: InMemoryRetention,
 }
 
 impl InMemoryStorage {
     /// Create a new in-memory storage
     pub fn new() -> Self {
-        Self { events: Vec::new() }
+        Self {
+            events: RwLock::new(EventBuffer::default()),
+            retention: InMemoryRetention::default(),
+        }
+    }
+
+    /// Limit the events kept
+    pub fn with_retention(mut self, retention: InMemoryRetention) -> Self {
+        self.retention = retention;
+        self
     }
 
     /// Add an event to storage
     pub fn add_event(&mut self, event: Event) {
-        self.events.push(event);
+        self.events.get_mut().unwrap().push(event, &self.retention);
     }
 
     /// Add multiple events
     pub fn add_events(&mut self, events: Vec<Event>) {
-        self.events.extend(events);
+        let buffer = self.events.get_mut().unwrap();
+        for event in events {
+            buffer.push(event, &self.retention);
+        }
+    }
+
+    /// Number of stored events
+    pub fn len(&self) -> usize {
+        self.events.read().unwrap().events.len()
+    }
+
+    /// Whether no events are stored
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Remove events older than the retention's `max_age`, returning how
+    /// many were removed
+    pub fn compact(&self) -> usize {
+        let Some(max_age) = self.retention.max_age else {
+            return 0;
+        };
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs() as i64;
+        self.compact_before(now - max_age.as_secs() as i64)
+    }
+
+    fn compact_before(&self, cutoff: i64) -> usize {
+        let removed = self.events.write().unwrap().remove_before(cutoff);
+        if removed > 0 {
+            tracing::debug!("Compacted {} expired events from memory", removed);
+        }
+        removed
+    }
+
+    /// Compact every `interval` until the storage is dropped
+    pub fn spawn_compaction(self: &Arc<Self>, interval: Duration) -> tokio::task::JoinHandle<()> {
+        let storage = Arc::downgrade(self);
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            ticker.tick().await;
+            loop {
+                ticker.tick().await;
+                match storage.upgrade() {
+                    Some(storage) => {
+                        storage.compact();
+                    }
+                    None => break,
+                }
+            }
+        })
     }
 }
 
@@ -131,6 +362,9 @@ impl Storage for InMemoryStorage {
         let (start, end) = time_range;
 
         let mut results: Vec<Event> = self
+            .events
+            .read()
+            .unwrap()
             .events
             .iter()
             .filter(|event| event.timestamp >= start && event.timestamp < end)
@@ -143,6 +377,14 @@ impl Storage for InMemoryStorage {
 
         Ok(results)
     }
+
+    async fn insert_events_batch(&self, events: Vec<Event>) -> Result<()> {
+        let mut buffer = self.events.write().unwrap();
+        for event in events {
+            buffer.push(event, &self.retention);
+        }
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -202,4 +444,71 @@ mod tests {
         assert_eq!(results.len(), 1);
         assert_eq!(results[0].timestamp, 200);
     }
+
+    #[tokio::test]
+    async fn test_in_memory_retention() {
+        let retention = InMemoryRetention::new()
+            .with_max_events(4)
+            .with_event_type_quota("login", 2);
+        let storage = InMemoryStorage::new().with_retention(retention);
+
+        for (timestamp, event_type) in [
+            (100, "login"),
+            (200, "transaction"),
+            (300, "login"),
+            (400, "login"),
+        ] {
+            storage
+                .insert_event(create_test_event(timestamp, event_type, 0.0))
+                .await
+                .unwrap();
+        }
+        // The oldest login was evicted by the quota
+        let timestamps =
+            |events: Vec<Event>| events.iter().map(|e| e.timestamp).collect::<Vec<_>>();
+        let events = storage.query_events((0, 1000), None).await.unwrap();
+        assert_eq!(timestamps(events), vec![200, 300, 400]);
+
+        storage
+            .insert_events_batch(vec![
+                create_test_event(500, "transaction", 0.0),
+                create_test_event(600, "transaction", 0.0),
+            ])
+            .await
+            .unwrap();
+        // The oldest events overall were evicted by the size limit
+        let events = storage.query_events((0, 1000), None).await.unwrap();
+        assert_eq!(timestamps(events), vec![300, 400, 500, 600]);
+
+        assert_eq!(storage.compact_before(450), 2);
+        storage
+            .insert_event(create_test_event(700, "login", 0.0))
+            .await
+            .unwrap();
+        let events = storage.query_events((0, 1000), None).await.unwrap();
+        assert_eq!(timestamps(events), vec![500, 600, 700]);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_background_compaction() {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs() as i64;
+        let mut storage = InMemoryStorage::new()
+            .with_retention(InMemoryRetention::new().with_max_age(Duration::from_secs(3600)));
+        storage.add_events(vec![
+            create_test_event(now - 7200, "login", 0.0),
+            create_test_event(now, "login", 0.0),
+        ]);
+
+        let storage = Arc::new(storage);
+        let compaction = storage.spawn_compaction(Duration::from_secs(60));
+        tokio::time::sleep(Duration::from_secs(61)).await;
+        assert_eq!(storage.len(), 1);
+
+        drop(storage);
+        tokio::time::sleep(Duration::from_secs(60)).await;
+        assert!(compaction.is_finished());
+    }
 }
@@ -0,0 +1,590 @@
+//! PostgreSQL / TimescaleDB event storage
+//!
+//! Events are stored as JSONB in a table partitioned by `event_timestamp`,
+//! either with native range partitions (created on demand when events for a
+//! new period arrive) or as a TimescaleDB hypertable. Retention drops whole
+//! partitions or chunks instead of deleting rows.
+//!
+//! Field filters use a GIN index on the payload. Fields listed in
+//! `indexed_fields` also get a B-tree index together with the timestamp, and
+//! string filters on them compare the extracted text so that index is used.
+
+use super::{Event, EventFilter, Storage, TimeRange};
+use crate::error::{Result, RuntimeError};
+use async_trait::async_trait;
+use chrono::DateTime;
+use corint_core::Value;
+use serde::{Deserialize, Serialize};
+use sqlx::postgres::PgArguments;
+use sqlx::{Arguments, PgPool, Row};
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::sync::Mutex;
+
+const SECONDS_PER_DAY: i64 = 86_400;
+
+/// How the event table is partitioned by time
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Partitioning {
+    /// PostgreSQL declarative range partitions
+    #[default]
+    Native,
+
+    /// TimescaleDB hypertable (requires the `timescaledb` extension)
+    Timescale,
+}
+
+/// PostgreSQL event storage configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PostgresStorageConfig {
+    /// Event table, optionally schema-qualified (default: `event_history`)
+    #[serde(default = "default_table")]
+    pub table: String,
+
+    /// Partitioning scheme
+    #[serde(default)]
+    pub partitioning: Partitioning,
+
+    /// Days covered by one partition or chunk (default: 1)
+    #[serde(default = "default_partition_days")]
+    pub partition_days: u32,
+
+    /// Payload fields with their own index, e.g. `user_id`
+    #[serde(default)]
+    pub indexed_fields: Vec<String>,
+
+    /// Days of events to keep; older partitions are dropped by
+    /// [`PostgresStorage::purge_expired`]
+    #[serde(default)]
+    pub retention_days: Option<u32>,
+}
+
+fn default_table() -> String {
+    "event_history".to_string()
+}
+
+fn default_partition_days() -> u32 {
+    1
+}
+
+impl Default for PostgresStorageConfig {
+    fn default() -> Self {
+        Self {
+            table: default_table(),
+            partitioning: Partitioning::default(),
+            partition_days: default_partition_days(),
+            indexed_fields: Vec::new(),
+            retention_days: None,
+        }
+    }
+}
+
+impl PostgresStorageConfig {
+    /// Store events in the given table
+    pub fn with_table(mut self, table: impl Into<String>) -> Self {
+        self.table = table.into();
+        self
+    }
+
+    /// Use a TimescaleDB hypertable instead of native partitions
+    pub fn with_timescale(mut self) -> Self {
+        self.partitioning = Partitioning::Timescale;
+        self
+    }
+
+    /// Index a payload field
+    pub fn with_indexed_field(mut self, field: impl Into<String>) -> Self {
+        self.indexed_fields.push(field.into());
+        self
+    }
+
+    /// Keep events for the given number of days
+    pub fn with_retention_days(mut self, days: u32) -> Self {
+        self.retention_days = Some(days);
+        self
+    }
+
+    /// Check table and field names, which are spliced into SQL
+    fn validate(&self) -> Result<()> {
+        let (schema, table) = split_table(&self.table);
+        for name in schema.into_iter().chain([table]) {
+            check_identifier(name)?;
+        }
+        for field in &self.indexed_fields {
+            check_identifier(field)?;
+        }
+        if self.partition_days == 0 {
+            return Err(RuntimeError::InvalidValue(
+                "partition_days must be at least 1".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    fn partition_seconds(&self) -> i64 {
+        self.partition_days as i64 * SECONDS_PER_DAY
+    }
+
+    /// Start and end (exclusive) of the partition holding `timestamp`
+    fn partition_bounds(&self, timestamp: i64) -> (i64, i64) {
+        let width = self.partition_seconds();
+        let start = timestamp.div_euclid(width) * width;
+        (start, start + width)
+    }
+
+    /// Partition table name, e.g. `event_history_p20240102`
+    fn partition_name(&self, start: i64) -> String {
+        let date = DateTime::from_timestamp(start, 0).unwrap_or_default();
+        format!("{}_p{}", self.table, date.format("%Y%m%d"))
+    }
+
+    /// Partition start encoded in a partition's (unqualified) table name
+    fn partition_start(&self, relname: &str) -> Option<i64> {
+        let (_, table) = split_table(&self.table);
+        let date = relname.strip_prefix(table)?.strip_prefix("_p")?;
+        let date = chrono::NaiveDate::parse_from_str(date, "%Y%m%d").ok()?;
+        Some(date.and_hms_opt(0, 0, 0)?.and_utc().timestamp())
+    }
+
+    /// Statements creating the table and its indexes
+    fn schema_statements(&self) -> Vec<String> {
+        let table = &self.table;
+        let index = |suffix: &str| format!("{}_{}", split_table(table).1, suffix);
+
+        let mut statements = vec![format!(
+            "CREATE TABLE IF NOT EXISTS {} (\
+             event_timestamp TIMESTAMPTZ NOT NULL, \
+             event_type TEXT, \
+             data JSONB NOT NULL){}",
+            table,
+            match self.partitioning {
+                Partitioning::Native => " PARTITION BY RANGE (event_timestamp)",
+                Partitioning::Timescale => "",
+            }
+        )];
+        if self.partitioning == Partitioning::Timescale {
+            statements.push(format!(
+                "SELECT create_hypertable('{}', 'event_timestamp', \
+                 chunk_time_interval => INTERVAL '{} days', if_not_exists => TRUE)",
+                table, self.partition_days
+            ));
+        }
+
+        statements.push(format!(
+            "CREATE INDEX IF NOT EXISTS {} ON {} (event_type, event_timestamp DESC)",
+            index("type_time"),
+            table
+        ));
+        statements.push(format!(
+            "CREATE INDEX IF NOT EXISTS {} ON {} USING GIN (data jsonb_path_ops)",
+            index("data"),
+            table
+        ));
+        for field in &self.indexed_fields {
+            statements.push(format!(
+                "CREATE INDEX IF NOT EXISTS {} ON {} ((data->>'{}'), event_timestamp DESC)",
+                index(&format!("{}_time", field)),
+                table,
+                field
+            ));
+        }
+        statements
+    }
+}
+
+/// Event storage backed by PostgreSQL or TimescaleDB
+pub struct PostgresStorage {
+    pool: PgPool,
+    config: PostgresStorageConfig,
+
+    /// Starts of native partitions known to exist
+    partitions: Mutex<HashSet<i64>>,
+}
+
+impl PostgresStorage {
+    /// Create a storage over `pool`; call [`create_schema`](Self::create_schema)
+    /// unless the table already exists
+    pub fn new(pool: PgPool, config: PostgresStorageConfig) -> Result<Self> {
+        config.validate()?;
+        Ok(Self {
+            pool,
+            config,
+            partitions: Mutex::new(HashSet::new()),
+        })
+    }
+
+    /// Create the event table, hypertable and indexes if missing
+    pub async fn create_schema(&self) -> Result<()> {
+        for statement in self.config.schema_statements() {
+            sqlx::query(&statement)
+                .execute(&self.pool)
+                .await
+                .map_err(db_error)?;
+        }
+        Ok(())
+    }
+
+    /// Drop partitions (or TimescaleDB chunks) older than the retention period
+    ///
+    /// Returns the number dropped; does nothing without `retention_days`.
+    pub async fn purge_expired(&self) -> Result<u64> {
+        let Some(days) = self.config.retention_days else {
+            return Ok(0);
+        };
+
+        let dropped = match self.config.partitioning {
+            Partitioning::Timescale => sqlx::query(
+                "SELECT drop_chunks($1::regclass, older_than => NOW() - make_interval(days => $2))",
+            )
+            .bind(&self.config.table)
+            .bind(days as i32)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(db_error)?
+            .len() as u64,
+            Partitioning::Native => {
+                let cutoff = chrono::Utc::now().timestamp() - days as i64 * SECONDS_PER_DAY;
+                let relnames: Vec<String> = sqlx::query_scalar(
+                    "SELECT c.relname::text FROM pg_inherits i \
+                     JOIN pg_class c ON c.oid = i.inhrelid \
+                     WHERE i.inhparent = $1::regclass",
+                )
+                .bind(&self.config.table)
+                .fetch_all(&self.pool)
+                .await
+                .map_err(db_error)?;
+
+                let mut dropped = 0;
+                for relname in relnames {
+                    let Some(start) = self.config.partition_start(&relname) else {
+                        continue;
+                    };
+                    let (_, end) = self.config.partition_bounds(start);
+                    if end > cutoff {
+                        continue;
+                    }
+                    let name = self.config.partition_name(start);
+                    sqlx::query(&format!("DROP TABLE IF EXISTS {}", name))
+                        .execute(&self.pool)
+                        .await
+                        .map_err(db_error)?;
+                    self.partitions.lock().unwrap().remove(&start);
+                    dropped += 1;
+                }
+                dropped
+            }
+        };
+
+        if dropped > 0 {
+            tracing::info!(
+                "Event retention dropped {} partitions of {}",
+                dropped,
+                self.config.table
+            );
+        }
+        Ok(dropped)
+    }
+
+    /// Create the native partitions the events fall into
+    async fn ensure_partitions(&self, events: &[Event]) -> Result<()> {
+        let missing: BTreeSet<i64> = {
+            let known = self.partitions.lock().unwrap();
+            events
+                .iter()
+                .map(|event| self.config.partition_bounds(event.timestamp).0)
+                .filter(|start| !known.contains(start))
+                .collect()
+        };
+
+        for start in missing {
+            let (_, end) = self.config.partition_bounds(start);
+            let sql = format!(
+                "CREATE TABLE IF NOT EXISTS {} PARTITION OF {} FOR VALUES FROM ('{}') TO ('{}')",
+                self.config.partition_name(start),
+                self.config.table,
+                timestamp_literal(start),
+                timestamp_literal(end)
+            );
+            match sqlx::query(&sql).execute(&self.pool).await {
+                Ok(_) => {}
+                // Another instance created it first
+                Err(e) if is_duplicate_table(&e) => {}
+                Err(e) => return Err(db_error(e)),
+            }
+            self.partitions.lock().unwrap().insert(start);
+        }
+        Ok(())
+    }
+
+    /// WHERE clause and arguments for a query
+    fn build_query(
+        &self,
+        time_range: TimeRange,
+        filter: Option<&EventFilter>,
+    ) -> Result<(String, PgArguments)> {
+        let mut args = PgArguments::default();
+        let mut conditions = vec![
+            "event_timestamp >= to_timestamp($1)".to_string(),
+            "event_timestamp < to_timestamp($2)".to_string(),
+        ];
+        add_arg(&mut args, time_range.0 as f64)?;
+        add_arg(&mut args, time_range.1 as f64)?;
+
+        if let Some(filter) = filter {
+            if let Some(event_type) = &filter.event_type {
+                add_arg(&mut args, event_type.clone())?;
+                conditions.push(format!("event_type = ${}", args.len()));
+            }
+
+            // Sorted so equal filters produce the same statement
+            let mut fields: Vec<(&String, &Value)> = filter.field_filters.iter().collect();
+            fields.sort_by(|a, b| a.0.cmp(b.0));
+            let mut contained = HashMap::new();
+            for (field, value) in fields {
+                match value {
+                    Value::String(s) if self.config.indexed_fields.contains(field) => {
+                        add_arg(&mut args, field.clone())?;
+                        add_arg(&mut args, s.clone())?;
+                        conditions.push(format!("data->>${} = ${}", args.len() - 1, args.len()));
+                    }
+                    _ => {
+                        contained.insert(field.clone(), value.clone());
+                    }
+                }
+            }
+            if !contained.is_empty() {
+                let json = serde_json::to_string(&contained).map_err(|e| {
+                    RuntimeError::InvalidValue(format!("Invalid field filter: {}", e))
+                })?;
+                add_arg(&mut args, json)?;
+                conditions.push(format!("data @> ${}::jsonb", args.len()));
+            }
+        }
+
+        let sql = format!(
+            "SELECT EXTRACT(EPOCH FROM event_timestamp)::float8 AS ts, data::text AS data \
+             FROM {} WHERE {} ORDER BY event_timestamp",
+            self.config.table,
+            conditions.join(" AND ")
+        );
+        Ok((sql, args))
+    }
+}
+
+#[async_trait]
+impl Storage for PostgresStorage {
+    async fn query_events(
+        &self,
+        time_range: TimeRange,
+        filter: Option<EventFilter>,
+    ) -> Result<Vec<Event>> {
+        let (sql, args) = self.build_query(time_range, filter.as_ref())?;
+        let rows = sqlx::query_with(&sql, args)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(db_error)?;
+
+        rows.iter()
+            .map(|row| {
+                let timestamp: f64 = row.try_get("ts").map_err(db_error)?;
+                let data: String = row.try_get("data").map_err(db_error)?;
+                let data = serde_json::from_str(&data).map_err(|e| {
+                    RuntimeError::RuntimeError(format!("Invalid stored event: {}", e))
+                })?;
+                Ok(Event {
+                    timestamp: timestamp as i64,
+                    data,
+                })
+            })
+            .collect()
+    }
+
+    async fn insert_events_batch(&self, events: Vec<Event>) -> Result<()> {
+        if events.is_empty() {
+            return Ok(());
+        }
+        if self.config.partitioning == Partitioning::Native {
+            self.ensure_partitions(&events).await?;
+        }
+
+        let mut timestamps = Vec::with_capacity(events.len());
+        let mut event_types = Vec::with_capacity(events.len());
+        let mut data = Vec::with_capacity(events.len());
+        for event in &events {
+            timestamps.push(event.timestamp as f64);
+            event_types.push(match event.data.get("event_type") {
+                Some(Value::String(event_type)) => Some(event_type.clone()),
+                _ => None,
+            });
+            data.push(
+                serde_json::to_string(&event.data).map_err(|e| {
+                    RuntimeError::InvalidValue(format!("Invalid event data: {}", e))
+                })?,
+            );
+        }
+
+        sqlx::query(&format!(
+            "INSERT INTO {} (event_timestamp, event_type, data) \
+             SELECT to_timestamp(t), e, d::jsonb \
+             FROM UNNEST($1::float8[], $2::text[], $3::text[]) AS u(t, e, d)",
+            self.config.table
+        ))
+        .bind(timestamps)
+        .bind(event_types)
+        .bind(data)
+        .execute(&self.pool)
+        .await
+        .map_err(db_error)?;
+        Ok(())
+    }
+}
+
+/// Split `schema.table` into its parts
+fn split_table(table: &str) -> (Option<&str>, &str) {
+    match table.split_once('.') {
+        Some((schema, table)) => (Some(schema), table),
+        None => (None, table),
+    }
+}
+
+/// Accept only plain lower-case identifiers, which need no quoting
+fn check_identifier(name: &str) -> Result<()> {
+    let valid = name
+        .chars()
+        .next()
+        .is_some_and(|c| c.is_ascii_lowercase() || c == '_')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_');
+    if valid {
+        Ok(())
+    } else {
+        Err(RuntimeError::InvalidValue(format!(
+            "Invalid identifier '{}': use lower-case letters, digits and underscores",
+            name
+        )))
+    }
+}
+
+/// UTC timestamp literal for partition bounds
+fn timestamp_literal(seconds: i64) -> String {
+    DateTime::from_timestamp(seconds, 0)
+        .unwrap_or_default()
+        .format("%Y-%m-%d %H:%M:%S+00")
+        .to_string()
+}
+
+fn add_arg<'q, T>(args: &mut PgArguments, value: T) -> Result<()>
+where
+    T: 'q + sqlx::Encode<'q, sqlx::Postgres> + sqlx::Type<sqlx::Postgres>,
+{
+    args.add(value)
+        .map_err(|e| RuntimeError::RuntimeError(format!("Failed to bind argument: {}", e)))
+}
+
+fn is_duplicate_table(e: &sqlx::Error) -> bool {
+    e.as_database_error()
+        .and_then(|e| e.code())
+        .is_some_and(|code| code == "42P07")
+}
+
+fn db_error(e: sqlx::Error) -> RuntimeError {
+    RuntimeError::RuntimeError(format!("Event storage database error: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_partition_bounds() {
+        let config = PostgresStorageConfig::default();
+        // 2024-01-02T10:00:00Z
+        let (start, end) = config.partition_bounds(1_704_189_600);
+        assert_eq!(start, 1_704_153_600);
+        assert_eq!(end - start, SECONDS_PER_DAY);
+        assert_eq!(config.partition_name(start), "event_history_p20240102");
+        assert_eq!(
+            config.partition_start("event_history_p20240102"),
+            Some(start)
+        );
+        assert_eq!(config.partition_start("event_history_default"), None);
+        assert_eq!(timestamp_literal(start), "2024-01-02 00:00:00+00");
+
+        let weekly = PostgresStorageConfig {
+            partition_days: 7,
+            ..PostgresStorageConfig::default().with_table("risk.events")
+        };
+        let (start, end) = weekly.partition_bounds(1_704_189_600);
+        assert!(start <= 1_704_189_600 && 1_704_189_600 < end);
+        assert_eq!(end - start, 7 * SECONDS_PER_DAY);
+        assert_eq!(weekly.partition_name(start), "risk.events_p20231228");
+        assert_eq!(weekly.partition_start("events_p20231228"), Some(start));
+    }
+
+    #[test]
+    fn test_schema_statements() {
+        let config = PostgresStorageConfig::default().with_indexed_field("user_id");
+        assert_eq!(
+            config.schema_statements(),
+            vec![
+                "CREATE TABLE IF NOT EXISTS event_history (event_timestamp TIMESTAMPTZ NOT NULL, \
+                 event_type TEXT, data JSONB NOT NULL) PARTITION BY RANGE (event_timestamp)",
+                "CREATE INDEX IF NOT EXISTS event_history_type_time ON event_history \
+                 (event_type, event_timestamp DESC)",
+                "CREATE INDEX IF NOT EXISTS event_history_data ON event_history \
+                 USING GIN (data jsonb_path_ops)",
+                "CREATE INDEX IF NOT EXISTS event_history_user_id_time ON event_history \
+                 ((data->>'user_id'), event_timestamp DESC)",
+            ]
+        );
+
+        let timescale = PostgresStorageConfig::default().with_timescale();
+        let statements = timescale.schema_statements();
+        assert!(!statements[0].contains("PARTITION BY"));
+        assert_eq!(
+            statements[1],
+            "SELECT create_hypertable('event_history', 'event_timestamp', \
+             chunk_time_interval => INTERVAL '1 days', if_not_exists => TRUE)"
+        );
+    }
+
+    #[test]
+    fn test_rejects_unsafe_names() {
+        assert!(PostgresStorageConfig::default().validate().is_ok());
+        assert!(PostgresStorageConfig::default()
+            .with_table("events; DROP TABLE events")
+            .validate()
+            .is_err());
+        assert!(PostgresStorageConfig::default()
+            .with_indexed_field("user_id')")
+            .validate()
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_build_query() {
+        let pool = sqlx::postgres::PgPoolOptions::new()
+            .connect_lazy("postgres://localhost/corint")
+            .unwrap();
+        let storage = PostgresStorage::new(
+            pool,
+            PostgresStorageConfig::default().with_indexed_field("user_id"),
+        )
+        .unwrap();
+
+        let filter = EventFilter::new()
+            .with_event_type("payment".to_string())
+            .with_field_filter("user_id".to_string(), Value::String("u1".to_string()))
+            .with_field_filter("amount".to_string(), Value::Number(100.0));
+        let (sql, args) = storage.build_query((0, 100), Some(&filter)).unwrap();
+        assert_eq!(
+            sql,
+            "SELECT EXTRACT(EPOCH FROM event_timestamp)::float8 AS ts, data::text AS data \
+             FROM event_history WHERE event_timestamp >= to_timestamp($1) \
+             AND event_timestamp < to_timestamp($2) AND event_type = $3 \
+             AND data->>$4 = $5 AND data @> $6::jsonb ORDER BY event_timestamp"
+        );
+        assert_eq!(args.len(), 6);
+    }
+}
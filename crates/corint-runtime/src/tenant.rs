@@ -0,0 +1,428 @@
+//! Per-tenant execution quotas
+//!
+//! In a deployment shared by several tenants, one tenant's traffic spike
+//! would otherwise take every worker and data source slot, and starve the
+//! decisions of all the others. [`TenantQuotas`] give each tenant its own
+//! budget: a cap on the decisions it has in flight and on the decisions per
+//! second it may start. A decision over its tenant's quota waits or fails
+//! fast like a call over a dependency's limit, with
+//! [`RuntimeError::ConcurrencyLimitReached`] or [`RuntimeError::RateLimited`];
+//! the other tenants are not affected.
+//!
+//! Tenants without an entry of their own share no budget: each gets its own
+//! copy of the default quota, if one is configured. Their limiters are
+//! dropped once idle for a while, and at most [`MAX_DEFAULT_TENANTS`] of them
+//! are tracked at once; the decisions of further tenants are rejected with
+//! [`RuntimeError::RateLimited`] until others expire.
+//!
+//! ```yaml
+//! tenant_quotas:
+//!   default:
+//!     concurrency:
+//!       max_concurrent: 50
+//!       queue_timeout_ms: 20
+//!     rate_limit:
+//!       tokens_per_sec: 200
+//!       burst: 50
+//!   tenants:
+//!     acme:
+//!       concurrency:
+//!         max_concurrent: 200
+//! ```
+
+use crate::concurrency::{ConcurrencyLimit, ConcurrencyLimiter, ConcurrencyStats};
+use crate::error::{Result, RuntimeError};
+use crate::rate_limit::{RateLimit, RateLimitStats, RateLimiter};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::OwnedSemaphorePermit;
+
+/// Tenants on the default quota whose limiters are tracked at once
+pub const MAX_DEFAULT_TENANTS: usize = 10_000;
+
+/// Time after its last decision that a tenant on the default quota is forgotten
+pub const IDLE_TENANT_TTL: Duration = Duration::from_secs(600);
+
+tokio::task_local! {
+    static CURRENT_TENANT: String;
+}
+
+/// Quota of one tenant
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct TenantQuota {
+    /// Decisions of the tenant in flight at once
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub concurrency: Option<ConcurrencyLimit>,
+
+    /// Decisions per second the tenant may start
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rate_limit: Option<RateLimit>,
+}
+
+impl TenantQuota {
+    /// Create a quota without limits
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cap the decisions in flight
+    pub fn with_concurrency(mut self, concurrency: ConcurrencyLimit) -> Self {
+        self.concurrency = Some(concurrency);
+        self
+    }
+
+    /// Cap the decisions started per second
+    pub fn with_rate_limit(mut self, rate_limit: RateLimit) -> Self {
+        self.rate_limit = Some(rate_limit);
+        self
+    }
+}
+
+/// Quotas of all tenants
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TenantQuotaConfig {
+    /// Quota of each tenant without an entry in `tenants`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default: Option<TenantQuota>,
+
+    /// Quotas of individual tenants, by tenant ID
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub tenants: HashMap<String, TenantQuota>,
+}
+
+impl TenantQuotaConfig {
+    /// Create a configuration without quotas
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the quota of tenants without one of their own
+    pub fn with_default(mut self, quota: TenantQuota) -> Self {
+        self.default = Some(quota);
+        self
+    }
+
+    /// Set the quota of a tenant
+    pub fn with_tenant(mut self, tenant: impl Into<String>, quota: TenantQuota) -> Self {
+        self.tenants.insert(tenant.into(), quota);
+        self
+    }
+
+    /// Quota of a tenant, if it has one
+    pub fn quota(&self, tenant: &str) -> Option<TenantQuota> {
+        self.tenants.get(tenant).copied().or(self.default)
+    }
+
+    /// Label of a tenant in metrics
+    ///
+    /// Tenants without an entry of their own are labeled `default`, so that
+    /// arbitrary tenant IDs do not each create a metric series.
+    pub fn metric_label<'a>(&self, tenant: &'a str) -> &'a str {
+        if self.tenants.contains_key(tenant) {
+            tenant
+        } else {
+            "default"
+        }
+    }
+}
+
+/// Point-in-time statistics of one tenant's quota
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct TenantStats {
+    /// Tenant ID
+    pub tenant: String,
+
+    /// Decisions in flight, if their number is capped
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub concurrency: Option<ConcurrencyStats>,
+
+    /// Decisions started, if their rate is capped
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rate_limit: Option<RateLimitStats>,
+}
+
+/// Limiters enforcing one tenant's quota
+#[derive(Debug)]
+struct TenantLimiter {
+    concurrency: Option<ConcurrencyLimiter>,
+    rate_limit: Option<RateLimiter>,
+}
+
+/// Limiter of a tenant and the time of its last decision
+#[derive(Debug)]
+struct TrackedLimiter {
+    limiter: Arc<TenantLimiter>,
+    last_used: Instant,
+}
+
+impl TenantLimiter {
+    fn new(tenant: &str, quota: TenantQuota) -> Self {
+        Self {
+            concurrency: quota
+                .concurrency
+                .map(|limit| ConcurrencyLimiter::new(tenant, limit)),
+            rate_limit: quota
+                .rate_limit
+                .map(|limit| RateLimiter::new(tenant, limit)),
+        }
+    }
+
+    /// Whether none of the tenant's decisions are in flight
+    fn is_idle(&self) -> bool {
+        self.concurrency
+            .as_ref()
+            .is_none_or(|concurrency| concurrency.stats().in_flight == 0)
+    }
+}
+
+/// Enforces the quotas of tenants
+///
+/// Limiters are created on a tenant's first decision. Those of tenants with
+/// a quota of their own are kept for the lifetime of the quotas; those of
+/// tenants on the default quota expire after [`IDLE_TENANT_TTL`] without
+/// decisions.
+#[derive(Debug)]
+pub struct TenantQuotas {
+    config: TenantQuotaConfig,
+    limiters: Mutex<HashMap<String, TrackedLimiter>>,
+    max_default_tenants: usize,
+    idle_ttl: Duration,
+}
+
+impl Default for TenantQuotas {
+    fn default() -> Self {
+        Self::new(TenantQuotaConfig::default())
+    }
+}
+
+impl TenantQuotas {
+    /// Enforce the quotas of `config`
+    pub fn new(config: TenantQuotaConfig) -> Self {
+        Self {
+            config,
+            limiters: Mutex::new(HashMap::new()),
+            max_default_tenants: MAX_DEFAULT_TENANTS,
+            idle_ttl: IDLE_TENANT_TTL,
+        }
+    }
+
+    /// Configured quotas
+    pub fn config(&self) -> &TenantQuotaConfig {
+        &self.config
+    }
+
+    /// Admit a decision of `tenant`
+    ///
+    /// Takes a token of the tenant's rate limit, then waits for one of its
+    /// concurrency slots, which is held until the returned permit is dropped.
+    /// A tenant without a quota is admitted at once.
+    pub async fn admit(&self, tenant: &str) -> Result<TenantPermit> {
+        let mut permit = TenantPermit::unlimited(tenant);
+        let Some(limiter) = self.limiter(tenant)? else {
+            return Ok(permit);
+        };
+        if let Some(rate_limit) = &limiter.rate_limit {
+            rate_limit.acquire().await?;
+        }
+        if let Some(concurrency) = &limiter.concurrency {
+            permit._slot = Some(concurrency.acquire().await?);
+        }
+        Ok(permit)
+    }
+
+    /// Limiter of `tenant`, if it has a quota
+    ///
+    /// Fails if the tenant is on the default quota and as many such tenants
+    /// as allowed are already tracked.
+    fn limiter(&self, tenant: &str) -> Result<Option<Arc<TenantLimiter>>> {
+        let Some(quota) = self.config.quota(tenant) else {
+            return Ok(None);
+        };
+        let now = Instant::now();
+        let mut limiters = self.limiters.lock().unwrap();
+        if let Some(tracked) = limiters.get_mut(tenant) {
+            tracked.last_used = now;
+            return Ok(Some(Arc::clone(&tracked.limiter)));
+        }
+
+        if !self.config.tenants.contains_key(tenant) {
+            limiters.retain(|tenant, tracked| {
+                self.config.tenants.contains_key(tenant)
+                    || now.duration_since(tracked.last_used) < self.idle_ttl
+                    || !tracked.limiter.is_idle()
+            });
+            let tracked_defaults = limiters
+                .keys()
+                .filter(|tenant| !self.config.tenants.contains_key(*tenant))
+                .count();
+            if tracked_defaults >= self.max_default_tenants {
+                return Err(RuntimeError::RateLimited(format!(
+                    "tenant '{}' not admitted: {} tenants on the default quota are active",
+                    tenant, tracked_defaults
+                )));
+            }
+        }
+
+        let limiter = Arc::new(TenantLimiter::new(tenant, quota));
+        limiters.insert(
+            tenant.to_string(),
+            TrackedLimiter {
+                limiter: Arc::clone(&limiter),
+                last_used: now,
+            },
+        );
+        Ok(Some(limiter))
+    }
+
+    /// Statistics of the tenants tracked, by tenant ID
+    pub fn stats(&self) -> Vec<TenantStats> {
+        let limiters = self.limiters.lock().unwrap();
+        let mut stats: Vec<TenantStats> = limiters
+            .iter()
+            .map(|(tenant, TrackedLimiter { limiter, .. })| TenantStats {
+                tenant: tenant.clone(),
+                concurrency: limiter.concurrency.as_ref().map(ConcurrencyLimiter::stats),
+                rate_limit: limiter.rate_limit.as_ref().map(RateLimiter::stats),
+            })
+            .collect();
+        stats.sort_by(|a, b| a.tenant.cmp(&b.tenant));
+        stats
+    }
+}
+
+/// Admission of a decision under its tenant's quota
+///
+/// Holds the tenant's concurrency slot, if any, until dropped.
+#[derive(Debug)]
+pub struct TenantPermit {
+    tenant: String,
+    _slot: Option<OwnedSemaphorePermit>,
+}
+
+impl TenantPermit {
+    /// Admission of a tenant without a quota
+    pub(crate) fn unlimited(tenant: &str) -> Self {
+        Self {
+            tenant: tenant.to_string(),
+            _slot: None,
+        }
+    }
+
+    /// Tenant admitted
+    pub fn tenant(&self) -> &str {
+        &self.tenant
+    }
+
+    /// Run `future` as the admitted decision
+    ///
+    /// Programs of the tenant executed within it are not admitted again.
+    pub async fn scope<F: Future>(self, future: F) -> F::Output {
+        let tenant = self.tenant.clone();
+        let output = CURRENT_TENANT.scope(tenant, future).await;
+        drop(self);
+        output
+    }
+}
+
+/// Tenant admitted in the current task, if any
+pub fn current_tenant() -> Option<String> {
+    CURRENT_TENANT.try_with(Clone::clone).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::RuntimeError;
+
+    #[tokio::test]
+    async fn test_tenant_quotas_are_separate() {
+        let quotas = TenantQuotas::new(
+            TenantQuotaConfig::new()
+                .with_default(
+                    TenantQuota::new()
+                        .with_concurrency(ConcurrencyLimit::new(1).with_queue_timeout_ms(10)),
+                )
+                .with_tenant(
+                    "acme",
+                    TenantQuota::new().with_concurrency(ConcurrencyLimit::new(2)),
+                ),
+        );
+
+        let _busy = quotas.admit("globex").await.unwrap();
+        let err = quotas.admit("globex").await.unwrap_err();
+        assert!(matches!(err, RuntimeError::ConcurrencyLimitReached(_)));
+
+        // Neither the tenant with its own quota nor another one on the
+        // default quota is held up by the saturated tenant
+        let _first = quotas.admit("acme").await.unwrap();
+        let _second = quotas.admit("acme").await.unwrap();
+        assert!(quotas.admit("initech").await.is_ok());
+
+        let stats = quotas.stats();
+        let tenants: Vec<_> = stats.iter().map(|s| s.tenant.as_str()).collect();
+        assert_eq!(tenants, vec!["acme", "globex", "initech"]);
+        assert_eq!(stats[0].concurrency.as_ref().unwrap().in_flight, 2);
+        assert_eq!(stats[1].concurrency.as_ref().unwrap().rejected, 1);
+    }
+
+    #[tokio::test]
+    async fn test_tenant_permit_scope() {
+        let quotas = TenantQuotas::new(TenantQuotaConfig::new());
+        assert!(quotas.stats().is_empty());
+
+        let permit = quotas.admit("acme").await.unwrap();
+        assert_eq!(current_tenant(), None);
+        let tenant = permit.scope(async { current_tenant() }).await;
+        assert_eq!(tenant.as_deref(), Some("acme"));
+    }
+
+    #[test]
+    fn test_tenant_quota_config() {
+        let config: TenantQuotaConfig = serde_yaml::from_str(
+            "default:\n  rate_limit:\n    tokens_per_sec: 100\ntenants:\n  acme:\n    concurrency:\n      max_concurrent: 20\n",
+        )
+        .unwrap();
+        assert_eq!(
+            config.quota("acme"),
+            Some(TenantQuota::new().with_concurrency(ConcurrencyLimit::new(20)))
+        );
+        assert_eq!(
+            config.quota("globex"),
+            Some(TenantQuota::new().with_rate_limit(RateLimit::new(100.0)))
+        );
+        assert_eq!(TenantQuotaConfig::new().quota("acme"), None);
+        assert_eq!(config.metric_label("acme"), "acme");
+        assert_eq!(config.metric_label("globex"), "default");
+    }
+
+    #[tokio::test]
+    async fn test_default_tenants_are_bounded_and_expire() {
+        let mut quotas = TenantQuotas::new(
+            TenantQuotaConfig::new()
+                .with_default(TenantQuota::new().with_concurrency(ConcurrencyLimit::new(5)))
+                .with_tenant(
+                    "acme",
+                    TenantQuota::new().with_concurrency(ConcurrencyLimit::new(5)),
+                ),
+        );
+        quotas.max_default_tenants = 2;
+
+        let _acme = quotas.admit("acme").await.unwrap();
+        let _busy = quotas.admit("globex").await.unwrap();
+        drop(quotas.admit("initech").await.unwrap());
+        let err = quotas.admit("hooli").await.unwrap_err();
+        assert!(matches!(err, RuntimeError::RateLimited(_)));
+        assert_eq!(quotas.stats().len(), 3);
+
+        // Once expired, the idle tenant makes room; the one with a decision
+        // in flight and the one with its own quota are kept
+        quotas.idle_ttl = Duration::ZERO;
+        assert!(quotas.admit("hooli").await.is_ok());
+        let stats = quotas.stats();
+        let tenants: Vec<_> = stats.iter().map(|s| s.tenant.as_str()).collect();
+        assert_eq!(tenants, vec!["acme", "globex", "hooli"]);
+    }
+}
@@ -1095,14 +1095,21 @@ fn test_error_stack_underflow() {
 
 #[test]
 fn test_error_type_error() {
-    let error = RuntimeError::TypeError("Cannot add string and number".to_string());
-    assert!(error.to_string().contains("Type error"));
+    let error = RuntimeError::TypeMismatch {
+        expr: "Add".to_string(),
+        message: "incompatible operands String and Number".to_string(),
+    };
+    assert!(error.to_string().contains("Type mismatch in Add"));
+    assert_eq!(error.code(), "TYPE_MISMATCH");
 }
 
 #[test]
 fn test_error_field_not_found() {
-    let error = RuntimeError::FieldNotFound("user.email".to_string());
-    assert!(error.to_string().contains("Field not found"));
+    let error = RuntimeError::MissingField {
+        path: "user.email".to_string(),
+    };
+    assert!(error.to_string().contains("Missing field: user.email"));
+    assert_eq!(error.code(), "MISSING_FIELD");
 }
 
 #[test]
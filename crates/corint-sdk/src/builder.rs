@@ -3,10 +3,21 @@
 use crate::config::{EngineConfig, LLMConfig, ServiceConfig, StorageConfig};
 use crate::decision_engine::DecisionEngine;
 use crate::error::Result;
-use corint_repository::{RepositoryConfig, RepositoryContent, RepositoryLoader};
+use corint_repository::{
+    RepositoryConfig, RepositoryContent, RepositoryLoader, RepositorySource, SecretResolver,
+};
+use corint_compiler::{StepPlugin, StepPlugins};
 use corint_runtime::feature::FeatureExecutor;
+use corint_runtime::{
+    Clock, CustomStepHandler, EmitDispatcher, ExperimentStore, OutcomeStore, Storage,
+};
+use std::collections::HashMap;
 use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::Duration;
+
+/// How often expired entries are purged from lists loaded from the repository
+const LIST_EXPIRY_SWEEP_INTERVAL: Duration = Duration::from_secs(300);
 
 /// Builder for DecisionEngine
 ///
@@ -37,8 +48,17 @@ use std::sync::Arc;
 pub struct DecisionEngineBuilder {
     config: EngineConfig,
     repository_config: Option<RepositoryConfig>,
+    secret_resolver: Option<Arc<dyn SecretResolver>>,
     feature_executor: Option<Arc<FeatureExecutor>>,
+    feature_fetch_limits: corint_runtime::feature::FetchLimits,
     list_service: Option<Arc<corint_runtime::lists::ListService>>,
+    clock: Option<Arc<dyn Clock>>,
+    outcome_store: Option<Arc<dyn OutcomeStore>>,
+    experiment_store: Option<Arc<dyn ExperimentStore>>,
+    event_storage: Option<Arc<dyn Storage>>,
+    emit_dispatcher: Option<Arc<EmitDispatcher>>,
+    step_plugins: StepPlugins,
+    custom_step_handlers: HashMap<String, Arc<dyn CustomStepHandler>>,
     #[cfg(feature = "sqlx")]
     result_writer: Option<Arc<corint_runtime::DecisionResultWriter>>,
     #[cfg(feature = "sqlx")]
@@ -55,8 +75,17 @@ impl DecisionEngineBuilder {
         Self {
             config: EngineConfig::new(),
             repository_config: None,
+            secret_resolver: None,
             feature_executor: None,
+            feature_fetch_limits: Default::default(),
             list_service: None,
+            clock: None,
+            outcome_store: None,
+            experiment_store: None,
+            event_storage: None,
+            emit_dispatcher: None,
+            step_plugins: StepPlugins::new(),
+            custom_step_handlers: HashMap::new(),
             #[cfg(feature = "sqlx")]
             result_writer: None,
             #[cfg(feature = "sqlx")]
@@ -110,10 +139,23 @@ impl DecisionEngineBuilder {
     ///     .await?;
     /// ```
     pub fn with_repository(mut self, config: RepositoryConfig) -> Self {
+        // Imports in a file system repository are relative to its root
+        if let (RepositorySource::FileSystem, Some(path)) = (&config.source, &config.base_path) {
+            self.config.compiler_options.library_base_path = path.clone();
+        }
         self.repository_config = Some(config);
         self
     }
 
+    /// Set the resolver for `${secret:...}` placeholders in repository YAML
+    ///
+    /// `${ENV_VAR}` placeholders are always resolved from the environment.
+    /// The resolver is kept for [`DecisionEngine::reload`].
+    pub fn with_secret_resolver(mut self, resolver: Arc<dyn SecretResolver>) -> Self {
+        self.secret_resolver = Some(resolver);
+        self
+    }
+
     /// Add a rule file
     pub fn add_rule_file(mut self, path: impl Into<PathBuf>) -> Self {
         self.config.rule_files.push(path.into());
@@ -151,6 +193,65 @@ impl DecisionEngineBuilder {
         self
     }
 
+    /// Add reason code catalog content
+    ///
+    /// Multiple catalogs are merged in the order they are added; later
+    /// definitions of the same code win.
+    ///
+    /// # Arguments
+    /// * `id` - Identifier for the catalog (used in error messages)
+    /// * `content` - YAML content of the reason code catalog
+    pub fn add_reason_catalog_content(
+        mut self,
+        id: impl Into<String>,
+        content: impl Into<String>,
+    ) -> Self {
+        self.config
+            .reason_catalog_contents
+            .push((id.into(), content.into()));
+        self
+    }
+
+    /// Add PII masking policy content
+    ///
+    /// The policy masks matching fields in persisted decision records and in
+    /// returned traces. Multiple policies are merged in the order they are
+    /// added; rules from later policies take precedence.
+    ///
+    /// # Arguments
+    /// * `id` - Identifier for the policy (used in error messages)
+    /// * `content` - YAML content of the masking policy
+    pub fn add_masking_policy_content(
+        mut self,
+        id: impl Into<String>,
+        content: impl Into<String>,
+    ) -> Self {
+        self.config
+            .masking_policy_contents
+            .push((id.into(), content.into()));
+        self
+    }
+
+    /// Add entity key definitions from content string
+    ///
+    /// Entities declare how composite keys such as `card_hash + merchant_id`
+    /// are derived from events. Features reference them with
+    /// `dimension_value: entity.<name>`.
+    ///
+    /// # Arguments
+    /// * `id` - Identifier for the document (used in error messages)
+    /// * `content` - YAML content with an `entities` list
+    pub fn add_entity_definition_content(
+        mut self,
+        id: impl Into<String>,
+        content: impl Into<String>,
+    ) -> Self {
+        self.config
+            .entity_definition_contents
+            .push((id.into(), content.into()));
+        self
+    }
+
     /// Set storage configuration
     pub fn with_storage(mut self, storage: StorageConfig) -> Self {
         self.config.storage = Some(storage);
@@ -181,6 +282,112 @@ impl DecisionEngineBuilder {
         self
     }
 
+    /// Sample persisted decisions for quality review
+    ///
+    /// Sampled decisions are flagged in their `DecisionRecord` and appear in the review queue.
+    pub fn with_sampling(mut self, sampling: corint_runtime::SamplingConfig) -> Self {
+        self.config.sampling = sampling;
+        self
+    }
+
+    /// Record or replay external API and data source responses per request
+    ///
+    /// In record mode, the responses seen by each decision are written to
+    /// `<directory>/<request_id>.json`. In replay mode, a request carrying the
+    /// same `request_id` in its metadata is answered from that file instead of
+    /// calling out, reproducing the recorded decision.
+    pub fn with_record_replay(mut self, record_replay: corint_runtime::RecordReplayConfig) -> Self {
+        self.config.record_replay = record_replay;
+        self
+    }
+
+    /// Append a record of every list hit to decision responses
+    ///
+    /// Each `list_hits` entry names the list, the matched value, the rule
+    /// (or ruleset or pipeline) that looked it up, the list version and the
+    /// time, e.g. to show when a sanctions list match influenced a decline.
+    pub fn with_list_hit_audit(mut self, enable: bool) -> Self {
+        self.config.audit_list_hits = enable;
+        self
+    }
+
+    /// Make every decision within `timeout`
+    ///
+    /// Work left when the time is up is skipped: pending external calls
+    /// resolve to their fallback and the remaining rules are not evaluated.
+    /// The decision is then made from the results gathered so far and the
+    /// response is marked `degraded`, with the scopes that timed out. A
+    /// request can set its own budget with `DecisionRequest::with_timeout`.
+    pub fn with_decision_timeout(mut self, timeout: std::time::Duration) -> Self {
+        self.config.decision_timeout_ms = Some(timeout.as_millis() as u64);
+        self
+    }
+
+    /// Abort programs that exceed `limits`
+    ///
+    /// A guard against runaway programs rather than a time budget: the
+    /// decision fails with `ExecutionLimitExceeded`, naming the program and
+    /// the instruction it was aborted at.
+    pub fn with_execution_limits(mut self, limits: corint_runtime::ExecutionLimits) -> Self {
+        self.config.execution_limits = limits;
+        self
+    }
+
+    /// Capture the inputs every decision consumes
+    ///
+    /// The event, feature values, API and service call results and list
+    /// lookup outcomes are attached to the trace and the persisted decision
+    /// record, so that `DecisionEngine::replay_decision` can reproduce the
+    /// decision later.
+    pub fn with_input_capture(mut self, enable: bool) -> Self {
+        self.config.capture_inputs = enable;
+        self
+    }
+
+    /// Profile the time spent per instruction, rule, ruleset, pipeline and feature
+    ///
+    /// Call counts and timings are aggregated over all decisions into
+    /// `DecisionEngine::profile_report`, to find the rules and features that
+    /// dominate latency. Adds a clock read to every instruction executed.
+    pub fn with_profiling(mut self, enable: bool) -> Self {
+        self.config.profiling = enable;
+        self
+    }
+
+    /// Set how comparisons and logical operators treat missing (`Null`) values
+    ///
+    /// With `NullSemantics::ThreeValued`, a comparison involving `Null` is
+    /// Unknown rather than false, `&&`, `||` and `!` follow Kleene logic, and
+    /// a rule whose condition is Unknown does not trigger even when negated.
+    /// The conditions that were Unknown are flagged in the trace.
+    pub fn with_null_semantics(mut self, semantics: corint_runtime::NullSemantics) -> Self {
+        self.config.null_semantics = semantics;
+        self
+    }
+
+    /// Give each tenant its own concurrency and rate quota
+    ///
+    /// Requests made `DecisionRequest::with_tenant` are admitted under the
+    /// quota of their tenant, so a spike of one tenant's traffic is throttled
+    /// without starving the decisions of the others. Admissions and
+    /// rejections are counted per tenant in the `tenant_admitted_total` and
+    /// `tenant_rejected_total` metrics, tenants on the default quota together.
+    pub fn with_tenant_quotas(mut self, quotas: corint_runtime::TenantQuotaConfig) -> Self {
+        self.config.tenant_quotas = quotas;
+        self
+    }
+
+    /// Return the decision already made for requests retrying an event
+    ///
+    /// A request whose `event_id` metadata repeats one decided within the TTL
+    /// is answered with the earlier response instead of being executed
+    /// again, so client retries are safe. Events are keyed per tenant, and
+    /// failed decisions are not kept.
+    pub fn with_idempotency(mut self, idempotency: crate::config::IdempotencyConfig) -> Self {
+        self.config.idempotency = Some(idempotency);
+        self
+    }
+
     /// Enable semantic analysis
     pub fn enable_semantic_analysis(mut self, enable: bool) -> Self {
         self.config.compiler_options.enable_semantic_analysis = enable;
@@ -199,23 +406,116 @@ impl DecisionEngineBuilder {
         self
     }
 
+    /// Enable common subexpression elimination across the rules of a ruleset
+    pub fn enable_common_subexpression_elimination(mut self, enable: bool) -> Self {
+        self.config.compiler_options.enable_common_subexpression_elimination = enable;
+        self
+    }
+
+    /// Enable cost-based reordering of the conditions of a rule
+    pub fn enable_condition_reordering(mut self, enable: bool) -> Self {
+        self.config.compiler_options.enable_condition_reordering = enable;
+        self
+    }
+
     /// Set feature executor for lazy feature calculation
     pub fn with_feature_executor(mut self, executor: Arc<FeatureExecutor>) -> Self {
         self.feature_executor = Some(executor);
         self
     }
 
+    /// Limit the time spent fetching the features of a ruleset step
+    ///
+    /// Applies to the feature executor initialized from the repository; an
+    /// executor set with `with_feature_executor` keeps its own limits.
+    pub fn with_feature_fetch_limits(mut self, limits: corint_runtime::feature::FetchLimits) -> Self {
+        self.feature_fetch_limits = limits;
+        self
+    }
+
     /// Set list service for list lookup operations
     pub fn with_list_service(mut self, service: Arc<corint_runtime::lists::ListService>) -> Self {
         self.list_service = Some(service);
         self
     }
 
+    /// Set the clock used to evaluate rule and ruleset effective windows
+    ///
+    /// Defaults to the system clock.
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = Some(clock);
+        self
+    }
+
+    /// Set the store used by `record_outcome` for decision outcome feedback
+    ///
+    /// Defaults to the decision database when one is configured.
+    pub fn with_outcome_store(mut self, store: Arc<dyn OutcomeStore>) -> Self {
+        self.outcome_store = Some(store);
+        self
+    }
+
+    /// Set the store champion/challenger experiment results are recorded to
+    ///
+    /// Defaults to the decision database when one is configured. Without a
+    /// store, challenger decisions are only logged.
+    pub fn with_experiment_store(mut self, store: Arc<dyn ExperimentStore>) -> Self {
+        self.experiment_store = Some(store);
+        self
+    }
+
+    /// Record each decided event to `storage`
+    ///
+    /// Features computed from the same storage then count the events this
+    /// engine processed without a separate ingestion pipeline.
+    pub fn with_event_storage(mut self, storage: Arc<dyn Storage>) -> Self {
+        self.event_storage = Some(storage);
+        self
+    }
+
+    /// Dispatch the side effects of emit steps with `dispatcher`
+    ///
+    /// Defaults to a dispatcher without sinks, which dead-letters every
+    /// emission.
+    pub fn with_emit_dispatcher(mut self, dispatcher: Arc<EmitDispatcher>) -> Self {
+        self.emit_dispatcher = Some(dispatcher);
+        self
+    }
+
+    /// Compile the pipeline steps of a custom type with `plugin`
+    ///
+    /// The plugin compiles them on every load, reloads and swaps included.
+    /// Steps usually compile to `CallCustom` instructions, run by the handler
+    /// registered under the same name with `with_custom_step_handler`.
+    pub fn with_step_plugin(mut self, plugin: Arc<dyn StepPlugin>) -> Self {
+        self.step_plugins.register(plugin);
+        self
+    }
+
+    /// Run the `CallCustom` instructions naming `name` with `handler`
+    pub fn with_custom_step_handler(
+        mut self,
+        name: impl Into<String>,
+        handler: Arc<dyn CustomStepHandler>,
+    ) -> Self {
+        self.custom_step_handlers.insert(name.into(), handler);
+        self
+    }
+
     /// Enable decision result persistence to database
+    ///
+    /// Outcome feedback and experiment results are stored in the same
+    /// database unless their own stores are set.
     #[cfg(feature = "sqlx")]
     pub fn with_result_writer(mut self, pool: sqlx::PgPool) -> Self {
-        use corint_runtime::DecisionResultWriter;
+        use corint_runtime::{DecisionResultWriter, PostgresExperimentStore, PostgresOutcomeStore};
         tracing::info!("Configuring DecisionResultWriter with database pool");
+        if self.outcome_store.is_none() {
+            self.outcome_store = Some(Arc::new(PostgresOutcomeStore::new(pool.clone())));
+        }
+        if self.experiment_store.is_none() {
+            self.experiment_store = Some(Arc::new(PostgresExperimentStore::new(pool.clone())));
+        }
         self.result_writer = Some(Arc::new(DecisionResultWriter::new(pool)));
         tracing::info!("DecisionResultWriter configured successfully");
         self
@@ -240,7 +540,10 @@ impl DecisionEngineBuilder {
     pub async fn build(mut self) -> Result<DecisionEngine> {
         // Load content from repository if configured
         if let Some(repo_config) = &self.repository_config {
-            let loader = RepositoryLoader::new(repo_config.clone());
+            let mut loader = RepositoryLoader::new(repo_config.clone());
+            if let Some(resolver) = &self.secret_resolver {
+                loader = loader.with_secret_resolver(Arc::clone(resolver));
+            }
             match loader.load_all().await {
                 Ok(content) => {
                     // Store content for auto-initialization
@@ -261,6 +564,7 @@ impl DecisionEngineBuilder {
         if self.feature_executor.is_none() {
             if let Some(ref content) = self.repository_content {
                 if let Some(executor) = Self::init_feature_executor_from_content(content, &self.repository_config, &self.server_datasources).await? {
+                    let executor = executor.with_fetch_limits(self.feature_fetch_limits);
                     self.feature_executor = Some(Arc::new(executor));
                     tracing::info!("✓ Auto-initialized FeatureExecutor from repository");
                 }
@@ -275,6 +579,8 @@ impl DecisionEngineBuilder {
                 #[cfg(not(feature = "sqlx"))]
                 let db_url: &Option<String> = &None;
                 if let Some(service) = Self::init_list_service_from_content(content, &self.repository_config, db_url).await? {
+                    // Expired entries never match; the sweep reclaims their storage
+                    service.spawn_expiry_sweep(LIST_EXPIRY_SWEEP_INTERVAL);
                     self.list_service = Some(Arc::new(service));
                     tracing::info!("✓ Auto-initialized ListService from repository");
                 }
@@ -292,7 +598,18 @@ impl DecisionEngineBuilder {
                         .await
                     {
                         Ok(pool) => {
-                            use corint_runtime::DecisionResultWriter;
+                            use corint_runtime::{
+                                DecisionResultWriter, PostgresExperimentStore,
+                                PostgresOutcomeStore,
+                            };
+                            if self.outcome_store.is_none() {
+                                self.outcome_store =
+                                    Some(Arc::new(PostgresOutcomeStore::new(pool.clone())));
+                            }
+                            if self.experiment_store.is_none() {
+                                self.experiment_store =
+                                    Some(Arc::new(PostgresExperimentStore::new(pool.clone())));
+                            }
                             self.result_writer = Some(Arc::new(DecisionResultWriter::new(pool)));
                             tracing::info!("✓ Auto-initialized ResultWriter from database_url");
                         }
@@ -310,22 +627,35 @@ impl DecisionEngineBuilder {
         let list_service = self.list_service.clone();
 
         #[cfg_attr(not(feature = "sqlx"), allow(unused_mut))]
-        let mut engine = DecisionEngine::new_with_feature_executor(
+        let mut engine = DecisionEngine::new_with_custom_steps(
             self.config,
             self.feature_executor,
             self.list_service,
+            self.step_plugins,
+            self.custom_step_handlers,
         )
         .await?;
 
         // Save reload information
         engine.repository_config = repository_config;
+        engine.secret_resolver = self.secret_resolver;
         engine.feature_executor = feature_executor;
         engine.list_service = list_service;
+        if let Some(clock) = self.clock {
+            engine.clock = clock;
+        }
+        engine.outcome_store = self.outcome_store;
+        engine.experiment_store = self.experiment_store;
+        engine.event_storage = self.event_storage;
+        if let Some(emit_dispatcher) = self.emit_dispatcher {
+            engine.emit_dispatcher = emit_dispatcher;
+        }
 
         // Set result writer if configured
         #[cfg(feature = "sqlx")]
         {
             if let Some(result_writer) = self.result_writer {
+                result_writer.set_masking_policy(engine.masking_policy.load_full());
                 engine.result_writer = Some(result_writer);
             }
         }
@@ -346,6 +676,21 @@ impl DecisionEngineBuilder {
             self.config.rule_contents.push((id, yaml));
         }
 
+        // Add reason code catalogs (validated against conclusions at compile time)
+        self.config
+            .reason_catalog_contents
+            .extend(content.reason_catalogs);
+
+        // Add PII masking policies (applied to persisted records and traces)
+        self.config
+            .masking_policy_contents
+            .extend(content.masking_policies);
+
+        // Add entity key definitions (shared by features and the engine)
+        self.config
+            .entity_definition_contents
+            .extend(content.entity_definitions);
+
         // Note: Rules, rulesets, and templates are typically:
         // 1. Included in pipeline YAML files via --- separators
         // 2. Referenced via `include` directives in pipelines
@@ -364,6 +709,14 @@ impl DecisionEngineBuilder {
         // initialized in build() method from repository content.
     }
 
+    /// Base path of a filesystem repository, holding its feature and datasource files
+    pub(crate) fn filesystem_base_path(config: &RepositoryConfig) -> Option<&str> {
+        match &config.source {
+            corint_repository::RepositorySource::FileSystem => config.base_path.as_deref(),
+            _ => None, // For non-filesystem repositories, we can't load feature files directly
+        }
+    }
+
     /// Initialize FeatureExecutor from repository content
     ///
     /// This function converts repository DataSourceConfig and FeatureDefinition
@@ -377,17 +730,7 @@ impl DecisionEngineBuilder {
         use corint_runtime::feature::registry::FeatureRegistry;
 
         // Get repository base path for loading feature files
-        let base_path = match repo_config {
-            Some(config) => {
-                match &config.source {
-                    corint_repository::RepositorySource::FileSystem => {
-                        config.base_path.as_ref().map(|p| p.as_str())
-                    }
-                    _ => None, // For non-filesystem repositories, we can't load feature files directly
-                }
-            }
-            None => None,
-        };
+        let base_path = repo_config.as_ref().and_then(Self::filesystem_base_path);
 
         // If we have a filesystem repository, load features from files (more reliable than converting)
         if let Some(base_path) = base_path {
@@ -397,7 +740,9 @@ impl DecisionEngineBuilder {
                 if let Err(e) = registry.load_from_directory(&feature_dir) {
                     tracing::warn!("Failed to load features from directory: {}", e);
                 } else {
-                    let mut executor = FeatureExecutor::new().with_stats();
+                    let entities = corint_runtime::EntityRegistry::from_documents(&content.entity_definitions)
+                        .map_err(|e| crate::error::SdkError::Config(format!("Failed to parse entity definitions: {}", e)))?;
+                    let mut executor = FeatureExecutor::new().with_stats().with_entities(entities);
 
                     let mut datasource_count = 0;
 
@@ -599,6 +944,8 @@ impl DecisionEngineBuilder {
                                                 corint_runtime::datasource::config::SQLProvider::SQLite => "sqlite",
                                                 corint_runtime::datasource::config::SQLProvider::PostgreSQL => "postgresql",
                                                 corint_runtime::datasource::config::SQLProvider::MySQL => "mysql",
+                                                corint_runtime::datasource::config::SQLProvider::BigQuery => "bigquery",
+                                                corint_runtime::datasource::config::SQLProvider::Snowflake => "snowflake",
                                             };
                                             (provider.to_string(), sql_config.connection_string.clone())
                                         }
@@ -682,9 +1029,29 @@ impl DecisionEngineBuilder {
                         let list_count = backends.len();
                         let list_ids: Vec<&str> = backends.keys().map(|s| s.as_str()).collect();
                         tracing::info!("✓ Loaded {} list(s): {:?}", list_count, list_ids);
-                        Ok(Some(corint_runtime::lists::ListService::new_with_backends(
-                            backends,
-                        )))
+                        let mut service = corint_runtime::lists::ListService::new_with_backends(backends);
+
+                        // Lists composed of the loaded lists
+                        match loader.load_compositions().await {
+                            Ok(compositions) if !compositions.is_empty() => {
+                                tracing::debug!("  ✓ Composed {} list(s)", compositions.len());
+                                service = service.with_compositions(compositions).await;
+                            }
+                            Ok(_) => {}
+                            Err(e) => tracing::warn!("Failed to load composed lists: {}", e),
+                        }
+
+                        // Keep lists with a sync section fresh from their external source
+                        match loader.load_syncs().await {
+                            Ok(syncs) => {
+                                for sync in syncs {
+                                    tracing::debug!("  ✓ Scheduled sync of list {}", sync.list_id());
+                                    service.spawn_sync(std::sync::Arc::new(sync));
+                                }
+                            }
+                            Err(e) => tracing::warn!("Failed to load list syncs: {}", e),
+                        }
+                        Ok(Some(service))
                     }
                 }
                 Err(e) => {
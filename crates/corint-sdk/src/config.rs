@@ -1,5 +1,8 @@
 //! Configuration types for DecisionEngine
 
+use corint_runtime::{
+    ExecutionLimits, NullSemantics, RecordReplayConfig, SamplingConfig, TenantQuotaConfig,
+};
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 
@@ -20,6 +23,18 @@ pub struct EngineConfig {
     #[serde(skip)]
     pub registry_content: Option<String>,
 
+    /// Reason code catalog contents (id, content)
+    #[serde(skip)]
+    pub reason_catalog_contents: Vec<(String, String)>,
+
+    /// PII masking policy contents (id, content)
+    #[serde(skip)]
+    pub masking_policy_contents: Vec<(String, String)>,
+
+    /// Entity key definition contents (id, content)
+    #[serde(skip)]
+    pub entity_definition_contents: Vec<(String, String)>,
+
     /// Storage configuration
     pub storage: Option<StorageConfig>,
 
@@ -37,6 +52,46 @@ pub struct EngineConfig {
 
     /// Compiler options
     pub compiler_options: CompilerOptions,
+
+    /// Sampling of persisted decisions for quality review
+    #[serde(default)]
+    pub sampling: SamplingConfig,
+
+    /// Recording or replay of external API and data source responses
+    #[serde(default)]
+    pub record_replay: RecordReplayConfig,
+
+    /// Append a record of every list hit to decision responses
+    #[serde(default)]
+    pub audit_list_hits: bool,
+
+    /// Time budget of a decision in milliseconds, after which it is made from partial results
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub decision_timeout_ms: Option<u64>,
+
+    /// Instruction and wall-clock limits after which a runaway program is aborted
+    #[serde(default)]
+    pub execution_limits: ExecutionLimits,
+
+    /// Capture the inputs each decision consumes, so that it can be replayed
+    #[serde(default)]
+    pub capture_inputs: bool,
+
+    /// Time instructions, rules and feature loads into the execution profile
+    #[serde(default)]
+    pub profiling: bool,
+
+    /// How comparisons and logical operators treat missing (`Null`) values
+    #[serde(default)]
+    pub null_semantics: NullSemantics,
+
+    /// Concurrency and rate quotas of the tenants sharing the engine
+    #[serde(default)]
+    pub tenant_quotas: TenantQuotaConfig,
+
+    /// Answer requests repeating a recent `event_id` with the decision already made
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub idempotency: Option<IdempotencyConfig>,
 }
 
 impl EngineConfig {
@@ -47,12 +102,25 @@ impl EngineConfig {
             rule_contents: Vec::new(),
             registry_file: None,
             registry_content: None,
+            reason_catalog_contents: Vec::new(),
+            masking_policy_contents: Vec::new(),
+            entity_definition_contents: Vec::new(),
             storage: None,
             llm: None,
             service: None,
             enable_metrics: true,
             enable_tracing: false,
             compiler_options: CompilerOptions::default(),
+            sampling: SamplingConfig::default(),
+            record_replay: RecordReplayConfig::default(),
+            audit_list_hits: false,
+            decision_timeout_ms: None,
+            execution_limits: ExecutionLimits::default(),
+            capture_inputs: false,
+            profiling: false,
+            null_semantics: NullSemantics::default(),
+            tenant_quotas: TenantQuotaConfig::default(),
+            idempotency: None,
         }
     }
 
@@ -97,6 +165,66 @@ impl EngineConfig {
         self.enable_tracing = enable;
         self
     }
+
+    /// Set the sampling configuration for quality review
+    pub fn with_sampling(mut self, sampling: SamplingConfig) -> Self {
+        self.sampling = sampling;
+        self
+    }
+
+    /// Set the record/replay configuration for external dependencies
+    pub fn with_record_replay(mut self, record_replay: RecordReplayConfig) -> Self {
+        self.record_replay = record_replay;
+        self
+    }
+
+    /// Enable auditing of list hits
+    pub fn with_list_hit_audit(mut self, enable: bool) -> Self {
+        self.audit_list_hits = enable;
+        self
+    }
+
+    /// Set the time budget of a decision
+    pub fn with_decision_timeout(mut self, timeout: std::time::Duration) -> Self {
+        self.decision_timeout_ms = Some(timeout.as_millis() as u64);
+        self
+    }
+
+    /// Set the execution limits of programs
+    pub fn with_execution_limits(mut self, limits: ExecutionLimits) -> Self {
+        self.execution_limits = limits;
+        self
+    }
+
+    /// Enable capturing the inputs of decisions
+    pub fn with_input_capture(mut self, enable: bool) -> Self {
+        self.capture_inputs = enable;
+        self
+    }
+
+    /// Enable execution profiling
+    pub fn with_profiling(mut self, enable: bool) -> Self {
+        self.profiling = enable;
+        self
+    }
+
+    /// Set how comparisons and logical operators treat `Null`
+    pub fn with_null_semantics(mut self, semantics: NullSemantics) -> Self {
+        self.null_semantics = semantics;
+        self
+    }
+
+    /// Set the quotas of tenants
+    pub fn with_tenant_quotas(mut self, quotas: TenantQuotaConfig) -> Self {
+        self.tenant_quotas = quotas;
+        self
+    }
+
+    /// Enable the idempotency cache of decisions
+    pub fn with_idempotency(mut self, idempotency: IdempotencyConfig) -> Self {
+        self.idempotency = Some(idempotency);
+        self
+    }
 }
 
 impl Default for EngineConfig {
@@ -185,6 +313,30 @@ pub struct CompilerOptions {
 
     /// Enable dead code elimination
     pub enable_dead_code_elimination: bool,
+
+    /// Compute conditions repeated across the rules of a ruleset once per request
+    #[serde(default = "default_common_subexpression_elimination")]
+    pub enable_common_subexpression_elimination: bool,
+
+    /// Evaluate the cheap conditions of a rule before the expensive ones
+    #[serde(default = "default_condition_reordering")]
+    pub enable_condition_reordering: bool,
+
+    /// Directory `import:` paths are resolved against
+    #[serde(default = "default_library_base_path")]
+    pub library_base_path: String,
+}
+
+fn default_common_subexpression_elimination() -> bool {
+    true
+}
+
+fn default_condition_reordering() -> bool {
+    true
+}
+
+fn default_library_base_path() -> String {
+    "repository".to_string()
 }
 
 impl Default for CompilerOptions {
@@ -193,6 +345,54 @@ impl Default for CompilerOptions {
             enable_semantic_analysis: true,
             enable_constant_folding: true,
             enable_dead_code_elimination: true,
+            enable_common_subexpression_elimination: default_common_subexpression_elimination(),
+            enable_condition_reordering: default_condition_reordering(),
+            library_base_path: default_library_base_path(),
+        }
+    }
+}
+
+/// Idempotency cache configuration
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IdempotencyConfig {
+    /// How long a decision is returned for repeats of its event, in seconds (default: 300)
+    #[serde(default = "default_idempotency_ttl_secs")]
+    pub ttl_secs: u64,
+
+    /// Maximum number of decisions kept (default: 10000)
+    #[serde(default = "default_idempotency_max_entries")]
+    pub max_entries: usize,
+}
+
+fn default_idempotency_ttl_secs() -> u64 {
+    300
+}
+
+fn default_idempotency_max_entries() -> usize {
+    10_000
+}
+
+impl IdempotencyConfig {
+    /// Keep decisions for `ttl`
+    pub fn new(ttl: std::time::Duration) -> Self {
+        Self {
+            ttl_secs: ttl.as_secs(),
+            max_entries: default_idempotency_max_entries(),
+        }
+    }
+
+    /// Set the maximum number of decisions kept
+    pub fn with_max_entries(mut self, max_entries: usize) -> Self {
+        self.max_entries = max_entries;
+        self
+    }
+}
+
+impl Default for IdempotencyConfig {
+    fn default() -> Self {
+        Self {
+            ttl_secs: default_idempotency_ttl_secs(),
+            max_entries: default_idempotency_max_entries(),
         }
     }
 }
@@ -354,6 +554,8 @@ mod tests {
         assert!(options.enable_semantic_analysis);
         assert!(options.enable_constant_folding);
         assert!(options.enable_dead_code_elimination);
+        assert!(options.enable_common_subexpression_elimination);
+        assert!(options.enable_condition_reordering);
     }
 
     #[test]
@@ -362,11 +564,15 @@ mod tests {
             enable_semantic_analysis: false,
             enable_constant_folding: true,
             enable_dead_code_elimination: false,
+            enable_common_subexpression_elimination: false,
+            enable_condition_reordering: false,
+            library_base_path: "rules".to_string(),
         };
 
         assert!(!options.enable_semantic_analysis);
         assert!(options.enable_constant_folding);
         assert!(!options.enable_dead_code_elimination);
+        assert_eq!(options.library_base_path, "rules");
     }
 
     #[test]
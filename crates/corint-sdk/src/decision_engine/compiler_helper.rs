@@ -1,10 +1,19 @@
 //! Rule compilation and loading utilities
 
+use crate::config::CompilerOptions;
 use crate::error::{Result, SdkError};
-use corint_compiler::Compiler;
+use corint_compiler::{
+    CompilationCache, CompilationCacheStats, Compiler, CompilerOptions as CompilerOpts, StepPlugins,
+};
 use corint_core::ir::Program;
-use corint_parser::{PipelineParser, RegistryParser, RuleParser, RulesetParser};
+use corint_core::ast::ReasonCatalog;
+use corint_parser::{
+    DecisionTableParser, PipelineParser, ReasonCatalogParser, RegistryParser, RuleParser,
+    RulesetParser,
+};
+use corint_runtime::{EntityRegistry, MaskingPolicy};
 use std::path::Path;
+use std::sync::Arc;
 
 pub(super) struct CompilerHelper;
 
@@ -28,14 +37,24 @@ pub(super) async fn load_and_compile_rules(path: &Path, compiler: &mut Compiler)
     for doc in documents.iter() {
         // Try rule first
         if let Ok(rule) = RuleParser::parse_from_yaml(doc) {
-            let prog = compiler.compile_rule(&rule)?;
+            let mut prog = compiler.compile_rule(&rule)?;
+            Compiler::locate_source(&mut prog, &path.display().to_string(), &content);
             programs.push(prog);
             continue;
         }
 
         // Try ruleset
         if let Ok(ruleset) = RulesetParser::parse_from_yaml(doc) {
-            let prog = compiler.compile_ruleset(&ruleset)?;
+            let mut prog = compiler.compile_ruleset(&ruleset)?;
+            Compiler::locate_source(&mut prog, &path.display().to_string(), &content);
+            programs.push(prog);
+            continue;
+        }
+
+        // Try decision table
+        if let Ok(table) = DecisionTableParser::parse_from_yaml(doc) {
+            let mut prog = compiler.compile_decision_table(&table)?;
+            Compiler::locate_source(&mut prog, &path.display().to_string(), &content);
             programs.push(prog);
             continue;
         }
@@ -138,15 +157,20 @@ pub(super) async fn compile_rules_from_content(
             resolved.rulesets.len()
         );
 
+        // Type check the pipeline and its rules against its event schema
+        compiler.check_types(&document.definition, &resolved.rules)?;
+
         // Compile all resolved rules first
         for rule in &resolved.rules {
-            let rule_prog = compiler.compile_rule(rule)?;
+            let mut rule_prog = compiler.compile_rule(rule)?;
+            compiler.locate_imported_source(&mut rule_prog);
             programs.push(rule_prog);
         }
 
         // Compile all resolved rulesets
         for ruleset in &resolved.rulesets {
-            let ruleset_prog = compiler.compile_ruleset(ruleset)?;
+            let mut ruleset_prog = compiler.compile_ruleset(ruleset)?;
+            compiler.locate_imported_source(&mut ruleset_prog);
             programs.push(ruleset_prog);
         }
 
@@ -183,7 +207,8 @@ pub(super) async fn compile_rules_from_content(
                 // Try to parse as rule
                 if let Ok(rule) = RuleParser::parse_from_yaml(doc) {
                     tracing::debug!("Found inline rule: {}", rule.id);
-                    let rule_prog = compiler.compile_rule(&rule)?;
+                    let mut rule_prog = compiler.compile_rule(&rule)?;
+                    Compiler::locate_source(&mut rule_prog, id, content);
                     programs.push(rule_prog);
                     continue;
                 }
@@ -191,10 +216,20 @@ pub(super) async fn compile_rules_from_content(
                 // Try to parse as ruleset
                 if let Ok(ruleset) = RulesetParser::parse_from_yaml(doc) {
                     tracing::debug!("Found inline ruleset: {}", ruleset.id);
-                    let ruleset_prog = compiler.compile_ruleset(&ruleset)?;
+                    let mut ruleset_prog = compiler.compile_ruleset(&ruleset)?;
+                    Compiler::locate_source(&mut ruleset_prog, id, content);
                     programs.push(ruleset_prog);
                     continue;
                 }
+
+                // Try to parse as decision table
+                if let Ok(table) = DecisionTableParser::parse_from_yaml(doc) {
+                    tracing::debug!("Found inline decision table: {}", table.id);
+                    let mut table_prog = compiler.compile_decision_table(&table)?;
+                    Compiler::locate_source(&mut table_prog, id, content);
+                    programs.push(table_prog);
+                    continue;
+                }
             }
         }
     } else {
@@ -205,14 +240,24 @@ pub(super) async fn compile_rules_from_content(
         for doc in documents.iter() {
             // Try rule first
             if let Ok(rule) = RuleParser::parse_from_yaml(doc) {
-                let prog = compiler.compile_rule(&rule)?;
+                let mut prog = compiler.compile_rule(&rule)?;
+                Compiler::locate_source(&mut prog, id, content);
                 programs.push(prog);
                 continue;
             }
 
             // Try ruleset
             if let Ok(ruleset) = RulesetParser::parse_from_yaml(doc) {
-                let prog = compiler.compile_ruleset(&ruleset)?;
+                let mut prog = compiler.compile_ruleset(&ruleset)?;
+                Compiler::locate_source(&mut prog, id, content);
+                programs.push(prog);
+                continue;
+            }
+
+            // Try decision table
+            if let Ok(table) = DecisionTableParser::parse_from_yaml(doc) {
+                let mut prog = compiler.compile_decision_table(&table)?;
+                Compiler::locate_source(&mut prog, id, content);
                 programs.push(prog);
                 continue;
             }
@@ -280,4 +325,85 @@ pub(super) async fn load_registry(path: &Path) -> Result<corint_core::ast::Pipel
     Ok(registry)
 }
 
+/// Parse and merge PII masking policies
+pub(super) fn load_masking_policy(contents: &[(String, String)]) -> Result<MaskingPolicy> {
+    let mut policy = MaskingPolicy::new();
+    for (id, content) in contents {
+        let parsed = MaskingPolicy::from_yaml(content).map_err(|e| {
+            SdkError::Config(format!("Failed to parse masking policy '{}': {}", id, e))
+        })?;
+        tracing::info!(
+            "✓ Loaded masking policy '{}' (version {}): {} rules",
+            id,
+            parsed.version.as_deref().unwrap_or("unversioned"),
+            parsed.rules.len()
+        );
+        policy.merge(parsed);
+    }
+    Ok(policy)
+}
+
+/// Parse and merge entity key definitions
+pub(super) fn load_entities(contents: &[(String, String)]) -> Result<EntityRegistry> {
+    let entities = EntityRegistry::from_documents(contents)
+        .map_err(|e| SdkError::Config(format!("Failed to parse entity definitions: {}", e)))?;
+    if !entities.is_empty() {
+        tracing::info!("✓ Loaded {} entity definitions", entities.len());
+    }
+    Ok(entities)
+}
+
+/// Compiler of the engine's definitions, configured by `options`
+///
+/// Programs of unchanged definitions are reused from `cache`, conclusion
+/// reason codes are validated against `reason_catalog`, and steps of custom
+/// types are compiled by `step_plugins`.
+pub(super) fn create_compiler(
+    options: &CompilerOptions,
+    cache: &Arc<CompilationCache>,
+    reason_catalog: &ReasonCatalog,
+    step_plugins: &StepPlugins,
+) -> Compiler {
+    let mut compiler = Compiler::with_options(CompilerOpts {
+        enable_semantic_analysis: options.enable_semantic_analysis,
+        enable_constant_folding: options.enable_constant_folding,
+        enable_dead_code_elimination: true, // FIXED: Bug with default actions resolved - now uses proper CFG analysis
+        enable_common_subexpression_elimination: options.enable_common_subexpression_elimination,
+        enable_condition_reordering: options.enable_condition_reordering,
+        library_base_path: options.library_base_path.clone(),
+    });
+    compiler.set_cache(Arc::clone(cache));
+    compiler.set_reason_catalog(reason_catalog.clone());
+    compiler.set_step_plugins(step_plugins.clone());
+    compiler
+}
+
+/// Drop the cached programs of removed or changed definitions after a load
+///
+/// `before` are the cache statistics from before the load started.
+pub(super) fn sweep_compilation_cache(cache: &CompilationCache, before: CompilationCacheStats) {
+    let dropped = cache.sweep();
+    let after = cache.stats();
+    let compiled = after.misses - before.misses;
+    tracing::info!(
+        "✓ Compiled {} of {} definitions, reused the others unchanged ({} dropped)",
+        compiled,
+        compiled + after.hits - before.hits,
+        dropped
+    );
+}
+
+/// Parse and merge reason code catalogs
+pub(super) fn load_reason_catalog(contents: &[(String, String)]) -> Result<ReasonCatalog> {
+    let mut catalog = ReasonCatalog::new();
+    for (id, content) in contents {
+        let parsed = ReasonCatalogParser::parse(content).map_err(|e| {
+            SdkError::Config(format!("Failed to parse reason code catalog '{}': {}", id, e))
+        })?;
+        tracing::info!("✓ Loaded reason code catalog '{}': {} codes", id, parsed.codes.len());
+        catalog.merge(parsed);
+    }
+    Ok(catalog)
+}
+
 }
@@ -4,54 +4,90 @@ use super::when_evaluator::WhenEvaluator;
 use super::trace_builder::TraceBuilder;
 use super::compiler_helper::CompilerHelper;
 
+use super::program_set::ProgramSet;
+use super::idempotency::IdempotencyCache;
 use super::types::{DecisionRequest, DecisionResponse};
 use crate::config::EngineConfig;
 use crate::error::{Result, SdkError};
-use corint_compiler::{Compiler, CompilerOptions as CompilerOpts};
-use corint_core::ast::{PipelineRegistry, Signal};
+use arc_swap::{ArcSwap, ArcSwapOption};
+use corint_compiler::{CompilationCache, CompilationCacheStats, StepPlugins};
+use corint_core::ast::{
+    Experiment, PipelineRegistry, ReasonCatalog, RegistryEntry, ScheduleEntry, Signal, WhenBlock,
+};
 use corint_core::ir::Program;
 use corint_core::Value;
 use corint_parser::RegistryParser;
 use corint_runtime::{
-    ApiConfig, ConditionTrace, DecisionResult, ExecutionTrace,
-    ExternalApiClient, MetricsCollector, PipelineExecutor, PipelineTrace, RuleTrace, RulesetTrace,
+    ApiConfig, CapturedInputs, Clock, ConditionTrace, CustomStepHandler, DecisionResult, EffectiveStatus,
+    EmitDispatcher, Emission, EntityRegistry, ErrorTrace, ExecutionBudget, ExecutionTrace, ExperimentRecord, ExperimentStore,
+    ExperimentSummary, ExternalApiClient, FeatureTrace, InputCapture, ListLookupScope, ListTrace,
+    MaskingPolicy, Metrics, MetricsCollector, OutcomeLabel, OutcomeRecord, OutcomeStore,
+    PipelineExecutor, PipelineTrace, ProfileReport, QueuedEmit, RecordReplayMode, Recording, ReplaySession, RequestMemo, RuleTrace,
+    RuleExecutionRecord, RulesetTrace, ScheduleRunSummary, Storage, SystemClock, TenantQuotas, TenantStats,
 };
 use std::collections::HashMap;
 use std::path::Path;
 use std::sync::Arc;
 
 pub struct DecisionEngine {
-    /// Compiled programs (one per rule/ruleset/pipeline)
-    programs: Vec<Program>,
+    /// Compiled programs, swapped atomically on reload
+    programs: ArcSwap<ProgramSet>,
 
-    /// Mapping of ruleset ID to compiled program (for pipeline routing)
-    ruleset_map: HashMap<String, Program>,
+    /// Optional pipeline registry for event routing
+    registry: ArcSwapOption<PipelineRegistry>,
 
-    /// Mapping of rule ID to compiled program
-    rule_map: HashMap<String, Program>,
+    /// Reason code catalog for resolving localized decision reasons
+    reason_catalog: ArcSwap<ReasonCatalog>,
 
-    /// Mapping of pipeline ID to compiled program
-    pipeline_map: HashMap<String, Program>,
+    /// Programs of the loaded definitions, reused by reloads for unchanged ones
+    compile_cache: Arc<CompilationCache>,
 
-    /// Optional pipeline registry for event routing
-    registry: Option<PipelineRegistry>,
+    /// Compiler plugins for custom step types, used by every load
+    step_plugins: StepPlugins,
+
+    /// PII masking policy applied to persisted records and returned traces
+    pub(crate) masking_policy: ArcSwap<MaskingPolicy>,
+
+    /// Entity key definitions shared with feature computation
+    entities: ArcSwap<EntityRegistry>,
 
     /// Pipeline executor
     executor: Arc<PipelineExecutor>,
 
+    /// Decisions returned again for retried events, if enabled
+    idempotency: Option<IdempotencyCache>,
+
     /// Metrics collector
     metrics: Arc<MetricsCollector>,
 
+    /// Clock used to evaluate rule and ruleset effective windows
+    pub(crate) clock: Arc<dyn Clock>,
+
     /// Configuration
     config: EngineConfig,
 
     /// Optional decision result writer for persisting decision results
     pub(crate) result_writer: Option<Arc<corint_runtime::DecisionResultWriter>>,
 
+    /// Optional store for outcome feedback on past decisions
+    pub(crate) outcome_store: Option<Arc<dyn OutcomeStore>>,
+
+    /// Optional store champion/challenger experiment results are recorded to
+    pub(crate) experiment_store: Option<Arc<dyn ExperimentStore>>,
+
+    /// Optional storage each decided event is recorded to
+    pub(crate) event_storage: Option<Arc<dyn Storage>>,
+
+    /// Dispatcher of the side effects of emit steps
+    pub(crate) emit_dispatcher: Arc<EmitDispatcher>,
+
     // Reload support: save builder state for reloading
     /// Repository configuration (if any) for reloading
     pub(crate) repository_config: Option<corint_repository::RepositoryConfig>,
 
+    /// Secret resolver for repository placeholders (for reload)
+    pub(crate) secret_resolver: Option<Arc<dyn corint_repository::SecretResolver>>,
+
     /// Feature executor (for reload)
     pub(crate) feature_executor: Option<Arc<corint_runtime::feature::FeatureExecutor>>,
 
@@ -90,18 +126,44 @@ impl DecisionEngine {
         config: EngineConfig,
         feature_executor: Option<Arc<corint_runtime::feature::FeatureExecutor>>,
         list_service: Option<Arc<corint_runtime::lists::ListService>>,
+    ) -> Result<Self> {
+        Self::new_with_custom_steps(
+            config,
+            feature_executor,
+            list_service,
+            StepPlugins::new(),
+            HashMap::new(),
+        )
+        .await
+    }
+
+    /// Create a new decision engine whose pipelines may have steps of custom types
+    ///
+    /// `step_plugins` compile the steps, and `custom_handlers` run the
+    /// `CallCustom` instructions they compile to, by handler name.
+    pub(crate) async fn new_with_custom_steps(
+        config: EngineConfig,
+        feature_executor: Option<Arc<corint_runtime::feature::FeatureExecutor>>,
+        list_service: Option<Arc<corint_runtime::lists::ListService>>,
+        step_plugins: StepPlugins,
+        custom_handlers: HashMap<String, Arc<dyn CustomStepHandler>>,
     ) -> Result<Self> {
         let mut programs = Vec::new();
 
-        // Compile all rule files
-        let compiler_opts = CompilerOpts {
-            enable_semantic_analysis: config.compiler_options.enable_semantic_analysis,
-            enable_constant_folding: config.compiler_options.enable_constant_folding,
-            enable_dead_code_elimination: true, // FIXED: Bug with default actions resolved - now uses proper CFG analysis
-            library_base_path: "repository".to_string(),
-        };
+        // Load reason code catalog so conclusion reason codes are validated during compilation
+        let reason_catalog = CompilerHelper::load_reason_catalog(&config.reason_catalog_contents)?;
+        let compile_cache = Arc::new(CompilationCache::new());
+        let mut compiler = CompilerHelper::create_compiler(
+            &config.compiler_options,
+            &compile_cache,
+            &reason_catalog,
+            &step_plugins,
+        );
+
+        let masking_policy =
+            Arc::new(CompilerHelper::load_masking_policy(&config.masking_policy_contents)?);
 
-        let mut compiler = Compiler::with_options(compiler_opts);
+        let entities = Arc::new(CompilerHelper::load_entities(&config.entity_definition_contents)?);
 
         // Compile rule files
         for rule_file in &config.rule_files {
@@ -112,25 +174,8 @@ impl DecisionEngine {
         for (id, content) in &config.rule_contents {
             programs.extend(CompilerHelper::compile_rules_from_content(id, content, &mut compiler).await?);
         }
-
-        // Build ruleset_map, rule_map, and pipeline_map for routing
-        let mut ruleset_map = HashMap::new();
-        let mut rule_map = HashMap::new();
-        let mut pipeline_map = HashMap::new();
-        for program in &programs {
-            match program.metadata.source_type.as_str() {
-                "ruleset" => {
-                    ruleset_map.insert(program.metadata.source_id.clone(), program.clone());
-                }
-                "rule" => {
-                    rule_map.insert(program.metadata.source_id.clone(), program.clone());
-                }
-                "pipeline" => {
-                    pipeline_map.insert(program.metadata.source_id.clone(), program.clone());
-                }
-                _ => {}
-            }
-        }
+        compiler.eliminate_common_subexpressions(&mut programs);
+        compile_cache.sweep();
 
         // Load optional registry file
         let registry = if let Some(registry_content) = &config.registry_content {
@@ -209,8 +254,11 @@ impl DecisionEngine {
         }
 
         // Create executor with API client
-        let mut pipeline_executor =
-            PipelineExecutor::new().with_external_api_client(Arc::new(api_client));
+        let mut pipeline_executor = PipelineExecutor::new()
+            .with_external_api_client(Arc::new(api_client))
+            .with_execution_limits(config.execution_limits.clone())
+            .with_profiling(config.profiling)
+            .with_null_semantics(config.null_semantics);
 
         // Clone feature_executor and list_service before using them (they will be moved)
         let feature_executor_clone = feature_executor.clone();
@@ -226,26 +274,617 @@ impl DecisionEngine {
             pipeline_executor = pipeline_executor.with_list_service(list_service);
         }
 
-        let executor = Arc::new(pipeline_executor);
+        for (name, handler) in custom_handlers {
+            pipeline_executor = pipeline_executor.with_custom_handler(name, handler);
+        }
+
+        let executor = Arc::new(
+            pipeline_executor
+                .with_tenant_quotas(Arc::new(TenantQuotas::new(config.tenant_quotas.clone()))),
+        );
         let metrics = executor.metrics();
 
         Ok(Self {
-            programs,
-            ruleset_map,
-            rule_map,
-            pipeline_map,
-            registry,
+            programs: ArcSwap::from_pointee(ProgramSet::new(programs)),
+            registry: ArcSwapOption::from_pointee(registry),
+            reason_catalog: ArcSwap::from_pointee(reason_catalog),
+            compile_cache,
+            step_plugins,
+            masking_policy: ArcSwap::new(masking_policy),
+            entities: ArcSwap::new(entities),
             executor,
+            idempotency: config.idempotency.map(IdempotencyCache::new),
             metrics,
+            clock: Arc::new(SystemClock),
             config,
             result_writer: None,
+            outcome_store: None,
+            experiment_store: None,
+            event_storage: None,
+            emit_dispatcher: Arc::new(EmitDispatcher::new()),
             repository_config: None,
+            secret_resolver: None,
             feature_executor: feature_executor_clone,
             list_service: list_service_clone,
         })
     }
 
-    pub async fn decide(&self, mut request: DecisionRequest) -> Result<DecisionResponse> {
+    /// Record a rule outside its effective window as skipped
+    ///
+    /// Returns `true` when the rule must not be executed.
+    fn skip_inactive_rule(
+        &self,
+        rule_program: &Program,
+        ruleset_id: Option<&str>,
+        request_id: &str,
+        now: chrono::DateTime<chrono::Utc>,
+        rule_executions: &mut Vec<RuleExecutionRecord>,
+    ) -> bool {
+        let Some(reason) = EffectiveStatus::of(&rule_program.metadata, now).skip_reason() else {
+            return false;
+        };
+
+        tracing::info!(
+            "Skipping rule {} ({})",
+            rule_program.metadata.source_id,
+            reason
+        );
+        rule_executions.push(TraceBuilder::create_skipped_rule_record(
+            request_id,
+            ruleset_id,
+            &rule_program.metadata.source_id,
+            rule_program.metadata.name.as_deref(),
+            reason,
+        ));
+        true
+    }
+
+    /// Whether a ruleset may stop before executing all of its rules
+    fn stops_early(ruleset_program: &Program) -> bool {
+        let custom = &ruleset_program.metadata.custom;
+        custom.contains_key("stop_on_score") || custom.contains_key("stop_on_signal")
+    }
+
+    /// Whether the rules executed so far settle a ruleset's outcome
+    ///
+    /// The ruleset's conclusion is evaluated on a copy of the result: the
+    /// outcome is settled once its total score reaches `stop_on_score` or it
+    /// concludes with `stop_on_signal`.
+    async fn ruleset_settled(
+        &self,
+        ruleset_program: &Program,
+        request: &DecisionRequest,
+        execution_result: &corint_runtime::result::ExecutionResult,
+    ) -> Result<bool> {
+        let custom = &ruleset_program.metadata.custom;
+        let stop_on_score = custom
+            .get("stop_on_score")
+            .and_then(|score| score.parse::<i32>().ok());
+        let stop_on_signal = custom
+            .get("stop_on_signal")
+            .and_then(|signal| serde_json::from_str::<Signal>(signal).ok());
+        if stop_on_score.is_none() && stop_on_signal.is_none() {
+            return Ok(false);
+        }
+
+        let outcome = self
+            .executor
+            .execute_with_result(
+                ruleset_program,
+                request.to_context_input(),
+                execution_result.clone(),
+            )
+            .await?;
+        Ok(stop_on_score.is_some_and(|score| outcome.score >= score)
+            || (stop_on_signal.is_some() && outcome.signal == stop_on_signal))
+    }
+
+    /// Fetch the features loaded by a step's programs concurrently
+    ///
+    /// Features already in the request, or computed earlier in it, are
+    /// skipped. The fetched values, `Null` for features that failed or timed
+    /// out, are added to the request so the programs find them instead of
+    /// fetching them one at a time. A missing feature that fails closed fails
+    /// the decision.
+    async fn prefetch_features(
+        &self,
+        programs: &[&Program],
+        request: &mut DecisionRequest,
+        feature_traces: &mut Vec<FeatureTrace>,
+    ) -> Result<()> {
+        let Some(ref feature_executor) = self.feature_executor else {
+            return Ok(());
+        };
+        // Replayed decisions answer features from their captured values
+        if corint_runtime::decision_replay::current_capture().is_some_and(|c| c.is_pinned()) {
+            return Ok(());
+        }
+
+        let provided = request.features.get_or_insert_with(HashMap::new);
+        let memo = corint_runtime::memo::current_memo();
+        let mut names: Vec<String> = Vec::new();
+        for name in programs.iter().flat_map(|program| program.feature_references()) {
+            let memoized = memo.as_ref().is_some_and(|memo| {
+                memo.contains(&corint_runtime::MemoKey::Feature(name.clone()))
+            });
+            if !provided.contains_key(&name)
+                && !memoized
+                && feature_executor.has_feature(&name)
+                && !names.contains(&name)
+            {
+                names.push(name);
+            }
+        }
+        if names.is_empty() {
+            return Ok(());
+        }
+
+        let fetches = match corint_runtime::ExecutionContext::new(request.to_context_input()) {
+            Ok(ctx) => feature_executor.fetch_features(&names, &ctx).await,
+            Err(e) => Err(e.into()),
+        };
+        match fetches {
+            Ok(fetches) => {
+                let features = request.features.get_or_insert_with(HashMap::new);
+                for fetch in fetches {
+                    feature_traces.push(FeatureTrace::from(&fetch));
+                    if names.contains(&fetch.feature) {
+                        features.insert(fetch.feature, fetch.value);
+                    }
+                }
+            }
+            Err(e) if e.is::<corint_runtime::feature::MissingFeatureError>() => {
+                let missing = e
+                    .downcast::<corint_runtime::feature::MissingFeatureError>()
+                    .expect("checked by the guard");
+                return Err(SdkError::RuntimeError(
+                    corint_runtime::RuntimeError::MissingField {
+                        path: missing.feature,
+                    },
+                ));
+            }
+            Err(e) => tracing::warn!("Failed to prefetch features {:?}: {}", names, e),
+        }
+        Ok(())
+    }
+
+    /// Record every rule of a ruleset outside its effective window as skipped
+    ///
+    /// Returns `true` when neither the rules nor the conclusion must be executed.
+    fn skip_inactive_ruleset(
+        programs: &ProgramSet,
+        ruleset_program: &Program,
+        request_id: &str,
+        now: chrono::DateTime<chrono::Utc>,
+        rule_executions: &mut Vec<RuleExecutionRecord>,
+    ) -> bool {
+        let Some(reason) = EffectiveStatus::of(&ruleset_program.metadata, now).skip_reason()
+        else {
+            return false;
+        };
+
+        let ruleset_id = ruleset_program.metadata.source_id.as_str();
+        tracing::info!("Skipping ruleset {} ({})", ruleset_id, reason);
+
+        let mut seen = std::collections::HashSet::new();
+        let rule_ids: Vec<&str> = ruleset_program
+            .metadata
+            .custom
+            .get("rules")
+            .map(|rules| rules.split(',').filter(|rid| seen.insert(*rid)).collect())
+            .unwrap_or_default();
+        for rule_id in rule_ids {
+            let rule_name = programs
+                .rule_map
+                .get(rule_id)
+                .and_then(|program| program.metadata.name.as_deref());
+            rule_executions.push(TraceBuilder::create_skipped_rule_record(
+                request_id,
+                Some(ruleset_id),
+                rule_id,
+                rule_name,
+                reason,
+            ));
+        }
+        true
+    }
+
+    /// Make a decision for a request
+    ///
+    /// When record/replay is configured, the external API and data source
+    /// responses of the request are recorded to, or replayed from, the
+    /// recording of its `request_id`.
+    ///
+    /// A request with a tenant is admitted under the tenant's quota first,
+    /// and fails with `ConcurrencyLimitReached` or `RateLimited` when the
+    /// tenant is over it.
+    ///
+    /// With the idempotency cache enabled, a request repeating the `event_id`
+    /// metadata of a request decided within the TTL is answered with the
+    /// response made then, without executing anything.
+    pub async fn decide(&self, request: DecisionRequest) -> Result<DecisionResponse> {
+        let cached = self
+            .idempotency
+            .as_ref()
+            .and_then(|cache| Some((cache, IdempotencyCache::key(&request)?)));
+        let Some((cache, key)) = cached else {
+            return self.decide_for_tenant(request).await;
+        };
+
+        let (response, replayed) = cache
+            .get_or_decide(key, || self.decide_for_tenant(request))
+            .await?;
+        if replayed {
+            tracing::debug!(
+                "Returning the decision already made for request_id {}",
+                response.request_id
+            );
+            self.metrics.counter("idempotent_replays_total").inc();
+        }
+        Ok(response)
+    }
+
+    /// Make a decision under the quota of the request's tenant, if it has one
+    async fn decide_for_tenant(&self, request: DecisionRequest) -> Result<DecisionResponse> {
+        match request.tenant.clone() {
+            Some(tenant) => {
+                let permit = self.executor.admit_tenant(&tenant).await?;
+                permit.scope(self.decide_admitted(request)).await
+            }
+            None => self.decide_admitted(request).await,
+        }
+    }
+
+    /// Make a decision for a request admitted under its tenant's quota
+    async fn decide_admitted(&self, mut request: DecisionRequest) -> Result<DecisionResponse> {
+        let record_replay = &self.config.record_replay;
+        if !record_replay.is_enabled() {
+            return self.decide_request(request).await;
+        }
+
+        let request_id = request
+            .metadata
+            .entry("request_id".to_string())
+            .or_insert_with(Self::generate_request_id)
+            .clone();
+        let path = record_replay.recording_path(&request_id);
+
+        let session = Arc::new(match record_replay.mode {
+            RecordReplayMode::Replay => ReplaySession::replay(Recording::load(&path)?),
+            _ => ReplaySession::record(request_id.clone()),
+        });
+        let response = Arc::clone(&session)
+            .scope(self.decide_request(request))
+            .await;
+
+        match session.mode() {
+            RecordReplayMode::Replay if session.unused_calls() > 0 => {
+                tracing::warn!(
+                    "Replay of request {} left {} recorded calls unused",
+                    request_id,
+                    session.unused_calls()
+                );
+            }
+            RecordReplayMode::Record => {
+                // A failed write must not fail the decision itself
+                if let Err(e) = session.recording().save(&path) {
+                    tracing::warn!("Failed to save recording of request {}: {}", request_id, e);
+                }
+            }
+            _ => {}
+        }
+
+        response
+    }
+
+    /// Make a decision, also evaluating the challenger of a running experiment
+    ///
+    /// Requests sent to a challenger are decided by both pipelines
+    /// concurrently. The champion's decision is returned and persisted; the
+    /// challenger's is only recorded to the experiment store for comparison.
+    async fn decide_request(&self, mut request: DecisionRequest) -> Result<DecisionResponse> {
+        let Some((experiment, challenger_request)) = self.challenger_request(&mut request) else {
+            return self.decide_scoped(request, false).await;
+        };
+
+        // Boxed: two evaluations side by side are too large for the stack
+        let (champion, challenger) = tokio::join!(
+            Box::pin(self.decide_scoped(request, false)),
+            Box::pin(self.decide_scoped(challenger_request, true))
+        );
+        let champion = champion?;
+
+        // A failing challenger must not affect the decision returned
+        match challenger {
+            Ok(challenger) => self.record_experiment(&experiment, &champion, &challenger).await,
+            Err(e) => tracing::warn!(
+                "Challenger '{}' of experiment '{}' failed on request {}: {}",
+                experiment.challenger,
+                experiment.id,
+                champion.request_id,
+                e
+            ),
+        }
+
+        Ok(champion)
+    }
+
+    /// Request to send to the challenger of the experiment `request` falls in, if any
+    ///
+    /// Forced pipelines and recorded or replayed requests are not part of
+    /// experiments, nor are events missing the experiment's key field.
+    fn challenger_request(
+        &self,
+        request: &mut DecisionRequest,
+    ) -> Option<(Experiment, DecisionRequest)> {
+        if request.options.pipeline.is_some()
+            || corint_runtime::replay::current_session().is_some()
+        {
+            return None;
+        }
+
+        let experiment = self
+            .registry
+            .load()
+            .as_ref()?
+            .registry
+            .iter()
+            .find(|entry| WhenEvaluator::evaluate_when_block(&entry.when, &request.event_data))?
+            .experiment
+            .clone()?;
+
+        let path: Vec<String> = experiment.key.split('.').map(str::to_string).collect();
+        let key_value = match WhenEvaluator::get_field_value(&request.event_data, &path)? {
+            Value::Null => return None,
+            Value::String(s) => s,
+            Value::Number(n) if n.fract() == 0.0 => format!("{}", n as i64),
+            other => serde_json::to_string(&other).unwrap_or_default(),
+        };
+        if !experiment.includes(&key_value) {
+            return None;
+        }
+        if self.programs.load().pipeline(&experiment.challenger).is_none() {
+            tracing::warn!(
+                "Challenger pipeline '{}' of experiment '{}' is not loaded",
+                experiment.challenger,
+                experiment.id
+            );
+            return None;
+        }
+
+        // Both decisions share the request ID they are compared by
+        request
+            .metadata
+            .entry("request_id".to_string())
+            .or_insert_with(Self::generate_request_id);
+        let mut challenger = request.clone();
+        challenger.options.pipeline = Some(experiment.challenger.clone());
+        challenger.options.enable_trace = false;
+
+        Some((experiment, challenger))
+    }
+
+    /// Record the champion and challenger decisions of a request
+    async fn record_experiment(
+        &self,
+        experiment: &Experiment,
+        champion: &DecisionResponse,
+        challenger: &DecisionResponse,
+    ) {
+        let variant = |response: &DecisionResponse, pipeline_id: &str| {
+            corint_runtime::VariantDecision {
+                pipeline_id: response
+                    .pipeline_id
+                    .clone()
+                    .unwrap_or_else(|| pipeline_id.to_string()),
+                decision: response.result.signal.as_ref().map(|signal| {
+                    match signal {
+                        Signal::Approve => "approve",
+                        Signal::Decline => "decline",
+                        Signal::Review => "review",
+                        Signal::Hold => "hold",
+                        Signal::Pass => "pass",
+                    }
+                    .to_string()
+                }),
+                score: response.result.score,
+                triggered_rules: response.result.triggered_rules.clone(),
+                processing_time_ms: response.processing_time_ms,
+            }
+        };
+        let record = ExperimentRecord::new(
+            champion.request_id.clone(),
+            experiment.id.clone(),
+            variant(champion, "unknown"),
+            variant(challenger, &experiment.challenger),
+        );
+
+        tracing::info!(
+            "Experiment '{}' on request {}: champion {:?}, challenger {:?}",
+            record.experiment_id,
+            record.request_id,
+            record.champion.decision,
+            record.challenger.decision
+        );
+
+        if let Some(ref store) = self.experiment_store {
+            // A failed write must not fail the decision itself
+            if let Err(e) = store.record_experiment(record).await {
+                tracing::warn!(
+                    "Failed to record experiment result of request {}: {}",
+                    champion.request_id,
+                    e
+                );
+            }
+        }
+    }
+
+    /// Make a decision with its list lookups and time budget scoped to the request
+    ///
+    /// Lists are looked up as of `options.as_of`, or as of the recording time
+    /// when replaying, instead of now. The decision must be made within
+    /// `options.timeout_ms`, or the engine's decision timeout. A `shadow`
+    /// decision is neither persisted nor recorded to event storage. With
+    /// input capture enabled, the inputs of other decisions are captured.
+    /// Features, list lookups and extractions are memoized for the request,
+    /// so the rules sharing them compute them once. A runtime error of a
+    /// pipeline with an `on_error` policy is decided by the policy.
+    async fn decide_scoped(
+        &self,
+        mut request: DecisionRequest,
+        shadow: bool,
+    ) -> Result<DecisionResponse> {
+        let as_of = request.options.as_of.or_else(|| {
+            corint_runtime::replay::current_session().and_then(|session| session.recorded_at())
+        });
+        let lookups =
+            Arc::new(ListLookupScope::new(as_of).with_hit_audit(self.config.audit_list_hits));
+        let timeout_ms = request.options.timeout_ms.or(self.config.decision_timeout_ms);
+        let budget = Arc::new(ExecutionBudget::new(
+            timeout_ms.map(std::time::Duration::from_millis),
+        ));
+        let request_id = request
+            .metadata
+            .entry("request_id".to_string())
+            .or_insert_with(Self::generate_request_id)
+            .clone();
+        let active_capture = corint_runtime::decision_replay::current_capture();
+        let pinned = active_capture
+            .as_ref()
+            .is_some_and(|capture| capture.is_pinned());
+        let capture = (self.config.capture_inputs && !shadow && active_capture.is_none()).then(|| {
+            let inputs = CapturedInputs::new(request_id.clone(), request.event_data.clone())
+                .with_captured_at(self.clock.now());
+            Arc::new(InputCapture::capture(inputs))
+        });
+
+        let start = std::time::Instant::now();
+        let metadata = request.metadata.clone();
+        let options = request.options.clone();
+        let programs = self.programs.load_full();
+        let mut matched_pipeline_id = None;
+        let evaluation = Arc::clone(&lookups).scope(self.evaluate_request(
+            request,
+            &lookups,
+            &budget,
+            shadow,
+            &programs,
+            &mut matched_pipeline_id,
+        ));
+        // Boxed: the evaluation is too large to keep on the stack of every caller
+        let evaluation = Box::pin(Arc::clone(&budget).scope(evaluation));
+        let evaluation = Arc::new(RequestMemo::new()).scope(evaluation);
+        let response = match capture {
+            Some(capture) => capture.scope(evaluation).await,
+            None => evaluation.await,
+        };
+
+        // A step failure is decided by the pipeline's on_error policy, if it
+        // has one; a pinned replay must fail rather than decide differently
+        let error = match response {
+            Err(SdkError::RuntimeError(error)) => error,
+            response => return response,
+        };
+        let on_error = matched_pipeline_id
+            .and_then(|pipeline_id| Some((programs.pipeline_on_error(&pipeline_id)?, pipeline_id)))
+            .filter(|_| !pinned);
+        let Some((on_error, pipeline_id)) = on_error else {
+            return Err(error.into());
+        };
+
+        tracing::error!(
+            "Pipeline {} failed, deciding by its {} policy (request_id={}): {}",
+            pipeline_id,
+            on_error.policy.as_str(),
+            request_id,
+            error
+        );
+        self.metrics
+            .labeled_counter(
+                "pipeline_errors_total",
+                &[
+                    ("pipeline_id", pipeline_id.as_str()),
+                    ("policy", on_error.policy.as_str()),
+                ],
+            )
+            .inc();
+
+        let signal = on_error.fallback_signal();
+        let result = DecisionResult {
+            signal: Some(signal.clone()),
+            actions: Vec::new(),
+            score: 0,
+            triggered_rules: Vec::new(),
+            explanation: format!(
+                "Pipeline {} failed, decided by its {} policy",
+                pipeline_id,
+                on_error.policy.as_str()
+            ),
+            reason_code: on_error.reason_code.clone(),
+            context: HashMap::new(),
+        };
+        let processing_time_ms = start.elapsed().as_millis() as u64;
+
+        if let Some(result_writer) = self.result_writer.as_ref().filter(|_| !shadow) {
+            let record = corint_runtime::DecisionRecord::from_decision_result(
+                request_id.clone(),
+                metadata.get("event_id").cloned(),
+                pipeline_id.clone(),
+                &result,
+                processing_time_ms,
+                Vec::new(),
+            );
+            if let Err(e) = result_writer.write_decision(record) {
+                tracing::error!(
+                    "Failed to queue decision record for request_id {}: {}",
+                    request_id,
+                    e
+                );
+            }
+        }
+
+        let trace = options.enable_trace.then(|| {
+            let mut pipeline_trace = PipelineTrace::new(pipeline_id.clone());
+            pipeline_trace.timeouts = budget.timeouts();
+            pipeline_trace.error = Some(ErrorTrace {
+                policy: on_error.policy,
+                signal,
+                message: error.to_string(),
+            });
+            ExecutionTrace::new()
+                .with_pipeline(pipeline_trace)
+                .with_time(processing_time_ms)
+        });
+        let reason = result.reason_code.as_deref().and_then(|code| {
+            self.reason_catalog
+                .load()
+                .resolve(code, options.locale.as_deref())
+        });
+
+        Ok(DecisionResponse {
+            request_id,
+            pipeline_id: Some(pipeline_id),
+            result,
+            reason,
+            processing_time_ms,
+            metadata,
+            trace,
+            list_hits: lookups.hits(),
+            degraded: budget.is_degraded(),
+            timeouts: budget.timeouts(),
+            error: Some(error.to_string()),
+        })
+    }
+
+    async fn evaluate_request(
+        &self,
+        mut request: DecisionRequest,
+        lookups: &ListLookupScope,
+        budget: &ExecutionBudget,
+        shadow: bool,
+        programs: &ProgramSet,
+        matched_pipeline_id: &mut Option<String>,
+    ) -> Result<DecisionResponse> {
         use corint_runtime::result::ExecutionResult;
 
         let start = std::time::Instant::now();
@@ -253,6 +892,18 @@ impl DecisionEngine {
         // Track rule executions for persistence
         let mut rule_executions: Vec<corint_runtime::RuleExecutionRecord> = Vec::new();
 
+        // Features fetched ahead of the ruleset steps, for the trace
+        let mut feature_traces: Vec<FeatureTrace> = Vec::new();
+
+        // Effective windows are evaluated against a single instant per request,
+        // the instant the decision was originally made when replaying it
+        let capture = corint_runtime::decision_replay::current_capture();
+        let now = capture
+            .as_ref()
+            .filter(|capture| capture.is_pinned())
+            .map(|capture| capture.captured_at())
+            .unwrap_or_else(|| self.clock.now());
+
         // Generate request ID from metadata or create new one
         // Generate at the very beginning of the request (only once)
         // Store it in metadata so it's available throughout the request lifecycle
@@ -276,6 +927,7 @@ impl DecisionEngine {
             score: 0,
             triggered_rules: Vec::new(),
             explanation: String::new(),
+            reason_code: None,
             context: HashMap::new(),
         };
 
@@ -283,21 +935,37 @@ impl DecisionEngine {
         let mut pipeline_handled_rules = false;
         // Track whether any pipeline actually matched (when 条件命中)
         let mut pipeline_matched = false;
-        // Track which pipeline was matched for this request
-        let mut matched_pipeline_id: Option<String> = None;
+        // Side effects queued by emit steps, dispatched once the decision is final
+        let mut queued_emits: Vec<QueuedEmit> = Vec::new();
         // Track branch execution info from pipeline (preserved before rules overwrite context)
         let mut executed_branch_index: Option<usize> = None;
         let mut executed_branch_condition: Option<String> = None;
 
         // PRIORITY 1: Use Registry-based routing if available
-        if let Some(ref registry) = self.registry {
+        if let Some(registry) = self.registry.load_full() {
             tracing::debug!(
                 "Using registry-based routing with {} entries",
                 registry.registry.len()
             );
 
+            // A forced pipeline (e.g. a scheduled run) bypasses `when` matching
+            let forced_entry = request.options.pipeline.as_ref().map(|pipeline| {
+                RegistryEntry::new(
+                    pipeline.clone(),
+                    WhenBlock {
+                        event_type: None,
+                        condition_group: None,
+                        conditions: None,
+                    },
+                )
+            });
+            let entries: Vec<&RegistryEntry> = match &forced_entry {
+                Some(entry) => vec![entry],
+                None => registry.registry.iter().collect(),
+            };
+
             // Find the first matching registry entry (top-to-bottom order)
-            for (idx, entry) in registry.registry.iter().enumerate() {
+            for (idx, entry) in entries.into_iter().enumerate() {
                 tracing::debug!(
                     "Checking registry entry {}: pipeline={}, when={:?}",
                     idx,
@@ -306,7 +974,9 @@ impl DecisionEngine {
                 );
 
                 // Evaluate when block against event data
-                if WhenEvaluator::evaluate_when_block(&entry.when, &request.event_data) {
+                if forced_entry.is_some()
+                    || WhenEvaluator::evaluate_when_block(&entry.when, &request.event_data)
+                {
                     tracing::info!(
                         "✓ Registry matched entry {}: pipeline={}",
                         idx,
@@ -314,10 +984,10 @@ impl DecisionEngine {
                     );
 
                     // Record the matched pipeline ID
-                    matched_pipeline_id = Some(entry.pipeline.clone());
+                    *matched_pipeline_id = Some(entry.pipeline.clone());
 
                     // Get the pipeline program
-                    if let Some(pipeline_program) = self.pipeline_map.get(&entry.pipeline) {
+                    if let Some(pipeline_program) = programs.pipeline_map.get(&entry.pipeline) {
                         // Log pipeline execution at INFO level
                         tracing::info!(
                             "🚀 Executing pipeline: {} (request_id={})",
@@ -339,6 +1009,7 @@ impl DecisionEngine {
 
                         // Update execution_result with pipeline context
                         execution_result.variables = result.context.clone();
+                        queued_emits.extend(QueuedEmit::from_context(&result.context));
 
                         // Preserve branch execution info from pipeline execution
                         if let Some(Value::Number(branch_idx)) =
@@ -382,20 +1053,62 @@ impl DecisionEngine {
 
                             // Execute ALL rulesets in order
                             for ruleset_id in &rulesets_to_execute {
-                                if let Some(ruleset_program) = self.ruleset_map.get(ruleset_id) {
+                                if let Some(ruleset_program) = programs.ruleset_map.get(ruleset_id) {
+                                    if Self::skip_inactive_ruleset(
+                                        programs,
+                                        ruleset_program,
+                                        &request_id,
+                                        now,
+                                        &mut rule_executions,
+                                    ) {
+                                        continue;
+                                    }
+
                                     // Execute rules first
                                     if let Some(rules_str) =
                                         ruleset_program.metadata.custom.get("rules")
                                     {
-                                        let mut seen = std::collections::HashSet::new();
-                                        let rule_ids: Vec<&str> = rules_str
-                                            .split(',')
-                                            .filter(|rid| seen.insert(*rid))
-                                            .collect();
+                                        let rule_ids = programs.ordered_rule_ids(rules_str);
+
+                                        // Rules skipped by an early stop must not be fetched for
+                                        let stops_early = Self::stops_early(ruleset_program);
+                                        if !stops_early {
+                                            let step_programs =
+                                                programs.active_rule_programs(&rule_ids, now);
+                                            self.prefetch_features(
+                                                &step_programs,
+                                                &mut request,
+                                                &mut feature_traces,
+                                            )
+                                            .await?;
+                                        }
 
+                                        let mut settled = false;
                                         for rule_id in rule_ids {
-                                            if let Some(rule_program) = self.rule_map.get(rule_id)
+                                            if let Some(rule_program) = programs.rule_map.get(rule_id)
                                             {
+                                                if settled {
+                                                    rule_executions.push(
+                                                        TraceBuilder::create_skipped_rule_record(
+                                                            &request_id,
+                                                            Some(ruleset_id.as_str()),
+                                                            rule_id,
+                                                            rule_program.metadata.name.as_deref(),
+                                                            "stopped_early",
+                                                        ),
+                                                    );
+                                                    continue;
+                                                }
+                                                if self.skip_inactive_rule(
+                                                    rule_program,
+                                                    Some(ruleset_id.as_str()),
+                                                    &request_id,
+                                                    now,
+                                                    &mut rule_executions,
+                                                ) {
+                                                    continue;
+                                                }
+
                                                 tracing::info!(
                                                     "Executing rule (via ruleset {}): {}",
                                                     ruleset_id,
@@ -417,9 +1130,10 @@ impl DecisionEngine {
                                                 let rule_time_ms =
                                                     rule_start.elapsed().as_millis() as u64;
                                                 let rule_score = rule_result.score - prev_score;
-                                                let triggered = rule_result
-                                                    .triggered_rules
-                                                    .contains(&rule_id.to_string());
+                                                let triggered = TraceBuilder::rule_triggered(
+                                                    rule_program,
+                                                    &rule_result.triggered_rules,
+                                                );
 
                                                 // Extract rule metadata for trace
                                                 let rule_name = rule_program.metadata.name.as_deref();
@@ -460,6 +1174,16 @@ impl DecisionEngine {
                                                     rule_result.triggered_rules;
                                                 // Merge computed features and variables into execution context
                                                 execution_result.variables.extend(rule_result.context);
+
+                                                if stops_early {
+                                                    settled = self
+                                                        .ruleset_settled(
+                                                            ruleset_program,
+                                                            &request,
+                                                            &execution_result,
+                                                        )
+                                                        .await?;
+                                                }
                                             }
                                         }
                                     }
@@ -473,6 +1197,8 @@ impl DecisionEngine {
                                             execution_result.clone(),
                                         )
                                         .await?;
+                                    // The ruleset may have aggregated its rule scores
+                                    execution_result.score = ruleset_result.score;
 
                                     // Store ruleset result in context for pipeline decision logic
                                     let mut result_map = std::collections::HashMap::new();
@@ -530,6 +1256,7 @@ impl DecisionEngine {
                                         combined_result.signal = ruleset_result.signal;
                                     }
                                     combined_result.explanation = ruleset_result.explanation;
+                                    combined_result.reason_code = ruleset_result.reason_code;
                                     combined_result.score = execution_result.score;
                                     combined_result.triggered_rules =
                                         execution_result.triggered_rules.clone();
@@ -584,6 +1311,9 @@ impl DecisionEngine {
                             if !decision_result.explanation.is_empty() {
                                 combined_result.explanation = decision_result.explanation;
                             }
+                            if decision_result.reason_code.is_some() {
+                                combined_result.reason_code = decision_result.reason_code;
+                            }
                             // Always update actions from decision result (even if empty, to override previous values)
                             combined_result.actions = decision_result.actions;
                         } else {
@@ -619,7 +1349,7 @@ impl DecisionEngine {
             let mut ruleset_programs = Vec::new();
             let mut pipeline_programs = Vec::new();
 
-            for program in &self.programs {
+            for program in programs.iter() {
                 match program.metadata.source_type.as_str() {
                     "rule" => rule_programs.push(program),
                     "ruleset" => ruleset_programs.push(program),
@@ -657,7 +1387,7 @@ impl DecisionEngine {
 
                 if let Some(pipeline_program) = selected_pipeline {
                     // Record the matched pipeline ID
-                    matched_pipeline_id = Some(pipeline_program.metadata.source_id.clone());
+                    *matched_pipeline_id = Some(pipeline_program.metadata.source_id.clone());
 
                     // Log pipeline execution at INFO level
                     tracing::info!(
@@ -692,6 +1422,7 @@ impl DecisionEngine {
 
                     // Update execution_result with pipeline context (important for subsequent rules)
                     execution_result.variables = result.context.clone();
+                    queued_emits.extend(QueuedEmit::from_context(&result.context));
 
                     // Preserve branch execution info before rules overwrite context
                     if let Some(Value::Number(branch_idx)) =
@@ -736,18 +1467,24 @@ impl DecisionEngine {
 
                         // Execute ALL rulesets in order
                         for ruleset_id in &rulesets_to_execute {
-                            if let Some(ruleset_program) = self.ruleset_map.get(ruleset_id) {
+                            if let Some(ruleset_program) = programs.ruleset_map.get(ruleset_id) {
+                                if Self::skip_inactive_ruleset(
+                                    programs,
+                                    ruleset_program,
+                                    &request_id,
+                                    now,
+                                    &mut rule_executions,
+                                ) {
+                                    continue;
+                                }
+
                                 // IMPORTANT: Execute rules FIRST before decision logic
                                 // Get the list of rules from ruleset metadata
                                 if let Some(rules_str) =
                                     ruleset_program.metadata.custom.get("rules")
                                 {
-                                    // Dedup rule IDs to avoid accidental double execution
-                                    let mut seen = std::collections::HashSet::new();
-                                    let rule_ids: Vec<&str> = rules_str
-                                        .split(',')
-                                        .filter(|rid| seen.insert(*rid))
-                                        .collect();
+                                    // Dedup rule IDs and order them by priority
+                                    let rule_ids = programs.ordered_rule_ids(rules_str);
                                     tracing::debug!(
                                         "Executing {} rules for ruleset {}: {:?}",
                                         rule_ids.len(),
@@ -755,9 +1492,46 @@ impl DecisionEngine {
                                         rule_ids
                                     );
 
-                                    // Execute each rule and accumulate results
+                                    // Rules skipped by an early stop must not be fetched for
+                                    let stops_early = Self::stops_early(ruleset_program);
+                                    if !stops_early {
+                                        let step_programs =
+                                            programs.active_rule_programs(&rule_ids, now);
+                                        self.prefetch_features(
+                                            &step_programs,
+                                            &mut request,
+                                            &mut feature_traces,
+                                        )
+                                        .await?;
+                                    }
+
+                                    // Execute each rule and accumulate results, until the
+                                    // ruleset's outcome is settled
+                                    let mut settled = false;
                                     for rule_id in rule_ids {
-                                        if let Some(rule_program) = self.rule_map.get(rule_id) {
+                                        if let Some(rule_program) = programs.rule_map.get(rule_id) {
+                                            if settled {
+                                                rule_executions.push(
+                                                    TraceBuilder::create_skipped_rule_record(
+                                                        &request_id,
+                                                        Some(ruleset_id.as_str()),
+                                                        rule_id,
+                                                        rule_program.metadata.name.as_deref(),
+                                                        "stopped_early",
+                                                    ),
+                                                );
+                                                continue;
+                                            }
+                                            if self.skip_inactive_rule(
+                                                rule_program,
+                                                Some(ruleset_id.as_str()),
+                                                &request_id,
+                                                now,
+                                                &mut rule_executions,
+                                            ) {
+                                                continue;
+                                            }
+
                                             tracing::info!(
                                                 "Executing rule (via ruleset {}): {}",
                                                 ruleset_id,
@@ -779,9 +1553,10 @@ impl DecisionEngine {
                                             let rule_time_ms =
                                                 rule_start.elapsed().as_millis() as u64;
                                             let rule_score = rule_result.score - prev_score;
-                                            let triggered = rule_result
-                                                .triggered_rules
-                                                .contains(&rule_id.to_string());
+                                            let triggered = TraceBuilder::rule_triggered(
+                                                rule_program,
+                                                &rule_result.triggered_rules,
+                                            );
 
                                             // Extract rule metadata for trace
                                             let rule_name = rule_program.metadata.name.as_deref();
@@ -823,6 +1598,16 @@ impl DecisionEngine {
                                                 rule_result.triggered_rules;
                                             // Merge computed features and variables into execution context
                                             execution_result.variables.extend(rule_result.context);
+
+                                            if stops_early {
+                                                settled = self
+                                                    .ruleset_settled(
+                                                        ruleset_program,
+                                                        &request,
+                                                        &execution_result,
+                                                    )
+                                                    .await?;
+                                            }
                                         }
                                     }
 
@@ -839,6 +1624,8 @@ impl DecisionEngine {
                                         execution_result.clone(),
                                     )
                                     .await?;
+                                // The ruleset may have aggregated its rule scores
+                                execution_result.score = ruleset_result.score;
 
                                 // Store ruleset result in context for pipeline decision logic
                                 let mut result_map = std::collections::HashMap::new();
@@ -903,6 +1690,7 @@ impl DecisionEngine {
                                     combined_result.signal = ruleset_result.signal;
                                 }
                                 combined_result.explanation = ruleset_result.explanation;
+                                combined_result.reason_code = ruleset_result.reason_code;
                                 combined_result.score = execution_result.score;
                                 combined_result.triggered_rules =
                                     execution_result.triggered_rules.clone();
@@ -948,6 +1736,9 @@ impl DecisionEngine {
                             if !decision_result.explanation.is_empty() {
                                 combined_result.explanation = decision_result.explanation;
                             }
+                            if decision_result.reason_code.is_some() {
+                                combined_result.reason_code = decision_result.reason_code;
+                            }
                             // Always override actions with decision output
                             combined_result.actions = decision_result.actions;
                         }
@@ -964,6 +1755,15 @@ impl DecisionEngine {
                     if !seen_rules.insert(program.metadata.source_id.clone()) {
                         continue;
                     }
+                    if self.skip_inactive_rule(
+                        program,
+                        None,
+                        &request_id,
+                        now,
+                        &mut rule_executions,
+                    ) {
+                        continue;
+                    }
                     tracing::info!("Executing rule (global): {}", program.metadata.source_id);
 
                     let rule_start = std::time::Instant::now();
@@ -981,7 +1781,7 @@ impl DecisionEngine {
                     let rule_time_ms = rule_start.elapsed().as_millis() as u64;
                     let rule_score = result.score - prev_score;
                     let rule_id = &program.metadata.source_id;
-                    let triggered = result.triggered_rules.contains(rule_id);
+                    let triggered = TraceBuilder::rule_triggered(program, &result.triggered_rules);
 
                     // Extract rule metadata for trace
                     let rule_name = program.metadata.name.as_deref();
@@ -1030,16 +1830,39 @@ impl DecisionEngine {
                         )
                         .await?;
 
+                    // The ruleset may have aggregated its rule scores
+                    execution_result.score = result.score;
+                    combined_result.score = result.score;
+
                     // Update combined result with decision from ruleset
                     if result.signal.is_some() {
                         combined_result.signal = result.signal;
                     }
+                    if result.reason_code.is_some() {
+                        combined_result.reason_code = result.reason_code;
+                    }
                 }
             }
         }
 
         let processing_time_ms = start.elapsed().as_millis() as u64;
 
+        // Record the event so later decisions count it; a failed write must
+        // not fail the decision itself
+        if let Some(storage) = self.event_storage.as_ref().filter(|_| !shadow) {
+            let event = corint_runtime::Event {
+                timestamp: now.timestamp(),
+                data: request.event_data.clone(),
+            };
+            if let Err(e) = storage.insert_event(event).await {
+                tracing::warn!(
+                    "Failed to record event of request {} to storage: {}",
+                    request_id,
+                    e
+                );
+            }
+        }
+
         // Persist decision result asynchronously if result writer is configured
         tracing::debug!("Checking result_writer in DecisionEngine.decide()...");
         tracing::debug!(
@@ -1047,7 +1870,7 @@ impl DecisionEngine {
             self.result_writer.is_some()
         );
 
-        if let Some(ref result_writer) = self.result_writer {
+        if let Some(result_writer) = self.result_writer.as_ref().filter(|_| !shadow) {
             tracing::debug!("Result writer is configured, preparing to persist decision result");
 
             // Extract request_id and event_id from metadata
@@ -1062,7 +1885,9 @@ impl DecisionEngine {
             tracing::debug!("Request ID: {}, Event ID: {:?}", request_id, event_id);
 
             // Determine pipeline_id (use first matched pipeline or default)
-            let pipeline_id = if let Some(ref registry) = self.registry {
+            let pipeline_id = if let Some(ref pipeline) = request.options.pipeline {
+                pipeline.clone()
+            } else if let Some(registry) = self.registry.load_full() {
                 // Find the matched pipeline from registry
                 registry
                     .registry
@@ -1070,9 +1895,9 @@ impl DecisionEngine {
                     .find(|entry| WhenEvaluator::evaluate_when_block(&entry.when, &request.event_data))
                     .map(|entry| entry.pipeline.clone())
                     .unwrap_or("unknown".to_string())
-            } else if !self.pipeline_map.is_empty() {
+            } else if !programs.pipeline_map.is_empty() {
                 // Use first pipeline ID
-                self.pipeline_map
+                programs.pipeline_map
                     .keys()
                     .next()
                     .cloned()
@@ -1084,7 +1909,7 @@ impl DecisionEngine {
             tracing::debug!("Pipeline ID: {}", pipeline_id);
 
             // Create decision record
-            let decision_record = corint_runtime::DecisionRecord::from_decision_result(
+            let mut decision_record = corint_runtime::DecisionRecord::from_decision_result(
                 request_id.clone(),
                 event_id,
                 pipeline_id,
@@ -1092,6 +1917,11 @@ impl DecisionEngine {
                 processing_time_ms,
                 rule_executions.clone(), // Clone for trace usage later
             );
+            decision_record.captured_inputs = capture.as_ref().map(|capture| capture.inputs());
+            decision_record.sampled = self
+                .config
+                .sampling
+                .should_sample(&decision_record, rand::random::<f64>());
 
             tracing::info!(
                 "Queuing decision record for persistence: request_id={}, score={}, action={:?}",
@@ -1120,6 +1950,19 @@ impl DecisionEngine {
             tracing::debug!("Result writer not configured, skipping persistence");
         }
 
+        // Dispatch the side effects of emit steps in the background; shadow
+        // and replayed decisions must not notify anyone
+        let replaying = corint_runtime::replay::current_session()
+            .is_some_and(|session| session.mode() == RecordReplayMode::Replay);
+        if !shadow && !replaying && !queued_emits.is_empty() {
+            let emissions = queued_emits
+                .into_iter()
+                .filter(|queued| queued.emits_for(combined_result.signal.as_ref()))
+                .map(|queued| Emission::new(request_id.clone(), queued, &combined_result))
+                .collect();
+            self.emit_dispatcher.dispatch(emissions);
+        }
+
         // Build trace if enabled
         let trace = if request.options.enable_trace {
             // Build execution trace from collected rule executions
@@ -1131,7 +1974,7 @@ impl DecisionEngine {
 
             // Add pipeline when_conditions from metadata
             if let Some(ref pid) = matched_pipeline_id {
-                if let Some(pipeline_program) = self.pipeline_map.get(pid) {
+                if let Some(pipeline_program) = programs.pipeline_map.get(pid) {
                     if let Some(when_conditions_str) =
                         pipeline_program.metadata.custom.get("when_conditions")
                     {
@@ -1145,7 +1988,7 @@ impl DecisionEngine {
 
             // Add step traces from pipeline metadata
             if let Some(ref pid) = matched_pipeline_id {
-                if let Some(pipeline_program) = self.pipeline_map.get(pid) {
+                if let Some(pipeline_program) = programs.pipeline_map.get(pid) {
                     if let Some(steps_json_str) = pipeline_program.metadata.custom.get("steps_json") {
                         let step_traces = TraceBuilder::build_step_traces_from_json(
                             steps_json_str,
@@ -1211,6 +2054,11 @@ impl DecisionEngine {
                     rule_trace.triggered = rule_exec.triggered;
                     rule_trace.score = rule_exec.score;
                     rule_trace.execution_time_ms = rule_exec.execution_time_ms;
+                    rule_trace.skipped = rule_exec.skip_reason.clone();
+                    rule_trace.source = programs
+                        .rule_map
+                        .get(&rule_exec.rule_id)
+                        .and_then(TraceBuilder::rule_source);
 
                     // Add rule conditions from metadata - prefer condition_group_json (new format)
                     if let Some(ref condition_group_json_str) = rule_exec.condition_group_json {
@@ -1238,6 +2086,10 @@ impl DecisionEngine {
                         }
                     }
 
+                    if self.config.null_semantics.is_three_valued() {
+                        TraceBuilder::mark_unknown_conditions(&mut rule_trace.conditions);
+                    }
+
                     if let Some(score) = rule_exec.score {
                         ruleset_score += score;
                     }
@@ -1260,6 +2112,9 @@ impl DecisionEngine {
                             &request.event_data,
                         );
                         ruleset_trace.conclusion = conclusion_traces;
+                        if let Some(program) = programs.ruleset_map.get(&ruleset_trace.ruleset_id) {
+                            TraceBuilder::locate_conclusion(&mut ruleset_trace.conclusion, program);
+                        }
                     }
                 } else if let Some(ref action) = combined_result.signal {
                     // Fallback to combined result if ruleset-specific result not found
@@ -1277,45 +2132,384 @@ impl DecisionEngine {
                 pipeline_trace = pipeline_trace.add_ruleset(ruleset_trace);
             }
 
-            Some(
-                ExecutionTrace::new()
-                    .with_pipeline(pipeline_trace)
-                    .with_time(processing_time_ms),
-            )
+            pipeline_trace.features = feature_traces;
+            pipeline_trace.lists = lookups
+                .consulted()
+                .into_iter()
+                .map(|(list_id, version)| ListTrace {
+                    list_id,
+                    version,
+                    as_of: lookups.as_of(),
+                })
+                .collect();
+            pipeline_trace.timeouts = budget.timeouts();
+
+            let mut trace = ExecutionTrace::new()
+                .with_pipeline(pipeline_trace)
+                .with_time(processing_time_ms);
+            if let Some(ref capture) = capture {
+                trace = trace.with_inputs(capture.inputs());
+            }
+
+            // Traces carry raw operand values; mask PII before returning them
+            self.masking_policy.load().mask_trace(&mut trace);
+
+            Some(trace)
         } else {
             None
         };
 
+        // Resolve the reason code into localized text for the requested locale
+        let reason = combined_result.reason_code.as_deref().and_then(|code| {
+            self.reason_catalog
+                .load()
+                .resolve(code, request.options.locale.as_deref())
+        });
+
         Ok(DecisionResponse {
             request_id,
-            pipeline_id: matched_pipeline_id,
+            pipeline_id: matched_pipeline_id.clone(),
             result: combined_result,
+            reason,
             processing_time_ms,
             metadata: request.metadata,
             trace,
+            list_hits: lookups.hits(),
+            degraded: budget.is_degraded(),
+            timeouts: budget.timeouts(),
+            error: None,
         })
     }
 
+    /// Replay a decision from the inputs captured when it was made
+    ///
+    /// The programs are re-executed with every feature value, call result and
+    /// list lookup answered from `inputs`, and effective windows evaluated as
+    /// of the original decision, so the same rules produce the same decision.
+    /// An input the programs consume that was not captured fails the replay.
+    /// The replay is traced, but neither persisted nor recorded to event
+    /// storage.
+    pub async fn replay_decision(&self, inputs: CapturedInputs) -> Result<DecisionResponse> {
+        let request = DecisionRequest::new(inputs.event.clone())
+            .with_metadata("request_id".to_string(), inputs.request_id.clone())
+            .with_trace();
+        let capture = Arc::new(InputCapture::pin(inputs));
+        capture.scope(self.decide_scoped(request, true)).await
+    }
+
     /// Get metrics collector
     pub fn metrics(&self) -> Arc<MetricsCollector> {
         self.metrics.clone()
     }
 
+    /// Time spent per instruction, rule, ruleset, pipeline and feature, hottest first
+    ///
+    /// Empty unless profiling is enabled with `DecisionEngineBuilder::with_profiling`.
+    pub fn profile_report(&self) -> ProfileReport {
+        self.metrics.profile_report()
+    }
+
+    /// Quota statistics of the tenants that made decisions
+    pub fn tenant_stats(&self) -> Vec<TenantStats> {
+        self.executor.tenant_stats()
+    }
+
+    /// Start a new profile, e.g. before measuring a representative load
+    pub fn reset_profile(&self) {
+        self.metrics.profile().reset();
+    }
+
+    /// Dispatcher of the side effects of emit steps, for their dead letters and stats
+    pub fn emit_dispatcher(&self) -> &Arc<EmitDispatcher> {
+        &self.emit_dispatcher
+    }
+
     /// Get configuration
     pub fn config(&self) -> &EngineConfig {
         &self.config
     }
 
+    /// Compiled programs of all loaded rules, rulesets and pipelines
+    pub fn programs(&self) -> Arc<ProgramSet> {
+        self.programs.load_full()
+    }
+
+    /// Statistics of the programs reused across loads
+    ///
+    /// Reloads and swaps only compile the rules, rulesets and pipelines that
+    /// changed since the last load; `hits` counts the ones reused unchanged.
+    pub fn compilation_cache_stats(&self) -> CompilationCacheStats {
+        self.compile_cache.stats()
+    }
+
+    /// Compile new rules and swap them in for the loaded programs
+    ///
+    /// `rule_contents` are `(id, YAML)` pairs, as in
+    /// `EngineConfig::rule_contents`, and replace all loaded rules, rulesets
+    /// and pipelines. They are compiled while the current programs keep
+    /// serving decisions, then swapped in at once: decisions in flight finish
+    /// with the programs they started with, and later ones see only the new
+    /// programs. If compilation fails, the current programs stay in place.
+    /// The registry, reason catalog and engine configuration are unchanged.
+    pub async fn swap_programs(&self, rule_contents: Vec<(String, String)>) -> Result<()> {
+        let programs = self
+            .compile_programs(&rule_contents, &self.reason_catalog.load())
+            .await?;
+        tracing::info!("✓ Swapped in {} compiled programs", programs.len());
+        self.programs.store(Arc::new(programs));
+        Ok(())
+    }
+
+    /// Compile `rule_contents`, reusing the programs of unchanged definitions
+    async fn compile_programs(
+        &self,
+        rule_contents: &[(String, String)],
+        reason_catalog: &ReasonCatalog,
+    ) -> Result<ProgramSet> {
+        let mut compiler = CompilerHelper::create_compiler(
+            &self.config.compiler_options,
+            &self.compile_cache,
+            reason_catalog,
+            &self.step_plugins,
+        );
+
+        let cache_stats = self.compile_cache.stats();
+        let mut programs = Vec::new();
+        for (id, content) in rule_contents {
+            programs.extend(CompilerHelper::compile_rules_from_content(id, content, &mut compiler).await?);
+        }
+        compiler.eliminate_common_subexpressions(&mut programs);
+        CompilerHelper::sweep_compilation_cache(&self.compile_cache, cache_stats);
+
+        Ok(ProgramSet::new(programs))
+    }
+
+    /// Scheduled pipelines declared in the registry
+    pub fn schedules(&self) -> Vec<ScheduleEntry> {
+        self.registry
+            .load()
+            .as_ref()
+            .map(|registry| registry.schedules.clone())
+            .unwrap_or_default()
+    }
+
+    /// Run a scheduled pipeline once
+    ///
+    /// Selects entities with the schedule's entity query and executes the pipeline
+    /// for each of them, `batch_size` decisions at a time. Each row becomes the
+    /// event data of one decision, which is persisted like any other decision.
+    /// Rows without a `type` column get the schedule ID as their event type.
+    pub async fn run_schedule(&self, schedule_id: &str) -> Result<ScheduleRunSummary> {
+        let registry = self.registry.load_full();
+        let schedule = registry
+            .as_deref()
+            .and_then(|registry| registry.schedule(schedule_id))
+            .ok_or_else(|| SdkError::Config(format!("Schedule '{}' not found", schedule_id)))?;
+
+        if self.programs.load().pipeline(&schedule.pipeline).is_none() {
+            return Err(SdkError::Config(format!(
+                "Schedule '{}' references unknown pipeline: {}",
+                schedule.id, schedule.pipeline
+            )));
+        }
+
+        let feature_executor = self.feature_executor.as_ref().ok_or_else(|| {
+            SdkError::Config(format!(
+                "Schedule '{}' requires datasources, but none are configured",
+                schedule.id
+            ))
+        })?;
+
+        let entities = feature_executor
+            .query_entities(&schedule.entities)
+            .await
+            .map_err(|e| {
+                SdkError::GenericError(format!(
+                    "Entity query for schedule '{}' failed: {}",
+                    schedule.id, e
+                ))
+            })?;
+
+        let mut summary = ScheduleRunSummary {
+            schedule_id: schedule.id.clone(),
+            pipeline: schedule.pipeline.clone(),
+            entities: entities.len(),
+            ..Default::default()
+        };
+
+        for batch in entities.chunks(schedule.batch_size.max(1)) {
+            let decisions = batch.iter().map(|entity| {
+                let mut event_data = entity.clone();
+                event_data
+                    .entry("type".to_string())
+                    .or_insert_with(|| Value::String(schedule.id.clone()));
+
+                let request = DecisionRequest::new(event_data)
+                    .with_pipeline(schedule.pipeline.clone())
+                    .with_metadata("schedule_id".to_string(), schedule.id.clone());
+                self.decide(request)
+            });
+
+            for result in futures::future::join_all(decisions).await {
+                match result {
+                    Ok(_) => summary.succeeded += 1,
+                    Err(e) => {
+                        summary.failed += 1;
+                        tracing::warn!("Scheduled decision for '{}' failed: {}", schedule.id, e);
+                    }
+                }
+            }
+            summary.batches += 1;
+        }
+
+        tracing::info!(
+            "Schedule '{}' evaluated {} entities ({} failed)",
+            schedule.id,
+            summary.entities,
+            summary.failed
+        );
+
+        Ok(summary)
+    }
+
+    /// Record the final outcome of a past decision
+    ///
+    /// Outcomes (chargebacks, confirmed fraud, false positives) are stored against
+    /// the decision's `request_id`. Recording a second outcome for the same request
+    /// replaces the first.
+    pub async fn record_outcome(
+        &self,
+        request_id: &str,
+        label: OutcomeLabel,
+        metadata: HashMap<String, Value>,
+    ) -> Result<()> {
+        if request_id.trim().is_empty() {
+            return Err(SdkError::GenericError(
+                "Outcome request_id must not be empty".to_string(),
+            ));
+        }
+
+        let store = self.outcome_store.as_ref().ok_or_else(|| {
+            SdkError::Config("Outcome feedback requires an outcome store".to_string())
+        })?;
+
+        store
+            .record_outcome(OutcomeRecord::new(request_id, label).with_metadata(metadata))
+            .await?;
+
+        tracing::info!(
+            "Recorded outcome '{}' for request_id: {}",
+            label.as_str(),
+            request_id
+        );
+
+        Ok(())
+    }
+
+    /// Compare the champion and challenger of an experiment over its recorded requests
+    pub async fn experiment_summary(&self, experiment_id: &str) -> Result<ExperimentSummary> {
+        let store = self.experiment_store.as_ref().ok_or_else(|| {
+            SdkError::Config("Experiment results require an experiment store".to_string())
+        })?;
+        let records = store.experiment_records(experiment_id).await?;
+        Ok(corint_runtime::summarize_experiment(experiment_id, &records))
+    }
+
+    /// Derive the key of a declared entity from event data
+    ///
+    /// Uses the same entity definitions as feature computation, so velocity
+    /// counters, deduplication and idempotency checks built on these keys
+    /// agree with the features.
+    pub fn entity_key(&self, entity: &str, event_data: &HashMap<String, Value>) -> Result<String> {
+        Ok(self.entities.load().resolve_key(entity, event_data)?)
+    }
+
+    /// Version of the feature definitions, incremented on each reload
+    ///
+    /// `None` when the engine has no feature executor.
+    pub fn feature_registry_version(&self) -> Option<u64> {
+        self.feature_executor
+            .as_ref()
+            .map(|executor| executor.registry_version())
+    }
+
+    /// List service used for `list.*` lookups, if lists are configured
+    ///
+    /// Entries added or removed through it take effect on the next decision.
+    pub fn list_service(&self) -> Option<&Arc<corint_runtime::lists::ListService>> {
+        self.list_service.as_ref()
+    }
+
+    /// Replace the feature executor's definitions with the repository's
+    ///
+    /// Every feature file must load; otherwise the current definitions stay.
+    async fn reload_features(&self) -> Result<()> {
+        use corint_runtime::feature::registry::FeatureRegistry;
+
+        let (Some(executor), Some(repo_config)) = (&self.feature_executor, &self.repository_config)
+        else {
+            return Ok(());
+        };
+        let Some(base_path) = crate::builder::DecisionEngineBuilder::filesystem_base_path(repo_config)
+        else {
+            return Ok(());
+        };
+        let feature_dir = Path::new(base_path).join("configs/features");
+        if !feature_dir.is_dir() {
+            return Ok(());
+        }
+
+        let entries = std::fs::read_dir(&feature_dir).map_err(|e| {
+            SdkError::Config(format!("Failed to read {}: {}", feature_dir.display(), e))
+        })?;
+        let mut paths: Vec<_> = entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| {
+                path.is_file()
+                    && path.extension().is_some_and(|ext| ext == "yaml" || ext == "yml")
+            })
+            .collect();
+        paths.sort();
+
+        let mut registry = FeatureRegistry::new();
+        for path in &paths {
+            registry
+                .load_from_file(path)
+                .map_err(|e| SdkError::Config(format!("Failed to reload features: {:#}", e)))?;
+        }
+
+        let features = registry.all_features().into_iter().cloned().collect();
+        let changes = executor
+            .replace_features(features)
+            .await
+            .map_err(|e| SdkError::Config(format!("Failed to reload features: {:#}", e)))?;
+        tracing::info!(
+            "✓ Reloaded features (version {}): {} added, {} updated, {} removed",
+            changes.registry_version,
+            changes.added.len(),
+            changes.updated.len(),
+            changes.removed.len()
+        );
+
+        Ok(())
+    }
+
     /// Reload rules and configurations from repository
     ///
     /// This method reloads all content from the configured repository and recompiles
-    /// all rules, rulesets, and pipelines. It preserves the feature executor,
-    /// list service, and result writer configurations.
+    /// all rules, rulesets, and pipelines. Feature definitions are swapped in the
+    /// existing feature executor, which keeps its datasource connections; the
+    /// list service and result writer configurations are preserved.
+    ///
+    /// Like [`swap_programs`](Self::swap_programs), the content is compiled
+    /// while the current programs keep serving decisions, and swapped in once
+    /// everything loaded. The engine configuration keeps the contents it was
+    /// built with.
     ///
     /// # Returns
     ///
     /// Returns an error if the repository is not configured or if reloading fails.
-    pub async fn reload(&mut self) -> Result<()> {
+    pub async fn reload(&self) -> Result<()> {
         use corint_repository::RepositoryLoader;
 
         // Check if repository is configured
@@ -1326,68 +2520,27 @@ impl DecisionEngine {
         tracing::info!("Reloading repository content...");
 
         // Load content from repository
-        let loader = RepositoryLoader::new(repo_config.clone());
+        let mut loader = RepositoryLoader::new(repo_config.clone());
+        if let Some(resolver) = &self.secret_resolver {
+            loader = loader.with_secret_resolver(Arc::clone(resolver));
+        }
         let content = loader.load_all().await.map_err(|e| {
             SdkError::Config(format!("Failed to load repository: {}", e))
         })?;
 
-        // Create a new config with repository content merged
-        let mut new_config = self.config.clone();
-
-        // Clear existing rule contents and files (they will be replaced by repository content)
-        new_config.rule_contents.clear();
-        new_config.rule_files.clear();
-        new_config.registry_content = None;
-        new_config.registry_file = None;
-
-        // Merge repository content into config
-        if let Some(registry) = content.registry {
-            new_config.registry_content = Some(registry);
-        }
-
-        // Add all pipelines as rule content
-        for (id, yaml) in content.pipelines {
-            new_config.rule_contents.push((id, yaml));
-        }
-
-        // Recompile all programs
-        let mut programs = Vec::new();
-        let compiler_opts = CompilerOpts {
-            enable_semantic_analysis: new_config.compiler_options.enable_semantic_analysis,
-            enable_constant_folding: new_config.compiler_options.enable_constant_folding,
-            enable_dead_code_elimination: true,
-            library_base_path: "repository".to_string(),
-        };
+        let reason_catalog = CompilerHelper::load_reason_catalog(&content.reason_catalogs)?;
+        let masking_policy =
+            Arc::new(CompilerHelper::load_masking_policy(&content.masking_policies)?);
+        let entities = Arc::new(CompilerHelper::load_entities(&content.entity_definitions)?);
 
-        let mut compiler = Compiler::with_options(compiler_opts);
-
-        // Compile rule contents (from repository)
-        for (id, content_str) in &new_config.rule_contents {
-            programs.extend(CompilerHelper::compile_rules_from_content(id, content_str, &mut compiler).await?);
-        }
-
-        // Build ruleset_map, rule_map, and pipeline_map for routing
-        let mut ruleset_map = HashMap::new();
-        let mut rule_map = HashMap::new();
-        let mut pipeline_map = HashMap::new();
-        for program in &programs {
-            match program.metadata.source_type.as_str() {
-                "ruleset" => {
-                    ruleset_map.insert(program.metadata.source_id.clone(), program.clone());
-                }
-                "rule" => {
-                    rule_map.insert(program.metadata.source_id.clone(), program.clone());
-                }
-                "pipeline" => {
-                    pipeline_map.insert(program.metadata.source_id.clone(), program.clone());
-                }
-                _ => {}
-            }
-        }
+        // Recompile all pipelines, reusing the programs of unchanged ones
+        let programs = self
+            .compile_programs(&content.pipelines, &reason_catalog)
+            .await?;
 
         // Load optional registry
-        let registry = if let Some(registry_content) = &new_config.registry_content {
-            match RegistryParser::parse(registry_content) {
+        let registry = match &content.registry {
+            Some(registry_content) => match RegistryParser::parse(registry_content) {
                 Ok(reg) => {
                     tracing::info!(
                         "✓ Reloaded pipeline registry: {} entries",
@@ -1402,18 +2555,21 @@ impl DecisionEngine {
                     );
                     None
                 }
-            }
-        } else {
-            None
+            },
+            None => None,
         };
 
+        self.reload_features().await?;
+
         // Update engine state
-        self.programs = programs;
-        self.ruleset_map = ruleset_map;
-        self.rule_map = rule_map;
-        self.pipeline_map = pipeline_map;
-        self.registry = registry;
-        self.config = new_config;
+        self.programs.store(Arc::new(programs));
+        self.registry.store(registry.map(Arc::new));
+        self.reason_catalog.store(Arc::new(reason_catalog));
+        if let Some(ref result_writer) = self.result_writer {
+            result_writer.set_masking_policy(masking_policy.clone());
+        }
+        self.masking_policy.store(masking_policy);
+        self.entities.store(entities);
 
         tracing::info!("✓ Repository reloaded successfully");
 
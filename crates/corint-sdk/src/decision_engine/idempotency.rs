@@ -0,0 +1,234 @@
+//! Idempotency of decisions
+//!
+//! Clients retry requests that timed out or failed in transit, and a retry
+//! must not decide its event a second time: the event would be counted twice
+//! by velocity features and its side effects emitted again. With an
+//! [`IdempotencyCache`] the engine answers a request carrying an `event_id`
+//! it decided within the TTL with the response it returned then.
+//!
+//! Concurrent requests for one event wait for the first of them to be
+//! decided. A failed decision is not kept, so the next retry is decided
+//! afresh. Events are keyed per tenant.
+
+use super::types::{DecisionRequest, DecisionResponse};
+use crate::config::IdempotencyConfig;
+use crate::error::Result;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::OnceCell;
+use tokio::time::Instant;
+
+/// Metadata key of the event ID requests are deduplicated by
+pub(crate) const EVENT_ID_KEY: &str = "event_id";
+
+/// Tenant and ID of an event
+pub(crate) type EventKey = (Option<String>, String);
+
+/// Decision of one event, once made
+#[derive(Debug)]
+struct Entry {
+    response: Arc<OnceCell<DecisionResponse>>,
+    created_at: Instant,
+}
+
+/// Recent decisions, by tenant and event ID
+#[derive(Debug)]
+pub(crate) struct IdempotencyCache {
+    config: IdempotencyConfig,
+    entries: Mutex<HashMap<EventKey, Entry>>,
+}
+
+impl IdempotencyCache {
+    pub(crate) fn new(config: IdempotencyConfig) -> Self {
+        Self {
+            config,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Key of the event of `request`, if it carries an event ID
+    pub(crate) fn key(request: &DecisionRequest) -> Option<EventKey> {
+        let event_id = request.metadata.get(EVENT_ID_KEY)?;
+        Some((request.tenant.clone(), event_id.clone()))
+    }
+
+    /// Response of the event of `key`, making the decision with `decide` if
+    /// it was not made within the TTL
+    ///
+    /// Also returns whether the response is one made before.
+    pub(crate) async fn get_or_decide<F, Fut>(
+        &self,
+        key: EventKey,
+        decide: F,
+    ) -> Result<(DecisionResponse, bool)>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<DecisionResponse>>,
+    {
+        let cell = self.cell(key);
+        let mut decided = false;
+        let response = cell
+            .get_or_try_init(|| {
+                decided = true;
+                decide()
+            })
+            .await?;
+        Ok((response.clone(), !decided))
+    }
+
+    /// Cell of the decision of `key`, created if missing or expired
+    fn cell(&self, key: EventKey) -> Arc<OnceCell<DecisionResponse>> {
+        let ttl = Duration::from_secs(self.config.ttl_secs);
+        let mut entries = self.entries.lock().unwrap();
+        if let Some(entry) = entries.get(&key) {
+            if entry.created_at.elapsed() < ttl {
+                return Arc::clone(&entry.response);
+            }
+        } else if entries.len() >= self.config.max_entries {
+            entries.retain(|_, entry| entry.created_at.elapsed() < ttl);
+            let oldest = entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.created_at)
+                .map(|(key, _)| key.clone());
+            if let Some(oldest) = oldest.filter(|_| entries.len() >= self.config.max_entries) {
+                entries.remove(&oldest);
+            }
+        }
+
+        let response = Arc::new(OnceCell::new());
+        entries.insert(
+            key,
+            Entry {
+                response: Arc::clone(&response),
+                created_at: Instant::now(),
+            },
+        );
+        response
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::SdkError;
+    use corint_core::ast::Signal;
+    use corint_runtime::DecisionResult;
+
+    fn response(request_id: &str) -> DecisionResponse {
+        DecisionResponse {
+            request_id: request_id.to_string(),
+            pipeline_id: None,
+            result: DecisionResult::new(Signal::Approve, 0),
+            reason: None,
+            processing_time_ms: 0,
+            metadata: HashMap::new(),
+            trace: None,
+            list_hits: Vec::new(),
+            degraded: false,
+            timeouts: Vec::new(),
+            error: None,
+        }
+    }
+
+    fn key(tenant: Option<&str>, event_id: &str) -> EventKey {
+        (tenant.map(str::to_string), event_id.to_string())
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_idempotency_cache() {
+        let cache = IdempotencyCache::new(IdempotencyConfig::new(Duration::from_secs(60)));
+
+        let (first, replayed) = cache
+            .get_or_decide(key(Some("acme"), "evt_1"), || async {
+                Ok(response("req_1"))
+            })
+            .await
+            .unwrap();
+        assert_eq!(first.request_id, "req_1");
+        assert!(!replayed);
+
+        let (again, replayed) = cache
+            .get_or_decide(key(Some("acme"), "evt_1"), || async {
+                Ok(response("req_2"))
+            })
+            .await
+            .unwrap();
+        assert_eq!(again.request_id, "req_1");
+        assert!(replayed);
+
+        // Failures are not kept
+        let failed = cache
+            .get_or_decide(key(Some("acme"), "evt_2"), || async {
+                Err(SdkError::NotInitialized)
+            })
+            .await;
+        assert!(failed.is_err());
+        let (retried, replayed) = cache
+            .get_or_decide(key(Some("acme"), "evt_2"), || async {
+                Ok(response("req_3"))
+            })
+            .await
+            .unwrap();
+        assert_eq!(retried.request_id, "req_3");
+        assert!(!replayed);
+
+        // An expired decision is made again
+        tokio::time::advance(Duration::from_secs(61)).await;
+        let (expired, replayed) = cache
+            .get_or_decide(key(Some("acme"), "evt_1"), || async {
+                Ok(response("req_4"))
+            })
+            .await
+            .unwrap();
+        assert_eq!(expired.request_id, "req_4");
+        assert!(!replayed);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_idempotency_cache_evicts_oldest() {
+        let cache = IdempotencyCache::new(
+            IdempotencyConfig::new(Duration::from_secs(60)).with_max_entries(2),
+        );
+        let decide = |event: &str| {
+            let key = key(None, event);
+            let decision = response(event);
+            async {
+                cache
+                    .get_or_decide(key, || async { Ok(decision) })
+                    .await
+                    .unwrap()
+                    .1
+            }
+        };
+        for event in ["evt_1", "evt_2", "evt_3"] {
+            assert!(!decide(event).await);
+            tokio::time::advance(Duration::from_secs(1)).await;
+        }
+
+        assert!(decide("evt_3").await);
+        assert!(decide("evt_2").await);
+        assert!(!decide("evt_1").await);
+    }
+
+    #[test]
+    fn test_key_separates_tenant_and_event_id() {
+        let request = |tenant: &str, event_id: &str| {
+            let mut request = DecisionRequest::new(HashMap::new()).with_tenant(tenant);
+            request
+                .metadata
+                .insert(EVENT_ID_KEY.to_string(), event_id.to_string());
+            request
+        };
+
+        assert_ne!(
+            IdempotencyCache::key(&request("acme:eu", "evt_1")),
+            IdempotencyCache::key(&request("acme", "eu:evt_1"))
+        );
+        assert_eq!(
+            IdempotencyCache::key(&request("acme", "evt_1")),
+            Some(key(Some("acme"), "evt_1"))
+        );
+    }
+}
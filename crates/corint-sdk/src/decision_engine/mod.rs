@@ -11,17 +11,22 @@
 //! - `when_evaluator`: When block and condition evaluation logic
 //! - `trace_builder`: Execution trace construction utilities
 //! - `compiler_helper`: Rule compilation and loading utilities
+//! - `program_set`: Compiled programs, indexed for routing and swapped atomically
+//! - `idempotency`: Recent decisions, returned again for retried events
 //! - `tests`: Unit tests (test-only)
 
 mod types;
 mod when_evaluator;
 mod trace_builder;
 mod compiler_helper;
+mod program_set;
+mod idempotency;
 mod engine;
 
 // Re-export public types
 pub use types::{DecisionOptions, DecisionRequest, DecisionResponse};
 pub use engine::DecisionEngine;
+pub use program_set::ProgramSet;
 
 // Tests module (only compiled in test mode)
 #[cfg(test)]
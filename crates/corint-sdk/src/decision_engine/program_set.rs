@@ -0,0 +1,123 @@
+//! Compiled programs of an engine
+//!
+//! The programs an engine decides with are kept in one immutable
+//! [`ProgramSet`], indexed by ruleset, rule and pipeline ID. A decision loads
+//! the current set once and routes through it from start to finish, so
+//! swapping in a new set while decisions are in flight never lets one of them
+//! mix old and new programs.
+
+use corint_core::ast::PipelineOnError;
+use corint_core::ir::Program;
+use corint_runtime::EffectiveStatus;
+use std::collections::HashMap;
+
+/// Compiled rules, rulesets and pipelines, indexed for routing
+#[derive(Debug, Clone, Default)]
+pub struct ProgramSet {
+    /// Compiled programs (one per rule/ruleset/pipeline)
+    programs: Vec<Program>,
+
+    /// Mapping of ruleset ID to compiled program (for pipeline routing)
+    pub(crate) ruleset_map: HashMap<String, Program>,
+
+    /// Mapping of rule ID to compiled program
+    pub(crate) rule_map: HashMap<String, Program>,
+
+    /// Mapping of pipeline ID to compiled program
+    pub(crate) pipeline_map: HashMap<String, Program>,
+}
+
+impl ProgramSet {
+    /// Index compiled programs by their source type and ID
+    pub fn new(programs: Vec<Program>) -> Self {
+        let mut ruleset_map = HashMap::new();
+        let mut rule_map = HashMap::new();
+        let mut pipeline_map = HashMap::new();
+        for program in &programs {
+            let map = match program.metadata.source_type.as_str() {
+                "ruleset" => &mut ruleset_map,
+                "rule" => &mut rule_map,
+                "pipeline" => &mut pipeline_map,
+                _ => continue,
+            };
+            map.insert(program.metadata.source_id.clone(), program.clone());
+        }
+
+        Self {
+            programs,
+            ruleset_map,
+            rule_map,
+            pipeline_map,
+        }
+    }
+
+    /// Iterate over all programs, in compilation order
+    pub fn iter(&self) -> std::slice::Iter<'_, Program> {
+        self.programs.iter()
+    }
+
+    /// Number of programs
+    pub fn len(&self) -> usize {
+        self.programs.len()
+    }
+
+    /// Whether there are no programs
+    pub fn is_empty(&self) -> bool {
+        self.programs.is_empty()
+    }
+
+    /// Program of a pipeline
+    pub fn pipeline(&self, pipeline_id: &str) -> Option<&Program> {
+        self.pipeline_map.get(pipeline_id)
+    }
+
+    /// Fallback decision of a pipeline whose step fails, if it has one
+    pub(crate) fn pipeline_on_error(&self, pipeline_id: &str) -> Option<PipelineOnError> {
+        let on_error = self
+            .pipeline(pipeline_id)?
+            .metadata
+            .custom
+            .get("on_error")?;
+        serde_json::from_str(on_error).ok()
+    }
+
+    /// Programs of the rules that are in their effective window
+    pub(crate) fn active_rule_programs(
+        &self,
+        rule_ids: &[&str],
+        now: chrono::DateTime<chrono::Utc>,
+    ) -> Vec<&Program> {
+        rule_ids
+            .iter()
+            .filter_map(|rule_id| self.rule_map.get(*rule_id))
+            .filter(|program| {
+                EffectiveStatus::of(&program.metadata, now)
+                    .skip_reason()
+                    .is_none()
+            })
+            .collect()
+    }
+
+    /// Rule IDs of a ruleset in evaluation order
+    ///
+    /// Duplicates are dropped to avoid executing a rule twice. Rules with a
+    /// higher priority come first; rules of equal priority keep their order
+    /// in the ruleset.
+    pub(crate) fn ordered_rule_ids<'a>(&self, rules_str: &'a str) -> Vec<&'a str> {
+        let mut seen = std::collections::HashSet::new();
+        let mut rule_ids: Vec<&str> = rules_str
+            .split(',')
+            .filter(|rid| seen.insert(*rid))
+            .collect();
+        rule_ids.sort_by_key(|rule_id| {
+            let priority = self
+                .rule_map
+                .get(*rule_id)
+                .and_then(|program| program.metadata.custom.get("priority"))
+                .and_then(|priority| priority.parse::<i32>().ok())
+                .unwrap_or(0);
+            std::cmp::Reverse(priority)
+        });
+        rule_ids
+    }
+}
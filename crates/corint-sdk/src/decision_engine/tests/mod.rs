@@ -420,6 +420,85 @@ ruleset:
     assert!(matches!(response.result.signal, Some(Signal::Decline)));
 }
 
+#[tokio::test]
+async fn test_decide_resolves_reason_code() {
+    use crate::builder::DecisionEngineBuilder;
+
+    let rule_content = r#"
+pipeline:
+  id: reason_pipeline
+  name: Reason Pipeline
+  when:
+    event.type: reason_test
+  steps:
+  - include:
+      ruleset: reason_ruleset
+
+---
+
+ruleset:
+  id: reason_ruleset
+  name: Reason Ruleset
+  rules: []
+  conclusion:
+  - when: risk_score > 50
+    signal: decline
+    reason_code: HIGH_RISK
+  - default: true
+    signal: approve
+"#;
+
+    let catalog = r#"
+reason_codes:
+  default_locale: en
+  codes:
+    - code: HIGH_RISK
+      messages:
+        en:
+          customer: We could not approve this request.
+          internal: Risk score above decline threshold
+        es: No pudimos aprobar esta solicitud.
+"#;
+
+    let engine = DecisionEngineBuilder::new()
+        .add_rule_content("reason_pipeline", rule_content)
+        .add_reason_catalog_content("default", catalog)
+        .build()
+        .await
+        .unwrap();
+
+    let mut event_data = HashMap::new();
+    event_data.insert("type".to_string(), Value::String("reason_test".to_string()));
+    event_data.insert("risk_score".to_string(), Value::Number(75.0));
+
+    let request = DecisionRequest::new(event_data.clone()).with_locale("es-MX");
+    let response = engine.decide(request).await.unwrap();
+
+    assert_eq!(response.result.reason_code.as_deref(), Some("HIGH_RISK"));
+    let reason = response.reason.unwrap();
+    assert_eq!(reason.locale, "es");
+    assert_eq!(reason.message, "No pudimos aprobar esta solicitud.");
+    assert_eq!(reason.internal_message, None);
+
+    // Approvals carry no reason code
+    event_data.insert("risk_score".to_string(), Value::Number(10.0));
+    let response = engine.decide(DecisionRequest::new(event_data)).await.unwrap();
+    assert!(response.result.reason_code.is_none());
+    assert!(response.reason.is_none());
+
+    // Referencing a code missing from the catalog fails at build time
+    let result = DecisionEngineBuilder::new()
+        .add_rule_content("reason_pipeline", rule_content)
+        .build()
+        .await;
+    assert!(matches!(
+        result,
+        Err(crate::error::SdkError::CompileError(
+            corint_compiler::error::CompileError::UnknownReasonCode { .. }
+        ))
+    ));
+}
+
 #[tokio::test]
 async fn test_builder_config_options() {
     use crate::builder::DecisionEngineBuilder;
@@ -546,3 +625,2662 @@ ruleset:
     );
     assert!(matches!(response.result.signal, Some(Signal::Decline)));
 }
+
+#[tokio::test]
+async fn test_decide_masks_pii_in_trace() {
+    use crate::builder::DecisionEngineBuilder;
+
+    let rule_content = r#"
+pipeline:
+  id: masking_pipeline
+  name: Masking Pipeline
+  when:
+    event.type: masking_test
+  steps:
+  - include:
+      ruleset: masking_ruleset
+
+---
+
+rule:
+  id: blocked_email
+  name: Blocked Email
+  when:
+    conditions:
+    - event.user.email == "fraud@example.com"
+  score: 100
+
+---
+
+ruleset:
+  id: masking_ruleset
+  rules:
+  - blocked_email
+  conclusion:
+  - default: true
+    signal: approve
+"#;
+
+    let policy = r#"
+masking_policy:
+  version: "1"
+  rules:
+    - field: email
+      strategy: redact
+"#;
+
+    let engine = DecisionEngineBuilder::new()
+        .add_rule_content("masking_pipeline", rule_content)
+        .add_masking_policy_content("default", policy)
+        .build()
+        .await
+        .unwrap();
+
+    let mut user = HashMap::new();
+    user.insert(
+        "email".to_string(),
+        Value::String("alice@example.com".to_string()),
+    );
+    let mut event_data = HashMap::new();
+    event_data.insert("type".to_string(), Value::String("masking_test".to_string()));
+    event_data.insert("user".to_string(), Value::Object(user));
+
+    let response = engine
+        .decide(DecisionRequest::new(event_data).with_trace())
+        .await
+        .unwrap();
+
+    let trace = serde_json::to_string(&response.trace.unwrap()).unwrap();
+    assert!(!trace.contains("alice@example.com"));
+    assert!(trace.contains("[REDACTED]"));
+}
+
+#[tokio::test]
+async fn test_decide_prefetches_step_features() {
+    use crate::builder::DecisionEngineBuilder;
+    use corint_runtime::feature::{FeatureDefinition, FeatureExecutor, InMemoryCounterStore};
+    use std::sync::Arc;
+
+    let rule_content = r#"
+pipeline:
+  id: login_pipeline
+  name: Login Pipeline
+  when:
+    event.type: login
+  steps:
+  - include:
+      ruleset: login_ruleset
+
+---
+
+rule:
+  id: repeated_failures
+  name: Repeated Failures
+  when:
+    conditions:
+    - features.login_failures_1h > 2
+  score: 60
+
+---
+
+rule:
+  id: failures_with_retries
+  name: Failures With Retries
+  when:
+    conditions:
+    - features.login_failures_1h > 0
+    - features.login_retries_1h > 0
+  score: 10
+
+---
+
+ruleset:
+  id: login_ruleset
+  rules:
+  - repeated_failures
+  - failures_with_retries
+  conclusion:
+  - default: true
+    signal: approve
+"#;
+
+    let features: Vec<FeatureDefinition> = serde_yaml::from_str(
+        r#"
+- name: login_failures_1h
+  type: counter
+  datasource: counters
+  counter: login_failures
+  dimension_value: "${event.user_id}"
+  window: 1h
+- name: login_retries_1h
+  type: counter
+  datasource: counters
+  counter: login_retries
+  dimension_value: "${event.user_id}"
+  window: 1h
+"#,
+    )
+    .unwrap();
+
+    let store = Arc::new(InMemoryCounterStore::new());
+    let mut feature_executor = FeatureExecutor::new();
+    feature_executor.add_counter_store("counters", store);
+    feature_executor.register_features(features).unwrap();
+    for _ in 0..3 {
+        feature_executor
+            .increment_counter("counters", "login_failures", "u1", 1.0)
+            .await
+            .unwrap();
+    }
+
+    let engine = DecisionEngineBuilder::new()
+        .add_rule_content("login_pipeline", rule_content)
+        .with_feature_executor(Arc::new(feature_executor))
+        .build()
+        .await
+        .unwrap();
+
+    let mut event_data = HashMap::new();
+    event_data.insert("type".to_string(), Value::String("login".to_string()));
+    event_data.insert("user_id".to_string(), Value::String("u1".to_string()));
+
+    let response = engine
+        .decide(DecisionRequest::new(event_data).with_trace())
+        .await
+        .unwrap();
+    assert_eq!(response.result.score, 60);
+
+    // Both features were fetched once for the step, before its rules ran
+    let pipeline = response.trace.unwrap().pipeline.unwrap();
+    let mut fetched: Vec<(&str, &str)> = pipeline
+        .features
+        .iter()
+        .map(|f| (f.feature.as_str(), f.status.as_str()))
+        .collect();
+    fetched.sort();
+    assert_eq!(
+        fetched,
+        vec![("login_failures_1h", "ok"), ("login_retries_1h", "ok")]
+    );
+}
+
+#[tokio::test]
+async fn test_decide_with_forced_pipeline_and_schedules() {
+    use crate::builder::DecisionEngineBuilder;
+    use corint_core::ast::Signal;
+
+    let rule_content = r#"
+pipeline:
+  id: dormant_pipeline
+  name: Dormant Account Pipeline
+  when:
+    event.type: dormant_review
+  entry: review
+  steps:
+    - step:
+        id: review
+        name: Review
+        type: ruleset
+        ruleset: dormant_ruleset
+
+---
+
+ruleset:
+  id: dormant_ruleset
+  name: Dormant Ruleset
+  rules: []
+  conclusion:
+  - when: days_inactive > 180
+    signal: review
+  - default: true
+    signal: approve
+"#;
+
+    let registry = r#"
+registry:
+  - pipeline: dormant_pipeline
+    when: event.type == "manual_review"
+
+schedules:
+  - id: dormant_review
+    pipeline: dormant_pipeline
+    schedule: "0 2 * * *"
+    entities:
+      datasource: accounts_db
+      entity: accounts
+"#;
+
+    let engine = DecisionEngineBuilder::new()
+        .add_rule_content("dormant_pipeline", rule_content)
+        .with_registry_content(registry)
+        .build()
+        .await
+        .unwrap();
+
+    assert_eq!(engine.schedules().len(), 1);
+    assert_eq!(engine.schedules()[0].id, "dormant_review");
+
+    // Scheduled runs tag each entity with the schedule ID as its event type
+    let mut event_data = HashMap::new();
+    event_data.insert("type".to_string(), Value::String("dormant_review".to_string()));
+    event_data.insert("days_inactive".to_string(), Value::Number(200.0));
+
+    // Without a forced pipeline no registry entry matches
+    let response = engine
+        .decide(DecisionRequest::new(event_data.clone()))
+        .await
+        .unwrap();
+    assert_ne!(response.result.signal, Some(Signal::Review));
+
+    let response = engine
+        .decide(DecisionRequest::new(event_data).with_pipeline("dormant_pipeline"))
+        .await
+        .unwrap();
+    assert_eq!(response.result.signal, Some(Signal::Review));
+
+    // Entity queries need datasources
+    assert!(engine.run_schedule("dormant_review").await.is_err());
+    assert!(engine.run_schedule("missing").await.is_err());
+}
+
+#[tokio::test]
+async fn test_decide_skips_rules_outside_effective_window() {
+    use crate::builder::DecisionEngineBuilder;
+    use corint_core::ast::Signal;
+    use corint_runtime::FixedClock;
+    use std::sync::Arc;
+
+    let rule_content = r#"
+pipeline:
+  id: promo_pipeline
+  name: Promo Pipeline
+  when:
+    event.type: checkout
+  steps:
+  - include:
+      ruleset: promo_ruleset
+
+---
+
+rule:
+  id: holiday_coupon_abuse
+  name: Holiday Coupon Abuse
+  effective_from: "2026-12-01T00:00:00Z"
+  effective_until: "2027-01-01T00:00:00Z"
+  when:
+    conditions:
+    - event.coupon_count > 3
+  score: 80
+
+---
+
+rule:
+  id: high_amount
+  name: High Amount
+  when:
+    conditions:
+    - event.amount > 1000
+  score: 10
+
+---
+
+ruleset:
+  id: promo_ruleset
+  rules:
+  - holiday_coupon_abuse
+  - high_amount
+  conclusion:
+  - when: total_score >= 80
+    signal: decline
+  - default: true
+    signal: approve
+"#;
+
+    let mut event_data = HashMap::new();
+    event_data.insert("type".to_string(), Value::String("checkout".to_string()));
+    event_data.insert("coupon_count".to_string(), Value::Number(5.0));
+    event_data.insert("amount".to_string(), Value::Number(50.0));
+
+    let decide_at = |now: &str| {
+        let now = chrono::DateTime::parse_from_rfc3339(now)
+            .unwrap()
+            .with_timezone(&chrono::Utc);
+        let event_data = event_data.clone();
+        async move {
+            let engine = DecisionEngineBuilder::new()
+                .add_rule_content("promo_pipeline", rule_content)
+                .with_clock(Arc::new(FixedClock::new(now)))
+                .build()
+                .await
+                .unwrap();
+            engine
+                .decide(DecisionRequest::new(event_data).with_trace())
+                .await
+                .unwrap()
+        }
+    };
+
+    let response = decide_at("2026-12-24T12:00:00Z").await;
+    assert_eq!(response.result.score, 80);
+    assert!(matches!(response.result.signal, Some(Signal::Decline)));
+
+    let response = decide_at("2027-01-02T00:00:00Z").await;
+    assert_eq!(response.result.score, 0);
+    assert!(matches!(response.result.signal, Some(Signal::Approve)));
+
+    let trace = response.trace.unwrap();
+    let rules = &trace.pipeline.unwrap().rulesets[0].rules;
+    let skipped = rules
+        .iter()
+        .find(|rule| rule.rule_id == "holiday_coupon_abuse")
+        .unwrap();
+    assert_eq!(skipped.skipped.as_deref(), Some("expired"));
+    assert!(!skipped.triggered);
+    let evaluated = rules.iter().find(|rule| rule.rule_id == "high_amount").unwrap();
+    assert_eq!(evaluated.skipped, None);
+}
+
+#[tokio::test]
+async fn test_decide_with_decision_table() {
+    use crate::builder::DecisionEngineBuilder;
+    use corint_core::ast::Signal;
+
+    let rule_content = r#"
+pipeline:
+  id: shipping_pipeline
+  name: Shipping Pipeline
+  when:
+    event.type: order
+  steps:
+  - include:
+      ruleset: shipping_ruleset
+
+---
+
+decision_table:
+  id: shipping_risk
+  name: Shipping Risk
+  hit_policy: first
+  inputs:
+    - event.country
+    - event.amount
+  rows:
+    - id: sanctioned
+      cells: ['in ["KP", "IR"]', -]
+      score: 100
+    - id: large_foreign
+      cells: ['!= "US"', '> 1000']
+      score: 60
+    - id: foreign
+      cells: ['!= "US"', -]
+      score: 20
+
+---
+
+ruleset:
+  id: shipping_ruleset
+  rules:
+  - shipping_risk
+  conclusion:
+  - when: total_score >= 60
+    signal: review
+  - default: true
+    signal: approve
+"#;
+
+    let engine = DecisionEngineBuilder::new()
+        .add_rule_content("shipping_pipeline", rule_content)
+        .build()
+        .await
+        .unwrap();
+    let decide = |country: &str, amount: f64| {
+        let mut event_data = HashMap::new();
+        event_data.insert("type".to_string(), Value::String("order".to_string()));
+        event_data.insert("country".to_string(), Value::String(country.to_string()));
+        event_data.insert("amount".to_string(), Value::Number(amount));
+        engine.decide(DecisionRequest::new(event_data).with_trace())
+    };
+
+    // Only the first matching row applies
+    let response = decide("FR", 5000.0).await.unwrap();
+    assert_eq!(response.result.score, 60);
+    assert!(matches!(response.result.signal, Some(Signal::Review)));
+    assert_eq!(
+        response.result.triggered_rules,
+        vec!["shipping_risk.large_foreign".to_string()]
+    );
+    let trace = response.trace.unwrap();
+    let table = &trace.pipeline.unwrap().rulesets[0].rules[0];
+    assert_eq!(table.rule_id, "shipping_risk");
+    assert!(table.triggered);
+
+    let response = decide("US", 5000.0).await.unwrap();
+    assert_eq!(response.result.score, 0);
+    assert!(matches!(response.result.signal, Some(Signal::Approve)));
+}
+
+#[tokio::test]
+async fn test_record_outcome() {
+    use crate::builder::DecisionEngineBuilder;
+    use corint_runtime::{InMemoryOutcomeStore, OutcomeLabel, OutcomeStore};
+    use std::sync::Arc;
+
+    let rule_content = r#"
+pipeline:
+  id: outcome_pipeline
+  name: Outcome Pipeline
+  when:
+    event.type: payment
+  steps:
+  - include:
+      ruleset: outcome_ruleset
+
+---
+
+ruleset:
+  id: outcome_ruleset
+  rules: []
+  conclusion:
+  - default: true
+    signal: approve
+"#;
+
+    let store = Arc::new(InMemoryOutcomeStore::new());
+    let engine = DecisionEngineBuilder::new()
+        .add_rule_content("outcome_pipeline", rule_content)
+        .with_outcome_store(store.clone())
+        .build()
+        .await
+        .unwrap();
+
+    let mut metadata = HashMap::new();
+    metadata.insert("case_id".to_string(), Value::String("CB-1042".to_string()));
+    engine
+        .record_outcome("req_1", OutcomeLabel::Chargeback, metadata)
+        .await
+        .unwrap();
+
+    let outcome = store.get_outcome("req_1").await.unwrap().unwrap();
+    assert_eq!(outcome.label, OutcomeLabel::Chargeback);
+    assert_eq!(
+        outcome.metadata.get("case_id"),
+        Some(&Value::String("CB-1042".to_string()))
+    );
+
+    assert!(engine
+        .record_outcome("", OutcomeLabel::Legitimate, HashMap::new())
+        .await
+        .is_err());
+
+    let engine = DecisionEngineBuilder::new()
+        .add_rule_content("outcome_pipeline", rule_content)
+        .build()
+        .await
+        .unwrap();
+    assert!(engine
+        .record_outcome("req_1", OutcomeLabel::Legitimate, HashMap::new())
+        .await
+        .is_err());
+}
+
+#[tokio::test]
+async fn test_champion_challenger_experiment() {
+    use crate::builder::DecisionEngineBuilder;
+    use corint_core::ast::Signal;
+    use corint_runtime::{ExperimentStore, InMemoryExperimentStore, InMemoryStorage};
+    use std::sync::Arc;
+
+    let champion = r#"
+pipeline:
+  id: payment_pipeline
+  name: Payment Pipeline
+  when:
+    event.type: payment
+  steps:
+  - include:
+      ruleset: payment_ruleset
+
+---
+
+ruleset:
+  id: payment_ruleset
+  rules: []
+  conclusion:
+  - default: true
+    signal: approve
+"#;
+
+    let challenger = r#"
+pipeline:
+  id: payment_pipeline_v2
+  name: Payment Pipeline v2
+  when:
+    event.type: payment
+  steps:
+  - include:
+      ruleset: payment_ruleset_v2
+
+---
+
+ruleset:
+  id: payment_ruleset_v2
+  rules: []
+  conclusion:
+  - when: amount > 100
+    signal: decline
+  - default: true
+    signal: approve
+"#;
+
+    let registry = |traffic_percent: u32| {
+        format!(
+            r#"
+registry:
+  - pipeline: payment_pipeline
+    when: event.type == "payment"
+    experiment:
+      id: payment_v2_trial
+      challenger: payment_pipeline_v2
+      traffic_percent: {}
+      key: event.user.id
+"#,
+            traffic_percent
+        )
+    };
+
+    let payment = |user_id: Option<&str>, amount: f64| {
+        let mut event_data = HashMap::new();
+        event_data.insert("type".to_string(), Value::String("payment".to_string()));
+        event_data.insert("amount".to_string(), Value::Number(amount));
+        if let Some(user_id) = user_id {
+            let mut user = HashMap::new();
+            user.insert("id".to_string(), Value::String(user_id.to_string()));
+            event_data.insert("user".to_string(), Value::Object(user));
+        }
+        DecisionRequest::new(event_data)
+    };
+
+    let store = Arc::new(InMemoryExperimentStore::new());
+    let storage = Arc::new(InMemoryStorage::new());
+    let engine = DecisionEngineBuilder::new()
+        .add_rule_content("payment_pipeline", champion)
+        .add_rule_content("payment_pipeline_v2", challenger)
+        .with_registry_content(registry(100))
+        .with_experiment_store(store.clone())
+        .with_event_storage(storage.clone())
+        .build()
+        .await
+        .unwrap();
+
+    // The champion's decision is returned; both are recorded
+    let response = engine.decide(payment(Some("u1"), 500.0)).await.unwrap();
+    assert_eq!(response.pipeline_id.as_deref(), Some("payment_pipeline"));
+    assert_eq!(response.result.signal, Some(Signal::Approve));
+    engine.decide(payment(Some("u2"), 50.0)).await.unwrap();
+
+    // Events without the key field are not part of the experiment
+    engine.decide(payment(None, 500.0)).await.unwrap();
+
+    let records = store.experiment_records("payment_v2_trial").await.unwrap();
+    assert_eq!(records.len(), 2);
+    assert_eq!(records[0].request_id, response.request_id);
+    assert_eq!(records[0].champion.decision.as_deref(), Some("approve"));
+    assert_eq!(records[0].challenger.pipeline_id, "payment_pipeline_v2");
+    assert_eq!(records[0].challenger.decision.as_deref(), Some("decline"));
+    assert!(records[1].agrees());
+
+    // Challenger decisions are not recorded as events
+    assert_eq!(storage.len(), 3);
+
+    let summary = engine.experiment_summary("payment_v2_trial").await.unwrap();
+    assert_eq!(summary.requests, 2);
+    assert_eq!(summary.agreed, 1);
+
+    // Without traffic the challenger is never evaluated
+    let store = Arc::new(InMemoryExperimentStore::new());
+    let engine = DecisionEngineBuilder::new()
+        .add_rule_content("payment_pipeline", champion)
+        .add_rule_content("payment_pipeline_v2", challenger)
+        .with_registry_content(registry(0))
+        .with_experiment_store(store.clone())
+        .build()
+        .await
+        .unwrap();
+    engine.decide(payment(Some("u1"), 500.0)).await.unwrap();
+    assert!(store
+        .experiment_records("payment_v2_trial")
+        .await
+        .unwrap()
+        .is_empty());
+}
+
+#[tokio::test]
+async fn test_records_decided_events() {
+    use crate::builder::DecisionEngineBuilder;
+    use corint_runtime::{EventFilter, FixedClock, InMemoryStorage, Storage};
+    use std::sync::Arc;
+
+    let rule_content = r#"
+pipeline:
+  id: history_pipeline
+  name: History Pipeline
+  when:
+    event.type: payment
+  steps:
+  - include:
+      ruleset: history_ruleset
+
+---
+
+ruleset:
+  id: history_ruleset
+  rules: []
+  conclusion:
+  - default: true
+    signal: approve
+"#;
+
+    let now = chrono::DateTime::parse_from_rfc3339("2026-03-01T12:00:00Z")
+        .unwrap()
+        .with_timezone(&chrono::Utc);
+    let storage = Arc::new(InMemoryStorage::new());
+    let engine = DecisionEngineBuilder::new()
+        .add_rule_content("history_pipeline", rule_content)
+        .with_clock(Arc::new(FixedClock::new(now)))
+        .with_event_storage(storage.clone())
+        .build()
+        .await
+        .unwrap();
+
+    for user in ["u1", "u1", "u2"] {
+        let mut event_data = HashMap::new();
+        event_data.insert("type".to_string(), Value::String("payment".to_string()));
+        event_data.insert("user_id".to_string(), Value::String(user.to_string()));
+        engine.decide(DecisionRequest::new(event_data)).await.unwrap();
+    }
+
+    let filter =
+        EventFilter::new().with_field_filter("user_id".to_string(), Value::String("u1".to_string()));
+    let events = storage
+        .query_events((now.timestamp(), now.timestamp() + 1), Some(filter))
+        .await
+        .unwrap();
+    assert_eq!(events.len(), 2);
+    assert_eq!(storage.len(), 3);
+}
+
+#[tokio::test]
+async fn test_entity_key() {
+    use crate::builder::DecisionEngineBuilder;
+
+    let rule_content = r#"
+pipeline:
+  id: entity_pipeline
+  name: Entity Pipeline
+  when:
+    event.type: payment
+  steps:
+  - include:
+      ruleset: entity_ruleset
+
+---
+
+ruleset:
+  id: entity_ruleset
+  rules: []
+  conclusion:
+  - default: true
+    signal: approve
+"#;
+
+    let entities = r#"
+entities:
+  - name: card_merchant
+    key: "${event.card_hash}:${event.merchant_id}"
+    normalize: [trim, lowercase]
+"#;
+
+    let engine = DecisionEngineBuilder::new()
+        .add_rule_content("entity_pipeline", rule_content)
+        .add_entity_definition_content("entities", entities)
+        .build()
+        .await
+        .unwrap();
+
+    let mut event = HashMap::new();
+    event.insert("card_hash".to_string(), Value::String(" CH_9F2 ".to_string()));
+    event.insert("merchant_id".to_string(), Value::String("MERCH_1".to_string()));
+    assert_eq!(
+        engine.entity_key("card_merchant", &event).unwrap(),
+        "ch_9f2:merch_1"
+    );
+
+    event.remove("merchant_id");
+    assert!(engine.entity_key("card_merchant", &event).is_err());
+    assert!(engine.entity_key("device", &event).is_err());
+}
+
+#[tokio::test]
+async fn test_decide_record_and_replay() {
+    use crate::builder::DecisionEngineBuilder;
+    use corint_runtime::{RecordReplayConfig, Recording};
+
+    let yaml_content = r#"
+pipeline:
+  id: replay_pipeline
+  name: Replay Pipeline
+  when:
+    event.type: test
+  steps:
+  - include:
+      ruleset: replay_ruleset
+
+---
+
+ruleset:
+  id: replay_ruleset
+  name: Replay Ruleset
+  rules: []
+  conclusion:
+  - default: true
+    signal: approve
+"#;
+    let dir = std::env::temp_dir().join(format!("corint_recordings_{}", std::process::id()));
+    let rule_file = dir.join("replay_pipeline.yaml");
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(&rule_file, yaml_content).unwrap();
+
+    let mut event_data = HashMap::new();
+    event_data.insert("type".to_string(), Value::String("test".to_string()));
+    let request = || {
+        DecisionRequest::new(event_data.clone())
+            .with_metadata("request_id".to_string(), "req_replay_1".to_string())
+    };
+
+    // Record mode writes one recording per request
+    let recorder = DecisionEngineBuilder::new()
+        .add_rule_file(&rule_file)
+        .with_record_replay(RecordReplayConfig::record(&dir))
+        .build()
+        .await
+        .unwrap();
+    recorder.decide(request()).await.unwrap();
+
+    let path = dir.join("req_replay_1.json");
+    let recording = Recording::load(&path).unwrap();
+    assert_eq!(recording.request_id, "req_replay_1");
+
+    // Replay mode reads it back for the same request ID
+    let replayer = DecisionEngineBuilder::new()
+        .add_rule_file(&rule_file)
+        .with_record_replay(RecordReplayConfig::replay(&dir))
+        .build()
+        .await
+        .unwrap();
+    let response = replayer.decide(request()).await.unwrap();
+    assert_eq!(response.request_id, "req_replay_1");
+
+    // Replaying a request that was never recorded fails
+    let unknown = DecisionRequest::new(event_data.clone())
+        .with_metadata("request_id".to_string(), "req_unknown".to_string());
+    assert!(replayer.decide(unknown).await.is_err());
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[tokio::test]
+async fn test_decide_looks_up_lists_as_of() {
+    use crate::builder::DecisionEngineBuilder;
+    use corint_runtime::lists::{ListBackend, ListService, MemoryBackend};
+    use std::sync::Arc;
+
+    let rule_content = r#"
+pipeline:
+  id: login_pipeline
+  name: Login Pipeline
+  when:
+    event.type: login
+  steps:
+  - include:
+      ruleset: login_ruleset
+
+---
+
+rule:
+  id: blocked_user
+  name: Blocked User
+  when:
+    conditions:
+    - event.user_id in list.blocked_users
+  score: 100
+
+---
+
+ruleset:
+  id: login_ruleset
+  rules:
+  - blocked_user
+"#;
+
+    let mut backends: HashMap<String, Box<dyn ListBackend>> = HashMap::new();
+    backends.insert("blocked_users".to_string(), Box::new(MemoryBackend::new()));
+    let lists = Arc::new(ListService::new_with_backends(backends));
+    let engine = DecisionEngineBuilder::new()
+        .add_rule_content("login_pipeline", rule_content)
+        .with_list_service(lists.clone())
+        .build()
+        .await
+        .unwrap();
+
+    let event_time = chrono::Utc::now();
+    tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+    lists
+        .add("blocked_users", Value::String("u1".to_string()))
+        .await
+        .unwrap();
+
+    let mut event_data = HashMap::new();
+    event_data.insert("type".to_string(), Value::String("login".to_string()));
+    event_data.insert("user_id".to_string(), Value::String("u1".to_string()));
+
+    let response = engine
+        .decide(DecisionRequest::new(event_data.clone()).with_trace())
+        .await
+        .unwrap();
+    assert_eq!(response.result.score, 100);
+    let trace = response.trace.unwrap().pipeline.unwrap();
+    assert_eq!(trace.lists.len(), 1);
+    assert_eq!((trace.lists[0].version, trace.lists[0].as_of), (1, None));
+
+    // Listed after the event: a backtest at the event time does not match
+    let response = engine
+        .decide(
+            DecisionRequest::new(event_data)
+                .with_trace()
+                .with_as_of(event_time),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.result.score, 0);
+    let trace = response.trace.unwrap().pipeline.unwrap();
+    assert_eq!(trace.lists[0].list_id, "blocked_users");
+    assert_eq!(trace.lists[0].version, 0);
+    assert_eq!(trace.lists[0].as_of, Some(event_time));
+}
+
+#[tokio::test]
+async fn test_decide_audits_list_hits() {
+    use crate::builder::DecisionEngineBuilder;
+    use corint_runtime::lists::{ListBackend, ListService, MemoryBackend};
+    use std::sync::Arc;
+
+    let rule_content = r#"
+pipeline:
+  id: payment_pipeline
+  name: Payment Pipeline
+  when:
+    event.type: payment
+  steps:
+  - include:
+      ruleset: payment_ruleset
+
+---
+
+rule:
+  id: sanctioned_payee
+  name: Sanctioned Payee
+  when:
+    conditions:
+    - event.payee in list.sanctions
+  score: 100
+
+---
+
+ruleset:
+  id: payment_ruleset
+  rules:
+  - sanctioned_payee
+"#;
+
+    let mut sanctions = MemoryBackend::new();
+    sanctions
+        .add("sanctions", Value::String("acme".to_string()))
+        .await
+        .unwrap();
+    let mut backends: HashMap<String, Box<dyn ListBackend>> = HashMap::new();
+    backends.insert("sanctions".to_string(), Box::new(sanctions));
+    let engine = DecisionEngineBuilder::new()
+        .add_rule_content("payment_pipeline", rule_content)
+        .with_list_service(Arc::new(ListService::new_with_backends(backends)))
+        .with_list_hit_audit(true)
+        .build()
+        .await
+        .unwrap();
+
+    let decide = |payee: &str| {
+        let mut event_data = HashMap::new();
+        event_data.insert("type".to_string(), Value::String("payment".to_string()));
+        event_data.insert("payee".to_string(), Value::String(payee.to_string()));
+        engine.decide(DecisionRequest::new(event_data))
+    };
+
+    let response = decide("acme").await.unwrap();
+    assert_eq!(response.list_hits.len(), 1);
+    let hit = &response.list_hits[0];
+    assert_eq!(hit.list_id, "sanctions");
+    assert_eq!(hit.value, Value::String("acme".to_string()));
+    assert_eq!((hit.source_type.as_str(), hit.source_id.as_str()), ("rule", "sanctioned_payee"));
+    assert!(!hit.negated);
+
+    assert!(decide("globex").await.unwrap().list_hits.is_empty());
+
+    let metrics = engine.metrics();
+    let counter = |name: &str| metrics.labeled_counter(name, &[("list_id", "sanctions")]).get();
+    assert_eq!(counter("list_lookups_total"), 2);
+    assert_eq!(counter("list_hits_total"), 1);
+    assert_eq!(counter("list_misses_total"), 1);
+}
+
+#[tokio::test]
+async fn test_decided_groups_skip_costly_conditions() {
+    use crate::builder::DecisionEngineBuilder;
+    use corint_runtime::lists::{ListBackend, ListService, MemoryBackend};
+    use std::sync::Arc;
+
+    let rule_content = r#"
+pipeline:
+  id: payment_pipeline
+  name: Payment Pipeline
+  when:
+    event.type: payment
+  steps:
+  - include:
+      ruleset: payment_ruleset
+
+---
+
+rule:
+  id: large_sanctioned_payment
+  name: Large Sanctioned Payment
+  when:
+    all:
+    - event.amount > 1000
+    - event.payee in list.sanctions
+  score: 100
+
+---
+
+rule:
+  id: trusted_or_sanctioned
+  name: Trusted Or Sanctioned
+  when:
+    any:
+    - event.trusted == true
+    - event.payee in list.sanctions
+  score: 10
+
+---
+
+rule:
+  id: legacy_large_sanctioned_payment
+  name: Legacy Large Sanctioned Payment
+  when:
+    conditions:
+    - event.amount > 1000
+    - event.payee in list.sanctions
+  score: 1
+
+---
+
+ruleset:
+  id: payment_ruleset
+  rules:
+  - large_sanctioned_payment
+  - trusted_or_sanctioned
+  - legacy_large_sanctioned_payment
+"#;
+
+    let mut sanctions = MemoryBackend::new();
+    sanctions
+        .add("sanctions", Value::String("acme".to_string()))
+        .await
+        .unwrap();
+    let mut backends: HashMap<String, Box<dyn ListBackend>> = HashMap::new();
+    backends.insert("sanctions".to_string(), Box::new(sanctions));
+    let engine = DecisionEngineBuilder::new()
+        .add_rule_content("payment_pipeline", rule_content)
+        .with_list_service(Arc::new(ListService::new_with_backends(backends)))
+        .build()
+        .await
+        .unwrap();
+
+    let decide = |amount: f64, trusted: bool| {
+        let mut event_data = HashMap::new();
+        event_data.insert("type".to_string(), Value::String("payment".to_string()));
+        event_data.insert("payee".to_string(), Value::String("acme".to_string()));
+        event_data.insert("amount".to_string(), Value::Number(amount));
+        event_data.insert("trusted".to_string(), Value::Bool(trusted));
+        engine.decide(DecisionRequest::new(event_data))
+    };
+    let lookups = || {
+        engine
+            .metrics()
+            .labeled_counter("list_lookups_total", &[("list_id", "sanctions")])
+            .get()
+    };
+
+    // The amount decides both "all" groups and the trust flag the "any" group
+    let response = decide(10.0, true).await.unwrap();
+    assert_eq!(response.result.score, 10);
+    assert_eq!(lookups(), 0);
+
+    let response = decide(5000.0, false).await.unwrap();
+    assert_eq!(response.result.score, 111);
+    assert_eq!(lookups(), 1);
+}
+
+#[tokio::test]
+async fn test_decide_degrades_on_timeout() {
+    use crate::builder::DecisionEngineBuilder;
+    use corint_runtime::TimeoutScope;
+    use std::time::Duration;
+
+    let rule_content = r#"
+pipeline:
+  id: payment_pipeline
+  name: Payment Pipeline
+  when:
+    event.type: payment
+  steps:
+  - include:
+      ruleset: payment_ruleset
+
+---
+
+rule:
+  id: high_amount
+  name: High Amount
+  when:
+    conditions:
+    - event.amount > 1000
+  score: 100
+
+---
+
+ruleset:
+  id: payment_ruleset
+  rules:
+  - high_amount
+"#;
+
+    let engine = DecisionEngineBuilder::new()
+        .add_rule_content("payment_pipeline", rule_content)
+        .build()
+        .await
+        .unwrap();
+
+    let request = || {
+        let mut event_data = HashMap::new();
+        event_data.insert("type".to_string(), Value::String("payment".to_string()));
+        event_data.insert("amount".to_string(), Value::Number(5000.0));
+        DecisionRequest::new(event_data).with_trace()
+    };
+
+    let response = engine.decide(request()).await.unwrap();
+    assert!(!response.degraded);
+    assert!(response.timeouts.is_empty());
+    assert_eq!(response.result.score, 100);
+
+    // Out of time before the pipeline starts, so no rule is evaluated
+    let response = engine
+        .decide(request().with_timeout(Duration::ZERO))
+        .await
+        .unwrap();
+    assert!(response.degraded);
+    assert_eq!(response.result.score, 0);
+    assert_eq!(response.timeouts.len(), 1);
+    assert_eq!(response.timeouts[0].scope, TimeoutScope::Decision);
+    let trace = response.trace.unwrap().pipeline.unwrap();
+    assert_eq!(trace.timeouts, response.timeouts);
+}
+
+#[tokio::test]
+async fn test_replay_decision_from_captured_inputs() {
+    use crate::builder::DecisionEngineBuilder;
+    use corint_runtime::feature::{FeatureDefinition, FeatureExecutor, InMemoryCounterStore};
+    use std::sync::Arc;
+
+    let rule_content = r#"
+pipeline:
+  id: login_pipeline
+  name: Login Pipeline
+  when:
+    event.type: login
+  steps:
+  - include:
+      ruleset: login_ruleset
+
+---
+
+rule:
+  id: repeated_failures
+  name: Repeated Failures
+  when:
+    conditions:
+    - features.login_failures_1h > 2
+  score: 60
+
+---
+
+rule:
+  id: failures_with_retries
+  name: Failures With Retries
+  when:
+    conditions:
+    - features.login_retries_1h > 0
+  score: 10
+
+---
+
+ruleset:
+  id: login_ruleset
+  rules:
+  - repeated_failures
+  - failures_with_retries
+  conclusion:
+  - when: total_score >= 70
+    signal: decline
+  - default: true
+    signal: approve
+"#;
+
+    let features: Vec<FeatureDefinition> = serde_yaml::from_str(
+        r#"
+- name: login_failures_1h
+  type: counter
+  datasource: counters
+  counter: login_failures
+  dimension_value: "${event.user_id}"
+  window: 1h
+- name: login_retries_1h
+  type: counter
+  datasource: counters
+  counter: login_retries
+  dimension_value: "${event.user_id}"
+  window: 1h
+"#,
+    )
+    .unwrap();
+
+    let store = Arc::new(InMemoryCounterStore::new());
+    let mut feature_executor = FeatureExecutor::new();
+    feature_executor.add_counter_store("counters", store);
+    feature_executor.register_features(features).unwrap();
+    let feature_executor = Arc::new(feature_executor);
+    for _ in 0..3 {
+        feature_executor
+            .increment_counter("counters", "login_failures", "u1", 1.0)
+            .await
+            .unwrap();
+    }
+
+    let engine = DecisionEngineBuilder::new()
+        .add_rule_content("login_pipeline", rule_content)
+        .with_feature_executor(Arc::clone(&feature_executor))
+        .with_input_capture(true)
+        .build()
+        .await
+        .unwrap();
+
+    let mut event_data = HashMap::new();
+    event_data.insert("type".to_string(), Value::String("login".to_string()));
+    event_data.insert("user_id".to_string(), Value::String("u1".to_string()));
+    let request = DecisionRequest::new(event_data).with_trace();
+
+    let original = engine.decide(request.clone()).await.unwrap();
+    assert_eq!(original.result.score, 60);
+    let inputs = original.trace.unwrap().inputs.unwrap();
+    assert_eq!(inputs.request_id, original.request_id);
+    assert_eq!(inputs.features["login_failures_1h"], Value::Number(3.0));
+
+    // The counters have moved on since: a live decision now differs
+    feature_executor
+        .increment_counter("counters", "login_retries", "u1", 1.0)
+        .await
+        .unwrap();
+    let live = engine.decide(request).await.unwrap();
+    assert_eq!(live.result.score, 70);
+
+    let replayed = engine.replay_decision(inputs.clone()).await.unwrap();
+    assert_eq!(replayed.request_id, original.request_id);
+    assert_eq!(replayed.result.score, original.result.score);
+    assert_eq!(replayed.result.signal, original.result.signal);
+    assert_eq!(replayed.result.triggered_rules, original.result.triggered_rules);
+
+    // Inputs that were not captured are not fetched live
+    let mut partial = inputs;
+    partial.features.remove("login_retries_1h");
+    assert!(engine.replay_decision(partial).await.is_err());
+}
+
+#[tokio::test]
+async fn test_ruleset_score_aggregation() {
+    use crate::builder::DecisionEngineBuilder;
+    use corint_core::ast::Signal;
+
+    let rule_content = r#"
+pipeline:
+  id: payment_pipeline
+  name: Payment Pipeline
+  when:
+    event.type: payment
+  steps:
+  - include:
+      ruleset: payment_ruleset
+
+---
+
+rule:
+  id: large_amount
+  name: Large Amount
+  when:
+    conditions:
+    - event.amount > 1000
+  score: 60
+
+---
+
+rule:
+  id: new_account
+  name: New Account
+  when:
+    conditions:
+    - event.account_age_days < 7
+  score: 50
+
+---
+
+ruleset:
+  id: payment_ruleset
+  rules:
+  - large_amount
+  - new_account
+  scoring:
+    strategy: weighted_sum
+    weights:
+      large_amount: 0.5
+    max: 200
+    normalize: true
+  conclusion:
+  - when: total_score >= 50
+    signal: decline
+  - default: true
+    signal: approve
+"#;
+
+    let engine = DecisionEngineBuilder::new()
+        .add_rule_content("payment_pipeline", rule_content)
+        .build()
+        .await
+        .unwrap();
+
+    let mut event_data = HashMap::new();
+    event_data.insert("type".to_string(), Value::String("payment".to_string()));
+    event_data.insert("amount".to_string(), Value::Number(5000.0));
+    event_data.insert("account_age_days".to_string(), Value::Number(2.0));
+
+    // 60 * 0.5 + 50 = 80 out of 200, normalized to 40; summed it would be 110
+    let response = engine.decide(DecisionRequest::new(event_data)).await.unwrap();
+    assert_eq!(response.result.score, 40);
+    assert_eq!(response.result.signal, Some(Signal::Approve));
+    assert_eq!(response.result.triggered_rules.len(), 2);
+}
+
+#[tokio::test]
+async fn test_ruleset_priority_and_early_stop() {
+    use crate::builder::DecisionEngineBuilder;
+    use corint_core::ast::Signal;
+
+    let rule_content = r#"
+pipeline:
+  id: payment_pipeline
+  name: Payment Pipeline
+  when:
+    event.type: payment
+  steps:
+  - include:
+      ruleset: payment_ruleset
+
+---
+
+rule:
+  id: device_history
+  name: Device History
+  when:
+    conditions:
+    - event.device_age_days < 1
+  score: 50
+
+---
+
+rule:
+  id: blocked_country
+  name: Blocked Country
+  priority: 10
+  when:
+    conditions:
+    - event.country == "XX"
+  score: 100
+
+---
+
+ruleset:
+  id: payment_ruleset
+  rules:
+  - device_history
+  - blocked_country
+  stop_on_score: 100
+  conclusion:
+  - when: total_score >= 100
+    signal: decline
+  - default: true
+    signal: approve
+"#;
+
+    let engine = DecisionEngineBuilder::new()
+        .add_rule_content("payment_pipeline", rule_content)
+        .build()
+        .await
+        .unwrap();
+
+    let event = |country: &str| {
+        let mut event_data = HashMap::new();
+        event_data.insert("type".to_string(), Value::String("payment".to_string()));
+        event_data.insert("country".to_string(), Value::String(country.to_string()));
+        event_data.insert("device_age_days".to_string(), Value::Number(0.0));
+        event_data
+    };
+
+    // The higher priority rule runs first and settles the ruleset
+    let response = engine.decide(DecisionRequest::new(event("XX"))).await.unwrap();
+    assert_eq!(response.result.score, 100);
+    assert_eq!(response.result.signal, Some(Signal::Decline));
+    assert_eq!(response.result.triggered_rules, vec!["blocked_country"]);
+
+    // Otherwise every rule is evaluated
+    let response = engine.decide(DecisionRequest::new(event("FR"))).await.unwrap();
+    assert_eq!(response.result.score, 50);
+    assert_eq!(response.result.signal, Some(Signal::Approve));
+    assert_eq!(response.result.triggered_rules, vec!["device_history"]);
+}
+
+#[tokio::test]
+async fn test_step_when_guard_with_else_branch() {
+    use crate::builder::DecisionEngineBuilder;
+
+    let rule_content = r#"
+pipeline:
+  id: onboarding_pipeline
+  name: Onboarding Pipeline
+  when:
+    event.type: onboarding
+  entry: documents
+  steps:
+    - step:
+        id: documents
+        name: Document Check
+        type: ruleset
+        ruleset: document_ruleset
+        when: event.kyc_verified != true
+        else:
+          skip_to: score
+        next: liveness
+    - step:
+        id: liveness
+        name: Liveness Check
+        type: ruleset
+        ruleset: liveness_ruleset
+        next: score
+    - step:
+        id: score
+        name: Score
+        type: ruleset
+        ruleset: score_ruleset
+
+---
+
+rule:
+  id: missing_document
+  name: Missing Document
+  when:
+    conditions:
+    - event.document_count < 1
+  score: 60
+
+---
+
+rule:
+  id: failed_liveness
+  name: Failed Liveness
+  when:
+    conditions:
+    - event.liveness_score < 0.5
+  score: 40
+
+---
+
+rule:
+  id: disposable_email
+  name: Disposable Email
+  when:
+    conditions:
+    - event.email_disposable == true
+  score: 30
+
+---
+
+ruleset:
+  id: document_ruleset
+  rules:
+  - missing_document
+  conclusion:
+  - default: true
+    signal: pass
+
+---
+
+ruleset:
+  id: liveness_ruleset
+  rules:
+  - failed_liveness
+  conclusion:
+  - default: true
+    signal: pass
+
+---
+
+ruleset:
+  id: score_ruleset
+  rules:
+  - disposable_email
+  conclusion:
+  - default: true
+    signal: approve
+"#;
+
+    let engine = DecisionEngineBuilder::new()
+        .add_rule_content("onboarding_pipeline", rule_content)
+        .build()
+        .await
+        .unwrap();
+
+    let event = |kyc_verified: bool| {
+        let mut event_data = HashMap::new();
+        event_data.insert("type".to_string(), Value::String("onboarding".to_string()));
+        event_data.insert("kyc_verified".to_string(), Value::Bool(kyc_verified));
+        event_data.insert("document_count".to_string(), Value::Number(0.0));
+        event_data.insert("liveness_score".to_string(), Value::Number(0.2));
+        event_data.insert("email_disposable".to_string(), Value::Bool(true));
+        event_data
+    };
+
+    let response = engine.decide(DecisionRequest::new(event(false))).await.unwrap();
+    let mut triggered = response.result.triggered_rules.clone();
+    triggered.sort();
+    assert_eq!(
+        triggered,
+        vec!["disposable_email", "failed_liveness", "missing_document"]
+    );
+
+    // Verified customers skip the document and liveness checks
+    let response = engine.decide(DecisionRequest::new(event(true))).await.unwrap();
+    assert_eq!(response.result.triggered_rules, vec!["disposable_email"]);
+    assert_eq!(response.result.score, 30);
+}
+
+#[tokio::test]
+async fn test_emit_step_dispatched_after_decision() {
+    use crate::builder::DecisionEngineBuilder;
+    use corint_core::ast::{EmitKind, Signal};
+    use corint_runtime::service::MockMqClient;
+    use corint_runtime::{EmitDispatcher, MqSink};
+    use std::sync::Arc;
+
+    let rule_content = r#"
+pipeline:
+  id: payment_pipeline
+  name: Payment Pipeline
+  when:
+    event.type: payment
+  entry: score
+  steps:
+    - step:
+        id: score
+        name: Score
+        type: ruleset
+        ruleset: payment_ruleset
+        next: publish
+    - step:
+        id: publish
+        name: Publish Decision
+        type: emit
+        emit: kafka
+        target: risk_decisions
+        payload:
+          user_id: event.user_id
+        next: open_case
+    - step:
+        id: open_case
+        name: Open Case
+        type: emit
+        emit: case
+        target: fraud_review
+        payload:
+          amount: event.amount
+        signals: [decline]
+
+---
+
+rule:
+  id: large_amount
+  name: Large Amount
+  when:
+    conditions:
+    - event.amount > 1000
+  score: 100
+
+---
+
+ruleset:
+  id: payment_ruleset
+  rules:
+  - large_amount
+  conclusion:
+  - when: total_score >= 100
+    signal: decline
+  - default: true
+    signal: approve
+"#;
+
+    let mq = Arc::new(MockMqClient::new());
+    let dispatcher = Arc::new(
+        EmitDispatcher::new().with_sink(EmitKind::Kafka, Arc::new(MqSink::new(mq.clone()))),
+    );
+    let engine = DecisionEngineBuilder::new()
+        .add_rule_content("payment_pipeline", rule_content)
+        .with_emit_dispatcher(dispatcher.clone())
+        .build()
+        .await
+        .unwrap();
+
+    let event = |amount: f64| {
+        let mut event_data = HashMap::new();
+        event_data.insert("type".to_string(), Value::String("payment".to_string()));
+        event_data.insert("user_id".to_string(), Value::String("u1".to_string()));
+        event_data.insert("amount".to_string(), Value::Number(amount));
+        event_data
+    };
+
+    let approved = engine.decide(DecisionRequest::new(event(50.0))).await.unwrap();
+    let declined = engine.decide(DecisionRequest::new(event(5000.0))).await.unwrap();
+    assert_eq!(declined.result.signal, Some(Signal::Decline));
+    dispatcher.wait_idle().await;
+
+    // Every decision is published, with the final decision attached
+    let published = mq.get_published_messages();
+    assert_eq!(published.len(), 2);
+    assert!(published.iter().all(|message| message.topic == "risk_decisions"));
+    let declined_message = published
+        .iter()
+        .find(|message| message.key.as_deref() == Some(declined.request_id.as_str()))
+        .unwrap();
+    let Value::Object(message) = &declined_message.value else {
+        panic!("expected an object message");
+    };
+    assert_eq!(message.get("score"), Some(&Value::Number(100.0)));
+    assert!(published
+        .iter()
+        .any(|message| message.key.as_deref() == Some(approved.request_id.as_str())));
+
+    // Only the declined payment opens a case, dead-lettered without a case sink
+    let dead_letters = dispatcher.dead_letters();
+    assert_eq!(dead_letters.len(), 1);
+    assert_eq!(dead_letters[0].emission.step_id, "open_case");
+    assert_eq!(dead_letters[0].emission.request_id, declined.request_id);
+    assert_eq!(
+        dead_letters[0].emission.payload.get("amount"),
+        Some(&Value::Number(5000.0))
+    );
+    assert_eq!(dispatcher.stats().delivered, 2);
+}
+
+#[tokio::test]
+async fn test_profile_report() {
+    use crate::builder::DecisionEngineBuilder;
+    use corint_runtime::ProfileScope;
+
+    let rule_content = r#"
+pipeline:
+  id: payment_pipeline
+  name: Payment Pipeline
+  when:
+    event.type: payment
+  steps:
+  - include:
+      ruleset: payment_ruleset
+
+---
+
+rule:
+  id: large_amount
+  name: Large Amount
+  when:
+    conditions:
+    - event.amount > 1000
+  score: 100
+
+---
+
+rule:
+  id: frequent_user
+  name: Frequent User
+  when:
+    conditions:
+    - features.txn_count_1h > 5
+  score: 50
+
+---
+
+ruleset:
+  id: payment_ruleset
+  rules:
+  - large_amount
+  - frequent_user
+  conclusion:
+  - when: total_score >= 100
+    signal: decline
+  - default: true
+    signal: approve
+"#;
+
+    let engine = DecisionEngineBuilder::new()
+        .add_rule_content("payment_pipeline", rule_content)
+        .with_profiling(true)
+        .build()
+        .await
+        .unwrap();
+
+    for amount in [50.0, 5000.0] {
+        let mut event_data = HashMap::new();
+        event_data.insert("type".to_string(), Value::String("payment".to_string()));
+        event_data.insert("amount".to_string(), Value::Number(amount));
+        let mut features = HashMap::new();
+        features.insert("txn_count_1h".to_string(), Value::Number(9.0));
+        engine
+            .decide(DecisionRequest::new(event_data).with_features(features))
+            .await
+            .unwrap();
+    }
+
+    let report = engine.profile_report();
+    for rule_id in ["large_amount", "frequent_user"] {
+        assert_eq!(report.get(ProfileScope::Rule, rule_id).unwrap().calls, 2);
+    }
+    assert_eq!(
+        report.get(ProfileScope::Ruleset, "payment_ruleset").unwrap().calls,
+        2
+    );
+    assert_eq!(
+        report.get(ProfileScope::Feature, "txn_count_1h").unwrap().calls,
+        2
+    );
+    assert_eq!(report.hottest(ProfileScope::Rule, 1).len(), 1);
+    assert!(report.get(ProfileScope::Instruction, "Compare").is_some());
+
+    engine.reset_profile();
+    assert!(engine.profile_report().entries.is_empty());
+}
+
+#[tokio::test]
+async fn test_decide_memoizes_lookups_per_request() {
+    use crate::builder::DecisionEngineBuilder;
+    use corint_runtime::feature::{FeatureDefinition, FeatureExecutor, InMemoryCounterStore};
+    use corint_runtime::lists::{ListBackend, ListService, MemoryBackend};
+    use std::sync::Arc;
+
+    // An early stop disables prefetching, so the rules load the feature themselves
+    let rule_content = r#"
+pipeline:
+  id: payment_pipeline
+  name: Payment Pipeline
+  when:
+    event.type: payment
+  steps:
+  - include:
+      ruleset: payment_ruleset
+
+---
+
+rule:
+  id: sanctioned_payee
+  name: Sanctioned Payee
+  when:
+    conditions:
+    - event.payee in list.sanctions
+  score: 10
+
+---
+
+rule:
+  id: sanctioned_frequent_payee
+  name: Sanctioned Frequent Payee
+  when:
+    conditions:
+    - event.payee in list.sanctions
+    - features.payments_1h > 2
+  score: 10
+
+---
+
+rule:
+  id: sanctioned_very_frequent_payee
+  name: Sanctioned Very Frequent Payee
+  when:
+    conditions:
+    - event.payee in list.sanctions
+    - features.payments_1h > 5
+  score: 10
+
+---
+
+ruleset:
+  id: payment_ruleset
+  stop_on_score: 1000
+  rules:
+  - sanctioned_payee
+  - sanctioned_frequent_payee
+  - sanctioned_very_frequent_payee
+"#;
+
+    let features: Vec<FeatureDefinition> = serde_yaml::from_str(
+        r#"
+- name: payments_1h
+  type: counter
+  datasource: counters
+  counter: payments
+  dimension_value: "${event.payee}"
+  window: 1h
+"#,
+    )
+    .unwrap();
+    let mut feature_executor = FeatureExecutor::new();
+    feature_executor.add_counter_store("counters", Arc::new(InMemoryCounterStore::new()));
+    feature_executor.register_features(features).unwrap();
+    for _ in 0..3 {
+        feature_executor
+            .increment_counter("counters", "payments", "acme", 1.0)
+            .await
+            .unwrap();
+    }
+
+    let mut sanctions = MemoryBackend::new();
+    sanctions
+        .add("sanctions", Value::String("acme".to_string()))
+        .await
+        .unwrap();
+    let mut backends: HashMap<String, Box<dyn ListBackend>> = HashMap::new();
+    backends.insert("sanctions".to_string(), Box::new(sanctions));
+    let engine = DecisionEngineBuilder::new()
+        .add_rule_content("payment_pipeline", rule_content)
+        .with_feature_executor(Arc::new(feature_executor))
+        .with_list_service(Arc::new(ListService::new_with_backends(backends)))
+        .with_list_hit_audit(true)
+        .build()
+        .await
+        .unwrap();
+
+    let decide = || {
+        let mut event_data = HashMap::new();
+        event_data.insert("type".to_string(), Value::String("payment".to_string()));
+        event_data.insert("payee".to_string(), Value::String("acme".to_string()));
+        engine.decide(DecisionRequest::new(event_data))
+    };
+
+    let response = decide().await.unwrap();
+    assert_eq!(response.result.score, 20);
+    // Every hit is still audited; the last rule's feature check is cheaper
+    // than its lookup and fails, so the lookup is skipped
+    assert_eq!(response.list_hits.len(), 2);
+
+    // The list is looked up and the feature computed once per request
+    let metrics = engine.metrics();
+    let memo_hits = |kind: &str| metrics.labeled_counter("memo_hits_total", &[("kind", kind)]).get();
+    let lookups = || {
+        metrics
+            .labeled_counter("list_lookups_total", &[("list_id", "sanctions")])
+            .get()
+    };
+    assert_eq!(lookups(), 1);
+    assert_eq!(memo_hits("list_lookup"), 1);
+    assert_eq!(memo_hits("feature"), 1);
+
+    decide().await.unwrap();
+    assert_eq!(lookups(), 2);
+    assert_eq!(memo_hits("list_lookup"), 2);
+}
+
+#[tokio::test]
+async fn test_three_valued_null_semantics() {
+    use crate::builder::DecisionEngineBuilder;
+    use corint_runtime::NullSemantics;
+
+    let rule_content = r#"
+pipeline:
+  id: signup_pipeline
+  name: Signup Pipeline
+  when:
+    event.type: signup
+  steps:
+  - include:
+      ruleset: signup_ruleset
+
+---
+
+rule:
+  id: not_adult
+  name: Not Adult
+  when:
+    not:
+    - event.age >= 18
+  score: 50
+
+---
+
+rule:
+  id: missing_age
+  name: Missing Age
+  when:
+    all:
+    - event.age is null
+  score: 10
+
+---
+
+rule:
+  id: minor_or_unknown
+  name: Minor Or Unknown
+  when:
+    all:
+    - coalesce(event.age, 0) < 18
+  score: 5
+
+---
+
+ruleset:
+  id: signup_ruleset
+  rules:
+  - not_adult
+  - missing_age
+  - minor_or_unknown
+"#;
+
+    let request = || {
+        let mut event_data = HashMap::new();
+        event_data.insert("type".to_string(), Value::String("signup".to_string()));
+        DecisionRequest::new(event_data).with_trace()
+    };
+
+    // A missing age compares false, so its negation triggers
+    let engine = DecisionEngineBuilder::new()
+        .add_rule_content("signup_pipeline", rule_content)
+        .build()
+        .await
+        .unwrap();
+    let response = engine.decide(request()).await.unwrap();
+    assert_eq!(response.result.score, 65);
+
+    // A missing age makes the comparison Unknown, negated or not
+    let engine = DecisionEngineBuilder::new()
+        .add_rule_content("signup_pipeline", rule_content)
+        .with_null_semantics(NullSemantics::ThreeValued)
+        .build()
+        .await
+        .unwrap();
+    let response = engine.decide(request()).await.unwrap();
+    assert_eq!(response.result.score, 15);
+
+    let pipeline = response.trace.unwrap().pipeline.unwrap();
+    let rules = &pipeline.rulesets[0].rules;
+    let not_adult = rules.iter().find(|rule| rule.rule_id == "not_adult").unwrap();
+    assert!(!not_adult.triggered);
+    let group = &not_adult.conditions[0];
+    assert!(group.unknown);
+    assert!(group.nested.as_ref().unwrap()[0].unknown);
+
+    let missing_age = rules.iter().find(|rule| rule.rule_id == "missing_age").unwrap();
+    assert!(missing_age.triggered);
+    assert!(!missing_age.conditions[0].unknown);
+    assert!(missing_age.conditions[0].result);
+}
+
+#[tokio::test]
+async fn test_tenant_quotas() {
+    use crate::builder::DecisionEngineBuilder;
+    use crate::error::SdkError;
+    use corint_runtime::{
+        RateLimit, RateLimitBehavior, RuntimeError, TenantQuota, TenantQuotaConfig,
+    };
+
+    let rule_content = r#"
+pipeline:
+  id: payment_pipeline
+  name: Payment Pipeline
+  when:
+    event.type: payment
+  steps:
+  - include:
+      ruleset: payment_ruleset
+
+---
+
+rule:
+  id: high_amount
+  name: High Amount
+  when:
+    all:
+    - event.amount > 1000
+  score: 50
+
+---
+
+ruleset:
+  id: payment_ruleset
+  rules:
+  - high_amount
+"#;
+
+    // One decision per tenant, then rejected until a token refills
+    let quota = TenantQuota::new()
+        .with_rate_limit(RateLimit::new(0.01).with_on_limit(RateLimitBehavior::Reject));
+    let engine = DecisionEngineBuilder::new()
+        .add_rule_content("payment_pipeline", rule_content)
+        .with_tenant_quotas(
+            TenantQuotaConfig::new()
+                .with_default(quota)
+                .with_tenant("acme", quota),
+        )
+        .build()
+        .await
+        .unwrap();
+
+    let request = |tenant: Option<&str>| {
+        let mut event_data = HashMap::new();
+        event_data.insert("type".to_string(), Value::String("payment".to_string()));
+        event_data.insert("amount".to_string(), Value::Number(5000.0));
+        let request = DecisionRequest::new(event_data);
+        match tenant {
+            Some(tenant) => request.with_tenant(tenant),
+            None => request,
+        }
+    };
+
+    // The pipeline, ruleset and rule of a decision are admitted once
+    let response = engine.decide(request(Some("acme"))).await.unwrap();
+    assert_eq!(response.result.score, 50);
+
+    let err = engine.decide(request(Some("acme"))).await.unwrap_err();
+    assert!(matches!(
+        err,
+        SdkError::RuntimeError(RuntimeError::RateLimited(_))
+    ));
+    assert_eq!(err.code(), "RATE_LIMITED");
+
+    // Other tenants and requests without a tenant are not affected
+    assert!(engine.decide(request(Some("globex"))).await.is_ok());
+    assert!(engine.decide(request(None)).await.is_ok());
+
+    let metrics = engine.metrics();
+    assert_eq!(
+        metrics
+            .labeled_counter("tenant_admitted_total", &[("tenant", "acme")])
+            .get(),
+        1
+    );
+    let stats = engine.tenant_stats();
+    assert_eq!(stats.len(), 2);
+    assert_eq!(stats[0].rate_limit.as_ref().unwrap().rejected, 1);
+}
+
+#[tokio::test]
+async fn test_idempotent_retries() {
+    use crate::builder::DecisionEngineBuilder;
+    use crate::config::IdempotencyConfig;
+    use corint_runtime::{Metrics, RateLimit, RateLimitBehavior, TenantQuota, TenantQuotaConfig};
+    use std::time::Duration;
+
+    let rule_content = r#"
+pipeline:
+  id: payment_pipeline
+  name: Payment Pipeline
+  when:
+    event.type: payment
+  steps:
+  - include:
+      ruleset: payment_ruleset
+
+---
+
+rule:
+  id: high_amount
+  name: High Amount
+  when:
+    all:
+    - event.amount > 1000
+  score: 50
+
+---
+
+ruleset:
+  id: payment_ruleset
+  rules:
+  - high_amount
+"#;
+
+    // One decision per tenant, so a retry that was decided again would fail
+    let quota = TenantQuota::new()
+        .with_rate_limit(RateLimit::new(0.01).with_on_limit(RateLimitBehavior::Reject));
+    let engine = DecisionEngineBuilder::new()
+        .add_rule_content("payment_pipeline", rule_content)
+        .with_tenant_quotas(TenantQuotaConfig::new().with_default(quota))
+        .with_idempotency(IdempotencyConfig::new(Duration::from_secs(60)))
+        .build()
+        .await
+        .unwrap();
+
+    let request = |tenant: &str, event_id: &str| {
+        let mut event_data = HashMap::new();
+        event_data.insert("type".to_string(), Value::String("payment".to_string()));
+        event_data.insert("amount".to_string(), Value::Number(5000.0));
+        DecisionRequest::new(event_data)
+            .with_tenant(tenant)
+            .with_metadata("event_id".to_string(), event_id.to_string())
+    };
+
+    let first = engine.decide(request("acme", "evt_1")).await.unwrap();
+    let retry = engine.decide(request("acme", "evt_1")).await.unwrap();
+    assert_eq!(retry.request_id, first.request_id);
+    assert_eq!(retry.result.score, 50);
+
+    // Event IDs are scoped to their tenant
+    let other = engine.decide(request("globex", "evt_1")).await.unwrap();
+    assert_ne!(other.request_id, first.request_id);
+
+    // A new event of the tenant is decided, and over its quota
+    assert!(engine.decide(request("acme", "evt_2")).await.is_err());
+
+    assert_eq!(engine.metrics().counter("idempotent_replays_total").get(), 1);
+}
+
+#[tokio::test]
+async fn test_shared_conditions_computed_once() {
+    use crate::builder::DecisionEngineBuilder;
+
+    let rule_content = r#"
+pipeline:
+  id: payment_pipeline
+  name: Payment Pipeline
+  when:
+    event.type: payment
+  steps:
+  - include:
+      ruleset: payment_ruleset
+
+---
+
+rule:
+  id: large_amount
+  name: Large Amount
+  when:
+    all:
+    - event.amount > 1000
+  score: 30
+
+---
+
+rule:
+  id: large_foreign_amount
+  name: Large Foreign Amount
+  when:
+    all:
+    - event.amount > 1000
+    - event.country != "US"
+  score: 40
+
+---
+
+ruleset:
+  id: payment_ruleset
+  rules:
+  - large_amount
+  - large_foreign_amount
+"#;
+
+    let engine = DecisionEngineBuilder::new()
+        .add_rule_content("payment_pipeline", rule_content)
+        .build()
+        .await
+        .unwrap();
+
+    let mut event_data = HashMap::new();
+    event_data.insert("type".to_string(), Value::String("payment".to_string()));
+    event_data.insert("amount".to_string(), Value::Number(5000.0));
+    event_data.insert("country".to_string(), Value::String("FR".to_string()));
+    let response = engine.decide(DecisionRequest::new(event_data)).await.unwrap();
+
+    assert_eq!(response.result.score, 70);
+    assert_eq!(response.result.triggered_rules.len(), 2);
+
+    // The second rule reuses the amount check of the first
+    let hits = engine
+        .metrics()
+        .labeled_counter("memo_hits_total", &[("kind", "slot")])
+        .get();
+    assert_eq!(hits, 1);
+}
+
+#[tokio::test]
+async fn test_pipeline_on_error_policy() {
+    use crate::builder::DecisionEngineBuilder;
+    use crate::error::SdkError;
+    use corint_core::ast::{ErrorPolicy, Signal};
+
+    let rule_content = |on_error: &str| {
+        format!(
+            r#"
+pipeline:
+  id: payment_pipeline
+  name: Payment Pipeline
+  when:
+    event.type: payment
+  entry: score
+  {}
+  steps:
+    - step:
+        id: score
+        name: Score
+        type: ruleset
+        ruleset: payment_ruleset
+
+---
+
+rule:
+  id: large_installment
+  name: Large Installment
+  when:
+    conditions:
+    - event.amount / event.installments > 500
+  score: 60
+
+---
+
+ruleset:
+  id: payment_ruleset
+  rules:
+  - large_installment
+  conclusion:
+  - default: true
+    signal: approve
+"#,
+            on_error
+        )
+    };
+    let build = |on_error: &str| {
+        DecisionEngineBuilder::new()
+            .add_rule_content("payment_pipeline", rule_content(on_error))
+            .build()
+    };
+
+    let mut event_data = HashMap::new();
+    event_data.insert("type".to_string(), Value::String("payment".to_string()));
+    event_data.insert("amount".to_string(), Value::Number(1200.0));
+    event_data.insert("installments".to_string(), Value::Number(0.0));
+    let request = || DecisionRequest::new(event_data.clone()).with_trace();
+
+    // Without a policy the error fails the request
+    let result = build("").await.unwrap().decide(request()).await;
+    assert!(matches!(result, Err(SdkError::RuntimeError(_))));
+
+    let engine = build("on_error: review").await.unwrap();
+    let response = engine.decide(request()).await.unwrap();
+    assert_eq!(response.pipeline_id.as_deref(), Some("payment_pipeline"));
+    assert_eq!(response.result.signal, Some(Signal::Review));
+    assert_eq!(response.result.score, 0);
+    assert!(response.error.unwrap().contains("Division by zero"));
+
+    let error = response.trace.unwrap().pipeline.unwrap().error.unwrap();
+    assert_eq!(error.policy, ErrorPolicy::Review);
+    assert_eq!(error.signal, Signal::Review);
+    assert_eq!(
+        engine
+            .metrics()
+            .labeled_counter(
+                "pipeline_errors_total",
+                &[("pipeline_id", "payment_pipeline"), ("policy", "review")]
+            )
+            .get(),
+        1
+    );
+
+    let on_error = "on_error: { policy: fail_open, signal: hold, reason_code: NO_SCORE }";
+    let response = build(on_error).await.unwrap().decide(request()).await.unwrap();
+    assert_eq!(response.result.signal, Some(Signal::Hold));
+    assert_eq!(response.result.reason_code.as_deref(), Some("NO_SCORE"));
+}
+
+#[tokio::test]
+async fn test_swap_programs() {
+    use crate::builder::DecisionEngineBuilder;
+    use std::sync::Arc;
+
+    let rule_content = |score: u32| {
+        format!(
+            r#"
+pipeline:
+  id: login_pipeline
+  name: Login Pipeline
+  when:
+    event.type: login
+  entry: score
+  steps:
+    - step:
+        id: score
+        name: Score
+        type: ruleset
+        ruleset: login_ruleset
+
+---
+
+rule:
+  id: new_device
+  name: New Device
+  when:
+    conditions:
+    - event.new_device == true
+  score: {}
+
+---
+
+ruleset:
+  id: login_ruleset
+  rules:
+  - new_device
+"#,
+            score
+        )
+    };
+
+    let engine = DecisionEngineBuilder::new()
+        .add_rule_content("login_pipeline", rule_content(40))
+        .build()
+        .await
+        .unwrap();
+    let loaded = engine.programs();
+
+    let mut event_data = HashMap::new();
+    event_data.insert("type".to_string(), Value::String("login".to_string()));
+    event_data.insert("new_device".to_string(), Value::Bool(true));
+    let response = engine.decide(DecisionRequest::new(event_data.clone())).await.unwrap();
+    assert_eq!(response.result.score, 40);
+
+    engine
+        .swap_programs(vec![("login_pipeline".to_string(), rule_content(70))])
+        .await
+        .unwrap();
+    let response = engine.decide(DecisionRequest::new(event_data.clone())).await.unwrap();
+    assert_eq!(response.result.score, 70);
+
+    // Programs loaded before the swap are left as they were
+    let swapped = engine.programs();
+    assert!(!Arc::ptr_eq(&loaded, &swapped));
+    assert_eq!(loaded.len(), swapped.len());
+
+    // Rules that fail to compile are not swapped in
+    let broken = ("broken".to_string(), "rule: [".to_string());
+    assert!(engine.swap_programs(vec![broken]).await.is_err());
+    assert!(Arc::ptr_eq(&swapped, &engine.programs()));
+    let response = engine.decide(DecisionRequest::new(event_data)).await.unwrap();
+    assert_eq!(response.result.score, 70);
+}
+
+#[tokio::test]
+async fn test_swap_programs_recompiles_changed_definitions() {
+    use crate::builder::DecisionEngineBuilder;
+
+    let rule_content = |score: u32| {
+        format!(
+            r#"
+pipeline:
+  id: login_pipeline
+  name: Login Pipeline
+  when:
+    event.type: login
+  entry: score
+  steps:
+    - step:
+        id: score
+        name: Score
+        type: ruleset
+        ruleset: login_ruleset
+
+---
+
+rule:
+  id: new_device
+  name: New Device
+  when:
+    conditions:
+    - event.new_device == true
+  score: {}
+
+---
+
+ruleset:
+  id: login_ruleset
+  rules:
+  - new_device
+"#,
+            score
+        )
+    };
+
+    let engine = DecisionEngineBuilder::new()
+        .add_rule_content("login_pipeline", rule_content(40))
+        .build()
+        .await
+        .unwrap();
+    assert_eq!(engine.compilation_cache_stats().misses, 3);
+
+    // Only the changed rule is compiled again
+    engine
+        .swap_programs(vec![("login_pipeline".to_string(), rule_content(70))])
+        .await
+        .unwrap();
+    let stats = engine.compilation_cache_stats();
+    assert_eq!((stats.hits, stats.misses), (2, 4));
+    // The program of the rule's previous version is dropped
+    assert_eq!(stats.entries, 3);
+
+    let mut event_data = HashMap::new();
+    event_data.insert("type".to_string(), Value::String("login".to_string()));
+    event_data.insert("new_device".to_string(), Value::Bool(true));
+    let response = engine.decide(DecisionRequest::new(event_data)).await.unwrap();
+    assert_eq!(response.result.score, 70);
+}
+
+#[tokio::test]
+async fn test_custom_step_types() {
+    use crate::builder::DecisionEngineBuilder;
+    use corint_compiler::StepPlugin;
+    use corint_core::ast::pipeline::{PipelineStep, StepDetails};
+    use corint_core::ir::Instruction;
+    use corint_runtime::{CustomCall, CustomStepHandler};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    /// Compiles `geo_velocity` steps into a call of the handler of the same name
+    struct GeoVelocityPlugin;
+
+    impl StepPlugin for GeoVelocityPlugin {
+        fn step_type(&self) -> &str {
+            "geo_velocity"
+        }
+
+        fn compile(&self, step: &PipelineStep) -> corint_compiler::Result<Vec<Instruction>> {
+            let StepDetails::Custom { fields } = &step.details else {
+                return Ok(Vec::new());
+            };
+            Ok(vec![
+                Instruction::LoadConst {
+                    value: Value::Number(fields["window_hours"].as_f64().unwrap_or_default()),
+                },
+                Instruction::CallCustom {
+                    handler: "geo_velocity".to_string(),
+                    step_id: step.id.clone(),
+                    args: vec!["window_hours".to_string()],
+                },
+                Instruction::Store {
+                    name: "geo_velocity".to_string(),
+                },
+            ])
+        }
+    }
+
+    /// Counts the geo velocity checks
+    #[derive(Default)]
+    struct GeoVelocity {
+        calls: AtomicUsize,
+    }
+
+    #[async_trait::async_trait]
+    impl CustomStepHandler for GeoVelocity {
+        async fn call(&self, call: CustomCall) -> corint_runtime::Result<Value> {
+            assert_eq!(call.arg("window_hours"), &Value::Number(24.0));
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            Ok(Value::Number(0.0))
+        }
+    }
+
+    let rule_content = |score: u32| {
+        format!(
+            r#"
+pipeline:
+  id: login_pipeline
+  name: Login Pipeline
+  when:
+    event.type: login
+  entry: velocity
+  steps:
+    - step:
+        id: velocity
+        name: Geo Velocity
+        type: geo_velocity
+        window_hours: 24
+        next: score
+    - step:
+        id: score
+        name: Score
+        type: ruleset
+        ruleset: login_ruleset
+
+---
+
+rule:
+  id: new_device
+  name: New Device
+  when:
+    conditions:
+    - event.new_device == true
+  score: {}
+
+---
+
+ruleset:
+  id: login_ruleset
+  rules:
+  - new_device
+"#,
+            score
+        )
+    };
+
+    // Steps of a type no plugin compiles are rejected
+    let result = DecisionEngineBuilder::new()
+        .add_rule_content("login_pipeline", rule_content(40))
+        .build()
+        .await;
+    assert!(result.is_err());
+
+    let geo_velocity = Arc::new(GeoVelocity::default());
+    let engine = DecisionEngineBuilder::new()
+        .add_rule_content("login_pipeline", rule_content(40))
+        .with_step_plugin(Arc::new(GeoVelocityPlugin))
+        .with_custom_step_handler("geo_velocity", geo_velocity.clone())
+        .build()
+        .await
+        .unwrap();
+    let decide = || {
+        let mut event_data = HashMap::new();
+        event_data.insert("type".to_string(), Value::String("login".to_string()));
+        event_data.insert("new_device".to_string(), Value::Bool(true));
+        engine.decide(DecisionRequest::new(event_data))
+    };
+    assert_eq!(decide().await.unwrap().result.score, 40);
+    assert_eq!(geo_velocity.calls.load(Ordering::SeqCst), 1);
+
+    // Swapped in programs are compiled with the same plugins
+    engine
+        .swap_programs(vec![("login_pipeline".to_string(), rule_content(70))])
+        .await
+        .unwrap();
+    assert_eq!(decide().await.unwrap().result.score, 70);
+    assert_eq!(geo_velocity.calls.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn test_reload_shared_engine() {
+    use crate::builder::DecisionEngineBuilder;
+    use corint_repository::RepositoryConfig;
+    use std::sync::Arc;
+
+    let rule_content = |score: u32| {
+        format!(
+            r#"
+pipeline:
+  id: login_pipeline
+  name: Login Pipeline
+  when:
+    event.type: login
+  entry: score
+  steps:
+    - step:
+        id: score
+        name: Score
+        type: ruleset
+        ruleset: login_ruleset
+
+---
+
+rule:
+  id: new_device
+  name: New Device
+  when:
+    conditions:
+    - event.new_device == true
+  score: {}
+
+---
+
+ruleset:
+  id: login_ruleset
+  rules:
+  - new_device
+"#,
+            score
+        )
+    };
+    let dir = std::env::temp_dir().join(format!("corint_reload_{}", std::process::id()));
+    let pipeline_file = dir.join("pipelines").join("login_pipeline.yaml");
+    std::fs::create_dir_all(pipeline_file.parent().unwrap()).unwrap();
+    std::fs::write(&pipeline_file, rule_content(40)).unwrap();
+
+    let engine = Arc::new(
+        DecisionEngineBuilder::new()
+            .with_repository(RepositoryConfig::file_system(dir.to_string_lossy()))
+            .build()
+            .await
+            .unwrap(),
+    );
+    let decide = || {
+        let mut event_data = HashMap::new();
+        event_data.insert("type".to_string(), Value::String("login".to_string()));
+        event_data.insert("new_device".to_string(), Value::Bool(true));
+        engine.decide(DecisionRequest::new(event_data))
+    };
+    assert_eq!(decide().await.unwrap().result.score, 40);
+
+    // Decisions are served while the engine reloads
+    std::fs::write(&pipeline_file, rule_content(70)).unwrap();
+    let (reloaded, response) = tokio::join!(engine.reload(), decide());
+    reloaded.unwrap();
+    assert!(matches!(response.unwrap().result.score, 40 | 70));
+    assert_eq!(decide().await.unwrap().result.score, 70);
+
+    // Content that fails to compile leaves the programs in place
+    let broken = rule_content(70).replace("entry: score", "entry: missing");
+    std::fs::write(&pipeline_file, broken).unwrap();
+    let loaded = engine.programs();
+    assert!(engine.reload().await.is_err());
+    assert!(Arc::ptr_eq(&loaded, &engine.programs()));
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_quantifier_binds_elements_without_shadowing_event_fields() {
+    use super::when_evaluator::WhenEvaluator;
+    use corint_core::ast::{Expression, Operator, QuantifierOp};
+
+    let item =
+        |price: f64| Value::Object(HashMap::from([("price".to_string(), Value::Number(price))]));
+    let mut event_data = HashMap::new();
+    event_data.insert(
+        "items".to_string(),
+        Value::Array(vec![item(10.0), item(500.0)]),
+    );
+    event_data.insert("item".to_string(), Value::String("gift_card".to_string()));
+
+    let collection = Expression::field_access(vec!["event".to_string(), "items".to_string()]);
+    let expensive = Expression::binary(
+        Expression::field_access(vec!["item".to_string(), "price".to_string()]),
+        Operator::Gt,
+        Expression::literal(Value::Number(100.0)),
+    );
+    assert!(WhenEvaluator::evaluate_quantifier(
+        QuantifierOp::AnyOf,
+        &collection,
+        "item",
+        &expensive,
+        &event_data
+    ));
+    assert!(!WhenEvaluator::evaluate_quantifier(
+        QuantifierOp::AllOf,
+        &collection,
+        "item",
+        &expensive,
+        &event_data
+    ));
+
+    // `event.item` still reads the event's own field
+    let gift_card = Expression::binary(
+        Expression::field_access(vec!["event".to_string(), "item".to_string()]),
+        Operator::Eq,
+        Expression::literal(Value::String("gift_card".to_string())),
+    );
+    assert!(WhenEvaluator::evaluate_quantifier(
+        QuantifierOp::AllOf,
+        &collection,
+        "item",
+        &gift_card,
+        &event_data
+    ));
+
+    // Arrays over the iteration limit never match
+    event_data.insert("items".to_string(), Value::Array(vec![item(500.0); 1001]));
+    assert!(!WhenEvaluator::evaluate_quantifier(
+        QuantifierOp::AnyOf,
+        &collection,
+        "item",
+        &expensive,
+        &event_data
+    ));
+}
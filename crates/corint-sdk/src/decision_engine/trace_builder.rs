@@ -2,6 +2,7 @@
 
 use super::when_evaluator::WhenEvaluator;
 use corint_core::ast::{Expression, WhenBlock};
+use corint_core::ir::Program;
 use corint_core::Value;
 use corint_runtime::{ConclusionTrace, ConditionTrace, StepTrace};
 use std::collections::HashMap;
@@ -298,6 +299,18 @@ fn expression_json_to_trace(
         }
     }
 
+    // Null checks are decided by the operand alone
+    if let Some(unary) = expr.get("Unary") {
+        let op = unary.get("op").and_then(|o| o.as_str()).unwrap_or("");
+        if op == "IsNull" || op == "IsNotNull" {
+            let operand = unary
+                .get("operand")
+                .and_then(|e| TraceBuilder::extract_value_from_expr_json(e, event_data))
+                .unwrap_or(Value::Null);
+            result = matches!(operand, Value::Null) == (op == "IsNull");
+        }
+    }
+
     let mut trace = ConditionTrace::new(expression, result);
     trace.left_value = left_value_for_display;
     trace.right_value = right_value_for_display;
@@ -719,6 +732,7 @@ pub(super) fn json_value_to_condition_trace(
                 result,
                 nested: None,
                 group_type: None,
+                unknown: false,
             }
         }
         _ => {
@@ -1082,7 +1096,127 @@ pub(super) fn create_rule_execution_record(
         rule_conditions: rule_conditions_json,
         conditions_json,
         condition_group_json,
+        skip_reason: None,
     }
 }
 
+/// Create a rule execution record for a rule that was skipped (e.g. outside its
+/// effective window, or after its ruleset stopped early)
+pub(super) fn create_skipped_rule_record(
+    request_id: &str,
+    ruleset_id: Option<&str>,
+    rule_id: &str,
+    rule_name: Option<&str>,
+    reason: &str,
+) -> corint_runtime::RuleExecutionRecord {
+    corint_runtime::RuleExecutionRecord {
+        request_id: request_id.to_string(),
+        ruleset_id: ruleset_id.map(|s| s.to_string()),
+        rule_id: rule_id.to_string(),
+        rule_name: rule_name.map(|s| s.to_string()),
+        triggered: false,
+        score: None,
+        execution_time_ms: None,
+        feature_values: None,
+        rule_conditions: None,
+        conditions_json: None,
+        condition_group_json: None,
+        skip_reason: Some(reason.to_string()),
+    }
+}
+
+/// Whether a rule program triggered, given the triggered rules after it ran
+///
+/// A decision table is triggered when one of its rows is, which are
+/// recorded as `<table>.<row>`.
+pub(super) fn rule_triggered(program: &Program, triggered_rules: &[String]) -> bool {
+    let id = &program.metadata.source_id;
+    if program.metadata.custom.contains_key("decision_table") {
+        let prefix = format!("{}.", id);
+        triggered_rules.iter().any(|rule| rule.starts_with(&prefix))
+    } else {
+        triggered_rules.contains(id)
+    }
+}
+
+/// Location of the first condition of a compiled rule, from its source map
+pub(super) fn rule_source(program: &Program) -> Option<String> {
+    program
+        .metadata
+        .source_map
+        .iter()
+        .filter(|span| span.line.is_some())
+        .min_by_key(|span| span.line)
+        .and_then(|span| span.location())
+}
+
+/// Set the location of each conditional branch of a conclusion trace
+///
+/// The ruleset program records the span of each branch condition in branch
+/// order, which is the order of the traces.
+pub(super) fn locate_conclusion(traces: &mut [ConclusionTrace], program: &Program) {
+    let mut spans = program.metadata.source_map.iter();
+    for trace in traces.iter_mut().filter(|trace| trace.condition != "default") {
+        let Some(span) = spans.next() else {
+            break;
+        };
+        trace.source = span.location();
+    }
+}
+
+/// Flag the conditions that were Unknown under three-valued null semantics
+///
+/// A comparison with a missing operand is Unknown, and groups combine their
+/// conditions with Kleene logic, so a group is only Unknown when its known
+/// conditions do not decide it. Unknown conditions are reported as not met.
+pub(super) fn mark_unknown_conditions(traces: &mut [ConditionTrace]) {
+    for trace in traces {
+        TraceBuilder::three_valued_result(trace);
+    }
+}
+
+/// Three-valued result of a condition trace, `None` for Unknown
+fn three_valued_result(trace: &mut ConditionTrace) -> Option<bool> {
+    let result = match trace.nested.as_mut() {
+        Some(nested) => {
+            let results: Vec<Option<bool>> = nested
+                .iter_mut()
+                .map(TraceBuilder::three_valued_result)
+                .collect();
+            let all = if results.contains(&Some(false)) {
+                Some(false)
+            } else if results.contains(&None) {
+                None
+            } else {
+                Some(true)
+            };
+            match trace.group_type.as_deref() {
+                Some("any") => {
+                    if results.contains(&Some(true)) {
+                        Some(true)
+                    } else if results.contains(&None) {
+                        None
+                    } else {
+                        Some(false)
+                    }
+                }
+                Some("not") => all.map(|all| !all),
+                _ => all,
+            }
+        }
+        None => {
+            let missing = |value: &Option<Value>| matches!(value, Some(Value::Null));
+            if missing(&trace.left_value) || missing(&trace.right_value) {
+                None
+            } else {
+                Some(trace.result)
+            }
+        }
+    };
+
+    trace.unknown = result.is_none();
+    trace.result = result.unwrap_or(false);
+    result
+}
+
 }
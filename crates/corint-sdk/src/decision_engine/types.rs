@@ -1,7 +1,9 @@
 //! Request/Response types for DecisionEngine
 
+use chrono::{DateTime, Utc};
+use corint_core::ast::ResolvedReason;
 use corint_core::Value;
-use corint_runtime::{ContextInput, DecisionResult, ExecutionTrace};
+use corint_runtime::{ContextInput, DecisionResult, ExecutionTrace, ListHit, TimeoutEvent};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
@@ -11,6 +13,24 @@ pub struct DecisionOptions {
     /// Enable detailed execution tracing
     #[serde(default)]
     pub enable_trace: bool,
+
+    /// Preferred locale for reason code messages (e.g., "en", "pt-BR")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub locale: Option<String>,
+
+    /// Execute this pipeline directly instead of routing through the registry
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pipeline: Option<String>,
+
+    /// Look lists up as they were at this time (e.g. the original event time
+    /// of a replayed or backtested decision) instead of now
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub as_of: Option<DateTime<Utc>>,
+
+    /// Make the decision within this many milliseconds, overriding the
+    /// engine's decision timeout
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_ms: Option<u64>,
 }
 
 /// Decision request (supports Phase 5 multi-namespace format)
@@ -46,6 +66,10 @@ pub struct DecisionRequest {
     /// Request options (including trace enablement)
     #[serde(default)]
     pub options: DecisionOptions,
+
+    /// Tenant the request is made for, whose quota it counts against
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tenant: Option<String>,
 }
 
 impl DecisionRequest {
@@ -60,6 +84,7 @@ impl DecisionRequest {
             vars: None,
             metadata: HashMap::new(),
             options: DecisionOptions::default(),
+            tenant: None,
         }
     }
 
@@ -69,6 +94,36 @@ impl DecisionRequest {
         self
     }
 
+    /// Set the preferred locale for reason code messages
+    pub fn with_locale(mut self, locale: impl Into<String>) -> Self {
+        self.options.locale = Some(locale.into());
+        self
+    }
+
+    /// Execute a specific pipeline, bypassing registry routing
+    pub fn with_pipeline(mut self, pipeline: impl Into<String>) -> Self {
+        self.options.pipeline = Some(pipeline.into());
+        self
+    }
+
+    /// Look lists up as they were at `as_of`
+    pub fn with_as_of(mut self, as_of: DateTime<Utc>) -> Self {
+        self.options.as_of = Some(as_of);
+        self
+    }
+
+    /// Make the decision within `timeout`, from partial results if need be
+    pub fn with_timeout(mut self, timeout: std::time::Duration) -> Self {
+        self.options.timeout_ms = Some(timeout.as_millis() as u64);
+        self
+    }
+
+    /// Make the request for a tenant, under the tenant's quota
+    pub fn with_tenant(mut self, tenant: impl Into<String>) -> Self {
+        self.tenant = Some(tenant.into());
+        self
+    }
+
     /// Add metadata
     pub fn with_metadata(mut self, key: String, value: String) -> Self {
         self.metadata.insert(key, value);
@@ -124,6 +179,9 @@ impl DecisionRequest {
         if let Some(vars) = &self.vars {
             input = input.with_vars(vars.clone());
         }
+        if let Some(tenant) = &self.tenant {
+            input = input.with_tenant(tenant.clone());
+        }
 
         input
     }
@@ -141,6 +199,10 @@ pub struct DecisionResponse {
     /// Decision result
     pub result: DecisionResult,
 
+    /// Reason code resolved against the reason catalog in the requested locale
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<ResolvedReason>,
+
     /// Processing time in milliseconds
     pub processing_time_ms: u64,
 
@@ -150,4 +212,20 @@ pub struct DecisionResponse {
     /// Execution trace (only present if enable_trace was set)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub trace: Option<ExecutionTrace>,
+
+    /// List lookups that found their value (only when list hit auditing is enabled)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub list_hits: Vec<ListHit>,
+
+    /// Whether a timeout cut the decision short, so it was made from partial results
+    #[serde(default)]
+    pub degraded: bool,
+
+    /// Scopes that ran out of time
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub timeouts: Vec<TimeoutEvent>,
+
+    /// Step failure decided by the pipeline's `on_error` policy instead of failing the request
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
 }
@@ -1,7 +1,10 @@
 //! When block and condition evaluation logic
 
-use corint_core::ast::{Condition, ConditionGroup, Expression, Operator, WhenBlock};
+use corint_core::ast::{
+    AggregateOp, Condition, ConditionGroup, Expression, Operator, QuantifierOp, WhenBlock,
+};
 use corint_core::Value;
+use corint_runtime::engine::pipeline_executor::DEFAULT_MAX_ITERATIONS;
 use corint_runtime::ConditionTrace;
 use std::collections::HashMap;
 
@@ -223,9 +226,158 @@ pub(super) fn evaluate_expression(expr: &Expression, event_data: &HashMap<String
             // Result access requires runtime context, not supported in this simple evaluator
             false
         }
+        Expression::Quantifier {
+            op,
+            collection,
+            binding,
+            predicate,
+        } => WhenEvaluator::evaluate_quantifier(*op, collection, binding, predicate, event_data),
+        Expression::Aggregate { .. } => {
+            WhenEvaluator::is_truthy(&WhenEvaluator::expression_to_value(expr, event_data))
+        }
     }
 }
 
+/// Evaluate an any_of/all_of quantifier by binding each array element in turn
+///
+/// Arrays longer than the runtime's default iteration limit do not match.
+pub(super) fn evaluate_quantifier(
+    op: QuantifierOp,
+    collection: &Expression,
+    binding: &str,
+    predicate: &Expression,
+    event_data: &HashMap<String, Value>,
+) -> bool {
+    let elements = match WhenEvaluator::expression_to_value(collection, event_data) {
+        Value::Array(arr) => arr,
+        _ => Vec::new(),
+    };
+
+    if elements.len() > DEFAULT_MAX_ITERATIONS {
+        tracing::warn!(
+            "{} over {} elements exceeds the iteration limit of {}, treating it as not matched",
+            op.name(),
+            elements.len(),
+            DEFAULT_MAX_ITERATIONS
+        );
+        return false;
+    }
+
+    let mut matches = elements.iter().map(|element| {
+        let bound = WhenEvaluator::bind_element(predicate, binding, element);
+        WhenEvaluator::evaluate_expression(&bound, event_data)
+    });
+
+    match op {
+        QuantifierOp::AnyOf => matches.any(|matched| matched),
+        QuantifierOp::AllOf => matches.all(|matched| matched),
+    }
+}
+
+/// Replace the references to a quantifier's binding in its predicate with
+/// the element they name
+///
+/// Only bare `<binding>` paths are the element: `event.<binding>` keeps
+/// reading the event, and nested quantifiers rebinding the same name keep
+/// their own scope.
+pub(super) fn bind_element(expr: &Expression, binding: &str, element: &Value) -> Expression {
+    let bind = |e: &Expression| Box::new(WhenEvaluator::bind_element(e, binding, element));
+    match expr {
+        Expression::FieldAccess(path) if path.first().map(String::as_str) == Some(binding) => {
+            let value = path[1..].iter().try_fold(element, |current, key| match current {
+                Value::Object(map) => map.get(key),
+                _ => None,
+            });
+            Expression::Literal(value.cloned().unwrap_or(Value::Null))
+        }
+        Expression::Binary { left, op, right } => Expression::Binary {
+            left: bind(left),
+            op: *op,
+            right: bind(right),
+        },
+        Expression::LogicalGroup { op, conditions } => Expression::LogicalGroup {
+            op: *op,
+            conditions: conditions
+                .iter()
+                .map(|c| WhenEvaluator::bind_element(c, binding, element))
+                .collect(),
+        },
+        Expression::Quantifier {
+            op,
+            collection,
+            binding: inner,
+            predicate,
+        } => Expression::Quantifier {
+            op: *op,
+            collection: bind(collection),
+            binding: inner.clone(),
+            predicate: if inner == binding {
+                predicate.clone()
+            } else {
+                bind(predicate)
+            },
+        },
+        Expression::Aggregate {
+            op,
+            collection,
+            path,
+        } => Expression::Aggregate {
+            op: *op,
+            collection: bind(collection),
+            path: path.clone(),
+        },
+        other => other.clone(),
+    }
+}
+
+/// Evaluate a count/sum/avg/min/max aggregate over an array field
+pub(super) fn evaluate_aggregate(
+    op: AggregateOp,
+    collection: &Expression,
+    path: &[String],
+    event_data: &HashMap<String, Value>,
+) -> Value {
+    let elements = match WhenEvaluator::expression_to_value(collection, event_data) {
+        Value::Array(arr) => arr,
+        _ => Vec::new(),
+    };
+
+    let values: Vec<Value> = elements
+        .into_iter()
+        .filter_map(|element| {
+            path.iter().try_fold(element, |current, key| match current {
+                Value::Object(mut map) => map.remove(key),
+                _ => None,
+            })
+        })
+        .filter(|value| !matches!(value, Value::Null))
+        .collect();
+
+    if op == AggregateOp::Count {
+        return Value::Number(values.len() as f64);
+    }
+
+    let numbers: Vec<f64> = values
+        .iter()
+        .filter_map(|value| match value {
+            Value::Number(n) => Some(*n),
+            _ => None,
+        })
+        .collect();
+
+    let result = match op {
+        AggregateOp::Sum => Some(numbers.iter().sum()),
+        AggregateOp::Avg if !numbers.is_empty() => {
+            Some(numbers.iter().sum::<f64>() / numbers.len() as f64)
+        }
+        AggregateOp::Min => numbers.iter().copied().reduce(f64::min),
+        AggregateOp::Max => numbers.iter().copied().reduce(f64::max),
+        _ => None,
+    };
+
+    result.map(Value::Number).unwrap_or(Value::Null)
+}
+
 /// Evaluate a binary expression
 pub(super) fn evaluate_binary_expression(
     left: &Expression,
@@ -308,6 +460,14 @@ pub(super) fn expression_to_value(expr: &Expression, event_data: &HashMap<String
             // Result access requires runtime context, not supported in this simple evaluator
             Value::Null
         }
+        Expression::Quantifier { .. } => {
+            Value::Bool(WhenEvaluator::evaluate_expression(expr, event_data))
+        }
+        Expression::Aggregate {
+            op,
+            collection,
+            path,
+        } => WhenEvaluator::evaluate_aggregate(*op, collection, path, event_data),
     }
 }
 
@@ -451,6 +611,30 @@ pub(super) fn expression_to_string(expr: &Expression) -> String {
                 None => format!("result.{}", field),
             }
         }
+        Expression::Quantifier {
+            op,
+            collection,
+            binding,
+            predicate,
+        } => format!(
+            "{}({}, {}, {})",
+            op.name(),
+            WhenEvaluator::expression_to_string(collection),
+            binding,
+            WhenEvaluator::expression_to_string(predicate)
+        ),
+        Expression::Aggregate {
+            op,
+            collection,
+            path,
+        } => {
+            let collection = WhenEvaluator::expression_to_string(collection);
+            if path.is_empty() {
+                format!("{}({})", op.name(), collection)
+            } else {
+                format!("{}({}[*].{})", op.name(), collection, path.join("."))
+            }
+        }
     }
 }
 
@@ -58,6 +58,36 @@ pub enum SdkError {
     GenericError(String),
 }
 
+impl SdkError {
+    /// Stable machine-readable code of this error
+    ///
+    /// Runtime errors keep the code of the [`corint_runtime::RuntimeError`]
+    /// they wrap.
+    pub fn code(&self) -> &'static str {
+        match self {
+            SdkError::ConfigError(_) | SdkError::Config(_) => "CONFIG_ERROR",
+            SdkError::ParseError(_) => "PARSE_ERROR",
+            SdkError::CompileError(_) => "COMPILE_ERROR",
+            SdkError::RuntimeError(e) => e.code(),
+            SdkError::IoError(_) => "IO_ERROR",
+            SdkError::InvalidRuleFile(_) => "INVALID_RULE_FILE",
+            SdkError::NotInitialized => "NOT_INITIALIZED",
+            SdkError::GenericError(_) => "SDK_ERROR",
+        }
+    }
+
+    /// Broad class of this error
+    ///
+    /// Errors loading rules or configuration are internal: the engine cannot
+    /// decide with them, whatever the request.
+    pub fn category(&self) -> corint_runtime::ErrorCategory {
+        match self {
+            SdkError::RuntimeError(e) => e.category(),
+            _ => corint_runtime::ErrorCategory::Internal,
+        }
+    }
+}
+
 /// Result type for SDK operations
 pub type Result<T> = std::result::Result<T, SdkError>;
 
@@ -100,6 +130,20 @@ mod tests {
         assert!(sdk_error.to_string().contains("File not found"));
     }
 
+    #[test]
+    fn test_error_codes() {
+        assert_eq!(SdkError::NotInitialized.code(), "NOT_INITIALIZED");
+        assert_eq!(SdkError::Config("bad".to_string()).code(), "CONFIG_ERROR");
+
+        let error: SdkError = corint_runtime::RuntimeError::DataSourceTimeout {
+            datasource: "clickhouse".to_string(),
+            message: "operation timed out".to_string(),
+        }
+        .into();
+        assert_eq!(error.code(), "DATASOURCE_TIMEOUT");
+        assert_eq!(error.category(), corint_runtime::ErrorCategory::Dependency);
+    }
+
     #[test]
     fn test_result_ok() {
         let result: Result<i32> = Ok(42);
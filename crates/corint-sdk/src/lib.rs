@@ -22,32 +22,48 @@ pub mod builder;
 pub mod config;
 pub mod decision_engine;
 pub mod error;
+pub mod repository_sync;
 pub mod score;
 pub mod validator;
 
 // Re-export main types
 pub use builder::DecisionEngineBuilder;
 pub use config::{
-    EngineConfig, LLMConfig, LLMProvider, ServiceConfig, ServiceType, StorageConfig, StorageType,
+    EngineConfig, IdempotencyConfig, LLMConfig, LLMProvider, ServiceConfig, ServiceType,
+    StorageConfig, StorageType,
+};
+pub use decision_engine::{
+    DecisionEngine, DecisionOptions, DecisionRequest, DecisionResponse, ProgramSet,
 };
-pub use decision_engine::{DecisionEngine, DecisionOptions, DecisionRequest, DecisionResponse};
 pub use error::{Result, SdkError};
 
+// Re-export repository sync types
+pub use repository_sync::{record_sync_metrics, spawn_repository_sync};
+
 // Re-export score normalization types
 pub use score::ScoreNormalizer;
 
 // Re-export validator types
 pub use validator::{
-    validate, validate_pipeline, validate_rule, validate_ruleset, Diagnostic, DiagnosticSeverity,
-    DocumentMetadata, DslType, DslValidator, ValidationResult,
+    validate, validate_pipeline, validate_references, validate_rule, validate_ruleset, Diagnostic,
+    DiagnosticSeverity, DocumentMetadata, DslType, DslValidator, ValidationResult,
 };
 
 // Re-export repository types for unified configuration
 pub use corint_repository::{
     ApiConfig, DataSourceConfig, FeatureDefinition, ListConfig, RepositoryConfig,
-    RepositoryContent, RepositoryLoader, RepositorySource,
+    RepositoryContent, RepositoryLoader, RepositorySource, SecretResolver, StaticSecretResolver,
 };
 
 // Re-export commonly used types from dependencies
-pub use corint_core::{ast::Signal, Value};
-pub use corint_runtime::{DecisionResult, MetricsCollector};
+pub use corint_compiler::{CompilationCacheStats, StepPlugin};
+pub use corint_core::{
+    ast::{EmitKind, Signal},
+    Value,
+};
+pub use corint_runtime::{
+    CapturedInputs, CustomCall, CustomStepHandler, DeadLetter, DecisionResult, EmitDispatcher, EmitSink, EmitStats, Emission,
+    ErrorCategory, Event, ExperimentRecord, ExperimentStore, InMemoryExperimentStore, InMemoryOutcomeStore, InMemoryStorage, MetricsCollector, OutcomeLabel,
+    NullSemantics, OutcomeRecord, OutcomeStore, ProfileEntry, ProfileReport, ProfileScope, Storage,
+    TenantQuota, TenantQuotaConfig, TenantStats,
+};
@@ -0,0 +1,118 @@
+//! Background repository sync reporting drift metrics
+//!
+//! Wraps [`corint_repository::spawn_sync`] so that every sync run is recorded
+//! in the engine's [`MetricsCollector`]:
+//!
+//! - `repository_sync_runs` / `repository_sync_failures`: completed and failed runs
+//! - `repository_drift_changed` / `repository_drift_removed`: drifted artifacts
+//! - `repository_artifacts_refreshed`: changed artifacts reloaded
+//! - `repository_sync_errors`: artifacts that could not be checked or reloaded
+//! - `repository_sync_duration`: histogram of run durations
+
+use corint_repository::{
+    spawn_sync, DriftKind, RepositoryError, SyncHandle, SyncReport, SyncableRepository,
+};
+use corint_runtime::{Metrics, MetricsCollector};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Record the result of one sync run
+pub fn record_sync_metrics(metrics: &dyn Metrics, result: Result<&SyncReport, &RepositoryError>) {
+    metrics.counter("repository_sync_runs").inc();
+    match result {
+        Ok(report) => {
+            metrics
+                .counter("repository_drift_changed")
+                .add(report.count(DriftKind::Changed) as u64);
+            metrics
+                .counter("repository_drift_removed")
+                .add(report.count(DriftKind::Removed) as u64);
+            metrics
+                .counter("repository_artifacts_refreshed")
+                .add(report.refreshed as u64);
+            metrics
+                .counter("repository_sync_errors")
+                .add(report.errors.len() as u64);
+            metrics.record_execution_time("repository_sync", report.elapsed);
+
+            for drift in &report.drifted {
+                tracing::info!(
+                    "Repository drift: {} '{}' {:?} ({:?} -> {:?})",
+                    drift.kind.as_str(),
+                    drift.identifier,
+                    drift.drift,
+                    drift.loaded_version,
+                    drift.remote_version
+                );
+            }
+        }
+        Err(e) => {
+            metrics.counter("repository_sync_failures").inc();
+            tracing::warn!("Repository sync failed: {}", e);
+        }
+    }
+}
+
+/// Sync a repository every `interval`, recording drift in `metrics`
+///
+/// The task stops when the returned handle is dropped.
+pub fn spawn_repository_sync<R>(
+    repo: Arc<R>,
+    interval: Duration,
+    metrics: Arc<MetricsCollector>,
+) -> SyncHandle
+where
+    R: SyncableRepository + ?Sized + 'static,
+{
+    spawn_sync(repo, interval, move |result| {
+        record_sync_metrics(metrics.as_ref(), result)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use corint_repository::{ArtifactDrift, BundleArtifactKind};
+
+    #[test]
+    fn test_record_sync_metrics() {
+        let metrics = MetricsCollector::new();
+        let report = SyncReport {
+            checked: 3,
+            drifted: vec![
+                ArtifactDrift {
+                    kind: BundleArtifactKind::Rule,
+                    identifier: "high_amount".to_string(),
+                    drift: DriftKind::Changed,
+                    loaded_version: Some("3".to_string()),
+                    remote_version: Some("4".to_string()),
+                },
+                ArtifactDrift {
+                    kind: BundleArtifactKind::Ruleset,
+                    identifier: "legacy".to_string(),
+                    drift: DriftKind::Removed,
+                    loaded_version: Some("1".to_string()),
+                    remote_version: None,
+                },
+            ],
+            refreshed: 1,
+            errors: vec![],
+            elapsed: Duration::from_millis(12),
+        };
+
+        record_sync_metrics(&metrics, Ok(&report));
+        record_sync_metrics(
+            &metrics,
+            Err(&RepositoryError::ApiError("timeout".to_string())),
+        );
+
+        assert_eq!(metrics.counter("repository_sync_runs").get(), 2);
+        assert_eq!(metrics.counter("repository_sync_failures").get(), 1);
+        assert_eq!(metrics.counter("repository_drift_changed").get(), 1);
+        assert_eq!(metrics.counter("repository_drift_removed").get(), 1);
+        assert_eq!(metrics.counter("repository_artifacts_refreshed").get(), 1);
+        assert!(metrics
+            .histogram_names()
+            .contains(&"repository_sync_duration".to_string()));
+    }
+}
@@ -34,6 +34,9 @@ pub use corint_compiler::{
     Diagnostic, DiagnosticSeverity, DocumentMetadata, DslType, DslValidator, ValidationResult,
 };
 
+use corint_compiler::{ConfigDefinitions, SemanticAnalyzer, SourceFile};
+use corint_repository::RepositoryContent;
+
 /// Convenience function to validate DSL content with auto-detection
 pub fn validate(content: &str) -> ValidationResult {
     let validator = DslValidator::new();
@@ -58,6 +61,40 @@ pub fn validate_pipeline(content: &str) -> ValidationResult {
     validator.validate(content, DslType::Pipeline)
 }
 
+/// Validate the references between the rules, rulesets and pipelines of
+/// repository content, and to its features, lists and APIs
+///
+/// Returns one diagnostic per undefined reference, with the ID of the
+/// definition file (its path for file system repositories) and the line.
+pub fn validate_references(content: &RepositoryContent) -> Vec<Diagnostic> {
+    let files: Vec<SourceFile> = content
+        .rules
+        .iter()
+        .chain(&content.rulesets)
+        .chain(&content.pipelines)
+        .map(|(id, yaml)| SourceFile::new(id, yaml))
+        .collect();
+
+    let mut configs = ConfigDefinitions::new();
+    configs.features = content
+        .feature_definitions
+        .iter()
+        .map(|feature| feature.name.clone())
+        .collect();
+    configs.lists = content
+        .list_configs
+        .iter()
+        .map(|list| list.id.clone())
+        .collect();
+    configs.apis = content
+        .api_configs
+        .iter()
+        .map(|api| (api.name.clone(), api.endpoints.keys().cloned().collect()))
+        .collect();
+
+    SemanticAnalyzer::new().analyze_references(&files, &configs)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -175,4 +212,41 @@ pipeline:
         assert!(result.valid, "Expected valid pipeline, got errors: {:?}", result.errors);
         assert_eq!(result.metadata.as_ref().unwrap().doc_type, DslType::Pipeline);
     }
+
+    #[test]
+    fn test_validate_references() {
+        let mut content = RepositoryContent::new();
+        content.add_rule(
+            "library/rules/high_amount.yaml",
+            "rule:\n  id: high_amount\n  name: High amount\n  when:\n    all:\n      - features.avg_amount > 100\n  score: 50\n",
+        );
+        content.add_ruleset(
+            "library/rulesets/payment.yaml",
+            "ruleset:\n  id: payment\n  rules:\n    - high_amount\n    - missing_rule\n",
+        );
+
+        let diagnostics = validate_references(&content);
+        let found: Vec<_> = diagnostics
+            .iter()
+            .map(|d| (d.file.as_deref().unwrap(), d.line.unwrap()))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                ("library/rules/high_amount.yaml", 6),
+                ("library/rulesets/payment.yaml", 5),
+            ]
+        );
+
+        content.rulesets[0].1 = content.rulesets[0].1.replace("    - missing_rule\n", "");
+        content.feature_definitions.push(
+            serde_json::from_value(serde_json::json!({
+                "name": "avg_amount",
+                "operator": "avg",
+                "datasource": "events",
+            }))
+            .unwrap(),
+        );
+        assert!(validate_references(&content).is_empty());
+    }
 }
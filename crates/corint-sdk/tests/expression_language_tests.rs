@@ -639,6 +639,170 @@ ruleset:
     response.assert_action(Signal::Decline);
 }
 
+#[tokio::test]
+async fn test_any_of_array_elements() {
+    let rule_yaml = r#"
+rule:
+  id: expensive_item
+  name: Expensive Item
+  when:
+    conditions:
+      - any_of(event.items, item.price > 1000)
+  score: 60
+"#;
+
+    let ruleset_yaml = r#"
+ruleset:
+  id: test_ruleset
+  rules:
+    - expensive_item
+  conclusion:
+    - when: total_score > 0
+      signal: review
+    - default: true
+      signal: approve
+"#;
+
+    let engine = TestEngine::new()
+        .with_rule(rule_yaml)
+        .with_ruleset(ruleset_yaml);
+
+    let item = |price: f64| {
+        let mut map = HashMap::new();
+        map.insert("price".to_string(), Value::Number(price));
+        Value::Object(map)
+    };
+
+    let mut event = HashMap::new();
+    event.insert(
+        "items".to_string(),
+        Value::Array(vec![item(20.0), item(1500.0)]),
+    );
+    let response = engine.execute_ruleset("test_ruleset", event).await;
+    response.assert_score(60);
+    response.assert_action(Signal::Review);
+
+    let mut event = HashMap::new();
+    event.insert("items".to_string(), Value::Array(vec![item(20.0), item(80.0)]));
+    let response = engine.execute_ruleset("test_ruleset", event).await;
+    response.assert_score(0);
+    response.assert_action(Signal::Approve);
+}
+
+#[tokio::test]
+async fn test_all_of_with_named_binding() {
+    let rule_yaml = r#"
+rule:
+  id: all_gift_cards
+  name: All Gift Cards
+  when:
+    conditions:
+      - all_of(event.items, line, line.category == "gift_card")
+  score: 40
+"#;
+
+    let ruleset_yaml = r#"
+ruleset:
+  id: test_ruleset
+  rules:
+    - all_gift_cards
+  conclusion:
+    - when: total_score > 0
+      signal: review
+    - default: true
+      signal: approve
+"#;
+
+    let engine = TestEngine::new()
+        .with_rule(rule_yaml)
+        .with_ruleset(ruleset_yaml);
+
+    let line = |category: &str| {
+        let mut map = HashMap::new();
+        map.insert("category".to_string(), Value::String(category.to_string()));
+        Value::Object(map)
+    };
+
+    let mut event = HashMap::new();
+    event.insert(
+        "items".to_string(),
+        Value::Array(vec![line("gift_card"), line("gift_card")]),
+    );
+    let response = engine.execute_ruleset("test_ruleset", event).await;
+    response.assert_score(40);
+
+    let mut event = HashMap::new();
+    event.insert(
+        "items".to_string(),
+        Value::Array(vec![line("gift_card"), line("electronics")]),
+    );
+    let response = engine.execute_ruleset("test_ruleset", event).await;
+    response.assert_score(0);
+}
+
+#[tokio::test]
+async fn test_array_aggregates_in_conditions() {
+    let rule_yaml = r#"
+rule:
+  id: large_basket
+  name: Large Basket
+  when:
+    any:
+      - sum(event.items[*].amount) > 5000
+      - count(event.items) > 10
+  score: 70
+"#;
+
+    let ruleset_yaml = r#"
+ruleset:
+  id: test_ruleset
+  rules:
+    - large_basket
+  conclusion:
+    - when: total_score > 0
+      signal: review
+    - default: true
+      signal: approve
+"#;
+
+    let engine = TestEngine::new()
+        .with_rule(rule_yaml)
+        .with_ruleset(ruleset_yaml);
+
+    let items = |amounts: &[f64]| {
+        Value::Array(
+            amounts
+                .iter()
+                .map(|amount| {
+                    let mut map = HashMap::new();
+                    map.insert("amount".to_string(), Value::Number(*amount));
+                    Value::Object(map)
+                })
+                .collect(),
+        )
+    };
+
+    // Total amount over threshold
+    let mut event = HashMap::new();
+    event.insert("items".to_string(), items(&[3000.0, 2500.0]));
+    let response = engine.execute_ruleset("test_ruleset", event).await;
+    response.assert_score(70);
+    response.assert_action(Signal::Review);
+
+    // Many small items
+    let mut event = HashMap::new();
+    event.insert("items".to_string(), items(&[10.0; 11]));
+    let response = engine.execute_ruleset("test_ruleset", event).await;
+    response.assert_score(70);
+
+    // Neither condition holds
+    let mut event = HashMap::new();
+    event.insert("items".to_string(), items(&[100.0, 200.0]));
+    let response = engine.execute_ruleset("test_ruleset", event).await;
+    response.assert_score(0);
+    response.assert_action(Signal::Approve);
+}
+
 // ============================================================================
 // Complex Expression Tests
 // ============================================================================
@@ -145,7 +145,7 @@ impl DecisionService for DecisionGrpcService {
     ) -> Result<Response<ReloadRepositoryResponse>, Status> {
         info!("Reloading repository via gRPC");
 
-        let mut engine = self.engine.write().await;
+        let engine = self.engine.read().await;
         match engine.reload().await {
             Ok(_) => {
                 info!("Repository reloaded successfully via gRPC");
@@ -3,4 +3,4 @@
 pub mod grpc;
 pub mod rest;
 
-pub use rest::create_router;
+pub use rest::{create_router, create_router_with_admin, create_router_with_state};
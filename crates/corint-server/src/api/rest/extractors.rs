@@ -2,9 +2,11 @@
 //!
 //! Provides custom request extractors with better error handling.
 
+use super::types::AppState;
+use crate::error::ServerError;
 use axum::{
-    extract::{rejection::JsonRejection, FromRequest, Request},
-    http::StatusCode,
+    extract::{rejection::JsonRejection, FromRequest, FromRequestParts, Request},
+    http::{request::Parts, StatusCode},
     Json,
 };
 use serde_json::json;
@@ -48,3 +50,80 @@ where
         }
     }
 }
+
+/// Authenticated admin caller
+///
+/// Accepts the configured admin API key as `Authorization: Bearer <key>` or
+/// `X-Admin-Key: <key>`. The optional `X-Admin-Actor` header names the operator
+/// recorded in audit entries.
+pub struct AdminAuth {
+    pub actor: String,
+}
+
+#[axum::async_trait]
+impl FromRequestParts<AppState> for AdminAuth {
+    type Rejection = ServerError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &AppState) -> Result<Self, Self::Rejection> {
+        let expected = state.admin.api_key.as_deref().ok_or_else(|| {
+            ServerError::Unauthorized("Admin API is disabled (no admin_api_key configured)".to_string())
+        })?;
+
+        let header = |name: &str| parts.headers.get(name).and_then(|v| v.to_str().ok());
+        let provided = header("authorization")
+            .and_then(|v| v.strip_prefix("Bearer "))
+            .or_else(|| header("x-admin-key"))
+            .ok_or_else(|| ServerError::Unauthorized("Missing admin credentials".to_string()))?;
+
+        if !constant_time_eq(provided.as_bytes(), expected.as_bytes()) {
+            return Err(ServerError::Unauthorized("Invalid admin credentials".to_string()));
+        }
+
+        let actor = header("x-admin-actor").unwrap_or("admin").to_string();
+        Ok(Self { actor })
+    }
+}
+
+/// Tenant of the caller, authenticated by its API key
+///
+/// Accepts a tenant API key as `Authorization: Bearer <key>` or
+/// `X-Api-Key: <key>`. Callers are not identified, and carry no tenant, when
+/// no tenant API keys are configured.
+pub struct TenantAuth(pub Option<String>);
+
+#[axum::async_trait]
+impl FromRequestParts<AppState> for TenantAuth {
+    type Rejection = ServerError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &AppState) -> Result<Self, Self::Rejection> {
+        if state.tenant_api_keys.is_empty() {
+            return Ok(Self(None));
+        }
+
+        let header = |name: &str| parts.headers.get(name).and_then(|v| v.to_str().ok());
+        let provided = header("authorization")
+            .and_then(|v| v.strip_prefix("Bearer "))
+            .or_else(|| header("x-api-key"))
+            .ok_or_else(|| ServerError::Unauthorized("Missing API key".to_string()))?;
+
+        // Compare against every key, so that the time taken does not tell
+        // how many keys were tried before the match
+        let tenant = state
+            .tenant_api_keys
+            .iter()
+            .fold(None, |found, (tenant, key)| {
+                if constant_time_eq(provided.as_bytes(), key.as_bytes()) {
+                    Some(tenant.clone())
+                } else {
+                    found
+                }
+            })
+            .ok_or_else(|| ServerError::Unauthorized("Invalid API key".to_string()))?;
+        Ok(Self(Some(tenant)))
+    }
+}
+
+/// Compare secrets without short-circuiting on the first mismatch
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
@@ -3,16 +3,23 @@
 //! HTTP request handlers for all REST API endpoints.
 
 use super::conversions::{extract_reason_codes, json_to_value, normalize_score, value_to_json};
-use super::extractors::JsonExtractor;
+use super::extractors::{AdminAuth, JsonExtractor, TenantAuth};
 use super::types::*;
 use crate::error::ServerError;
 use axum::{
-    extract::State,
+    body::Bytes,
+    extract::{Path, Query, State},
+    http::HeaderMap,
     Json,
 };
+use corint_repository::{AuditQuery, InvalidationNotice, WEBHOOK_SIGNATURE_HEADER};
+use corint_runtime::lists::{ListEntry, ListService};
+use corint_runtime::ReviewQueueQuery;
 use corint_core::{ast::Signal, Value};
 use corint_sdk::DecisionRequest;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::Duration;
 use tracing::{error, info};
 
 /// Health check endpoint
@@ -27,6 +34,7 @@ pub(super) async fn health() -> Json<HealthResponse> {
 #[axum::debug_handler]
 pub(super) async fn decide(
     State(state): State<AppState>,
+    TenantAuth(tenant): TenantAuth,
     JsonExtractor(payload): JsonExtractor<DecideRequestPayload>,
 ) -> Result<Json<DecideResponsePayload>, ServerError> {
     let options = payload.options.unwrap_or_default();
@@ -77,6 +85,24 @@ pub(super) async fn decide(
         request = request.with_trace();
     }
 
+    // Resolve reason code messages in the requested locale
+    if let Some(locale) = options.locale {
+        request = request.with_locale(locale);
+    }
+
+    if let Some(timeout_ms) = options.timeout_ms {
+        request = request.with_timeout(std::time::Duration::from_millis(timeout_ms));
+    }
+
+    // The tenant is the one the caller authenticated as
+    if let Some(tenant) = tenant {
+        request = request.with_tenant(tenant);
+    }
+
+    if let Some(event_id) = options.event_id {
+        request = request.with_metadata("event_id".to_string(), event_id);
+    }
+
     // Execute decision (acquire read lock - allows concurrent reads)
     let engine = state.engine.read().await;
     let response = engine.decide(request).await?;
@@ -96,6 +122,14 @@ pub(super) async fn decide(
         .unwrap_or("pass")
         .to_string();
 
+    // Catalog reason code (if the conclusion set one) comes first
+    let mut reason_codes: Vec<String> = response.result.reason_code.iter().cloned().collect();
+    for code in extract_reason_codes(&response.result.explanation) {
+        if !reason_codes.contains(&code) {
+            reason_codes.push(code);
+        }
+    }
+
     // Build the response
     Ok(Json(DecideResponsePayload {
         request_id: response.request_id,
@@ -115,7 +149,8 @@ pub(super) async fn decide(
             },
             cognition: CognitionPayload {
                 summary: response.result.explanation.clone(),
-                reason_codes: extract_reason_codes(&response.result.explanation),
+                reason_codes,
+                message: response.reason.map(|reason| reason.message),
             },
         },
         features: if options.return_features {
@@ -131,6 +166,10 @@ pub(super) async fn decide(
             None
         },
         trace: response.trace,
+        list_hits: response.list_hits,
+        degraded: response.degraded,
+        timeouts: response.timeouts,
+        error: response.error,
     }))
 }
 
@@ -138,18 +177,401 @@ pub(super) async fn decide(
 pub(super) async fn reload_repository(State(state): State<AppState>) -> Result<Json<ReloadResponse>, ServerError> {
     info!("Received repository reload request");
 
-    // Reload engine using SDK's reload method (acquire write lock - exclusive access)
-    {
-        let mut engine = state.engine.write().await;
+    // Reload engine using SDK's reload method; decisions keep being served meanwhile
+    let feature_registry_version = {
+        let engine = state.engine.read().await;
         engine.reload().await.map_err(|e| {
             error!("Failed to reload repository: {}", e);
             ServerError::InternalError(anyhow::anyhow!("Failed to reload repository: {}", e))
         })?;
-    }
+        engine.feature_registry_version()
+    };
 
     info!("Repository reloaded successfully");
     Ok(Json(ReloadResponse {
         success: true,
         message: "Repository reloaded successfully".to_string(),
+        feature_registry_version,
+    }))
+}
+
+/// Repository change webhook
+///
+/// A rule-management service pushes an [`InvalidationNotice`] signed with the
+/// shared secret; the engine then reloads the repository.
+pub(super) async fn repository_webhook(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<Json<ReloadResponse>, ServerError> {
+    let secret = state.admin.webhook_secret.as_deref().ok_or_else(|| {
+        ServerError::Unauthorized(
+            "Repository webhook is disabled (no repository_webhook_secret configured)".to_string(),
+        )
+    })?;
+
+    let signature = headers
+        .get(WEBHOOK_SIGNATURE_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .ok_or_else(|| ServerError::Unauthorized("Missing webhook signature".to_string()))?;
+    if !InvalidationNotice::verify_signature(secret.as_bytes(), &body, signature) {
+        return Err(ServerError::Unauthorized("Invalid webhook signature".to_string()));
+    }
+
+    let notice = InvalidationNotice::from_json(&body)
+        .map_err(|e| ServerError::InvalidRequest(e.to_string()))?;
+    info!(
+        "Received repository webhook for {}:{}",
+        notice.kind.as_deref().unwrap_or("*"),
+        notice.id.as_deref().unwrap_or("*")
+    );
+
+    let feature_registry_version = {
+        let engine = state.engine.read().await;
+        engine.reload().await.map_err(|e| {
+            error!("Failed to reload repository from webhook: {}", e);
+            ServerError::InternalError(anyhow::anyhow!("Failed to reload repository: {}", e))
+        })?;
+        engine.feature_registry_version()
+    };
+
+    Ok(Json(ReloadResponse {
+        success: true,
+        message: "Repository reloaded successfully".to_string(),
+        feature_registry_version,
+    }))
+}
+
+/// Record the final outcome of a past decision
+pub(super) async fn record_outcome(
+    State(state): State<AppState>,
+    JsonExtractor(payload): JsonExtractor<OutcomeRequest>,
+) -> Result<Json<OutcomeResponse>, ServerError> {
+    if payload.request_id.trim().is_empty() {
+        return Err(ServerError::InvalidRequest(
+            "'request_id' is required".to_string(),
+        ));
+    }
+
+    info!(
+        "Received outcome '{}' for request_id: {}",
+        payload.label.as_str(),
+        payload.request_id
+    );
+
+    let metadata = payload
+        .metadata
+        .unwrap_or_default()
+        .into_iter()
+        .map(|(k, v)| (k, json_to_value(v)))
+        .collect();
+
+    {
+        let engine = state.engine.read().await;
+        engine
+            .record_outcome(&payload.request_id, payload.label, metadata)
+            .await
+            .map_err(|e| {
+                error!("Failed to record outcome: {}", e);
+                ServerError::InternalError(anyhow::anyhow!("Failed to record outcome: {}", e))
+            })?;
+    }
+
+    Ok(Json(OutcomeResponse {
+        success: true,
+        request_id: payload.request_id,
+        label: payload.label,
+    }))
+}
+
+/// Purge the persisted decision history of a single entity (admin only)
+pub(super) async fn purge_decisions(
+    State(state): State<AppState>,
+    admin: AdminAuth,
+    JsonExtractor(payload): JsonExtractor<PurgeRequest>,
+) -> Result<Json<PurgeResponse>, ServerError> {
+    if payload.field.trim().is_empty() || payload.value.is_empty() {
+        return Err(ServerError::InvalidRequest(
+            "Both 'field' and 'value' are required".to_string(),
+        ));
+    }
+
+    let retention = state.admin.retention.as_ref().ok_or_else(|| {
+        ServerError::InternalError(anyhow::anyhow!(
+            "Decision persistence is not configured (database_url is not set)"
+        ))
+    })?;
+
+    info!(
+        "Received decision purge request for field '{}' from actor '{}'",
+        payload.field, admin.actor
+    );
+
+    let summary = retention
+        .purge_by_entity(
+            &payload.field,
+            &payload.value,
+            &admin.actor,
+            payload.reason.as_deref(),
+        )
+        .await
+        .map_err(|e| {
+            error!("Failed to purge decisions: {}", e);
+            ServerError::InternalError(anyhow::anyhow!("Failed to purge decisions: {}", e))
+        })?;
+
+    Ok(Json(PurgeResponse {
+        success: true,
+        risk_decisions: summary.risk_decisions,
+        rule_executions: summary.rule_executions,
+        events: summary.events,
+        decision_outcomes: summary.decision_outcomes,
+        experiment_results: summary.experiment_results,
+    }))
+}
+
+/// List sampled decisions awaiting quality review (admin only)
+pub(super) async fn review_queue(
+    State(state): State<AppState>,
+    admin: AdminAuth,
+    Query(query): Query<ReviewQueueQuery>,
+) -> Result<Json<ReviewQueueResponse>, ServerError> {
+    let review_queue = state.admin.review_queue.as_ref().ok_or_else(|| {
+        ServerError::InternalError(anyhow::anyhow!(
+            "Decision persistence is not configured (database_url is not set)"
+        ))
+    })?;
+
+    info!("Received review queue query from actor '{}'", admin.actor);
+
+    let decisions = review_queue.fetch(&query).await.map_err(|e| {
+        error!("Failed to fetch review queue: {}", e);
+        ServerError::InternalError(anyhow::anyhow!("Failed to fetch review queue: {}", e))
+    })?;
+
+    Ok(Json(ReviewQueueResponse { decisions }))
+}
+
+/// Query the repository audit trail (admin only)
+pub(super) async fn query_audit_log(
+    State(state): State<AppState>,
+    admin: AdminAuth,
+    Query(query): Query<AuditQuery>,
+) -> Result<Json<AuditLogResponse>, ServerError> {
+    let audit_log = state.admin.audit_log.as_ref().ok_or_else(|| {
+        ServerError::InternalError(anyhow::anyhow!(
+            "Repository audit log is not configured (requires a database repository)"
+        ))
+    })?;
+
+    info!("Received audit log query from actor '{}'", admin.actor);
+
+    let entries = audit_log.query(&query).await.map_err(|e| {
+        error!("Failed to query audit log: {}", e);
+        ServerError::InternalError(anyhow::anyhow!("Failed to query audit log: {}", e))
+    })?;
+
+    Ok(Json(AuditLogResponse { entries }))
+}
+
+/// List the configured lists (admin only)
+pub(super) async fn lists(
+    State(state): State<AppState>,
+    _admin: AdminAuth,
+) -> Result<Json<ListsResponse>, ServerError> {
+    let service = state.engine.read().await.list_service().cloned();
+    let mut lists = match service {
+        Some(service) => service.list_ids().await,
+        None => Vec::new(),
+    };
+    lists.sort();
+
+    Ok(Json(ListsResponse { lists }))
+}
+
+/// Get the unexpired entries of a list (admin only)
+pub(super) async fn list_entries(
+    State(state): State<AppState>,
+    _admin: AdminAuth,
+    Path(list_id): Path<String>,
+) -> Result<Json<ListEntriesResponse>, ServerError> {
+    let service = list_service(&state, &list_id).await?;
+
+    let entries = service.get_entries(&list_id).await.map_err(|e| {
+        error!("Failed to get entries of list '{}': {}", list_id, e);
+        ServerError::InternalError(anyhow::anyhow!("Failed to get list entries: {}", e))
+    })?;
+
+    Ok(Json(ListEntriesResponse {
+        list_id,
+        entries: entries.into_iter().map(list_entry_payload).collect(),
     }))
 }
+
+/// Add an entry to a list; it applies to the next decision (admin only)
+pub(super) async fn add_list_entry(
+    State(state): State<AppState>,
+    admin: AdminAuth,
+    Path(list_id): Path<String>,
+    JsonExtractor(payload): JsonExtractor<ListEntryRequest>,
+) -> Result<Json<ListEntryResponse>, ServerError> {
+    let service = list_service(&state, &list_id).await?;
+    let entry = list_entry(payload)?;
+
+    info!(
+        "Adding {:?} to list '{}' for actor '{}'",
+        entry.value, list_id, admin.actor
+    );
+
+    service.add_entry(&list_id, entry.clone()).await.map_err(|e| {
+        error!("Failed to add entry to list '{}': {}", list_id, e);
+        ServerError::InternalError(anyhow::anyhow!("Failed to add list entry: {}", e))
+    })?;
+
+    Ok(Json(ListEntryResponse {
+        success: true,
+        list_id,
+        entry: list_entry_payload(entry),
+    }))
+}
+
+/// Remove a value from a list (admin only)
+pub(super) async fn remove_list_entry(
+    State(state): State<AppState>,
+    admin: AdminAuth,
+    Path((list_id, value)): Path<(String, String)>,
+) -> Result<Json<ListEntryRemovedResponse>, ServerError> {
+    let service = list_service(&state, &list_id).await?;
+
+    info!(
+        "Removing '{}' from list '{}' for actor '{}'",
+        value, list_id, admin.actor
+    );
+
+    service
+        .remove(&list_id, &Value::String(value.clone()))
+        .await
+        .map_err(|e| {
+            error!("Failed to remove entry from list '{}': {}", list_id, e);
+            ServerError::InternalError(anyhow::anyhow!("Failed to remove list entry: {}", e))
+        })?;
+
+    Ok(Json(ListEntryRemovedResponse {
+        success: true,
+        list_id,
+        value,
+    }))
+}
+
+/// Add many entries to a list, optionally replacing its contents (admin only)
+pub(super) async fn import_list_entries(
+    State(state): State<AppState>,
+    admin: AdminAuth,
+    Path(list_id): Path<String>,
+    JsonExtractor(payload): JsonExtractor<ListImportRequest>,
+) -> Result<Json<ListImportResponse>, ServerError> {
+    let service = list_service(&state, &list_id).await?;
+
+    // Validate every entry before changing the list
+    let entries = payload
+        .entries
+        .into_iter()
+        .map(list_entry)
+        .collect::<Result<Vec<_>, _>>()?;
+
+    info!(
+        "Importing {} entries into list '{}' (replace={}) for actor '{}'",
+        entries.len(),
+        list_id,
+        payload.replace,
+        admin.actor
+    );
+
+    let import_error = |e: corint_runtime::RuntimeError| {
+        error!("Failed to import entries into list '{}': {}", list_id, e);
+        ServerError::InternalError(anyhow::anyhow!("Failed to import list entries: {}", e))
+    };
+
+    let mut removed = 0;
+    if payload.replace {
+        let values: HashSet<String> = entries
+            .iter()
+            .map(|entry| ListService::entry_key(&entry.value))
+            .collect();
+        (_, removed) = service
+            .replace_all(&list_id, &values)
+            .await
+            .map_err(import_error)?;
+    }
+    let imported = entries.len();
+    for entry in entries {
+        // Values added by the replace still need their metadata
+        if payload.replace && entry.expires_at.is_none() && entry.reason.is_none() {
+            continue;
+        }
+        service
+            .add_entry(&list_id, entry)
+            .await
+            .map_err(import_error)?;
+    }
+
+    Ok(Json(ListImportResponse {
+        success: true,
+        list_id,
+        imported,
+        removed,
+    }))
+}
+
+/// The engine's list service, if it has list `list_id`
+async fn list_service(state: &AppState, list_id: &str) -> Result<Arc<ListService>, ServerError> {
+    let service = state
+        .engine
+        .read()
+        .await
+        .list_service()
+        .cloned()
+        .ok_or_else(|| ServerError::NotFound("No lists are configured".to_string()))?;
+
+    if !service.has_list(list_id).await {
+        return Err(ServerError::NotFound(format!("List '{}' not found", list_id)));
+    }
+    Ok(service)
+}
+
+/// Validate a list entry request
+fn list_entry(request: ListEntryRequest) -> Result<ListEntry, ServerError> {
+    let value = match request.value {
+        serde_json::Value::String(s) if !s.trim().is_empty() => Value::String(s),
+        value @ (serde_json::Value::Number(_) | serde_json::Value::Bool(_)) => json_to_value(value),
+        _ => {
+            return Err(ServerError::InvalidRequest(
+                "List entry 'value' must be a non-empty string, a number or a boolean".to_string(),
+            ))
+        }
+    };
+
+    let mut entry = ListEntry::new(value);
+    entry = match (request.expires_at, request.ttl_secs) {
+        (Some(_), Some(_)) => {
+            return Err(ServerError::InvalidRequest(
+                "Set either 'expires_at' or 'ttl_secs', not both".to_string(),
+            ))
+        }
+        (Some(expires_at), None) => entry.with_expires_at(expires_at),
+        (None, Some(ttl_secs)) => entry.with_ttl(Duration::from_secs(ttl_secs)),
+        (None, None) => entry,
+    };
+    if let Some(reason) = request.reason {
+        entry = entry.with_reason(reason);
+    }
+    Ok(entry)
+}
+
+fn list_entry_payload(entry: ListEntry) -> ListEntryPayload {
+    ListEntryPayload {
+        value: value_to_json(entry.value),
+        expires_at: entry.expires_at,
+        reason: entry.reason,
+    }
+}
@@ -17,9 +17,11 @@ pub mod types;
 
 // Re-export public API
 pub use extractors::JsonExtractor;
-pub use router::create_router;
+pub use router::{create_router, create_router_with_admin, create_router_with_state};
 pub use types::{
-    AppState, CognitionPayload, DecideRequestPayload, DecideResponsePayload,
-    DecisionPayload, EvidencePayload, HealthResponse, ReloadResponse, RequestOptions,
-    ScoresPayload,
+    AdminState, AppState, AuditLogResponse, CognitionPayload, DecideRequestPayload, DecideResponsePayload,
+    DecisionPayload, EvidencePayload, HealthResponse, ListEntriesResponse, ListEntryPayload,
+    ListEntryRemovedResponse, ListEntryRequest, ListEntryResponse, ListImportRequest, ListImportResponse,
+    ListsResponse, OutcomeRequest, OutcomeResponse, PurgeRequest, PurgeResponse, ReloadResponse,
+    RequestOptions, ReviewQueueResponse, ScoresPayload,
 };
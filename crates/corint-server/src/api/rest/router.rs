@@ -3,9 +3,9 @@
 //! Creates Axum routers for REST API endpoints.
 
 use super::handlers::*;
-use super::types::AppState;
+use super::types::{AdminState, AppState};
 use axum::{
-    routing::{get, post},
+    routing::{delete, get, post},
     Router,
 };
 use corint_sdk::DecisionEngine;
@@ -16,6 +16,11 @@ use tower_http::trace::TraceLayer;
 
 /// Create REST API router
 pub fn create_router(engine: Arc<DecisionEngine>) -> Router {
+    create_router_with_admin(engine, AdminState::default())
+}
+
+/// Create REST API router with admin endpoints enabled by `admin`
+pub fn create_router_with_admin(engine: Arc<DecisionEngine>, admin: AdminState) -> Router {
     let state = AppState {
         engine: Arc::new(RwLock::new(
             Arc::try_unwrap(engine).unwrap_or_else(|_arc| {
@@ -26,12 +31,28 @@ pub fn create_router(engine: Arc<DecisionEngine>) -> Router {
                 panic!("Cannot create router: DecisionEngine Arc has multiple references. This is a programming error.");
             })
         )),
+        admin,
+        tenant_api_keys: Arc::default(),
     };
 
+    create_router_with_state(state)
+}
+
+/// Create REST API router over an existing (shared) application state
+pub fn create_router_with_state(state: AppState) -> Router {
     Router::new()
         .route("/health", get(health))
         .route("/v1/decide", post(decide))
         .route("/v1/repo/reload", post(reload_repository))  // Changed from GET to POST
+        .route("/v1/repo/webhook", post(repository_webhook))
+        .route("/v1/outcomes", post(record_outcome))
+        .route("/v1/admin/decisions/purge", post(purge_decisions))
+        .route("/v1/admin/audit", get(query_audit_log))
+        .route("/v1/admin/review-queue", get(review_queue))
+        .route("/v1/lists", get(lists))
+        .route("/v1/lists/:list_id/entries", get(list_entries).post(add_list_entry))
+        .route("/v1/lists/:list_id/entries/:value", delete(remove_list_entry))
+        .route("/v1/lists/:list_id/import", post(import_list_entries))
         .with_state(state)
         .layer(CorsLayer::permissive())
         .layer(TraceLayer::new_for_http())
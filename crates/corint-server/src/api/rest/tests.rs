@@ -176,10 +176,15 @@ fn test_decide_response_payload_fields() {
             cognition: CognitionPayload {
                 summary: "Low risk transaction".to_string(),
                 reason_codes: vec!["LOW_RISK".to_string()],
+                message: None,
             },
         },
         features: None,
         trace: None,
+        list_hits: vec![],
+        degraded: false,
+        timeouts: vec![],
+        error: None,
     };
 
     assert_eq!(response.request_id, "req_123");
@@ -212,6 +217,55 @@ fn test_decide_request_payload_empty() {
     assert!(payload.options.is_none());
 }
 
+#[test]
+fn test_purge_request_deserialize() {
+    let payload: PurgeRequest = serde_json::from_value(serde_json::json!({
+        "field": "user_id",
+        "value": "user_123"
+    }))
+    .unwrap();
+
+    assert_eq!(payload.field, "user_id");
+    assert_eq!(payload.value, "user_123");
+    assert!(payload.reason.is_none());
+}
+
+#[test]
+fn test_outcome_request_deserialize() {
+    let payload: OutcomeRequest = serde_json::from_value(serde_json::json!({
+        "request_id": "req_20260101120000_a3f2e1",
+        "label": "chargeback",
+        "metadata": { "reason_code": "10.4" }
+    }))
+    .unwrap();
+
+    assert_eq!(payload.label, corint_runtime::OutcomeLabel::Chargeback);
+    assert_eq!(payload.metadata.unwrap().len(), 1);
+
+    let invalid = serde_json::from_value::<OutcomeRequest>(serde_json::json!({
+        "request_id": "req_1",
+        "label": "refund"
+    }));
+    assert!(invalid.is_err());
+}
+
+#[test]
+fn test_audit_log_response_serialize() {
+    let entry = corint_repository::AuditEntry::new(
+        corint_repository::BundleArtifactKind::Rule,
+        "high_amount",
+        corint_repository::AuditOperation::Update,
+        "alice",
+    );
+    let response = AuditLogResponse {
+        entries: vec![entry],
+    };
+
+    let json = serde_json::to_value(&response).unwrap();
+    assert_eq!(json["entries"][0]["artifact_id"], "high_amount");
+    assert_eq!(json["entries"][0]["actor"], "alice");
+}
+
 #[test]
 fn test_request_options_defaults() {
     let options = RequestOptions::default();
@@ -282,3 +336,24 @@ fn test_json_to_value_mixed_types() {
         panic!("Expected Object");
     }
 }
+
+#[test]
+fn test_list_entry_request_deserialize() {
+    let payload: ListEntryRequest = serde_json::from_value(serde_json::json!({
+        "value": "fraud@example.com",
+        "reason": "Confirmed fraud account",
+        "expires_at": "2030-12-31T23:59:59Z"
+    }))
+    .unwrap();
+
+    assert_eq!(payload.value, "fraud@example.com");
+    assert!(payload.expires_at.is_some());
+    assert!(payload.ttl_secs.is_none());
+
+    let import: ListImportRequest = serde_json::from_value(serde_json::json!({
+        "entries": [{"value": "10.0.0.50"}, {"value": 42, "ttl_secs": 3600}]
+    }))
+    .unwrap();
+    assert_eq!(import.entries.len(), 2);
+    assert!(!import.replace);
+}
@@ -2,7 +2,12 @@
 //!
 //! Request and response types for the REST API endpoints.
 
-use corint_runtime::ExecutionTrace;
+use corint_repository::{AuditEntry, AuditLog};
+use corint_runtime::{
+    DecisionRetention, DecisionReviewQueue, ExecutionTrace, ListHit, OutcomeLabel,
+    SampledDecision, TimeoutEvent,
+};
+use chrono::{DateTime, Utc};
 use corint_sdk::DecisionEngine;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -13,6 +18,29 @@ use tokio::sync::RwLock;
 #[derive(Clone)]
 pub struct AppState {
     pub engine: Arc<RwLock<DecisionEngine>>,
+    pub admin: AdminState,
+
+    /// API keys of tenants, by tenant ID (decisions carry no tenant when empty)
+    pub tenant_api_keys: Arc<HashMap<String, String>>,
+}
+
+/// Admin API state
+#[derive(Clone, Default)]
+pub struct AdminState {
+    /// API key required by admin endpoints (admin API is disabled when unset)
+    pub api_key: Option<String>,
+
+    /// Retention service for persisted decisions (requires a database)
+    pub retention: Option<DecisionRetention>,
+
+    /// Review queue of sampled decisions (requires a database)
+    pub review_queue: Option<DecisionReviewQueue>,
+
+    /// Repository audit trail (requires a database repository)
+    pub audit_log: Option<Arc<dyn AuditLog>>,
+
+    /// Shared secret for signed repository webhooks (webhook is disabled when unset)
+    pub webhook_secret: Option<String>,
 }
 
 /// Health check response
@@ -72,6 +100,18 @@ pub struct RequestOptions {
     /// Whether to process asynchronously
     #[serde(default, rename = "async")]
     pub async_mode: bool,
+
+    /// Preferred locale for reason code messages (e.g., "en", "pt-BR")
+    #[serde(default)]
+    pub locale: Option<String>,
+
+    /// Time budget of the decision in milliseconds, overriding the server's
+    #[serde(default)]
+    pub timeout_ms: Option<u64>,
+
+    /// ID of the event, so that retries of the request are not decided again
+    #[serde(default)]
+    pub event_id: Option<String>,
 }
 
 /// Decision response payload (matches API_REQUEST.md spec)
@@ -99,6 +139,20 @@ pub struct DecideResponsePayload {
     /// Detailed execution trace (only present if options.enable_trace = true)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub trace: Option<ExecutionTrace>,
+
+    /// List lookups that found their value (only present if audit_list_hits is enabled)
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub list_hits: Vec<ListHit>,
+
+    /// Whether a timeout cut the decision short, so it was made from partial results
+    pub degraded: bool,
+
+    /// Scopes that ran out of time (only present if the decision is degraded)
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub timeouts: Vec<TimeoutEvent>,
+    /// Step failure decided by the pipeline's on_error policy (only present if a step failed)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
 }
 
 /// Decision payload (nested in response)
@@ -149,6 +203,10 @@ pub struct CognitionPayload {
 
     /// Machine-readable reason codes
     pub reason_codes: Vec<String>,
+
+    /// Customer-facing message for the decision's reason code, in the requested locale
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
 }
 
 /// Reload repository endpoint response
@@ -156,4 +214,145 @@ pub struct CognitionPayload {
 pub struct ReloadResponse {
     pub success: bool,
     pub message: String,
+    /// Version of the feature definitions after the reload
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub feature_registry_version: Option<u64>,
+}
+
+/// Purge decisions request payload
+#[derive(Debug, Deserialize)]
+pub struct PurgeRequest {
+    /// Entity field to match (e.g. "user_id", "email", "customer.email")
+    pub field: String,
+
+    /// Entity value whose decision history should be deleted
+    pub value: String,
+
+    /// Optional reason recorded in the audit entry (e.g. a ticket reference)
+    #[serde(default)]
+    pub reason: Option<String>,
+}
+
+/// Purge decisions response payload
+#[derive(Debug, Serialize)]
+pub struct PurgeResponse {
+    pub success: bool,
+    pub risk_decisions: u64,
+    pub rule_executions: u64,
+    pub events: u64,
+    pub decision_outcomes: u64,
+    pub experiment_results: u64,
+}
+
+/// Outcome feedback request payload
+#[derive(Debug, Deserialize)]
+pub struct OutcomeRequest {
+    /// Request ID of the decision the outcome belongs to
+    pub request_id: String,
+
+    /// Outcome label (confirmed_fraud, chargeback, false_positive, legitimate)
+    pub label: OutcomeLabel,
+
+    /// Optional details (case ID, chargeback reason code, ...)
+    #[serde(default)]
+    pub metadata: Option<HashMap<String, serde_json::Value>>,
+}
+
+/// Outcome feedback response payload
+#[derive(Debug, Serialize)]
+pub struct OutcomeResponse {
+    pub success: bool,
+    pub request_id: String,
+    pub label: OutcomeLabel,
+}
+
+/// Review queue response payload
+#[derive(Debug, Serialize)]
+pub struct ReviewQueueResponse {
+    pub decisions: Vec<SampledDecision>,
+}
+
+/// Repository audit log response payload
+#[derive(Debug, Serialize)]
+pub struct AuditLogResponse {
+    pub entries: Vec<AuditEntry>,
+}
+
+/// Configured lists response payload
+#[derive(Debug, Serialize)]
+pub struct ListsResponse {
+    pub lists: Vec<String>,
+}
+
+/// List entry with its metadata
+#[derive(Debug, Serialize)]
+pub struct ListEntryPayload {
+    pub value: serde_json::Value,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<DateTime<Utc>>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+/// List entries response payload
+#[derive(Debug, Serialize)]
+pub struct ListEntriesResponse {
+    pub list_id: String,
+    pub entries: Vec<ListEntryPayload>,
+}
+
+/// Add list entry request payload (also an item of a bulk import)
+#[derive(Debug, Deserialize)]
+pub struct ListEntryRequest {
+    /// Value to add (string, number or boolean)
+    pub value: serde_json::Value,
+
+    /// Why the entry was added (e.g. a case reference)
+    #[serde(default)]
+    pub reason: Option<String>,
+
+    /// When the entry expires
+    #[serde(default)]
+    pub expires_at: Option<DateTime<Utc>>,
+
+    /// Seconds until the entry expires (alternative to `expires_at`)
+    #[serde(default)]
+    pub ttl_secs: Option<u64>,
+}
+
+/// Add list entry response payload
+#[derive(Debug, Serialize)]
+pub struct ListEntryResponse {
+    pub success: bool,
+    pub list_id: String,
+    pub entry: ListEntryPayload,
+}
+
+/// Remove list entry response payload
+#[derive(Debug, Serialize)]
+pub struct ListEntryRemovedResponse {
+    pub success: bool,
+    pub list_id: String,
+    pub value: String,
+}
+
+/// Bulk list import request payload
+#[derive(Debug, Deserialize)]
+pub struct ListImportRequest {
+    pub entries: Vec<ListEntryRequest>,
+
+    /// Remove the entries that are not in the import
+    #[serde(default)]
+    pub replace: bool,
+}
+
+/// Bulk list import response payload
+#[derive(Debug, Serialize)]
+pub struct ListImportResponse {
+    pub success: bool,
+    pub list_id: String,
+    pub imported: usize,
+    pub removed: usize,
 }
@@ -1,5 +1,10 @@
 //! Server configuration
 
+use corint_repository::IntegrityConfig;
+use corint_runtime::{
+    ExecutionLimits, NullSemantics, RetentionConfig, SamplingConfig, TenantQuotaConfig,
+};
+use corint_sdk::IdempotencyConfig;
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 
@@ -33,6 +38,17 @@ pub enum RepositoryType {
         #[serde(default)]
         api_key: Option<String>,
     },
+    /// Redis repository (requires the `redis` feature)
+    Redis {
+        /// Redis connection URL
+        url: String,
+    },
+    /// MongoDB repository (requires the `mongodb` feature)
+    #[serde(rename = "mongodb")]
+    MongoDB {
+        /// MongoDB connection string
+        url: String,
+    },
 }
 
 /// Database type
@@ -69,7 +85,7 @@ pub struct DatasourceConfig {
     #[serde(rename = "type")]
     pub source_type: String,
 
-    /// Provider (postgresql, mysql, sqlite, redis, clickhouse, etc.)
+    /// Provider (postgresql, mysql, sqlite, bigquery, snowflake, redis, clickhouse, etc.)
     pub provider: String,
 
     /// Connection string or URL
@@ -86,6 +102,22 @@ pub struct DatasourceConfig {
     /// Additional options
     #[serde(default)]
     pub options: std::collections::HashMap<String, String>,
+
+    /// Optional cap on concurrent queries (for feature calculation datasources)
+    #[serde(default)]
+    pub concurrency: Option<corint_runtime::ConcurrencyLimit>,
+
+    /// Optional circuit breaker suspending queries while the datasource is failing
+    #[serde(default)]
+    pub circuit_breaker: Option<corint_runtime::CircuitBreakerConfig>,
+
+    /// Optional retry policy for failed queries
+    #[serde(default)]
+    pub retry: Option<corint_runtime::RetryPolicy>,
+
+    /// Caching of query results, e.g. `strategy: stale_while_revalidate`
+    #[serde(default)]
+    pub cache: Option<corint_runtime::datasource::CacheStrategy>,
 }
 
 impl DatasourceConfig {
@@ -103,6 +135,8 @@ impl DatasourceConfig {
                     "postgresql" | "postgres" => SQLProvider::PostgreSQL,
                     "mysql" => SQLProvider::MySQL,
                     "sqlite" => SQLProvider::SQLite,
+                    "bigquery" => SQLProvider::BigQuery,
+                    "snowflake" => SQLProvider::Snowflake,
                     _ => return Err(anyhow::anyhow!("Unsupported SQL provider: {}", self.provider)),
                 };
 
@@ -178,6 +212,10 @@ impl DatasourceConfig {
             pool_size,
             timeout_ms,
             pooling_enabled: true,
+            concurrency: self.concurrency,
+            circuit_breaker: self.circuit_breaker,
+            retry: self.retry.clone(),
+            cache: self.cache.clone(),
         })
     }
 }
@@ -208,6 +246,11 @@ pub struct ServerSettings {
     /// Log level (trace, debug, info, warn, error)
     #[serde(default = "default_log_level")]
     pub log_level: String,
+
+    /// Run scheduled pipelines declared in the registry
+    /// (disable on all but one instance when running multiple replicas)
+    #[serde(default = "default_true")]
+    pub enable_scheduler: bool,
 }
 
 fn default_host() -> String {
@@ -250,6 +293,64 @@ pub struct ServerConfig {
     /// If not set, decision results will not be persisted to database
     #[serde(default)]
     pub database_url: Option<String>,
+
+    /// Retention TTLs for persisted decision data (requires database_url)
+    #[serde(default)]
+    pub retention: RetentionConfig,
+
+    /// Sampling of persisted decisions for quality review (requires database_url)
+    #[serde(default)]
+    pub sampling: SamplingConfig,
+
+    /// Return a record of every list hit with each decision
+    #[serde(default)]
+    pub audit_list_hits: bool,
+
+    /// Time budget of a decision in milliseconds, after which it is made from
+    /// partial results and marked as degraded (optional)
+    #[serde(default)]
+    pub decision_timeout_ms: Option<u64>,
+
+    /// Instruction and wall-clock limits after which a runaway program is aborted
+    #[serde(default)]
+    pub execution_limits: ExecutionLimits,
+
+    /// Capture the inputs of decisions into their records, to replay them
+    #[serde(default)]
+    pub capture_inputs: bool,
+
+    /// Profile the time spent per instruction, rule and feature
+    #[serde(default)]
+    pub profiling: bool,
+
+    /// How conditions treat missing values: two_valued (default) or three_valued
+    #[serde(default)]
+    pub null_semantics: NullSemantics,
+
+    /// Concurrency and rate quotas per tenant (no quotas by default)
+    #[serde(default)]
+    pub tenant_quotas: TenantQuotaConfig,
+
+    /// API keys of tenants, by tenant ID; a decision is made for the tenant
+    /// whose key it presents (required by tenant quotas)
+    #[serde(default)]
+    pub tenant_api_keys: std::collections::HashMap<String, String>,
+
+    /// Replay of decisions for retried event IDs (disabled if not set)
+    #[serde(default)]
+    pub idempotency: Option<IdempotencyConfig>,
+
+    /// Checksum/signature verification of repository content on load (optional)
+    #[serde(default)]
+    pub repository_integrity: Option<IntegrityConfig>,
+
+    /// API key for admin endpoints (admin API is disabled if not set)
+    #[serde(default)]
+    pub admin_api_key: Option<String>,
+
+    /// Shared secret for signed repository webhooks (webhook is disabled if not set)
+    #[serde(default)]
+    pub repository_webhook_secret: Option<String>,
 }
 
 impl Default for ServerSettings {
@@ -261,6 +362,7 @@ impl Default for ServerSettings {
             enable_metrics: default_true(),
             enable_tracing: default_true(),
             log_level: default_log_level(),
+            enable_scheduler: default_true(),
         }
     }
 }
@@ -273,6 +375,20 @@ impl Default for ServerConfig {
             datasource: std::collections::HashMap::new(),
             default_datasource: None,
             database_url: None,
+            retention: RetentionConfig::default(),
+            sampling: SamplingConfig::default(),
+            audit_list_hits: false,
+            decision_timeout_ms: None,
+            execution_limits: ExecutionLimits::default(),
+            capture_inputs: false,
+            profiling: false,
+            null_semantics: NullSemantics::default(),
+            tenant_quotas: TenantQuotaConfig::default(),
+            tenant_api_keys: std::collections::HashMap::new(),
+            idempotency: None,
+            repository_integrity: None,
+            admin_api_key: None,
+            repository_webhook_secret: None,
         }
     }
 }
@@ -289,16 +405,32 @@ impl ServerConfig {
             .add_source(config::Environment::with_prefix("CORINT"))
             .build();
 
-        match config_result {
+        let config: Self = match config_result {
             Ok(cfg) => cfg
                 .try_deserialize()
-                .map_err(|e| anyhow::anyhow!("Failed to deserialize config: {}", e)),
+                .map_err(|e| anyhow::anyhow!("Failed to deserialize config: {}", e))?,
             Err(_) => {
                 // Use default config if no config file found
                 tracing::info!("No config file found, using default configuration");
-                Ok(Self::default())
+                Self::default()
             }
+        };
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Check settings that depend on each other
+    ///
+    /// Tenant quotas need tenant API keys: the tenant of a decision is the
+    /// one its caller authenticates as, never one the caller names.
+    pub fn validate(&self) -> anyhow::Result<()> {
+        let quotas = &self.tenant_quotas;
+        if (quotas.default.is_some() || !quotas.tenants.is_empty()) && self.tenant_api_keys.is_empty() {
+            return Err(anyhow::anyhow!(
+                "tenant_quotas require tenant_api_keys to identify the tenant of each decision"
+            ));
         }
+        Ok(())
     }
 }
 
@@ -315,9 +447,29 @@ mod tests {
         assert!(config.server.enable_metrics);
         assert!(config.server.enable_tracing);
         assert_eq!(config.server.log_level, "info");
+        assert!(config.server.enable_scheduler);
         assert!(config.database_url.is_none());
         assert!(config.datasource.is_empty());
         assert!(config.default_datasource.is_none());
+        assert!(!config.retention.is_enabled());
+        assert!(!config.sampling.is_enabled());
+        assert!(config.repository_integrity.is_none());
+        assert!(config.admin_api_key.is_none());
+        assert!(config.repository_webhook_secret.is_none());
+        assert!(config.tenant_api_keys.is_empty());
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn test_tenant_quotas_require_api_keys() {
+        let mut config = ServerConfig {
+            tenant_quotas: TenantQuotaConfig::new().with_default(corint_runtime::TenantQuota::new()),
+            ..Default::default()
+        };
+        assert!(config.validate().is_err());
+
+        config.tenant_api_keys.insert("acme".to_string(), "acme-key".to_string());
+        assert!(config.validate().is_ok());
     }
 
     #[test]
@@ -434,6 +586,7 @@ mod tests {
                 enable_metrics: true,
                 enable_tracing: false,
                 log_level: "debug".to_string(),
+                enable_scheduler: true,
             },
             database_url: Some("postgresql://localhost/test".to_string()),
         };
@@ -16,42 +16,19 @@ use tracing::{info, warn};
 /// This makes it easy to use the SDK from other languages via FFI without
 /// needing to implement complex initialization logic.
 pub async fn init_engine(config: &ServerConfig) -> Result<corint_sdk::DecisionEngine> {
-    // Convert server repository config to SDK repository config
-    let repo_config = match &config.repository {
-        RepositoryType::FileSystem { path } => {
-            RepositoryConfig::file_system(path.to_string_lossy().to_string())
-        }
-        RepositoryType::Database { datasource, url, db_type: _ } => {
-            // If datasource name is provided, look it up in server.yaml datasources
-            if let Some(ds_name) = datasource {
-                if let Some(ds_config) = config.datasource.get(ds_name) {
-                    // Use connection string from server.yaml datasource config
-                    RepositoryConfig::database(ds_config.connection_string.clone())
-                } else {
-                    return Err(anyhow::anyhow!(
-                        "Datasource '{}' not found in server.yaml datasource section. \
-                        Please define it in the 'datasource' section of server.yaml.",
-                        ds_name
-                    ));
-                }
-            } else if let Some(url) = url {
-                // Fallback to legacy url field for backward compatibility
-                RepositoryConfig::database(url.clone())
-            } else {
-                return Err(anyhow::anyhow!(
-                    "Database repository requires either 'datasource' or 'url' field"
-                ));
-            }
-        }
-        RepositoryType::Api { base_url, api_key } => {
-            let config = RepositoryConfig::api(base_url.clone());
-            if let Some(key) = api_key {
-                config.with_api_key(key.clone())
-            } else {
-                config
-            }
-        }
-    };
+    init_engine_with(config, |builder| builder).await
+}
+
+/// Initialize decision engine, customizing its builder before it is built
+///
+/// Servers embedding step types of their own register the compiler plugins
+/// and handlers of those types here, with `with_step_plugin` and
+/// `with_custom_step_handler`.
+pub async fn init_engine_with(
+    config: &ServerConfig,
+    customize: impl FnOnce(DecisionEngineBuilder) -> DecisionEngineBuilder,
+) -> Result<corint_sdk::DecisionEngine> {
+    let repo_config = repository_config(config)?;
 
     // Convert server datasources to runtime datasource configs
     let mut server_datasources = std::collections::HashMap::new();
@@ -69,7 +46,22 @@ pub async fn init_engine(config: &ServerConfig) -> Result<corint_sdk::DecisionEn
     let mut builder = DecisionEngineBuilder::new()
         .with_repository(repo_config)
         .enable_metrics(config.server.enable_metrics)
-        .enable_tracing(config.server.enable_tracing);
+        .enable_tracing(config.server.enable_tracing)
+        .with_sampling(config.sampling.clone())
+        .with_list_hit_audit(config.audit_list_hits)
+        .with_execution_limits(config.execution_limits.clone())
+        .with_input_capture(config.capture_inputs)
+        .with_profiling(config.profiling)
+        .with_null_semantics(config.null_semantics)
+        .with_tenant_quotas(config.tenant_quotas.clone());
+
+    if let Some(idempotency) = config.idempotency {
+        builder = builder.with_idempotency(idempotency);
+    }
+
+    if let Some(timeout_ms) = config.decision_timeout_ms {
+        builder = builder.with_decision_timeout(std::time::Duration::from_millis(timeout_ms));
+    }
 
     // Set server datasources (takes precedence over repository datasources)
     if !server_datasources.is_empty() {
@@ -97,7 +89,103 @@ pub async fn init_engine(config: &ServerConfig) -> Result<corint_sdk::DecisionEn
     // - FeatureExecutor from server.yaml datasources (or repository/configs/datasources) and repository/configs/features
     // - ListService from repository/configs/lists
     // - ResultWriter from database_url (if configured)
-    let engine = builder.build().await?;
+    let engine = customize(builder).build().await?;
 
     Ok(engine)
 }
+
+/// Initialize the repository audit log used by the admin API
+///
+/// Only database (PostgreSQL) repositories keep a queryable audit trail.
+pub async fn init_audit_log(
+    config: &ServerConfig,
+) -> Result<Option<std::sync::Arc<dyn corint_repository::AuditLog>>> {
+    let repo_config = repository_config(config)?;
+
+    #[cfg(feature = "postgres")]
+    if repo_config.source == corint_repository::RepositorySource::Database {
+        if let Some(url) = &repo_config.database_url {
+            let audit_log = corint_repository::PostgresAuditLog::new(url).await?;
+            return Ok(Some(std::sync::Arc::new(audit_log)));
+        }
+    }
+
+    let _ = repo_config;
+    Ok(None)
+}
+
+/// Apply pending repository schema migrations (`--migrate-only`)
+///
+/// Only database (PostgreSQL) repositories have a schema to migrate.
+pub async fn migrate_repository(config: &ServerConfig) -> Result<()> {
+    let repo_config = repository_config(config)?;
+
+    #[cfg(feature = "postgres")]
+    if repo_config.source == corint_repository::RepositorySource::Database {
+        if let Some(url) = &repo_config.database_url {
+            let report = corint_repository::PostgresRepository::migrate_only(url).await?;
+            for migration in &report.applied {
+                info!(
+                    "✓ Applied migration {:03} {}",
+                    migration.version, migration.description
+                );
+            }
+            info!(
+                "Repository schema is at version {}",
+                report.current_version.unwrap_or_default()
+            );
+            return Ok(());
+        }
+    }
+
+    Err(anyhow::anyhow!(
+        "--migrate-only requires a PostgreSQL database repository (and the 'postgres' feature), got {:?}",
+        repo_config.source
+    ))
+}
+
+/// Convert server repository config to SDK repository config
+pub fn repository_config(config: &ServerConfig) -> Result<RepositoryConfig> {
+    let repo_config = match &config.repository {
+        RepositoryType::FileSystem { path } => {
+            RepositoryConfig::file_system(path.to_string_lossy().to_string())
+        }
+        RepositoryType::Database { datasource, url, db_type: _ } => {
+            // If datasource name is provided, look it up in server.yaml datasources
+            if let Some(ds_name) = datasource {
+                if let Some(ds_config) = config.datasource.get(ds_name) {
+                    // Use connection string from server.yaml datasource config
+                    RepositoryConfig::database(ds_config.connection_string.clone())
+                } else {
+                    return Err(anyhow::anyhow!(
+                        "Datasource '{}' not found in server.yaml datasource section. \
+                        Please define it in the 'datasource' section of server.yaml.",
+                        ds_name
+                    ));
+                }
+            } else if let Some(url) = url {
+                // Fallback to legacy url field for backward compatibility
+                RepositoryConfig::database(url.clone())
+            } else {
+                return Err(anyhow::anyhow!(
+                    "Database repository requires either 'datasource' or 'url' field"
+                ));
+            }
+        }
+        RepositoryType::Api { base_url, api_key } => {
+            let config = RepositoryConfig::api(base_url.clone());
+            if let Some(key) = api_key {
+                config.with_api_key(key.clone())
+            } else {
+                config
+            }
+        }
+        RepositoryType::Redis { url } => RepositoryConfig::redis(url.clone()),
+        RepositoryType::MongoDB { url } => RepositoryConfig::mongodb(url.clone()),
+    };
+
+    match &config.repository_integrity {
+        Some(integrity) => Ok(repo_config.with_integrity(integrity.clone())),
+        None => Ok(repo_config),
+    }
+}
@@ -6,6 +6,7 @@ use axum::{
     Json,
 };
 use chrono::Utc;
+use corint_sdk::ErrorCategory;
 use serde::Serialize;
 use serde_json::json;
 use thiserror::Error;
@@ -48,6 +49,10 @@ pub enum ServerError {
     #[error("Not found: {0}")]
     NotFound(String),
 
+    /// Missing or invalid credentials
+    #[error("Unauthorized: {0}")]
+    Unauthorized(String),
+
     /// Rate limit exceeded
     #[error("Rate limit exceeded")]
     RateLimitExceeded { retry_after: u32 },
@@ -84,16 +89,31 @@ pub struct ErrorDetails {
     pub retry_after: Option<u32>,
 }
 
+/// HTTP status of an engine error of `category`
+///
+/// Events lacking the fields or types the rules need are the caller's to fix;
+/// failing dependencies are worth retrying later.
+fn engine_error_status(category: ErrorCategory) -> StatusCode {
+    match category {
+        ErrorCategory::Input => StatusCode::UNPROCESSABLE_ENTITY,
+        ErrorCategory::Dependency => StatusCode::SERVICE_UNAVAILABLE,
+        ErrorCategory::Execution | ErrorCategory::Internal => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
+
 impl IntoResponse for ServerError {
     fn into_response(self) -> Response {
         let request_id = generate_request_id();
 
         let (status, code, message, details, retry_after) = match &self {
             ServerError::EngineError(e) => (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                "INTERNAL_ERROR",
+                engine_error_status(e.category()),
+                e.code(),
                 format!("An error occurred while processing your request: {}", e),
-                Some(json!({ "hint": format!("Please contact support with request_id: {}", request_id) })),
+                Some(json!({
+                    "category": e.category().as_str(),
+                    "hint": format!("Please contact support with request_id: {}", request_id),
+                })),
                 None,
             ),
             ServerError::InvalidRequest(msg) => (
@@ -124,6 +144,13 @@ impl IntoResponse for ServerError {
                 None,
                 None,
             ),
+            ServerError::Unauthorized(msg) => (
+                StatusCode::UNAUTHORIZED,
+                "UNAUTHORIZED",
+                msg.clone(),
+                None,
+                None,
+            ),
             ServerError::RateLimitExceeded { retry_after } => (
                 StatusCode::TOO_MANY_REQUESTS,
                 "RATE_LIMIT_EXCEEDED",
@@ -180,6 +207,12 @@ mod tests {
         assert_eq!(err.to_string(), "Not found: pipeline not found");
     }
 
+    #[test]
+    fn test_unauthorized_display() {
+        let err = ServerError::Unauthorized("Missing admin credentials".to_string());
+        assert_eq!(err.to_string(), "Unauthorized: Missing admin credentials");
+    }
+
     #[test]
     fn test_error_debug_format() {
         let sdk_err = corint_sdk::error::SdkError::NotInitialized;
@@ -212,6 +245,32 @@ mod tests {
         assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
     }
 
+    #[test]
+    fn test_into_response_engine_error_codes() {
+        let err = ServerError::EngineError(
+            corint_runtime::RuntimeError::MissingField {
+                path: "event.user.id".to_string(),
+            }
+            .into(),
+        );
+        assert_eq!(
+            err.into_response().status(),
+            StatusCode::UNPROCESSABLE_ENTITY
+        );
+
+        let err = ServerError::EngineError(
+            corint_runtime::RuntimeError::ListBackendUnavailable {
+                list_id: "blocked_ips".to_string(),
+                message: "connection refused".to_string(),
+            }
+            .into(),
+        );
+        assert_eq!(
+            err.into_response().status(),
+            StatusCode::SERVICE_UNAVAILABLE
+        );
+    }
+
     #[test]
     fn test_into_response_invalid_request() {
         let err = ServerError::InvalidRequest("bad input".to_string());
@@ -234,6 +293,13 @@ mod tests {
         assert_eq!(response.status(), StatusCode::NOT_FOUND);
     }
 
+    #[test]
+    fn test_into_response_unauthorized() {
+        let err = ServerError::Unauthorized("Invalid admin credentials".to_string());
+        let response = err.into_response();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+
     #[test]
     fn test_error_is_send_sync() {
         fn assert_send_sync<T: Send + Sync>() {}
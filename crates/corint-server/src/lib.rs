@@ -6,5 +6,6 @@ pub mod api;
 pub mod config;
 pub mod engine;
 pub mod error;
+pub mod scheduler;
 
 // Note: repository_loader.rs is deprecated - use corint_sdk::RepositoryConfig instead
@@ -6,6 +6,7 @@ pub mod api;
 pub mod config;
 pub mod engine;
 pub mod error;
+pub mod scheduler;
 
 use crate::api::grpc::pb::decision_service_server::DecisionServiceServer;
 use crate::api::grpc::DecisionGrpcService;
@@ -27,12 +28,31 @@ async fn main() -> Result<()> {
     let config = ServerConfig::load()?;
     info!("Loaded configuration: {:?}", config);
 
+    // Upgrade the repository schema and exit, e.g. from a deploy job
+    if std::env::args().any(|arg| arg == "--migrate-only") {
+        return engine::migrate_repository(&config).await;
+    }
+
     // Initialize decision engine
-    let engine = engine::init_engine(&config).await?;
+    let engine = Arc::new(RwLock::new(engine::init_engine(&config).await?));
     info!("Decision engine initialized");
 
+    // Start scheduled pipelines (shares the engine so reloads apply)
+    if config.server.enable_scheduler {
+        scheduler::spawn(engine.clone());
+        info!(
+            "✓ Scheduler started ({} schedules)",
+            engine.read().await.schedules().len()
+        );
+    }
+
     // Create router
-    let app = api::create_router(Arc::new(engine));
+    let admin = init_admin(&config).await?;
+    let app = api::create_router_with_state(api::rest::AppState {
+        engine,
+        admin,
+        tenant_api_keys: Arc::new(config.tenant_api_keys.clone()),
+    });
 
     // Start HTTP server
     let http_addr = format!("{}:{}", config.server.host, config.server.port);
@@ -43,6 +63,11 @@ async fn main() -> Result<()> {
     info!("  Health check: http://{}/health", http_addr);
     info!("  Decision API: http://{}/v1/decide", http_addr);
     info!("  Reload repository: POST http://{}/v1/repo/reload", http_addr);
+    if config.admin_api_key.is_some() {
+        info!("  Purge decisions: POST http://{}/v1/admin/decisions/purge", http_addr);
+        info!("  Audit log: GET http://{}/v1/admin/audit", http_addr);
+        info!("  List entries: http://{}/v1/lists/{{list_id}}/entries", http_addr);
+    }
 
     // Start gRPC server if configured
     if let Some(grpc_port) = config.server.grpc_port {
@@ -83,6 +108,56 @@ async fn main() -> Result<()> {
     Ok(())
 }
 
+/// Initialize admin API state (retention, review queue) and the retention sweep task
+async fn init_admin(config: &ServerConfig) -> Result<api::rest::AdminState> {
+    #[allow(unused_mut)]
+    let mut admin = api::rest::AdminState {
+        api_key: config.admin_api_key.clone(),
+        retention: None,
+        review_queue: None,
+        webhook_secret: config.repository_webhook_secret.clone(),
+        audit_log: engine::init_audit_log(config).await?,
+    };
+
+    #[cfg(feature = "sqlx")]
+    {
+        let database_url = config
+            .database_url
+            .clone()
+            .or_else(|| std::env::var("DATABASE_URL").ok());
+
+        if let Some(db_url) = database_url {
+            let pool = sqlx::postgres::PgPoolOptions::new()
+                .max_connections(2)
+                .connect(&db_url)
+                .await?;
+            let retention = corint_runtime::DecisionRetention::new(pool.clone());
+            admin.review_queue = Some(corint_runtime::DecisionReviewQueue::new(pool));
+
+            if config.retention.is_enabled() {
+                let sweeper = retention.clone();
+                let retention_config = config.retention.clone();
+                tokio::spawn(async move {
+                    let mut interval = tokio::time::interval(std::time::Duration::from_secs(
+                        retention_config.purge_interval_secs.max(1),
+                    ));
+                    loop {
+                        interval.tick().await;
+                        if let Err(e) = sweeper.purge_expired(&retention_config).await {
+                            tracing::error!("Retention sweep failed: {}", e);
+                        }
+                    }
+                });
+                info!("✓ Decision retention sweep enabled");
+            }
+
+            admin.retention = Some(retention);
+        }
+    }
+
+    Ok(admin)
+}
+
 /// Initialize tracing subscriber
 fn init_tracing() -> Result<()> {
     tracing_subscriber::registry()
@@ -0,0 +1,119 @@
+//! Scheduled pipeline runner
+//!
+//! Wakes at the start of every minute and runs the registry schedules whose
+//! cron expression matches that minute. Schedules are read from the engine on
+//! every tick, so repository reloads take effect without a restart.
+
+use chrono::{DateTime, Timelike, Utc};
+use corint_core::ast::ScheduleEntry;
+use corint_runtime::CronSchedule;
+use corint_sdk::DecisionEngine;
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
+use tokio::sync::RwLock;
+use tokio::task::JoinHandle;
+use tracing::{error, info, warn};
+
+/// Spawn the scheduler loop
+pub fn spawn(engine: Arc<RwLock<DecisionEngine>>) -> JoinHandle<()> {
+    let running = Arc::new(Mutex::new(HashSet::new()));
+
+    tokio::spawn(async move {
+        loop {
+            tokio::time::sleep(until_next_minute(Utc::now())).await;
+            run_due(&engine, &running, Utc::now()).await;
+        }
+    })
+}
+
+/// IDs of the schedules due in the minute containing `at`
+pub fn due_schedules(schedules: &[ScheduleEntry], at: DateTime<Utc>) -> Vec<String> {
+    schedules
+        .iter()
+        .filter(|schedule| match CronSchedule::parse(&schedule.schedule) {
+            Ok(cron) => cron.matches(at),
+            Err(e) => {
+                warn!("Skipping schedule '{}': {}", schedule.id, e);
+                false
+            }
+        })
+        .map(|schedule| schedule.id.clone())
+        .collect()
+}
+
+/// Start every due schedule that is not still running from a previous tick
+async fn run_due(
+    engine: &Arc<RwLock<DecisionEngine>>,
+    running: &Arc<Mutex<HashSet<String>>>,
+    at: DateTime<Utc>,
+) {
+    let due = due_schedules(&engine.read().await.schedules(), at);
+
+    for schedule_id in due {
+        if !running.lock().unwrap().insert(schedule_id.clone()) {
+            warn!(
+                "Schedule '{}' is still running, skipping this run",
+                schedule_id
+            );
+            continue;
+        }
+
+        let engine = engine.clone();
+        let running = running.clone();
+        tokio::spawn(async move {
+            info!("Running schedule '{}'", schedule_id);
+            match engine.read().await.run_schedule(&schedule_id).await {
+                Ok(summary) => info!(
+                    "Schedule '{}' finished: {} entities, {} batches, {} failed",
+                    schedule_id, summary.entities, summary.batches, summary.failed
+                ),
+                Err(e) => error!("Schedule '{}' failed: {}", schedule_id, e),
+            }
+            running.lock().unwrap().remove(&schedule_id);
+        });
+    }
+}
+
+/// Time remaining until the start of the next minute
+fn until_next_minute(now: DateTime<Utc>) -> std::time::Duration {
+    let elapsed = std::time::Duration::new(now.second() as u64, now.nanosecond());
+    std::time::Duration::from_secs(60).saturating_sub(elapsed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use corint_core::ast::EntityQuery;
+
+    #[test]
+    fn test_due_schedules() {
+        let schedules = vec![
+            ScheduleEntry::new(
+                "nightly",
+                "p1",
+                "0 2 * * *",
+                EntityQuery::new("db", "accounts"),
+            ),
+            ScheduleEntry::new(
+                "hourly",
+                "p2",
+                "0 * * * *",
+                EntityQuery::new("db", "accounts"),
+            ),
+            ScheduleEntry::new("broken", "p3", "bad", EntityQuery::new("db", "accounts")),
+        ];
+
+        let at = Utc.with_ymd_and_hms(2024, 3, 10, 2, 0, 15).unwrap();
+        assert_eq!(due_schedules(&schedules, at), vec!["nightly", "hourly"]);
+
+        let at = Utc.with_ymd_and_hms(2024, 3, 10, 3, 0, 0).unwrap();
+        assert_eq!(due_schedules(&schedules, at), vec!["hourly"]);
+    }
+
+    #[test]
+    fn test_until_next_minute() {
+        let now = Utc.with_ymd_and_hms(2024, 3, 10, 2, 0, 45).unwrap();
+        assert_eq!(until_next_minute(now), std::time::Duration::from_secs(15));
+    }
+}
@@ -662,3 +662,221 @@ async fn test_decide_method_get_not_allowed() {
 
     assert_eq!(response.status(), StatusCode::METHOD_NOT_ALLOWED);
 }
+
+#[tokio::test]
+async fn test_list_entries_endpoints() {
+    use corint_runtime::lists::{ListBackend, ListService, MemoryBackend};
+    use corint_server::api::rest::AdminState;
+    use std::collections::HashMap;
+
+    let mut backends: HashMap<String, Box<dyn ListBackend>> = HashMap::new();
+    backends.insert(
+        "blocked_accounts".to_string(),
+        Box::new(MemoryBackend::new()),
+    );
+    let service = Arc::new(ListService::new_with_backends(backends));
+    let engine = corint_sdk::DecisionEngineBuilder::new()
+        .with_list_service(service.clone())
+        .build()
+        .await
+        .unwrap();
+    let router = corint_server::api::create_router_with_admin(
+        Arc::new(engine),
+        AdminState {
+            api_key: Some("secret".to_string()),
+            ..Default::default()
+        },
+    );
+
+    let request = |method: &str, uri: &str, body: serde_json::Value| {
+        Request::builder()
+            .method(method)
+            .uri(uri)
+            .header("content-type", "application/json")
+            .header("authorization", "Bearer secret")
+            .body(Body::from(body.to_string()))
+            .unwrap()
+    };
+    let account = |s: &str| corint_core::Value::String(s.to_string());
+
+    let response = router
+        .clone()
+        .oneshot(request(
+            "POST",
+            "/v1/lists/blocked_accounts/entries",
+            json!({"value": "acct_1", "reason": "case 812", "ttl_secs": 86400}),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(service
+        .contains("blocked_accounts", &account("acct_1"))
+        .await
+        .unwrap());
+
+    let response = router
+        .clone()
+        .oneshot(request(
+            "POST",
+            "/v1/lists/blocked_accounts/import",
+            json!({"entries": [{"value": "acct_2"}, {"value": "acct_3"}], "replace": true}),
+        ))
+        .await
+        .unwrap();
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let json: Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(json["imported"], 2);
+    assert_eq!(json["removed"], 1);
+
+    let response = router
+        .clone()
+        .oneshot(request(
+            "DELETE",
+            "/v1/lists/blocked_accounts/entries/acct_2",
+            Value::Null,
+        ))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = router
+        .clone()
+        .oneshot(request(
+            "GET",
+            "/v1/lists/blocked_accounts/entries",
+            Value::Null,
+        ))
+        .await
+        .unwrap();
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let json: Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(json["entries"], json!([{"value": "acct_3"}]));
+
+    // Unknown lists, invalid values and missing credentials are rejected
+    let response = router
+        .clone()
+        .oneshot(request("GET", "/v1/lists/missing/entries", Value::Null))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    let response = router
+        .clone()
+        .oneshot(request(
+            "POST",
+            "/v1/lists/blocked_accounts/entries",
+            json!({"value": ""}),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    let response = router
+        .oneshot(
+            Request::builder()
+                .uri("/v1/lists/blocked_accounts/entries")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn test_decide_tenant_from_api_key() {
+    use corint_runtime::{RateLimit, RateLimitBehavior, TenantQuota, TenantQuotaConfig};
+    use corint_server::api::rest::{AdminState, AppState};
+    use std::collections::HashMap;
+    use tokio::sync::RwLock;
+
+    let rule_content = r#"
+pipeline:
+  id: payment_pipeline
+  name: Payment Pipeline
+  when:
+    event.type: payment
+  steps:
+  - include:
+      ruleset: payment_ruleset
+
+---
+
+rule:
+  id: high_amount
+  name: High Amount
+  when:
+    all:
+    - event.amount > 1000
+  score: 50
+
+---
+
+ruleset:
+  id: payment_ruleset
+  rules:
+  - high_amount
+"#;
+
+    // One decision per tenant, then rejected until a token refills
+    let quota = TenantQuota::new()
+        .with_rate_limit(RateLimit::new(0.01).with_on_limit(RateLimitBehavior::Reject));
+    let engine = DecisionEngineBuilder::new()
+        .add_rule_content("payment_pipeline", rule_content)
+        .with_tenant_quotas(TenantQuotaConfig::new().with_default(quota))
+        .build()
+        .await
+        .unwrap();
+    let router = corint_server::api::create_router_with_state(AppState {
+        engine: Arc::new(RwLock::new(engine)),
+        admin: AdminState::default(),
+        tenant_api_keys: Arc::new(HashMap::from([
+            ("acme".to_string(), "acme-key".to_string()),
+            ("globex".to_string(), "globex-key".to_string()),
+        ])),
+    });
+
+    let decide = |key: Option<&str>, tenant: &str| {
+        let mut request = Request::builder()
+            .method("POST")
+            .uri("/v1/decide")
+            .header("content-type", "application/json");
+        if let Some(key) = key {
+            request = request.header("x-api-key", key);
+        }
+        let body = json!({
+            "event": {"type": "payment", "amount": 5000},
+            "options": {"tenant": tenant}
+        });
+        request.body(Body::from(body.to_string())).unwrap()
+    };
+
+    let response = router
+        .clone()
+        .oneshot(decide(Some("acme-key"), "globex"))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    // The tenant named in the body is ignored: the decision counted against
+    // acme's quota, and globex still has its own
+    let response = router
+        .clone()
+        .oneshot(decide(Some("acme-key"), "globex"))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+    let response = router
+        .clone()
+        .oneshot(decide(Some("globex-key"), "acme"))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    // Callers without a valid key are not identified as any tenant
+    let response = router.clone().oneshot(decide(None, "acme")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    let response = router
+        .oneshot(decide(Some("wrong-key"), "acme"))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}
@@ -0,0 +1,195 @@
+//! CORINT WASM - WebAssembly bindings for the CORINT decision engine
+//!
+//! Runs compiled rules in browsers and edge workers, on top of the pure
+//! `corint_runtime::Executor` (built without the `native` feature, so
+//! without tokio, network clients or storage). Programs are compiled ahead
+//! of time, with `Compiler::compile_to_file` or serialized to JSON, and
+//! evaluated against events given as JSON.
+//!
+//! Only rule programs are evaluated: ruleset conclusions, pipelines,
+//! features, external calls and list backends need the native runtime.
+
+use corint_core::ir::Program;
+use corint_core::Value;
+use corint_runtime::{Executor, Result, RuntimeError};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::pin;
+use std::task::{Context, Poll, Waker};
+use wasm_bindgen::prelude::*;
+
+/// Outcome of evaluating an event against the loaded rules
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Evaluation {
+    /// Sum of the scores of the triggered rules
+    pub score: i32,
+
+    /// Triggered rules, in program order
+    pub triggered_rules: Vec<String>,
+}
+
+/// Decision engine over a set of compiled rules
+#[wasm_bindgen]
+pub struct DecisionEngine {
+    rules: Vec<Program>,
+}
+
+impl DecisionEngine {
+    /// Load the rules of a binary artifact; other programs are ignored
+    pub fn from_artifact(bytes: &[u8]) -> Result<Self> {
+        Ok(Self::from_programs(Program::load_all_from_bytes(bytes)?))
+    }
+
+    /// Load the rules of a JSON array of programs; other programs are ignored
+    pub fn from_json_programs(json: &str) -> Result<Self> {
+        let programs: Vec<Program> = serde_json::from_str(json)
+            .map_err(|e| RuntimeError::InvalidValue(format!("invalid programs: {}", e)))?;
+        Ok(Self::from_programs(programs))
+    }
+
+    /// Keep the rule programs of `programs`
+    pub fn from_programs(programs: Vec<Program>) -> Self {
+        let rules = programs
+            .into_iter()
+            .filter(|program| program.metadata.source_type == "rule")
+            .collect();
+        Self { rules }
+    }
+
+    /// Evaluate an event, given as a JSON object, against every rule
+    pub fn evaluate_event(&self, event_json: &str) -> Result<Evaluation> {
+        let event: HashMap<String, Value> = serde_json::from_str(event_json)
+            .map_err(|e| RuntimeError::InvalidValue(format!("invalid event: {}", e)))?;
+
+        let mut evaluation = Evaluation {
+            score: 0,
+            triggered_rules: Vec::new(),
+        };
+        for rule in &self.rules {
+            let result = block_on(Executor::execute(rule, event.clone()))?;
+            evaluation.score += result.score;
+            evaluation.triggered_rules.extend(result.triggered_rules);
+        }
+        Ok(evaluation)
+    }
+}
+
+#[wasm_bindgen]
+impl DecisionEngine {
+    /// Create an engine from a binary artifact
+    #[wasm_bindgen(constructor)]
+    pub fn new(artifact: &[u8]) -> std::result::Result<DecisionEngine, JsError> {
+        Self::from_artifact(artifact).map_err(js_error)
+    }
+
+    /// Create an engine from a JSON array of programs
+    #[wasm_bindgen(js_name = fromJson)]
+    pub fn from_json(programs: &str) -> std::result::Result<DecisionEngine, JsError> {
+        Self::from_json_programs(programs).map_err(js_error)
+    }
+
+    /// IDs of the loaded rules
+    #[wasm_bindgen(js_name = ruleIds)]
+    pub fn rule_ids(&self) -> Vec<String> {
+        self.rules
+            .iter()
+            .map(|rule| rule.metadata.source_id.clone())
+            .collect()
+    }
+
+    /// Evaluate an event given as JSON; returns `{"score", "triggered_rules"}` as JSON
+    pub fn evaluate(&self, event: &str) -> std::result::Result<String, JsError> {
+        let evaluation = self.evaluate_event(event).map_err(js_error)?;
+        serde_json::to_string(&evaluation).map_err(|e| JsError::new(&e.to_string()))
+    }
+}
+
+fn js_error(error: RuntimeError) -> JsError {
+    JsError::new(&error.to_string())
+}
+
+/// Run a future to completion on the calling thread
+///
+/// Without the `native` feature the executor never waits, so a single poll
+/// completes it; there is no async runtime to fall back on in the browser.
+fn block_on<F: Future<Output = Result<T>>, T>(future: F) -> Result<T> {
+    let mut context = Context::from_waker(Waker::noop());
+    match pin!(future).poll(&mut context) {
+        Poll::Ready(output) => output,
+        Poll::Pending => Err(RuntimeError::InvalidOperation(
+            "program suspended, which requires the native runtime".to_string(),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use corint_compiler::Compiler;
+    use corint_parser::RuleParser;
+
+    fn rule(yaml: &str) -> Program {
+        Compiler::new()
+            .compile_rule(&RuleParser::parse(yaml).unwrap())
+            .unwrap()
+    }
+
+    fn engine() -> DecisionEngine {
+        let programs = vec![
+            rule(
+                r#"
+rule:
+  id: high_amount
+  name: High amount
+  when:
+    all:
+      - event.amount > 1000
+  score: 60
+"#,
+            ),
+            rule(
+                r#"
+rule:
+  id: new_account
+  name: New account
+  when:
+    all:
+      - event.account_age_days < 7
+  score: 30
+"#,
+            ),
+        ];
+        DecisionEngine::from_artifact(&corint_core::ir::binary::encode(&programs).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_evaluate_rules_from_artifact() {
+        let engine = engine();
+        assert_eq!(engine.rule_ids(), vec!["high_amount", "new_account"]);
+
+        let evaluation = engine
+            .evaluate_event(r#"{"amount": 5000, "account_age_days": 3}"#)
+            .unwrap();
+        assert_eq!(evaluation.score, 90);
+        assert_eq!(
+            evaluation.triggered_rules,
+            vec!["high_amount".to_string(), "new_account".to_string()]
+        );
+
+        let evaluation = engine.evaluate_event(r#"{"amount": 50}"#).unwrap();
+        assert_eq!(evaluation.score, 0);
+        assert!(evaluation.triggered_rules.is_empty());
+    }
+
+    #[test]
+    fn test_json_programs_and_invalid_input() {
+        let programs = serde_json::to_string(&engine().rules).unwrap();
+        let engine = DecisionEngine::from_json_programs(&programs).unwrap();
+        assert_eq!(engine.rules.len(), 2);
+
+        assert!(engine.evaluate_event("[1, 2]").is_err());
+        assert!(DecisionEngine::from_artifact(b"not an artifact").is_err());
+        assert!(DecisionEngine::from_json_programs("{}").is_err());
+    }
+}
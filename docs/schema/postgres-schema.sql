@@ -153,6 +153,12 @@ CREATE TABLE risk_decisions (
     -- ========== Processing Metadata ==========
     processing_time_ms INT,                      -- Decision processing time in milliseconds
 
+    -- ========== Quality Review ==========
+    sampled BOOLEAN NOT NULL DEFAULT FALSE,      -- Selected by the sampling configuration for manual review
+
+    -- ========== Replay ==========
+    captured_inputs JSONB,                       -- Event, feature, call and list values consumed (input capture)
+
     -- ========== Timestamps ==========
     created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
 );
@@ -181,6 +187,10 @@ CREATE INDEX idx_risk_decisions_high_risk ON risk_decisions (risk_score DESC, cr
 -- Time-based queries
 CREATE INDEX idx_risk_decisions_timestamp ON risk_decisions (created_at DESC);
 
+-- Review queue (sampled decisions)
+CREATE INDEX idx_risk_decisions_sampled ON risk_decisions (created_at DESC)
+    WHERE sampled = TRUE;
+
 -- JSONB index for feature values
 CREATE INDEX idx_risk_decisions_features_gin ON risk_decisions USING GIN (feature_values);
 
@@ -287,7 +297,86 @@ CREATE INDEX idx_rule_exec_triggered ON rule_executions (triggered, created_at D
 CREATE INDEX idx_rule_exec_time ON rule_executions (created_at DESC);
 
 -- ============================================================================
--- 4. Comments and Documentation
+-- 4. Decision Purge Audit Table (decision_purge_audit)
+-- Records every subject erasure (e.g. GDPR requests) performed via the admin API
+-- ============================================================================
+
+CREATE TABLE decision_purge_audit (
+    id BIGSERIAL PRIMARY KEY,
+    actor VARCHAR(255) NOT NULL,                 -- Operator who requested the purge
+    entity_field VARCHAR(255) NOT NULL,          -- Field used to match the subject (user_id, email, ...)
+    entity_value_hash CHAR(64) NOT NULL,         -- SHA-256 of the purged value (never the raw value)
+    reason TEXT,                                 -- Optional reason or ticket reference
+
+    -- Rows deleted per table
+    risk_decisions_deleted BIGINT NOT NULL DEFAULT 0,
+    rule_executions_deleted BIGINT NOT NULL DEFAULT 0,
+    events_deleted BIGINT NOT NULL DEFAULT 0,
+
+    purged_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_purge_audit_value_hash ON decision_purge_audit (entity_value_hash);
+CREATE INDEX idx_purge_audit_time ON decision_purge_audit (purged_at DESC);
+
+-- ============================================================================
+-- 5. Decision Outcomes Table (decision_outcomes)
+-- Final outcome labels (chargebacks, confirmed fraud, false positives) for past decisions
+-- ============================================================================
+
+CREATE TABLE decision_outcomes (
+    request_id VARCHAR(64) PRIMARY KEY,          -- Links to risk_decisions.request_id
+    label VARCHAR(32) NOT NULL,                  -- confirmed_fraud, chargeback, false_positive, legitimate
+    metadata JSONB,                              -- Case ID, chargeback reason code, ...
+    recorded_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+
+    CONSTRAINT chk_outcome_label CHECK (
+        label IN ('confirmed_fraud', 'chargeback', 'false_positive', 'legitimate')
+    )
+);
+
+CREATE INDEX idx_outcomes_label ON decision_outcomes (label);
+CREATE INDEX idx_outcomes_time ON decision_outcomes (recorded_at DESC);
+
+-- ============================================================================
+-- 6. Entity Links Table (entity_links)
+-- Adjacency table for graph features (users per device, email degree, ...)
+-- Each link is stored once per direction
+-- ============================================================================
+
+CREATE TABLE entity_links (
+    src_type VARCHAR(64) NOT NULL,               -- Node type, e.g. device_id
+    src_id VARCHAR(255) NOT NULL,
+    dst_type VARCHAR(64) NOT NULL,               -- Linked node type, e.g. user_id
+    dst_id VARCHAR(255) NOT NULL,
+    event_timestamp TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_entity_links_src ON entity_links (src_type, src_id, dst_type, event_timestamp DESC);
+
+-- ============================================================================
+-- 6a. Experiment Results Table (experiment_results)
+-- Champion and challenger decisions of requests in a registry experiment
+-- ============================================================================
+
+CREATE TABLE experiment_results (
+    request_id VARCHAR(64) NOT NULL,             -- Links to risk_decisions.request_id (champion)
+    experiment_id VARCHAR(255) NOT NULL,
+    champion_pipeline_id VARCHAR(255) NOT NULL,
+    challenger_pipeline_id VARCHAR(255) NOT NULL,
+    champion_decision VARCHAR(50),
+    challenger_decision VARCHAR(50),
+    champion JSONB NOT NULL,                     -- Score, triggered rules, processing time
+    challenger JSONB NOT NULL,
+    recorded_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+
+    PRIMARY KEY (request_id, experiment_id)
+);
+
+CREATE INDEX idx_experiment_results_experiment ON experiment_results (experiment_id, recorded_at);
+
+-- ============================================================================
+-- 7. Comments and Documentation
 -- ============================================================================
 
 COMMENT ON TABLE events IS 'Core events table: Stores raw event data (input) with time-based partitioning';
@@ -299,9 +388,19 @@ COMMENT ON TABLE risk_decisions IS 'Risk decisions table: Stores decision result
 COMMENT ON COLUMN risk_decisions.request_id IS 'Links to events.request_id (one-to-one relationship)';
 COMMENT ON COLUMN risk_decisions.feature_values IS 'JSONB field: Calculated feature values used for this decision';
 COMMENT ON COLUMN risk_decisions.rule_scores IS 'JSONB field: Individual rule scores';
+COMMENT ON COLUMN risk_decisions.captured_inputs IS 'JSONB field: Inputs the decision consumed, to replay it deterministically (only with input capture enabled)';
+COMMENT ON COLUMN risk_decisions.sampled IS 'Whether the decision was sampled for quality review (see review queue API)';
 
 COMMENT ON TABLE rule_executions IS 'Rule execution log: Record rule engine execution history';
 
+COMMENT ON TABLE decision_outcomes IS 'Outcome feedback: one label per decision, joined with risk_decisions to measure rule precision';
+
+COMMENT ON TABLE experiment_results IS 'Champion/challenger experiments: both decisions of each request sent to a challenger pipeline';
+
+COMMENT ON TABLE entity_links IS 'Graph adjacency: links between entities observed in events, read by graph features';
+
+COMMENT ON TABLE decision_purge_audit IS 'Audit trail of subject purges: one row per purge_by_entity call';
+
 -- ============================================================================
 -- End of Schema
 -- ============================================================================